@@ -14,6 +14,9 @@
 
 #![forbid(unsafe_code)]
 
+mod mnemonic;
+pub use mnemonic::*;
+
 use snarkvm::{
     console::{network::prelude::*, types::Field},
     prelude::*,
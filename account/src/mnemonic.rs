@@ -0,0 +1,203 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::Account;
+
+use snarkvm::{
+    console::{network::prelude::*, types::Field},
+    prelude::PrivateKey,
+};
+
+use anyhow::Context;
+use bip39::Language;
+use core::fmt;
+use hmac::{Hmac, Mac};
+use sha2::Sha512;
+use std::path::Path;
+use zeroize::{Zeroize, Zeroizing};
+
+/// The HMAC key that separates the derivation of Aleo accounts from any other use of the BIP39 seed.
+pub const DERIVATION_DOMAIN: &[u8] = b"Aleo Account Derivation";
+
+/// An English BIP39 mnemonic, from which an operator derives the accounts of its nodes.
+///
+/// The account at `index` is derived as follows:
+/// 1. `seed := PBKDF2-HMAC-SHA512(mnemonic, "mnemonic", 2048)`, i.e. the BIP39 seed with an empty passphrase.
+/// 2. `digest := HMAC-SHA512(DERIVATION_DOMAIN, seed || index)`, with `index` as 4 little-endian bytes.
+/// 3. `private_key := PrivateKey::try_from(Field::from_bytes_le_mod_order(digest))`,
+///    i.e. the private key of `snarkos account new --seed <field>`.
+///
+/// The phrase is zeroized when the mnemonic is dropped, and is never displayed.
+pub struct Mnemonic(bip39::Mnemonic);
+
+impl Mnemonic {
+    /// Parses an English BIP39 mnemonic, ensuring its checksum is valid.
+    pub fn from_phrase(phrase: &str) -> Result<Self> {
+        match bip39::Mnemonic::parse_in(Language::English, phrase) {
+            Ok(mnemonic) => Ok(Self(mnemonic)),
+            // Note: The error only refers to a word by its position, so the phrase is never included.
+            Err(error) => bail!("Invalid BIP39 mnemonic - {error}"),
+        }
+    }
+
+    /// Reads an English BIP39 mnemonic from the given file.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let phrase = Zeroizing::new(
+            std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read the mnemonic file '{}'", path.display()))?,
+        );
+        Self::from_phrase(phrase.trim())
+    }
+
+    /// Returns the field element from which the private key at the given index is derived.
+    pub fn derive_seed<N: Network>(&self, index: u32) -> Result<Field<N>> {
+        // Compute the BIP39 seed, with an empty passphrase.
+        let seed = Zeroizing::new(self.0.to_seed(""));
+        // Compute the digest of the seed and the index.
+        let mut mac = Hmac::<Sha512>::new_from_slice(DERIVATION_DOMAIN).map_err(|e| anyhow!("{e}"))?;
+        mac.update(seed.as_ref());
+        mac.update(&index.to_le_bytes());
+        let mut digest = Zeroizing::new([0u8; 64]);
+        digest.copy_from_slice(&mac.finalize().into_bytes());
+        // Reduce the digest into a field element.
+        Ok(Field::new(<N as Environment>::Field::from_bytes_le_mod_order(digest.as_ref())))
+    }
+
+    /// Returns the private key at the given index.
+    pub fn derive_private_key<N: Network>(&self, index: u32) -> Result<PrivateKey<N>> {
+        PrivateKey::try_from(self.derive_seed(index)?)
+    }
+}
+
+impl Drop for Mnemonic {
+    /// Zeroizes the phrase.
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl fmt::Debug for Mnemonic {
+    /// Renders the mnemonic without its phrase.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Mnemonic({} words)", self.0.word_count())
+    }
+}
+
+impl<N: Network> Account<N> {
+    /// Derives the account at the given index from the given mnemonic.
+    pub fn from_mnemonic(mnemonic: &Mnemonic, index: u32) -> Result<Self> {
+        Self::try_from(mnemonic.derive_private_key(index)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkvm::prelude::MainnetV0;
+
+    type CurrentNetwork = MainnetV0;
+
+    /// The test vectors of the derivation, as `(mnemonic, BIP39 seed, [(index, field seed)])`.
+    /// Note: The field seeds are the `--seed` of `snarkos account new`, so these pin the derived private keys.
+    const TEST_VECTORS: [(&str, &str, [(u32, &str); 3]); 2] = [
+        (
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            "5eb00bbddcf069084889a8ab9155568165f5c453ccb85e70811aaed6f6da5fc19a5ac40b389cd370d086206dec8aa6c43daea6690f20ad3d8d48b2d2ce9e38e4",
+            [
+                (0, "3645372368613170123376745067257565501744664951647747314543055779472460358891field"),
+                (1, "2880824701247620923634980016679276904798167679619160915391961230888234877255field"),
+                (u32::MAX, "6923782901674890454612547438111438107438027373443727937209756931068517162918field"),
+            ],
+        ),
+        (
+            "legal winner thank year wave sausage worth useful legal winner thank yellow",
+            "878386efb78845b3355bd15ea4d39ef97d179cb712b77d5c12b6be415fffeffe5f377ba02bf3f8544ab800b955e51fbff09828f682052a20faa6addbbddfb096",
+            [
+                (0, "6738024660053949203616587739488243838166334170402733570581892182918775977480field"),
+                (1, "5527930591033516132227827974438018445717888948807298540254482561281044521317field"),
+                (u32::MAX, "5786876135367920517748225460287153476847059413119122084614770517655757826330field"),
+            ],
+        ),
+    ];
+
+    #[test]
+    fn test_vectors() {
+        for (phrase, expected_seed, indices) in TEST_VECTORS {
+            let mnemonic = Mnemonic::from_phrase(phrase).unwrap();
+            // Ensure the BIP39 seed matches the reference implementation.
+            let seed = mnemonic.0.to_seed("").iter().map(|byte| format!("{byte:02x}")).collect::<String>();
+            assert_eq!(seed, expected_seed);
+            // Ensure the derived field seeds match.
+            for (index, expected) in indices {
+                let field = mnemonic.derive_seed::<CurrentNetwork>(index).unwrap();
+                assert_eq!(field, Field::from_str(expected).unwrap());
+                // Ensure the private key is the one of the field seed.
+                let private_key = mnemonic.derive_private_key::<CurrentNetwork>(index).unwrap();
+                assert_eq!(private_key, PrivateKey::try_from(field).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn test_derivation_is_deterministic() {
+        let (phrase, _, _) = TEST_VECTORS[0];
+        for index in [0, 1, 42, u32::MAX] {
+            let first = Account::<CurrentNetwork>::from_mnemonic(&Mnemonic::from_phrase(phrase).unwrap(), index);
+            let second = Account::<CurrentNetwork>::from_mnemonic(&Mnemonic::from_phrase(phrase).unwrap(), index);
+            assert_eq!(first.unwrap().address(), second.unwrap().address());
+        }
+    }
+
+    #[test]
+    fn test_derivation_indices_are_unrelated() {
+        let (phrase, _, _) = TEST_VECTORS[0];
+        let mnemonic = Mnemonic::from_phrase(phrase).unwrap();
+        // Ensure every index yields a distinct private key and address.
+        let accounts = (0..16)
+            .map(|index| Account::<CurrentNetwork>::from_mnemonic(&mnemonic, index).unwrap())
+            .collect::<Vec<_>>();
+        for (i, account) in accounts.iter().enumerate() {
+            for other in accounts.iter().skip(i + 1) {
+                assert_ne!(account.private_key(), other.private_key());
+                assert_ne!(account.address(), other.address());
+            }
+        }
+        // Ensure another mnemonic yields other accounts at the same indices.
+        let other = Mnemonic::from_phrase(TEST_VECTORS[1].0).unwrap();
+        for (index, account) in accounts.iter().enumerate() {
+            let other = Account::<CurrentNetwork>::from_mnemonic(&other, index as u32).unwrap();
+            assert_ne!(account.address(), other.address());
+        }
+    }
+
+    #[test]
+    fn test_invalid_mnemonic() {
+        // Ensure an invalid checksum is rejected.
+        let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon";
+        assert!(Mnemonic::from_phrase(phrase).is_err());
+        // Ensure an unknown word is rejected, without echoing it.
+        let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon snarkos";
+        let error = Mnemonic::from_phrase(phrase).unwrap_err().to_string();
+        assert!(!error.contains("snarkos"));
+        // Ensure an invalid number of words is rejected.
+        assert!(Mnemonic::from_phrase("abandon about").is_err());
+    }
+
+    #[test]
+    fn test_debug_is_redacted() {
+        let (phrase, _, _) = TEST_VECTORS[0];
+        let mnemonic = Mnemonic::from_phrase(phrase).unwrap();
+        assert_eq!(format!("{mnemonic:?}"), "Mnemonic(12 words)");
+    }
+}
@@ -22,6 +22,8 @@ use snarkvm::{
     utilities::ToBytes,
 };
 
+use snarkos_account::Mnemonic;
+
 use anyhow::{anyhow, bail, Result};
 use clap::Parser;
 use colored::Colorize;
@@ -53,6 +55,18 @@ pub enum Account {
         #[clap(long)]
         discreet: bool,
     },
+    /// Derives the Aleo account at the given index from a BIP39 mnemonic
+    Derive {
+        /// Specify the path to a file containing the English BIP39 mnemonic
+        #[clap(long = "mnemonic-file")]
+        mnemonic_file: String,
+        /// The index of the account to derive
+        #[clap(long = "index")]
+        index: u32,
+        /// Print sensitive information (such as the private key) discreetly in an alternate screen
+        #[clap(long)]
+        discreet: bool,
+    },
     Sign {
         /// Specify the account private key of the node
         #[clap(long = "private-key")]
@@ -109,6 +123,7 @@ impl Account {
                     Self::new_seeded(seed, discreet)
                 }
             }
+            Self::Derive { mnemonic_file, index, discreet } => Self::derive(mnemonic_file, index, discreet),
             Self::Sign { message, seed, raw, private_key, private_key_file } => {
                 let key = match (private_key, private_key_file) {
                     (Some(private_key), None) => private_key,
@@ -176,22 +191,7 @@ impl Account {
             // Return the result if a candidate was found.
             if let Some(account) = account {
                 println!(); // Add a newline for formatting.
-                if !discreet {
-                    return Ok(account.to_string());
-                }
-                display_string_discreetly(
-                    &format!("{:>12}  {}", "Private Key".cyan().bold(), account.private_key()),
-                    "### Do not share or lose this private key! Press any key to complete. ###",
-                )
-                .unwrap();
-                let account_info = format!(
-                    " {:>12}  {}\n {:>12}  {}",
-                    "View Key".cyan().bold(),
-                    account.view_key(),
-                    "Address".cyan().bold(),
-                    account.address()
-                );
-                return Ok(account_info);
+                return display_account(&account, discreet);
             } else {
                 let rate = ITERATIONS / timer.elapsed().as_millis();
                 let rate = format!("[{rate} a/ms]");
@@ -217,22 +217,18 @@ impl Account {
         // Construct the account.
         let account = snarkos_account::Account::<Network>::try_from(private_key)?;
         // Print the new Aleo account.
-        if !discreet {
-            return Ok(account.to_string());
-        }
-        display_string_discreetly(
-            &format!("{:>12}  {}", "Private Key".cyan().bold(), account.private_key()),
-            "### Do not share or lose this private key! Press any key to complete. ###",
-        )
-        .unwrap();
-        let account_info = format!(
-            " {:>12}  {}\n {:>12}  {}",
-            "View Key".cyan().bold(),
-            account.view_key(),
-            "Address".cyan().bold(),
-            account.address()
-        );
-        Ok(account_info)
+        display_account(&account, discreet)
+    }
+
+    /// Derives the Aleo account at the given index from the mnemonic in the given file.
+    fn derive(mnemonic_file: String, index: u32, discreet: bool) -> Result<String> {
+        // Read the mnemonic.
+        let path = mnemonic_file.parse::<PathBuf>().map_err(|e| anyhow!("Invalid path - {e}"))?;
+        let mnemonic = Mnemonic::from_file(&path)?;
+        // Derive the account.
+        let account = snarkos_account::Account::<Network>::from_mnemonic(&mnemonic, index)?;
+        // Print the derived Aleo account.
+        display_account(&account, discreet)
     }
 
     // Sign a message with an Aleo private key
@@ -293,6 +289,26 @@ impl Account {
     }
 }
 
+// Returns the account as a string, or prints the private key to an alternate screen if `discreet` is set.
+fn display_account(account: &snarkos_account::Account<Network>, discreet: bool) -> Result<String> {
+    if !discreet {
+        return Ok(account.to_string());
+    }
+    display_string_discreetly(
+        &format!("{:>12}  {}", "Private Key".cyan().bold(), account.private_key()),
+        "### Do not share or lose this private key! Press any key to complete. ###",
+    )
+    .unwrap();
+    let account_info = format!(
+        " {:>12}  {}\n {:>12}  {}",
+        "View Key".cyan().bold(),
+        account.view_key(),
+        "Address".cyan().bold(),
+        account.address()
+    );
+    Ok(account_info)
+}
+
 // Print the string to an alternate screen, so that the string won't been printed to the terminal.
 fn display_string_discreetly(discreet_string: &str, continue_message: &str) -> Result<()> {
     use crossterm::{
@@ -380,6 +396,30 @@ mod tests {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn test_derive() {
+        let directory = std::env::temp_dir().join(format!("snarkos-mnemonic-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("mnemonic.txt");
+        let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        std::fs::write(&path, format!("{phrase}\n")).unwrap();
+        let mnemonic_file = path.to_str().unwrap().to_string();
+
+        // Ensure the derived account is the account seeded with the derived field element.
+        let seed = Some("3645372368613170123376745067257565501744664951647747314543055779472460358891".to_string());
+        let expected = Account::New { seed, vanity: None, discreet: false }.parse().unwrap();
+        let account = Account::Derive { mnemonic_file: mnemonic_file.clone(), index: 0, discreet: false };
+        assert_eq!(account.parse().unwrap(), expected);
+
+        // Ensure another index derives another account.
+        let account = Account::Derive { mnemonic_file: mnemonic_file.clone(), index: 1, discreet: false };
+        assert_ne!(account.parse().unwrap(), expected);
+
+        // Ensure an invalid mnemonic is rejected.
+        std::fs::write(&path, "abandon abandon abandon").unwrap();
+        assert!(Account::Derive { mnemonic_file, index: 0, discreet: false }.parse().is_err());
+    }
+
     #[test]
     fn test_signature_raw() {
         let key = "APrivateKey1zkp61PAYmrYEKLtRWeWhUoDpFnGLNuHrCciSqN49T86dw3p".to_string();
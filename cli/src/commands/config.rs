@@ -0,0 +1,625 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use clap::Parser;
+use colored::Colorize;
+use indexmap::IndexMap;
+use serde::{Deserialize, Deserializer};
+use snarkos_node_rest::{ApiToken, ApiTokens};
+use std::{
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// The template written by `snarkos config generate`.
+pub const CONFIG_TEMPLATE: &str = r#"# The snarkOS node configuration file.
+#
+# Every setting is optional. A flag given on the command line takes precedence over this file,
+# and a setting that is missing from this file falls back to the command-line default.
+# Uncomment a setting to apply it.
+
+# The network ID of this node.
+#network = 0
+# The node type, one of "client", "prover", "validator", or "archive".
+#node_type = "client"
+# The account private key of the node.
+#private_key = "APrivateKey1..."
+# The path to a file containing the account private key of the node (exclusive with 'private_key').
+#private_key_file = "/path/to/private.key"
+# The path to a file containing a BIP39 mnemonic, from which the account of the node is derived (exclusive with the private key).
+#account_mnemonic_file = "/path/to/mnemonic.txt"
+# The index of the account derived from the mnemonic.
+#account_index = 0
+# The endpoint of a remote signer for the validator, as "tcp://<ip>:<port>" or "unix://<path>" (exclusive with the private key).
+#signer = "unix:///run/snarkos/signer.sock"
+# The path to a file containing the secret shared with the remote signer.
+#signer_secret_file = "/path/to/signer.secret"
+# The path to a directory containing the ledger.
+#storage_path = "/path/to/ledger"
+# Whether the node indexes the transactions of record commitments and serial numbers (increases storage use).
+#record_index = false
+# Whether the node decrypts the records of the view keys registered over the REST server (requires the JWT).
+#record_scan = false
+# Whether the view keys registered for record scanning and their records are persisted in the ledger directory.
+#record_scan_persist = false
+# Whether the node indexes the changes to the mapping entries of programs in each block (increases storage use).
+#state_changes = false
+# The number of the most recent blocks whose state changes are retained.
+#state_changes_retention = 50000
+# The program ID(s) whose transactions the node refuses to accept or propagate (updatable over the REST server with the JWT).
+#program_denylist = ["spam_faucet.aleo"]
+# The number of threads dedicated to the verification of transactions, solutions, and certificates (default: half of the cores).
+#verification_threads = 4
+# The number of threads used by the prover to solve the puzzle (default: all the cores).
+#prover_threads = 4
+# The percentage of time the prover spends solving the puzzle, from 1 to 100.
+#prover_duty_cycle = 100
+
+[p2p]
+# The IP address and port for the node server.
+#listen = "0.0.0.0:4130"
+# The IP address and port of the peer(s) to connect to.
+#peers = ["127.0.0.1:4131"]
+# The IP address and port of the validator(s) to connect to.
+#validators = ["127.0.0.1:5001"]
+# Whether the validator allows untrusted peers to connect.
+#allow_external_peers = false
+# Whether the node maps its listening port on the gateway of its network over UPnP.
+#upnp = false
+# The hostname(s) of the DNS seeds to discover peers from, with an optional port (default port: 4130).
+#dns_seeds = ["seed.example.com"]
+# The HTTPS URL(s) of the signed seed lists to discover peers from.
+#seed_lists = ["https://example.com/peers.json"]
+# The address that signs the seed lists.
+#seed_list_signer = "aleo1..."
+# The SOCKS5 proxy to establish the outbound peer connections through (disabled if unset).
+#proxy = "socks5://127.0.0.1:9050"
+# The IP address and port at which the node is reachable, to advertise instead of the proxy's address.
+#external_addr = "203.0.113.7:4130"
+# The outbound bandwidth budget of the node in bytes per second (unlimited if unset).
+#outbound_bandwidth = 10000000
+# The weights of the consensus, blocks, gossip, and maintenance traffic in the outbound bandwidth budget.
+#outbound_bandwidth_weights = [8, 4, 2, 1]
+# The maximum number of untrusted peers to connect to in a single subnet (default: a seventh of the peers).
+#max_peers_per_subnet = 3
+# The path of the file to append the peer connection events to, as JSON lines (disabled if unset).
+#audit_log = "peers.jsonl"
+# The size in bytes at which the peer audit log is rotated.
+#audit_log_max_size = 67108864
+# The fraction of the transactions broadcast by the node, whose propagation delay is sampled (0.0 to 1.0).
+#propagation_sample_rate = 0.0
+
+[bft]
+# The IP address and port for the BFT (development mode only).
+#listen = "0.0.0.0:5000"
+# Whether the validator starts as an observer if its address is not a staked member of the committee.
+#allow_unstaked = false
+# The path of a file to record the events of the BFT to, for a deterministic replay.
+#event_log = "/path/to/events.log"
+# Where the validator keeps the pending transmissions of the BFT ("memory" or "persistent").
+#storage = "persistent"
+# The seconds the validator waits before proposing a batch without transactions (0 proposes it right away, at most 20).
+#empty_batch_delay = 20
+
+[rest]
+# Whether the node initializes the REST server.
+#enabled = true
+# The IP address(es) and port(s) for the REST server, e.g. ["127.0.0.1:3030", "[::1]:3030"].
+#listen = ["0.0.0.0:3030"]
+# The requests per second (RPS) rate limit per IP for the REST server.
+#rps = 10
+# Whether the REST server exposes Prometheus metrics at `/metrics`.
+#metrics = false
+# Whether the REST server exposes the debug routes of the BFT at `/mainnet/bft` and `/mainnet/debug`.
+#debug = false
+# Whether the REST server serves the block archive at `/mainnet/archive`, for other nodes to prefetch blocks from.
+#block_archive = false
+# The API tokens that authorize requests to the sensitive routes, each with the scopes it grants: "read", "admin", or "dev".
+#tokens = [{ token = "replace-with-a-long-random-token", scopes = ["read"] }]
+# The paths to the PEM files of the TLS certificate chain and private key, which are reloaded when they change.
+#tls_cert = "/path/to/fullchain.pem"
+#tls_key = "/path/to/privkey.pem"
+# The IP address(es) and port(s) to serve the REST server over HTTPS, e.g. ["0.0.0.0:3443"] (default: the `listen` addresses).
+#tls_listen = ["0.0.0.0:3443"]
+
+[log]
+# The verbosity of the node, one of 0, 1, 2, 3, or 4.
+#verbosity = 1
+# The path to the file where logs will be stored (default: 'snarkos.log' in the temporary directory).
+#logfile = "/tmp/snarkos.log"
+# Whether the node renders the display.
+#display = true
+# Whether the node enables the metrics exporter.
+#metrics = false
+
+[cdn]
+# Whether the node prefetches initial blocks from a CDN.
+#enabled = true
+# The CDN to prefetch initial blocks from, or a comma-separated list of mirrors of the CDN, tried in order.
+#url = "https://s3.us-west-1.amazonaws.com/testnet3.blocks/phase3"
+
+[dev]
+# Enables development mode, with a unique ID for this node.
+#id = 0
+# The number of genesis validators in development mode.
+#num_validators = 4
+# Whether node 0 generates traffic to drive the network in development mode.
+#transactions = true
+
+# The custom bonded balances in development mode, as a mapping of
+# staker address to [validator address, withdrawal address, amount in microcredits].
+#[dev.bonded_balances]
+#"aleo1staker" = ["aleo1validator", "aleo1withdrawal", 10000000000000]
+"#;
+
+/// Commands to manage the node configuration file.
+#[derive(Debug, Parser)]
+pub enum Config {
+    /// Generate a commented configuration file template.
+    Generate(Generate),
+}
+
+impl Config {
+    pub fn parse(self) -> Result<String> {
+        match self {
+            Self::Generate(generate) => generate.parse(),
+        }
+    }
+}
+
+/// Generates a commented configuration file template.
+#[derive(Debug, Parser)]
+pub struct Generate {
+    /// Specify the path to write the configuration file to
+    #[clap(default_value = "snarkos.toml", long = "path")]
+    pub path: PathBuf,
+    /// If the flag is set, an existing file at the path will be overwritten
+    #[clap(long)]
+    pub force: bool,
+}
+
+impl Generate {
+    /// Writes the configuration file template to the path.
+    pub fn parse(self) -> Result<String> {
+        // Ensure an existing file is not overwritten by accident.
+        if self.path.exists() && !self.force {
+            bail!("The file '{}' already exists, use '--force' to overwrite it", self.path.display());
+        }
+        // Write the template to the path.
+        std::fs::write(&self.path, CONFIG_TEMPLATE)
+            .with_context(|| format!("Failed to write the config file '{}'", self.path.display()))?;
+        // Prepare the path string.
+        let path_string = format!("(in \"{}\")", self.path.display()).dimmed();
+        Ok(format!("✅ Wrote the node configuration template {path_string}"))
+    }
+}
+
+/// The node type, as specified in the configuration file.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigNodeType {
+    Client,
+    Prover,
+    Validator,
+    Archive,
+}
+
+/// The node configuration file.
+///
+/// Every setting is optional, as a setting given on the command line takes precedence,
+/// and a missing setting falls back to the command-line default.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NodeConfig {
+    /// The network ID of this node.
+    pub network: Option<u16>,
+    /// The node type.
+    pub node_type: Option<ConfigNodeType>,
+    /// The account private key of the node.
+    pub private_key: Option<String>,
+    /// The path to a file containing the account private key of the node.
+    pub private_key_file: Option<PathBuf>,
+    /// The path to a file containing a BIP39 mnemonic, from which the account of the node is derived.
+    pub account_mnemonic_file: Option<PathBuf>,
+    /// The index of the account derived from the mnemonic.
+    pub account_index: Option<u32>,
+    /// The endpoint of a remote signer for the validator.
+    pub signer: Option<String>,
+    /// The path to a file containing the secret shared with the remote signer.
+    pub signer_secret_file: Option<PathBuf>,
+    /// The path to a directory containing the ledger.
+    pub storage_path: Option<PathBuf>,
+    /// Whether the node indexes the transactions of record commitments and serial numbers.
+    pub record_index: Option<bool>,
+    /// Whether the node decrypts the records of the view keys registered over the REST server.
+    pub record_scan: Option<bool>,
+    /// Whether the view keys registered for record scanning and their records are persisted.
+    pub record_scan_persist: Option<bool>,
+    /// Whether the node indexes the changes to the mapping entries of programs in each block.
+    pub state_changes: Option<bool>,
+    /// The number of the most recent blocks whose state changes are retained.
+    pub state_changes_retention: Option<u32>,
+    /// The program IDs whose transactions the node refuses to accept or propagate.
+    pub program_denylist: Option<Vec<String>>,
+    /// The number of threads dedicated to the verification of transactions, solutions, and certificates.
+    pub verification_threads: Option<usize>,
+    /// The number of threads used by the prover to solve the puzzle.
+    pub prover_threads: Option<usize>,
+    /// The percentage of time the prover spends solving the puzzle.
+    pub prover_duty_cycle: Option<u8>,
+    /// The peer-to-peer settings.
+    #[serde(default)]
+    pub p2p: P2PConfig,
+    /// The BFT settings.
+    #[serde(default)]
+    pub bft: BftConfig,
+    /// The REST server settings.
+    #[serde(default)]
+    pub rest: RestConfig,
+    /// The logging settings.
+    #[serde(default)]
+    pub log: LogConfig,
+    /// The CDN settings.
+    #[serde(default)]
+    pub cdn: CdnConfig,
+    /// The development mode settings.
+    #[serde(default)]
+    pub dev: DevConfig,
+}
+
+/// The `[p2p]` section of the node configuration file.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct P2PConfig {
+    /// The IP address and port for the node server.
+    pub listen: Option<SocketAddr>,
+    /// The IP address and port of the peer(s) to connect to.
+    pub peers: Option<Vec<SocketAddr>>,
+    /// The IP address and port of the validator(s) to connect to.
+    pub validators: Option<Vec<SocketAddr>>,
+    /// Whether the validator allows untrusted peers to connect.
+    pub allow_external_peers: Option<bool>,
+    /// Whether the node maps its listening port on the gateway of its network over UPnP.
+    pub upnp: Option<bool>,
+    /// The hostname(s) of the DNS seeds to discover peers from.
+    pub dns_seeds: Option<Vec<String>>,
+    /// The HTTPS URL(s) of the signed seed lists to discover peers from.
+    pub seed_lists: Option<Vec<String>>,
+    /// The address that signs the seed lists.
+    pub seed_list_signer: Option<String>,
+    /// The SOCKS5 proxy to establish the outbound peer connections through.
+    pub proxy: Option<String>,
+    /// The IP address and port at which the node is reachable, to advertise instead of the proxy's address.
+    pub external_addr: Option<SocketAddr>,
+    /// The outbound bandwidth budget of the node in bytes per second.
+    pub outbound_bandwidth: Option<u64>,
+    /// The weights of the consensus, blocks, gossip, and maintenance traffic in the outbound bandwidth budget.
+    pub outbound_bandwidth_weights: Option<Vec<u32>>,
+    /// The maximum number of untrusted peers to connect to in a single subnet.
+    pub max_peers_per_subnet: Option<usize>,
+    /// The path of the file to append the peer connection events to.
+    pub audit_log: Option<PathBuf>,
+    /// The size in bytes at which the peer audit log is rotated.
+    pub audit_log_max_size: Option<u64>,
+    /// The fraction of the transactions broadcast by the node, whose propagation delay is sampled.
+    pub propagation_sample_rate: Option<f64>,
+}
+
+/// The `[bft]` section of the node configuration file.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BftConfig {
+    /// The IP address and port for the BFT.
+    pub listen: Option<SocketAddr>,
+    /// Whether the validator starts as an observer if its address is not a staked member of the committee.
+    pub allow_unstaked: Option<bool>,
+    /// The path of a file to record the events of the BFT to, for a deterministic replay.
+    pub event_log: Option<PathBuf>,
+    /// Where the validator keeps the pending transmissions of the BFT.
+    pub storage: Option<String>,
+    /// The seconds the validator waits before proposing a batch without transactions.
+    pub empty_batch_delay: Option<u64>,
+}
+
+/// The `[rest]` section of the node configuration file.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RestConfig {
+    /// Whether the node initializes the REST server.
+    pub enabled: Option<bool>,
+    /// The IP address(es) and port(s) for the REST server.
+    #[serde(default, deserialize_with = "deserialize_one_or_many")]
+    pub listen: Option<Vec<SocketAddr>>,
+    /// The requests per second (RPS) rate limit per IP for the REST server.
+    pub rps: Option<u32>,
+    /// Whether the REST server exposes Prometheus metrics at `/metrics`.
+    pub metrics: Option<bool>,
+    /// Whether the REST server exposes the debug routes of the BFT.
+    pub debug: Option<bool>,
+    /// Whether the REST server serves the block archive.
+    pub block_archive: Option<bool>,
+    /// The API tokens that authorize requests to the sensitive routes.
+    pub tokens: Option<Vec<ApiToken>>,
+    /// The path to the PEM file of the TLS certificate chain.
+    pub tls_cert: Option<PathBuf>,
+    /// The path to the PEM file of the TLS private key.
+    pub tls_key: Option<PathBuf>,
+    /// The IP address(es) and port(s) to serve the REST server over HTTPS.
+    #[serde(default, deserialize_with = "deserialize_one_or_many")]
+    pub tls_listen: Option<Vec<SocketAddr>>,
+}
+
+/// The `[log]` section of the node configuration file.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LogConfig {
+    /// The verbosity of the node.
+    pub verbosity: Option<u8>,
+    /// The path to the file where logs will be stored.
+    pub logfile: Option<PathBuf>,
+    /// Whether the node renders the display.
+    pub display: Option<bool>,
+    /// Whether the node enables the metrics exporter.
+    pub metrics: Option<bool>,
+}
+
+/// The `[cdn]` section of the node configuration file.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CdnConfig {
+    /// Whether the node prefetches initial blocks from a CDN.
+    pub enabled: Option<bool>,
+    /// The CDN to prefetch initial blocks from, or a comma-separated list of mirrors of the CDN.
+    pub url: Option<String>,
+}
+
+/// The `[dev]` section of the node configuration file.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DevConfig {
+    /// The unique ID of this node in development mode.
+    pub id: Option<u16>,
+    /// The number of genesis validators in development mode.
+    pub num_validators: Option<u16>,
+    /// Whether node 0 generates traffic to drive the network in development mode.
+    pub transactions: Option<bool>,
+    /// A mapping of `staker_address` to `(validator_address, withdrawal_address, amount)`.
+    pub bonded_balances: Option<IndexMap<String, (String, String, u64)>>,
+}
+
+impl NodeConfig {
+    /// The maximum verbosity of the node.
+    const MAX_VERBOSITY: u8 = 4;
+
+    /// Loads the node configuration file from the given path.
+    pub fn load(path: &Path) -> Result<Self> {
+        // Read the configuration file.
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read the config file '{}'", path.display()))?;
+        // Parse the configuration file.
+        Self::from_str(&contents).with_context(|| format!("Invalid config file '{}'", path.display()))
+    }
+
+    /// Ensures the settings in the configuration file are well-formed.
+    fn check(&self) -> Result<()> {
+        // Ensure the network ID is supported.
+        if let Some(network) = self.network {
+            ensure!(network == 0, "Invalid value for 'network': unsupported network ID {network}");
+        }
+        // Ensure only one private key setting is provided.
+        if self.private_key.is_some() && self.private_key_file.is_some() {
+            bail!("Cannot set 'private_key' and 'private_key_file' simultaneously, please use only one");
+        }
+        // Ensure the mnemonic is not set alongside a private key.
+        if self.account_mnemonic_file.is_some() && (self.private_key.is_some() || self.private_key_file.is_some()) {
+            bail!(
+                "Cannot set 'account_mnemonic_file' alongside 'private_key' or 'private_key_file', please use only one"
+            );
+        }
+        // Ensure the account index is only set with a mnemonic.
+        if self.account_index.is_some() && self.account_mnemonic_file.is_none() {
+            bail!("Cannot set 'account_index' without 'account_mnemonic_file'");
+        }
+        // Ensure the remote signer is not set alongside a private key.
+        let has_account =
+            self.private_key.is_some() || self.private_key_file.is_some() || self.account_mnemonic_file.is_some();
+        if self.signer.is_some() && has_account {
+            bail!("Cannot set 'signer' alongside the private key or 'account_mnemonic_file', please use only one");
+        }
+        // Ensure the retention window of the state changes is nonzero.
+        if let Some(retention) = self.state_changes_retention {
+            ensure!(retention > 0, "Invalid value for 'state_changes_retention': must be greater than 0");
+        }
+        // Ensure the prover duty cycle is a percentage.
+        if let Some(duty_cycle) = self.prover_duty_cycle {
+            ensure!(
+                (1..=100).contains(&duty_cycle),
+                "Invalid value for 'prover_duty_cycle': expected 1 to 100, found {duty_cycle}"
+            );
+        }
+        // Ensure the REST server has an address to listen on.
+        if let Some(listen) = &self.rest.listen {
+            ensure!(!listen.is_empty(), "Invalid value for 'rest.listen': must not be empty");
+        }
+        // Ensure the REST rate limit is nonzero.
+        if let Some(rps) = self.rest.rps {
+            ensure!(rps > 0, "Invalid value for 'rest.rps': must be greater than 0");
+        }
+        // Ensure the maximum number of peers per subnet is nonzero.
+        if let Some(max_peers_per_subnet) = self.p2p.max_peers_per_subnet {
+            ensure!(max_peers_per_subnet > 0, "Invalid value for 'p2p.max_peers_per_subnet': must be greater than 0");
+        }
+        // Ensure the propagation sample rate is a fraction.
+        if let Some(sample_rate) = self.p2p.propagation_sample_rate {
+            ensure!(
+                (0.0..=1.0).contains(&sample_rate),
+                "Invalid value for 'p2p.propagation_sample_rate': expected 0.0 to 1.0, found {sample_rate}"
+            );
+        }
+        // Ensure the API tokens are well-formed.
+        if let Some(tokens) = &self.rest.tokens {
+            ApiTokens::new(tokens).map_err(|error| anyhow!("Invalid value for 'rest.tokens': {error}"))?;
+        }
+        // Ensure the verbosity is within range.
+        if let Some(verbosity) = self.log.verbosity {
+            ensure!(
+                verbosity <= Self::MAX_VERBOSITY,
+                "Invalid value for 'log.verbosity': expected 0 to {}, found {verbosity}",
+                Self::MAX_VERBOSITY
+            );
+        }
+        // Ensure the CDN is not empty, if it is enabled.
+        if let Some(url) = &self.cdn.url {
+            if self.cdn.enabled != Some(false) {
+                ensure!(!url.trim().is_empty(), "Invalid value for 'cdn.url': must not be empty");
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for NodeConfig {
+    type Err = anyhow::Error;
+
+    /// Parses and checks the node configuration from a TOML string.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Note: The TOML error already names the offending key and its location.
+        let config: Self = toml::from_str(s).map_err(|error| anyhow!("{error}"))?;
+        config.check()?;
+        Ok(config)
+    }
+}
+
+/// Deserializes either a single value or a list of values into a list.
+fn deserialize_one_or_many<'de, D: Deserializer<'de>, T: Deserialize<'de>>(
+    deserializer: D,
+) -> Result<Option<Vec<T>>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany<T> {
+        One(T),
+        Many(Vec<T>),
+    }
+    match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(value) => Ok(Some(vec![value])),
+        OneOrMany::Many(values) => Ok(Some(values)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkos_node_rest::Scope;
+
+    /// Returns the template with every setting uncommented.
+    fn uncommented_template() -> String {
+        CONFIG_TEMPLATE
+            .lines()
+            .map(|line| match line.strip_prefix('#') {
+                Some(setting) if !setting.is_empty() && !setting.starts_with(' ') => setting,
+                _ => line,
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_template() {
+        // The template, as generated, does not set anything.
+        assert_eq!(NodeConfig::from_str(CONFIG_TEMPLATE).unwrap(), NodeConfig::default());
+
+        // Every setting in the template is a known key with a well-typed value.
+        let config: NodeConfig = toml::from_str(&uncommented_template()).unwrap();
+        assert_eq!(config.node_type, Some(ConfigNodeType::Client));
+        assert_eq!(config.p2p.listen, Some(SocketAddr::from_str("0.0.0.0:4130").unwrap()));
+        assert_eq!(config.p2p.outbound_bandwidth_weights, Some(vec![8, 4, 2, 1]));
+        assert_eq!(config.p2p.max_peers_per_subnet, Some(3));
+        assert_eq!(config.p2p.audit_log_max_size, Some(64 * 1024 * 1024));
+        assert_eq!(config.p2p.propagation_sample_rate, Some(0.0));
+        assert_eq!(config.rest.rps, Some(10));
+        assert_eq!(config.rest.metrics, Some(false));
+        assert_eq!(config.rest.debug, Some(false));
+        assert_eq!(config.rest.block_archive, Some(false));
+        assert_eq!(config.rest.tokens.unwrap()[0].scopes, vec![Scope::Read]);
+        assert_eq!(config.record_index, Some(false));
+        assert_eq!(config.record_scan_persist, Some(false));
+        assert_eq!(config.state_changes, Some(false));
+        assert_eq!(config.state_changes_retention, Some(50000));
+        assert_eq!(config.program_denylist, Some(vec!["spam_faucet.aleo".to_string()]));
+        assert_eq!(config.verification_threads, Some(4));
+        assert_eq!(config.prover_threads, Some(4));
+        assert_eq!(config.prover_duty_cycle, Some(100));
+        assert_eq!(config.log.verbosity, Some(1));
+        assert_eq!(config.dev.bonded_balances.unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_partial_config() {
+        let config = NodeConfig::from_str("node_type = \"validator\"\n[rest]\nrps = 20\n").unwrap();
+        assert_eq!(config.node_type, Some(ConfigNodeType::Validator));
+        assert_eq!(config.rest.rps, Some(20));
+        assert_eq!(NodeConfig::from_str("node_type = \"archive\"").unwrap().node_type, Some(ConfigNodeType::Archive));
+        assert_eq!(config.rest.listen, None);
+        assert_eq!(config.p2p, P2PConfig::default());
+        assert_eq!(config.log, LogConfig::default());
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        // Returns the error message for the given configuration.
+        let error = |config: &str| format!("{:#}", NodeConfig::from_str(config).unwrap_err());
+
+        // Unknown keys are rejected.
+        assert!(error("bogus = 1").contains("bogus"));
+        assert!(error("[rest]\nport = 3030").contains("port"));
+        assert!(error("[bogus]").contains("bogus"));
+        // Malformed values name the offending key.
+        assert!(error("[rest]\nrps = \"ten\"").contains("rest.rps"));
+        assert!(error("[p2p]\npeers = [\"127.0.0.1\"]").contains("p2p.peers"));
+        assert!(error("node_type = \"miner\"").contains("node_type"));
+        // Out-of-range values name the offending key.
+        assert!(error("network = 1").contains("'network'"));
+        assert!(error("[rest]\nrps = 0").contains("'rest.rps'"));
+        assert!(error("[p2p]\nmax_peers_per_subnet = 0").contains("'p2p.max_peers_per_subnet'"));
+        assert!(error("state_changes_retention = 0").contains("'state_changes_retention'"));
+        assert!(error("prover_duty_cycle = 0").contains("'prover_duty_cycle'"));
+        assert!(error("[rest]\nlisten = []").contains("'rest.listen'"));
+        assert!(error("[rest]\nlisten = [\"localhost\"]").contains("rest.listen"));
+        assert!(error("[log]\nverbosity = 5").contains("'log.verbosity'"));
+        assert!(error("[rest]\ntokens = [{ token = \"short\", scopes = [\"read\"] }]").contains("'rest.tokens'"));
+        assert!(error("[rest]\ntokens = [{ token = \"0123456789abcdef\", scopes = [\"root\"] }]").contains("root"));
+        assert!(error("[cdn]\nurl = \"\"").contains("'cdn.url'"));
+        assert!(error("private_key = \"key\"\nprivate_key_file = \"key.txt\"").contains("'private_key_file'"));
+        assert!(error("private_key = \"key\"\nsigner = \"tcp://127.0.0.1:4000\"").contains("'signer'"));
+        assert!(error("private_key = \"key\"\naccount_mnemonic_file = \"m.txt\"").contains("'account_mnemonic_file'"));
+        assert!(error("account_index = 1").contains("'account_index'"));
+    }
+
+    #[test]
+    fn test_generate() {
+        let path = std::env::temp_dir().join(format!("snarkos-config-{}.toml", std::process::id()));
+
+        // Generate the template.
+        Generate { path: path.clone(), force: false }.parse().unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), CONFIG_TEMPLATE);
+        assert_eq!(NodeConfig::load(&path).unwrap(), NodeConfig::default());
+        // An existing file is only overwritten if forced.
+        assert!(Generate { path: path.clone(), force: false }.parse().is_err());
+        assert!(Generate { path: path.clone(), force: true }.parse().is_ok());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
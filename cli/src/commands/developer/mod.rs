@@ -24,6 +24,9 @@ pub use execute::*;
 mod scan;
 pub use scan::*;
 
+mod transfer;
+pub use transfer::*;
+
 mod transfer_private;
 pub use transfer_private::*;
 
@@ -49,6 +52,7 @@ use snarkvm::{
 use anyhow::{bail, ensure, Result};
 use clap::Parser;
 use colored::Colorize;
+use serde_json::Value as JsonValue;
 use std::{path::PathBuf, str::FromStr};
 
 type CurrentAleo = snarkvm::circuit::AleoV0;
@@ -65,6 +69,8 @@ pub enum Developer {
     Execute(Execute),
     /// Scan the node for records.
     Scan(Scan),
+    /// Transfer public credits with a private key from a file, and broadcast the transfer to a node.
+    Transfer(Transfer),
     /// Execute the `credits.aleo/transfer_private` function.
     TransferPrivate(TransferPrivate),
 }
@@ -76,6 +82,7 @@ impl Developer {
             Self::Deploy(deploy) => deploy.parse(),
             Self::Execute(execute) => execute.parse(),
             Self::Scan(scan) => scan.parse(),
+            Self::Transfer(transfer) => transfer.parse(),
             Self::TransferPrivate(transfer_private) => transfer_private.parse(),
         }
     }
@@ -197,13 +204,21 @@ impl Developer {
         if let Some(endpoint) = broadcast {
             // Send the deployment request to the local development node.
             match ureq::post(endpoint).send_json(&transaction) {
-                Ok(id) => {
-                    // Remove the quotes from the response.
-                    let response_string = id.into_string()?.trim_matches('\"').to_string();
+                Ok(response) => {
+                    // Note: The response is the transaction ID, or an object with the transaction ID
+                    // if the transaction replaced or is queued behind any unconfirmed transactions.
+                    let response = response.into_json::<JsonValue>()?;
+                    let response_id = response.get("transaction_id").unwrap_or(&response);
                     ensure!(
-                        response_string == transaction_id.to_string(),
-                        "The response does not match the transaction id. ({response_string} != {transaction_id})"
+                        response_id.as_str() == Some(transaction_id.to_string().as_str()),
+                        "The response does not match the transaction id. ({response_id} != {transaction_id})"
                     );
+                    if let Some(replaced) = response.get("replaced") {
+                        println!("🔁 Transaction {transaction_id} replaced the unconfirmed transactions {replaced}");
+                    }
+                    if let Some(conflicts) = response.get("queued_behind") {
+                        println!("⏸️  Transaction {transaction_id} is queued behind the transactions {conflicts}");
+                    }
 
                     match transaction {
                         Transaction::Deploy(..) => {
@@ -258,7 +258,7 @@ impl Scan {
         // Scan the blocks via the CDN.
         rt.block_on(async move {
             let _ = snarkos_node_cdn::load_blocks(
-                &cdn,
+                &[cdn],
                 cdn_request_start,
                 Some(cdn_request_end),
                 _shutdown,
@@ -0,0 +1,238 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{CurrentNetwork, Developer};
+use crate::commands::check_permissions;
+use snarkvm::prelude::{
+    query::Query,
+    store::{helpers::memory::ConsensusMemory, ConsensusStore},
+    Address,
+    PrivateKey,
+    Value,
+    VM,
+};
+
+use aleo_std::StorageMode;
+use anyhow::{bail, ensure, Context, Result};
+use clap::Parser;
+use colored::Colorize;
+use serde_json::Value as JsonValue;
+use std::{
+    path::PathBuf,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+use zeroize::Zeroize;
+
+/// The timeout of a request to the REST API of the node, in seconds.
+const REQUEST_TIMEOUT_IN_SECS: u64 = 30;
+/// The interval at which the status of the transaction is polled, in seconds.
+const WAIT_INTERVAL_IN_SECS: u64 = 2;
+
+/// Transfers public credits with the `credits.aleo/transfer_public` function, signed with the key in the given file.
+#[derive(Debug, Parser)]
+pub struct Transfer {
+    /// The recipient address.
+    #[clap(long)]
+    to: Address<CurrentNetwork>,
+    /// The number of microcredits to transfer.
+    #[clap(long)]
+    amount: u64,
+    /// The priority fee in microcredits.
+    #[clap(long)]
+    fee: u64,
+    /// The path to the file containing the private key of the sender (readable only by the owner).
+    #[clap(long)]
+    keystore: PathBuf,
+    /// The REST API of the node used to query the state, and to broadcast the transaction.
+    #[clap(default_value = "http://127.0.0.1:3030", long)]
+    rest: String,
+    /// If set, waits until the transaction is confirmed, or until the timeout elapses.
+    #[clap(long)]
+    wait: bool,
+    /// The maximum time to wait for the transaction to be confirmed, in seconds.
+    #[clap(default_value = "120", long, requires = "wait")]
+    wait_timeout: u64,
+}
+
+impl Transfer {
+    /// Creates, signs, and broadcasts the transfer, and optionally waits for it to be confirmed.
+    pub fn parse(self) -> Result<String> {
+        // Retrieve the private key of the sender.
+        let private_key = self.read_private_key()?;
+        let sender = Address::try_from(&private_key)?;
+        let rest = self.rest.trim_end_matches('/').to_string();
+
+        println!("📦 Creating public transfer of {} microcredits to {}...\n", self.amount, self.to);
+
+        // Generate the transfer_public transaction.
+        // Note: The `credits.aleo` program is embedded in the VM, so only the state root is queried from the node.
+        let transaction = {
+            let store =
+                ConsensusStore::<CurrentNetwork, ConsensusMemory<CurrentNetwork>>::open(StorageMode::Production)?;
+            let vm = VM::from(store)?;
+            let inputs = [Value::from_str(&self.to.to_string())?, Value::from_str(&format!("{}u64", self.amount))?];
+            vm.execute(
+                &private_key,
+                ("credits.aleo", "transfer_public"),
+                inputs.iter(),
+                None,
+                self.fee,
+                Some(Query::from(&rest)),
+                &mut rand::thread_rng(),
+            )?
+        };
+        let transaction_id = transaction.id();
+
+        // Ensure the sender can pay for the transfer and its fee, as an underfunded transfer is only rejected in a block.
+        let fee = *transaction.fee_amount()?;
+        let Some(total) = self.amount.checked_add(fee) else {
+            bail!("The transfer of {} microcredits and its fee of {fee} microcredits overflow", self.amount)
+        };
+        let balance = Developer::get_public_balance(&sender, &rest)?;
+        ensure!(
+            balance >= total,
+            "Insufficient balance: {sender} has {balance} microcredits, and the transfer requires {total} microcredits ({} + a fee of {fee})",
+            self.amount
+        );
+        println!("✅ Created public transfer {transaction_id} with a fee of {fee} microcredits\n");
+
+        // Broadcast the transaction, surfacing the reason if the node rejects it.
+        let endpoint = format!("{rest}/mainnet/transaction/broadcast");
+        let response =
+            match ureq::post(&endpoint).timeout(Duration::from_secs(REQUEST_TIMEOUT_IN_SECS)).send_json(&transaction) {
+                Ok(response) => response.into_json::<JsonValue>()?,
+                Err(ureq::Error::Status(code, response)) => {
+                    let reason = response.into_string().unwrap_or_default();
+                    bail!("❌ The node rejected transfer {transaction_id} (status {code}) - {reason}")
+                }
+                Err(ureq::Error::Transport(error)) => bail!("❌ Unable to reach the node at {rest} ({error})"),
+            };
+        // Note: A transaction that conflicts with an unconfirmed transaction may be queued behind it.
+        if let Some(conflicts) = response.get("queued_behind") {
+            println!("⏸️  Transfer {transaction_id} is queued behind the unconfirmed transactions {conflicts}\n");
+        }
+        println!("⌛ Transfer {transaction_id} has been broadcast to {rest}.\n");
+
+        match self.wait {
+            true => self.wait_for_confirmation(&rest, &transaction_id.to_string()),
+            false => Ok(transaction_id.to_string()),
+        }
+    }
+
+    /// Returns the private key in the keystore file, after ensuring the file is readable only by the owner.
+    fn read_private_key(&self) -> Result<PrivateKey<CurrentNetwork>> {
+        check_permissions(&self.keystore)?;
+        let mut contents = std::fs::read_to_string(&self.keystore)
+            .with_context(|| format!("Failed to read the keystore '{}'", self.keystore.display()))?;
+        let private_key = PrivateKey::from_str(contents.trim());
+        contents.zeroize();
+        private_key
+            .with_context(|| format!("The keystore '{}' does not contain a private key", self.keystore.display()))
+    }
+
+    /// Polls the node until the transaction is confirmed, rejected, or the timeout elapses.
+    fn wait_for_confirmation(&self, rest: &str, transaction_id: &str) -> Result<String> {
+        let get = |route: String| -> Result<JsonValue> {
+            let endpoint = format!("{rest}/mainnet/{route}");
+            Ok(ureq::get(&endpoint).timeout(Duration::from_secs(REQUEST_TIMEOUT_IN_SECS)).call()?.into_json()?)
+        };
+
+        let deadline = Instant::now() + Duration::from_secs(self.wait_timeout);
+        loop {
+            // A node with a memory pool reports the outcome of the transaction, including the reason for a rejection.
+            // Otherwise, the transaction is confirmed once it is found in a block.
+            match get(format!("transaction/{transaction_id}/outcome")) {
+                Ok(outcome) => match outcome["status"].as_str() {
+                    Some("accepted") => {
+                        return Ok(format!("✅ Transfer {transaction_id} was accepted in block {}", outcome["height"]));
+                    }
+                    Some("rejected") => bail!(
+                        "❌ Transfer {transaction_id} was rejected in block {} - {}",
+                        outcome["height"],
+                        outcome["reason"]
+                    ),
+                    Some("evicted") => {
+                        bail!("❌ Transfer {transaction_id} was evicted from the memory pool - {}", outcome["cause"])
+                    }
+                    _ => (),
+                },
+                Err(_) => {
+                    if let Ok(block_hash @ JsonValue::String(_)) = get(format!("find/blockHash/{transaction_id}")) {
+                        return Ok(format!("✅ Transfer {transaction_id} was confirmed in block {block_hash}"));
+                    }
+                }
+            }
+            ensure!(
+                Instant::now() < deadline,
+                "Transfer {transaction_id} was not confirmed within {} seconds",
+                self.wait_timeout
+            );
+            println!("{}", format!("Waiting for transfer {transaction_id} to be confirmed...").dimmed());
+            std::thread::sleep(Duration::from_secs(WAIT_INTERVAL_IN_SECS));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkvm::prelude::TestRng;
+
+    /// Returns a transfer that is signed with the key in the given keystore.
+    fn sample_transfer(keystore: &std::path::Path) -> Transfer {
+        let to = Address::try_from(PrivateKey::<CurrentNetwork>::new(&mut TestRng::default()).unwrap()).unwrap();
+        let args = ["snarkos", "--to", &to.to_string(), "--amount", "1", "--fee", "0", "--keystore"];
+        Transfer::try_parse_from(args.into_iter().chain([keystore.to_str().unwrap()])).unwrap()
+    }
+
+    #[test]
+    fn test_wait_timeout_requires_wait() {
+        let to = Address::try_from(PrivateKey::<CurrentNetwork>::new(&mut TestRng::default()).unwrap()).unwrap();
+        let args = ["snarkos", "--to", &to.to_string(), "--amount", "1", "--fee", "0", "--keystore", "key"];
+        assert!(Transfer::try_parse_from(args.into_iter().chain(["--wait-timeout", "5"])).is_err());
+        let transfer = Transfer::try_parse_from(args.into_iter().chain(["--wait", "--wait-timeout", "5"])).unwrap();
+        assert!(transfer.wait);
+        assert_eq!(transfer.wait_timeout, 5);
+        assert_eq!(transfer.rest, "http://127.0.0.1:3030");
+    }
+
+    #[cfg(target_family = "unix")]
+    #[test]
+    fn test_read_private_key() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let directory = std::env::temp_dir().join(format!("snarkos-keystore-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        std::fs::set_permissions(&directory, std::fs::Permissions::from_mode(0o700)).unwrap();
+        let keystore = directory.join("private.key");
+        let private_key = PrivateKey::<CurrentNetwork>::new(&mut TestRng::default()).unwrap();
+        std::fs::write(&keystore, format!("{private_key}\n")).unwrap();
+
+        // Ensure a keystore that is readable by others is rejected.
+        std::fs::set_permissions(&keystore, std::fs::Permissions::from_mode(0o644)).unwrap();
+        assert!(sample_transfer(&keystore).read_private_key().is_err());
+
+        // Ensure the private key is read from a keystore that is readable only by the owner.
+        std::fs::set_permissions(&keystore, std::fs::Permissions::from_mode(0o600)).unwrap();
+        assert_eq!(sample_transfer(&keystore).read_private_key().unwrap(), private_key);
+
+        // Ensure a keystore without a private key is rejected.
+        std::fs::write(&keystore, "not a private key").unwrap();
+        let error = sample_transfer(&keystore).read_private_key().unwrap_err();
+        assert!(error.to_string().contains("does not contain a private key"), "{error}");
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,247 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::Start;
+use snarkos_node::{router::messages::NodeType, Node};
+use snarkvm::console::network::MainnetV0;
+
+use anyhow::{ensure, Result};
+use clap::Parser;
+use colored::Colorize;
+use std::{
+    net::{SocketAddr, TcpListener},
+    path::PathBuf,
+    sync::Arc,
+    time::Duration,
+};
+use tokio::runtime::{self, Runtime};
+
+/// The minimum number of validators in a devnet, which is the minimum size of the development committee.
+const MIN_DEVNET_VALIDATORS: u16 = 4;
+/// The number of tokio worker threads of each node in the devnet.
+const NUM_WORKER_THREADS_PER_NODE: usize = 2;
+/// The interval at which the block heights of the nodes are checked, in seconds.
+const HEIGHT_CHECK_INTERVAL_IN_SECS: u64 = 1;
+
+/// Starts a devnet of validators and clients in a single process.
+#[derive(Clone, Debug, Parser)]
+pub struct Devnet {
+    /// Specify the number of validators in the devnet
+    #[clap(default_value = "4", long = "validators")]
+    pub validators: u16,
+    /// Specify the number of clients in the devnet
+    #[clap(default_value = "1", long = "clients")]
+    pub clients: u16,
+    /// If set, the devnet exits once every node has reached the given block height
+    #[clap(long = "blocks")]
+    pub blocks: Option<u32>,
+    /// Specify the path to the directory containing the storage of the nodes [default: a new temporary directory]
+    #[clap(long = "storage")]
+    pub storage: Option<PathBuf>,
+    /// If the flag is set, node 0 will not generate traffic to drive the network
+    #[clap(long = "no-dev-txs")]
+    pub no_dev_txs: bool,
+    /// If the flag is set, the nodes will serve their block archive, for other nodes to prefetch blocks from
+    #[clap(long = "serve-block-archive")]
+    pub serve_block_archive: bool,
+    /// Specify the fraction of the transactions broadcast by the nodes, whose propagation delay is sampled
+    #[clap(default_value_t = 0.0, long = "propagation-sample-rate")]
+    pub propagation_sample_rate: f64,
+    /// Specify the verbosity of the nodes [options: 0, 1, 2, 3, 4]
+    #[clap(default_value = "1", long = "verbosity")]
+    pub verbosity: u8,
+}
+
+/// The addresses assigned to a node in the devnet.
+#[derive(Copy, Clone, Debug)]
+struct DevnetNode {
+    /// The node type.
+    node_type: NodeType,
+    /// The IP of the node server.
+    node_ip: SocketAddr,
+    /// The IP of the BFT, if the node is a validator.
+    bft_ip: Option<SocketAddr>,
+    /// The IP of the REST server.
+    rest_ip: SocketAddr,
+}
+
+impl Devnet {
+    /// Starts the devnet.
+    pub fn parse(self) -> Result<String> {
+        ensure!(
+            self.validators >= MIN_DEVNET_VALIDATORS,
+            "A devnet requires at least {MIN_DEVNET_VALIDATORS} validators, found {}",
+            self.validators
+        );
+
+        // Initialize the storage directory.
+        let storage = match &self.storage {
+            Some(storage) => storage.clone(),
+            None => std::env::temp_dir().join(format!("snarkos-devnet-{}", std::process::id())),
+        };
+        std::fs::create_dir_all(&storage)?;
+        // Note: The storage of a development node is in the current directory, and is named after its ID.
+        // Moving into the storage directory gives each node a distinct directory under it.
+        std::env::set_current_dir(&storage)?;
+        println!("📁 The storage of the devnet is at {}.\n", storage.display().to_string().bold());
+
+        // Initialize the logger, prefixing each line with the name of the node.
+        crate::helpers::initialize_devnet_logger(self.verbosity, storage.join("devnet.log"));
+
+        // Assign the addresses of the nodes.
+        let nodes = self.assign_nodes()?;
+        // Initialize the parallelization parameters, which are shared by the nodes.
+        rayon::ThreadPoolBuilder::new().stack_size(8 * 1024 * 1024).num_threads(num_cpus::get()).build_global()?;
+        // Initialize a runtime for each node, whose threads are named after the node.
+        let runtimes = (0..nodes.len()).map(Self::runtime).collect::<Result<Vec<_>>>()?;
+
+        // Run the devnet.
+        let result = runtime::Builder::new_current_thread().enable_all().build()?.block_on(self.run(&nodes, &runtimes));
+
+        // Stop the runtimes, without waiting for their remaining tasks.
+        runtimes.into_iter().for_each(Runtime::shutdown_background);
+        result
+    }
+
+    /// Returns the addresses of the nodes, on local ports that are currently unused.
+    fn assign_nodes(&self) -> Result<Vec<DevnetNode>> {
+        // Reserve the ports at once, so that no port is assigned twice.
+        let num_nodes = self.validators as usize + self.clients as usize;
+        let listeners = (0..3 * num_nodes).map(|_| TcpListener::bind("127.0.0.1:0")).collect::<Result<Vec<_>, _>>()?;
+        let ips = listeners.iter().map(|listener| listener.local_addr()).collect::<Result<Vec<_>, _>>()?;
+        // Release the ports, so that the nodes can bind to them.
+        drop(listeners);
+
+        Ok(ips
+            .chunks(3)
+            .enumerate()
+            .map(|(index, ips)| {
+                let is_validator = index < self.validators as usize;
+                DevnetNode {
+                    node_type: if is_validator { NodeType::Validator } else { NodeType::Client },
+                    node_ip: ips[0],
+                    bft_ip: is_validator.then_some(ips[1]),
+                    rest_ip: ips[2],
+                }
+            })
+            .collect())
+    }
+
+    /// Returns a runtime for the node with the given index.
+    fn runtime(index: usize) -> Result<Runtime> {
+        Ok(runtime::Builder::new_multi_thread()
+            .enable_all()
+            .thread_name(format!("node-{index}"))
+            .thread_stack_size(8 * 1024 * 1024)
+            .worker_threads(NUM_WORKER_THREADS_PER_NODE)
+            .build()?)
+    }
+
+    /// Starts the nodes, and runs until Ctrl-C, or until every node reaches the given block height.
+    async fn run(&self, nodes: &[DevnetNode], runtimes: &[Runtime]) -> Result<String> {
+        // Start the nodes in order, as each node connects to the nodes before it.
+        let mut started = Vec::with_capacity(nodes.len());
+        for (index, (node, runtime)) in nodes.iter().zip(runtimes).enumerate() {
+            // Connect to the nodes before this one, as in development mode.
+            let trusted_peers = nodes[..index].iter().map(|node| node.node_ip).collect::<Vec<_>>();
+            // Connect to the BFT of every other validator.
+            let trusted_validators = nodes
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != index)
+                .filter_map(|(_, node)| node.bft_ip)
+                .collect::<Vec<_>>();
+
+            // Initialize the node with the configurations of `snarkos start`.
+            let mut config = Start::devnet_node(
+                index as u16,
+                self.validators,
+                node.node_type,
+                node.node_ip,
+                node.bft_ip,
+                node.rest_ip,
+                &trusted_peers,
+                &trusted_validators,
+                !self.no_dev_txs,
+                self.serve_block_archive,
+                self.propagation_sample_rate,
+            )?;
+            let handle = runtime.spawn(async move { config.parse_node::<MainnetV0>().await });
+            started.push(Arc::new(handle.await??));
+
+            println!(
+                "🌐 Node {index} ({}) is listening at {}, and serving the REST API at {}.\n",
+                node.node_type,
+                node.node_ip,
+                format!("http://{}", node.rest_ip).bold()
+            );
+        }
+
+        // If no block height is given, run until Ctrl-C, which is handled by the nodes.
+        let Some(blocks) = self.blocks else {
+            return std::future::pending().await;
+        };
+
+        // Wait until every node reaches the given block height.
+        loop {
+            let heights = started.iter().filter_map(|node| node.latest_height()).collect::<Vec<_>>();
+            if heights.iter().all(|height| *height >= blocks) {
+                break;
+            }
+            tokio::time::sleep(Duration::from_secs(HEIGHT_CHECK_INTERVAL_IN_SECS)).await;
+        }
+
+        // Shut down the nodes.
+        for (node, runtime) in started.into_iter().zip(runtimes) {
+            runtime.spawn(async move { node.shut_down().await }).await?;
+        }
+        Ok(format!("✅ The devnet of {} nodes reached block {blocks}", nodes.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assign_nodes() {
+        let devnet = Devnet::try_parse_from(["snarkos", "--validators", "4", "--clients", "2"].iter()).unwrap();
+        let nodes = devnet.assign_nodes().unwrap();
+        assert_eq!(nodes.len(), 6);
+
+        // Ensure the first nodes are the validators, and only the validators have a BFT.
+        for (index, node) in nodes.iter().enumerate() {
+            assert_eq!(node.node_type.is_validator(), index < 4);
+            assert_eq!(node.bft_ip.is_some(), index < 4);
+        }
+
+        // Ensure every port is distinct.
+        let mut ports = nodes
+            .iter()
+            .flat_map(|node| [Some(node.node_ip), node.bft_ip, Some(node.rest_ip)])
+            .flatten()
+            .map(|ip| ip.port())
+            .collect::<Vec<_>>();
+        let num_ports = ports.len();
+        ports.sort_unstable();
+        ports.dedup();
+        assert_eq!(ports.len(), num_ports);
+    }
+
+    #[test]
+    fn test_devnet_requires_committee() {
+        let devnet = Devnet::try_parse_from(["snarkos", "--validators", "3"].iter()).unwrap();
+        assert!(devnet.parse().is_err());
+    }
+}
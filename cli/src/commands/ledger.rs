@@ -0,0 +1,707 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkos_node::bft::ledger_service::{ConsistencyCheck, FilesystemSpaceReporter, SpaceReporter};
+use snarkvm::{
+    console::network::{MainnetV0, Network},
+    ledger::{
+        committee::Committee,
+        store::{helpers::rocksdb::ConsensusDB, ConsensusStore},
+    },
+};
+
+use aleo_std::StorageMode;
+use anyhow::{anyhow, bail, ensure, Result};
+use clap::Parser;
+use colored::Colorize;
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    num::NonZeroU32,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::atomic::AtomicBool,
+};
+
+/// The length of the key prefix of each map in the ledger, which is the network ID and the map ID.
+/// Note: This matches the prefix extractor of the ledger database of the node.
+const MAP_PREFIX_LEN: usize = 4;
+
+/// Commands to inspect the ledger in storage.
+#[derive(Debug, Parser)]
+pub enum Ledger {
+    /// Check the consistency of the ledger in storage, while the node is stopped.
+    Check(Check),
+    /// Export the committee and stake history of the ledger in storage, while the node is stopped.
+    ExportCommittees(ExportCommittees),
+    /// Compact the ledger in storage, to reclaim the space of stale entries, while the node is stopped.
+    Compact(Compact),
+    /// Clone the ledger in storage into a fresh, compacted copy, while the node is stopped.
+    Clone(CloneLedger),
+}
+
+impl Ledger {
+    pub fn parse(self) -> Result<String> {
+        match self {
+            Self::Check(check) => check.parse(),
+            Self::ExportCommittees(export) => export.parse(),
+            Self::Compact(compact) => compact.parse(),
+            Self::Clone(clone) => clone.parse(),
+        }
+    }
+}
+
+/// Checks the consistency of the ledger in storage.
+#[derive(Debug, Parser)]
+pub struct Check {
+    /// Specify the network of the ledger.
+    #[clap(default_value = "0", long = "network")]
+    pub network: u16,
+    /// Enables development mode, specify the unique ID of the local node to check.
+    #[clap(long)]
+    pub dev: Option<u16>,
+    /// Specify the path to a directory containing the ledger
+    #[clap(long = "path")]
+    pub path: Option<PathBuf>,
+    /// If the flag is set, every block is re-validated on top of its previous blocks, which can take a long time
+    #[clap(long)]
+    pub deep: bool,
+    /// Specify the number of latest blocks over which the state root is recomputed
+    #[clap(long = "state-root-depth")]
+    pub state_root_depth: Option<u32>,
+}
+
+impl Check {
+    /// Checks the consistency of the ledger.
+    pub fn parse(self) -> Result<String> {
+        let storage_mode = match &self.path {
+            Some(path) => StorageMode::Custom(path.clone()),
+            None => StorageMode::from(self.dev),
+        };
+        match self.network {
+            0 => self.check::<MainnetV0>(storage_mode),
+            _ => bail!("Unsupported network ID {}", self.network),
+        }
+    }
+
+    /// Checks the consistency of the ledger in the given storage.
+    fn check<N: Network>(&self, storage_mode: StorageMode) -> Result<String> {
+        // Ensure the ledger exists, as loading it would otherwise initialize a new one.
+        let path = aleo_std::aleo_ledger_dir(self.network, storage_mode.clone());
+        if !path.exists() {
+            bail!("No snarkOS node storage was found (in \"{}\")", path.display());
+        }
+        // Read the genesis block from storage.
+        let genesis = {
+            let store = ConsensusStore::<N, ConsensusDB<N>>::open(storage_mode.clone())?;
+            let hash = store.block_store().get_block_hash(0)?.ok_or_else(|| anyhow!("Missing the genesis block"))?;
+            store.block_store().get_block(&hash)?.ok_or_else(|| anyhow!("Missing the genesis block"))?
+        };
+        // Load the ledger.
+        let ledger = snarkvm::prelude::Ledger::<N, ConsensusDB<N>>::load(genesis, storage_mode)?;
+
+        // Run the check.
+        let mut check = ConsistencyCheck::default().with_deep(self.deep);
+        if let Some(state_root_depth) = self.state_root_depth {
+            check = check.with_state_root_depth(state_root_depth);
+        }
+        let report = check.run(&ledger, &AtomicBool::new(false), |progress| {
+            println!("Checked the {} up to block {}/{}", progress.stage, progress.height, progress.latest_height)
+        })?;
+
+        // Print the inconsistencies.
+        let mode = if report.is_deep { "deep" } else { "shallow" };
+        let summary = format!("the ledger up to block {} ({mode} check)", report.latest_height);
+        match report.is_consistent() {
+            true => Ok(format!("✅ No inconsistencies were found in {summary}").dimmed().to_string()),
+            false => {
+                for inconsistency in &report.inconsistencies {
+                    eprintln!("{}", inconsistency.to_string().red());
+                }
+                bail!("Found {} inconsistencies in {summary}", report.inconsistencies.len())
+            }
+        }
+    }
+}
+
+/// Compacts the ledger in storage.
+#[derive(Debug, Parser)]
+pub struct Compact {
+    /// Specify the network of the ledger.
+    #[clap(default_value = "0", long = "network")]
+    pub network: u16,
+    /// Enables development mode, specify the unique ID of the local node to compact.
+    #[clap(long)]
+    pub dev: Option<u16>,
+    /// Specify the path to a directory containing the ledger
+    #[clap(long = "path")]
+    pub path: Option<PathBuf>,
+}
+
+impl Compact {
+    /// Compacts the ledger.
+    pub fn parse(self) -> Result<String> {
+        let storage_mode = match &self.path {
+            Some(path) => StorageMode::Custom(path.clone()),
+            None => StorageMode::from(self.dev),
+        };
+        let path = aleo_std::aleo_ledger_dir(self.network, storage_mode);
+        let (size_before, size_after) = compact_ledger(&path, "compacting the ledger")?;
+        Ok(format!(
+            "✅ Compacted the ledger (in \"{}\") from {} to {}",
+            path.display(),
+            format_size(size_before),
+            format_size(size_after)
+        )
+        .dimmed()
+        .to_string())
+    }
+}
+
+/// Clones the ledger in storage into a fresh, compacted copy.
+#[derive(Debug, Parser)]
+pub struct CloneLedger {
+    /// Specify the network of the ledger.
+    #[clap(default_value = "0", long = "network")]
+    pub network: u16,
+    /// Enables development mode, specify the unique ID of the local node to clone.
+    #[clap(long)]
+    pub dev: Option<u16>,
+    /// Specify the path to a directory containing the ledger
+    #[clap(long = "path")]
+    pub path: Option<PathBuf>,
+    /// Specify the path of the new directory to clone the ledger into
+    #[clap(long = "to")]
+    pub to: PathBuf,
+}
+
+impl CloneLedger {
+    /// Clones the ledger.
+    pub fn parse(self) -> Result<String> {
+        let storage_mode = match &self.path {
+            Some(path) => StorageMode::Custom(path.clone()),
+            None => StorageMode::from(self.dev),
+        };
+        match self.network {
+            0 => self.clone_ledger::<MainnetV0>(storage_mode),
+            _ => bail!("Unsupported network ID {}", self.network),
+        }
+    }
+
+    /// Clones the ledger in the given storage, and ensures the clone has the same blocks.
+    fn clone_ledger<N: Network>(&self, storage_mode: StorageMode) -> Result<String> {
+        let path = aleo_std::aleo_ledger_dir(self.network, storage_mode.clone());
+        ensure!(!self.to.exists(), "The destination \"{}\" already exists", self.to.display());
+
+        // Create a checkpoint of the ledger.
+        // Note: The files of a checkpoint are hard links to the files of the ledger, if they are on the same file system.
+        {
+            let database = open_database(&path, "cloning the ledger")?;
+            println!("Creating a checkpoint of the ledger (in \"{}\")...", path.display());
+            rocksdb::checkpoint::Checkpoint::new(&database)?.create_checkpoint(&self.to)?;
+        }
+        // Compact the clone, which rewrites its files, so that it no longer shares any file with the ledger.
+        compact_ledger(&self.to, "cloning the ledger")?;
+        // Ensure the clone has the same entries as the ledger.
+        let num_entries = compare_databases(&path, &self.to)?;
+
+        // Ensure the clone has the same blocks as the ledger.
+        let (num_blocks, latest_hash) = read_latest_block::<N>(storage_mode)?;
+        let (clone_num_blocks, clone_latest_hash) = read_latest_block::<N>(StorageMode::Custom(self.to.clone()))?;
+        ensure!(
+            (num_blocks, latest_hash) == (clone_num_blocks, clone_latest_hash),
+            "The clone has {clone_num_blocks} blocks up to '{clone_latest_hash}', instead of {num_blocks} blocks up to '{latest_hash}'"
+        );
+
+        Ok(format!(
+            "✅ Cloned the ledger (in \"{}\") to \"{}\" ({num_entries} entries, {num_blocks} blocks up to '{latest_hash}', {})",
+            path.display(),
+            self.to.display(),
+            format_size(FilesystemSpaceReporter.directory_size(&self.to)?)
+        )
+        .dimmed()
+        .to_string())
+    }
+}
+
+/// Returns the number of blocks and the latest block hash of the ledger in the given storage.
+fn read_latest_block<N: Network>(storage_mode: StorageMode) -> Result<(u32, N::BlockHash)> {
+    let store = ConsensusStore::<N, ConsensusDB<N>>::open(storage_mode)?;
+    let height = store.block_store().max_height().ok_or_else(|| anyhow!("Missing the genesis block"))?;
+    let hash = store.block_store().get_block_hash(height)?.ok_or_else(|| anyhow!("Missing the block {height}"))?;
+    Ok((height + 1, hash))
+}
+
+/// Returns the options of the ledger database, which match the options of the node.
+fn database_options() -> rocksdb::Options {
+    let mut options = rocksdb::Options::default();
+    options.set_compression_type(rocksdb::DBCompressionType::Lz4);
+    options.set_prefix_extractor(rocksdb::SliceTransform::create_fixed_prefix(MAP_PREFIX_LEN));
+    options.increase_parallelism(2);
+    options.set_max_background_jobs(4);
+    options
+}
+
+/// Returns the read options to iterate over every entry of the ledger database, across the maps.
+fn total_order_read_options() -> rocksdb::ReadOptions {
+    let mut options = rocksdb::ReadOptions::default();
+    options.set_total_order_seek(true);
+    options
+}
+
+/// Opens the ledger database at the given path, with all of its column families, to perform the given action.
+/// Note: RocksDB only allows a single process to open the ledger, so the node must be stopped.
+fn open_database(path: &Path, action: &str) -> Result<rocksdb::DB> {
+    // Ensure the ledger exists, as opening it would otherwise fail with an obscure error.
+    if !path.exists() {
+        bail!("No snarkOS node storage was found (in \"{}\")", path.display());
+    }
+    let options = database_options();
+    let column_families = rocksdb::DB::list_cf(&options, path)?;
+    match rocksdb::DB::open_cf(&options, path, column_families) {
+        Ok(database) => Ok(database),
+        Err(error) if error.to_string().contains("LOCK") => {
+            bail!("The ledger is in use (in \"{}\"), stop the node before {action}", path.display())
+        }
+        Err(error) => Err(error.into()),
+    }
+}
+
+/// Compacts every column family of the ledger database at the given path, one map at a time, to perform the
+/// given action, and returns the size of the database before and after the compaction.
+fn compact_ledger(path: &Path, action: &str) -> Result<(u64, u64)> {
+    let size_before = FilesystemSpaceReporter.directory_size(path)?;
+    {
+        let database = open_database(path, action)?;
+        compact_database(&database, |column_family, num_compacted, num_maps| {
+            println!("Compacted {num_compacted}/{num_maps} maps of the column family '{column_family}'")
+        })?;
+    }
+    // Note: The files replaced by the compaction are deleted once the database is closed.
+    Ok((size_before, FilesystemSpaceReporter.directory_size(path)?))
+}
+
+/// Compacts every column family of the given database, one map at a time, and reports the progress
+/// with the name of the column family, the number of compacted maps, and the number of maps.
+fn compact_database(database: &rocksdb::DB, mut on_progress: impl FnMut(&str, usize, usize)) -> Result<()> {
+    let mut options = rocksdb::CompactOptions::default();
+    // Rewrite the files of the bottommost level as well, to drop the deleted and overwritten entries.
+    options.set_bottommost_level_compaction(rocksdb::BottommostLevelCompaction::Force);
+    for column_family in rocksdb::DB::list_cf(&database_options(), database.path())? {
+        let Some(handle) = database.cf_handle(&column_family) else {
+            bail!("Missing the column family '{column_family}'")
+        };
+        // Compact the key range of each map.
+        let prefixes = map_prefixes(database, handle);
+        for (i, prefix) in prefixes.iter().enumerate() {
+            database.compact_range_cf_opt(handle, Some(prefix), next_prefix(prefix), &options);
+            on_progress(&column_family, i + 1, prefixes.len());
+        }
+    }
+    Ok(())
+}
+
+/// Returns the distinct map prefixes of the keys in the given column family, in order.
+fn map_prefixes(database: &rocksdb::DB, handle: &rocksdb::ColumnFamily) -> Vec<Vec<u8>> {
+    let mut prefixes = Vec::new();
+    let mut iterator = database.raw_iterator_cf_opt(handle, total_order_read_options());
+    iterator.seek_to_first();
+    while let Some(key) = iterator.key() {
+        let prefix = key[..key.len().min(MAP_PREFIX_LEN)].to_vec();
+        let next = next_prefix(&prefix);
+        prefixes.push(prefix);
+        // Skip to the first key after the map.
+        match next {
+            Some(next) => iterator.seek(next),
+            None => break,
+        }
+    }
+    prefixes
+}
+
+/// Returns the smallest key after every key with the given prefix, or `None` if there is no such key.
+fn next_prefix(prefix: &[u8]) -> Option<Vec<u8>> {
+    let mut next = prefix.to_vec();
+    while let Some(byte) = next.pop() {
+        if byte < u8::MAX {
+            next.push(byte + 1);
+            return Some(next);
+        }
+    }
+    None
+}
+
+/// Ensures the ledger databases at the given paths have the same entries, and returns the number of entries.
+fn compare_databases(path: &Path, other_path: &Path) -> Result<usize> {
+    let (database, other) =
+        (open_database(path, "cloning the ledger")?, open_database(other_path, "cloning the ledger")?);
+    let mut num_entries = 0;
+    for column_family in rocksdb::DB::list_cf(&database_options(), path)? {
+        let (Some(handle), Some(other_handle)) = (database.cf_handle(&column_family), other.cf_handle(&column_family))
+        else {
+            bail!("The column family '{column_family}' is missing from \"{}\"", other_path.display())
+        };
+        let entries = database.iterator_cf_opt(handle, total_order_read_options(), rocksdb::IteratorMode::Start);
+        let mut other_entries =
+            other.iterator_cf_opt(other_handle, total_order_read_options(), rocksdb::IteratorMode::Start);
+        for entry in entries {
+            ensure!(Some(entry?) == other_entries.next().transpose()?, "The clone differs from the ledger");
+            num_entries += 1;
+        }
+        ensure!(other_entries.next().is_none(), "The clone has more entries than the ledger");
+    }
+    Ok(num_entries)
+}
+
+/// Returns the given number of bytes in a human-readable form.
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    match unit {
+        0 => format!("{bytes} B"),
+        _ => format!("{size:.1} {}", UNITS[unit]),
+    }
+}
+
+/// The format of an exported committee history.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl FromStr for ExportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(format: &str) -> Result<Self> {
+        match format.to_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "json" => Ok(Self::Json),
+            _ => bail!("Unsupported export format '{format}' (expected 'csv' or 'json')"),
+        }
+    }
+}
+
+/// Exports the committee and stake history of the ledger in storage.
+#[derive(Debug, Parser)]
+pub struct ExportCommittees {
+    /// Specify the network of the ledger.
+    #[clap(default_value = "0", long = "network")]
+    pub network: u16,
+    /// Enables development mode, specify the unique ID of the local node to export from.
+    #[clap(long)]
+    pub dev: Option<u16>,
+    /// Specify the path to a directory containing the ledger
+    #[clap(long = "path")]
+    pub path: Option<PathBuf>,
+    /// Specify the first block height to export
+    #[clap(default_value = "0", long = "start")]
+    pub start: u32,
+    /// Specify the last block height to export (inclusive), or the latest block height if omitted
+    #[clap(long = "end")]
+    pub end: Option<u32>,
+    /// Specify the interval between exported block heights, to sample the history
+    #[clap(default_value = "1", long = "every")]
+    pub every: NonZeroU32,
+    /// Specify the format of the export, 'csv' or 'json'
+    #[clap(default_value = "csv", long = "format")]
+    pub format: ExportFormat,
+    /// Specify the path of the file to export to
+    #[clap(long = "output")]
+    pub output: PathBuf,
+}
+
+impl ExportCommittees {
+    /// Exports the committee history of the ledger.
+    pub fn parse(self) -> Result<String> {
+        let storage_mode = match &self.path {
+            Some(path) => StorageMode::Custom(path.clone()),
+            None => StorageMode::from(self.dev),
+        };
+        match self.network {
+            0 => self.export::<MainnetV0>(storage_mode),
+            _ => bail!("Unsupported network ID {}", self.network),
+        }
+    }
+
+    /// Exports the committee history of the ledger in the given storage.
+    fn export<N: Network>(&self, storage_mode: StorageMode) -> Result<String> {
+        // Ensure the ledger exists, as opening it would otherwise initialize a new one.
+        let path = aleo_std::aleo_ledger_dir(self.network, storage_mode.clone());
+        if !path.exists() {
+            bail!("No snarkOS node storage was found (in \"{}\")", path.display());
+        }
+        // Open the storage.
+        // Note: RocksDB only allows a single process to open the ledger, so the node must be stopped.
+        let store = match ConsensusStore::<N, ConsensusDB<N>>::open(storage_mode) {
+            Ok(store) => store,
+            Err(error) if error.to_string().contains("LOCK") => {
+                bail!("The ledger is in use (in \"{}\"), stop the node before exporting the committees", path.display())
+            }
+            Err(error) => return Err(error),
+        };
+        let committee_store = store.finalize_store().committee_store();
+        // Determine the range of block heights.
+        let end = match self.end {
+            Some(end) => end,
+            None => committee_store.current_height()?,
+        };
+        if self.start > end {
+            bail!("The start height {} is above the end height {end}", self.start);
+        }
+        // Stream the committees, one block height at a time.
+        let committees = (self.start..=end).step_by(self.every.get() as usize).map(|height| {
+            match committee_store.get_committee(height)? {
+                Some(committee) => Ok((height, committee)),
+                None => bail!("Missing the committee for block {height}"),
+            }
+        });
+        // Write the committees to the output file.
+        let mut writer = BufWriter::new(File::create(&self.output)?);
+        let num_rows = write_committees(&mut writer, self.format, committees)?;
+        writer.flush()?;
+
+        Ok(format!(
+            "✅ Exported {num_rows} committee members from blocks {} to {end} to \"{}\"",
+            self.start,
+            self.output.display()
+        )
+        .dimmed()
+        .to_string())
+    }
+}
+
+/// Writes one row per `(height, member address, stake, is_open)` of the given committees, with a header.
+/// Returns the number of rows written.
+fn write_committees<N: Network>(
+    writer: &mut impl Write,
+    format: ExportFormat,
+    committees: impl Iterator<Item = Result<(u32, Committee<N>)>>,
+) -> Result<usize> {
+    let mut num_rows = 0;
+    // Write the header.
+    match format {
+        ExportFormat::Csv => writeln!(writer, "height,address,stake,is_open")?,
+        ExportFormat::Json => write!(writer, "[")?,
+    }
+    // Write the rows.
+    for committee in committees {
+        let (height, committee) = committee?;
+        for (address, (stake, is_open)) in committee.members() {
+            match format {
+                ExportFormat::Csv => writeln!(writer, "{height},{address},{stake},{is_open}")?,
+                ExportFormat::Json => {
+                    let separator = if num_rows == 0 { "" } else { "," };
+                    let row =
+                        serde_json::json!({ "height": height, "address": address, "stake": stake, "is_open": is_open });
+                    write!(writer, "{separator}\n  {row}")?;
+                }
+            }
+            num_rows += 1;
+        }
+    }
+    // Write the footer.
+    if format == ExportFormat::Json {
+        writeln!(writer, "\n]")?;
+    }
+    Ok(num_rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkvm::prelude::{Address, PrivateKey, TestRng};
+
+    use indexmap::IndexMap;
+    use std::collections::BTreeMap;
+
+    type CurrentNetwork = MainnetV0;
+
+    /// Samples the committees for a small range of block heights, with stakes that change every block.
+    fn sample_committees(rng: &mut TestRng, num_heights: u32) -> Vec<(u32, Committee<CurrentNetwork>)> {
+        let addresses = (0..4)
+            .map(|_| Address::try_from(PrivateKey::<CurrentNetwork>::new(rng).unwrap()).unwrap())
+            .collect::<Vec<_>>();
+        (0..num_heights)
+            .map(|height| {
+                let members = addresses
+                    .iter()
+                    .enumerate()
+                    .map(|(i, address)| (*address, (10_000_000_000_000 + (height as u64 * 4 + i as u64), i % 2 == 0)))
+                    .collect::<IndexMap<_, _>>();
+                (height, Committee::new(height as u64 * 2, members).unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_export_committees_csv() {
+        let rng = &mut TestRng::default();
+        let committees = sample_committees(rng, 5);
+
+        // Export the committees.
+        let mut output = Vec::new();
+        let num_rows =
+            write_committees(&mut output, ExportFormat::Csv, committees.clone().into_iter().map(Ok)).unwrap();
+        assert_eq!(num_rows, 5 * 4);
+
+        // Parse the CSV back, and sum the stakes per block height.
+        let output = String::from_utf8(output).unwrap();
+        let mut lines = output.lines();
+        assert_eq!(lines.next(), Some("height,address,stake,is_open"));
+        let mut rows = BTreeMap::<u32, (usize, u64)>::new();
+        for line in lines {
+            let columns = line.split(',').collect::<Vec<_>>();
+            assert_eq!(columns.len(), 4);
+            let entry = rows.entry(columns[0].parse().unwrap()).or_default();
+            entry.0 += 1;
+            entry.1 += columns[2].parse::<u64>().unwrap();
+        }
+        // Ensure the counts and stake sums match the committee totals.
+        assert_eq!(rows.len(), committees.len());
+        for (height, committee) in &committees {
+            assert_eq!(rows[height], (committee.num_members(), committee.total_stake()));
+        }
+    }
+
+    #[test]
+    fn test_export_committees_json() {
+        let rng = &mut TestRng::default();
+        let committees = sample_committees(rng, 3);
+
+        // Export the committees.
+        let mut output = Vec::new();
+        write_committees(&mut output, ExportFormat::Json, committees.clone().into_iter().map(Ok)).unwrap();
+
+        // Parse the JSON back.
+        let rows: Vec<serde_json::Value> = serde_json::from_slice(&output).unwrap();
+        assert_eq!(rows.len(), 3 * 4);
+        for (height, committee) in &committees {
+            let stake = rows
+                .iter()
+                .filter(|row| row["height"].as_u64() == Some(*height as u64))
+                .map(|row| row["stake"].as_u64().unwrap())
+                .sum::<u64>();
+            assert_eq!(stake, committee.total_stake());
+        }
+
+        // Ensure an empty export is still valid JSON.
+        let mut output = Vec::new();
+        write_committees::<CurrentNetwork>(&mut output, ExportFormat::Json, std::iter::empty()).unwrap();
+        assert!(serde_json::from_slice::<Vec<serde_json::Value>>(&output).unwrap().is_empty());
+    }
+
+    /// Returns a new, empty directory for a test database.
+    fn temp_database_dir(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("snarkos-ledger-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        path
+    }
+
+    /// Writes entries into maps of a test database, and deletes or overwrites most of them.
+    fn populate_database(path: &Path) {
+        let mut options = database_options();
+        options.create_if_missing(true);
+        let database = rocksdb::DB::open(&options, path).unwrap();
+        for map_id in 0u16..4 {
+            let prefix = [0u16.to_le_bytes(), map_id.to_le_bytes()].concat();
+            for i in 0u32..2_000 {
+                let key = [prefix.as_slice(), &i.to_le_bytes()].concat();
+                database.put(&key, [map_id as u8; 256]).unwrap();
+                if i % 4 != 0 {
+                    database.delete(&key).unwrap();
+                } else {
+                    database.put(&key, i.to_le_bytes()).unwrap();
+                }
+            }
+            database.flush().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_next_prefix() {
+        assert_eq!(next_prefix(&[0, 0, 1, 0]), Some(vec![0, 0, 1, 1]));
+        assert_eq!(next_prefix(&[0, 0, 1, 255]), Some(vec![0, 0, 2]));
+        assert_eq!(next_prefix(&[255, 255]), None);
+        assert_eq!(next_prefix(&[]), None);
+    }
+
+    #[test]
+    fn test_compact_database() {
+        let path = temp_database_dir("compact");
+        populate_database(&path);
+
+        // Read the entries before the compaction.
+        let entries = |path: &Path| {
+            let database = open_database(path, "testing").unwrap();
+            let entries = database
+                .iterator_opt(rocksdb::IteratorMode::Start, total_order_read_options())
+                .map(|entry| entry.unwrap())
+                .collect::<Vec<_>>();
+            entries
+        };
+        let expected = entries(&path);
+        assert_eq!(expected.len(), 4 * 500);
+
+        // Compact the database, and ensure each map was compacted.
+        let mut num_compacted = 0;
+        {
+            let database = open_database(&path, "testing").unwrap();
+            assert_eq!(map_prefixes(&database, database.cf_handle("default").unwrap()).len(), 4);
+            compact_database(&database, |_, _, _| num_compacted += 1).unwrap();
+        }
+        assert_eq!(num_compacted, 4);
+
+        // Ensure the entries are intact, and the database did not grow.
+        let (size_before, size_after) = compact_ledger(&path, "testing").unwrap();
+        assert!(size_after <= size_before);
+        assert_eq!(entries(&path), expected);
+
+        // Ensure a checkpoint of the database has the same entries.
+        let clone_path = temp_database_dir("compact-clone");
+        {
+            let database = open_database(&path, "testing").unwrap();
+            rocksdb::checkpoint::Checkpoint::new(&database).unwrap().create_checkpoint(&clone_path).unwrap();
+        }
+        compact_ledger(&clone_path, "testing").unwrap();
+        assert_eq!(compare_databases(&path, &clone_path).unwrap(), expected.len());
+
+        std::fs::remove_dir_all(&path).unwrap();
+        std::fs::remove_dir_all(&clone_path).unwrap();
+    }
+
+    #[test]
+    fn test_open_database_in_use() {
+        let path = temp_database_dir("in-use");
+        populate_database(&path);
+
+        // Ensure the database can't be opened twice.
+        let database = open_database(&path, "testing").unwrap();
+        let error = open_database(&path, "compacting the ledger").unwrap_err();
+        assert!(error.to_string().contains("is in use"), "{error}");
+        assert!(error.to_string().contains("stop the node before compacting the ledger"), "{error}");
+        drop(database);
+
+        // Ensure a missing database is reported.
+        let error = open_database(&path.join("missing"), "testing").unwrap_err();
+        assert!(error.to_string().contains("No snarkOS node storage was found"), "{error}");
+
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+}
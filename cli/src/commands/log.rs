@@ -0,0 +1,151 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkos_node::router::{read_audit_log, AuditEvent};
+
+use anyhow::{bail, Result};
+use clap::Parser;
+use std::{
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Commands to query the logs of a node.
+#[derive(Debug, Parser)]
+pub enum Log {
+    /// Print the peer connection events from the peer audit log of a node.
+    Peers(Peers),
+}
+
+impl Log {
+    pub fn parse(self) -> Result<String> {
+        match self {
+            Self::Peers(peers) => peers.parse(),
+        }
+    }
+}
+
+/// Prints the peer connection events from a peer audit log, as JSON lines.
+#[derive(Debug, Parser)]
+pub struct Peers {
+    /// Specify the path of the peer audit log, as given to `snarkos start --peer-audit-log`
+    #[clap(long = "path")]
+    pub path: PathBuf,
+    /// Specify how far back to print the events from, such as `90s`, `30m`, `1h`, or `2d` (default: all)
+    #[clap(long = "since")]
+    pub since: Option<String>,
+    /// Specify the IP address, or the IP address and port, of the peer to print the events of (default: all)
+    #[clap(long = "peer")]
+    pub peer: Option<String>,
+}
+
+impl Peers {
+    pub fn parse(self) -> Result<String> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+        let events = read_audit_log(&self.path)?;
+        let lines = self.filter(events, now)?.iter().map(serde_json::to_string).collect::<Result<Vec<_>, _>>()?;
+        Ok(lines.join("\n"))
+    }
+
+    /// Returns the events that match the filters, as of the given UTC epoch timestamp.
+    fn filter(&self, events: Vec<AuditEvent>, now: i64) -> Result<Vec<AuditEvent>> {
+        let since = match &self.since {
+            Some(since) => Some(now.saturating_sub(parse_duration_in_secs(since)? as i64)),
+            None => None,
+        };
+        Ok(events
+            .into_iter()
+            .filter(|event| since.map_or(true, |since| event.timestamp >= since))
+            .filter(|event| match &self.peer {
+                Some(peer) => event.peer_ip.to_string() == *peer || event.peer_ip.ip().to_string() == *peer,
+                None => true,
+            })
+            .collect())
+    }
+}
+
+/// Returns the number of seconds in the given duration, such as `90s`, `30m`, `1h`, or `2d`.
+fn parse_duration_in_secs(duration: &str) -> Result<u64> {
+    let duration = duration.trim();
+    let (amount, unit) = duration.split_at(duration.find(|c: char| !c.is_ascii_digit()).unwrap_or(duration.len()));
+    let Ok(amount) = amount.parse::<u64>() else {
+        bail!("Invalid duration '{duration}' (expected e.g. '90s', '30m', '1h', or '2d')");
+    };
+    let multiplier = match unit {
+        "s" | "" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => bail!("Invalid duration unit '{unit}' (expected 's', 'm', 'h', or 'd')"),
+    };
+    Ok(amount.saturating_mul(multiplier))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkos_node::router::{AuditEventKind, Initiator};
+
+    use std::net::SocketAddr;
+
+    fn sample_event(timestamp: i64, peer_ip: &str) -> AuditEvent {
+        let mut event = AuditEvent::new(AuditEventKind::Connect, peer_ip.parse::<SocketAddr>().unwrap())
+            .with_initiator(Initiator::Local);
+        event.timestamp = timestamp;
+        event
+    }
+
+    #[test]
+    fn test_parse_duration_in_secs() {
+        assert_eq!(parse_duration_in_secs("90s").unwrap(), 90);
+        assert_eq!(parse_duration_in_secs("90").unwrap(), 90);
+        assert_eq!(parse_duration_in_secs("30m").unwrap(), 30 * 60);
+        assert_eq!(parse_duration_in_secs("1h").unwrap(), 60 * 60);
+        assert_eq!(parse_duration_in_secs("2d").unwrap(), 2 * 24 * 60 * 60);
+        assert!(parse_duration_in_secs("").is_err());
+        assert!(parse_duration_in_secs("h").is_err());
+        assert!(parse_duration_in_secs("1w").is_err());
+        assert!(parse_duration_in_secs("-1h").is_err());
+    }
+
+    #[test]
+    fn test_filter_peer_events() {
+        let events = vec![
+            sample_event(1_000, "127.0.0.1:4130"),
+            sample_event(5_000, "127.0.0.1:4131"),
+            sample_event(9_000, "10.0.0.1:4130"),
+        ];
+        let peers = |since: Option<&str>, peer: Option<&str>| Peers {
+            path: PathBuf::new(),
+            since: since.map(str::to_string),
+            peer: peer.map(str::to_string),
+        };
+        let ips = |events: Vec<AuditEvent>| events.iter().map(|event| event.peer_ip.to_string()).collect::<Vec<_>>();
+
+        // Without filters, every event is returned.
+        assert_eq!(peers(None, None).filter(events.clone(), 10_000).unwrap().len(), 3);
+        // Filter by the age of the events.
+        assert_eq!(ips(peers(Some("1h"), None).filter(events.clone(), 10_000).unwrap()), vec![
+            "127.0.0.1:4131",
+            "10.0.0.1:4130"
+        ]);
+        // Filter by the IP address, with or without the port.
+        assert_eq!(peers(None, Some("127.0.0.1")).filter(events.clone(), 10_000).unwrap().len(), 2);
+        assert_eq!(ips(peers(None, Some("127.0.0.1:4131")).filter(events.clone(), 10_000).unwrap()), vec![
+            "127.0.0.1:4131"
+        ]);
+        // An invalid duration is rejected.
+        assert!(peers(Some("soon"), None).filter(events, 10_000).is_err());
+    }
+}
@@ -18,9 +18,27 @@ pub use account::*;
 mod clean;
 pub use clean::*;
 
+mod config;
+pub use config::*;
+
 mod developer;
 pub use developer::*;
 
+mod devnet;
+pub use devnet::*;
+
+mod ledger;
+pub use ledger::*;
+
+mod log;
+pub use log::*;
+
+mod node;
+pub use node::*;
+
+mod replay;
+pub use replay::*;
+
 mod start;
 pub use start::*;
 
@@ -56,7 +74,19 @@ pub enum Command {
     #[clap(name = "clean")]
     Clean(Clean),
     #[clap(subcommand)]
+    Config(Config),
+    #[clap(subcommand)]
     Developer(Developer),
+    #[clap(name = "devnet")]
+    Devnet(Devnet),
+    #[clap(subcommand)]
+    Ledger(Ledger),
+    #[clap(subcommand)]
+    Log(Log),
+    #[clap(subcommand)]
+    Node(Node),
+    #[clap(name = "replay")]
+    Replay(Replay),
     #[clap(name = "start")]
     Start(Box<Start>),
     #[clap(name = "update")]
@@ -69,7 +99,13 @@ impl Command {
         match self {
             Self::Account(command) => command.parse(),
             Self::Clean(command) => command.parse(),
+            Self::Config(command) => command.parse(),
             Self::Developer(command) => command.parse(),
+            Self::Devnet(command) => command.parse(),
+            Self::Ledger(command) => command.parse(),
+            Self::Log(command) => command.parse(),
+            Self::Node(command) => command.parse(),
+            Self::Replay(command) => command.parse(),
             Self::Start(command) => command.parse(),
             Self::Update(command) => command.parse(),
         }
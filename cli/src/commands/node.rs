@@ -0,0 +1,321 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::{bail, Result};
+use clap::Parser;
+use colored::Colorize;
+use crossterm::{
+    cursor::MoveTo,
+    terminal::{Clear, ClearType},
+    ExecutableCommand,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::{
+    num::NonZeroU64,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// The number of seconds after which the latest block is considered stale.
+const STALE_TIP_THRESHOLD_IN_SECS: i64 = 60;
+/// The timeout of each request to the REST API of the node, in seconds.
+const REQUEST_TIMEOUT_IN_SECS: u64 = 5;
+/// The width of the labels in the summary.
+const LABEL_WIDTH: usize = 14;
+
+/// Commands to inspect a running node.
+#[derive(Debug, Parser)]
+pub enum Node {
+    /// Print a summary of the state of a running node, from its REST API.
+    Info(Info),
+}
+
+impl Node {
+    pub fn parse(self) -> Result<String> {
+        match self {
+            Self::Info(info) => info.parse(),
+        }
+    }
+}
+
+/// Prints a summary of the state of a running node.
+#[derive(Debug, Parser)]
+pub struct Info {
+    /// Specify the endpoint of the REST API of the node
+    #[clap(default_value = "http://127.0.0.1:3030", long = "rest")]
+    pub rest: String,
+    /// If the flag is set, the raw JSON returned by the node is printed
+    #[clap(long)]
+    pub json: bool,
+    /// Specify the interval in seconds at which the summary is refreshed, until Ctrl-C
+    #[clap(long = "watch")]
+    pub watch: Option<NonZeroU64>,
+}
+
+/// The state of a node, as reported by its REST API.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct NodeInfo {
+    /// The type of the node.
+    pub node_type: String,
+    /// The height of the latest block.
+    pub latest_height: u32,
+    /// The timestamp of the latest block, if reported by the node.
+    #[serde(default)]
+    pub latest_timestamp: Option<i64>,
+    /// Whether the node is synced.
+    pub is_synced: bool,
+    /// The current BFT round, if the node is a validator.
+    #[serde(default)]
+    pub bft_round: Option<u64>,
+    /// The number of unconfirmed transmissions, if the node is a validator.
+    #[serde(default)]
+    pub memory_pool: Option<MemoryPoolSizes>,
+    /// The number of connected peers.
+    #[serde(skip)]
+    pub num_peers: usize,
+}
+
+/// The number of unconfirmed transmissions in the memory pool of a node.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct MemoryPoolSizes {
+    /// The number of unconfirmed solutions.
+    pub solutions: usize,
+    /// The number of unconfirmed transactions.
+    pub transactions: usize,
+}
+
+impl NodeInfo {
+    /// Returns the node info from the responses of the node status and peer count endpoints.
+    pub fn from_responses(status: &Value, num_peers: &Value) -> Result<Self> {
+        let mut info = Self::deserialize(status)?;
+        let Some(num_peers) = num_peers.as_u64() else {
+            bail!("The node returned an invalid number of peers ({num_peers})");
+        };
+        info.num_peers = num_peers as usize;
+        Ok(info)
+    }
+
+    /// Returns the number of seconds since the latest block, if its timestamp is known.
+    pub fn block_age_in_secs(&self, now: i64) -> Option<i64> {
+        self.latest_timestamp.map(|timestamp| now.saturating_sub(timestamp).max(0))
+    }
+
+    /// Returns the warnings about the state of the node.
+    pub fn warnings(&self, now: i64) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if !self.is_synced {
+            warnings.push("The node is syncing".to_string());
+        }
+        if self.num_peers == 0 {
+            warnings.push("The node has no connected peers".to_string());
+        }
+        if let Some(age) = self.block_age_in_secs(now).filter(|age| *age > STALE_TIP_THRESHOLD_IN_SECS) {
+            warnings.push(format!("The latest block is stale ({age}s old)"));
+        }
+        warnings
+    }
+
+    /// Returns the human-readable summary of the node info.
+    pub fn summary(&self, now: i64) -> String {
+        let line = |label: &str, value: String| format!("  {} {value}\n", format!("{label:<LABEL_WIDTH$}").bold());
+
+        let mut summary = String::new();
+        summary += &line("Node type", self.node_type.clone());
+        // Summarize the latest block, highlighting a stale tip.
+        let latest_block = match self.block_age_in_secs(now) {
+            Some(age) if age > STALE_TIP_THRESHOLD_IN_SECS => format!("{} ({age}s ago)", self.latest_height).yellow(),
+            Some(age) => format!("{} ({age}s ago)", self.latest_height).normal(),
+            None => self.latest_height.to_string().normal(),
+        };
+        summary += &line("Latest block", latest_block.to_string());
+        summary += &line("Sync", match self.is_synced {
+            true => "synced".green().to_string(),
+            false => "syncing".yellow().to_string(),
+        });
+        summary += &line("Peers", match self.num_peers {
+            0 => "0".red().to_string(),
+            num_peers => num_peers.to_string(),
+        });
+        if let Some(round) = self.bft_round {
+            summary += &line("BFT round", round.to_string());
+        }
+        if let Some(sizes) = self.memory_pool {
+            summary +=
+                &line("Memory pool", format!("{} solutions, {} transactions", sizes.solutions, sizes.transactions));
+        }
+        // Append the warnings.
+        for warning in self.warnings(now) {
+            summary += &format!("\n  {}", format!("⚠️  {warning}").yellow());
+        }
+        summary
+    }
+}
+
+impl Info {
+    /// Prints a summary of the state of the node, once or periodically.
+    pub fn parse(self) -> Result<String> {
+        let Some(interval) = self.watch else {
+            return self.render();
+        };
+        // Refresh the summary until Ctrl-C, like `top`.
+        let mut stdout = std::io::stdout();
+        loop {
+            let output = self.render().unwrap_or_else(|error| format!("⚠️  {error}"));
+            stdout.execute(Clear(ClearType::All))?.execute(MoveTo(0, 0))?;
+            println!("{output}\n\n{}", format!("Refreshing every {interval}s (press Ctrl-C to exit)").dimmed());
+            std::thread::sleep(Duration::from_secs(interval.get()));
+        }
+    }
+
+    /// Returns the summary of the state of the node, or the raw JSON if requested.
+    fn render(&self) -> Result<String> {
+        let status = self.get("node/status")?;
+        let num_peers = self.get("peers/count")?;
+        match self.json {
+            true => Ok(serde_json::to_string_pretty(&json!({ "status": status, "num_peers": num_peers }))?),
+            false => {
+                let info = NodeInfo::from_responses(&status, &num_peers)?;
+                Ok(format!("🛰️  Node at {}\n\n{}", self.rest.bold(), info.summary(unix_timestamp())))
+            }
+        }
+    }
+
+    /// Returns the JSON response of the given route of the REST API.
+    fn get(&self, route: &str) -> Result<Value> {
+        let endpoint = format!("{}/mainnet/{route}", self.rest.trim_end_matches('/'));
+        match ureq::get(&endpoint).timeout(Duration::from_secs(REQUEST_TIMEOUT_IN_SECS)).call() {
+            Ok(response) => Ok(response.into_json()?),
+            Err(ureq::Error::Status(code, response)) => {
+                let message = response.into_string().unwrap_or_default();
+                bail!("The node responded to '{endpoint}' with status {code} - {message}")
+            }
+            Err(ureq::Error::Transport(error)) => bail!(
+                "Unable to reach the node at {} ({error})\nIs the node running, and is its REST API enabled on this address (see `--rest` in `snarkos start`)?",
+                self.rest
+            ),
+        }
+    }
+}
+
+/// Returns the current UNIX timestamp, in seconds.
+fn unix_timestamp() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_secs() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+    };
+
+    /// Returns a sample response of the node status endpoint of a validator.
+    fn sample_status(latest_timestamp: i64, is_synced: bool) -> Value {
+        json!({
+            "node_type": "validator",
+            "latest_height": 42,
+            "latest_timestamp": latest_timestamp,
+            "is_synced": is_synced,
+            "bft_round": 85,
+            "memory_pool": { "solutions": 1, "transactions": 7 },
+            "disk": null,
+            "clock": { "status": "normal", "offset_in_ms": 0, "num_peers": 3, "is_proposing_halted": false },
+        })
+    }
+
+    /// Serves the given responses of the REST API on a local port, and returns the endpoint.
+    fn serve(status: Value, num_peers: usize) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                // Read the request line, and the headers until the empty line.
+                let mut reader = BufReader::new(&stream);
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut header = String::new();
+                while reader.read_line(&mut header).unwrap() > 2 {
+                    header.clear();
+                }
+                let body = match request_line.split_whitespace().nth(1) {
+                    Some("/mainnet/node/status") => status.to_string(),
+                    Some("/mainnet/peers/count") => num_peers.to_string(),
+                    _ => "null".to_string(),
+                };
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        endpoint
+    }
+
+    #[test]
+    fn test_node_info() {
+        let now = unix_timestamp();
+        let rest = serve(sample_status(now - 5, true), 4);
+
+        // Ensure the fields are parsed from the responses of the node.
+        let info = Info::try_parse_from(["snarkos", "--rest", rest.as_str()]).unwrap();
+        let node_info =
+            NodeInfo::from_responses(&info.get("node/status").unwrap(), &info.get("peers/count").unwrap()).unwrap();
+        assert_eq!(node_info.node_type, "validator");
+        assert_eq!(node_info.latest_height, 42);
+        assert!(node_info.is_synced);
+        assert_eq!(node_info.num_peers, 4);
+        assert_eq!(node_info.bft_round, Some(85));
+        assert_eq!(node_info.memory_pool, Some(MemoryPoolSizes { solutions: 1, transactions: 7 }));
+        assert!(node_info.warnings(now).is_empty());
+
+        // Ensure the summary and the raw JSON are rendered.
+        assert!(info.parse().unwrap().contains("Memory pool"));
+        let info = Info::try_parse_from(["snarkos", "--rest", rest.as_str(), "--json"]).unwrap();
+        let output: Value = serde_json::from_str(&info.parse().unwrap()).unwrap();
+        assert_eq!(output["num_peers"], 4);
+        assert_eq!(output["status"]["latest_height"], 42);
+    }
+
+    #[test]
+    fn test_node_info_warnings() {
+        let now = unix_timestamp();
+        // A client reports neither a BFT round nor a memory pool.
+        let status =
+            json!({ "node_type": "client", "latest_height": 7, "latest_timestamp": now - 600, "is_synced": false });
+        let node_info = NodeInfo::from_responses(&status, &json!(0)).unwrap();
+        assert_eq!(node_info.bft_round, None);
+        assert_eq!(node_info.memory_pool, None);
+
+        let warnings = node_info.warnings(now);
+        assert_eq!(warnings.len(), 3);
+        assert!(warnings[0].contains("syncing"));
+        assert!(warnings[1].contains("no connected peers"));
+        assert!(warnings[2].contains("stale"));
+        assert!(!node_info.summary(now).contains("BFT round"));
+    }
+
+    #[test]
+    fn test_unreachable_node() {
+        // Reserve a local port, and release it so that nothing is listening on it.
+        let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let rest = format!("http://127.0.0.1:{port}");
+
+        let info = Info::try_parse_from(["snarkos", "--rest", rest.as_str()]).unwrap();
+        let error = info.parse().unwrap_err();
+        assert!(error.to_string().contains("Is the node running"));
+    }
+}
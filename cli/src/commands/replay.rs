@@ -0,0 +1,75 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkos_node::bft::{helpers::EventLog, ledger_service::CoreLedgerService, replay_event_log};
+use snarkvm::{
+    console::network::{MainnetV0, Network},
+    ledger::store::helpers::memory::ConsensusMemory,
+    prelude::Ledger,
+};
+
+use aleo_std::StorageMode;
+use anyhow::{bail, Result};
+use clap::Parser;
+use colored::Colorize;
+use std::{path::PathBuf, sync::Arc};
+use tokio::runtime;
+
+/// Replays an event log, as recorded by a validator with `--bft-event-log`, and reports the first commit that diverges.
+#[derive(Debug, Parser)]
+pub struct Replay {
+    /// Specify the network of the event log.
+    #[clap(default_value = "0", long = "network")]
+    pub network: u16,
+    /// Specify the path to the event log.
+    #[clap(long = "log")]
+    pub log: PathBuf,
+}
+
+impl Replay {
+    /// Replays the event log.
+    pub fn parse(self) -> Result<String> {
+        // Initialize the runtime.
+        let runtime = runtime::Builder::new_multi_thread().enable_all().build()?;
+        match self.network {
+            0 => runtime.block_on(Self::replay::<MainnetV0>(self.log)),
+            _ => bail!("Unsupported network ID {}", self.network),
+        }
+    }
+
+    /// Replays the event log at the given path, through a fresh primary on an in-memory ledger.
+    async fn replay<N: Network>(path: PathBuf) -> Result<String> {
+        // Read the event log.
+        let log = EventLog::<N>::open(&path)?;
+        // Initialize the ledger at the genesis block of the event log.
+        let ledger = Ledger::<N, ConsensusMemory<N>>::load(log.genesis.clone(), StorageMode::Production)?;
+        let ledger_service = Arc::new(CoreLedgerService::new(ledger, Default::default()));
+        // Replay the event log.
+        let outcome = replay_event_log(&log, ledger_service).await?;
+
+        // Compare the replayed commits with the recorded commits.
+        let recorded = log.commits();
+        let summary = format!(
+            "Replayed {} events ({} failed), with {} of {} recorded commits",
+            outcome.num_events,
+            outcome.num_failed_events,
+            outcome.commits.len(),
+            recorded.len()
+        );
+        match outcome.first_divergence(&recorded) {
+            Some(divergence) => bail!("{summary}\nThe replay diverged - {divergence}"),
+            None => Ok(format!("✅ {summary}").dimmed().to_string()),
+        }
+    }
+}
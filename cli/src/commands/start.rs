@@ -12,14 +12,37 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use snarkos_account::Account;
+use super::{ConfigNodeType, NodeConfig};
+use snarkos_account::{Account, Mnemonic};
 use snarkos_display::Display;
-use snarkos_node::{bft::MEMORY_POOL_PORT, router::messages::NodeType, Node};
+use snarkos_node::{
+    bft::{
+        helpers::{EmptyBatchMode, RemoteSigner, Signer, SignerEndpoint},
+        ledger_service::{init_verification_pool, VerificationPoolConfig, DEFAULT_STATE_CHANGES_RETENTION},
+        MAX_EMPTY_BATCH_DELAY_IN_SECS,
+        MEMORY_POOL_PORT,
+    },
+    consensus::TransmissionStorageMode,
+    router::{
+        messages::NodeType,
+        AuditLog,
+        ProxyConfig,
+        ProxySeedResolver,
+        SeedDiscovery,
+        SeedListSource,
+        DEFAULT_AUDIT_LOG_MAX_SIZE,
+    },
+    tcp::{BandwidthConfig, TrafficClass},
+    Node,
+    RecordScanMode,
+};
+use snarkos_node_rest::{ApiToken, RestConfig, RestTls};
 use snarkvm::{
     console::{
         account::{Address, PrivateKey},
         algorithms::Hash,
         network::{MainnetV0, Network},
+        program::ProgramID,
     },
     ledger::{
         block::Block,
@@ -32,15 +55,15 @@ use snarkvm::{
 };
 
 use aleo_std::StorageMode;
-use anyhow::{bail, ensure, Result};
-use clap::Parser;
+use anyhow::{anyhow, bail, ensure, Result};
+use clap::{parser::ValueSource, ArgMatches, Parser};
 use colored::Colorize;
 use core::str::FromStr;
 use indexmap::IndexMap;
 use rand::SeedableRng;
 use rand_chacha::ChaChaRng;
 use serde::{Deserialize, Serialize};
-use std::{net::SocketAddr, path::PathBuf};
+use std::{net::SocketAddr, path::PathBuf, sync::Arc, time::Duration};
 use tokio::runtime::{self, Runtime};
 
 /// The recommended minimum number of 'open files' limit for a validator.
@@ -48,6 +71,9 @@ use tokio::runtime::{self, Runtime};
 #[cfg(target_family = "unix")]
 const RECOMMENDED_MIN_NOFILES_LIMIT: u64 = 2048;
 
+/// The default CDN to prefetch initial blocks from.
+const DEFAULT_CDN: &str = "https://s3.us-west-1.amazonaws.com/testnet3.blocks/phase3";
+
 /// The development mode RNG seed.
 const DEVELOPMENT_MODE_RNG_SEED: u64 = 1234567890u64;
 /// The development mode number of genesis committee members.
@@ -71,6 +97,9 @@ pub struct Start {
     /// Specify the network ID of this node
     #[clap(default_value = "0", long = "network")]
     pub network: u16,
+    /// Specify the path to a TOML configuration file for the node (command-line flags take precedence)
+    #[clap(long = "config")]
+    pub config: Option<PathBuf>,
 
     /// Specify this node as a validator
     #[clap(long = "validator")]
@@ -81,6 +110,9 @@ pub struct Start {
     /// Specify this node as a client
     #[clap(long = "client")]
     pub client: bool,
+    /// Specify this node as an archive, serving the ledger without participating in consensus
+    #[clap(long = "archive")]
+    pub archive: bool,
 
     /// Specify the account private key of the node
     #[clap(long = "private-key")]
@@ -88,6 +120,18 @@ pub struct Start {
     /// Specify the path to a file containing the account private key of the node
     #[clap(long = "private-key-file")]
     pub private_key_file: Option<PathBuf>,
+    /// Specify the path to a file containing a BIP39 mnemonic, from which the account of the node is derived
+    #[clap(long = "account-mnemonic-file")]
+    pub account_mnemonic_file: Option<PathBuf>,
+    /// Specify the index of the account derived from the mnemonic
+    #[clap(long = "account-index")]
+    pub account_index: Option<u32>,
+    /// Specify the endpoint of a remote signer for the validator, as 'tcp://<ip>:<port>' or 'unix://<path>'
+    #[clap(long = "signer")]
+    pub signer: Option<String>,
+    /// Specify the path to a file containing the secret shared with the remote signer
+    #[clap(long = "signer-secret-file")]
+    pub signer_secret_file: Option<PathBuf>,
 
     /// Specify the IP address and port for the node server
     #[clap(default_value = "0.0.0.0:4130", long = "node")]
@@ -102,15 +146,33 @@ pub struct Start {
     #[clap(default_value = "", long = "validators")]
     pub validators: String,
 
-    /// Specify the IP address and port for the REST server
+    /// Specify the IP address and port for the REST server, repeated to listen on several addresses
     #[clap(default_value = "0.0.0.0:3030", long = "rest")]
-    pub rest: SocketAddr,
+    pub rest: Vec<SocketAddr>,
     /// Specify the requests per second (RPS) rate limit per IP for the REST server
     #[clap(default_value = "10", long = "rest-rps")]
     pub rest_rps: u32,
+    /// If the flag is set, the REST server will expose Prometheus metrics at `/metrics`
+    #[clap(long = "rest-metrics")]
+    pub rest_metrics: bool,
+    /// If the flag is set, the REST server will expose the debug routes of the BFT at `/mainnet/bft` and `/mainnet/debug`
+    #[clap(long = "rest-debug")]
+    pub rest_debug: bool,
+    /// If the flag is set, the REST server will serve the block archive at `/mainnet/archive`, for other nodes to prefetch blocks from
+    #[clap(long = "serve-block-archive")]
+    pub serve_block_archive: bool,
     /// If the flag is set, the node will not initialize the REST server
     #[clap(long)]
     pub norest: bool,
+    /// Specify the path to the PEM file of the TLS certificate chain of the REST server, which is reloaded when it changes
+    #[clap(long = "rest-tls-cert")]
+    pub rest_tls_cert: Option<PathBuf>,
+    /// Specify the path to the PEM file of the TLS private key of the REST server, which is reloaded when it changes
+    #[clap(long = "rest-tls-key")]
+    pub rest_tls_key: Option<PathBuf>,
+    /// Specify the IP address and port to serve the REST server over HTTPS, repeated for several addresses [default: the `--rest` addresses]
+    #[clap(long = "rest-tls")]
+    pub rest_tls: Vec<SocketAddr>,
 
     /// If the flag is set, the node will not render the display
     #[clap(long)]
@@ -125,8 +187,8 @@ pub struct Start {
     #[clap(default_value = "false", long = "metrics")]
     pub metrics: bool,
 
-    /// Enables the node to prefetch initial blocks from a CDN
-    #[clap(default_value = "https://s3.us-west-1.amazonaws.com/testnet3.blocks/phase3", long = "cdn")]
+    /// Enables the node to prefetch initial blocks from a CDN, or from a comma-separated list of mirrors of the CDN, tried in order
+    #[clap(default_value = DEFAULT_CDN, long = "cdn")]
     pub cdn: String,
     /// If the flag is set, the node will not prefetch from a CDN
     #[clap(long)]
@@ -144,6 +206,21 @@ pub struct Start {
     /// Specify the path to a directory containing the ledger
     #[clap(long = "storage_path")]
     pub storage_path: Option<PathBuf>,
+    /// If the flag is set, the node will index the transactions of record commitments and serial numbers, and the puzzle rewards of addresses
+    #[clap(long = "enable-record-index")]
+    pub enable_record_index: bool,
+    /// If the flag is set, the node will decrypt the records of the view keys registered over the REST server (requires the JWT)
+    #[clap(long = "enable-record-scan")]
+    pub enable_record_scan: bool,
+    /// If the flag is set, the view keys registered for record scanning and their records will be persisted
+    #[clap(long = "record-scan-persist")]
+    pub record_scan_persist: bool,
+    /// If the flag is set, the node will index the changes to the mapping entries of programs in each block
+    #[clap(long = "enable-state-changes")]
+    pub enable_state_changes: bool,
+    /// Specify the number of the most recent blocks whose state changes are retained (default: 50000)
+    #[clap(long = "state-changes-retention")]
+    pub state_changes_retention: Option<u32>,
 
     /// If development mode is enabled, specify the custom bonded balances as a json object. (default: None)
     #[clap(long)]
@@ -152,6 +229,73 @@ pub struct Start {
     /// If the flag is set, the validator will allow untrusted peers to connect
     #[clap(long = "allow-external-peers")]
     allow_external_peers: bool,
+    /// If the flag is set, the node will map its listening port on the gateway of its network over UPnP
+    #[clap(long = "upnp")]
+    upnp: bool,
+    /// Specify the hostname(s) of the DNS seeds to discover peers from, with an optional port (default port: 4130)
+    #[clap(default_value = "", long = "dns-seeds")]
+    dns_seeds: String,
+    /// Specify the HTTPS URL(s) of the signed seed lists to discover peers from (requires '--seed-list-signer')
+    #[clap(default_value = "", long = "seed-lists")]
+    seed_lists: String,
+    /// Specify the address that signs the seed lists
+    #[clap(long = "seed-list-signer")]
+    seed_list_signer: Option<String>,
+    /// Specify the SOCKS5 proxy to establish the outbound peer connections through, as 'socks5://host:port'
+    #[clap(long = "proxy")]
+    proxy: Option<String>,
+    /// Specify the IP address and port at which the node is reachable, to advertise instead of the proxy's address
+    #[clap(long = "external-addr")]
+    external_addr: Option<SocketAddr>,
+    /// Specify the outbound bandwidth budget of the node in bytes per second (default: unlimited)
+    #[clap(long = "outbound-bandwidth")]
+    outbound_bandwidth: Option<u64>,
+    /// Specify the weights of the consensus, blocks, gossip, and maintenance traffic in the outbound bandwidth budget
+    #[clap(default_value = "8,4,2,1", long = "outbound-bandwidth-weights")]
+    outbound_bandwidth_weights: String,
+    /// Specify the maximum number of untrusted peers to connect to in a single subnet (default: a seventh of the peers)
+    #[clap(long = "max-peers-per-subnet")]
+    max_peers_per_subnet: Option<usize>,
+    /// Specify the path of a file to append the peer connection events to, as JSON lines
+    #[clap(long = "peer-audit-log")]
+    peer_audit_log: Option<PathBuf>,
+    /// Specify the size in bytes at which the peer audit log is rotated
+    #[clap(default_value_t = DEFAULT_AUDIT_LOG_MAX_SIZE, long = "peer-audit-log-max-size")]
+    peer_audit_log_max_size: u64,
+    /// Specify the fraction of the transactions broadcast by the node, whose propagation delay is sampled
+    #[clap(default_value_t = 0.0, long = "propagation-sample-rate")]
+    propagation_sample_rate: f64,
+    /// If the flag is set, a validator without stake in the committee starts as an observer, instead of exiting
+    #[clap(long = "allow-unstaked")]
+    allow_unstaked: bool,
+    /// Specify the path of a file to record the events of the BFT to, for a deterministic replay with `snarkos replay`
+    #[clap(long = "bft-event-log")]
+    bft_event_log: Option<PathBuf>,
+    /// Specify where the validator keeps the pending transmissions of the BFT [options: memory, persistent]
+    #[clap(default_value = "persistent", long = "bft-storage")]
+    bft_storage: String,
+    /// Specify the seconds a validator waits before proposing a batch without transactions, where 0 proposes it right away
+    #[clap(long = "empty-batch-delay")]
+    empty_batch_delay: Option<u64>,
+    /// Specify the program ID(s) whose transactions the node refuses to accept or propagate
+    #[clap(default_value = "", long = "program-denylist")]
+    program_denylist: String,
+    /// Specify the number of threads dedicated to the verification of transactions, solutions, and certificates (default: half of the cores)
+    #[clap(long = "verification-threads")]
+    verification_threads: Option<usize>,
+    /// Specify the number of threads used by the prover to solve the puzzle (default: all the cores)
+    #[clap(long = "prover-threads")]
+    prover_threads: Option<usize>,
+    /// Specify the percentage of time the prover spends solving the puzzle, from 1 to 100
+    #[clap(default_value = "100", long = "prover-duty-cycle")]
+    prover_duty_cycle: u8,
+
+    /// The API tokens that authorize requests to the sensitive REST routes, as set in the configuration file
+    #[clap(skip)]
+    api_tokens: Vec<ApiToken>,
+    /// If set, the ports and trusted peers of the development node were assigned by `snarkos devnet`
+    #[clap(skip)]
+    dev_ports_assigned: bool,
 }
 
 impl Start {
@@ -186,6 +330,192 @@ impl Start {
 }
 
 impl Start {
+    /// Returns the configurations of a development node, whose ports and trusted peers are assigned by `snarkos devnet`.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn devnet_node(
+        dev: u16,
+        num_validators: u16,
+        node_type: NodeType,
+        node_ip: SocketAddr,
+        bft_ip: Option<SocketAddr>,
+        rest_ip: SocketAddr,
+        trusted_peers: &[SocketAddr],
+        trusted_validators: &[SocketAddr],
+        dev_txs: bool,
+        serve_block_archive: bool,
+        propagation_sample_rate: f64,
+    ) -> Result<Self> {
+        let join = |ips: &[SocketAddr]| ips.iter().map(|ip| ip.to_string()).collect::<Vec<_>>().join(",");
+
+        let mut args = vec![
+            "snarkos".to_string(),
+            "--nodisplay".to_string(),
+            "--dev".to_string(),
+            dev.to_string(),
+            "--dev-num-validators".to_string(),
+            num_validators.to_string(),
+            "--node".to_string(),
+            node_ip.to_string(),
+            "--rest".to_string(),
+            rest_ip.to_string(),
+            "--rest-debug".to_string(),
+            "--peers".to_string(),
+            join(trusted_peers),
+        ];
+        match node_type {
+            NodeType::Validator => args.extend([
+                "--validator".to_string(),
+                "--allow-external-peers".to_string(),
+                "--validators".to_string(),
+                join(trusted_validators),
+            ]),
+            NodeType::Client => args.push("--client".to_string()),
+            _ => bail!("A devnet only consists of validators and clients"),
+        }
+        if let Some(bft_ip) = bft_ip {
+            args.extend(["--bft".to_string(), bft_ip.to_string()]);
+        }
+        if !dev_txs {
+            args.push("--no-dev-txs".to_string());
+        }
+        if serve_block_archive {
+            args.push("--serve-block-archive".to_string());
+        }
+        args.extend(["--propagation-sample-rate".to_string(), propagation_sample_rate.to_string()]);
+
+        let mut start = Self::try_parse_from(args)?;
+        start.dev_ports_assigned = true;
+        Ok(start)
+    }
+
+    /// Applies the node configuration file, if one is specified, to the configurations.
+    /// Arguments that were explicitly set in the given command-line `matches` take precedence.
+    pub fn apply_config_file(&mut self, matches: &ArgMatches) -> Result<()> {
+        if let Some(path) = self.config.clone() {
+            // Load the node configuration file.
+            let config = NodeConfig::load(&path)?;
+            // Apply the node configuration file.
+            self.apply_config(config, matches);
+        }
+        Ok(())
+    }
+
+    /// Applies the given node configuration to every argument that was not explicitly set on the command line.
+    fn apply_config(&mut self, config: NodeConfig, matches: &ArgMatches) {
+        // Returns `true` if the given argument was explicitly set on the command line.
+        let is_explicit = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+        // Sets the given argument to the configured value, if it was not explicitly set on the command line.
+        fn apply<T>(is_explicit: &impl Fn(&str) -> bool, id: &str, argument: &mut T, value: Option<T>) {
+            if let Some(value) = value {
+                if !is_explicit(id) {
+                    *argument = value;
+                }
+            }
+        }
+
+        apply(&is_explicit, "network", &mut self.network, config.network);
+
+        // Apply the node type, unless any node type was explicitly set on the command line.
+        if let Some(node_type) = config.node_type {
+            let is_explicit_node_type = ["validator", "prover", "client", "archive"].into_iter().any(is_explicit);
+            if !is_explicit_node_type {
+                self.validator = node_type == ConfigNodeType::Validator;
+                self.prover = node_type == ConfigNodeType::Prover;
+                self.client = node_type == ConfigNodeType::Client;
+                self.archive = node_type == ConfigNodeType::Archive;
+            }
+        }
+
+        // Apply the private key, mnemonic, or remote signer, unless any of them was explicitly set on the command line.
+        let is_explicit_account = ["private_key", "private_key_file", "account_mnemonic_file", "signer"];
+        if !is_explicit_account.into_iter().any(is_explicit) {
+            apply(&is_explicit, "private_key", &mut self.private_key, config.private_key.map(Some));
+            apply(&is_explicit, "private_key_file", &mut self.private_key_file, config.private_key_file.map(Some));
+            let mnemonic_file = config.account_mnemonic_file.map(Some);
+            apply(&is_explicit, "account_mnemonic_file", &mut self.account_mnemonic_file, mnemonic_file);
+            apply(&is_explicit, "signer", &mut self.signer, config.signer.map(Some));
+        }
+        apply(&is_explicit, "account_index", &mut self.account_index, config.account_index.map(Some));
+        apply(&is_explicit, "signer_secret_file", &mut self.signer_secret_file, config.signer_secret_file.map(Some));
+        apply(&is_explicit, "storage_path", &mut self.storage_path, config.storage_path.map(Some));
+        apply(&is_explicit, "enable_record_index", &mut self.enable_record_index, config.record_index);
+        apply(&is_explicit, "enable_record_scan", &mut self.enable_record_scan, config.record_scan);
+        apply(&is_explicit, "record_scan_persist", &mut self.record_scan_persist, config.record_scan_persist);
+        apply(&is_explicit, "enable_state_changes", &mut self.enable_state_changes, config.state_changes);
+        let retention = config.state_changes_retention.map(Some);
+        apply(&is_explicit, "state_changes_retention", &mut self.state_changes_retention, retention);
+        let program_denylist = config.program_denylist.map(|programs| programs.join(","));
+        apply(&is_explicit, "program_denylist", &mut self.program_denylist, program_denylist);
+        let verification_threads = config.verification_threads.map(Some);
+        apply(&is_explicit, "verification_threads", &mut self.verification_threads, verification_threads);
+        apply(&is_explicit, "prover_threads", &mut self.prover_threads, config.prover_threads.map(Some));
+        apply(&is_explicit, "prover_duty_cycle", &mut self.prover_duty_cycle, config.prover_duty_cycle);
+
+        // Apply the peer-to-peer settings.
+        let join = |ips: Vec<SocketAddr>| ips.iter().map(|ip| ip.to_string()).collect::<Vec<_>>().join(",");
+        apply(&is_explicit, "node", &mut self.node, config.p2p.listen);
+        apply(&is_explicit, "peers", &mut self.peers, config.p2p.peers.map(join));
+        apply(&is_explicit, "validators", &mut self.validators, config.p2p.validators.map(join));
+        apply(&is_explicit, "allow_external_peers", &mut self.allow_external_peers, config.p2p.allow_external_peers);
+        apply(&is_explicit, "upnp", &mut self.upnp, config.p2p.upnp);
+        apply(&is_explicit, "dns_seeds", &mut self.dns_seeds, config.p2p.dns_seeds.map(|seeds| seeds.join(",")));
+        apply(&is_explicit, "seed_lists", &mut self.seed_lists, config.p2p.seed_lists.map(|urls| urls.join(",")));
+        let signer = config.p2p.seed_list_signer.map(Some);
+        apply(&is_explicit, "seed_list_signer", &mut self.seed_list_signer, signer);
+        apply(&is_explicit, "proxy", &mut self.proxy, config.p2p.proxy.map(Some));
+        apply(&is_explicit, "external_addr", &mut self.external_addr, config.p2p.external_addr.map(Some));
+        let bandwidth = config.p2p.outbound_bandwidth.map(Some);
+        apply(&is_explicit, "outbound_bandwidth", &mut self.outbound_bandwidth, bandwidth);
+        let weights = config.p2p.outbound_bandwidth_weights;
+        let weights = weights.map(|weights| weights.iter().map(|w| w.to_string()).collect::<Vec<_>>().join(","));
+        apply(&is_explicit, "outbound_bandwidth_weights", &mut self.outbound_bandwidth_weights, weights);
+        let max_peers_per_subnet = config.p2p.max_peers_per_subnet.map(Some);
+        apply(&is_explicit, "max_peers_per_subnet", &mut self.max_peers_per_subnet, max_peers_per_subnet);
+        apply(&is_explicit, "peer_audit_log", &mut self.peer_audit_log, config.p2p.audit_log.map(Some));
+        let audit_log_max_size = config.p2p.audit_log_max_size;
+        apply(&is_explicit, "peer_audit_log_max_size", &mut self.peer_audit_log_max_size, audit_log_max_size);
+        let sample_rate = config.p2p.propagation_sample_rate;
+        apply(&is_explicit, "propagation_sample_rate", &mut self.propagation_sample_rate, sample_rate);
+        // Apply the BFT settings.
+        apply(&is_explicit, "bft", &mut self.bft, config.bft.listen.map(Some));
+        apply(&is_explicit, "allow_unstaked", &mut self.allow_unstaked, config.bft.allow_unstaked);
+        apply(&is_explicit, "bft_event_log", &mut self.bft_event_log, config.bft.event_log.map(Some));
+        apply(&is_explicit, "bft_storage", &mut self.bft_storage, config.bft.storage);
+        apply(&is_explicit, "empty_batch_delay", &mut self.empty_batch_delay, config.bft.empty_batch_delay.map(Some));
+
+        // Apply the REST server settings.
+        apply(&is_explicit, "norest", &mut self.norest, config.rest.enabled.map(|enabled| !enabled));
+        apply(&is_explicit, "rest", &mut self.rest, config.rest.listen);
+        apply(&is_explicit, "rest_tls_cert", &mut self.rest_tls_cert, config.rest.tls_cert.map(Some));
+        apply(&is_explicit, "rest_tls_key", &mut self.rest_tls_key, config.rest.tls_key.map(Some));
+        apply(&is_explicit, "rest_tls", &mut self.rest_tls, config.rest.tls_listen);
+        apply(&is_explicit, "rest_rps", &mut self.rest_rps, config.rest.rps);
+        apply(&is_explicit, "rest_metrics", &mut self.rest_metrics, config.rest.metrics);
+        apply(&is_explicit, "rest_debug", &mut self.rest_debug, config.rest.debug);
+        apply(&is_explicit, "serve_block_archive", &mut self.serve_block_archive, config.rest.block_archive);
+        // Note: The API tokens are only set in the configuration file, to keep them out of the process arguments.
+        if let Some(tokens) = config.rest.tokens {
+            self.api_tokens = tokens;
+        }
+
+        // Apply the logging settings.
+        apply(&is_explicit, "verbosity", &mut self.verbosity, config.log.verbosity);
+        apply(&is_explicit, "logfile", &mut self.logfile, config.log.logfile);
+        apply(&is_explicit, "nodisplay", &mut self.nodisplay, config.log.display.map(|display| !display));
+        apply(&is_explicit, "metrics", &mut self.metrics, config.log.metrics);
+
+        // Apply the CDN settings.
+        apply(&is_explicit, "nocdn", &mut self.nocdn, config.cdn.enabled.map(|enabled| !enabled));
+        apply(&is_explicit, "cdn", &mut self.cdn, config.cdn.url);
+
+        // Apply the development mode settings.
+        apply(&is_explicit, "dev", &mut self.dev, config.dev.id.map(Some));
+        apply(&is_explicit, "dev_num_validators", &mut self.dev_num_validators, config.dev.num_validators.map(Some));
+        apply(&is_explicit, "no_dev_txs", &mut self.no_dev_txs, config.dev.transactions.map(|txs| !txs));
+        let bonded_balances = config.dev.bonded_balances.map(|balances| Some(BondedBalances(balances)));
+        apply(&is_explicit, "dev_bonded_balances", &mut self.dev_bonded_balances, bonded_balances);
+    }
+
     /// Returns the initial peer(s) to connect to, from the given configurations.
     fn parse_trusted_peers(&self) -> Result<Vec<SocketAddr>> {
         match self.peers.is_empty() {
@@ -222,28 +552,230 @@ impl Start {
         }
     }
 
-    /// Returns the CDN to prefetch initial blocks from, from the given configurations.
-    fn parse_cdn(&self) -> Option<String> {
+    /// Returns the program ID(s) whose transactions the node refuses to accept or propagate, from the given configurations.
+    fn parse_program_denylist<N: Network>(&self) -> Result<Vec<ProgramID<N>>> {
+        match self.program_denylist.is_empty() {
+            true => Ok(vec![]),
+            false => self
+                .program_denylist
+                .split(',')
+                .map(|program_id| {
+                    ProgramID::from_str(program_id.trim()).map_err(|e| {
+                        anyhow!("The program ID supplied to --program-denylist ('{program_id}') is malformed: {e}")
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the discovery of peers from the DNS seeds and the signed seed lists, from the given configurations.
+    fn parse_seed_discovery<N: Network>(&self) -> Result<SeedDiscovery<N>> {
+        let split = |list: &str| {
+            list.split(',').map(str::trim).filter(|item| !item.is_empty()).map(str::to_string).collect::<Vec<_>>()
+        };
+        let dns_seeds = split(&self.dns_seeds);
+        let urls = split(&self.seed_lists);
+        let proxy = self.parse_proxy()?;
+        let seed_lists = match (urls.is_empty(), &self.seed_list_signer) {
+            (true, _) => vec![],
+            // Note: The seed lists are fetched over HTTPS, which would bypass the proxy.
+            (false, _) if proxy.is_some() => bail!("The '--seed-lists' flag is not supported with the '--proxy' flag"),
+            (false, None) => bail!("The '--seed-lists' flag requires the '--seed-list-signer' flag"),
+            (false, Some(signer)) => {
+                let signer = Address::<N>::from_str(signer.trim())
+                    .map_err(|e| anyhow!("The address supplied to --seed-list-signer ('{signer}') is malformed: {e}"))?;
+                for url in &urls {
+                    ensure!(url.starts_with("https://"), "The seed list '{url}' must be served over HTTPS");
+                }
+                urls.into_iter().map(|url| SeedListSource { url, signer }).collect()
+            }
+        };
+        let discovery = SeedDiscovery::new(dns_seeds, seed_lists);
+        // Resolve the DNS seeds through the proxy, if enabled.
+        match proxy {
+            Some(proxy) => Ok(discovery.with_resolver(Arc::new(ProxySeedResolver::new(proxy.proxy)))),
+            None => Ok(discovery),
+        }
+    }
+
+    /// Returns the configuration of the proxy for the outbound peer connections, from the given configurations.
+    fn parse_proxy(&self) -> Result<Option<ProxyConfig>> {
+        match &self.proxy {
+            Some(url) => {
+                // Note: The BFT gateway of a validator dials the other validators directly.
+                ensure!(!self.validator, "The '--proxy' flag is not supported for validators");
+                Ok(Some(ProxyConfig::from_url(url, self.external_addr)?))
+            }
+            None => {
+                ensure!(self.external_addr.is_none(), "The '--external-addr' flag requires the '--proxy' flag");
+                Ok(None)
+            }
+        }
+    }
+
+    /// Returns the configuration of the outbound bandwidth, from the given configurations.
+    fn parse_outbound_bandwidth(&self) -> Result<BandwidthConfig> {
+        let weights = self
+            .outbound_bandwidth_weights
+            .split(',')
+            .map(|weight| {
+                weight.trim().parse::<u32>().map_err(|e| {
+                    anyhow!("The weight supplied to --outbound-bandwidth-weights ('{weight}') is malformed: {e}")
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        ensure!(
+            weights.len() == TrafficClass::COUNT,
+            "The '--outbound-bandwidth-weights' flag requires {} weights (consensus, blocks, gossip, maintenance)",
+            TrafficClass::COUNT
+        );
+        ensure!(weights[0] > 0, "The consensus weight in '--outbound-bandwidth-weights' must be positive");
+        let mut config = BandwidthConfig::default();
+        for (class, weight) in TrafficClass::ALL.into_iter().zip(weights) {
+            config = config.with_weight(class, weight);
+        }
+        match self.outbound_bandwidth {
+            Some(0) => bail!("The '--outbound-bandwidth' flag must be positive"),
+            Some(bytes_per_sec) => Ok(config.with_bytes_per_sec(bytes_per_sec)),
+            None => Ok(config),
+        }
+    }
+
+    /// Returns the mode of the record scanner, from the given configurations.
+    fn parse_record_scan_mode(&self) -> Result<RecordScanMode> {
+        match (self.enable_record_scan, self.record_scan_persist) {
+            (false, false) => Ok(RecordScanMode::Disabled),
+            (false, true) => bail!("The '--record-scan-persist' flag requires '--enable-record-scan'"),
+            (true, false) => Ok(RecordScanMode::Memory),
+            (true, true) => Ok(RecordScanMode::Persistent),
+        }
+    }
+
+    /// Returns the retention window of the state change index, or `None` if the index is disabled.
+    fn parse_state_changes_retention(&self) -> Result<Option<u32>> {
+        match (self.enable_state_changes, self.state_changes_retention) {
+            (false, None) => Ok(None),
+            (false, Some(_)) => bail!("The '--state-changes-retention' flag requires '--enable-state-changes'"),
+            (true, Some(0)) => bail!("The '--state-changes-retention' flag must be positive"),
+            (true, retention) => Ok(Some(retention.unwrap_or(DEFAULT_STATE_CHANGES_RETENTION))),
+        }
+    }
+
+    /// Returns the addresses of the REST server served over HTTP, and the TLS configuration of the addresses served over HTTPS.
+    fn parse_rest_listeners(&self) -> Result<(Vec<SocketAddr>, Option<RestTls>)> {
+        if self.norest {
+            return Ok((vec![], None));
+        }
+        match (&self.rest_tls_cert, &self.rest_tls_key) {
+            (None, None) => {
+                ensure!(
+                    self.rest_tls.is_empty(),
+                    "The '--rest-tls' flag requires '--rest-tls-cert' and '--rest-tls-key'"
+                );
+                Ok((self.rest.clone(), None))
+            }
+            (Some(cert_path), Some(key_path)) => {
+                // Serve HTTPS on the given addresses alongside HTTP, or on the REST addresses in place of HTTP.
+                let (rest_ips, tls_ips) = match self.rest_tls.is_empty() {
+                    true => (vec![], self.rest.clone()),
+                    false => (self.rest.clone(), self.rest_tls.clone()),
+                };
+                let rest_tls = RestTls { cert_path: cert_path.clone(), key_path: key_path.clone(), rest_ips: tls_ips };
+                Ok((rest_ips, Some(rest_tls)))
+            }
+            _ => bail!("The '--rest-tls-cert' and '--rest-tls-key' flags must be set together"),
+        }
+    }
+
+    /// Returns the configuration of the REST server, from the given configurations.
+    fn parse_rest_config(&self) -> Result<RestConfig> {
+        let (rest_ips, rest_tls) = self.parse_rest_listeners()?;
+        Ok(RestConfig {
+            rest_tls,
+            ..RestConfig::new(rest_ips)
+                .with_rps(self.rest_rps)
+                .with_metrics(self.rest_metrics)
+                .with_debug(self.rest_debug)
+                .with_block_archive(self.serve_block_archive)
+                .with_api_tokens(self.api_tokens.clone())
+        })
+    }
+
+    /// Returns the storage of the pending transmissions of the BFT, from the given configurations.
+    fn parse_transmission_storage(&self) -> Result<TransmissionStorageMode> {
+        match self.bft_storage.as_str() {
+            "memory" => Ok(TransmissionStorageMode::Memory),
+            "persistent" => Ok(TransmissionStorageMode::Persistent),
+            storage => bail!("Invalid '--bft-storage' option '{storage}' (options: memory, persistent)"),
+        }
+    }
+
+    /// Returns the behavior of the primary when it has no transactions to propose, from the given configurations.
+    fn parse_empty_batch_mode(&self) -> Result<EmptyBatchMode> {
+        match self.empty_batch_delay {
+            None => Ok(EmptyBatchMode::default()),
+            Some(0) => Ok(EmptyBatchMode::Propose),
+            Some(delay) => {
+                ensure!(
+                    delay <= MAX_EMPTY_BATCH_DELAY_IN_SECS,
+                    "The '--empty-batch-delay' flag must be at most {MAX_EMPTY_BATCH_DELAY_IN_SECS} seconds"
+                );
+                Ok(EmptyBatchMode::Delay { delay: Duration::from_secs(delay) })
+            }
+        }
+    }
+
+    /// Returns the mirrors of the CDN to prefetch initial blocks from, from the given configurations.
+    fn parse_cdn(&self) -> Option<Vec<String>> {
         // Determine if the node type is not declared.
-        let is_no_node_type = !(self.validator || self.prover || self.client);
+        let is_no_node_type = !(self.validator || self.prover || self.client || self.archive);
+        // Parse the mirrors of the CDN.
+        let base_urls = self
+            .cdn
+            .split(',')
+            .map(|url| url.trim().trim_end_matches('/'))
+            .filter(|url| !url.is_empty())
+            .map(|url| url.to_string())
+            .collect::<Vec<_>>();
 
         // Disable CDN if:
-        //  1. The node is in development mode.
+        //  1. The node is in development mode, and the CDN is the default one (whose blocks are not from a devnet).
         //  2. The user has explicitly disabled CDN.
         //  3. The node is a prover (no need to sync).
         //  4. The node type is not declared (defaults to client) (no need to sync).
-        if self.dev.is_some() || self.cdn.is_empty() || self.nocdn || self.prover || is_no_node_type {
+        if (self.dev.is_some() && self.cdn == DEFAULT_CDN)
+            || base_urls.is_empty()
+            || self.nocdn
+            || self.prover
+            || is_no_node_type
+        {
             None
         }
         // Enable the CDN otherwise.
         else {
-            Some(self.cdn.clone())
+            Some(base_urls)
         }
     }
 
     /// Read the private key directly from an argument or from a filesystem location,
     /// returning the Aleo account.
     fn parse_private_key<N: Network>(&self) -> Result<Account<N>> {
+        // Ensure the account index is only used with a mnemonic.
+        if self.account_index.is_some() && self.account_mnemonic_file.is_none() {
+            bail!("The '--account-index' argument requires '--account-mnemonic-file'")
+        }
+        // Derive the account from the mnemonic, if one is specified.
+        if let Some(path) = &self.account_mnemonic_file {
+            // Ensure only one account flag is provided to the CLI.
+            if self.private_key.is_some() || self.private_key_file.is_some() || self.dev.is_some() {
+                bail!("Cannot use '--account-mnemonic-file' with '--private-key', '--private-key-file', or '--dev'")
+            }
+            let Some(index) = self.account_index else {
+                bail!("Missing the '--account-index' argument for the '--account-mnemonic-file'")
+            };
+            check_permissions(path)?;
+            return Account::from_mnemonic(&Mnemonic::from_file(path)?, index);
+        }
         match self.dev {
             None => match (&self.private_key, &self.private_key_file) {
                 // Parse the private key directly.
@@ -253,10 +785,12 @@ impl Start {
                     check_permissions(path)?;
                     Account::from_str(std::fs::read_to_string(path)?.trim())
                 }
-                // Ensure the private key is provided to the CLI, except for clients or nodes in development mode.
-                (None, None) => match self.client {
+                // Ensure the private key is provided to the CLI, except for clients, archives, validators with a remote signer,
+                // or nodes in development mode.
+                // Note: A validator with a remote signer only uses this account as its identity in the router.
+                (None, None) => match self.client || self.archive || self.signer.is_some() {
                     true => Account::new(&mut rand::thread_rng()),
-                    false => bail!("Missing the '--private-key' or '--private-key-file' argument"),
+                    false => bail!("Missing the '--private-key', '--account-mnemonic-file', or '--signer' argument"),
                 },
                 // Ensure only one private key flag is provided to the CLI.
                 (Some(_), Some(_)) => {
@@ -280,6 +814,33 @@ impl Start {
         }
     }
 
+    /// Returns the remote signer of the validator, if one is specified.
+    async fn parse_signer<N: Network>(&self) -> Result<Option<Arc<dyn Signer<N>>>> {
+        let Some(endpoint) = &self.signer else {
+            ensure!(self.signer_secret_file.is_none(), "The '--signer-secret-file' argument requires '--signer'");
+            return Ok(None);
+        };
+        // Ensure the remote signer is only used by validators.
+        ensure!(self.validator, "The '--signer' argument is only supported for validators");
+        // Ensure the remote signer is not used alongside a private key.
+        if self.private_key.is_some() || self.private_key_file.is_some() || self.account_mnemonic_file.is_some() {
+            bail!("Cannot use '--signer' with a private key or '--account-mnemonic-file', please use only one")
+        }
+        // Parse the endpoint of the remote signer.
+        let endpoint = SignerEndpoint::from_str(endpoint)?;
+        // Read the secret shared with the remote signer.
+        let Some(path) = &self.signer_secret_file else {
+            bail!("Missing the '--signer-secret-file' argument for the remote signer")
+        };
+        check_permissions(path)?;
+        let secret = std::fs::read_to_string(path)?.trim().as_bytes().to_vec();
+        // Connect to the remote signer.
+        match RemoteSigner::<N>::connect(endpoint.clone(), secret).await {
+            Ok(signer) => Ok(Some(Arc::new(signer))),
+            Err(error) => bail!("Failed to connect to the remote signer at '{endpoint}' - {error}"),
+        }
+    }
+
     /// Updates the configurations if the node is in development mode.
     fn parse_development(
         &mut self,
@@ -290,6 +851,10 @@ impl Start {
         // and add each of them to the trusted peers. In addition, set the node IP to `4130 + dev`,
         // and the REST IP to `3030 + dev`.
         if let Some(dev) = self.dev {
+            // If the ports and trusted peers were assigned by `snarkos devnet`, leave them as they are.
+            if self.dev_ports_assigned {
+                return Ok(());
+            }
             // Add the dev nodes to the trusted peers.
             if trusted_peers.is_empty() {
                 for i in 0..dev {
@@ -314,7 +879,7 @@ impl Start {
             //
             // Note: the reason the `bft` flag is an option is to detect for remote devnet testing.
             if !self.norest && self.bft.is_none() {
-                self.rest = SocketAddr::from_str(&format!("0.0.0.0:{}", 3030 + dev))?;
+                self.rest = vec![SocketAddr::from_str(&format!("0.0.0.0:{}", 3030 + dev))?];
             }
         }
         Ok(())
@@ -457,6 +1022,8 @@ impl Start {
             NodeType::Validator
         } else if self.prover {
             NodeType::Prover
+        } else if self.archive {
+            NodeType::Archive
         } else {
             NodeType::Client
         }
@@ -464,7 +1031,7 @@ impl Start {
 
     /// Returns the node type corresponding to the given configurations.
     #[rustfmt::skip]
-    async fn parse_node<N: Network>(&mut self) -> Result<Node<N>> {
+    pub(crate) async fn parse_node<N: Network>(&mut self) -> Result<Node<N>> {
         // Print the welcome.
         println!("{}", crate::helpers::welcome_message());
 
@@ -482,19 +1049,23 @@ impl Start {
         let genesis = self.parse_genesis::<N>()?;
         // Parse the private key of the node.
         let account = self.parse_private_key::<N>()?;
+        // Parse the remote signer of the node.
+        let signer = self.parse_signer::<N>().await?;
+        // Determine the address of the node.
+        let address = match &signer {
+            Some(signer) => signer.address(),
+            None => account.address(),
+        };
         // Parse the node type.
         let node_type = self.parse_node_type();
 
-        // Parse the REST IP.
-        let rest_ip = match self.norest {
-            true => None,
-            false => Some(self.rest),
-        };
+        // Parse the configuration of the REST server.
+        let rest = self.parse_rest_config()?;
 
         // If the display is not enabled, render the welcome message.
         if self.nodisplay {
             // Print the Aleo address.
-            println!("👛 Your Aleo address is {}.\n", account.address().to_string().bold());
+            println!("👛 Your Aleo address is {}.\n", address.to_string().bold());
             // Print the node type and network.
             println!(
                 "🧭 Starting {} on {} at {}.\n",
@@ -504,11 +1075,13 @@ impl Start {
             );
 
             // If the node is running a REST server, print the REST IP and JWT.
-            if node_type.is_validator() {
-                if let Some(rest_ip) = rest_ip {
-                    println!("🌐 Starting the REST server at {}.\n", rest_ip.to_string().bold());
+            // Note: The JWT is also required to update the program denylist, and to register view keys with the record scanner.
+            if node_type.is_validator() || node_type.is_client() || self.enable_record_scan {
+                if !rest.rest_ips.is_empty() {
+                    let rest_ips = rest.rest_ips.iter().map(|ip| ip.to_string()).collect::<Vec<_>>().join(", ");
+                    println!("🌐 Starting the REST server at {}.\n", rest_ips.bold());
 
-                    if let Ok(jwt_token) = snarkos_node_rest::Claims::new(account.address()).to_jwt_string() {
+                    if let Ok(jwt_token) = snarkos_node_rest::Claims::new(address).to_jwt_string() {
                         println!("🔑 Your one-time JWT token is {}\n", jwt_token.dimmed());
                     }
                 }
@@ -527,6 +1100,11 @@ impl Start {
         if self.metrics {
             metrics::initialize_metrics();
         }
+        // Initialize the verification pool, if its number of threads is specified.
+        if let Some(num_threads) = self.verification_threads {
+            ensure!(num_threads > 0, "The number of verification threads must be greater than zero");
+            init_verification_pool(VerificationPoolConfig::new(num_threads))?;
+        }
 
         // Initialize the storage mode.
         let storage_mode = match &self.storage_path {
@@ -546,13 +1124,58 @@ impl Start {
             }
         };
 
+        // Parse the mode of the record scanner.
+        let record_scan = self.parse_record_scan_mode()?;
+        // Parse the retention window of the state change index.
+        let state_changes_retention = self.parse_state_changes_retention()?;
+        // Parse the storage of the pending transmissions of the BFT.
+        let transmission_storage = self.parse_transmission_storage()?;
+        // Parse the behavior of the primary when it has no transactions to propose.
+        let empty_batch_mode = self.parse_empty_batch_mode()?;
+        // Parse the program denylist.
+        let program_denylist = self.parse_program_denylist::<N>()?;
+        // Parse the outbound bandwidth.
+        let outbound_bandwidth = self.parse_outbound_bandwidth()?;
+        // Parse the discovery of peers from the seeds.
+        let seed_discovery = self.parse_seed_discovery::<N>()?;
+        // Parse the proxy of the outbound peer connections.
+        let proxy = self.parse_proxy()?;
+        // Ensure the maximum number of peers per subnet is nonzero.
+        ensure!(self.max_peers_per_subnet != Some(0), "The '--max-peers-per-subnet' flag must be greater than 0");
+        // Ensure the propagation sample rate is a fraction.
+        ensure!(
+            (0.0..=1.0).contains(&self.propagation_sample_rate),
+            "The '--propagation-sample-rate' flag must be between 0.0 and 1.0"
+        );
+
         // Initialize the node.
         let bft_ip = if self.dev.is_some() { self.bft } else { None };
-        match node_type {
-            NodeType::Validator => Node::new_validator(self.node, bft_ip, rest_ip, self.rest_rps, account, &trusted_peers, &trusted_validators, genesis, cdn, storage_mode, self.allow_external_peers, dev_txs).await,
-            NodeType::Prover => Node::new_prover(self.node, account, &trusted_peers, genesis, storage_mode).await,
-            NodeType::Client => Node::new_client(self.node, rest_ip, self.rest_rps, account, &trusted_peers, genesis, cdn, storage_mode).await,
+        let node = match node_type {
+            NodeType::Validator => Node::new_validator(self.node, bft_ip, rest, self.enable_record_index, record_scan, state_changes_retention, account, signer, &trusted_peers, &trusted_validators, genesis, cdn, storage_mode, transmission_storage, self.allow_external_peers, dev_txs, self.allow_unstaked, self.bft_event_log.clone(), program_denylist, empty_batch_mode).await,
+            NodeType::Prover => Node::new_prover(self.node, account, &trusted_peers, proxy, genesis, storage_mode, self.prover_threads, self.prover_duty_cycle).await,
+            NodeType::Client => Node::new_client(self.node, rest, self.enable_record_index, record_scan, state_changes_retention, account, &trusted_peers, proxy, genesis, cdn, storage_mode, program_denylist).await,
+            NodeType::Archive => Node::new_archive(self.node, rest, self.enable_record_index, record_scan, state_changes_retention, account, &trusted_peers, proxy, genesis, cdn, storage_mode).await,
+        }?;
+
+        // Map the listening port on the gateway, if enabled.
+        if self.upnp {
+            node.start_port_mapping();
         }
+        // Discover peers from the seeds, if any are configured.
+        node.start_seed_discovery(seed_discovery);
+        // Limit the outbound bandwidth, if enabled.
+        node.set_outbound_bandwidth(outbound_bandwidth)?;
+        // Limit the number of peers per subnet, if configured.
+        if let Some(max_peers_per_subnet) = self.max_peers_per_subnet {
+            node.set_max_peers_per_subnet(max_peers_per_subnet);
+        }
+        // Record the peer connection events, if enabled.
+        if let Some(path) = &self.peer_audit_log {
+            node.set_peer_audit_log(Arc::new(AuditLog::start(path, self.peer_audit_log_max_size)?));
+        }
+        // Sample the propagation delay of the broadcast transactions, if enabled.
+        node.set_propagation_sample_rate(self.propagation_sample_rate);
+        Ok(node)
     }
 
     /// Returns a runtime for the node.
@@ -584,7 +1207,7 @@ impl Start {
     }
 }
 
-fn check_permissions(path: &PathBuf) -> Result<(), snarkvm::prelude::Error> {
+pub(crate) fn check_permissions(path: &PathBuf) -> Result<(), snarkvm::prelude::Error> {
     #[cfg(target_family = "unix")]
     {
         use std::os::unix::fs::PermissionsExt;
@@ -719,6 +1342,191 @@ mod tests {
         ]);
     }
 
+    #[test]
+    fn test_parse_record_scan_mode() {
+        let config = Start::try_parse_from(["snarkos", "--client"].iter()).unwrap();
+        assert_eq!(config.parse_record_scan_mode().unwrap(), RecordScanMode::Disabled);
+        let config = Start::try_parse_from(["snarkos", "--client", "--enable-record-scan"].iter()).unwrap();
+        assert_eq!(config.parse_record_scan_mode().unwrap(), RecordScanMode::Memory);
+        let config =
+            Start::try_parse_from(["snarkos", "--client", "--enable-record-scan", "--record-scan-persist"].iter())
+                .unwrap();
+        assert_eq!(config.parse_record_scan_mode().unwrap(), RecordScanMode::Persistent);
+        // Persistence requires the record scanner to be enabled.
+        let config = Start::try_parse_from(["snarkos", "--client", "--record-scan-persist"].iter()).unwrap();
+        assert!(config.parse_record_scan_mode().is_err());
+    }
+
+    #[test]
+    fn test_parse_state_changes_retention() {
+        let config = Start::try_parse_from(["snarkos", "--client"].iter()).unwrap();
+        assert_eq!(config.parse_state_changes_retention().unwrap(), None);
+        let config = Start::try_parse_from(["snarkos", "--client", "--enable-state-changes"].iter()).unwrap();
+        assert_eq!(config.parse_state_changes_retention().unwrap(), Some(DEFAULT_STATE_CHANGES_RETENTION));
+        let args = ["snarkos", "--client", "--enable-state-changes", "--state-changes-retention", "100"];
+        let config = Start::try_parse_from(args.iter()).unwrap();
+        assert_eq!(config.parse_state_changes_retention().unwrap(), Some(100));
+        // The retention window requires the index to be enabled, and must be positive.
+        let config = Start::try_parse_from(["snarkos", "--client", "--state-changes-retention", "100"].iter()).unwrap();
+        assert!(config.parse_state_changes_retention().is_err());
+        let args = ["snarkos", "--client", "--enable-state-changes", "--state-changes-retention", "0"];
+        let config = Start::try_parse_from(args.iter()).unwrap();
+        assert!(config.parse_state_changes_retention().is_err());
+    }
+
+    #[test]
+    fn test_parse_rest_listeners() {
+        let (v4, v6) = (SocketAddr::from_str("127.0.0.1:3030").unwrap(), SocketAddr::from_str("[::1]:3443").unwrap());
+
+        // HTTP is served by default.
+        let config = Start::try_parse_from(["snarkos", "--rest", "127.0.0.1:3030"].iter()).unwrap();
+        assert_eq!(config.parse_rest_listeners().unwrap(), (vec![v4], None));
+        // No address is served without the REST server.
+        let config = Start::try_parse_from(["snarkos", "--norest", "--rest-tls-cert", "cert.pem"].iter()).unwrap();
+        assert_eq!(config.parse_rest_listeners().unwrap(), (vec![], None));
+
+        // The REST addresses are served over HTTPS, if no HTTPS address is given.
+        let tls = ["--rest-tls-cert", "cert.pem", "--rest-tls-key", "key.pem"];
+        let config = Start::try_parse_from(["snarkos", "--rest", "127.0.0.1:3030"].iter().chain(&tls)).unwrap();
+        let (rest_ips, rest_tls) = config.parse_rest_listeners().unwrap();
+        assert!(rest_ips.is_empty());
+        assert_eq!(rest_tls.unwrap().rest_ips, vec![v4]);
+        // HTTP and HTTPS are served on separate addresses.
+        let config = Start::try_parse_from(
+            ["snarkos", "--rest", "127.0.0.1:3030", "--rest-tls", "[::1]:3443"].iter().chain(&tls),
+        )
+        .unwrap();
+        let (rest_ips, rest_tls) = config.parse_rest_listeners().unwrap();
+        assert_eq!(rest_ips, vec![v4]);
+        let rest_tls = rest_tls.unwrap();
+        assert_eq!(rest_tls.rest_ips, vec![v6]);
+        assert_eq!((rest_tls.cert_path, rest_tls.key_path), (PathBuf::from("cert.pem"), PathBuf::from("key.pem")));
+
+        // The certificate and key must be set together, and are required to serve HTTPS.
+        let config = Start::try_parse_from(["snarkos", "--rest-tls-cert", "cert.pem"].iter()).unwrap();
+        assert!(config.parse_rest_listeners().is_err());
+        let config = Start::try_parse_from(["snarkos", "--rest-tls", "[::1]:3443"].iter()).unwrap();
+        assert!(config.parse_rest_listeners().is_err());
+    }
+
+    #[test]
+    fn test_parse_transmission_storage() {
+        let config = Start::try_parse_from(["snarkos", "--validator"].iter()).unwrap();
+        assert_eq!(config.parse_transmission_storage().unwrap(), TransmissionStorageMode::Persistent);
+        let config = Start::try_parse_from(["snarkos", "--validator", "--bft-storage", "memory"].iter()).unwrap();
+        assert_eq!(config.parse_transmission_storage().unwrap(), TransmissionStorageMode::Memory);
+        let config = Start::try_parse_from(["snarkos", "--validator", "--bft-storage", "disk"].iter()).unwrap();
+        assert!(config.parse_transmission_storage().is_err());
+    }
+
+    #[test]
+    fn test_parse_empty_batch_mode() {
+        let config = Start::try_parse_from(["snarkos", "--validator"].iter()).unwrap();
+        assert_eq!(config.parse_empty_batch_mode().unwrap(), EmptyBatchMode::default());
+        let config = Start::try_parse_from(["snarkos", "--validator", "--empty-batch-delay", "0"].iter()).unwrap();
+        assert_eq!(config.parse_empty_batch_mode().unwrap(), EmptyBatchMode::Propose);
+        let config = Start::try_parse_from(["snarkos", "--validator", "--empty-batch-delay", "5"].iter()).unwrap();
+        assert_eq!(config.parse_empty_batch_mode().unwrap(), EmptyBatchMode::Delay { delay: Duration::from_secs(5) });
+        let delay = (MAX_EMPTY_BATCH_DELAY_IN_SECS + 1).to_string();
+        let config = Start::try_parse_from(["snarkos", "--validator", "--empty-batch-delay", &delay].iter()).unwrap();
+        assert!(config.parse_empty_batch_mode().is_err());
+    }
+
+    #[test]
+    fn test_parse_outbound_bandwidth() {
+        // The outbound bandwidth is unlimited by default.
+        let config = Start::try_parse_from(["snarkos", "--client"].iter()).unwrap();
+        assert_eq!(config.parse_outbound_bandwidth().unwrap(), BandwidthConfig::default());
+
+        let config = Start::try_parse_from(
+            ["snarkos", "--client", "--outbound-bandwidth", "1000000", "--outbound-bandwidth-weights", "10, 1, 1, 0"]
+                .iter(),
+        )
+        .unwrap();
+        let bandwidth = config.parse_outbound_bandwidth().unwrap();
+        assert_eq!(bandwidth.bytes_per_sec, Some(1_000_000));
+        assert_eq!(bandwidth.weights, [10, 1, 1, 0]);
+
+        // The budget must be positive, and every class must have a weight.
+        let config = Start::try_parse_from(["snarkos", "--client", "--outbound-bandwidth", "0"].iter()).unwrap();
+        assert!(config.parse_outbound_bandwidth().is_err());
+        let config =
+            Start::try_parse_from(["snarkos", "--client", "--outbound-bandwidth-weights", "8,4,2"].iter()).unwrap();
+        assert!(config.parse_outbound_bandwidth().is_err());
+        let config =
+            Start::try_parse_from(["snarkos", "--client", "--outbound-bandwidth-weights", "0,4,2,1"].iter()).unwrap();
+        assert!(config.parse_outbound_bandwidth().is_err());
+    }
+
+    #[test]
+    fn test_parse_seed_discovery() {
+        // No seeds are queried by default.
+        let config = Start::try_parse_from(["snarkos", "--client"].iter()).unwrap();
+        assert!(config.parse_seed_discovery::<CurrentNetwork>().unwrap().is_empty());
+
+        let config =
+            Start::try_parse_from(["snarkos", "--client", "--dns-seeds", "seed.example.com, "].iter()).unwrap();
+        assert!(!config.parse_seed_discovery::<CurrentNetwork>().unwrap().is_empty());
+
+        // The seed lists require a well-formed signer, and HTTPS.
+        let private_key = PrivateKey::<CurrentNetwork>::new(&mut ChaChaRng::seed_from_u64(1)).unwrap();
+        let signer = Address::try_from(private_key).unwrap().to_string();
+        let url = "https://example.com/peers.json";
+        let config = Start::try_parse_from(["snarkos", "--client", "--seed-lists", url].iter()).unwrap();
+        assert!(config.parse_seed_discovery::<CurrentNetwork>().is_err());
+        let config =
+            Start::try_parse_from(["snarkos", "--client", "--seed-lists", url, "--seed-list-signer", &signer].iter())
+                .unwrap();
+        assert!(!config.parse_seed_discovery::<CurrentNetwork>().unwrap().is_empty());
+        let config =
+            Start::try_parse_from(["snarkos", "--client", "--seed-lists", url, "--seed-list-signer", "aleo1xyz"].iter())
+                .unwrap();
+        assert!(config.parse_seed_discovery::<CurrentNetwork>().is_err());
+        let url = "http://example.com/peers.json";
+        let config =
+            Start::try_parse_from(["snarkos", "--client", "--seed-lists", url, "--seed-list-signer", &signer].iter())
+                .unwrap();
+        assert!(config.parse_seed_discovery::<CurrentNetwork>().is_err());
+    }
+
+    #[test]
+    fn test_parse_proxy() {
+        // No proxy is used by default.
+        let config = Start::try_parse_from(["snarkos", "--client"].iter()).unwrap();
+        assert_eq!(config.parse_proxy().unwrap(), None);
+
+        let proxy = "socks5://127.0.0.1:9050";
+        let config = Start::try_parse_from(
+            ["snarkos", "--client", "--proxy", proxy, "--external-addr", "203.0.113.7:4130"].iter(),
+        )
+        .unwrap();
+        let expected = ProxyConfig {
+            proxy: "127.0.0.1:9050".parse().unwrap(),
+            external_addr: Some("203.0.113.7:4130".parse().unwrap()),
+        };
+        assert_eq!(config.parse_proxy().unwrap(), Some(expected));
+        // The DNS seeds are supported through the proxy, but the seed lists are not.
+        let config =
+            Start::try_parse_from(["snarkos", "--client", "--proxy", proxy, "--dns-seeds", "seed.example.com"].iter())
+                .unwrap();
+        assert!(!config.parse_seed_discovery::<CurrentNetwork>().unwrap().is_empty());
+        let url = "https://example.com/peers.json";
+        let config = Start::try_parse_from(
+            ["snarkos", "--client", "--proxy", proxy, "--seed-lists", url, "--seed-list-signer", "aleo1xyz"].iter(),
+        )
+        .unwrap();
+        assert!(config.parse_seed_discovery::<CurrentNetwork>().is_err());
+
+        // The external address requires a proxy, and validators do not support a proxy.
+        let config =
+            Start::try_parse_from(["snarkos", "--client", "--external-addr", "203.0.113.7:4130"].iter()).unwrap();
+        assert!(config.parse_proxy().is_err());
+        let config = Start::try_parse_from(["snarkos", "--validator", "--proxy", proxy].iter()).unwrap();
+        assert!(config.parse_proxy().is_err());
+        let config = Start::try_parse_from(["snarkos", "--client", "--proxy", "http://127.0.0.1:9050"].iter()).unwrap();
+        assert!(config.parse_proxy().is_err());
+    }
+
     #[test]
     fn test_parse_cdn() {
         // Validator (Prod)
@@ -736,11 +1544,12 @@ mod tests {
         let config =
             Start::try_parse_from(["snarkos", "--dev", "0", "--validator", "--private-key", "aleo1xx"].iter()).unwrap();
         assert!(config.parse_cdn().is_none());
+        // Note: A devnet node may prefetch blocks from another node of the devnet that serves its block archive.
         let config = Start::try_parse_from(
             ["snarkos", "--dev", "0", "--validator", "--private-key", "aleo1xx", "--cdn", "url"].iter(),
         )
         .unwrap();
-        assert!(config.parse_cdn().is_none());
+        assert_eq!(config.parse_cdn(), Some(vec!["url".to_string()]));
         let config = Start::try_parse_from(
             ["snarkos", "--dev", "0", "--validator", "--private-key", "aleo1xx", "--cdn", ""].iter(),
         )
@@ -790,13 +1599,20 @@ mod tests {
             ["snarkos", "--dev", "0", "--client", "--private-key", "aleo1xx", "--cdn", "url"].iter(),
         )
         .unwrap();
-        assert!(config.parse_cdn().is_none());
+        assert_eq!(config.parse_cdn(), Some(vec!["url".to_string()]));
         let config = Start::try_parse_from(
             ["snarkos", "--dev", "0", "--client", "--private-key", "aleo1xx", "--cdn", ""].iter(),
         )
         .unwrap();
         assert!(config.parse_cdn().is_none());
 
+        // Archive (Prod)
+        let config = Start::try_parse_from(["snarkos", "--archive"].iter()).unwrap();
+        assert!(config.parse_cdn().is_some());
+        assert_eq!(config.parse_node_type(), NodeType::Archive);
+        let config = Start::try_parse_from(["snarkos", "--archive", "--cdn", ""].iter()).unwrap();
+        assert!(config.parse_cdn().is_none());
+
         // Default (Prod)
         let config = Start::try_parse_from(["snarkos"].iter()).unwrap();
         assert!(config.parse_cdn().is_none());
@@ -814,6 +1630,21 @@ mod tests {
         assert!(config.parse_cdn().is_none());
     }
 
+    #[test]
+    fn test_parse_cdn_mirrors() {
+        // Ensure the mirrors are kept in order, without empty entries or trailing slashes.
+        let config = Start::try_parse_from(
+            ["snarkos", "--client", "--cdn", "http://127.0.0.1:3030/mainnet/archive/, ,https://mirror.example"].iter(),
+        )
+        .unwrap();
+        assert_eq!(
+            config.parse_cdn(),
+            Some(vec!["http://127.0.0.1:3030/mainnet/archive".to_string(), "https://mirror.example".to_string()])
+        );
+        let config = Start::try_parse_from(["snarkos", "--client", "--cdn", " , "].iter()).unwrap();
+        assert!(config.parse_cdn().is_none());
+    }
+
     #[test]
     fn test_parse_development_and_genesis() {
         let prod_genesis = Block::from_bytes_le(CurrentNetwork::genesis_bytes()).unwrap();
@@ -835,7 +1666,7 @@ mod tests {
         config.parse_development(&mut trusted_peers, &mut trusted_validators).unwrap();
         let expected_genesis = config.parse_genesis::<CurrentNetwork>().unwrap();
         assert_eq!(config.node, SocketAddr::from_str("0.0.0.0:4130").unwrap());
-        assert_eq!(config.rest, SocketAddr::from_str("0.0.0.0:3030").unwrap());
+        assert_eq!(config.rest, vec![SocketAddr::from_str("0.0.0.0:3030").unwrap()]);
         assert_eq!(trusted_peers.len(), 0);
         assert_eq!(trusted_validators.len(), 1);
         assert!(!config.validator);
@@ -850,7 +1681,7 @@ mod tests {
         config.parse_development(&mut trusted_peers, &mut trusted_validators).unwrap();
         let genesis = config.parse_genesis::<CurrentNetwork>().unwrap();
         assert_eq!(config.node, SocketAddr::from_str("0.0.0.0:4131").unwrap());
-        assert_eq!(config.rest, SocketAddr::from_str("0.0.0.0:3031").unwrap());
+        assert_eq!(config.rest, vec![SocketAddr::from_str("0.0.0.0:3031").unwrap()]);
         assert_eq!(trusted_peers.len(), 1);
         assert_eq!(trusted_validators.len(), 1);
         assert!(config.validator);
@@ -865,7 +1696,7 @@ mod tests {
         config.parse_development(&mut trusted_peers, &mut trusted_validators).unwrap();
         let genesis = config.parse_genesis::<CurrentNetwork>().unwrap();
         assert_eq!(config.node, SocketAddr::from_str("0.0.0.0:4132").unwrap());
-        assert_eq!(config.rest, SocketAddr::from_str("0.0.0.0:3032").unwrap());
+        assert_eq!(config.rest, vec![SocketAddr::from_str("0.0.0.0:3032").unwrap()]);
         assert_eq!(trusted_peers.len(), 2);
         assert_eq!(trusted_validators.len(), 2);
         assert!(!config.validator);
@@ -880,7 +1711,7 @@ mod tests {
         config.parse_development(&mut trusted_peers, &mut trusted_validators).unwrap();
         let genesis = config.parse_genesis::<CurrentNetwork>().unwrap();
         assert_eq!(config.node, SocketAddr::from_str("0.0.0.0:4133").unwrap());
-        assert_eq!(config.rest, SocketAddr::from_str("0.0.0.0:3033").unwrap());
+        assert_eq!(config.rest, vec![SocketAddr::from_str("0.0.0.0:3033").unwrap()]);
         assert_eq!(trusted_peers.len(), 3);
         assert_eq!(trusted_validators.len(), 2);
         assert!(!config.validator);
@@ -889,6 +1720,118 @@ mod tests {
         assert_eq!(genesis, expected_genesis);
     }
 
+    #[test]
+    fn test_apply_config() {
+        use clap::{CommandFactory, FromArgMatches};
+
+        // Returns the configurations parsed from the given arguments, with the given node configuration applied.
+        let parse = |args: &[&str], config: &str| {
+            let matches = Start::command().try_get_matches_from(args).unwrap();
+            let mut start = Start::from_arg_matches(&matches).unwrap();
+            start.apply_config(NodeConfig::from_str(config).unwrap(), &matches);
+            start
+        };
+
+        let config = r#"
+            node_type = "validator"
+            program_denylist = ["first.aleo", "second.aleo"]
+            [p2p]
+            listen = "127.0.0.1:4140"
+            peers = ["1.2.3.4:5", "6.7.8.9:0"]
+            [rest]
+            enabled = false
+            rps = 20
+            [log]
+            verbosity = 3
+        "#;
+
+        // The configuration file takes precedence over the defaults.
+        let start = parse(&["snarkos"], config);
+        assert!(start.validator && !start.prover && !start.client);
+        assert_eq!(start.node, SocketAddr::from_str("127.0.0.1:4140").unwrap());
+        assert_eq!(start.peers, "1.2.3.4:5,6.7.8.9:0");
+        assert!(start.norest);
+        assert_eq!(start.rest_rps, 20);
+        assert_eq!(start.verbosity, 3);
+        assert_eq!(start.program_denylist, "first.aleo,second.aleo");
+        assert_eq!(start.parse_program_denylist::<CurrentNetwork>().unwrap().len(), 2);
+        // Settings missing from the configuration file fall back to the defaults.
+        assert_eq!(start.rest, vec![SocketAddr::from_str("0.0.0.0:3030").unwrap()]);
+        assert_eq!(start.validators, "");
+        assert_eq!(start.dev, None);
+
+        // The command line takes precedence over the configuration file, even when set to the default.
+        let start =
+            parse(&["snarkos", "--client", "--rest-rps", "10", "--verbosity", "1", "--node", "0.0.0.0:4130"], config);
+        assert!(!start.validator && !start.prover && start.client && !start.archive);
+        assert_eq!(start.node, SocketAddr::from_str("0.0.0.0:4130").unwrap());
+        assert_eq!(start.rest_rps, 10);
+        assert_eq!(start.verbosity, 1);
+        // Settings not given on the command line are still taken from the configuration file.
+        assert_eq!(start.peers, "1.2.3.4:5,6.7.8.9:0");
+        assert!(start.norest);
+
+        // The node type from the configuration file is ignored if any node type is given on the command line.
+        let start = parse(&["snarkos", "--archive"], config);
+        assert!(!start.validator && start.archive);
+        let start = parse(&["snarkos"], "node_type = \"archive\"");
+        assert!(!start.client && start.archive);
+
+        // A private key on the command line overrides a private key file in the configuration file.
+        let start = parse(&["snarkos", "--private-key", "KEY"], "private_key_file = \"key.txt\"");
+        assert_eq!(start.private_key.as_deref(), Some("KEY"));
+        assert_eq!(start.private_key_file, None);
+        // A mnemonic on the command line overrides a private key in the configuration file, but not the index.
+        let config = "private_key = \"KEY\"\naccount_index = 3";
+        let start = parse(&["snarkos", "--account-mnemonic-file", "mnemonic.txt"], config);
+        assert_eq!(start.account_mnemonic_file, Some(PathBuf::from("mnemonic.txt")));
+        assert_eq!(start.account_index, Some(3));
+        assert_eq!(start.private_key, None);
+
+        // A malformed program ID in the denylist is refused.
+        let start = parse(&["snarkos", "--program-denylist", "first.aleo,not a program"], "");
+        assert!(start.parse_program_denylist::<CurrentNetwork>().is_err());
+    }
+
+    #[test]
+    fn test_parse_ipv6_addresses() {
+        use clap::{CommandFactory, FromArgMatches};
+
+        // Returns the configurations parsed from the given arguments, with the given node configuration applied.
+        let parse = |args: &[&str], config: &str| {
+            let matches = Start::command().try_get_matches_from(args).unwrap();
+            let mut start = Start::from_arg_matches(&matches).unwrap();
+            start.apply_config(NodeConfig::from_str(config).unwrap(), &matches);
+            start
+        };
+        let (v4, v6) = (SocketAddr::from_str("127.0.0.1:3033").unwrap(), SocketAddr::from_str("[::1]:3033").unwrap());
+
+        // The REST server listens on every address given on the command line.
+        let start =
+            parse(&["snarkos", "--rest", "127.0.0.1:3033", "--rest", "[::1]:3033", "--peers", "[::1]:4130"], "");
+        assert_eq!(start.rest, vec![v4, v6]);
+        assert_eq!(start.parse_trusted_peers().unwrap(), vec![SocketAddr::from_str("[::1]:4130").unwrap()]);
+
+        // The REST server listens on every address given in the configuration file.
+        let config = r#"
+            [p2p]
+            listen = "[::]:4130"
+            peers = ["[::1]:4131", "127.0.0.1:4132"]
+            validators = ["[fe80::1]:5000"]
+            [rest]
+            listen = ["127.0.0.1:3033", "[::1]:3033"]
+        "#;
+        let start = parse(&["snarkos"], config);
+        assert_eq!(start.rest, vec![v4, v6]);
+        assert_eq!(start.node, SocketAddr::from_str("[::]:4130").unwrap());
+        assert_eq!(start.parse_trusted_peers().unwrap().len(), 2);
+        assert_eq!(start.parse_trusted_validators().unwrap(), vec![SocketAddr::from_str("[fe80::1]:5000").unwrap()]);
+
+        // A single REST address in the configuration file is still accepted.
+        let start = parse(&["snarkos"], "[rest]\nlisten = \"[::1]:3033\"");
+        assert_eq!(start.rest, vec![v6]);
+    }
+
     #[test]
     fn clap_snarkos_start() {
         let arg_vec = vec![
@@ -917,7 +1860,7 @@ mod tests {
             assert!(start.validator);
             assert_eq!(start.private_key.as_deref(), Some("PRIVATE_KEY"));
             assert_eq!(start.cdn, "CDN");
-            assert_eq!(start.rest, "127.0.0.1:3030".parse().unwrap());
+            assert_eq!(start.rest, vec!["127.0.0.1:3030".parse().unwrap()]);
             assert_eq!(start.network, 0);
             assert_eq!(start.peers, "IP1,IP2,IP3");
             assert_eq!(start.validators, "IP1,IP2,IP3");
@@ -35,6 +35,22 @@ use tracing_subscriber::{
 /// 6 => info, debug, trace, snarkos_node_tcp=trace
 /// ```
 pub fn initialize_logger<P: AsRef<Path>>(verbosity: u8, nodisplay: bool, logfile: P) -> mpsc::Receiver<Vec<u8>> {
+    initialize_logger_inner(verbosity, nodisplay, logfile, false)
+}
+
+/// Initializes the logger for a devnet running in a single process, without the display.
+/// Each line is prefixed with the name of the thread that logged it, which identifies the node.
+pub fn initialize_devnet_logger<P: AsRef<Path>>(verbosity: u8, logfile: P) {
+    let _ = initialize_logger_inner(verbosity, true, logfile, true);
+}
+
+/// Initializes the logger, with the thread names in each line if `thread_names` is set.
+fn initialize_logger_inner<P: AsRef<Path>>(
+    verbosity: u8,
+    nodisplay: bool,
+    logfile: P,
+    thread_names: bool,
+) -> mpsc::Receiver<Vec<u8>> {
     match verbosity {
         0 => std::env::set_var("RUST_LOG", "info"),
         1 => std::env::set_var("RUST_LOG", "debug"),
@@ -111,6 +127,7 @@ pub fn initialize_logger<P: AsRef<Path>>(verbosity: u8, nodisplay: bool, logfile
                 .with_ansi(log_sender.is_none() && io::stdout().is_tty())
                 .with_writer(move || LogWriter::new(&log_sender))
                 .with_target(verbosity > 2)
+                .with_thread_names(thread_names)
                 .with_filter(filter),
         )
         .with(
@@ -119,6 +136,7 @@ pub fn initialize_logger<P: AsRef<Path>>(verbosity: u8, nodisplay: bool, logfile
                 .with_ansi(false)
                 .with_writer(logfile)
                 .with_target(verbosity > 2)
+                .with_thread_names(thread_names)
                 .with_filter(filter2),
         )
         .try_init();
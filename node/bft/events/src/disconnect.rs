@@ -71,11 +71,26 @@ impl FromBytes for Disconnect {
 }
 
 #[cfg(test)]
-mod tests {
+pub mod tests {
     use crate::{Disconnect, DisconnectReason};
     use snarkvm::console::prelude::{FromBytes, ToBytes};
 
     use bytes::{Buf, BufMut, BytesMut};
+    use proptest::{
+        prelude::{BoxedStrategy, Strategy},
+        sample::select,
+    };
+
+    pub fn any_disconnect() -> BoxedStrategy<Disconnect> {
+        select(vec![
+            DisconnectReason::InvalidChallengeResponse,
+            DisconnectReason::NoReasonGiven,
+            DisconnectReason::ProtocolViolation,
+            DisconnectReason::OutdatedClientVersion,
+        ])
+        .prop_map(Disconnect::from)
+        .boxed()
+    }
 
     #[test]
     fn serialize_deserialize() {
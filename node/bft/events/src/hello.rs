@@ -0,0 +1,73 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+
+/// The first event of a session of sequenced events, which resets the sequence of the receiver.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Hello {
+    /// The session of the sender.
+    pub session: u64,
+}
+
+impl Hello {
+    /// Initializes a new hello event.
+    pub fn new(session: u64) -> Self {
+        Self { session }
+    }
+}
+
+impl EventTrait for Hello {
+    /// Returns the event name.
+    #[inline]
+    fn name(&self) -> Cow<'static, str> {
+        "Hello".into()
+    }
+}
+
+impl ToBytes for Hello {
+    fn write_le<W: Write>(&self, mut writer: W) -> IoResult<()> {
+        self.session.write_le(&mut writer)
+    }
+}
+
+impl FromBytes for Hello {
+    fn read_le<R: Read>(mut reader: R) -> IoResult<Self> {
+        let session = u64::read_le(&mut reader)?;
+        Ok(Self { session })
+    }
+}
+
+#[cfg(test)]
+pub mod prop_tests {
+    use crate::Hello;
+    use snarkvm::console::prelude::{FromBytes, ToBytes};
+
+    use bytes::{Buf, BufMut, BytesMut};
+    use proptest::prelude::{any, BoxedStrategy, Strategy};
+    use test_strategy::proptest;
+
+    pub fn any_hello() -> BoxedStrategy<Hello> {
+        any::<u64>().prop_map(Hello::new).boxed()
+    }
+
+    #[proptest]
+    fn serialize_deserialize(#[strategy(any_hello())] original: Hello) {
+        let mut buf = BytesMut::default().writer();
+        Hello::write_le(&original, &mut buf).unwrap();
+
+        let deserialized = Hello::read_le(buf.into_inner().reader()).unwrap();
+        assert_eq!(original, deserialized);
+    }
+}
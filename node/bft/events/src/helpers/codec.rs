@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::Event;
+use crate::{Event, Hello};
 use snarkvm::prelude::{FromBytes, Network, ToBytes};
 
 use bytes::{Buf, BufMut, BytesMut};
@@ -23,7 +23,7 @@ use tracing::*;
 /// The maximum size of an event that can be transmitted during the handshake.
 const MAX_HANDSHAKE_SIZE: usize = 1024 * 1024; // 1 MiB
 /// The maximum size of an event that can be transmitted in the network.
-const MAX_EVENT_SIZE: usize = 128 * 1024 * 1024; // 128 MiB
+pub(crate) const MAX_EVENT_SIZE: usize = 128 * 1024 * 1024; // 128 MiB
 
 /// The type of noise handshake to use for network encryption.
 pub const NOISE_HANDSHAKE_TYPE: &str = "Noise_XX_25519_ChaChaPoly_BLAKE2s";
@@ -90,6 +90,118 @@ impl<N: Network> Decoder for EventCodec<N> {
     }
 }
 
+/// An event received in a sequenced frame.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SequencedEvent<N: Network> {
+    /// The session of the sender.
+    pub session: u64,
+    /// The sequence number of the event in the session.
+    pub sequence: u64,
+    /// The event.
+    pub event: Event<N>,
+}
+
+/// The codec used to decode and encode network `Event`s after the handshake, in sequenced frames.
+///
+/// Each frame starts with the session of the sender and the sequence number of the event in the session.
+/// The first frame of a session is a `Hello` event with sequence number 0, which resets the sequence of the receiver,
+/// so that the frames of a previous session of the sender (i.e. of a previous connection) can be identified.
+///
+/// With the peers on an event version that predates the sequenced frames, the events are sent in plain frames,
+/// which are decoded with a session and a sequence number of `0`.
+pub struct SequencedEventCodec<N: Network> {
+    codec: LengthDelimitedCodec,
+    /// Whether the events are sent in sequenced frames.
+    is_sequenced: bool,
+    /// The session of the sender, which is only used to encode events.
+    session: u64,
+    /// The sequence number of the next encoded event.
+    next_sequence: u64,
+    _phantom: PhantomData<N>,
+}
+
+impl<N: Network> SequencedEventCodec<N> {
+    /// Initializes a new codec, which encodes the events in the given session.
+    pub fn new(session: u64) -> Self {
+        Self {
+            codec: LengthDelimitedCodec::builder().max_frame_length(MAX_EVENT_SIZE).little_endian().new_codec(),
+            is_sequenced: true,
+            session,
+            next_sequence: 0,
+            _phantom: Default::default(),
+        }
+    }
+
+    /// Initializes a new codec, which encodes the events in plain frames, for a peer predating sequenced frames.
+    pub fn unsequenced() -> Self {
+        Self { is_sequenced: false, ..Self::new(0) }
+    }
+
+    /// Encodes the given event in the next frame of the session.
+    fn encode_frame(&mut self, event: &Event<N>, dst: &mut BytesMut) -> Result<(), std::io::Error> {
+        let mut frame = BytesMut::new().writer();
+        self.session
+            .write_le(&mut frame)
+            .and_then(|_| self.next_sequence.write_le(&mut frame))
+            .and_then(|_| event.write_le(&mut frame))
+            // This error should never happen, the conversion is for greater compatibility.
+            .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidData, "serialization error"))?;
+        self.next_sequence += 1;
+        self.codec.encode(frame.into_inner().freeze(), dst)
+    }
+}
+
+impl<N: Network> Encoder<Event<N>> for SequencedEventCodec<N> {
+    type Error = std::io::Error;
+
+    fn encode(&mut self, event: Event<N>, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        // Send the event in a plain frame, if the peer predates the sequenced frames.
+        if !self.is_sequenced {
+            let mut frame = BytesMut::new().writer();
+            event
+                .write_le(&mut frame)
+                // This error should never happen, the conversion is for greater compatibility.
+                .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidData, "serialization error"))?;
+            return self.codec.encode(frame.into_inner().freeze(), dst);
+        }
+        // Start the session with a hello event.
+        if self.next_sequence == 0 {
+            self.encode_frame(&Event::Hello(Hello::new(self.session)), dst)?;
+        }
+        self.encode_frame(&event, dst)
+    }
+}
+
+impl<N: Network> Decoder for SequencedEventCodec<N> {
+    type Error = std::io::Error;
+    type Item = SequencedEvent<N>;
+
+    fn decode(&mut self, source: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        // Decode a frame containing bytes belonging to an event.
+        let bytes = match self.codec.decode(source)? {
+            Some(bytes) => bytes,
+            None => return Ok(None),
+        };
+
+        // Convert the bytes to a sequenced event, or fail if it is not valid.
+        let mut reader = bytes.reader();
+        let sequenced_event = match self.is_sequenced {
+            true => u64::read_le(&mut reader).and_then(|session| {
+                let sequence = u64::read_le(&mut reader)?;
+                Ok(SequencedEvent { session, sequence, event: Event::read_le(&mut reader)? })
+            }),
+            false => Event::read_le(&mut reader).map(|event| SequencedEvent { session: 0, sequence: 0, event }),
+        };
+        match sequenced_event {
+            Ok(sequenced_event) => Ok(Some(sequenced_event)),
+            Err(error) => {
+                error!("Failed to deserialize a sequenced event: {}", error);
+                Err(std::io::ErrorKind::InvalidData.into())
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -111,4 +223,44 @@ mod tests {
     fn event_roundtrip(#[strategy(any_event())] event: Event<CurrentNetwork>) {
         assert_roundtrip(event)
     }
+
+    #[proptest]
+    fn sequenced_event_roundtrip(
+        #[strategy(any_event())] event: Event<CurrentNetwork>,
+        #[strategy(any_event())] other: Event<CurrentNetwork>,
+        session: u64,
+    ) {
+        let mut codec = SequencedEventCodec::<CurrentNetwork>::new(session);
+        let mut encoded_events = BytesMut::new();
+        codec.encode(event.clone(), &mut encoded_events).unwrap();
+        codec.encode(other.clone(), &mut encoded_events).unwrap();
+
+        // Ensure the session starts with a hello event, followed by the events in sequence.
+        let mut decoded = std::iter::from_fn(|| codec.decode(&mut encoded_events).unwrap()).collect::<Vec<_>>();
+        assert_eq!(decoded.len(), 3);
+        assert!(decoded.iter().all(|decoded| decoded.session == session));
+        assert_eq!(decoded.iter().map(|decoded| decoded.sequence).collect::<Vec<_>>(), vec![0, 1, 2]);
+        assert_eq!(decoded.remove(0).event, Event::Hello(Hello::new(session)));
+        assert_eq!(decoded[0].event.to_bytes_le().unwrap(), event.to_bytes_le().unwrap());
+        assert_eq!(decoded[1].event.to_bytes_le().unwrap(), other.to_bytes_le().unwrap());
+    }
+
+    #[proptest]
+    fn unsequenced_event_is_legacy(#[strategy(any_event())] event: Event<CurrentNetwork>) {
+        let mut codec = SequencedEventCodec::<CurrentNetwork>::unsequenced();
+        let mut encoded_event = BytesMut::new();
+        codec.encode(event.clone(), &mut encoded_event).unwrap();
+
+        // Ensure the event is sent in the plain frame of a peer that predates the sequenced frames.
+        let mut legacy_codec = EventCodec::<CurrentNetwork>::default();
+        let mut legacy_encoded_event = BytesMut::new();
+        legacy_codec.encode(event.clone(), &mut legacy_encoded_event).unwrap();
+        assert_eq!(encoded_event, legacy_encoded_event);
+
+        // Ensure the plain frame is decoded without a hello event.
+        let decoded = codec.decode(&mut encoded_event).unwrap().unwrap();
+        assert_eq!((decoded.session, decoded.sequence), (0, 0));
+        assert_eq!(decoded.event.to_bytes_le().unwrap(), event.to_bytes_le().unwrap());
+        assert!(codec.decode(&mut encoded_event).unwrap().is_none());
+    }
 }
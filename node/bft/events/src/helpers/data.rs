@@ -0,0 +1,263 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{helpers::codec::MAX_EVENT_SIZE, DataBlocks};
+use snarkvm::{
+    ledger::narwhal::{BatchCertificate, BatchHeader, Data},
+    prelude::{
+        block::{Header, Transaction},
+        error,
+        puzzle::Solution,
+        FromBytes,
+        IoResult,
+        Network,
+        Signature,
+        ToBytes,
+    },
+};
+
+use anyhow::{bail, ensure, Result};
+use bytes::Bytes;
+use std::{io::Read, time::Duration};
+
+/// The maximum amount of time to spend deserializing a payload received from the network.
+pub const DATA_DESERIALIZATION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A type that may be received from the network as a deferred `Data` payload.
+pub trait DataLimit {
+    /// The maximum serialized size of the type, in bytes.
+    const MAXIMUM_SIZE: usize;
+    /// The name of the type, used in error messages.
+    const NAME: &'static str;
+}
+
+impl<N: Network> DataLimit for Header<N> {
+    const MAXIMUM_SIZE: usize = 4 * 1024;
+    const NAME: &'static str = "block header";
+}
+
+impl<N: Network> DataLimit for Signature<N> {
+    const MAXIMUM_SIZE: usize = 1024;
+    const NAME: &'static str = "signature";
+}
+
+impl<N: Network> DataLimit for Solution<N> {
+    const MAXIMUM_SIZE: usize = 4 * 1024;
+    const NAME: &'static str = "solution";
+}
+
+impl<N: Network> DataLimit for Transaction<N> {
+    const MAXIMUM_SIZE: usize = 1024 * 1024;
+    const NAME: &'static str = "transaction";
+}
+
+impl<N: Network> DataLimit for BatchHeader<N> {
+    const MAXIMUM_SIZE: usize = 1024 * 1024;
+    const NAME: &'static str = "batch header";
+}
+
+impl<N: Network> DataLimit for BatchCertificate<N> {
+    const MAXIMUM_SIZE: usize = 1024 * 1024;
+    const NAME: &'static str = "batch certificate";
+}
+
+impl<N: Network> DataLimit for DataBlocks<N> {
+    const MAXIMUM_SIZE: usize = MAX_EVENT_SIZE;
+    const NAME: &'static str = "blocks";
+}
+
+/// Ensures the given payload does not exceed the maximum serialized size for its type.
+pub fn ensure_data_size<T: DataLimit + FromBytes + ToBytes + Send + 'static>(data: &Data<T>) -> Result<()> {
+    // Objects were constructed locally, so only buffers need to be checked.
+    if let Data::Buffer(bytes) = data {
+        ensure!(
+            bytes.len() <= T::MAXIMUM_SIZE,
+            "The {} is too large ({} bytes, at most {} bytes are allowed)",
+            T::NAME,
+            bytes.len(),
+            T::MAXIMUM_SIZE
+        );
+    }
+    Ok(())
+}
+
+/// Reads a `Data` payload from the given reader, failing if its length prefix exceeds the maximum serialized size
+/// for its type.
+///
+/// Unlike `Data::read_le`, the length prefix is checked before any bytes are read, and the buffer only grows
+/// with the bytes that are actually received, so that a forged length prefix cannot trigger a large allocation.
+pub fn read_data<T: DataLimit + FromBytes + ToBytes + Send + 'static, R: Read>(mut reader: R) -> IoResult<Data<T>> {
+    // Ensure the number of bytes is within the bounds.
+    let num_bytes = u32::read_le(&mut reader)? as usize;
+    if num_bytes > T::MAXIMUM_SIZE {
+        return Err(error(format!(
+            "The {} is too large ({num_bytes} bytes, at most {} bytes are allowed)",
+            T::NAME,
+            T::MAXIMUM_SIZE
+        )));
+    }
+    // Read the bytes.
+    let mut buffer = Vec::new();
+    reader.take(num_bytes as u64).read_to_end(&mut buffer)?;
+    if buffer.len() != num_bytes {
+        return Err(error(format!("The {} is truncated ({} of {num_bytes} bytes)", T::NAME, buffer.len())));
+    }
+    Ok(Data::Buffer(Bytes::from(buffer)))
+}
+
+/// Returns the deserialized payload, after ensuring it does not exceed the maximum serialized size for its type.
+///
+/// The payload is deserialized on the blocking pool, and an error is returned if it is oversized, malformed,
+/// or not deserialized within `DATA_DESERIALIZATION_TIMEOUT`. Note that a timed out task runs to completion
+/// in the background, as blocking tasks cannot be aborted; its result is discarded.
+pub async fn deserialize_data<T: DataLimit + FromBytes + ToBytes + Send + 'static>(data: Data<T>) -> Result<T> {
+    // Ensure the payload does not exceed the maximum size.
+    ensure_data_size(&data)?;
+    // Deserialize the payload on the blocking pool.
+    let task = tokio::task::spawn_blocking(move || data.deserialize_blocking());
+    match tokio::time::timeout(DATA_DESERIALIZATION_TIMEOUT, task).await {
+        Ok(Ok(result)) => result,
+        Ok(Err(error)) => bail!("[tokio::spawn_blocking] {error}"),
+        Err(_) => bail!("Timed out while deserializing the {}", T::NAME),
+    }
+}
+
+/// An extension of `Data`, for forwarding a payload without a deserialization round trip.
+pub trait DataBytes: Sized {
+    /// Returns the canonical serialized bytes of the payload.
+    ///
+    /// A payload received as a buffer is returned as is, which only increments the reference count of the buffer.
+    /// In debug builds, this asserts that re-serializing a valid buffer would produce identical bytes.
+    fn canonical_bytes(&self) -> Result<Bytes>;
+
+    /// Returns the payload in its serialized form, so that it is not serialized again for every peer it is sent to.
+    /// If the payload cannot be serialized, it is returned unchanged.
+    fn into_buffer(self) -> Self;
+}
+
+impl<T: FromBytes + ToBytes + Send + 'static> DataBytes for Data<T> {
+    fn canonical_bytes(&self) -> Result<Bytes> {
+        match self {
+            Self::Object(object) => Ok(Bytes::from(object.to_bytes_le()?)),
+            Self::Buffer(bytes) => {
+                #[cfg(debug_assertions)]
+                if let Ok(object) = T::from_bytes_le(bytes) {
+                    debug_assert_eq!(
+                        object.to_bytes_le().ok().as_deref(),
+                        Some(&bytes[..]),
+                        "The buffer is not in the canonical serialized form"
+                    );
+                }
+                Ok(bytes.clone())
+            }
+        }
+    }
+
+    fn into_buffer(self) -> Self {
+        match self.canonical_bytes() {
+            Ok(bytes) => Self::Buffer(bytes),
+            Err(_) => self,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkvm::{ledger::block::Block, prelude::MainnetV0};
+
+    type CurrentNetwork = MainnetV0;
+
+    /// Returns the serialized genesis block header.
+    fn sample_header_bytes() -> Vec<u8> {
+        let block = Block::<CurrentNetwork>::from_bytes_le(CurrentNetwork::genesis_bytes()).unwrap();
+        block.header().to_bytes_le().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_deserialize_data() {
+        let bytes = sample_header_bytes();
+        let expected = Header::<CurrentNetwork>::from_bytes_le(&bytes).unwrap();
+
+        // Ensure a valid buffer is deserialized.
+        let data = Data::<Header<CurrentNetwork>>::Buffer(Bytes::from(bytes));
+        assert_eq!(deserialize_data(data).await.unwrap(), expected);
+        // Ensure an object is returned as is.
+        let data = Data::Object(expected.clone());
+        assert_eq!(deserialize_data(data).await.unwrap(), expected);
+    }
+
+    #[tokio::test]
+    async fn test_deserialize_data_rejects_oversized_payload() {
+        // Append trailing bytes to a valid header, so that it exceeds the maximum size.
+        let mut bytes = sample_header_bytes();
+        bytes.resize(Header::<CurrentNetwork>::MAXIMUM_SIZE + 1, 0);
+
+        let data = Data::<Header<CurrentNetwork>>::Buffer(Bytes::from(bytes));
+        assert!(ensure_data_size(&data).is_err());
+        let error = deserialize_data(data).await.unwrap_err();
+        assert!(error.to_string().contains("too large"), "{error}");
+    }
+
+    #[test]
+    fn test_canonical_bytes() {
+        let bytes = sample_header_bytes();
+        let header = Header::<CurrentNetwork>::from_bytes_le(&bytes).unwrap();
+
+        // Ensure a buffer is returned without copying its bytes.
+        let data = Data::<Header<CurrentNetwork>>::Buffer(Bytes::from(bytes.clone()));
+        let canonical = data.canonical_bytes().unwrap();
+        assert_eq!(canonical, bytes);
+        let Data::Buffer(buffer) = &data else { unreachable!() };
+        assert_eq!(canonical.as_ptr(), buffer.as_ptr());
+        // Ensure an object is serialized to the same bytes.
+        assert_eq!(Data::Object(header.clone()).canonical_bytes().unwrap(), bytes);
+
+        // Ensure an object in its serialized form is written to the wire identically.
+        let object = Data::Object(header);
+        let buffer = object.clone().into_buffer();
+        assert!(matches!(buffer, Data::Buffer(_)));
+        assert_eq!(object.to_bytes_le().unwrap(), buffer.to_bytes_le().unwrap());
+    }
+
+    #[test]
+    fn test_read_data() {
+        let bytes = sample_header_bytes();
+
+        // Ensure a payload is read as the buffer that was written.
+        let written = Data::<Header<CurrentNetwork>>::Buffer(Bytes::from(bytes.clone())).to_bytes_le().unwrap();
+        let data = read_data::<Header<CurrentNetwork>, _>(&written[..]).unwrap();
+        assert_eq!(data.canonical_bytes().unwrap(), bytes);
+
+        // Ensure a length prefix above the maximum size is rejected before the payload is read.
+        for num_bytes in [Header::<CurrentNetwork>::MAXIMUM_SIZE as u32 + 1, u32::MAX] {
+            let error = read_data::<Header<CurrentNetwork>, _>(&num_bytes.to_le_bytes()[..]).unwrap_err();
+            assert!(error.to_string().contains("too large"), "{error}");
+        }
+
+        // Ensure a length prefix beyond the end of the payload is rejected.
+        let error = read_data::<Header<CurrentNetwork>, _>(&written[..written.len() - 1]).unwrap_err();
+        assert!(error.to_string().contains("truncated"), "{error}");
+    }
+
+    #[tokio::test]
+    async fn test_deserialize_data_rejects_truncated_payload() {
+        let bytes = sample_header_bytes();
+        let truncated = bytes[..bytes.len() / 2].to_vec();
+
+        let data = Data::<Header<CurrentNetwork>>::Buffer(Bytes::from(truncated));
+        assert!(ensure_data_size(&data).is_ok());
+        assert!(deserialize_data(data).await.is_err());
+    }
+}
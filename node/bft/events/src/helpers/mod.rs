@@ -14,3 +14,9 @@
 
 mod codec;
 pub use codec::*;
+
+mod data;
+pub use data::*;
+
+mod version;
+pub use version::*;
@@ -0,0 +1,77 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// The features of the event protocol that depend on the event version negotiated with a peer.
+///
+/// The features are gated on the version that introduced them, so that the peers on an older version
+/// remain connected, and are only sent the events they are able to process.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Feature {
+    /// Block responses of more than one block.
+    MultipleBlocksPerResponse,
+    /// Large transmissions transferred in `TransmissionChunkResponse` events.
+    TransmissionChunks,
+    /// Events sent in sequenced frames, starting with a `Hello` event.
+    SequencedFrames,
+}
+
+impl Feature {
+    /// The list of all features.
+    pub const ALL: [Self; 3] = [Self::MultipleBlocksPerResponse, Self::TransmissionChunks, Self::SequencedFrames];
+
+    /// Returns the first event version supporting the feature.
+    pub const fn since(&self) -> u32 {
+        match self {
+            Self::MultipleBlocksPerResponse => 7,
+            Self::TransmissionChunks => 8,
+            Self::SequencedFrames => 9,
+        }
+    }
+
+    /// Returns `true` if the feature is available on the given event version.
+    pub const fn is_enabled(&self, version: u32) -> bool {
+        version >= self.since()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Event;
+
+    type CurrentNetwork = snarkvm::prelude::MainnetV0;
+
+    #[test]
+    fn test_features() {
+        for feature in Feature::ALL {
+            // Ensure each feature is only available from the version that introduced it.
+            assert!(!feature.is_enabled(feature.since() - 1));
+            assert!(feature.is_enabled(feature.since()));
+            // Ensure each feature is available on the current version, but not on the minimum version.
+            assert!(feature.is_enabled(Event::<CurrentNetwork>::VERSION));
+            assert!(!feature.is_enabled(Event::<CurrentNetwork>::MINIMUM_VERSION));
+        }
+    }
+
+    #[test]
+    fn test_negotiate() {
+        type E = Event<CurrentNetwork>;
+        // Ensure the outdated versions are refused, and the older versions are negotiated as is.
+        assert_eq!(E::negotiate(E::MINIMUM_VERSION - 1), None);
+        assert_eq!(E::negotiate(E::MINIMUM_VERSION), Some(E::MINIMUM_VERSION));
+        assert_eq!(E::negotiate(E::VERSION), Some(E::VERSION));
+        // Ensure a newer version is negotiated down to the current version.
+        assert_eq!(E::negotiate(E::VERSION + 1), Some(E::VERSION));
+    }
+}
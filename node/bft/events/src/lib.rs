@@ -44,12 +44,21 @@ pub use challenge_response::ChallengeResponse;
 mod disconnect;
 pub use disconnect::{Disconnect, DisconnectReason};
 
+mod hello;
+pub use hello::Hello;
+
 mod helpers;
 pub use helpers::*;
 
 mod primary_ping;
 pub use primary_ping::PrimaryPing;
 
+mod transmission_chunk_request;
+pub use transmission_chunk_request::TransmissionChunkRequest;
+
+mod transmission_chunk_response;
+pub use transmission_chunk_response::*;
+
 mod transmission_request;
 pub use transmission_request::TransmissionRequest;
 
@@ -65,7 +74,10 @@ pub use validators_response::ValidatorsResponse;
 mod worker_ping;
 pub use worker_ping::WorkerPing;
 
-use snarkos_node_sync_locators::BlockLocators;
+#[cfg(any(test, feature = "test"))]
+pub mod test_vectors;
+
+use snarkos_node_sync_locators::{BlockLocators, MAXIMUM_BLOCKS_PER_REQUEST};
 use snarkvm::{
     console::prelude::{error, FromBytes, Network, Read, ToBytes, Write},
     ledger::{
@@ -76,6 +88,7 @@ use snarkvm::{
 };
 
 use anyhow::{bail, ensure, Result};
+use bytes::Bytes;
 use indexmap::{IndexMap, IndexSet};
 use serde::{Deserialize, Serialize};
 pub use std::io::{self, Result as IoResult};
@@ -101,7 +114,10 @@ pub enum Event<N: Network> {
     ChallengeRequest(ChallengeRequest<N>),
     ChallengeResponse(ChallengeResponse<N>),
     Disconnect(Disconnect),
+    Hello(Hello),
     PrimaryPing(PrimaryPing<N>),
+    TransmissionChunkRequest(TransmissionChunkRequest<N>),
+    TransmissionChunkResponse(TransmissionChunkResponse<N>),
     TransmissionRequest(TransmissionRequest<N>),
     TransmissionResponse(TransmissionResponse<N>),
     ValidatorsRequest(ValidatorsRequest),
@@ -116,8 +132,20 @@ impl<N: Network> From<DisconnectReason> for Event<N> {
 }
 
 impl<N: Network> Event<N> {
+    /// The minimum version of the event protocol, on which peers remain able to connect.
+    pub const MINIMUM_VERSION: u32 = 6;
     /// The version of the event protocol; it can be incremented in order to force users to update.
-    pub const VERSION: u32 = 6;
+    /// Note: The features introduced since the minimum version are only used with the peers that support them.
+    pub const VERSION: u32 = 9;
+
+    /// Returns the event version negotiated with a peer on the given version, or `None` if its version is outdated.
+    pub const fn negotiate(version: u32) -> Option<u32> {
+        match version < Self::MINIMUM_VERSION {
+            true => None,
+            false if version < Self::VERSION => Some(version),
+            false => Some(Self::VERSION),
+        }
+    }
 
     /// Returns the event name.
     #[inline]
@@ -133,7 +161,10 @@ impl<N: Network> Event<N> {
             Self::ChallengeRequest(event) => event.name(),
             Self::ChallengeResponse(event) => event.name(),
             Self::Disconnect(event) => event.name(),
+            Self::Hello(event) => event.name(),
             Self::PrimaryPing(event) => event.name(),
+            Self::TransmissionChunkRequest(event) => event.name(),
+            Self::TransmissionChunkResponse(event) => event.name(),
             Self::TransmissionRequest(event) => event.name(),
             Self::TransmissionResponse(event) => event.name(),
             Self::ValidatorsRequest(event) => event.name(),
@@ -156,7 +187,10 @@ impl<N: Network> Event<N> {
             Self::ChallengeRequest(..) => 7,
             Self::ChallengeResponse(..) => 8,
             Self::Disconnect(..) => 9,
+            Self::Hello(..) => 18,
             Self::PrimaryPing(..) => 10,
+            Self::TransmissionChunkRequest(..) => 16,
+            Self::TransmissionChunkResponse(..) => 17,
             Self::TransmissionRequest(..) => 11,
             Self::TransmissionResponse(..) => 12,
             Self::ValidatorsRequest(..) => 13,
@@ -164,6 +198,28 @@ impl<N: Network> Event<N> {
             Self::WorkerPing(..) => 15,
         }
     }
+
+    /// Returns the event with its object payloads in their serialized form.
+    /// This ensures an event that is sent to many peers is only serialized once.
+    pub fn into_buffered(self) -> Self {
+        match self {
+            Self::BatchPropose(BatchPropose { round, batch_header }) => {
+                Self::BatchPropose(BatchPropose::new(round, batch_header.into_buffer()))
+            }
+            Self::BatchCertified(BatchCertified { certificate }) => {
+                Self::BatchCertified(BatchCertified::new(certificate.into_buffer()))
+            }
+            Self::TransmissionResponse(TransmissionResponse { transmission_id, transmission }) => {
+                let transmission = match transmission {
+                    Transmission::Solution(solution) => Transmission::Solution(solution.into_buffer()),
+                    Transmission::Transaction(transaction) => Transmission::Transaction(transaction.into_buffer()),
+                    transmission => transmission,
+                };
+                Self::TransmissionResponse(TransmissionResponse::new(transmission_id, transmission))
+            }
+            event => event,
+        }
+    }
 }
 
 impl<N: Network> ToBytes for Event<N> {
@@ -181,7 +237,10 @@ impl<N: Network> ToBytes for Event<N> {
             Self::ChallengeRequest(event) => event.write_le(writer),
             Self::ChallengeResponse(event) => event.write_le(writer),
             Self::Disconnect(event) => event.write_le(writer),
+            Self::Hello(event) => event.write_le(writer),
             Self::PrimaryPing(event) => event.write_le(writer),
+            Self::TransmissionChunkRequest(event) => event.write_le(writer),
+            Self::TransmissionChunkResponse(event) => event.write_le(writer),
             Self::TransmissionRequest(event) => event.write_le(writer),
             Self::TransmissionResponse(event) => event.write_le(writer),
             Self::ValidatorsRequest(event) => event.write_le(writer),
@@ -214,7 +273,10 @@ impl<N: Network> FromBytes for Event<N> {
             13 => Self::ValidatorsRequest(ValidatorsRequest::read_le(&mut reader)?),
             14 => Self::ValidatorsResponse(ValidatorsResponse::read_le(&mut reader)?),
             15 => Self::WorkerPing(WorkerPing::read_le(&mut reader)?),
-            16.. => return Err(error("Unknown event ID {id}")),
+            16 => Self::TransmissionChunkRequest(TransmissionChunkRequest::read_le(&mut reader)?),
+            17 => Self::TransmissionChunkResponse(TransmissionChunkResponse::read_le(&mut reader)?),
+            18 => Self::Hello(Hello::read_le(&mut reader)?),
+            19.. => return Err(error("Unknown event ID {id}")),
         };
 
         // Ensure that there are no "dangling" bytes.
@@ -251,27 +313,44 @@ pub mod prop_tests {
         batch_certified::prop_tests::any_batch_certified,
         batch_propose::prop_tests::any_batch_propose,
         batch_signature::prop_tests::any_batch_signature,
+        block_request::prop_tests::any_block_request,
+        block_response::prop_tests::{any_block, any_block_response},
         certificate_request::prop_tests::any_certificate_request,
         certificate_response::prop_tests::any_certificate_response,
         challenge_request::prop_tests::any_challenge_request,
         challenge_response::prop_tests::any_challenge_response,
+        disconnect::tests::any_disconnect,
+        hello::prop_tests::any_hello,
+        primary_ping::prop_tests::any_primary_ping,
+        transmission_chunk_request::prop_tests::any_transmission_chunk_request,
+        transmission_chunk_response::prop_tests::any_transmission_chunk_response,
         transmission_request::prop_tests::any_transmission_request,
         transmission_response::prop_tests::any_transmission_response,
+        validators_request::tests::any_validators_request,
+        validators_response::prop_tests::any_validators_response,
         worker_ping::prop_tests::any_worker_ping,
-        Disconnect,
-        DisconnectReason,
+        BlockResponse,
+        DataBlocks,
         Event,
+        TransmissionChunkRequest,
+        TransmissionChunkResponse,
+        MAX_TRANSMISSION_CHUNKS,
+        TRANSMISSION_CHUNK_SIZE_IN_BYTES,
     };
     use snarkvm::{
         console::{network::Network, types::Field},
-        ledger::{narwhal::TransmissionID, puzzle::SolutionID},
+        ledger::{
+            narwhal::{Data, TransmissionID},
+            puzzle::SolutionID,
+        },
         prelude::{FromBytes, Rng, ToBytes, Uniform},
     };
 
+    use bytes::Bytes;
     use proptest::{
-        prelude::{any, BoxedStrategy, Just, Strategy},
+        collection::vec,
+        prelude::{any, BoxedStrategy, Just, ProptestConfig, Strategy},
         prop_oneof,
-        sample::Selector,
     };
     use test_strategy::proptest;
 
@@ -308,24 +387,59 @@ pub mod prop_tests {
             any_certificate_request().prop_map(Event::CertificateRequest),
             any_certificate_response().prop_map(Event::CertificateResponse),
             any_challenge_request().prop_map(Event::ChallengeRequest),
+            any_block_request().prop_map(Event::BlockRequest),
+            any_block_response().prop_map(Event::BlockResponse),
             any_challenge_response().prop_map(Event::ChallengeResponse),
-            (
-                Just(vec![
-                    DisconnectReason::ProtocolViolation,
-                    DisconnectReason::NoReasonGiven,
-                    DisconnectReason::InvalidChallengeResponse,
-                    DisconnectReason::OutdatedClientVersion,
-                ]),
-                any::<Selector>()
-            )
-                .prop_map(|(reasons, selector)| Event::Disconnect(Disconnect::from(selector.select(reasons)))),
+            any_disconnect().prop_map(Event::Disconnect),
+            any_hello().prop_map(Event::Hello),
+            any_primary_ping().prop_map(Event::PrimaryPing),
+            any_transmission_chunk_request().prop_map(Event::TransmissionChunkRequest),
+            any_transmission_chunk_response().prop_map(Event::TransmissionChunkResponse),
             any_transmission_request().prop_map(Event::TransmissionRequest),
             any_transmission_response().prop_map(Event::TransmissionResponse),
+            any_validators_request().prop_map(Event::ValidatorsRequest),
+            any_validators_response().prop_map(Event::ValidatorsResponse),
             any_worker_ping().prop_map(Event::WorkerPing)
         ]
         .boxed()
     }
 
+    /// Returns a strategy for the events whose collections and indices are at their maximum.
+    pub fn any_event_at_capacity() -> BoxedStrategy<Event<CurrentNetwork>> {
+        prop_oneof![
+            (any_block_request(), vec(any_block(), BlockResponse::<CurrentNetwork>::MAXIMUM_NUMBER_OF_BLOCKS as usize))
+                .prop_map(|(request, blocks)| {
+                    Event::BlockResponse(BlockResponse { request, blocks: Data::Object(DataBlocks(blocks)) })
+                }),
+            any_transmission_id().prop_map(|transmission_id| {
+                Event::TransmissionChunkRequest(TransmissionChunkRequest::new(
+                    transmission_id,
+                    MAX_TRANSMISSION_CHUNKS - 1,
+                ))
+            }),
+            (any_transmission_id(), any::<u8>()).prop_map(|(transmission_id, byte)| {
+                let bytes = Bytes::from(vec![byte; TRANSMISSION_CHUNK_SIZE_IN_BYTES]);
+                Event::TransmissionChunkResponse(TransmissionChunkResponse::new(
+                    transmission_id,
+                    MAX_TRANSMISSION_CHUNKS - 1,
+                    MAX_TRANSMISSION_CHUNKS,
+                    bytes,
+                ))
+            }),
+        ]
+        .boxed()
+    }
+
+    /// Checks that the given event is deserialized from its bytes, and serialized back into the same bytes.
+    fn check_roundtrip(event: &Event<CurrentNetwork>) {
+        let bytes = event.to_bytes_le().unwrap();
+        let decoded = Event::<CurrentNetwork>::read_le(&bytes[..]).unwrap();
+        assert_eq!(decoded.id(), event.id());
+        assert_eq!(decoded.name(), event.name());
+        // Note: The events with lazily deserialized payloads are compared by their bytes.
+        assert_eq!(decoded.to_bytes_le().unwrap(), bytes);
+    }
+
     #[proptest]
     fn serialize_deserialize(#[strategy(any_event())] original: Event<CurrentNetwork>) {
         let mut buf = Vec::new();
@@ -335,4 +449,23 @@ pub mod prop_tests {
         assert_eq!(original.id(), deserialized.id());
         assert_eq!(original.name(), deserialized.name());
     }
+
+    #[proptest]
+    fn event_roundtrip(#[strategy(any_event())] original: Event<CurrentNetwork>) {
+        check_roundtrip(&original);
+    }
+
+    #[proptest(ProptestConfig::with_cases(16))]
+    fn event_at_capacity_roundtrip(#[strategy(any_event_at_capacity())] original: Event<CurrentNetwork>) {
+        check_roundtrip(&original);
+    }
+    #[proptest]
+    fn buffered_event_is_wire_compatible(#[strategy(any_event())] original: Event<CurrentNetwork>) {
+        let mut expected = Vec::new();
+        Event::write_le(&original, &mut expected).unwrap();
+
+        let mut buf = Vec::new();
+        Event::write_le(&original.into_buffered(), &mut buf).unwrap();
+        assert_eq!(expected, buf);
+    }
 }
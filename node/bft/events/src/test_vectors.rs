@@ -0,0 +1,94 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+
+/// The path of the event test vectors, relative to the root of this crate.
+pub const EVENT_TEST_VECTORS_PATH: &str = "test-vectors/events.txt";
+
+/// The header of the event test vectors.
+const HEADER: &str = "\
+# The serialized events whose encoding is pinned by the conformance tests, as `<name> <hex>` lines.
+# Regenerate with `cargo run -p snarkos-node-bft-events --features test --bin event-test-vectors`.
+";
+
+/// Returns the sample events that are pinned by the test vectors, along with their names.
+///
+/// Note: The events that carry ledger objects (batch headers, certificates, transmissions, signatures, or blocks)
+/// are serialized by snarkVM, and are only covered by the round-trip fuzz tests.
+pub fn sample_events<N: Network>() -> Vec<(&'static str, Event<N>)> {
+    vec![
+        ("BlockRequest", Event::BlockRequest(BlockRequest::new(5, 10))),
+        ("CertificateRequest", Event::CertificateRequest(CertificateRequest::new(Field::from_u64(11)))),
+        ("Disconnect", Event::from(DisconnectReason::ProtocolViolation)),
+        ("Hello", Event::Hello(Hello::new(0x0123_4567_89ab_cdef))),
+        ("ValidatorsRequest", Event::ValidatorsRequest(ValidatorsRequest)),
+    ]
+}
+
+/// Returns the test vectors of the sample events.
+pub fn to_test_vectors<N: Network>() -> Result<String> {
+    let mut vectors = HEADER.to_string();
+    for (name, event) in sample_events::<N>() {
+        let bytes = event.to_bytes_le()?;
+        vectors.push_str(&format!("{name} {}\n", bytes.iter().map(|byte| format!("{byte:02x}")).collect::<String>()));
+    }
+    Ok(vectors)
+}
+
+/// Parses the given test vectors into the names and the bytes of the serialized events.
+pub fn parse_test_vectors(vectors: &str) -> Result<Vec<(String, Vec<u8>)>> {
+    let mut parsed = Vec::new();
+    for line in vectors.lines().filter(|line| !line.is_empty() && !line.starts_with('#')) {
+        let Some((name, hex)) = line.split_once(' ') else {
+            bail!("Malformed test vector '{line}'");
+        };
+        ensure!(hex.len() % 2 == 0 && hex.is_ascii(), "Malformed hex in the test vector '{name}'");
+        let bytes = (0..hex.len())
+            .step_by(2)
+            .map(|index| u8::from_str_radix(&hex[index..index + 2], 16))
+            .collect::<Result<Vec<_>, _>>()?;
+        parsed.push((name.to_string(), bytes));
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type CurrentNetwork = snarkvm::prelude::MainnetV0;
+
+    /// The committed test vectors.
+    const VECTORS: &str = include_str!("../test-vectors/events.txt");
+
+    #[test]
+    fn test_serialization_matches_test_vectors() {
+        // Note: A failure here means the wire format changed; if that is intended, regenerate the test vectors.
+        assert_eq!(to_test_vectors::<CurrentNetwork>().unwrap(), VECTORS);
+    }
+
+    #[test]
+    fn test_test_vectors_deserialize() {
+        let samples = sample_events::<CurrentNetwork>();
+        let vectors = parse_test_vectors(VECTORS).unwrap();
+        assert_eq!(vectors.len(), samples.len());
+
+        for ((name, bytes), (expected_name, expected)) in vectors.into_iter().zip(samples) {
+            assert_eq!(name, expected_name);
+            let event = Event::<CurrentNetwork>::read_le(&bytes[..]).unwrap();
+            assert_eq!(event, expected, "Test vector '{name}' does not deserialize to the sample event");
+        }
+    }
+}
@@ -0,0 +1,128 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+
+/// The size in bytes of each chunk of a transmission that is transferred in chunks.
+pub const TRANSMISSION_CHUNK_SIZE_IN_BYTES: usize = 128 * 1024;
+/// The maximum number of chunks of a transmission, which covers the maximum size of a transaction.
+pub const MAX_TRANSMISSION_CHUNKS: u32 = 16;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransmissionChunkResponse<N: Network> {
+    pub transmission_id: TransmissionID<N>,
+    pub chunk_index: u32,
+    pub total_chunks: u32,
+    pub bytes: Bytes,
+}
+
+impl<N: Network> TransmissionChunkResponse<N> {
+    /// Initializes a new transmission chunk response event.
+    pub fn new(transmission_id: TransmissionID<N>, chunk_index: u32, total_chunks: u32, bytes: Bytes) -> Self {
+        Self { transmission_id, chunk_index, total_chunks, bytes }
+    }
+}
+
+impl<N: Network> EventTrait for TransmissionChunkResponse<N> {
+    /// Returns the event name.
+    #[inline]
+    fn name(&self) -> Cow<'static, str> {
+        "TransmissionChunkResponse".into()
+    }
+}
+
+impl<N: Network> ToBytes for TransmissionChunkResponse<N> {
+    fn write_le<W: Write>(&self, mut writer: W) -> IoResult<()> {
+        self.transmission_id.write_le(&mut writer)?;
+        self.chunk_index.write_le(&mut writer)?;
+        self.total_chunks.write_le(&mut writer)?;
+        u32::try_from(self.bytes.len()).map_err(error)?.write_le(&mut writer)?;
+        writer.write_all(&self.bytes)?;
+        Ok(())
+    }
+}
+
+impl<N: Network> FromBytes for TransmissionChunkResponse<N> {
+    fn read_le<R: Read>(mut reader: R) -> IoResult<Self> {
+        let transmission_id = TransmissionID::read_le(&mut reader)?;
+        let chunk_index = u32::read_le(&mut reader)?;
+        let total_chunks = u32::read_le(&mut reader)?;
+        if total_chunks == 0 || total_chunks > MAX_TRANSMISSION_CHUNKS || chunk_index >= total_chunks {
+            return Err(error(format!("Invalid transmission chunk {chunk_index} of {total_chunks}")));
+        }
+        let num_bytes = u32::read_le(&mut reader)? as usize;
+        if num_bytes == 0 || num_bytes > TRANSMISSION_CHUNK_SIZE_IN_BYTES {
+            return Err(error(format!("Invalid transmission chunk size ({num_bytes} bytes)")));
+        }
+        let mut bytes = vec![0u8; num_bytes];
+        reader.read_exact(&mut bytes)?;
+
+        Ok(Self { transmission_id, chunk_index, total_chunks, bytes: bytes.into() })
+    }
+}
+
+#[cfg(test)]
+pub mod prop_tests {
+    use crate::{
+        prop_tests::any_transmission_id,
+        TransmissionChunkResponse,
+        MAX_TRANSMISSION_CHUNKS,
+        TRANSMISSION_CHUNK_SIZE_IN_BYTES,
+    };
+    use snarkvm::{
+        console::prelude::{FromBytes, ToBytes},
+        ledger::narwhal::TransmissionID,
+    };
+
+    use bytes::{Buf, BufMut, Bytes, BytesMut};
+    use proptest::{
+        collection,
+        prelude::{any, BoxedStrategy, Just, Strategy},
+    };
+    use test_strategy::proptest;
+
+    type CurrentNetwork = snarkvm::prelude::MainnetV0;
+
+    pub fn any_transmission_chunk_response() -> BoxedStrategy<TransmissionChunkResponse<CurrentNetwork>> {
+        (
+            any_transmission_id(),
+            (1..=MAX_TRANSMISSION_CHUNKS).prop_flat_map(|total_chunks| (0..total_chunks, Just(total_chunks))),
+            collection::vec(any::<u8>(), 1..=TRANSMISSION_CHUNK_SIZE_IN_BYTES.min(1024)),
+        )
+            .prop_map(|(transmission_id, (chunk_index, total_chunks), bytes)| {
+                TransmissionChunkResponse::new(transmission_id, chunk_index, total_chunks, Bytes::from(bytes))
+            })
+            .boxed()
+    }
+
+    #[proptest]
+    fn serialize_deserialize(
+        #[strategy(any_transmission_chunk_response())] original: TransmissionChunkResponse<CurrentNetwork>,
+    ) {
+        let mut buf = BytesMut::default().writer();
+        TransmissionChunkResponse::write_le(&original, &mut buf).unwrap();
+
+        let deserialized = TransmissionChunkResponse::read_le(buf.into_inner().reader()).unwrap();
+        assert_eq!(original, deserialized);
+    }
+
+    #[test]
+    fn test_oversized_chunk_is_rejected() {
+        let mut buf = BytesMut::default().writer();
+        let transmission_id = TransmissionID::<CurrentNetwork>::Ratification;
+        let bytes = Bytes::from(vec![0u8; TRANSMISSION_CHUNK_SIZE_IN_BYTES + 1]);
+        TransmissionChunkResponse::new(transmission_id, 0, 1, bytes).write_le(&mut buf).unwrap();
+        assert!(TransmissionChunkResponse::<CurrentNetwork>::read_le(buf.into_inner().reader()).is_err());
+    }
+}
@@ -42,8 +42,13 @@ pub mod tests {
     use crate::ValidatorsRequest;
 
     use bytes::{Buf, BufMut, BytesMut};
+    use proptest::prelude::{BoxedStrategy, Just, Strategy};
     use snarkvm::utilities::{FromBytes, ToBytes};
 
+    pub fn any_validators_request() -> BoxedStrategy<ValidatorsRequest> {
+        Just(ValidatorsRequest).boxed()
+    }
+
     #[test]
     fn validators_request_roundtrip() {
         let validators_request = ValidatorsRequest;
@@ -0,0 +1,115 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkos_node_bft_events::{Event, TransmissionResponse};
+use snarkvm::{
+    ledger::{
+        ledger_test_helpers::sample_fee_public_transaction,
+        narwhal::{Data, Transmission, TransmissionID},
+    },
+    prelude::{MainnetV0, TestRng, ToBytes},
+};
+
+use bytes::Bytes;
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    io,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+type CurrentNetwork = MainnetV0;
+
+/// An allocator that tracks the number of allocations, and the number of bytes allocated.
+struct CountingAllocator {
+    allocations: AtomicUsize,
+    bytes: AtomicUsize,
+}
+
+impl CountingAllocator {
+    /// Returns the number of allocations, and the number of bytes allocated so far.
+    fn snapshot(&self) -> (usize, usize) {
+        (self.allocations.load(Ordering::SeqCst), self.bytes.load(Ordering::SeqCst))
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            self.allocations.fetch_add(1, Ordering::SeqCst);
+            self.bytes.fetch_add(layout.size(), Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator =
+    CountingAllocator { allocations: AtomicUsize::new(0), bytes: AtomicUsize::new(0) };
+
+/// Returns the number of allocations, and the number of bytes allocated, to send the given event to the given number of peers.
+fn forward(event: &Event<CurrentNetwork>, num_peers: usize) -> (usize, usize) {
+    let (allocations, bytes) = ALLOCATOR.snapshot();
+    for _ in 0..num_peers {
+        event.write_le(io::sink()).unwrap();
+    }
+    let (allocations_after, bytes_after) = ALLOCATOR.snapshot();
+    (allocations_after - allocations, bytes_after - bytes)
+}
+
+/// This is the only test in this binary, so that the allocations of other tests are not counted.
+#[test]
+fn test_forward_transmission_allocations() {
+    const NUM_PEERS: usize = 16;
+
+    let rng = &mut TestRng::default();
+    let transaction = sample_fee_public_transaction(rng);
+    let transmission_id = TransmissionID::Transaction(transaction.id());
+    let serialized = Bytes::from(transaction.to_bytes_le().unwrap());
+
+    // A transmission that was deserialized upon receipt, and is re-serialized for every peer.
+    let object = Event::TransmissionResponse(TransmissionResponse::new(
+        transmission_id,
+        Transmission::Transaction(Data::Object(transaction)),
+    ));
+    // A transmission that retains the buffer it was received as.
+    let buffer = Event::TransmissionResponse(TransmissionResponse::new(
+        transmission_id,
+        Transmission::Transaction(Data::Buffer(serialized)),
+    ));
+    // Ensure both forms are identical on the wire.
+    assert_eq!(object.to_bytes_le().unwrap(), buffer.to_bytes_le().unwrap());
+
+    // Measure the allocations of forwarding each form.
+    let (object_allocations, object_bytes) = forward(&object, NUM_PEERS);
+    let (buffer_allocations, buffer_bytes) = forward(&buffer, NUM_PEERS);
+    println!(
+        "Per forwarded transmission: {} allocations ({} bytes) as an object, {} allocations ({} bytes) as a buffer",
+        object_allocations / NUM_PEERS,
+        object_bytes / NUM_PEERS,
+        buffer_allocations / NUM_PEERS,
+        buffer_bytes / NUM_PEERS,
+    );
+
+    // Ensure forwarding the buffer does not re-serialize the transmission.
+    assert!(buffer_allocations < object_allocations);
+    assert!(
+        buffer_bytes < object_bytes,
+        "Forwarding the buffer allocated {buffer_bytes} bytes, while forwarding the object allocated {object_bytes} bytes"
+    );
+}
@@ -0,0 +1,306 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkvm::{
+    ledger::{block::Block, store::ConsensusStorage, Ledger},
+    prelude::{ensure, FromBytes, Network, Result, ToBytes},
+};
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{Cursor, Read, Write},
+    path::PathBuf,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// The number of blocks between the progress logs of the backfill.
+const BACKFILL_LOG_INTERVAL: u32 = 1000;
+
+/// The payload of an entry of a [`BlockLog`], holding the data indexed for a block.
+pub trait LogPayload: Sized {
+    /// Writes the payload to the given writer.
+    fn write_payload(&self, writer: &mut Vec<u8>) -> Result<()>;
+
+    /// Reads the payload of the entry for the block at the given height.
+    fn read_payload(height: u32, reader: &mut &[u8]) -> Result<Self>;
+}
+
+/// An append-only log of one entry per block, in block height order, which persists an index of the blocks.
+///
+/// Each entry is written as `(height, length, payload)` with a single write, and a partially-written trailing entry
+/// is discarded when the log is reopened, so that a block is either fully indexed or not indexed at all.
+pub struct BlockLog {
+    /// The path and the file of the log, if the index is persistent.
+    file: Option<(PathBuf, File)>,
+    /// The number of entries in the log.
+    num_entries: usize,
+}
+
+impl BlockLog {
+    /// Opens the log of the given index at the given path, or an in-memory log if no path is given,
+    /// and replays its entries in order. The replay stops at the first malformed entry, or at the first entry
+    /// that `replay` refuses, and the rest of the log is discarded.
+    pub fn open<P: LogPayload>(
+        name: &str,
+        path: Option<PathBuf>,
+        mut replay: impl FnMut(u32, P) -> bool,
+    ) -> Result<Self> {
+        let Some(path) = path else {
+            return Ok(Self { file: None, num_entries: 0 });
+        };
+        // Open the log file.
+        let mut file = OpenOptions::new().read(true).append(true).create(true).open(&path)?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        // Replay the log, stopping at the first malformed or refused entry.
+        let mut reader = Cursor::new(bytes.as_slice());
+        let mut num_valid_bytes = 0;
+        let mut num_entries = 0;
+        while (reader.position() as usize) < bytes.len() {
+            match read_entry::<P>(&mut reader) {
+                Ok((height, payload)) if replay(height, payload) => {
+                    num_valid_bytes = reader.position();
+                    num_entries += 1;
+                }
+                _ => break,
+            }
+        }
+        // Discard the trailing bytes of a partially-written entry.
+        if num_valid_bytes < bytes.len() as u64 {
+            tracing::warn!("Discarding a partially-written entry of the {name} at '{}'", path.display());
+            file.set_len(num_valid_bytes)?;
+        }
+        Ok(Self { file: Some((path, file)), num_entries })
+    }
+
+    /// Returns the number of entries in the log.
+    pub fn num_entries(&self) -> usize {
+        self.num_entries
+    }
+
+    /// Appends the entry for the block at the given height, if the log is persistent.
+    pub fn append<P: LogPayload>(&mut self, height: u32, payload: &P) -> Result<()> {
+        if let Some((_, file)) = self.file.as_mut() {
+            let mut bytes = Vec::new();
+            write_entry(&mut bytes, height, payload)?;
+            file.write_all(&bytes)?;
+            file.sync_data()?;
+            self.num_entries += 1;
+        }
+        Ok(())
+    }
+
+    /// Rewrites the log with the given entries, if the log is persistent.
+    /// The entries are written to a temporary file, which then atomically replaces the log.
+    pub fn rewrite<'a, P: LogPayload + 'a>(&mut self, entries: impl IntoIterator<Item = (u32, &'a P)>) -> Result<()> {
+        let Some((path, _)) = self.file.as_ref() else {
+            return Ok(());
+        };
+        let path = path.clone();
+        let mut bytes = Vec::new();
+        let mut num_entries = 0;
+        for (height, payload) in entries {
+            write_entry(&mut bytes, height, payload)?;
+            num_entries += 1;
+        }
+        let temp_path = path.with_extension("tmp");
+        let mut temp_file = File::create(&temp_path)?;
+        temp_file.write_all(&bytes)?;
+        temp_file.sync_all()?;
+        std::fs::rename(&temp_path, &path)?;
+        // Reopen the log, to append the next entries.
+        let file = OpenOptions::new().read(true).append(true).open(&path)?;
+        self.file = Some((path, file));
+        self.num_entries = num_entries;
+        Ok(())
+    }
+
+    /// Indexes the blocks in the given ledger that are not yet indexed, until the index reaches the latest block.
+    /// The backfill is resumable, as it starts from the next block to index, and stops if the node shuts down.
+    /// Note: `insert` is a no-op for a block that was indexed concurrently as it was added to the ledger.
+    pub fn backfill<N: Network, C: ConsensusStorage<N>>(
+        name: &str,
+        ledger: &Ledger<N, C>,
+        shutdown: &AtomicBool,
+        next_height: impl Fn() -> u32,
+        mut insert: impl FnMut(&Block<N>) -> Result<bool>,
+    ) -> Result<()> {
+        let start_height = next_height();
+        if start_height <= ledger.latest_height() {
+            tracing::info!("Backfilling the {name} from block {start_height}...");
+        }
+        loop {
+            // Retrieve the next block to index, stopping once the index has caught up with the ledger.
+            let height = next_height();
+            let latest_height = ledger.latest_height();
+            if height > latest_height {
+                break;
+            }
+            // Stop if the node is shutting down.
+            if shutdown.load(Ordering::Relaxed) {
+                tracing::info!("Stopped backfilling the {name} at block {height} (resumes on restart)");
+                return Ok(());
+            }
+            // Index the block.
+            insert(&ledger.get_block(height)?)?;
+            // Log the progress.
+            if height % BACKFILL_LOG_INTERVAL == 0 && height > start_height {
+                tracing::info!("Backfilled the {name} up to block {height} of {latest_height}");
+            }
+        }
+        if next_height() > start_height {
+            tracing::info!("Finished backfilling the {name} up to block {}", next_height() - 1);
+        }
+        Ok(())
+    }
+}
+
+/// Writes an entry of the log, as `(height, length, payload)`.
+fn write_entry<P: LogPayload>(writer: &mut Vec<u8>, height: u32, payload: &P) -> Result<()> {
+    let mut bytes = Vec::new();
+    payload.write_payload(&mut bytes)?;
+    height.write_le(&mut *writer)?;
+    u32::try_from(bytes.len())?.write_le(&mut *writer)?;
+    writer.extend(bytes);
+    Ok(())
+}
+
+/// Reads an entry of the log, and returns the height and the payload of the block.
+fn read_entry<P: LogPayload>(reader: &mut Cursor<&[u8]>) -> Result<(u32, P)> {
+    let height = u32::read_le(&mut *reader)?;
+    let length = u32::read_le(&mut *reader)? as usize;
+    // Ensure the payload was fully written.
+    let start = reader.position() as usize;
+    ensure!(start + length <= reader.get_ref().len(), "The entry for block {height} is incomplete");
+    let mut bytes = &reader.get_ref()[start..start + length];
+    reader.set_position((start + length) as u64);
+
+    let payload = P::read_payload(height, &mut bytes)?;
+    ensure!(bytes.is_empty(), "The entry for block {height} has trailing bytes");
+    Ok((height, payload))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A sample payload, holding a list of numbers.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    struct Numbers(Vec<u64>);
+
+    impl LogPayload for Numbers {
+        fn write_payload(&self, writer: &mut Vec<u8>) -> Result<()> {
+            (self.0.len() as u32).write_le(&mut *writer)?;
+            self.0.iter().try_for_each(|number| number.write_le(&mut *writer))?;
+            Ok(())
+        }
+
+        fn read_payload(_height: u32, reader: &mut &[u8]) -> Result<Self> {
+            let num_numbers = u32::read_le(&mut *reader)?;
+            (0..num_numbers).map(|_| Ok(u64::read_le(&mut *reader)?)).collect::<Result<_>>().map(Self)
+        }
+    }
+
+    /// Returns a path for a block log.
+    fn sample_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("snarkos-block-log-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    /// Opens the block log at the given path, and returns it with its replayed entries.
+    fn open(path: &PathBuf) -> (BlockLog, Vec<(u32, Numbers)>) {
+        let mut entries = Vec::new();
+        let log = BlockLog::open("test log", Some(path.clone()), |height, numbers| {
+            entries.push((height, numbers));
+            true
+        })
+        .unwrap();
+        (log, entries)
+    }
+
+    #[test]
+    fn test_append_and_replay() {
+        let path = sample_path("replay");
+        let blocks = [(0, Numbers(vec![1, 2])), (1, Numbers(vec![])), (2, Numbers(vec![3]))];
+
+        // Append the entries, and ensure they are replayed in order.
+        let (mut log, entries) = open(&path);
+        assert!(entries.is_empty());
+        for (height, numbers) in &blocks {
+            log.append(*height, numbers).unwrap();
+        }
+        assert_eq!(log.num_entries(), 3);
+        drop(log);
+        let (log, entries) = open(&path);
+        assert_eq!(log.num_entries(), 3);
+        assert_eq!(entries, blocks);
+        drop(log);
+
+        // Ensure the replay stops at the first refused entry, and discards the rest of the log.
+        let log = BlockLog::open::<Numbers>("test log", Some(path.clone()), |height, _| height < 2).unwrap();
+        assert_eq!(log.num_entries(), 2);
+        drop(log);
+        let (_, entries) = open(&path);
+        assert_eq!(entries, blocks[..2]);
+
+        // Ensure an in-memory log holds no entries.
+        let mut log = BlockLog::open::<Numbers>("test log", None, |_, _| true).unwrap();
+        log.append(0, &Numbers(vec![1])).unwrap();
+        assert_eq!(log.num_entries(), 0);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_partially_written_entry() {
+        let path = sample_path("partial");
+        let (mut log, _) = open(&path);
+        log.append(0, &Numbers(vec![1, 2])).unwrap();
+        drop(log);
+
+        // Simulate a partially-written entry, and ensure it is discarded when reopening the log.
+        let length = std::fs::metadata(&path).unwrap().len();
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&[1, 0, 0, 0, 100, 0, 0, 0, 1]).unwrap();
+        drop(file);
+        let (log, entries) = open(&path);
+        assert_eq!(entries, vec![(0, Numbers(vec![1, 2]))]);
+        assert_eq!(log.num_entries(), 1);
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), length);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_rewrite() {
+        let path = sample_path("rewrite");
+        let (mut log, _) = open(&path);
+        for height in 0..5 {
+            log.append(height, &Numbers(vec![height as u64])).unwrap();
+        }
+
+        // Rewrite the log with the last two entries, and ensure the next entries are appended to it.
+        let retained = [(3, Numbers(vec![3])), (4, Numbers(vec![4]))];
+        log.rewrite(retained.iter().map(|(height, numbers)| (*height, numbers))).unwrap();
+        assert_eq!(log.num_entries(), 2);
+        log.append(5, &Numbers(vec![5])).unwrap();
+        drop(log);
+        let (log, entries) = open(&path);
+        assert_eq!(log.num_entries(), 3);
+        assert_eq!(entries, vec![(3, Numbers(vec![3])), (4, Numbers(vec![4])), (5, Numbers(vec![5]))]);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
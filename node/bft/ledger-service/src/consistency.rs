@@ -0,0 +1,554 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkvm::{
+    console::program::BLOCKS_DEPTH,
+    ledger::{
+        block::Block,
+        store::{helpers::memory::ConsensusMemory, ConsensusStorage},
+        Ledger,
+    },
+    prelude::{Network, Result, ToBits},
+};
+
+use aleo_std::StorageMode;
+use std::{
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// The number of blocks between the progress reports of the consistency check.
+pub const CONSISTENCY_PROGRESS_INTERVAL: u32 = 1000;
+/// The default number of latest blocks over which the state root is recomputed.
+pub const DEFAULT_STATE_ROOT_DEPTH: u32 = 100;
+
+/// A read-only view of the blocks of a ledger, as inspected by the consistency check.
+pub trait BlockView<N: Network> {
+    /// Returns the latest block height.
+    fn latest_height(&self) -> u32;
+    /// Returns the latest state root.
+    fn latest_state_root(&self) -> N::StateRoot;
+    /// Returns the block hash for the given block height.
+    fn get_hash(&self, height: u32) -> Result<N::BlockHash>;
+    /// Returns the block height for the given block hash.
+    fn get_height(&self, hash: &N::BlockHash) -> Result<u32>;
+    /// Returns the block for the given block height.
+    fn get_block(&self, height: u32) -> Result<Block<N>>;
+    /// Returns the hash of the block that contains the given transaction ID, if it is indexed.
+    fn find_block_hash(&self, transaction_id: &N::TransactionID) -> Result<Option<N::BlockHash>>;
+}
+
+impl<N: Network, C: ConsensusStorage<N>> BlockView<N> for Ledger<N, C> {
+    fn latest_height(&self) -> u32 {
+        Ledger::latest_height(self)
+    }
+
+    fn latest_state_root(&self) -> N::StateRoot {
+        Ledger::latest_state_root(self)
+    }
+
+    fn get_hash(&self, height: u32) -> Result<N::BlockHash> {
+        Ledger::get_hash(self, height)
+    }
+
+    fn get_height(&self, hash: &N::BlockHash) -> Result<u32> {
+        Ledger::get_height(self, hash)
+    }
+
+    fn get_block(&self, height: u32) -> Result<Block<N>> {
+        Ledger::get_block(self, height)
+    }
+
+    fn find_block_hash(&self, transaction_id: &N::TransactionID) -> Result<Option<N::BlockHash>> {
+        Ledger::find_block_hash(self, transaction_id)
+    }
+}
+
+/// The kind of an inconsistency in the ledger.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum InconsistencyKind {
+    /// The block height has no block hash.
+    MissingBlockHash,
+    /// The block hash maps back to a different block height.
+    HeightMismatch,
+    /// The block for the block height is missing or unreadable.
+    MissingBlock,
+    /// The stored block has a different height or hash than its index.
+    BlockMismatch,
+    /// The previous block hash of the block does not match the hash of the previous block.
+    PreviousHashMismatch,
+    /// A transaction of the block is not indexed to the block.
+    TransactionIndexMismatch,
+    /// The state root does not match its recomputation from the block hashes.
+    StateRootMismatch,
+    /// The block fails to validate on top of its previous blocks.
+    InvalidBlock,
+}
+
+impl InconsistencyKind {
+    /// Returns the name of the kind of inconsistency.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::MissingBlockHash => "missing_block_hash",
+            Self::HeightMismatch => "height_mismatch",
+            Self::MissingBlock => "missing_block",
+            Self::BlockMismatch => "block_mismatch",
+            Self::PreviousHashMismatch => "previous_hash_mismatch",
+            Self::TransactionIndexMismatch => "transaction_index_mismatch",
+            Self::StateRootMismatch => "state_root_mismatch",
+            Self::InvalidBlock => "invalid_block",
+        }
+    }
+}
+
+impl fmt::Display for InconsistencyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// An inconsistency in the ledger, at the given block height and key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Inconsistency {
+    /// The block height of the inconsistency.
+    pub height: u32,
+    /// The kind of inconsistency.
+    pub kind: InconsistencyKind,
+    /// The key of the inconsistent entry, e.g. a block hash or transaction ID.
+    pub key: String,
+    /// A description of the inconsistency.
+    pub message: String,
+}
+
+impl fmt::Display for Inconsistency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Block {} ({}) - {}: {}", self.height, self.key, self.kind, self.message)
+    }
+}
+
+/// The stage of the consistency check.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CheckStage {
+    /// Checking the block heights, hashes, and transaction indexes.
+    Blocks,
+    /// Recomputing the state root.
+    StateRoot,
+    /// Re-validating the block contents (deep mode only).
+    Contents,
+}
+
+impl fmt::Display for CheckStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Blocks => write!(f, "blocks"),
+            Self::StateRoot => write!(f, "state root"),
+            Self::Contents => write!(f, "block contents"),
+        }
+    }
+}
+
+/// The progress of the consistency check, as reported while it runs.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CheckProgress {
+    /// The current stage.
+    pub stage: CheckStage,
+    /// The block height that was last checked.
+    pub height: u32,
+    /// The latest block height of the ledger.
+    pub latest_height: u32,
+}
+
+/// The report of a consistency check.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConsistencyReport {
+    /// The latest block height of the ledger, when the check started.
+    pub latest_height: u32,
+    /// Whether the block contents were re-validated.
+    pub is_deep: bool,
+    /// Whether the check was cancelled before it completed.
+    pub is_cancelled: bool,
+    /// The inconsistencies that were found, in the order they were found.
+    pub inconsistencies: Vec<Inconsistency>,
+}
+
+impl ConsistencyReport {
+    /// Returns `true` if the check completed without finding any inconsistencies.
+    pub fn is_consistent(&self) -> bool {
+        !self.is_cancelled && self.inconsistencies.is_empty()
+    }
+
+    /// Records an inconsistency.
+    fn push(&mut self, height: u32, kind: InconsistencyKind, key: impl ToString, message: impl ToString) {
+        self.inconsistencies.push(Inconsistency { height, kind, key: key.to_string(), message: message.to_string() });
+    }
+}
+
+/// A consistency check over the blocks of a ledger.
+///
+/// The check verifies that the block heights are contiguous, that each block links to its previous block,
+/// that the transactions of each block are indexed to it, and that the state roots of the latest blocks
+/// match a recomputation from the block hashes. In deep mode, it also re-validates every block on top of
+/// its previous blocks, in a temporary in-memory ledger.
+#[derive(Copy, Clone, Debug)]
+pub struct ConsistencyCheck {
+    /// The number of latest blocks over which the state root is recomputed.
+    state_root_depth: u32,
+    /// Whether the block contents are re-validated.
+    is_deep: bool,
+}
+
+impl Default for ConsistencyCheck {
+    fn default() -> Self {
+        Self { state_root_depth: DEFAULT_STATE_ROOT_DEPTH, is_deep: false }
+    }
+}
+
+impl ConsistencyCheck {
+    /// Sets the number of latest blocks over which the state root is recomputed.
+    pub fn with_state_root_depth(mut self, state_root_depth: u32) -> Self {
+        self.state_root_depth = state_root_depth;
+        self
+    }
+
+    /// Sets whether the block contents are re-validated, which can take a long time.
+    pub fn with_deep(mut self, is_deep: bool) -> Self {
+        self.is_deep = is_deep;
+        self
+    }
+
+    /// Runs the consistency check over the given ledger, reporting the progress as it runs.
+    /// If `cancel` is set, the check stops early, and returns the partial report.
+    pub fn run<N: Network>(
+        &self,
+        ledger: &impl BlockView<N>,
+        cancel: &AtomicBool,
+        mut on_progress: impl FnMut(CheckProgress),
+    ) -> Result<ConsistencyReport> {
+        let latest_height = ledger.latest_height();
+        let mut report = ConsistencyReport { latest_height, is_deep: self.is_deep, ..Default::default() };
+        // Reports the progress of the given stage, at every interval and at the end of the stage.
+        let mut report_progress = |stage, height| {
+            if height % CONSISTENCY_PROGRESS_INTERVAL == 0 || height == latest_height {
+                on_progress(CheckProgress { stage, height, latest_height });
+            }
+        };
+
+        // Check the blocks, collecting their hashes.
+        let mut hashes = Vec::with_capacity(latest_height as usize + 1);
+        for height in 0..=latest_height {
+            if cancel.load(Ordering::Relaxed) {
+                report.is_cancelled = true;
+                return Ok(report);
+            }
+            hashes.push(self.check_block(ledger, height, hashes.last().copied().flatten(), &mut report));
+            report_progress(CheckStage::Blocks, height);
+        }
+
+        // Recompute the state root, if every block hash is present.
+        if let Some(hashes) = hashes.into_iter().collect::<Option<Vec<_>>>() {
+            let start_height = latest_height.saturating_sub(self.state_root_depth);
+            // Construct the block tree up to the start height.
+            let leaves = hashes[..=start_height as usize].iter().map(|hash| hash.to_bits_le()).collect::<Vec<_>>();
+            let mut tree = N::merkle_tree_bhp::<BLOCKS_DEPTH>(&leaves)?;
+            // Ensure each of the latest blocks commits to the state root of its previous blocks.
+            for height in start_height + 1..=latest_height {
+                if cancel.load(Ordering::Relaxed) {
+                    report.is_cancelled = true;
+                    return Ok(report);
+                }
+                let expected = N::StateRoot::from(*tree.root());
+                if let Ok(block) = ledger.get_block(height) {
+                    if block.header().previous_state_root() != expected {
+                        let message = format!("expected a previous state root of '{expected}'");
+                        report.push(height, InconsistencyKind::StateRootMismatch, block.hash(), message);
+                    }
+                }
+                tree.append(&[hashes[height as usize].to_bits_le()])?;
+                report_progress(CheckStage::StateRoot, height);
+            }
+            // Ensure the latest state root matches the recomputed state root.
+            let (latest_state_root, expected) = (ledger.latest_state_root(), N::StateRoot::from(*tree.root()));
+            if latest_state_root != expected {
+                let message = format!("the latest state root is '{latest_state_root}', expected '{expected}'");
+                report.push(latest_height, InconsistencyKind::StateRootMismatch, "latest", message);
+            }
+        }
+
+        // Re-validate the block contents, if the check is deep.
+        if self.is_deep {
+            self.check_contents(ledger, cancel, &mut report, |height| report_progress(CheckStage::Contents, height))?;
+        }
+        Ok(report)
+    }
+
+    /// Checks the block at the given height against its indexes, returning its hash if it is present.
+    fn check_block<N: Network>(
+        &self,
+        ledger: &impl BlockView<N>,
+        height: u32,
+        previous_hash: Option<N::BlockHash>,
+        report: &mut ConsistencyReport,
+    ) -> Option<N::BlockHash> {
+        // Ensure the height has a block hash.
+        let hash = match ledger.get_hash(height) {
+            Ok(hash) => hash,
+            Err(error) => {
+                report.push(height, InconsistencyKind::MissingBlockHash, height, error);
+                return None;
+            }
+        };
+        // Ensure the block hash maps back to the height.
+        match ledger.get_height(&hash) {
+            Ok(indexed_height) if indexed_height == height => (),
+            Ok(indexed_height) => {
+                let message = format!("the block hash is indexed at block {indexed_height}");
+                report.push(height, InconsistencyKind::HeightMismatch, hash, message);
+            }
+            Err(error) => report.push(height, InconsistencyKind::HeightMismatch, hash, error),
+        }
+        // Ensure the block is present, and matches its index.
+        let block = match ledger.get_block(height) {
+            Ok(block) => block,
+            Err(error) => {
+                report.push(height, InconsistencyKind::MissingBlock, hash, error);
+                return Some(hash);
+            }
+        };
+        if block.height() != height || block.hash() != hash {
+            let message = format!("the stored block is block {} ({})", block.height(), block.hash());
+            report.push(height, InconsistencyKind::BlockMismatch, hash, message);
+        }
+        // Ensure the block links to the previous block.
+        if let Some(previous_hash) = previous_hash {
+            if block.previous_hash() != previous_hash {
+                let message =
+                    format!("the previous block hash is '{}', expected '{previous_hash}'", block.previous_hash());
+                report.push(height, InconsistencyKind::PreviousHashMismatch, hash, message);
+            }
+        }
+        // Ensure the transactions of the block are indexed to the block.
+        for transaction_id in block.transactions().transaction_ids() {
+            match ledger.find_block_hash(transaction_id) {
+                Ok(Some(indexed_hash)) if indexed_hash == hash => (),
+                Ok(Some(indexed_hash)) => {
+                    let message = format!("the transaction is indexed to block '{indexed_hash}'");
+                    report.push(height, InconsistencyKind::TransactionIndexMismatch, transaction_id, message);
+                }
+                Ok(None) => {
+                    report.push(height, InconsistencyKind::TransactionIndexMismatch, transaction_id, "not indexed")
+                }
+                Err(error) => report.push(height, InconsistencyKind::TransactionIndexMismatch, transaction_id, error),
+            }
+        }
+        Some(hash)
+    }
+
+    /// Re-validates each block on top of its previous blocks, in a temporary in-memory ledger.
+    /// Note: The first invalid block stops the check, as none of the later blocks can be validated.
+    fn check_contents<N: Network>(
+        &self,
+        ledger: &impl BlockView<N>,
+        cancel: &AtomicBool,
+        report: &mut ConsistencyReport,
+        mut on_progress: impl FnMut(u32),
+    ) -> Result<()> {
+        // Initialize the temporary ledger with the genesis block.
+        let temporary = Ledger::<N, ConsensusMemory<N>>::load(ledger.get_block(0)?, StorageMode::Production)?;
+        for height in 1..=report.latest_height {
+            if cancel.load(Ordering::Relaxed) {
+                report.is_cancelled = true;
+                return Ok(());
+            }
+            // Note: A missing block has already been reported.
+            let Ok(block) = ledger.get_block(height) else {
+                return Ok(());
+            };
+            if let Err(error) = temporary.check_next_block(&block) {
+                report.push(height, InconsistencyKind::InvalidBlock, block.hash(), error);
+                return Ok(());
+            }
+            temporary.advance_to_next_block(&block)?;
+            on_progress(height);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers;
+    use snarkvm::prelude::{bail, MainnetV0, TestRng};
+
+    use std::collections::HashMap;
+
+    type CurrentNetwork = MainnetV0;
+    type CurrentLedger = Ledger<CurrentNetwork, ConsensusMemory<CurrentNetwork>>;
+
+    /// A view of a ledger, with overridden entries to simulate a corrupted database.
+    struct CorruptedView<'a> {
+        ledger: &'a CurrentLedger,
+        hashes: HashMap<u32, Option<<CurrentNetwork as Network>::BlockHash>>,
+        blocks: HashMap<u32, Block<CurrentNetwork>>,
+        transactions:
+            HashMap<<CurrentNetwork as Network>::TransactionID, Option<<CurrentNetwork as Network>::BlockHash>>,
+    }
+
+    impl<'a> CorruptedView<'a> {
+        fn new(ledger: &'a CurrentLedger) -> Self {
+            Self { ledger, hashes: Default::default(), blocks: Default::default(), transactions: Default::default() }
+        }
+    }
+
+    impl BlockView<CurrentNetwork> for CorruptedView<'_> {
+        fn latest_height(&self) -> u32 {
+            self.ledger.latest_height()
+        }
+
+        fn latest_state_root(&self) -> <CurrentNetwork as Network>::StateRoot {
+            self.ledger.latest_state_root()
+        }
+
+        fn get_hash(&self, height: u32) -> Result<<CurrentNetwork as Network>::BlockHash> {
+            match self.hashes.get(&height) {
+                Some(Some(hash)) => Ok(*hash),
+                Some(None) => bail!("Missing block hash for block {height}"),
+                None => self.ledger.get_hash(height),
+            }
+        }
+
+        fn get_height(&self, hash: &<CurrentNetwork as Network>::BlockHash) -> Result<u32> {
+            self.ledger.get_height(hash)
+        }
+
+        fn get_block(&self, height: u32) -> Result<Block<CurrentNetwork>> {
+            match self.blocks.get(&height) {
+                Some(block) => Ok(block.clone()),
+                None => self.ledger.get_block(height),
+            }
+        }
+
+        fn find_block_hash(
+            &self,
+            transaction_id: &<CurrentNetwork as Network>::TransactionID,
+        ) -> Result<Option<<CurrentNetwork as Network>::BlockHash>> {
+            match self.transactions.get(transaction_id) {
+                Some(hash) => Ok(*hash),
+                None => self.ledger.find_block_hash(transaction_id),
+            }
+        }
+    }
+
+    /// Returns a ledger with a genesis block and the given number of additional blocks.
+    fn sample_ledger(num_blocks: u32, rng: &mut TestRng) -> CurrentLedger {
+        let (ledger, private_key) = test_helpers::sample_ledger(rng);
+        for _ in 0..num_blocks {
+            let block = ledger.prepare_advance_to_next_beacon_block(&private_key, vec![], vec![], vec![], rng).unwrap();
+            ledger.advance_to_next_block(&block).unwrap();
+        }
+        ledger
+    }
+
+    /// Runs the consistency check over the given view, and returns the kinds of inconsistencies it found.
+    fn check_kinds(check: ConsistencyCheck, view: &impl BlockView<CurrentNetwork>) -> Vec<(u32, InconsistencyKind)> {
+        let report = check.run(view, &AtomicBool::new(false), |_| ()).unwrap();
+        assert!(!report.is_cancelled);
+        report.inconsistencies.iter().map(|inconsistency| (inconsistency.height, inconsistency.kind)).collect()
+    }
+
+    #[test]
+    fn test_consistent_ledger() {
+        let rng = &mut TestRng::default();
+        let ledger = sample_ledger(3, rng);
+
+        // Check the ledger, reporting the progress of each stage.
+        let mut progress = Vec::new();
+        let check = ConsistencyCheck::default().with_deep(true);
+        let report = check.run(&ledger, &AtomicBool::new(false), |update| progress.push(update)).unwrap();
+        assert!(report.is_consistent());
+        assert!(report.is_deep);
+        assert_eq!(report.latest_height, 3);
+        assert_eq!(progress.last().unwrap().stage, CheckStage::Contents);
+        assert_eq!(progress.last().unwrap().height, 3);
+
+        // Check the ledger, with a state root depth beyond the latest height.
+        let check = ConsistencyCheck::default().with_state_root_depth(10);
+        assert!(check_kinds(check, &ledger).is_empty());
+    }
+
+    #[test]
+    fn test_missing_block_hash() {
+        let rng = &mut TestRng::default();
+        let ledger = sample_ledger(3, rng);
+
+        // Remove the block hash of block 2.
+        let mut view = CorruptedView::new(&ledger);
+        view.hashes.insert(2, None);
+        assert_eq!(check_kinds(ConsistencyCheck::default(), &view), vec![(2, InconsistencyKind::MissingBlockHash)]);
+    }
+
+    #[test]
+    fn test_mismatched_block() {
+        let rng = &mut TestRng::default();
+        let ledger = sample_ledger(3, rng);
+
+        // Store block 1 in place of block 2.
+        let mut view = CorruptedView::new(&ledger);
+        view.blocks.insert(2, ledger.get_block(1).unwrap());
+        let kinds = check_kinds(ConsistencyCheck::default(), &view);
+        assert!(kinds.contains(&(2, InconsistencyKind::BlockMismatch)));
+        assert!(kinds.contains(&(2, InconsistencyKind::PreviousHashMismatch)));
+    }
+
+    #[test]
+    fn test_mismatched_state_root() {
+        let rng = &mut TestRng::default();
+        let ledger = sample_ledger(3, rng);
+
+        // Point the block hash of block 2 to a different block, so the recomputed state root diverges.
+        let mut view = CorruptedView::new(&ledger);
+        view.hashes.insert(2, Some(ledger.get_hash(1).unwrap()));
+        let kinds = check_kinds(ConsistencyCheck::default(), &view);
+        assert!(kinds.contains(&(2, InconsistencyKind::HeightMismatch)));
+        assert!(kinds.contains(&(3, InconsistencyKind::StateRootMismatch)));
+    }
+
+    #[test]
+    fn test_unindexed_transaction() {
+        let rng = &mut TestRng::default();
+        let ledger = sample_ledger(1, rng);
+
+        // Remove the index of a genesis transaction.
+        let genesis = ledger.get_block(0).unwrap();
+        let transaction_id = *genesis.transactions().transaction_ids().next().unwrap();
+        let mut view = CorruptedView::new(&ledger);
+        view.transactions.insert(transaction_id, None);
+        let report = ConsistencyCheck::default().run(&view, &AtomicBool::new(false), |_| ()).unwrap();
+        assert_eq!(report.inconsistencies.len(), 1);
+        assert_eq!(report.inconsistencies[0].kind, InconsistencyKind::TransactionIndexMismatch);
+        assert_eq!(report.inconsistencies[0].key, transaction_id.to_string());
+    }
+
+    #[test]
+    fn test_cancelled_check() {
+        let rng = &mut TestRng::default();
+        let ledger = sample_ledger(2, rng);
+
+        // Cancel the check before it starts.
+        let report = ConsistencyCheck::default().run(&ledger, &AtomicBool::new(true), |_| ()).unwrap();
+        assert!(report.is_cancelled);
+        assert!(!report.is_consistent());
+        assert!(report.inconsistencies.is_empty());
+    }
+}
@@ -0,0 +1,302 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkvm::prelude::{bail, Result};
+
+use parking_lot::RwLock;
+use std::{
+    fmt,
+    io,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+/// The number of bytes in a gibibyte.
+const GIB: u64 = 1024 * 1024 * 1024;
+
+/// The number of seconds between the measurements of the disk space.
+pub const DISK_CHECK_INTERVAL_IN_SECS: u64 = 30; // seconds
+/// The free disk space, below which the node stops accepting new transactions and solutions.
+pub const DISK_LOW_SPACE_THRESHOLD_IN_BYTES: u64 = 10 * GIB; // bytes
+/// The free disk space, below which the node stops advancing blocks and shuts down.
+pub const DISK_CRITICAL_SPACE_THRESHOLD_IN_BYTES: u64 = GIB; // bytes
+
+/// A reporter of the disk space.
+pub trait SpaceReporter: Send + Sync {
+    /// Returns the free space on the volume backing the given path, in bytes.
+    fn available_space(&self, path: &Path) -> io::Result<u64>;
+
+    /// Returns the total size of the files in the given directory, in bytes.
+    fn directory_size(&self, path: &Path) -> io::Result<u64>;
+}
+
+/// A reporter of the disk space of the filesystem.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct FilesystemSpaceReporter;
+
+impl SpaceReporter for FilesystemSpaceReporter {
+    /// Returns the free space on the volume backing the given path, in bytes.
+    fn available_space(&self, path: &Path) -> io::Result<u64> {
+        fs2::available_space(path)
+    }
+
+    /// Returns the total size of the files in the given directory, in bytes.
+    fn directory_size(&self, path: &Path) -> io::Result<u64> {
+        let mut size = 0;
+        let mut directories = vec![path.to_path_buf()];
+        while let Some(directory) = directories.pop() {
+            for entry in std::fs::read_dir(directory)? {
+                let entry = entry?;
+                let metadata = entry.metadata()?;
+                match metadata.is_dir() {
+                    true => directories.push(entry.path()),
+                    false => size += metadata.len(),
+                }
+            }
+        }
+        Ok(size)
+    }
+}
+
+/// The configuration of the disk monitor.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DiskConfig {
+    /// The time between the measurements of the disk space.
+    pub check_interval: Duration,
+    /// The free disk space, below which the node stops accepting new transactions and solutions.
+    pub low_space_threshold: u64,
+    /// The free disk space, below which the node stops advancing blocks and shuts down.
+    pub critical_space_threshold: u64,
+}
+
+impl Default for DiskConfig {
+    /// Returns the default disk configuration.
+    fn default() -> Self {
+        Self {
+            check_interval: Duration::from_secs(DISK_CHECK_INTERVAL_IN_SECS),
+            low_space_threshold: DISK_LOW_SPACE_THRESHOLD_IN_BYTES,
+            critical_space_threshold: DISK_CRITICAL_SPACE_THRESHOLD_IN_BYTES,
+        }
+    }
+}
+
+/// The status of the disk space, in order of degradation.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DiskStatus {
+    /// The free disk space is sufficient.
+    #[default]
+    Healthy,
+    /// The free disk space is low, and the node stops accepting new transactions and solutions.
+    Low,
+    /// The free disk space is critically low, and the node stops advancing blocks and shuts down.
+    Critical,
+}
+
+impl fmt::Display for DiskStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Healthy => write!(f, "healthy"),
+            Self::Low => write!(f, "low"),
+            Self::Critical => write!(f, "critical"),
+        }
+    }
+}
+
+/// A measurement of the disk space.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct DiskUsage {
+    /// The free space on the volume backing the ledger, in bytes.
+    pub available_bytes: u64,
+    /// The size of the ledger, in bytes.
+    pub ledger_bytes: u64,
+    /// The status of the disk space.
+    pub status: DiskStatus,
+}
+
+/// A monitor of the disk space of the ledger, which caches its measurements for the configured interval.
+pub struct DiskMonitor {
+    /// The path of the ledger.
+    path: PathBuf,
+    /// The configuration of the monitor.
+    config: DiskConfig,
+    /// The reporter of the disk space.
+    reporter: Box<dyn SpaceReporter>,
+    /// The latest measurement, and the time at which it was taken.
+    usage: RwLock<Option<(Instant, DiskUsage)>>,
+}
+
+impl DiskMonitor {
+    /// Initializes a new disk monitor for the ledger at the given path.
+    pub fn new(path: PathBuf, config: DiskConfig) -> Self {
+        Self { path, config, reporter: Box::new(FilesystemSpaceReporter), usage: Default::default() }
+    }
+
+    /// Returns the disk monitor, measuring the disk space with the given reporter.
+    pub fn with_reporter(mut self, reporter: impl SpaceReporter + 'static) -> Self {
+        self.reporter = Box::new(reporter);
+        self
+    }
+
+    /// Returns the configuration of the monitor.
+    pub const fn config(&self) -> &DiskConfig {
+        &self.config
+    }
+
+    /// Returns the latest measurement, measuring the disk space if the cached measurement has expired.
+    pub fn usage(&self) -> DiskUsage {
+        if let Some((timestamp, usage)) = *self.usage.read() {
+            if timestamp.elapsed() < self.config.check_interval {
+                return usage;
+            }
+        }
+        self.refresh()
+    }
+
+    /// Returns the status of the disk space, measuring the disk space if the cached measurement has expired.
+    pub fn status(&self) -> DiskStatus {
+        self.usage().status
+    }
+
+    /// Measures the disk space, and caches the measurement.
+    /// Note: If the free space cannot be measured, the previous status is retained.
+    pub fn refresh(&self) -> DiskUsage {
+        let previous = self.usage.read().map(|(_, usage)| usage).unwrap_or_default();
+        let usage = match self.reporter.available_space(&self.path) {
+            Ok(available_bytes) => DiskUsage {
+                available_bytes,
+                ledger_bytes: self.reporter.directory_size(&self.path).unwrap_or(previous.ledger_bytes),
+                status: self.status_for(available_bytes),
+            },
+            Err(error) => {
+                tracing::warn!("Failed to measure the disk space of '{}' - {error}", self.path.display());
+                previous
+            }
+        };
+        *self.usage.write() = Some((Instant::now(), usage));
+        usage
+    }
+
+    /// Ensures the node may accept new transactions and solutions.
+    pub fn ensure_intake(&self) -> Result<()> {
+        let usage = self.usage();
+        if usage.status >= DiskStatus::Low {
+            bail!("The node is low on disk space ({} bytes available)", usage.available_bytes);
+        }
+        Ok(())
+    }
+
+    /// Ensures the node may advance to the next block.
+    pub fn ensure_advance(&self) -> Result<()> {
+        let usage = self.usage();
+        if usage.status >= DiskStatus::Critical {
+            bail!("The node is critically low on disk space ({} bytes available)", usage.available_bytes);
+        }
+        Ok(())
+    }
+
+    /// Returns the status of the disk space, given the free space in bytes.
+    fn status_for(&self, available_bytes: u64) -> DiskStatus {
+        match available_bytes {
+            bytes if bytes < self.config.critical_space_threshold => DiskStatus::Critical,
+            bytes if bytes < self.config.low_space_threshold => DiskStatus::Low,
+            _ => DiskStatus::Healthy,
+        }
+    }
+}
+
+impl fmt::Debug for DiskMonitor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DiskMonitor").field("path", &self.path).field("config", &self.config).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    };
+
+    /// A reporter with an adjustable amount of free space.
+    #[derive(Clone, Default)]
+    struct FakeSpaceReporter {
+        available_bytes: Arc<AtomicU64>,
+    }
+
+    impl SpaceReporter for FakeSpaceReporter {
+        fn available_space(&self, _path: &Path) -> io::Result<u64> {
+            Ok(self.available_bytes.load(Ordering::Relaxed))
+        }
+
+        fn directory_size(&self, _path: &Path) -> io::Result<u64> {
+            Ok(GIB)
+        }
+    }
+
+    fn sample_monitor(check_interval: Duration) -> (DiskMonitor, FakeSpaceReporter) {
+        let reporter = FakeSpaceReporter::default();
+        let config = DiskConfig { check_interval, low_space_threshold: 100, critical_space_threshold: 10 };
+        let monitor = DiskMonitor::new(PathBuf::from("ledger"), config).with_reporter(reporter.clone());
+        (monitor, reporter)
+    }
+
+    #[test]
+    fn test_disk_degradation() {
+        let (monitor, reporter) = sample_monitor(Duration::ZERO);
+
+        // Ensure the node accepts transmissions and advances blocks while the free space is sufficient.
+        reporter.available_bytes.store(1000, Ordering::Relaxed);
+        assert_eq!(monitor.usage(), DiskUsage {
+            available_bytes: 1000,
+            ledger_bytes: GIB,
+            status: DiskStatus::Healthy
+        });
+        assert!(monitor.ensure_intake().is_ok());
+        assert!(monitor.ensure_advance().is_ok());
+
+        // Ensure the node stops accepting transmissions, but still advances blocks, once the free space is low.
+        reporter.available_bytes.store(99, Ordering::Relaxed);
+        assert_eq!(monitor.status(), DiskStatus::Low);
+        assert!(monitor.ensure_intake().is_err());
+        assert!(monitor.ensure_advance().is_ok());
+
+        // Ensure the node stops advancing blocks once the free space is critically low.
+        reporter.available_bytes.store(9, Ordering::Relaxed);
+        assert_eq!(monitor.status(), DiskStatus::Critical);
+        assert!(monitor.ensure_intake().is_err());
+        assert!(monitor.ensure_advance().is_err());
+
+        // Ensure the node recovers once the free space is sufficient again.
+        reporter.available_bytes.store(100, Ordering::Relaxed);
+        assert_eq!(monitor.status(), DiskStatus::Healthy);
+        assert!(monitor.ensure_intake().is_ok());
+    }
+
+    #[test]
+    fn test_disk_measurement_is_cached() {
+        let (monitor, reporter) = sample_monitor(Duration::from_secs(3600));
+
+        // Ensure the measurement is cached for the check interval.
+        reporter.available_bytes.store(1000, Ordering::Relaxed);
+        assert_eq!(monitor.status(), DiskStatus::Healthy);
+        reporter.available_bytes.store(0, Ordering::Relaxed);
+        assert_eq!(monitor.status(), DiskStatus::Healthy);
+
+        // Ensure a refresh measures the disk space again.
+        assert_eq!(monitor.refresh().status, DiskStatus::Critical);
+        assert_eq!(monitor.status(), DiskStatus::Critical);
+    }
+}
@@ -12,9 +12,26 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{fmt_id, spawn_blocking, LedgerService};
+use crate::{
+    blocks_range,
+    committee_lookback_round,
+    fmt_id,
+    spawn_blocking,
+    transactions_range,
+    verification_pool,
+    BlocksIter,
+    DiskMonitor,
+    LedgerService,
+    RecordScanner,
+    ReplayFilter,
+    RewardIndex,
+    StateChangeIndex,
+    TransactionsIter,
+    VerificationCategory,
+};
 use snarkvm::{
     ledger::{
+        authority::Authority,
         block::{Block, Transaction},
         committee::Committee,
         narwhal::{BatchCertificate, Data, Subdag, Transmission, TransmissionID},
@@ -36,6 +53,7 @@ use std::{
         Arc,
     },
 };
+use tokio::sync::Notify;
 
 /// The capacity of the LRU holding the recently queried committees.
 const COMMITTEE_CACHE_SIZE: usize = 16;
@@ -46,6 +64,12 @@ pub struct CoreLedgerService<N: Network, C: ConsensusStorage<N>> {
     ledger: Ledger<N, C>,
     committee_cache: Arc<Mutex<LruCache<u64, Committee<N>>>>,
     latest_leader: Arc<RwLock<Option<(u64, Address<N>)>>>,
+    record_scanner: Option<Arc<RecordScanner<N>>>,
+    reward_index: Option<Arc<RewardIndex<N>>>,
+    state_changes: Option<Arc<StateChangeIndex<N>>>,
+    replay_filter: Option<Arc<ReplayFilter<N, C>>>,
+    disk_monitor: Option<Arc<DiskMonitor>>,
+    block_notify: Option<Arc<Notify>>,
     shutdown: Arc<AtomicBool>,
 }
 
@@ -53,7 +77,66 @@ impl<N: Network, C: ConsensusStorage<N>> CoreLedgerService<N, C> {
     /// Initializes a new core ledger service.
     pub fn new(ledger: Ledger<N, C>, shutdown: Arc<AtomicBool>) -> Self {
         let committee_cache = Arc::new(Mutex::new(LruCache::new(COMMITTEE_CACHE_SIZE.try_into().unwrap())));
-        Self { ledger, committee_cache, latest_leader: Default::default(), shutdown }
+        Self {
+            ledger,
+            committee_cache,
+            latest_leader: Default::default(),
+            record_scanner: None,
+            reward_index: None,
+            state_changes: None,
+            replay_filter: None,
+            disk_monitor: None,
+            block_notify: None,
+            shutdown,
+        }
+    }
+
+    /// Returns the core ledger service, updating the given reward index as blocks are advanced.
+    pub fn with_reward_index(mut self, reward_index: Arc<RewardIndex<N>>) -> Self {
+        self.reward_index = Some(reward_index);
+        self
+    }
+
+    /// Returns the core ledger service, updating the given state change index as blocks are advanced.
+    pub fn with_state_changes(mut self, state_changes: Arc<StateChangeIndex<N>>) -> Self {
+        self.state_changes = Some(state_changes);
+        self
+    }
+
+    /// Returns the core ledger service, updating the given replay filter as blocks are advanced.
+    pub fn with_replay_filter(mut self, replay_filter: Arc<ReplayFilter<N, C>>) -> Self {
+        self.replay_filter = Some(replay_filter);
+        self
+    }
+
+    /// Returns the core ledger service, scanning the blocks for the registered view keys as they are advanced.
+    pub fn with_record_scanner(mut self, record_scanner: Arc<RecordScanner<N>>) -> Self {
+        self.record_scanner = Some(record_scanner);
+        self
+    }
+
+    /// Returns the core ledger service, refusing to advance blocks once the disk space is critically low.
+    pub fn with_disk_monitor(mut self, disk_monitor: Arc<DiskMonitor>) -> Self {
+        self.disk_monitor = Some(disk_monitor);
+        self
+    }
+
+    /// Returns the core ledger service, notifying the given waiter whenever a block is advanced.
+    pub fn with_block_notify(mut self, block_notify: Arc<Notify>) -> Self {
+        self.block_notify = Some(block_notify);
+        self
+    }
+
+    /// Returns an iterator over the blocks in the given block range, loading one block at a time.
+    /// The range is inclusive of the start and exclusive of the end.
+    pub fn blocks_range(&self, heights: Range<u32>) -> BlocksIter<N, C> {
+        blocks_range(self.ledger.clone(), heights)
+    }
+
+    /// Returns an iterator over the transactions in the given block range, loading one block at a time.
+    /// The range is inclusive of the start and exclusive of the end.
+    pub fn transactions_range(&self, heights: Range<u32>) -> TransactionsIter<N, C> {
+        transactions_range(self.ledger.clone(), heights)
     }
 }
 
@@ -81,6 +164,16 @@ impl<N: Network, C: ConsensusStorage<N>> LedgerService<N> for CoreLedgerService<
         self.ledger.latest_block()
     }
 
+    /// Returns the UNIX timestamp (in seconds) of the latest block in the ledger.
+    fn latest_block_timestamp(&self) -> Option<i64> {
+        Some(self.ledger.latest_header().timestamp())
+    }
+
+    /// Returns the latest epoch hash, which the solutions for the current epoch must target.
+    fn latest_epoch_hash(&self) -> Result<N::BlockHash> {
+        self.ledger.latest_epoch_hash()
+    }
+
     /// Returns the latest cached leader and its associated round.
     fn latest_leader(&self) -> Option<(u64, Address<N>)> {
         *self.latest_leader.read()
@@ -178,18 +271,8 @@ impl<N: Network, C: ConsensusStorage<N>> LedgerService<N> for CoreLedgerService<
     /// Returns the committee lookback for the given round.
     /// If the committee lookback round is in the future, then the current committee is returned.
     fn get_committee_lookback_for_round(&self, round: u64) -> Result<Committee<N>> {
-        // Get the round number for the previous committee. Note, we subtract 2 from odd rounds,
-        // because committees are updated in even rounds.
-        let previous_round = match round % 2 == 0 {
-            true => round.saturating_sub(1),
-            false => round.saturating_sub(2),
-        };
-
-        // Get the committee lookback round.
-        let committee_lookback_round = previous_round.saturating_sub(Committee::<N>::COMMITTEE_LOOKBACK_RANGE);
-
         // Retrieve the committee for the committee lookback round.
-        self.get_committee_for_round(committee_lookback_round)
+        self.get_committee_for_round(committee_lookback_round::<N>(round))
     }
 
     /// Returns `true` if the ledger contains the given certificate ID in block history.
@@ -210,8 +293,11 @@ impl<N: Network, C: ConsensusStorage<N>> LedgerService<N> for CoreLedgerService<
     fn ensure_transmission_is_well_formed(
         &self,
         transmission_id: TransmissionID<N>,
-        transmission: &mut Transmission<N>,
+        transmission: &Transmission<N>,
     ) -> Result<()> {
+        // Note: The transmission is only deserialized for verification. It is deliberately not replaced with
+        // the deserialized object, so that a buffer received from a peer is forwarded as is, instead of being
+        // re-serialized for every peer that requests it.
         match (transmission_id, transmission) {
             (TransmissionID::Ratification, Transmission::Ratification) => {}
             (TransmissionID::Transaction(expected_transaction_id), Transmission::Transaction(transaction_data)) => {
@@ -230,9 +316,6 @@ impl<N: Network, C: ConsensusStorage<N>> LedgerService<N> for CoreLedgerService<
                         if transaction.is_fee() {
                             bail!("Received a fee transaction in a transmission");
                         }
-
-                        // Update the transmission with the deserialized transaction.
-                        *transaction_data = Data::Object(transaction);
                     }
                     Err(err) => {
                         bail!("Failed to deserialize transaction: {err}");
@@ -249,9 +332,6 @@ impl<N: Network, C: ConsensusStorage<N>> LedgerService<N> for CoreLedgerService<
                                 fmt_id(solution.id()),
                             );
                         }
-
-                        // Update the transmission with the deserialized solution.
-                        *solution_data = Data::Object(solution);
                     }
                     Err(err) => {
                         bail!("Failed to deserialize solution: {err}");
@@ -282,7 +362,8 @@ impl<N: Network, C: ConsensusStorage<N>> LedgerService<N> for CoreLedgerService<
 
         // Ensure that the solution is valid for the given epoch.
         let puzzle = self.ledger.puzzle().clone();
-        match spawn_blocking!(puzzle.check_solution(&solution, epoch_hash, proof_target)) {
+        let verification = move || puzzle.check_solution(&solution, epoch_hash, proof_target);
+        match verification_pool().run(VerificationCategory::Solution, verification).await? {
             Ok(()) => Ok(()),
             Err(e) => bail!("Invalid solution '{}' for the current epoch - {e}", fmt_id(solution_id)),
         }
@@ -306,7 +387,8 @@ impl<N: Network, C: ConsensusStorage<N>> LedgerService<N> for CoreLedgerService<
         }
         // Check the transaction is well-formed.
         let ledger = self.ledger.clone();
-        spawn_blocking!(ledger.check_transaction_basic(&transaction, None, &mut rand::thread_rng()))
+        let verification = move || ledger.check_transaction_basic(&transaction, None, &mut rand::thread_rng());
+        verification_pool().run(VerificationCategory::Transaction, verification).await?
     }
 
     /// Checks the given block is valid next block.
@@ -314,6 +396,31 @@ impl<N: Network, C: ConsensusStorage<N>> LedgerService<N> for CoreLedgerService<
         self.ledger.check_next_block(block, &mut rand::thread_rng())
     }
 
+    /// Checks the portions of the given block that do not depend on the latest state of the ledger,
+    /// namely its transactions root, its beacon signature (if any), and its transactions.
+    fn check_block_stateless(&self, block: &Block<N>) -> Result<()> {
+        // Ensure the transactions root matches the block header.
+        if block.transactions().to_transactions_root()? != block.header().transactions_root() {
+            bail!("Block {} has an invalid transactions root", block.height());
+        }
+        // Ensure the signature is valid, if the block is a beacon block.
+        if let Authority::Beacon(signature) = block.authority() {
+            if !signature.verify(&signature.to_address(), &[*block.hash()]) {
+                bail!("Block {} has an invalid beacon signature", block.height());
+            }
+        }
+        // Ensure the transactions are well-formed.
+        let rng = &mut rand::thread_rng();
+        for confirmed in block.transactions().iter() {
+            let rejected_id = confirmed.to_rejected_id()?;
+            let transaction = confirmed.to_unconfirmed_transaction()?;
+            if let Err(error) = self.ledger.check_transaction_basic(&transaction, rejected_id, rng) {
+                bail!("Invalid transaction {} in block {} - {error}", fmt_id(transaction.id()), block.height());
+            }
+        }
+        Ok(())
+    }
+
     /// Returns a candidate for the next block in the ledger, using a committed subdag and its transmissions.
     #[cfg(feature = "ledger-write")]
     fn prepare_advance_to_next_quorum_block(
@@ -331,9 +438,41 @@ impl<N: Network, C: ConsensusStorage<N>> LedgerService<N> for CoreLedgerService<
         if self.shutdown.load(Ordering::Relaxed) {
             bail!("Skipping advancing to block {} - The node is shutting down", block.height());
         }
+        // If the disk space is critically low, then skip advancing to the next block, rather than corrupting the ledger.
+        if let Some(disk_monitor) = &self.disk_monitor {
+            if let Err(error) = disk_monitor.ensure_advance() {
+                bail!("Skipping advancing to block {} - {error}", block.height());
+            }
+        }
         // Advance to the next block.
         self.ledger.advance_to_next_block(block)?;
         tracing::info!("\n\nAdvanced to block {} at round {} - {}\n", block.height(), block.round(), block.hash());
+        // Update the reward index, if it is enabled.
+        if let Some(reward_index) = &self.reward_index {
+            if let Err(error) = reward_index.insert_block(self.ledger.puzzle(), block) {
+                tracing::error!("Failed to index the puzzle rewards of block {} - {error}", block.height());
+            }
+        }
+        // Update the state change index, if it is enabled.
+        if let Some(state_changes) = &self.state_changes {
+            if let Err(error) = state_changes.insert_block(&self.ledger, block) {
+                tracing::error!("Failed to index the state changes of block {} - {error}", block.height());
+            }
+        }
+        // Insert the confirmed transmissions into the replay filter, if it is enabled.
+        if let Some(replay_filter) = &self.replay_filter {
+            replay_filter.insert_block(block);
+        }
+        // Scan the block for the registered view keys, if the record scanner is enabled.
+        if let Some(record_scanner) = &self.record_scanner {
+            if let Err(error) = record_scanner.scan_block(block) {
+                tracing::error!("Failed to scan the records of block {} - {error}", block.height());
+            }
+        }
+        // Notify the waiter of the new block, if one is registered.
+        if let Some(block_notify) = &self.block_notify {
+            block_notify.notify_one();
+        }
         Ok(())
     }
 }
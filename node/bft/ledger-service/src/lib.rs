@@ -17,11 +17,36 @@
 #[macro_use]
 extern crate async_trait;
 
+#[cfg(feature = "ledger")]
+pub mod block_log;
+#[cfg(feature = "ledger")]
+pub use block_log::*;
+
+#[cfg(feature = "ledger")]
+pub mod consistency;
+#[cfg(feature = "ledger")]
+pub use consistency::*;
+
+#[cfg(feature = "ledger")]
+pub mod disk;
+#[cfg(feature = "ledger")]
+pub use disk::*;
+
 #[cfg(feature = "ledger")]
 pub mod ledger;
 #[cfg(feature = "ledger")]
 pub use ledger::*;
 
+#[cfg(feature = "ledger")]
+pub mod metadata;
+#[cfg(feature = "ledger")]
+pub use metadata::*;
+
+#[cfg(feature = "ledger")]
+pub mod migrations;
+#[cfg(feature = "ledger")]
+pub use migrations::*;
+
 #[cfg(feature = "mock")]
 pub mod mock;
 #[cfg(feature = "mock")]
@@ -32,14 +57,69 @@ pub mod prover;
 #[cfg(feature = "prover")]
 pub use prover::*;
 
+#[cfg(feature = "ledger")]
+pub mod record_index;
+#[cfg(feature = "ledger")]
+pub use record_index::*;
+
+#[cfg(feature = "ledger")]
+pub mod record_scanner;
+#[cfg(feature = "ledger")]
+pub use record_scanner::*;
+
+#[cfg(feature = "ledger")]
+pub mod replay_filter;
+#[cfg(feature = "ledger")]
+pub use replay_filter::*;
+
+#[cfg(feature = "ledger")]
+pub mod reward_index;
+#[cfg(feature = "ledger")]
+pub use reward_index::*;
+
+#[cfg(feature = "ledger")]
+pub mod state_changes;
+#[cfg(feature = "ledger")]
+pub use state_changes::*;
+
+#[cfg(feature = "ledger")]
+pub mod stream;
+#[cfg(feature = "ledger")]
+pub use stream::*;
+
+#[cfg(all(feature = "ledger", any(test, feature = "test")))]
+pub mod test_helpers;
+
 #[cfg(feature = "translucent")]
 pub mod translucent;
 #[cfg(feature = "translucent")]
 pub use translucent::*;
 
+#[cfg(feature = "ledger")]
+pub mod verification_pool;
+#[cfg(feature = "ledger")]
+pub use verification_pool::*;
+
 pub mod traits;
 pub use traits::*;
 
+use snarkvm::prelude::{committee::Committee, Network};
+
+/// The number of blocks below the latest block, beyond which a block is considered final.
+pub const ALEO_MAXIMUM_FORK_DEPTH: u32 = 64;
+
+/// Returns the round whose committee governs the given round, i.e. the committee lookback round.
+/// Note: Committees are updated in even rounds, so 2 is subtracted from odd rounds.
+pub fn committee_lookback_round<N: Network>(round: u64) -> u64 {
+    // Get the round number for the previous committee.
+    let previous_round = match round % 2 == 0 {
+        true => round.saturating_sub(1),
+        false => round.saturating_sub(2),
+    };
+    // Get the committee lookback round.
+    previous_round.saturating_sub(Committee::<N>::COMMITTEE_LOOKBACK_RANGE)
+}
+
 /// Formats an ID into a truncated identifier (for logging purposes).
 pub fn fmt_id(id: impl ToString) -> String {
     let id = id.to_string();
@@ -0,0 +1,365 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{Migration, MIGRATIONS};
+use snarkvm::prelude::{anyhow, bail, FromBytes, Network, Result, ToBytes};
+
+use std::{fs, io::Write, path::Path};
+
+/// The version of the storage schema of the ledger, which is incremented on breaking changes to its layout.
+pub const LEDGER_SCHEMA_VERSION: u16 = 2;
+/// The name of the metadata file in the ledger directory.
+pub const LEDGER_METADATA_FILE: &str = "ledger_metadata";
+
+/// The metadata of a ledger database, which is written on its creation, and validated on every open,
+/// so that a node never opens a ledger that was created for a different network.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LedgerMetadata<N: Network> {
+    /// The version of the storage schema.
+    pub schema_version: u16,
+    /// The ID of the network.
+    pub network_id: u16,
+    /// The index of the development node, if the ledger belongs to a development network.
+    pub dev: Option<u16>,
+    /// The hash of the genesis block.
+    pub genesis_hash: N::BlockHash,
+}
+
+impl<N: Network> LedgerMetadata<N> {
+    /// Initializes the metadata of a ledger for the running node, with the given genesis block hash and development index.
+    pub fn new(genesis_hash: N::BlockHash, dev: Option<u16>) -> Self {
+        Self { schema_version: LEDGER_SCHEMA_VERSION, network_id: N::ID, dev, genesis_hash }
+    }
+
+    /// Ensures the ledger in the given directory was created for the same network, genesis block, and development index
+    /// as the running node, and with a supported schema version. If the ledger has no metadata yet, it is written.
+    /// If the ledger uses an older schema version, it is migrated to the current schema version.
+    pub fn ensure_compatible(&self, directory: &Path) -> Result<()> {
+        self.ensure_compatible_with(directory, MIGRATIONS)
+    }
+
+    /// Ensures the ledger in the given directory is compatible, migrating it with the given migrations if needed.
+    fn ensure_compatible_with(&self, directory: &Path, migrations: &[Migration]) -> Result<()> {
+        let path = directory.join(LEDGER_METADATA_FILE);
+        // If the metadata does not exist, this is a new ledger, or one that predates the metadata.
+        if !path.exists() {
+            fs::create_dir_all(directory)?;
+            // A new ledger is written at the current schema version.
+            if fs::read_dir(directory)?.next().is_none() {
+                return self.write(&path);
+            }
+            // A ledger that predates the metadata uses the first schema version, and is migrated below.
+            Self { schema_version: 1, ..self.clone() }.write(&path)?;
+        }
+        // Read the metadata, checking the fields in order, as a ledger of a newer schema may not be readable.
+        let bytes = fs::read(&path)?;
+        let mut reader = bytes.as_slice();
+        let schema_version = u16::read_le(&mut reader)?;
+        if schema_version > self.schema_version {
+            bail!(
+                "The ledger at '{}' uses storage schema version {schema_version}, but this node only supports up to version {} - upgrade snarkOS to open it",
+                directory.display(),
+                self.schema_version
+            );
+        }
+        let network_id = u16::read_le(&mut reader)?;
+        if network_id != self.network_id {
+            bail!(
+                "The ledger at '{}' was created for network ID {network_id}, but this node is running network ID {} ({})",
+                directory.display(),
+                self.network_id,
+                N::NAME
+            );
+        }
+        let dev = match u8::read_le(&mut reader)? {
+            0 => None,
+            1 => Some(u16::read_le(&mut reader)?),
+            _ => bail!("The ledger metadata at '{}' is malformed", path.display()),
+        };
+        if dev != self.dev {
+            bail!(
+                "The ledger at '{}' was created for {}, but this node is {}",
+                directory.display(),
+                fmt_dev(dev),
+                fmt_dev(self.dev)
+            );
+        }
+        let genesis_hash = N::BlockHash::read_le(&mut reader)?;
+        if genesis_hash != self.genesis_hash {
+            bail!(
+                "The ledger at '{}' was created with genesis block '{genesis_hash}', but this node is running genesis block '{}'",
+                directory.display(),
+                self.genesis_hash
+            );
+        }
+        // Migrate the ledger, if it uses an older schema version.
+        if schema_version < self.schema_version {
+            self.migrate(directory, schema_version, migrations)?;
+        }
+        Ok(())
+    }
+
+    /// Migrates the ledger in the given directory from the given schema version to the schema version of this metadata.
+    /// The schema version is recorded after each migration, so that an interrupted upgrade resumes from the next one.
+    fn migrate(&self, directory: &Path, mut schema_version: u16, migrations: &[Migration]) -> Result<()> {
+        let path = directory.join(LEDGER_METADATA_FILE);
+        // Back up the metadata prior to the migration, so that a failed migration can be diagnosed.
+        let backup_path = directory.join(format!("{LEDGER_METADATA_FILE}.v{schema_version}.bak"));
+        fs::copy(&path, &backup_path)?;
+
+        while schema_version < self.schema_version {
+            let Some(migration) = migrations.iter().find(|migration| migration.from_version == schema_version) else {
+                bail!(
+                    "The ledger at '{}' uses storage schema version {schema_version}, which cannot be migrated - run `snarkos clean` to reset it",
+                    directory.display()
+                );
+            };
+            tracing::info!("Migrating the ledger from schema version {schema_version} - {}", migration.description);
+            (migration.apply)(directory).map_err(|error| {
+                anyhow!(
+                    "Failed to migrate the ledger at '{}' from schema version {schema_version} ({error}) - the prior metadata is backed up at '{}'",
+                    directory.display(),
+                    backup_path.display()
+                )
+            })?;
+            // Record the progress of the migration.
+            schema_version += 1;
+            Self { schema_version, ..self.clone() }.write(&path)?;
+        }
+        tracing::info!("Migrated the ledger to schema version {schema_version}");
+        Ok(())
+    }
+
+    /// Writes the metadata to the given path.
+    /// Note: The file is written to a temporary path and then renamed, so that it is replaced atomically.
+    fn write(&self, path: &Path) -> Result<()> {
+        let mut bytes = Vec::new();
+        self.schema_version.write_le(&mut bytes)?;
+        self.network_id.write_le(&mut bytes)?;
+        match self.dev {
+            None => 0u8.write_le(&mut bytes)?,
+            Some(dev) => {
+                1u8.write_le(&mut bytes)?;
+                dev.write_le(&mut bytes)?;
+            }
+        }
+        self.genesis_hash.write_le(&mut bytes)?;
+        let temporary_path = path.with_extension("tmp");
+        let mut file = fs::File::create(&temporary_path)?;
+        file.write_all(&bytes)?;
+        file.sync_data()?;
+        fs::rename(temporary_path, path)?;
+        Ok(())
+    }
+}
+
+/// Returns a description of the given development index.
+fn fmt_dev(dev: Option<u16>) -> String {
+    match dev {
+        Some(dev) => format!("development node {dev}"),
+        None => "a non-development node".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::INDEXES_DIRECTORY;
+    use snarkvm::prelude::{MainnetV0, Rng, TestRng};
+
+    use std::path::PathBuf;
+
+    type CurrentNetwork = MainnetV0;
+
+    /// Returns a fresh directory for a ledger.
+    fn sample_directory(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("snarkos-ledger-metadata-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        directory
+    }
+
+    /// Overwrites the metadata in the given directory with the given fields.
+    fn write_raw(directory: &Path, schema_version: u16, network_id: u16) {
+        let path = directory.join(LEDGER_METADATA_FILE);
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[..2].copy_from_slice(&schema_version.to_le_bytes());
+        bytes[2..4].copy_from_slice(&network_id.to_le_bytes());
+        fs::write(path, bytes).unwrap();
+    }
+
+    /// Returns the schema version recorded in the metadata in the given directory.
+    fn read_schema_version(directory: &Path) -> u16 {
+        let bytes = fs::read(directory.join(LEDGER_METADATA_FILE)).unwrap();
+        u16::from_le_bytes([bytes[0], bytes[1]])
+    }
+
+    /// A sample migration, which appends its version to a log in the ledger directory.
+    fn append_to_log(directory: &Path) -> Result<()> {
+        let mut log = fs::read(directory.join("log")).unwrap_or_default();
+        log.push(read_schema_version(directory) as u8);
+        Ok(fs::write(directory.join("log"), log)?)
+    }
+
+    /// A sample migration, which fails while a marker file exists in the ledger directory.
+    fn fail_if_marked(directory: &Path) -> Result<()> {
+        if directory.join("fail").exists() {
+            bail!("the marker exists");
+        }
+        append_to_log(directory)
+    }
+
+    #[test]
+    fn test_metadata_is_written_and_validated() {
+        let rng = &mut TestRng::default();
+        let directory = sample_directory("roundtrip");
+        let metadata = LedgerMetadata::<CurrentNetwork>::new(rng.gen(), None);
+
+        // Check that the metadata is written on creation, and accepted on every open.
+        metadata.ensure_compatible(&directory).unwrap();
+        assert!(directory.join(LEDGER_METADATA_FILE).exists());
+        metadata.ensure_compatible(&directory).unwrap();
+
+        // Check that a different genesis block is refused, naming both hashes.
+        let other = LedgerMetadata::<CurrentNetwork>::new(rng.gen(), None);
+        let error = other.ensure_compatible(&directory).unwrap_err().to_string();
+        assert!(error.contains(&metadata.genesis_hash.to_string()), "{error}");
+        assert!(error.contains(&other.genesis_hash.to_string()), "{error}");
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_metadata_refuses_other_network() {
+        let rng = &mut TestRng::default();
+        let directory = sample_directory("network");
+        let metadata = LedgerMetadata::<CurrentNetwork>::new(rng.gen(), None);
+        metadata.ensure_compatible(&directory).unwrap();
+
+        // Simulate a ledger that was created for another network.
+        let other_id = CurrentNetwork::ID + 1;
+        write_raw(&directory, LEDGER_SCHEMA_VERSION, other_id);
+        let error = metadata.ensure_compatible(&directory).unwrap_err().to_string();
+        assert!(error.contains(&format!("created for network ID {other_id}")), "{error}");
+        assert!(error.contains(&format!("running network ID {}", CurrentNetwork::ID)), "{error}");
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_metadata_refuses_other_dev_index() {
+        let rng = &mut TestRng::default();
+        let directory = sample_directory("dev");
+        let genesis_hash = rng.gen();
+        LedgerMetadata::<CurrentNetwork>::new(genesis_hash, Some(0)).ensure_compatible(&directory).unwrap();
+
+        // Check that another development node, or a non-development node, cannot share the directory.
+        let error = LedgerMetadata::<CurrentNetwork>::new(genesis_hash, Some(1))
+            .ensure_compatible(&directory)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("created for development node 0, but this node is development node 1"), "{error}");
+        let error = LedgerMetadata::<CurrentNetwork>::new(genesis_hash, None)
+            .ensure_compatible(&directory)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("but this node is a non-development node"), "{error}");
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_metadata_refuses_newer_schema() {
+        let rng = &mut TestRng::default();
+        let directory = sample_directory("schema");
+        let metadata = LedgerMetadata::<CurrentNetwork>::new(rng.gen(), None);
+        metadata.ensure_compatible(&directory).unwrap();
+
+        // Simulate a ledger that was created by a newer version of snarkOS.
+        write_raw(&directory, LEDGER_SCHEMA_VERSION + 1, CurrentNetwork::ID);
+        let error = metadata.ensure_compatible(&directory).unwrap_err().to_string();
+        assert!(error.contains(&format!("schema version {}", LEDGER_SCHEMA_VERSION + 1)), "{error}");
+        assert!(error.contains("upgrade snarkOS"), "{error}");
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_metadata_migrates_old_schema() {
+        let rng = &mut TestRng::default();
+        let directory = sample_directory("migrate");
+        let genesis_hash = rng.gen();
+
+        // Create a fixture ledger at the first schema version, with an index in the ledger directory.
+        let old_metadata =
+            LedgerMetadata::<CurrentNetwork> { schema_version: 1, ..LedgerMetadata::new(genesis_hash, None) };
+        old_metadata.ensure_compatible(&directory).unwrap();
+        fs::write(directory.join("record_index"), b"records").unwrap();
+
+        // Check the ledger is migrated on open.
+        LedgerMetadata::<CurrentNetwork>::new(genesis_hash, None).ensure_compatible(&directory).unwrap();
+        assert_eq!(read_schema_version(&directory), LEDGER_SCHEMA_VERSION);
+        assert!(!directory.join("record_index").exists());
+        assert_eq!(fs::read(directory.join(INDEXES_DIRECTORY).join("record_index")).unwrap(), b"records");
+        // Check the metadata prior to the migration is backed up.
+        let backup = fs::read(directory.join(format!("{LEDGER_METADATA_FILE}.v1.bak"))).unwrap();
+        assert_eq!(u16::from_le_bytes([backup[0], backup[1]]), 1);
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_metadata_migrates_ledger_predating_metadata() {
+        let rng = &mut TestRng::default();
+        let directory = sample_directory("legacy");
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("state_changes"), b"changes").unwrap();
+
+        // Check a ledger without metadata is treated as the first schema version, and migrated.
+        LedgerMetadata::<CurrentNetwork>::new(rng.gen(), None).ensure_compatible(&directory).unwrap();
+        assert_eq!(read_schema_version(&directory), LEDGER_SCHEMA_VERSION);
+        assert_eq!(fs::read(directory.join(INDEXES_DIRECTORY).join("state_changes")).unwrap(), b"changes");
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_metadata_migration_resumes_after_failure() {
+        let rng = &mut TestRng::default();
+        let directory = sample_directory("resume");
+        let genesis_hash = rng.gen();
+        let migrations = [Migration { from_version: 1, description: "first", apply: append_to_log }, Migration {
+            from_version: 2,
+            description: "second",
+            apply: fail_if_marked,
+        }];
+
+        // Create a fixture ledger at the first schema version, whose second migration fails.
+        let metadata = LedgerMetadata::<CurrentNetwork>::new(genesis_hash, None);
+        LedgerMetadata { schema_version: 1, ..metadata.clone() }.ensure_compatible(&directory).unwrap();
+        fs::write(directory.join("fail"), []).unwrap();
+
+        // Check the failure names the backup, and the progress of the first migration is recorded.
+        let metadata = LedgerMetadata { schema_version: 3, ..metadata };
+        let error = metadata.ensure_compatible_with(&directory, &migrations).unwrap_err().to_string();
+        assert!(error.contains("from schema version 2 (the marker exists)"), "{error}");
+        assert!(error.contains(&format!("{LEDGER_METADATA_FILE}.v1.bak")), "{error}");
+        assert_eq!(read_schema_version(&directory), 2);
+
+        // Check the migration resumes from the failed step, without re-running the first migration.
+        fs::remove_file(directory.join("fail")).unwrap();
+        metadata.ensure_compatible_with(&directory, &migrations).unwrap();
+        assert_eq!(read_schema_version(&directory), 3);
+        assert_eq!(fs::read(directory.join("log")).unwrap(), vec![1, 2]);
+
+        // Check a missing migration is refused.
+        let metadata = LedgerMetadata { schema_version: 4, ..metadata };
+        let error = metadata.ensure_compatible_with(&directory, &migrations).unwrap_err().to_string();
+        assert!(error.contains("cannot be migrated"), "{error}");
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,89 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkvm::prelude::Result;
+
+use std::{fs, path::Path};
+
+/// The name of the subdirectory of the ledger directory that holds the indexes maintained by snarkOS.
+pub const INDEXES_DIRECTORY: &str = "indexes";
+/// The names of the indexes maintained by snarkOS in the ledger directory.
+pub const INDEX_FILES: [&str; 4] = ["record_index", "reward_index", "state_changes", "record_scanner"];
+
+/// A forward migration of a ledger directory from one storage schema version to the next.
+#[derive(Copy, Clone)]
+pub struct Migration {
+    /// The schema version that the migration upgrades from.
+    pub from_version: u16,
+    /// A description of the migration.
+    pub description: &'static str,
+    /// Applies the migration to the given ledger directory.
+    /// Note: A migration must be idempotent, as it is re-run in full if the node stops before its completion is recorded.
+    pub apply: fn(&Path) -> Result<()>,
+}
+
+/// The migrations of the storage schema, in order of the version they upgrade from.
+pub const MIGRATIONS: &[Migration] = &[Migration {
+    from_version: 1,
+    description: "Move the indexes of snarkOS into their own subdirectory",
+    apply: move_indexes_to_subdirectory,
+}];
+
+/// Moves the indexes of snarkOS from the ledger directory into the indexes subdirectory,
+/// so that they are kept apart from the files of the ledger database.
+fn move_indexes_to_subdirectory(directory: &Path) -> Result<()> {
+    let indexes_directory = directory.join(INDEXES_DIRECTORY);
+    fs::create_dir_all(&indexes_directory)?;
+    for name in INDEX_FILES {
+        // Skip the indexes that are disabled, or that were already moved.
+        let path = directory.join(name);
+        if path.exists() {
+            fs::rename(path, indexes_directory.join(name))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrations_are_ordered() {
+        // Check the migrations form a contiguous chain, ending at the current schema version.
+        for (i, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.from_version, 1 + i as u16, "{}", migration.description);
+        }
+        assert_eq!(MIGRATIONS.len() as u16 + 1, crate::LEDGER_SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_move_indexes_to_subdirectory_is_idempotent() {
+        let directory = std::env::temp_dir().join(format!("snarkos-migration-indexes-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("record_index"), b"records").unwrap();
+
+        // Check the existing index is moved, and the missing indexes are skipped.
+        move_indexes_to_subdirectory(&directory).unwrap();
+        assert!(!directory.join("record_index").exists());
+        assert_eq!(fs::read(directory.join(INDEXES_DIRECTORY).join("record_index")).unwrap(), b"records");
+        assert!(!directory.join(INDEXES_DIRECTORY).join("reward_index").exists());
+
+        // Check the migration can be re-run, as if the node stopped before its completion was recorded.
+        move_indexes_to_subdirectory(&directory).unwrap();
+        assert_eq!(fs::read(directory.join(INDEXES_DIRECTORY).join("record_index")).unwrap(), b"records");
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -33,12 +33,13 @@ use tracing::*;
 pub struct MockLedgerService<N: Network> {
     committee: Committee<N>,
     height_to_round_and_hash: Mutex<BTreeMap<u32, (u64, N::BlockHash)>>,
+    latest_block_timestamp: Mutex<Option<i64>>,
 }
 
 impl<N: Network> MockLedgerService<N> {
     /// Initializes a new mock ledger service.
     pub fn new(committee: Committee<N>) -> Self {
-        Self { committee, height_to_round_and_hash: Default::default() }
+        Self { committee, height_to_round_and_hash: Default::default(), latest_block_timestamp: Default::default() }
     }
 
     /// Initializes a new mock ledger service at the specified height.
@@ -47,7 +48,25 @@ impl<N: Network> MockLedgerService<N> {
         for i in 0..=height {
             height_to_hash.insert(i, (i as u64 * 2, Field::<N>::from_u32(i).into()));
         }
-        Self { committee, height_to_round_and_hash: Mutex::new(height_to_hash) }
+        Self {
+            committee,
+            height_to_round_and_hash: Mutex::new(height_to_hash),
+            latest_block_timestamp: Default::default(),
+        }
+    }
+
+    /// Advances the mock ledger service to the specified height, without storing any blocks.
+    pub fn advance_to_height(&self, height: u32) {
+        let mut height_to_round_and_hash = self.height_to_round_and_hash.lock();
+        let start = height_to_round_and_hash.last_key_value().map_or(0, |(latest_height, _)| latest_height + 1);
+        for i in start..=height {
+            height_to_round_and_hash.insert(i, (i as u64 * 2, Field::<N>::from_u32(i).into()));
+        }
+    }
+
+    /// Sets the UNIX timestamp (in seconds) of the latest block, which is unknown by default.
+    pub fn set_latest_block_timestamp(&self, timestamp: i64) {
+        *self.latest_block_timestamp.lock() = Some(timestamp);
     }
 }
 
@@ -68,6 +87,16 @@ impl<N: Network> LedgerService<N> for MockLedgerService<N> {
         unreachable!("MockLedgerService does not support latest_block")
     }
 
+    /// Returns the UNIX timestamp (in seconds) of the latest block, if it was set.
+    fn latest_block_timestamp(&self) -> Option<i64> {
+        *self.latest_block_timestamp.lock()
+    }
+
+    /// Returns the latest epoch hash, which the solutions for the current epoch must target.
+    fn latest_epoch_hash(&self) -> Result<N::BlockHash> {
+        bail!("MockLedgerService does not support latest_epoch_hash")
+    }
+
     /// Returns the latest cached leader and its associated round.
     fn latest_leader(&self) -> Option<(u64, Address<N>)> {
         None
@@ -173,7 +202,7 @@ impl<N: Network> LedgerService<N> for MockLedgerService<N> {
     fn ensure_transmission_is_well_formed(
         &self,
         transmission_id: TransmissionID<N>,
-        _transmission: &mut Transmission<N>,
+        _transmission: &Transmission<N>,
     ) -> Result<()> {
         trace!("[MockLedgerService] Ensure transmission ID matches {:?} - Ok", fmt_id(transmission_id));
         Ok(())
@@ -200,6 +229,11 @@ impl<N: Network> LedgerService<N> for MockLedgerService<N> {
         Ok(())
     }
 
+    /// Checks the portions of the given block that do not depend on the latest state of the ledger.
+    fn check_block_stateless(&self, _block: &Block<N>) -> Result<()> {
+        Ok(())
+    }
+
     /// Returns a candidate for the next block in the ledger, using a committed subdag and its transmissions.
     #[cfg(feature = "ledger-write")]
     fn prepare_advance_to_next_quorum_block(
@@ -56,6 +56,11 @@ impl<N: Network> LedgerService<N> for ProverLedgerService<N> {
         unreachable!("Latest block does not exist in prover")
     }
 
+    /// Returns the latest epoch hash, which the solutions for the current epoch must target.
+    fn latest_epoch_hash(&self) -> Result<N::BlockHash> {
+        bail!("Latest epoch hash does not exist in prover")
+    }
+
     /// Returns the latest cached leader and its associated round.
     fn latest_leader(&self) -> Option<(u64, Address<N>)> {
         unreachable!("Latest leader does not exist in prover");
@@ -143,7 +148,7 @@ impl<N: Network> LedgerService<N> for ProverLedgerService<N> {
     fn ensure_transmission_is_well_formed(
         &self,
         _transmission_id: TransmissionID<N>,
-        _transmission: &mut Transmission<N>,
+        _transmission: &Transmission<N>,
     ) -> Result<()> {
         Ok(())
     }
@@ -167,6 +172,11 @@ impl<N: Network> LedgerService<N> for ProverLedgerService<N> {
         Ok(())
     }
 
+    /// Checks the portions of the given block that do not depend on the latest state of the ledger.
+    fn check_block_stateless(&self, _block: &Block<N>) -> Result<()> {
+        Ok(())
+    }
+
     /// Returns a candidate for the next block in the ledger, using a committed subdag and its transmissions.
     #[cfg(feature = "ledger-write")]
     fn prepare_advance_to_next_quorum_block(
@@ -0,0 +1,157 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkvm::{
+    ledger::{store::ConsensusStorage, Ledger},
+    prelude::{bail, Field, Network, Result},
+};
+
+/// The location of a record commitment, as `(block height, transaction ID, output index)`.
+/// Note: The output index is the position of the commitment among all of the outputs of the transaction.
+pub type CommitmentLocation<N> = (u32, <N as Network>::TransactionID, u16);
+
+/// The location of a serial number, as `(block height, transaction ID)`.
+pub type SerialNumberLocation<N> = (u32, <N as Network>::TransactionID);
+
+/// An index from record commitments and serial numbers to the transactions that created and spent them.
+///
+/// The index is served from the storage of the ledger, which maps each input and output ID to its transition,
+/// each transition to its transaction, and each transaction to its block. As the commitment of a record output
+/// and the serial number of a record input are their IDs, the index needs no storage of its own, and is always
+/// consistent with the ledger.
+pub struct RecordIndex<N: Network, C: ConsensusStorage<N>> {
+    /// The ledger.
+    ledger: Ledger<N, C>,
+}
+
+impl<N: Network, C: ConsensusStorage<N>> RecordIndex<N, C> {
+    /// Initializes the record index over the given ledger.
+    pub fn new(ledger: Ledger<N, C>) -> Self {
+        Self { ledger }
+    }
+
+    /// Returns the location of the given record commitment, if it is in the ledger.
+    pub fn get_commitment(&self, commitment: &Field<N>) -> Result<Option<CommitmentLocation<N>>> {
+        if !self.ledger.contains_commitment(commitment)? {
+            return Ok(None);
+        }
+        let (height, transaction_id) = self.find_transaction(commitment)?;
+        // Determine the position of the commitment among the outputs of the transaction.
+        let transaction = self.ledger.get_transaction(transaction_id)?;
+        let Some(output_index) = transaction.commitments().position(|candidate| candidate == commitment) else {
+            bail!("Transaction '{transaction_id}' is missing the commitment '{commitment}'");
+        };
+        let Ok(output_index) = u16::try_from(output_index) else {
+            bail!("Transaction '{transaction_id}' has too many outputs to index");
+        };
+        Ok(Some((height, transaction_id, output_index)))
+    }
+
+    /// Returns the location of the given serial number, if it is in the ledger.
+    pub fn get_serial_number(&self, serial_number: &Field<N>) -> Result<Option<SerialNumberLocation<N>>> {
+        if !self.ledger.contains_serial_number(serial_number)? {
+            return Ok(None);
+        }
+        self.find_transaction(serial_number).map(Some)
+    }
+
+    /// Returns the block height and the ID of the transaction with the given input or output ID.
+    fn find_transaction(&self, input_or_output_id: &Field<N>) -> Result<(u32, N::TransactionID)> {
+        let transition_id = self.ledger.find_transition_id(input_or_output_id)?;
+        let Some(transaction_id) = self.ledger.find_transaction_id_from_transition_id(&transition_id)? else {
+            bail!("Transition '{transition_id}' is missing its transaction in the ledger");
+        };
+        let Some(block_hash) = self.ledger.find_block_hash(&transaction_id)? else {
+            bail!("Transaction '{transaction_id}' is missing its block in the ledger");
+        };
+        Ok((self.ledger.get_height(&block_hash)?, transaction_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::sample_ledger;
+    use snarkvm::{
+        ledger::{store::helpers::memory::ConsensusMemory, Block},
+        prelude::{MainnetV0, PrivateKey, RecordsFilter, TestRng, Uniform, Value, ViewKey},
+    };
+
+    use std::str::FromStr;
+
+    type CurrentNetwork = MainnetV0;
+    type CurrentLedger = Ledger<CurrentNetwork, ConsensusMemory<CurrentNetwork>>;
+
+    /// Advances the ledger with a block that spends a record of the given account, and returns the block.
+    fn advance_with_spend(
+        ledger: &CurrentLedger,
+        private_key: &PrivateKey<CurrentNetwork>,
+        rng: &mut TestRng,
+    ) -> Block<CurrentNetwork> {
+        // Find an unspent record of the account.
+        let view_key = ViewKey::try_from(private_key).unwrap();
+        let (_, record) = ledger.find_records(&view_key, RecordsFilter::Unspent).unwrap().next().unwrap();
+        // Transfer part of the record to the account itself.
+        let address = view_key.to_address();
+        let inputs =
+            [Value::Record(record), Value::from_str(&address.to_string()).unwrap(), Value::from_str("1u64").unwrap()];
+        let transaction = ledger
+            .vm()
+            .execute(private_key, ("credits.aleo", "transfer_private"), inputs.into_iter(), None, 0, None, rng)
+            .unwrap();
+        // Advance the ledger.
+        let block =
+            ledger.prepare_advance_to_next_beacon_block(private_key, vec![], vec![], vec![transaction], rng).unwrap();
+        ledger.advance_to_next_block(&block).unwrap();
+        block
+    }
+
+    /// Checks that every commitment and serial number of the given block is indexed at its location.
+    fn check_block(
+        index: &RecordIndex<CurrentNetwork, ConsensusMemory<CurrentNetwork>>,
+        block: &Block<CurrentNetwork>,
+    ) {
+        for transaction in block.transactions().iter().map(|confirmed| confirmed.transaction()) {
+            for (output_index, commitment) in transaction.commitments().enumerate() {
+                let location = (block.height(), transaction.id(), output_index as u16);
+                assert_eq!(index.get_commitment(commitment).unwrap(), Some(location));
+            }
+            for serial_number in transaction.serial_numbers() {
+                assert_eq!(index.get_serial_number(serial_number).unwrap(), Some((block.height(), transaction.id())));
+            }
+        }
+    }
+
+    #[test]
+    fn test_record_index() {
+        let rng = &mut TestRng::default();
+        let (ledger, private_key) = sample_ledger(rng);
+        let index = RecordIndex::new(ledger.clone());
+
+        // Ensure the commitments of the genesis block are found.
+        let genesis = ledger.get_block(0).unwrap();
+        assert!(genesis.transactions().iter().any(|confirmed| confirmed.transaction().commitments().next().is_some()));
+        check_block(&index, &genesis);
+
+        // Ensure the commitments and serial numbers of the next block are found, as it is added to the ledger.
+        let block = advance_with_spend(&ledger, &private_key, rng);
+        assert!(block.transactions().iter().any(|confirmed| confirmed.transaction().serial_numbers().next().is_some()));
+        check_block(&index, &genesis);
+        check_block(&index, &block);
+
+        // Ensure nonexistent commitments and serial numbers are not found.
+        assert_eq!(index.get_commitment(&Field::rand(rng)).unwrap(), None);
+        assert_eq!(index.get_serial_number(&Field::rand(rng)).unwrap(), None);
+    }
+}
@@ -0,0 +1,418 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkvm::{
+    ledger::{block::Block, store::ConsensusStorage, Ledger},
+    prelude::{ensure, Address, Field, FromBytes, Network, Plaintext, Record, Result, ToBytes, ViewKey},
+};
+
+use indexmap::IndexMap;
+use parking_lot::RwLock;
+use std::{
+    fs,
+    io::{Cursor, Write},
+    ops::Range,
+    path::PathBuf,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// The maximum number of view keys that can be registered with the record scanner.
+pub const MAXIMUM_SCAN_VIEW_KEYS: usize = 16;
+/// The maximum number of blocks that can be backfilled for a registered view key.
+pub const MAXIMUM_SCAN_BACKFILL_BLOCKS: u32 = 50_000;
+
+/// A record that was decrypted with a registered view key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScannedRecord<N: Network> {
+    /// The height of the block containing the record.
+    pub height: u32,
+    /// The ID of the transaction that output the record.
+    pub transaction_id: N::TransactionID,
+    /// The commitment of the record.
+    pub commitment: Field<N>,
+    /// The decrypted record.
+    pub record: Record<N, Plaintext<N>>,
+}
+
+/// The registered view key, and the records decrypted with it.
+struct ScanEntry<N: Network> {
+    /// The view key.
+    view_key: ViewKey<N>,
+    /// The map of record commitments to the decrypted records.
+    records: IndexMap<Field<N>, ScannedRecord<N>>,
+}
+
+/// A service that scans the blocks for the records owned by the registered view keys.
+///
+/// New blocks are scanned as they are added to the ledger, and earlier blocks are scanned with a bounded backfill.
+/// The view keys and their records are held in memory, unless a path is given to persist them to.
+/// Note: The persisted file is rewritten as a whole when a view key is registered or a record is found,
+/// as the number of view keys is capped.
+pub struct RecordScanner<N: Network> {
+    /// The map of addresses to their registered view key and decrypted records.
+    entries: RwLock<IndexMap<Address<N>, ScanEntry<N>>>,
+    /// The path of the file to persist the view keys and records to, if persistence is enabled.
+    path: Option<PathBuf>,
+}
+
+impl<N: Network> RecordScanner<N> {
+    /// Opens the record scanner, restoring the view keys and records from the given path, if any.
+    pub fn open(path: Option<PathBuf>) -> Result<Self> {
+        let mut entries = IndexMap::new();
+        if let Some(path) = &path {
+            if path.exists() {
+                let bytes = fs::read(path)?;
+                let mut reader = Cursor::new(bytes.as_slice());
+                let num_entries = u32::read_le(&mut reader)?;
+                for _ in 0..num_entries {
+                    let entry = read_entry::<N>(&mut reader)?;
+                    entries.insert(entry.view_key.to_address(), entry);
+                }
+                ensure!(reader.position() as usize == bytes.len(), "The record scanner file has trailing bytes");
+            }
+        }
+        Ok(Self { entries: RwLock::new(entries), path })
+    }
+
+    /// Returns the number of registered view keys.
+    pub fn num_view_keys(&self) -> usize {
+        self.entries.read().len()
+    }
+
+    /// Returns `true` if the view key of the given address is registered.
+    pub fn is_registered(&self, address: &Address<N>) -> bool {
+        self.entries.read().contains_key(address)
+    }
+
+    /// Registers the given view key, and returns its address.
+    /// If the view key is already registered, its records are retained.
+    pub fn register(&self, view_key: ViewKey<N>) -> Result<Address<N>> {
+        let address = view_key.to_address();
+        let mut entries = self.entries.write();
+        if entries.contains_key(&address) {
+            return Ok(address);
+        }
+        // Ensure the number of view keys does not exceed the maximum.
+        ensure!(
+            entries.len() < MAXIMUM_SCAN_VIEW_KEYS,
+            "The record scanner has reached the maximum of {MAXIMUM_SCAN_VIEW_KEYS} view keys"
+        );
+        entries.insert(address, ScanEntry { view_key, records: IndexMap::new() });
+        self.persist(&entries)?;
+        Ok(address)
+    }
+
+    /// Ensures the given backfill range is within the maximum number of blocks.
+    pub fn check_backfill_range(heights: &Range<u32>) -> Result<()> {
+        ensure!(heights.start <= heights.end, "The backfill range {heights:?} is empty");
+        ensure!(
+            heights.end - heights.start <= MAXIMUM_SCAN_BACKFILL_BLOCKS,
+            "The backfill range {heights:?} exceeds the maximum of {MAXIMUM_SCAN_BACKFILL_BLOCKS} blocks"
+        );
+        Ok(())
+    }
+
+    /// Returns the records found for the given address in the given block range, in block height order,
+    /// or `None` if the view key of the address is not registered.
+    pub fn get_records(&self, address: &Address<N>, heights: Range<u32>) -> Option<Vec<ScannedRecord<N>>> {
+        let entries = self.entries.read();
+        let mut records = entries
+            .get(address)?
+            .records
+            .values()
+            .filter(|record| heights.contains(&record.height))
+            .cloned()
+            .collect::<Vec<_>>();
+        records.sort_by_key(|record| record.height);
+        Some(records)
+    }
+
+    /// Scans the given block for the records owned by any of the registered view keys.
+    pub fn scan_block(&self, block: &Block<N>) -> Result<()> {
+        let mut entries = self.entries.write();
+        let mut is_updated = false;
+        for entry in entries.values_mut() {
+            is_updated |= entry.scan_block(block)?;
+        }
+        if is_updated {
+            self.persist(&entries)?;
+        }
+        Ok(())
+    }
+
+    /// Scans the blocks in the given range of the ledger for the records owned by the view key of the given address.
+    /// The range is inclusive of the start and exclusive of the end, and is clamped to the latest block.
+    pub fn backfill<C: ConsensusStorage<N>>(
+        &self,
+        ledger: &Ledger<N, C>,
+        address: &Address<N>,
+        heights: Range<u32>,
+        shutdown: &AtomicBool,
+    ) -> Result<()> {
+        Self::check_backfill_range(&heights)?;
+        let end_height = heights.end.min(ledger.latest_height().saturating_add(1));
+        tracing::info!("Backfilling the record scan of '{address}' for blocks {}..{end_height}", heights.start);
+        for height in heights.start..end_height {
+            // Stop if the node is shutting down.
+            if shutdown.load(Ordering::Relaxed) {
+                tracing::info!("Stopped backfilling the record scan of '{address}' at block {height}");
+                return Ok(());
+            }
+            // Retrieve the block before locking the entries, as it may be slow.
+            let block = ledger.get_block(height)?;
+            let mut entries = self.entries.write();
+            // Stop if the view key was removed in the meantime.
+            let Some(entry) = entries.get_mut(address) else {
+                return Ok(());
+            };
+            if entry.scan_block(&block)? {
+                self.persist(&entries)?;
+            }
+        }
+        tracing::info!("Finished backfilling the record scan of '{address}'");
+        Ok(())
+    }
+
+    /// Persists the given entries, if persistence is enabled.
+    /// Note: The file is written to a temporary path and then renamed, so that it is replaced atomically.
+    fn persist(&self, entries: &IndexMap<Address<N>, ScanEntry<N>>) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let mut bytes = Vec::new();
+        (entries.len() as u32).write_le(&mut bytes)?;
+        for entry in entries.values() {
+            write_entry(&mut bytes, entry)?;
+        }
+        let temporary_path = path.with_extension("tmp");
+        let mut file = fs::File::create(&temporary_path)?;
+        file.write_all(&bytes)?;
+        file.sync_data()?;
+        fs::rename(temporary_path, path)?;
+        Ok(())
+    }
+}
+
+impl<N: Network> ScanEntry<N> {
+    /// Scans the given block for the records owned by the view key, and returns `true` if a new record was found.
+    fn scan_block(&mut self, block: &Block<N>) -> Result<bool> {
+        let mut is_updated = false;
+        for transaction in block.transactions().iter().map(|confirmed| confirmed.transaction()) {
+            for (commitment, record) in transaction.records() {
+                if self.records.contains_key(commitment) || !record.is_owner(&self.view_key) {
+                    continue;
+                }
+                let record = record.decrypt(&self.view_key)?;
+                let scanned = ScannedRecord {
+                    height: block.height(),
+                    transaction_id: transaction.id(),
+                    commitment: *commitment,
+                    record,
+                };
+                self.records.insert(*commitment, scanned);
+                is_updated = true;
+            }
+        }
+        Ok(is_updated)
+    }
+}
+
+/// Writes the given entry, as `(view key, number of records, records)`.
+fn write_entry<N: Network>(writer: &mut Vec<u8>, entry: &ScanEntry<N>) -> Result<()> {
+    entry.view_key.write_le(&mut *writer)?;
+    (entry.records.len() as u32).write_le(&mut *writer)?;
+    for record in entry.records.values() {
+        record.height.write_le(&mut *writer)?;
+        record.transaction_id.write_le(&mut *writer)?;
+        record.commitment.write_le(&mut *writer)?;
+        record.record.write_le(&mut *writer)?;
+    }
+    Ok(())
+}
+
+/// Reads an entry.
+fn read_entry<N: Network>(reader: &mut Cursor<&[u8]>) -> Result<ScanEntry<N>> {
+    let view_key = ViewKey::read_le(&mut *reader)?;
+    let num_records = u32::read_le(&mut *reader)?;
+    let mut records = IndexMap::new();
+    for _ in 0..num_records {
+        let height = u32::read_le(&mut *reader)?;
+        let transaction_id = N::TransactionID::read_le(&mut *reader)?;
+        let commitment = Field::read_le(&mut *reader)?;
+        let record = Record::read_le(&mut *reader)?;
+        records.insert(commitment, ScannedRecord { height, transaction_id, commitment, record });
+    }
+    Ok(ScanEntry { view_key, records })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::sample_ledger;
+    use snarkvm::{
+        ledger::store::helpers::memory::ConsensusMemory,
+        prelude::{Entry, Identifier, Literal, PrivateKey, RecordsFilter, TestRng, Value},
+    };
+
+    use std::str::FromStr;
+
+    type CurrentNetwork = snarkvm::prelude::MainnetV0;
+    type CurrentLedger = Ledger<CurrentNetwork, ConsensusMemory<CurrentNetwork>>;
+
+    /// Advances the ledger with a block that sends a record with the given amount to the given address.
+    fn advance_with_transfer(
+        ledger: &CurrentLedger,
+        private_key: &PrivateKey<CurrentNetwork>,
+        recipient: Address<CurrentNetwork>,
+        amount: u64,
+        rng: &mut TestRng,
+    ) -> Block<CurrentNetwork> {
+        // Find an unspent record of the sender.
+        let view_key = ViewKey::try_from(private_key).unwrap();
+        let (_, record) = ledger.find_records(&view_key, RecordsFilter::Unspent).unwrap().next().unwrap();
+        // Transfer part of the record to the recipient.
+        let inputs = [
+            Value::Record(record),
+            Value::from_str(&recipient.to_string()).unwrap(),
+            Value::from_str(&format!("{amount}u64")).unwrap(),
+        ];
+        let transaction = ledger
+            .vm()
+            .execute(private_key, ("credits.aleo", "transfer_private"), inputs.into_iter(), None, 0, None, rng)
+            .unwrap();
+        // Advance the ledger.
+        let block =
+            ledger.prepare_advance_to_next_beacon_block(private_key, vec![], vec![], vec![transaction], rng).unwrap();
+        ledger.advance_to_next_block(&block).unwrap();
+        block
+    }
+
+    /// Returns the amount of microcredits in the given record.
+    fn microcredits(record: &Record<CurrentNetwork, Plaintext<CurrentNetwork>>) -> u64 {
+        match record.data().get(&Identifier::from_str("microcredits").unwrap()) {
+            Some(Entry::Private(Plaintext::Literal(Literal::U64(amount), _))) => **amount,
+            _ => panic!("The record has no microcredits"),
+        }
+    }
+
+    /// Returns a path for the file of a record scanner.
+    fn sample_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("snarkos-record-scanner-{name}-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_scan_new_block() {
+        let rng = &mut TestRng::default();
+        let (ledger, private_key) = sample_ledger(rng);
+
+        // Register the view key of a recipient.
+        let scanner = RecordScanner::open(None).unwrap();
+        let recipient_view_key = ViewKey::try_from(PrivateKey::new(rng).unwrap()).unwrap();
+        let recipient = scanner.register(recipient_view_key).unwrap();
+        assert!(scanner.is_registered(&recipient));
+        assert_eq!(scanner.get_records(&recipient, 0..u32::MAX).unwrap(), vec![]);
+
+        // Send a record to the recipient, and scan the block as it is advanced.
+        let block = advance_with_transfer(&ledger, &private_key, recipient, 1234, rng);
+        scanner.scan_block(&block).unwrap();
+
+        // Ensure the record is found and decrypted.
+        let records = scanner.get_records(&recipient, 0..u32::MAX).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].height, 1);
+        assert_eq!(**records[0].record.owner(), recipient);
+        assert_eq!(microcredits(&records[0].record), 1234);
+        assert!(scanner.get_records(&recipient, 2..u32::MAX).unwrap().is_empty());
+
+        // Scanning the block again does not duplicate the record.
+        scanner.scan_block(&block).unwrap();
+        assert_eq!(scanner.get_records(&recipient, 0..u32::MAX).unwrap().len(), 1);
+
+        // An unregistered address has no records.
+        let other = Address::try_from(&PrivateKey::new(rng).unwrap()).unwrap();
+        assert!(scanner.get_records(&other, 0..u32::MAX).is_none());
+    }
+
+    #[test]
+    fn test_backfill() {
+        let rng = &mut TestRng::default();
+        let (ledger, private_key) = sample_ledger(rng);
+
+        // Send two records to the recipient, before its view key is registered.
+        let recipient_view_key = ViewKey::try_from(PrivateKey::new(rng).unwrap()).unwrap();
+        let recipient = recipient_view_key.to_address();
+        advance_with_transfer(&ledger, &private_key, recipient, 1, rng);
+        advance_with_transfer(&ledger, &private_key, recipient, 2, rng);
+
+        // Register the view key, and backfill the second block only.
+        let scanner = RecordScanner::open(None).unwrap();
+        scanner.register(recipient_view_key).unwrap();
+        scanner.backfill(&ledger, &recipient, 2..10, &AtomicBool::new(false)).unwrap();
+        let records = scanner.get_records(&recipient, 0..u32::MAX).unwrap();
+        assert_eq!(records.iter().map(|record| microcredits(&record.record)).collect::<Vec<_>>(), vec![2]);
+
+        // Backfill the whole ledger.
+        scanner.backfill(&ledger, &recipient, 0..10, &AtomicBool::new(false)).unwrap();
+        let records = scanner.get_records(&recipient, 0..u32::MAX).unwrap();
+        assert_eq!(records.iter().map(|record| microcredits(&record.record)).collect::<Vec<_>>(), vec![1, 2]);
+
+        // Ensure the backfill range is capped.
+        assert!(scanner
+            .backfill(&ledger, &recipient, 0..MAXIMUM_SCAN_BACKFILL_BLOCKS + 1, &AtomicBool::new(false))
+            .is_err());
+    }
+
+    #[test]
+    fn test_maximum_view_keys() {
+        let rng = &mut TestRng::default();
+        let scanner = RecordScanner::<CurrentNetwork>::open(None).unwrap();
+        let view_keys = (0..MAXIMUM_SCAN_VIEW_KEYS)
+            .map(|_| ViewKey::try_from(PrivateKey::new(rng).unwrap()).unwrap())
+            .collect::<Vec<_>>();
+        for view_key in &view_keys {
+            scanner.register(*view_key).unwrap();
+        }
+        assert_eq!(scanner.num_view_keys(), MAXIMUM_SCAN_VIEW_KEYS);
+        // Registering an existing view key is a no-op.
+        scanner.register(view_keys[0]).unwrap();
+        // Registering a new view key fails.
+        assert!(scanner.register(ViewKey::try_from(PrivateKey::new(rng).unwrap()).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_persistence() {
+        let rng = &mut TestRng::default();
+        let (ledger, private_key) = sample_ledger(rng);
+        let path = sample_path("persistence");
+
+        // Register a view key, and find a record.
+        let scanner = RecordScanner::open(Some(path.clone())).unwrap();
+        let recipient = scanner.register(ViewKey::try_from(PrivateKey::new(rng).unwrap()).unwrap()).unwrap();
+        scanner.scan_block(&advance_with_transfer(&ledger, &private_key, recipient, 5, rng)).unwrap();
+        let records = scanner.get_records(&recipient, 0..u32::MAX).unwrap();
+        drop(scanner);
+
+        // Reopen the scanner, and ensure the view key and record are restored.
+        let scanner = RecordScanner::<CurrentNetwork>::open(Some(path.clone())).unwrap();
+        assert!(scanner.is_registered(&recipient));
+        assert_eq!(scanner.get_records(&recipient, 0..u32::MAX).unwrap(), records);
+
+        // Ensure an in-memory scanner does not persist anything.
+        let scanner = RecordScanner::<CurrentNetwork>::open(None).unwrap();
+        assert_eq!(scanner.num_view_keys(), 0);
+        fs::remove_file(path).unwrap();
+    }
+}
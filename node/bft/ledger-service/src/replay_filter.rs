@@ -0,0 +1,309 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkvm::{
+    ledger::{block::Block, narwhal::TransmissionID, store::ConsensusStorage, Ledger},
+    prelude::{Network, Result},
+};
+
+use parking_lot::RwLock;
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap, HashMap},
+    hash::{Hash, Hasher},
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+};
+
+/// The default number of recent blocks whose confirmed transactions and solutions are held exactly.
+pub const DEFAULT_REPLAY_FILTER_RECENT_BLOCKS: u32 = 1024;
+/// The number of latest blocks that are loaded into the replay filter when the node starts.
+const REPLAY_FILTER_BACKFILL_BLOCKS: u32 = 16 * DEFAULT_REPLAY_FILTER_RECENT_BLOCKS;
+/// The number of bits of the bloom filter over the older blocks (4 MiB).
+const BLOOM_FILTER_BITS: usize = 1 << 25;
+/// The number of hash functions of the bloom filter.
+const BLOOM_FILTER_HASHES: u64 = 7;
+
+/// A bloom filter, which may report an item that was never inserted, but never misses an inserted item.
+struct BloomFilter {
+    /// The bits of the filter.
+    bits: Vec<u64>,
+}
+
+impl BloomFilter {
+    /// Initializes an empty bloom filter with (at least) the given number of bits.
+    fn new(num_bits: usize) -> Self {
+        Self { bits: vec![0; num_bits.div_ceil(64).max(1)] }
+    }
+
+    /// Returns the indices of the bits of the given item.
+    fn indices<T: Hash>(&self, item: &T) -> impl Iterator<Item = usize> {
+        let hash = |seed: u64| {
+            let mut hasher = DefaultHasher::new();
+            seed.hash(&mut hasher);
+            item.hash(&mut hasher);
+            hasher.finish()
+        };
+        // Derive every index from two hashes of the item.
+        let (first, second) = (hash(0), hash(1) | 1);
+        let num_bits = self.bits.len() as u64 * 64;
+        (0..BLOOM_FILTER_HASHES).map(move |i| (first.wrapping_add(i.wrapping_mul(second)) % num_bits) as usize)
+    }
+
+    /// Inserts the given item.
+    fn insert<T: Hash>(&mut self, item: &T) {
+        for index in self.indices(item).collect::<Vec<_>>() {
+            self.bits[index / 64] |= 1 << (index % 64);
+        }
+    }
+
+    /// Returns `true` if the given item may have been inserted.
+    fn contains<T: Hash>(&self, item: &T) -> bool {
+        self.indices(item).all(|index| self.bits[index / 64] & (1 << (index % 64)) != 0)
+    }
+}
+
+/// The in-memory state of the replay filter.
+struct FilterState<N: Network> {
+    /// The recent blocks, mapping each height to the transmission IDs confirmed in it.
+    recent_blocks: BTreeMap<u32, Vec<TransmissionID<N>>>,
+    /// The transmission IDs confirmed in the recent blocks, mapped to their block height.
+    recent: HashMap<TransmissionID<N>, u32>,
+    /// The bloom filter over the transmission IDs confirmed in the older blocks.
+    older: BloomFilter,
+}
+
+/// A filter of the recently confirmed transactions and solutions, which is consulted at intake,
+/// so that the copies that peers keep gossiping after their confirmation are dropped cheaply.
+///
+/// The transmissions confirmed in the recent blocks are held exactly, alongside their block height.
+/// As blocks leave the recent window, their transmissions are moved into a bloom filter, to keep the memory small.
+/// A match in the bloom filter is confirmed in the ledger, so a false positive falls through to the full intake.
+pub struct ReplayFilter<N: Network, C: ConsensusStorage<N>> {
+    /// The ledger, to confirm the matches of the bloom filter.
+    ledger: Ledger<N, C>,
+    /// The number of recent blocks whose transmissions are held exactly.
+    num_recent_blocks: u32,
+    /// The in-memory state of the filter.
+    state: RwLock<FilterState<N>>,
+    /// The number of matches of the bloom filter that were not confirmed in the ledger.
+    num_false_positives: AtomicU64,
+}
+
+impl<N: Network, C: ConsensusStorage<N>> ReplayFilter<N, C> {
+    /// Initializes an empty replay filter over the given ledger.
+    pub fn new(ledger: Ledger<N, C>) -> Self {
+        let state = FilterState {
+            recent_blocks: Default::default(),
+            recent: Default::default(),
+            older: BloomFilter::new(BLOOM_FILTER_BITS),
+        };
+        Self {
+            ledger,
+            num_recent_blocks: DEFAULT_REPLAY_FILTER_RECENT_BLOCKS,
+            state: RwLock::new(state),
+            num_false_positives: Default::default(),
+        }
+    }
+
+    /// Returns the replay filter, holding the transmissions of the given number of recent blocks exactly.
+    pub fn with_num_recent_blocks(mut self, num_recent_blocks: u32) -> Self {
+        self.num_recent_blocks = num_recent_blocks.max(1);
+        self
+    }
+
+    /// Returns the height of the block that confirmed the given transaction or solution, if it is known to be confirmed.
+    /// Note: `None` does not imply that the transmission is unconfirmed, so the caller must proceed with the full intake.
+    pub fn confirmed_height(&self, transmission_id: &TransmissionID<N>) -> Option<u32> {
+        {
+            let state = self.state.read();
+            // Check the recent blocks.
+            if let Some(height) = state.recent.get(transmission_id) {
+                return Some(*height);
+            }
+            // Check the bloom filter over the older blocks.
+            if !state.older.contains(transmission_id) {
+                return None;
+            }
+        }
+        // Confirm the match in the ledger, as the bloom filter may report a false positive.
+        match self.find_confirmed_height(transmission_id) {
+            Ok(Some(height)) => Some(height),
+            _ => {
+                self.num_false_positives.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    /// Returns the number of matches of the bloom filter that were not confirmed in the ledger.
+    pub fn num_false_positives(&self) -> u64 {
+        self.num_false_positives.load(Ordering::Relaxed)
+    }
+
+    /// Inserts the transactions and solutions confirmed in the given block.
+    pub fn insert_block(&self, block: &Block<N>) {
+        let mut transmission_ids = block.transaction_ids().map(TransmissionID::from).collect::<Vec<_>>();
+        if let Some(solutions) = &**block.solutions() {
+            transmission_ids.extend(solutions.keys().map(|solution_id| TransmissionID::from(*solution_id)));
+        }
+        self.insert(block.height(), transmission_ids);
+    }
+
+    /// Loads the latest blocks of the ledger into the filter, skipping the blocks that were already inserted.
+    pub fn backfill(&self, shutdown: &AtomicBool) -> Result<()> {
+        let latest_height = self.ledger.latest_height();
+        let start_height = latest_height.saturating_sub(REPLAY_FILTER_BACKFILL_BLOCKS - 1);
+        // Load the blocks from the latest, so that the recent blocks are available first.
+        for height in (start_height..=latest_height).rev() {
+            if shutdown.load(Ordering::Relaxed) {
+                break;
+            }
+            if self.state.read().recent_blocks.contains_key(&height) {
+                continue;
+            }
+            self.insert_block(&self.ledger.get_block(height)?);
+        }
+        Ok(())
+    }
+
+    /// Inserts the given transmission IDs, confirmed in the block at the given height.
+    fn insert(&self, height: u32, transmission_ids: Vec<TransmissionID<N>>) {
+        let mut guard = self.state.write();
+        let state = &mut *guard;
+        // If the block precedes the recent blocks, insert its transmissions into the bloom filter.
+        let latest_height = state.recent_blocks.last_key_value().map(|(height, _)| *height).unwrap_or(0);
+        if height.saturating_add(self.num_recent_blocks) <= latest_height {
+            for transmission_id in &transmission_ids {
+                state.older.insert(transmission_id);
+            }
+            return;
+        }
+        // Insert the block into the recent blocks.
+        for transmission_id in &transmission_ids {
+            state.recent.insert(*transmission_id, height);
+        }
+        state.recent_blocks.insert(height, transmission_ids);
+        // Move the blocks that left the recent window into the bloom filter.
+        while state.recent_blocks.len() > self.num_recent_blocks as usize {
+            let Some((_, transmission_ids)) = state.recent_blocks.pop_first() else {
+                break;
+            };
+            for transmission_id in transmission_ids {
+                state.recent.remove(&transmission_id);
+                state.older.insert(&transmission_id);
+            }
+        }
+    }
+
+    /// Returns the height of the block that confirmed the given transmission in the ledger, if any.
+    fn find_confirmed_height(&self, transmission_id: &TransmissionID<N>) -> Result<Option<u32>> {
+        match transmission_id {
+            TransmissionID::Ratification => Ok(None),
+            TransmissionID::Solution(solution_id) => self.ledger.find_block_height_from_solution_id(solution_id),
+            TransmissionID::Transaction(transaction_id) => match self.ledger.find_block_hash(transaction_id)? {
+                Some(block_hash) => Ok(Some(self.ledger.get_height(&block_hash)?)),
+                None => Ok(None),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers;
+    use snarkvm::{
+        ledger::store::helpers::memory::ConsensusMemory,
+        prelude::{Field, MainnetV0, TestRng, Uniform},
+    };
+
+    type CurrentNetwork = MainnetV0;
+    type CurrentLedger = Ledger<CurrentNetwork, ConsensusMemory<CurrentNetwork>>;
+
+    /// Returns a ledger with a genesis block.
+    fn sample_ledger(rng: &mut TestRng) -> CurrentLedger {
+        test_helpers::sample_ledger(rng).0
+    }
+
+    /// Returns a sample transmission ID, which was never confirmed.
+    fn sample_transmission_id(rng: &mut TestRng) -> TransmissionID<CurrentNetwork> {
+        let transaction_id: <CurrentNetwork as Network>::TransactionID = Field::rand(rng).into();
+        TransmissionID::from(&transaction_id)
+    }
+
+    #[test]
+    fn test_bloom_filter_has_no_false_negatives() {
+        let mut bloom = BloomFilter::new(1 << 12);
+        for item in 0..100u64 {
+            bloom.insert(&item);
+        }
+        assert!((0..100u64).all(|item| bloom.contains(&item)));
+        // Ensure the filter does not report most of the other items.
+        assert!((100..1100u64).filter(|item| bloom.contains(item)).count() < 100);
+    }
+
+    #[test]
+    fn test_recent_blocks_are_held_exactly() {
+        let rng = &mut TestRng::default();
+        let filter = ReplayFilter::new(sample_ledger(rng)).with_num_recent_blocks(2);
+
+        let ids = (0..3).map(|_| sample_transmission_id(rng)).collect::<Vec<_>>();
+        for (height, id) in ids.iter().enumerate() {
+            filter.insert(height as u32 + 1, vec![*id]);
+        }
+        // Ensure the recent blocks return their height.
+        assert_eq!(filter.confirmed_height(&ids[1]), Some(2));
+        assert_eq!(filter.confirmed_height(&ids[2]), Some(3));
+        // Ensure an unknown transmission falls through.
+        assert_eq!(filter.confirmed_height(&sample_transmission_id(rng)), None);
+
+        // Ensure the block that left the window was moved into the bloom filter, and as it is
+        // not in the ledger, the match is counted as a false positive and falls through.
+        assert!(filter.state.read().older.contains(&ids[0]));
+        assert!(!filter.state.read().recent.contains_key(&ids[0]));
+        assert_eq!(filter.confirmed_height(&ids[0]), None);
+        assert_eq!(filter.num_false_positives(), 1);
+    }
+
+    #[test]
+    fn test_older_blocks_are_confirmed_in_the_ledger() {
+        let rng = &mut TestRng::default();
+        let ledger = sample_ledger(rng);
+        let genesis = ledger.get_block(0).unwrap();
+        let filter = ReplayFilter::new(ledger).with_num_recent_blocks(1);
+
+        // Insert the genesis block, and push it out of the recent window.
+        filter.insert_block(&genesis);
+        filter.insert(1, vec![sample_transmission_id(rng)]);
+
+        // Ensure the genesis transactions are found through the bloom filter, and confirmed in the ledger.
+        for transaction_id in genesis.transaction_ids() {
+            assert_eq!(filter.confirmed_height(&TransmissionID::from(transaction_id)), Some(0));
+        }
+        assert_eq!(filter.num_false_positives(), 0);
+    }
+
+    #[test]
+    fn test_backfill() {
+        let rng = &mut TestRng::default();
+        let ledger = sample_ledger(rng);
+        let genesis = ledger.get_block(0).unwrap();
+        let filter = ReplayFilter::new(ledger);
+
+        // Ensure the genesis transactions are unknown until the filter is backfilled.
+        let transaction_id = TransmissionID::from(genesis.transaction_ids().next().unwrap());
+        assert_eq!(filter.confirmed_height(&transaction_id), None);
+        filter.backfill(&AtomicBool::new(false)).unwrap();
+        assert_eq!(filter.confirmed_height(&transaction_id), Some(0));
+    }
+}
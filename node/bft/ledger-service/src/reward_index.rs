@@ -0,0 +1,296 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{BlockLog, LogPayload};
+use snarkvm::{
+    ledger::{
+        block::{Block, Ratify},
+        puzzle::Puzzle,
+        store::ConsensusStorage,
+        Ledger,
+    },
+    prelude::{Address, FromBytes, Network, Result, ToBytes},
+};
+
+use indexmap::IndexMap;
+use parking_lot::{Mutex, RwLock};
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::Range,
+    path::PathBuf,
+    sync::atomic::AtomicBool,
+};
+
+/// The puzzle reward of an address in a block.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct BlockReward {
+    /// The portion of the puzzle reward of the block earned by the address, in microcredits.
+    pub reward: u64,
+    /// The number of solutions of the address included in the block.
+    pub num_solutions: u32,
+}
+
+/// Returns the puzzle rewards of each address with solutions in the given block.
+///
+/// The puzzle reward of the block is split among its solutions in proportion to their proof targets,
+/// as the ledger does when it distributes the puzzle reward to the provers.
+pub fn block_rewards<N: Network>(puzzle: &Puzzle<N>, block: &Block<N>) -> Result<IndexMap<Address<N>, BlockReward>> {
+    // Retrieve the puzzle reward of the block.
+    let puzzle_reward = block
+        .ratifications()
+        .iter()
+        .find_map(|ratify| match ratify {
+            Ratify::PuzzleReward(reward) => Some(*reward),
+            _ => None,
+        })
+        .unwrap_or(0);
+
+    // Compute the proof target of each solution.
+    let mut proof_targets = Vec::new();
+    if let Some(solutions) = &**block.solutions() {
+        for solution in solutions.values() {
+            proof_targets.push((solution.address(), puzzle.get_proof_target(solution)?));
+        }
+    }
+    split_puzzle_reward(puzzle_reward, &proof_targets)
+}
+
+/// Returns the shares of the given puzzle reward of each address, in proportion to the proof targets of its solutions.
+fn split_puzzle_reward<N: Network>(
+    puzzle_reward: u64,
+    proof_targets: &[(Address<N>, u64)],
+) -> Result<IndexMap<Address<N>, BlockReward>> {
+    // Note: The sum of the proof targets may exceed `u64::MAX`, so the shares are computed as `u128`.
+    let combined_proof_target = proof_targets.iter().map(|(_, proof_target)| *proof_target as u128).sum::<u128>();
+    let mut rewards = IndexMap::<Address<N>, BlockReward>::new();
+    for (address, proof_target) in proof_targets {
+        let share = (puzzle_reward as u128 * *proof_target as u128).checked_div(combined_proof_target).unwrap_or(0);
+        let entry = rewards.entry(*address).or_default();
+        entry.reward += u64::try_from(share)?;
+        entry.num_solutions += 1;
+    }
+    Ok(rewards)
+}
+
+/// The in-memory state of the reward index.
+struct IndexState<N: Network> {
+    /// The height of the next block to index.
+    next_height: u32,
+    /// The map of addresses to their puzzle rewards, by block height.
+    rewards: HashMap<Address<N>, BTreeMap<u32, BlockReward>>,
+}
+
+/// The puzzle rewards of each address in a block, as persisted in the log of the reward index.
+type BlockRewards<N> = IndexMap<Address<N>, BlockReward>;
+
+impl<N: Network> LogPayload for BlockRewards<N> {
+    fn write_payload(&self, writer: &mut Vec<u8>) -> Result<()> {
+        (self.len() as u32).write_le(&mut *writer)?;
+        for (address, reward) in self {
+            address.write_le(&mut *writer)?;
+            reward.reward.write_le(&mut *writer)?;
+            reward.num_solutions.write_le(&mut *writer)?;
+        }
+        Ok(())
+    }
+
+    fn read_payload(_height: u32, reader: &mut &[u8]) -> Result<Self> {
+        let num_rewards = u32::read_le(&mut *reader)?;
+        let mut rewards = IndexMap::new();
+        for _ in 0..num_rewards {
+            let address = Address::read_le(&mut *reader)?;
+            let reward = u64::read_le(&mut *reader)?;
+            let num_solutions = u32::read_le(&mut *reader)?;
+            rewards.insert(address, BlockReward { reward, num_solutions });
+        }
+        Ok(rewards)
+    }
+}
+
+/// An index from addresses to the puzzle rewards they earned in each block.
+///
+/// The index is persisted as a [`BlockLog`] of the puzzle rewards of each block.
+pub struct RewardIndex<N: Network> {
+    /// The in-memory state of the index.
+    state: RwLock<IndexState<N>>,
+    /// The log of the index.
+    log: Mutex<BlockLog>,
+}
+
+impl<N: Network> RewardIndex<N> {
+    /// Opens the reward index at the given path, or an in-memory index if no path is given.
+    /// If the log file exists, the index resumes from the last fully-indexed block.
+    pub fn open(path: Option<PathBuf>) -> Result<Self> {
+        let mut state = IndexState { next_height: 0, rewards: HashMap::new() };
+        let log = BlockLog::open("reward index", path, |height, rewards: BlockRewards<N>| {
+            // Ensure the blocks are replayed in order.
+            if height != state.next_height {
+                return false;
+            }
+            for (address, reward) in rewards {
+                state.rewards.entry(address).or_default().insert(height, reward);
+            }
+            state.next_height += 1;
+            true
+        })?;
+        Ok(Self { state: RwLock::new(state), log: Mutex::new(log) })
+    }
+
+    /// Returns the height of the next block to index.
+    pub fn next_height(&self) -> u32 {
+        self.state.read().next_height
+    }
+
+    /// Returns the puzzle rewards of the given address in the given range of block heights, in height order,
+    /// or `None` if the range is not fully indexed yet.
+    /// Note: Only the blocks in which the address earned a reward are returned.
+    pub fn get_rewards(&self, address: &Address<N>, heights: Range<u32>) -> Option<Vec<(u32, BlockReward)>> {
+        let state = self.state.read();
+        if heights.end > state.next_height {
+            return None;
+        }
+        let rewards = match state.rewards.get(address) {
+            Some(rewards) if !heights.is_empty() => rewards.range(heights).map(|(h, r)| (*h, *r)).collect(),
+            _ => Vec::new(),
+        };
+        Some(rewards)
+    }
+
+    /// Indexes the given block, if it is the next block to index, and returns `true` if it was indexed.
+    /// Blocks that are already indexed, or that are ahead of the index (i.e. during a backfill), are skipped.
+    pub fn insert_block(&self, puzzle: &Puzzle<N>, block: &Block<N>) -> Result<bool> {
+        // Skip the block early, as computing its rewards requires the proof target of each solution.
+        if block.height() != self.next_height() {
+            return Ok(false);
+        }
+        self.insert_rewards(block.height(), block_rewards(puzzle, block)?)
+    }
+
+    /// Indexes the given puzzle rewards of the block at the given height, if it is the next block to index,
+    /// and returns `true` if they were indexed.
+    fn insert_rewards(&self, height: u32, rewards: BlockRewards<N>) -> Result<bool> {
+        // Hold the write lock throughout, so that readers observe either none or all of the block.
+        let mut state = self.state.write();
+        if height != state.next_height {
+            return Ok(false);
+        }
+
+        // Persist the entry.
+        self.log.lock().append(height, &rewards)?;
+
+        // Update the in-memory state.
+        for (address, reward) in rewards {
+            state.rewards.entry(address).or_default().insert(height, reward);
+        }
+        state.next_height += 1;
+        Ok(true)
+    }
+
+    /// Indexes the blocks in the given ledger that are not yet indexed, until the index reaches the latest block.
+    /// The backfill is resumable, as it starts from the next block to index, and stops if the node shuts down.
+    pub fn backfill<C: ConsensusStorage<N>>(&self, ledger: &Ledger<N, C>, shutdown: &AtomicBool) -> Result<()> {
+        let next_height = || self.next_height();
+        BlockLog::backfill("reward index", ledger, shutdown, next_height, |block| {
+            self.insert_block(ledger.puzzle(), block)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkvm::prelude::{MainnetV0, PrivateKey, TestRng};
+
+    type CurrentNetwork = MainnetV0;
+
+    /// Returns the given number of new addresses.
+    fn sample_addresses(rng: &mut TestRng, num_addresses: usize) -> Vec<Address<CurrentNetwork>> {
+        (0..num_addresses).map(|_| Address::try_from(PrivateKey::new(rng).unwrap()).unwrap()).collect()
+    }
+
+    /// Returns a path for the log file of a reward index.
+    fn sample_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("snarkos-reward-index-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_split_puzzle_reward() {
+        let rng = &mut TestRng::default();
+        let addresses = sample_addresses(rng, 2);
+
+        // Split the reward among three solutions, two of which are from the first address.
+        let proof_targets = [(addresses[0], 100), (addresses[1], 200), (addresses[0], 100)];
+        let rewards = split_puzzle_reward(1_000, &proof_targets).unwrap();
+        assert_eq!(rewards[&addresses[0]], BlockReward { reward: 250 + 250, num_solutions: 2 });
+        assert_eq!(rewards[&addresses[1]], BlockReward { reward: 500, num_solutions: 1 });
+
+        // Ensure the shares are rounded down, and never exceed the puzzle reward.
+        let proof_targets = [(addresses[0], 1), (addresses[1], 2)];
+        let rewards = split_puzzle_reward(100, &proof_targets).unwrap();
+        assert_eq!(rewards[&addresses[0]].reward, 33);
+        assert_eq!(rewards[&addresses[1]].reward, 66);
+
+        // Ensure large proof targets do not overflow.
+        let proof_targets = [(addresses[0], u64::MAX), (addresses[1], u64::MAX)];
+        let rewards = split_puzzle_reward(u64::MAX, &proof_targets).unwrap();
+        assert_eq!(rewards[&addresses[0]].reward, u64::MAX / 2);
+
+        // Ensure a block without solutions has no rewards.
+        assert!(split_puzzle_reward::<CurrentNetwork>(1_000, &[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_get_rewards_and_resume() {
+        let rng = &mut TestRng::default();
+        let addresses = sample_addresses(rng, 2);
+        let path = sample_path("resume");
+
+        // Index the rewards of three blocks, in which the first address earns a reward in blocks 0 and 2.
+        let index = RewardIndex::<CurrentNetwork>::open(Some(path.clone())).unwrap();
+        let reward = |reward, num_solutions| BlockReward { reward, num_solutions };
+        let blocks = [
+            IndexMap::from([(addresses[0], reward(10, 1)), (addresses[1], reward(20, 2))]),
+            IndexMap::from([(addresses[1], reward(30, 1))]),
+            IndexMap::from([(addresses[0], reward(40, 3))]),
+        ];
+        for (height, rewards) in blocks.iter().enumerate() {
+            assert!(index.insert_rewards(height as u32, rewards.clone()).unwrap());
+        }
+        // Ensure blocks are not indexed twice, or out of order.
+        assert!(!index.insert_rewards(1, blocks[1].clone()).unwrap());
+        assert!(!index.insert_rewards(4, blocks[1].clone()).unwrap());
+        assert_eq!(index.next_height(), 3);
+
+        // Ensure the rewards are returned for the requested range.
+        assert_eq!(index.get_rewards(&addresses[0], 0..3), Some(vec![(0, reward(10, 1)), (2, reward(40, 3))]));
+        assert_eq!(index.get_rewards(&addresses[0], 1..3), Some(vec![(2, reward(40, 3))]));
+        assert_eq!(index.get_rewards(&addresses[1], 1..2), Some(vec![(1, reward(30, 1))]));
+        assert_eq!(index.get_rewards(&addresses[0], 1..1), Some(vec![]));
+        assert_eq!(index.get_rewards(&sample_addresses(rng, 1)[0], 0..3), Some(vec![]));
+        // Ensure a range that is not fully indexed is not returned.
+        assert_eq!(index.get_rewards(&addresses[0], 0..4), None);
+        drop(index);
+
+        // Reopen the index, and ensure it resumes with the same rewards.
+        let index = RewardIndex::<CurrentNetwork>::open(Some(path.clone())).unwrap();
+        assert_eq!(index.next_height(), 3);
+        assert_eq!(index.get_rewards(&addresses[0], 0..3), Some(vec![(0, reward(10, 1)), (2, reward(40, 3))]));
+        assert_eq!(index.get_rewards(&addresses[1], 0..3), Some(vec![(0, reward(20, 2)), (1, reward(30, 1))]));
+        drop(index);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,488 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{BlockLog, LogPayload};
+use snarkvm::{
+    ledger::{block::Block, store::ConsensusStorage, Ledger},
+    prelude::{bail, ensure, Field, FromBytes, Identifier, Network, ProgramID, Result, ToBits, ToBytes},
+    synthesizer::program::FinalizeOperation,
+};
+
+use parking_lot::{Mutex, RwLock};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+    sync::atomic::AtomicBool,
+};
+
+/// The default number of the most recent blocks retained by the state change index.
+pub const DEFAULT_STATE_CHANGES_RETENTION: u32 = 50_000;
+
+/// The kind of change to a mapping entry.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StateChangeKind {
+    /// A new key was inserted into the mapping.
+    Insert,
+    /// The value of an existing key was updated.
+    Update,
+    /// The key was removed from the mapping.
+    Remove,
+}
+
+impl StateChangeKind {
+    /// Returns the name of the kind of change.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Insert => "insert",
+            Self::Update => "update",
+            Self::Remove => "remove",
+        }
+    }
+}
+
+/// A change to a mapping entry of a program, from the finalize operations of a confirmed transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StateChange<N: Network> {
+    /// The ID of the transaction that made the change.
+    pub transaction_id: N::TransactionID,
+    /// The program ID and the name of the mapping, if the mapping ID was resolved.
+    pub mapping: Option<(ProgramID<N>, Identifier<N>)>,
+    /// The ID of the mapping.
+    pub mapping_id: Field<N>,
+    /// The kind of change.
+    pub kind: StateChangeKind,
+    /// The ID of the key, as committed in the finalize operation.
+    pub key_id: Field<N>,
+    /// The ID of the new value, as committed in the finalize operation, or `None` if the key was removed.
+    pub value_id: Option<Field<N>>,
+}
+
+/// Returns the ID of the given mapping, as the finalize store derives it.
+fn to_mapping_id<N: Network>(program_id: &ProgramID<N>, mapping_name: &Identifier<N>) -> Result<Field<N>> {
+    let mut preimage = Vec::new();
+    program_id.write_bits_le(&mut preimage);
+    false.write_bits_le(&mut preimage); // Separator
+    mapping_name.write_bits_le(&mut preimage);
+    N::hash_bhp1024(&preimage)
+}
+
+/// Returns the changes to the mapping entries made by the finalize operations of the transactions in the given block.
+///
+/// The mapping IDs are resolved against the mappings of the programs of each transaction, which the ledger holds
+/// once the block is added. Changes to the mappings themselves (i.e. their initialization or removal) are skipped.
+pub fn block_state_changes<N: Network, C: ConsensusStorage<N>>(
+    ledger: &Ledger<N, C>,
+    block: &Block<N>,
+) -> Result<Vec<StateChange<N>>> {
+    // The mapping IDs of the programs resolved so far in the block.
+    let mut resolved = HashMap::<ProgramID<N>, Vec<(Field<N>, Identifier<N>)>>::new();
+    let mut changes = Vec::new();
+    for confirmed in block.transactions().iter() {
+        if confirmed.finalize_operations().is_empty() {
+            continue;
+        }
+        let transaction_id = confirmed.to_unconfirmed_transaction_id()?;
+        let transaction = confirmed.transaction();
+
+        // Resolve the mapping IDs of the programs of the transaction.
+        let mut mappings = HashMap::new();
+        let deployment = transaction.deployment().map(|deployment| deployment.program());
+        let program_ids = transaction.transitions().map(|transition| *transition.program_id());
+        for program_id in program_ids.chain(deployment.map(|program| *program.id())) {
+            if !resolved.contains_key(&program_id) {
+                let program = match deployment {
+                    Some(program) if program.id() == &program_id => program.clone(),
+                    _ => ledger.get_program(program_id)?,
+                };
+                let mapping_ids = program
+                    .mappings()
+                    .keys()
+                    .map(|name| Ok((to_mapping_id(&program_id, name)?, *name)))
+                    .collect::<Result<Vec<_>>>()?;
+                resolved.insert(program_id, mapping_ids);
+            }
+            for (mapping_id, name) in &resolved[&program_id] {
+                mappings.insert(*mapping_id, (program_id, *name));
+            }
+        }
+
+        // Summarize the changes to the mapping entries.
+        for operation in confirmed.finalize_operations().iter() {
+            let (mapping_id, kind, key_id, value_id) = match operation {
+                FinalizeOperation::InsertKeyValue(mapping_id, key_id, value_id) => {
+                    (*mapping_id, StateChangeKind::Insert, *key_id, Some(*value_id))
+                }
+                FinalizeOperation::UpdateKeyValue(mapping_id, key_id, value_id) => {
+                    (*mapping_id, StateChangeKind::Update, *key_id, Some(*value_id))
+                }
+                FinalizeOperation::RemoveKeyValue(mapping_id, key_id) => {
+                    (*mapping_id, StateChangeKind::Remove, *key_id, None)
+                }
+                _ => continue,
+            };
+            let mapping = mappings.get(&mapping_id).copied();
+            changes.push(StateChange { transaction_id, mapping, mapping_id, kind, key_id, value_id });
+        }
+    }
+    Ok(changes)
+}
+
+/// The in-memory state of the state change index.
+struct IndexState<N: Network> {
+    /// The height of the next block to index.
+    next_height: u32,
+    /// The state changes of each retained block.
+    blocks: BTreeMap<u32, Vec<StateChange<N>>>,
+}
+
+impl<N: Network> IndexState<N> {
+    /// Removes the blocks that are older than the given retention window.
+    fn prune(&mut self, retention: u32) {
+        let oldest_height = self.next_height.saturating_sub(retention);
+        self.blocks = self.blocks.split_off(&oldest_height);
+    }
+}
+
+/// An index of the changes to the mapping entries of programs in each block, for the most recent blocks.
+///
+/// The index is persisted as a [`BlockLog`] of the state changes of each block. Once the log holds
+/// twice the retention window, it is rewritten with the retained blocks only, so that its size remains bounded.
+pub struct StateChangeIndex<N: Network> {
+    /// The number of the most recent blocks retained.
+    retention: u32,
+    /// The in-memory state of the index.
+    state: RwLock<IndexState<N>>,
+    /// The log of the index.
+    log: Mutex<BlockLog>,
+}
+
+impl<N: Network> StateChangeIndex<N> {
+    /// Opens the state change index at the given path, or an in-memory index if no path is given,
+    /// retaining the given number of the most recent blocks.
+    /// If the log file exists, the index resumes from the last fully-indexed block.
+    pub fn open(path: Option<PathBuf>, retention: u32) -> Result<Self> {
+        ensure!(retention > 0, "The retention window of the state change index must be positive");
+        let mut state = IndexState { next_height: 0, blocks: BTreeMap::new() };
+        let log = BlockLog::open("state changes", path, |height, changes: Vec<StateChange<N>>| {
+            // Ensure the blocks are replayed in order.
+            // Note: The heights are increasing, though not contiguous, as the index skips the blocks
+            // that are beyond the retention window when it is backfilled.
+            if height < state.next_height {
+                return false;
+            }
+            state.blocks.insert(height, changes);
+            state.next_height = height + 1;
+            true
+        })?;
+        state.prune(retention);
+        Ok(Self { retention, state: RwLock::new(state), log: Mutex::new(log) })
+    }
+
+    /// Returns the number of the most recent blocks retained.
+    pub fn retention(&self) -> u32 {
+        self.retention
+    }
+
+    /// Returns the height of the next block to index.
+    pub fn next_height(&self) -> u32 {
+        self.state.read().next_height
+    }
+
+    /// Returns the state changes of the block at the given height,
+    /// or `None` if the block is not indexed yet, or is older than the retention window.
+    pub fn get_state_changes(&self, height: u32) -> Option<Vec<StateChange<N>>> {
+        self.state.read().blocks.get(&height).cloned()
+    }
+
+    /// Indexes the given block, if it is the next block to index, and returns `true` if it was indexed.
+    /// Blocks that are already indexed, or that are ahead of the index (i.e. during a backfill), are skipped.
+    pub fn insert_block<C: ConsensusStorage<N>>(&self, ledger: &Ledger<N, C>, block: &Block<N>) -> Result<bool> {
+        // Skip the block early, as resolving its state changes requires the programs of its transactions.
+        if block.height() != self.next_height() {
+            return Ok(false);
+        }
+        self.insert_state_changes(block.height(), block_state_changes(ledger, block)?)
+    }
+
+    /// Indexes the given state changes of the block at the given height, if it is the next block to index,
+    /// and returns `true` if they were indexed.
+    fn insert_state_changes(&self, height: u32, changes: Vec<StateChange<N>>) -> Result<bool> {
+        // Hold the write lock throughout, so that readers observe either none or all of the block.
+        let mut state = self.state.write();
+        if height != state.next_height {
+            return Ok(false);
+        }
+
+        // Persist the entry.
+        let mut log = self.log.lock();
+        log.append(height, &changes)?;
+
+        // Update the in-memory state.
+        state.blocks.insert(height, changes);
+        state.next_height += 1;
+        state.prune(self.retention);
+
+        // Rewrite the log with the retained blocks, once it holds twice the retention window.
+        if log.num_entries() > 2 * self.retention as usize {
+            log.rewrite(state.blocks.iter().map(|(height, changes)| (*height, changes)))?;
+        }
+        Ok(true)
+    }
+
+    /// Moves the index forward to the given height, skipping the blocks in between,
+    /// if the index is behind the given height.
+    fn skip_to(&self, height: u32) {
+        let mut state = self.state.write();
+        if height > state.next_height {
+            state.next_height = height;
+            state.prune(self.retention);
+        }
+    }
+
+    /// Indexes the blocks in the given ledger that are not yet indexed, until the index reaches the latest block.
+    /// Only the blocks in the retention window are indexed, as the older blocks would be pruned immediately.
+    /// The backfill is resumable, as it starts from the next block to index, and stops if the node shuts down.
+    pub fn backfill<C: ConsensusStorage<N>>(&self, ledger: &Ledger<N, C>, shutdown: &AtomicBool) -> Result<()> {
+        // Skip the blocks that are older than the retention window.
+        self.skip_to(ledger.latest_height().saturating_sub(self.retention - 1));
+
+        let next_height = || self.next_height();
+        BlockLog::backfill("state changes", ledger, shutdown, next_height, |block| self.insert_block(ledger, block))
+    }
+}
+
+impl<N: Network> LogPayload for Vec<StateChange<N>> {
+    fn write_payload(&self, writer: &mut Vec<u8>) -> Result<()> {
+        (self.len() as u32).write_le(&mut *writer)?;
+        for change in self {
+            change.transaction_id.write_le(&mut *writer)?;
+            match &change.mapping {
+                Some((program_id, mapping_name)) => {
+                    true.write_le(&mut *writer)?;
+                    program_id.write_le(&mut *writer)?;
+                    mapping_name.write_le(&mut *writer)?;
+                }
+                None => false.write_le(&mut *writer)?,
+            }
+            change.mapping_id.write_le(&mut *writer)?;
+            let kind: u8 = match change.kind {
+                StateChangeKind::Insert => 0,
+                StateChangeKind::Update => 1,
+                StateChangeKind::Remove => 2,
+            };
+            kind.write_le(&mut *writer)?;
+            change.key_id.write_le(&mut *writer)?;
+            match &change.value_id {
+                Some(value_id) => value_id.write_le(&mut *writer)?,
+                None => ensure!(change.kind == StateChangeKind::Remove, "A state change is missing its value ID"),
+            }
+        }
+        Ok(())
+    }
+
+    fn read_payload(height: u32, reader: &mut &[u8]) -> Result<Self> {
+        let num_changes = u32::read_le(&mut *reader)?;
+        let mut changes = Vec::new();
+        for _ in 0..num_changes {
+            let transaction_id = N::TransactionID::read_le(&mut *reader)?;
+            let mapping = match bool::read_le(&mut *reader)? {
+                true => Some((ProgramID::read_le(&mut *reader)?, Identifier::read_le(&mut *reader)?)),
+                false => None,
+            };
+            let mapping_id = Field::read_le(&mut *reader)?;
+            let kind = match u8::read_le(&mut *reader)? {
+                0 => StateChangeKind::Insert,
+                1 => StateChangeKind::Update,
+                2 => StateChangeKind::Remove,
+                kind => bail!("Invalid state change kind {kind} in the entry for block {height}"),
+            };
+            let key_id = Field::read_le(&mut *reader)?;
+            let value_id = match kind {
+                StateChangeKind::Remove => None,
+                _ => Some(Field::read_le(&mut *reader)?),
+            };
+            changes.push(StateChange { transaction_id, mapping, mapping_id, kind, key_id, value_id });
+        }
+        Ok(changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::sample_ledger;
+    use snarkvm::prelude::{Address, MainnetV0, PrivateKey, TestRng, Uniform, Value};
+
+    use std::str::FromStr;
+
+    type CurrentNetwork = MainnetV0;
+
+    /// Returns a path for the log file of a state change index.
+    fn sample_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("snarkos-state-changes-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    /// Returns the given number of sample state changes, of each kind in turn.
+    fn sample_changes(rng: &mut TestRng, num_changes: usize) -> Vec<StateChange<CurrentNetwork>> {
+        let program_id = ProgramID::from_str("token.aleo").unwrap();
+        let mapping_name = Identifier::from_str("balances").unwrap();
+        let kinds = [StateChangeKind::Insert, StateChangeKind::Update, StateChangeKind::Remove];
+        (0..num_changes)
+            .map(|i| StateChange {
+                transaction_id: Field::rand(rng).into(),
+                mapping: (i % 2 == 0).then_some((program_id, mapping_name)),
+                mapping_id: to_mapping_id(&program_id, &mapping_name).unwrap(),
+                kind: kinds[i % 3],
+                key_id: Field::rand(rng),
+                value_id: (kinds[i % 3] != StateChangeKind::Remove).then(|| Field::rand(rng)),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_block_state_changes() {
+        let rng = &mut TestRng::default();
+
+        // Initialize a ledger, whose genesis account holds a public balance.
+        let (ledger, private_key) = sample_ledger(rng);
+
+        // Advance the ledger with a block that transfers public credits to a new address.
+        let recipient = Address::try_from(PrivateKey::<CurrentNetwork>::new(rng).unwrap()).unwrap();
+        let inputs = [Value::from_str(&recipient.to_string()).unwrap(), Value::from_str("10u64").unwrap()];
+        let transaction = ledger
+            .vm()
+            .execute(&private_key, ("credits.aleo", "transfer_public"), inputs.into_iter(), None, 0, None, rng)
+            .unwrap();
+        let transaction_id = transaction.id();
+        let block =
+            ledger.prepare_advance_to_next_beacon_block(&private_key, vec![], vec![], vec![transaction], rng).unwrap();
+        ledger.advance_to_next_block(&block).unwrap();
+
+        // Ensure each key-value operation of the transaction is summarized, with its key and value IDs.
+        let changes = block_state_changes(&ledger, &block).unwrap();
+        let confirmed = block.transactions().iter().next().unwrap();
+        let operations = confirmed
+            .finalize_operations()
+            .iter()
+            .filter_map(|operation| match operation {
+                FinalizeOperation::InsertKeyValue(mapping_id, key_id, value_id)
+                | FinalizeOperation::UpdateKeyValue(mapping_id, key_id, value_id) => {
+                    Some((*mapping_id, *key_id, Some(*value_id)))
+                }
+                FinalizeOperation::RemoveKeyValue(mapping_id, key_id) => Some((*mapping_id, *key_id, None)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert!(!operations.is_empty());
+        assert_eq!(changes.len(), operations.len());
+
+        // Ensure the changes are attributed to the transaction, and resolved to the `account` mapping of `credits.aleo`.
+        let program_id = ProgramID::from_str("credits.aleo").unwrap();
+        let account = Identifier::from_str("account").unwrap();
+        let account_id = to_mapping_id(&program_id, &account).unwrap();
+        for (change, (mapping_id, key_id, value_id)) in changes.iter().zip(operations) {
+            assert_eq!(change.transaction_id, transaction_id);
+            assert_eq!(change.mapping_id, mapping_id);
+            assert_eq!(change.key_id, key_id);
+            assert_eq!(change.value_id, value_id);
+            assert_eq!(change.mapping_id, account_id);
+            assert_eq!(change.mapping, Some((program_id, account)));
+        }
+        // Ensure the balance of the recipient was inserted.
+        assert!(changes.iter().any(|change| change.kind == StateChangeKind::Insert));
+    }
+
+    #[test]
+    fn test_to_mapping_id() {
+        let program_id = ProgramID::<CurrentNetwork>::from_str("credits.aleo").unwrap();
+        let account = Identifier::from_str("account").unwrap();
+        let bonded = Identifier::from_str("bonded").unwrap();
+        // Ensure the mapping ID is deterministic, and distinct for each mapping.
+        assert_eq!(to_mapping_id(&program_id, &account).unwrap(), to_mapping_id(&program_id, &account).unwrap());
+        assert_ne!(to_mapping_id(&program_id, &account).unwrap(), to_mapping_id(&program_id, &bonded).unwrap());
+    }
+
+    #[test]
+    fn test_get_state_changes_and_resume() {
+        let rng = &mut TestRng::default();
+        let path = sample_path("resume");
+
+        // Index the state changes of three blocks, the second of which has none.
+        let index = StateChangeIndex::<CurrentNetwork>::open(Some(path.clone()), 10).unwrap();
+        let blocks = [sample_changes(rng, 3), vec![], sample_changes(rng, 4)];
+        for (height, changes) in blocks.iter().enumerate() {
+            assert!(index.insert_state_changes(height as u32, changes.clone()).unwrap());
+        }
+        // Ensure blocks are not indexed twice, or out of order.
+        assert!(!index.insert_state_changes(1, blocks[1].clone()).unwrap());
+        assert!(!index.insert_state_changes(4, blocks[1].clone()).unwrap());
+        assert_eq!(index.next_height(), 3);
+
+        // Ensure the state changes are returned for each indexed block.
+        for (height, changes) in blocks.iter().enumerate() {
+            assert_eq!(index.get_state_changes(height as u32).as_ref(), Some(changes));
+        }
+        assert_eq!(index.get_state_changes(3), None);
+        drop(index);
+
+        // Reopen the index, and ensure it resumes with the same state changes.
+        let index = StateChangeIndex::<CurrentNetwork>::open(Some(path.clone()), 10).unwrap();
+        assert_eq!(index.next_height(), 3);
+        assert_eq!(index.get_state_changes(2).as_ref(), Some(&blocks[2]));
+        assert_eq!(index.get_state_changes(0).as_ref(), Some(&blocks[0]));
+        drop(index);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_retention() {
+        let rng = &mut TestRng::default();
+        let path = sample_path("retention");
+
+        // Ensure the retention window must be positive.
+        assert!(StateChangeIndex::<CurrentNetwork>::open(None, 0).is_err());
+
+        // Skip to block 100, as the backfill does for the blocks beyond the retention window.
+        let index = StateChangeIndex::<CurrentNetwork>::open(Some(path.clone()), 3).unwrap();
+        index.skip_to(100);
+        assert_eq!(index.next_height(), 100);
+        index.skip_to(50);
+        assert_eq!(index.next_height(), 100);
+
+        // Index ten blocks, and ensure only the last three are retained.
+        let blocks = (0..10).map(|_| sample_changes(rng, 2)).collect::<Vec<_>>();
+        for (i, changes) in blocks.iter().enumerate() {
+            assert!(index.insert_state_changes(100 + i as u32, changes.clone()).unwrap());
+        }
+        assert_eq!(index.get_state_changes(106), None);
+        for (i, changes) in blocks.iter().enumerate().skip(7) {
+            assert_eq!(index.get_state_changes(100 + i as u32).as_ref(), Some(changes));
+        }
+        // Ensure the log was compacted, as it exceeded twice the retention window.
+        assert!(index.log.lock().num_entries() <= 6);
+        drop(index);
+
+        // Reopen the index, and ensure it resumes with the retained blocks only.
+        let index = StateChangeIndex::<CurrentNetwork>::open(Some(path.clone()), 3).unwrap();
+        assert_eq!(index.next_height(), 110);
+        assert_eq!(index.get_state_changes(106), None);
+        assert_eq!(index.get_state_changes(109).as_ref(), Some(&blocks[9]));
+        assert_eq!(index.state.read().blocks.len(), 3);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,232 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkvm::{
+    ledger::{
+        block::{Block, Transaction},
+        store::ConsensusStorage,
+        Ledger,
+    },
+    prelude::{anyhow, Network, Result},
+};
+
+use futures::{stream, Stream};
+use std::{iter::FusedIterator, ops::Range, vec};
+
+/// Returns an iterator over the blocks in the given block range, loading one block at a time.
+/// The range is inclusive of the start and exclusive of the end.
+///
+/// The iterator is `Send`, and may be bridged into a parallel iterator with `rayon`'s `par_bridge`.
+pub fn blocks_range<N: Network, C: ConsensusStorage<N>>(ledger: Ledger<N, C>, heights: Range<u32>) -> BlocksIter<N, C> {
+    BlocksIter { ledger, heights }
+}
+
+/// Returns an iterator over the transactions in the given block range, loading one block at a time.
+/// The range is inclusive of the start and exclusive of the end.
+pub fn transactions_range<N: Network, C: ConsensusStorage<N>>(
+    ledger: Ledger<N, C>,
+    heights: Range<u32>,
+) -> TransactionsIter<N, C> {
+    TransactionsIter { blocks: blocks_range(ledger, heights), pending: Vec::new().into_iter() }
+}
+
+/// Returns a stream of the blocks in the given block range, loading each block on a blocking task.
+/// The range is inclusive of the start and exclusive of the end.
+///
+/// The stream ends after the first error from the blocking task itself.
+pub fn blocks_stream<N: Network, C: ConsensusStorage<N>>(
+    ledger: Ledger<N, C>,
+    heights: Range<u32>,
+) -> impl Stream<Item = Result<Block<N>>> + Send {
+    iter_stream(blocks_range(ledger, heights))
+}
+
+/// Returns a stream of the transactions in the given block range, loading each block on a blocking task.
+/// The range is inclusive of the start and exclusive of the end.
+///
+/// The stream ends after the first error from the blocking task itself.
+pub fn transactions_stream<N: Network, C: ConsensusStorage<N>>(
+    ledger: Ledger<N, C>,
+    heights: Range<u32>,
+) -> impl Stream<Item = Result<Transaction<N>>> + Send {
+    iter_stream(transactions_range(ledger, heights))
+}
+
+/// Returns a stream that advances the given iterator on a blocking task.
+fn iter_stream<T: Send + 'static, I: Iterator<Item = Result<T>> + Send + 'static>(
+    iter: I,
+) -> impl Stream<Item = Result<T>> + Send {
+    stream::unfold(Some(iter), |state| async move {
+        let mut iter = state?;
+        match tokio::task::spawn_blocking(move || (iter.next(), iter)).await {
+            Ok((item, iter)) => item.map(|item| (item, Some(iter))),
+            Err(error) => Some((Err(anyhow!("[tokio::spawn_blocking] {error}")), None)),
+        }
+    })
+}
+
+/// An iterator over the blocks in a block range.
+pub struct BlocksIter<N: Network, C: ConsensusStorage<N>> {
+    ledger: Ledger<N, C>,
+    heights: Range<u32>,
+}
+
+impl<N: Network, C: ConsensusStorage<N>> Iterator for BlocksIter<N, C> {
+    type Item = Result<Block<N>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let height = self.heights.next()?;
+        Some(self.ledger.get_block(height))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.heights.size_hint()
+    }
+}
+
+impl<N: Network, C: ConsensusStorage<N>> DoubleEndedIterator for BlocksIter<N, C> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let height = self.heights.next_back()?;
+        Some(self.ledger.get_block(height))
+    }
+}
+
+impl<N: Network, C: ConsensusStorage<N>> ExactSizeIterator for BlocksIter<N, C> {}
+
+impl<N: Network, C: ConsensusStorage<N>> FusedIterator for BlocksIter<N, C> {}
+
+/// An iterator over the transactions in a block range.
+pub struct TransactionsIter<N: Network, C: ConsensusStorage<N>> {
+    blocks: BlocksIter<N, C>,
+    /// The remaining transactions of the current block.
+    pending: vec::IntoIter<Transaction<N>>,
+}
+
+impl<N: Network, C: ConsensusStorage<N>> Iterator for TransactionsIter<N, C> {
+    type Item = Result<Transaction<N>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            // Return the next transaction of the current block.
+            if let Some(transaction) = self.pending.next() {
+                return Some(Ok(transaction));
+            }
+            // Load the next block, dropping the previous one.
+            match self.blocks.next()? {
+                Ok(block) => {
+                    self.pending = block
+                        .transactions()
+                        .iter()
+                        .map(|confirmed| confirmed.transaction().clone())
+                        .collect::<Vec<_>>()
+                        .into_iter()
+                }
+                Err(error) => return Some(Err(error)),
+            }
+        }
+    }
+}
+
+impl<N: Network, C: ConsensusStorage<N>> FusedIterator for TransactionsIter<N, C> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers;
+    use snarkvm::{
+        ledger::store::helpers::memory::ConsensusMemory,
+        prelude::{MainnetV0, TestRng},
+    };
+
+    use futures::StreamExt;
+
+    type CurrentNetwork = MainnetV0;
+    type CurrentLedger = Ledger<CurrentNetwork, ConsensusMemory<CurrentNetwork>>;
+
+    /// Returns a ledger with the given number of blocks, including the genesis block.
+    fn sample_ledger(num_blocks: u32, rng: &mut TestRng) -> CurrentLedger {
+        let (ledger, private_key) = test_helpers::sample_ledger(rng);
+        for _ in 1..num_blocks {
+            let block = ledger.prepare_advance_to_next_beacon_block(&private_key, vec![], vec![], vec![], rng).unwrap();
+            ledger.advance_to_next_block(&block).unwrap();
+        }
+        ledger
+    }
+
+    /// Returns the transactions in the given blocks.
+    fn collect_transactions(blocks: &[Block<CurrentNetwork>]) -> Vec<Transaction<CurrentNetwork>> {
+        blocks
+            .iter()
+            .flat_map(|block| block.transactions().iter().map(|confirmed| confirmed.transaction().clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_blocks_range() {
+        let rng = &mut TestRng::default();
+        let ledger = sample_ledger(4, rng);
+
+        // Ensure the iterator matches the eager lookup.
+        let expected = ledger.get_blocks(0..4).unwrap();
+        let blocks = blocks_range(ledger.clone(), 0..4).collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(blocks, expected);
+
+        // Ensure the iterator is exact, and can be reversed.
+        let iter = blocks_range(ledger.clone(), 1..3);
+        assert_eq!(iter.len(), 2);
+        let reversed = iter.rev().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(reversed, vec![expected[2].clone(), expected[1].clone()]);
+
+        // Ensure an empty range yields nothing.
+        assert_eq!(blocks_range(ledger.clone(), 2..2).count(), 0);
+
+        // Ensure a missing block yields an error.
+        let mut iter = blocks_range(ledger, 3..5);
+        assert!(iter.next().unwrap().is_ok());
+        assert!(iter.next().unwrap().is_err());
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn test_transactions_range() {
+        let rng = &mut TestRng::default();
+        let ledger = sample_ledger(3, rng);
+
+        // Ensure the iterator matches the transactions of the eager lookup.
+        let expected = collect_transactions(&ledger.get_blocks(0..3).unwrap());
+        assert!(!expected.is_empty());
+        let transactions = transactions_range(ledger.clone(), 0..3).collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(transactions, expected);
+
+        // Ensure a missing block yields an error.
+        assert!(transactions_range(ledger, 3..4).next().unwrap().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_streams() {
+        let rng = &mut TestRng::default();
+        let ledger = sample_ledger(3, rng);
+        let expected = ledger.get_blocks(0..3).unwrap();
+
+        // Ensure the block stream matches the eager lookup.
+        let blocks = blocks_stream(ledger.clone(), 0..3).collect::<Vec<_>>().await;
+        let blocks = blocks.into_iter().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(blocks, expected);
+
+        // Ensure the transaction stream matches the transactions of the eager lookup.
+        let transactions = transactions_stream(ledger, 0..3).collect::<Vec<_>>().await;
+        let transactions = transactions.into_iter().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(transactions, collect_transactions(&expected));
+    }
+}
@@ -0,0 +1,35 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkvm::{
+    ledger::{
+        store::{helpers::memory::ConsensusMemory, ConsensusStore},
+        Ledger,
+    },
+    prelude::{MainnetV0, PrivateKey, TestRng, VM},
+};
+
+use aleo_std::StorageMode;
+
+type CurrentNetwork = MainnetV0;
+
+/// Returns a ledger with a genesis block, and the private key of the genesis account.
+pub fn sample_ledger(
+    rng: &mut TestRng,
+) -> (Ledger<CurrentNetwork, ConsensusMemory<CurrentNetwork>>, PrivateKey<CurrentNetwork>) {
+    let private_key = PrivateKey::new(rng).unwrap();
+    let vm = VM::from(ConsensusStore::<CurrentNetwork, ConsensusMemory<CurrentNetwork>>::open(None).unwrap()).unwrap();
+    let genesis = vm.genesis_beacon(&private_key, rng).unwrap();
+    (Ledger::load(genesis, StorageMode::Production).unwrap(), private_key)
+}
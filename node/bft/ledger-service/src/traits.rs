@@ -36,6 +36,14 @@ pub trait LedgerService<N: Network>: Debug + Send + Sync {
     /// Returns the latest block in the ledger.
     fn latest_block(&self) -> Block<N>;
 
+    /// Returns the UNIX timestamp (in seconds) of the latest block in the ledger, if the ledger has blocks.
+    fn latest_block_timestamp(&self) -> Option<i64> {
+        None
+    }
+
+    /// Returns the latest epoch hash, which the solutions for the current epoch must target.
+    fn latest_epoch_hash(&self) -> Result<N::BlockHash>;
+
     /// Returns the latest cached leader and its associated round.
     fn latest_leader(&self) -> Option<(u64, Address<N>)>;
 
@@ -88,10 +96,11 @@ pub trait LedgerService<N: Network>: Debug + Send + Sync {
     fn contains_transmission(&self, transmission_id: &TransmissionID<N>) -> Result<bool>;
 
     /// Ensures that the given transmission is not a fee and matches the given transmission ID.
+    /// The transmission is left in its received form, so that it can be forwarded without re-serialization.
     fn ensure_transmission_is_well_formed(
         &self,
         transmission_id: TransmissionID<N>,
-        transmission: &mut Transmission<N>,
+        transmission: &Transmission<N>,
     ) -> Result<()>;
 
     /// Checks the given solution is well-formed.
@@ -107,6 +116,9 @@ pub trait LedgerService<N: Network>: Debug + Send + Sync {
     /// Checks the given block is valid next block.
     fn check_next_block(&self, block: &Block<N>) -> Result<()>;
 
+    /// Checks the portions of the given block that do not depend on the latest state of the ledger.
+    fn check_block_stateless(&self, block: &Block<N>) -> Result<()>;
+
     /// Returns a candidate for the next block in the ledger, using a committed subdag and its transmissions.
     #[cfg(feature = "ledger-write")]
     fn prepare_advance_to_next_quorum_block(
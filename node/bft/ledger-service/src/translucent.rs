@@ -67,6 +67,16 @@ impl<N: Network, C: ConsensusStorage<N>> LedgerService<N> for TranslucentLedgerS
         self.inner.latest_block()
     }
 
+    /// Returns the UNIX timestamp (in seconds) of the latest block in the ledger.
+    fn latest_block_timestamp(&self) -> Option<i64> {
+        self.inner.latest_block_timestamp()
+    }
+
+    /// Returns the latest epoch hash, which the solutions for the current epoch must target.
+    fn latest_epoch_hash(&self) -> Result<N::BlockHash> {
+        self.inner.latest_epoch_hash()
+    }
+
     /// Returns the latest cached leader and its associated round.
     fn latest_leader(&self) -> Option<(u64, Address<N>)> {
         self.inner.latest_leader()
@@ -153,7 +163,7 @@ impl<N: Network, C: ConsensusStorage<N>> LedgerService<N> for TranslucentLedgerS
     fn ensure_transmission_is_well_formed(
         &self,
         _transmission_id: TransmissionID<N>,
-        _transmission: &mut Transmission<N>,
+        _transmission: &Transmission<N>,
     ) -> Result<()> {
         Ok(())
     }
@@ -177,6 +187,11 @@ impl<N: Network, C: ConsensusStorage<N>> LedgerService<N> for TranslucentLedgerS
         Ok(())
     }
 
+    /// Always succeeds.
+    fn check_block_stateless(&self, _block: &Block<N>) -> Result<()> {
+        Ok(())
+    }
+
     /// Returns a candidate for the next block in the ledger, using a committed subdag and its transmissions.
     fn prepare_advance_to_next_quorum_block(
         &self,
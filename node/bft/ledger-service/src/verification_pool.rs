@@ -0,0 +1,391 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkvm::prelude::{bail, ensure, Result};
+
+use std::{
+    fmt,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc,
+        OnceLock,
+    },
+};
+use tokio::sync::{oneshot, Semaphore};
+
+/// The verification pool of the node, once initialized.
+static VERIFICATION_POOL: OnceLock<Arc<VerificationPool>> = OnceLock::new();
+
+/// Initializes the verification pool of the node with the given configuration.
+/// This must be called at most once, before the verification pool is first used.
+pub fn init_verification_pool(config: VerificationPoolConfig) -> Result<()> {
+    let pool = Arc::new(VerificationPool::new(config)?);
+    ensure!(VERIFICATION_POOL.set(pool).is_ok(), "The verification pool is already initialized");
+    Ok(())
+}
+
+/// Returns the verification pool of the node, initializing it with the default configuration if needed.
+pub fn verification_pool() -> Arc<VerificationPool> {
+    VERIFICATION_POOL
+        .get_or_init(|| {
+            Arc::new(VerificationPool::new(Default::default()).expect("Failed to initialize the verification pool"))
+        })
+        .clone()
+}
+
+/// The category of a CPU-heavy verification, which is capped in the number of concurrent verifications.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum VerificationCategory {
+    /// The verification of an unconfirmed transaction, at the intake of the memory pool.
+    Transaction,
+    /// The verification of an unconfirmed solution, at the intake of the memory pool.
+    Solution,
+    /// The verification of a batch header or a batch certificate from a peer.
+    Certificate,
+}
+
+impl VerificationCategory {
+    /// The verification categories.
+    pub const ALL: [Self; Self::COUNT] = [Self::Transaction, Self::Solution, Self::Certificate];
+    /// The number of verification categories.
+    pub const COUNT: usize = 3;
+
+    /// Returns the index of the verification category in [`VerificationCategory::ALL`].
+    pub const fn index(self) -> usize {
+        match self {
+            Self::Transaction => 0,
+            Self::Solution => 1,
+            Self::Certificate => 2,
+        }
+    }
+
+    /// Returns the name of the verification category.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Transaction => "transaction",
+            Self::Solution => "solution",
+            Self::Certificate => "certificate",
+        }
+    }
+}
+
+impl fmt::Display for VerificationCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// The configuration of the verification pool.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerificationPoolConfig {
+    /// The number of threads in the verification pool.
+    pub num_threads: usize,
+    /// The maximum number of concurrent verifications of each category, in the order of [`VerificationCategory::ALL`].
+    pub max_concurrency: [usize; VerificationCategory::COUNT],
+}
+
+impl VerificationPoolConfig {
+    /// Returns the configuration of a verification pool with the given number of threads (at least one).
+    /// The transactions and the solutions may each occupy up to half of the threads, so that a burst of
+    /// unconfirmed transmissions does not delay the verification of the certificates of the BFT.
+    pub fn new(num_threads: usize) -> Self {
+        let num_threads = num_threads.max(1);
+        let half = num_threads.div_ceil(2);
+        Self { num_threads, max_concurrency: [half, half, num_threads] }
+    }
+
+    /// Sets the maximum number of concurrent verifications of the given category (at least one).
+    pub fn with_max_concurrency(mut self, category: VerificationCategory, max_concurrency: usize) -> Self {
+        self.max_concurrency[category.index()] = max_concurrency.max(1);
+        self
+    }
+}
+
+impl Default for VerificationPoolConfig {
+    /// Returns the configuration of a verification pool with half of the available cores,
+    /// leaving the other half to the async runtime and the storage.
+    fn default() -> Self {
+        let num_cores = std::thread::available_parallelism().map(|cores| cores.get()).unwrap_or(1);
+        Self::new(num_cores / 2)
+    }
+}
+
+/// A dedicated thread pool for the CPU-heavy verification of the node.
+///
+/// The verifications are submitted from async tasks, which are suspended (rather than blocking a worker of the
+/// async runtime) until their verification completes, so that the network I/O is not delayed under load.
+pub struct VerificationPool {
+    /// The thread pool.
+    pool: rayon::ThreadPool,
+    /// The permits to run a verification, for each category.
+    permits: [Arc<Semaphore>; VerificationCategory::COUNT],
+    /// The number of verifications that are waiting to run, for each category.
+    queue_depths: Arc<[AtomicUsize; VerificationCategory::COUNT]>,
+    /// The number of verifications that started running, for each category.
+    num_verifications: Arc<[AtomicU64; VerificationCategory::COUNT]>,
+}
+
+impl VerificationPool {
+    /// Initializes a new verification pool with the given configuration.
+    pub fn new(config: VerificationPoolConfig) -> Result<Self> {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .stack_size(8 * 1024 * 1024)
+            .num_threads(config.num_threads.max(1))
+            .thread_name(|index| format!("verification-{index}"))
+            .build()?;
+        let permits = config.max_concurrency.map(|max_concurrency| Arc::new(Semaphore::new(max_concurrency.max(1))));
+        Ok(Self { pool, permits, queue_depths: Default::default(), num_verifications: Default::default() })
+    }
+
+    /// Returns the number of threads in the verification pool.
+    pub fn num_threads(&self) -> usize {
+        self.pool.current_num_threads()
+    }
+
+    /// Returns the number of verifications of the given category that are waiting to run.
+    pub fn queue_depth(&self, category: VerificationCategory) -> usize {
+        self.queue_depths[category.index()].load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of verifications of the given category that started running.
+    pub fn num_verifications(&self, category: VerificationCategory) -> u64 {
+        self.num_verifications[category.index()].load(Ordering::Relaxed)
+    }
+
+    /// Runs the given verification on the verification pool, once a permit of its category is available,
+    /// and returns its result. If the verification panics, an error is returned instead.
+    pub async fn run<T: Send + 'static>(
+        &self,
+        category: VerificationCategory,
+        verify: impl FnOnce() -> T + Send + 'static,
+    ) -> Result<T> {
+        // Count the verification as queued, until it starts running (or is cancelled).
+        let queued = Queued::new(self.queue_depths.clone(), category);
+        // Wait for a permit of the category.
+        let Ok(permit) = self.permits[category.index()].clone().acquire_owned().await else {
+            bail!("The verification pool is closed")
+        };
+
+        let (sender, receiver) = oneshot::channel();
+        let num_verifications = self.num_verifications.clone();
+        self.pool.spawn(move || {
+            drop(queued);
+            num_verifications[category.index()].fetch_add(1, Ordering::Relaxed);
+            let result = panic::catch_unwind(AssertUnwindSafe(verify));
+            drop(permit);
+            // The receiver may have been dropped, if the caller is no longer waiting on the result.
+            let _ = sender.send(result);
+        });
+
+        match receiver.await {
+            Ok(Ok(value)) => Ok(value),
+            Ok(Err(_)) => bail!("The {category} verification panicked"),
+            Err(_) => bail!("The {category} verification was dropped"),
+        }
+    }
+}
+
+/// A verification that is waiting to run, which is counted in the queue depth of its category until dropped.
+struct Queued {
+    /// The number of verifications that are waiting to run, for each category.
+    queue_depths: Arc<[AtomicUsize; VerificationCategory::COUNT]>,
+    /// The category of the verification.
+    category: VerificationCategory,
+}
+
+impl Queued {
+    /// Counts a new verification of the given category as queued.
+    fn new(queue_depths: Arc<[AtomicUsize; VerificationCategory::COUNT]>, category: VerificationCategory) -> Self {
+        let _depth = queue_depths[category.index()].fetch_add(1, Ordering::Relaxed) + 1;
+        #[cfg(feature = "metrics")]
+        metrics::gauge(queue_depth_metric(category), _depth as f64);
+        Self { queue_depths, category }
+    }
+}
+
+impl Drop for Queued {
+    fn drop(&mut self) {
+        let _depth = self.queue_depths[self.category.index()].fetch_sub(1, Ordering::Relaxed) - 1;
+        #[cfg(feature = "metrics")]
+        metrics::gauge(queue_depth_metric(self.category), _depth as f64);
+    }
+}
+
+/// Returns the name of the queue depth metric of the given category.
+#[cfg(feature = "metrics")]
+const fn queue_depth_metric(category: VerificationCategory) -> &'static str {
+    match category {
+        VerificationCategory::Transaction => metrics::verification::TRANSACTION_QUEUE_DEPTH,
+        VerificationCategory::Solution => metrics::verification::SOLUTION_QUEUE_DEPTH,
+        VerificationCategory::Certificate => metrics::verification::CERTIFICATE_QUEUE_DEPTH,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::{Duration, Instant};
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+    };
+
+    /// Spins the current thread for the given duration, as a stand-in for a CPU-heavy verification.
+    fn spin(duration: Duration) {
+        let start = Instant::now();
+        while start.elapsed() < duration {
+            std::hint::spin_loop();
+        }
+    }
+
+    #[test]
+    fn test_config() {
+        let config = VerificationPoolConfig::new(5);
+        assert_eq!(config.num_threads, 5);
+        assert_eq!(config.max_concurrency, [3, 3, 5]);
+
+        let config = VerificationPoolConfig::new(0).with_max_concurrency(VerificationCategory::Certificate, 0);
+        assert_eq!(config.num_threads, 1);
+        assert_eq!(config.max_concurrency, [1, 1, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_run() {
+        let pool = VerificationPool::new(VerificationPoolConfig::new(2)).unwrap();
+        assert_eq!(pool.num_threads(), 2);
+
+        // The result of the verification is returned.
+        let thread_name =
+            pool.run(VerificationCategory::Transaction, || std::thread::current().name().map(String::from));
+        assert_eq!(thread_name.await.unwrap().as_deref().map(|name| name.starts_with("verification-")), Some(true));
+        // A panicking verification returns an error.
+        let result = pool.run(VerificationCategory::Solution, || panic!("Invalid solution")).await;
+        assert!(result.is_err());
+        // The pool remains usable after a panic.
+        assert_eq!(pool.run(VerificationCategory::Solution, || 1 + 1).await.unwrap(), 2);
+        // The verifications are counted in their category.
+        assert_eq!(pool.num_verifications(VerificationCategory::Transaction), 1);
+        assert_eq!(pool.num_verifications(VerificationCategory::Solution), 2);
+        assert_eq!(pool.num_verifications(VerificationCategory::Certificate), 0);
+    }
+
+    #[tokio::test]
+    async fn test_max_concurrency() {
+        let config = VerificationPoolConfig::new(4).with_max_concurrency(VerificationCategory::Transaction, 1);
+        let pool = Arc::new(VerificationPool::new(config).unwrap());
+
+        // Track the maximum number of concurrent transaction verifications.
+        let running = Arc::new(AtomicUsize::new(0));
+        let max_running = Arc::new(AtomicUsize::new(0));
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let (pool, running, max_running) = (pool.clone(), running.clone(), max_running.clone());
+                tokio::spawn(async move {
+                    pool.run(VerificationCategory::Transaction, move || {
+                        let num_running = running.fetch_add(1, Ordering::SeqCst) + 1;
+                        max_running.fetch_max(num_running, Ordering::SeqCst);
+                        spin(Duration::from_millis(50));
+                        running.fetch_sub(1, Ordering::SeqCst);
+                    })
+                    .await
+                })
+            })
+            .collect();
+
+        // While the transactions are capped, the certificates still run.
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(pool.queue_depth(VerificationCategory::Transaction) > 0);
+        let start = Instant::now();
+        pool.run(VerificationCategory::Certificate, || ()).await.unwrap();
+        assert!(start.elapsed() < Duration::from_millis(50));
+
+        for handle in handles {
+            handle.await.unwrap().unwrap();
+        }
+        assert_eq!(max_running.load(Ordering::SeqCst), 1);
+        assert_eq!(pool.queue_depth(VerificationCategory::Transaction), 0);
+    }
+
+    #[tokio::test]
+    async fn test_queue_depth_of_cancelled_verification() {
+        let config = VerificationPoolConfig::new(1);
+        let pool = Arc::new(VerificationPool::new(config).unwrap());
+
+        // Occupy the only permit of the solutions.
+        let pool_ = pool.clone();
+        let running = tokio::spawn(async move {
+            pool_.run(VerificationCategory::Solution, || spin(Duration::from_millis(100))).await
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        // Queue another solution, and cancel it while it waits for a permit.
+        let queued = pool.run(VerificationCategory::Solution, || ());
+        assert!(tokio::time::timeout(Duration::from_millis(20), queued).await.is_err());
+        assert_eq!(pool.queue_depth(VerificationCategory::Solution), 0);
+        running.await.unwrap().unwrap();
+    }
+
+    /// Saturates the verification pool, and checks that the async runtime keeps answering pings promptly.
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_ping_latency_under_verification_load() {
+        // The bound on the ping latency, well below the duration of a single verification.
+        const MAX_PING_LATENCY: Duration = Duration::from_millis(100);
+        const VERIFICATION_TIME: Duration = Duration::from_millis(250);
+
+        // Start a peer that answers every ping with a pong.
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let peer_addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut ping = [0u8; 1];
+            while stream.read_exact(&mut ping).await.is_ok() {
+                if stream.write_all(&ping).await.is_err() {
+                    break;
+                }
+            }
+        });
+        let mut stream = TcpStream::connect(peer_addr).await.unwrap();
+
+        // Saturate the verification pool with more verifications than it has threads, across every category.
+        let pool = Arc::new(VerificationPool::new(VerificationPoolConfig::new(2)).unwrap());
+        let verifications: Vec<_> = (0..24)
+            .map(|i| {
+                let pool = pool.clone();
+                let category = VerificationCategory::ALL[i % VerificationCategory::COUNT];
+                tokio::spawn(async move { pool.run(category, || spin(VERIFICATION_TIME)).await })
+            })
+            .collect();
+
+        // Ping the peer while the verifications are running.
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let mut max_latency = Duration::ZERO;
+        for _ in 0..20 {
+            let start = Instant::now();
+            stream.write_all(&[1]).await.unwrap();
+            let mut pong = [0u8; 1];
+            stream.read_exact(&mut pong).await.unwrap();
+            max_latency = max_latency.max(start.elapsed());
+            tokio::time::sleep(Duration::from_millis(25)).await;
+        }
+        // Ensure the verifications were still running throughout the pings.
+        assert!(VerificationCategory::ALL.iter().map(|category| pool.queue_depth(*category)).sum::<usize>() > 0);
+        assert!(max_latency < MAX_PING_LATENCY, "The ping latency reached {max_latency:?} under verification load");
+
+        for verification in verifications {
+            verification.await.unwrap().unwrap();
+        }
+    }
+}
@@ -0,0 +1,116 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "ledger")]
+
+use snarkos_node_bft_ledger_service::blocks_range;
+use snarkvm::{
+    ledger::store::{helpers::memory::ConsensusMemory, ConsensusStore},
+    prelude::{MainnetV0, PrivateKey, TestRng, VM},
+};
+
+use aleo_std::StorageMode;
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+type CurrentNetwork = MainnetV0;
+type CurrentLedger = snarkvm::ledger::Ledger<CurrentNetwork, ConsensusMemory<CurrentNetwork>>;
+
+/// An allocator that tracks the number of live bytes, and the peak since the last reset.
+struct CountingAllocator {
+    current: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+impl CountingAllocator {
+    /// Resets the peak to the number of live bytes.
+    fn reset_peak(&self) {
+        self.peak.store(self.current.load(Ordering::SeqCst), Ordering::SeqCst);
+    }
+
+    /// Returns the peak number of bytes allocated since the last reset, above the given baseline.
+    fn peak_above(&self, baseline: usize) -> usize {
+        self.peak.load(Ordering::SeqCst).saturating_sub(baseline)
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let current = self.current.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+            self.peak.fetch_max(current, Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        self.current.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator { current: AtomicUsize::new(0), peak: AtomicUsize::new(0) };
+
+/// Returns a ledger with the given number of blocks, including the genesis block.
+fn sample_ledger(num_blocks: u32, rng: &mut TestRng) -> CurrentLedger {
+    let private_key = PrivateKey::new(rng).unwrap();
+    let vm = VM::from(ConsensusStore::<CurrentNetwork, ConsensusMemory<CurrentNetwork>>::open(None).unwrap()).unwrap();
+    let genesis = vm.genesis_beacon(&private_key, rng).unwrap();
+    let ledger = CurrentLedger::load(genesis, StorageMode::Production).unwrap();
+    for _ in 1..num_blocks {
+        let block = ledger.prepare_advance_to_next_beacon_block(&private_key, vec![], vec![], vec![], rng).unwrap();
+        ledger.advance_to_next_block(&block).unwrap();
+    }
+    ledger
+}
+
+/// This is the only test in this binary, so that the allocations of other tests are not counted.
+#[test]
+fn test_blocks_range_peak_allocations() {
+    const NUM_BLOCKS: u32 = 16;
+
+    let rng = &mut TestRng::default();
+    let ledger = sample_ledger(NUM_BLOCKS, rng);
+    // Load the blocks once, so that any lazily-initialized state is not counted.
+    let expected = ledger.get_blocks(0..NUM_BLOCKS).unwrap();
+
+    // Measure the peak allocations of loading the range at once.
+    let baseline = ALLOCATOR.current.load(Ordering::SeqCst);
+    ALLOCATOR.reset_peak();
+    let blocks = ledger.get_blocks(0..NUM_BLOCKS).unwrap();
+    let eager_peak = ALLOCATOR.peak_above(baseline);
+    assert_eq!(blocks, expected);
+    drop(blocks);
+
+    // Measure the peak allocations of streaming the range, checking each block as it is loaded.
+    let baseline = ALLOCATOR.current.load(Ordering::SeqCst);
+    ALLOCATOR.reset_peak();
+    let mut num_blocks = 0;
+    for (block, expected) in blocks_range(ledger.clone(), 0..NUM_BLOCKS).zip(&expected) {
+        assert_eq!(&block.unwrap(), expected);
+        num_blocks += 1;
+    }
+    let streaming_peak = ALLOCATOR.peak_above(baseline);
+    assert_eq!(num_blocks, NUM_BLOCKS);
+
+    // Ensure streaming holds fewer bytes at once than loading the range into memory.
+    assert!(
+        streaming_peak < eager_peak,
+        "Streaming peaked at {streaming_peak} bytes, while loading the range peaked at {eager_peak} bytes"
+    );
+}
@@ -16,18 +16,22 @@ use crate::{
     helpers::{
         fmt_id,
         init_bft_channels,
-        now,
         BFTReceiver,
         ConsensusSender,
+        DagSnapshot,
+        EmptyBatchMode,
         PrimaryReceiver,
         PrimarySender,
+        PriorityLaneConfig,
+        RecordedEvent,
+        Signer,
         Storage,
         DAG,
     },
     Primary,
+    CONSENSUS_LAG_WARNING_INTERVAL_IN_MS,
     MAX_LEADER_CERTIFICATE_DELAY_IN_SECS,
 };
-use snarkos_account::Account;
 use snarkos_node_bft_ledger_service::LedgerService;
 use snarkvm::{
     console::account::Address,
@@ -51,9 +55,10 @@ use std::{
         atomic::{AtomicI64, Ordering},
         Arc,
     },
+    time::Duration,
 };
 use tokio::{
-    sync::{oneshot, Mutex as TMutex, OnceCell},
+    sync::{mpsc, oneshot, Mutex as TMutex, OnceCell},
     task::JoinHandle,
 };
 
@@ -78,7 +83,7 @@ pub struct BFT<N: Network> {
 impl<N: Network> BFT<N> {
     /// Initializes a new instance of the BFT.
     pub fn new(
-        account: Account<N>,
+        signer: impl Signer<N> + 'static,
         storage: Storage<N>,
         ledger: Arc<dyn LedgerService<N>>,
         ip: Option<SocketAddr>,
@@ -86,7 +91,7 @@ impl<N: Network> BFT<N> {
         dev: Option<u16>,
     ) -> Result<Self> {
         Ok(Self {
-            primary: Primary::new(account, storage, ledger, ip, trusted_validators, dev)?,
+            primary: Primary::new(signer, storage, ledger, ip, trusted_validators, dev)?,
             dag: Default::default(),
             leader_certificate: Default::default(),
             leader_certificate_timer: Default::default(),
@@ -96,6 +101,18 @@ impl<N: Network> BFT<N> {
         })
     }
 
+    /// Sets the quotas of the priority lane, for consensus-critical transmissions.
+    pub fn with_priority_lane(mut self, config: PriorityLaneConfig) -> Self {
+        self.primary = self.primary.with_priority_lane(config);
+        self
+    }
+
+    /// Sets the behavior of the primary when it has no unconfirmed transactions to propose.
+    pub fn with_empty_batch_mode(mut self, mode: EmptyBatchMode) -> Self {
+        self.primary = self.primary.with_empty_batch_mode(mode);
+        self
+    }
+
     /// Run the BFT instance.
     pub async fn run(
         &mut self,
@@ -118,6 +135,18 @@ impl<N: Network> BFT<N> {
         Ok(())
     }
 
+    /// Prepares the BFT to replay a recorded event log, without running the primary.
+    pub(crate) fn prepare_replay(&mut self, consensus_sender: ConsensusSender<N>) {
+        // Initialize the BFT channels.
+        let (bft_sender, bft_receiver) = init_bft_channels::<N>();
+        // First, start the BFT handlers.
+        self.start_handlers(bft_receiver);
+        // Next, prepare the primary to replay.
+        self.primary.prepare_replay(bft_sender);
+        // Lastly, set the consensus sender.
+        self.consensus_sender.set(consensus_sender).expect("Consensus sender already set");
+    }
+
     /// Returns `true` if the primary is synced.
     pub fn is_synced(&self) -> bool {
         self.primary.is_synced()
@@ -147,6 +176,15 @@ impl<N: Network> BFT<N> {
     pub const fn leader_certificate(&self) -> &Arc<RwLock<Option<BatchCertificate<N>>>> {
         &self.leader_certificate
     }
+
+    /// Returns a snapshot of the DAG for the last `num_rounds` rounds, marking the certificates
+    /// that have been committed by the BFT or into a block in the ledger.
+    pub fn dag_snapshot(&self, num_rounds: u64) -> DagSnapshot<N> {
+        self.storage().dag_snapshot(num_rounds, |certificate| {
+            self.dag.read().is_recently_committed(certificate.round(), certificate.id())
+                || self.ledger().contains_certificate(&certificate.id()).unwrap_or(false)
+        })
+    }
 }
 
 impl<N: Network> BFT<N> {
@@ -191,6 +229,31 @@ impl<N: Network> BFT<N> {
     pub fn unconfirmed_transactions(&self) -> impl '_ + Iterator<Item = (N::TransactionID, Data<Transaction<N>>)> {
         self.primary.unconfirmed_transactions()
     }
+
+    /// Returns `true` if the given transmission is in the in-flight batch proposal, or in a certified batch.
+    pub fn is_transmission_in_flight(&self, transmission_id: impl Into<TransmissionID<N>>) -> bool {
+        self.primary.is_transmission_in_flight(transmission_id)
+    }
+
+    /// Returns `true` if the given transaction is in the memory pool, or in flight.
+    pub fn contains_unconfirmed_transaction(&self, transaction_id: N::TransactionID) -> bool {
+        self.primary.contains_unconfirmed_transaction(transaction_id)
+    }
+
+    /// Returns the unconfirmed solution, if it is in the memory pool, or in flight.
+    pub fn get_unconfirmed_solution(&self, solution_id: SolutionID<N>) -> Option<Data<Solution<N>>> {
+        self.primary.get_unconfirmed_solution(solution_id)
+    }
+
+    /// Returns the unconfirmed transaction, if it is in the memory pool, or in flight.
+    pub fn get_unconfirmed_transaction(&self, transaction_id: N::TransactionID) -> Option<Data<Transaction<N>>> {
+        self.primary.get_unconfirmed_transaction(transaction_id)
+    }
+
+    /// Removes the given unconfirmed transaction from the memory pool, returning `true` if it was removed.
+    pub fn remove_unconfirmed_transaction(&self, transaction_id: N::TransactionID) -> Result<bool> {
+        self.primary.remove_unconfirmed_transaction(transaction_id)
+    }
 }
 
 impl<N: Network> BFT<N> {
@@ -214,7 +277,7 @@ impl<N: Network> BFT<N> {
             let start = self.leader_certificate_timer.load(Ordering::SeqCst);
             // Only log if the timer was set, otherwise we get a time difference since the EPOCH.
             if start > 0 {
-                let end = now();
+                let end = self.storage().clock().now();
                 let elapsed = std::time::Duration::from_secs((end - start) as u64);
                 metrics::histogram(metrics::bft::COMMIT_ROUNDS_LATENCY, elapsed.as_secs_f64());
             }
@@ -254,7 +317,7 @@ impl<N: Network> BFT<N> {
                 return false;
             }
             // Update the timer for the leader certificate.
-            self.leader_certificate_timer.store(now(), Ordering::SeqCst);
+            self.leader_certificate_timer.store(self.storage().clock().now(), Ordering::SeqCst);
         }
 
         is_ready
@@ -356,7 +419,8 @@ impl<N: Network> BFT<N> {
 
     /// Returns `true` if the timer for the leader certificate has expired.
     fn is_timer_expired(&self) -> bool {
-        self.leader_certificate_timer.load(Ordering::SeqCst) + MAX_LEADER_CERTIFICATE_DELAY_IN_SECS <= now()
+        self.leader_certificate_timer.load(Ordering::SeqCst) + MAX_LEADER_CERTIFICATE_DELAY_IN_SECS
+            <= self.storage().clock().now()
     }
 
     /// Returns 'true' if the quorum threshold `(2f + 1)` is reached for this round under one of the following conditions:
@@ -581,8 +645,8 @@ impl<N: Network> BFT<N> {
 
         // Iterate over the leader certificates to commit.
         for leader_certificate in leader_certificates.into_iter().rev() {
-            // Retrieve the leader certificate round.
-            let leader_round = leader_certificate.round();
+            // Retrieve the leader certificate round and ID.
+            let (leader_round, leader_certificate_id) = (leader_certificate.round(), leader_certificate.id());
             // Compute the commit subdag.
             let commit_subdag = match self.order_dag_with_dfs::<ALLOW_LEDGER_ACCESS>(leader_certificate) {
                 Ok(subdag) => subdag,
@@ -634,11 +698,32 @@ impl<N: Network> BFT<N> {
             // Trigger consensus.
             if let Some(consensus_sender) = self.consensus_sender.get() {
                 // Initialize a callback sender and receiver.
-                let (callback_sender, callback_receiver) = oneshot::channel();
+                let (callback_sender, mut callback_receiver) = oneshot::channel();
                 // Send the subdag and transmissions to consensus.
-                consensus_sender.tx_consensus_subdag.send((subdag, transmissions, callback_sender)).await?;
-                // Await the callback to continue.
-                match callback_receiver.await {
+                // Note: The channel is bounded, so that the BFT pauses committing while block production is behind,
+                // instead of buffering the subdags. This cannot deadlock with the reinsertion of the transmissions
+                // of a failed block, as the primary handles those in separate tasks, without the BFT lock.
+                let message = (subdag, transmissions, callback_sender);
+                let message = match consensus_sender.tx_consensus_subdag.try_send(message) {
+                    Ok(()) => None,
+                    Err(mpsc::error::TrySendError::Full(message)) => Some(message),
+                    Err(mpsc::error::TrySendError::Closed(_)) => {
+                        bail!("BFT failed to send the subdag for round {anchor_round} - consensus has stopped")
+                    }
+                };
+                if let Some(message) = message {
+                    self.warn_consensus_lag(anchor_round);
+                    consensus_sender.tx_consensus_subdag.send(message).await?;
+                }
+                // Await the callback to continue, warning periodically while block production is behind.
+                let interval = Duration::from_millis(CONSENSUS_LAG_WARNING_INTERVAL_IN_MS);
+                let callback = loop {
+                    match tokio::time::timeout(interval, &mut callback_receiver).await {
+                        Ok(callback) => break callback,
+                        Err(_) => self.warn_consensus_lag(anchor_round),
+                    }
+                };
+                match callback {
                     Ok(Ok(())) => (), // continue
                     Ok(Err(e)) => {
                         error!("BFT failed to advance the subdag for round {anchor_round} - {e}");
@@ -651,6 +736,10 @@ impl<N: Network> BFT<N> {
                 }
             }
 
+            // Record the commit.
+            self.primary
+                .gateway()
+                .record_event(|| RecordedEvent::Committed { round: anchor_round, leader_certificate_id });
             info!(
                 "\n\nCommitting a subdag from round {anchor_round} with {num_transmissions} transmissions: {subdag_metadata:?}\n"
             );
@@ -667,6 +756,14 @@ impl<N: Network> BFT<N> {
         Ok(())
     }
 
+    /// Warns that the BFT paused committing, as block production is behind the subdag at the given anchor round.
+    fn warn_consensus_lag(&self, anchor_round: u64) {
+        let lag = self.primary.current_round().saturating_sub(anchor_round);
+        warn!(
+            "Block production is behind the BFT by {lag} rounds (at the subdag of round {anchor_round}) - pausing commits until it catches up"
+        );
+    }
+
     /// Returns the subdag of batch certificates to commit.
     fn order_dag_with_dfs<const ALLOW_LEDGER_ACCESS: bool>(
         &self,
@@ -1126,7 +1223,7 @@ mod tests {
 
         // Retrieve the leader certificate.
         let leader = committee.get_leader(2).unwrap();
-        let leader_certificate = storage.get_certificate_for_round_with_author(2, leader).unwrap();
+        let leader_certificate = storage.get_certificate_for_round_and_author(2, leader).unwrap();
 
         // Initialize the BFT.
         let account = Account::new(rng)?;
@@ -1313,7 +1410,7 @@ mod tests {
 
         // Get the leader certificate.
         let leader = committee.get_leader(commit_round).unwrap();
-        let leader_certificate = storage.get_certificate_for_round_with_author(commit_round, leader).unwrap();
+        let leader_certificate = storage.get_certificate_for_round_and_author(commit_round, leader).unwrap();
 
         // Initialize the BFT.
         let account = Account::new(rng)?;
@@ -1338,6 +1435,105 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_commit_pauses_while_block_production_is_behind() -> Result<()> {
+        use crate::{
+            helpers::{init_consensus_channels, MAX_CONSENSUS_CHANNEL_SIZE},
+            CONSENSUS_LAG_WARNING_INTERVAL_IN_MS,
+        };
+        use std::{
+            sync::atomic::{AtomicUsize, Ordering},
+            time::Duration,
+        };
+
+        let rng = &mut TestRng::default();
+
+        // Initialize the round parameters.
+        let max_gc_rounds = 1;
+        let committee_round = 0;
+        let commit_round = 2;
+        let current_round = commit_round + 1;
+
+        // Sample the certificates.
+        let (_, certificates) = snarkvm::ledger::narwhal::batch_certificate::test_helpers::sample_batch_certificate_with_previous_certificates(
+            current_round,
+            rng,
+        );
+
+        // Initialize the committee.
+        let committee = snarkvm::ledger::committee::test_helpers::sample_committee_for_round_and_members(
+            committee_round,
+            vec![
+                certificates[0].author(),
+                certificates[1].author(),
+                certificates[2].author(),
+                certificates[3].author(),
+            ],
+            rng,
+        );
+
+        // Initialize the ledger and the storage.
+        let ledger = Arc::new(MockLedgerService::new(committee.clone()));
+        let transmissions = Arc::new(BFTMemoryService::new());
+        let storage = Storage::new(ledger.clone(), transmissions, max_gc_rounds);
+        for certificate in certificates.iter() {
+            storage.testing_only_insert_certificate_testing_only(certificate.clone());
+        }
+
+        // Get the leader certificate.
+        let leader = committee.get_leader(commit_round).unwrap();
+        let leader_certificate = storage.get_certificate_for_round_and_author(commit_round, leader).unwrap();
+
+        // Initialize the BFT, and insert the certificates.
+        let account = Account::new(rng)?;
+        let bft = BFT::new(account, storage.clone(), ledger, None, &[], None)?;
+        *bft.dag.write() = crate::helpers::dag::test_helpers::mock_dag_with_modified_last_committed_round(commit_round);
+        for certificate in certificates {
+            assert!(bft.update_dag::<false>(certificate).await.is_ok());
+        }
+
+        // Initialize a mock consensus, whose first block is delayed, as if its disk stalled.
+        let (consensus_sender, mut consensus_receiver) = init_consensus_channels::<CurrentNetwork>();
+        bft.consensus_sender.set(consensus_sender).unwrap();
+        let delay = Duration::from_millis(CONSENSUS_LAG_WARNING_INTERVAL_IN_MS + 500);
+        let num_blocks = Arc::new(AtomicUsize::new(0));
+        let num_blocks_ = num_blocks.clone();
+        let consensus = tokio::spawn(async move {
+            while let Some((_subdag, _transmissions, callback)) = consensus_receiver.recv().await {
+                if num_blocks_.load(Ordering::SeqCst) == 0 {
+                    tokio::time::sleep(delay).await;
+                }
+                num_blocks_.fetch_add(1, Ordering::SeqCst);
+                callback.send(Ok(())).ok();
+            }
+        });
+
+        // Commit the leader certificate, while block production is delayed.
+        let bft_ = bft.clone();
+        let commit = tokio::spawn(async move { bft_.commit_leader_certificate::<false>(leader_certificate).await });
+        tokio::time::sleep(delay / 2).await;
+
+        // Ensure the BFT paused committing, and buffers no more than the capacity of the channel.
+        let sender = &bft.consensus_sender.get().unwrap().tx_consensus_subdag;
+        assert!(!commit.is_finished());
+        assert_eq!(sender.max_capacity(), MAX_CONSENSUS_CHANNEL_SIZE);
+        assert!(sender.max_capacity() - sender.capacity() <= MAX_CONSENSUS_CHANNEL_SIZE);
+        assert_eq!(num_blocks.load(Ordering::SeqCst), 0);
+        assert_eq!(bft.storage().gc_round(), committee_round.saturating_sub(max_gc_rounds));
+
+        // Ensure the BFT resumes once the block is produced, and warned of the lag in the meantime.
+        commit.await??;
+        assert!(logs_contain("Block production is behind the BFT"));
+        assert_eq!(num_blocks.load(Ordering::SeqCst), 1);
+        assert_eq!(bft.storage().gc_round(), commit_round - max_gc_rounds);
+        // Ensure nothing is left buffered in the channel.
+        assert_eq!(sender.capacity(), MAX_CONSENSUS_CHANNEL_SIZE);
+
+        consensus.abort();
+        Ok(())
+    }
+
     #[tokio::test]
     #[tracing_test::traced_test]
     async fn test_sync_bft_dag_at_bootup() -> Result<()> {
@@ -1379,7 +1575,7 @@ mod tests {
 
         // Get the leader certificate.
         let leader = committee.get_leader(commit_round).unwrap();
-        let leader_certificate = storage.get_certificate_for_round_with_author(commit_round, leader).unwrap();
+        let leader_certificate = storage.get_certificate_for_round_and_author(commit_round, leader).unwrap();
 
         // Initialize the BFT.
         let account = Account::new(rng)?;
@@ -1550,8 +1746,8 @@ mod tests {
             storage.testing_only_insert_certificate_testing_only(certificate.clone());
         }
         // Get the leader certificates.
-        let leader_certificate = storage.get_certificate_for_round_with_author(commit_round, leader).unwrap();
-        let next_leader_certificate = storage.get_certificate_for_round_with_author(next_round, next_leader).unwrap();
+        let leader_certificate = storage.get_certificate_for_round_and_author(commit_round, leader).unwrap();
+        let next_leader_certificate = storage.get_certificate_for_round_and_author(next_round, next_leader).unwrap();
 
         // Initialize the BFT without bootup.
         let account = Account::new(rng)?;
@@ -1781,7 +1977,7 @@ mod tests {
         }
 
         // Get the next leader certificate to commit.
-        let next_leader_certificate = storage.get_certificate_for_round_with_author(next_round, next_leader).unwrap();
+        let next_leader_certificate = storage.get_certificate_for_round_and_author(next_round, next_leader).unwrap();
         let commit_subdag = bootup_bft.order_dag_with_dfs::<false>(next_leader_certificate).unwrap();
         let committed_certificates = commit_subdag.values().flatten();
 
@@ -14,15 +14,29 @@
 
 use crate::{
     events::{EventCodec, PrimaryPing},
-    helpers::{assign_to_worker, Cache, PrimarySender, Resolver, Storage, SyncSender, WorkerSender},
-    spawn_blocking,
+    helpers::{
+        assign_to_worker,
+        Cache,
+        EventRecorder,
+        PrimarySender,
+        RecordedEvent,
+        Resolver,
+        SequenceStatus,
+        SequenceTracker,
+        Signer,
+        SigningContext,
+        Storage,
+        SyncSender,
+        WorkerSender,
+    },
     Worker,
     CONTEXT,
     MAX_BATCH_DELAY_IN_MS,
     MEMORY_POOL_PORT,
 };
-use snarkos_account::Account;
 use snarkos_node_bft_events::{
+    deserialize_data,
+    ensure_data_size,
     BlockRequest,
     BlockResponse,
     CertificateRequest,
@@ -33,6 +47,9 @@ use snarkos_node_bft_events::{
     DisconnectReason,
     Event,
     EventTrait,
+    Feature,
+    SequencedEvent,
+    SequencedEventCodec,
     TransmissionRequest,
     TransmissionResponse,
     ValidatorsRequest,
@@ -47,6 +64,7 @@ use snarkos_node_tcp::{
     Config,
     Connection,
     ConnectionSide,
+    QueuePolicy,
     Tcp,
     P2P,
 };
@@ -54,7 +72,7 @@ use snarkvm::{
     console::prelude::*,
     ledger::{
         committee::Committee,
-        narwhal::{BatchHeader, Data},
+        narwhal::{BatchHeader, Data, Transmission},
     },
     prelude::Address,
 };
@@ -64,7 +82,15 @@ use futures::SinkExt;
 use indexmap::{IndexMap, IndexSet};
 use parking_lot::{Mutex, RwLock};
 use rand::seq::{IteratorRandom, SliceRandom};
-use std::{collections::HashSet, future::Future, io, net::SocketAddr, sync::Arc, time::Duration};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    future::Future,
+    io,
+    net::SocketAddr,
+    sync::Arc,
+    time::Duration,
+};
 use tokio::{
     net::TcpStream,
     sync::{oneshot, OnceCell},
@@ -94,12 +120,18 @@ const MAX_VALIDATORS_TO_SEND: usize = 200;
 pub trait Transport<N: Network>: Send + Sync {
     async fn send(&self, peer_ip: SocketAddr, event: Event<N>) -> Option<oneshot::Receiver<io::Result<()>>>;
     fn broadcast(&self, event: Event<N>);
+
+    /// Returns `true` if the given peer supports the given feature of the event protocol.
+    /// By default, the peers are assumed to be on the current event version.
+    fn supports_feature(&self, _peer_ip: SocketAddr, feature: Feature) -> bool {
+        feature.is_enabled(Event::<N>::VERSION)
+    }
 }
 
 #[derive(Clone)]
 pub struct Gateway<N: Network> {
-    /// The account of the node.
-    account: Account<N>,
+    /// The signer of the node.
+    signer: Arc<dyn Signer<N>>,
     /// The storage.
     storage: Storage<N>,
     /// The ledger service.
@@ -110,10 +142,14 @@ pub struct Gateway<N: Network> {
     cache: Arc<Cache<N>>,
     /// The resolver.
     resolver: Arc<Resolver<N>>,
+    /// The tracker of the sequence numbers of the events received from each peer.
+    sequences: Arc<SequenceTracker>,
     /// The set of trusted validators.
     trusted_validators: IndexSet<SocketAddr>,
     /// The map of connected peer IPs to their peer handlers.
     connected_peers: Arc<RwLock<IndexSet<SocketAddr>>>,
+    /// The event versions negotiated with the connected peers.
+    event_versions: Arc<RwLock<HashMap<SocketAddr, u32>>>,
     /// The set of handshaking peers. While `Tcp` already recognizes the connecting IP addresses
     /// and prevents duplicate outbound connection attempts to the same IP address, it is unable to
     /// prevent simultaneous "two-way" connections between two peers (i.e. both nodes simultaneously
@@ -125,6 +161,8 @@ pub struct Gateway<N: Network> {
     worker_senders: Arc<OnceCell<IndexMap<u8, WorkerSender<N>>>>,
     /// The sync sender.
     sync_sender: Arc<OnceCell<SyncSender<N>>>,
+    /// The event recorder, if the events that mutate the state of the memory pool are recorded.
+    event_recorder: Arc<OnceCell<EventRecorder<N>>>,
     /// The spawned handles.
     handles: Arc<Mutex<Vec<JoinHandle<()>>>>,
     /// The development mode.
@@ -134,7 +172,7 @@ pub struct Gateway<N: Network> {
 impl<N: Network> Gateway<N> {
     /// Initializes a new gateway.
     pub fn new(
-        account: Account<N>,
+        signer: impl Signer<N> + 'static,
         storage: Storage<N>,
         ledger: Arc<dyn LedgerService<N>>,
         ip: Option<SocketAddr>,
@@ -147,22 +185,31 @@ impl<N: Network> Gateway<N> {
             (None, None) => SocketAddr::from_str(&format!("0.0.0.0:{}", MEMORY_POOL_PORT))?,
             (Some(ip), _) => ip,
         };
-        // Initialize the TCP stack.
-        let tcp = Tcp::new(Config::new(ip, Committee::<N>::MAX_COMMITTEE_SIZE));
+        // Initialize the TCP stack, exempting the trusted validators from the limit of connections per IP.
+        let mut config = Config::new(ip, Committee::<N>::MAX_COMMITTEE_SIZE);
+        config.trusted_ips = trusted_validators.iter().map(|validator_ip| validator_ip.ip()).collect();
+        // Note: The validators of a development network run on a single host.
+        if dev.is_some() {
+            config.max_connections_per_ip = Committee::<N>::MAX_COMMITTEE_SIZE;
+        }
+        let tcp = Tcp::new(config);
         // Return the gateway.
         Ok(Self {
-            account,
+            signer: Arc::new(signer),
             storage,
             ledger,
             tcp,
             cache: Default::default(),
             resolver: Default::default(),
+            sequences: Default::default(),
             trusted_validators: trusted_validators.iter().copied().collect(),
             connected_peers: Default::default(),
+            event_versions: Default::default(),
             connecting_peers: Default::default(),
             primary_sender: Default::default(),
             worker_senders: Default::default(),
             sync_sender: Default::default(),
+            event_recorder: Default::default(),
             handles: Default::default(),
             dev,
         })
@@ -253,12 +300,17 @@ impl<N: Network> CommunicationService for Gateway<N> {
     async fn send(&self, peer_ip: SocketAddr, message: Self::Message) -> Option<oneshot::Receiver<io::Result<()>>> {
         Transport::send(self, peer_ip, message).await
     }
+
+    /// Returns `true` if the outbound queue of the specified peer is full.
+    fn is_congested(&self, peer_ip: SocketAddr) -> bool {
+        self.resolver.get_ambiguous(peer_ip).is_some_and(|peer_addr| self.tcp.is_congested(peer_addr))
+    }
 }
 
 impl<N: Network> Gateway<N> {
-    /// Returns the account of the node.
-    pub const fn account(&self) -> &Account<N> {
-        &self.account
+    /// Returns the signer of the node.
+    pub const fn signer(&self) -> &Arc<dyn Signer<N>> {
+        &self.signer
     }
 
     /// Returns the IP address of this node.
@@ -282,6 +334,20 @@ impl<N: Network> Gateway<N> {
         &self.resolver
     }
 
+    /// Sets the event recorder, to record the events that mutate the state of the memory pool.
+    pub fn set_event_recorder(&self, recorder: EventRecorder<N>) -> Result<()> {
+        self.event_recorder.set(recorder).map_err(|_| anyhow!("The event recorder is already set"))
+    }
+
+    /// Records the given event, if an event recorder is set.
+    pub(crate) fn record_event(&self, event: impl FnOnce() -> RecordedEvent<N>) {
+        if let Some(recorder) = self.event_recorder.get() {
+            if let Err(e) = recorder.record(self.storage.clock().now(), event()) {
+                warn!("{CONTEXT} Failed to record an event - {e}");
+            }
+        }
+    }
+
     /// Returns the primary sender.
     pub fn primary_sender(&self) -> &PrimarySender<N> {
         self.primary_sender.get().expect("Primary sender not set in gateway")
@@ -313,6 +379,12 @@ impl<N: Network> Gateway<N> {
         self.connected_peers.read().contains(&ip)
     }
 
+    /// Returns the event version negotiated with the given peer IP,
+    /// or the current event version if the peer did not connect through a handshake.
+    pub fn event_version(&self, ip: SocketAddr) -> u32 {
+        self.event_versions.read().get(&ip).copied().unwrap_or(Event::<N>::VERSION)
+    }
+
     /// Returns `true` if the node is connecting to the given peer IP.
     pub fn is_connecting_ip(&self, ip: SocketAddr) -> bool {
         self.connecting_peers.lock().contains(&ip)
@@ -460,6 +532,22 @@ impl<N: Network> Gateway<N> {
         metrics::gauge(metrics::bft::CONNECTING, self.connecting_peers.lock().len() as f64);
     }
 
+    /// Returns `true` if the events exchanged with the given peer address are sent in sequenced frames.
+    fn is_sequenced(&self, peer_addr: SocketAddr) -> bool {
+        let version = self.resolver.get_listener(peer_addr).map(|peer_ip| self.event_version(peer_ip));
+        Feature::SequencedFrames.is_enabled(version.unwrap_or(Event::<N>::VERSION))
+    }
+
+    /// Records the event version negotiated with the given peer, which is on the given event version.
+    fn insert_event_version(&self, peer_ip: SocketAddr, version: u32) {
+        if let Some(version) = Event::<N>::negotiate(version) {
+            if version < Event::<N>::VERSION {
+                debug!("{CONTEXT} Gateway is connecting to '{peer_ip}' on the older event version {version}");
+            }
+            self.event_versions.write().insert(peer_ip, version);
+        }
+    }
+
     /// Inserts the given peer into the connected peers.
     #[cfg(not(test))]
     fn insert_connected_peer(&self, peer_ip: SocketAddr, peer_addr: SocketAddr, address: Address<N>) {
@@ -496,6 +584,7 @@ impl<N: Network> Gateway<N> {
         self.resolver.remove_peer(peer_ip);
         // Remove this peer from the connected peers, if it exists.
         self.connected_peers.write().shift_remove(&peer_ip);
+        self.event_versions.write().remove(&peer_ip);
         #[cfg(feature = "metrics")]
         self.update_metrics();
     }
@@ -627,7 +716,7 @@ impl<N: Network> Gateway<N> {
                     // Retrieve the block response.
                     let BlockResponse { request, blocks } = block_response;
                     // Perform the deferred non-blocking deserialization of the blocks.
-                    let blocks = blocks.deserialize().await.map_err(|error| anyhow!("[BlockResponse] {error}"))?;
+                    let blocks = deserialize_data(blocks).await.map_err(|error| anyhow!("[BlockResponse] {error}"))?;
                     // Ensure the block response is well-formed.
                     blocks.ensure_response_is_well_formed(peer_ip, request.start_height, request.end_height)?;
                     // Send the blocks to the sync module.
@@ -646,6 +735,8 @@ impl<N: Network> Gateway<N> {
                 Ok(())
             }
             Event::CertificateResponse(certificate_response) => {
+                // Record the certificate, so it can be served when the event log is replayed.
+                self.record_event(|| RecordedEvent::Certificate(certificate_response.certificate.clone()));
                 // If a sync sender was provided, send the certificate response to the sync module.
                 if let Some(sync_sender) = self.sync_sender.get() {
                     // Send the certificate response to the sync module.
@@ -660,11 +751,13 @@ impl<N: Network> Gateway<N> {
             Event::Disconnect(disconnect) => {
                 bail!("{CONTEXT} {:?}", disconnect.reason)
             }
+            // The session of the peer was already reset when sequencing the event.
+            Event::Hello(..) => Ok(()),
             Event::PrimaryPing(ping) => {
                 let PrimaryPing { version, block_locators, primary_certificate } = ping;
 
                 // Ensure the event version is not outdated.
-                if version < Event::<N>::VERSION {
+                if version < Event::<N>::MINIMUM_VERSION {
                     bail!("Dropping '{peer_ip}' on event version {version} (outdated)");
                 }
 
@@ -680,6 +773,32 @@ impl<N: Network> Gateway<N> {
                 let _ = self.primary_sender().tx_primary_ping.send((peer_ip, primary_certificate)).await;
                 Ok(())
             }
+            Event::TransmissionChunkRequest(request) => {
+                // Determine the worker ID.
+                let Ok(worker_id) = assign_to_worker(request.transmission_id, self.num_workers()) else {
+                    warn!("{CONTEXT} Unable to assign transmission ID '{}' to a worker", request.transmission_id);
+                    return Ok(());
+                };
+                // Send the transmission chunk request to the worker.
+                if let Some(sender) = self.get_worker_sender(worker_id) {
+                    let _ = sender.tx_transmission_chunk_request.send((peer_ip, request)).await;
+                }
+                Ok(())
+            }
+            Event::TransmissionChunkResponse(response) => {
+                // Determine the worker ID.
+                let Ok(worker_id) = assign_to_worker(response.transmission_id, self.num_workers()) else {
+                    warn!("{CONTEXT} Unable to assign transmission ID '{}' to a worker", response.transmission_id);
+                    return Ok(());
+                };
+                // Send the transmission chunk response to the worker.
+                // Note: The size of each chunk is bounded on deserialization, and the size of the reassembled
+                // transmission is checked by the worker.
+                if let Some(sender) = self.get_worker_sender(worker_id) {
+                    let _ = sender.tx_transmission_chunk_response.send((peer_ip, response)).await;
+                }
+                Ok(())
+            }
             Event::TransmissionRequest(request) => {
                 // TODO (howardwu): Add rate limiting checks on this event, on a per-peer basis.
                 // Determine the worker ID.
@@ -695,6 +814,17 @@ impl<N: Network> Gateway<N> {
                 Ok(())
             }
             Event::TransmissionResponse(response) => {
+                // Ensure the transmission does not exceed the maximum size, before it is deserialized by the worker.
+                match &response.transmission {
+                    Transmission::Solution(solution) => ensure_data_size(solution)?,
+                    Transmission::Transaction(transaction) => ensure_data_size(transaction)?,
+                    Transmission::Ratification => {}
+                }
+                // Record the transmission, so it can be served when the event log is replayed.
+                self.record_event(|| RecordedEvent::Transmission {
+                    transmission_id: response.transmission_id,
+                    transmission: response.transmission.clone(),
+                });
                 // Determine the worker ID.
                 let Ok(worker_id) = assign_to_worker(response.transmission_id, self.num_workers()) else {
                     warn!("{CONTEXT} Unable to assign transmission ID '{}' to a worker", response.transmission_id);
@@ -768,7 +898,7 @@ impl<N: Network> Gateway<N> {
                             }
 
                             // Ensure the validator address is not this node.
-                            if self_.account.address() == validator_address {
+                            if self_.signer.address() == validator_address {
                                 continue;
                             }
                             // Ensure the validator IP is not already connected or connecting.
@@ -908,6 +1038,26 @@ impl<N: Network> Gateway<N> {
         }
     }
 
+    /// Attempts to re-dial the committee members that are not connected.
+    ///
+    /// This reconnects to any disconnected trusted validators, and sends a `ValidatorsRequest`
+    /// to every connected validator, in order to discover the committee members that are missing.
+    pub fn redial_validators(&self) {
+        // Reconnect to any disconnected trusted validators.
+        self.handle_trusted_validators();
+        // Retrieve the connected validators.
+        let validators = self.connected_peers().read().clone();
+        for validator_ip in validators {
+            let self_ = self.clone();
+            tokio::spawn(async move {
+                // Increment the number of outbound validators requests for this validator.
+                self_.cache.increment_outbound_validators_requests(validator_ip);
+                // Send a `ValidatorsRequest` to the validator.
+                let _ = Transport::send(&self_, validator_ip, Event::ValidatorsRequest(ValidatorsRequest)).await;
+            });
+        }
+    }
+
     /// This function attempts to disconnect any validators that are not in the current committee.
     fn handle_unauthorized_validators(&self) {
         let self_ = self.clone();
@@ -982,7 +1132,13 @@ impl<N: Network> Transport<N> for Gateway<N> {
             send!(self, insert_outbound_certificate, CACHE_REQUESTS_INTERVAL, max_cache_certificates)
         }
         // If the event type is a transmission request, increment the cache.
-        else if matches!(event, Event::TransmissionRequest(_)) | matches!(event, Event::TransmissionResponse(_)) {
+        else if matches!(
+            event,
+            Event::TransmissionRequest(_)
+                | Event::TransmissionResponse(_)
+                | Event::TransmissionChunkRequest(_)
+                | Event::TransmissionChunkResponse(_)
+        ) {
             // Update the outbound event cache. This is necessary to ensure we don't under count the outbound events.
             self.cache.insert_outbound_event(peer_ip, CACHE_EVENTS_INTERVAL);
             // Send the event to the peer.
@@ -996,11 +1152,11 @@ impl<N: Network> Transport<N> for Gateway<N> {
     }
 
     /// Broadcasts the given event to all connected peers.
-    // TODO(ljedrz): the event should be checked for the presence of Data::Object, and
-    // serialized in advance if it's there.
     fn broadcast(&self, event: Event<N>) {
         // Ensure there are connected peers.
         if self.number_of_connected_peers() > 0 {
+            // Serialize the payload once, instead of once per peer.
+            let event = event.into_buffered();
             let self_ = self.clone();
             let connected_peers = self.connected_peers.read().clone();
             tokio::spawn(async move {
@@ -1012,6 +1168,11 @@ impl<N: Network> Transport<N> for Gateway<N> {
             });
         }
     }
+
+    /// Returns `true` if the given peer supports the given feature of the event protocol.
+    fn supports_feature(&self, peer_ip: SocketAddr, feature: Feature) -> bool {
+        feature.is_enabled(self.event_version(peer_ip))
+    }
 }
 
 impl<N: Network> P2P for Gateway<N> {
@@ -1023,8 +1184,8 @@ impl<N: Network> P2P for Gateway<N> {
 
 #[async_trait]
 impl<N: Network> Reading for Gateway<N> {
-    type Codec = EventCodec<N>;
-    type Message = Event<N>;
+    type Codec = SequencedEventCodec<N>;
+    type Message = SequencedEvent<N>;
 
     /// The maximum queue depth of incoming messages for a single peer.
     const MESSAGE_QUEUE_DEPTH: usize = 2
@@ -1034,14 +1195,41 @@ impl<N: Network> Reading for Gateway<N> {
 
     /// Creates a [`Decoder`] used to interpret messages from the network.
     /// The `side` param indicates the connection side **from the node's perspective**.
-    fn codec(&self, _peer_addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
-        Default::default()
+    fn codec(&self, peer_addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
+        match self.is_sequenced(peer_addr) {
+            // The session is only used to encode events.
+            true => SequencedEventCodec::new(0),
+            false => SequencedEventCodec::unsequenced(),
+        }
     }
 
     /// Processes a message received from the network.
     async fn process_message(&self, peer_addr: SocketAddr, message: Self::Message) -> io::Result<()> {
+        let SequencedEvent { session, sequence, event } = message;
+        // Drop the duplicate and stale events, and the events outside of the reorder window.
+        if let Some(peer_ip) = self.resolver.get_listener(peer_addr).filter(|_| self.is_sequenced(peer_addr)) {
+            let is_hello = matches!(event, Event::Hello(..));
+            match self.sequences.check(peer_ip, session, sequence, is_hello) {
+                SequenceStatus::Accepted => (),
+                SequenceStatus::Duplicate | SequenceStatus::Stale => {
+                    trace!(
+                        "{CONTEXT} Dropping '{}' from '{peer_ip}' (sequence {sequence} of session {session})",
+                        event.name()
+                    );
+                    return Ok(());
+                }
+                SequenceStatus::OutOfWindow => {
+                    let num_out_of_window = self.sequences.num_out_of_window();
+                    warn!(
+                        "{CONTEXT} Dropping '{}' from '{peer_ip}' (sequence {sequence} is out of the reorder window, total = {num_out_of_window})",
+                        event.name()
+                    );
+                    return Ok(());
+                }
+            }
+        }
         // Process the message. Disconnect if the peer violated the protocol.
-        if let Err(error) = self.inbound(peer_addr, message).await {
+        if let Err(error) = self.inbound(peer_addr, event).await {
             if let Some(peer_ip) = self.resolver.get_listener(peer_addr) {
                 warn!("{CONTEXT} Disconnecting from '{peer_ip}' - {error}");
                 let self_ = self.clone();
@@ -1058,7 +1246,7 @@ impl<N: Network> Reading for Gateway<N> {
 
 #[async_trait]
 impl<N: Network> Writing for Gateway<N> {
-    type Codec = EventCodec<N>;
+    type Codec = SequencedEventCodec<N>;
     type Message = Event<N>;
 
     /// The maximum queue depth of outgoing messages for a single peer.
@@ -1069,8 +1257,31 @@ impl<N: Network> Writing for Gateway<N> {
 
     /// Creates an [`Encoder`] used to write the outbound messages to the target stream.
     /// The `side` parameter indicates the connection side **from the node's perspective**.
-    fn codec(&self, _peer_addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
-        Default::default()
+    fn codec(&self, peer_addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
+        match self.is_sequenced(peer_addr) {
+            // Start a new session for each connection, so that the peer can drop the frames of the previous one.
+            true => SequencedEventCodec::new(rand::random()),
+            false => SequencedEventCodec::unsequenced(),
+        }
+    }
+
+    /// Returns the queue policy of the given outbound event, which determines whether it is dropped
+    /// if the outbound queue of the peer is full. Direct responses are never dropped.
+    fn queue_policy(&self, event: &Self::Message) -> QueuePolicy {
+        match event {
+            Event::BlockResponse(..)
+            | Event::CertificateResponse(..)
+            | Event::TransmissionChunkResponse(..)
+            | Event::TransmissionResponse(..)
+            | Event::ValidatorsResponse(..) => QueuePolicy::Response,
+            Event::WorkerPing(..) => QueuePolicy::Gossip,
+            _ => QueuePolicy::Refuse,
+        }
+    }
+
+    /// Returns the type of the given outbound event, under which it is counted if it is dropped.
+    fn message_type(&self, event: &Self::Message) -> Cow<'static, str> {
+        event.name()
     }
 }
 
@@ -1194,7 +1405,7 @@ impl<N: Network> Gateway<N> {
         // Sample a random nonce.
         let our_nonce = rng.gen();
         // Send a challenge request to the peer.
-        let our_request = ChallengeRequest::new(self.local_ip().port(), self.account.address(), our_nonce);
+        let our_request = ChallengeRequest::new(self.local_ip().port(), self.signer.address(), our_nonce);
         send_event(&mut framed, peer_addr, Event::ChallengeRequest(our_request)).await?;
 
         /* Step 2: Receive the peer's challenge response followed by the challenge request. */
@@ -1222,14 +1433,15 @@ impl<N: Network> Gateway<N> {
         // Sign the counterparty nonce.
         let response_nonce: u64 = rng.gen();
         let data = [peer_request.nonce.to_le_bytes(), response_nonce.to_le_bytes()].concat();
-        let Ok(our_signature) = self.account.sign_bytes(&data, rng) else {
+        let Ok(our_signature) = self.signer.sign_bytes(SigningContext::Handshake, data).await else {
             return Err(error(format!("Failed to sign the challenge request nonce from '{peer_addr}'")));
         };
         // Send the challenge response.
         let our_response = ChallengeResponse { signature: Data::Object(our_signature), nonce: response_nonce };
         send_event(&mut framed, peer_addr, Event::ChallengeResponse(our_response)).await?;
 
-        // Add the peer to the gateway.
+        // Add the peer to the gateway, on the event version negotiated with it.
+        self.insert_event_version(peer_ip, peer_request.version);
         self.insert_connected_peer(peer_ip, peer_addr, peer_request.address);
 
         Ok((peer_ip, framed))
@@ -1251,7 +1463,7 @@ impl<N: Network> Gateway<N> {
         let peer_request = expect_event!(Event::ChallengeRequest, framed, peer_addr);
 
         // Ensure the address is not the same as this node.
-        if self.account.address() == peer_request.address {
+        if self.signer.address() == peer_request.address {
             return Err(error("Skipping request to connect to self".to_string()));
         }
 
@@ -1277,7 +1489,7 @@ impl<N: Network> Gateway<N> {
         // Sign the counterparty nonce.
         let response_nonce: u64 = rng.gen();
         let data = [peer_request.nonce.to_le_bytes(), response_nonce.to_le_bytes()].concat();
-        let Ok(our_signature) = self.account.sign_bytes(&data, rng) else {
+        let Ok(our_signature) = self.signer.sign_bytes(SigningContext::Handshake, data).await else {
             return Err(error(format!("Failed to sign the challenge request nonce from '{peer_addr}'")));
         };
         // Send the challenge response.
@@ -1287,7 +1499,7 @@ impl<N: Network> Gateway<N> {
         // Sample a random nonce.
         let our_nonce = rng.gen();
         // Send the challenge request.
-        let our_request = ChallengeRequest::new(self.local_ip().port(), self.account.address(), our_nonce);
+        let our_request = ChallengeRequest::new(self.local_ip().port(), self.signer.address(), our_nonce);
         send_event(&mut framed, peer_addr, Event::ChallengeRequest(our_request)).await?;
 
         /* Step 3: Receive the challenge response. */
@@ -1301,7 +1513,8 @@ impl<N: Network> Gateway<N> {
             send_event(&mut framed, peer_addr, reason.into()).await?;
             return Err(error(format!("Dropped '{peer_addr}' for reason: {reason:?}")));
         }
-        // Add the peer to the gateway.
+        // Add the peer to the gateway, on the event version negotiated with it.
+        self.insert_event_version(peer_ip, peer_request.version);
         self.insert_connected_peer(peer_ip, peer_addr, peer_request.address);
 
         Ok((peer_ip, framed))
@@ -1312,7 +1525,7 @@ impl<N: Network> Gateway<N> {
         // Retrieve the components of the challenge request.
         let &ChallengeRequest { version, listener_port: _, address, nonce: _ } = event;
         // Ensure the event protocol version is not outdated.
-        if version < Event::<N>::VERSION {
+        if Event::<N>::negotiate(version).is_none() {
             warn!("{CONTEXT} Gateway is dropping '{peer_addr}' on version {version} (outdated)");
             return Some(DisconnectReason::OutdatedClientVersion);
         }
@@ -1340,7 +1553,7 @@ impl<N: Network> Gateway<N> {
         // Retrieve the components of the challenge response.
         let ChallengeResponse { signature, nonce } = response;
         // Perform the deferred non-blocking deserialization of the signature.
-        let Ok(signature) = spawn_blocking!(signature.deserialize_blocking()) else {
+        let Ok(signature) = deserialize_data(signature).await else {
             warn!("{CONTEXT} Gateway handshake with '{peer_addr}' failed (cannot deserialize the signature)");
             return Some(DisconnectReason::InvalidChallengeResponse);
         };
@@ -1397,7 +1610,7 @@ mod prop_tests {
     impl Debug for Gateway<CurrentNetwork> {
         fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
             // TODO implement Debug properly and move it over to production code
-            f.debug_tuple("Gateway").field(&self.account.address()).field(&self.tcp.config()).finish()
+            f.debug_tuple("Gateway").field(&self.signer.address()).field(&self.tcp.config()).finish()
         }
     }
 
@@ -1490,7 +1703,7 @@ mod prop_tests {
 
         let tcp_config = gateway.tcp().config();
         assert_eq!(tcp_config.max_connections, Committee::<CurrentNetwork>::MAX_COMMITTEE_SIZE);
-        assert_eq!(gateway.account().address(), account.address());
+        assert_eq!(gateway.signer().address(), account.address());
     }
 
     #[proptest]
@@ -1512,7 +1725,7 @@ mod prop_tests {
 
         let tcp_config = gateway.tcp().config();
         assert_eq!(tcp_config.max_connections, Committee::<CurrentNetwork>::MAX_COMMITTEE_SIZE);
-        assert_eq!(gateway.account().address(), account.address());
+        assert_eq!(gateway.signer().address(), account.address());
     }
 
     #[proptest(async = "tokio")]
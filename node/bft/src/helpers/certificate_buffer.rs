@@ -0,0 +1,451 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{MAX_PENDING_CERTIFICATES_PER_ROUND, PENDING_CERTIFICATE_TTL_IN_SECS};
+use snarkvm::{
+    console::{account::Address, network::Network, types::Field},
+    ledger::narwhal::{BatchCertificate, Transmission, TransmissionID},
+};
+
+use indexmap::{IndexMap, IndexSet};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::{collections::HashMap, net::SocketAddr, time::Duration};
+use tokio::time::Instant;
+
+/// A dependency of a certificate, which must be in storage before the certificate is inserted.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Dependency<N: Network> {
+    /// A previous certificate, by certificate ID.
+    Certificate(Field<N>),
+    /// A transmission, by transmission ID.
+    Transmission(TransmissionID<N>),
+}
+
+/// A certificate that is buffered until its missing dependencies arrive.
+#[derive(Clone, Debug)]
+pub struct PendingCertificate<N: Network> {
+    /// The IP of the peer that sent the certificate.
+    pub peer_ip: SocketAddr,
+    /// The certificate.
+    pub certificate: BatchCertificate<N>,
+    /// The transmissions of the certificate that are not in storage, and were fetched so far.
+    pub transmissions: HashMap<TransmissionID<N>, Transmission<N>>,
+    /// The dependencies that are still missing.
+    missing: IndexSet<Dependency<N>>,
+    /// The time the certificate was buffered.
+    buffered_at: Instant,
+}
+
+/// A snapshot of a buffered certificate.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(bound = "")]
+pub struct PendingCertificateSummary<N: Network> {
+    /// The certificate ID.
+    pub certificate_id: Field<N>,
+    /// The round of the certificate.
+    pub round: u64,
+    /// The author of the certificate.
+    pub author: Address<N>,
+    /// The IP of the peer that sent the certificate.
+    pub peer_ip: SocketAddr,
+    /// The missing previous certificates.
+    pub missing_certificates: Vec<Field<N>>,
+    /// The number of missing transmissions.
+    pub num_missing_transmissions: usize,
+    /// The number of milliseconds since the certificate was buffered.
+    pub age_in_ms: u128,
+}
+
+/// A snapshot of the certificate buffer, and its counters since the node started.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(bound = "")]
+pub struct CertificateBufferSummary<N: Network> {
+    /// The buffered certificates, from the oldest to the newest.
+    pub certificates: Vec<PendingCertificateSummary<N>>,
+    /// The number of certificates that were buffered.
+    pub num_buffered: u64,
+    /// The number of certificates that were released, once their dependencies arrived.
+    pub num_released: u64,
+    /// The number of certificates that were discarded, after their TTL or once their round was garbage collected.
+    pub num_expired: u64,
+    /// The number of certificates that were not buffered, because their round was at capacity.
+    pub num_rejected: u64,
+}
+
+/// The state of the certificate buffer.
+struct Inner<N: Network> {
+    /// The map of `certificate ID` to buffered certificates, from the oldest to the newest.
+    certificates: IndexMap<Field<N>, PendingCertificate<N>>,
+    /// The map of missing dependencies to the IDs of the buffered certificates waiting on them.
+    dependents: HashMap<Dependency<N>, IndexSet<Field<N>>>,
+    /// The number of certificates that were buffered.
+    num_buffered: u64,
+    /// The number of certificates that were released.
+    num_released: u64,
+    /// The number of certificates that were discarded.
+    num_expired: u64,
+    /// The number of certificates that were not buffered.
+    num_rejected: u64,
+}
+
+impl<N: Network> Inner<N> {
+    /// Removes the given certificate, and its entries in the map of dependents.
+    fn remove(&mut self, certificate_id: &Field<N>) -> Option<PendingCertificate<N>> {
+        let pending = self.certificates.shift_remove(certificate_id)?;
+        for dependency in &pending.missing {
+            if let Some(dependents) = self.dependents.get_mut(dependency) {
+                dependents.swap_remove(certificate_id);
+                if dependents.is_empty() {
+                    self.dependents.remove(dependency);
+                }
+            }
+        }
+        Some(pending)
+    }
+
+    /// Marks the given dependency as arrived, and returns the buffered certificates that are no longer missing any.
+    /// If the dependency is a transmission, it is kept with the certificates, for their insertion.
+    fn resolve(
+        &mut self,
+        dependency: &Dependency<N>,
+        transmission: Option<&Transmission<N>>,
+    ) -> Vec<PendingCertificate<N>> {
+        let Some(dependents) = self.dependents.remove(dependency) else {
+            return Vec::new();
+        };
+        let mut ready = Vec::new();
+        for certificate_id in dependents {
+            let Some(pending) = self.certificates.get_mut(&certificate_id) else {
+                continue;
+            };
+            pending.missing.swap_remove(dependency);
+            if let (Dependency::Transmission(transmission_id), Some(transmission)) = (dependency, transmission) {
+                pending.transmissions.insert(*transmission_id, transmission.clone());
+            }
+            if pending.missing.is_empty() {
+                ready.extend(self.certificates.shift_remove(&certificate_id));
+            }
+        }
+        self.num_released += ready.len() as u64;
+        ready
+    }
+}
+
+/// A bounded buffer of the certificates that arrived before their previous certificates or transmissions,
+/// keyed by their missing dependencies, so that they are inserted as soon as the dependencies arrive.
+pub struct CertificateBuffer<N: Network> {
+    /// The time after which a buffered certificate is discarded.
+    ttl: Duration,
+    /// The maximum number of buffered certificates for each round.
+    max_per_round: usize,
+    /// The state of the buffer.
+    inner: Mutex<Inner<N>>,
+}
+
+impl<N: Network> CertificateBuffer<N> {
+    /// Initializes a new certificate buffer, with the given TTL and the given maximum number of certificates per round.
+    pub fn new(ttl: Duration, max_per_round: usize) -> Self {
+        let inner = Inner {
+            certificates: Default::default(),
+            dependents: Default::default(),
+            num_buffered: 0,
+            num_released: 0,
+            num_expired: 0,
+            num_rejected: 0,
+        };
+        Self { ttl, max_per_round, inner: Mutex::new(inner) }
+    }
+
+    /// Returns the number of buffered certificates.
+    pub fn len(&self) -> usize {
+        self.inner.lock().certificates.len()
+    }
+
+    /// Returns `true` if there are no buffered certificates.
+    pub fn is_empty(&self) -> bool {
+        self.inner.lock().certificates.is_empty()
+    }
+
+    /// Returns `true` if the given certificate is buffered.
+    pub fn contains(&self, certificate_id: &Field<N>) -> bool {
+        self.inner.lock().certificates.contains_key(certificate_id)
+    }
+
+    /// Buffers the given certificate from the given peer until the given missing dependencies arrive,
+    /// along with the given transmissions that were fetched so far, and returns `true` if it was buffered.
+    pub fn insert(
+        &self,
+        peer_ip: SocketAddr,
+        certificate: BatchCertificate<N>,
+        transmissions: HashMap<TransmissionID<N>, Transmission<N>>,
+        missing: IndexSet<Dependency<N>>,
+        now: Instant,
+    ) -> bool {
+        // A certificate without missing dependencies can be inserted right away.
+        if missing.is_empty() {
+            return false;
+        }
+        let mut inner = self.inner.lock();
+        let certificate_id = certificate.id();
+        if inner.certificates.contains_key(&certificate_id) {
+            return false;
+        }
+        // Ensure the round of the certificate is not at capacity.
+        let round = certificate.round();
+        if inner.certificates.values().filter(|pending| pending.certificate.round() == round).count()
+            >= self.max_per_round
+        {
+            inner.num_rejected += 1;
+            return false;
+        }
+        // Buffer the certificate, under each of its missing dependencies.
+        for dependency in &missing {
+            inner.dependents.entry(*dependency).or_default().insert(certificate_id);
+        }
+        let pending = PendingCertificate { peer_ip, certificate, transmissions, missing, buffered_at: now };
+        inner.certificates.insert(certificate_id, pending);
+        inner.num_buffered += 1;
+        true
+    }
+
+    /// Marks the given certificate as stored, and returns the buffered certificates that are no longer missing any dependency.
+    pub fn resolve_certificate(&self, certificate_id: Field<N>) -> Vec<PendingCertificate<N>> {
+        self.inner.lock().resolve(&Dependency::Certificate(certificate_id), None)
+    }
+
+    /// Records the given fetched transmission, and returns the buffered certificates that are no longer missing any dependency.
+    pub fn resolve_transmission(
+        &self,
+        transmission_id: TransmissionID<N>,
+        transmission: Transmission<N>,
+    ) -> Vec<PendingCertificate<N>> {
+        self.inner.lock().resolve(&Dependency::Transmission(transmission_id), Some(&transmission))
+    }
+
+    /// Marks the missing dependencies that satisfy the given predicate as arrived, e.g. those stored through
+    /// another path, and returns the buffered certificates that are no longer missing any dependency.
+    pub fn resolve_if(&self, is_available: impl Fn(&Dependency<N>) -> bool) -> Vec<PendingCertificate<N>> {
+        let mut inner = self.inner.lock();
+        let available =
+            inner.dependents.keys().filter(|dependency| is_available(dependency)).copied().collect::<Vec<_>>();
+        available.iter().flat_map(|dependency| inner.resolve(dependency, None)).collect()
+    }
+
+    /// Discards the buffered certificates that were buffered longer than the TTL as of the given time,
+    /// or whose round is at or below the given GC round, and returns the number of discarded certificates.
+    pub fn expire(&self, gc_round: u64, now: Instant) -> usize {
+        let mut inner = self.inner.lock();
+        let expired = inner
+            .certificates
+            .iter()
+            .filter(|(_, pending)| {
+                pending.certificate.round() <= gc_round
+                    || now.saturating_duration_since(pending.buffered_at) >= self.ttl
+            })
+            .map(|(certificate_id, _)| *certificate_id)
+            .collect::<Vec<_>>();
+        for certificate_id in &expired {
+            inner.remove(certificate_id);
+        }
+        inner.num_expired += expired.len() as u64;
+        expired.len()
+    }
+
+    /// Returns a snapshot of the buffered certificates as of the given time, and the counters of the buffer.
+    pub fn summary(&self, now: Instant) -> CertificateBufferSummary<N> {
+        let inner = self.inner.lock();
+        let certificates = inner
+            .certificates
+            .iter()
+            .map(|(certificate_id, pending)| PendingCertificateSummary {
+                certificate_id: *certificate_id,
+                round: pending.certificate.round(),
+                author: pending.certificate.author(),
+                peer_ip: pending.peer_ip,
+                missing_certificates: pending
+                    .missing
+                    .iter()
+                    .filter_map(|dependency| match dependency {
+                        Dependency::Certificate(certificate_id) => Some(*certificate_id),
+                        Dependency::Transmission(..) => None,
+                    })
+                    .collect(),
+                num_missing_transmissions: pending
+                    .missing
+                    .iter()
+                    .filter(|dependency| matches!(dependency, Dependency::Transmission(..)))
+                    .count(),
+                age_in_ms: now.saturating_duration_since(pending.buffered_at).as_millis(),
+            })
+            .collect();
+        CertificateBufferSummary {
+            certificates,
+            num_buffered: inner.num_buffered,
+            num_released: inner.num_released,
+            num_expired: inner.num_expired,
+            num_rejected: inner.num_rejected,
+        }
+    }
+}
+
+impl<N: Network> Default for CertificateBuffer<N> {
+    /// Initializes a new certificate buffer, with the default TTL and capacity.
+    fn default() -> Self {
+        Self::new(Duration::from_secs(PENDING_CERTIFICATE_TTL_IN_SECS), MAX_PENDING_CERTIFICATES_PER_ROUND)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkvm::{
+        ledger::narwhal::batch_certificate::test_helpers::sample_batch_certificate_for_round_with_previous_certificate_ids,
+        prelude::{TestRng, Uniform},
+    };
+
+    type CurrentNetwork = snarkvm::prelude::MainnetV0;
+
+    /// Samples a certificate for the given round, with the given previous certificate IDs.
+    fn sample_certificate(
+        round: u64,
+        previous_certificate_ids: &[Field<CurrentNetwork>],
+        rng: &mut TestRng,
+    ) -> BatchCertificate<CurrentNetwork> {
+        let previous_certificate_ids = previous_certificate_ids.iter().copied().collect();
+        sample_batch_certificate_for_round_with_previous_certificate_ids(round, previous_certificate_ids, rng)
+    }
+
+    /// Returns the previous certificates of the given certificate, as dependencies.
+    fn previous_dependencies(certificate: &BatchCertificate<CurrentNetwork>) -> IndexSet<Dependency<CurrentNetwork>> {
+        certificate.previous_certificate_ids().iter().map(|id| Dependency::Certificate(*id)).collect()
+    }
+
+    #[test]
+    fn test_release_once_dependencies_arrive() {
+        let rng = &mut TestRng::default();
+        let buffer = CertificateBuffer::<CurrentNetwork>::default();
+        let peer_ip = "127.0.0.1:5000".parse().unwrap();
+        let now = Instant::now();
+
+        // Buffer a certificate, which misses two previous certificates and a transmission.
+        let previous_ids = [Field::rand(rng), Field::rand(rng)];
+        let certificate = sample_certificate(5, &previous_ids, rng);
+        let transmission_id = TransmissionID::Ratification;
+        let mut missing = previous_dependencies(&certificate);
+        missing.insert(Dependency::Transmission(transmission_id));
+        assert!(buffer.insert(peer_ip, certificate.clone(), Default::default(), missing, now));
+        assert!(buffer.contains(&certificate.id()));
+        // Ensure a certificate is buffered once, and a certificate without missing dependencies is not buffered.
+        assert!(!buffer.insert(
+            peer_ip,
+            certificate.clone(),
+            Default::default(),
+            previous_dependencies(&certificate),
+            now
+        ));
+        assert!(!buffer.insert(peer_ip, sample_certificate(5, &[], rng), Default::default(), IndexSet::new(), now));
+
+        // Ensure the certificate is released only once every dependency arrived.
+        assert!(buffer.resolve_certificate(previous_ids[0]).is_empty());
+        assert!(buffer.resolve_certificate(Field::rand(rng)).is_empty());
+        assert!(buffer.resolve_transmission(transmission_id, Transmission::Ratification).is_empty());
+        let ready = buffer.resolve_certificate(previous_ids[1]);
+        assert_eq!(ready.len(), 1);
+        assert_eq!(ready[0].certificate, certificate);
+        assert_eq!(ready[0].peer_ip, peer_ip);
+        // Ensure the fetched transmission is released with the certificate.
+        assert_eq!(ready[0].transmissions.get(&transmission_id), Some(&Transmission::Ratification));
+
+        assert!(buffer.is_empty());
+        let summary = buffer.summary(now);
+        assert_eq!((summary.num_buffered, summary.num_released, summary.num_expired), (1, 1, 0));
+    }
+
+    #[test]
+    fn test_release_chain_of_dependencies() {
+        let rng = &mut TestRng::default();
+        let buffer = CertificateBuffer::<CurrentNetwork>::default();
+        let peer_ip = "127.0.0.1:5000".parse().unwrap();
+        let now = Instant::now();
+
+        // Buffer a certificate for round 6, which waits on a buffered certificate for round 5.
+        let missing_id = Field::rand(rng);
+        let parent = sample_certificate(5, &[missing_id], rng);
+        let child = sample_certificate(6, &[parent.id()], rng);
+        assert!(buffer.insert(peer_ip, child.clone(), Default::default(), previous_dependencies(&child), now));
+        assert!(buffer.insert(peer_ip, parent.clone(), Default::default(), previous_dependencies(&parent), now));
+
+        // Ensure the dependencies that arrived through another path release the certificates, one round at a time.
+        let ready = buffer.resolve_if(|dependency| *dependency == Dependency::Certificate(missing_id));
+        assert_eq!(ready.iter().map(|pending| pending.certificate.id()).collect::<Vec<_>>(), vec![parent.id()]);
+        let ready = buffer.resolve_certificate(parent.id());
+        assert_eq!(ready.iter().map(|pending| pending.certificate.id()).collect::<Vec<_>>(), vec![child.id()]);
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn test_expire_and_capacity() {
+        let rng = &mut TestRng::default();
+        let buffer = CertificateBuffer::<CurrentNetwork>::new(Duration::from_secs(10), 2);
+        let peer_ip = "127.0.0.1:5000".parse().unwrap();
+        let start = Instant::now();
+
+        // Fill round 5 to capacity, and ensure a third certificate for the round is rejected.
+        let missing_id = Field::rand(rng);
+        for _ in 0..2 {
+            let certificate = sample_certificate(5, &[missing_id], rng);
+            assert!(buffer.insert(
+                peer_ip,
+                certificate.clone(),
+                Default::default(),
+                previous_dependencies(&certificate),
+                start
+            ));
+        }
+        let certificate = sample_certificate(5, &[missing_id], rng);
+        assert!(!buffer.insert(
+            peer_ip,
+            certificate.clone(),
+            Default::default(),
+            previous_dependencies(&certificate),
+            start
+        ));
+        // Ensure a certificate for another round is buffered later.
+        let later = start + Duration::from_secs(5);
+        let certificate = sample_certificate(7, &[missing_id], rng);
+        assert!(buffer.insert(
+            peer_ip,
+            certificate.clone(),
+            Default::default(),
+            previous_dependencies(&certificate),
+            later
+        ));
+        assert_eq!(buffer.len(), 3);
+
+        // Ensure the certificates below the GC round are discarded.
+        assert_eq!(buffer.expire(5, later), 2);
+        assert_eq!(buffer.len(), 1);
+        // Ensure the certificates past their TTL are discarded.
+        assert_eq!(buffer.expire(0, later + Duration::from_secs(9)), 0);
+        assert_eq!(buffer.expire(0, later + Duration::from_secs(10)), 1);
+        assert!(buffer.is_empty());
+        // Ensure the discarded certificates are no longer waiting on their dependencies.
+        assert!(buffer.resolve_certificate(missing_id).is_empty());
+
+        let summary = buffer.summary(later);
+        assert_eq!((summary.num_buffered, summary.num_released), (3, 0));
+        assert_eq!((summary.num_expired, summary.num_rejected), (3, 1));
+    }
+}
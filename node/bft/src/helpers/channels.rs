@@ -17,6 +17,8 @@ use crate::events::{
     BatchSignature,
     CertificateRequest,
     CertificateResponse,
+    TransmissionChunkRequest,
+    TransmissionChunkResponse,
     TransmissionRequest,
     TransmissionResponse,
 };
@@ -36,6 +38,10 @@ use std::net::SocketAddr;
 use tokio::sync::{mpsc, oneshot};
 
 const MAX_CHANNEL_SIZE: usize = 8192;
+/// The capacity of the channel of committed subdags from the BFT to consensus.
+/// Note: The capacity is small, so that if block production falls behind (e.g. on a disk stall), the BFT pauses
+/// committing subdags, instead of buffering them and their transmissions in memory.
+pub const MAX_CONSENSUS_CHANNEL_SIZE: usize = 2;
 
 #[derive(Debug)]
 pub struct ConsensusSender<N: Network> {
@@ -51,7 +57,7 @@ pub struct ConsensusReceiver<N: Network> {
 
 /// Initializes the consensus channels.
 pub fn init_consensus_channels<N: Network>() -> (ConsensusSender<N>, ConsensusReceiver<N>) {
-    let (tx_consensus_subdag, rx_consensus_subdag) = mpsc::channel(MAX_CHANNEL_SIZE);
+    let (tx_consensus_subdag, rx_consensus_subdag) = mpsc::channel(MAX_CONSENSUS_CHANNEL_SIZE);
 
     let sender = ConsensusSender { tx_consensus_subdag };
     let receiver = ConsensusReceiver { rx_consensus_subdag };
@@ -127,6 +133,7 @@ pub struct PrimarySender<N: Network> {
     pub tx_batch_certified: mpsc::Sender<(SocketAddr, Data<BatchCertificate<N>>)>,
     pub tx_primary_ping: mpsc::Sender<(SocketAddr, Data<BatchCertificate<N>>)>,
     pub tx_unconfirmed_solution: mpsc::Sender<(SolutionID<N>, Data<Solution<N>>, oneshot::Sender<Result<()>>)>,
+    pub tx_priority_solution: mpsc::Sender<(SolutionID<N>, Data<Solution<N>>, oneshot::Sender<Result<()>>)>,
     pub tx_unconfirmed_transaction: mpsc::Sender<(N::TransactionID, Data<Transaction<N>>, oneshot::Sender<Result<()>>)>,
 }
 
@@ -145,6 +152,16 @@ impl<N: Network> PrimarySender<N> {
         callback_receiver.await?
     }
 
+    /// Sends the consensus-critical solution to the priority lane of the primary.
+    pub async fn send_priority_solution(&self, solution_id: SolutionID<N>, solution: Data<Solution<N>>) -> Result<()> {
+        // Initialize a callback sender and receiver.
+        let (callback_sender, callback_receiver) = oneshot::channel();
+        // Send the consensus-critical solution to the primary.
+        self.tx_priority_solution.send((solution_id, solution, callback_sender)).await?;
+        // Await the callback to continue.
+        callback_receiver.await?
+    }
+
     /// Sends the unconfirmed transaction to the primary.
     pub async fn send_unconfirmed_transaction(
         &self,
@@ -167,6 +184,7 @@ pub struct PrimaryReceiver<N: Network> {
     pub rx_batch_certified: mpsc::Receiver<(SocketAddr, Data<BatchCertificate<N>>)>,
     pub rx_primary_ping: mpsc::Receiver<(SocketAddr, Data<BatchCertificate<N>>)>,
     pub rx_unconfirmed_solution: mpsc::Receiver<(SolutionID<N>, Data<Solution<N>>, oneshot::Sender<Result<()>>)>,
+    pub rx_priority_solution: mpsc::Receiver<(SolutionID<N>, Data<Solution<N>>, oneshot::Sender<Result<()>>)>,
     pub rx_unconfirmed_transaction:
         mpsc::Receiver<(N::TransactionID, Data<Transaction<N>>, oneshot::Sender<Result<()>>)>,
 }
@@ -178,6 +196,7 @@ pub fn init_primary_channels<N: Network>() -> (PrimarySender<N>, PrimaryReceiver
     let (tx_batch_certified, rx_batch_certified) = mpsc::channel(MAX_CHANNEL_SIZE);
     let (tx_primary_ping, rx_primary_ping) = mpsc::channel(MAX_CHANNEL_SIZE);
     let (tx_unconfirmed_solution, rx_unconfirmed_solution) = mpsc::channel(MAX_CHANNEL_SIZE);
+    let (tx_priority_solution, rx_priority_solution) = mpsc::channel(MAX_CHANNEL_SIZE);
     let (tx_unconfirmed_transaction, rx_unconfirmed_transaction) = mpsc::channel(MAX_CHANNEL_SIZE);
 
     let sender = PrimarySender {
@@ -186,6 +205,7 @@ pub fn init_primary_channels<N: Network>() -> (PrimarySender<N>, PrimaryReceiver
         tx_batch_certified,
         tx_primary_ping,
         tx_unconfirmed_solution,
+        tx_priority_solution,
         tx_unconfirmed_transaction,
     };
     let receiver = PrimaryReceiver {
@@ -194,6 +214,7 @@ pub fn init_primary_channels<N: Network>() -> (PrimarySender<N>, PrimaryReceiver
         rx_batch_certified,
         rx_primary_ping,
         rx_unconfirmed_solution,
+        rx_priority_solution,
         rx_unconfirmed_transaction,
     };
 
@@ -205,6 +226,8 @@ pub struct WorkerSender<N: Network> {
     pub tx_worker_ping: mpsc::Sender<(SocketAddr, TransmissionID<N>)>,
     pub tx_transmission_request: mpsc::Sender<(SocketAddr, TransmissionRequest<N>)>,
     pub tx_transmission_response: mpsc::Sender<(SocketAddr, TransmissionResponse<N>)>,
+    pub tx_transmission_chunk_request: mpsc::Sender<(SocketAddr, TransmissionChunkRequest<N>)>,
+    pub tx_transmission_chunk_response: mpsc::Sender<(SocketAddr, TransmissionChunkResponse<N>)>,
 }
 
 #[derive(Debug)]
@@ -212,6 +235,8 @@ pub struct WorkerReceiver<N: Network> {
     pub rx_worker_ping: mpsc::Receiver<(SocketAddr, TransmissionID<N>)>,
     pub rx_transmission_request: mpsc::Receiver<(SocketAddr, TransmissionRequest<N>)>,
     pub rx_transmission_response: mpsc::Receiver<(SocketAddr, TransmissionResponse<N>)>,
+    pub rx_transmission_chunk_request: mpsc::Receiver<(SocketAddr, TransmissionChunkRequest<N>)>,
+    pub rx_transmission_chunk_response: mpsc::Receiver<(SocketAddr, TransmissionChunkResponse<N>)>,
 }
 
 /// Initializes the worker channels.
@@ -219,9 +244,23 @@ pub fn init_worker_channels<N: Network>() -> (WorkerSender<N>, WorkerReceiver<N>
     let (tx_worker_ping, rx_worker_ping) = mpsc::channel(MAX_CHANNEL_SIZE);
     let (tx_transmission_request, rx_transmission_request) = mpsc::channel(MAX_CHANNEL_SIZE);
     let (tx_transmission_response, rx_transmission_response) = mpsc::channel(MAX_CHANNEL_SIZE);
-
-    let sender = WorkerSender { tx_worker_ping, tx_transmission_request, tx_transmission_response };
-    let receiver = WorkerReceiver { rx_worker_ping, rx_transmission_request, rx_transmission_response };
+    let (tx_transmission_chunk_request, rx_transmission_chunk_request) = mpsc::channel(MAX_CHANNEL_SIZE);
+    let (tx_transmission_chunk_response, rx_transmission_chunk_response) = mpsc::channel(MAX_CHANNEL_SIZE);
+
+    let sender = WorkerSender {
+        tx_worker_ping,
+        tx_transmission_request,
+        tx_transmission_response,
+        tx_transmission_chunk_request,
+        tx_transmission_chunk_response,
+    };
+    let receiver = WorkerReceiver {
+        rx_worker_ping,
+        rx_transmission_request,
+        rx_transmission_response,
+        rx_transmission_chunk_request,
+        rx_transmission_chunk_response,
+    };
 
     (sender, receiver)
 }
@@ -0,0 +1,231 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkvm::{
+    console::{account::Address, network::Network, types::Field},
+    ledger::narwhal::BatchCertificate,
+};
+
+use serde::Serialize;
+use std::fmt::Write;
+
+/// The default number of rounds in a DAG snapshot.
+pub const DEFAULT_DAG_SNAPSHOT_ROUNDS: u64 = 10;
+/// The maximum number of rounds in a DAG snapshot.
+pub const MAX_DAG_SNAPSHOT_ROUNDS: u64 = 100;
+
+/// A certificate in a snapshot of the DAG.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(bound = "")]
+pub struct DagVertex<N: Network> {
+    /// The certificate ID.
+    pub certificate_id: Field<N>,
+    /// The round of the certificate.
+    pub round: u64,
+    /// The author of the certificate.
+    pub author: Address<N>,
+    /// The timestamp of the certificate.
+    pub timestamp: i64,
+    /// Whether the certificate has been committed into a block.
+    pub is_committed: bool,
+}
+
+/// An edge from a certificate to one of its previous certificates, in a snapshot of the DAG.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(bound = "")]
+pub struct DagEdge<N: Network> {
+    /// The ID of the certificate.
+    pub from: Field<N>,
+    /// The ID of the previous certificate.
+    pub to: Field<N>,
+}
+
+/// A snapshot of the DAG over a range of rounds.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(bound = "")]
+pub struct DagSnapshot<N: Network> {
+    /// The first round of the snapshot (inclusive).
+    pub start_round: u64,
+    /// The last round of the snapshot (inclusive).
+    pub end_round: u64,
+    /// The certificates in the snapshot, ordered by round.
+    pub vertices: Vec<DagVertex<N>>,
+    /// The edges from the certificates in the snapshot to their previous certificates.
+    pub edges: Vec<DagEdge<N>>,
+}
+
+impl<N: Network> DagSnapshot<N> {
+    /// Initializes a new snapshot from the given certificates, using `is_committed` to determine
+    /// whether each certificate has been committed into a block.
+    pub fn new(
+        start_round: u64,
+        end_round: u64,
+        certificates: &[BatchCertificate<N>],
+        is_committed: impl Fn(&BatchCertificate<N>) -> bool,
+    ) -> Self {
+        let mut vertices = Vec::with_capacity(certificates.len());
+        let mut edges = Vec::new();
+        for certificate in certificates {
+            let certificate_id = certificate.id();
+            vertices.push(DagVertex {
+                certificate_id,
+                round: certificate.round(),
+                author: certificate.author(),
+                timestamp: certificate.timestamp(),
+                is_committed: is_committed(certificate),
+            });
+            edges.extend(
+                certificate.previous_certificate_ids().iter().map(|to| DagEdge { from: certificate_id, to: *to }),
+            );
+        }
+        // Order the certificates by round, for a stable layout.
+        vertices.sort_by_key(|vertex| vertex.round);
+        Self { start_round, end_round, vertices, edges }
+    }
+
+    /// Returns the snapshot in the Graphviz DOT format, where the committed certificates are filled.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph dag {\n    rankdir=\"BT\";\n    node [shape=box];\n");
+        for vertex in &self.vertices {
+            // Abbreviate the author, to keep the labels readable.
+            let author = vertex.author.to_string();
+            let author = author.get(..12).unwrap_or(&author);
+            let style = match vertex.is_committed {
+                true => "style=filled, fillcolor=\"palegreen\"",
+                false => "style=dashed",
+            };
+            let _ = writeln!(
+                dot,
+                "    \"{}\" [label=\"round {}\\n{author}\", {style}];",
+                vertex.certificate_id, vertex.round
+            );
+        }
+        for edge in &self.edges {
+            let _ = writeln!(dot, "    \"{}\" -> \"{}\";", edge.from, edge.to);
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::Storage;
+    use snarkos_node_bft_ledger_service::MockLedgerService;
+    use snarkos_node_bft_storage_service::BFTMemoryService;
+    use snarkvm::{
+        ledger::{
+            committee::test_helpers::sample_committee,
+            narwhal::batch_certificate::test_helpers::sample_batch_certificate_for_round_with_previous_certificate_ids,
+        },
+        utilities::TestRng,
+    };
+
+    use indexmap::IndexSet;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    type CurrentNetwork = snarkvm::prelude::MainnetV0;
+
+    /// Inserts a small DAG into storage, with two certificates in rounds 1 and 2, and one certificate in round 3.
+    /// Returns the certificates, by round.
+    fn sample_dag(storage: &Storage<CurrentNetwork>, rng: &mut TestRng) -> Vec<Vec<BatchCertificate<CurrentNetwork>>> {
+        let mut rounds: Vec<Vec<BatchCertificate<CurrentNetwork>>> = Vec::new();
+        for (round, num_certificates) in [(1, 2), (2, 2), (3, 1)] {
+            // Link the certificates to all certificates of the previous round.
+            let previous_ids: IndexSet<_> = rounds
+                .last()
+                .map_or(Default::default(), |previous| previous.iter().map(|certificate| certificate.id()).collect());
+            let certificates = (0..num_certificates)
+                .map(|_| {
+                    sample_batch_certificate_for_round_with_previous_certificate_ids(round, previous_ids.clone(), rng)
+                })
+                .collect::<Vec<_>>();
+            certificates
+                .iter()
+                .for_each(|certificate| storage.testing_only_insert_certificate_testing_only(certificate.clone()));
+            rounds.push(certificates);
+        }
+        // Advance storage to the last round.
+        while storage.current_round() < 3 {
+            storage.increment_to_next_round(storage.current_round()).unwrap();
+        }
+        rounds
+    }
+
+    /// Initializes a new storage, with a sample DAG.
+    fn sample_storage(rng: &mut TestRng) -> (Storage<CurrentNetwork>, Vec<Vec<BatchCertificate<CurrentNetwork>>>) {
+        let ledger = Arc::new(MockLedgerService::new(sample_committee(rng)));
+        let storage = Storage::new(ledger, Arc::new(BFTMemoryService::new()), 10);
+        let rounds = sample_dag(&storage, rng);
+        (storage, rounds)
+    }
+
+    #[test]
+    fn test_dag_snapshot_json() {
+        let rng = &mut TestRng::default();
+        let (storage, rounds) = sample_storage(rng);
+
+        // Snapshot the DAG, where the certificates of round 1 are committed.
+        let snapshot = storage.dag_snapshot(3, |certificate| certificate.round() == 1);
+        assert_eq!((snapshot.start_round, snapshot.end_round), (1, 3));
+        assert_eq!(snapshot.vertices.len(), 5);
+        assert!(snapshot.vertices.iter().all(|vertex| vertex.is_committed == (vertex.round == 1)));
+
+        // Ensure the edge list links every certificate to the certificates of the previous round.
+        let json = serde_json::to_value(&snapshot).unwrap();
+        let edges = json["edges"].as_array().unwrap();
+        assert_eq!(edges.len(), 2 * 2 + 2);
+        for pair in rounds.windows(2) {
+            for certificate in &pair[1] {
+                for previous in &pair[0] {
+                    assert!(edges.contains(&json!({ "from": certificate.id(), "to": previous.id() })));
+                }
+            }
+        }
+
+        // Ensure a shorter snapshot only contains the certificates of the latest rounds.
+        let snapshot = storage.dag_snapshot(2, |_| false);
+        assert_eq!((snapshot.start_round, snapshot.end_round), (2, 3));
+        assert_eq!(snapshot.vertices.len(), 3);
+        assert!(snapshot.vertices.iter().all(|vertex| vertex.round >= 2));
+    }
+
+    #[test]
+    fn test_dag_snapshot_dot() {
+        let rng = &mut TestRng::default();
+        let (storage, rounds) = sample_storage(rng);
+
+        // Snapshot the DAG, where the certificates of round 1 are committed.
+        let dot = storage.dag_snapshot(3, |certificate| certificate.round() == 1).to_dot();
+        assert!(dot.starts_with("digraph dag {"));
+
+        // Ensure the committed certificates are styled differently from the uncommitted certificates.
+        for certificate in rounds.iter().flatten() {
+            let node = format!("\"{}\" [label=\"round {}", certificate.id(), certificate.round());
+            let line = dot.lines().find(|line| line.trim_start().starts_with(&node)).unwrap();
+            assert_eq!(line.contains("palegreen"), certificate.round() == 1);
+            assert_eq!(line.contains("dashed"), certificate.round() != 1);
+        }
+        // Ensure the edges link every certificate to the certificates of the previous round.
+        for pair in rounds.windows(2) {
+            for certificate in &pair[1] {
+                for previous in &pair[0] {
+                    assert!(dot.contains(&format!("\"{}\" -> \"{}\";", certificate.id(), previous.id())));
+                }
+            }
+        }
+    }
+}
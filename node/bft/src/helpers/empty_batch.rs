@@ -0,0 +1,81 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{EMPTY_BATCH_DELAY_ENABLED, MAX_EMPTY_BATCH_DELAY_IN_SECS};
+
+use std::time::Duration;
+
+/// The behavior of the primary when it has no unconfirmed transactions to propose.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EmptyBatchMode {
+    /// Delay proposing a batch without transactions, until the given delay has elapsed since the last proposal.
+    /// Note: The delay is capped at `MAX_EMPTY_BATCH_DELAY_IN_SECS`, so that the rounds still advance.
+    Delay { delay: Duration },
+    /// Propose a batch without transactions right away.
+    Propose,
+}
+
+impl Default for EmptyBatchMode {
+    /// Returns the default empty batch mode.
+    fn default() -> Self {
+        match EMPTY_BATCH_DELAY_ENABLED {
+            true => Self::Delay { delay: Duration::from_secs(MAX_EMPTY_BATCH_DELAY_IN_SECS) },
+            false => Self::Propose,
+        }
+    }
+}
+
+impl EmptyBatchMode {
+    /// Returns the delay before a batch without transactions is proposed.
+    pub fn delay(&self) -> Duration {
+        match self {
+            Self::Delay { delay } => (*delay).min(Duration::from_secs(MAX_EMPTY_BATCH_DELAY_IN_SECS)),
+            Self::Propose => Duration::ZERO,
+        }
+    }
+
+    /// Returns `true` if a batch without transactions may be proposed, given the time since the last proposal.
+    /// If `is_waived` is `true`, the delay does not apply, e.g. because the other validators are already proposing.
+    pub fn is_empty_batch_ready(&self, time_since_last_proposal: Duration, is_waived: bool) -> bool {
+        is_waived || time_since_last_proposal >= self.delay()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_batch_delay() {
+        let mode = EmptyBatchMode::Delay { delay: Duration::from_secs(5) };
+
+        // Ensure the empty batch is delayed until the delay has elapsed.
+        assert!(!mode.is_empty_batch_ready(Duration::ZERO, false));
+        assert!(!mode.is_empty_batch_ready(Duration::from_secs(4), false));
+        assert!(mode.is_empty_batch_ready(Duration::from_secs(5), false));
+        // Ensure the delay does not apply if it is waived.
+        assert!(mode.is_empty_batch_ready(Duration::ZERO, true));
+
+        // Ensure the delay is capped at the maximum delay.
+        let mode = EmptyBatchMode::Delay { delay: Duration::from_secs(10 * MAX_EMPTY_BATCH_DELAY_IN_SECS) };
+        assert_eq!(mode.delay(), Duration::from_secs(MAX_EMPTY_BATCH_DELAY_IN_SECS));
+        assert!(mode.is_empty_batch_ready(Duration::from_secs(MAX_EMPTY_BATCH_DELAY_IN_SECS), false));
+    }
+
+    #[test]
+    fn test_empty_batch_propose() {
+        // Ensure the empty batch is proposed right away.
+        assert!(EmptyBatchMode::Propose.is_empty_batch_ready(Duration::ZERO, false));
+    }
+}
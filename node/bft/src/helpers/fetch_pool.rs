@@ -0,0 +1,239 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{MAX_CONCURRENT_SOLUTION_FETCHES, MAX_CONCURRENT_TRANSACTION_FETCHES};
+use snarkvm::{console::prelude::*, ledger::narwhal::TransmissionID};
+
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// The pool a transmission fetch is scheduled on.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FetchPool {
+    /// The pool for solution fetches.
+    Solutions,
+    /// The pool for transaction (and ratification) fetches.
+    Transactions,
+}
+
+impl FetchPool {
+    /// Returns the pool for the given transmission ID.
+    pub fn of<N: Network>(transmission_id: &TransmissionID<N>) -> Self {
+        match transmission_id {
+            TransmissionID::Solution(..) => Self::Solutions,
+            TransmissionID::Transaction(..) | TransmissionID::Ratification => Self::Transactions,
+        }
+    }
+}
+
+/// The maximum number of in-flight fetches for each pool.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FetchLimits {
+    /// The maximum number of in-flight solution fetches.
+    pub solutions: usize,
+    /// The maximum number of in-flight transaction fetches.
+    pub transactions: usize,
+}
+
+impl Default for FetchLimits {
+    /// Returns the default fetch limits.
+    fn default() -> Self {
+        Self { solutions: MAX_CONCURRENT_SOLUTION_FETCHES, transactions: MAX_CONCURRENT_TRANSACTION_FETCHES }
+    }
+}
+
+/// A bounded set of slots for fetches from a single pool.
+#[derive(Debug)]
+struct Slots {
+    /// The semaphore bounding the number of in-flight fetches.
+    semaphore: Arc<Semaphore>,
+    /// The number of fetches waiting for a slot.
+    queued: AtomicUsize,
+}
+
+impl Slots {
+    /// Initializes a new set of slots with the given limit.
+    fn new(limit: usize) -> Self {
+        Self { semaphore: Arc::new(Semaphore::new(limit)), queued: Default::default() }
+    }
+}
+
+/// The worker pools used to schedule transmission fetches.
+/// Solutions and transactions are bounded independently, so a backlog in one pool never starves the other.
+#[derive(Debug)]
+pub struct FetchPools {
+    /// The slots for solution fetches.
+    solutions: Slots,
+    /// The slots for transaction fetches.
+    transactions: Slots,
+}
+
+impl Default for FetchPools {
+    /// Initializes the fetch pools with the default limits.
+    fn default() -> Self {
+        Self::new(FetchLimits::default())
+    }
+}
+
+impl FetchPools {
+    /// Initializes the fetch pools with the given limits.
+    pub fn new(limits: FetchLimits) -> Self {
+        Self { solutions: Slots::new(limits.solutions), transactions: Slots::new(limits.transactions) }
+    }
+
+    /// Returns the slots for the given pool.
+    fn slots(&self, pool: FetchPool) -> &Slots {
+        match pool {
+            FetchPool::Solutions => &self.solutions,
+            FetchPool::Transactions => &self.transactions,
+        }
+    }
+
+    /// Returns the number of fetches waiting for a slot in the given pool.
+    pub fn num_queued(&self, pool: FetchPool) -> usize {
+        self.slots(pool).queued.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of free slots in the given pool.
+    pub fn num_available(&self, pool: FetchPool) -> usize {
+        self.slots(pool).semaphore.available_permits()
+    }
+
+    /// Waits for a free slot in the given pool, and returns a permit that releases the slot when dropped.
+    pub async fn acquire(&self, pool: FetchPool) -> Result<FetchPermit> {
+        let slots = self.slots(pool);
+        // Mark the fetch as queued.
+        let queued = slots.queued.fetch_add(1, Ordering::Relaxed) + 1;
+        update_queue_depth_metric(pool, queued);
+        // Ensure the fetch is dequeued, even if this future is cancelled (e.g. on timeout).
+        let _guard = QueueGuard { pool, queued: &slots.queued };
+        // Wait for a free slot.
+        let permit = slots.semaphore.clone().acquire_owned().await?;
+        Ok(FetchPermit { pool, start: Instant::now(), _permit: permit })
+    }
+}
+
+/// A guard that removes a fetch from the queue of its pool when dropped.
+struct QueueGuard<'a> {
+    /// The pool the fetch is queued on.
+    pool: FetchPool,
+    /// The number of fetches waiting for a slot.
+    queued: &'a AtomicUsize,
+}
+
+impl Drop for QueueGuard<'_> {
+    fn drop(&mut self) {
+        let queued = self.queued.fetch_sub(1, Ordering::Relaxed).saturating_sub(1);
+        update_queue_depth_metric(self.pool, queued);
+    }
+}
+
+/// A slot in a fetch pool, held until the fetch completes or times out.
+#[derive(Debug)]
+pub struct FetchPermit {
+    /// The pool the slot belongs to.
+    pool: FetchPool,
+    /// The time the slot was acquired.
+    start: Instant,
+    /// The semaphore permit, released when dropped.
+    _permit: OwnedSemaphorePermit,
+}
+
+impl FetchPermit {
+    /// Returns the pool the slot belongs to.
+    pub const fn pool(&self) -> FetchPool {
+        self.pool
+    }
+
+    /// Returns the time elapsed since the slot was acquired.
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+impl Drop for FetchPermit {
+    fn drop(&mut self) {
+        #[cfg(feature = "metrics")]
+        {
+            let name = match self.pool {
+                FetchPool::Solutions => metrics::bft::SOLUTION_FETCH_LATENCY,
+                FetchPool::Transactions => metrics::bft::TRANSACTION_FETCH_LATENCY,
+            };
+            metrics::histogram(name, self.elapsed().as_secs_f64());
+        }
+    }
+}
+
+/// Updates the queue depth metric for the given pool.
+#[allow(unused_variables)]
+fn update_queue_depth_metric(pool: FetchPool, queued: usize) {
+    #[cfg(feature = "metrics")]
+    {
+        let name = match pool {
+            FetchPool::Solutions => metrics::bft::SOLUTION_FETCH_QUEUE_DEPTH,
+            FetchPool::Transactions => metrics::bft::TRANSACTION_FETCH_QUEUE_DEPTH,
+        };
+        metrics::gauge(name, queued as f64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkvm::console::types::Field;
+
+    use tokio::time::timeout;
+
+    type CurrentNetwork = snarkvm::prelude::MainnetV0;
+
+    #[test]
+    fn test_fetch_pool_of() {
+        let rng = &mut TestRng::default();
+
+        let solution_id = TransmissionID::<CurrentNetwork>::Solution(rng.gen::<u64>().into());
+        let transaction_id = TransmissionID::<CurrentNetwork>::Transaction(Field::rand(rng).into());
+        assert_eq!(FetchPool::of(&solution_id), FetchPool::Solutions);
+        assert_eq!(FetchPool::of(&transaction_id), FetchPool::Transactions);
+        assert_eq!(FetchPool::of(&TransmissionID::<CurrentNetwork>::Ratification), FetchPool::Transactions);
+    }
+
+    #[tokio::test]
+    async fn test_pools_are_independent() {
+        let pools = FetchPools::new(FetchLimits { solutions: 1, transactions: 2 });
+
+        // Saturate the solution pool.
+        let permit = pools.acquire(FetchPool::Solutions).await.unwrap();
+        assert_eq!(permit.pool(), FetchPool::Solutions);
+        assert_eq!(pools.num_available(FetchPool::Solutions), 0);
+        // Ensure another solution fetch waits, and is dequeued when cancelled.
+        assert!(timeout(Duration::from_millis(50), pools.acquire(FetchPool::Solutions)).await.is_err());
+        assert_eq!(pools.num_queued(FetchPool::Solutions), 0);
+
+        // Ensure transaction fetches are unaffected.
+        let first = timeout(Duration::from_millis(50), pools.acquire(FetchPool::Transactions)).await.unwrap().unwrap();
+        let second = timeout(Duration::from_millis(50), pools.acquire(FetchPool::Transactions)).await.unwrap().unwrap();
+        assert_eq!(pools.num_available(FetchPool::Transactions), 0);
+
+        // Ensure the slots are released when the permits are dropped.
+        drop((permit, first, second));
+        assert_eq!(pools.num_available(FetchPool::Solutions), 1);
+        assert_eq!(pools.num_available(FetchPool::Transactions), 2);
+    }
+}
@@ -15,33 +15,66 @@
 pub mod cache;
 pub use cache::*;
 
+pub mod certificate_buffer;
+pub use certificate_buffer::*;
+
 pub mod channels;
 pub use channels::*;
 
 pub mod dag;
 pub use dag::*;
 
+pub mod dag_snapshot;
+pub use dag_snapshot::*;
+
+pub mod empty_batch;
+pub use empty_batch::*;
+
+pub mod fetch_pool;
+pub use fetch_pool::*;
+
 pub mod partition;
 pub use partition::*;
 
 pub mod pending;
 pub use pending::*;
 
+pub mod priority;
+pub use priority::*;
+
 pub mod proposal;
 pub use proposal::*;
 
 pub mod ready;
 pub use ready::*;
 
+pub mod recorder;
+pub use recorder::*;
+
 pub mod resolver;
 pub use resolver::*;
 
+pub mod sequence;
+pub use sequence::*;
+
+pub mod signer;
+pub use signer::*;
+
+pub mod stall;
+pub use stall::*;
+
 pub mod storage;
 pub use storage::*;
 
+pub mod summary;
+pub use summary::*;
+
 pub mod timestamp;
 pub use timestamp::*;
 
+pub mod transmission_chunks;
+pub use transmission_chunks::*;
+
 /// Formats an ID into a truncated identifier (for logging purposes).
 pub fn fmt_id(id: impl ToString) -> String {
     let id = id.to_string();
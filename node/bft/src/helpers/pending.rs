@@ -27,6 +27,9 @@ use time::OffsetDateTime;
 use tokio::sync::oneshot;
 
 const CALLBACK_TIMEOUT_IN_SECS: i64 = MAX_FETCH_TIMEOUT_IN_MS as i64 / 1000;
+/// The duration for which a sent request is remembered, in seconds.
+/// Note: This outlasts the callback timeout, so that a late response from a peer that was asked is not penalized.
+const REQUEST_HISTORY_IN_SECS: i64 = 10 * CALLBACK_TIMEOUT_IN_SECS;
 
 /// Returns the maximum number of redundant requests for the number of validators in the specified round.
 pub fn max_redundant_requests<N: Network>(ledger: Arc<dyn LedgerService<N>>, round: u64) -> usize {
@@ -43,6 +46,11 @@ pub fn max_redundant_requests<N: Network>(ledger: Arc<dyn LedgerService<N>>, rou
     1 + num_validators.saturating_div(3)
 }
 
+/// Returns the current UNIX timestamp, in seconds.
+fn now() -> i64 {
+    OffsetDateTime::now_utc().unix_timestamp()
+}
+
 #[derive(Debug)]
 pub struct Pending<T: PartialEq + Eq + Hash, V: Clone> {
     /// The map of pending `items` to `peer IPs` that have the item.
@@ -50,6 +58,11 @@ pub struct Pending<T: PartialEq + Eq + Hash, V: Clone> {
     /// The optional callback queue.
     /// Each callback has a timeout and a flag indicating if it is associated with a sent request.
     callbacks: Mutex<HashMap<T, Vec<(oneshot::Sender<V>, i64, bool)>>>,
+    /// The timestamps of the requests sent for each `item` to each `peer IP`.
+    /// Note: These outlive the pending `item`, in order to tell late responses apart from unsolicited ones.
+    requests: Mutex<HashMap<(T, SocketAddr), i64>>,
+    /// The clock, returning the current UNIX timestamp in seconds.
+    clock: fn() -> i64,
 }
 
 impl<T: Copy + Clone + PartialEq + Eq + Hash, V: Clone> Default for Pending<T, V> {
@@ -62,7 +75,12 @@ impl<T: Copy + Clone + PartialEq + Eq + Hash, V: Clone> Default for Pending<T, V
 impl<T: Copy + Clone + PartialEq + Eq + Hash, V: Clone> Pending<T, V> {
     /// Initializes a new instance of the pending queue.
     pub fn new() -> Self {
-        Self { pending: Default::default(), callbacks: Default::default() }
+        Self::with_clock(now)
+    }
+
+    /// Initializes a new instance of the pending queue, which expires the callbacks using the given clock.
+    fn with_clock(clock: fn() -> i64) -> Self {
+        Self { pending: Default::default(), callbacks: Default::default(), requests: Default::default(), clock }
     }
 
     /// Returns `true` if the pending queue is empty.
@@ -111,6 +129,17 @@ impl<T: Copy + Clone + PartialEq + Eq + Hash, V: Clone> Pending<T, V> {
             .map_or(0, |callbacks| callbacks.iter().filter(|(_, _, request_sent)| *request_sent).count())
     }
 
+    /// Returns `true` if a request for the specified `item` was recently sent to the specified `peer IP`.
+    /// Note: This remains `true` after the `item` is removed, so that a late response is not mistaken as unsolicited.
+    pub fn is_requested(&self, item: impl Into<T>, peer_ip: SocketAddr) -> bool {
+        // Fetch the current timestamp.
+        let now = (self.clock)();
+        self.requests
+            .lock()
+            .get(&(item.into(), peer_ip))
+            .map_or(false, |timestamp| now - *timestamp <= REQUEST_HISTORY_IN_SECS)
+    }
+
     /// Inserts the specified `item` and `peer IP` to the pending queue,
     /// returning `true` if the `peer IP` was newly-inserted into the entry for the `item`.
     ///
@@ -131,11 +160,15 @@ impl<T: Copy + Clone + PartialEq + Eq + Hash, V: Clone> Pending<T, V> {
 
         // If a callback is provided, insert it into the callback queue.
         if let Some((callback, request_sent)) = callback {
-            self.callbacks.lock().entry(item).or_default().push((
-                callback,
-                OffsetDateTime::now_utc().unix_timestamp(),
-                request_sent,
-            ));
+            // Fetch the current timestamp.
+            let now = (self.clock)();
+            self.callbacks.lock().entry(item).or_default().push((callback, now, request_sent));
+            // If a request is sent, remember it, and forget the requests that are too old.
+            if request_sent {
+                let mut requests = self.requests.lock();
+                requests.retain(|_, timestamp| now - *timestamp <= REQUEST_HISTORY_IN_SECS);
+                requests.insert((item, peer_ip), now);
+            }
         }
         // Return the result.
         result
@@ -166,7 +199,7 @@ impl<T: Copy + Clone + PartialEq + Eq + Hash, V: Clone> Pending<T, V> {
         // Clear the callbacks that have expired.
         if let Some(callbacks) = self.callbacks.lock().get_mut(&item.into()) {
             // Fetch the current timestamp.
-            let now = OffsetDateTime::now_utc().unix_timestamp();
+            let now = (self.clock)();
             // Remove the callbacks that have expired.
             callbacks.retain(|(_, timestamp, _)| now - *timestamp <= CALLBACK_TIMEOUT_IN_SECS);
         }
@@ -181,12 +214,27 @@ mod tests {
         prelude::{Rng, TestRng},
     };
 
-    use std::{thread, time::Duration};
+    use std::cell::Cell;
 
     type CurrentNetwork = snarkvm::prelude::MainnetV0;
 
     const ITERATIONS: usize = 100;
 
+    thread_local! {
+        /// The current UNIX timestamp of the mocked clock, in seconds.
+        static MOCKED_NOW: Cell<i64> = const { Cell::new(0) };
+    }
+
+    /// Returns the current UNIX timestamp of the mocked clock.
+    fn mocked_now() -> i64 {
+        MOCKED_NOW.with(Cell::get)
+    }
+
+    /// Advances the mocked clock by the given number of seconds.
+    fn advance_mocked_clock(secs: i64) {
+        MOCKED_NOW.with(|now| now.set(now.get() + secs));
+    }
+
     #[test]
     fn test_pending() {
         let rng = &mut TestRng::default();
@@ -250,7 +298,7 @@ mod tests {
         let rng = &mut TestRng::default();
 
         // Initialize the ready queue.
-        let pending = Pending::<TransmissionID<CurrentNetwork>, ()>::new();
+        let pending = Pending::<TransmissionID<CurrentNetwork>, ()>::with_clock(mocked_now);
 
         // Check initially empty.
         assert!(pending.is_empty());
@@ -273,22 +321,22 @@ mod tests {
         assert!(pending.insert(solution_id_1, addr_1, Some((callback_sender_1, true))));
         assert!(pending.insert(solution_id_1, addr_2, Some((callback_sender_2, true))));
 
-        // Sleep for a few seconds.
-        thread::sleep(Duration::from_secs(CALLBACK_TIMEOUT_IN_SECS as u64 - 1));
+        // Advance the clock by a few seconds.
+        advance_mocked_clock(CALLBACK_TIMEOUT_IN_SECS - 1);
 
         assert!(pending.insert(solution_id_1, addr_3, Some((callback_sender_3, true))));
 
         // Check that the number of callbacks has not changed.
         assert_eq!(pending.num_callbacks(solution_id_1), 3);
 
-        // Wait for 2 seconds.
-        thread::sleep(Duration::from_secs(2));
+        // Advance the clock by 2 seconds.
+        advance_mocked_clock(2);
 
         // Ensure that the expired callbacks have been removed.
         assert_eq!(pending.num_callbacks(solution_id_1), 1);
 
-        // Wait for `CALLBACK_TIMEOUT_IN_SECS` seconds.
-        thread::sleep(Duration::from_secs(CALLBACK_TIMEOUT_IN_SECS as u64));
+        // Advance the clock by `CALLBACK_TIMEOUT_IN_SECS` seconds.
+        advance_mocked_clock(CALLBACK_TIMEOUT_IN_SECS);
 
         // Ensure that the expired callbacks have been removed.
         assert_eq!(pending.num_callbacks(solution_id_1), 0);
@@ -324,6 +372,128 @@ mod tests {
             assert_eq!(pending.num_sent_requests(solution_id), expected_num_sent_requests);
         }
     }
+
+    #[test]
+    fn test_is_requested() {
+        let rng = &mut TestRng::default();
+
+        // Initialize the pending queue.
+        let pending = Pending::<TransmissionID<CurrentNetwork>, ()>::new();
+
+        // Initialize the solution IDs.
+        let solution_id_1 = TransmissionID::Solution(rng.gen::<u64>().into());
+        let solution_id_2 = TransmissionID::Solution(rng.gen::<u64>().into());
+
+        // Initialize the SocketAddrs.
+        let addr_1 = SocketAddr::from(([127, 0, 0, 1], 1234));
+        let addr_2 = SocketAddr::from(([127, 0, 0, 1], 2345));
+
+        // Send a request for the first solution ID to the first peer, and skip sending it to the second peer.
+        let (callback_sender_1, _) = oneshot::channel();
+        let (callback_sender_2, _) = oneshot::channel();
+        assert!(pending.insert(solution_id_1, addr_1, Some((callback_sender_1, true))));
+        assert!(pending.insert(solution_id_1, addr_2, Some((callback_sender_2, false))));
+
+        // Check only the first peer was sent the request.
+        assert!(pending.is_requested(solution_id_1, addr_1));
+        assert!(!pending.is_requested(solution_id_1, addr_2));
+        // Check a mismatched item was not requested from either peer.
+        assert!(!pending.is_requested(solution_id_2, addr_1));
+        assert!(!pending.is_requested(solution_id_2, addr_2));
+
+        // Send a request for the second solution ID to the second peer.
+        let (callback_sender_3, _) = oneshot::channel();
+        assert!(pending.insert(solution_id_2, addr_2, Some((callback_sender_3, true))));
+        // Check the requests are tracked per item and peer.
+        assert!(!pending.is_requested(solution_id_2, addr_1));
+        assert!(pending.is_requested(solution_id_2, addr_2));
+        assert!(!pending.is_requested(solution_id_1, addr_2));
+
+        // Complete the first solution ID.
+        assert!(pending.remove(solution_id_1, Some(())).is_some());
+        // Check a duplicate response finds no pending item, while the request is still remembered.
+        assert!(!pending.contains(solution_id_1));
+        assert!(pending.remove(solution_id_1, Some(())).is_none());
+        assert!(pending.is_requested(solution_id_1, addr_1));
+        assert!(!pending.is_requested(solution_id_1, addr_2));
+        // Check the request for the second solution ID is unaffected.
+        assert!(pending.contains(solution_id_2));
+        assert!(pending.is_requested(solution_id_2, addr_2));
+    }
+
+    #[test]
+    fn test_is_requested_after_timeout() {
+        let rng = &mut TestRng::default();
+
+        // Initialize the pending queue.
+        let pending = Pending::<TransmissionID<CurrentNetwork>, ()>::with_clock(mocked_now);
+
+        // Initialize the solution ID.
+        let solution_id = TransmissionID::Solution(rng.gen::<u64>().into());
+        // Initialize the SocketAddr.
+        let addr = SocketAddr::from(([127, 0, 0, 1], 1234));
+
+        // Send a request to the peer.
+        let (callback_sender_1, _) = oneshot::channel();
+        assert!(pending.insert(solution_id, addr, Some((callback_sender_1, true))));
+        assert_eq!(pending.num_sent_requests(solution_id), 1);
+
+        // Let the callback expire.
+        advance_mocked_clock(CALLBACK_TIMEOUT_IN_SECS + 1);
+        // Ensure the expired request is no longer counted, while a late response is still expected.
+        assert_eq!(pending.num_sent_requests(solution_id), 0);
+        assert!(pending.is_requested(solution_id, addr));
+
+        // Re-request the item from the same peer.
+        let (callback_sender_2, _) = oneshot::channel();
+        assert!(!pending.insert(solution_id, addr, Some((callback_sender_2, true))));
+        assert_eq!(pending.num_sent_requests(solution_id), 1);
+
+        // Let the re-request be forgotten.
+        advance_mocked_clock(REQUEST_HISTORY_IN_SECS + 1);
+        // Ensure a response is no longer expected from the peer.
+        assert!(!pending.is_requested(solution_id, addr));
+    }
+
+    #[test]
+    fn test_remove_notifies_every_peer() {
+        let rng = &mut TestRng::default();
+
+        // Initialize the pending queue.
+        let pending = Pending::<TransmissionID<CurrentNetwork>, ()>::new();
+
+        // Initialize the solution ID.
+        let solution_id = TransmissionID::Solution(rng.gen::<u64>().into());
+
+        // Initialize the SocketAddrs.
+        let addr_1 = SocketAddr::from(([127, 0, 0, 1], 1234));
+        let addr_2 = SocketAddr::from(([127, 0, 0, 1], 2345));
+        let addr_3 = SocketAddr::from(([127, 0, 0, 1], 3456));
+
+        // Send requests to the first and third peers, and skip sending it to the second peer.
+        let (callback_sender_1, mut callback_receiver_1) = oneshot::channel();
+        let (callback_sender_2, mut callback_receiver_2) = oneshot::channel();
+        let (callback_sender_3, mut callback_receiver_3) = oneshot::channel();
+        assert!(pending.insert(solution_id, addr_1, Some((callback_sender_1, true))));
+        assert!(pending.insert(solution_id, addr_2, Some((callback_sender_2, false))));
+        assert!(pending.insert(solution_id, addr_3, Some((callback_sender_3, true))));
+        assert_eq!(pending.num_callbacks(solution_id), 3);
+
+        // Finish the item with the response of the first peer.
+        assert_eq!(pending.remove(solution_id, Some(())), Some(HashSet::from([addr_1, addr_2, addr_3])));
+        // Ensure the callbacks of every peer are notified.
+        assert!(callback_receiver_1.try_recv().is_ok());
+        assert!(callback_receiver_2.try_recv().is_ok());
+        assert!(callback_receiver_3.try_recv().is_ok());
+        // Ensure the solution ID is no longer pending.
+        assert!(!pending.contains(solution_id));
+        assert_eq!(pending.num_callbacks(solution_id), 0);
+        assert!(pending.is_empty());
+
+        // Ensure a late response from the third peer is expected, while one from the second peer is unsolicited.
+        assert!(pending.is_requested(solution_id, addr_3));
+        assert!(!pending.is_requested(solution_id, addr_2));
+    }
 }
 
 #[cfg(test)]
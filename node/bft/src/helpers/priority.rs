@@ -0,0 +1,135 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkvm::{
+    ledger::{
+        narwhal::{Data, Transmission, TransmissionID},
+        puzzle::Solution,
+    },
+    prelude::Network,
+};
+
+/// The default maximum number of consensus-critical solutions in the priority lane of the memory pool.
+pub const DEFAULT_MAX_PRIORITY_SOLUTIONS: usize = 8;
+/// The default maximum number of consensus-critical ratifications in the priority lane of the memory pool.
+pub const DEFAULT_MAX_PRIORITY_RATIFICATIONS: usize = 4;
+
+/// The priority of a transmission, as classified when it enters the memory pool.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TransmissionPriority {
+    /// The transmission is needed for the liveness of consensus, and is proposed ahead of ordinary transmissions.
+    Critical,
+    /// The transmission is proposed in the order it was received.
+    Ordinary,
+}
+
+impl TransmissionPriority {
+    /// Returns the priority of the given transmission, given the latest epoch hash, if it is known.
+    /// Ratifications, and solutions targeting the current epoch, are consensus-critical.
+    ///
+    /// Note: This classification is cheap, as a serialized solution is never deserialized, and is considered ordinary.
+    pub fn classify<N: Network>(transmission: &Transmission<N>, latest_epoch_hash: Option<N::BlockHash>) -> Self {
+        match transmission {
+            Transmission::Ratification => Self::Critical,
+            Transmission::Solution(Data::Object(solution)) => Self::classify_solution(solution, latest_epoch_hash),
+            Transmission::Solution(Data::Buffer(..)) | Transmission::Transaction(..) => Self::Ordinary,
+        }
+    }
+
+    /// Returns the priority of the given solution, given the latest epoch hash, if it is known.
+    /// A solution targeting the current epoch is consensus-critical.
+    pub fn classify_solution<N: Network>(solution: &Solution<N>, latest_epoch_hash: Option<N::BlockHash>) -> Self {
+        match latest_epoch_hash {
+            Some(epoch_hash) if solution.epoch_hash() == epoch_hash => Self::Critical,
+            _ => Self::Ordinary,
+        }
+    }
+
+    /// Returns `true` if the transmission is consensus-critical.
+    pub const fn is_critical(&self) -> bool {
+        matches!(self, Self::Critical)
+    }
+}
+
+/// The quotas of the priority lane, which admits consensus-critical transmissions when the memory pool is full.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PriorityLaneConfig {
+    /// The maximum number of solutions in the priority lane.
+    pub max_solutions: usize,
+    /// The maximum number of ratifications in the priority lane.
+    pub max_ratifications: usize,
+}
+
+impl Default for PriorityLaneConfig {
+    /// Returns the default priority lane configuration.
+    fn default() -> Self {
+        Self { max_solutions: DEFAULT_MAX_PRIORITY_SOLUTIONS, max_ratifications: DEFAULT_MAX_PRIORITY_RATIFICATIONS }
+    }
+}
+
+impl PriorityLaneConfig {
+    /// Returns the maximum number of transmissions of the same kind as the given transmission ID in the priority lane.
+    /// Note: Transactions are never admitted into the priority lane.
+    pub const fn quota<N: Network>(&self, transmission_id: &TransmissionID<N>) -> usize {
+        match transmission_id {
+            TransmissionID::Ratification => self.max_ratifications,
+            TransmissionID::Solution(..) => self.max_solutions,
+            TransmissionID::Transaction(..) => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkvm::prelude::{Field, Rng, TestRng, Uniform};
+
+    use ::bytes::Bytes;
+
+    type CurrentNetwork = snarkvm::prelude::MainnetV0;
+
+    #[test]
+    fn test_classify() {
+        let rng = &mut TestRng::default();
+
+        // Sample random fake bytes.
+        let data = |rng: &mut TestRng| Data::Buffer(Bytes::from((0..512).map(|_| rng.gen::<u8>()).collect::<Vec<_>>()));
+        let epoch_hash = Some(Field::<CurrentNetwork>::rand(rng).into());
+
+        // Ensure ratifications are consensus-critical.
+        let priority = TransmissionPriority::classify::<CurrentNetwork>(&Transmission::Ratification, epoch_hash);
+        assert_eq!(priority, TransmissionPriority::Critical);
+        // Ensure transactions are ordinary.
+        let priority =
+            TransmissionPriority::classify::<CurrentNetwork>(&Transmission::Transaction(data(rng)), epoch_hash);
+        assert_eq!(priority, TransmissionPriority::Ordinary);
+        // Ensure serialized solutions are ordinary, as they are not deserialized to be classified.
+        let priority = TransmissionPriority::classify::<CurrentNetwork>(&Transmission::Solution(data(rng)), epoch_hash);
+        assert_eq!(priority, TransmissionPriority::Ordinary);
+    }
+
+    #[test]
+    fn test_quota() {
+        let rng = &mut TestRng::default();
+        let config = PriorityLaneConfig { max_solutions: 3, max_ratifications: 2 };
+
+        // Ensure each kind of transmission has its own quota, and transactions have none.
+        assert_eq!(config.quota::<CurrentNetwork>(&TransmissionID::Ratification), 2);
+        assert_eq!(config.quota::<CurrentNetwork>(&TransmissionID::Solution(rng.gen::<u64>().into())), 3);
+        assert_eq!(
+            config.quota::<CurrentNetwork>(&TransmissionID::Transaction(Field::<CurrentNetwork>::rand(rng).into())),
+            0
+        );
+    }
+}
@@ -29,6 +29,8 @@ use std::sync::Arc;
 pub struct Ready<N: Network> {
     /// The current map of `(transmission ID, transmission)` entries.
     transmissions: Arc<RwLock<IndexMap<TransmissionID<N>, Transmission<N>>>>,
+    /// The set of consensus-critical transmission IDs, which are drained ahead of the other transmissions.
+    priority: Arc<RwLock<IndexSet<TransmissionID<N>>>>,
 }
 
 impl<N: Network> Default for Ready<N> {
@@ -41,7 +43,7 @@ impl<N: Network> Default for Ready<N> {
 impl<N: Network> Ready<N> {
     /// Initializes a new instance of the ready queue.
     pub fn new() -> Self {
-        Self { transmissions: Default::default() }
+        Self { transmissions: Default::default(), priority: Default::default() }
     }
 
     /// Returns `true` if the ready queue is empty.
@@ -54,6 +56,12 @@ impl<N: Network> Ready<N> {
         self.transmissions.read().len()
     }
 
+    /// Returns the number of consensus-critical transmissions of the same kind as the given transmission ID.
+    pub fn num_priority_transmissions(&self, transmission_id: &TransmissionID<N>) -> usize {
+        let kind = std::mem::discriminant(transmission_id);
+        self.priority.read().iter().filter(|id| std::mem::discriminant(*id) == kind).count()
+    }
+
     /// Returns the number of ratifications in the ready queue.
     pub fn num_ratifications(&self) -> usize {
         self.transmissions.read().keys().filter(|id| matches!(id, TransmissionID::Ratification)).count()
@@ -117,14 +125,56 @@ impl<N: Network> Ready<N> {
         is_new
     }
 
+    /// Inserts the specified consensus-critical (`transmission ID`, `transmission`) to the ready queue,
+    /// to be drained ahead of the other transmissions.
+    /// Returns `true` if the transmission is new, and was added to the ready queue.
+    pub fn insert_priority(
+        &self,
+        transmission_id: impl Into<TransmissionID<N>>,
+        transmission: Transmission<N>,
+    ) -> bool {
+        let transmission_id = transmission_id.into();
+        // Acquire the write locks.
+        let mut transmissions = self.transmissions.write();
+        let mut priority = self.priority.write();
+        // Insert the transmission ID.
+        let is_new = transmissions.insert(transmission_id, transmission).is_none();
+        // Mark the transmission ID as consensus-critical.
+        if is_new {
+            priority.insert(transmission_id);
+        }
+        // Return whether the transmission is new.
+        is_new
+    }
+
+    /// Removes the specified `transmission ID` from the ready queue, returning the transmission if it existed.
+    pub fn remove(&self, transmission_id: impl Into<TransmissionID<N>>) -> Option<Transmission<N>> {
+        let transmission_id = transmission_id.into();
+        // Acquire the write locks.
+        let mut transmissions = self.transmissions.write();
+        let mut priority = self.priority.write();
+        // Remove the transmission ID.
+        priority.shift_remove(&transmission_id);
+        transmissions.shift_remove(&transmission_id)
+    }
+
     /// Removes up to the specified number of transmissions and returns them.
+    /// Note: The consensus-critical transmissions are drained first.
     pub fn drain(&self, num_transmissions: usize) -> IndexMap<TransmissionID<N>, Transmission<N>> {
-        // Acquire the write lock.
+        // Acquire the write locks.
         let mut transmissions = self.transmissions.write();
-        // Determine the number of transmissions to drain.
-        let range = 0..transmissions.len().min(num_transmissions);
+        let mut priority = self.priority.write();
+        // Drain the consensus-critical transmissions first.
+        let range = 0..priority.len().min(num_transmissions);
+        let mut drained = priority
+            .drain(range)
+            .filter_map(|id| transmissions.shift_remove(&id).map(|transmission| (id, transmission)))
+            .collect::<IndexMap<_, _>>();
+        // Determine the number of remaining transmissions to drain.
+        let range = 0..transmissions.len().min(num_transmissions.saturating_sub(drained.len()));
         // Drain the transmission IDs.
-        transmissions.drain(range).collect::<IndexMap<_, _>>()
+        drained.extend(transmissions.drain(range));
+        drained
     }
 }
 
@@ -223,4 +273,35 @@ mod tests {
         // Check the number of transmissions.
         assert_eq!(ready.num_transmissions(), 1);
     }
+
+    #[test]
+    fn test_ready_priority() {
+        let rng = &mut TestRng::default();
+
+        // Sample random fake bytes.
+        let data = |rng: &mut TestRng| Data::Buffer(Bytes::from((0..512).map(|_| rng.gen::<u8>()).collect::<Vec<_>>()));
+
+        // Initialize the ready queue.
+        let ready = Ready::<CurrentNetwork>::new();
+
+        // Insert the ordinary solutions, followed by a consensus-critical solution.
+        let solution_id_1 = TransmissionID::Solution(rng.gen::<u64>().into());
+        let solution_id_2 = TransmissionID::Solution(rng.gen::<u64>().into());
+        let solution_id_3 = TransmissionID::Solution(rng.gen::<u64>().into());
+        assert!(ready.insert(solution_id_1, Transmission::Solution(data(rng))));
+        assert!(ready.insert(solution_id_2, Transmission::Solution(data(rng))));
+        assert!(ready.insert_priority(solution_id_3, Transmission::Solution(data(rng))));
+        assert!(!ready.insert_priority(solution_id_3, Transmission::Solution(data(rng))));
+
+        // Check the number of transmissions.
+        assert_eq!(ready.num_transmissions(), 3);
+        assert_eq!(ready.num_priority_transmissions(&solution_id_1), 1);
+        assert_eq!(ready.num_priority_transmissions(&TransmissionID::Ratification), 0);
+
+        // Ensure the consensus-critical solution is drained first.
+        let transmission_ids = ready.drain(2).into_keys().collect::<Vec<_>>();
+        assert_eq!(transmission_ids, vec![solution_id_3, solution_id_1]);
+        assert_eq!(ready.num_priority_transmissions(&solution_id_1), 0);
+        assert_eq!(ready.transmission_ids(), [solution_id_2].into_iter().collect::<IndexSet<_>>());
+    }
 }
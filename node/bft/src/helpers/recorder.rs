@@ -0,0 +1,387 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::events::{BatchPropose, BatchSignature};
+use snarkvm::{
+    console::prelude::*,
+    ledger::{
+        block::Block,
+        narwhal::{BatchCertificate, BatchHeader, Transmission, TransmissionID},
+    },
+    prelude::{Address, Field},
+};
+
+use indexmap::IndexMap;
+use parking_lot::Mutex;
+use std::{
+    fmt,
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    marker::PhantomData,
+    net::SocketAddr,
+    path::Path,
+};
+
+/// The magic bytes at the start of an event log.
+const EVENT_LOG_MAGIC: [u8; 8] = *b"SNOSBFTL";
+/// The version of the event log format.
+const EVENT_LOG_VERSION: u8 = 1;
+/// The maximum size of a record in an event log, in bytes.
+const MAX_RECORD_SIZE: u32 = 1 << 30;
+
+/// An event that mutated the state of the memory pool, as recorded in an event log.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RecordedEvent<N: Network> {
+    /// A batch proposal, as processed from the given peer.
+    BatchPropose { peer_ip: SocketAddr, address: Address<N>, batch_propose: BatchPropose<N> },
+    /// A batch signature, as processed from the given peer.
+    BatchSignature { peer_ip: SocketAddr, address: Address<N>, batch_signature: BatchSignature<N> },
+    /// A batch certificate, as processed from the given peer.
+    BatchCertified { peer_ip: SocketAddr, address: Address<N>, certificate: BatchCertificate<N> },
+    /// A certificate, as fetched from a peer.
+    Certificate(BatchCertificate<N>),
+    /// A transmission, as fetched from a peer, or as received unconfirmed.
+    Transmission { transmission_id: TransmissionID<N>, transmission: Transmission<N> },
+    /// A batch certificate of this node, along with its transmissions.
+    Certified { certificate: BatchCertificate<N>, transmissions: IndexMap<TransmissionID<N>, Transmission<N>> },
+    /// An attempt to advance from the given round, once the batch proposal timer fired.
+    Timeout { round: u64 },
+    /// A leader certificate, as committed by the BFT.
+    Committed { round: u64, leader_certificate_id: Field<N> },
+}
+
+impl<N: Network> RecordedEvent<N> {
+    /// Returns the name of the recorded event.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::BatchPropose { .. } => "BatchPropose",
+            Self::BatchSignature { .. } => "BatchSignature",
+            Self::BatchCertified { .. } => "BatchCertified",
+            Self::Certificate(..) => "Certificate",
+            Self::Transmission { .. } => "Transmission",
+            Self::Certified { .. } => "Certified",
+            Self::Timeout { .. } => "Timeout",
+            Self::Committed { .. } => "Committed",
+        }
+    }
+}
+
+impl<N: Network> ToBytes for RecordedEvent<N> {
+    fn write_le<W: Write>(&self, mut writer: W) -> IoResult<()> {
+        match self {
+            Self::BatchPropose { peer_ip, address, batch_propose } => {
+                0u8.write_le(&mut writer)?;
+                peer_ip.write_le(&mut writer)?;
+                address.write_le(&mut writer)?;
+                batch_propose.write_le(&mut writer)
+            }
+            Self::BatchSignature { peer_ip, address, batch_signature } => {
+                1u8.write_le(&mut writer)?;
+                peer_ip.write_le(&mut writer)?;
+                address.write_le(&mut writer)?;
+                batch_signature.write_le(&mut writer)
+            }
+            Self::BatchCertified { peer_ip, address, certificate } => {
+                2u8.write_le(&mut writer)?;
+                peer_ip.write_le(&mut writer)?;
+                address.write_le(&mut writer)?;
+                certificate.write_le(&mut writer)
+            }
+            Self::Certificate(certificate) => {
+                3u8.write_le(&mut writer)?;
+                certificate.write_le(&mut writer)
+            }
+            Self::Transmission { transmission_id, transmission } => {
+                4u8.write_le(&mut writer)?;
+                transmission_id.write_le(&mut writer)?;
+                transmission.write_le(&mut writer)
+            }
+            Self::Certified { certificate, transmissions } => {
+                5u8.write_le(&mut writer)?;
+                certificate.write_le(&mut writer)?;
+                u32::try_from(transmissions.len()).map_err(error)?.write_le(&mut writer)?;
+                for (transmission_id, transmission) in transmissions {
+                    transmission_id.write_le(&mut writer)?;
+                    transmission.write_le(&mut writer)?;
+                }
+                Ok(())
+            }
+            Self::Timeout { round } => {
+                6u8.write_le(&mut writer)?;
+                round.write_le(&mut writer)
+            }
+            Self::Committed { round, leader_certificate_id } => {
+                7u8.write_le(&mut writer)?;
+                round.write_le(&mut writer)?;
+                leader_certificate_id.write_le(&mut writer)
+            }
+        }
+    }
+}
+
+impl<N: Network> FromBytes for RecordedEvent<N> {
+    fn read_le<R: Read>(mut reader: R) -> IoResult<Self> {
+        match u8::read_le(&mut reader)? {
+            0 => Ok(Self::BatchPropose {
+                peer_ip: SocketAddr::read_le(&mut reader)?,
+                address: Address::read_le(&mut reader)?,
+                batch_propose: BatchPropose::read_le(&mut reader)?,
+            }),
+            1 => Ok(Self::BatchSignature {
+                peer_ip: SocketAddr::read_le(&mut reader)?,
+                address: Address::read_le(&mut reader)?,
+                batch_signature: BatchSignature::read_le(&mut reader)?,
+            }),
+            2 => Ok(Self::BatchCertified {
+                peer_ip: SocketAddr::read_le(&mut reader)?,
+                address: Address::read_le(&mut reader)?,
+                certificate: BatchCertificate::read_le(&mut reader)?,
+            }),
+            3 => Ok(Self::Certificate(BatchCertificate::read_le(&mut reader)?)),
+            4 => Ok(Self::Transmission {
+                transmission_id: TransmissionID::read_le(&mut reader)?,
+                transmission: Transmission::read_le(&mut reader)?,
+            }),
+            5 => {
+                let certificate = BatchCertificate::read_le(&mut reader)?;
+                let num_transmissions = u32::read_le(&mut reader)?;
+                // Ensure the number of transmissions is within the batch limit.
+                if num_transmissions as usize > BatchHeader::<N>::MAX_TRANSMISSIONS_PER_BATCH {
+                    return Err(error(format!(
+                        "Too many transmissions in a recorded certificate ({num_transmissions})"
+                    )));
+                }
+                let mut transmissions = IndexMap::with_capacity(num_transmissions as usize);
+                for _ in 0..num_transmissions {
+                    transmissions.insert(TransmissionID::read_le(&mut reader)?, Transmission::read_le(&mut reader)?);
+                }
+                Ok(Self::Certified { certificate, transmissions })
+            }
+            6 => Ok(Self::Timeout { round: u64::read_le(&mut reader)? }),
+            7 => Ok(Self::Committed {
+                round: u64::read_le(&mut reader)?,
+                leader_certificate_id: Field::read_le(&mut reader)?,
+            }),
+            variant => Err(error(format!("Invalid recorded event variant '{variant}'"))),
+        }
+    }
+}
+
+/// A recorded event, along with the UTC epoch timestamp at which it was recorded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Record<N: Network> {
+    /// The UTC epoch timestamp of the record.
+    pub timestamp: i64,
+    /// The recorded event.
+    pub event: RecordedEvent<N>,
+}
+
+impl<N: Network> ToBytes for Record<N> {
+    fn write_le<W: Write>(&self, mut writer: W) -> IoResult<()> {
+        self.timestamp.write_le(&mut writer)?;
+        self.event.write_le(&mut writer)
+    }
+}
+
+impl<N: Network> FromBytes for Record<N> {
+    fn read_le<R: Read>(mut reader: R) -> IoResult<Self> {
+        let timestamp = i64::read_le(&mut reader)?;
+        let event = RecordedEvent::read_le(&mut reader)?;
+        Ok(Self { timestamp, event })
+    }
+}
+
+/// Appends the events that mutate the state of the memory pool to an event log, so they can be replayed.
+///
+/// The event log starts with a header containing the address of the node and the genesis block,
+/// followed by the records, each prefixed with its length in bytes.
+pub struct EventRecorder<N: Network> {
+    /// The writer of the event log.
+    writer: Mutex<BufWriter<File>>,
+    /// The phantom data.
+    _phantom: PhantomData<N>,
+}
+
+impl<N: Network> EventRecorder<N> {
+    /// Creates a new event log at the given path, for the node with the given address and genesis block.
+    pub fn create(path: impl AsRef<Path>, address: Address<N>, genesis: &Block<N>) -> Result<Self> {
+        let path = path.as_ref();
+        let file =
+            File::create(path).map_err(|e| anyhow!("Failed to create the event log '{}' - {e}", path.display()))?;
+        let mut writer = BufWriter::new(file);
+        // Write the header.
+        writer.write_all(&EVENT_LOG_MAGIC)?;
+        EVENT_LOG_VERSION.write_le(&mut writer)?;
+        let mut header = address.to_bytes_le()?;
+        genesis.write_le(&mut header)?;
+        write_frame(&mut writer, &header)?;
+        writer.flush()?;
+        Ok(Self { writer: Mutex::new(writer), _phantom: PhantomData })
+    }
+
+    /// Appends the given event to the event log, with the given timestamp.
+    pub fn record(&self, timestamp: i64, event: RecordedEvent<N>) -> Result<()> {
+        let bytes = Record { timestamp, event }.to_bytes_le()?;
+        let mut writer = self.writer.lock();
+        write_frame(&mut *writer, &bytes)?;
+        // Flush every record, so that the event log is complete up to the last event if the node crashes.
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+impl<N: Network> fmt::Debug for EventRecorder<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventRecorder").finish_non_exhaustive()
+    }
+}
+
+/// An event log, as read back from disk.
+#[derive(Clone, Debug)]
+pub struct EventLog<N: Network> {
+    /// The address of the node that recorded the event log.
+    pub address: Address<N>,
+    /// The genesis block of the node that recorded the event log.
+    pub genesis: Block<N>,
+    /// The records, in the order they were recorded.
+    pub records: Vec<Record<N>>,
+}
+
+impl<N: Network> EventLog<N> {
+    /// Reads the event log at the given path.
+    ///
+    /// Note: A truncated record at the end of the event log (e.g. from a crash) is ignored.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| anyhow!("Failed to open the event log '{}' - {e}", path.display()))?;
+        let mut reader = BufReader::new(file);
+        // Read the header.
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        ensure!(magic == EVENT_LOG_MAGIC, "'{}' is not an event log", path.display());
+        let version = u8::read_le(&mut reader)?;
+        ensure!(version == EVENT_LOG_VERSION, "Unsupported event log version {version}");
+        let Some(header) = read_frame(&mut reader)? else { bail!("The event log header is truncated") };
+        let mut header = &header[..];
+        let address = Address::read_le(&mut header)?;
+        let genesis = Block::read_le(&mut header)?;
+        // Read the records.
+        let mut records = Vec::new();
+        while let Some(bytes) = read_frame(&mut reader)? {
+            records.push(Record::from_bytes_le(&bytes)?);
+        }
+        Ok(Self { address, genesis, records })
+    }
+
+    /// Returns the leader certificates that were committed, as `(round, leader certificate ID)`, in order.
+    pub fn commits(&self) -> Vec<(u64, Field<N>)> {
+        self.records
+            .iter()
+            .filter_map(|record| match record.event {
+                RecordedEvent::Committed { round, leader_certificate_id } => Some((round, leader_certificate_id)),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Writes the given bytes, prefixed with their length.
+fn write_frame<W: Write>(mut writer: W, bytes: &[u8]) -> Result<()> {
+    let length = u32::try_from(bytes.len())?;
+    ensure!(length <= MAX_RECORD_SIZE, "Record is too large ({length} bytes)");
+    length.write_le(&mut writer)?;
+    writer.write_all(bytes)?;
+    Ok(())
+}
+
+/// Reads the next length-prefixed bytes, returning `None` at the end of the input, or if the frame is truncated.
+fn read_frame<R: Read>(mut reader: R) -> Result<Option<Vec<u8>>> {
+    let mut length = [0u8; 4];
+    match reader.read_exact(&mut length) {
+        Ok(()) => (),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    }
+    let length = u32::from_le_bytes(length);
+    ensure!(length <= MAX_RECORD_SIZE, "Record is too large ({length} bytes)");
+    let mut bytes = vec![0u8; length as usize];
+    match reader.read_exact(&mut bytes) {
+        Ok(()) => Ok(Some(bytes)),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkvm::{
+        ledger::narwhal::batch_certificate::test_helpers::sample_batch_certificate,
+        prelude::{TestRng, Uniform},
+    };
+
+    type CurrentNetwork = snarkvm::prelude::MainnetV0;
+
+    #[test]
+    fn test_event_log_round_trip() {
+        let rng = &mut TestRng::default();
+        let path = std::env::temp_dir().join(format!("snarkos-bft-event-log-{}", u64::rand(rng)));
+
+        // Record a few events.
+        let address = Address::<CurrentNetwork>::rand(rng);
+        let genesis = Block::<CurrentNetwork>::from_bytes_le(CurrentNetwork::genesis_bytes()).unwrap();
+        let peer_ip = SocketAddr::from(([127, 0, 0, 1], 5001));
+        let certificate = sample_batch_certificate(rng);
+        let events = vec![
+            RecordedEvent::BatchCertified { peer_ip, address: certificate.author(), certificate: certificate.clone() },
+            RecordedEvent::Certificate(certificate.clone()),
+            RecordedEvent::Timeout { round: 2 },
+            RecordedEvent::Committed { round: 2, leader_certificate_id: certificate.id() },
+        ];
+        let recorder = EventRecorder::create(&path, address, &genesis).unwrap();
+        for (timestamp, event) in events.iter().enumerate() {
+            recorder.record(timestamp as i64, event.clone()).unwrap();
+        }
+        drop(recorder);
+
+        // Append a truncated record, as if the node crashed while recording.
+        {
+            let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+            file.write_all(&100u32.to_le_bytes()).unwrap();
+            file.write_all(&[0u8; 10]).unwrap();
+        }
+
+        // Ensure the event log is read back, without the truncated record.
+        let log = EventLog::<CurrentNetwork>::open(&path).unwrap();
+        assert_eq!(log.address, address);
+        assert_eq!(log.genesis, genesis);
+        assert_eq!(log.records.len(), events.len());
+        for (timestamp, (record, event)) in log.records.iter().zip(events).enumerate() {
+            assert_eq!(record.timestamp, timestamp as i64);
+            assert_eq!(record.event, event);
+        }
+        assert_eq!(log.commits(), vec![(2, certificate.id())]);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_event_log_rejects_other_files() {
+        let path =
+            std::env::temp_dir().join(format!("snarkos-bft-not-an-event-log-{}", u64::rand(&mut TestRng::default())));
+        std::fs::write(&path, b"not an event log").unwrap();
+        assert!(EventLog::<CurrentNetwork>::open(&path).is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,229 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use parking_lot::Mutex;
+use std::{
+    collections::{HashMap, VecDeque},
+    net::SocketAddr,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// The number of sequence numbers below the highest sequence number of a session that may still be delivered.
+pub const SEQUENCE_REORDER_WINDOW: u64 = 64;
+/// The number of previous sessions of a peer that are remembered, to identify their stale frames.
+const MAX_RETIRED_SESSIONS: usize = 8;
+
+/// The status of a sequenced event received from a peer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SequenceStatus {
+    /// The event is new, and should be processed.
+    Accepted,
+    /// The event was already received in the current session of the peer.
+    Duplicate,
+    /// The event is from a previous session of the peer.
+    Stale,
+    /// The event is too far behind the highest sequence number of the current session of the peer.
+    OutOfWindow,
+}
+
+/// The sequence state of the events received from a peer.
+#[derive(Default)]
+struct PeerSequence {
+    /// The current session of the peer, if a hello event was received.
+    session: Option<u64>,
+    /// The highest sequence number received in the current session.
+    highest: u64,
+    /// The bitmask of the received sequence numbers in the window, where bit `i` is `highest - i`.
+    received: u64,
+    /// The previous sessions of the peer, from oldest to newest.
+    retired: VecDeque<u64>,
+}
+
+impl PeerSequence {
+    /// Returns the status of the given event, and records it if it is accepted.
+    fn check(&mut self, session: u64, sequence: u64, is_hello: bool) -> SequenceStatus {
+        // Drop the events of a previous session.
+        if self.retired.contains(&session) {
+            return SequenceStatus::Stale;
+        }
+        // Start a new session on a hello event.
+        if is_hello && self.session != Some(session) {
+            if let Some(previous) = self.session.replace(session) {
+                if self.retired.len() == MAX_RETIRED_SESSIONS {
+                    self.retired.pop_front();
+                }
+                self.retired.push_back(previous);
+            }
+            self.highest = sequence;
+            self.received = 1;
+            return SequenceStatus::Accepted;
+        }
+        // Drop the events that precede the hello event of their session.
+        if self.session != Some(session) {
+            return SequenceStatus::Stale;
+        }
+        // Accept the events ahead of the highest sequence number, and shift the window.
+        if sequence > self.highest {
+            let shift = sequence - self.highest;
+            self.received = if shift >= SEQUENCE_REORDER_WINDOW { 0 } else { self.received << shift };
+            self.received |= 1;
+            self.highest = sequence;
+            return SequenceStatus::Accepted;
+        }
+        // Accept the events within the window that were not yet received.
+        let offset = self.highest - sequence;
+        if offset >= SEQUENCE_REORDER_WINDOW {
+            return SequenceStatus::OutOfWindow;
+        }
+        match self.received & (1 << offset) != 0 {
+            true => SequenceStatus::Duplicate,
+            false => {
+                self.received |= 1 << offset;
+                SequenceStatus::Accepted
+            }
+        }
+    }
+}
+
+/// A tracker of the sequence numbers of the events received from each peer.
+///
+/// The state of a peer is kept after it disconnects, so that the frames of its previous session
+/// that are still in flight after it reconnects are identified and dropped.
+#[derive(Default)]
+pub struct SequenceTracker {
+    /// The map of peer IPs to their sequence state.
+    peers: Mutex<HashMap<SocketAddr, PeerSequence>>,
+    /// The number of dropped duplicate events.
+    num_duplicates: AtomicU64,
+    /// The number of dropped stale events.
+    num_stale: AtomicU64,
+    /// The number of dropped out-of-window events.
+    num_out_of_window: AtomicU64,
+}
+
+impl SequenceTracker {
+    /// Returns the status of the given event from the given peer, and records it if it is accepted.
+    pub fn check(&self, peer_ip: SocketAddr, session: u64, sequence: u64, is_hello: bool) -> SequenceStatus {
+        let status = self.peers.lock().entry(peer_ip).or_default().check(session, sequence, is_hello);
+        let counter = match status {
+            SequenceStatus::Accepted => return status,
+            SequenceStatus::Duplicate => &self.num_duplicates,
+            SequenceStatus::Stale => &self.num_stale,
+            SequenceStatus::OutOfWindow => &self.num_out_of_window,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        status
+    }
+
+    /// Returns the number of dropped duplicate events.
+    pub fn num_duplicates(&self) -> u64 {
+        self.num_duplicates.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of dropped stale events.
+    pub fn num_stale(&self) -> u64 {
+        self.num_stale.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of dropped out-of-window events.
+    pub fn num_out_of_window(&self) -> u64 {
+        self.num_out_of_window.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkos_node_bft_events::{Event, SequencedEvent, SequencedEventCodec, ValidatorsRequest};
+
+    use bytes::BytesMut;
+    use tokio_util::codec::{Decoder, Encoder};
+
+    type CurrentNetwork = snarkvm::prelude::MainnetV0;
+
+    /// Encodes the given number of events in the given session, and returns the decoded frames.
+    fn sample_frames(session: u64, num_events: usize) -> Vec<SequencedEvent<CurrentNetwork>> {
+        let mut codec = SequencedEventCodec::<CurrentNetwork>::new(session);
+        let mut bytes = BytesMut::new();
+        for _ in 0..num_events {
+            codec.encode(Event::ValidatorsRequest(ValidatorsRequest), &mut bytes).unwrap();
+        }
+        std::iter::from_fn(|| codec.decode(&mut bytes).unwrap()).collect()
+    }
+
+    /// Checks the given frame with the given tracker.
+    fn check(tracker: &SequenceTracker, peer_ip: SocketAddr, frame: &SequencedEvent<CurrentNetwork>) -> SequenceStatus {
+        tracker.check(peer_ip, frame.session, frame.sequence, matches!(frame.event, Event::Hello(..)))
+    }
+
+    #[test]
+    fn test_duplicates_and_window() {
+        let tracker = SequenceTracker::default();
+        let peer_ip = SocketAddr::from(([127, 0, 0, 1], 5000));
+
+        // Ensure the events of a session are accepted in order, and their duplicates are dropped.
+        assert_eq!(tracker.check(peer_ip, 1, 0, true), SequenceStatus::Accepted);
+        assert_eq!(tracker.check(peer_ip, 1, 1, false), SequenceStatus::Accepted);
+        assert_eq!(tracker.check(peer_ip, 1, 1, false), SequenceStatus::Duplicate);
+        assert_eq!(tracker.check(peer_ip, 1, 0, true), SequenceStatus::Duplicate);
+
+        // Ensure the events within the window are accepted out of order, once.
+        assert_eq!(tracker.check(peer_ip, 1, 10, false), SequenceStatus::Accepted);
+        assert_eq!(tracker.check(peer_ip, 1, 5, false), SequenceStatus::Accepted);
+        assert_eq!(tracker.check(peer_ip, 1, 5, false), SequenceStatus::Duplicate);
+
+        // Ensure the events behind the window are dropped.
+        assert_eq!(tracker.check(peer_ip, 1, 10 + SEQUENCE_REORDER_WINDOW, false), SequenceStatus::Accepted);
+        assert_eq!(tracker.check(peer_ip, 1, 10, false), SequenceStatus::OutOfWindow);
+        assert_eq!(tracker.check(peer_ip, 1, 11, false), SequenceStatus::Accepted);
+
+        assert_eq!(tracker.num_duplicates(), 3);
+        assert_eq!(tracker.num_out_of_window(), 1);
+        assert_eq!(tracker.num_stale(), 0);
+    }
+
+    #[test]
+    fn test_reconnect_drops_stale_frames() {
+        let tracker = SequenceTracker::default();
+        let peer_ip = SocketAddr::from(([127, 0, 0, 1], 5000));
+
+        // Receive the first frames of the old session, while the rest are still in flight.
+        let old_frames = sample_frames(1, 5);
+        assert_eq!(old_frames.len(), 6);
+        for frame in &old_frames[..3] {
+            assert_eq!(check(&tracker, peer_ip, frame), SequenceStatus::Accepted);
+        }
+
+        // The peer reconnects, and the frames of the new session are received.
+        let new_frames = sample_frames(2, 3);
+        assert_eq!(new_frames[0].event, Event::Hello(snarkos_node_bft_events::Hello::new(2)));
+        for frame in &new_frames[..2] {
+            assert_eq!(check(&tracker, peer_ip, frame), SequenceStatus::Accepted);
+        }
+
+        // Ensure the delayed frames of the old session are dropped, even interleaved with the new session.
+        for frame in &old_frames[3..] {
+            assert_eq!(check(&tracker, peer_ip, frame), SequenceStatus::Stale);
+        }
+        assert_eq!(check(&tracker, peer_ip, &new_frames[2]), SequenceStatus::Accepted);
+        // Ensure a replayed hello of the old session does not restore it.
+        assert_eq!(check(&tracker, peer_ip, &old_frames[0]), SequenceStatus::Stale);
+        assert_eq!(check(&tracker, peer_ip, &new_frames[1]), SequenceStatus::Duplicate);
+
+        // Ensure the stale frames are counted as such, and not as out-of-window events, which are logged as warnings.
+        assert_eq!(tracker.num_stale(), 4);
+        assert_eq!(tracker.num_out_of_window(), 0);
+        assert_eq!(tracker.num_duplicates(), 1);
+    }
+}
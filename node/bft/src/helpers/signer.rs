@@ -0,0 +1,689 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{spawn_blocking, MAX_BATCH_DELAY_IN_MS};
+use snarkos_account::Account;
+use snarkvm::prelude::{bail, ensure, Address, Field, FromBytes, Network, Result, Signature, ToBytes};
+
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use std::{fmt, net::SocketAddr, path::PathBuf, str::FromStr, sync::Arc, time::Duration};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::TcpStream,
+    sync::Mutex,
+    time::timeout,
+};
+
+/// The maximum number of milliseconds to wait for a remote signing operation.
+pub const REMOTE_SIGNER_TIMEOUT_IN_MS: u64 = MAX_BATCH_DELAY_IN_MS / 2; // ms
+/// The minimum length of the secret shared with a remote signer, in bytes.
+pub const MIN_REMOTE_SIGNER_SECRET_LENGTH: usize = 32;
+/// The maximum size of a frame exchanged with a remote signer, in bytes.
+const MAX_FRAME_SIZE: u32 = 1 << 16;
+/// The size of the nonces and proofs exchanged to authenticate a remote signer connection, in bytes.
+const AUTH_SIZE: usize = 32;
+/// The size of the message authentication code appended to each frame of a remote signer session, in bytes.
+const MAC_SIZE: usize = 32;
+
+/// The operation for which a signature is requested.
+///
+/// Note: These are all the operations that use the key of the validator. The heartbeats are not signed:
+/// neither the router heartbeat nor the primary ping carries a signature, and the router handshake
+/// is signed with the separate identity of the router, so there is no heartbeat context.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SigningContext {
+    /// Signing the batch header of our own batch proposal.
+    BatchProposal,
+    /// Endorsing the batch proposal of another validator.
+    BatchSignature,
+    /// Signing the nonce of a peer during the gateway handshake.
+    Handshake,
+}
+
+impl SigningContext {
+    /// Returns the byte representation of the signing context.
+    const fn to_u8(self) -> u8 {
+        match self {
+            Self::BatchProposal => 0,
+            Self::BatchSignature => 1,
+            Self::Handshake => 2,
+        }
+    }
+
+    /// Returns the signing context for the given byte representation.
+    fn from_u8(context: u8) -> Result<Self> {
+        match context {
+            0 => Ok(Self::BatchProposal),
+            1 => Ok(Self::BatchSignature),
+            2 => Ok(Self::Handshake),
+            _ => bail!("Invalid signing context '{context}'"),
+        }
+    }
+}
+
+/// The signing operations of a validator.
+#[async_trait]
+pub trait Signer<N: Network>: Send + Sync {
+    /// Returns the address of the signer.
+    fn address(&self) -> Address<N>;
+
+    /// Returns a signature for the given field elements, in the given context.
+    async fn sign(&self, context: SigningContext, message: Vec<Field<N>>) -> Result<Signature<N>>;
+
+    /// Returns a signature for the given bytes, in the given context.
+    async fn sign_bytes(&self, context: SigningContext, message: Vec<u8>) -> Result<Signature<N>>;
+}
+
+#[async_trait]
+impl<N: Network> Signer<N> for Account<N> {
+    /// Returns the address of the account.
+    fn address(&self) -> Address<N> {
+        Account::address(self)
+    }
+
+    /// Returns a signature for the given field elements, using the in-process private key.
+    async fn sign(&self, _context: SigningContext, message: Vec<Field<N>>) -> Result<Signature<N>> {
+        let account = self.clone();
+        spawn_blocking!(Account::sign(&account, &message, &mut rand::thread_rng()))
+    }
+
+    /// Returns a signature for the given bytes, using the in-process private key.
+    async fn sign_bytes(&self, _context: SigningContext, message: Vec<u8>) -> Result<Signature<N>> {
+        let account = self.clone();
+        spawn_blocking!(Account::sign_bytes(&account, &message, &mut rand::thread_rng()))
+    }
+}
+
+#[async_trait]
+impl<N: Network, S: Signer<N> + ?Sized> Signer<N> for Arc<S> {
+    fn address(&self) -> Address<N> {
+        (**self).address()
+    }
+
+    async fn sign(&self, context: SigningContext, message: Vec<Field<N>>) -> Result<Signature<N>> {
+        (**self).sign(context, message).await
+    }
+
+    async fn sign_bytes(&self, context: SigningContext, message: Vec<u8>) -> Result<Signature<N>> {
+        (**self).sign_bytes(context, message).await
+    }
+}
+
+/// The endpoint of a remote signer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SignerEndpoint {
+    /// A TCP endpoint, given as `tcp://<ip>:<port>`.
+    Tcp(SocketAddr),
+    /// A Unix socket endpoint, given as `unix://<path>`.
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl FromStr for SignerEndpoint {
+    type Err = anyhow::Error;
+
+    /// Parses a remote signer endpoint from a string.
+    fn from_str(endpoint: &str) -> Result<Self> {
+        if let Some(ip) = endpoint.strip_prefix("tcp://") {
+            return Ok(Self::Tcp(ip.parse()?));
+        }
+        #[cfg(unix)]
+        if let Some(path) = endpoint.strip_prefix("unix://") {
+            ensure!(!path.is_empty(), "Missing the path of the remote signer socket");
+            return Ok(Self::Unix(PathBuf::from(path)));
+        }
+        bail!("Invalid remote signer endpoint '{endpoint}' - expected 'tcp://<ip>:<port>' or 'unix://<path>'")
+    }
+}
+
+impl fmt::Display for SignerEndpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Tcp(ip) => write!(f, "tcp://{ip}"),
+            #[cfg(unix)]
+            Self::Unix(path) => write!(f, "unix://{}", path.display()),
+        }
+    }
+}
+
+/// A request to a remote signer.
+///
+/// A request is encoded as `[kind: u8][context: u8][message]`, where the message of a field request
+/// is the concatenation of the little-endian field elements.
+///
+/// Note: A request carries the whole message rather than its digest, as an Aleo signature is computed over
+/// the message itself, so a signature over a digest would not verify against the message. For the same reason,
+/// the authentication code of a frame covers the whole message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SignerRequest<N: Network> {
+    /// A request for the address of the signer.
+    Address,
+    /// A request to sign the given field elements.
+    Sign(SigningContext, Vec<Field<N>>),
+    /// A request to sign the given bytes.
+    SignBytes(SigningContext, Vec<u8>),
+}
+
+impl<N: Network> SignerRequest<N> {
+    /// Returns the byte representation of the request.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        match self {
+            Self::Address => Ok(vec![0, 0]),
+            Self::Sign(context, message) => {
+                let mut bytes = vec![1, context.to_u8()];
+                for field in message {
+                    field.write_le(&mut bytes)?;
+                }
+                Ok(bytes)
+            }
+            Self::SignBytes(context, message) => Ok([&[2, context.to_u8()], message.as_slice()].concat()),
+        }
+    }
+
+    /// Returns the request for the given byte representation.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        ensure!(bytes.len() >= 2, "Malformed remote signer request");
+        let (kind, context, mut message) = (bytes[0], bytes[1], &bytes[2..]);
+        match kind {
+            0 => Ok(Self::Address),
+            1 => {
+                let mut fields = Vec::new();
+                while !message.is_empty() {
+                    fields.push(Field::read_le(&mut message)?);
+                }
+                Ok(Self::Sign(SigningContext::from_u8(context)?, fields))
+            }
+            2 => Ok(Self::SignBytes(SigningContext::from_u8(context)?, message.to_vec())),
+            _ => bail!("Invalid remote signer request kind '{kind}'"),
+        }
+    }
+}
+
+/// A stream to a remote signer.
+trait SignerStream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> SignerStream for T {}
+
+/// A signer that delegates the signing operations to an external signing daemon,
+/// so that the private key of the validator never enters the node process.
+///
+/// The node and the daemon authenticate each other with a shared secret, and then exchange
+/// length-prefixed frames: a [`SignerRequest`], answered by `[0][response]` on success,
+/// where the response is the little-endian address or signature, or by `[1][message]` on failure.
+/// Each frame is authenticated with a [`SignerSession`] key derived from the secret and both nonces,
+/// so that a TCP connection cannot be hijacked or replayed after the handshake.
+pub struct RemoteSigner<N: Network> {
+    /// The endpoint of the remote signer.
+    endpoint: SignerEndpoint,
+    /// The secret shared with the remote signer.
+    secret: Vec<u8>,
+    /// The address of the remote signer.
+    address: Address<N>,
+    /// The maximum duration of a signing operation.
+    timeout: Duration,
+    /// The connection to the remote signer, if it is established.
+    connection: Mutex<Option<SignerConnection>>,
+}
+
+/// An authenticated connection to a remote signer.
+type SignerConnection = (Box<dyn SignerStream>, SignerSession);
+
+impl<N: Network> RemoteSigner<N> {
+    /// Connects to the remote signer at the given endpoint, and retrieves its address.
+    pub async fn connect(endpoint: SignerEndpoint, secret: Vec<u8>) -> Result<Self> {
+        Self::connect_with_timeout(endpoint, secret, Duration::from_millis(REMOTE_SIGNER_TIMEOUT_IN_MS)).await
+    }
+
+    /// Connects to the remote signer at the given endpoint with the given operation timeout,
+    /// and retrieves its address.
+    pub async fn connect_with_timeout(endpoint: SignerEndpoint, secret: Vec<u8>, timeout: Duration) -> Result<Self> {
+        ensure!(
+            secret.len() >= MIN_REMOTE_SIGNER_SECRET_LENGTH,
+            "The remote signer secret must be at least {MIN_REMOTE_SIGNER_SECRET_LENGTH} bytes"
+        );
+        // Retrieve the address of the remote signer.
+        let mut connection = None;
+        let response =
+            Self::request_with_timeout(&endpoint, &secret, timeout, &mut connection, SignerRequest::Address).await?;
+        let address = Address::read_le(&response[..])?;
+        Ok(Self { endpoint, secret, address, timeout, connection: Mutex::new(connection) })
+    }
+
+    /// Returns the endpoint of the remote signer.
+    pub const fn endpoint(&self) -> &SignerEndpoint {
+        &self.endpoint
+    }
+
+    /// Sends the given request to the remote signer, and returns the response.
+    async fn request(&self, request: SignerRequest<N>) -> Result<Vec<u8>> {
+        let mut connection = self.connection.lock().await;
+        Self::request_with_timeout(&self.endpoint, &self.secret, self.timeout, &mut connection, request).await
+    }
+
+    /// Sends the given request to the remote signer within the given timeout, and returns the response.
+    /// If the request fails, the connection is dropped, and re-established on the next request.
+    async fn request_with_timeout(
+        endpoint: &SignerEndpoint,
+        secret: &[u8],
+        duration: Duration,
+        connection: &mut Option<SignerConnection>,
+        request: SignerRequest<N>,
+    ) -> Result<Vec<u8>> {
+        let result = timeout(duration, Self::exchange(endpoint, secret, connection, request)).await;
+        match result {
+            Ok(Ok(response)) => Ok(response),
+            Ok(Err(error)) => {
+                *connection = None;
+                bail!("Remote signer at '{endpoint}' failed - {error}")
+            }
+            Err(_) => {
+                *connection = None;
+                bail!("Remote signer at '{endpoint}' timed out after {} ms", duration.as_millis())
+            }
+        }
+    }
+
+    /// Sends the given request to the remote signer, connecting to it if necessary, and returns the response.
+    async fn exchange(
+        endpoint: &SignerEndpoint,
+        secret: &[u8],
+        connection: &mut Option<SignerConnection>,
+        request: SignerRequest<N>,
+    ) -> Result<Vec<u8>> {
+        // Connect to the remote signer, if the connection is not established.
+        if connection.is_none() {
+            *connection = Some(Self::open(endpoint, secret).await?);
+        }
+        let Some((stream, session)) = connection.as_mut() else { bail!("Missing the connection to the remote signer") };
+        // Send the request.
+        session.write_frame(stream, &request.to_bytes()?).await?;
+        // Read the response.
+        let response = session.read_frame(stream).await?;
+        match response.split_first() {
+            Some((0, response)) => Ok(response.to_vec()),
+            Some((1, message)) => bail!("{}", String::from_utf8_lossy(message)),
+            _ => bail!("Malformed remote signer response"),
+        }
+    }
+
+    /// Opens an authenticated connection to the remote signer.
+    async fn open(endpoint: &SignerEndpoint, secret: &[u8]) -> Result<SignerConnection> {
+        let mut stream: Box<dyn SignerStream> = match endpoint {
+            SignerEndpoint::Tcp(ip) => Box::new(TcpStream::connect(ip).await?),
+            #[cfg(unix)]
+            SignerEndpoint::Unix(path) => Box::new(tokio::net::UnixStream::connect(path).await?),
+        };
+        // Send our nonce to the remote signer.
+        let our_nonce: [u8; AUTH_SIZE] = rand::thread_rng().gen();
+        stream.write_all(&our_nonce).await?;
+        // Ensure the remote signer proves knowledge of the secret for our nonce.
+        let mut proof = [0u8; AUTH_SIZE];
+        stream.read_exact(&mut proof).await?;
+        let is_authenticated = is_valid_proof(secret, AuthRole::Signer, &our_nonce, &proof);
+        ensure!(is_authenticated, "The remote signer failed to authenticate");
+        // Prove knowledge of the secret for the nonce of the remote signer.
+        let mut signer_nonce = [0u8; AUTH_SIZE];
+        stream.read_exact(&mut signer_nonce).await?;
+        stream.write_all(&auth_proof(secret, AuthRole::Node, &signer_nonce)).await?;
+        stream.flush().await?;
+        // Authenticate the frames of the connection with a key derived from both nonces.
+        let session = SignerSession::new(secret, AuthRole::Node, &our_nonce, &signer_nonce);
+        Ok((stream, session))
+    }
+}
+
+#[async_trait]
+impl<N: Network> Signer<N> for RemoteSigner<N> {
+    /// Returns the address of the remote signer.
+    fn address(&self) -> Address<N> {
+        self.address
+    }
+
+    /// Returns a signature for the given field elements from the remote signer.
+    async fn sign(&self, context: SigningContext, message: Vec<Field<N>>) -> Result<Signature<N>> {
+        let response = self.request(SignerRequest::Sign(context, message.clone())).await?;
+        let signature = Signature::read_le(&response[..])?;
+        // Ensure the remote signer returned a valid signature.
+        ensure!(signature.verify(&self.address, &message), "The remote signer returned an invalid signature");
+        Ok(signature)
+    }
+
+    /// Returns a signature for the given bytes from the remote signer.
+    async fn sign_bytes(&self, context: SigningContext, message: Vec<u8>) -> Result<Signature<N>> {
+        let response = self.request(SignerRequest::SignBytes(context, message.clone())).await?;
+        let signature = Signature::read_le(&response[..])?;
+        // Ensure the remote signer returned a valid signature.
+        ensure!(signature.verify_bytes(&self.address, &message), "The remote signer returned an invalid signature");
+        Ok(signature)
+    }
+}
+
+/// The side of a remote signer connection that proves knowledge of the shared secret.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AuthRole {
+    Node,
+    Signer,
+}
+
+impl AuthRole {
+    /// Returns the label of the role, which domain-separates the proofs and frames of each side.
+    const fn label(self) -> &'static [u8] {
+        match self {
+            Self::Node => b"snarkos-node",
+            Self::Signer => b"snarkos-signer",
+        }
+    }
+}
+
+/// Returns the proof of knowledge of the shared secret for the given role and nonce.
+pub fn auth_proof(secret: &[u8], role: AuthRole, nonce: &[u8; AUTH_SIZE]) -> [u8; AUTH_SIZE] {
+    let mut hasher = Sha256::new();
+    hasher.update((secret.len() as u64).to_le_bytes());
+    hasher.update(secret);
+    hasher.update(role.label());
+    hasher.update(nonce);
+    hasher.finalize().into()
+}
+
+/// Returns `true` if the given proof is valid for the given role and nonce, in constant time.
+pub fn is_valid_proof(secret: &[u8], role: AuthRole, nonce: &[u8; AUTH_SIZE], proof: &[u8; AUTH_SIZE]) -> bool {
+    is_equal(&auth_proof(secret, role, nonce), proof)
+}
+
+/// The state of an authenticated remote signer session, which appends a message authentication code to each frame.
+///
+/// The code of a frame covers the role of its sender, its sequence number in that direction, and its bytes,
+/// so that frames cannot be forged, reordered, replayed, or reflected back to their sender.
+pub struct SignerSession {
+    /// The key of the session, derived from the shared secret and the nonces of the node and the remote signer.
+    key: [u8; MAC_SIZE],
+    /// The role of this side of the session.
+    role: AuthRole,
+    /// The sequence number of the next frame to send.
+    sent: u64,
+    /// The sequence number of the next frame to receive.
+    received: u64,
+}
+
+impl SignerSession {
+    /// Initializes a new session for the given role, from the shared secret and the nonces of the handshake.
+    pub fn new(secret: &[u8], role: AuthRole, node_nonce: &[u8; AUTH_SIZE], signer_nonce: &[u8; AUTH_SIZE]) -> Self {
+        let mut mac = hmac_sha256(secret);
+        mac.update(b"snarkos-signer-session");
+        mac.update(node_nonce);
+        mac.update(signer_nonce);
+        Self { key: mac.finalize().into_bytes().into(), role, sent: 0, received: 0 }
+    }
+
+    /// Returns the message authentication code of the given frame, before it is finalized.
+    fn mac(&self, role: AuthRole, sequence: u64, bytes: &[u8]) -> Hmac<Sha256> {
+        let mut mac = hmac_sha256(&self.key);
+        mac.update(role.label());
+        mac.update(&sequence.to_le_bytes());
+        mac.update(bytes);
+        mac
+    }
+
+    /// Writes the given bytes to the stream as an authenticated frame.
+    pub async fn write_frame<S: AsyncWrite + Unpin + ?Sized>(&mut self, stream: &mut S, bytes: &[u8]) -> Result<()> {
+        let mac = self.mac(self.role, self.sent, bytes).finalize().into_bytes();
+        self.sent += 1;
+        write_frame(stream, &[bytes, mac.as_slice()].concat()).await
+    }
+
+    /// Reads an authenticated frame from the stream, and returns its bytes.
+    pub async fn read_frame<S: AsyncRead + Unpin + ?Sized>(&mut self, stream: &mut S) -> Result<Vec<u8>> {
+        let mut bytes = read_frame(stream).await?;
+        ensure!(bytes.len() >= MAC_SIZE, "Remote signer frame is missing its authentication code");
+        let mac = bytes.split_off(bytes.len() - MAC_SIZE);
+        let peer_role = match self.role {
+            AuthRole::Node => AuthRole::Signer,
+            AuthRole::Signer => AuthRole::Node,
+        };
+        // Note: The authentication code is compared in constant time.
+        let is_valid = self.mac(peer_role, self.received, &bytes).verify_slice(&mac).is_ok();
+        ensure!(is_valid, "Remote signer frame failed authentication");
+        self.received += 1;
+        Ok(bytes)
+    }
+}
+
+/// Returns an HMAC-SHA256 with the given key.
+fn hmac_sha256(key: &[u8]) -> Hmac<Sha256> {
+    // Note: HMAC accepts keys of any length, so this is infallible.
+    Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length")
+}
+
+/// Returns `true` if the given byte strings are equal, in constant time for equal lengths.
+fn is_equal(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b.iter()).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+/// Writes the given bytes to the stream as a length-prefixed frame.
+pub async fn write_frame<S: AsyncWrite + Unpin + ?Sized>(stream: &mut S, bytes: &[u8]) -> Result<()> {
+    let length = u32::try_from(bytes.len())?;
+    ensure!(length <= MAX_FRAME_SIZE, "Remote signer frame is too large ({length} bytes)");
+    stream.write_all(&length.to_le_bytes()).await?;
+    stream.write_all(bytes).await?;
+    stream.flush().await?;
+    Ok(())
+}
+
+/// Reads a length-prefixed frame from the stream.
+pub async fn read_frame<S: AsyncRead + Unpin + ?Sized>(stream: &mut S) -> Result<Vec<u8>> {
+    let length = stream.read_u32_le().await?;
+    ensure!(length <= MAX_FRAME_SIZE, "Remote signer frame is too large ({length} bytes)");
+    let mut bytes = vec![0u8; length as usize];
+    stream.read_exact(&mut bytes).await?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkvm::prelude::{TestRng, Uniform};
+    use tokio::net::TcpListener;
+
+    type CurrentNetwork = snarkvm::prelude::MainnetV0;
+
+    const SECRET: &[u8] = b"0123456789abcdef0123456789abcdef";
+
+    /// Starts a mock signing daemon for the given account, which stalls on signing requests if `stall` is set.
+    async fn start_mock_signer(account: Account<CurrentNetwork>, secret: &'static [u8], stall: bool) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let ip = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let account = account.clone();
+                tokio::spawn(async move {
+                    // Authenticate the node.
+                    let mut node_nonce = [0u8; AUTH_SIZE];
+                    stream.read_exact(&mut node_nonce).await.unwrap();
+                    stream.write_all(&auth_proof(secret, AuthRole::Signer, &node_nonce)).await.unwrap();
+                    let our_nonce: [u8; AUTH_SIZE] = rand::thread_rng().gen();
+                    stream.write_all(&our_nonce).await.unwrap();
+                    let mut proof = [0u8; AUTH_SIZE];
+                    stream.read_exact(&mut proof).await.unwrap();
+                    assert!(is_valid_proof(secret, AuthRole::Node, &our_nonce, &proof));
+                    let mut session = SignerSession::new(secret, AuthRole::Signer, &node_nonce, &our_nonce);
+                    // Serve the requests.
+                    while let Ok(request) = session.read_frame(&mut stream).await {
+                        let response = match SignerRequest::<CurrentNetwork>::from_bytes(&request).unwrap() {
+                            SignerRequest::Address => account.address().to_bytes_le().unwrap(),
+                            _ if stall => std::future::pending().await,
+                            SignerRequest::Sign(_, message) => {
+                                let signature = account.sign(&message, &mut rand::thread_rng()).unwrap();
+                                signature.to_bytes_le().unwrap()
+                            }
+                            SignerRequest::SignBytes(_, message) => {
+                                let signature = account.sign_bytes(&message, &mut rand::thread_rng()).unwrap();
+                                signature.to_bytes_le().unwrap()
+                            }
+                        };
+                        session.write_frame(&mut stream, &[&[0], response.as_slice()].concat()).await.unwrap();
+                    }
+                });
+            }
+        });
+        ip
+    }
+
+    #[test]
+    fn test_signer_endpoint() {
+        let endpoint = SignerEndpoint::from_str("tcp://127.0.0.1:4000").unwrap();
+        assert_eq!(endpoint, SignerEndpoint::Tcp("127.0.0.1:4000".parse().unwrap()));
+        assert_eq!(endpoint.to_string(), "tcp://127.0.0.1:4000");
+        #[cfg(unix)]
+        assert_eq!(
+            SignerEndpoint::from_str("unix:///run/signer.sock").unwrap(),
+            SignerEndpoint::Unix("/run/signer.sock".into())
+        );
+        assert!(SignerEndpoint::from_str("127.0.0.1:4000").is_err());
+        assert!(SignerEndpoint::from_str("tcp://localhost").is_err());
+    }
+
+    #[test]
+    fn test_signer_request_bytes() {
+        let rng = &mut TestRng::default();
+        let requests = [
+            SignerRequest::<CurrentNetwork>::Address,
+            SignerRequest::Sign(SigningContext::BatchProposal, vec![Field::rand(rng)]),
+            SignerRequest::Sign(SigningContext::BatchSignature, vec![Field::rand(rng), Field::rand(rng)]),
+            SignerRequest::SignBytes(SigningContext::Handshake, vec![1, 2, 3]),
+        ];
+        for request in requests {
+            assert_eq!(SignerRequest::from_bytes(&request.to_bytes().unwrap()).unwrap(), request);
+        }
+        assert!(SignerRequest::<CurrentNetwork>::from_bytes(&[3, 0]).is_err());
+        assert!(SignerRequest::<CurrentNetwork>::from_bytes(&[1, 3]).is_err());
+    }
+
+    #[test]
+    fn test_hmac_sha256() {
+        // Test case 2 of RFC 4231.
+        let mut mac = hmac_sha256(b"Jefe");
+        mac.update(b"what do ya want for nothing?");
+        let mac = mac.finalize().into_bytes();
+        let expected = "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843";
+        assert_eq!(mac.iter().map(|byte| format!("{byte:02x}")).collect::<String>(), expected);
+    }
+
+    #[tokio::test]
+    async fn test_signer_session() {
+        let (node_nonce, signer_nonce) = ([1u8; AUTH_SIZE], [2u8; AUTH_SIZE]);
+        // Note: A session is dropped after any failure, so each case starts a new session.
+        let sessions = || {
+            let node = SignerSession::new(SECRET, AuthRole::Node, &node_nonce, &signer_nonce);
+            let signer = SignerSession::new(SECRET, AuthRole::Signer, &node_nonce, &signer_nonce);
+            (node, signer)
+        };
+
+        // Ensure the frames are exchanged in both directions.
+        let (mut node, mut signer) = sessions();
+        let (mut node_stream, mut signer_stream) = tokio::io::duplex(1024);
+        for _ in 0..2 {
+            node.write_frame(&mut node_stream, b"request").await.unwrap();
+            assert_eq!(signer.read_frame(&mut signer_stream).await.unwrap(), b"request");
+            signer.write_frame(&mut signer_stream, b"response").await.unwrap();
+            assert_eq!(node.read_frame(&mut node_stream).await.unwrap(), b"response");
+        }
+
+        // Ensure a tampered frame is refused.
+        let (mut node, mut signer) = sessions();
+        let mut frame = Vec::new();
+        node.write_frame(&mut frame, b"request").await.unwrap();
+        frame[4] ^= 1;
+        assert!(signer.read_frame(&mut &frame[..]).await.is_err());
+
+        // Ensure a replayed frame is refused.
+        let (mut node, mut signer) = sessions();
+        let mut frame = Vec::new();
+        node.write_frame(&mut frame, b"request").await.unwrap();
+        assert!(signer.read_frame(&mut &frame[..]).await.is_ok());
+        assert!(signer.read_frame(&mut &frame[..]).await.is_err());
+
+        // Ensure a frame reflected back to its sender is refused.
+        let (mut node, _) = sessions();
+        let mut frame = Vec::new();
+        node.write_frame(&mut frame, b"request").await.unwrap();
+        assert!(node.read_frame(&mut &frame[..]).await.is_err());
+
+        // Ensure a session with different nonces refuses the frames.
+        let (mut node, _) = sessions();
+        let mut other = SignerSession::new(SECRET, AuthRole::Signer, &signer_nonce, &node_nonce);
+        let mut frame = Vec::new();
+        node.write_frame(&mut frame, b"request").await.unwrap();
+        assert!(other.read_frame(&mut &frame[..]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_remote_signer() {
+        let rng = &mut TestRng::default();
+        let account = Account::<CurrentNetwork>::new(rng).unwrap();
+        let ip = start_mock_signer(account.clone(), SECRET, false).await;
+
+        // Connect to the remote signer.
+        let signer = RemoteSigner::<CurrentNetwork>::connect(SignerEndpoint::Tcp(ip), SECRET.to_vec()).await.unwrap();
+        assert_eq!(Signer::address(&signer), account.address());
+
+        // Sign a batch ID.
+        let batch_id = Field::rand(rng);
+        let signature = signer.sign(SigningContext::BatchSignature, vec![batch_id]).await.unwrap();
+        assert!(signature.verify(&account.address(), &[batch_id]));
+        // Sign a handshake nonce.
+        let signature = signer.sign_bytes(SigningContext::Handshake, vec![1, 2, 3]).await.unwrap();
+        assert!(signature.verify_bytes(&account.address(), &[1, 2, 3]));
+    }
+
+    #[tokio::test]
+    async fn test_remote_signer_timeout() {
+        let rng = &mut TestRng::default();
+        let account = Account::<CurrentNetwork>::new(rng).unwrap();
+        let ip = start_mock_signer(account.clone(), SECRET, true).await;
+
+        // Connect to the remote signer.
+        let timeout = Duration::from_millis(200);
+        let endpoint = SignerEndpoint::Tcp(ip);
+        let signer =
+            RemoteSigner::<CurrentNetwork>::connect_with_timeout(endpoint, SECRET.to_vec(), timeout).await.unwrap();
+
+        // A stalled signing operation fails with a timeout, and a later operation is attempted anew.
+        for _ in 0..2 {
+            let error = signer.sign(SigningContext::BatchProposal, vec![Field::rand(rng)]).await.unwrap_err();
+            assert!(error.to_string().contains("timed out"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remote_signer_authentication() {
+        let rng = &mut TestRng::default();
+        let account = Account::<CurrentNetwork>::new(rng).unwrap();
+        let ip = start_mock_signer(account, SECRET, false).await;
+
+        // A node with a different secret refuses the remote signer.
+        let secret = b"fedcba9876543210fedcba9876543210".to_vec();
+        assert!(RemoteSigner::<CurrentNetwork>::connect(SignerEndpoint::Tcp(ip), secret).await.is_err());
+        // A secret that is too short is refused.
+        assert!(RemoteSigner::<CurrentNetwork>::connect(SignerEndpoint::Tcp(ip), b"short".to_vec()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_remote_signer_unavailable() {
+        // Reserve a port, and close the listener.
+        let ip = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+        assert!(RemoteSigner::<CurrentNetwork>::connect(SignerEndpoint::Tcp(ip), SECRET.to_vec()).await.is_err());
+    }
+}
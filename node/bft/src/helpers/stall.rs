@@ -0,0 +1,316 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    STALL_CERTIFICATE_THRESHOLD_IN_SECS,
+    STALL_DETECTION_ENABLED,
+    STALL_ESCALATION_INTERVAL_IN_SECS,
+    STALL_ROUND_THRESHOLD_IN_SECS,
+};
+use snarkvm::{
+    ledger::committee::Committee,
+    prelude::{Address, Network},
+};
+
+use parking_lot::Mutex;
+use std::{collections::HashSet, fmt, time::Duration};
+use tokio::time::Instant;
+
+/// The configuration of the stall detector.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct StallConfig {
+    /// Whether stall detection is enabled.
+    pub enabled: bool,
+    /// The time without a round advance, after which the BFT may be considered stalled.
+    pub round_threshold: Duration,
+    /// The time without a new certificate, after which the BFT may be considered stalled.
+    pub certificate_threshold: Duration,
+    /// The time between the escalating recovery actions, once the BFT is stalled.
+    pub escalation_interval: Duration,
+}
+
+impl Default for StallConfig {
+    /// Returns the default stall configuration.
+    fn default() -> Self {
+        Self {
+            enabled: STALL_DETECTION_ENABLED,
+            round_threshold: Duration::from_secs(STALL_ROUND_THRESHOLD_IN_SECS),
+            certificate_threshold: Duration::from_secs(STALL_CERTIFICATE_THRESHOLD_IN_SECS),
+            escalation_interval: Duration::from_secs(STALL_ESCALATION_INTERVAL_IN_SECS),
+        }
+    }
+}
+
+/// The recovery actions taken while the BFT is stalled, in order of escalation.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RecoveryAction {
+    /// Re-broadcast our latest certificate and our proposal, if any.
+    Rebroadcast,
+    /// Request the missing certificates of the previous round from all connected committee members.
+    RequestCertificates,
+    /// Re-dial the committee members that are not connected.
+    Redial,
+    /// Log a `STALLED` event with a diagnosis of the stall.
+    ReportStall,
+}
+
+impl RecoveryAction {
+    /// The recovery actions, in order of escalation.
+    pub const ESCALATION: [Self; 4] = [Self::Rebroadcast, Self::RequestCertificates, Self::Redial, Self::ReportStall];
+}
+
+/// The likely cause of a stall.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StallDiagnosis {
+    /// Too few committee members are connected to reach the quorum threshold.
+    MissingPeers { connected: usize, members: usize },
+    /// The certificates for the round have not reached the quorum threshold.
+    MissingCertificates { round: u64, certificates: usize, members: usize },
+    /// There are no transmissions to propose.
+    NoTransmissions,
+    /// The peers and certificates are sufficient, but the round has not advanced.
+    Unknown,
+}
+
+impl fmt::Display for StallDiagnosis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingPeers { connected, members } => {
+                write!(f, "missing peers ({connected} of {members} committee members connected)")
+            }
+            Self::MissingCertificates { round, certificates, members } => {
+                write!(f, "missing certificates ({certificates} of {members} certificates for round {round})")
+            }
+            Self::NoTransmissions => write!(f, "no transmissions to propose"),
+            Self::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// Returns the likely cause of a stall, given the committee, the connected committee members,
+/// the authors of the certificates for the given round, and the number of unconfirmed transmissions.
+pub fn diagnose_stall<N: Network>(
+    committee: &Committee<N>,
+    self_address: Address<N>,
+    connected: &HashSet<Address<N>>,
+    round: u64,
+    authors: &HashSet<Address<N>>,
+    num_unconfirmed_transmissions: usize,
+) -> StallDiagnosis {
+    // Determine the committee members that are reachable, including this node.
+    let reachable = committee
+        .members()
+        .keys()
+        .filter(|address| **address == self_address || connected.contains(*address))
+        .copied()
+        .collect::<HashSet<_>>();
+    if !committee.is_quorum_threshold_reached(&reachable) {
+        return StallDiagnosis::MissingPeers { connected: reachable.len(), members: committee.num_members() };
+    }
+    // Determine if the certificates for the round have reached the quorum threshold.
+    if !committee.is_quorum_threshold_reached(authors) {
+        return StallDiagnosis::MissingCertificates {
+            round,
+            certificates: authors.len(),
+            members: committee.num_members(),
+        };
+    }
+    // Determine if there are any transmissions to propose.
+    match num_unconfirmed_transmissions {
+        0 => StallDiagnosis::NoTransmissions,
+        _ => StallDiagnosis::Unknown,
+    }
+}
+
+/// The progress observed by the stall detector.
+#[derive(Debug)]
+struct Progress {
+    /// The latest round.
+    round: u64,
+    /// The number of certificates observed for the latest round.
+    num_certificates: usize,
+    /// The time of the latest round advance.
+    last_round_advance: Instant,
+    /// The time of the latest new certificate.
+    last_certificate: Instant,
+    /// The number of recovery actions taken since the stall began.
+    num_actions: u32,
+}
+
+/// Detects stalled BFT rounds, and schedules the escalating recovery actions.
+#[derive(Debug)]
+pub struct StallDetector {
+    /// The stall configuration.
+    config: StallConfig,
+    /// The progress observed so far.
+    progress: Mutex<Progress>,
+}
+
+impl StallDetector {
+    /// Initializes a new stall detector, with the given configuration.
+    pub fn new(config: StallConfig) -> Self {
+        let now = Instant::now();
+        let progress =
+            Progress { round: 0, num_certificates: 0, last_round_advance: now, last_certificate: now, num_actions: 0 };
+        Self { config, progress: Mutex::new(progress) }
+    }
+
+    /// Returns the stall configuration.
+    pub const fn config(&self) -> &StallConfig {
+        &self.config
+    }
+
+    /// Returns `true` if recovery actions have been taken since the last progress.
+    pub fn is_stalled(&self) -> bool {
+        self.progress.lock().num_actions > 0
+    }
+
+    /// Returns the duration since the BFT last made progress, as of the given time.
+    pub fn time_since_progress(&self, now: Instant) -> Duration {
+        let progress = self.progress.lock();
+        now.saturating_duration_since(progress.last_round_advance.max(progress.last_certificate))
+    }
+
+    /// Records the given round and number of certificates for the round, and returns the recovery action
+    /// to take as of the given time, if the BFT is stalled.
+    ///
+    /// Once stalled, the recovery actions are returned one escalation interval apart, in the order of
+    /// `RecoveryAction::ESCALATION`, and the sequence restarts until the BFT makes progress again.
+    pub fn observe(&self, round: u64, num_certificates: usize, now: Instant) -> Option<RecoveryAction> {
+        if !self.config.enabled {
+            return None;
+        }
+        let mut progress = self.progress.lock();
+        // Record any progress, and reset the escalation.
+        if round != progress.round {
+            progress.round = round;
+            progress.num_certificates = num_certificates;
+            progress.last_round_advance = now;
+            progress.last_certificate = now;
+            progress.num_actions = 0;
+            return None;
+        }
+        if num_certificates > progress.num_certificates {
+            progress.num_certificates = num_certificates;
+            progress.last_certificate = now;
+            progress.num_actions = 0;
+            return None;
+        }
+        // Determine when the BFT is considered stalled.
+        let stalled_at = (progress.last_round_advance + self.config.round_threshold)
+            .max(progress.last_certificate + self.config.certificate_threshold);
+        // Determine when the next recovery action is due.
+        let next_action_at = stalled_at + self.config.escalation_interval * progress.num_actions;
+        if now < next_action_at {
+            return None;
+        }
+        // Select the next recovery action.
+        let action = RecoveryAction::ESCALATION[progress.num_actions as usize % RecoveryAction::ESCALATION.len()];
+        progress.num_actions = progress.num_actions.saturating_add(1);
+        Some(action)
+    }
+}
+
+impl Default for StallDetector {
+    /// Initializes a new stall detector, with the default configuration.
+    fn default() -> Self {
+        Self::new(StallConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkvm::prelude::TestRng;
+
+    type CurrentNetwork = snarkvm::prelude::MainnetV0;
+
+    /// Returns a stall configuration with the given thresholds and escalation interval, in seconds.
+    fn sample_config(round: u64, certificate: u64, escalation: u64) -> StallConfig {
+        StallConfig {
+            enabled: true,
+            round_threshold: Duration::from_secs(round),
+            certificate_threshold: Duration::from_secs(certificate),
+            escalation_interval: Duration::from_secs(escalation),
+        }
+    }
+
+    #[test]
+    fn test_stall_escalation() {
+        let detector = StallDetector::new(sample_config(60, 30, 10));
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        // Advance to round 5, and observe a certificate at 20s.
+        assert_eq!(detector.observe(5, 1, at(0)), None);
+        assert_eq!(detector.observe(5, 2, at(20)), None);
+        // Ensure the BFT is not stalled before both thresholds are exceeded.
+        assert_eq!(detector.observe(5, 2, at(55)), None);
+        assert!(!detector.is_stalled());
+
+        // Ensure the recovery actions escalate one interval apart.
+        assert_eq!(detector.observe(5, 2, at(60)), Some(RecoveryAction::Rebroadcast));
+        assert!(detector.is_stalled());
+        assert_eq!(detector.observe(5, 2, at(65)), None);
+        assert_eq!(detector.observe(5, 2, at(70)), Some(RecoveryAction::RequestCertificates));
+        assert_eq!(detector.observe(5, 2, at(80)), Some(RecoveryAction::Redial));
+        assert_eq!(detector.observe(5, 2, at(90)), Some(RecoveryAction::ReportStall));
+        // Ensure the escalation restarts while the BFT remains stalled.
+        assert_eq!(detector.observe(5, 2, at(100)), Some(RecoveryAction::Rebroadcast));
+        assert_eq!(detector.time_since_progress(at(100)), Duration::from_secs(80));
+
+        // Ensure a new certificate resets the escalation.
+        assert_eq!(detector.observe(5, 3, at(105)), None);
+        assert!(!detector.is_stalled());
+        assert_eq!(detector.observe(5, 3, at(150)), None);
+        // Ensure a round advance resets the escalation.
+        assert_eq!(detector.observe(5, 3, at(160)), Some(RecoveryAction::Rebroadcast));
+        assert_eq!(detector.observe(6, 0, at(161)), None);
+        assert!(!detector.is_stalled());
+        assert_eq!(detector.observe(6, 0, at(220)), None);
+        assert_eq!(detector.observe(6, 0, at(221)), Some(RecoveryAction::Rebroadcast));
+    }
+
+    #[test]
+    fn test_stall_detection_disabled() {
+        let detector = StallDetector::new(StallConfig { enabled: false, ..sample_config(0, 0, 0) });
+        let now = Instant::now();
+        assert_eq!(detector.observe(1, 0, now), None);
+        assert_eq!(detector.observe(1, 0, now + Duration::from_secs(3600)), None);
+    }
+
+    #[test]
+    fn test_diagnose_stall() {
+        let rng = &mut TestRng::default();
+        // Sample a committee of equally-staked members.
+        let committee = snarkvm::ledger::committee::test_helpers::sample_committee(rng);
+        let members = committee.members().keys().copied().collect::<Vec<_>>();
+        let num_members = members.len();
+        let self_address = members[0];
+        let all = members.iter().copied().collect::<HashSet<_>>();
+        let one = HashSet::from([members[1]]);
+
+        // Ensure missing peers are diagnosed first.
+        let diagnosis = diagnose_stall::<CurrentNetwork>(&committee, self_address, &one, 3, &all, 5);
+        assert_eq!(diagnosis, StallDiagnosis::MissingPeers { connected: 2, members: num_members });
+        // Ensure missing certificates are diagnosed next.
+        let diagnosis = diagnose_stall::<CurrentNetwork>(&committee, self_address, &all, 3, &one, 5);
+        assert_eq!(diagnosis, StallDiagnosis::MissingCertificates { round: 3, certificates: 1, members: num_members });
+        // Ensure a lack of transmissions is diagnosed last.
+        let diagnosis = diagnose_stall::<CurrentNetwork>(&committee, self_address, &all, 3, &all, 0);
+        assert_eq!(diagnosis, StallDiagnosis::NoTransmissions);
+        let diagnosis = diagnose_stall::<CurrentNetwork>(&committee, self_address, &all, 3, &all, 5);
+        assert_eq!(diagnosis, StallDiagnosis::Unknown);
+    }
+}
@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::helpers::{check_timestamp_for_liveness, fmt_id};
+use crate::helpers::{fmt_id, Clock, DagSnapshot};
 use snarkos_node_bft_ledger_service::LedgerService;
 use snarkos_node_bft_storage_service::StorageService;
 use snarkvm::{
@@ -27,12 +27,60 @@ use indexmap::{map::Entry, IndexMap, IndexSet};
 use parking_lot::RwLock;
 use std::{
     collections::{HashMap, HashSet},
+    fmt,
     sync::{
         atomic::{AtomicU32, AtomicU64, Ordering},
         Arc,
     },
 };
 
+/// The error returned when the author of a certificate already has a different certificate in its round.
+/// An honest author creates one certificate per round, so this is evidence that the author equivocated.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CertificateEquivocation<N: Network> {
+    /// The round of the certificates.
+    pub round: u64,
+    /// The author of the certificates.
+    pub author: Address<N>,
+    /// The ID of the certificate in storage.
+    pub existing_certificate_id: Field<N>,
+    /// The ID of the rejected certificate.
+    pub certificate_id: Field<N>,
+}
+
+impl<N: Network> fmt::Display for CertificateEquivocation<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Author {} already has certificate '{}' in round {}, and equivocated with certificate '{}'",
+            self.author,
+            fmt_id(self.existing_certificate_id),
+            self.round,
+            fmt_id(self.certificate_id)
+        )
+    }
+}
+
+impl<N: Network> std::error::Error for CertificateEquivocation<N> {}
+
+/// The error returned when a certificate is inserted for a round that was already garbage collected.
+/// Such a certificate is unreachable, so it is refused instead of leaking in storage.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RoundGarbageCollected {
+    /// The round of the certificate.
+    pub round: u64,
+    /// The GC round of the storage.
+    pub gc_round: u64,
+}
+
+impl fmt::Display for RoundGarbageCollected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Round {} was already garbage collected (gc = {})", self.round, self.gc_round)
+    }
+}
+
+impl std::error::Error for RoundGarbageCollected {}
+
 #[derive(Clone, Debug)]
 pub struct Storage<N: Network>(Arc<StorageInner<N>>);
 
@@ -67,6 +115,8 @@ impl<N: Network> std::ops::Deref for Storage<N> {
 pub struct StorageInner<N: Network> {
     /// The ledger service.
     ledger: Arc<dyn LedgerService<N>>,
+    /// The clock.
+    clock: Clock,
     /* Once per block */
     /// The current height.
     current_height: AtomicU32,
@@ -94,6 +144,16 @@ impl<N: Network> Storage<N> {
         ledger: Arc<dyn LedgerService<N>>,
         transmissions: Arc<dyn StorageService<N>>,
         max_gc_rounds: u64,
+    ) -> Self {
+        Self::new_with_clock(ledger, transmissions, max_gc_rounds, Clock::System)
+    }
+
+    /// Initializes a new instance of storage, which reads the current time from the given clock.
+    pub fn new_with_clock(
+        ledger: Arc<dyn LedgerService<N>>,
+        transmissions: Arc<dyn StorageService<N>>,
+        max_gc_rounds: u64,
+        clock: Clock,
     ) -> Self {
         // Retrieve the current committee.
         let committee = ledger.current_committee().expect("Ledger is missing a committee.");
@@ -103,6 +163,7 @@ impl<N: Network> Storage<N> {
         // Return the storage.
         let storage = Self(Arc::new(StorageInner {
             ledger,
+            clock,
             current_height: Default::default(),
             current_round: Default::default(),
             gc_round: Default::default(),
@@ -122,6 +183,11 @@ impl<N: Network> Storage<N> {
 }
 
 impl<N: Network> Storage<N> {
+    /// Returns the clock.
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
     /// Returns the current height.
     pub fn current_height(&self) -> u32 {
         // Get the current height.
@@ -215,6 +281,10 @@ impl<N: Network> Storage<N> {
         let next_gc_round = next_round.saturating_sub(self.max_gc_rounds);
         // Check if storage needs to be garbage collected.
         if next_gc_round > current_gc_round {
+            // Update the GC round.
+            // Note: The GC round is updated first, so that a certificate inserted concurrently is either
+            // refused, or fully inserted before its round is removed below (see `insert_certificate_atomic`).
+            self.gc_round.store(next_gc_round, Ordering::SeqCst);
             // Remove the GC round(s) from storage.
             for gc_round in current_gc_round..=next_gc_round {
                 // Iterate over the certificates for the GC round.
@@ -223,8 +293,43 @@ impl<N: Network> Storage<N> {
                     self.remove_certificate(certificate.id());
                 }
             }
-            // Update the GC round.
-            self.gc_round.store(next_gc_round, Ordering::SeqCst);
+            // Remove any remaining entries at or below the GC round.
+            self.remove_entries_at_or_below(next_gc_round);
+        }
+    }
+
+    /// Removes the `rounds`, `certificates`, and `batch_ids` entries at or below the given GC round,
+    /// which are left behind by certificates that reached storage outside of the garbage collection of their round.
+    fn remove_entries_at_or_below(&self, gc_round: u64) {
+        // Remove the remaining certificates, along with their transmissions.
+        let certificate_ids = self
+            .certificates
+            .read()
+            .iter()
+            .filter(|(_, certificate)| certificate.round() <= gc_round)
+            .map(|(certificate_id, _)| *certificate_id)
+            .collect::<Vec<_>>();
+        for certificate_id in &certificate_ids {
+            self.remove_certificate(*certificate_id);
+        }
+        // Remove the remaining rounds and batch IDs, which no longer refer to a certificate.
+        let num_rounds = {
+            let mut rounds = self.rounds.write();
+            let num_rounds = rounds.len();
+            rounds.retain(|round, _| *round > gc_round);
+            num_rounds - rounds.len()
+        };
+        let num_batch_ids = {
+            let mut batch_ids = self.batch_ids.write();
+            let num_batch_ids = batch_ids.len();
+            batch_ids.retain(|_, round| *round > gc_round);
+            num_batch_ids - batch_ids.len()
+        };
+        if !certificate_ids.is_empty() || num_rounds > 0 || num_batch_ids > 0 {
+            warn!(
+                "Garbage collected {} certificates, {num_rounds} rounds, and {num_batch_ids} batch IDs left at or below round {gc_round}",
+                certificate_ids.len()
+            );
         }
     }
 }
@@ -292,19 +397,27 @@ impl<N: Network> Storage<N> {
         self.certificates.read().get(&certificate_id).cloned()
     }
 
+    /// Returns the certificate ID for the given `round` and `author`.
+    /// If the author does not have a certificate for the round in storage, `None` is returned.
+    pub fn get_certificate_id_for_round_and_author(&self, round: u64, author: Address<N>) -> Option<Field<N>> {
+        self.rounds.read().get(&round).and_then(|entries| {
+            entries.iter().find_map(|(certificate_id, _, a)| (a == &author).then_some(*certificate_id))
+        })
+    }
+
     /// Returns the certificate for the given `round` and `author`.
-    /// If the round does not exist in storage, `None` is returned.
-    /// If the author for the round does not exist in storage, `None` is returned.
-    pub fn get_certificate_for_round_with_author(&self, round: u64, author: Address<N>) -> Option<BatchCertificate<N>> {
-        // Retrieve the certificates.
-        if let Some(entries) = self.rounds.read().get(&round) {
-            let certificates = self.certificates.read();
-            entries.iter().find_map(
-                |(certificate_id, _, a)| if a == &author { certificates.get(certificate_id).cloned() } else { None },
-            )
-        } else {
-            Default::default()
-        }
+    /// If the author does not have a certificate for the round in storage, `None` is returned.
+    pub fn get_certificate_for_round_and_author(&self, round: u64, author: Address<N>) -> Option<BatchCertificate<N>> {
+        // Retrieve the certificate ID.
+        let certificate_id = self.get_certificate_id_for_round_and_author(round, author)?;
+        // Retrieve the certificate.
+        self.certificates.read().get(&certificate_id).cloned()
+    }
+
+    /// Returns the authors of the certificates for the given `round`.
+    /// If the round does not exist in storage, an empty set is returned.
+    pub fn get_certificate_authors_for_round(&self, round: u64) -> HashSet<Address<N>> {
+        self.rounds.read().get(&round).map(|set| set.iter().map(|(_, _, author)| *author).collect()).unwrap_or_default()
     }
 
     /// Returns the certificates for the given `round`.
@@ -323,6 +436,34 @@ impl<N: Network> Storage<N> {
         }
     }
 
+    /// Returns a snapshot of the DAG for the last `num_rounds` rounds, up to the current round,
+    /// where `is_committed` determines whether a certificate has been committed into a block.
+    ///
+    /// The read locks are held briefly per map, rather than across the construction of the snapshot.
+    pub fn dag_snapshot(&self, num_rounds: u64, is_committed: impl Fn(&BatchCertificate<N>) -> bool) -> DagSnapshot<N> {
+        // Determine the range of rounds.
+        let end_round = self.current_round();
+        let start_round = end_round.saturating_sub(num_rounds.saturating_sub(1)).max(1);
+        // Retrieve the certificate IDs for the rounds.
+        let certificate_ids = {
+            let rounds = self.rounds.read();
+            (start_round..=end_round)
+                .filter_map(|round| rounds.get(&round))
+                .flat_map(|entries| entries.iter().map(|(certificate_id, _, _)| *certificate_id))
+                .collect::<Vec<_>>()
+        };
+        // Retrieve the certificates.
+        let certificates = {
+            let certificates = self.certificates.read();
+            certificate_ids
+                .iter()
+                .filter_map(|certificate_id| certificates.get(certificate_id).cloned())
+                .collect::<Vec<_>>()
+        };
+        // Construct the snapshot, without holding any locks.
+        DagSnapshot::new(start_round, end_round, &certificates, is_committed)
+    }
+
     /// Checks the given `batch_header` for validity, returning the missing transmissions from storage.
     ///
     /// This method ensures the following invariants:
@@ -362,7 +503,7 @@ impl<N: Network> Storage<N> {
         }
 
         // Check the timestamp for liveness.
-        check_timestamp_for_liveness(batch_header.timestamp())?;
+        self.clock.check_timestamp_for_liveness(batch_header.timestamp())?;
 
         // Retrieve the missing transmissions in storage from the given transmissions.
         let missing_transmissions = self
@@ -449,15 +590,16 @@ impl<N: Network> Storage<N> {
         }
 
         // Ensure the storage does not already contain a certificate for this author in this round.
-        if self.contains_certificate_in_round_from(round, certificate.author()) {
-            bail!("Certificate with this author for round {round} already exists in storage {gc_log}")
+        let author = certificate.author();
+        if let Some(existing_certificate_id) = self.get_certificate_id_for_round_and_author(round, author) {
+            bail!(CertificateEquivocation { round, author, existing_certificate_id, certificate_id: certificate.id() })
         }
 
         // Ensure the batch header is well-formed.
         let missing_transmissions = self.check_batch_header(certificate.batch_header(), transmissions)?;
 
         // Check the timestamp for liveness.
-        check_timestamp_for_liveness(certificate.timestamp())?;
+        self.clock.check_timestamp_for_liveness(certificate.timestamp())?;
 
         // Retrieve the committee lookback for the batch round.
         let Ok(committee_lookback) = self.ledger.get_committee_lookback_for_round(round) else {
@@ -493,8 +635,10 @@ impl<N: Network> Storage<N> {
     /// This method triggers updates to the `rounds`, `certificates`, `batch_ids`, and `transmissions` maps.
     ///
     /// This method ensures the following invariants:
+    /// - The certificate round is above the GC round (see `RoundGarbageCollected`).
     /// - The certificate ID does not already exist in storage.
     /// - The batch ID does not already exist in storage.
+    /// - The author does not already have a certificate for the round (see `CertificateEquivocation`).
     /// - All transmissions declared in the certificate are provided or exist in storage (up to GC).
     /// - All previous certificates declared in the certificate exist in storage (up to GC).
     /// - All previous certificates are for the previous round (i.e. round - 1).
@@ -505,12 +649,14 @@ impl<N: Network> Storage<N> {
         transmissions: HashMap<TransmissionID<N>, Transmission<N>>,
     ) -> Result<()> {
         // Ensure the certificate round is above the GC round.
-        ensure!(certificate.round() > self.gc_round(), "Certificate round is at or below the GC round");
+        let (round, gc_round) = (certificate.round(), self.gc_round());
+        if round <= gc_round {
+            bail!(RoundGarbageCollected { round, gc_round })
+        }
         // Ensure the certificate and its transmissions are valid.
         let missing_transmissions = self.check_certificate(&certificate, transmissions)?;
         // Insert the certificate into storage.
-        self.insert_certificate_atomic(certificate, missing_transmissions);
-        Ok(())
+        self.insert_certificate_atomic(certificate, missing_transmissions)
     }
 
     /// Inserts the given `certificate` into storage.
@@ -518,11 +664,14 @@ impl<N: Network> Storage<N> {
     /// This method assumes **all missing** transmissions are provided in the `missing_transmissions` map.
     ///
     /// This method triggers updates to the `rounds`, `certificates`, `batch_ids`, and `transmissions` maps.
+    ///
+    /// If the round was garbage collected, a `RoundGarbageCollected` is returned.
+    /// If the author already has a different certificate for the round, a `CertificateEquivocation` is returned.
     fn insert_certificate_atomic(
         &self,
         certificate: BatchCertificate<N>,
         missing_transmissions: HashMap<TransmissionID<N>, Transmission<N>>,
-    ) {
+    ) -> Result<()> {
         // Retrieve the round.
         let round = certificate.round();
         // Retrieve the certificate ID.
@@ -532,8 +681,30 @@ impl<N: Network> Storage<N> {
         // Retrieve the author of the batch.
         let author = certificate.author();
 
-        // Insert the round to certificate ID entry.
-        self.rounds.write().entry(round).or_default().insert((certificate_id, batch_id, author));
+        // Note: The lock on the rounds is held until the certificate is fully inserted,
+        // so that the garbage collection of its round cannot interleave with the insertion.
+        let mut rounds = self.rounds.write();
+        // Ensure the round was not garbage collected since the certificate was checked.
+        let gc_round = self.gc_round();
+        if round <= gc_round {
+            bail!(RoundGarbageCollected { round, gc_round })
+        }
+        // Insert the round to certificate ID entry, unless the author already has a certificate for the round.
+        // Note: The check is performed under the lock, so that concurrent insertions cannot both succeed.
+        let entries = rounds.entry(round).or_default();
+        if let Some((existing_certificate_id, ..)) = entries.iter().find(|(_, _, a)| a == &author) {
+            // If the certificate is already in storage, there is nothing to insert.
+            if existing_certificate_id == &certificate_id {
+                return Ok(());
+            }
+            bail!(CertificateEquivocation {
+                round,
+                author,
+                existing_certificate_id: *existing_certificate_id,
+                certificate_id
+            })
+        }
+        entries.insert((certificate_id, batch_id, author));
         // Obtain the certificate's transmission ids.
         let transmission_ids = certificate.transmission_ids().clone();
         // Insert the certificate.
@@ -542,6 +713,7 @@ impl<N: Network> Storage<N> {
         self.batch_ids.write().insert(batch_id, round);
         // Insert the certificate ID for each of the transmissions into storage.
         self.transmissions.insert_transmissions(certificate_id, transmission_ids, missing_transmissions);
+        Ok(())
     }
 
     /// Removes the given `certificate ID` from storage.
@@ -759,8 +931,8 @@ mod tests {
     use snarkos_node_bft_ledger_service::MockLedgerService;
     use snarkos_node_bft_storage_service::BFTMemoryService;
     use snarkvm::{
-        ledger::narwhal::Data,
-        prelude::{Rng, TestRng},
+        ledger::narwhal::{batch_certificate::test_helpers::sample_batch_certificate_for_round, Data},
+        prelude::{Rng, TestRng, Uniform},
     };
 
     use ::bytes::Bytes;
@@ -858,13 +1030,13 @@ mod tests {
         let (missing_transmissions, transmissions) = sample_transmissions(&certificate, rng);
 
         // Insert the certificate.
-        storage.insert_certificate_atomic(certificate.clone(), missing_transmissions);
+        storage.insert_certificate_atomic(certificate.clone(), missing_transmissions).unwrap();
         // Ensure the certificate exists in storage.
         assert!(storage.contains_certificate(certificate_id));
         // Ensure the certificate is stored in the correct round.
         assert_eq!(storage.get_certificates_for_round(round), indexset! { certificate.clone() });
         // Ensure the certificate is stored for the correct round and author.
-        assert_eq!(storage.get_certificate_for_round_with_author(round, author), Some(certificate.clone()));
+        assert_eq!(storage.get_certificate_for_round_and_author(round, author), Some(certificate.clone()));
 
         // Check that the underlying storage representation is correct.
         {
@@ -890,11 +1062,96 @@ mod tests {
         // Ensure the certificate is no longer stored in the round.
         assert!(storage.get_certificates_for_round(round).is_empty());
         // Ensure the certificate is no longer stored for the round and author.
-        assert_eq!(storage.get_certificate_for_round_with_author(round, author), None);
+        assert_eq!(storage.get_certificate_for_round_and_author(round, author), None);
         // Ensure the storage is empty.
         assert_storage(&storage, &[], &[], &[], &Default::default());
     }
 
+    #[test]
+    fn test_insert_certificate_at_or_below_gc_round() {
+        let rng = &mut TestRng::default();
+
+        // Sample a committee.
+        let committee = snarkvm::ledger::committee::test_helpers::sample_committee(rng);
+        // Initialize the ledger.
+        let ledger = Arc::new(MockLedgerService::new(committee));
+        // Initialize the storage.
+        let storage = Storage::<CurrentNetwork>::new(ledger, Arc::new(BFTMemoryService::new()), 1);
+
+        // Garbage collect up to round 3.
+        storage.garbage_collect_certificates(4);
+        assert_eq!(storage.gc_round(), 3);
+
+        // Ensure a late certificate at or below the GC round is refused.
+        for round in 1..=3 {
+            let certificate = sample_batch_certificate_for_round(round, rng);
+            let expected = RoundGarbageCollected { round, gc_round: 3 };
+            let error = storage.insert_certificate_atomic(certificate.clone(), Default::default()).unwrap_err();
+            assert_eq!(error.downcast_ref::<RoundGarbageCollected>(), Some(&expected));
+            let error = storage.insert_certificate(certificate, Default::default()).unwrap_err();
+            assert_eq!(error.downcast_ref::<RoundGarbageCollected>(), Some(&expected));
+        }
+        // Ensure nothing was inserted.
+        assert_storage(&storage, &[], &[], &[], &Default::default());
+
+        // Ensure a certificate above the GC round is inserted.
+        let certificate = sample_batch_certificate_for_round(4, rng);
+        let (missing_transmissions, _) = sample_transmissions(&certificate, rng);
+        storage.insert_certificate_atomic(certificate.clone(), missing_transmissions).unwrap();
+        assert!(storage.contains_certificate(certificate.id()));
+    }
+
+    #[test]
+    fn test_garbage_collection_is_exhaustive() {
+        let rng = &mut TestRng::default();
+
+        // Sample a committee.
+        let committee = snarkvm::ledger::committee::test_helpers::sample_committee(rng);
+        // Initialize the ledger.
+        let ledger = Arc::new(MockLedgerService::new(committee));
+        // Initialize the storage.
+        let storage = Storage::<CurrentNetwork>::new(ledger, Arc::new(BFTMemoryService::new()), 2);
+
+        // Insert a certificate for each round.
+        for round in 1..=6 {
+            let certificate = sample_batch_certificate_for_round(round, rng);
+            let (missing_transmissions, _) = sample_transmissions(&certificate, rng);
+            storage.insert_certificate_atomic(certificate, missing_transmissions).unwrap();
+        }
+        // Garbage collect up to round 3.
+        storage.garbage_collect_certificates(5);
+        assert_eq!(storage.gc_round(), 3);
+
+        // Leave entries around the GC boundary, as if they reached storage outside of the garbage collection.
+        // A late certificate below the GC round, and one at the next GC round.
+        storage.testing_only_insert_certificate_testing_only(sample_batch_certificate_for_round(2, rng));
+        storage.testing_only_insert_certificate_testing_only(sample_batch_certificate_for_round(4, rng));
+        // A round entry whose certificate was removed, and a batch ID without a certificate.
+        let author = sample_batch_certificate_for_round(3, rng).author();
+        let (certificate_id, batch_id) = (Field::rand(rng), Field::rand(rng));
+        storage.rounds.write().entry(3).or_default().insert((certificate_id, batch_id, author));
+        storage.batch_ids.write().insert(Field::rand(rng), 1);
+
+        // Garbage collect up to round 4, twice, as the garbage collection is idempotent.
+        for _ in 0..2 {
+            storage.garbage_collect_certificates(6);
+            assert_eq!(storage.gc_round(), 4);
+
+            // Ensure nothing at or below the GC round remains in any map.
+            assert!(storage.rounds_iter().all(|(round, _)| round > 4));
+            assert!(storage.certificates_iter().all(|(_, certificate)| certificate.round() > 4));
+            assert!(storage.batch_ids_iter().all(|(_, round)| round > 4));
+            // Ensure the transmissions only refer to the remaining certificates.
+            let certificate_ids = storage.certificates_iter().map(|(id, _)| id).collect::<HashSet<_>>();
+            for (_, (_, transmission_certificate_ids)) in storage.transmissions_iter() {
+                assert!(transmission_certificate_ids.iter().all(|id| certificate_ids.contains(id)));
+            }
+            // Ensure the certificates above the GC round remain.
+            assert_eq!(storage.rounds_iter().map(|(round, _)| round).collect::<HashSet<_>>(), HashSet::from([5, 6]));
+            assert_eq!(storage.batch_ids_iter().count(), 2);
+        }
+    }
+
     #[test]
     fn test_certificate_duplicate() {
         let rng = &mut TestRng::default();
@@ -930,21 +1187,21 @@ mod tests {
         let (missing_transmissions, transmissions) = sample_transmissions(&certificate, rng);
 
         // Insert the certificate.
-        storage.insert_certificate_atomic(certificate.clone(), missing_transmissions.clone());
+        storage.insert_certificate_atomic(certificate.clone(), missing_transmissions.clone()).unwrap();
         // Ensure the certificate exists in storage.
         assert!(storage.contains_certificate(certificate_id));
         // Check that the underlying storage representation is correct.
         assert_storage(&storage, &rounds, &certificates, &batch_ids, &transmissions);
 
         // Insert the certificate again - without any missing transmissions.
-        storage.insert_certificate_atomic(certificate.clone(), Default::default());
+        storage.insert_certificate_atomic(certificate.clone(), Default::default()).unwrap();
         // Ensure the certificate exists in storage.
         assert!(storage.contains_certificate(certificate_id));
         // Check that the underlying storage representation remains unchanged.
         assert_storage(&storage, &rounds, &certificates, &batch_ids, &transmissions);
 
         // Insert the certificate again - with all of the original missing transmissions.
-        storage.insert_certificate_atomic(certificate, missing_transmissions);
+        storage.insert_certificate_atomic(certificate, missing_transmissions).unwrap();
         // Ensure the certificate exists in storage.
         assert!(storage.contains_certificate(certificate_id));
         // Check that the underlying storage representation remains unchanged.
@@ -1176,24 +1433,92 @@ pub mod prop_tests {
         // Insert the certificate.
         let missing_transmissions: HashMap<TransmissionID<CurrentNetwork>, Transmission<CurrentNetwork>> =
             transmission_map.into_iter().collect();
-        storage.insert_certificate_atomic(certificate.clone(), missing_transmissions.clone());
+        storage.insert_certificate_atomic(certificate.clone(), missing_transmissions.clone()).unwrap();
         // Ensure the certificate exists in storage.
         assert!(storage.contains_certificate(certificate_id));
         // Check that the underlying storage representation is correct.
         assert_storage(&storage, &rounds, &certificates, &batch_ids, &internal_transmissions);
 
         // Insert the certificate again - without any missing transmissions.
-        storage.insert_certificate_atomic(certificate.clone(), Default::default());
+        storage.insert_certificate_atomic(certificate.clone(), Default::default()).unwrap();
         // Ensure the certificate exists in storage.
         assert!(storage.contains_certificate(certificate_id));
         // Check that the underlying storage representation remains unchanged.
         assert_storage(&storage, &rounds, &certificates, &batch_ids, &internal_transmissions);
 
         // Insert the certificate again - with all of the original missing transmissions.
-        storage.insert_certificate_atomic(certificate, missing_transmissions);
+        storage.insert_certificate_atomic(certificate, missing_transmissions).unwrap();
         // Ensure the certificate exists in storage.
         assert!(storage.contains_certificate(certificate_id));
         // Check that the underlying storage representation remains unchanged.
         assert_storage(&storage, &rounds, &certificates, &batch_ids, &internal_transmissions);
     }
+
+    #[proptest]
+    fn test_certificate_equivocation(context: CommitteeContext, mut rng: CryptoTestRng, selector: Selector) {
+        let CommitteeContext(committee, ValidatorSet(validators)) = context;
+        let committee_id = committee.id();
+
+        // Initialize the storage.
+        let ledger = Arc::new(MockLedgerService::new(committee));
+        let storage = Storage::<CurrentNetwork>::new(ledger, Arc::new(BFTMemoryService::new()), 1);
+
+        // Create two different certificates from the same author for the same round.
+        let signer = selector.select(&validators);
+        let mut signers = validators.clone();
+        signers.remove(signer);
+        let round = 1;
+        let mut sample_certificate = |timestamp| {
+            let batch_header = BatchHeader::new(
+                &signer.private_key,
+                round,
+                timestamp,
+                committee_id,
+                Default::default(),
+                Default::default(),
+                &mut rng,
+            )
+            .unwrap();
+            let signatures = sign_batch_header(&ValidatorSet(signers.clone()), &batch_header, &mut rng);
+            BatchCertificate::from(batch_header, signatures).unwrap()
+        };
+        let certificate = sample_certificate(now());
+        let equivocation = sample_certificate(now() + 1);
+        let author = certificate.author();
+        assert_eq!(author, equivocation.author());
+        assert_ne!(certificate.id(), equivocation.id());
+
+        // Insert the first certificate.
+        storage.insert_certificate_atomic(certificate.clone(), Default::default()).unwrap();
+        assert_eq!(storage.get_certificate_for_round_and_author(round, author), Some(certificate.clone()));
+        assert_eq!(storage.get_certificate_id_for_round_and_author(round, author), Some(certificate.id()));
+
+        // Ensure the second certificate is rejected as an equivocation.
+        let error = storage.insert_certificate_atomic(equivocation.clone(), Default::default()).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<CertificateEquivocation<CurrentNetwork>>(),
+            Some(&CertificateEquivocation {
+                round,
+                author,
+                existing_certificate_id: certificate.id(),
+                certificate_id: equivocation.id(),
+            })
+        );
+        // Ensure the check rejects it in the same way.
+        let error = storage.check_certificate(&equivocation, Default::default()).unwrap_err();
+        assert!(error.downcast_ref::<CertificateEquivocation<CurrentNetwork>>().is_some());
+
+        // Ensure the storage only contains the first certificate.
+        assert!(!storage.contains_certificate(equivocation.id()));
+        assert_eq!(storage.get_certificates_for_round(round), indexset! { certificate.clone() });
+        assert_eq!(storage.get_certificate_for_round_and_author(round, author), Some(certificate));
+
+        // Garbage collect the round.
+        storage.garbage_collect_certificates(round + 2);
+        assert_eq!(storage.get_certificate_for_round_and_author(round, author), None);
+        assert!(!storage.contains_certificates_for_round(round));
+        // Ensure the round remains closed to the author, as it is at or below the GC round.
+        assert!(storage.insert_certificate(equivocation, Default::default()).is_err());
+        assert!(!storage.contains_certificates_for_round(round));
+    }
 }
@@ -0,0 +1,112 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkvm::{
+    console::{account::Address, network::Network, types::Field},
+    ledger::committee::Committee,
+};
+
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// A snapshot of the batch proposal of the primary.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(bound = "")]
+pub struct ProposalSummary<N: Network> {
+    /// The batch ID of the proposal.
+    pub batch_id: Field<N>,
+    /// The round of the proposal.
+    pub round: u64,
+    /// The timestamp of the proposal.
+    pub timestamp: i64,
+    /// The number of transmissions in the proposal.
+    pub num_transmissions: usize,
+    /// The committee members that have signed the proposal (including the author).
+    pub signers: Vec<Address<N>>,
+    /// The committee members that have not yet signed the proposal.
+    pub pending: Vec<Address<N>>,
+    /// Whether the signers have reached the quorum threshold.
+    pub is_quorum_threshold_reached: bool,
+}
+
+impl<N: Network> ProposalSummary<N> {
+    /// Initializes a new proposal summary, partitioning the committee into signers and pending members.
+    pub fn new(
+        batch_id: Field<N>,
+        round: u64,
+        timestamp: i64,
+        num_transmissions: usize,
+        signers: &HashSet<Address<N>>,
+        committee: &Committee<N>,
+    ) -> Self {
+        let (signers, pending) = partition_members(committee, signers);
+        let is_quorum_threshold_reached = committee.is_quorum_threshold_reached(&signers.iter().copied().collect());
+        Self { batch_id, round, timestamp, num_transmissions, signers, pending, is_quorum_threshold_reached }
+    }
+}
+
+/// A snapshot of the round state of the primary.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(bound = "")]
+pub struct RoundSummary<N: Network> {
+    /// The current round.
+    pub round: u64,
+    /// Whether the primary is synced.
+    pub is_synced: bool,
+    /// Whether the primary is awaiting the block that defines the committee of the current round,
+    /// in which case it neither proposes nor signs batches.
+    pub is_awaiting_committee_sync: bool,
+    /// Whether the primary has a proposal or a certificate in the current round.
+    pub has_proposed: bool,
+    /// Whether the certificates in the current round have reached the quorum threshold,
+    /// allowing the primary to advance to the next round.
+    pub is_ready_to_advance: bool,
+    /// The committee members with a certificate in storage for the current round.
+    pub authors: Vec<Address<N>>,
+    /// The committee members without a certificate in storage for the current round.
+    pub missing_authors: Vec<Address<N>>,
+}
+
+impl<N: Network> RoundSummary<N> {
+    /// Initializes a new round summary, partitioning the committee into authors and missing authors.
+    pub fn new(
+        round: u64,
+        is_synced: bool,
+        is_awaiting_committee_sync: bool,
+        has_proposed: bool,
+        authors: &HashSet<Address<N>>,
+        committee: &Committee<N>,
+    ) -> Self {
+        let (authors, missing_authors) = partition_members(committee, authors);
+        let is_ready_to_advance = committee.is_quorum_threshold_reached(&authors.iter().copied().collect());
+        Self {
+            round,
+            is_synced,
+            is_awaiting_committee_sync,
+            has_proposed,
+            is_ready_to_advance,
+            authors,
+            missing_authors,
+        }
+    }
+}
+
+/// Partitions the committee members into those in the given set, and those not in the given set.
+/// Addresses in the given set that are not committee members are ignored.
+fn partition_members<N: Network>(
+    committee: &Committee<N>,
+    addresses: &HashSet<Address<N>>,
+) -> (Vec<Address<N>>, Vec<Address<N>>) {
+    committee.members().keys().partition(|address| addresses.contains(*address))
+}
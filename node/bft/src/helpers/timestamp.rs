@@ -15,6 +15,10 @@
 use crate::MAX_TIMESTAMP_DELTA_IN_SECS;
 use snarkvm::prelude::{bail, Result};
 
+use std::sync::{
+    atomic::{AtomicI64, Ordering},
+    Arc,
+};
 use time::OffsetDateTime;
 
 /// Returns the current UTC epoch timestamp.
@@ -24,11 +28,52 @@ pub fn now() -> i64 {
 
 /// Sanity checks the timestamp for liveness.
 pub fn check_timestamp_for_liveness(timestamp: i64) -> Result<()> {
-    // Ensure the timestamp is within range.
-    if timestamp > (now() + MAX_TIMESTAMP_DELTA_IN_SECS) {
-        bail!("Timestamp {timestamp} is too far in the future")
+    Clock::System.check_timestamp_for_liveness(timestamp)
+}
+
+/// The source of the UTC epoch timestamps used by the memory pool.
+#[derive(Clone, Debug, Default)]
+pub enum Clock {
+    /// The system clock.
+    #[default]
+    System,
+    /// A clock that only advances when it is set, e.g. to the timestamps of a recorded event log.
+    Manual(Arc<AtomicI64>),
+}
+
+impl Clock {
+    /// Returns a manual clock, starting at the given timestamp.
+    pub fn manual(timestamp: i64) -> Self {
+        Self::Manual(Arc::new(AtomicI64::new(timestamp)))
+    }
+
+    /// Returns the current UTC epoch timestamp.
+    pub fn now(&self) -> i64 {
+        match self {
+            Self::System => now(),
+            Self::Manual(timestamp) => timestamp.load(Ordering::SeqCst),
+        }
+    }
+
+    /// Sets the timestamp of a manual clock.
+    pub fn set(&self, timestamp: i64) -> Result<()> {
+        match self {
+            Self::System => bail!("Cannot set the system clock"),
+            Self::Manual(current) => {
+                current.store(timestamp, Ordering::SeqCst);
+                Ok(())
+            }
+        }
+    }
+
+    /// Sanity checks the timestamp for liveness.
+    pub fn check_timestamp_for_liveness(&self, timestamp: i64) -> Result<()> {
+        // Ensure the timestamp is within range.
+        if timestamp > (self.now() + MAX_TIMESTAMP_DELTA_IN_SECS) {
+            bail!("Timestamp {timestamp} is too far in the future")
+        }
+        Ok(())
     }
-    Ok(())
 }
 
 #[cfg(test)]
@@ -56,4 +101,18 @@ mod prop_tests {
     fn test_check_timestamp_for_liveness_too_far_in_future(#[strategy(any_invalid_timestamp())] timestamp: i64) {
         assert!(check_timestamp_for_liveness(timestamp).is_err());
     }
+
+    #[test]
+    fn test_manual_clock() {
+        let clock = Clock::manual(100);
+        assert_eq!(clock.now(), 100);
+        // Ensure the liveness check is relative to the manual clock.
+        assert!(clock.check_timestamp_for_liveness(100 + MAX_TIMESTAMP_DELTA_IN_SECS).is_ok());
+        assert!(clock.check_timestamp_for_liveness(101 + MAX_TIMESTAMP_DELTA_IN_SECS).is_err());
+        // Ensure the manual clock only advances when it is set, and is shared by its clones.
+        clock.clone().set(200).unwrap();
+        assert_eq!(clock.now(), 200);
+        // Ensure the system clock cannot be set.
+        assert!(Clock::System.set(200).is_err());
+    }
 }
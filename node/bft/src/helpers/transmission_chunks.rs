@@ -0,0 +1,437 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    events::{TransmissionChunkResponse, MAX_TRANSMISSION_CHUNKS, TRANSMISSION_CHUNK_SIZE_IN_BYTES},
+    MAX_FETCH_TIMEOUT_IN_MS,
+};
+use snarkvm::{
+    console::prelude::*,
+    ledger::narwhal::{Transmission, TransmissionID},
+};
+
+use bytes::{Bytes, BytesMut};
+use indexmap::IndexMap;
+use parking_lot::Mutex;
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+/// The serialized size in bytes above which a transmission is transferred in chunks.
+pub const CHUNKED_TRANSMISSION_THRESHOLD_IN_BYTES: usize = 2 * TRANSMISSION_CHUNK_SIZE_IN_BYTES;
+/// The maximum number of chunked transmissions that are reassembled at once.
+pub const MAX_CONCURRENT_REASSEMBLIES: usize = 8;
+/// The number of milliseconds after which a missing chunk is requested again.
+pub const CHUNK_REQUEST_TIMEOUT_IN_MS: u64 = 1000;
+/// The number of milliseconds after which an incomplete chunked transfer is abandoned.
+pub const CHUNKED_TRANSFER_TIMEOUT_IN_MS: u64 = MAX_FETCH_TIMEOUT_IN_MS;
+/// The maximum number of serialized transmissions that are kept, to serve their chunks.
+const MAX_SERVED_TRANSMISSIONS: usize = 8;
+
+/// Returns the chunk with the given index of the serialized transmission, along with the total number of chunks.
+fn split_chunk(bytes: &Bytes, chunk_index: u32) -> Option<(u32, Bytes)> {
+    let total_chunks = bytes.len().div_ceil(TRANSMISSION_CHUNK_SIZE_IN_BYTES);
+    let start = (chunk_index as usize).checked_mul(TRANSMISSION_CHUNK_SIZE_IN_BYTES)?;
+    let end = start.saturating_add(TRANSMISSION_CHUNK_SIZE_IN_BYTES).min(bytes.len());
+    (start < bytes.len()).then(|| (total_chunks as u32, bytes.slice(start..end)))
+}
+
+/// The outcome of receiving a chunk of a transmission.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ChunkProgress {
+    /// The chunk started a new transfer, and the given chunks should be requested from the peer.
+    Started(Vec<u32>),
+    /// The transfer is in progress, and the given chunks should be requested from the peer.
+    Incomplete(Vec<u32>),
+    /// The transfer is complete, with the reassembled bytes of the transmission.
+    Complete(Bytes),
+}
+
+/// A transmission that is reassembled from the chunks received from a peer.
+#[derive(Debug)]
+struct ChunkedTransfer {
+    /// The IP of the peer serving the transfer.
+    peer_ip: SocketAddr,
+    /// The received chunks, by chunk index.
+    chunks: Vec<Option<Bytes>>,
+    /// The time at which each chunk was last requested, by chunk index.
+    requested_at: Vec<Option<Instant>>,
+    /// The time at which the transfer started.
+    started_at: Instant,
+}
+
+impl ChunkedTransfer {
+    /// Initializes a new transfer of the given number of chunks from the given peer.
+    fn new(peer_ip: SocketAddr, total_chunks: u32, now: Instant) -> Result<Self> {
+        ensure!(
+            (1..=MAX_TRANSMISSION_CHUNKS).contains(&total_chunks),
+            "Invalid number of transmission chunks ({total_chunks})"
+        );
+        let total_chunks = total_chunks as usize;
+        Ok(Self { peer_ip, chunks: vec![None; total_chunks], requested_at: vec![None; total_chunks], started_at: now })
+    }
+
+    /// Returns `true` if all chunks were received.
+    fn is_complete(&self) -> bool {
+        self.chunks.iter().all(Option::is_some)
+    }
+
+    /// Returns `true` if the transfer did not complete in time.
+    fn is_expired(&self, now: Instant) -> bool {
+        now.saturating_duration_since(self.started_at) > Duration::from_millis(CHUNKED_TRANSFER_TIMEOUT_IN_MS)
+    }
+
+    /// Inserts the given chunk, and returns `true` if it was not received before.
+    fn insert(&mut self, chunk_index: u32, total_chunks: u32, bytes: Bytes) -> Result<bool> {
+        ensure!(total_chunks as usize == self.chunks.len(), "Mismatching number of chunks ({total_chunks})");
+        let is_last = chunk_index as usize + 1 == self.chunks.len();
+        let Some(chunk) = self.chunks.get_mut(chunk_index as usize) else {
+            bail!("Invalid chunk index {chunk_index} of {total_chunks}")
+        };
+        // Ensure every chunk but the last is full, so that the chunks reassemble at the expected offsets.
+        ensure!(is_last || bytes.len() == TRANSMISSION_CHUNK_SIZE_IN_BYTES, "Chunk {chunk_index} is not full");
+        if chunk.is_some() {
+            return Ok(false);
+        }
+        *chunk = Some(bytes);
+        Ok(true)
+    }
+
+    /// Returns the indices of the missing chunks that were never requested, or were requested too long ago,
+    /// and marks them as requested.
+    fn next_requests(&mut self, now: Instant) -> Vec<u32> {
+        let timeout = Duration::from_millis(CHUNK_REQUEST_TIMEOUT_IN_MS);
+        let mut requests = Vec::new();
+        for (index, (chunk, requested_at)) in self.chunks.iter().zip(self.requested_at.iter_mut()).enumerate() {
+            let is_due = requested_at.map_or(true, |time| now.saturating_duration_since(time) >= timeout);
+            if chunk.is_none() && is_due {
+                *requested_at = Some(now);
+                requests.push(index as u32);
+            }
+        }
+        requests
+    }
+
+    /// Returns the reassembled bytes of the transmission.
+    fn into_bytes(self) -> Bytes {
+        let mut bytes = BytesMut::new();
+        self.chunks.into_iter().flatten().for_each(|chunk| bytes.extend_from_slice(&chunk));
+        bytes.freeze()
+    }
+}
+
+/// The transmissions that are transferred in chunks by a worker, both the transfers it reassembles,
+/// and the serialized transmissions it serves.
+#[derive(Debug)]
+pub struct TransmissionChunks<N: Network> {
+    /// The map of transmission IDs to the transfers being reassembled.
+    transfers: Mutex<HashMap<TransmissionID<N>, ChunkedTransfer>>,
+    /// The map of transmission IDs to the serialized transmissions being served.
+    served: Mutex<IndexMap<TransmissionID<N>, Bytes>>,
+}
+
+impl<N: Network> Default for TransmissionChunks<N> {
+    /// Initializes a new instance of the transmission chunks.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N: Network> TransmissionChunks<N> {
+    /// Initializes a new instance of the transmission chunks.
+    pub fn new() -> Self {
+        Self { transfers: Default::default(), served: Default::default() }
+    }
+
+    /// Returns the number of transfers being reassembled.
+    pub fn num_transfers(&self) -> usize {
+        self.transfers.lock().len()
+    }
+
+    /// Returns `true` if the transmission is being reassembled.
+    pub fn contains_transfer(&self, transmission_id: TransmissionID<N>) -> bool {
+        self.transfers.lock().contains_key(&transmission_id)
+    }
+
+    /// Removes the transfer of the given transmission, if it exists.
+    pub fn remove_transfer(&self, transmission_id: TransmissionID<N>) {
+        self.transfers.lock().remove(&transmission_id);
+    }
+
+    /// Inserts a chunk of the transmission received from the given peer, and returns the progress of the transfer.
+    pub fn insert_chunk(&self, peer_ip: SocketAddr, response: TransmissionChunkResponse<N>) -> Result<ChunkProgress> {
+        self.insert_chunk_at(peer_ip, response, Instant::now())
+    }
+
+    /// Inserts a chunk of the transmission received from the given peer at the given time,
+    /// and returns the progress of the transfer.
+    fn insert_chunk_at(
+        &self,
+        peer_ip: SocketAddr,
+        response: TransmissionChunkResponse<N>,
+        now: Instant,
+    ) -> Result<ChunkProgress> {
+        let TransmissionChunkResponse { transmission_id, chunk_index, total_chunks, bytes } = response;
+
+        let mut transfers = self.transfers.lock();
+        // Remove the expired transfers.
+        transfers.retain(|_, transfer| !transfer.is_expired(now));
+        // Retrieve the transfer, or start a new one if there is room.
+        let num_transfers = transfers.len();
+        let (transfer, is_new) = match transfers.entry(transmission_id) {
+            Entry::Occupied(entry) => {
+                // Note: A transfer is only served by one peer, so that its chunks are consistent.
+                ensure!(entry.get().peer_ip == peer_ip, "The transmission is being transferred by another peer");
+                (entry.into_mut(), false)
+            }
+            Entry::Vacant(entry) => {
+                ensure!(num_transfers < MAX_CONCURRENT_REASSEMBLIES, "Too many transmissions are being reassembled");
+                (entry.insert(ChunkedTransfer::new(peer_ip, total_chunks, now)?), true)
+            }
+        };
+        // Insert the chunk.
+        if let Err(error) = transfer.insert(chunk_index, total_chunks, bytes) {
+            // Note: A new transfer is dropped on an invalid chunk, so that it does not take up a slot.
+            if is_new {
+                transfers.remove(&transmission_id);
+            }
+            return Err(error);
+        }
+        // If the transfer is complete, return the reassembled bytes.
+        if transfer.is_complete() {
+            let transfer = transfers.remove(&transmission_id).expect("The transfer must exist");
+            return Ok(ChunkProgress::Complete(transfer.into_bytes()));
+        }
+        // Otherwise, return the chunks to request.
+        let requests = transfer.next_requests(now);
+        Ok(match is_new {
+            true => ChunkProgress::Started(requests),
+            false => ChunkProgress::Incomplete(requests),
+        })
+    }
+
+    /// Returns the peer and the missing chunks to request again for the given transfer,
+    /// or `None` if the transfer finished or expired.
+    pub fn retry_requests(&self, transmission_id: TransmissionID<N>) -> Option<(SocketAddr, Vec<u32>)> {
+        self.retry_requests_at(transmission_id, Instant::now())
+    }
+
+    /// Returns the peer and the missing chunks to request again for the given transfer at the given time,
+    /// or `None` if the transfer finished or expired.
+    fn retry_requests_at(&self, transmission_id: TransmissionID<N>, now: Instant) -> Option<(SocketAddr, Vec<u32>)> {
+        let mut transfers = self.transfers.lock();
+        let transfer = transfers.get_mut(&transmission_id)?;
+        if transfer.is_expired(now) {
+            transfers.remove(&transmission_id);
+            return None;
+        }
+        Some((transfer.peer_ip, transfer.next_requests(now)))
+    }
+
+    /// Returns the first chunk of the given transmission, or `None` if it is small enough to be sent in one message.
+    pub fn first_chunk(
+        &self,
+        transmission_id: TransmissionID<N>,
+        transmission: &Transmission<N>,
+    ) -> Result<Option<TransmissionChunkResponse<N>>> {
+        let bytes = Bytes::from(transmission.to_bytes_le()?);
+        // Note: A transmission that exceeds the maximum number of chunks is sent in one message,
+        // which is refused by the peer as it exceeds the maximum size of a transmission.
+        let max_size = MAX_TRANSMISSION_CHUNKS as usize * TRANSMISSION_CHUNK_SIZE_IN_BYTES;
+        if bytes.len() <= CHUNKED_TRANSMISSION_THRESHOLD_IN_BYTES || bytes.len() > max_size {
+            return Ok(None);
+        }
+        let response = self.chunk_of(transmission_id, &bytes, 0);
+        self.insert_served(transmission_id, bytes);
+        Ok(response)
+    }
+
+    /// Returns the requested chunk of the given transmission, or `None` if the transmission or chunk does not exist.
+    /// The transmission is only retrieved and serialized again, if it is no longer being served.
+    pub fn chunk(
+        &self,
+        transmission_id: TransmissionID<N>,
+        chunk_index: u32,
+        transmission: impl FnOnce() -> Option<Transmission<N>>,
+    ) -> Result<Option<TransmissionChunkResponse<N>>> {
+        let served = self.served.lock().get(&transmission_id).cloned();
+        let bytes = match served {
+            Some(bytes) => bytes,
+            None => {
+                let Some(transmission) = transmission() else {
+                    return Ok(None);
+                };
+                let bytes = Bytes::from(transmission.to_bytes_le()?);
+                self.insert_served(transmission_id, bytes.clone());
+                bytes
+            }
+        };
+        Ok(self.chunk_of(transmission_id, &bytes, chunk_index))
+    }
+
+    /// Returns the chunk with the given index of the serialized transmission.
+    fn chunk_of(
+        &self,
+        transmission_id: TransmissionID<N>,
+        bytes: &Bytes,
+        chunk_index: u32,
+    ) -> Option<TransmissionChunkResponse<N>> {
+        split_chunk(bytes, chunk_index).map(|(total_chunks, chunk)| {
+            TransmissionChunkResponse::new(transmission_id, chunk_index, total_chunks, chunk)
+        })
+    }
+
+    /// Keeps the serialized transmission, to serve its chunks.
+    fn insert_served(&self, transmission_id: TransmissionID<N>, bytes: Bytes) {
+        let mut served = self.served.lock();
+        served.insert(transmission_id, bytes);
+        // Evict the oldest transmissions.
+        while served.len() > MAX_SERVED_TRANSMISSIONS {
+            served.shift_remove_index(0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkvm::{
+        console::types::Field,
+        ledger::narwhal::Data,
+        prelude::{TestRng, Uniform},
+    };
+
+    type CurrentNetwork = snarkvm::prelude::MainnetV0;
+
+    /// Returns a sample transaction transmission of the given size.
+    fn sample_transmission(
+        num_bytes: usize,
+        rng: &mut TestRng,
+    ) -> (TransmissionID<CurrentNetwork>, Transmission<CurrentNetwork>) {
+        let transaction_id: <CurrentNetwork as Network>::TransactionID = Field::<CurrentNetwork>::rand(rng).into();
+        let bytes = (0..num_bytes).map(|_| rng.gen::<u8>()).collect::<Vec<_>>();
+        (TransmissionID::Transaction(transaction_id), Transmission::Transaction(Data::Buffer(Bytes::from(bytes))))
+    }
+
+    #[test]
+    fn test_small_transmission_is_not_chunked() {
+        let rng = &mut TestRng::default();
+        let chunks = TransmissionChunks::<CurrentNetwork>::new();
+        let (transmission_id, transmission) = sample_transmission(CHUNKED_TRANSMISSION_THRESHOLD_IN_BYTES / 2, rng);
+        assert_eq!(chunks.first_chunk(transmission_id, &transmission).unwrap(), None);
+    }
+
+    #[test]
+    fn test_chunks_reassemble() {
+        let rng = &mut TestRng::default();
+        let chunks = TransmissionChunks::<CurrentNetwork>::new();
+        let peer_ip = SocketAddr::from(([127, 0, 0, 1], 1234));
+        let (transmission_id, transmission) = sample_transmission(3 * TRANSMISSION_CHUNK_SIZE_IN_BYTES + 100, rng);
+
+        // Ensure the first chunk starts the transfer, and the remaining chunks are requested.
+        let first = chunks.first_chunk(transmission_id, &transmission).unwrap().unwrap();
+        assert_eq!((first.chunk_index, first.total_chunks), (0, 4));
+        assert_eq!(chunks.insert_chunk(peer_ip, first).unwrap(), ChunkProgress::Started(vec![1, 2, 3]));
+
+        // Insert the remaining chunks, in any order.
+        for chunk_index in [3, 1] {
+            let response = chunks.chunk(transmission_id, chunk_index, || None).unwrap().unwrap();
+            assert_eq!(chunks.insert_chunk(peer_ip, response).unwrap(), ChunkProgress::Incomplete(vec![]));
+        }
+        let response = chunks.chunk(transmission_id, 2, || None).unwrap().unwrap();
+        let ChunkProgress::Complete(bytes) = chunks.insert_chunk(peer_ip, response).unwrap() else {
+            panic!("The transfer should be complete")
+        };
+        // Ensure the reassembled bytes match the transmission.
+        assert_eq!(Transmission::<CurrentNetwork>::read_le(&bytes[..]).unwrap(), transmission);
+        assert_eq!(chunks.num_transfers(), 0);
+    }
+
+    #[test]
+    fn test_missing_chunks_are_requested_again() {
+        let rng = &mut TestRng::default();
+        let chunks = TransmissionChunks::<CurrentNetwork>::new();
+        let peer_ip = SocketAddr::from(([127, 0, 0, 1], 1234));
+        let (transmission_id, transmission) = sample_transmission(2 * TRANSMISSION_CHUNK_SIZE_IN_BYTES + 100, rng);
+
+        let now = Instant::now();
+        let first = chunks.first_chunk(transmission_id, &transmission).unwrap().unwrap();
+        assert_eq!(chunks.insert_chunk_at(peer_ip, first, now).unwrap(), ChunkProgress::Started(vec![1, 2]));
+        let response = chunks.chunk(transmission_id, 2, || None).unwrap().unwrap();
+        assert_eq!(chunks.insert_chunk_at(peer_ip, response, now).unwrap(), ChunkProgress::Incomplete(vec![]));
+
+        // Ensure the missing chunk is only requested again after the timeout, without restarting the transfer.
+        assert_eq!(chunks.retry_requests_at(transmission_id, now), Some((peer_ip, vec![])));
+        let later = now + Duration::from_millis(CHUNK_REQUEST_TIMEOUT_IN_MS);
+        assert_eq!(chunks.retry_requests_at(transmission_id, later), Some((peer_ip, vec![1])));
+        assert_eq!(chunks.retry_requests_at(transmission_id, later), Some((peer_ip, vec![])));
+
+        // Ensure the transfer is abandoned once it expires.
+        let expired = now + Duration::from_millis(CHUNKED_TRANSFER_TIMEOUT_IN_MS + 1);
+        assert_eq!(chunks.retry_requests_at(transmission_id, expired), None);
+        assert!(!chunks.contains_transfer(transmission_id));
+    }
+
+    #[test]
+    fn test_reassemblies_are_bounded() {
+        let rng = &mut TestRng::default();
+        let chunks = TransmissionChunks::<CurrentNetwork>::new();
+        let peer_ip = SocketAddr::from(([127, 0, 0, 1], 1234));
+
+        // Start the maximum number of transfers.
+        let now = Instant::now();
+        for _ in 0..MAX_CONCURRENT_REASSEMBLIES {
+            let (transmission_id, transmission) = sample_transmission(2 * TRANSMISSION_CHUNK_SIZE_IN_BYTES + 100, rng);
+            let first = chunks.first_chunk(transmission_id, &transmission).unwrap().unwrap();
+            assert!(matches!(chunks.insert_chunk_at(peer_ip, first, now).unwrap(), ChunkProgress::Started(_)));
+        }
+        // Ensure another transfer is refused.
+        let (transmission_id, transmission) = sample_transmission(2 * TRANSMISSION_CHUNK_SIZE_IN_BYTES + 100, rng);
+        let first = chunks.first_chunk(transmission_id, &transmission).unwrap().unwrap();
+        assert!(chunks.insert_chunk_at(peer_ip, first.clone(), now).is_err());
+        assert_eq!(chunks.num_transfers(), MAX_CONCURRENT_REASSEMBLIES);
+
+        // Ensure the transfer is accepted, once the other transfers expire.
+        let expired = now + Duration::from_millis(CHUNKED_TRANSFER_TIMEOUT_IN_MS + 1);
+        assert!(matches!(chunks.insert_chunk_at(peer_ip, first, expired).unwrap(), ChunkProgress::Started(_)));
+        assert_eq!(chunks.num_transfers(), 1);
+    }
+
+    #[test]
+    fn test_inconsistent_chunks_are_refused() {
+        let rng = &mut TestRng::default();
+        let chunks = TransmissionChunks::<CurrentNetwork>::new();
+        let peer_ip = SocketAddr::from(([127, 0, 0, 1], 1234));
+        let other_ip = SocketAddr::from(([127, 0, 0, 1], 5678));
+        let (transmission_id, transmission) = sample_transmission(2 * TRANSMISSION_CHUNK_SIZE_IN_BYTES + 100, rng);
+
+        let first = chunks.first_chunk(transmission_id, &transmission).unwrap().unwrap();
+        chunks.insert_chunk(peer_ip, first).unwrap();
+        let response = chunks.chunk(transmission_id, 1, || None).unwrap().unwrap();
+
+        // Ensure a chunk from another peer is refused.
+        assert!(chunks.insert_chunk(other_ip, response.clone()).is_err());
+        // Ensure a chunk with a mismatching number of chunks is refused.
+        let mut mismatching = response.clone();
+        mismatching.total_chunks += 1;
+        assert!(chunks.insert_chunk(peer_ip, mismatching).is_err());
+        // Ensure a truncated chunk is refused.
+        let mut truncated = response;
+        truncated.bytes = truncated.bytes.slice(1..);
+        assert!(chunks.insert_chunk(peer_ip, truncated).is_err());
+        assert!(chunks.contains_transfer(transmission_id));
+    }
+}
@@ -36,6 +36,9 @@ pub use gateway::*;
 mod primary;
 pub use primary::*;
 
+mod replay;
+pub use replay::*;
+
 mod sync;
 pub use sync::*;
 
@@ -51,6 +54,10 @@ pub const MEMORY_POOL_PORT: u16 = 5000; // port
 pub const MAX_BATCH_DELAY_IN_MS: u64 = 2500; // ms
 /// The maximum number of milliseconds to wait before timing out on a fetch.
 pub const MAX_FETCH_TIMEOUT_IN_MS: u64 = 3 * MAX_BATCH_DELAY_IN_MS; // ms
+/// The maximum number of solution fetches that may be in flight at once.
+pub const MAX_CONCURRENT_SOLUTION_FETCHES: usize = 64; // fetches
+/// The maximum number of transaction fetches that may be in flight at once.
+pub const MAX_CONCURRENT_TRANSACTION_FETCHES: usize = 256; // fetches
 /// The maximum number of seconds allowed for the leader to send their certificate.
 pub const MAX_LEADER_CERTIFICATE_DELAY_IN_SECS: i64 = 2 * MAX_BATCH_DELAY_IN_MS as i64 / 1000; // seconds
 /// The maximum number of seconds before the timestamp is considered expired.
@@ -64,6 +71,32 @@ pub const PRIMARY_PING_IN_MS: u64 = 2 * MAX_BATCH_DELAY_IN_MS; // ms
 /// The frequency at which each worker broadcasts a ping to every other node.
 pub const WORKER_PING_IN_MS: u64 = 4 * MAX_BATCH_DELAY_IN_MS; // ms
 
+/// Whether each primary checks for stalled rounds and takes recovery actions.
+pub const STALL_DETECTION_ENABLED: bool = true;
+/// The frequency at which each primary checks for a stalled round.
+pub const STALL_CHECK_INTERVAL_IN_MS: u64 = MAX_BATCH_DELAY_IN_MS; // ms
+/// The number of seconds without a round advance, after which a round may be considered stalled.
+pub const STALL_ROUND_THRESHOLD_IN_SECS: u64 = 60; // seconds
+/// The number of seconds without a new certificate, after which a round may be considered stalled.
+pub const STALL_CERTIFICATE_THRESHOLD_IN_SECS: u64 = 30; // seconds
+/// The number of seconds between the escalating recovery actions for a stalled round.
+pub const STALL_ESCALATION_INTERVAL_IN_SECS: u64 = 15; // seconds
+/// The frequency at which the BFT warns that its commits are paused, while it waits for a block to be produced.
+pub const CONSENSUS_LAG_WARNING_INTERVAL_IN_MS: u64 = MAX_BATCH_DELAY_IN_MS; // ms
+
+/// Whether each primary delays proposing a batch without transactions, instead of proposing it right away.
+pub const EMPTY_BATCH_DELAY_ENABLED: bool = true;
+/// The maximum number of seconds a primary delays proposing a batch without transactions.
+/// Note: This must stay below `STALL_ROUND_THRESHOLD_IN_SECS`, so that an idle round is not considered stalled.
+pub const MAX_EMPTY_BATCH_DELAY_IN_SECS: u64 = 20; // seconds
+
+/// The number of seconds a certificate is buffered, while its missing previous certificates or transmissions are fetched.
+pub const PENDING_CERTIFICATE_TTL_IN_SECS: u64 = 60; // seconds
+/// The maximum number of certificates buffered for each round, while their missing dependencies are fetched.
+pub const MAX_PENDING_CERTIFICATES_PER_ROUND: usize = 64; // certificates
+/// The frequency at which each primary expires the buffered certificates, and retries those whose dependencies arrived.
+pub const PENDING_CERTIFICATE_CHECK_INTERVAL_IN_MS: u64 = MAX_BATCH_DELAY_IN_MS; // ms
+
 /// A helper macro to spawn a blocking task.
 #[macro_export]
 macro_rules! spawn_blocking {
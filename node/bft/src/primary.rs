@@ -13,33 +13,55 @@
 // limitations under the License.
 
 use crate::{
-    events::{BatchPropose, BatchSignature, Event},
+    events::{deserialize_data, BatchPropose, BatchSignature, Event},
     helpers::{
         assign_to_worker,
         assign_to_workers,
+        diagnose_stall,
         fmt_id,
         init_sync_channels,
         init_worker_channels,
-        now,
         BFTSender,
+        CertificateBuffer,
+        CertificateBufferSummary,
+        CertificateEquivocation,
+        Dependency,
+        EmptyBatchMode,
+        PendingCertificate,
         PrimaryReceiver,
         PrimarySender,
+        PriorityLaneConfig,
         Proposal,
+        ProposalSummary,
+        RecordedEvent,
+        RecoveryAction,
+        RoundSummary,
+        Signer,
+        SigningContext,
+        StallConfig,
+        StallDetector,
         Storage,
     },
     spawn_blocking,
     Gateway,
+    ReplayPool,
     Sync,
     Transport,
     Worker,
     MAX_BATCH_DELAY_IN_MS,
     MAX_WORKERS,
+    PENDING_CERTIFICATE_CHECK_INTERVAL_IN_MS,
     PRIMARY_PING_IN_MS,
+    STALL_CHECK_INTERVAL_IN_MS,
     WORKER_PING_IN_MS,
 };
-use snarkos_account::Account;
 use snarkos_node_bft_events::PrimaryPing;
-use snarkos_node_bft_ledger_service::LedgerService;
+use snarkos_node_bft_ledger_service::{
+    committee_lookback_round,
+    verification_pool,
+    LedgerService,
+    VerificationCategory,
+};
 use snarkvm::{
     console::{
         account::Signature,
@@ -63,12 +85,16 @@ use std::{
     collections::{HashMap, HashSet},
     future::Future,
     net::SocketAddr,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
     time::Duration,
 };
 use tokio::{
     sync::{Mutex as TMutex, OnceCell},
     task::JoinHandle,
+    time::Instant,
 };
 
 /// A helper type for an optional proposed batch.
@@ -96,15 +122,38 @@ pub struct Primary<N: Network> {
     handles: Arc<Mutex<Vec<JoinHandle<()>>>>,
     /// The lock for propose_batch.
     propose_lock: Arc<TMutex<u64>>,
+    /// The maximum number of solutions a batch may reference, when proposing and when validating batches.
+    max_solutions_per_batch: usize,
+    /// Whether the primary participates in the BFT, by proposing batches.
+    is_participating: Arc<AtomicBool>,
+    /// Whether the system clock is skewed against the network, beyond the timestamp tolerance of the BFT.
+    is_clock_skewed: Arc<AtomicBool>,
+    /// Whether the ledger lacks the block that defines the committee of the current round.
+    is_awaiting_committee_sync: Arc<AtomicBool>,
+    /// The stall detector.
+    stall_detector: Arc<StallDetector>,
+    /// The behavior of the primary when it has no unconfirmed transactions to propose.
+    empty_batch_mode: EmptyBatchMode,
+    /// The quotas of the priority lane, for consensus-critical transmissions.
+    priority_lane: PriorityLaneConfig,
+    /// The time of the last batch proposal.
+    last_proposal: Arc<Mutex<Instant>>,
+    /// The certificates that arrived before their previous certificates or transmissions.
+    certificate_buffer: Arc<CertificateBuffer<N>>,
 }
 
 impl<N: Network> Primary<N> {
+    /// The maximum number of solutions a batch may reference.
+    /// Note: A block includes at most `N::MAX_SOLUTIONS` solutions, so any further solutions of a batch would be
+    /// fetched only to be aborted. Batches from peers above this limit are rejected before any missing
+    /// transmissions are fetched. The number of transmissions is already limited by the batch header.
+    pub const MAX_SOLUTIONS_PER_BATCH: usize = N::MAX_SOLUTIONS;
     /// The maximum number of unconfirmed transmissions to send to the primary.
     pub const MAX_TRANSMISSIONS_TOLERANCE: usize = BatchHeader::<N>::MAX_TRANSMISSIONS_PER_BATCH * 2;
 
     /// Initializes a new primary instance.
     pub fn new(
-        account: Account<N>,
+        signer: impl Signer<N> + 'static,
         storage: Storage<N>,
         ledger: Arc<dyn LedgerService<N>>,
         ip: Option<SocketAddr>,
@@ -112,7 +161,7 @@ impl<N: Network> Primary<N> {
         dev: Option<u16>,
     ) -> Result<Self> {
         // Initialize the gateway.
-        let gateway = Gateway::new(account, storage.clone(), ledger.clone(), ip, trusted_validators, dev)?;
+        let gateway = Gateway::new(signer, storage.clone(), ledger.clone(), ip, trusted_validators, dev)?;
         // Initialize the sync module.
         let sync = Sync::new(gateway.clone(), storage.clone(), ledger.clone());
         // Initialize the primary instance.
@@ -127,9 +176,36 @@ impl<N: Network> Primary<N> {
             signed_proposals: Default::default(),
             handles: Default::default(),
             propose_lock: Default::default(),
+            max_solutions_per_batch: Self::MAX_SOLUTIONS_PER_BATCH,
+            is_participating: Arc::new(AtomicBool::new(true)),
+            is_clock_skewed: Default::default(),
+            is_awaiting_committee_sync: Default::default(),
+            stall_detector: Default::default(),
+            empty_batch_mode: Default::default(),
+            priority_lane: Default::default(),
+            last_proposal: Arc::new(Mutex::new(Instant::now())),
+            certificate_buffer: Default::default(),
         })
     }
 
+    /// Sets the configuration of the stall detector.
+    pub fn with_stall_config(mut self, config: StallConfig) -> Self {
+        self.stall_detector = Arc::new(StallDetector::new(config));
+        self
+    }
+
+    /// Sets the behavior of the primary when it has no unconfirmed transactions to propose.
+    pub fn with_empty_batch_mode(mut self, mode: EmptyBatchMode) -> Self {
+        self.empty_batch_mode = mode;
+        self
+    }
+
+    /// Sets the quotas of the priority lane, for consensus-critical transmissions.
+    pub fn with_priority_lane(mut self, config: PriorityLaneConfig) -> Self {
+        self.priority_lane = config;
+        self
+    }
+
     /// Run the primary instance.
     pub async fn run(
         &mut self,
@@ -194,6 +270,11 @@ impl<N: Network> Primary<N> {
         self.sync.is_synced()
     }
 
+    /// Returns the sync module.
+    pub const fn sync(&self) -> &Sync<N> {
+        &self.sync
+    }
+
     /// Returns the gateway.
     pub const fn gateway(&self) -> &Gateway<N> {
         &self.gateway
@@ -223,6 +304,41 @@ impl<N: Network> Primary<N> {
     pub fn proposed_batch(&self) -> &Arc<ProposedBatch<N>> {
         &self.proposed_batch
     }
+
+    /// Returns `true` if the primary participates in the BFT, by proposing batches.
+    pub fn is_participating(&self) -> bool {
+        self.is_participating.load(Ordering::Relaxed)
+    }
+
+    /// Sets whether the primary participates in the BFT, by proposing batches.
+    /// Note: A primary that does not participate still syncs and serves certificates to its peers.
+    pub fn set_participating(&self, is_participating: bool) {
+        self.is_participating.store(is_participating, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if the system clock is skewed against the network, beyond the timestamp tolerance of the BFT.
+    pub fn is_clock_skewed(&self) -> bool {
+        self.is_clock_skewed.load(Ordering::Relaxed)
+    }
+
+    /// Sets whether the system clock is skewed against the network, beyond the timestamp tolerance of the BFT.
+    /// Note: A primary with a skewed clock stops proposing batches, as its peers would reject their timestamps,
+    /// but it keeps validating and syncing.
+    pub fn set_clock_skewed(&self, is_clock_skewed: bool) {
+        self.is_clock_skewed.store(is_clock_skewed, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if the primary is awaiting the block that defines the committee of the current round.
+    pub fn is_awaiting_committee_sync(&self) -> bool {
+        self.is_awaiting_committee_sync.load(Ordering::Relaxed)
+    }
+
+    /// Returns `true` if the ledger lacks the block that defines the committee of the given round.
+    /// Note: Until that block is applied, the ledger returns the latest committee for the round,
+    /// which is stale if the block changes the committee (i.e. through bonding or unbonding).
+    fn is_committee_unsynced_for_round(&self, round: u64) -> bool {
+        committee_lookback_round::<N>(round) > self.ledger.latest_round()
+    }
 }
 
 impl<N: Network> Primary<N> {
@@ -269,6 +385,114 @@ impl<N: Network> Primary<N> {
     }
 }
 
+impl<N: Network> Primary<N> {
+    /// Returns `true` if the given transmission is in the in-flight batch proposal, or in a certified batch.
+    pub fn is_transmission_in_flight(&self, transmission_id: impl Into<TransmissionID<N>>) -> bool {
+        let transmission_id = transmission_id.into();
+        self.proposed_batch.read().as_ref().map_or(false, |proposal| proposal.contains_transmission(transmission_id))
+            || self.storage.contains_transmission(transmission_id)
+    }
+
+    /// Returns `true` if the given transaction is in the ready queue of its worker, or in flight.
+    pub fn contains_unconfirmed_transaction(&self, transaction_id: N::TransactionID) -> bool {
+        let Ok(worker_id) = assign_to_worker::<N>(&transaction_id, self.num_workers()) else {
+            return false;
+        };
+        self.workers.get(worker_id as usize).map_or(false, |worker| worker.is_ready(&transaction_id))
+            || self.is_transmission_in_flight(&transaction_id)
+    }
+
+    /// Returns the unconfirmed solution, if it is in the ready queue of its worker, the proposed batch, or storage.
+    pub fn get_unconfirmed_solution(&self, solution_id: SolutionID<N>) -> Option<Data<Solution<N>>> {
+        // Compute the worker ID.
+        let worker_id = assign_to_worker::<N>(solution_id, self.num_workers()).ok()?;
+        // Retrieve the solution from the worker.
+        match self.workers.get(worker_id as usize)?.get_transmission(TransmissionID::Solution(solution_id))? {
+            Transmission::Solution(solution) => Some(solution),
+            _ => None,
+        }
+    }
+
+    /// Returns the unconfirmed transaction, if it is in the ready queue of its worker, the proposed batch, or storage.
+    pub fn get_unconfirmed_transaction(&self, transaction_id: N::TransactionID) -> Option<Data<Transaction<N>>> {
+        // Compute the worker ID.
+        let worker_id = assign_to_worker::<N>(&transaction_id, self.num_workers()).ok()?;
+        // Retrieve the transaction from the worker.
+        match self.workers.get(worker_id as usize)?.get_transmission(TransmissionID::from(&transaction_id))? {
+            Transmission::Transaction(transaction) => Some(transaction),
+            _ => None,
+        }
+    }
+
+    /// Removes the given unconfirmed transaction from the workers, returning `true` if it was removed.
+    ///
+    /// Note: A transaction that is in the in-flight batch proposal, or in a certified batch, cannot be removed.
+    pub fn remove_unconfirmed_transaction(&self, transaction_id: N::TransactionID) -> Result<bool> {
+        // Ensure the transaction is not in flight.
+        if self.is_transmission_in_flight(&transaction_id) {
+            bail!("Transaction '{}' is already included in a batch", fmt_id(transaction_id));
+        }
+        // Compute the worker ID.
+        let worker_id = assign_to_worker::<N>(&transaction_id, self.num_workers())?;
+        // Remove the transaction from the worker.
+        match self.workers.get(worker_id as usize) {
+            Some(worker) => Ok(worker.remove(&transaction_id)),
+            None => bail!("Unable to find worker {worker_id}"),
+        }
+    }
+}
+
+impl<N: Network> Primary<N> {
+    /// Returns a summary of the batch proposal of our primary, if one currently exists.
+    pub fn proposal_summary(&self) -> Result<Option<ProposalSummary<N>>> {
+        // Retrieve the details of the proposal, holding the lock only for the duration of the copy.
+        let Some((batch_id, round, timestamp, num_transmissions, signers)) =
+            self.proposed_batch.read().as_ref().map(|proposal| {
+                (
+                    proposal.batch_id(),
+                    proposal.round(),
+                    proposal.timestamp(),
+                    proposal.transmissions().len(),
+                    proposal.signers(),
+                )
+            })
+        else {
+            return Ok(None);
+        };
+        // Retrieve the committee lookback for the round.
+        let committee_lookback = self.ledger.get_committee_lookback_for_round(round)?;
+        // Return the proposal summary.
+        Ok(Some(ProposalSummary::new(batch_id, round, timestamp, num_transmissions, &signers, &committee_lookback)))
+    }
+
+    /// Returns a summary of the current round of our primary.
+    pub fn round_summary(&self) -> Result<RoundSummary<N>> {
+        // Retrieve the current round.
+        let round = self.current_round();
+        // Retrieve the committee lookback for the round.
+        let committee_lookback = self.ledger.get_committee_lookback_for_round(round)?;
+        // Retrieve the authors of the certificates for the round.
+        let authors = self.storage.get_certificate_authors_for_round(round);
+        // Determine whether our primary has proposed a batch in the round.
+        let has_proposed = authors.contains(&self.gateway.signer().address())
+            || self.proposed_batch.read().as_ref().map_or(false, |proposal| proposal.round() == round);
+        // Return the round summary.
+        Ok(RoundSummary::new(
+            round,
+            self.is_synced(),
+            self.is_awaiting_committee_sync(),
+            has_proposed,
+            &authors,
+            &committee_lookback,
+        ))
+    }
+
+    /// Returns a snapshot of the certificates that are buffered until their missing dependencies arrive.
+    pub fn certificate_buffer_summary(&self) -> CertificateBufferSummary<N> {
+        self.certificate_buffer.summary(Instant::now())
+    }
+}
+
 impl<N: Network> Primary<N> {
     /// Proposes the batch for the current round.
     ///
@@ -278,6 +502,17 @@ impl<N: Network> Primary<N> {
     /// 3. Set the batch proposal in the primary.
     /// 4. Broadcast the batch header to all validators for signing.
     pub async fn propose_batch(&self) -> Result<()> {
+        // Ensure the primary participates in the BFT.
+        if !self.is_participating() {
+            debug!("Primary is not participating in the BFT, skipping the batch proposal");
+            return Ok(());
+        }
+        // Ensure the system clock is not skewed beyond the timestamp tolerance of the BFT.
+        if self.is_clock_skewed() {
+            debug!("Primary has a skewed system clock, skipping the batch proposal");
+            return Ok(());
+        }
+
         // This function isn't re-entrant.
         let mut lock_guard = self.propose_lock.lock().await;
 
@@ -322,9 +557,11 @@ impl<N: Network> Primary<N> {
         metrics::gauge(metrics::bft::PROPOSAL_ROUND, round as f64);
 
         // Ensure the primary has not proposed a batch for this round before.
-        if self.storage.contains_certificate_in_round_from(round, self.gateway.account().address()) {
+        if self.storage.contains_certificate_in_round_from(round, self.gateway.signer().address()) {
             // If a BFT sender was provided, attempt to advance the current round.
             if let Some(bft_sender) = self.bft_sender.get() {
+                // Record the attempt to advance the current round.
+                self.gateway.record_event(|| RecordedEvent::Timeout { round: self.current_round() });
                 match bft_sender.send_primary_round_to_bft(self.current_round()).await {
                     // 'is_ready' is true if the primary is ready to propose a batch for the next round.
                     Ok(true) => (), // continue,
@@ -340,6 +577,28 @@ impl<N: Network> Primary<N> {
             bail!("Primary is safely skipping {}", format!("(round {round} was already certified)").dimmed());
         }
 
+        // Ensure the ledger contains the block that defines the committee of the round.
+        let is_awaiting_committee_sync = self.is_committee_unsynced_for_round(round);
+        let was_awaiting_committee_sync =
+            self.is_awaiting_committee_sync.swap(is_awaiting_committee_sync, Ordering::Relaxed);
+        if was_awaiting_committee_sync != is_awaiting_committee_sync {
+            match is_awaiting_committee_sync {
+                true => {
+                    warn!(
+                        "Primary is awaiting committee sync for round {round} (the latest block is in round {})",
+                        self.ledger.latest_round()
+                    );
+                    // Prioritize block sync, to retrieve the block that defines the committee.
+                    self.sync.prioritize_block_sync();
+                }
+                false => info!("Primary has synced the committee for round {round}"),
+            }
+        }
+        if is_awaiting_committee_sync {
+            debug!("Primary is safely skipping a batch proposal {}", "(awaiting committee sync)".dimmed());
+            return Ok(());
+        }
+
         // Retrieve the committee to check against.
         let committee_lookback = self.ledger.get_committee_lookback_for_round(round)?;
         // Check if the primary is connected to enough validators to reach quorum threshold.
@@ -347,7 +606,7 @@ impl<N: Network> Primary<N> {
             // Retrieve the connected validator addresses.
             let mut connected_validators = self.gateway.connected_addresses();
             // Append the primary to the set.
-            connected_validators.insert(self.gateway.account().address());
+            connected_validators.insert(self.gateway.signer().address());
             // If quorum threshold is not reached, return early.
             if !committee_lookback.is_quorum_threshold_reached(&connected_validators) {
                 debug!(
@@ -393,6 +652,10 @@ impl<N: Network> Primary<N> {
         let num_transmissions_per_worker = BatchHeader::<N>::MAX_TRANSMISSIONS_PER_BATCH / self.num_workers() as usize;
         // Initialize the map of transmissions.
         let mut transmissions: IndexMap<_, _> = Default::default();
+        // Initialize the map of solutions beyond the limit of the batch, which are left for the next proposal.
+        let mut deferred_solutions: IndexMap<_, _> = Default::default();
+        // Initialize a tracker for the number of solutions.
+        let mut num_solutions = 0;
         // Initialize a tracker for the number of transactions.
         let mut num_transactions = 0;
         // Take the transmissions from the workers.
@@ -427,11 +690,19 @@ impl<N: Network> Primary<N> {
                     // Check the transmission is still valid.
                     match (id, transmission.clone()) {
                         (TransmissionID::Solution(solution_id), Transmission::Solution(solution)) => {
+                            // Check if the batch already references the maximum number of solutions.
+                            if num_solutions >= self.max_solutions_per_batch {
+                                trace!("Proposing - Deferring solution '{}' - The batch is full", fmt_id(solution_id));
+                                deferred_solutions.insert(id, transmission);
+                                continue 'inner;
+                            }
                             // Check if the solution is still valid.
                             if let Err(e) = self.ledger.check_solution_basic(solution_id, solution).await {
                                 trace!("Proposing - Skipping solution '{}' - {e}", fmt_id(solution_id));
                                 continue 'inner;
                             }
+                            // Increment the number of solutions.
+                            num_solutions += 1;
                         }
                         (TransmissionID::Transaction(transaction_id), Transmission::Transaction(transaction)) => {
                             // Check if the transaction is still valid.
@@ -454,15 +725,18 @@ impl<N: Network> Primary<N> {
                 }
             }
         }
-        // If there are no unconfirmed transmissions to propose, return early.
-        if transmissions.is_empty() {
-            debug!("Primary is safely skipping a batch proposal {}", "(no unconfirmed transmissions)".dimmed());
-            return Ok(());
-        }
-        // If there are no unconfirmed transactions to propose, return early.
+        // Reinsert the deferred solutions back into the ready queue for the next proposal.
+        self.reinsert_transmissions_into_workers(deferred_solutions)?;
+        // If there are no unconfirmed transactions to propose, delay the batch proposal, per the empty batch mode.
         if num_transactions == 0 {
-            debug!("Primary is safely skipping a batch proposal {}", "(no unconfirmed transactions)".dimmed());
-            return Ok(());
+            let time_since_last_proposal = self.last_proposal.lock().elapsed();
+            let is_waived = self.is_empty_batch_delay_waived(round);
+            if !self.empty_batch_mode.is_empty_batch_ready(time_since_last_proposal, is_waived) {
+                debug!("Primary is safely skipping a batch proposal {}", "(no unconfirmed transactions)".dimmed());
+                // Reinsert the transmissions back into the ready queue for the next proposal.
+                self.reinsert_transmissions_into_workers(transmissions)?;
+                return Ok(());
+            }
         }
         // Ditto if the batch had already been proposed.
         ensure!(round > 0, "Round 0 cannot have transaction batches");
@@ -471,29 +745,44 @@ impl<N: Network> Primary<N> {
             return Ok(());
         }
 
-        *lock_guard = round;
-
         /* Proceeding to sign & propose the batch. */
         info!("Proposing a batch with {} transmissions for round {round}...", transmissions.len());
 
-        // Retrieve the private key.
-        let private_key = *self.gateway.account().private_key();
+        // Retrieve the signer.
+        let signer = self.gateway.signer();
         // Retrieve the committee ID.
         let committee_id = committee_lookback.id();
         // Prepare the transmission IDs.
-        let transmission_ids = transmissions.keys().copied().collect();
+        let transmission_ids: IndexSet<_> = transmissions.keys().copied().collect();
         // Prepare the previous batch certificate IDs.
-        let previous_certificate_ids = previous_certificates.into_iter().map(|c| c.id()).collect();
-        // Sign the batch header.
-        let batch_header = spawn_blocking!(BatchHeader::new(
-            &private_key,
+        let previous_certificate_ids: IndexSet<_> = previous_certificates.into_iter().map(|c| c.id()).collect();
+        // Compute the batch ID.
+        let (author, timestamp) = (signer.address(), self.storage.clock().now());
+        let batch_id = BatchHeader::compute_batch_id(
+            author,
             round,
-            now(),
+            timestamp,
+            committee_id,
+            &transmission_ids,
+            &previous_certificate_ids,
+        )?;
+        // Sign the batch ID.
+        // Note: If the signer fails, the round is not marked as proposed, so the primary may try again.
+        let signature = signer.sign(SigningContext::BatchProposal, vec![batch_id]).await?;
+        // Construct the batch header.
+        let batch_header = spawn_blocking!(BatchHeader::from(
+            author,
+            round,
+            timestamp,
             committee_id,
             transmission_ids,
             previous_certificate_ids,
-            &mut rand::thread_rng()
+            signature
         ))?;
+        // Mark the round as proposed.
+        *lock_guard = round;
+        // Update the time of the last proposal.
+        *self.last_proposal.lock() = Instant::now();
         // Construct the proposal.
         let proposal = Proposal::new(committee_lookback, batch_header.clone(), transmissions)?;
         // Broadcast the batch to all validators for signing.
@@ -516,7 +805,14 @@ impl<N: Network> Primary<N> {
         let BatchPropose { round: batch_round, batch_header } = batch_propose;
 
         // Deserialize the batch header.
-        let batch_header = spawn_blocking!(batch_header.deserialize_blocking())?;
+        let batch_header = match deserialize_data(batch_header).await {
+            Ok(batch_header) => batch_header,
+            Err(error) => {
+                // Proceed to disconnect the validator.
+                self.gateway.disconnect(peer_ip);
+                bail!("Malicious peer - failed to deserialize the batch header from '{peer_ip}' - {error}");
+            }
+        };
         // Ensure the round matches in the batch header.
         if batch_round != batch_header.round() {
             // Proceed to disconnect the validator.
@@ -546,10 +842,16 @@ impl<N: Network> Primary<N> {
             bail!("Malicious peer - proposed batch from a non-committee member ({batch_author})");
         }
         // Ensure the batch proposal is not from the current primary.
-        if self.gateway.account().address() == batch_author {
+        if self.gateway.signer().address() == batch_author {
             bail!("Invalid peer - proposed batch from myself ({batch_author})");
         }
 
+        // Ensure the ledger contains the block that defines the committee of the batch round, before signing.
+        // Note: Otherwise, the expected committee ID may be stale, and the peer would be mistaken for a malicious one.
+        if self.is_committee_unsynced_for_round(batch_round) {
+            bail!("Primary is awaiting committee sync for round {batch_round}, skipping the batch proposal from '{peer_ip}'");
+        }
+
         // Ensure that the batch proposal's committee ID matches the expected committee ID.
         let expected_committee_id = self.ledger.get_committee_lookback_for_round(batch_round)?.id();
         if expected_committee_id != batch_header.committee_id() {
@@ -560,6 +862,12 @@ impl<N: Network> Primary<N> {
                 batch_header.committee_id()
             );
         }
+        // Ensure the batch proposal does not reference too many solutions.
+        if let Err(e) = self.ensure_solutions_within_limit(&batch_header) {
+            // Proceed to disconnect the validator.
+            self.gateway.disconnect(peer_ip);
+            bail!("Malicious peer - {e}");
+        }
 
         // Retrieve the cached round and batch ID for this validator.
         if let Some((signed_round, signed_batch_id, signature)) =
@@ -596,10 +904,10 @@ impl<N: Network> Primary<N> {
         }
 
         // If the peer is ahead, use the batch header to sync up to the peer.
-        let mut transmissions = self.sync_with_batch_header_from_peer(peer_ip, &batch_header).await?;
+        let transmissions = self.sync_with_batch_header_from_peer(peer_ip, &batch_header).await?;
 
         // Check that the transmission ids match and are not fee transactions.
-        if let Err(err) = cfg_iter_mut!(transmissions).try_for_each(|(transmission_id, transmission)| {
+        if let Err(err) = cfg_iter!(transmissions).try_for_each(|(transmission_id, transmission)| {
             // If the transmission is not well-formed, then return early.
             self.ledger.ensure_transmission_is_well_formed(*transmission_id, transmission)
         }) {
@@ -618,7 +926,8 @@ impl<N: Network> Primary<N> {
 
         // Ensure the batch header from the peer is valid.
         let (storage, header) = (self.storage.clone(), batch_header.clone());
-        let missing_transmissions = spawn_blocking!(storage.check_batch_header(&header, transmissions))?;
+        let verification = move || storage.check_batch_header(&header, transmissions);
+        let missing_transmissions = verification_pool().run(VerificationCategory::Certificate, verification).await??;
         // Inserts the missing transmissions into the workers.
         self.insert_missing_transmissions_into_workers(peer_ip, missing_transmissions.into_iter())?;
 
@@ -627,8 +936,7 @@ impl<N: Network> Primary<N> {
         // Retrieve the batch ID.
         let batch_id = batch_header.batch_id();
         // Sign the batch ID.
-        let account = self.gateway.account().clone();
-        let signature = spawn_blocking!(account.sign(&[batch_id], &mut rand::thread_rng()))?;
+        let signature = self.gateway.signer().sign(SigningContext::BatchSignature, vec![batch_id]).await?;
 
         // Ensure the proposal has not already been signed.
         //
@@ -695,7 +1003,7 @@ impl<N: Network> Primary<N> {
             bail!("Malicious peer - batch signature is from a different validator ({signer})");
         }
         // Ensure the batch signature is not from the current primary.
-        if self.gateway.account().address() == signer {
+        if self.gateway.signer().address() == signer {
             bail!("Invalid peer - received a batch signature from myself ({signer})");
         }
 
@@ -755,7 +1063,7 @@ impl<N: Network> Primary<N> {
         // If there was an error storing the certificate, reinsert the transmissions back into the ready queue.
         if let Err(e) = self.store_and_broadcast_certificate(&proposal, &committee_lookback).await {
             // Reinsert the transmissions back into the ready queue for the next proposal.
-            self.reinsert_transmissions_into_workers(proposal)?;
+            self.reinsert_transmissions_into_workers(proposal.into_transmissions())?;
             return Err(e);
         }
 
@@ -784,6 +1092,19 @@ impl<N: Network> Primary<N> {
         let author = certificate.author();
         // Retrieve the batch certificate round.
         let certificate_round = certificate.round();
+
+        // Ensure storage does not already contain a different certificate from the author for the round.
+        let existing_certificate = self.storage.get_certificate_for_round_and_author(certificate_round, author);
+        if let Some(existing_certificate) = existing_certificate {
+            let equivocation = CertificateEquivocation {
+                round: certificate_round,
+                author,
+                existing_certificate_id: existing_certificate.id(),
+                certificate_id: certificate.id(),
+            };
+            warn!("Detected an equivocation (received from '{peer_ip}') - {equivocation}");
+            bail!(equivocation)
+        }
         // Retrieve the batch certificate committee ID.
         let committee_id = certificate.committee_id();
 
@@ -794,9 +1115,15 @@ impl<N: Network> Primary<N> {
             bail!("Malicious peer - Received a batch certificate from an unauthorized validator IP ({peer_ip})");
         }
         // Ensure the batch certificate is not from the current primary.
-        if self.gateway.account().address() == author {
+        if self.gateway.signer().address() == author {
             bail!("Received a batch certificate for myself ({author})");
         }
+        // Ensure the batch certificate does not reference too many solutions.
+        if let Err(e) = self.ensure_solutions_within_limit(certificate.batch_header()) {
+            // Proceed to disconnect the validator.
+            self.gateway.disconnect(peer_ip);
+            bail!("Malicious peer - {e}");
+        }
 
         // Store the certificate, after ensuring it is valid.
         self.sync_with_certificate_from_peer(peer_ip, certificate).await?;
@@ -852,6 +1179,7 @@ impl<N: Network> Primary<N> {
             mut rx_batch_certified,
             mut rx_primary_ping,
             mut rx_unconfirmed_solution,
+            mut rx_priority_solution,
             mut rx_unconfirmed_transaction,
         } = primary_receiver;
 
@@ -874,35 +1202,10 @@ impl<N: Network> Primary<N> {
                     };
 
                     // Retrieve the latest certificate of the primary.
-                    let primary_certificate = {
-                        // Retrieve the primary address.
-                        let primary_address = self_.gateway.account().address();
-
-                        // Iterate backwards from the latest round to find the primary certificate.
-                        let mut certificate = None;
-                        let mut current_round = self_.current_round();
-                        while certificate.is_none() {
-                            // If the current round is 0, then break the while loop.
-                            if current_round == 0 {
-                                break;
-                            }
-                            // Retrieve the certificates.
-                            let certificates = self_.storage.get_certificates_for_round(current_round);
-                            // Retrieve the primary certificate.
-                            certificate =
-                                certificates.into_iter().find(|certificate| certificate.author() == primary_address);
-                            // If the primary certificate was not found, decrement the round.
-                            if certificate.is_none() {
-                                current_round = current_round.saturating_sub(1);
-                            }
-                        }
-
-                        // Determine if the primary certificate was found.
-                        match certificate {
-                            Some(certificate) => certificate,
-                            // Skip this iteration of the loop (do not send a primary ping).
-                            None => continue,
-                        }
+                    let primary_certificate = match self_.latest_certificate_of_primary() {
+                        Some(certificate) => certificate,
+                        // Skip this iteration of the loop (do not send a primary ping).
+                        None => continue,
                     };
 
                     // Construct the primary ping.
@@ -913,6 +1216,34 @@ impl<N: Network> Primary<N> {
             });
         }
 
+        // Start the check of the buffered certificates.
+        let self_ = self.clone();
+        self.spawn(async move {
+            loop {
+                // Sleep briefly.
+                tokio::time::sleep(Duration::from_millis(PENDING_CERTIFICATE_CHECK_INTERVAL_IN_MS)).await;
+                // Discard the expired buffered certificates, and store those whose dependencies arrived.
+                self_.check_buffered_certificates().await;
+            }
+        });
+
+        // Start the stall detector.
+        if self.sync.is_gateway_mode() && self.stall_detector.config().enabled {
+            let self_ = self.clone();
+            self.spawn(async move {
+                loop {
+                    // Sleep briefly.
+                    tokio::time::sleep(Duration::from_millis(STALL_CHECK_INTERVAL_IN_MS)).await;
+                    // If the primary is not synced, then do not check for a stall.
+                    if !self_.sync.is_synced() {
+                        continue;
+                    }
+                    // Check for a stall, and take the next recovery action, if any.
+                    self_.check_for_stall().await;
+                }
+            });
+        }
+
         // Start the primary ping handler.
         let self_ = self.clone();
         self.spawn(async move {
@@ -928,11 +1259,23 @@ impl<N: Network> Primary<N> {
                     let self_ = self_.clone();
                     tokio::spawn(async move {
                         // Deserialize the primary certificate in the primary ping.
-                        let Ok(primary_certificate) = spawn_blocking!(primary_certificate.deserialize_blocking())
-                        else {
-                            warn!("Failed to deserialize primary certificate in 'PrimaryPing' from '{peer_ip}'");
-                            return;
+                        let primary_certificate = match deserialize_data(primary_certificate).await {
+                            Ok(primary_certificate) => primary_certificate,
+                            Err(e) => {
+                                warn!(
+                                    "Failed to deserialize primary certificate in 'PrimaryPing' from '{peer_ip}' - {e}"
+                                );
+                                // Proceed to disconnect the validator.
+                                self_.gateway.disconnect(peer_ip);
+                                return;
+                            }
                         };
+                        // Record the primary certificate.
+                        self_.record_event_from_peer(peer_ip, |address| RecordedEvent::BatchCertified {
+                            peer_ip,
+                            address,
+                            certificate: primary_certificate.clone(),
+                        });
                         // Process the primary certificate.
                         if let Err(e) = self_.process_batch_certificate_from_peer(peer_ip, primary_certificate).await {
                             warn!("Cannot process a primary certificate in a 'PrimaryPing' from '{peer_ip}' - {e}");
@@ -996,6 +1339,12 @@ impl<N: Network> Primary<N> {
                     trace!("Skipping a batch proposal from '{peer_ip}' {}", "(node is syncing)".dimmed());
                     continue;
                 }
+                // Record the batch proposal.
+                self_.record_event_from_peer(peer_ip, |address| RecordedEvent::BatchPropose {
+                    peer_ip,
+                    address,
+                    batch_propose: batch_propose.clone(),
+                });
                 // Spawn a task to process the proposed batch.
                 let self_ = self_.clone();
                 tokio::spawn(async move {
@@ -1016,6 +1365,12 @@ impl<N: Network> Primary<N> {
                     trace!("Skipping a batch signature from '{peer_ip}' {}", "(node is syncing)".dimmed());
                     continue;
                 }
+                // Record the batch signature.
+                self_.record_event_from_peer(peer_ip, |address| RecordedEvent::BatchSignature {
+                    peer_ip,
+                    address,
+                    batch_signature: batch_signature.clone(),
+                });
                 // Process the batch signature.
                 // Note: Do NOT spawn a task around this function call. Processing signatures from peers
                 // is a critical path, and we should only store the minimum required number of signatures.
@@ -1040,10 +1395,21 @@ impl<N: Network> Primary<N> {
                 let self_ = self_.clone();
                 tokio::spawn(async move {
                     // Deserialize the batch certificate.
-                    let Ok(batch_certificate) = spawn_blocking!(batch_certificate.deserialize_blocking()) else {
-                        warn!("Failed to deserialize the batch certificate from '{peer_ip}'");
-                        return;
+                    let batch_certificate = match deserialize_data(batch_certificate).await {
+                        Ok(batch_certificate) => batch_certificate,
+                        Err(e) => {
+                            warn!("Failed to deserialize the batch certificate from '{peer_ip}' - {e}");
+                            // Proceed to disconnect the validator.
+                            self_.gateway.disconnect(peer_ip);
+                            return;
+                        }
                     };
+                    // Record the batch certificate.
+                    self_.record_event_from_peer(peer_ip, |address| RecordedEvent::BatchCertified {
+                        peer_ip,
+                        address,
+                        certificate: batch_certificate.clone(),
+                    });
                     // Process the batch certificate.
                     if let Err(e) = self_.process_batch_certificate_from_peer(peer_ip, batch_certificate).await {
                         warn!("Cannot store a certificate from '{peer_ip}' - {e}");
@@ -1061,6 +1427,11 @@ impl<N: Network> Primary<N> {
                     error!("Unable to determine the worker ID for the unconfirmed solution");
                     continue;
                 };
+                // Record the unconfirmed solution.
+                self_.gateway.record_event(|| RecordedEvent::Transmission {
+                    transmission_id: TransmissionID::Solution(solution_id),
+                    transmission: Transmission::Solution(solution.clone()),
+                });
                 let self_ = self_.clone();
                 tokio::spawn(async move {
                     // Retrieve the worker.
@@ -1073,6 +1444,25 @@ impl<N: Network> Primary<N> {
             }
         });
 
+        // Process the consensus-critical solutions.
+        let self_ = self.clone();
+        self.spawn(async move {
+            while let Some((solution_id, solution, callback)) = rx_priority_solution.recv().await {
+                // Record the consensus-critical solution.
+                self_.gateway.record_event(|| RecordedEvent::Transmission {
+                    transmission_id: TransmissionID::Solution(solution_id),
+                    transmission: Transmission::Solution(solution.clone()),
+                });
+                let self_ = self_.clone();
+                tokio::spawn(async move {
+                    // Process the consensus-critical solution.
+                    let result = self_.process_priority_solution(solution_id, solution).await;
+                    // Send the result to the callback.
+                    callback.send(result).ok();
+                });
+            }
+        });
+
         // Process the unconfirmed transactions.
         let self_ = self.clone();
         self.spawn(async move {
@@ -1083,6 +1473,11 @@ impl<N: Network> Primary<N> {
                     error!("Unable to determine the worker ID for the unconfirmed transaction");
                     continue;
                 };
+                // Record the unconfirmed transaction.
+                self_.gateway.record_event(|| RecordedEvent::Transmission {
+                    transmission_id: TransmissionID::Transaction(transaction_id),
+                    transmission: Transmission::Transaction(transaction.clone()),
+                });
                 let self_ = self_.clone();
                 tokio::spawn(async move {
                     // Retrieve the worker.
@@ -1096,6 +1491,24 @@ impl<N: Network> Primary<N> {
         });
     }
 
+    /// Adds the given consensus-critical solution to the priority lane of its worker,
+    /// so that it is proposed ahead of the ordinary transmissions, even if the memory pool is full.
+    /// Returns an error if the quota of the priority lane for solutions is reached.
+    async fn process_priority_solution(&self, solution_id: SolutionID<N>, solution: Data<Solution<N>>) -> Result<()> {
+        let transmission_id = TransmissionID::Solution(solution_id);
+        // Ensure the quota of the priority lane is not reached.
+        let quota = self.priority_lane.quota(&transmission_id);
+        let num_priority_solutions =
+            self.workers.iter().map(|worker| worker.num_priority_transmissions(&transmission_id)).sum::<usize>();
+        if num_priority_solutions >= quota {
+            bail!("The priority lane is full ({num_priority_solutions}/{quota} solutions)")
+        }
+        // Compute the worker ID.
+        let worker_id = assign_to_worker(solution_id, self.num_workers())?;
+        // Process the consensus-critical solution.
+        self.workers[worker_id as usize].process_priority_solution(solution_id, solution).await
+    }
+
     /// Checks if the proposed batch is expired, and clears the proposed batch if it has expired.
     async fn check_proposed_batch_for_expiration(&self) -> Result<()> {
         // Check if the proposed batch is timed out or stale.
@@ -1108,7 +1521,7 @@ impl<N: Network> Primary<N> {
             // Reset the proposed batch.
             let proposal = self.proposed_batch.write().take();
             if let Some(proposal) = proposal {
-                self.reinsert_transmissions_into_workers(proposal)?;
+                self.reinsert_transmissions_into_workers(proposal.into_transmissions())?;
             }
         }
         Ok(())
@@ -1196,6 +1609,11 @@ impl<N: Network> Primary<N> {
         // Convert the transmissions into a HashMap.
         // Note: Do not change the `Proposal` to use a HashMap. The ordering there is necessary for safety.
         let transmissions = transmissions.into_iter().collect::<HashMap<_, _>>();
+        // Record the certified batch.
+        self.gateway.record_event(|| RecordedEvent::Certified {
+            certificate: certificate.clone(),
+            transmissions: transmissions.iter().map(|(id, transmission)| (*id, transmission.clone())).collect(),
+        });
         // Store the certified batch.
         let (storage, certificate_) = (self.storage.clone(), certificate.clone());
         spawn_blocking!(storage.insert_certificate(certificate_, transmissions))?;
@@ -1230,16 +1648,32 @@ impl<N: Network> Primary<N> {
         })
     }
 
-    /// Re-inserts the transmissions from the proposal into the workers.
-    fn reinsert_transmissions_into_workers(&self, proposal: Proposal<N>) -> Result<()> {
+    /// Re-inserts the given transmissions into the workers.
+    fn reinsert_transmissions_into_workers(
+        &self,
+        transmissions: IndexMap<TransmissionID<N>, Transmission<N>>,
+    ) -> Result<()> {
         // Re-insert the transmissions into the workers.
-        assign_to_workers(
-            &self.workers,
-            proposal.into_transmissions().into_iter(),
-            |worker, transmission_id, transmission| {
-                worker.reinsert(transmission_id, transmission);
-            },
-        )
+        assign_to_workers(&self.workers, transmissions.into_iter(), |worker, transmission_id, transmission| {
+            worker.reinsert(transmission_id, transmission);
+        })
+    }
+
+    /// Returns `true` if the delay for a batch without transactions does not apply for the given round.
+    /// This is the case if other validators have already certified batches for the round, so that the round
+    /// reaches quorum, or if the recent rounds contain transmissions, so that they are committed without delay.
+    fn is_empty_batch_delay_waived(&self, round: u64) -> bool {
+        // Check if other validators have already certified batches for the round.
+        if !self.storage.get_certificates_for_round(round).is_empty() {
+            return true;
+        }
+        // Check if the certificates of the recent rounds contain transmissions.
+        (round.saturating_sub(2)..round).any(|round| {
+            self.storage
+                .get_certificates_for_round(round)
+                .iter()
+                .any(|certificate| !certificate.transmission_ids().is_empty())
+        })
     }
 
     /// Recursively stores a given batch certificate, after ensuring:
@@ -1270,28 +1704,211 @@ impl<N: Network> Primary<N> {
         if self.storage.contains_certificate(certificate.id()) {
             return Ok(());
         }
+        // If the certificate is buffered, wait for its missing dependencies to arrive.
+        if self.certificate_buffer.contains(&certificate.id()) {
+            return Ok(());
+        }
 
         // If the peer is ahead, use the batch header to sync up to the peer.
-        let missing_transmissions = self.sync_with_batch_header_from_peer(peer_ip, batch_header).await?;
+        let missing_transmissions = match self.sync_with_batch_header_from_peer(peer_ip, batch_header).await {
+            Ok(missing_transmissions) => missing_transmissions,
+            // If the missing dependencies could not be fetched in time, buffer the certificate until they arrive.
+            Err(error) => match self.buffer_certificate(peer_ip, certificate, Default::default()) {
+                true => return Ok(()),
+                false => return Err(error),
+            },
+        };
 
         // Check if the certificate needs to be stored.
         if !self.storage.contains_certificate(certificate.id()) {
+            // If a previous certificate is still missing, e.g. because it is buffered, buffer the certificate as well.
+            if !self.missing_dependencies(certificate.batch_header(), &missing_transmissions).is_empty() {
+                match self.buffer_certificate(peer_ip, certificate, missing_transmissions) {
+                    true => return Ok(()),
+                    false => bail!("Missing dependencies for the certificate in round {batch_round} from '{peer_ip}'"),
+                }
+            }
             // Store the batch certificate.
-            let (storage, certificate_) = (self.storage.clone(), certificate.clone());
-            spawn_blocking!(storage.insert_certificate(certificate_, missing_transmissions))?;
-            debug!("Stored a batch certificate for round {batch_round} from '{peer_ip}'");
-            // If a BFT sender was provided, send the round and certificate to the BFT.
-            if let Some(bft_sender) = self.bft_sender.get() {
-                // Send the certificate to the BFT.
-                if let Err(e) = bft_sender.send_primary_certificate_to_bft(certificate).await {
-                    warn!("Failed to update the BFT DAG from sync: {e}");
-                    return Err(e);
-                };
+            let certificate_id = certificate.id();
+            self.store_certificate_from_peer(peer_ip, certificate, missing_transmissions).await?;
+            // Store the buffered certificates that were waiting on the certificate.
+            self.release_buffered_certificates(self.certificate_buffer.resolve_certificate(certificate_id)).await;
+        }
+        Ok(())
+    }
+
+    /// Stores the given certificate from the given peer, after ensuring it is valid, and sends it to the BFT.
+    async fn store_certificate_from_peer(
+        &self,
+        peer_ip: SocketAddr,
+        certificate: BatchCertificate<N>,
+        missing_transmissions: HashMap<TransmissionID<N>, Transmission<N>>,
+    ) -> Result<()> {
+        // Retrieve the batch round.
+        let batch_round = certificate.round();
+        // Store the batch certificate.
+        let (storage, certificate_) = (self.storage.clone(), certificate.clone());
+        let verification = move || storage.insert_certificate(certificate_, missing_transmissions);
+        let result = verification_pool().run(VerificationCategory::Certificate, verification).await;
+        if let Err(error) = result.and_then(|result| result) {
+            // If the author already has a different certificate for the round, report the equivocation.
+            if let Some(equivocation) = error.downcast_ref::<CertificateEquivocation<N>>() {
+                warn!("Detected an equivocation (received from '{peer_ip}') - {equivocation}");
             }
+            return Err(error);
+        }
+        debug!("Stored a batch certificate for round {batch_round} from '{peer_ip}'");
+        // If a BFT sender was provided, send the round and certificate to the BFT.
+        if let Some(bft_sender) = self.bft_sender.get() {
+            // Send the certificate to the BFT.
+            if let Err(e) = bft_sender.send_primary_certificate_to_bft(certificate).await {
+                warn!("Failed to update the BFT DAG from sync: {e}");
+                return Err(e);
+            };
         }
         Ok(())
     }
 
+    /// Returns the previous certificates and transmissions of the given batch header that are missing from storage,
+    /// excluding the given fetched transmissions.
+    fn missing_dependencies(
+        &self,
+        batch_header: &BatchHeader<N>,
+        transmissions: &HashMap<TransmissionID<N>, Transmission<N>>,
+    ) -> IndexSet<Dependency<N>> {
+        let mut missing = IndexSet::new();
+        // Note: As in storage, the previous certificates are only required above the GC round.
+        if batch_header.round().saturating_sub(1) > self.storage.gc_round() {
+            missing.extend(
+                batch_header
+                    .previous_certificate_ids()
+                    .iter()
+                    .filter(|certificate_id| !self.storage.contains_certificate(**certificate_id))
+                    .map(|certificate_id| Dependency::Certificate(*certificate_id)),
+            );
+        }
+        missing.extend(
+            batch_header
+                .transmission_ids()
+                .iter()
+                .filter(|transmission_id| {
+                    !self.storage.contains_transmission(**transmission_id)
+                        && !transmissions.contains_key(*transmission_id)
+                })
+                .map(|transmission_id| Dependency::Transmission(*transmission_id)),
+        );
+        missing
+    }
+
+    /// Buffers the given certificate from the given peer, if it is missing previous certificates or transmissions,
+    /// and fetches them from the peer. Returns `true` if the certificate was buffered.
+    fn buffer_certificate(
+        &self,
+        peer_ip: SocketAddr,
+        certificate: BatchCertificate<N>,
+        transmissions: HashMap<TransmissionID<N>, Transmission<N>>,
+    ) -> bool {
+        let (certificate_id, round) = (certificate.id(), certificate.round());
+        // Ensure the round is within the range of the GC rounds, so that the number of buffered rounds is bounded.
+        if round > self.current_round() + self.storage.max_gc_rounds() {
+            return false;
+        }
+        // Buffer the certificate, under its missing dependencies.
+        let missing = self.missing_dependencies(certificate.batch_header(), &transmissions);
+        if !self.certificate_buffer.insert(peer_ip, certificate, transmissions, missing.clone(), Instant::now()) {
+            return false;
+        }
+        debug!(
+            "Buffered certificate {} for round {round} from '{peer_ip}', until its {} missing dependencies arrive",
+            fmt_id(certificate_id),
+            missing.len()
+        );
+        // Fetch the missing dependencies.
+        self.fetch_buffered_dependencies(peer_ip, round, missing);
+        true
+    }
+
+    /// Fetches the given missing dependencies of a buffered certificate for the given round from the given peer,
+    /// and stores the buffered certificates once their dependencies arrive.
+    fn fetch_buffered_dependencies(&self, peer_ip: SocketAddr, round: u64, missing: IndexSet<Dependency<N>>) {
+        for dependency in missing {
+            let self_ = self.clone();
+            self.spawn(async move {
+                match dependency {
+                    Dependency::Certificate(certificate_id) => {
+                        // Note: Storing the previous certificate stores the buffered certificates waiting on it.
+                        let result = match self_.sync.send_certificate_request(peer_ip, certificate_id).await {
+                            Ok(certificate) => self_.sync_with_certificate_from_peer(peer_ip, certificate).await,
+                            Err(error) => Err(error),
+                        };
+                        if let Err(e) = result {
+                            debug!(
+                                "Failed to fetch certificate {} for round {round} from '{peer_ip}' - {e}",
+                                fmt_id(certificate_id)
+                            );
+                        }
+                    }
+                    Dependency::Transmission(transmission_id) => {
+                        let result = async {
+                            let worker_id = assign_to_worker(transmission_id, self_.num_workers())?;
+                            let Some(worker) = self_.workers.get(worker_id as usize) else {
+                                bail!("Unable to find worker {worker_id}")
+                            };
+                            worker.get_or_fetch_transmission(peer_ip, transmission_id).await
+                        };
+                        match result.await {
+                            Ok((transmission_id, transmission)) => {
+                                let ready =
+                                    self_.certificate_buffer.resolve_transmission(transmission_id, transmission);
+                                self_.release_buffered_certificates(ready).await;
+                            }
+                            Err(e) => debug!(
+                                "Failed to fetch transmission {} for round {round} from '{peer_ip}' - {e}",
+                                fmt_id(transmission_id)
+                            ),
+                        }
+                    }
+                }
+            });
+        }
+    }
+
+    /// Stores the given buffered certificates, whose dependencies arrived,
+    /// along with the buffered certificates that were waiting on them in turn.
+    async fn release_buffered_certificates(&self, mut ready: Vec<PendingCertificate<N>>) {
+        while let Some(PendingCertificate { peer_ip, certificate, transmissions, .. }) = ready.pop() {
+            let (certificate_id, round) = (certificate.id(), certificate.round());
+            // Skip the certificates that are outdated.
+            if round <= self.storage.gc_round() {
+                continue;
+            }
+            // Store the certificate, unless it was stored in the meantime.
+            if !self.storage.contains_certificate(certificate_id) {
+                if let Err(e) = self.store_certificate_from_peer(peer_ip, certificate, transmissions).await {
+                    warn!("Failed to store buffered certificate {} for round {round} - {e}", fmt_id(certificate_id));
+                    continue;
+                }
+            }
+            // Store the buffered certificates that were waiting on the certificate.
+            ready.extend(self.certificate_buffer.resolve_certificate(certificate_id));
+        }
+    }
+
+    /// Discards the expired buffered certificates, and stores those whose dependencies arrived through another path.
+    async fn check_buffered_certificates(&self) {
+        // Discard the buffered certificates that expired, or whose round was garbage collected.
+        let num_expired = self.certificate_buffer.expire(self.storage.gc_round(), Instant::now());
+        if num_expired > 0 {
+            debug!("Discarded {num_expired} buffered certificates, whose dependencies did not arrive in time");
+        }
+        // Store the buffered certificates whose dependencies are now in storage, e.g. after syncing.
+        let ready = self.certificate_buffer.resolve_if(|dependency| match dependency {
+            Dependency::Certificate(certificate_id) => self.storage.contains_certificate(*certificate_id),
+            Dependency::Transmission(transmission_id) => self.storage.contains_transmission(*transmission_id),
+        });
+        self.release_buffered_certificates(ready).await;
+    }
+
     /// Recursively syncs using the given batch header.
     async fn sync_with_batch_header_from_peer(
         &self,
@@ -1305,6 +1922,8 @@ impl<N: Network> Primary<N> {
         if batch_round <= self.storage.gc_round() {
             bail!("Round {batch_round} is too far in the past")
         }
+        // Ensure the batch header does not reference too many solutions, prior to fetching any transmissions.
+        self.ensure_solutions_within_limit(batch_header)?;
 
         // Determine if quorum threshold is reached on the batch round.
         let is_quorum_threshold_reached = {
@@ -1346,6 +1965,20 @@ impl<N: Network> Primary<N> {
         Ok(missing_transmissions)
     }
 
+    /// Ensures the given batch header does not reference more than the maximum number of solutions.
+    fn ensure_solutions_within_limit(&self, batch_header: &BatchHeader<N>) -> Result<()> {
+        // Retrieve the number of solutions referenced by the batch header.
+        let num_solutions =
+            batch_header.transmission_ids().iter().filter(|id| matches!(id, TransmissionID::Solution(..))).count();
+        ensure!(
+            num_solutions <= self.max_solutions_per_batch,
+            "Batch in round {} references too many solutions ({num_solutions} > {})",
+            batch_header.round(),
+            self.max_solutions_per_batch
+        );
+        Ok(())
+    }
+
     /// Fetches any missing transmissions for the specified batch header.
     /// If a transmission does not exist, it will be fetched from the specified peer IP.
     async fn fetch_missing_transmissions(
@@ -1426,6 +2059,26 @@ impl<N: Network> Primary<N> {
         Ok(missing_previous_certificates)
     }
 
+    /// Records the given event from the given peer, if an event recorder is set.
+    fn record_event_from_peer(&self, peer_ip: SocketAddr, event: impl FnOnce(Address<N>) -> RecordedEvent<N>) {
+        if let Some(address) = self.gateway.resolver().get_address(peer_ip) {
+            self.gateway.record_event(|| event(address));
+        }
+    }
+
+    /// Returns the latest certificate authored by this primary, if one exists in storage.
+    fn latest_certificate_of_primary(&self) -> Option<BatchCertificate<N>> {
+        // Retrieve the primary address.
+        let primary_address = self.gateway.signer().address();
+        // Iterate backwards from the current round to find the primary certificate.
+        (1..=self.current_round()).rev().find_map(|round| {
+            self.storage
+                .get_certificates_for_round(round)
+                .into_iter()
+                .find(|certificate| certificate.author() == primary_address)
+        })
+    }
+
     /// Fetches any missing certificates for the specified batch header from the specified peer.
     async fn fetch_missing_certificates(
         &self,
@@ -1472,32 +2125,238 @@ impl<N: Network> Primary<N> {
 }
 
 impl<N: Network> Primary<N> {
-    /// Spawns a task with the given future; it should only be used for long-running tasks.
-    fn spawn<T: Future<Output = ()> + Send + 'static>(&self, future: T) {
-        self.handles.lock().push(tokio::spawn(future));
-    }
-
-    /// Shuts down the primary.
-    pub async fn shut_down(&self) {
-        info!("Shutting down the primary...");
-        // Shut down the workers.
-        self.workers.iter().for_each(|worker| worker.shut_down());
-        // Abort the tasks.
-        self.handles.lock().iter().for_each(|handle| handle.abort());
-        // Close the gateway.
-        self.gateway.shut_down().await;
+    /// Checks whether the current round has stalled, and takes the next recovery action, if any.
+    async fn check_for_stall(&self) {
+        // Retrieve the current round, and the number of certificates for the round.
+        let round = self.current_round();
+        let num_certificates = self.storage.get_certificate_authors_for_round(round).len();
+        // Determine the next recovery action, if the round has stalled.
+        if let Some(action) = self.stall_detector.observe(round, num_certificates, Instant::now()) {
+            debug!("Round {round} has stalled, taking recovery action '{action:?}'");
+            if let Err(e) = self.execute_recovery_action(action, round) {
+                warn!("Failed to take recovery action '{action:?}' for round {round} - {e}");
+            }
+        }
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use snarkos_node_bft_ledger_service::MockLedgerService;
-    use snarkos_node_bft_storage_service::BFTMemoryService;
-    use snarkvm::{
-        ledger::committee::{Committee, MIN_VALIDATOR_STAKE},
-        prelude::{Address, Signature},
-    };
+
+    /// Executes the given recovery action for the given stalled round.
+    fn execute_recovery_action(&self, action: RecoveryAction, round: u64) -> Result<()> {
+        match action {
+            RecoveryAction::Rebroadcast => {
+                // Re-broadcast the latest certificate of the primary.
+                if let Some(certificate) = self.latest_certificate_of_primary() {
+                    self.gateway.broadcast(Event::BatchCertified(certificate.into()));
+                }
+                // Re-broadcast the batch proposal, if there is one.
+                if let Some(batch_header) =
+                    self.proposed_batch.read().as_ref().map(|proposal| proposal.batch_header().clone())
+                {
+                    self.gateway.broadcast(Event::BatchPropose(batch_header.into()));
+                }
+            }
+            RecoveryAction::RequestCertificates => {
+                // Retrieve the previous round.
+                let previous_round = round.saturating_sub(1);
+                // Collect the previous certificate IDs referenced by the current round and the batch proposal.
+                let mut certificate_ids = self
+                    .storage
+                    .get_certificates_for_round(round)
+                    .iter()
+                    .flat_map(|certificate| certificate.previous_certificate_ids().clone())
+                    .collect::<IndexSet<_>>();
+                if let Some(proposal) = self.proposed_batch.read().as_ref() {
+                    certificate_ids.extend(proposal.batch_header().previous_certificate_ids());
+                }
+                // Retain the certificate IDs that are missing from storage.
+                certificate_ids.retain(|certificate_id| !self.storage.contains_certificate(*certificate_id));
+                if certificate_ids.is_empty() {
+                    debug!("No missing certificates to request for round {previous_round}");
+                    return Ok(());
+                }
+                // Request the missing certificates from the connected committee members.
+                let committee = self.ledger.get_committee_lookback_for_round(round)?;
+                for peer_ip in self.gateway.connected_peers().read().iter().copied() {
+                    // Ensure the peer is a committee member.
+                    match self.gateway.resolver().get_address(peer_ip) {
+                        Some(address) if committee.is_committee_member(address) => (),
+                        _ => continue,
+                    }
+                    let (self_, certificate_ids) = (self.clone(), certificate_ids.clone());
+                    tokio::spawn(async move {
+                        // Fetch the missing certificates from the peer.
+                        let certificates =
+                            match self_.fetch_missing_certificates(peer_ip, previous_round, &certificate_ids).await {
+                                Ok(certificates) => certificates,
+                                Err(e) => {
+                                    debug!("Failed to fetch certificates from '{peer_ip}' - {e}");
+                                    return;
+                                }
+                            };
+                        // Store the missing certificates.
+                        for certificate in certificates {
+                            if let Err(e) = self_.sync_with_certificate_from_peer(peer_ip, certificate).await {
+                                debug!(
+                                    "Failed to store a certificate for round {previous_round} from '{peer_ip}' - {e}"
+                                );
+                            }
+                        }
+                    });
+                }
+            }
+            RecoveryAction::Redial => self.gateway.redial_validators(),
+            RecoveryAction::ReportStall => {
+                // Diagnose the stall.
+                let diagnosis = diagnose_stall(
+                    &self.ledger.get_committee_lookback_for_round(round)?,
+                    self.gateway.signer().address(),
+                    &self.gateway.connected_addresses(),
+                    round,
+                    &self.storage.get_certificate_authors_for_round(round),
+                    self.num_unconfirmed_transmissions(),
+                );
+                let stalled_for_secs = self.stall_detector.time_since_progress(Instant::now()).as_secs();
+                // Log the stall.
+                warn!(
+                    round,
+                    stalled_for_secs,
+                    %diagnosis,
+                    "STALLED - Round {round} has not advanced in {stalled_for_secs}s ({diagnosis})"
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<N: Network> Primary<N> {
+    /// Prepares the primary to replay a recorded event log, without running the gateway or the workers.
+    pub(crate) fn prepare_replay(&mut self, bft_sender: BFTSender<N>) {
+        // Set the BFT sender in the primary.
+        self.bft_sender.set(bft_sender).expect("BFT sender already set");
+        // Ensure the primary does not propose batches of its own, as these were recorded as certified batches.
+        self.set_participating(false);
+    }
+
+    /// Replays the given recorded event, using the pool for the certificates and transmissions it depends on.
+    ///
+    /// Batch proposals are not signed, and batch signatures are not processed, as the replay does not
+    /// hold the key of the validator. Instead, the certified batches of the validator are replayed as recorded.
+    pub(crate) async fn replay_event(&self, event: RecordedEvent<N>, pool: &ReplayPool<N>) -> Result<()> {
+        match event {
+            RecordedEvent::BatchPropose { batch_propose, .. } => {
+                // Deserialize the batch header.
+                let batch_header = deserialize_data(batch_propose.batch_header).await?;
+                // Store the previous certificates that the proposal depends on.
+                self.replay_previous_certificates(&batch_header, pool).await
+            }
+            RecordedEvent::BatchCertified { certificate, .. } | RecordedEvent::Certificate(certificate) => {
+                // Retrieve the certificate round.
+                let certificate_round = certificate.round();
+                // Store the certificate.
+                self.replay_certificate(certificate, pool).await?;
+                // Retrieve the certificate authors for the certificate round.
+                let authors = self.storage.get_certificate_authors_for_round(certificate_round);
+                // Check if the certificates have reached the quorum threshold.
+                let is_quorum = self
+                    .ledger
+                    .get_committee_lookback_for_round(certificate_round)?
+                    .is_quorum_threshold_reached(&authors);
+                // If quorum is reached for the current round, then proceed to the next round.
+                let current_round = self.current_round();
+                if is_quorum && certificate_round >= current_round {
+                    self.try_increment_to_the_next_round(current_round + 1).await?;
+                }
+                Ok(())
+            }
+            RecordedEvent::Certified { certificate, .. } => {
+                // Retrieve the certificate round.
+                let certificate_round = certificate.round();
+                // Store the certificate.
+                self.replay_certificate(certificate, pool).await?;
+                // Increment to the next round.
+                self.try_increment_to_the_next_round(certificate_round + 1).await
+            }
+            RecordedEvent::Timeout { .. } => {
+                // Attempt to advance the current round.
+                if let Some(bft_sender) = self.bft_sender.get() {
+                    bft_sender.send_primary_round_to_bft(self.current_round()).await?;
+                }
+                Ok(())
+            }
+            RecordedEvent::BatchSignature { .. }
+            | RecordedEvent::Transmission { .. }
+            | RecordedEvent::Committed { .. } => Ok(()),
+        }
+    }
+
+    /// Stores the given certificate, after storing any missing previous certificates from the pool.
+    #[async_recursion::async_recursion]
+    async fn replay_certificate(&self, certificate: BatchCertificate<N>, pool: &ReplayPool<N>) -> Result<()> {
+        // If the certificate is outdated, or already exists in storage, return early.
+        if certificate.round() <= self.storage.gc_round() || self.storage.contains_certificate(certificate.id()) {
+            return Ok(());
+        }
+        // Store the previous certificates.
+        self.replay_previous_certificates(certificate.batch_header(), pool).await?;
+        // Retrieve the missing transmissions from the pool.
+        let transmissions = certificate
+            .transmission_ids()
+            .iter()
+            .filter(|transmission_id| !self.storage.contains_transmission(**transmission_id))
+            .filter_map(|transmission_id| Some((*transmission_id, pool.get_transmission(transmission_id)?)))
+            .collect::<HashMap<_, _>>();
+        // Store the certificate.
+        let (storage, certificate_) = (self.storage.clone(), certificate.clone());
+        spawn_blocking!(storage.insert_certificate(certificate_, transmissions))?;
+        // If a BFT sender was provided, send the certificate to the BFT.
+        if let Some(bft_sender) = self.bft_sender.get() {
+            bft_sender.send_primary_certificate_to_bft(certificate).await?;
+        }
+        Ok(())
+    }
+
+    /// Stores the missing previous certificates of the given batch header from the pool.
+    async fn replay_previous_certificates(&self, batch_header: &BatchHeader<N>, pool: &ReplayPool<N>) -> Result<()> {
+        for certificate_id in batch_header.previous_certificate_ids() {
+            // Note: A certificate that is not in the pool is reported by storage, when inserting its child.
+            if let Some(certificate) = pool.get_certificate(certificate_id) {
+                self.replay_certificate(certificate, pool).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<N: Network> Primary<N> {
+    /// Spawns a task with the given future; it should only be used for long-running tasks.
+    fn spawn<T: Future<Output = ()> + Send + 'static>(&self, future: T) {
+        self.handles.lock().push(tokio::spawn(future));
+    }
+
+    /// Shuts down the primary.
+    pub async fn shut_down(&self) {
+        info!("Shutting down the primary...");
+        // Shut down the workers.
+        self.workers.iter().for_each(|worker| worker.shut_down());
+        // Abort the tasks.
+        self.handles.lock().iter().for_each(|handle| handle.abort());
+        // Close the gateway.
+        self.gateway.shut_down().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::now;
+    use snarkos_account::Account;
+    use snarkos_node_bft_ledger_service::MockLedgerService;
+    use snarkos_node_bft_storage_service::BFTMemoryService;
+    use snarkos_node_tcp::P2P;
+    use snarkvm::{
+        ledger::committee::{Committee, MIN_VALIDATOR_STAKE},
+        prelude::{Address, Signature},
+    };
 
     use bytes::Bytes;
     use indexmap::IndexSet;
@@ -1509,6 +2368,15 @@ mod tests {
     async fn primary_without_handlers(
         rng: &mut TestRng,
     ) -> (Primary<CurrentNetwork>, Vec<(SocketAddr, Account<CurrentNetwork>)>) {
+        let (primary, _, accounts) = primary_and_ledger_without_handlers(rng).await;
+        (primary, accounts)
+    }
+
+    // Returns a primary, its mock ledger, and a list of accounts in the configured committee.
+    async fn primary_and_ledger_without_handlers(
+        rng: &mut TestRng,
+    ) -> (Primary<CurrentNetwork>, Arc<MockLedgerService<CurrentNetwork>>, Vec<(SocketAddr, Account<CurrentNetwork>)>)
+    {
         // Create a committee containing the primary's account.
         let (accounts, committee) = {
             const COMMITTEE_SIZE: usize = 4;
@@ -1530,7 +2398,7 @@ mod tests {
         let storage = Storage::new(ledger.clone(), Arc::new(BFTMemoryService::new()), 10);
 
         // Initialize the primary.
-        let mut primary = Primary::new(account, storage, ledger, None, &[], None).unwrap();
+        let mut primary = Primary::new(account, storage, ledger.clone(), None, &[], None).unwrap();
 
         // Construct a worker instance.
         primary.workers = Arc::from([Worker::new(
@@ -1545,7 +2413,7 @@ mod tests {
             primary.gateway.insert_connected_peer(a.0, a.0, a.1.address());
         }
 
-        (primary, accounts)
+        (primary, ledger, accounts)
     }
 
     // Creates a mock solution.
@@ -1624,7 +2492,7 @@ mod tests {
         // Each committee member signs the batch.
         let mut signatures = Vec::with_capacity(accounts.len() - 1);
         for (socket_addr, account) in accounts {
-            if account.address() == primary.gateway.account().address() {
+            if account.address() == primary.gateway.signer().address() {
                 continue;
             }
             let batch_id = primary.proposed_batch.read().as_ref().unwrap().batch_id();
@@ -1731,6 +2599,33 @@ mod tests {
         }
     }
 
+    // Connects the primary to a listener standing in for the given peer, and returns the (ambiguous) peer address.
+    async fn connect_to_peer(
+        primary: &Primary<CurrentNetwork>,
+        peer_ip: SocketAddr,
+        address: Address<CurrentNetwork>,
+    ) -> SocketAddr {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let peer_addr = listener.local_addr().unwrap();
+        // Keep the accepted connection open.
+        tokio::spawn(async move {
+            let _stream = listener.accept().await;
+            std::future::pending::<()>().await
+        });
+        primary.gateway.tcp().connect(peer_addr).await.unwrap();
+        primary.gateway.resolver().insert_peer(peer_ip, peer_addr, address);
+        peer_addr
+    }
+
+    // Waits until the primary is disconnected from the given (ambiguous) peer address.
+    async fn wait_for_disconnect(primary: &Primary<CurrentNetwork>, peer_addr: SocketAddr) {
+        let start = Instant::now();
+        while primary.gateway.tcp().is_connected(peer_addr) {
+            assert!(start.elapsed() < Duration::from_secs(10), "The peer was not disconnected");
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
     #[tokio::test]
     async fn test_propose_batch() {
         let mut rng = TestRng::default();
@@ -1757,6 +2652,241 @@ mod tests {
         assert!(primary.proposed_batch.read().is_some());
     }
 
+    #[tokio::test]
+    async fn test_propose_batch_when_not_participating() {
+        let mut rng = TestRng::default();
+        let (primary, _) = primary_without_handlers(&mut rng).await;
+        assert!(primary.is_participating());
+
+        // Generate a solution and a transaction.
+        let (solution_id, solution) = sample_unconfirmed_solution(&mut rng);
+        let (transaction_id, transaction) = sample_unconfirmed_transaction(&mut rng);
+
+        // Store it on one of the workers.
+        primary.workers[0].process_unconfirmed_solution(solution_id, solution).await.unwrap();
+        primary.workers[0].process_unconfirmed_transaction(transaction_id, transaction).await.unwrap();
+
+        // Ensure the primary does not propose a batch while it is not participating.
+        primary.set_participating(false);
+        assert!(primary.propose_batch().await.is_ok());
+        assert!(primary.proposed_batch.read().is_none());
+
+        // Ensure the primary proposes a batch once it participates again.
+        primary.set_participating(true);
+        assert!(primary.propose_batch().await.is_ok());
+        assert!(primary.proposed_batch.read().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_propose_batch_when_clock_is_skewed() {
+        let mut rng = TestRng::default();
+        let (primary, _) = primary_without_handlers(&mut rng).await;
+        assert!(!primary.is_clock_skewed());
+
+        // Generate a solution and a transaction.
+        let (solution_id, solution) = sample_unconfirmed_solution(&mut rng);
+        let (transaction_id, transaction) = sample_unconfirmed_transaction(&mut rng);
+
+        // Store it on one of the workers.
+        primary.workers[0].process_unconfirmed_solution(solution_id, solution).await.unwrap();
+        primary.workers[0].process_unconfirmed_transaction(transaction_id, transaction).await.unwrap();
+
+        // Ensure the primary does not propose a batch while its clock is skewed.
+        primary.set_clock_skewed(true);
+        assert!(primary.propose_batch().await.is_ok());
+        assert!(primary.proposed_batch.read().is_none());
+
+        // Ensure the primary proposes a batch once its clock is back in range.
+        primary.set_clock_skewed(false);
+        assert!(primary.propose_batch().await.is_ok());
+        assert!(primary.proposed_batch.read().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_propose_batch_with_priority_solutions() {
+        let mut rng = TestRng::default();
+        let (primary, _) = primary_without_handlers(&mut rng).await;
+        let primary = primary.with_priority_lane(PriorityLaneConfig { max_solutions: 2, max_ratifications: 0 });
+
+        // Fill the memory pool to capacity with transactions.
+        for _ in 0..Primary::<CurrentNetwork>::MAX_TRANSMISSIONS_TOLERANCE {
+            let (transaction_id, transaction) = sample_unconfirmed_transaction(&mut rng);
+            primary.workers[0].process_unconfirmed_transaction(transaction_id, transaction).await.unwrap();
+        }
+        assert_eq!(primary.num_unconfirmed_transmissions(), Primary::<CurrentNetwork>::MAX_TRANSMISSIONS_TOLERANCE);
+
+        // Ensure the consensus-critical solutions within the quota are accepted.
+        let (solution_id_1, solution_1) = sample_unconfirmed_solution(&mut rng);
+        let (solution_id_2, solution_2) = sample_unconfirmed_solution(&mut rng);
+        assert!(primary.process_priority_solution(solution_id_1, solution_1).await.is_ok());
+        assert!(primary.process_priority_solution(solution_id_2, solution_2).await.is_ok());
+        // Ensure a flood of consensus-critical solutions beyond the quota is not accepted.
+        for _ in 0..10 {
+            let (solution_id, solution) = sample_unconfirmed_solution(&mut rng);
+            assert!(primary.process_priority_solution(solution_id, solution).await.is_err());
+        }
+        assert_eq!(primary.num_unconfirmed_solutions(), 2);
+
+        // Propose the batch.
+        assert!(primary.propose_batch().await.is_ok());
+
+        // Ensure the accepted solutions are proposed ahead of the transactions.
+        let proposed_transmissions = primary.proposed_batch.read().as_ref().unwrap().transmissions().clone();
+        let proposed_ids = proposed_transmissions.keys().take(2).copied().collect::<Vec<_>>();
+        assert_eq!(proposed_ids, vec![
+            TransmissionID::Solution(solution_id_1),
+            TransmissionID::Solution(solution_id_2)
+        ]);
+        // Ensure the priority lane has room again, once the solutions are proposed.
+        let (solution_id, solution) = sample_unconfirmed_solution(&mut rng);
+        assert!(primary.process_priority_solution(solution_id, solution).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_propose_empty_batch_after_delay() {
+        let mut rng = TestRng::default();
+        let (primary, _) = primary_without_handlers(&mut rng).await;
+        let delay = Duration::from_secs(10);
+        let primary = primary.with_empty_batch_mode(EmptyBatchMode::Delay { delay });
+
+        // Ensure the primary does not propose an empty batch before the delay has elapsed.
+        assert!(primary.propose_batch().await.is_ok());
+        assert!(primary.proposed_batch.read().is_none());
+
+        // Ensure the primary proposes an empty batch once the delay has elapsed.
+        *primary.last_proposal.lock() = Instant::now() - delay;
+        assert!(primary.propose_batch().await.is_ok());
+        let proposed_batch = primary.proposed_batch.read();
+        assert!(proposed_batch.as_ref().unwrap().batch_header().transmission_ids().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_propose_batch_during_empty_batch_delay() {
+        let mut rng = TestRng::default();
+        let (primary, _) = primary_without_handlers(&mut rng).await;
+        let delay = Duration::from_secs(10);
+        let primary = primary.with_empty_batch_mode(EmptyBatchMode::Delay { delay });
+
+        // Ensure the primary does not propose an empty batch before the delay has elapsed.
+        assert!(primary.propose_batch().await.is_ok());
+        assert!(primary.proposed_batch.read().is_none());
+
+        // Store a transaction on one of the workers.
+        let (transaction_id, transaction) = sample_unconfirmed_transaction(&mut rng);
+        primary.workers[0].process_unconfirmed_transaction(transaction_id, transaction).await.unwrap();
+
+        // Ensure the primary proposes the transaction right away.
+        assert!(primary.propose_batch().await.is_ok());
+        let proposed_batch = primary.proposed_batch.read();
+        let transmission_id = TransmissionID::Transaction(transaction_id);
+        assert!(proposed_batch.as_ref().unwrap().batch_header().transmission_ids().contains(&transmission_id));
+    }
+
+    #[tokio::test]
+    async fn test_propose_empty_batch_without_delay() {
+        let mut rng = TestRng::default();
+        let (primary, _) = primary_without_handlers(&mut rng).await;
+        let primary = primary.with_empty_batch_mode(EmptyBatchMode::Propose);
+
+        // Ensure the primary proposes an empty batch right away.
+        assert!(primary.propose_batch().await.is_ok());
+        let proposed_batch = primary.proposed_batch.read();
+        assert!(proposed_batch.as_ref().unwrap().batch_header().transmission_ids().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_stall_recovery_actions() {
+        let mut rng = TestRng::default();
+        let (primary, accounts) = primary_without_handlers(&mut rng).await;
+        // Consider the round stalled as soon as it stops making progress.
+        let primary = primary.with_stall_config(StallConfig {
+            enabled: true,
+            round_threshold: Duration::ZERO,
+            certificate_threshold: Duration::ZERO,
+            escalation_interval: Duration::ZERO,
+        });
+
+        // Store certificates up to the current round.
+        let round = 3;
+        store_certificate_chain(&primary, &accounts, round, &mut rng);
+        assert_eq!(primary.current_round(), round);
+
+        // Ensure the latest certificate of the primary is found.
+        let certificate = primary.latest_certificate_of_primary().unwrap();
+        assert_eq!(certificate.round(), round - 1);
+        assert_eq!(certificate.author(), accounts[0].1.address());
+
+        // Ensure the first check only records the round.
+        let now = Instant::now();
+        assert_eq!(primary.stall_detector.observe(round, 0, now), None);
+        assert!(!primary.stall_detector.is_stalled());
+
+        // Ensure every recovery action is taken, in order, while the round remains stalled.
+        for expected in RecoveryAction::ESCALATION {
+            let action = primary.stall_detector.observe(round, 0, now).unwrap();
+            assert_eq!(action, expected);
+            assert!(primary.execute_recovery_action(action, round).is_ok());
+        }
+        assert!(primary.stall_detector.is_stalled());
+    }
+
+    #[tokio::test]
+    async fn test_propose_batch_respects_solution_limit() {
+        let mut rng = TestRng::default();
+        let (mut primary, _) = primary_without_handlers(&mut rng).await;
+        // Lower the solution limit of the primary.
+        primary.max_solutions_per_batch = 1;
+
+        // Generate two solutions and a transaction.
+        let (solution_id_1, solution_1) = sample_unconfirmed_solution(&mut rng);
+        let (solution_id_2, solution_2) = sample_unconfirmed_solution(&mut rng);
+        let (transaction_id, transaction) = sample_unconfirmed_transaction(&mut rng);
+
+        // Store them on one of the workers.
+        primary.workers[0].process_unconfirmed_solution(solution_id_1, solution_1).await.unwrap();
+        primary.workers[0].process_unconfirmed_solution(solution_id_2, solution_2).await.unwrap();
+        primary.workers[0].process_unconfirmed_transaction(transaction_id, transaction).await.unwrap();
+
+        // Propose a batch, and check it does not exceed the limit.
+        assert!(primary.propose_batch().await.is_ok());
+        let proposed_batch = primary.proposed_batch.read();
+        let transmission_ids = proposed_batch.as_ref().unwrap().transmissions().keys().copied().collect::<Vec<_>>();
+        assert_eq!(transmission_ids, vec![solution_id_1.into(), (&transaction_id).into()]);
+        // Check that the remaining solution is left in the worker.
+        assert_eq!(primary.workers[0].num_transmissions(), 1);
+        assert!(primary.workers[0].contains_transmission(solution_id_2));
+    }
+
+    #[tokio::test]
+    async fn test_remove_unconfirmed_transaction() {
+        let mut rng = TestRng::default();
+        let (primary, _) = primary_without_handlers(&mut rng).await;
+
+        // Generate a solution and two transactions.
+        let (solution_id, solution) = sample_unconfirmed_solution(&mut rng);
+        let (transaction_id_1, transaction_1) = sample_unconfirmed_transaction(&mut rng);
+        let (transaction_id_2, transaction_2) = sample_unconfirmed_transaction(&mut rng);
+
+        // Store the solution and the first transaction on one of the workers.
+        primary.workers[0].process_unconfirmed_solution(solution_id, solution).await.unwrap();
+        primary.workers[0].process_unconfirmed_transaction(transaction_id_1, transaction_1).await.unwrap();
+
+        // Propose a batch, which includes the first transaction.
+        assert!(primary.propose_batch().await.is_ok());
+        assert!(primary.is_transmission_in_flight(&transaction_id_1));
+        // Ensure the in-flight transaction cannot be removed.
+        assert!(primary.remove_unconfirmed_transaction(transaction_id_1).is_err());
+
+        // Store the second transaction on the worker.
+        primary.workers[0].process_unconfirmed_transaction(transaction_id_2, transaction_2).await.unwrap();
+        assert!(!primary.is_transmission_in_flight(&transaction_id_2));
+        // Ensure the pending transaction can be removed.
+        assert!(primary.remove_unconfirmed_transaction(transaction_id_2).unwrap());
+        assert_eq!(primary.num_unconfirmed_transactions(), 0);
+        // Ensure removing it again is a no-op.
+        assert!(!primary.remove_unconfirmed_transaction(transaction_id_2).unwrap());
+    }
+
     #[tokio::test]
     async fn test_propose_batch_in_round() {
         let round = 3;
@@ -1917,6 +3047,125 @@ mod tests {
         primary.process_batch_propose_from_peer(peer_ip, (*proposal.batch_header()).clone().into()).await.unwrap();
     }
 
+    #[tokio::test]
+    async fn test_propose_batch_awaiting_committee_sync() {
+        // The round, whose committee is defined in the round after the latest block of the ledger.
+        let round = 4 + Committee::<CurrentNetwork>::COMMITTEE_LOOKBACK_RANGE;
+        let mut rng = TestRng::default();
+        let (primary, ledger, accounts) = primary_and_ledger_without_handlers(&mut rng).await;
+
+        // Fill primary storage, with the ledger lagging one block behind the committee of the round.
+        store_certificate_chain(&primary, &accounts, round, &mut rng);
+        let committee_round = committee_lookback_round::<CurrentNetwork>(round);
+        ledger.advance_to_height(committee_round as u32 - 1);
+        assert_eq!(primary.current_round(), round);
+
+        // Store a solution and a transaction on one of the workers.
+        let (solution_id, solution) = sample_unconfirmed_solution(&mut rng);
+        let (transaction_id, transaction) = sample_unconfirmed_transaction(&mut rng);
+        primary.workers[0].process_unconfirmed_solution(solution_id, solution).await.unwrap();
+        primary.workers[0].process_unconfirmed_transaction(transaction_id, transaction).await.unwrap();
+
+        // Ensure the primary does not propose a batch under the stale committee.
+        assert!(primary.propose_batch().await.is_ok());
+        assert!(primary.proposed_batch.read().is_none());
+        assert!(primary.is_awaiting_committee_sync());
+        assert!(primary.round_summary().unwrap().is_awaiting_committee_sync);
+
+        // Advance the ledger to the block that defines the committee.
+        ledger.advance_to_height(committee_round as u32);
+
+        // Ensure the primary proposes a batch, once the committee is synced.
+        assert!(primary.propose_batch().await.is_ok());
+        assert!(primary.proposed_batch.read().is_some());
+        assert!(!primary.is_awaiting_committee_sync());
+    }
+
+    #[tokio::test]
+    async fn test_batch_propose_from_peer_awaiting_committee_sync() {
+        // The round, whose committee is defined in the round after the latest block of the ledger.
+        let round = 4 + Committee::<CurrentNetwork>::COMMITTEE_LOOKBACK_RANGE;
+        let mut rng = TestRng::default();
+        let (primary, ledger, accounts) = primary_and_ledger_without_handlers(&mut rng).await;
+
+        // Fill primary storage, with the ledger lagging one block behind the committee of the round.
+        let previous_certificates = store_certificate_chain(&primary, &accounts, round, &mut rng);
+        let committee_round = committee_lookback_round::<CurrentNetwork>(round);
+        ledger.advance_to_height(committee_round as u32 - 1);
+
+        // Create a valid proposal with an author that isn't the primary.
+        let peer_account = &accounts[1];
+        let peer_ip = peer_account.0;
+        let proposal = create_test_proposal(
+            &peer_account.1,
+            primary.ledger.current_committee().unwrap(),
+            round,
+            previous_certificates,
+            now(),
+            &mut rng,
+        );
+        for (transmission_id, transmission) in proposal.transmissions() {
+            primary.workers[0].process_transmission_from_peer(peer_ip, *transmission_id, transmission.clone())
+        }
+        primary.gateway.resolver().insert_peer(peer_ip, peer_ip, peer_account.1.address());
+
+        // Ensure the primary does not sign the batch under the stale committee, nor disconnect the peer.
+        let batch_propose = BatchPropose::from((*proposal.batch_header()).clone());
+        assert!(primary.process_batch_propose_from_peer(peer_ip, batch_propose.clone()).await.is_err());
+        assert!(primary.signed_proposals.read().get(&peer_account.1.address()).is_none());
+        assert!(primary.gateway.is_connected_ip(peer_ip));
+
+        // Advance the ledger to the block that defines the committee.
+        ledger.advance_to_height(committee_round as u32);
+
+        // Ensure the primary signs the batch, once the committee is synced.
+        primary.process_batch_propose_from_peer(peer_ip, batch_propose).await.unwrap();
+        assert!(primary.signed_proposals.read().get(&peer_account.1.address()).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_batch_propose_from_peer_over_solution_limit() {
+        let mut rng = TestRng::default();
+        let (mut primary, accounts) = primary_without_handlers(&mut rng).await;
+        // Lower the solution limit of the primary, such that the proposal is one over the limit.
+        primary.max_solutions_per_batch = 0;
+
+        // Create a proposal with a solution and a transaction, with an author that isn't the primary.
+        let round = 1;
+        let peer_account = &accounts[1];
+        let peer_ip = peer_account.0;
+        let timestamp = now();
+        let proposal = create_test_proposal(
+            &peer_account.1,
+            primary.ledger.current_committee().unwrap(),
+            round,
+            Default::default(),
+            timestamp,
+            &mut rng,
+        );
+
+        // The author must be known to resolver to pass propose checks.
+        let peer_addr = connect_to_peer(&primary, peer_ip, peer_account.1.address()).await;
+
+        // Try to process the batch proposal from the peer, should fail as a malicious proposal.
+        let error = primary
+            .process_batch_propose_from_peer(peer_ip, (*proposal.batch_header()).clone().into())
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(error.starts_with("Malicious peer"), "{error}");
+        assert!(error.contains("too many solutions (1 > 0)"), "{error}");
+
+        // Check that none of the (missing) transmissions were requested from the peer.
+        for transmission_id in proposal.batch_header().transmission_ids() {
+            assert!(!primary.workers[0].pending().contains(*transmission_id));
+        }
+        // Check that the proposal was not signed.
+        assert!(primary.signed_proposals.read().get(&peer_account.1.address()).is_none());
+        // Check that the peer was disconnected.
+        wait_for_disconnect(&primary, peer_addr).await;
+    }
+
     #[tokio::test]
     async fn test_batch_propose_from_peer_wrong_round() {
         let mut rng = TestRng::default();
@@ -2008,7 +3257,7 @@ mod tests {
         let round = 1;
         let timestamp = now();
         let proposal = create_test_proposal(
-            primary.gateway.account(),
+            &accounts[0].1,
             primary.ledger.current_committee().unwrap(),
             round,
             Default::default(),
@@ -2028,11 +3277,71 @@ mod tests {
         }
 
         // Check the certificate was created and stored by the primary.
-        assert!(primary.storage.contains_certificate_in_round_from(round, primary.gateway.account().address()));
+        assert!(primary.storage.contains_certificate_in_round_from(round, primary.gateway.signer().address()));
         // Check the round was incremented.
         assert_eq!(primary.current_round(), round + 1);
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_proposal_and_round_summary() {
+        let mut rng = TestRng::default();
+        let (primary, accounts) = primary_without_handlers(&mut rng).await;
+        map_account_addresses(&primary, &accounts);
+        let address = primary.gateway.signer().address();
+
+        // Check there is no proposal, and the round is missing every author.
+        let round = 1;
+        assert!(primary.proposal_summary().unwrap().is_none());
+        let summary = primary.round_summary().unwrap();
+        assert_eq!(summary.round, round);
+        assert!(!summary.is_awaiting_committee_sync);
+        assert!(!summary.has_proposed);
+        assert!(summary.authors.is_empty());
+        assert_eq!(summary.missing_authors.len(), accounts.len());
+
+        // Store a valid proposal on the primary.
+        let proposal = create_test_proposal(
+            &accounts[0].1,
+            primary.ledger.current_committee().unwrap(),
+            round,
+            Default::default(),
+            now(),
+            &mut rng,
+        );
+        *primary.proposed_batch.write() = Some(proposal);
+
+        // Check the proposal is only signed by our primary.
+        let summary = primary.proposal_summary().unwrap().unwrap();
+        assert_eq!(summary.round, round);
+        assert_eq!(summary.num_transmissions, 2);
+        assert_eq!(summary.signers, vec![address]);
+        assert_eq!(summary.pending.len(), accounts.len() - 1);
+        assert!(!summary.is_quorum_threshold_reached);
+        assert!(primary.round_summary().unwrap().has_proposed);
+
+        // Each committee member signs the batch.
+        let signatures = peer_signatures_for_proposal(&primary, &accounts, &mut rng);
+
+        // Process the first signature, and check the signer set grows.
+        let (socket_addr, signature) = signatures[0];
+        primary.process_batch_signature_from_peer(socket_addr, signature).await.unwrap();
+        let summary = primary.proposal_summary().unwrap().unwrap();
+        assert_eq!(summary.signers.len(), 2);
+        assert!(summary.signers.contains(&signature.to_address()));
+        assert_eq!(summary.pending.len(), accounts.len() - 2);
+
+        // Process the remaining signatures, which certifies the batch.
+        for (socket_addr, signature) in signatures.into_iter().skip(1) {
+            primary.process_batch_signature_from_peer(socket_addr, signature).await.unwrap();
+        }
+
+        // Check the proposal was certified, and the round summary includes our certificate.
+        assert!(primary.proposal_summary().unwrap().is_none());
+        let summary = primary.round_summary().unwrap();
+        assert_eq!(summary.round, round + 1);
+        assert!(primary.storage.get_certificate_authors_for_round(round).contains(&address));
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_batch_signature_from_peer_in_round() {
         let round = 5;
@@ -2046,7 +3355,7 @@ mod tests {
         // Create a valid proposal.
         let timestamp = now();
         let proposal = create_test_proposal(
-            primary.gateway.account(),
+            &accounts[0].1,
             primary.ledger.current_committee().unwrap(),
             round,
             previous_certificates,
@@ -2066,7 +3375,7 @@ mod tests {
         }
 
         // Check the certificate was created and stored by the primary.
-        assert!(primary.storage.contains_certificate_in_round_from(round, primary.gateway.account().address()));
+        assert!(primary.storage.contains_certificate_in_round_from(round, primary.gateway.signer().address()));
         // Check the round was incremented.
         assert_eq!(primary.current_round(), round + 1);
     }
@@ -2081,7 +3390,7 @@ mod tests {
         let round = 1;
         let timestamp = now();
         let proposal = create_test_proposal(
-            primary.gateway.account(),
+            &accounts[0].1,
             primary.ledger.current_committee().unwrap(),
             round,
             Default::default(),
@@ -2100,7 +3409,7 @@ mod tests {
         primary.process_batch_signature_from_peer(*socket_addr, *signature).await.unwrap();
 
         // Check the certificate was not created and stored by the primary.
-        assert!(!primary.storage.contains_certificate_in_round_from(round, primary.gateway.account().address()));
+        assert!(!primary.storage.contains_certificate_in_round_from(round, primary.gateway.signer().address()));
         // Check the round was incremented.
         assert_eq!(primary.current_round(), round);
     }
@@ -2118,7 +3427,7 @@ mod tests {
         // Create a valid proposal.
         let timestamp = now();
         let proposal = create_test_proposal(
-            primary.gateway.account(),
+            &accounts[0].1,
             primary.ledger.current_committee().unwrap(),
             round,
             previous_certificates,
@@ -2137,8 +3446,137 @@ mod tests {
         primary.process_batch_signature_from_peer(*socket_addr, *signature).await.unwrap();
 
         // Check the certificate was not created and stored by the primary.
-        assert!(!primary.storage.contains_certificate_in_round_from(round, primary.gateway.account().address()));
+        assert!(!primary.storage.contains_certificate_in_round_from(round, primary.gateway.signer().address()));
         // Check the round was incremented.
         assert_eq!(primary.current_round(), round);
     }
+
+    #[tokio::test]
+    async fn test_insert_certificate_beyond_aggressive_gc() {
+        let mut rng = TestRng::default();
+        let (primary, accounts) = primary_without_handlers(&mut rng).await;
+
+        // Initialize a storage that garbage collects every round but the latest.
+        let storage = Storage::new(primary.ledger.clone(), Arc::new(BFTMemoryService::new()), 1);
+
+        let mut previous_certificates = IndexSet::<Field<CurrentNetwork>>::new();
+        for round in 1..=4 {
+            // Ensure each certificate validates against the committee from the ledger,
+            // even though the certificates of the previous round have been garbage collected.
+            let mut next_certificates = IndexSet::<Field<CurrentNetwork>>::new();
+            for (_, account) in accounts.iter() {
+                let (certificate, transmissions) = create_batch_certificate(
+                    account.address(),
+                    &accounts,
+                    round,
+                    previous_certificates.clone(),
+                    &mut rng,
+                );
+                next_certificates.insert(certificate.id());
+                storage.insert_certificate(certificate, transmissions).unwrap();
+            }
+            previous_certificates = next_certificates;
+
+            // Advance to the next round, and garbage collect the current round.
+            assert_eq!(storage.increment_to_next_round(round).unwrap(), round + 1);
+            storage.garbage_collect_certificates(round + 1);
+            assert_eq!(storage.gc_round(), round);
+            assert!(!storage.contains_certificates_for_round(round));
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_certificate_before_previous_certificates_is_buffered() {
+        let round = 5;
+        let mut rng = TestRng::default();
+        let (primary, accounts) = primary_without_handlers(&mut rng).await;
+        map_account_addresses(&primary, &accounts);
+        let peer_ip = accounts[1].0;
+
+        // Store the certificates up to round 3.
+        let previous_certificate_ids = store_certificate_chain(&primary, &accounts, round - 1, &mut rng);
+
+        // Create the certificates for round 4, and a certificate for round 5 on top of them.
+        // Note: The transmissions are in the ready queue of the worker, so that only the certificates are missing.
+        let mut parents = Vec::with_capacity(accounts.len());
+        for (_, account) in accounts.iter() {
+            let (certificate, transmissions) = create_batch_certificate(
+                account.address(),
+                &accounts,
+                round - 1,
+                previous_certificate_ids.clone(),
+                &mut rng,
+            );
+            for (transmission_id, transmission) in transmissions {
+                assert!(primary.workers[0].reinsert(transmission_id, transmission));
+            }
+            parents.push(certificate);
+        }
+        let parent_ids = parents.iter().map(BatchCertificate::id).collect::<IndexSet<_>>();
+        let (certificate, transmissions) =
+            create_batch_certificate(accounts[1].1.address(), &accounts, round, parent_ids, &mut rng);
+        for (transmission_id, transmission) in transmissions {
+            assert!(primary.workers[0].reinsert(transmission_id, transmission));
+        }
+
+        // Deliver the certificate for round 5 before its previous certificates, which cannot be fetched.
+        primary.sync_with_certificate_from_peer(peer_ip, certificate.clone()).await.unwrap();
+        assert!(!primary.storage.contains_certificate(certificate.id()));
+        // Check the certificate is buffered, until its previous certificates arrive.
+        let summary = primary.certificate_buffer_summary();
+        assert_eq!(summary.certificates.len(), 1);
+        assert_eq!(summary.certificates[0].certificate_id, certificate.id());
+        assert_eq!(summary.certificates[0].missing_certificates.len(), accounts.len());
+
+        // Deliver the previous certificates.
+        for parent in parents {
+            primary.sync_with_certificate_from_peer(peer_ip, parent).await.unwrap();
+        }
+
+        // Check the certificate was stored once its previous certificates arrived, without requesting it again.
+        let start = Instant::now();
+        while !primary.storage.contains_certificate(certificate.id()) {
+            assert!(start.elapsed() < Duration::from_secs(10), "The buffered certificate was not stored");
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(!primary.sync.contains_pending_certificate(certificate.id()));
+        let summary = primary.certificate_buffer_summary();
+        assert!(summary.certificates.is_empty());
+        assert_eq!((summary.num_buffered, summary.num_released, summary.num_expired), (1, 1, 0));
+    }
+
+    #[tokio::test]
+    async fn test_certificate_response_from_peers() {
+        let mut rng = TestRng::default();
+        let (primary, accounts) = primary_without_handlers(&mut rng).await;
+
+        // Connect to the peers.
+        let mut peer_addrs = Vec::with_capacity(3);
+        for (peer_ip, account) in accounts.iter().skip(1).take(3) {
+            peer_addrs.push(connect_to_peer(&primary, *peer_ip, account.address()).await);
+        }
+        let (peer_ip_1, peer_ip_2, peer_ip_3) = (accounts[1].0, accounts[2].0, accounts[3].0);
+
+        // Request a certificate from the first and second peers.
+        let (certificate, _) =
+            create_batch_certificate(accounts[1].1.address(), &accounts, 1, Default::default(), &mut rng);
+        let certificate_id = certificate.id();
+        let mut callback_receiver_1 = primary.sync.insert_pending_certificate(peer_ip_1, certificate_id);
+        let mut callback_receiver_2 = primary.sync.insert_pending_certificate(peer_ip_2, certificate_id);
+
+        // Respond from the first peer, which resolves the requests sent to both peers.
+        primary.sync.finish_certificate_request(peer_ip_1, certificate.clone().into());
+        assert_eq!(callback_receiver_1.try_recv().unwrap(), certificate);
+        assert_eq!(callback_receiver_2.try_recv().unwrap(), certificate);
+        assert!(!primary.sync.contains_pending_certificate(certificate_id));
+
+        // Respond late from the second peer, and respond from the third peer, which was not asked.
+        primary.sync.finish_certificate_request(peer_ip_2, certificate.clone().into());
+        primary.sync.finish_certificate_request(peer_ip_3, certificate.into());
+
+        // Check that only the third peer is disconnected.
+        wait_for_disconnect(&primary, peer_addrs[2]).await;
+        assert!(primary.gateway.tcp().is_connected(peer_addrs[0]));
+        assert!(primary.gateway.tcp().is_connected(peer_addrs[1]));
+    }
 }
@@ -0,0 +1,240 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    helpers::{init_consensus_channels, Clock, EventLog, RecordedEvent, Signer, SigningContext, Storage},
+    BFT,
+};
+use snarkos_node_bft_ledger_service::LedgerService;
+use snarkos_node_bft_storage_service::BFTMemoryService;
+use snarkvm::{
+    console::account::{Address, Signature},
+    ledger::narwhal::{BatchCertificate, BatchHeader, Transmission, TransmissionID},
+    prelude::{bail, Field, Network, Result},
+};
+
+use parking_lot::Mutex;
+use std::{collections::HashMap, fmt, sync::Arc};
+
+/// A signer for replaying an event log, which holds the address of the recording validator, but not its key.
+/// Note: As the replay never signs, it does not depend on any randomness.
+#[derive(Clone, Debug)]
+pub struct ReplaySigner<N: Network> {
+    address: Address<N>,
+}
+
+impl<N: Network> ReplaySigner<N> {
+    /// Initializes a new replay signer for the given address.
+    pub const fn new(address: Address<N>) -> Self {
+        Self { address }
+    }
+}
+
+#[async_trait]
+impl<N: Network> Signer<N> for ReplaySigner<N> {
+    /// Returns the address of the recording validator.
+    fn address(&self) -> Address<N> {
+        self.address
+    }
+
+    /// Fails, as the replay does not hold the key of the recording validator.
+    async fn sign(&self, context: SigningContext, _message: Vec<Field<N>>) -> Result<Signature<N>> {
+        bail!("Unable to sign a {context:?} message during a replay")
+    }
+
+    /// Fails, as the replay does not hold the key of the recording validator.
+    async fn sign_bytes(&self, context: SigningContext, _message: Vec<u8>) -> Result<Signature<N>> {
+        bail!("Unable to sign a {context:?} message during a replay")
+    }
+}
+
+/// The certificates and transmissions of an event log, which are available to the replay.
+#[derive(Clone, Debug)]
+pub struct ReplayPool<N: Network> {
+    /// The certificates, by certificate ID.
+    certificates: HashMap<Field<N>, BatchCertificate<N>>,
+    /// The transmissions, by transmission ID.
+    transmissions: HashMap<TransmissionID<N>, Transmission<N>>,
+}
+
+impl<N: Network> ReplayPool<N> {
+    /// Initializes the pool from the certificates and transmissions in the given event log.
+    pub fn new(log: &EventLog<N>) -> Self {
+        let mut certificates = HashMap::new();
+        let mut transmissions = HashMap::new();
+        for record in &log.records {
+            match &record.event {
+                RecordedEvent::BatchCertified { certificate, .. } | RecordedEvent::Certificate(certificate) => {
+                    certificates.insert(certificate.id(), certificate.clone());
+                }
+                RecordedEvent::Transmission { transmission_id, transmission } => {
+                    transmissions.insert(*transmission_id, transmission.clone());
+                }
+                RecordedEvent::Certified { certificate, transmissions: certified_transmissions } => {
+                    certificates.insert(certificate.id(), certificate.clone());
+                    transmissions
+                        .extend(certified_transmissions.iter().map(|(id, transmission)| (*id, transmission.clone())));
+                }
+                _ => (),
+            }
+        }
+        Self { certificates, transmissions }
+    }
+
+    /// Returns the certificate for the given certificate ID, if it exists.
+    pub fn get_certificate(&self, certificate_id: &Field<N>) -> Option<BatchCertificate<N>> {
+        self.certificates.get(certificate_id).cloned()
+    }
+
+    /// Returns the transmission for the given transmission ID, if it exists.
+    pub fn get_transmission(&self, transmission_id: &TransmissionID<N>) -> Option<Transmission<N>> {
+        self.transmissions.get(transmission_id).cloned()
+    }
+}
+
+/// The outcome of replaying an event log.
+#[derive(Clone, Debug)]
+pub struct ReplayOutcome<N: Network> {
+    /// The number of replayed events.
+    pub num_events: usize,
+    /// The number of events that failed to replay.
+    pub num_failed_events: usize,
+    /// The leader certificates that were committed, as `(round, leader certificate ID)`, in order.
+    pub commits: Vec<(u64, Field<N>)>,
+}
+
+impl<N: Network> ReplayOutcome<N> {
+    /// Returns the first divergence between the recorded commits and the replayed commits, if any.
+    pub fn first_divergence(&self, recorded: &[(u64, Field<N>)]) -> Option<Divergence<N>> {
+        (0..recorded.len().max(self.commits.len())).find_map(|index| {
+            let (recorded, replayed) = (recorded.get(index).copied(), self.commits.get(index).copied());
+            (recorded != replayed).then_some(Divergence { index, recorded, replayed })
+        })
+    }
+}
+
+/// The first commit that differs between an event log and its replay.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence<N: Network> {
+    /// The index of the commit.
+    pub index: usize,
+    /// The recorded commit, if one exists.
+    pub recorded: Option<(u64, Field<N>)>,
+    /// The replayed commit, if one exists.
+    pub replayed: Option<(u64, Field<N>)>,
+}
+
+impl<N: Network> fmt::Display for Divergence<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let commit = |commit: Option<(u64, Field<N>)>| match commit {
+            Some((round, leader_certificate_id)) => {
+                format!("round {round} (leader certificate {leader_certificate_id})")
+            }
+            None => "nothing".to_string(),
+        };
+        write!(
+            f,
+            "commit {} was {} in the log, but {} in the replay",
+            self.index,
+            commit(self.recorded),
+            commit(self.replayed)
+        )
+    }
+}
+
+/// Replays the given event log through a fresh primary and storage, returning the commits of the BFT.
+///
+/// The clock of the storage is set to the timestamp of each record before it is replayed,
+/// so that the replay of an event log is deterministic. The given ledger must be at the genesis block of the log.
+pub async fn replay_event_log<N: Network>(
+    log: &EventLog<N>,
+    ledger: Arc<dyn LedgerService<N>>,
+) -> Result<ReplayOutcome<N>> {
+    // Initialize a manual clock, starting at the first recorded timestamp.
+    let clock = Clock::manual(log.records.first().map_or(0, |record| record.timestamp));
+    // Initialize the storage.
+    let transmissions = Arc::new(BFTMemoryService::new());
+    let storage =
+        Storage::new_with_clock(ledger.clone(), transmissions, BatchHeader::<N>::MAX_GC_ROUNDS as u64, clock.clone());
+    // Initialize the BFT, as the recording validator.
+    let mut bft = BFT::new(ReplaySigner::new(log.address), storage, ledger, None, &[], None)?;
+
+    // Initialize the consensus channels, in order to capture the commits.
+    let (consensus_sender, mut consensus_receiver) = init_consensus_channels::<N>();
+    let commits = Arc::new(Mutex::new(Vec::new()));
+    let commits_ = commits.clone();
+    let handle = tokio::spawn(async move {
+        while let Some((subdag, _, callback)) = consensus_receiver.rx_consensus_subdag.recv().await {
+            commits_.lock().push((subdag.anchor_round(), subdag.leader_certificate().id()));
+            callback.send(Ok(())).ok();
+        }
+    });
+    // Prepare the BFT to replay.
+    bft.prepare_replay(consensus_sender);
+
+    // Initialize the pool of certificates and transmissions.
+    let pool = ReplayPool::new(log);
+    // Replay the records, in order.
+    let mut num_failed_events = 0;
+    for record in &log.records {
+        // Set the clock to the recorded timestamp.
+        clock.set(record.timestamp)?;
+        // Replay the event.
+        let name = record.event.name();
+        if let Err(e) = bft.primary().replay_event(record.event.clone(), &pool).await {
+            debug!("Failed to replay a '{name}' event recorded at {} - {e}", record.timestamp);
+            num_failed_events += 1;
+        }
+    }
+
+    // Shut down the BFT.
+    bft.shut_down().await;
+    handle.abort();
+
+    let commits = commits.lock().clone();
+    Ok(ReplayOutcome { num_events: log.records.len(), num_failed_events, commits })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkvm::prelude::{TestRng, Uniform};
+
+    type CurrentNetwork = snarkvm::console::network::MainnetV0;
+
+    #[test]
+    fn test_first_divergence() {
+        let mut rng = TestRng::default();
+        let commits: Vec<(u64, Field<CurrentNetwork>)> = (1..=3).map(|i| (2 * i, Field::rand(&mut rng))).collect();
+        let outcome = |commits: &[(u64, Field<CurrentNetwork>)]| ReplayOutcome {
+            num_events: 0,
+            num_failed_events: 0,
+            commits: commits.to_vec(),
+        };
+
+        // Identical commits do not diverge.
+        assert_eq!(outcome(&commits).first_divergence(&commits), None);
+        // A different commit diverges at its index.
+        let mut other = commits.clone();
+        other[1].1 = Field::rand(&mut rng);
+        let divergence = outcome(&other).first_divergence(&commits).unwrap();
+        assert_eq!(divergence.index, 1);
+        assert_eq!(divergence.recorded, Some(commits[1]));
+        assert_eq!(divergence.replayed, Some(other[1]));
+        // A missing commit diverges at the end of the replay.
+        let divergence = outcome(&commits[..2]).first_divergence(&commits).unwrap();
+        assert_eq!(divergence.index, 2);
+        assert_eq!(divergence.replayed, None);
+    }
+}
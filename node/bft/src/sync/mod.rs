@@ -19,9 +19,14 @@ use crate::{
     MAX_FETCH_TIMEOUT_IN_MS,
     PRIMARY_PING_IN_MS,
 };
-use snarkos_node_bft_events::{CertificateRequest, CertificateResponse, Event};
+use snarkos_node_bft_events::{CertificateRequest, CertificateResponse, DisconnectReason, Event, Feature};
 use snarkos_node_bft_ledger_service::LedgerService;
-use snarkos_node_sync::{locators::BlockLocators, BlockSync, BlockSyncMode};
+use snarkos_node_sync::{
+    locators::{BlockLocators, MAXIMUM_BLOCKS_PER_REQUEST},
+    BlockSync,
+    BlockSyncMode,
+    VerificationPipeline,
+};
 use snarkvm::{
     console::{network::Network, types::Field},
     ledger::{authority::Authority, block::Block, narwhal::BatchCertificate},
@@ -33,7 +38,7 @@ use parking_lot::Mutex;
 use rayon::prelude::*;
 use std::{collections::HashMap, future::Future, net::SocketAddr, sync::Arc};
 use tokio::{
-    sync::{oneshot, Mutex as TMutex, OnceCell},
+    sync::{oneshot, Mutex as TMutex, Notify, OnceCell},
     task::JoinHandle,
 };
 
@@ -57,6 +62,8 @@ pub struct Sync<N: Network> {
     response_lock: Arc<TMutex<()>>,
     /// The sync lock.
     sync_lock: Arc<TMutex<()>>,
+    /// The notifier that wakes the block sync loop early, when block sync is prioritized.
+    block_sync_priority: Arc<Notify>,
 }
 
 impl<N: Network> Sync<N> {
@@ -75,6 +82,7 @@ impl<N: Network> Sync<N> {
             handles: Default::default(),
             response_lock: Default::default(),
             sync_lock: Default::default(),
+            block_sync_priority: Default::default(),
         }
     }
 
@@ -96,8 +104,11 @@ impl<N: Network> Sync<N> {
         let self_ = self.clone();
         self.handles.lock().push(tokio::spawn(async move {
             loop {
-                // Sleep briefly to avoid triggering spam detection.
-                tokio::time::sleep(std::time::Duration::from_millis(PRIMARY_PING_IN_MS)).await;
+                // Sleep briefly to avoid triggering spam detection, unless block sync was prioritized.
+                tokio::select! {
+                    _ = tokio::time::sleep(std::time::Duration::from_millis(PRIMARY_PING_IN_MS)) => (),
+                    _ = self_.block_sync_priority.notified() => (),
+                }
                 // Perform the sync routine.
                 let communication = &self_.gateway;
                 // let communication = &node.router;
@@ -156,6 +167,12 @@ impl<N: Network> Sync<N> {
             while let Some((peer_ip, locators, callback)) = rx_block_sync_update_peer_locators.recv().await {
                 let self_clone = self_.clone();
                 tokio::spawn(async move {
+                    // Limit the block requests to peers that only serve a single block per response.
+                    let limit = match self_clone.gateway.supports_feature(peer_ip, Feature::MultipleBlocksPerResponse) {
+                        true => MAXIMUM_BLOCKS_PER_REQUEST,
+                        false => 1,
+                    };
+                    self_clone.block_sync.set_block_request_limit(peer_ip, limit);
                     // Update the peer locators.
                     let result = self_clone.block_sync.update_peer_locators(peer_ip, locators);
                     // Send the result to the callback.
@@ -279,12 +296,7 @@ impl<N: Network> Sync<N> {
         // Determine if we can sync the ledger without updating the BFT first.
         if current_height <= max_gc_height {
             // Try to advance the ledger *to tip* without updating the BFT.
-            while let Some(block) = self.block_sync.process_next_block(current_height) {
-                info!("Syncing the ledger to block {}...", block.height());
-                self.sync_ledger_with_block_without_bft(block).await?;
-                // Update the current height.
-                current_height += 1;
-            }
+            current_height = self.sync_ledger_with_blocks_without_bft(current_height).await?;
             // Sync the storage with the ledger if we should transition to the BFT sync.
             if current_height > max_gc_height {
                 if let Err(e) = self.sync_storage_with_ledger_at_bootup().await {
@@ -304,24 +316,32 @@ impl<N: Network> Sync<N> {
         Ok(())
     }
 
-    /// Syncs the ledger with the given block without updating the BFT.
-    async fn sync_ledger_with_block_without_bft(&self, block: Block<N>) -> Result<()> {
+    /// Syncs the ledger with the sync blocks, starting at the given height, without updating the BFT.
+    /// The upcoming blocks are checked ahead of time, while each block is applied in order.
+    /// Returns the next block height to sync.
+    async fn sync_ledger_with_blocks_without_bft(&self, current_height: u32) -> Result<u32> {
         // Acquire the sync lock.
         let _lock = self.sync_lock.lock().await;
 
         let self_ = self.clone();
         tokio::task::spawn_blocking(move || {
-            // Check the next block.
-            self_.ledger.check_next_block(&block)?;
-            // Attempt to advance to the next block.
-            self_.ledger.advance_to_next_block(&block)?;
-
-            // Sync the height with the block.
-            self_.storage.sync_height_with_block(block.height());
-            // Sync the round with the block.
-            self_.storage.sync_round_with_block(block.round());
-
-            Ok(())
+            let mut pipeline =
+                VerificationPipeline::new(self_.ledger.clone(), self_.block_sync.verification_lookahead());
+            let mut next_height = current_height;
+            let next_block = || {
+                let block = self_.block_sync.process_next_block(next_height)?;
+                next_height += 1;
+                Some(block)
+            };
+            pipeline.advance_with(next_block, |block| {
+                info!("Synced the ledger to block {}", block.height());
+                // Sync the height with the block.
+                self_.storage.sync_height_with_block(block.height());
+                // Sync the round with the block.
+                self_.storage.sync_round_with_block(block.round());
+            })?;
+
+            Ok(self_.ledger.latest_block_height() + 1)
         })
         .await?
     }
@@ -379,6 +399,17 @@ impl<N: Network> Sync<N> {
         self.block_sync.is_block_synced()
     }
 
+    /// Wakes the block sync loop, to request blocks from peers without waiting for the next iteration.
+    /// Note: The primary prioritizes block sync when its ledger lacks the block that defines the committee of its round.
+    pub fn prioritize_block_sync(&self) {
+        self.block_sync_priority.notify_one();
+    }
+
+    /// Returns the block sync module.
+    pub const fn block_sync(&self) -> &BlockSync<N> {
+        &self.block_sync
+    }
+
     /// Returns `true` if the node is in gateway mode.
     pub const fn is_gateway_mode(&self) -> bool {
         self.block_sync.mode().is_gateway()
@@ -392,6 +423,24 @@ impl<N: Network> Sync<N> {
 
 // Methods to assist with fetching batch certificates from peers.
 impl<N: Network> Sync<N> {
+    #[cfg(test)]
+    /// Returns `true` if a request for the given certificate is pending.
+    pub(crate) fn contains_pending_certificate(&self, certificate_id: Field<N>) -> bool {
+        self.pending.contains(certificate_id)
+    }
+
+    #[cfg(test)]
+    /// Inserts a request for the given certificate as sent to the given peer, without sending it.
+    pub(crate) fn insert_pending_certificate(
+        &self,
+        peer_ip: SocketAddr,
+        certificate_id: Field<N>,
+    ) -> oneshot::Receiver<BatchCertificate<N>> {
+        let (callback_sender, callback_receiver) = oneshot::channel();
+        self.pending.insert(certificate_id, peer_ip, Some((callback_sender, true)));
+        callback_receiver
+    }
+
     /// Sends a certificate request to the specified peer.
     pub async fn send_certificate_request(
         &self,
@@ -445,16 +494,26 @@ impl<N: Network> Sync<N> {
     }
 
     /// Handles the incoming certificate response.
-    /// This method ensures the certificate response is well-formed and matches the certificate ID.
-    fn finish_certificate_request(&self, peer_ip: SocketAddr, response: CertificateResponse<N>) {
+    /// This method ensures the certificate response matches a certificate ID that was requested from the peer.
+    pub(crate) fn finish_certificate_request(&self, peer_ip: SocketAddr, response: CertificateResponse<N>) {
         let certificate = response.certificate;
-        // Check if the peer IP exists in the pending queue for the given certificate ID.
-        let exists = self.pending.get(certificate.id()).unwrap_or_default().contains(&peer_ip);
-        // If the peer IP exists, finish the pending request.
-        if exists {
-            // TODO: Validate the certificate.
-            // Remove the certificate ID from the pending queue.
-            self.pending.remove(certificate.id(), Some(certificate));
+        // Compute the certificate ID.
+        let certificate_id = certificate.id();
+        // Ensure the certificate was requested from this peer.
+        if !self.pending.is_requested(certificate_id, peer_ip) {
+            warn!("Disconnecting '{peer_ip}' for sending unrequested certificate {}", fmt_id(certificate_id));
+            let self_ = self.clone();
+            tokio::spawn(async move {
+                let _ = self_.gateway.send(peer_ip, DisconnectReason::ProtocolViolation.into()).await;
+                // Disconnect from this peer.
+                self_.gateway.disconnect(peer_ip);
+            });
+            return;
+        }
+        // Remove the certificate ID from the pending queue, notifying the callbacks of every peer that was asked.
+        // Note: A response to an answered request is skipped, as it may be late or duplicate rather than malicious.
+        if self.pending.remove(certificate_id, Some(certificate)).is_none() {
+            trace!("Skipping a late certificate response for {} from '{peer_ip}'", fmt_id(certificate_id));
         }
     }
 }
@@ -13,8 +13,30 @@
 // limitations under the License.
 
 use crate::{
-    events::{Event, TransmissionRequest, TransmissionResponse},
-    helpers::{fmt_id, max_redundant_requests, Pending, Ready, Storage, WorkerReceiver},
+    events::{
+        ensure_data_size,
+        Event,
+        Feature,
+        TransmissionChunkRequest,
+        TransmissionChunkResponse,
+        TransmissionRequest,
+        TransmissionResponse,
+    },
+    helpers::{
+        fmt_id,
+        max_redundant_requests,
+        ChunkProgress,
+        FetchLimits,
+        FetchPool,
+        FetchPools,
+        Pending,
+        Ready,
+        Storage,
+        TransmissionChunks,
+        TransmissionPriority,
+        WorkerReceiver,
+        CHUNK_REQUEST_TIMEOUT_IN_MS,
+    },
     spawn_blocking,
     ProposedBatch,
     Transport,
@@ -34,7 +56,11 @@ use snarkvm::{
 use indexmap::{IndexMap, IndexSet};
 use parking_lot::Mutex;
 use std::{future::Future, net::SocketAddr, sync::Arc, time::Duration};
-use tokio::{sync::oneshot, task::JoinHandle, time::timeout};
+use tokio::{
+    sync::oneshot,
+    task::JoinHandle,
+    time::{timeout_at, Instant},
+};
 
 #[derive(Clone)]
 pub struct Worker<N: Network> {
@@ -52,6 +78,10 @@ pub struct Worker<N: Network> {
     ready: Ready<N>,
     /// The pending transmissions queue.
     pending: Arc<Pending<TransmissionID<N>, Transmission<N>>>,
+    /// The worker pools for solution and transaction fetches.
+    fetch_pools: Arc<FetchPools>,
+    /// The transmissions that are transferred in chunks.
+    chunks: Arc<TransmissionChunks<N>>,
     /// The spawned handles.
     handles: Arc<Mutex<Vec<JoinHandle<()>>>>,
 }
@@ -76,10 +106,18 @@ impl<N: Network> Worker<N> {
             proposed_batch,
             ready: Default::default(),
             pending: Default::default(),
+            fetch_pools: Default::default(),
+            chunks: Default::default(),
             handles: Default::default(),
         })
     }
 
+    /// Sets the maximum number of in-flight solution and transaction fetches.
+    pub fn with_fetch_limits(mut self, limits: FetchLimits) -> Self {
+        self.fetch_pools = Arc::new(FetchPools::new(limits));
+        self
+    }
+
     /// Run the worker instance.
     pub fn run(&self, receiver: WorkerReceiver<N>) {
         info!("Starting worker instance {} of the memory pool...", self.id);
@@ -96,6 +134,11 @@ impl<N: Network> Worker<N> {
     pub fn pending(&self) -> &Arc<Pending<TransmissionID<N>, Transmission<N>>> {
         &self.pending
     }
+
+    /// Returns a reference to the fetch pools.
+    pub fn fetch_pools(&self) -> &Arc<FetchPools> {
+        &self.fetch_pools
+    }
 }
 
 impl<N: Network> Worker<N> {
@@ -112,6 +155,11 @@ impl<N: Network> Worker<N> {
         self.ready.num_transmissions()
     }
 
+    /// Returns the number of consensus-critical transmissions of the same kind as the given transmission ID.
+    pub fn num_priority_transmissions(&self, transmission_id: &TransmissionID<N>) -> usize {
+        self.ready.num_priority_transmissions(transmission_id)
+    }
+
     /// Returns the number of ratifications in the ready queue.
     pub fn num_ratifications(&self) -> usize {
         self.ready.num_ratifications()
@@ -129,6 +177,11 @@ impl<N: Network> Worker<N> {
 }
 
 impl<N: Network> Worker<N> {
+    /// Returns `true` if the given transmission is in the ready queue.
+    pub fn is_ready(&self, transmission_id: impl Into<TransmissionID<N>>) -> bool {
+        self.ready.contains(transmission_id)
+    }
+
     /// Returns the transmission IDs in the ready queue.
     pub fn transmission_ids(&self) -> IndexSet<TransmissionID<N>> {
         self.ready.transmission_ids()
@@ -216,6 +269,11 @@ impl<N: Network> Worker<N> {
         false
     }
 
+    /// Removes the specified transmission from the ready queue, returning `true` if it was removed.
+    pub(crate) fn remove(&self, transmission_id: impl Into<TransmissionID<N>>) -> bool {
+        self.ready.remove(transmission_id).is_some()
+    }
+
     /// Broadcasts a worker ping event.
     pub(crate) fn broadcast_ping(&self) {
         // Retrieve the transmission IDs.
@@ -305,6 +363,26 @@ impl<N: Network> Worker<N> {
         &self,
         solution_id: SolutionID<N>,
         solution: Data<Solution<N>>,
+    ) -> Result<()> {
+        self.process_solution_with_priority(solution_id, solution, TransmissionPriority::Ordinary).await
+    }
+
+    /// Handles the incoming consensus-critical solution, which is drained ahead of the other transmissions.
+    /// Note: The caller is responsible for enforcing the quota of the priority lane.
+    pub(crate) async fn process_priority_solution(
+        &self,
+        solution_id: SolutionID<N>,
+        solution: Data<Solution<N>>,
+    ) -> Result<()> {
+        self.process_solution_with_priority(solution_id, solution, TransmissionPriority::Critical).await
+    }
+
+    /// Handles the incoming solution, inserting it into the ready queue with the given priority.
+    async fn process_solution_with_priority(
+        &self,
+        solution_id: SolutionID<N>,
+        solution: Data<Solution<N>>,
+        priority: TransmissionPriority,
     ) -> Result<()> {
         // Construct the transmission.
         let transmission = Transmission::Solution(solution.clone());
@@ -317,8 +395,12 @@ impl<N: Network> Worker<N> {
         // Check that the solution is well-formed and unique.
         self.ledger.check_solution_basic(solution_id, solution).await?;
         // Adds the solution to the ready queue.
-        if self.ready.insert(solution_id, transmission) {
-            trace!("Worker {} - Added unconfirmed solution '{}'", self.id, fmt_id(solution_id));
+        let is_new = match priority {
+            TransmissionPriority::Critical => self.ready.insert_priority(solution_id, transmission),
+            TransmissionPriority::Ordinary => self.ready.insert(solution_id, transmission),
+        };
+        if is_new {
+            trace!("Worker {} - Added unconfirmed solution '{}' ({priority:?})", self.id, fmt_id(solution_id));
         }
         Ok(())
     }
@@ -350,7 +432,13 @@ impl<N: Network> Worker<N> {
 impl<N: Network> Worker<N> {
     /// Starts the worker handlers.
     fn start_handlers(&self, receiver: WorkerReceiver<N>) {
-        let WorkerReceiver { mut rx_worker_ping, mut rx_transmission_request, mut rx_transmission_response } = receiver;
+        let WorkerReceiver {
+            mut rx_worker_ping,
+            mut rx_transmission_request,
+            mut rx_transmission_response,
+            mut rx_transmission_chunk_request,
+            mut rx_transmission_chunk_response,
+        } = receiver;
 
         // Process the ping events.
         let self_ = self.clone();
@@ -380,6 +468,27 @@ impl<N: Network> Worker<N> {
                 });
             }
         });
+
+        // Process the transmission chunk requests.
+        let self_ = self.clone();
+        self.spawn(async move {
+            while let Some((peer_ip, chunk_request)) = rx_transmission_chunk_request.recv().await {
+                self_.send_transmission_chunk(peer_ip, chunk_request);
+            }
+        });
+
+        // Process the transmission chunk responses.
+        let self_ = self.clone();
+        self.spawn(async move {
+            while let Some((peer_ip, chunk_response)) = rx_transmission_chunk_response.recv().await {
+                // Process the transmission chunk, which reassembles and verifies the transmission once it is complete.
+                let self__ = self_.clone();
+                let _ = spawn_blocking!({
+                    self__.process_transmission_chunk(peer_ip, chunk_response);
+                    Ok(())
+                });
+            }
+        });
     }
 
     /// Sends a transmission request to the specified peer.
@@ -388,6 +497,15 @@ impl<N: Network> Worker<N> {
         peer_ip: SocketAddr,
         transmission_id: TransmissionID<N>,
     ) -> Result<(TransmissionID<N>, Transmission<N>)> {
+        // Determine the deadline for the fetch, including the time spent waiting for a slot.
+        let deadline = Instant::now() + Duration::from_millis(MAX_FETCH_TIMEOUT_IN_MS);
+        // Wait for a slot in the pool for this transmission type.
+        // Note: The slot is held until the transmission is fetched or the request times out.
+        let pool = FetchPool::of(&transmission_id);
+        let permit = match timeout_at(deadline, self.fetch_pools.acquire(pool)).await {
+            Ok(permit) => permit?,
+            Err(e) => bail!("Unable to fetch transmission - ({pool:?} pool is saturated) {e}"),
+        };
         // Initialize a oneshot channel.
         let (callback_sender, callback_receiver) = oneshot::channel();
         // Determine how many sent requests are pending.
@@ -413,9 +531,12 @@ impl<N: Network> Worker<N> {
             );
         }
         // Wait for the transmission to be fetched.
-        match timeout(Duration::from_millis(MAX_FETCH_TIMEOUT_IN_MS), callback_receiver).await {
+        match timeout_at(deadline, callback_receiver).await {
             // If the transmission was fetched, return it.
-            Ok(result) => Ok((transmission_id, result?)),
+            Ok(result) => {
+                trace!("Fetched transmission {} in {:?} ({pool:?} pool)", fmt_id(transmission_id), permit.elapsed());
+                Ok((transmission_id, result?))
+            }
             // If the transmission was not fetched, return an error.
             Err(e) => bail!("Unable to fetch transmission - (timeout) {e}"),
         }
@@ -424,13 +545,13 @@ impl<N: Network> Worker<N> {
     /// Handles the incoming transmission response.
     /// This method ensures the transmission response is well-formed and matches the transmission ID.
     fn finish_transmission_request(&self, peer_ip: SocketAddr, response: TransmissionResponse<N>) {
-        let TransmissionResponse { transmission_id, mut transmission } = response;
+        let TransmissionResponse { transmission_id, transmission } = response;
         // Check if the peer IP exists in the pending queue for the given transmission ID.
         let exists = self.pending.get(transmission_id).unwrap_or_default().contains(&peer_ip);
         // If the peer IP exists, finish the pending request.
         if exists {
             // Ensure the transmission is not a fee and matches the transmission ID.
-            match self.ledger.ensure_transmission_is_well_formed(transmission_id, &mut transmission) {
+            match self.ledger.ensure_transmission_is_well_formed(transmission_id, &transmission) {
                 Ok(()) => {
                     // Remove the transmission ID from the pending queue.
                     self.pending.remove(transmission_id, Some(transmission));
@@ -440,7 +561,93 @@ impl<N: Network> Worker<N> {
         }
     }
 
+    /// Handles the incoming transmission chunk, and requests the missing chunks of the transmission from the peer.
+    /// Once all chunks are received, the reassembled transmission is processed as a transmission response.
+    fn process_transmission_chunk(&self, peer_ip: SocketAddr, response: TransmissionChunkResponse<N>) {
+        let transmission_id = response.transmission_id;
+        // Ensure the transmission was requested from the peer.
+        if !self.pending.get(transmission_id).unwrap_or_default().contains(&peer_ip) {
+            return;
+        }
+        // Insert the chunk into the transfer.
+        match self.chunks.insert_chunk(peer_ip, response) {
+            Ok(ChunkProgress::Started(chunk_indices)) => {
+                self.send_transmission_chunk_requests(peer_ip, transmission_id, chunk_indices);
+                // Request the missing chunks again, until the transfer completes or expires.
+                self.spawn_chunk_retries(transmission_id);
+            }
+            Ok(ChunkProgress::Incomplete(chunk_indices)) => {
+                self.send_transmission_chunk_requests(peer_ip, transmission_id, chunk_indices);
+            }
+            Ok(ChunkProgress::Complete(bytes)) => {
+                // Deserialize the reassembled transmission, and ensure it does not exceed the maximum size.
+                let transmission =
+                    Transmission::read_le(&bytes[..]).map_err(anyhow::Error::from).and_then(|transmission| {
+                        match &transmission {
+                            Transmission::Solution(solution) => ensure_data_size(solution)?,
+                            Transmission::Transaction(transaction) => ensure_data_size(transaction)?,
+                            Transmission::Ratification => {}
+                        }
+                        Ok(transmission)
+                    });
+                match transmission {
+                    // Note: This ensures the reassembled transmission matches the transmission ID.
+                    Ok(transmission) => self
+                        .finish_transmission_request(peer_ip, TransmissionResponse::new(transmission_id, transmission)),
+                    Err(error) => warn!("Failed to reassemble transmission {} - {error}", fmt_id(transmission_id)),
+                }
+            }
+            Err(error) => {
+                debug!("Dropping a chunk of transmission {} from '{peer_ip}' - {error}", fmt_id(transmission_id))
+            }
+        }
+    }
+
+    /// Sends the requests for the given chunks of the transmission to the specified peer.
+    fn send_transmission_chunk_requests(
+        &self,
+        peer_ip: SocketAddr,
+        transmission_id: TransmissionID<N>,
+        chunk_indices: Vec<u32>,
+    ) {
+        if chunk_indices.is_empty() {
+            return;
+        }
+        let self_ = self.clone();
+        tokio::spawn(async move {
+            for chunk_index in chunk_indices {
+                let event =
+                    Event::TransmissionChunkRequest(TransmissionChunkRequest::new(transmission_id, chunk_index));
+                if self_.gateway.send(peer_ip, event).await.is_none() {
+                    debug!("Failed to request chunk {chunk_index} of transmission {}", fmt_id(transmission_id));
+                    break;
+                }
+            }
+        });
+    }
+
+    /// Periodically requests the missing chunks of the transmission again, without restarting the transfer,
+    /// until the transfer completes or expires, or the transmission is no longer pending.
+    fn spawn_chunk_retries(&self, transmission_id: TransmissionID<N>) {
+        let self_ = self.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(Duration::from_millis(CHUNK_REQUEST_TIMEOUT_IN_MS)).await;
+                // If the transmission was fetched otherwise, drop the transfer.
+                if !self_.pending.contains(transmission_id) {
+                    self_.chunks.remove_transfer(transmission_id);
+                    break;
+                }
+                let Some((peer_ip, chunk_indices)) = self_.chunks.retry_requests(transmission_id) else {
+                    break;
+                };
+                self_.send_transmission_chunk_requests(peer_ip, transmission_id, chunk_indices);
+            }
+        });
+    }
+
     /// Sends the requested transmission to the specified peer.
+    /// If the transmission is large, only its first chunk is sent, and the peer requests the remaining chunks.
     fn send_transmission_response(&self, peer_ip: SocketAddr, request: TransmissionRequest<N>) {
         let TransmissionRequest { transmission_id } = request;
         // Attempt to retrieve the transmission.
@@ -448,11 +655,40 @@ impl<N: Network> Worker<N> {
             // Send the transmission response to the peer.
             let self_ = self.clone();
             tokio::spawn(async move {
-                self_.gateway.send(peer_ip, Event::TransmissionResponse((transmission_id, transmission).into())).await;
+                // Note: The peers that predate the transmission chunks are sent the transmission in full.
+                let first_chunk = match self_.gateway.supports_feature(peer_ip, Feature::TransmissionChunks) {
+                    true => self_.chunks.first_chunk(transmission_id, &transmission),
+                    false => Ok(None),
+                };
+                let event = match first_chunk {
+                    Ok(Some(chunk)) => Event::TransmissionChunkResponse(chunk),
+                    Ok(None) => Event::TransmissionResponse((transmission_id, transmission).into()),
+                    Err(error) => {
+                        warn!("Failed to serialize transmission {} - {error}", fmt_id(transmission_id));
+                        return;
+                    }
+                };
+                self_.gateway.send(peer_ip, event).await;
             });
         }
     }
 
+    /// Sends the requested chunk of a transmission to the specified peer.
+    fn send_transmission_chunk(&self, peer_ip: SocketAddr, request: TransmissionChunkRequest<N>) {
+        let TransmissionChunkRequest { transmission_id, chunk_index } = request;
+        let self_ = self.clone();
+        tokio::spawn(async move {
+            // Retrieve the chunk, serializing the transmission again only if it is no longer being served.
+            match self_.chunks.chunk(transmission_id, chunk_index, || self_.get_transmission(transmission_id)) {
+                Ok(Some(chunk)) => {
+                    self_.gateway.send(peer_ip, Event::TransmissionChunkResponse(chunk)).await;
+                }
+                Ok(None) => {}
+                Err(error) => warn!("Failed to serialize transmission {} - {error}", fmt_id(transmission_id)),
+            }
+        });
+    }
+
     /// Spawns a task with the given future; it should only be used for long-running tasks.
     fn spawn<T: Future<Output = ()> + Send + 'static>(&self, future: T) {
         self.handles.lock().push(tokio::spawn(future));
@@ -507,6 +743,7 @@ mod tests {
             fn latest_round(&self) -> u64;
             fn latest_block_height(&self) -> u32;
             fn latest_block(&self) -> Block<N>;
+            fn latest_epoch_hash(&self) -> Result<N::BlockHash>;
             fn latest_leader(&self) -> Option<(u64, Address<N>)>;
             fn update_latest_leader(&self, round: u64, leader: Address<N>);
             fn contains_block_height(&self, height: u32) -> bool;
@@ -526,7 +763,7 @@ mod tests {
             fn ensure_transmission_is_well_formed(
                 &self,
                 transmission_id: TransmissionID<N>,
-                transmission: &mut Transmission<N>,
+                transmission: &Transmission<N>,
             ) -> Result<()>;
             async fn check_solution_basic(
                 &self,
@@ -539,6 +776,7 @@ mod tests {
                 transaction: Data<Transaction<N>>,
             ) -> Result<()>;
             fn check_next_block(&self, block: &Block<N>) -> Result<()>;
+            fn check_block_stateless(&self, block: &Block<N>) -> Result<()>;
             fn prepare_advance_to_next_quorum_block(
                 &self,
                 subdag: Subdag<N>,
@@ -879,6 +1117,153 @@ mod tests {
         assert!(worker.ready.contains(transmission_id));
     }
 
+    #[tokio::test]
+    async fn test_chunked_transmission_resumes_after_lost_chunk() {
+        let mut rng = &mut TestRng::default();
+        // Sample a committee.
+        let committee = snarkvm::ledger::committee::test_helpers::sample_committee(rng);
+        // Setup the mock ledgers.
+        let sample_ledger = |committee: Committee<CurrentNetwork>| {
+            let committee_clone = committee.clone();
+            let mut mock_ledger = MockLedger::default();
+            mock_ledger.expect_current_committee().returning(move || Ok(committee.clone()));
+            mock_ledger.expect_get_committee_lookback_for_round().returning(move |_| Ok(committee_clone.clone()));
+            mock_ledger.expect_contains_transmission().returning(|_| Ok(false));
+            mock_ledger.expect_ensure_transmission_is_well_formed().returning(|_, _| Ok(()));
+            let ledger: Arc<dyn LedgerService<CurrentNetwork>> = Arc::new(mock_ledger);
+            ledger
+        };
+        // Setup the mock gateways, which forward the sent events to the test.
+        let sample_gateway = |sender: tokio::sync::mpsc::UnboundedSender<Event<CurrentNetwork>>| {
+            let mut gateway = MockGateway::default();
+            gateway.expect_send().returning(move |_, event| {
+                let _ = sender.send(event);
+                let (_tx, rx) = oneshot::channel();
+                Some(rx)
+            });
+            gateway
+        };
+        let (requester_sender, mut requester_events) = tokio::sync::mpsc::unbounded_channel();
+        let (responder_sender, mut responder_events) = tokio::sync::mpsc::unbounded_channel();
+
+        // Create the Workers.
+        let create_worker = |gateway: MockGateway<CurrentNetwork>| {
+            let ledger = sample_ledger(committee.clone());
+            let storage = Storage::<CurrentNetwork>::new(ledger.clone(), Arc::new(BFTMemoryService::new()), 1);
+            Worker::new(0, Arc::new(gateway), storage, ledger, Default::default()).unwrap()
+        };
+        let requester = create_worker(sample_gateway(requester_sender));
+        let responder = create_worker(sample_gateway(responder_sender));
+        let requester_ip = SocketAddr::from(([127, 0, 0, 1], 1234));
+        let responder_ip = SocketAddr::from(([127, 0, 0, 1], 5678));
+
+        // Insert a transaction that is transferred in three chunks into the responder.
+        let size = 2 * crate::events::TRANSMISSION_CHUNK_SIZE_IN_BYTES + 100;
+        let transaction_id: <CurrentNetwork as Network>::TransactionID = Field::<CurrentNetwork>::rand(&mut rng).into();
+        let transmission_id = TransmissionID::Transaction(transaction_id);
+        let transmission = Transmission::Transaction(Data::Buffer(Bytes::from(
+            (0..size).map(|_| rng.gen::<u8>()).collect::<Vec<_>>(),
+        )));
+        assert!(responder.ready.insert(transmission_id, transmission.clone()));
+
+        // Fetch the transaction from the responder.
+        let requester_ = requester.clone();
+        let fetch =
+            tokio::spawn(async move { requester_.get_or_fetch_transmission(responder_ip, transmission_id).await });
+        // Relay the events between the workers, losing the second chunk once.
+        let mut requested_chunks = Vec::new();
+        let mut is_chunk_lost = false;
+        while !fetch.is_finished() {
+            tokio::select! {
+                Some(event) = requester_events.recv() => match event {
+                    Event::TransmissionRequest(request) => responder.send_transmission_response(requester_ip, request),
+                    Event::TransmissionChunkRequest(request) => {
+                        requested_chunks.push(request.chunk_index);
+                        responder.send_transmission_chunk(requester_ip, request);
+                    }
+                    _ => {}
+                },
+                Some(event) = responder_events.recv() => match event {
+                    Event::TransmissionChunkResponse(response) if response.chunk_index == 1 && !is_chunk_lost => {
+                        is_chunk_lost = true;
+                    }
+                    Event::TransmissionChunkResponse(response) => {
+                        requester.process_transmission_chunk(responder_ip, response)
+                    }
+                    _ => {}
+                },
+                _ = tokio::time::sleep(Duration::from_millis(10)) => {}
+            }
+        }
+
+        // Ensure the transaction was fetched, and only the lost chunk was requested again.
+        let (candidate_id, candidate) = fetch.await.unwrap().unwrap();
+        assert_eq!(candidate_id, transmission_id);
+        assert_eq!(candidate, transmission);
+        assert!(is_chunk_lost);
+        assert_eq!(requested_chunks, vec![1, 2, 1]);
+        assert!(!requester.pending.contains(transmission_id));
+        assert!(!requester.chunks.contains_transfer(transmission_id));
+    }
+
+    #[tokio::test]
+    async fn test_saturated_solution_pool_does_not_block_transactions() {
+        let rng = &mut TestRng::default();
+        // Sample a committee.
+        let committee = snarkvm::ledger::committee::test_helpers::sample_committee(rng);
+        let committee_clone = committee.clone();
+        // Setup the mock gateway and ledger.
+        let mut gateway = MockGateway::default();
+        gateway.expect_send().returning(|_, _| {
+            let (_tx, rx) = oneshot::channel();
+            Some(rx)
+        });
+        let mut mock_ledger = MockLedger::default();
+        mock_ledger.expect_current_committee().returning(move || Ok(committee.clone()));
+        mock_ledger.expect_get_committee_lookback_for_round().returning(move |_| Ok(committee_clone.clone()));
+        mock_ledger.expect_ensure_transmission_is_well_formed().returning(|_, _| Ok(()));
+        let ledger: Arc<dyn LedgerService<CurrentNetwork>> = Arc::new(mock_ledger);
+        // Initialize the storage.
+        let storage = Storage::<CurrentNetwork>::new(ledger.clone(), Arc::new(BFTMemoryService::new()), 1);
+
+        // Create the Worker, with small fetch pools.
+        let worker = Worker::new(0, Arc::new(gateway), storage, ledger, Default::default())
+            .unwrap()
+            .with_fetch_limits(FetchLimits { solutions: 2, transactions: 2 });
+        let peer_ip = SocketAddr::from(([127, 0, 0, 1], 1234));
+
+        // Saturate the solution pool with requests that are never answered.
+        for _ in 0..4 {
+            let worker_ = worker.clone();
+            let solution_id = TransmissionID::Solution(rng.gen::<u64>().into());
+            tokio::spawn(async move { worker_.send_transmission_request(peer_ip, solution_id).await });
+        }
+        while worker.fetch_pools().num_queued(FetchPool::Solutions) < 2 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(worker.fetch_pools().num_available(FetchPool::Solutions), 0);
+
+        // Fetch a transaction.
+        let transaction_id: <CurrentNetwork as Network>::TransactionID = Field::<CurrentNetwork>::rand(rng).into();
+        let transmission_id = TransmissionID::Transaction(transaction_id);
+        let worker_ = worker.clone();
+        let handle = tokio::spawn(async move { worker_.send_transmission_request(peer_ip, transmission_id).await });
+        while !worker.pending.contains(transmission_id) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        // Fake the transmission response.
+        worker.finish_transmission_request(peer_ip, TransmissionResponse {
+            transmission_id,
+            transmission: Transmission::Transaction(Data::Buffer(Bytes::from(vec![0; 512]))),
+        });
+
+        // Ensure the transaction fetch completes while the solution pool is still saturated.
+        let result = tokio::time::timeout(Duration::from_secs(1), handle).await.unwrap().unwrap();
+        assert_eq!(result.unwrap().0, transmission_id);
+        assert_eq!(worker.fetch_pools().num_queued(FetchPool::Solutions), 2);
+        assert_eq!(worker.fetch_pools().num_available(FetchPool::Transactions), 2);
+    }
+
     #[tokio::test]
     async fn test_storage_gc_on_initialization() {
         let rng = &mut TestRng::default();
@@ -0,0 +1,96 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkos_node_bft_storage_service::{BFTMemoryService, StorageService, DEFAULT_NUM_TRANSMISSION_SHARDS};
+use snarkvm::{
+    ledger::narwhal::{Data, Transmission, TransmissionID},
+    prelude::{Field, Rng, TestRng, Uniform},
+};
+
+use bytes::Bytes;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use indexmap::IndexSet;
+use std::{collections::HashMap, sync::Arc};
+
+type CurrentNetwork = snarkvm::prelude::MainnetV0;
+
+/// The number of concurrent writers.
+const NUM_WRITERS: usize = 8;
+/// The number of certificates inserted and removed by each writer.
+const NUM_CERTIFICATES_PER_WRITER: usize = 16;
+/// The number of transmissions referenced by each certificate.
+const NUM_TRANSMISSIONS_PER_CERTIFICATE: usize = 200;
+
+type Certificate = (Field<CurrentNetwork>, HashMap<TransmissionID<CurrentNetwork>, Transmission<CurrentNetwork>>);
+
+/// Samples the certificates of each writer.
+fn sample_writers(rng: &mut TestRng) -> Vec<Vec<Certificate>> {
+    (0..NUM_WRITERS)
+        .map(|_| {
+            (0..NUM_CERTIFICATES_PER_WRITER)
+                .map(|_| {
+                    let transmissions = (0..NUM_TRANSMISSIONS_PER_CERTIFICATE)
+                        .map(|_| {
+                            let bytes = Bytes::from((0..64).map(|_| rng.gen::<u8>()).collect::<Vec<_>>());
+                            (
+                                TransmissionID::Solution(rng.gen::<u64>().into()),
+                                Transmission::Solution(Data::Buffer(bytes)),
+                            )
+                        })
+                        .collect();
+                    (Field::rand(rng), transmissions)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Inserts and removes the certificates of every writer concurrently.
+fn insert_and_remove(service: &Arc<BFTMemoryService<CurrentNetwork>>, writers: &[Vec<Certificate>]) {
+    std::thread::scope(|scope| {
+        for certificates in writers {
+            let service = service.clone();
+            scope.spawn(move || {
+                for (certificate_id, transmissions) in certificates {
+                    let transmission_ids = transmissions.keys().copied().collect::<IndexSet<_>>();
+                    service.insert_transmissions(*certificate_id, transmission_ids.clone(), transmissions.clone());
+                    service.remove_transmissions(certificate_id, &transmission_ids);
+                }
+            });
+        }
+    });
+}
+
+fn concurrent_writers(c: &mut Criterion) {
+    let rng = &mut TestRng::default();
+    let writers = sample_writers(rng);
+
+    let mut group = c.benchmark_group("memory_service_concurrent_writers");
+    group.throughput(Throughput::Elements((NUM_WRITERS * NUM_CERTIFICATES_PER_WRITER) as u64));
+    // Compare a single lock (the baseline) against the default number of shards.
+    for num_shards in [1, DEFAULT_NUM_TRANSMISSION_SHARDS] {
+        group.bench_with_input(BenchmarkId::from_parameter(num_shards), &num_shards, |b, num_shards| {
+            let service = Arc::new(BFTMemoryService::<CurrentNetwork>::with_num_shards(*num_shards));
+            b.iter(|| insert_and_remove(&service, &writers))
+        });
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = memory;
+    config = Criterion::default().sample_size(10);
+    targets = concurrent_writers
+}
+criterion_main!(memory);
@@ -20,14 +20,28 @@ use snarkvm::{
 
 use indexmap::{indexset, map::Entry, IndexMap, IndexSet};
 use parking_lot::RwLock;
-use std::collections::HashMap;
+use std::{
+    collections::{hash_map::RandomState, HashMap},
+    hash::BuildHasher,
+};
 use tracing::error;
 
+/// The default number of shards of the transmissions map.
+pub const DEFAULT_NUM_TRANSMISSION_SHARDS: usize = 32;
+
+/// A shard of the map of `transmission ID` to `(transmission, certificate IDs)` entries.
+type TransmissionShard<N> = IndexMap<TransmissionID<N>, (Transmission<N>, IndexSet<Field<N>>)>;
+
 /// A BFT in-memory storage service.
+///
+/// The transmissions are sharded by a hash of their transmission ID, so that writers to different shards
+/// do not contend on the same lock.
 #[derive(Debug)]
 pub struct BFTMemoryService<N: Network> {
-    /// The map of `transmission ID` to `(transmission, certificate IDs)` entries.
-    transmissions: RwLock<IndexMap<TransmissionID<N>, (Transmission<N>, IndexSet<Field<N>>)>>,
+    /// The shards of the map of `transmission ID` to `(transmission, certificate IDs)` entries.
+    shards: Box<[RwLock<TransmissionShard<N>>]>,
+    /// The hasher that assigns each transmission ID to a shard.
+    hasher: RandomState,
 }
 
 impl<N: Network> Default for BFTMemoryService<N> {
@@ -40,7 +54,36 @@ impl<N: Network> Default for BFTMemoryService<N> {
 impl<N: Network> BFTMemoryService<N> {
     /// Initializes a new BFT in-memory storage service.
     pub fn new() -> Self {
-        Self { transmissions: Default::default() }
+        Self::with_num_shards(DEFAULT_NUM_TRANSMISSION_SHARDS)
+    }
+
+    /// Initializes a new BFT in-memory storage service, with the given number of shards.
+    /// Note: A single shard is equivalent to a single lock over all transmissions.
+    pub fn with_num_shards(num_shards: usize) -> Self {
+        let shards = (0..num_shards.max(1)).map(|_| Default::default()).collect();
+        Self { shards, hasher: RandomState::new() }
+    }
+
+    /// Returns the index of the shard for the given transmission ID.
+    fn shard_index(&self, transmission_id: &TransmissionID<N>) -> usize {
+        (self.hasher.hash_one(transmission_id) % self.shards.len() as u64) as usize
+    }
+
+    /// Returns the shard for the given transmission ID.
+    fn shard(&self, transmission_id: &TransmissionID<N>) -> &RwLock<TransmissionShard<N>> {
+        &self.shards[self.shard_index(transmission_id)]
+    }
+
+    /// Returns the given transmission IDs, grouped by the index of their shard.
+    fn group_by_shard<'a>(
+        &self,
+        transmission_ids: impl IntoIterator<Item = &'a TransmissionID<N>>,
+    ) -> Vec<Vec<TransmissionID<N>>> {
+        let mut groups = vec![Vec::new(); self.shards.len()];
+        for transmission_id in transmission_ids {
+            groups[self.shard_index(transmission_id)].push(*transmission_id);
+        }
+        groups
     }
 }
 
@@ -48,14 +91,14 @@ impl<N: Network> StorageService<N> for BFTMemoryService<N> {
     /// Returns `true` if the storage contains the specified `transmission ID`.
     fn contains_transmission(&self, transmission_id: TransmissionID<N>) -> bool {
         // Check if the transmission ID exists in storage.
-        self.transmissions.read().contains_key(&transmission_id)
+        self.shard(&transmission_id).read().contains_key(&transmission_id)
     }
 
     /// Returns the transmission for the given `transmission ID`.
     /// If the transmission ID does not exist in storage, `None` is returned.
     fn get_transmission(&self, transmission_id: TransmissionID<N>) -> Option<Transmission<N>> {
         // Get the transmission.
-        self.transmissions.read().get(&transmission_id).map(|(transmission, _)| transmission).cloned()
+        self.shard(&transmission_id).read().get(&transmission_id).map(|(transmission, _)| transmission).cloned()
     }
 
     /// Returns the missing transmissions in storage from the given transmissions.
@@ -66,18 +109,23 @@ impl<N: Network> StorageService<N> for BFTMemoryService<N> {
     ) -> Result<HashMap<TransmissionID<N>, Transmission<N>>> {
         // Initialize a list for the missing transmissions from storage.
         let mut missing_transmissions = HashMap::new();
-        // Lock the existing transmissions.
-        let known_transmissions = self.transmissions.read();
         // Ensure the declared transmission IDs are all present in storage or the given transmissions map.
-        for transmission_id in batch_header.transmission_ids() {
-            // If the transmission ID does not exist, ensure it was provided by the caller.
-            if !known_transmissions.contains_key(transmission_id) {
-                // Retrieve the transmission.
-                let Some(transmission) = transmissions.remove(transmission_id) else {
-                    bail!("Failed to provide a transmission");
-                };
-                // Append the transmission.
-                missing_transmissions.insert(*transmission_id, transmission);
+        for (shard, transmission_ids) in self.shards.iter().zip(self.group_by_shard(batch_header.transmission_ids())) {
+            if transmission_ids.is_empty() {
+                continue;
+            }
+            // Lock the existing transmissions of the shard.
+            let known_transmissions = shard.read();
+            for transmission_id in transmission_ids {
+                // If the transmission ID does not exist, ensure it was provided by the caller.
+                if !known_transmissions.contains_key(&transmission_id) {
+                    // Retrieve the transmission.
+                    let Some(transmission) = transmissions.remove(&transmission_id) else {
+                        bail!("Failed to provide a transmission");
+                    };
+                    // Append the transmission.
+                    missing_transmissions.insert(transmission_id, transmission);
+                }
             }
         }
         Ok(missing_transmissions)
@@ -90,29 +138,34 @@ impl<N: Network> StorageService<N> for BFTMemoryService<N> {
         transmission_ids: IndexSet<TransmissionID<N>>,
         mut missing_transmissions: HashMap<TransmissionID<N>, Transmission<N>>,
     ) {
-        // Acquire the transmissions write lock.
-        let mut transmissions = self.transmissions.write();
-        // Inserts the following:
-        //   - Inserts **only the missing** transmissions from storage.
-        //   - Inserts the certificate ID into the corresponding set for **all** transmissions.
-        'outer: for transmission_id in transmission_ids {
-            // Retrieve the transmission entry.
-            match transmissions.entry(transmission_id) {
-                Entry::Occupied(mut occupied_entry) => {
-                    let (_, certificate_ids) = occupied_entry.get_mut();
-                    // Insert the certificate ID into the set.
-                    certificate_ids.insert(certificate_id);
-                }
-                Entry::Vacant(vacant_entry) => {
-                    // Retrieve the missing transmission.
-                    let Some(transmission) = missing_transmissions.remove(&transmission_id) else {
-                        error!("Failed to provide a missing transmission {transmission_id}");
-                        continue 'outer;
-                    };
-                    // Prepare the set of certificate IDs.
-                    let certificate_ids = indexset! { certificate_id };
-                    // Insert the transmission and a new set with the certificate ID.
-                    vacant_entry.insert((transmission, certificate_ids));
+        for (shard, transmission_ids) in self.shards.iter().zip(self.group_by_shard(&transmission_ids)) {
+            if transmission_ids.is_empty() {
+                continue;
+            }
+            // Acquire the write lock of the shard, only for the transmission IDs in the shard.
+            let mut transmissions = shard.write();
+            // Inserts the following:
+            //   - Inserts **only the missing** transmissions from storage.
+            //   - Inserts the certificate ID into the corresponding set for **all** transmissions.
+            'outer: for transmission_id in transmission_ids {
+                // Retrieve the transmission entry.
+                match transmissions.entry(transmission_id) {
+                    Entry::Occupied(mut occupied_entry) => {
+                        let (_, certificate_ids) = occupied_entry.get_mut();
+                        // Insert the certificate ID into the set.
+                        certificate_ids.insert(certificate_id);
+                    }
+                    Entry::Vacant(vacant_entry) => {
+                        // Retrieve the missing transmission.
+                        let Some(transmission) = missing_transmissions.remove(&transmission_id) else {
+                            error!("Failed to provide a missing transmission {transmission_id}");
+                            continue 'outer;
+                        };
+                        // Prepare the set of certificate IDs.
+                        let certificate_ids = indexset! { certificate_id };
+                        // Insert the transmission and a new set with the certificate ID.
+                        vacant_entry.insert((transmission, certificate_ids));
+                    }
                 }
             }
         }
@@ -122,30 +175,64 @@ impl<N: Network> StorageService<N> for BFTMemoryService<N> {
     ///
     /// If the transmission no longer references any certificate IDs, the entry is removed from storage.
     fn remove_transmissions(&self, certificate_id: &Field<N>, transmission_ids: &IndexSet<TransmissionID<N>>) {
-        // Acquire the transmissions write lock.
-        let mut transmissions = self.transmissions.write();
-        // If this is the last certificate ID for the transmission ID, remove the transmission.
-        for transmission_id in transmission_ids {
-            // Remove the certificate ID for the transmission ID, and determine if there are any more certificate IDs.
-            match transmissions.entry(*transmission_id) {
-                Entry::Occupied(mut occupied_entry) => {
-                    let (_, certificate_ids) = occupied_entry.get_mut();
-                    // Remove the certificate ID for the transmission ID.
-                    certificate_ids.swap_remove(certificate_id);
-                    // If there are no more certificate IDs for the transmission ID, remove the transmission.
-                    if certificate_ids.is_empty() {
-                        // Remove the entry for the transmission ID.
-                        occupied_entry.shift_remove();
+        for (shard, transmission_ids) in self.shards.iter().zip(self.group_by_shard(transmission_ids)) {
+            if transmission_ids.is_empty() {
+                continue;
+            }
+            // Acquire the write lock of the shard, only for the transmission IDs in the shard.
+            let mut transmissions = shard.write();
+            // If this is the last certificate ID for the transmission ID, remove the transmission.
+            for transmission_id in transmission_ids {
+                // Remove the certificate ID for the transmission ID, and determine if there are any more certificate IDs.
+                match transmissions.entry(transmission_id) {
+                    Entry::Occupied(mut occupied_entry) => {
+                        let (_, certificate_ids) = occupied_entry.get_mut();
+                        // Remove the certificate ID for the transmission ID.
+                        certificate_ids.swap_remove(certificate_id);
+                        // If there are no more certificate IDs for the transmission ID, remove the transmission.
+                        if certificate_ids.is_empty() {
+                            // Remove the entry for the transmission ID.
+                            occupied_entry.shift_remove();
+                        }
                     }
+                    Entry::Vacant(_) => {}
                 }
-                Entry::Vacant(_) => {}
             }
         }
     }
 
     /// Returns a HashMap over the `(transmission ID, (transmission, certificate IDs))` entries.
+    /// Note: The entries are collected one shard at a time.
     #[cfg(any(test, feature = "test"))]
     fn as_hashmap(&self) -> HashMap<TransmissionID<N>, (Transmission<N>, IndexSet<Field<N>>)> {
-        self.transmissions.read().clone().into_iter().collect()
+        self.shards.iter().flat_map(|shard| shard.read().clone()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::test_helpers::{check_concurrent_writers, check_insert_and_remove};
+
+    type CurrentNetwork = snarkvm::prelude::MainnetV0;
+
+    #[test]
+    fn test_insert_and_remove() {
+        check_insert_and_remove(BFTMemoryService::<CurrentNetwork>::new());
+    }
+
+    #[test]
+    fn test_insert_and_remove_across_shards() {
+        check_insert_and_remove(BFTMemoryService::<CurrentNetwork>::with_num_shards(4));
+    }
+
+    #[test]
+    fn test_concurrent_writers() {
+        check_concurrent_writers(BFTMemoryService::<CurrentNetwork>::new());
+    }
+
+    #[test]
+    fn test_concurrent_writers_on_a_single_shard() {
+        check_concurrent_writers(BFTMemoryService::<CurrentNetwork>::with_num_shards(1));
     }
 }
@@ -18,6 +18,7 @@ use snarkvm::{
         narwhal::{BatchHeader, Transmission, TransmissionID},
         store::{
             cow_to_cloned,
+            cow_to_copied,
             helpers::{
                 rocksdb::{
                     internal::{self, BFTMap, Database, MapID},
@@ -33,57 +34,112 @@ use snarkvm::{
 
 use aleo_std::StorageMode;
 use indexmap::{indexset, IndexSet};
-use std::{borrow::Cow, collections::HashMap};
+use lru::LruCache;
+use parking_lot::{Mutex, RwLock};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    num::NonZeroUsize,
+};
 use tracing::error;
 
+/// The default number of transmissions held in the cache of the persistent storage.
+pub const DEFAULT_TRANSMISSION_CACHE_CAPACITY: usize = 1 << 14;
+
+/// An entry of `(transmission, certificate IDs)`.
+type TransmissionEntry<N> = (Transmission<N>, IndexSet<Field<N>>);
+
 /// A BFT persistent storage service.
+///
+/// The IDs of the stored transmissions are indexed in memory, and the recently inserted or read transmissions
+/// are cached, so that only the cache misses of `get_transmission` and the writes reach the database.
 #[derive(Debug)]
 pub struct BFTPersistentStorage<N: Network> {
     /// The map of `transmission ID` to `(transmission, certificate IDs)` entries.
-    transmissions: DataMap<TransmissionID<N>, (Transmission<N>, IndexSet<Field<N>>)>,
+    transmissions: DataMap<TransmissionID<N>, TransmissionEntry<N>>,
+    /// The IDs of the transmissions in the map.
+    transmission_ids: RwLock<HashSet<TransmissionID<N>>>,
+    /// The cache of the recently inserted or read transmissions.
+    cache: Mutex<LruCache<TransmissionID<N>, Transmission<N>>>,
+    /// The lock of the writers, so that the certificate IDs of each entry are updated in turn.
+    write_lock: Mutex<()>,
 }
 
 impl<N: Network> BFTPersistentStorage<N> {
     /// Initializes a new BFT persistent storage service.
+    /// Note: The transmissions of a previous run are loaded from the existing storage.
     pub fn open(storage_mode: StorageMode) -> Result<Self> {
-        Ok(Self { transmissions: internal::RocksDB::open_map(N::ID, storage_mode, MapID::BFT(BFTMap::Transmissions))? })
+        Self::from_map(internal::RocksDB::open_map(N::ID, storage_mode, MapID::BFT(BFTMap::Transmissions))?)
     }
 
     /// Initializes a new BFT persistent storage service.
     #[cfg(any(test, feature = "test"))]
     pub fn open_testing(temp_dir: std::path::PathBuf, dev: Option<u16>) -> Result<Self> {
+        Self::from_map(internal::RocksDB::open_map_testing(temp_dir, dev, MapID::BFT(BFTMap::Transmissions))?)
+    }
+
+    /// Returns the storage service, caching up to the given number of transmissions.
+    pub fn with_cache_capacity(self, capacity: NonZeroUsize) -> Self {
+        *self.cache.lock() = LruCache::new(capacity);
+        self
+    }
+
+    /// Initializes the storage service from the given map, indexing the IDs of its transmissions.
+    fn from_map(transmissions: DataMap<TransmissionID<N>, TransmissionEntry<N>>) -> Result<Self> {
+        let transmission_ids = transmissions.keys_confirmed().map(|key| cow_to_copied!(key)).collect();
         Ok(Self {
-            transmissions: internal::RocksDB::open_map_testing(temp_dir, dev, MapID::BFT(BFTMap::Transmissions))?,
+            transmissions,
+            transmission_ids: RwLock::new(transmission_ids),
+            cache: Mutex::new(LruCache::new(NonZeroUsize::new(DEFAULT_TRANSMISSION_CACHE_CAPACITY).unwrap())),
+            write_lock: Default::default(),
         })
     }
+
+    /// Writes the given entries in a single atomic batch, where an entry of `None` is removed.
+    /// Note: If any atomic write is in progress on the same database, the batch is written along with it.
+    fn write_batch(&self, entries: Vec<(TransmissionID<N>, Option<TransmissionEntry<N>>)>) -> Result<()> {
+        self.transmissions.start_atomic();
+        for (transmission_id, entry) in entries {
+            let result = match entry {
+                Some(entry) => self.transmissions.insert(transmission_id, entry),
+                None => self.transmissions.remove(&transmission_id),
+            };
+            if let Err(error) = result {
+                self.transmissions.abort_atomic();
+                return Err(error);
+            }
+        }
+        self.transmissions.finish_atomic()
+    }
 }
 
 impl<N: Network> StorageService<N> for BFTPersistentStorage<N> {
     /// Returns `true` if the storage contains the specified `transmission ID`.
     fn contains_transmission(&self, transmission_id: TransmissionID<N>) -> bool {
-        // Check if the transmission ID exists in storage.
-        let result = self.transmissions.contains_key_confirmed(&transmission_id);
-        // If the result is an error, log the error.
-        if let Err(error) = &result {
-            error!("Failed to check if transmission ID exists in storage - {error}");
-        }
-        // Return the result.
-        result.unwrap_or(false)
+        // Check if the transmission ID exists in the index.
+        self.transmission_ids.read().contains(&transmission_id)
     }
 
     /// Returns the transmission for the given `transmission ID`.
     /// If the transmission ID does not exist in storage, `None` is returned.
     fn get_transmission(&self, transmission_id: TransmissionID<N>) -> Option<Transmission<N>> {
+        // Check the cache first.
+        if let Some(transmission) = self.cache.lock().get(&transmission_id) {
+            return Some(transmission.clone());
+        }
         // Get the transmission.
-        match self.transmissions.get_confirmed(&transmission_id) {
-            Ok(Some(Cow::Owned((transmission, _)))) => Some(transmission),
-            Ok(Some(Cow::Borrowed((transmission, _)))) => Some(transmission.clone()),
-            Ok(None) => None,
+        let transmission = match self.transmissions.get_confirmed(&transmission_id) {
+            Ok(Some(Cow::Owned((transmission, _)))) => transmission,
+            Ok(Some(Cow::Borrowed((transmission, _)))) => transmission.clone(),
+            Ok(None) => return None,
             Err(error) => {
                 error!("Failed to get transmission from storage - {error}");
-                None
+                return None;
             }
-        }
+        };
+        // Cache the transmission.
+        self.cache.lock().put(transmission_id, transmission.clone());
+        Some(transmission)
     }
 
     /// Returns the missing transmissions in storage from the given transmissions.
@@ -94,10 +150,12 @@ impl<N: Network> StorageService<N> for BFTPersistentStorage<N> {
     ) -> Result<HashMap<TransmissionID<N>, Transmission<N>>> {
         // Initialize a list for the missing transmissions from storage.
         let mut missing_transmissions = HashMap::new();
+        // Lock the index of the existing transmissions.
+        let known_transmission_ids = self.transmission_ids.read();
         // Ensure the declared transmission IDs are all present in storage or the given transmissions map.
         for transmission_id in batch_header.transmission_ids() {
             // If the transmission ID does not exist, ensure it was provided by the caller.
-            if !self.contains_transmission(*transmission_id) {
+            if !known_transmission_ids.contains(transmission_id) {
                 // Retrieve the transmission.
                 let Some(transmission) = transmissions.remove(transmission_id) else {
                     bail!("Failed to provide a transmission");
@@ -116,9 +174,13 @@ impl<N: Network> StorageService<N> for BFTPersistentStorage<N> {
         transmission_ids: IndexSet<TransmissionID<N>>,
         mut missing_transmissions: HashMap<TransmissionID<N>, Transmission<N>>,
     ) {
-        // Inserts the following:
+        // Acquire the write lock, so that no other writer updates the same entries in the meantime.
+        let _write_lock = self.write_lock.lock();
+        // Prepare the following, to be written in a single batch:
         //   - Inserts **only the missing** transmissions from storage.
         //   - Inserts the certificate ID into the corresponding set for **all** transmissions.
+        let mut entries = Vec::with_capacity(transmission_ids.len());
+        let mut inserted_transmissions = Vec::new();
         'outer: for transmission_id in transmission_ids {
             // Retrieve the transmission entry.
             match self.transmissions.get_confirmed(&transmission_id) {
@@ -127,10 +189,7 @@ impl<N: Network> StorageService<N> for BFTPersistentStorage<N> {
                     // Insert the certificate ID into the set.
                     certificate_ids.insert(certificate_id);
                     // Update the transmission entry.
-                    if let Err(e) = self.transmissions.insert(transmission_id, (transmission, certificate_ids)) {
-                        error!("Failed to insert transmission {transmission_id} into storage - {e}");
-                        continue 'outer;
-                    }
+                    entries.push((transmission_id, Some((transmission, certificate_ids))));
                 }
                 Ok(None) => {
                     // Retrieve the missing transmission.
@@ -138,13 +197,9 @@ impl<N: Network> StorageService<N> for BFTPersistentStorage<N> {
                         error!("Failed to provide a missing transmission {transmission_id}");
                         continue 'outer;
                     };
-                    // Prepare the set of certificate IDs.
-                    let certificate_ids = indexset! { certificate_id };
+                    inserted_transmissions.push((transmission_id, transmission.clone()));
                     // Insert the transmission and a new set with the certificate ID.
-                    if let Err(e) = self.transmissions.insert(transmission_id, (transmission, certificate_ids)) {
-                        error!("Failed to insert transmission {transmission_id} into storage - {e}");
-                        continue 'outer;
-                    }
+                    entries.push((transmission_id, Some((transmission, indexset! { certificate_id }))));
                 }
                 Err(e) => {
                     error!("Failed to process the 'insert' for transmission {transmission_id} into storage - {e}");
@@ -152,37 +207,44 @@ impl<N: Network> StorageService<N> for BFTPersistentStorage<N> {
                 }
             }
         }
+        // Write the entries.
+        if let Err(e) = self.write_batch(entries) {
+            error!("Failed to insert the transmissions of certificate {certificate_id} into storage - {e}");
+            return;
+        }
+        // Index and cache the inserted transmissions.
+        let mut transmission_ids = self.transmission_ids.write();
+        let mut cache = self.cache.lock();
+        for (transmission_id, transmission) in inserted_transmissions {
+            transmission_ids.insert(transmission_id);
+            cache.put(transmission_id, transmission);
+        }
     }
 
     /// Removes the certificate ID for the transmissions from storage.
     ///
     /// If the transmission no longer references any certificate IDs, the entry is removed from storage.
     fn remove_transmissions(&self, certificate_id: &Field<N>, transmission_ids: &IndexSet<TransmissionID<N>>) {
-        // If this is the last certificate ID for the transmission ID, remove the transmission.
+        // Acquire the write lock, so that no other writer updates the same entries in the meantime.
+        let _write_lock = self.write_lock.lock();
+        // Prepare the updated entries, to be written in a single batch.
+        let mut entries = Vec::with_capacity(transmission_ids.len());
+        let mut removed_transmission_ids = Vec::new();
         'outer: for transmission_id in transmission_ids {
             // Retrieve the transmission entry.
             match self.transmissions.get_confirmed(transmission_id) {
                 Ok(Some(entry)) => {
                     let (transmission, mut certificate_ids) = cow_to_cloned!(entry);
-                    // Insert the certificate ID into the set.
+                    // Remove the certificate ID from the set.
                     certificate_ids.swap_remove(certificate_id);
                     // If there are no more certificate IDs for the transmission ID, remove the transmission.
                     if certificate_ids.is_empty() {
-                        // Remove the transmission entry.
-                        if let Err(e) = self.transmissions.remove(transmission_id) {
-                            error!("Failed to remove transmission {transmission_id} (now empty) from storage - {e}");
-                            continue 'outer;
-                        }
+                        removed_transmission_ids.push(*transmission_id);
+                        entries.push((*transmission_id, None));
                     }
                     // Otherwise, update the transmission entry.
                     else {
-                        // Update the transmission entry.
-                        if let Err(e) = self.transmissions.insert(*transmission_id, (transmission, certificate_ids)) {
-                            error!(
-                                "Failed to remove transmission {transmission_id} for certificate {certificate_id} from storage - {e}"
-                            );
-                            continue 'outer;
-                        }
+                        entries.push((*transmission_id, Some((transmission, certificate_ids))));
                     }
                 }
                 Ok(None) => { /* no-op */ }
@@ -192,12 +254,95 @@ impl<N: Network> StorageService<N> for BFTPersistentStorage<N> {
                 }
             }
         }
+        // Write the entries.
+        if let Err(e) = self.write_batch(entries) {
+            error!("Failed to remove the transmissions of certificate {certificate_id} from storage - {e}");
+            return;
+        }
+        // Remove the removed transmissions from the index and the cache.
+        let mut transmission_ids = self.transmission_ids.write();
+        let mut cache = self.cache.lock();
+        for transmission_id in removed_transmission_ids {
+            transmission_ids.remove(&transmission_id);
+            cache.pop(&transmission_id);
+        }
     }
 
     /// Returns a HashMap over the `(transmission ID, (transmission, certificate IDs))` entries.
     #[cfg(any(test, feature = "test"))]
     fn as_hashmap(&self) -> HashMap<TransmissionID<N>, (Transmission<N>, IndexSet<Field<N>>)> {
-        use snarkvm::ledger::store::cow_to_copied;
         self.transmissions.iter_confirmed().map(|(k, v)| (cow_to_copied!(k), cow_to_cloned!(v))).collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::test_helpers::{check_concurrent_writers, check_insert_and_remove, sample_transmissions};
+    use snarkvm::prelude::{TestRng, Uniform};
+
+    use std::path::PathBuf;
+
+    type CurrentNetwork = snarkvm::prelude::MainnetV0;
+
+    /// Returns a new temporary directory for the storage.
+    fn temp_dir(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("snarkos-bft-storage-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        path
+    }
+
+    #[test]
+    fn test_insert_and_remove() {
+        check_insert_and_remove(
+            BFTPersistentStorage::<CurrentNetwork>::open_testing(temp_dir("insert"), None).unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_insert_and_remove_without_cache() {
+        let storage = BFTPersistentStorage::<CurrentNetwork>::open_testing(temp_dir("no-cache"), None).unwrap();
+        check_insert_and_remove(storage.with_cache_capacity(NonZeroUsize::new(1).unwrap()));
+    }
+
+    #[test]
+    fn test_concurrent_writers() {
+        check_concurrent_writers(
+            BFTPersistentStorage::<CurrentNetwork>::open_testing(temp_dir("writers"), None).unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_transmissions_survive_a_restart() {
+        let rng = &mut TestRng::default();
+        let path = temp_dir("restart");
+
+        // Insert the transmissions of two certificates, and remove one of them.
+        let transmissions = sample_transmissions(rng, 32);
+        let transmission_ids = transmissions.keys().copied().collect::<IndexSet<_>>();
+        let (certificate_1, certificate_2) = (Field::rand(rng), Field::rand(rng));
+        let expected = {
+            let storage = BFTPersistentStorage::<CurrentNetwork>::open_testing(path.clone(), None).unwrap();
+            storage.insert_transmissions(certificate_1, transmission_ids.clone(), transmissions.clone());
+            storage.insert_transmissions(certificate_2, transmission_ids.clone(), Default::default());
+            storage.remove_transmissions(&certificate_1, &transmission_ids);
+            storage.as_hashmap()
+            // Note: The storage is dropped here, as on a shutdown of the node.
+        };
+        assert_eq!(expected.len(), 32);
+
+        // Reopen the storage, and ensure the transmissions were recovered.
+        let storage = BFTPersistentStorage::<CurrentNetwork>::open_testing(path, None).unwrap();
+        assert_eq!(storage.as_hashmap(), expected);
+        for (transmission_id, transmission) in &transmissions {
+            assert!(storage.contains_transmission(*transmission_id));
+            assert_eq!(storage.get_transmission(*transmission_id).as_ref(), Some(transmission));
+            assert_eq!(expected[transmission_id].1, indexset! { certificate_2 });
+        }
+
+        // Ensure the recovered transmissions are removed once no certificate references them.
+        storage.remove_transmissions(&certificate_2, &transmission_ids);
+        assert!(storage.as_hashmap().is_empty());
+        assert!(transmission_ids.iter().all(|id| !storage.contains_transmission(*id)));
+    }
+}
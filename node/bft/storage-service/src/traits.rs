@@ -52,3 +52,115 @@ pub trait StorageService<N: Network>: Debug + Send + Sync {
     #[cfg(any(test, feature = "test"))]
     fn as_hashmap(&self) -> HashMap<TransmissionID<N>, (Transmission<N>, IndexSet<Field<N>>)>;
 }
+
+#[cfg(all(test, any(feature = "memory", feature = "persistent")))]
+pub(crate) mod test_helpers {
+    use super::*;
+    use snarkvm::{
+        ledger::narwhal::Data,
+        prelude::{Rng, TestRng, Uniform},
+    };
+
+    use ::bytes::Bytes;
+    use indexmap::indexset;
+
+    type CurrentNetwork = snarkvm::prelude::MainnetV0;
+
+    /// Samples the given number of random fake solution transmissions.
+    pub(crate) fn sample_transmissions(
+        rng: &mut TestRng,
+        num_transmissions: usize,
+    ) -> HashMap<TransmissionID<CurrentNetwork>, Transmission<CurrentNetwork>> {
+        (0..num_transmissions)
+            .map(|_| {
+                let bytes = Bytes::from((0..64).map(|_| rng.gen::<u8>()).collect::<Vec<_>>());
+                (TransmissionID::Solution(rng.gen::<u64>().into()), Transmission::Solution(Data::Buffer(bytes)))
+            })
+            .collect()
+    }
+
+    /// Checks that the transmissions of two certificates are inserted and removed as they are referenced.
+    pub(crate) fn check_insert_and_remove(service: impl StorageService<CurrentNetwork>) {
+        let rng = &mut TestRng::default();
+
+        // Insert the transmissions for two certificates, which share half of their transmissions.
+        let transmissions = sample_transmissions(rng, 64);
+        let transmission_ids = transmissions.keys().copied().collect::<IndexSet<_>>();
+        let shared_ids = transmission_ids.iter().take(32).copied().collect::<IndexSet<_>>();
+        let (certificate_1, certificate_2) = (Field::rand(rng), Field::rand(rng));
+        service.insert_transmissions(certificate_1, transmission_ids.clone(), transmissions.clone());
+        service.insert_transmissions(certificate_2, shared_ids.clone(), Default::default());
+        assert_eq!(service.as_hashmap().len(), 64);
+        assert!(transmission_ids.iter().all(|id| service.contains_transmission(*id)));
+        assert!(transmissions
+            .iter()
+            .all(|(id, transmission)| service.get_transmission(*id).as_ref() == Some(transmission)));
+
+        // Remove the first certificate, and ensure only the shared transmissions remain.
+        service.remove_transmissions(&certificate_1, &transmission_ids);
+        let remaining = service.as_hashmap();
+        assert_eq!(remaining.len(), 32);
+        assert!(shared_ids.iter().all(|id| remaining[id].1 == indexset! { certificate_2 }));
+        assert!(transmission_ids.iter().all(|id| service.contains_transmission(*id) == shared_ids.contains(id)));
+
+        // Remove the second certificate, and ensure the storage is empty.
+        service.remove_transmissions(&certificate_2, &shared_ids);
+        assert!(service.as_hashmap().is_empty());
+        assert!(transmission_ids.iter().all(|id| service.get_transmission(*id).is_none()));
+    }
+
+    /// Checks that concurrent writers keep the certificate IDs of the shared transmissions consistent.
+    pub(crate) fn check_concurrent_writers(service: impl StorageService<CurrentNetwork>) {
+        const NUM_WRITERS: usize = 16;
+        const NUM_CERTIFICATES_PER_WRITER: usize = 32;
+        const NUM_TRANSMISSIONS_PER_CERTIFICATE: usize = 50;
+
+        let rng = &mut TestRng::default();
+
+        // Sample the certificates of each writer, where each writer also references the transmissions of the shared certificate.
+        let shared = sample_transmissions(rng, NUM_TRANSMISSIONS_PER_CERTIFICATE);
+        let shared_ids = shared.keys().copied().collect::<IndexSet<_>>();
+        service.insert_transmissions(Field::rand(rng), shared_ids.clone(), shared);
+        let writers = (0..NUM_WRITERS)
+            .map(|_| {
+                (0..NUM_CERTIFICATES_PER_WRITER)
+                    .map(|_| (Field::rand(rng), sample_transmissions(rng, NUM_TRANSMISSIONS_PER_CERTIFICATE)))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        // Insert and remove the certificates concurrently, keeping every other certificate.
+        std::thread::scope(|scope| {
+            for certificates in &writers {
+                let (service, shared_ids) = (&service, shared_ids.clone());
+                scope.spawn(move || {
+                    for (i, (certificate_id, transmissions)) in certificates.iter().enumerate() {
+                        let mut transmission_ids = transmissions.keys().copied().collect::<IndexSet<_>>();
+                        transmission_ids.extend(shared_ids.iter().copied());
+                        service.insert_transmissions(*certificate_id, transmission_ids.clone(), transmissions.clone());
+                        if i % 2 == 1 {
+                            service.remove_transmissions(certificate_id, &transmission_ids);
+                        }
+                    }
+                });
+            }
+        });
+
+        // Ensure the storage contains exactly the transmissions of the kept certificates, and the shared transmissions.
+        let entries = service.as_hashmap();
+        let num_kept = NUM_WRITERS * NUM_CERTIFICATES_PER_WRITER / 2;
+        assert_eq!(entries.len(), (num_kept + 1) * NUM_TRANSMISSIONS_PER_CERTIFICATE);
+        for certificates in &writers {
+            for (i, (certificate_id, transmissions)) in certificates.iter().enumerate() {
+                for transmission_id in transmissions.keys() {
+                    match i % 2 == 1 {
+                        true => assert!(!entries.contains_key(transmission_id)),
+                        false => assert_eq!(entries[transmission_id].1, indexset! { *certificate_id }),
+                    }
+                }
+            }
+        }
+        // Ensure the shared transmissions reference the shared certificate and every kept certificate.
+        assert!(shared_ids.iter().all(|id| entries[id].1.len() == num_kept + 1));
+    }
+}
@@ -20,7 +20,11 @@ mod components;
 use crate::common::primary::{TestNetwork, TestNetworkConfig};
 use deadline::deadline;
 use itertools::Itertools;
-use snarkos_node_bft::MAX_FETCH_TIMEOUT_IN_MS;
+use snarkos_node_bft::{
+    helpers::{EventLog, EventRecorder},
+    replay_event_log,
+    MAX_FETCH_TIMEOUT_IN_MS,
+};
 use std::time::Duration;
 use tokio::time::sleep;
 
@@ -274,3 +278,45 @@ async fn test_transient_break() {
     let network_clone = network.clone();
     deadline!(Duration::from_secs(60), move || { network_clone.is_round_reached(RECOVERY_ROUND) });
 }
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_record_and_replay() {
+    // Start N nodes, recording the events of the first node.
+    const N: u16 = 4;
+    const TRANSMISSION_INTERVAL_MS: u64 = 10;
+    let mut network = TestNetwork::new(TestNetworkConfig {
+        num_nodes: N,
+        bft: true,
+        connect_all: true,
+        fire_transmissions: Some(TRANSMISSION_INTERVAL_MS),
+        // Set this to Some(0..=4) to see the logs.
+        log_level: None,
+        log_connections: false,
+    });
+    let path = std::env::temp_dir().join(format!("snarkos-bft-event-log-{}", std::process::id()));
+    let primary = network.validators[&0].primary.clone();
+    let recorder =
+        EventRecorder::create(&path, primary.gateway().signer().address(), &primary.ledger().get_block(0).unwrap())
+            .unwrap();
+    primary.gateway().set_event_recorder(recorder).unwrap();
+    network.start().await;
+
+    // Let the nodes advance through the rounds, and commit a few leaders.
+    const TARGET_ROUND: u64 = 12;
+    let network_clone = network.clone();
+    deadline!(Duration::from_secs(60), move || { network_clone.is_round_reached(TARGET_ROUND) });
+    for validator in network.validators.values() {
+        validator.bft.get().unwrap().shut_down().await;
+    }
+
+    // Replay the event log, through a fresh primary and storage.
+    let log = EventLog::open(&path).unwrap();
+    let recorded = log.commits();
+    assert!(!recorded.is_empty());
+    let outcome = replay_event_log(&log, primary.ledger().clone()).await.unwrap();
+    std::fs::remove_file(&path).ok();
+
+    // Ensure the replay committed the same leaders, in the same order.
+    assert_eq!(outcome.first_divergence(&recorded), None);
+    assert_eq!(outcome.commits, recorded);
+}
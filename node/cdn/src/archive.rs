@@ -0,0 +1,155 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkvm::prelude::{block::Block, Deserialize, Network, Serialize};
+
+use anyhow::{bail, ensure, Result};
+use sha2::{Digest, Sha256};
+use std::str::FromStr;
+
+/// The number of blocks per file.
+pub const BLOCKS_PER_FILE: u32 = 50;
+
+/// A representation of the 'latest.json' file object.
+#[derive(Deserialize, Serialize, Debug)]
+pub(crate) struct LatestState {
+    pub(crate) exclusive_height: u32,
+    pub(crate) inclusive_height: u32,
+    pub(crate) hash: String,
+}
+
+/// A file of a block archive, as requested by the CDN client.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ArchiveFile {
+    /// The 'latest.json' file, containing the height of the archive.
+    Latest,
+    /// The '{start}.{end}.blocks' file, containing the blocks of the chunk starting at the given height.
+    Blocks(u32),
+    /// The '{start}.{end}.sum' file, containing the checksum of the blocks file of the chunk starting at the given height.
+    Checksum(u32),
+}
+
+impl FromStr for ArchiveFile {
+    type Err = anyhow::Error;
+
+    /// Parses the given file name, ensuring the chunk is aligned to `BLOCKS_PER_FILE`.
+    fn from_str(name: &str) -> Result<Self> {
+        if name == "latest.json" {
+            return Ok(Self::Latest);
+        }
+        let Some((range, extension)) = name.rsplit_once('.') else { bail!("Invalid archive file '{name}'") };
+        let Some((start, end)) = range.split_once('.') else { bail!("Invalid archive file '{name}'") };
+        let (start, end) = (start.parse::<u32>()?, end.parse::<u32>()?);
+        ensure!(
+            start % BLOCKS_PER_FILE == 0 && end.checked_sub(start) == Some(BLOCKS_PER_FILE),
+            "Invalid chunk '{start}.{end}' (chunks contain {BLOCKS_PER_FILE} blocks, starting at a multiple of {BLOCKS_PER_FILE})"
+        );
+        match extension {
+            "blocks" => Ok(Self::Blocks(start)),
+            "sum" => Ok(Self::Checksum(start)),
+            _ => bail!("Invalid archive file '{name}'"),
+        }
+    }
+}
+
+/// Returns the exclusive height of the block archive of a ledger with the given latest height,
+/// which only contains complete chunks of blocks that can no longer be rolled back.
+pub fn archive_height(latest_height: u32, maximum_fork_depth: u32) -> u32 {
+    let final_height = (latest_height + 1).saturating_sub(maximum_fork_depth);
+    final_height - (final_height % BLOCKS_PER_FILE)
+}
+
+/// Returns the serialized 'latest.json' file of a block archive with the given exclusive height,
+/// and the given hash of its last block.
+///
+/// Note: The CDN client rounds the height up to the next chunk, after subtracting a few blocks, so that
+/// an archive of complete chunks is synced up to its exclusive height.
+pub fn encode_latest_state(exclusive_height: u32, hash: String) -> Result<Vec<u8>> {
+    ensure!(exclusive_height > 0, "The block archive does not contain a complete chunk of blocks yet");
+    let latest = LatestState { exclusive_height, inclusive_height: exclusive_height - 1, hash };
+    Ok(bincode::serialize(&serde_json::to_string(&latest)?)?)
+}
+
+/// Returns the serialized '{start}.{end}.blocks' file of the given chunk of blocks.
+pub fn encode_blocks<N: Network>(blocks: &[Block<N>]) -> Result<Vec<u8>> {
+    Ok(bincode::serialize(blocks)?)
+}
+
+/// Returns the checksum of the given serialized chunk of blocks, as served in its '{start}.{end}.sum' file.
+pub fn checksum(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Ensures the given serialized chunk of blocks matches the given checksum.
+pub(crate) fn verify_checksum(bytes: &[u8], expected: &str) -> Result<()> {
+    let actual = checksum(bytes);
+    ensure!(actual == expected.trim(), "The checksum does not match (expected {}, found {actual})", expected.trim());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_file() {
+        assert_eq!(ArchiveFile::from_str("latest.json").unwrap(), ArchiveFile::Latest);
+        assert_eq!(ArchiveFile::from_str("0.50.blocks").unwrap(), ArchiveFile::Blocks(0));
+        assert_eq!(ArchiveFile::from_str("100.150.sum").unwrap(), ArchiveFile::Checksum(100));
+
+        // Ensure the chunks are aligned to the chunk size.
+        assert!(ArchiveFile::from_str("10.60.blocks").is_err());
+        assert!(ArchiveFile::from_str("0.100.blocks").is_err());
+        assert!(ArchiveFile::from_str("50.0.blocks").is_err());
+        // Ensure the other files are rejected.
+        assert!(ArchiveFile::from_str("0.50.json").is_err());
+        assert!(ArchiveFile::from_str("0.blocks").is_err());
+        assert!(ArchiveFile::from_str("latest").is_err());
+    }
+
+    #[test]
+    fn test_archive_height() {
+        // Ensure the archive only contains complete chunks, of blocks that can no longer be rolled back.
+        assert_eq!(archive_height(0, 10), 0);
+        assert_eq!(archive_height(58, 10), 0);
+        assert_eq!(archive_height(59, 10), 50);
+        assert_eq!(archive_height(160, 10), 150);
+        assert_eq!(archive_height(160, 0), 150);
+        assert_eq!(archive_height(199, 0), 200);
+    }
+
+    #[test]
+    fn test_latest_state() {
+        assert!(encode_latest_state(0, "hash".to_string()).is_err());
+
+        // Ensure the CDN client syncs up to the exclusive height of the archive.
+        for exclusive_height in [BLOCKS_PER_FILE, 4 * BLOCKS_PER_FILE] {
+            let bytes = encode_latest_state(exclusive_height, "hash".to_string()).unwrap();
+            let latest: LatestState = serde_json::from_str(&bincode::deserialize::<String>(&bytes).unwrap()).unwrap();
+            assert_eq!(latest.exclusive_height, exclusive_height);
+            assert_eq!(latest.inclusive_height, exclusive_height - 1);
+            assert_eq!(crate::blocks::cdn_tip::<BLOCKS_PER_FILE>(latest.exclusive_height), exclusive_height);
+        }
+    }
+
+    #[test]
+    fn test_verify_checksum() {
+        let bytes = b"blocks";
+        let sum = checksum(bytes);
+        assert_eq!(sum.len(), 64);
+        assert!(verify_checksum(bytes, &sum).is_ok());
+        assert!(verify_checksum(bytes, &format!("{sum}\n")).is_ok());
+        assert!(verify_checksum(b"blockz", &sum).is_err());
+    }
+}
@@ -16,20 +16,18 @@
 // https://github.com/rust-lang/rust-clippy/issues/6446
 #![allow(clippy::await_holding_lock)]
 
+use crate::archive::{verify_checksum, LatestState, BLOCKS_PER_FILE};
 use snarkvm::prelude::{
     block::Block,
     store::{cow_to_copied, ConsensusStorage},
-    Deserialize,
-    DeserializeOwned,
     Ledger,
     Network,
-    Serialize,
 };
 
-use anyhow::{anyhow, bail, Result};
+use anyhow::{anyhow, bail, ensure, Result};
 use colored::Colorize;
 use parking_lot::Mutex;
-use reqwest::Client;
+use reqwest::{Client, StatusCode};
 use std::{
     cmp,
     sync::{
@@ -39,8 +37,6 @@ use std::{
     time::{Duration, Instant},
 };
 
-/// The number of blocks per file.
-const BLOCKS_PER_FILE: u32 = 50;
 /// The desired number of concurrent requests to the CDN.
 const CONCURRENT_REQUESTS: u32 = 16;
 /// Maximum number of pending sync blocks.
@@ -50,12 +46,12 @@ const MAXIMUM_REQUEST_ATTEMPTS: u8 = 10;
 /// The supported network.
 const NETWORK_ID: u16 = 0;
 
-/// Loads blocks from a CDN into the ledger.
+/// Loads blocks from a CDN into the ledger, failing over to the next of the given mirrors of the CDN on errors.
 ///
 /// On success, this function returns the completed block height.
 /// On failure, this function returns the last successful block height (if any), along with the error.
 pub async fn sync_ledger_with_cdn<N: Network, C: ConsensusStorage<N>>(
-    base_url: &str,
+    base_urls: &[String],
     ledger: Ledger<N, C>,
     shutdown: Arc<AtomicBool>,
 ) -> Result<u32, (u32, anyhow::Error)> {
@@ -63,7 +59,7 @@ pub async fn sync_ledger_with_cdn<N: Network, C: ConsensusStorage<N>>(
     let start_height = ledger.latest_height() + 1;
     // Load the blocks from the CDN into the ledger.
     let ledger_clone = ledger.clone();
-    let result = load_blocks(base_url, start_height, None, shutdown, move |block: Block<N>| {
+    let result = load_blocks(base_urls, start_height, None, shutdown, move |block: Block<N>| {
         ledger_clone.advance_to_next_block(&block)
     })
     .await;
@@ -96,12 +92,12 @@ pub async fn sync_ledger_with_cdn<N: Network, C: ConsensusStorage<N>>(
     }
 }
 
-/// Loads blocks from a CDN and process them with the given function.
+/// Loads blocks from a CDN and process them with the given function, failing over to the next of the given mirrors of the CDN on errors.
 ///
 /// On success, this function returns the completed block height.
 /// On failure, this function returns the last successful block height (if any), along with the error.
 pub async fn load_blocks<N: Network>(
-    base_url: &str,
+    base_urls: &[String],
     start_height: u32,
     end_height: Option<u32>,
     shutdown: Arc<AtomicBool>,
@@ -111,6 +107,10 @@ pub async fn load_blocks<N: Network>(
     if N::ID != NETWORK_ID {
         return Err((start_height, anyhow!("The network ({}) is not supported", N::ID)));
     }
+    // If no CDN is given, return.
+    if base_urls.is_empty() {
+        return Err((start_height, anyhow!("No CDN was given")));
+    }
 
     // Create a Client to maintain a connection pool throughout the sync.
     let client = match Client::builder().build() {
@@ -121,7 +121,7 @@ pub async fn load_blocks<N: Network>(
     };
 
     // Fetch the CDN height.
-    let cdn_height = match cdn_height::<BLOCKS_PER_FILE>(&client, base_url).await {
+    let cdn_height = match cdn_height::<BLOCKS_PER_FILE>(&client, base_urls).await {
         Ok(cdn_height) => cdn_height,
         Err(error) => return Err((start_height, error)),
     };
@@ -161,10 +161,10 @@ pub async fn load_blocks<N: Network>(
 
     // Spawn a background task responsible for concurrent downloads.
     let pending_blocks_clone = pending_blocks.clone();
-    let base_url = base_url.to_owned();
+    let base_urls = base_urls.to_vec();
     let shutdown_clone = shutdown.clone();
     tokio::spawn(async move {
-        download_block_bundles(client, base_url, cdn_start, cdn_end, pending_blocks_clone, shutdown_clone).await;
+        download_block_bundles(client, base_urls, cdn_start, cdn_end, pending_blocks_clone, shutdown_clone).await;
     });
 
     // A loop for inserting the pending blocks into the ledger.
@@ -238,7 +238,7 @@ pub async fn load_blocks<N: Network>(
 
 async fn download_block_bundles<N: Network>(
     client: Client,
-    base_url: String,
+    base_urls: Vec<String>,
     cdn_start: u32,
     cdn_end: u32,
     pending_blocks: Arc<Mutex<Vec<Block<N>>>>,
@@ -275,13 +275,14 @@ async fn download_block_bundles<N: Network>(
             let end = start + BLOCKS_PER_FILE;
 
             // If this request would breach the upper limit, stop downloading.
-            if end > cdn_end + BLOCKS_PER_FILE {
+            // Note: A mirror may only serve the chunks up to the CDN height.
+            if start >= cdn_end {
                 debug!("Finishing network requests to the CDN...");
                 break;
             }
 
             let client_clone = client.clone();
-            let base_url_clone = base_url.clone();
+            let base_urls_clone = base_urls.clone();
             let pending_blocks_clone = pending_blocks.clone();
             let active_requests_clone = active_requests.clone();
             let shutdown_clone = shutdown.clone();
@@ -292,16 +293,13 @@ async fn download_block_bundles<N: Network>(
                 let ctx = format!("blocks {start} to {end}");
                 debug!("Requesting {ctx} (of {cdn_end})");
 
-                // Prepare the URL.
-                let blocks_url = format!("{base_url_clone}/{start}.{end}.blocks");
-                let ctx = format!("blocks {start} to {end}");
                 // Download blocks, retrying on failure.
                 let mut attempts = 0;
                 let request_time = Instant::now();
 
                 loop {
                     // Fetch the blocks.
-                    match cdn_get(client_clone.clone(), &blocks_url, &ctx).await {
+                    match cdn_get_blocks(&client_clone, &base_urls_clone, start, end).await {
                         Ok::<Vec<Block<N>>, _>(blocks) => {
                             // Keep the collection of pending blocks sorted by the height.
                             let mut pending_blocks = pending_blocks_clone.lock();
@@ -319,7 +317,7 @@ async fn download_block_bundles<N: Network>(
                             // case the maximum number of attempts has been breached.
                             attempts += 1;
                             if attempts > MAXIMUM_REQUEST_ATTEMPTS {
-                                warn!("Maximum number of requests to {blocks_url} reached - shutting down...");
+                                warn!("Maximum number of requests for {ctx} reached - shutting down...");
                                 shutdown_clone.store(true, Ordering::Relaxed);
                                 break;
                             }
@@ -344,22 +342,27 @@ async fn download_block_bundles<N: Network>(
     debug!("Finished network requests to the CDN");
 }
 
-/// Retrieves the CDN height with the given base URL.
-///
-/// Note: This function decrements the tip by a few blocks, to ensure the
-/// tip is not on a block that is not yet available on the CDN.
-async fn cdn_height<const BLOCKS_PER_FILE: u32>(client: &Client, base_url: &str) -> Result<u32> {
-    // A representation of the 'latest.json' file object.
-    #[derive(Deserialize, Serialize, Debug)]
-    struct LatestState {
-        exclusive_height: u32,
-        inclusive_height: u32,
-        hash: String,
+/// Retrieves the CDN height from the first of the given mirrors that responds.
+async fn cdn_height<const BLOCKS_PER_FILE: u32>(client: &Client, base_urls: &[String]) -> Result<u32> {
+    let mut last_error = anyhow!("No CDN was given");
+    for base_url in base_urls {
+        match cdn_height_from::<BLOCKS_PER_FILE>(client, base_url).await {
+            Ok(height) => return Ok(height),
+            Err(error) => {
+                warn!("{error} (from '{base_url}')");
+                last_error = error;
+            }
+        }
     }
+    Err(last_error)
+}
+
+/// Retrieves the CDN height with the given base URL.
+async fn cdn_height_from<const BLOCKS_PER_FILE: u32>(client: &Client, base_url: &str) -> Result<u32> {
     // Prepare the URL.
     let latest_json_url = format!("{base_url}/latest.json");
     // Send the request.
-    let response = match client.get(latest_json_url).send().await {
+    let response = match client.get(latest_json_url).send().await.and_then(|response| response.error_for_status()) {
         Ok(response) => response,
         Err(error) => bail!("Failed to fetch the CDN height - {error}"),
     };
@@ -374,33 +377,90 @@ async fn cdn_height<const BLOCKS_PER_FILE: u32>(client: &Client, base_url: &str)
         Err(error) => bail!("Failed to deserialize the CDN height response - {error}"),
     };
     // Parse the string for the tip.
-    let tip = match serde_json::from_str::<LatestState>(&latest_state_string) {
-        Ok(latest) => latest.exclusive_height,
+    match serde_json::from_str::<LatestState>(&latest_state_string) {
+        Ok(latest) => Ok(cdn_tip::<BLOCKS_PER_FILE>(latest.exclusive_height)),
         Err(error) => bail!("Failed to extract the CDN height response - {error}"),
-    };
+    }
+}
+
+/// Returns the CDN height for the given exclusive height of the CDN.
+///
+/// Note: This function decrements the tip by a few blocks, to ensure the
+/// tip is not on a block that is not yet available on the CDN.
+pub(crate) fn cdn_tip<const BLOCKS_PER_FILE: u32>(exclusive_height: u32) -> u32 {
     // Decrement the tip by a few blocks to ensure the CDN is caught up.
-    let tip = tip.saturating_sub(10);
+    let tip = exclusive_height.saturating_sub(10);
     // Adjust the tip to the closest subsequent multiple of BLOCKS_PER_FILE.
-    Ok(tip - (tip % BLOCKS_PER_FILE) + BLOCKS_PER_FILE)
+    tip - (tip % BLOCKS_PER_FILE) + BLOCKS_PER_FILE
+}
+
+/// Retrieves the given chunk of blocks from the first of the given mirrors that serves it intact.
+async fn cdn_get_blocks<N: Network>(
+    client: &Client,
+    base_urls: &[String],
+    start: u32,
+    end: u32,
+) -> Result<Vec<Block<N>>> {
+    let mut last_error = anyhow!("No CDN was given");
+    for base_url in base_urls {
+        match cdn_get_blocks_from(client, base_url, start, end).await {
+            Ok(blocks) => return Ok(blocks),
+            Err(error) => {
+                warn!("{error} (from '{base_url}')");
+                last_error = error;
+            }
+        }
+    }
+    Err(last_error)
 }
 
-/// Retrieves the objects from the CDN with the given URL.
-async fn cdn_get<T: 'static + DeserializeOwned + Send>(client: Client, url: &str, ctx: &str) -> Result<T> {
+/// Retrieves the given chunk of blocks from the given mirror, verifying its checksum if the mirror serves one.
+async fn cdn_get_blocks_from<N: Network>(
+    client: &Client,
+    base_url: &str,
+    start: u32,
+    end: u32,
+) -> Result<Vec<Block<N>>> {
+    let ctx = format!("blocks {start} to {end}");
+    // Fetch the blocks and their checksum.
+    let bytes = cdn_get_bytes(client, &format!("{base_url}/{start}.{end}.blocks"), &ctx).await?;
+    let checksum = match client.get(format!("{base_url}/{start}.{end}.sum")).send().await {
+        // Note: The checksum is optional, as it is not served by every CDN.
+        Ok(response) if matches!(response.status(), StatusCode::NOT_FOUND | StatusCode::FORBIDDEN) => None,
+        Ok(response) => match response.error_for_status() {
+            Ok(response) => Some(response.text().await?),
+            Err(error) => bail!("Failed to fetch the checksum of {ctx} - {error}"),
+        },
+        Err(error) => bail!("Failed to fetch the checksum of {ctx} - {error}"),
+    };
+    if let Some(checksum) = checksum {
+        verify_checksum(&bytes, &checksum).map_err(|error| anyhow!("Corrupted {ctx} - {error}"))?;
+    }
+    // Parse the blocks.
+    let blocks = match tokio::task::spawn_blocking(move || bincode::deserialize::<Vec<Block<N>>>(&bytes)).await {
+        Ok(Ok(blocks)) => blocks,
+        Ok(Err(error)) => bail!("Failed to deserialize {ctx} - {error}"),
+        Err(error) => bail!("Failed to join task for {ctx} - {error}"),
+    };
+    // Ensure the blocks belong to the chunk.
+    ensure!(
+        blocks.iter().all(|block| (start..end).contains(&block.height())),
+        "Received blocks outside of the range of {ctx}"
+    );
+    Ok(blocks)
+}
+
+/// Retrieves the bytes from the CDN with the given URL.
+async fn cdn_get_bytes(client: &Client, url: &str, ctx: &str) -> Result<Vec<u8>> {
     // Fetch the bytes from the given URL.
-    let response = match client.get(url).send().await {
+    let response = match client.get(url).send().await.and_then(|response| response.error_for_status()) {
         Ok(response) => response,
         Err(error) => bail!("Failed to fetch {ctx} - {error}"),
     };
     // Parse the response.
-    let bytes = match response.bytes().await {
-        Ok(bytes) => bytes,
+    match response.bytes().await {
+        Ok(bytes) => Ok(bytes.to_vec()),
         Err(error) => bail!("Failed to parse {ctx} - {error}"),
-    };
-    // Parse the objects.
-    match tokio::task::spawn_blocking(move || bincode::deserialize::<T>(&bytes)).await {
-        Ok(Ok(objects)) => Ok(objects),
-        Ok(Err(error)) => bail!("Failed to deserialize {ctx} - {error}"),
-        Err(error) => bail!("Failed to join task for {ctx} - {error}"),
     }
 }
 
@@ -435,7 +495,7 @@ fn log_progress<const OBJECTS_PER_FILE: u32>(
 #[cfg(test)]
 mod tests {
     use crate::{
-        blocks::{cdn_get, cdn_height, log_progress, BLOCKS_PER_FILE},
+        blocks::{cdn_get_bytes, cdn_height, log_progress, BLOCKS_PER_FILE},
         load_blocks,
     };
     use snarkvm::prelude::{block::Block, MainnetV0};
@@ -457,7 +517,8 @@ mod tests {
 
         let rt = tokio::runtime::Runtime::new().unwrap();
         rt.block_on(async {
-            let completed_height = load_blocks(TEST_BASE_URL, start, end, Default::default(), process).await.unwrap();
+            let completed_height =
+                load_blocks(&[TEST_BASE_URL.to_string()], start, end, Default::default(), process).await.unwrap();
             assert_eq!(blocks.read().len(), expected);
             if expected > 0 {
                 assert_eq!(blocks.read().last().unwrap().height(), completed_height);
@@ -502,18 +563,19 @@ mod tests {
         let rt = tokio::runtime::Runtime::new().unwrap();
         let client = reqwest::Client::builder().build().unwrap();
         rt.block_on(async {
-            let height = cdn_height::<BLOCKS_PER_FILE>(&client, TEST_BASE_URL).await.unwrap();
+            let height = cdn_height::<BLOCKS_PER_FILE>(&client, &[TEST_BASE_URL.to_string()]).await.unwrap();
             assert!(height > 0);
         });
     }
 
     #[test]
-    fn test_cdn_get() {
+    fn test_cdn_get_bytes() {
         let rt = tokio::runtime::Runtime::new().unwrap();
         rt.block_on(async {
             let client = reqwest::Client::new();
-            let height =
-                cdn_get::<u32>(client, &format!("{TEST_BASE_URL}/mainnet/latest/height"), "height").await.unwrap();
+            let bytes =
+                cdn_get_bytes(&client, &format!("{TEST_BASE_URL}/mainnet/latest/height"), "height").await.unwrap();
+            let height = bincode::deserialize::<u32>(&bytes).unwrap();
             assert!(height > 0);
         });
     }
@@ -17,5 +17,8 @@
 #[macro_use]
 extern crate tracing;
 
+mod archive;
+pub use archive::{archive_height, checksum, encode_blocks, encode_latest_state, ArchiveFile, BLOCKS_PER_FILE};
+
 mod blocks;
 pub use blocks::{load_blocks, sync_ledger_with_cdn};
@@ -0,0 +1,83 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkvm::{
+    ledger::narwhal::{Transmission, TransmissionID},
+    prelude::Network,
+};
+
+use indexmap::IndexMap;
+
+/// Removes the transmissions that are already confirmed, according to the given predicate,
+/// and returns the remaining transmissions along with the number of removed transmissions.
+///
+/// A committed subdag may reference transmissions that were already included in a block,
+/// e.g. when the BFT re-delivers a subdag after a restart.
+pub fn remove_confirmed_transmissions<N: Network>(
+    transmissions: IndexMap<TransmissionID<N>, Transmission<N>>,
+    is_confirmed: impl Fn(&TransmissionID<N>) -> bool,
+) -> (IndexMap<TransmissionID<N>, Transmission<N>>, usize) {
+    let num_transmissions = transmissions.len();
+    let remaining: IndexMap<_, _> = transmissions.into_iter().filter(|(id, _)| !is_confirmed(id)).collect();
+    let num_confirmed = num_transmissions - remaining.len();
+    (remaining, num_confirmed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkvm::prelude::{Field, TestRng, Uniform};
+
+    use rand::Rng;
+
+    type CurrentNetwork = snarkvm::prelude::MainnetV0;
+
+    fn sample_transmissions(
+        rng: &mut TestRng,
+    ) -> IndexMap<TransmissionID<CurrentNetwork>, Transmission<CurrentNetwork>> {
+        let mut transmissions = IndexMap::new();
+        transmissions.insert(TransmissionID::Ratification, Transmission::Ratification);
+        for _ in 0..3 {
+            let id = TransmissionID::Transaction(Field::rand(rng).into());
+            transmissions.insert(id, Transmission::Ratification);
+        }
+        let id = TransmissionID::Solution(rng.gen::<u64>().into());
+        transmissions.insert(id, Transmission::Ratification);
+        transmissions
+    }
+
+    #[test]
+    fn test_remove_confirmed_transmissions() {
+        let rng = &mut TestRng::default();
+        let transmissions = sample_transmissions(rng);
+        let ids = transmissions.keys().copied().collect::<Vec<_>>();
+
+        // Ensure nothing is removed if no transmission is confirmed.
+        let (remaining, num_confirmed) = remove_confirmed_transmissions(transmissions.clone(), |_| false);
+        assert_eq!(remaining, transmissions);
+        assert_eq!(num_confirmed, 0);
+
+        // Ensure the partially-overlapping transmissions are removed, preserving the order of the rest.
+        let confirmed = [ids[1], ids[4]];
+        let (remaining, num_confirmed) =
+            remove_confirmed_transmissions(transmissions.clone(), |id| confirmed.contains(id));
+        assert_eq!(remaining.keys().copied().collect::<Vec<_>>(), vec![ids[0], ids[2], ids[3]]);
+        assert_eq!(num_confirmed, 2);
+
+        // Ensure all of the transmissions are removed if they are all confirmed.
+        let (remaining, num_confirmed) = remove_confirmed_transmissions(transmissions, |_| true);
+        assert!(remaining.is_empty());
+        assert_eq!(num_confirmed, ids.len());
+    }
+}
@@ -0,0 +1,206 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkvm::{
+    ledger::block::Transaction,
+    prelude::{bail, Network, ProgramID, Result},
+};
+
+use indexmap::IndexSet;
+use lru::LruCache;
+use parking_lot::RwLock;
+
+/// A local denylist of programs, whose transactions this node refuses to admit into its memory pool or relay.
+///
+/// Note: The denylist is a local relay policy. It is never consulted when validating blocks,
+/// so the confirmed transactions of a denylisted program are processed and served as usual.
+#[derive(Debug, Default)]
+pub struct ProgramDenylist<N: Network> {
+    /// The set of denylisted program IDs.
+    programs: RwLock<IndexSet<ProgramID<N>>>,
+}
+
+impl<N: Network> ProgramDenylist<N> {
+    /// Initializes a new denylist with the given program IDs.
+    pub fn new(programs: impl IntoIterator<Item = ProgramID<N>>) -> Self {
+        Self { programs: RwLock::new(programs.into_iter().collect()) }
+    }
+
+    /// Returns the denylisted program IDs.
+    pub fn programs(&self) -> Vec<ProgramID<N>> {
+        self.programs.read().iter().copied().collect()
+    }
+
+    /// Returns the number of denylisted programs.
+    pub fn len(&self) -> usize {
+        self.programs.read().len()
+    }
+
+    /// Returns `true` if no program is denylisted.
+    pub fn is_empty(&self) -> bool {
+        self.programs.read().is_empty()
+    }
+
+    /// Returns `true` if the given program ID is denylisted.
+    pub fn contains(&self, program_id: &ProgramID<N>) -> bool {
+        self.programs.read().contains(program_id)
+    }
+
+    /// Replaces the denylisted programs with the given program IDs.
+    /// Returns the program IDs that were not denylisted before.
+    pub fn set(&self, programs: impl IntoIterator<Item = ProgramID<N>>) -> Vec<ProgramID<N>> {
+        let programs = programs.into_iter().collect::<IndexSet<_>>();
+        let mut current = self.programs.write();
+        let added = programs.difference(&current).copied().collect();
+        *current = programs;
+        added
+    }
+
+    /// Returns the first denylisted program referenced by the given transaction, if any.
+    ///
+    /// A transaction references the programs of its transitions, and for a deployment,
+    /// the deployed program and the programs it imports.
+    pub fn denied_program(&self, transaction: &Transaction<N>) -> Option<ProgramID<N>> {
+        let programs = self.programs.read();
+        // Return early if no program is denylisted.
+        if programs.is_empty() {
+            return None;
+        }
+        // Check the deployed program and its imports.
+        if let Some(deployment) = transaction.deployment() {
+            let mut deployed = std::iter::once(deployment.program_id()).chain(deployment.program().imports().keys());
+            if let Some(program_id) = deployed.find(|program_id| programs.contains(*program_id)) {
+                return Some(*program_id);
+            }
+        }
+        // Check the programs of the transitions, including the fee transition.
+        transaction.transitions().map(|transition| *transition.program_id()).find(|id| programs.contains(id))
+    }
+
+    /// Ensures the given transaction does not reference a denylisted program.
+    pub fn ensure_allowed(&self, transaction: &Transaction<N>) -> Result<()> {
+        if let Some(program_id) = self.denied_program(transaction) {
+            bail!("Transaction '{}' references the denylisted program '{program_id}'", transaction.id())
+        }
+        Ok(())
+    }
+
+    /// Removes the transactions that reference a denylisted program from the given queue.
+    /// Returns the IDs of the removed transactions.
+    pub fn evict_from(&self, queue: &mut LruCache<N::TransactionID, Transaction<N>>) -> Vec<N::TransactionID> {
+        // Determine the denied transactions.
+        let denied = queue
+            .iter()
+            .filter(|(_, transaction)| self.denied_program(transaction).is_some())
+            .map(|(transaction_id, _)| *transaction_id)
+            .collect::<Vec<_>>();
+        // Remove the denied transactions from the queue.
+        for transaction_id in &denied {
+            queue.pop(transaction_id);
+        }
+        denied
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkvm::{
+        ledger::ledger_test_helpers::{sample_deployment_transaction, sample_execution_transaction_with_fee},
+        prelude::TestRng,
+    };
+
+    use std::{num::NonZeroUsize, str::FromStr};
+
+    type CurrentNetwork = snarkvm::prelude::MainnetV0;
+
+    /// Returns the program ID of the first transition of the given transaction.
+    fn executed_program(transaction: &Transaction<CurrentNetwork>) -> ProgramID<CurrentNetwork> {
+        *transaction.transitions().next().unwrap().program_id()
+    }
+
+    #[test]
+    fn test_set_returns_added_programs() {
+        let first = ProgramID::<CurrentNetwork>::from_str("first.aleo").unwrap();
+        let second = ProgramID::<CurrentNetwork>::from_str("second.aleo").unwrap();
+
+        let denylist = ProgramDenylist::new([first]);
+        assert!(denylist.contains(&first));
+        assert!(!denylist.contains(&second));
+
+        // Only the newly denylisted program is returned.
+        assert_eq!(denylist.set([first, second]), vec![second]);
+        assert_eq!(denylist.programs(), vec![first, second]);
+
+        // Removing a program returns no new program.
+        assert!(denylist.set([second]).is_empty());
+        assert!(!denylist.contains(&first));
+        assert_eq!(denylist.len(), 1);
+
+        // Clearing the denylist allows every program again.
+        assert!(denylist.set([]).is_empty());
+        assert!(denylist.is_empty());
+    }
+
+    #[test]
+    fn test_ensure_allowed() {
+        let rng = &mut TestRng::default();
+        let execution = sample_execution_transaction_with_fee(false, rng);
+        let deployment = sample_deployment_transaction(false, rng);
+        let deployed = *deployment.deployment().unwrap().program_id();
+
+        // An empty denylist allows every transaction.
+        let denylist = ProgramDenylist::<CurrentNetwork>::default();
+        assert!(denylist.ensure_allowed(&execution).is_ok());
+        assert!(denylist.ensure_allowed(&deployment).is_ok());
+
+        // Denylisting the executed program refuses the execution.
+        denylist.set([executed_program(&execution)]);
+        assert_eq!(denylist.denied_program(&execution), Some(executed_program(&execution)));
+        assert!(denylist.ensure_allowed(&execution).is_err());
+
+        // Denylisting the deployed program refuses the deployment only.
+        denylist.set([deployed]);
+        assert!(denylist.ensure_allowed(&execution).is_ok());
+        assert_eq!(denylist.denied_program(&deployment), Some(deployed));
+        assert!(denylist.ensure_allowed(&deployment).is_err());
+
+        // Denylisting 'credits.aleo' refuses both, as their fees are paid through it.
+        denylist.set([ProgramID::from_str("credits.aleo").unwrap()]);
+        assert!(denylist.ensure_allowed(&execution).is_err());
+        assert!(denylist.ensure_allowed(&deployment).is_err());
+    }
+
+    #[test]
+    fn test_evict_from_queue() {
+        let rng = &mut TestRng::default();
+        let execution = sample_execution_transaction_with_fee(false, rng);
+        let deployment = sample_deployment_transaction(false, rng);
+
+        let mut queue = LruCache::new(NonZeroUsize::new(4).unwrap());
+        queue.put(execution.id(), execution.clone());
+        queue.put(deployment.id(), deployment.clone());
+
+        // Nothing is evicted while the denylist is empty.
+        let denylist = ProgramDenylist::<CurrentNetwork>::default();
+        assert!(denylist.evict_from(&mut queue).is_empty());
+        assert_eq!(queue.len(), 2);
+
+        // Denylisting the deployed program evicts the deployment, and keeps the execution.
+        denylist.set([*deployment.deployment().unwrap().program_id()]);
+        assert_eq!(denylist.evict_from(&mut queue), vec![deployment.id()]);
+        assert_eq!(queue.len(), 1);
+        assert!(queue.contains(&execution.id()));
+    }
+}
@@ -0,0 +1,326 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkos_node_bft_ledger_service::LedgerService;
+use snarkvm::{
+    ledger::{
+        block::Block,
+        narwhal::{Transmission, TransmissionID},
+    },
+    prelude::{ensure, FromBytes, Network, Result, ToBytes},
+};
+
+use indexmap::IndexMap;
+use parking_lot::Mutex;
+use std::{
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// An entry of the block journal, holding the block being advanced to and the transmissions it was built from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JournalEntry<N: Network> {
+    /// The block being advanced to.
+    pub block: Block<N>,
+    /// The transmissions of the block.
+    pub transmissions: IndexMap<TransmissionID<N>, Transmission<N>>,
+}
+
+/// The outcome of recovering from the entry left in the block journal.
+#[derive(Debug, PartialEq, Eq)]
+pub enum JournalRecovery<N: Network> {
+    /// The journal is empty, as the last block advance completed.
+    Clean,
+    /// The block was already in the ledger, and only the journal was cleared.
+    Completed { height: u32 },
+    /// The block was missing from the ledger, and was re-applied.
+    Reapplied { height: u32 },
+    /// The block could not be re-applied, and its transmissions must be reinserted into the memory pool.
+    Reinsert { height: u32, transmissions: IndexMap<TransmissionID<N>, Transmission<N>> },
+}
+
+/// A write-ahead journal of the block that consensus is advancing to.
+///
+/// The journal holds at most one entry: it is written before the ledger advances to the block,
+/// and truncated after the block is advanced. An entry that is present on startup means the node
+/// stopped in between, and is resolved with `recover` before the BFT runs.
+///
+/// Each entry is written to a temporary file that is then renamed over the journal,
+/// so that the journal holds either a complete entry or none at all.
+pub struct BlockJournal<N: Network> {
+    /// The path of the journal file, if it is persistent.
+    path: Option<PathBuf>,
+    /// The pending entry of an in-memory journal.
+    entry: Mutex<Option<JournalEntry<N>>>,
+}
+
+impl<N: Network> BlockJournal<N> {
+    /// Opens the block journal at the given path, or an in-memory journal if no path is given.
+    pub fn open(path: Option<PathBuf>) -> Result<Self> {
+        let entry = match &path {
+            Some(path) => {
+                // Remove the temporary file of an entry that was not fully written.
+                let _ = fs::remove_file(temporary_path(path));
+                // Read the pending entry, if there is one.
+                match fs::read(path) {
+                    Ok(bytes) => match read_entry(&bytes) {
+                        Ok(entry) => Some(entry),
+                        Err(error) => {
+                            warn!(
+                                "Discarding a malformed entry of the block journal at '{}' - {error}",
+                                path.display()
+                            );
+                            fs::remove_file(path)?;
+                            None
+                        }
+                    },
+                    Err(error) if error.kind() == std::io::ErrorKind::NotFound => None,
+                    Err(error) => return Err(error.into()),
+                }
+            }
+            None => None,
+        };
+        Ok(Self { path, entry: Mutex::new(entry) })
+    }
+
+    /// Returns the pending entry of the journal, if there is one.
+    pub fn pending(&self) -> Option<JournalEntry<N>> {
+        self.entry.lock().clone()
+    }
+
+    /// Records the given block and its transmissions, before the ledger advances to the block.
+    pub fn write(&self, block: &Block<N>, transmissions: &IndexMap<TransmissionID<N>, Transmission<N>>) -> Result<()> {
+        let mut entry = self.entry.lock();
+        if let Some(path) = &self.path {
+            let mut bytes = Vec::new();
+            write_entry(&mut bytes, block, transmissions)?;
+            // Write the entry to a temporary file, and move it over the journal once it is durable.
+            let temporary_path = temporary_path(path);
+            let mut file = File::create(&temporary_path)?;
+            file.write_all(&bytes)?;
+            file.sync_all()?;
+            fs::rename(&temporary_path, path)?;
+        }
+        *entry = Some(JournalEntry { block: block.clone(), transmissions: transmissions.clone() });
+        Ok(())
+    }
+
+    /// Clears the journal, after the ledger advanced to the block (or failed to).
+    pub fn truncate(&self) -> Result<()> {
+        let mut entry = self.entry.lock();
+        if let Some(path) = &self.path {
+            match fs::remove_file(path) {
+                Ok(()) => (),
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => (),
+                Err(error) => return Err(error.into()),
+            }
+        }
+        *entry = None;
+        Ok(())
+    }
+
+    /// Resolves the pending entry of the journal against the given ledger, and clears the journal.
+    ///
+    /// If the block is already in the ledger, the advance completed before the node stopped.
+    /// If the block is the next block of the ledger, it is re-applied. Otherwise, its transmissions are returned,
+    /// to be reinserted into the memory pool.
+    pub fn recover(&self, ledger: &dyn LedgerService<N>) -> Result<JournalRecovery<N>> {
+        let Some(JournalEntry { block, transmissions }) = self.pending() else {
+            return Ok(JournalRecovery::Clean);
+        };
+        let height = block.height();
+
+        let recovery = if ledger.contains_block_height(height) && ledger.get_block_hash(height)? == block.hash() {
+            // The block was advanced to, but the journal was not truncated.
+            JournalRecovery::Completed { height }
+        } else if height == ledger.latest_block_height().saturating_add(1) && ledger.check_next_block(&block).is_ok() {
+            // The block was not advanced to, and is still the next block of the ledger.
+            ledger.advance_to_next_block(&block)?;
+            JournalRecovery::Reapplied { height }
+        } else {
+            // The block can no longer be advanced to.
+            JournalRecovery::Reinsert { height, transmissions }
+        };
+
+        self.truncate()?;
+        Ok(recovery)
+    }
+}
+
+/// Returns the path of the temporary file for the given journal path.
+fn temporary_path(path: &Path) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".tmp");
+    path.into()
+}
+
+/// Writes an entry of the journal, as `(block, number of transmissions, transmissions)`.
+fn write_entry<N: Network>(
+    writer: &mut Vec<u8>,
+    block: &Block<N>,
+    transmissions: &IndexMap<TransmissionID<N>, Transmission<N>>,
+) -> Result<()> {
+    block.write_le(&mut *writer)?;
+    u32::try_from(transmissions.len())?.write_le(&mut *writer)?;
+    for (transmission_id, transmission) in transmissions {
+        transmission_id.write_le(&mut *writer)?;
+        transmission.write_le(&mut *writer)?;
+    }
+    Ok(())
+}
+
+/// Reads an entry of the journal.
+fn read_entry<N: Network>(mut bytes: &[u8]) -> Result<JournalEntry<N>> {
+    let block = Block::read_le(&mut bytes)?;
+    let num_transmissions = u32::read_le(&mut bytes)?;
+    let mut transmissions = IndexMap::new();
+    for _ in 0..num_transmissions {
+        let transmission_id = TransmissionID::read_le(&mut bytes)?;
+        let transmission = Transmission::read_le(&mut bytes)?;
+        transmissions.insert(transmission_id, transmission);
+    }
+    ensure!(bytes.is_empty(), "The journal entry has trailing bytes");
+    Ok(JournalEntry { block, transmissions })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkos_node_bft_ledger_service::{test_helpers::sample_ledger, CoreLedgerService};
+    use snarkvm::{
+        ledger::{store::helpers::memory::ConsensusMemory, Ledger},
+        prelude::{MainnetV0, TestRng},
+    };
+
+    type CurrentNetwork = MainnetV0;
+    type CurrentLedger = Ledger<CurrentNetwork, ConsensusMemory<CurrentNetwork>>;
+
+    /// Returns the ledger service for the given ledger.
+    fn sample_service(ledger: &CurrentLedger) -> CoreLedgerService<CurrentNetwork, ConsensusMemory<CurrentNetwork>> {
+        CoreLedgerService::new(ledger.clone(), Default::default())
+    }
+
+    /// Returns the transmissions of a journal entry.
+    fn sample_transmissions() -> IndexMap<TransmissionID<CurrentNetwork>, Transmission<CurrentNetwork>> {
+        IndexMap::from([(TransmissionID::Ratification, Transmission::Ratification)])
+    }
+
+    /// Returns a path for the journal file.
+    fn sample_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("snarkos-block-journal-{name}-{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_file(temporary_path(&path));
+        path
+    }
+
+    #[test]
+    fn test_write_and_truncate() {
+        let rng = &mut TestRng::default();
+        let (ledger, private_key) = sample_ledger(rng);
+        let block = ledger.prepare_advance_to_next_beacon_block(&private_key, vec![], vec![], vec![], rng).unwrap();
+        let path = sample_path("write");
+
+        // Ensure a fresh journal is empty.
+        let journal = BlockJournal::<CurrentNetwork>::open(Some(path.clone())).unwrap();
+        assert!(journal.pending().is_none());
+
+        // Ensure a written entry survives a reopen.
+        journal.write(&block, &sample_transmissions()).unwrap();
+        let expected = JournalEntry { block, transmissions: sample_transmissions() };
+        assert_eq!(journal.pending(), Some(expected.clone()));
+        assert_eq!(BlockJournal::open(Some(path.clone())).unwrap().pending(), Some(expected));
+
+        // Ensure a truncated journal is empty after a reopen.
+        journal.truncate().unwrap();
+        assert!(journal.pending().is_none());
+        assert!(BlockJournal::<CurrentNetwork>::open(Some(path.clone())).unwrap().pending().is_none());
+
+        // Ensure a malformed journal, or the temporary file of an unfinished write, is discarded.
+        fs::write(&path, [1, 2, 3]).unwrap();
+        fs::write(temporary_path(&path), [1, 2, 3]).unwrap();
+        assert!(BlockJournal::<CurrentNetwork>::open(Some(path.clone())).unwrap().pending().is_none());
+        assert!(!path.exists());
+        assert!(!temporary_path(&path).exists());
+    }
+
+    #[test]
+    fn test_recover_after_crash_before_advance() {
+        let rng = &mut TestRng::default();
+        let (ledger, private_key) = sample_ledger(rng);
+        let block = ledger.prepare_advance_to_next_beacon_block(&private_key, vec![], vec![], vec![], rng).unwrap();
+        let path = sample_path("before-advance");
+
+        // Write the journal, and stop before the ledger advances.
+        BlockJournal::<CurrentNetwork>::open(Some(path.clone()))
+            .unwrap()
+            .write(&block, &sample_transmissions())
+            .unwrap();
+
+        // Ensure the block is re-applied on restart, and the journal is cleared.
+        let journal = BlockJournal::<CurrentNetwork>::open(Some(path.clone())).unwrap();
+        let recovery = journal.recover(&sample_service(&ledger)).unwrap();
+        assert_eq!(recovery, JournalRecovery::Reapplied { height: 1 });
+        assert_eq!(ledger.latest_hash(), block.hash());
+        assert!(!path.exists());
+
+        // Ensure a second recovery is a no-op.
+        assert_eq!(journal.recover(&sample_service(&ledger)).unwrap(), JournalRecovery::Clean);
+    }
+
+    #[test]
+    fn test_recover_after_crash_before_truncate() {
+        let rng = &mut TestRng::default();
+        let (ledger, private_key) = sample_ledger(rng);
+        let block = ledger.prepare_advance_to_next_beacon_block(&private_key, vec![], vec![], vec![], rng).unwrap();
+        let path = sample_path("before-truncate");
+
+        // Write the journal and advance the ledger, and stop before the journal is truncated.
+        BlockJournal::<CurrentNetwork>::open(Some(path.clone()))
+            .unwrap()
+            .write(&block, &sample_transmissions())
+            .unwrap();
+        ledger.advance_to_next_block(&block).unwrap();
+
+        // Ensure the journal is cleared on restart, without advancing the ledger again.
+        let journal = BlockJournal::<CurrentNetwork>::open(Some(path.clone())).unwrap();
+        let recovery = journal.recover(&sample_service(&ledger)).unwrap();
+        assert_eq!(recovery, JournalRecovery::Completed { height: 1 });
+        assert_eq!(ledger.latest_height(), 1);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_recover_stale_block() {
+        let rng = &mut TestRng::default();
+        let (ledger, _) = sample_ledger(rng);
+        // Prepare a block on a different chain, which cannot be applied to the ledger.
+        let (other_ledger, other_private_key) = sample_ledger(rng);
+        let block =
+            other_ledger.prepare_advance_to_next_beacon_block(&other_private_key, vec![], vec![], vec![], rng).unwrap();
+        let path = sample_path("stale");
+
+        BlockJournal::<CurrentNetwork>::open(Some(path.clone()))
+            .unwrap()
+            .write(&block, &sample_transmissions())
+            .unwrap();
+
+        // Ensure the transmissions are returned for reinsertion, and the journal is cleared.
+        let journal = BlockJournal::<CurrentNetwork>::open(Some(path.clone())).unwrap();
+        let recovery = journal.recover(&sample_service(&ledger)).unwrap();
+        assert_eq!(recovery, JournalRecovery::Reinsert { height: 1, transmissions: sample_transmissions() });
+        assert_eq!(ledger.latest_height(), 0);
+        assert!(!path.exists());
+    }
+}
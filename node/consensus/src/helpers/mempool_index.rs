@@ -0,0 +1,337 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkvm::{
+    ledger::block::{Input, Output, Transaction, Transition},
+    prelude::{Address, Argument, Future, Literal, Network, Plaintext, ProgramID},
+};
+
+use indexmap::IndexSet;
+use lru::LruCache;
+use std::{collections::HashMap, num::NonZeroUsize};
+
+/// The attributes of an unconfirmed transaction that are indexed by the memory pool.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransactionAttributes<N: Network> {
+    /// The programs touched by the transitions of the transaction (excluding the fee), or the deployed program.
+    pub program_ids: IndexSet<ProgramID<N>>,
+    /// The addresses in the visible inputs and outputs of the transaction, including the fee payer and the deployer.
+    pub addresses: IndexSet<Address<N>>,
+}
+
+impl<N: Network> TransactionAttributes<N> {
+    /// Returns the indexed attributes of the given transaction.
+    pub fn new(transaction: &Transaction<N>) -> Self {
+        let mut program_ids = IndexSet::new();
+        let mut addresses = IndexSet::new();
+        match transaction {
+            Transaction::Deploy(_, owner, deployment, _) => {
+                program_ids.insert(*deployment.program_id());
+                addresses.insert(owner.address());
+            }
+            Transaction::Execute(_, execution, _) => {
+                program_ids.extend(execution.transitions().map(|transition| *transition.program_id()));
+            }
+            Transaction::Fee(..) => (),
+        }
+        // Note: The fee payer of a public fee is among the visible outputs of the fee transition.
+        for transition in transaction.transitions() {
+            collect_transition_addresses(transition, &mut addresses);
+        }
+        Self { program_ids, addresses }
+    }
+}
+
+/// An index over the cheap attributes of the unconfirmed transactions, computed once as they enter the memory pool,
+/// so that filtered queries do not deserialize every transaction in the memory pool.
+pub struct MempoolIndex<N: Network> {
+    /// The map of `transaction ID` to `attributes` entries.
+    transactions: LruCache<N::TransactionID, TransactionAttributes<N>>,
+    /// The map of `program ID` to `transaction IDs` entries.
+    programs: HashMap<ProgramID<N>, IndexSet<N::TransactionID>>,
+    /// The map of `address` to `transaction IDs` entries.
+    addresses: HashMap<Address<N>, IndexSet<N::TransactionID>>,
+}
+
+impl<N: Network> MempoolIndex<N> {
+    /// Initializes a new memory pool index, tracking up to the given number of transactions.
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self { transactions: LruCache::new(capacity), programs: Default::default(), addresses: Default::default() }
+    }
+
+    /// Returns the number of transactions in the index.
+    pub fn len(&self) -> usize {
+        self.transactions.len()
+    }
+
+    /// Returns `true` if the index is empty.
+    pub fn is_empty(&self) -> bool {
+        self.transactions.is_empty()
+    }
+
+    /// Returns `true` if the index contains the given transaction ID.
+    pub fn contains(&self, transaction_id: &N::TransactionID) -> bool {
+        self.transactions.contains(transaction_id)
+    }
+
+    /// Returns the IDs of the transactions that touch the given program (if any) and involve the given address (if any),
+    /// in the order they were indexed. If neither a program nor an address is given, no transactions are returned.
+    ///
+    /// Note: The cost of this lookup is proportional to the number of matching transactions, not the size of the index.
+    pub fn find(&self, program_id: Option<&ProgramID<N>>, address: Option<&Address<N>>) -> Vec<N::TransactionID> {
+        // Retrieve the candidates from the most selective available map.
+        let candidates = match (program_id, address) {
+            (Some(program_id), _) => self.programs.get(program_id),
+            (None, Some(address)) => self.addresses.get(address),
+            (None, None) => None,
+        };
+        candidates
+            .into_iter()
+            .flatten()
+            .filter(|transaction_id| match self.transactions.peek(*transaction_id) {
+                Some(attributes) => {
+                    program_id.map_or(true, |program_id| attributes.program_ids.contains(program_id))
+                        && address.map_or(true, |address| attributes.addresses.contains(address))
+                }
+                None => false,
+            })
+            .copied()
+            .collect()
+    }
+
+    /// Inserts the given transaction into the index.
+    pub fn insert(&mut self, transaction: &Transaction<N>) {
+        self.insert_attributes(transaction.id(), TransactionAttributes::new(transaction));
+    }
+
+    /// Inserts the given attributes of the given transaction ID into the index.
+    fn insert_attributes(&mut self, transaction_id: N::TransactionID, attributes: TransactionAttributes<N>) {
+        // Index the attributes.
+        for program_id in &attributes.program_ids {
+            self.programs.entry(*program_id).or_default().insert(transaction_id);
+        }
+        for address in &attributes.addresses {
+            self.addresses.entry(*address).or_default().insert(transaction_id);
+        }
+        // Insert the transaction, removing the attributes of an evicted transaction.
+        if let Some((evicted_id, evicted_attributes)) = self.transactions.push(transaction_id, attributes) {
+            if evicted_id != transaction_id {
+                self.remove_attributes(&evicted_id, &evicted_attributes);
+            }
+        }
+    }
+
+    /// Removes the given transaction from the index, returning `true` if it existed.
+    pub fn remove(&mut self, transaction_id: &N::TransactionID) -> bool {
+        match self.transactions.pop(transaction_id) {
+            Some(attributes) => {
+                self.remove_attributes(transaction_id, &attributes);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes the given attributes of the given transaction ID.
+    fn remove_attributes(&mut self, transaction_id: &N::TransactionID, attributes: &TransactionAttributes<N>) {
+        for program_id in &attributes.program_ids {
+            if let Some(transaction_ids) = self.programs.get_mut(program_id) {
+                transaction_ids.shift_remove(transaction_id);
+                if transaction_ids.is_empty() {
+                    self.programs.remove(program_id);
+                }
+            }
+        }
+        for address in &attributes.addresses {
+            if let Some(transaction_ids) = self.addresses.get_mut(address) {
+                transaction_ids.shift_remove(transaction_id);
+                if transaction_ids.is_empty() {
+                    self.addresses.remove(address);
+                }
+            }
+        }
+    }
+}
+
+/// Inserts the addresses in the visible inputs and outputs of the given transition.
+fn collect_transition_addresses<N: Network>(transition: &Transition<N>, addresses: &mut IndexSet<Address<N>>) {
+    for input in transition.inputs() {
+        if let Input::Constant(_, Some(plaintext)) | Input::Public(_, Some(plaintext)) = input {
+            collect_plaintext_addresses(plaintext, addresses);
+        }
+    }
+    for output in transition.outputs() {
+        match output {
+            Output::Constant(_, Some(plaintext)) | Output::Public(_, Some(plaintext)) => {
+                collect_plaintext_addresses(plaintext, addresses)
+            }
+            Output::Future(_, Some(future)) => collect_future_addresses(future, addresses),
+            _ => (),
+        }
+    }
+}
+
+/// Inserts the addresses in the arguments of the given future.
+fn collect_future_addresses<N: Network>(future: &Future<N>, addresses: &mut IndexSet<Address<N>>) {
+    for argument in future.arguments() {
+        match argument {
+            Argument::Plaintext(plaintext) => collect_plaintext_addresses(plaintext, addresses),
+            Argument::Future(future) => collect_future_addresses(future, addresses),
+        }
+    }
+}
+
+/// Inserts the addresses in the given plaintext.
+fn collect_plaintext_addresses<N: Network>(plaintext: &Plaintext<N>, addresses: &mut IndexSet<Address<N>>) {
+    match plaintext {
+        Plaintext::Literal(Literal::Address(address), _) => {
+            addresses.insert(*address);
+        }
+        Plaintext::Literal(..) => (),
+        Plaintext::Struct(members, _) => {
+            members.values().for_each(|member| collect_plaintext_addresses(member, addresses));
+        }
+        Plaintext::Array(elements, _) => {
+            elements.iter().for_each(|element| collect_plaintext_addresses(element, addresses));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkvm::{
+        ledger::ledger_test_helpers::{sample_deployment_transaction, sample_execution_transaction_with_fee},
+        prelude::{Field, PrivateKey, TestRng, Uniform},
+    };
+
+    use std::str::FromStr;
+
+    type CurrentNetwork = snarkvm::prelude::MainnetV0;
+    type TransactionID = <CurrentNetwork as Network>::TransactionID;
+
+    fn sample_index() -> MempoolIndex<CurrentNetwork> {
+        MempoolIndex::new(NonZeroUsize::new(1 << 16).unwrap())
+    }
+
+    fn sample_program_id(name: &str) -> ProgramID<CurrentNetwork> {
+        ProgramID::from_str(&format!("{name}.aleo")).unwrap()
+    }
+
+    #[test]
+    fn test_mempool_index_filters() {
+        let rng = &mut TestRng::default();
+        let mut index = sample_index();
+
+        // Insert a deployment, and an execution that pays a public fee.
+        let deployment = sample_deployment_transaction(true, rng);
+        let execution = sample_execution_transaction_with_fee(false, rng);
+        index.insert(&deployment);
+        index.insert(&execution);
+        assert_eq!(index.len(), 2);
+
+        // Ensure the deployment is found by the deployed program.
+        let deployed_program_id = *deployment.deployment().unwrap().program_id();
+        let deployment_attributes = TransactionAttributes::new(&deployment);
+        assert_eq!(deployment_attributes.program_ids.iter().collect::<Vec<_>>(), vec![&deployed_program_id]);
+        assert!(index.find(Some(&deployed_program_id), None).contains(&deployment.id()));
+
+        // Ensure the execution is found by the programs of its transitions, excluding the fee.
+        let execution_attributes = TransactionAttributes::new(&execution);
+        for transition in execution.execution().unwrap().transitions() {
+            assert!(execution_attributes.program_ids.contains(transition.program_id()));
+            assert!(index.find(Some(transition.program_id()), None).contains(&execution.id()));
+        }
+
+        // Ensure the execution is found by the addresses in its visible inputs and outputs.
+        // Note: The fee payer of the public fee is among the visible outputs of the fee transition.
+        assert!(!execution_attributes.addresses.is_empty());
+        for address in &execution_attributes.addresses {
+            assert!(index.find(None, Some(address)).contains(&execution.id()));
+        }
+        // Ensure the deployment is found by its deployer.
+        let Transaction::Deploy(_, owner, _, _) = &deployment else { panic!("Expected a deployment") };
+        let deployer = owner.address();
+        assert!(index.find(None, Some(&deployer)).contains(&deployment.id()));
+
+        // Ensure a filter on both a program and an address only returns the transactions that match both.
+        assert!(index.find(Some(&deployed_program_id), Some(&deployer)).contains(&deployment.id()));
+        let other_address = Address::try_from(PrivateKey::<CurrentNetwork>::new(rng).unwrap()).unwrap();
+        assert!(index.find(Some(&deployed_program_id), Some(&other_address)).is_empty());
+
+        // Ensure an unknown program, or an empty filter, matches nothing.
+        assert!(index.find(Some(&sample_program_id("unknown_program")), None).is_empty());
+        assert!(index.find(None, None).is_empty());
+
+        // Ensure a removed transaction is no longer found, and its entries are released.
+        assert!(index.remove(&deployment.id()));
+        assert!(!index.remove(&deployment.id()));
+        assert!(!index.contains(&deployment.id()));
+        assert!(!index.find(Some(&deployed_program_id), None).contains(&deployment.id()));
+        assert!(!index.find(None, Some(&deployer)).contains(&deployment.id()));
+        assert_eq!(index.len(), 1);
+    }
+
+    #[test]
+    fn test_mempool_index_lookup_is_independent_of_size() {
+        let rng = &mut TestRng::default();
+        let program_id = sample_program_id("wallet_program");
+
+        // Indexes the given number of unrelated transactions, followed by the given matching transactions.
+        let sample_index_with = |num_unrelated: usize, matching: &[TransactionID], rng: &mut TestRng| {
+            let mut index = sample_index();
+            for i in 0..num_unrelated {
+                let program_ids = IndexSet::from([sample_program_id(&format!("unrelated_{i}"))]);
+                let attributes = TransactionAttributes { program_ids, addresses: IndexSet::new() };
+                index.insert_attributes(Field::rand(rng).into(), attributes);
+            }
+            for transaction_id in matching {
+                let attributes =
+                    TransactionAttributes { program_ids: IndexSet::from([program_id]), addresses: IndexSet::new() };
+                index.insert_attributes(*transaction_id, attributes);
+            }
+            index
+        };
+
+        let matching: Vec<TransactionID> = (0..3).map(|_| Field::rand(rng).into()).collect();
+        for num_unrelated in [10, 10_000] {
+            let index = sample_index_with(num_unrelated, &matching, rng);
+            assert_eq!(index.len(), num_unrelated + matching.len());
+            // Ensure the lookup returns the matching transactions, in insertion order.
+            assert_eq!(index.find(Some(&program_id), None), matching);
+            // Ensure the lookup only visits the matching candidates, regardless of the size of the index.
+            assert_eq!(index.programs.get(&program_id).unwrap().len(), matching.len());
+        }
+    }
+
+    #[test]
+    fn test_mempool_index_eviction() {
+        let rng = &mut TestRng::default();
+        let mut index = MempoolIndex::<CurrentNetwork>::new(NonZeroUsize::new(1).unwrap());
+        let program_ids = [sample_program_id("first_program"), sample_program_id("second_program")];
+        let transaction_ids: Vec<TransactionID> = (0..2).map(|_| Field::rand(rng).into()).collect();
+
+        // Insert two transactions into an index with room for one.
+        for (transaction_id, program_id) in transaction_ids.iter().zip(program_ids) {
+            let attributes =
+                TransactionAttributes { program_ids: IndexSet::from([program_id]), addresses: IndexSet::new() };
+            index.insert_attributes(*transaction_id, attributes);
+        }
+        // Ensure the entries of the evicted transaction are released.
+        assert!(!index.contains(&transaction_ids[0]));
+        assert!(index.find(Some(&program_ids[0]), None).is_empty());
+        assert_eq!(index.find(Some(&program_ids[1]), None), vec![transaction_ids[1]]);
+        assert!(!index.programs.contains_key(&program_ids[0]));
+    }
+}
@@ -0,0 +1,37 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+pub mod confirmed;
+pub use confirmed::*;
+
+pub mod denylist;
+pub use denylist::*;
+
+pub mod journal;
+pub use journal::*;
+
+pub mod mempool_index;
+pub use mempool_index::*;
+
+pub mod outcomes;
+pub use outcomes::*;
+
+pub mod replacement;
+pub use replacement::*;
+
+pub mod timings;
+pub use timings::*;
+
+pub mod solution_quota;
+pub use solution_quota::*;
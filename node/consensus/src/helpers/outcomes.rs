@@ -0,0 +1,322 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkvm::{
+    ledger::block::{Block, ConfirmedTransaction, Rejected},
+    prelude::{Network, Result},
+};
+
+use indexmap::IndexMap;
+use std::num::NonZeroUsize;
+
+/// The number of recent transaction outcomes retained by the journal.
+pub const TRANSACTION_OUTCOMES_CAPACITY: usize = 1 << 13;
+
+/// The reason a transaction is rejected, when it was aborted, and the ledger no longer reports why.
+/// Note: A block records the IDs of its aborted transactions, but not the reason each was aborted.
+pub const ABORTED_REASON: &str = "The transaction was aborted during speculation, so the fee was not consumed";
+
+/// Returns the reason the given confirmed transaction was rejected, or `None` if it was accepted.
+///
+/// The reason names the deployment or execution that the ledger recorded as rejected.
+/// Note: The ledger records the rejected deployment or execution, but not the error it failed to finalize with.
+pub fn rejection_reason<N: Network>(confirmed: &ConfirmedTransaction<N>) -> Option<String> {
+    match confirmed {
+        ConfirmedTransaction::AcceptedDeploy(..) | ConfirmedTransaction::AcceptedExecute(..) => None,
+        ConfirmedTransaction::RejectedDeploy(_, _, rejected, _)
+        | ConfirmedTransaction::RejectedExecute(_, _, rejected, _) => Some(rejected_reason(rejected)),
+    }
+}
+
+/// Returns the reason for the given rejected deployment or execution.
+fn rejected_reason<N: Network>(rejected: &Rejected<N>) -> String {
+    match (rejected.deployment(), rejected.execution()) {
+        (Some(deployment), _) => {
+            format!("The deployment of '{}' failed to finalize, so only the fee was consumed", deployment.program_id())
+        }
+        // Note: The last transition of an execution is the function that was called.
+        (_, Some(execution)) => match execution.transitions().last() {
+            Some(transition) => format!(
+                "The execution of '{}/{}' failed to finalize, so only the fee was consumed",
+                transition.program_id(),
+                transition.function_name()
+            ),
+            None => "The execution failed to finalize, so only the fee was consumed".to_string(),
+        },
+        (None, None) => "The transaction failed to finalize, so only the fee was consumed".to_string(),
+    }
+}
+
+/// The outcome of an unconfirmed transaction that was received by this node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TransactionOutcome {
+    /// The transaction is awaiting inclusion in a block.
+    Pending,
+    /// The transaction was accepted in the block at the given height.
+    Accepted { height: u32 },
+    /// The transaction was rejected in the block at the given height, for the given reason.
+    Rejected { height: u32, reason: String },
+    /// The transaction was evicted from the memory pool, for the given cause.
+    Evicted { cause: String },
+}
+
+/// A bounded journal of the outcomes of recent unconfirmed transactions, keyed by transaction ID.
+///
+/// The journal is in-memory only, and evicts the least-recently updated outcome once it reaches capacity.
+pub struct TransactionOutcomes<N: Network> {
+    /// The map of `transaction ID` to `outcome` entries, ordered from least to most recently updated.
+    outcomes: IndexMap<N::TransactionID, TransactionOutcome>,
+    /// The maximum number of outcomes to retain.
+    capacity: NonZeroUsize,
+}
+
+impl<N: Network> TransactionOutcomes<N> {
+    /// Initializes a new journal, retaining up to the given number of outcomes.
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self { outcomes: Default::default(), capacity }
+    }
+
+    /// Returns the maximum number of outcomes retained by the journal.
+    pub const fn capacity(&self) -> usize {
+        self.capacity.get()
+    }
+
+    /// Returns the number of outcomes in the journal.
+    pub fn len(&self) -> usize {
+        self.outcomes.len()
+    }
+
+    /// Returns `true` if the journal is empty.
+    pub fn is_empty(&self) -> bool {
+        self.outcomes.is_empty()
+    }
+
+    /// Returns the outcome of the given transaction ID, if it is in the journal.
+    pub fn get(&self, transaction_id: &N::TransactionID) -> Option<&TransactionOutcome> {
+        self.outcomes.get(transaction_id)
+    }
+
+    /// Records the given outcome for the given transaction ID, evicting the oldest outcome if the journal is full.
+    pub fn insert(&mut self, transaction_id: N::TransactionID, outcome: TransactionOutcome) {
+        // Move the transaction to the back of the journal, as the most recently updated outcome.
+        self.outcomes.shift_remove(&transaction_id);
+        self.outcomes.insert(transaction_id, outcome);
+        // Evict the oldest outcomes, if the journal is over capacity.
+        while self.outcomes.len() > self.capacity.get() {
+            self.outcomes.shift_remove_index(0);
+        }
+    }
+
+    /// Records the outcomes of the confirmed and aborted transactions in the given block.
+    pub fn insert_block(&mut self, block: &Block<N>) -> Result<()> {
+        let height = block.height();
+        // Record the confirmed transactions.
+        for confirmed in block.transactions().iter() {
+            // Note: A rejected transaction is confirmed under the ID of its fee transaction,
+            // so the outcome is recorded under the ID of the transaction that was submitted.
+            let transaction_id = confirmed.to_unconfirmed_transaction_id()?;
+            let outcome = match rejection_reason(confirmed) {
+                None => TransactionOutcome::Accepted { height },
+                Some(reason) => TransactionOutcome::Rejected { height, reason },
+            };
+            self.insert(transaction_id, outcome);
+        }
+        // Record the aborted transactions.
+        for transaction_id in block.aborted_transaction_ids() {
+            self.insert(*transaction_id, TransactionOutcome::Rejected { height, reason: ABORTED_REASON.to_string() });
+        }
+        Ok(())
+    }
+
+    /// Records the given reason for the transaction that was aborted in the block at the given height,
+    /// unless its outcome was updated since.
+    pub fn set_aborted_reason(&mut self, transaction_id: &N::TransactionID, height: u32, reason: String) {
+        if let Some(TransactionOutcome::Rejected { height: aborted_height, reason: aborted_reason }) =
+            self.outcomes.get_mut(transaction_id)
+        {
+            if *aborted_height == height && aborted_reason == ABORTED_REASON {
+                *aborted_reason = reason;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkos_node_bft_ledger_service::test_helpers::sample_ledger;
+    use snarkvm::{
+        ledger::{block::Transaction, store::helpers::memory::ConsensusMemory, Ledger},
+        prelude::{Address, Field, PrivateKey, TestRng, Uniform, Value},
+    };
+
+    use std::str::FromStr;
+
+    type CurrentNetwork = snarkvm::prelude::MainnetV0;
+    type TransactionID = <CurrentNetwork as Network>::TransactionID;
+
+    fn sample_transaction_ids(rng: &mut TestRng, num: usize) -> Vec<TransactionID> {
+        (0..num).map(|_| Field::rand(rng).into()).collect()
+    }
+
+    /// Returns a public transfer of the given amount to the sender, paying the fee publicly.
+    fn sample_transfer(
+        ledger: &Ledger<CurrentNetwork, ConsensusMemory<CurrentNetwork>>,
+        private_key: &PrivateKey<CurrentNetwork>,
+        amount: u64,
+        rng: &mut TestRng,
+    ) -> Transaction<CurrentNetwork> {
+        let address = Address::try_from(private_key).unwrap();
+        let inputs =
+            [Value::from_str(&address.to_string()).unwrap(), Value::from_str(&format!("{amount}u64")).unwrap()];
+        let locator = ("credits.aleo", "transfer_public");
+        ledger.vm().execute(private_key, locator, inputs.into_iter(), None, 0, None, rng).unwrap()
+    }
+
+    #[test]
+    fn test_insert_updates_outcome() {
+        let rng = &mut TestRng::default();
+        let mut outcomes = TransactionOutcomes::<CurrentNetwork>::new(NonZeroUsize::new(4).unwrap());
+        let ids = sample_transaction_ids(rng, 3);
+
+        // Each outcome path is recorded, and a later outcome replaces an earlier one.
+        outcomes.insert(ids[0], TransactionOutcome::Pending);
+        outcomes.insert(ids[1], TransactionOutcome::Pending);
+        outcomes.insert(ids[2], TransactionOutcome::Evicted { cause: "replaced".to_string() });
+        outcomes.insert(ids[0], TransactionOutcome::Accepted { height: 5 });
+        outcomes.insert(ids[1], TransactionOutcome::Rejected { height: 5, reason: "aborted".to_string() });
+
+        assert_eq!(outcomes.len(), 3);
+        assert_eq!(outcomes.get(&ids[0]), Some(&TransactionOutcome::Accepted { height: 5 }));
+        assert_eq!(
+            outcomes.get(&ids[1]),
+            Some(&TransactionOutcome::Rejected { height: 5, reason: "aborted".to_string() })
+        );
+        assert_eq!(outcomes.get(&ids[2]), Some(&TransactionOutcome::Evicted { cause: "replaced".to_string() }));
+    }
+
+    #[test]
+    fn test_insert_evicts_oldest() {
+        let rng = &mut TestRng::default();
+        let mut outcomes = TransactionOutcomes::<CurrentNetwork>::new(NonZeroUsize::new(4).unwrap());
+        let ids = sample_transaction_ids(rng, 6);
+
+        // Fill the journal to capacity.
+        for id in &ids[..4] {
+            outcomes.insert(*id, TransactionOutcome::Pending);
+        }
+        assert_eq!(outcomes.len(), outcomes.capacity());
+
+        // Updating an outcome moves it to the back of the journal.
+        outcomes.insert(ids[0], TransactionOutcome::Accepted { height: 1 });
+        // Inserting past capacity evicts the least-recently updated outcomes.
+        outcomes.insert(ids[4], TransactionOutcome::Pending);
+        outcomes.insert(ids[5], TransactionOutcome::Pending);
+
+        assert_eq!(outcomes.len(), 4);
+        assert!(outcomes.get(&ids[1]).is_none());
+        assert!(outcomes.get(&ids[2]).is_none());
+        assert_eq!(outcomes.get(&ids[0]), Some(&TransactionOutcome::Accepted { height: 1 }));
+        assert!(outcomes.get(&ids[3]).is_some());
+        assert!(outcomes.get(&ids[5]).is_some());
+    }
+
+    #[test]
+    fn test_reinsert_after_eviction() {
+        let rng = &mut TestRng::default();
+        let mut outcomes = TransactionOutcomes::<CurrentNetwork>::new(NonZeroUsize::new(2).unwrap());
+        let ids = sample_transaction_ids(rng, 3);
+
+        // Evict the first outcome from the journal.
+        outcomes.insert(ids[0], TransactionOutcome::Pending);
+        outcomes.insert(ids[1], TransactionOutcome::Pending);
+        outcomes.insert(ids[2], TransactionOutcome::Pending);
+        assert!(outcomes.get(&ids[0]).is_none());
+
+        // Ensure a transaction that is received again is recorded again, as the most recently updated outcome.
+        outcomes.insert(ids[0], TransactionOutcome::Pending);
+        assert_eq!(outcomes.get(&ids[0]), Some(&TransactionOutcome::Pending));
+        assert!(outcomes.get(&ids[1]).is_none());
+        assert!(outcomes.get(&ids[2]).is_some());
+
+        // Ensure a transaction that is reinserted after a failed block returns to pending.
+        outcomes.insert(ids[2], TransactionOutcome::Evicted { cause: "failed block".to_string() });
+        outcomes.insert(ids[2], TransactionOutcome::Pending);
+        assert_eq!(outcomes.get(&ids[2]), Some(&TransactionOutcome::Pending));
+        assert_eq!(outcomes.len(), 2);
+    }
+
+    #[test]
+    fn test_insert_block() {
+        let rng = &mut TestRng::default();
+        let (ledger, private_key) = sample_ledger(rng);
+
+        // Sample a transfer that is accepted, a transfer beyond the balance that fails to finalize,
+        // and a transfer from an account that cannot pay its fee, which is aborted.
+        let accepted = sample_transfer(&ledger, &private_key, 1, rng);
+        let rejected = sample_transfer(&ledger, &private_key, u64::MAX, rng);
+        let aborted = sample_transfer(&ledger, &PrivateKey::new(rng).unwrap(), 1, rng);
+        let transactions = vec![accepted.clone(), rejected.clone(), aborted.clone()];
+        let block =
+            ledger.prepare_advance_to_next_beacon_block(&private_key, vec![], vec![], transactions, rng).unwrap();
+        assert_eq!(block.aborted_transaction_ids(), &vec![aborted.id()]);
+
+        // Record the transactions as pending, and then the outcomes of the block.
+        let mut outcomes = TransactionOutcomes::<CurrentNetwork>::new(NonZeroUsize::new(4).unwrap());
+        for transaction in [&accepted, &rejected, &aborted] {
+            outcomes.insert(transaction.id(), TransactionOutcome::Pending);
+        }
+        outcomes.insert_block(&block).unwrap();
+        assert_eq!(outcomes.len(), 3);
+
+        // Ensure each outcome is recorded under the ID that was submitted.
+        assert_eq!(outcomes.get(&accepted.id()), Some(&TransactionOutcome::Accepted { height: 1 }));
+        // Ensure the rejection names the execution that the ledger recorded as rejected.
+        let reason = "The execution of 'credits.aleo/transfer_public' failed to finalize, so only the fee was consumed";
+        assert_eq!(
+            outcomes.get(&rejected.id()),
+            Some(&TransactionOutcome::Rejected { height: 1, reason: reason.to_string() })
+        );
+        assert_eq!(
+            outcomes.get(&aborted.id()),
+            Some(&TransactionOutcome::Rejected { height: 1, reason: ABORTED_REASON.to_string() })
+        );
+    }
+
+    #[test]
+    fn test_set_aborted_reason() {
+        let rng = &mut TestRng::default();
+        let mut outcomes = TransactionOutcomes::<CurrentNetwork>::new(NonZeroUsize::new(4).unwrap());
+        let ids = sample_transaction_ids(rng, 3);
+
+        outcomes.insert(ids[0], TransactionOutcome::Rejected { height: 5, reason: ABORTED_REASON.to_string() });
+        outcomes.insert(ids[1], TransactionOutcome::Rejected { height: 5, reason: "finalize".to_string() });
+        outcomes.insert(ids[2], TransactionOutcome::Pending);
+
+        // Ensure the reason of an aborted transaction is recorded.
+        outcomes.set_aborted_reason(&ids[0], 5, "insufficient fee".to_string());
+        assert_eq!(
+            outcomes.get(&ids[0]),
+            Some(&TransactionOutcome::Rejected { height: 5, reason: "insufficient fee".to_string() })
+        );
+        // Ensure the outcomes that were not aborted in the block are unchanged.
+        outcomes.set_aborted_reason(&ids[1], 5, "insufficient fee".to_string());
+        outcomes.set_aborted_reason(&ids[2], 5, "insufficient fee".to_string());
+        assert_eq!(
+            outcomes.get(&ids[1]),
+            Some(&TransactionOutcome::Rejected { height: 5, reason: "finalize".to_string() })
+        );
+        assert_eq!(outcomes.get(&ids[2]), Some(&TransactionOutcome::Pending));
+    }
+}
@@ -0,0 +1,498 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkos_node_bft::helpers::fmt_id;
+use snarkvm::{
+    ledger::block::Transaction,
+    prelude::{bail, Field, Network, Result},
+};
+
+use indexmap::IndexSet;
+use lru::LruCache;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    num::NonZeroUsize,
+    str::FromStr,
+};
+
+/// The default minimum fee increment (in microcredits) required to replace an unconfirmed transaction.
+pub const DEFAULT_REPLACEMENT_FEE_INCREMENT: u64 = 1_000;
+
+/// The policy for a transaction that conflicts with unconfirmed transactions it cannot replace.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// The transaction is rejected.
+    #[default]
+    Reject,
+    /// The transaction is queued, and enters the memory pool once the conflicting transactions are evicted.
+    Queue,
+}
+
+impl FromStr for ConflictPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "reject" => Ok(Self::Reject),
+            "queue" => Ok(Self::Queue),
+            _ => bail!("Invalid conflict policy '{s}' - expected 'reject' or 'queue'"),
+        }
+    }
+}
+
+/// The decision of the memory pool on an unconfirmed transaction, with respect to the unconfirmed transactions it conflicts with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConflictDecision<N: Network> {
+    /// The transaction does not conflict with any unconfirmed transaction.
+    Accepted,
+    /// The transaction replaced the given conflicting transactions, with a higher fee.
+    Replaced(Vec<N::TransactionID>),
+    /// The transaction is queued behind the given conflicting transactions.
+    Queued(Vec<N::TransactionID>),
+}
+
+/// The error returned when a transaction conflicts with unconfirmed transactions that it cannot replace.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransactionConflict<N: Network> {
+    /// The IDs of the conflicting unconfirmed transactions.
+    pub conflicting_ids: Vec<N::TransactionID>,
+    /// The reason the conflicting transactions cannot be replaced.
+    pub reason: String,
+}
+
+impl<N: Network> fmt::Display for TransactionConflict<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ids = self.conflicting_ids.iter().map(|id| format!("'{id}'")).collect::<Vec<_>>().join(", ");
+        write!(f, "The transaction conflicts with unconfirmed transaction {ids} - {}", self.reason)
+    }
+}
+
+impl<N: Network> std::error::Error for TransactionConflict<N> {}
+
+/// An index over the serial numbers spent by unconfirmed transactions,
+/// used to detect conflicting transactions and to apply replace-by-fee semantics.
+pub struct ReplacementIndex<N: Network> {
+    /// The map of `transaction ID` to `(fee, serial numbers)` entries.
+    transactions: LruCache<N::TransactionID, (u64, Vec<Field<N>>)>,
+    /// The map of `serial number` to `transaction ID` entries.
+    serial_numbers: HashMap<Field<N>, N::TransactionID>,
+}
+
+impl<N: Network> ReplacementIndex<N> {
+    /// Initializes a new replacement index, tracking up to the given number of transactions.
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self { transactions: LruCache::new(capacity), serial_numbers: Default::default() }
+    }
+
+    /// Returns the number of transactions in the index.
+    pub fn len(&self) -> usize {
+        self.transactions.len()
+    }
+
+    /// Returns `true` if the index is empty.
+    pub fn is_empty(&self) -> bool {
+        self.transactions.is_empty()
+    }
+
+    /// Returns `true` if the index contains the given transaction ID.
+    pub fn contains(&self, transaction_id: &N::TransactionID) -> bool {
+        self.transactions.contains(transaction_id)
+    }
+
+    /// Returns the unconfirmed transaction IDs that spend any of the given serial numbers.
+    pub fn conflicts<'a>(&self, serial_numbers: impl IntoIterator<Item = &'a Field<N>>) -> IndexSet<N::TransactionID> {
+        serial_numbers.into_iter().filter_map(|serial_number| self.serial_numbers.get(serial_number).copied()).collect()
+    }
+
+    /// Checks if a transaction with the given serial numbers and fee may enter the memory pool,
+    /// returning the IDs of the unconfirmed transactions it would replace.
+    ///
+    /// A replacement is only permitted if the fee is at least the sum of the fees of the conflicting
+    /// transactions plus the given increment, and none of the conflicting transactions are in flight.
+    pub fn check_replacement(
+        &self,
+        serial_numbers: &[Field<N>],
+        fee: u64,
+        increment: u64,
+        is_in_flight: impl Fn(&N::TransactionID) -> bool,
+    ) -> Result<IndexSet<N::TransactionID>> {
+        // Retrieve the conflicting transactions.
+        let conflicts = self.conflicts(serial_numbers);
+        // If there are no conflicts, return early.
+        if conflicts.is_empty() {
+            return Ok(conflicts);
+        }
+        // Returns the error for conflicting transactions that cannot be replaced, for the given reason.
+        let conflict =
+            |reason: String| TransactionConflict::<N> { conflicting_ids: conflicts.iter().copied().collect(), reason };
+        // Compute the minimum fee required to replace the conflicting transactions.
+        let mut required_fee = increment;
+        for transaction_id in &conflicts {
+            // Ensure the conflicting transaction is not in flight.
+            if is_in_flight(transaction_id) {
+                let reason = format!("transaction '{}' is already in a batch", fmt_id(transaction_id));
+                return Err(conflict(reason).into());
+            }
+            // Add the fee of the conflicting transaction.
+            let old_fee = self.transactions.peek(transaction_id).map(|(fee, _)| *fee).unwrap_or_default();
+            required_fee = required_fee.saturating_add(old_fee);
+        }
+        // Ensure the fee is sufficient to replace the conflicting transactions.
+        if fee < required_fee {
+            let reason = format!("insufficient fee to replace it - found {fee}, expected at least {required_fee}");
+            return Err(conflict(reason).into());
+        }
+        Ok(conflicts)
+    }
+
+    /// Inserts the given transaction into the index.
+    pub fn insert(&mut self, transaction_id: N::TransactionID, fee: u64, serial_numbers: Vec<Field<N>>) {
+        // Index the serial numbers.
+        for serial_number in &serial_numbers {
+            self.serial_numbers.insert(*serial_number, transaction_id);
+        }
+        // Insert the transaction, removing the serial numbers of an evicted transaction.
+        if let Some((evicted_id, (_, evicted_serial_numbers))) =
+            self.transactions.push(transaction_id, (fee, serial_numbers))
+        {
+            if evicted_id != transaction_id {
+                self.remove_serial_numbers(&evicted_id, &evicted_serial_numbers);
+            }
+        }
+    }
+
+    /// Removes the given transaction from the index, returning `true` if it existed.
+    pub fn remove(&mut self, transaction_id: &N::TransactionID) -> bool {
+        match self.transactions.pop(transaction_id) {
+            Some((_, serial_numbers)) => {
+                self.remove_serial_numbers(transaction_id, &serial_numbers);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes the transactions that spend any of the given serial numbers from the index, if they are no longer
+    /// pending, e.g. as the worker dropped them. Returns the IDs of the removed transactions.
+    pub fn remove_stale<'a>(
+        &mut self,
+        serial_numbers: impl IntoIterator<Item = &'a Field<N>>,
+        is_pending: impl Fn(&N::TransactionID) -> bool,
+    ) -> Vec<N::TransactionID> {
+        let stale = self.conflicts(serial_numbers).into_iter().filter(|id| !is_pending(id)).collect::<Vec<_>>();
+        for transaction_id in &stale {
+            self.remove(transaction_id);
+        }
+        stale
+    }
+
+    /// Removes the transactions that spend any of the given (confirmed) serial numbers from the index.
+    pub fn remove_spent<'a>(&mut self, serial_numbers: impl IntoIterator<Item = &'a Field<N>>) {
+        for transaction_id in self.conflicts(serial_numbers) {
+            self.remove(&transaction_id);
+        }
+    }
+
+    /// Removes the given serial numbers, if they are still mapped to the given transaction ID.
+    fn remove_serial_numbers(&mut self, transaction_id: &N::TransactionID, serial_numbers: &[Field<N>]) {
+        for serial_number in serial_numbers {
+            if self.serial_numbers.get(serial_number) == Some(transaction_id) {
+                self.serial_numbers.remove(serial_number);
+            }
+        }
+    }
+}
+
+/// A queue of the transactions held back behind the unconfirmed transactions they conflict with,
+/// under [`ConflictPolicy::Queue`].
+pub struct ConflictQueue<N: Network, T = Transaction<N>> {
+    /// The map of `transaction ID` to `(transaction, serial numbers, conflicting transaction IDs)` entries.
+    transactions: LruCache<N::TransactionID, (T, Vec<Field<N>>, IndexSet<N::TransactionID>)>,
+}
+
+impl<N: Network, T> ConflictQueue<N, T> {
+    /// Initializes a new conflict queue, holding up to the given number of transactions.
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self { transactions: LruCache::new(capacity) }
+    }
+
+    /// Returns the number of transactions in the queue.
+    pub fn len(&self) -> usize {
+        self.transactions.len()
+    }
+
+    /// Returns `true` if the queue is empty.
+    pub fn is_empty(&self) -> bool {
+        self.transactions.is_empty()
+    }
+
+    /// Returns `true` if the queue contains the given transaction ID.
+    pub fn contains(&self, transaction_id: &N::TransactionID) -> bool {
+        self.transactions.contains(transaction_id)
+    }
+
+    /// Queues the given transaction behind the given conflicting transactions.
+    /// Returns the ID of the transaction that was dropped from the full queue, if any.
+    pub fn insert(
+        &mut self,
+        transaction_id: N::TransactionID,
+        transaction: T,
+        serial_numbers: Vec<Field<N>>,
+        conflicts: IndexSet<N::TransactionID>,
+    ) -> Option<N::TransactionID> {
+        match self.transactions.push(transaction_id, (transaction, serial_numbers, conflicts)) {
+            Some((dropped_id, _)) if dropped_id != transaction_id => Some(dropped_id),
+            _ => None,
+        }
+    }
+
+    /// Removes the given transaction from the queue, returning `true` if it existed.
+    pub fn remove(&mut self, transaction_id: &N::TransactionID) -> bool {
+        self.transactions.pop(transaction_id).is_some()
+    }
+
+    /// Removes and returns the queued transactions that no longer conflict with any unconfirmed transaction,
+    /// once the given transaction was evicted from the memory pool.
+    pub fn release(&mut self, evicted_id: &N::TransactionID) -> Vec<(N::TransactionID, T)> {
+        // Determine the queued transactions that were only waiting on the evicted transaction.
+        let mut released = vec![];
+        for (transaction_id, (_, _, conflicts)) in self.transactions.iter_mut() {
+            if conflicts.shift_remove(evicted_id) && conflicts.is_empty() {
+                released.push(*transaction_id);
+            }
+        }
+        // Remove the released transactions from the queue.
+        released
+            .into_iter()
+            .filter_map(|transaction_id| self.transactions.pop(&transaction_id).map(|(tx, ..)| (transaction_id, tx)))
+            .collect()
+    }
+
+    /// Removes the queued transactions that spend any of the given (confirmed) serial numbers, as they can no
+    /// longer be valid, and returns their IDs.
+    pub fn remove_spent<'a>(
+        &mut self,
+        serial_numbers: impl IntoIterator<Item = &'a Field<N>>,
+    ) -> Vec<N::TransactionID> {
+        let serial_numbers = serial_numbers.into_iter().collect::<HashSet<_>>();
+        // Determine the queued transactions that spend a confirmed serial number.
+        let spent = self
+            .transactions
+            .iter()
+            .filter(|(_, (_, spends, _))| spends.iter().any(|serial_number| serial_numbers.contains(serial_number)))
+            .map(|(transaction_id, _)| *transaction_id)
+            .collect::<Vec<_>>();
+        // Remove the spent transactions from the queue.
+        for transaction_id in &spent {
+            self.transactions.pop(transaction_id);
+        }
+        spent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkvm::prelude::{TestRng, Uniform};
+
+    type CurrentNetwork = snarkvm::prelude::MainnetV0;
+    type TransactionID = <CurrentNetwork as Network>::TransactionID;
+
+    const INCREMENT: u64 = DEFAULT_REPLACEMENT_FEE_INCREMENT;
+
+    fn sample_index() -> ReplacementIndex<CurrentNetwork> {
+        ReplacementIndex::new(NonZeroUsize::new(16).unwrap())
+    }
+
+    fn sample_serial_numbers(rng: &mut TestRng, num: usize) -> Vec<Field<CurrentNetwork>> {
+        (0..num).map(|_| Field::rand(rng)).collect()
+    }
+
+    #[test]
+    fn test_replacement_succeeds() {
+        let rng = &mut TestRng::default();
+        let mut index = sample_index();
+
+        // Insert the original transaction.
+        let old_id: TransactionID = Field::rand(rng).into();
+        let serial_numbers = sample_serial_numbers(rng, 2);
+        index.insert(old_id, 10_000, serial_numbers.clone());
+
+        // A transaction that spends one of the same records, with a sufficient fee bump, replaces the original.
+        let new_id: TransactionID = Field::rand(rng).into();
+        let new_serial_numbers = vec![serial_numbers[1], Field::rand(rng)];
+        let replaced = index.check_replacement(&new_serial_numbers, 10_000 + INCREMENT, INCREMENT, |_| false).unwrap();
+        assert_eq!(replaced.into_iter().collect::<Vec<_>>(), vec![old_id]);
+
+        // Apply the replacement.
+        assert!(index.remove(&old_id));
+        index.insert(new_id, 10_000 + INCREMENT, new_serial_numbers.clone());
+        assert!(!index.contains(&old_id));
+        assert!(index.contains(&new_id));
+        // The serial number that is no longer spent is released.
+        assert!(index.conflicts(&serial_numbers[..1]).is_empty());
+        assert_eq!(index.conflicts(&new_serial_numbers).len(), 1);
+    }
+
+    #[test]
+    fn test_double_spend_is_rejected() {
+        let rng = &mut TestRng::default();
+        let mut index = sample_index();
+
+        // Submit two transactions that spend the same record, with the same fee.
+        let first_id: TransactionID = Field::rand(rng).into();
+        let second_id: TransactionID = Field::rand(rng).into();
+        let serial_numbers = sample_serial_numbers(rng, 1);
+        assert!(index.check_replacement(&serial_numbers, 10_000, INCREMENT, |_| false).unwrap().is_empty());
+        index.insert(first_id, 10_000, serial_numbers.clone());
+        let error = index.check_replacement(&serial_numbers, 10_000, INCREMENT, |_| false).unwrap_err();
+
+        // Ensure the second transaction is rejected with a conflict naming the first transaction.
+        let conflict = error.downcast_ref::<TransactionConflict<CurrentNetwork>>().unwrap();
+        assert_eq!(conflict.conflicting_ids, vec![first_id]);
+        assert!(error.to_string().contains(&first_id.to_string()), "{error}");
+        // Ensure exactly the first transaction is pending.
+        assert!(index.contains(&first_id));
+        assert!(!index.contains(&second_id));
+        assert_eq!(index.len(), 1);
+    }
+
+    #[test]
+    fn test_conflict_queue() {
+        let rng = &mut TestRng::default();
+        let mut queue = ConflictQueue::<CurrentNetwork, &str>::new(NonZeroUsize::new(2).unwrap());
+
+        let ids = (0..4).map(|_| Field::rand(rng).into()).collect::<Vec<TransactionID>>();
+        let serial_numbers = (0..2).map(|_| sample_serial_numbers(rng, 1)).collect::<Vec<_>>();
+
+        // Queue a transaction behind two conflicting transactions.
+        let conflicts = IndexSet::from([ids[0], ids[1]]);
+        assert_eq!(queue.insert(ids[2], "second", serial_numbers[0].clone(), conflicts), None);
+        // Queue another transaction behind a single conflicting transaction.
+        assert_eq!(queue.insert(ids[3], "third", serial_numbers[1].clone(), IndexSet::from([ids[1]])), None);
+
+        // The queued transactions are released once all of their conflicting transactions are evicted.
+        assert!(queue.release(&ids[0]).is_empty());
+        assert_eq!(queue.release(&ids[1]).into_iter().map(|(_, tx)| tx).collect::<Vec<_>>(), vec!["second", "third"]);
+        assert!(queue.is_empty());
+
+        // A queued transaction is dropped once its record is spent by a confirmed transaction.
+        queue.insert(ids[2], "second", serial_numbers[0].clone(), IndexSet::from([ids[0]]));
+        assert_eq!(queue.remove_spent(&serial_numbers[0]), vec![ids[2]]);
+        assert!(!queue.contains(&ids[2]));
+
+        // The least-recently queued transaction is dropped from a full queue.
+        queue.insert(ids[0], "first", vec![], IndexSet::from([ids[3]]));
+        queue.insert(ids[1], "second", vec![], IndexSet::from([ids[3]]));
+        assert_eq!(queue.insert(ids[2], "third", vec![], IndexSet::from([ids[3]])), Some(ids[0]));
+        assert_eq!(queue.len(), 2);
+    }
+
+    #[test]
+    fn test_replacement_insufficient_fee() {
+        let rng = &mut TestRng::default();
+        let mut index = sample_index();
+
+        // Insert the original transaction.
+        let old_id: TransactionID = Field::rand(rng).into();
+        let serial_numbers = sample_serial_numbers(rng, 1);
+        index.insert(old_id, 10_000, serial_numbers.clone());
+
+        // A fee bump below the increment is rejected.
+        assert!(index.check_replacement(&serial_numbers, 10_000, INCREMENT, |_| false).is_err());
+        assert!(index.check_replacement(&serial_numbers, 10_000 + INCREMENT - 1, INCREMENT, |_| false).is_err());
+        // A lower fee is rejected.
+        assert!(index.check_replacement(&serial_numbers, 1, INCREMENT, |_| false).is_err());
+        // The original transaction is retained.
+        assert!(index.contains(&old_id));
+    }
+
+    #[test]
+    fn test_replacement_of_multiple_conflicts() {
+        let rng = &mut TestRng::default();
+        let mut index = sample_index();
+
+        // Insert two original transactions.
+        let serial_numbers_1 = sample_serial_numbers(rng, 1);
+        let serial_numbers_2 = sample_serial_numbers(rng, 1);
+        index.insert(Field::rand(rng).into(), 5_000, serial_numbers_1.clone());
+        index.insert(Field::rand(rng).into(), 7_000, serial_numbers_2.clone());
+
+        // A transaction conflicting with both must pay for both.
+        let serial_numbers = [serial_numbers_1[0], serial_numbers_2[0]];
+        assert!(index.check_replacement(&serial_numbers, 7_000 + INCREMENT, INCREMENT, |_| false).is_err());
+        let replaced = index.check_replacement(&serial_numbers, 12_000 + INCREMENT, INCREMENT, |_| false).unwrap();
+        assert_eq!(replaced.len(), 2);
+    }
+
+    #[test]
+    fn test_replacement_in_flight() {
+        let rng = &mut TestRng::default();
+        let mut index = sample_index();
+
+        // Insert the original transaction.
+        let old_id: TransactionID = Field::rand(rng).into();
+        let serial_numbers = sample_serial_numbers(rng, 1);
+        index.insert(old_id, 10_000, serial_numbers.clone());
+
+        // A transaction that is in the in-flight batch proposal cannot be replaced, regardless of the fee.
+        assert!(index.check_replacement(&serial_numbers, u64::MAX, INCREMENT, |id| *id == old_id).is_err());
+        assert!(index.contains(&old_id));
+    }
+
+    #[test]
+    fn test_remove_spent_and_eviction() {
+        let rng = &mut TestRng::default();
+        let mut index = ReplacementIndex::<CurrentNetwork>::new(NonZeroUsize::new(2).unwrap());
+
+        let serial_numbers = (0..3).map(|_| sample_serial_numbers(rng, 1)).collect::<Vec<_>>();
+        let ids = (0..3).map(|_| Field::rand(rng).into()).collect::<Vec<TransactionID>>();
+
+        // Insert the first two transactions.
+        index.insert(ids[0], 1, serial_numbers[0].clone());
+        index.insert(ids[1], 1, serial_numbers[1].clone());
+        // Remove the first transaction, as if it were confirmed.
+        index.remove_spent(&serial_numbers[0]);
+        assert!(!index.contains(&ids[0]));
+        assert_eq!(index.len(), 1);
+
+        // Fill the index beyond capacity, and ensure the evicted serial numbers are released.
+        index.insert(ids[0], 1, serial_numbers[0].clone());
+        index.insert(ids[2], 1, serial_numbers[2].clone());
+        assert_eq!(index.len(), 2);
+        assert!(!index.contains(&ids[1]));
+        assert!(index.conflicts(&serial_numbers[1]).is_empty());
+    }
+
+    #[test]
+    fn test_remove_stale() {
+        let rng = &mut TestRng::default();
+        let mut index = sample_index();
+
+        let serial_numbers = sample_serial_numbers(rng, 2);
+        let (dropped_id, pending_id): (TransactionID, TransactionID) =
+            (Field::rand(rng).into(), Field::rand(rng).into());
+        index.insert(dropped_id, 10_000, vec![serial_numbers[0]]);
+        index.insert(pending_id, 10_000, vec![serial_numbers[1]]);
+
+        // Ensure only the transaction that the worker dropped is removed, and its serial number is released.
+        let stale = index.remove_stale(&serial_numbers, |transaction_id| *transaction_id == pending_id);
+        assert_eq!(stale, vec![dropped_id]);
+        assert!(!index.contains(&dropped_id));
+        assert!(index.contains(&pending_id));
+        // Ensure a transaction that reuses the released serial number no longer conflicts.
+        assert!(index.check_replacement(&[serial_numbers[0]], 0, INCREMENT, |_| false).unwrap().is_empty());
+    }
+}
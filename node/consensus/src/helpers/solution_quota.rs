@@ -0,0 +1,198 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkvm::{
+    ledger::puzzle::SolutionID,
+    prelude::{bail, Address, Network, Result},
+};
+
+use std::collections::{hash_map::Entry, HashMap};
+
+/// The default maximum number of pending solutions from a single prover address.
+pub const DEFAULT_MAX_SOLUTIONS_PER_ADDRESS: usize = 16;
+
+/// The quota of pending solutions for each prover address in the memory pool, so that a single prover address
+/// cannot crowd out the solutions of other provers for the current epoch.
+pub struct SolutionQuota<N: Network> {
+    /// The maximum number of pending solutions from a single prover address.
+    limit: usize,
+    /// The epoch hash of the pending solutions, if it is known.
+    epoch_hash: Option<N::BlockHash>,
+    /// The map of `solution ID` to `prover address` entries.
+    solutions: HashMap<SolutionID<N>, Address<N>>,
+    /// The map of `prover address` to `number of pending solutions` entries.
+    counts: HashMap<Address<N>, usize>,
+}
+
+impl<N: Network> Default for SolutionQuota<N> {
+    /// Initializes a new solution quota, with the default limit.
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_SOLUTIONS_PER_ADDRESS)
+    }
+}
+
+impl<N: Network> SolutionQuota<N> {
+    /// Initializes a new solution quota, with the given maximum number of pending solutions per prover address.
+    pub fn new(limit: usize) -> Self {
+        Self { limit, epoch_hash: None, solutions: Default::default(), counts: Default::default() }
+    }
+
+    /// Returns the maximum number of pending solutions from a single prover address.
+    pub const fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Sets the maximum number of pending solutions from a single prover address.
+    /// Note: Prover addresses above the new limit keep their pending solutions, but are refused new ones.
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+    }
+
+    /// Returns the number of pending solutions.
+    pub fn len(&self) -> usize {
+        self.solutions.len()
+    }
+
+    /// Returns `true` if there are no pending solutions.
+    pub fn is_empty(&self) -> bool {
+        self.solutions.is_empty()
+    }
+
+    /// Returns the number of pending solutions from the given prover address.
+    pub fn count(&self, address: &Address<N>) -> usize {
+        self.counts.get(address).copied().unwrap_or(0)
+    }
+
+    /// Records the given pending solution from the given prover address,
+    /// returning an error if the prover address has reached the per-address solution limit.
+    pub fn insert(&mut self, solution_id: SolutionID<N>, address: Address<N>) -> Result<()> {
+        // If the solution is already pending, it is not counted again.
+        if self.solutions.contains_key(&solution_id) {
+            return Ok(());
+        }
+        // Ensure the prover address is below the per-address solution limit.
+        let count = self.counts.entry(address).or_default();
+        if *count >= self.limit {
+            bail!("Prover '{address}' reached the per-address solution limit ({} pending solutions)", self.limit)
+        }
+        *count += 1;
+        self.solutions.insert(solution_id, address);
+        Ok(())
+    }
+
+    /// Releases the given solution, returning `true` if it was pending.
+    pub fn remove(&mut self, solution_id: &SolutionID<N>) -> bool {
+        // Retrieve the prover address of the solution.
+        let Some(address) = self.solutions.remove(solution_id) else {
+            return false;
+        };
+        // Decrement the number of pending solutions from the prover address.
+        if let Entry::Occupied(mut entry) = self.counts.entry(address) {
+            *entry.get_mut() = entry.get().saturating_sub(1);
+            if *entry.get() == 0 {
+                entry.remove();
+            }
+        }
+        true
+    }
+
+    /// Sets the epoch hash of the pending solutions, returning `true` if the epoch changed.
+    /// If the epoch changed, every pending solution is released, as it is stale.
+    pub fn set_epoch(&mut self, epoch_hash: N::BlockHash) -> bool {
+        match self.epoch_hash.replace(epoch_hash) {
+            Some(previous) if previous != epoch_hash => {
+                self.solutions.clear();
+                self.counts.clear();
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkvm::prelude::{Field, PrivateKey, TestRng, Uniform};
+
+    type CurrentNetwork = snarkvm::prelude::MainnetV0;
+
+    fn sample_address(rng: &mut TestRng) -> Address<CurrentNetwork> {
+        Address::try_from(PrivateKey::<CurrentNetwork>::new(rng).unwrap()).unwrap()
+    }
+
+    fn sample_solution_id(rng: &mut TestRng) -> SolutionID<CurrentNetwork> {
+        SolutionID::from(u64::rand(rng))
+    }
+
+    #[test]
+    fn test_solution_quota_per_address() {
+        let rng = &mut TestRng::default();
+        let mut quota = SolutionQuota::<CurrentNetwork>::new(4);
+        let (flooder, prover) = (sample_address(rng), sample_address(rng));
+
+        // Submit more solutions than the limit from the first address.
+        let mut num_rejected = 0;
+        for _ in 0..10 {
+            if let Err(e) = quota.insert(sample_solution_id(rng), flooder) {
+                assert!(e.to_string().contains("per-address solution limit"));
+                num_rejected += 1;
+            }
+        }
+        assert_eq!(num_rejected, 6);
+        assert_eq!(quota.count(&flooder), 4);
+
+        // Ensure the solutions of the second address, below the limit, are unaffected.
+        let solution_ids = (0..3).map(|_| sample_solution_id(rng)).collect::<Vec<_>>();
+        for solution_id in &solution_ids {
+            quota.insert(*solution_id, prover).unwrap();
+        }
+        assert_eq!(quota.count(&prover), 3);
+        assert_eq!(quota.len(), 7);
+
+        // Ensure a pending solution is not counted twice.
+        quota.insert(solution_ids[0], prover).unwrap();
+        assert_eq!(quota.count(&prover), 3);
+
+        // Ensure a released solution frees its slot.
+        assert!(quota.remove(&solution_ids[0]));
+        assert!(!quota.remove(&solution_ids[0]));
+        assert_eq!(quota.count(&prover), 2);
+        assert_eq!(quota.count(&flooder), 4);
+    }
+
+    #[test]
+    fn test_solution_quota_epoch() {
+        let rng = &mut TestRng::default();
+        let mut quota = SolutionQuota::<CurrentNetwork>::new(2);
+        let address = sample_address(rng);
+
+        // Set the initial epoch, which does not release any solution.
+        let epoch_hash = Field::<CurrentNetwork>::rand(rng).into();
+        assert!(!quota.set_epoch(epoch_hash));
+        quota.insert(sample_solution_id(rng), address).unwrap();
+        quota.insert(sample_solution_id(rng), address).unwrap();
+        assert!(quota.insert(sample_solution_id(rng), address).is_err());
+
+        // Ensure the same epoch does not release any solution.
+        assert!(!quota.set_epoch(epoch_hash));
+        assert_eq!(quota.count(&address), 2);
+
+        // Ensure a new epoch releases every solution, and resets the counts.
+        assert!(quota.set_epoch(Field::<CurrentNetwork>::rand(rng).into()));
+        assert!(quota.is_empty());
+        assert_eq!(quota.count(&address), 0);
+        quota.insert(sample_solution_id(rng), address).unwrap();
+    }
+}
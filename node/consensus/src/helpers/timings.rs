@@ -0,0 +1,214 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{collections::VecDeque, time::Instant};
+
+/// The number of recent block timings retained by consensus.
+pub const BLOCK_TIMINGS_CAPACITY: usize = 256;
+
+/// The time spent in each stage of advancing to a block, in microseconds.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct BlockTiming {
+    /// The height of the block.
+    pub height: u32,
+    /// The number of transactions in the block.
+    pub num_transactions: usize,
+    /// The time spent speculating on the transmissions, to create the candidate block.
+    pub speculate_micros: u64,
+    /// The time spent checking that the candidate block is well-formed.
+    pub verify_micros: u64,
+    /// The time spent recording the block in the block journal.
+    pub journal_micros: u64,
+    /// The time spent finalizing the block, and writing it to storage.
+    /// Note: The ledger finalizes and writes the block in a single atomic batch, so these are timed together.
+    pub advance_micros: u64,
+    /// The time spent across all of the stages.
+    pub total_micros: u64,
+}
+
+impl BlockTiming {
+    /// Returns the sum of the stages, in microseconds.
+    pub const fn sum_of_stages(&self) -> u64 {
+        self.speculate_micros + self.verify_micros + self.journal_micros + self.advance_micros
+    }
+}
+
+/// A timer that measures consecutive stages, without allocating.
+#[derive(Copy, Clone, Debug)]
+pub struct StageTimer {
+    /// The instant the timer was started.
+    start: Instant,
+    /// The instant the latest stage ended.
+    last: Instant,
+}
+
+impl StageTimer {
+    /// Starts a new timer.
+    pub fn start() -> Self {
+        let now = Instant::now();
+        Self { start: now, last: now }
+    }
+
+    /// Ends the current stage, and returns its duration in microseconds.
+    pub fn lap(&mut self) -> u64 {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last).as_micros() as u64;
+        self.last = now;
+        elapsed
+    }
+
+    /// Returns the time from the start of the timer to the end of the latest stage, in microseconds.
+    pub fn total(&self) -> u64 {
+        self.last.duration_since(self.start).as_micros() as u64
+    }
+}
+
+/// A bounded record of the timings of the most recent blocks.
+///
+/// The record is in-memory only, and its capacity is allocated upfront, so that recording a timing never allocates.
+pub struct BlockTimings {
+    /// The timings, ordered from oldest to newest.
+    timings: VecDeque<BlockTiming>,
+    /// The maximum number of timings to retain.
+    capacity: usize,
+}
+
+impl Default for BlockTimings {
+    /// Initializes a new record, retaining up to `BLOCK_TIMINGS_CAPACITY` timings.
+    fn default() -> Self {
+        Self::new(BLOCK_TIMINGS_CAPACITY)
+    }
+}
+
+impl BlockTimings {
+    /// Initializes a new record, retaining up to the given number of timings.
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self { timings: VecDeque::with_capacity(capacity), capacity }
+    }
+
+    /// Returns the number of timings in the record.
+    pub fn len(&self) -> usize {
+        self.timings.len()
+    }
+
+    /// Returns `true` if the record is empty.
+    pub fn is_empty(&self) -> bool {
+        self.timings.is_empty()
+    }
+
+    /// Inserts the given timing, evicting the oldest timing if the record is full.
+    pub fn insert(&mut self, timing: BlockTiming) {
+        if self.timings.len() >= self.capacity {
+            self.timings.pop_front();
+        }
+        self.timings.push_back(timing);
+    }
+
+    /// Returns up to the given number of the most recent timings, ordered from newest to oldest.
+    pub fn latest(&self, count: usize) -> Vec<BlockTiming> {
+        self.timings.iter().rev().take(count).copied().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::BlockJournal;
+    use snarkos_node_bft::helpers::{Transmission, TransmissionID};
+    use snarkos_node_bft_ledger_service::test_helpers::sample_ledger;
+    use snarkvm::prelude::{MainnetV0, TestRng};
+
+    use indexmap::IndexMap;
+
+    type CurrentNetwork = MainnetV0;
+
+    /// Returns a timing for the given height.
+    fn sample_timing(height: u32) -> BlockTiming {
+        BlockTiming { height, ..Default::default() }
+    }
+
+    #[test]
+    fn test_insert_and_evict() {
+        let mut timings = BlockTimings::new(3);
+        let capacity = timings.timings.capacity();
+        assert!(timings.is_empty());
+        for height in 1..=5 {
+            timings.insert(sample_timing(height));
+        }
+        // Ensure only the most recent timings are retained, without growing the buffer.
+        assert_eq!(timings.len(), 3);
+        assert_eq!(timings.timings.capacity(), capacity);
+        let heights = timings.latest(usize::MAX).iter().map(|timing| timing.height).collect::<Vec<_>>();
+        assert_eq!(heights, vec![5, 4, 3]);
+        // Ensure the count is respected.
+        let heights = timings.latest(2).iter().map(|timing| timing.height).collect::<Vec<_>>();
+        assert_eq!(heights, vec![5, 4]);
+    }
+
+    #[test]
+    fn test_stage_timer() {
+        let mut timer = StageTimer::start();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let first = timer.lap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let second = timer.lap();
+        assert!(first >= 5_000 && second >= 5_000);
+        // Ensure the stages partition the total, up to the truncation of each stage to a microsecond.
+        assert!(timer.total() >= first + second);
+        assert!(timer.total() <= first + second + 2);
+    }
+
+    #[test]
+    fn test_timings_of_advanced_blocks() {
+        let rng = &mut TestRng::default();
+        let (ledger, private_key) = sample_ledger(rng);
+        let journal = BlockJournal::<CurrentNetwork>::open(None).unwrap();
+        let transmissions = IndexMap::from([(TransmissionID::Ratification, Transmission::Ratification)]);
+        let mut timings = BlockTimings::default();
+
+        // Advance several blocks, timing each stage as consensus does.
+        for _ in 0..3 {
+            let mut timer = StageTimer::start();
+            let block = ledger.prepare_advance_to_next_beacon_block(&private_key, vec![], vec![], vec![], rng).unwrap();
+            let speculate_micros = timer.lap();
+            ledger.check_next_block(&block, rng).unwrap();
+            let verify_micros = timer.lap();
+            journal.write(&block, &transmissions).unwrap();
+            let journal_micros = timer.lap();
+            ledger.advance_to_next_block(&block).unwrap();
+            let advance_micros = timer.lap();
+            journal.truncate().unwrap();
+            timings.insert(BlockTiming {
+                height: block.height(),
+                num_transactions: block.transactions().len(),
+                speculate_micros,
+                verify_micros,
+                journal_micros,
+                advance_micros,
+                total_micros: timer.total(),
+            });
+        }
+
+        // Ensure every block is recorded, and its stages sum to approximately the total.
+        let latest = timings.latest(usize::MAX);
+        assert_eq!(latest.iter().map(|timing| timing.height).collect::<Vec<_>>(), vec![3, 2, 1]);
+        for timing in latest {
+            assert!(timing.speculate_micros > 0);
+            assert!(timing.advance_micros > 0);
+            assert!(timing.sum_of_stages() <= timing.total_micros);
+            assert!(timing.total_micros - timing.sum_of_stages() <= 4);
+        }
+    }
+}
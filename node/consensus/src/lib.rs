@@ -17,25 +17,31 @@
 #[macro_use]
 extern crate tracing;
 
-use snarkos_account::Account;
+mod helpers;
+pub use helpers::*;
+
 use snarkos_node_bft::{
     helpers::{
         fmt_id,
         init_consensus_channels,
         ConsensusReceiver,
+        EmptyBatchMode,
         PrimaryReceiver,
         PrimarySender,
+        PriorityLaneConfig,
+        Signer,
         Storage as NarwhalStorage,
+        TransmissionPriority,
     },
     spawn_blocking,
     Primary,
     BFT,
 };
-use snarkos_node_bft_ledger_service::LedgerService;
-use snarkos_node_bft_storage_service::BFTPersistentStorage;
+use snarkos_node_bft_ledger_service::{DiskMonitor, LedgerService};
+use snarkos_node_bft_storage_service::{BFTMemoryService, BFTPersistentStorage, StorageService};
 use snarkvm::{
     ledger::{
-        block::Transaction,
+        block::{Block, Transaction},
         narwhal::{BatchHeader, Data, Subdag, Transmission, TransmissionID},
         puzzle::{Solution, SolutionID},
     },
@@ -45,14 +51,19 @@ use snarkvm::{
 use aleo_std::StorageMode;
 use anyhow::Result;
 use colored::Colorize;
-use indexmap::IndexMap;
+use indexmap::{IndexMap, IndexSet};
 use lru::LruCache;
-use parking_lot::Mutex;
-use std::{future::Future, net::SocketAddr, num::NonZeroUsize, sync::Arc};
-use tokio::{
-    sync::{oneshot, OnceCell},
-    task::JoinHandle,
+use parking_lot::{Mutex, RwLock};
+use std::{
+    future::Future,
+    net::SocketAddr,
+    num::NonZeroUsize,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
 };
+use tokio::{sync::oneshot, task::JoinHandle};
 
 /// The capacity of the queue reserved for deployments.
 /// Note: This is an inbound queue capacity, not a Narwhal-enforced capacity.
@@ -66,6 +77,8 @@ const CAPACITY_FOR_SOLUTIONS: usize = 1 << 10;
 /// The **suggested** maximum number of deployments in each interval.
 /// Note: This is an inbound queue limit, not a Narwhal-enforced limit.
 const MAX_DEPLOYMENTS_PER_INTERVAL: usize = 1;
+/// The capacity of the queue of transactions held back behind conflicting unconfirmed transactions.
+const CAPACITY_FOR_CONFLICTS: usize = 1 << 10;
 
 /// Helper struct to track incoming transactions.
 struct TransactionsQueue<N: Network> {
@@ -82,22 +95,101 @@ impl<N: Network> Default for TransactionsQueue<N> {
     }
 }
 
+/// The storage of the pending transmissions of the BFT.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum TransmissionStorageMode {
+    /// The transmissions are held in memory only, and are lost on a restart.
+    Memory,
+    /// The transmissions are persisted in the ledger directory, and are recovered on a restart.
+    #[default]
+    Persistent,
+}
+
+/// The lifecycle state of the consensus.
+enum ConsensusState<N: Network> {
+    /// The consensus has yet to run.
+    Ready,
+    /// The consensus is running, and sends the unconfirmed transmissions to the primary with the given sender.
+    Running(PrimarySender<N>),
+    /// The consensus was stopped, and its BFT is rebuilt before it runs again.
+    Stopped,
+}
+
+/// The components of the BFT, from which it is rebuilt when the consensus is restarted.
+#[derive(Clone)]
+struct BFTConfig<N: Network> {
+    /// The signer of the validator.
+    signer: Arc<dyn Signer<N>>,
+    /// The storage of the Narwhal transmissions.
+    transmissions: Arc<dyn StorageService<N>>,
+    /// The IP of the gateway, if it is set.
+    ip: Option<SocketAddr>,
+    /// The trusted validators.
+    trusted_validators: Vec<SocketAddr>,
+    /// The development ID, if it is present.
+    dev: Option<u16>,
+    /// The quotas of the priority lane, if they are set.
+    priority_lane: Option<PriorityLaneConfig>,
+    /// The behavior of the primary when it has no unconfirmed transactions to propose.
+    empty_batch_mode: EmptyBatchMode,
+}
+
+impl<N: Network> BFTConfig<N> {
+    /// Initializes a new BFT, deriving its committee from the latest block in the ledger.
+    fn build(&self, ledger: &Arc<dyn LedgerService<N>>) -> Result<BFT<N>> {
+        // Initialize the Narwhal storage.
+        let storage =
+            NarwhalStorage::new(ledger.clone(), self.transmissions.clone(), BatchHeader::<N>::MAX_GC_ROUNDS as u64);
+        // Initialize the BFT.
+        let bft = BFT::new(self.signer.clone(), storage, ledger.clone(), self.ip, &self.trusted_validators, self.dev)?
+            .with_empty_batch_mode(self.empty_batch_mode);
+        match self.priority_lane {
+            Some(config) => Ok(bft.with_priority_lane(config)),
+            None => Ok(bft),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct Consensus<N: Network> {
     /// The ledger.
     ledger: Arc<dyn LedgerService<N>>,
-    /// The BFT.
-    bft: BFT<N>,
-    /// The primary sender.
-    primary_sender: Arc<OnceCell<PrimarySender<N>>>,
+    /// The BFT, which is rebuilt when the consensus is restarted.
+    bft: Arc<RwLock<BFT<N>>>,
+    /// The components of the BFT, from which it is rebuilt when the consensus is restarted.
+    bft_config: BFTConfig<N>,
+    /// The lifecycle state of the consensus.
+    state: Arc<Mutex<ConsensusState<N>>>,
     /// The unconfirmed solutions queue.
     solutions_queue: Arc<Mutex<LruCache<SolutionID<N>, Solution<N>>>>,
     /// The unconfirmed transactions queue.
     transactions_queue: Arc<Mutex<TransactionsQueue<N>>>,
     /// The recently-seen unconfirmed solutions.
     seen_solutions: Arc<Mutex<LruCache<SolutionID<N>, ()>>>,
+    /// The quota of pending solutions for each prover address.
+    solution_quota: Arc<Mutex<SolutionQuota<N>>>,
     /// The recently-seen unconfirmed transactions.
     seen_transactions: Arc<Mutex<LruCache<N::TransactionID, ()>>>,
+    /// The index of serial numbers spent by unconfirmed transactions, used for replace-by-fee.
+    replacements: Arc<Mutex<ReplacementIndex<N>>>,
+    /// The index of the programs and addresses of unconfirmed transactions, used for filtered queries.
+    mempool_index: Arc<Mutex<MempoolIndex<N>>>,
+    /// The minimum fee increment (in microcredits) required to replace an unconfirmed transaction.
+    replacement_fee_increment: Arc<AtomicU64>,
+    /// The policy for transactions that conflict with unconfirmed transactions they cannot replace.
+    conflict_policy: Arc<Mutex<ConflictPolicy>>,
+    /// The transactions held back behind the conflicting unconfirmed transactions, under the queue policy.
+    conflict_queue: Arc<Mutex<ConflictQueue<N>>>,
+    /// The journal of outcomes for recent unconfirmed transactions.
+    transaction_outcomes: Arc<Mutex<TransactionOutcomes<N>>>,
+    /// The write-ahead journal of the block being advanced to.
+    block_journal: Arc<BlockJournal<N>>,
+    /// The timing breakdowns of the most recent blocks.
+    block_timings: Arc<Mutex<BlockTimings>>,
+    /// The disk monitor, if the intake of transmissions depends on the free disk space.
+    disk_monitor: Option<Arc<DiskMonitor>>,
+    /// The denylist of programs, whose transactions are refused by the memory pool.
+    program_denylist: Arc<ProgramDenylist<N>>,
     /// The spawned handles.
     handles: Arc<Mutex<Vec<JoinHandle<()>>>>,
 }
@@ -105,114 +197,366 @@ pub struct Consensus<N: Network> {
 impl<N: Network> Consensus<N> {
     /// Initializes a new instance of consensus.
     pub fn new(
-        account: Account<N>,
+        signer: impl Signer<N> + 'static,
         ledger: Arc<dyn LedgerService<N>>,
         ip: Option<SocketAddr>,
         trusted_validators: &[SocketAddr],
         storage_mode: StorageMode,
+        transmission_storage: TransmissionStorageMode,
     ) -> Result<Self> {
         // Recover the development ID, if it is present.
         let dev = match storage_mode {
             StorageMode::Development(id) => Some(id),
             StorageMode::Production | StorageMode::Custom(..) => None,
         };
+        // Open the block journal.
+        // Note: The journal is kept in the ledger directory, so that `snarkos clean` removes it alongside the ledger.
+        let directory = aleo_std::aleo_ledger_dir(N::ID, storage_mode.clone());
+        std::fs::create_dir_all(&directory)?;
+        let block_journal = Arc::new(BlockJournal::open(Some(directory.join("block_journal")))?);
         // Initialize the Narwhal transmissions.
-        let transmissions = Arc::new(BFTPersistentStorage::open(storage_mode)?);
-        // Initialize the Narwhal storage.
-        let storage = NarwhalStorage::new(ledger.clone(), transmissions, BatchHeader::<N>::MAX_GC_ROUNDS as u64);
+        let transmissions: Arc<dyn StorageService<N>> = match transmission_storage {
+            TransmissionStorageMode::Memory => Arc::new(BFTMemoryService::new()),
+            TransmissionStorageMode::Persistent => Arc::new(BFTPersistentStorage::open(storage_mode)?),
+        };
         // Initialize the BFT.
-        let bft = BFT::new(account, storage, ledger.clone(), ip, trusted_validators, dev)?;
+        let bft_config = BFTConfig {
+            signer: Arc::new(signer),
+            transmissions,
+            ip,
+            trusted_validators: trusted_validators.to_vec(),
+            dev,
+            priority_lane: None,
+            empty_batch_mode: Default::default(),
+        };
+        let bft = bft_config.build(&ledger)?;
         // Return the consensus.
         Ok(Self {
             ledger,
-            bft,
-            primary_sender: Default::default(),
+            bft: Arc::new(RwLock::new(bft)),
+            bft_config,
+            state: Arc::new(Mutex::new(ConsensusState::Ready)),
             solutions_queue: Arc::new(Mutex::new(LruCache::new(NonZeroUsize::new(CAPACITY_FOR_SOLUTIONS).unwrap()))),
             transactions_queue: Default::default(),
             seen_solutions: Arc::new(Mutex::new(LruCache::new(NonZeroUsize::new(1 << 16).unwrap()))),
             seen_transactions: Arc::new(Mutex::new(LruCache::new(NonZeroUsize::new(1 << 16).unwrap()))),
+            solution_quota: Default::default(),
+            replacements: Arc::new(Mutex::new(ReplacementIndex::new(NonZeroUsize::new(1 << 16).unwrap()))),
+            mempool_index: Arc::new(Mutex::new(MempoolIndex::new(NonZeroUsize::new(1 << 16).unwrap()))),
+            replacement_fee_increment: Arc::new(AtomicU64::new(DEFAULT_REPLACEMENT_FEE_INCREMENT)),
+            conflict_policy: Default::default(),
+            conflict_queue: Arc::new(Mutex::new(ConflictQueue::new(
+                NonZeroUsize::new(CAPACITY_FOR_CONFLICTS).unwrap(),
+            ))),
+            transaction_outcomes: Arc::new(Mutex::new(TransactionOutcomes::new(
+                NonZeroUsize::new(TRANSACTION_OUTCOMES_CAPACITY).unwrap(),
+            ))),
+            block_journal,
+            block_timings: Default::default(),
+            disk_monitor: None,
+            program_denylist: Default::default(),
             handles: Default::default(),
         })
     }
 
+    /// Returns the consensus, refusing new transactions and solutions while the disk space is low.
+    pub fn with_disk_monitor(mut self, disk_monitor: Arc<DiskMonitor>) -> Self {
+        self.disk_monitor = Some(disk_monitor);
+        self
+    }
+
+    /// Returns the consensus, refusing the transactions that reference a program in the given denylist.
+    pub fn with_program_denylist(mut self, program_denylist: Arc<ProgramDenylist<N>>) -> Self {
+        self.program_denylist = program_denylist;
+        self
+    }
+
+    /// Returns the consensus, with the given quotas for the priority lane of the memory pool.
+    /// Note: Consensus-critical transmissions in the priority lane bypass the capacity of the memory pool.
+    pub fn with_priority_lane(mut self, config: PriorityLaneConfig) -> Self {
+        self.bft_config.priority_lane = Some(config);
+        self.bft = Arc::new(RwLock::new(self.bft().with_priority_lane(config)));
+        self
+    }
+
+    /// Returns the consensus, with the given behavior of the primary when it has no transactions to propose.
+    pub fn with_empty_batch_mode(mut self, mode: EmptyBatchMode) -> Self {
+        self.bft_config.empty_batch_mode = mode;
+        self.bft = Arc::new(RwLock::new(self.bft().with_empty_batch_mode(mode)));
+        self
+    }
+
     /// Run the consensus instance.
-    pub async fn run(&mut self, primary_sender: PrimarySender<N>, primary_receiver: PrimaryReceiver<N>) -> Result<()> {
+    ///
+    /// If the consensus was stopped, the BFT is rebuilt with the given channels, and its committee is derived
+    /// from the latest block in the ledger. Returns an error if the consensus is already running.
+    pub async fn run(&self, primary_sender: PrimarySender<N>, primary_receiver: PrimaryReceiver<N>) -> Result<()> {
+        // Transition to the running state, so that a concurrent call to run the consensus fails.
+        let was_stopped = {
+            let mut state = self.state.lock();
+            let was_stopped = match *state {
+                ConsensusState::Ready => false,
+                ConsensusState::Running(_) => bail!("The consensus is already running"),
+                ConsensusState::Stopped => true,
+            };
+            *state = ConsensusState::Running(primary_sender.clone());
+            was_stopped
+        };
         info!("Starting the consensus instance...");
-        // Set the primary sender.
-        self.primary_sender.set(primary_sender.clone()).expect("Primary sender already set");
+        // If the consensus fails to start, shut down the components that were started.
+        if let Err(error) = self.start(was_stopped, primary_sender, primary_receiver).await {
+            *self.state.lock() = ConsensusState::Stopped;
+            self.shut_down_components().await;
+            return Err(error);
+        }
+        Ok(())
+    }
 
-        // First, initialize the consensus channels.
+    /// Starts the BFT and the consensus handlers, rebuilding the BFT if the consensus was stopped.
+    async fn start(
+        &self,
+        was_stopped: bool,
+        primary_sender: PrimarySender<N>,
+        primary_receiver: PrimaryReceiver<N>,
+    ) -> Result<()> {
+        // If the consensus was stopped, rebuild the BFT from the latest state of the ledger.
+        if was_stopped {
+            let previous = self.bft();
+            let bft = self.bft_config.build(&self.ledger)?;
+            // Carry over the settings that were applied to the previous BFT.
+            if let Some(scheduler) = previous.primary().gateway().tcp().bandwidth_scheduler() {
+                bft.primary().gateway().tcp().set_bandwidth_scheduler(scheduler.clone())?;
+            }
+            bft.primary().set_participating(previous.primary().is_participating());
+            bft.primary().set_clock_skewed(previous.primary().is_clock_skewed());
+            *self.bft.write() = bft;
+        }
+        // First, resolve the block advance that was interrupted when the node last stopped, if any.
+        let transmissions = self.recover_block_journal()?;
+        // Next, initialize the consensus channels.
         let (consensus_sender, consensus_receiver) = init_consensus_channels();
         // Then, start the consensus handlers.
         self.start_handlers(consensus_receiver);
         // Lastly, the consensus.
-        self.bft.run(Some(consensus_sender), primary_sender, primary_receiver).await?;
+        self.bft().run(Some(consensus_sender), primary_sender, primary_receiver).await?;
+        // Reinsert the transmissions of an interrupted block that could not be re-applied.
+        if let Some(transmissions) = transmissions {
+            self.reinsert_transmissions(transmissions).await;
+        }
         Ok(())
     }
 
+    /// Resolves the entry left in the block journal, and returns the transmissions to reinsert into the memory pool, if any.
+    fn recover_block_journal(&self) -> Result<Option<IndexMap<TransmissionID<N>, Transmission<N>>>> {
+        match self.block_journal.recover(self.ledger.as_ref())? {
+            JournalRecovery::Clean => Ok(None),
+            JournalRecovery::Completed { height } => {
+                info!("Recovered the interrupted advance to block {height}, which is already in the ledger");
+                Ok(None)
+            }
+            JournalRecovery::Reapplied { height } => {
+                info!("Recovered the interrupted advance to block {height}, by re-applying the block");
+                Ok(None)
+            }
+            JournalRecovery::Reinsert { height, transmissions } => {
+                warn!(
+                    "Discarded the interrupted advance to block {height}, reinserting its {} transmissions into the memory pool",
+                    transmissions.len()
+                );
+                Ok(Some(transmissions))
+            }
+        }
+    }
+
     /// Returns the ledger.
     pub const fn ledger(&self) -> &Arc<dyn LedgerService<N>> {
         &self.ledger
     }
 
     /// Returns the BFT.
-    pub const fn bft(&self) -> &BFT<N> {
-        &self.bft
+    /// Note: The BFT is rebuilt when the consensus is restarted, so it must not be retained across a restart.
+    pub fn bft(&self) -> BFT<N> {
+        self.bft.read().clone()
+    }
+
+    /// Returns `true` if the consensus is running.
+    pub fn is_running(&self) -> bool {
+        matches!(*self.state.lock(), ConsensusState::Running(_))
+    }
+
+    /// Returns the primary sender, if the consensus is running.
+    pub fn primary_sender(&self) -> Result<PrimarySender<N>> {
+        match &*self.state.lock() {
+            ConsensusState::Running(primary_sender) => Ok(primary_sender.clone()),
+            ConsensusState::Ready | ConsensusState::Stopped => bail!("The consensus is not running"),
+        }
     }
 
-    /// Returns the primary sender.
-    pub fn primary_sender(&self) -> &PrimarySender<N> {
-        self.primary_sender.get().expect("Primary sender not set")
+    /// Returns the denylist of programs, whose transactions are refused by the memory pool.
+    pub fn program_denylist(&self) -> &Arc<ProgramDenylist<N>> {
+        &self.program_denylist
+    }
+
+    /// Returns the minimum fee increment (in microcredits) required to replace an unconfirmed transaction.
+    pub fn replacement_fee_increment(&self) -> u64 {
+        self.replacement_fee_increment.load(Ordering::Relaxed)
+    }
+
+    /// Sets the minimum fee increment (in microcredits) required to replace an unconfirmed transaction.
+    pub fn set_replacement_fee_increment(&self, increment: u64) {
+        self.replacement_fee_increment.store(increment, Ordering::Relaxed);
+    }
+
+    /// Returns the maximum number of pending solutions from a single prover address.
+    pub fn max_solutions_per_address(&self) -> usize {
+        self.solution_quota.lock().limit()
+    }
+
+    /// Sets the maximum number of pending solutions from a single prover address.
+    pub fn set_max_solutions_per_address(&self, limit: usize) {
+        self.solution_quota.lock().set_limit(limit);
+    }
+
+    /// Returns the policy for transactions that conflict with unconfirmed transactions they cannot replace.
+    pub fn conflict_policy(&self) -> ConflictPolicy {
+        *self.conflict_policy.lock()
+    }
+
+    /// Sets the policy for transactions that conflict with unconfirmed transactions they cannot replace.
+    pub fn set_conflict_policy(&self, policy: ConflictPolicy) {
+        *self.conflict_policy.lock() = policy;
     }
 }
 
 impl<N: Network> Consensus<N> {
+    /// Returns the outcome of the given transaction, if it is among the recent unconfirmed transactions.
+    pub fn transaction_outcome(&self, transaction_id: &N::TransactionID) -> Option<TransactionOutcome> {
+        self.transaction_outcomes.lock().get(transaction_id).cloned()
+    }
+
+    /// Returns the number of recent transaction outcomes retained by consensus.
+    pub fn transaction_outcomes_capacity(&self) -> usize {
+        self.transaction_outcomes.lock().capacity()
+    }
+
+    /// Returns up to the given number of the most recent block timings, ordered from newest to oldest.
+    pub fn block_timings(&self, count: usize) -> Vec<BlockTiming> {
+        self.block_timings.lock().latest(count)
+    }
+
     /// Returns the number of unconfirmed transmissions.
     pub fn num_unconfirmed_transmissions(&self) -> usize {
-        self.bft.num_unconfirmed_transmissions()
+        self.bft().num_unconfirmed_transmissions()
     }
 
     /// Returns the number of unconfirmed ratifications.
     pub fn num_unconfirmed_ratifications(&self) -> usize {
-        self.bft.num_unconfirmed_ratifications()
+        self.bft().num_unconfirmed_ratifications()
     }
 
     /// Returns the number of solutions.
     pub fn num_unconfirmed_solutions(&self) -> usize {
-        self.bft.num_unconfirmed_solutions()
+        self.bft().num_unconfirmed_solutions()
     }
 
     /// Returns the number of unconfirmed transactions.
     pub fn num_unconfirmed_transactions(&self) -> usize {
-        self.bft.num_unconfirmed_transactions()
+        self.bft().num_unconfirmed_transactions()
     }
 }
 
 impl<N: Network> Consensus<N> {
     /// Returns the unconfirmed transmission IDs.
     pub fn unconfirmed_transmission_ids(&self) -> impl '_ + Iterator<Item = TransmissionID<N>> {
-        self.bft.unconfirmed_transmission_ids()
+        self.bft().unconfirmed_transmission_ids().collect::<Vec<_>>().into_iter()
     }
 
     /// Returns the unconfirmed transmissions.
     pub fn unconfirmed_transmissions(&self) -> impl '_ + Iterator<Item = (TransmissionID<N>, Transmission<N>)> {
-        self.bft.unconfirmed_transmissions()
+        self.bft().unconfirmed_transmissions().collect::<Vec<_>>().into_iter()
     }
 
     /// Returns the unconfirmed solutions.
     pub fn unconfirmed_solutions(&self) -> impl '_ + Iterator<Item = (SolutionID<N>, Data<Solution<N>>)> {
-        self.bft.unconfirmed_solutions()
+        self.bft().unconfirmed_solutions().collect::<Vec<_>>().into_iter()
     }
 
     /// Returns the unconfirmed transactions.
     pub fn unconfirmed_transactions(&self) -> impl '_ + Iterator<Item = (N::TransactionID, Data<Transaction<N>>)> {
-        self.bft.unconfirmed_transactions()
+        self.bft().unconfirmed_transactions().collect::<Vec<_>>().into_iter()
+    }
+
+    /// Returns the unconfirmed solution, if it is in the queue, the memory pool, or in flight.
+    pub fn get_unconfirmed_solution(&self, solution_id: SolutionID<N>) -> Option<Data<Solution<N>>> {
+        // Check the queue.
+        if let Some(solution) = self.solutions_queue.lock().peek(&solution_id) {
+            return Some(Data::Object(solution.clone()));
+        }
+        // Check the memory pool.
+        self.bft().get_unconfirmed_solution(solution_id)
+    }
+
+    /// Returns the unconfirmed transaction, if it is in the queue, the memory pool, or in flight.
+    pub fn get_unconfirmed_transaction(&self, transaction_id: N::TransactionID) -> Option<Data<Transaction<N>>> {
+        // Check the queue.
+        {
+            let tx_queue = self.transactions_queue.lock();
+            if let Some(transaction) =
+                tx_queue.deployments.peek(&transaction_id).or_else(|| tx_queue.executions.peek(&transaction_id))
+            {
+                return Some(Data::Object(transaction.clone()));
+            }
+        }
+        // Check the memory pool.
+        self.bft().get_unconfirmed_transaction(transaction_id)
+    }
+
+    /// Returns up to `limit` unconfirmed transactions that touch the given program (if any) and involve the given
+    /// address (if any), along with the total number of such transactions.
+    ///
+    /// Note: The transactions are looked up in the memory pool index, so the cost of this query is proportional
+    /// to the number of matching transactions, and not to the size of the memory pool.
+    pub fn find_unconfirmed_transactions(
+        &self,
+        program_id: Option<&ProgramID<N>>,
+        address: Option<&Address<N>>,
+        limit: usize,
+    ) -> (usize, Vec<(N::TransactionID, Data<Transaction<N>>)>) {
+        // Retrieve the matching transaction IDs.
+        let transaction_ids = self.mempool_index.lock().find(program_id, address);
+
+        let mut num_matches = 0;
+        let mut transactions = Vec::with_capacity(limit.min(transaction_ids.len()));
+        let mut stale = vec![];
+        for transaction_id in transaction_ids {
+            match self.get_unconfirmed_transaction(transaction_id) {
+                Some(transaction) => {
+                    num_matches += 1;
+                    if transactions.len() < limit {
+                        transactions.push((transaction_id, transaction));
+                    }
+                }
+                None => stale.push(transaction_id),
+            }
+        }
+        // Remove the transactions that left the memory pool without being confirmed (e.g. if they were invalid).
+        if !stale.is_empty() {
+            let mut mempool_index = self.mempool_index.lock();
+            for transaction_id in &stale {
+                mempool_index.remove(transaction_id);
+            }
+        }
+        (num_matches, transactions)
     }
 }
 
 impl<N: Network> Consensus<N> {
     /// Adds the given unconfirmed solution to the memory pool.
     pub async fn add_unconfirmed_solution(&self, solution: Solution<N>) -> Result<()> {
+        // Ensure the consensus is running.
+        ensure!(self.is_running(), "The consensus is not running");
+        // Ensure the node has sufficient disk space to accept new solutions.
+        self.ensure_disk_space_for_intake()?;
         #[cfg(feature = "metrics")]
         {
             metrics::increment_gauge(metrics::consensus::UNCONFIRMED_SOLUTIONS, 1f64);
@@ -231,10 +575,36 @@ impl<N: Network> Consensus<N> {
             if self.ledger.contains_transmission(&TransmissionID::from(solution_id))? {
                 bail!("Solution '{}' exists in the ledger {}", fmt_id(solution_id), "(skipping)".dimmed());
             }
+            // Ensure the prover address of the solution is below the per-address solution limit.
+            if let Err(e) = self.solution_quota.lock().insert(solution_id, solution.address()) {
+                // Forget the solution, so that it may be resubmitted once the prover address is below the limit.
+                self.seen_solutions.lock().pop(&solution_id);
+                bail!("Refusing solution '{}' - {e}", fmt_id(solution_id));
+            }
+            // Classify the solution, and send a consensus-critical solution through the priority lane.
+            // Note: If the quota of the priority lane is reached, the solution is queued as an ordinary solution.
+            let priority = TransmissionPriority::classify_solution(&solution, self.ledger.latest_epoch_hash().ok());
+            if priority.is_critical() {
+                match self.primary_sender()?.send_priority_solution(solution_id, Data::Object(solution.clone())).await {
+                    Ok(()) => {
+                        trace!("Added consensus-critical solution '{}' to the priority lane", fmt_id(solution_id));
+                        return Ok(());
+                    }
+                    Err(e) => trace!("Queueing consensus-critical solution '{}' - {e}", fmt_id(solution_id)),
+                }
+            }
             // Add the solution to the memory pool.
             trace!("Received unconfirmed solution '{}' in the queue", fmt_id(solution_id));
-            if self.solutions_queue.lock().put(solution_id, solution).is_some() {
-                bail!("Solution '{}' exists in the memory pool", fmt_id(solution_id));
+            let evicted = self.solutions_queue.lock().push(solution_id, solution);
+            match evicted {
+                Some((id, _)) if id == solution_id => {
+                    bail!("Solution '{}' exists in the memory pool", fmt_id(solution_id))
+                }
+                // Release the solution that was evicted from the full queue.
+                Some((evicted_id, _)) => {
+                    self.solution_quota.lock().remove(&evicted_id);
+                }
+                None => (),
             }
         }
 
@@ -258,13 +628,16 @@ impl<N: Network> Consensus<N> {
             (0..num_solutions).filter_map(|_| queue.pop_lru().map(|(_, solution)| solution)).collect::<Vec<_>>()
         };
         // Iterate over the solutions.
+        let primary_sender = self.primary_sender()?;
         for solution in solutions.into_iter() {
             let solution_id = solution.id();
             trace!("Adding unconfirmed solution '{}' to the memory pool...", fmt_id(solution_id));
             // Send the unconfirmed solution to the primary.
-            if let Err(e) = self.primary_sender().send_unconfirmed_solution(solution_id, Data::Object(solution)).await {
+            if let Err(e) = primary_sender.send_unconfirmed_solution(solution_id, Data::Object(solution)).await {
+                // Release the solution, as it is no longer pending.
+                self.solution_quota.lock().remove(&solution_id);
                 // If the BFT is synced, then log the warning.
-                if self.bft.is_synced() {
+                if self.bft().is_synced() {
                     warn!("Failed to add unconfirmed solution '{}' to the memory pool - {e}", fmt_id(solution_id));
                 }
             }
@@ -273,44 +646,82 @@ impl<N: Network> Consensus<N> {
     }
 
     /// Adds the given unconfirmed transaction to the memory pool.
-    pub async fn add_unconfirmed_transaction(&self, transaction: Transaction<N>) -> Result<()> {
+    /// Returns the decision on the unconfirmed transactions that conflict with the given transaction, if any.
+    pub async fn add_unconfirmed_transaction(&self, transaction: Transaction<N>) -> Result<ConflictDecision<N>> {
+        // Ensure the consensus is running.
+        ensure!(self.is_running(), "The consensus is not running");
+        // Ensure the node has sufficient disk space to accept new transactions.
+        self.ensure_disk_space_for_intake()?;
         #[cfg(feature = "metrics")]
         {
             metrics::increment_gauge(metrics::consensus::UNCONFIRMED_TRANSACTIONS, 1f64);
             metrics::increment_gauge(metrics::consensus::UNCONFIRMED_TRANSMISSIONS, 1f64);
         }
         // Process the unconfirmed transaction.
-        {
+        let decision = {
             let transaction_id = transaction.id();
 
             // Check that the transaction is not a fee transaction.
             if transaction.is_fee() {
                 bail!("Transaction '{}' is a fee transaction {}", fmt_id(transaction_id), "(skipping)".dimmed());
             }
+            // Check that the transaction does not reference a denylisted program.
+            // Note: This precedes the check for recently-seen transactions, so that the transaction
+            // is accepted again once its program is removed from the denylist.
+            self.program_denylist.ensure_allowed(&transaction)?;
             // Check if the transaction was recently seen.
-            if self.seen_transactions.lock().put(transaction_id, ()).is_some() {
+            // Note: A transaction is only marked as seen once it enters the memory pool, so that a refused
+            // transaction is checked again when it is resubmitted.
+            if self.seen_transactions.lock().contains(&transaction_id) {
                 // If the transaction was recently seen, return early.
-                return Ok(());
+                return Ok(ConflictDecision::Accepted);
             }
             // Check if the transaction already exists in the ledger.
             if self.ledger.contains_transmission(&TransmissionID::from(&transaction_id))? {
                 bail!("Transaction '{}' exists in the ledger {}", fmt_id(transaction_id), "(skipping)".dimmed());
             }
+            // Check if the transaction conflicts with any unconfirmed transactions.
+            let conflicts = match self.check_conflicting_transactions(&transaction) {
+                Ok(conflicts) => conflicts,
+                // Otherwise, queue the transaction behind the conflicting transactions, if the policy permits.
+                Err(error) => return self.queue_conflicting_transaction(transaction, error),
+            };
+            // If the transaction may replace the conflicting transactions, verify it before replacing them.
+            if !conflicts.is_empty() {
+                return self.replace_conflicting_transactions(transaction).await;
+            }
+            // Index the programs and addresses of the transaction, for filtered queries of the memory pool.
+            self.mempool_index.lock().insert(&transaction);
             // Add the transaction to the memory pool.
             trace!("Received unconfirmed transaction '{}' in the queue", fmt_id(transaction_id));
-            if transaction.is_deploy() {
-                if self.transactions_queue.lock().deployments.put(transaction_id, transaction).is_some() {
+            let evicted = match transaction.is_deploy() {
+                true => self.transactions_queue.lock().deployments.push(transaction_id, transaction),
+                false => self.transactions_queue.lock().executions.push(transaction_id, transaction),
+            };
+            match evicted {
+                Some((evicted_id, _)) if evicted_id == transaction_id => {
                     bail!("Transaction '{}' exists in the memory pool", fmt_id(transaction_id));
                 }
-            } else if self.transactions_queue.lock().executions.put(transaction_id, transaction).is_some() {
-                bail!("Transaction '{}' exists in the memory pool", fmt_id(transaction_id));
+                // Record the transaction that was evicted from the full queue.
+                Some((evicted_id, _)) => {
+                    self.mempool_index.lock().remove(&evicted_id);
+                    self.transaction_outcomes.lock().insert(evicted_id, TransactionOutcome::Evicted {
+                        cause: "The memory pool queue was full".to_string(),
+                    });
+                    self.release_queued_transactions(&evicted_id);
+                }
+                None => (),
             }
-        }
+            self.seen_transactions.lock().put(transaction_id, ());
+            // Record the transaction as pending.
+            self.transaction_outcomes.lock().insert(transaction_id, TransactionOutcome::Pending);
+            ConflictDecision::Accepted
+        };
 
         // If the memory pool of this node is full, return early.
         let num_unconfirmed_transmissions = self.num_unconfirmed_transmissions();
         if num_unconfirmed_transmissions >= Primary::<N>::MAX_TRANSMISSIONS_TOLERANCE {
-            return Ok(());
+            return Ok(decision);
         }
         // Retrieve the transactions.
         let transactions = {
@@ -337,23 +748,267 @@ impl<N: Network> Consensus<N> {
                 .collect_vec()
         };
         // Iterate over the transactions.
+        let primary_sender = self.primary_sender()?;
         for transaction in transactions.into_iter() {
             let transaction_id = transaction.id();
             trace!("Adding unconfirmed transaction '{}' to the memory pool...", fmt_id(transaction_id));
-            // Send the unconfirmed transaction to the primary.
-            if let Err(e) =
-                self.primary_sender().send_unconfirmed_transaction(transaction_id, Data::Object(transaction)).await
-            {
+            // Send the unconfirmed transaction to the primary, which verifies it before adding it to its worker.
+            let data = Data::Object(transaction.clone());
+            if let Err(e) = primary_sender.send_unconfirmed_transaction(transaction_id, data).await {
                 // If the BFT is synced, then log the warning.
-                if self.bft.is_synced() {
+                if self.bft().is_synced() {
                     warn!(
                         "Failed to add unconfirmed transaction '{}' to the memory pool - {e}",
                         fmt_id(transaction_id)
                     );
                 }
+                // Record the transaction as evicted.
+                self.mempool_index.lock().remove(&transaction_id);
+                self.transaction_outcomes.lock().insert(transaction_id, TransactionOutcome::Evicted {
+                    cause: format!("The memory pool refused the transaction - {e}"),
+                });
+                continue;
+            }
+            // Index the serial numbers spent by the verified transaction, resolving its conflicts with
+            // the transactions that were verified since it was queued.
+            if let Err(error) = self.admit_verified_transaction(&transaction) {
+                self.mempool_index.lock().remove(&transaction_id);
+                // Queue the transaction behind the conflicting transactions, if the policy permits.
+                if let Err(error) = self.queue_conflicting_transaction(transaction, error) {
+                    debug!("Evicting unconfirmed transaction '{}' - {error}", fmt_id(transaction_id));
+                    self.transaction_outcomes.lock().insert(transaction_id, TransactionOutcome::Evicted {
+                        cause: format!("The memory pool refused the transaction - {error}"),
+                    });
+                }
             }
         }
-        Ok(())
+        Ok(decision)
+    }
+
+    /// Ensures the node has sufficient disk space to accept new transmissions, if the disk monitor is enabled.
+    pub fn ensure_disk_space_for_intake(&self) -> Result<()> {
+        match &self.disk_monitor {
+            Some(disk_monitor) => disk_monitor.ensure_intake(),
+            None => Ok(()),
+        }
+    }
+
+    /// Removes the unconfirmed transactions that reference a denylisted program from the memory pool.
+    /// Returns the IDs of the evicted transactions.
+    ///
+    /// Note: Transactions that are already in the in-flight batch proposal (or a certified batch) are never evicted.
+    pub fn evict_denylisted_transactions(&self) -> Vec<N::TransactionID> {
+        // Return early if no program is denylisted.
+        if self.program_denylist.is_empty() {
+            return vec![];
+        }
+        // Remove the denied transactions from the queue.
+        let mut evicted = {
+            let mut tx_queue = self.transactions_queue.lock();
+            let mut evicted = self.program_denylist.evict_from(&mut tx_queue.deployments);
+            evicted.extend(self.program_denylist.evict_from(&mut tx_queue.executions));
+            evicted
+        };
+        // Determine the denied transactions in the workers.
+        let bft = self.bft();
+        let denied = bft
+            .unconfirmed_transactions()
+            .filter(|(transaction_id, _)| !bft.is_transmission_in_flight(transaction_id))
+            .filter_map(|(transaction_id, transaction)| match transaction.deserialize_blocking() {
+                Ok(transaction) => self.program_denylist.denied_program(&transaction).map(|_| transaction_id),
+                Err(error) => {
+                    warn!("Failed to deserialize unconfirmed transaction '{}' - {error}", fmt_id(transaction_id));
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        // Remove the denied transactions from the workers.
+        for transaction_id in denied {
+            match bft.remove_unconfirmed_transaction(transaction_id) {
+                Ok(true) => evicted.push(transaction_id),
+                Ok(false) => (),
+                Err(error) => debug!("Unable to evict transaction '{}' - {error}", fmt_id(transaction_id)),
+            }
+        }
+        // Record the evicted transactions.
+        for transaction_id in &evicted {
+            self.replacements.lock().remove(transaction_id);
+            self.mempool_index.lock().remove(transaction_id);
+            self.transaction_outcomes.lock().insert(*transaction_id, TransactionOutcome::Evicted {
+                cause: "The transaction references a denylisted program".to_string(),
+            });
+            info!("Evicted unconfirmed transaction '{}' (denylisted program)", fmt_id(transaction_id));
+            self.release_queued_transactions(transaction_id);
+        }
+        evicted
+    }
+
+    /// Returns the IDs of the unconfirmed transactions that the given transaction would replace,
+    /// or an error if it conflicts with unconfirmed transactions that it cannot replace.
+    ///
+    /// Note: Transactions that are already in the in-flight batch proposal (or a certified batch) are never replaced.
+    fn check_conflicting_transactions(&self, transaction: &Transaction<N>) -> Result<IndexSet<N::TransactionID>> {
+        // Retrieve the serial numbers spent by the transaction.
+        let serial_numbers = transaction.serial_numbers().copied().collect::<Vec<_>>();
+        // If the transaction does not spend any records, it cannot conflict.
+        if serial_numbers.is_empty() {
+            return Ok(Default::default());
+        }
+        // Retrieve the fee of the transaction.
+        let fee = *transaction.fee_amount()?;
+        // Acquire the lock on the replacement index.
+        let mut replacements = self.replacements.lock();
+        // Remove the conflicting transactions that are no longer pending, e.g. as the worker dropped them.
+        for stale_id in replacements.remove_stale(&serial_numbers, |id| self.is_pending_transaction(id)) {
+            self.release_queued_transactions(&stale_id);
+        }
+        // Determine the conflicting transactions that would be replaced.
+        replacements.check_replacement(&serial_numbers, fee, self.replacement_fee_increment(), |transaction_id| {
+            self.bft().is_transmission_in_flight(transaction_id)
+        })
+    }
+
+    /// Verifies the given transaction in the workers, and then removes the unconfirmed transactions that conflict
+    /// with it from the memory pool, if it pays a sufficiently higher fee.
+    ///
+    /// Note: The conflicting transactions are only removed once the worker accepted the transaction,
+    /// so that an invalid transaction that claims a higher fee cannot evict the valid transactions it conflicts with.
+    async fn replace_conflicting_transactions(&self, transaction: Transaction<N>) -> Result<ConflictDecision<N>> {
+        let transaction_id = transaction.id();
+        // Send the transaction to the primary, which verifies it before adding it to its worker.
+        self.primary_sender()?.send_unconfirmed_transaction(transaction_id, Data::Object(transaction.clone())).await?;
+        // Replace the conflicting transactions.
+        let replaced = self.admit_verified_transaction(&transaction)?;
+        self.mempool_index.lock().insert(&transaction);
+        self.seen_transactions.lock().put(transaction_id, ());
+        // Record the transaction as pending.
+        self.transaction_outcomes.lock().insert(transaction_id, TransactionOutcome::Pending);
+        Ok(ConflictDecision::Replaced(replaced.into_iter().collect()))
+    }
+
+    /// Indexes the serial numbers spent by the given transaction, once its worker verified it, and removes the
+    /// unconfirmed transactions that conflict with it from the memory pool, if it pays a sufficiently higher fee.
+    /// Returns the IDs of the replaced transactions.
+    ///
+    /// If the transaction conflicts with unconfirmed transactions that it cannot replace, it is removed from
+    /// its worker.
+    /// Note: Only verified transactions are indexed, so that an invalid transaction cannot block the valid
+    /// transactions that spend the same records.
+    fn admit_verified_transaction(&self, transaction: &Transaction<N>) -> Result<IndexSet<N::TransactionID>> {
+        let transaction_id = transaction.id();
+        // Retrieve the serial numbers spent by the transaction.
+        let serial_numbers = transaction.serial_numbers().copied().collect::<Vec<_>>();
+        // If the transaction does not spend any records, it cannot conflict.
+        if serial_numbers.is_empty() {
+            return Ok(Default::default());
+        }
+        // Retrieve the fee of the transaction.
+        let fee = *transaction.fee_amount()?;
+
+        // Acquire the lock on the replacement index, for the duration of the replacement.
+        let mut replacements = self.replacements.lock();
+        // Remove the previous entry of the transaction, e.g. if it was reinserted, and the conflicting transactions
+        // that are no longer pending, e.g. as the worker dropped them.
+        replacements.remove(&transaction_id);
+        for stale_id in replacements.remove_stale(&serial_numbers, |id| self.is_pending_transaction(id)) {
+            self.release_queued_transactions(&stale_id);
+        }
+        // Ensure the conflicting transactions can be replaced, as they may have been verified or entered a batch
+        // since the transaction was checked.
+        let conflicts = match replacements.check_replacement(
+            &serial_numbers,
+            fee,
+            self.replacement_fee_increment(),
+            |transaction_id| self.bft().is_transmission_in_flight(transaction_id),
+        ) {
+            Ok(conflicts) => conflicts,
+            Err(error) => {
+                // Remove the transaction from the workers, as it may not enter the memory pool.
+                if let Err(e) = self.bft().remove_unconfirmed_transaction(transaction_id) {
+                    warn!("Failed to remove unconfirmed transaction '{}' - {e}", fmt_id(transaction_id));
+                }
+                return Err(error);
+            }
+        };
+        // Remove the conflicting transactions from the memory pool.
+        for conflict_id in &conflicts {
+            // Remove the conflicting transaction from the queue.
+            {
+                let mut tx_queue = self.transactions_queue.lock();
+                tx_queue.deployments.pop(conflict_id);
+                tx_queue.executions.pop(conflict_id);
+            }
+            // Remove the conflicting transaction from the workers.
+            self.bft().remove_unconfirmed_transaction(*conflict_id)?;
+            // Remove the conflicting transaction from the indexes.
+            replacements.remove(conflict_id);
+            self.mempool_index.lock().remove(conflict_id);
+            // Record the conflicting transaction as evicted.
+            self.transaction_outcomes.lock().insert(*conflict_id, TransactionOutcome::Evicted {
+                cause: format!("Replaced by transaction '{transaction_id}' with a higher fee"),
+            });
+            info!("Replaced unconfirmed transaction '{}' with '{}'", fmt_id(conflict_id), fmt_id(transaction_id));
+            self.release_queued_transactions(conflict_id);
+        }
+        // Index the transaction.
+        replacements.insert(transaction_id, fee, serial_numbers);
+        Ok(conflicts)
+    }
+
+    /// Returns `true` if the given transaction is in the memory pool of the workers, or in flight.
+    fn is_pending_transaction(&self, transaction_id: &N::TransactionID) -> bool {
+        self.bft().contains_unconfirmed_transaction(*transaction_id)
+    }
+
+    /// Queues the given transaction behind the unconfirmed transactions it conflicts with, if the conflict policy
+    /// permits it, and returns the decision. Otherwise, returns the given error.
+    fn queue_conflicting_transaction(
+        &self,
+        transaction: Transaction<N>,
+        error: anyhow::Error,
+    ) -> Result<ConflictDecision<N>> {
+        // Ensure the error is a conflict, and the conflict policy is to queue the transaction.
+        let conflicting_ids = match error.downcast_ref::<TransactionConflict<N>>() {
+            Some(conflict) if self.conflict_policy() == ConflictPolicy::Queue => conflict.conflicting_ids.clone(),
+            _ => return Err(error),
+        };
+        let transaction_id = transaction.id();
+        let serial_numbers = transaction.serial_numbers().copied().collect();
+        // Queue the transaction.
+        let conflicts = conflicting_ids.iter().copied().collect();
+        let dropped = self.conflict_queue.lock().insert(transaction_id, transaction, serial_numbers, conflicts);
+        // Record the transaction that was dropped from the full queue.
+        if let Some(dropped_id) = dropped {
+            self.transaction_outcomes.lock().insert(dropped_id, TransactionOutcome::Evicted {
+                cause: "The queue of conflicting transactions was full".to_string(),
+            });
+        }
+        // Record the transaction as pending.
+        self.transaction_outcomes.lock().insert(transaction_id, TransactionOutcome::Pending);
+        debug!("Queued unconfirmed transaction '{}' behind conflicting transactions", fmt_id(transaction_id));
+        Ok(ConflictDecision::Queued(conflicting_ids))
+    }
+
+    /// Adds the queued transactions that no longer conflict with any unconfirmed transaction to the memory pool,
+    /// once the given transaction was evicted.
+    fn release_queued_transactions(&self, evicted_id: &N::TransactionID) {
+        let released = self.conflict_queue.lock().release(evicted_id);
+        if released.is_empty() {
+            return;
+        }
+        let self_ = self.clone();
+        tokio::spawn(async move {
+            for (transaction_id, transaction) in released {
+                // Forget the transaction, so that it is not skipped as recently seen.
+                self_.seen_transactions.lock().pop(&transaction_id);
+                if let Err(error) = self_.add_unconfirmed_transaction(transaction).await {
+                    debug!("Unable to add queued transaction '{}' - {error}", fmt_id(transaction_id));
+                    self_.transaction_outcomes.lock().insert(transaction_id, TransactionOutcome::Evicted {
+                        cause: format!("The memory pool refused the queued transaction - {error}"),
+                    });
+                }
+            }
+        });
     }
 }
 
@@ -407,12 +1062,108 @@ impl<N: Network> Consensus<N> {
         #[cfg(feature = "metrics")]
         let current_block_timestamp = self.ledger.latest_block().header().metadata().timestamp();
 
+        // Remove the transmissions that are already in the ledger, e.g. if the BFT re-delivered the subdag.
+        let (transmissions, num_confirmed) = remove_confirmed_transmissions(transmissions, |id| {
+            matches!(self.ledger.contains_transmission(id), Ok(true))
+        });
+        // Note: The block is built from the remaining transmissions, even if none remain,
+        // as the block still commits the certificates of the subdag.
+        if num_confirmed > 0 {
+            info!(
+                "Excluding {num_confirmed} transmissions that are already in the ledger from the subdag at round {}",
+                subdag.anchor_round()
+            );
+        }
+
+        // Time each stage of advancing to the next block.
+        let mut timer = StageTimer::start();
         // Create the candidate next block.
-        let next_block = self.ledger.prepare_advance_to_next_quorum_block(subdag, transmissions)?;
+        let next_block = self.ledger.prepare_advance_to_next_quorum_block(subdag, transmissions.clone())?;
+        let speculate_micros = timer.lap();
         // Check that the block is well-formed.
         self.ledger.check_next_block(&next_block)?;
+        let verify_micros = timer.lap();
+        // Record the block in the journal, so that an interrupted advance is recovered when the node restarts.
+        self.block_journal.write(&next_block, &transmissions)?;
+        let journal_micros = timer.lap();
         // Advance to the next block.
-        self.ledger.advance_to_next_block(&next_block)?;
+        let result = self.ledger.advance_to_next_block(&next_block);
+        let advance_micros = timer.lap();
+        // Clear the journal, as the block is either advanced to, or its transmissions are reinserted by the caller.
+        if let Err(e) = self.block_journal.truncate() {
+            warn!("Failed to truncate the block journal - {e}");
+        }
+        result?;
+        // Record the timing breakdown of the block.
+        let timing = BlockTiming {
+            height: next_block.height(),
+            num_transactions: next_block.transactions().len(),
+            speculate_micros,
+            verify_micros,
+            journal_micros,
+            advance_micros,
+            total_micros: timer.total(),
+        };
+        self.block_timings.lock().insert(timing);
+        // Release the spent serial numbers and the aborted transactions of the block from the replacement index.
+        {
+            let mut replacements = self.replacements.lock();
+            replacements.remove_spent(next_block.serial_numbers());
+            for transaction_id in next_block.aborted_transaction_ids() {
+                if replacements.remove(transaction_id) {
+                    self.release_queued_transactions(transaction_id);
+                }
+            }
+        }
+        // Drop the queued transactions that conflict with a transaction in the block, as they can no longer be valid.
+        for transaction_id in self.conflict_queue.lock().remove_spent(next_block.serial_numbers()) {
+            self.transaction_outcomes.lock().insert(transaction_id, TransactionOutcome::Evicted {
+                cause: "The transaction conflicts with a confirmed transaction".to_string(),
+            });
+        }
+        // Remove the transactions that were confirmed or aborted in the block from the memory pool index.
+        {
+            let mut mempool_index = self.mempool_index.lock();
+            for confirmed in next_block.transactions().iter() {
+                if let Ok(transaction_id) = confirmed.to_unconfirmed_transaction_id() {
+                    mempool_index.remove(&transaction_id);
+                }
+            }
+            for transaction_id in next_block.aborted_transaction_ids() {
+                mempool_index.remove(transaction_id);
+            }
+        }
+        // Release the solutions that were confirmed or aborted in the block from the solution quota.
+        {
+            let mut solution_quota = self.solution_quota.lock();
+            for solution_id in next_block.solutions().solution_ids().chain(next_block.aborted_solution_ids()) {
+                solution_quota.remove(solution_id);
+            }
+            // If the epoch changed, purge the stale-epoch solutions from the queue, and reset the counts.
+            if let Ok(epoch_hash) = self.ledger.latest_epoch_hash() {
+                if solution_quota.set_epoch(epoch_hash) {
+                    let mut queue = self.solutions_queue.lock();
+                    let stale_ids = queue
+                        .iter()
+                        .filter(|(_, solution)| solution.epoch_hash() != epoch_hash)
+                        .map(|(solution_id, _)| *solution_id)
+                        .collect::<Vec<_>>();
+                    for solution_id in &stale_ids {
+                        queue.pop(solution_id);
+                    }
+                    // Recount the current-epoch solutions that remain in the queue.
+                    for (solution_id, solution) in queue.iter() {
+                        solution_quota.insert(*solution_id, solution.address()).ok();
+                    }
+                    debug!("Purged {} stale-epoch solutions from the memory pool", stale_ids.len());
+                }
+            }
+        }
+        // Record the outcomes of the transactions in the block.
+        if let Err(e) = self.transaction_outcomes.lock().insert_block(&next_block) {
+            warn!("Failed to record the transaction outcomes for block {} - {e}", next_block.height());
+        }
+        self.record_aborted_reasons(&next_block, &transmissions);
 
         #[cfg(feature = "metrics")]
         {
@@ -431,17 +1182,62 @@ impl<N: Network> Consensus<N> {
             metrics::gauge(metrics::consensus::COMMITTED_CERTIFICATES, num_committed_certificates as f64);
             metrics::histogram(metrics::consensus::CERTIFICATE_COMMIT_LATENCY, elapsed.as_secs_f64());
             metrics::histogram(metrics::consensus::BLOCK_LATENCY, block_latency as f64);
+            metrics::histogram(metrics::blocks::SPECULATE_TIME, timing.speculate_micros as f64 / 1_000_000f64);
+            metrics::histogram(metrics::blocks::VERIFY_TIME, timing.verify_micros as f64 / 1_000_000f64);
+            metrics::histogram(metrics::blocks::JOURNAL_TIME, timing.journal_micros as f64 / 1_000_000f64);
+            metrics::histogram(metrics::blocks::ADVANCE_TIME, timing.advance_micros as f64 / 1_000_000f64);
+            metrics::histogram(metrics::blocks::TOTAL_TIME, timing.total_micros as f64 / 1_000_000f64);
         }
         Ok(())
     }
 
+    /// Records the reasons the aborted transactions of the given block were rejected, by checking each against
+    /// the ledger.
+    /// Note: A block does not record the reason a transaction was aborted, so it is recovered in the background.
+    fn record_aborted_reasons(&self, block: &Block<N>, transmissions: &IndexMap<TransmissionID<N>, Transmission<N>>) {
+        // Retrieve the aborted transactions of the block.
+        let aborted = block
+            .aborted_transaction_ids()
+            .iter()
+            .filter_map(|transaction_id| match transmissions.get(&TransmissionID::Transaction(*transaction_id)) {
+                Some(Transmission::Transaction(transaction)) => Some((*transaction_id, transaction.clone())),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if aborted.is_empty() {
+            return;
+        }
+        let height = block.height();
+        let self_ = self.clone();
+        self.spawn(async move {
+            for (transaction_id, transaction) in aborted {
+                // Note: A transaction that passes the check was aborted for a reason the ledger does not report.
+                if let Err(error) = self_.ledger.check_transaction_basic(transaction_id, transaction).await {
+                    let reason = format!("The transaction was aborted, so the fee was not consumed - {error}");
+                    self_.transaction_outcomes.lock().set_aborted_reason(&transaction_id, height, reason);
+                }
+            }
+        });
+    }
+
     /// Reinserts the given transmissions into the memory pool.
     async fn reinsert_transmissions(&self, transmissions: IndexMap<TransmissionID<N>, Transmission<N>>) {
         // Iterate over the transmissions.
         for (transmission_id, transmission) in transmissions.into_iter() {
+            // Skip the transmissions that are already in the ledger, as they must not be proposed again.
+            if let Ok(true) = self.ledger.contains_transmission(&transmission_id) {
+                trace!("Skipping the reinsertion of transmission {}, as it is in the ledger", fmt_id(transmission_id));
+                continue;
+            }
             // Reinsert the transmission into the memory pool.
             if let Err(e) = self.reinsert_transmission(transmission_id, transmission).await {
                 warn!("Unable to reinsert transmission {} into the memory pool - {e}", fmt_id(transmission_id));
+                // Record the transaction as evicted.
+                if let TransmissionID::Transaction(transaction_id) = transmission_id {
+                    self.transaction_outcomes.lock().insert(transaction_id, TransactionOutcome::Evicted {
+                        cause: format!("Failed to reinsert the transaction after a failed block - {e}"),
+                    });
+                }
             }
         }
     }
@@ -459,11 +1255,11 @@ impl<N: Network> Consensus<N> {
             (TransmissionID::Ratification, Transmission::Ratification) => return Ok(()),
             (TransmissionID::Solution(solution_id), Transmission::Solution(solution)) => {
                 // Send the solution to the primary.
-                self.primary_sender().tx_unconfirmed_solution.send((solution_id, solution, callback)).await?;
+                self.primary_sender()?.tx_unconfirmed_solution.send((solution_id, solution, callback)).await?;
             }
             (TransmissionID::Transaction(transaction_id), Transmission::Transaction(transaction)) => {
                 // Send the transaction to the primary.
-                self.primary_sender().tx_unconfirmed_transaction.send((transaction_id, transaction, callback)).await?;
+                self.primary_sender()?.tx_unconfirmed_transaction.send((transaction_id, transaction, callback)).await?;
             }
             _ => bail!("Mismatching `(transmission_id, transmission)` pair in consensus"),
         }
@@ -476,12 +1272,33 @@ impl<N: Network> Consensus<N> {
         self.handles.lock().push(tokio::spawn(future));
     }
 
+    /// Stops the consensus, shutting down the BFT and the consensus handlers, and leaving the ledger untouched.
+    /// The consensus may then be run again, with fresh channels.
+    ///
+    /// Note: The event recorder of the gateway is not carried over to the rebuilt BFT.
+    pub async fn stop(&self) -> Result<()> {
+        {
+            let mut state = self.state.lock();
+            ensure!(matches!(*state, ConsensusState::Running(_)), "The consensus is not running");
+            *state = ConsensusState::Stopped;
+        }
+        info!("Stopping consensus...");
+        self.shut_down_components().await;
+        Ok(())
+    }
+
     /// Shuts down the BFT.
     pub async fn shut_down(&self) {
         info!("Shutting down consensus...");
+        *self.state.lock() = ConsensusState::Stopped;
+        self.shut_down_components().await;
+    }
+
+    /// Shuts down the BFT, and aborts the consensus handlers.
+    async fn shut_down_components(&self) {
         // Shut down the BFT.
-        self.bft.shut_down().await;
+        self.bft().shut_down().await;
         // Abort the tasks.
-        self.handles.lock().iter().for_each(|handle| handle.abort());
+        self.handles.lock().drain(..).for_each(|handle| handle.abort());
     }
 }
@@ -0,0 +1,184 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkos_account::Account;
+use snarkos_node_bft::helpers::{init_primary_channels, Signer};
+use snarkos_node_bft_ledger_service::CoreLedgerService;
+use snarkos_node_consensus::{Consensus, TransmissionStorageMode};
+use snarkvm::{
+    ledger::{
+        block::Block,
+        committee::{Committee, MIN_VALIDATOR_STAKE},
+        store::{helpers::memory::ConsensusMemory, ConsensusStore},
+        Ledger,
+    },
+    prelude::{Address, Network, TestRng, VM},
+};
+
+use aleo_std::StorageMode;
+use indexmap::IndexMap;
+use std::{
+    sync::{Arc, OnceLock},
+    time::{Duration, Instant},
+};
+
+type CurrentNetwork = snarkvm::prelude::MainnetV0;
+type CurrentLedger = Ledger<CurrentNetwork, ConsensusMemory<CurrentNetwork>>;
+
+/// The number of validators in the test committee.
+const NUM_VALIDATORS: u16 = 4;
+
+/// Returns the accounts of the test committee.
+fn sample_accounts() -> Vec<Account<CurrentNetwork>> {
+    (0..NUM_VALIDATORS).map(|i| Account::new(&mut TestRng::fixed(i as u64)).unwrap()).collect()
+}
+
+/// Returns the genesis block of the test committee, which is shared by the tests.
+fn sample_genesis_block() -> Block<CurrentNetwork> {
+    static GENESIS: OnceLock<Block<CurrentNetwork>> = OnceLock::new();
+    GENESIS
+        .get_or_init(|| {
+            let accounts = sample_accounts();
+            let members = accounts.iter().map(|account| (account.address(), (MIN_VALIDATOR_STAKE, false))).collect();
+            let committee = Committee::<CurrentNetwork>::new(0u64, members).unwrap();
+            let bonded_balances = accounts
+                .iter()
+                .map(|account| (account.address(), (account.address(), account.address(), MIN_VALIDATOR_STAKE)))
+                .collect::<IndexMap<_, _>>();
+            let public_balance =
+                (CurrentNetwork::STARTING_SUPPLY - NUM_VALIDATORS as u64 * MIN_VALIDATOR_STAKE) / NUM_VALIDATORS as u64;
+            let public_balances =
+                accounts.iter().map(|account| (account.address(), public_balance)).collect::<IndexMap<_, _>>();
+            let vm = VM::from(ConsensusStore::<CurrentNetwork, ConsensusMemory<CurrentNetwork>>::open(None).unwrap())
+                .unwrap();
+            let rng = &mut TestRng::default();
+            vm.genesis_quorum(accounts[0].private_key(), committee, public_balances, bonded_balances, rng).unwrap()
+        })
+        .clone()
+}
+
+/// Returns the consensus of the given validator of the test committee, and its ledger.
+/// Note: The development ID determines the port of the gateway.
+fn sample_consensus(account: Account<CurrentNetwork>, dev: u16) -> (Consensus<CurrentNetwork>, CurrentLedger) {
+    let ledger = CurrentLedger::load(sample_genesis_block(), StorageMode::Production).unwrap();
+    let ledger_service = Arc::new(CoreLedgerService::new(ledger.clone(), Default::default()));
+    let consensus = Consensus::new(
+        account,
+        ledger_service,
+        None,
+        &[],
+        StorageMode::Development(dev),
+        TransmissionStorageMode::Memory,
+    )
+    .unwrap();
+    (consensus, ledger)
+}
+
+/// Runs the given consensus with fresh channels.
+async fn run(consensus: &Consensus<CurrentNetwork>) -> anyhow::Result<()> {
+    let (primary_sender, primary_receiver) = init_primary_channels();
+    consensus.run(primary_sender, primary_receiver).await
+}
+
+/// Connects the gateways of the given validators to each other.
+async fn connect_all(validators: &[(Consensus<CurrentNetwork>, CurrentLedger)]) {
+    for (i, (consensus, _)) in validators.iter().enumerate() {
+        for (other, _) in validators.iter().skip(i + 1) {
+            consensus.bft().primary().gateway().connect(other.bft().primary().gateway().local_ip());
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+}
+
+/// Waits until a quorum of the given validators reached the given height, or panics after the given timeout.
+async fn wait_for_height(validators: &[(Consensus<CurrentNetwork>, CurrentLedger)], height: u32, timeout: Duration) {
+    let start = Instant::now();
+    let quorum = validators.len() * 2 / 3 + 1;
+    while validators.iter().filter(|(_, ledger)| ledger.latest_height() >= height).count() < quorum {
+        assert!(start.elapsed() < timeout, "The validators did not reach block {height}");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_run_and_stop() {
+    let account = sample_accounts().remove(0);
+    let address: Address<CurrentNetwork> = account.address();
+    let (consensus, _ledger) = sample_consensus(account, 30);
+
+    // Ensure the consensus cannot be stopped before it runs.
+    assert!(!consensus.is_running());
+    assert!(consensus.stop().await.is_err());
+    assert!(consensus.primary_sender().is_err());
+
+    // Run the consensus, and ensure it cannot run twice.
+    run(&consensus).await.unwrap();
+    assert!(consensus.is_running());
+    assert!(consensus.primary_sender().is_ok());
+    assert!(run(&consensus).await.is_err());
+
+    // Stop the consensus, and ensure it cannot be stopped twice.
+    consensus.stop().await.unwrap();
+    assert!(!consensus.is_running());
+    assert!(consensus.primary_sender().is_err());
+    assert!(consensus.stop().await.is_err());
+
+    // Run the consensus again, and ensure the rebuilt BFT listens on the same gateway.
+    run(&consensus).await.unwrap();
+    assert!(consensus.is_running());
+    let gateway = consensus.bft().primary().gateway().clone();
+    assert_eq!(gateway.local_ip().port(), 5030);
+    assert_eq!(gateway.signer().address(), address);
+    assert_eq!(consensus.bft().primary().current_round(), 1);
+
+    consensus.shut_down().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "long-running e2e test"]
+async fn test_blocks_are_produced_after_restart() {
+    // Note: A block without transmissions is only produced once a minute, to keep the timestamps progressing.
+    const TIMEOUT: Duration = Duration::from_secs(180);
+
+    let validators = sample_accounts()
+        .into_iter()
+        .enumerate()
+        .map(|(i, account)| sample_consensus(account, 20 + i as u16))
+        .collect::<Vec<_>>();
+
+    // Run the validators, and wait for the first block.
+    for (consensus, _) in &validators {
+        run(consensus).await.unwrap();
+    }
+    connect_all(&validators).await;
+    wait_for_height(&validators, 1, TIMEOUT).await;
+
+    // Stop the validators, and ensure the ledgers are untouched.
+    for (consensus, _) in &validators {
+        consensus.stop().await.unwrap();
+    }
+    let height = validators.iter().map(|(_, ledger)| ledger.latest_height()).max().unwrap();
+    assert!(height >= 1);
+
+    // Run the validators again, in the same process, and ensure they continue to produce blocks.
+    for (consensus, _) in &validators {
+        run(consensus).await.unwrap();
+    }
+    connect_all(&validators).await;
+    wait_for_height(&validators, height + 1, TIMEOUT).await;
+
+    for (consensus, _) in &validators {
+        consensus.shut_down().await;
+    }
+}
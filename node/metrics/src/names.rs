@@ -12,14 +12,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-pub(super) const COUNTER_NAMES: [&str; 1] = [bft::LEADERS_ELECTED];
+pub(super) const COUNTER_NAMES: [&str; 3] =
+    [bft::LEADERS_ELECTED, router::DROPPED_AUDIT_EVENTS, tcp::DROPPED_OUTBOUND_MESSAGES];
 
-pub(super) const GAUGE_NAMES: [&str; 18] = [
+pub(super) const GAUGE_NAMES: [&str; 27] = [
     bft::CONNECTED,
     bft::CONNECTING,
     bft::LAST_STORED_ROUND,
     bft::PROPOSAL_ROUND,
     bft::CERTIFIED_BATCHES,
+    bft::SOLUTION_FETCH_QUEUE_DEPTH,
+    bft::TRANSACTION_FETCH_QUEUE_DEPTH,
     blocks::HEIGHT,
     blocks::SOLUTIONS,
     blocks::TRANSACTIONS,
@@ -29,16 +32,30 @@ pub(super) const GAUGE_NAMES: [&str; 18] = [
     consensus::UNCONFIRMED_SOLUTIONS,
     consensus::UNCONFIRMED_TRANSACTIONS,
     consensus::UNCONFIRMED_TRANSMISSIONS,
+    disk::AVAILABLE_BYTES,
+    disk::LEDGER_BYTES,
     router::CONNECTED,
     router::CANDIDATE,
     router::RESTRICTED,
+    router::DEPRECATED,
+    router::QUEUED_BLOCK_RESPONSES,
     tcp::TCP_TASKS,
+    verification::TRANSACTION_QUEUE_DEPTH,
+    verification::SOLUTION_QUEUE_DEPTH,
+    verification::CERTIFICATE_QUEUE_DEPTH,
 ];
 
-pub(super) const HISTOGRAM_NAMES: [&str; 7] = [
+pub(super) const HISTOGRAM_NAMES: [&str; 14] = [
     bft::COMMIT_ROUNDS_LATENCY,
+    bft::SOLUTION_FETCH_LATENCY,
+    bft::TRANSACTION_FETCH_LATENCY,
     consensus::CERTIFICATE_COMMIT_LATENCY,
     consensus::BLOCK_LATENCY,
+    blocks::SPECULATE_TIME,
+    blocks::VERIFY_TIME,
+    blocks::JOURNAL_TIME,
+    blocks::ADVANCE_TIME,
+    blocks::TOTAL_TIME,
     tcp::NOISE_CODEC_ENCRYPTION_TIME,
     tcp::NOISE_CODEC_DECRYPTION_TIME,
     tcp::NOISE_CODEC_ENCRYPTION_SIZE,
@@ -53,6 +70,10 @@ pub mod bft {
     pub const LEADERS_ELECTED: &str = "snarkos_bft_leaders_elected_total";
     pub const PROPOSAL_ROUND: &str = "snarkos_bft_primary_proposal_round";
     pub const CERTIFIED_BATCHES: &str = "snarkos_bft_primary_certified_batches";
+    pub const SOLUTION_FETCH_QUEUE_DEPTH: &str = "snarkos_bft_worker_solution_fetch_queue_depth";
+    pub const SOLUTION_FETCH_LATENCY: &str = "snarkos_bft_worker_solution_fetch_latency_secs";
+    pub const TRANSACTION_FETCH_QUEUE_DEPTH: &str = "snarkos_bft_worker_transaction_fetch_queue_depth";
+    pub const TRANSACTION_FETCH_LATENCY: &str = "snarkos_bft_worker_transaction_fetch_latency_secs";
 }
 
 pub mod blocks {
@@ -60,6 +81,11 @@ pub mod blocks {
     pub const TRANSACTIONS: &str = "snarkos_blocks_transactions_total";
     pub const TRANSMISSIONS: &str = "snarkos_blocks_transmissions_total";
     pub const SOLUTIONS: &str = "snarkos_blocks_solutions_total";
+    pub const SPECULATE_TIME: &str = "snarkos_blocks_speculate_time_secs";
+    pub const VERIFY_TIME: &str = "snarkos_blocks_verify_time_secs";
+    pub const JOURNAL_TIME: &str = "snarkos_blocks_journal_time_secs";
+    pub const ADVANCE_TIME: &str = "snarkos_blocks_advance_time_secs";
+    pub const TOTAL_TIME: &str = "snarkos_blocks_total_time_secs";
 }
 
 pub mod consensus {
@@ -72,10 +98,18 @@ pub mod consensus {
     pub const UNCONFIRMED_SOLUTIONS: &str = "snarkos_consensus_unconfirmed_solutions_total";
 }
 
+pub mod disk {
+    pub const AVAILABLE_BYTES: &str = "snarkos_disk_available_bytes";
+    pub const LEDGER_BYTES: &str = "snarkos_disk_ledger_bytes";
+}
+
 pub mod router {
     pub const CONNECTED: &str = "snarkos_router_connected_total";
     pub const CANDIDATE: &str = "snarkos_router_candidate_total";
     pub const RESTRICTED: &str = "snarkos_router_restricted_total";
+    pub const DEPRECATED: &str = "snarkos_router_deprecated_total";
+    pub const QUEUED_BLOCK_RESPONSES: &str = "snarkos_router_queued_block_responses_total";
+    pub const DROPPED_AUDIT_EVENTS: &str = "snarkos_router_dropped_audit_events_total";
 }
 
 pub mod tcp {
@@ -84,4 +118,11 @@ pub mod tcp {
     pub const NOISE_CODEC_ENCRYPTION_SIZE: &str = "snarkos_tcp_noise_codec_encryption_size";
     pub const NOISE_CODEC_DECRYPTION_SIZE: &str = "snarkos_tcp_noise_codec_decryption_size";
     pub const TCP_TASKS: &str = "snarkos_tcp_tasks_total";
+    pub const DROPPED_OUTBOUND_MESSAGES: &str = "snarkos_tcp_dropped_outbound_messages_total";
+}
+
+pub mod verification {
+    pub const TRANSACTION_QUEUE_DEPTH: &str = "snarkos_verification_transaction_queue_depth";
+    pub const SOLUTION_QUEUE_DEPTH: &str = "snarkos_verification_solution_queue_depth";
+    pub const CERTIFICATE_QUEUE_DEPTH: &str = "snarkos_verification_certificate_queue_depth";
 }
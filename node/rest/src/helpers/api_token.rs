@@ -0,0 +1,275 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::{bail, ensure, Result};
+use axum::{
+    body::Body,
+    extract::{MatchedPath, State},
+    http::{header::WWW_AUTHENTICATE, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use axum_extra::headers::{
+    authorization::{Authorization, Bearer},
+    HeaderMapExt,
+};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::{collections::HashSet, fmt, sync::Arc};
+
+/// The minimum length of an API token.
+pub const MIN_API_TOKEN_LENGTH: usize = 16;
+
+/// The scopes required by the sensitive routes of the REST server, which are the only routes that require an API token.
+/// Every other route remains public, and is served without checking the `Authorization` header.
+/// Note: The routes are matched by their pattern, as registered in the router.
+pub const ROUTE_SCOPES: &[(&str, Scope)] = &[
+    // The internals of the node.
+    ("/mainnet/node/status", Scope::Read),
+    ("/mainnet/node/sync", Scope::Read),
+    ("/mainnet/node/propagation", Scope::Read),
+    ("/mainnet/peers/all/metrics", Scope::Read),
+    ("/mainnet/peers/all/metrics/subnets", Scope::Read),
+    ("/metrics", Scope::Read),
+    // The management of the node.
+    ("/mainnet/node/denylist", Scope::Admin),
+    // The BFT debug routes (only exposed if enabled) and the development routes (only exposed in development mode).
+    ("/mainnet/bft/dag", Scope::Dev),
+    ("/mainnet/bft/proposal", Scope::Dev),
+    ("/mainnet/bft/round", Scope::Dev),
+    ("/mainnet/bft/certificates/pending", Scope::Dev),
+    ("/mainnet/debug/blockTimings", Scope::Dev),
+    ("/mainnet/dev/deploy", Scope::Dev),
+    ("/mainnet/dev/execute", Scope::Dev),
+    ("/mainnet/dev/compareChain", Scope::Dev),
+    ("/mainnet/node/check", Scope::Dev),
+    ("/mainnet/node/bft/restart", Scope::Dev),
+];
+
+/// Returns the scope required by the given route, if the route requires an API token.
+pub fn route_scope(route: &str) -> Option<Scope> {
+    ROUTE_SCOPES.iter().find(|(pattern, _)| *pattern == route).map(|(_, scope)| *scope)
+}
+
+/// The scope of an API token.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    /// Grants access to the internals of the node.
+    Read,
+    /// Grants access to the management of the node.
+    Admin,
+    /// Grants access to the BFT debug routes and the development routes.
+    Dev,
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Read => write!(f, "read"),
+            Self::Admin => write!(f, "admin"),
+            Self::Dev => write!(f, "dev"),
+        }
+    }
+}
+
+/// An API token, as defined in the node configuration file, and the scopes it grants.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiToken {
+    /// The token.
+    pub token: String,
+    /// The scopes granted by the token.
+    pub scopes: Vec<Scope>,
+}
+
+impl ApiToken {
+    /// Ensures the token is long enough, and grants at least one scope.
+    pub fn check(&self) -> Result<()> {
+        ensure!(
+            self.token.len() >= MIN_API_TOKEN_LENGTH,
+            "An API token must contain at least {MIN_API_TOKEN_LENGTH} characters"
+        );
+        ensure!(!self.scopes.is_empty(), "An API token must grant at least one scope");
+        Ok(())
+    }
+}
+
+/// A marker of the requests that were authorized with an API token.
+#[derive(Copy, Clone, Debug)]
+pub struct ApiTokenAuthorization(pub Scope);
+
+/// The API tokens of the REST server, stored as digests, so that they are compared in constant time.
+pub struct ApiTokens {
+    /// The digests of the tokens, and the scopes they grant.
+    tokens: Vec<([u8; 32], HashSet<Scope>)>,
+}
+
+impl ApiTokens {
+    /// Initializes the API tokens from the given tokens.
+    pub fn new(tokens: &[ApiToken]) -> Result<Self> {
+        let mut digests = HashSet::with_capacity(tokens.len());
+        for token in tokens {
+            token.check()?;
+            if !digests.insert(digest(&token.token)) {
+                bail!("The API tokens must be unique");
+            }
+        }
+        Ok(Self {
+            tokens: tokens.iter().map(|token| (digest(&token.token), token.scopes.iter().copied().collect())).collect(),
+        })
+    }
+
+    /// Returns `Ok` if the given token grants the given scope, and otherwise the status of the response,
+    /// which is `401 Unauthorized` if the token is missing or unknown, and `403 Forbidden` if it lacks the scope.
+    pub fn authorize(&self, token: Option<&str>, scope: Scope) -> Result<(), StatusCode> {
+        let Some(token) = token else { return Err(StatusCode::UNAUTHORIZED) };
+        let token = digest(token);
+        // Note: Every token is compared, so that the duration does not depend on which token matches.
+        let mut scopes = None;
+        for (digest, granted) in &self.tokens {
+            if constant_time_eq(digest, &token) {
+                scopes = Some(granted);
+            }
+        }
+        match scopes {
+            Some(scopes) if scopes.contains(&scope) => Ok(()),
+            Some(_) => Err(StatusCode::FORBIDDEN),
+            None => Err(StatusCode::UNAUTHORIZED),
+        }
+    }
+}
+
+/// Returns the digest of the given token.
+fn digest(token: &str) -> [u8; 32] {
+    Sha256::digest(token.as_bytes()).into()
+}
+
+/// Returns `true` if the given digests are equal, in constant time.
+fn constant_time_eq(a: &[u8; 32], b: &[u8; 32]) -> bool {
+    a.iter().zip(b).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+/// Ensures the request holds an API token with the scope required by its route, if the route requires one.
+pub async fn api_token_middleware(
+    State(tokens): State<Arc<ApiTokens>>,
+    mut request: Request<Body>,
+    next: Next,
+) -> Result<Response, Response> {
+    let scope = request.extensions().get::<MatchedPath>().and_then(|route| route_scope(route.as_str()));
+    if let Some(scope) = scope {
+        let auth = request.headers().typed_get::<Authorization<Bearer>>();
+        match tokens.authorize(auth.as_ref().map(|auth| auth.token()), scope) {
+            Ok(()) => {
+                request.extensions_mut().insert(ApiTokenAuthorization(scope));
+            }
+            Err(StatusCode::UNAUTHORIZED) => {
+                let message = format!("This route requires an API token with the '{scope}' scope");
+                return Err((StatusCode::UNAUTHORIZED, [(WWW_AUTHENTICATE, "Bearer")], message).into_response());
+            }
+            Err(status) => {
+                let message = format!("The API token does not grant the '{scope}' scope");
+                return Err((status, message).into_response());
+            }
+        }
+    }
+    Ok(next.run(request).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{middleware, routing::get, Router};
+    use tower::ServiceExt;
+
+    const READ_TOKEN: &str = "read-token-0123456789";
+    const ADMIN_TOKEN: &str = "admin-token-0123456789";
+    const DEV_TOKEN: &str = "dev-token-0123456789";
+
+    /// Returns a router with a route of each scope and a public route, which require the sample API tokens.
+    fn sample_router() -> Router {
+        let tokens = ApiTokens::new(&[
+            ApiToken { token: READ_TOKEN.to_string(), scopes: vec![Scope::Read] },
+            ApiToken { token: ADMIN_TOKEN.to_string(), scopes: vec![Scope::Admin] },
+            ApiToken { token: DEV_TOKEN.to_string(), scopes: vec![Scope::Dev] },
+        ])
+        .unwrap();
+        Router::new()
+            .route("/mainnet/node/status", get(|| async { "status" }))
+            .route("/mainnet/node/denylist", get(|| async { "denylist" }))
+            .route("/mainnet/bft/dag", get(|| async { "dag" }))
+            .route("/mainnet/block/:height_or_hash", get(|| async { "block" }))
+            .route_layer(middleware::from_fn_with_state(Arc::new(tokens), api_token_middleware))
+    }
+
+    /// Returns the status of the response to a request of the given route, with the given API token (if any).
+    async fn status(route: &str, token: Option<&str>) -> StatusCode {
+        let mut request = Request::builder().uri(route);
+        if let Some(token) = token {
+            request = request.header("authorization", format!("Bearer {token}"));
+        }
+        sample_router().oneshot(request.body(Body::empty()).unwrap()).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_scopes() {
+        for (route, token, other_token) in [
+            ("/mainnet/node/status", READ_TOKEN, ADMIN_TOKEN),
+            ("/mainnet/node/denylist", ADMIN_TOKEN, DEV_TOKEN),
+            ("/mainnet/bft/dag", DEV_TOKEN, READ_TOKEN),
+        ] {
+            // Ensure a missing or unknown token is unauthorized.
+            assert_eq!(status(route, None).await, StatusCode::UNAUTHORIZED, "{route}");
+            assert_eq!(status(route, Some("unknown-token-0123456789")).await, StatusCode::UNAUTHORIZED, "{route}");
+            // Ensure a token without the scope of the route is forbidden.
+            assert_eq!(status(route, Some(other_token)).await, StatusCode::FORBIDDEN, "{route}");
+            // Ensure a token with the scope of the route is authorized.
+            assert_eq!(status(route, Some(token)).await, StatusCode::OK, "{route}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_public_routes() {
+        // Ensure the routes without a scope ignore the `Authorization` header.
+        assert_eq!(status("/mainnet/block/1", None).await, StatusCode::OK);
+        assert_eq!(status("/mainnet/block/1", Some("unknown-token-0123456789")).await, StatusCode::OK);
+        assert_eq!(status("/mainnet/block/1", Some(READ_TOKEN)).await, StatusCode::OK);
+    }
+
+    #[test]
+    fn test_api_tokens() {
+        let token = |token: &str, scopes: Vec<Scope>| ApiToken { token: token.to_string(), scopes };
+
+        // Ensure the tokens are long enough, unique, and grant a scope.
+        assert!(ApiTokens::new(&[token("short", vec![Scope::Read])]).is_err());
+        assert!(ApiTokens::new(&[token(READ_TOKEN, vec![])]).is_err());
+        assert!(ApiTokens::new(&[token(READ_TOKEN, vec![Scope::Read]), token(READ_TOKEN, vec![Scope::Dev])]).is_err());
+
+        // Ensure a token may grant several scopes.
+        let tokens = ApiTokens::new(&[token(ADMIN_TOKEN, vec![Scope::Read, Scope::Admin])]).unwrap();
+        assert_eq!(tokens.authorize(Some(ADMIN_TOKEN), Scope::Read), Ok(()));
+        assert_eq!(tokens.authorize(Some(ADMIN_TOKEN), Scope::Admin), Ok(()));
+        assert_eq!(tokens.authorize(Some(ADMIN_TOKEN), Scope::Dev), Err(StatusCode::FORBIDDEN));
+        assert_eq!(tokens.authorize(None, Scope::Read), Err(StatusCode::UNAUTHORIZED));
+    }
+
+    #[test]
+    fn test_route_scopes() {
+        // Ensure every route is declared once.
+        let routes = ROUTE_SCOPES.iter().map(|(route, _)| *route).collect::<HashSet<_>>();
+        assert_eq!(routes.len(), ROUTE_SCOPES.len());
+        assert_eq!(route_scope("/mainnet/node/denylist"), Some(Scope::Admin));
+        assert_eq!(route_scope("/mainnet/block/:height_or_hash"), None);
+    }
+}
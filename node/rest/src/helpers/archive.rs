@@ -0,0 +1,110 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::Result;
+use axum::body::Bytes;
+use lru::LruCache;
+use parking_lot::Mutex;
+use std::num::NonZeroUsize;
+
+/// The maximum number of chunks of blocks kept in the archive cache.
+const MAX_CACHED_CHUNKS: usize = 16;
+
+/// A serialized chunk of blocks of the block archive, and its checksum.
+#[derive(Clone, Debug)]
+pub struct ArchiveChunk {
+    /// The serialized blocks.
+    pub blocks: Bytes,
+    /// The checksum of the serialized blocks.
+    pub checksum: String,
+}
+
+impl ArchiveChunk {
+    /// Initializes a new chunk from the given serialized blocks.
+    pub fn new(blocks: Vec<u8>) -> Self {
+        let checksum = snarkos_node_cdn::checksum(&blocks);
+        Self { blocks: blocks.into(), checksum }
+    }
+}
+
+/// A bounded cache of the recently generated chunks of the block archive, keyed by their start height.
+/// Note: Only the chunks of blocks that can no longer be rolled back are served, so entries are never invalidated.
+pub struct ArchiveCache {
+    /// The map of start heights to chunks, evicting the least recently used entry.
+    chunks: Mutex<LruCache<u32, ArchiveChunk>>,
+}
+
+impl Default for ArchiveCache {
+    /// Initializes a new archive cache.
+    fn default() -> Self {
+        Self { chunks: Mutex::new(LruCache::new(NonZeroUsize::new(MAX_CACHED_CHUNKS).unwrap())) }
+    }
+}
+
+impl ArchiveCache {
+    /// Returns the cached chunk starting at the given height, or caches the chunk from the given function.
+    pub fn get_or_try_insert(&self, start: u32, f: impl FnOnce() -> Result<ArchiveChunk>) -> Result<ArchiveChunk> {
+        if let Some(chunk) = self.chunks.lock().get(&start) {
+            return Ok(chunk.clone());
+        }
+        // Note: The lock is not held while the chunk is generated.
+        let chunk = f()?;
+        self.chunks.lock().put(start, chunk.clone());
+        Ok(chunk)
+    }
+
+    /// Returns the number of cached chunks.
+    pub fn len(&self) -> usize {
+        self.chunks.lock().len()
+    }
+
+    /// Returns `true` if there are no cached chunks.
+    pub fn is_empty(&self) -> bool {
+        self.chunks.lock().is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[test]
+    fn test_archive_cache() {
+        let cache = ArchiveCache::default();
+        let num_generated = AtomicU32::new(0);
+        let generate = |start: u32| {
+            num_generated.fetch_add(1, Ordering::Relaxed);
+            Ok(ArchiveChunk::new(start.to_le_bytes().to_vec()))
+        };
+
+        // Ensure a chunk is generated once, and served from the cache afterwards.
+        let chunk = cache.get_or_try_insert(0, || generate(0)).unwrap();
+        assert_eq!(chunk.checksum, snarkos_node_cdn::checksum(&0u32.to_le_bytes()));
+        assert_eq!(cache.get_or_try_insert(0, || generate(0)).unwrap().blocks, chunk.blocks);
+        assert_eq!(num_generated.load(Ordering::Relaxed), 1);
+
+        // Ensure the failures are not cached.
+        assert!(cache.get_or_try_insert(50, || anyhow::bail!("The chunk is unavailable")).is_err());
+        assert_eq!(cache.len(), 1);
+
+        // Ensure the least recently used chunk is evicted.
+        for start in 1..=MAX_CACHED_CHUNKS as u32 {
+            cache.get_or_try_insert(start * 50, || generate(start * 50)).unwrap();
+        }
+        assert_eq!(cache.len(), MAX_CACHED_CHUNKS);
+        cache.get_or_try_insert(0, || generate(0)).unwrap();
+        assert_eq!(num_generated.load(Ordering::Relaxed), 2 + MAX_CACHED_CHUNKS as u32);
+    }
+}
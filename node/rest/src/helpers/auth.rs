@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::ApiTokenAuthorization;
 use snarkvm::prelude::*;
 
 use ::time::OffsetDateTime;
@@ -74,6 +75,11 @@ impl Claims {
 }
 
 pub async fn auth_middleware(request: Request<Body>, next: Next) -> Result<Response, Response> {
+    // Skip the JWT, if the request was already authorized with an API token for the route.
+    if request.extensions().get::<ApiTokenAuthorization>().is_some() {
+        return Ok(next.run(request).await);
+    }
+
     // Deconstruct the request to extract the auth token.
     let (mut parts, body) = request.into_parts();
     let auth: TypedHeader<Authorization<Bearer>> =
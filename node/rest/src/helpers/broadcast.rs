@@ -0,0 +1,170 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::RestError;
+use snarkvm::prelude::{block::Transaction, Network};
+
+use serde::Deserialize;
+use std::{collections::HashSet, future::Future};
+
+/// The maximum number of transactions in a single batch broadcast.
+pub const MAX_BROADCAST_TRANSACTIONS: usize = 64;
+/// The maximum size of the body of a single batch broadcast, in bytes.
+pub const MAX_BROADCAST_SIZE_IN_BYTES: usize = 8 * 1024 * 1024; // 8 MiB
+
+/// Parses the given JSON array of transactions, returning either the transaction or an error for each entry, in order.
+/// Note: A malformed entry does not fail the batch, only the body itself must be a bounded JSON array.
+pub fn parse_transaction_batch<N: Network>(body: &[u8]) -> Result<Vec<Result<Transaction<N>, String>>, RestError> {
+    // Ensure the body is within the size limit.
+    if body.len() > MAX_BROADCAST_SIZE_IN_BYTES {
+        return Err(RestError(format!("The batch exceeds the maximum size of {MAX_BROADCAST_SIZE_IN_BYTES} bytes")));
+    }
+    // Parse the body as a JSON array, deferring the parsing of each transaction.
+    let entries: Vec<serde_json::Value> =
+        serde_json::from_slice(body).map_err(|error| RestError(format!("The batch is not a JSON array - {error}")))?;
+    // Ensure the number of transactions is within the limit.
+    if entries.is_empty() || entries.len() > MAX_BROADCAST_TRANSACTIONS {
+        return Err(RestError(format!(
+            "The batch must contain between 1 and {MAX_BROADCAST_TRANSACTIONS} transactions"
+        )));
+    }
+    // Parse each transaction.
+    Ok(entries
+        .into_iter()
+        .map(|entry| serde_json::from_value(entry).map_err(|error| format!("Malformed transaction - {error}")))
+        .collect())
+}
+
+/// Returns the ID claimed by the given JSON transaction, without deserializing the rest of the transaction.
+/// Note: The claimed ID is not authenticated, so it may only be used to skip the intake of the transaction.
+pub fn peek_transaction_id<N: Network>(body: &[u8]) -> Option<N::TransactionID> {
+    #[derive(Deserialize)]
+    #[serde(bound = "")]
+    struct TransactionHeader<N: Network> {
+        id: N::TransactionID,
+    }
+    serde_json::from_slice::<TransactionHeader<N>>(body).ok().map(|header| header.id)
+}
+
+/// Adds the given transactions with `add_transaction`, in order, skipping the malformed and duplicate transactions.
+/// Returns either the transaction ID or an error for each entry, in order, and the accepted transactions.
+pub async fn add_transaction_batch<N: Network, F, Fut>(
+    transactions: Vec<Result<Transaction<N>, String>>,
+    mut add_transaction: F,
+) -> (Vec<Result<N::TransactionID, String>>, Vec<Transaction<N>>)
+where
+    F: FnMut(Transaction<N>) -> Fut,
+    Fut: Future<Output = anyhow::Result<()>>,
+{
+    let mut results = Vec::with_capacity(transactions.len());
+    let mut accepted = Vec::with_capacity(transactions.len());
+    let mut seen = HashSet::with_capacity(transactions.len());
+    for transaction in transactions {
+        let result = match transaction {
+            Ok(transaction) => {
+                let transaction_id = transaction.id();
+                // Ensure the transaction is not repeated in the batch.
+                if !seen.insert(transaction_id) {
+                    Err(format!("Transaction '{transaction_id}' is a duplicate in the batch"))
+                } else {
+                    match add_transaction(transaction.clone()).await {
+                        Ok(()) => {
+                            accepted.push(transaction);
+                            Ok(transaction_id)
+                        }
+                        Err(error) => Err(error.to_string()),
+                    }
+                }
+            }
+            Err(error) => Err(error),
+        };
+        results.push(result);
+    }
+    (results, accepted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkvm::{ledger::ledger_test_helpers::sample_fee_public_transaction, prelude::TestRng};
+
+    use anyhow::anyhow;
+    use parking_lot::Mutex;
+    use serde_json::json;
+
+    type CurrentNetwork = snarkvm::prelude::MainnetV0;
+
+    #[test]
+    fn test_parse_transaction_batch_bounds() {
+        // Ensure the body must be a JSON array.
+        assert!(parse_transaction_batch::<CurrentNetwork>(b"{}").is_err());
+        // Ensure the batch must not be empty.
+        assert!(parse_transaction_batch::<CurrentNetwork>(b"[]").is_err());
+        // Ensure the batch must not contain too many transactions.
+        let body = serde_json::to_vec(&vec![json!({}); MAX_BROADCAST_TRANSACTIONS + 1]).unwrap();
+        assert!(parse_transaction_batch::<CurrentNetwork>(&body).is_err());
+        // Ensure the batch must not exceed the size limit.
+        let body = serde_json::to_vec(&vec!["a".repeat(MAX_BROADCAST_SIZE_IN_BYTES)]).unwrap();
+        assert!(parse_transaction_batch::<CurrentNetwork>(&body).is_err());
+    }
+
+    #[test]
+    fn test_peek_transaction_id() {
+        let rng = &mut TestRng::default();
+        let transaction = sample_fee_public_transaction(rng);
+
+        // Ensure the ID is read from a well-formed transaction.
+        let body = serde_json::to_vec(&transaction).unwrap();
+        assert_eq!(peek_transaction_id::<CurrentNetwork>(&body), Some(transaction.id()));
+        // Ensure nothing is read from a body without an ID.
+        assert_eq!(peek_transaction_id::<CurrentNetwork>(b"{}"), None);
+        assert_eq!(peek_transaction_id::<CurrentNetwork>(b"[]"), None);
+    }
+
+    #[tokio::test]
+    async fn test_add_transaction_batch() {
+        let rng = &mut TestRng::default();
+        let valid = sample_fee_public_transaction(rng);
+        let rejected = sample_fee_public_transaction(rng);
+
+        // Construct a batch with a valid, a malformed, a duplicate, and a rejected transaction.
+        let body = serde_json::to_vec(&json!([valid, { "type": "execute" }, valid, rejected])).unwrap();
+        let transactions = parse_transaction_batch::<CurrentNetwork>(&body).unwrap();
+        assert_eq!(transactions.len(), 4);
+
+        // Add the transactions to a mock memory pool, which rejects the last transaction.
+        let memory_pool = Mutex::new(vec![]);
+        let (results, accepted) = add_transaction_batch(transactions, |transaction| {
+            let result = match transaction.id() == rejected.id() {
+                true => Err(anyhow!("Transaction is invalid")),
+                false => {
+                    memory_pool.lock().push(transaction.id());
+                    Ok(())
+                }
+            };
+            async move { result }
+        })
+        .await;
+
+        // Ensure the results are in the original order.
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[0], Ok(valid.id()));
+        assert!(results[1].as_ref().unwrap_err().starts_with("Malformed transaction"));
+        assert!(results[2].as_ref().unwrap_err().contains("duplicate"));
+        assert_eq!(results[3], Err("Transaction is invalid".to_string()));
+        // Ensure only the valid transaction reached the memory pool, and is accepted for propagation.
+        assert_eq!(*memory_pool.lock(), vec![valid.id()]);
+        assert_eq!(accepted, vec![valid]);
+    }
+}
@@ -0,0 +1,288 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::RestError;
+
+use anyhow::Result;
+use axum::{
+    body::{Body, Bytes},
+    handler::Handler,
+    http::{
+        header::{CACHE_CONTROL, CONTENT_TYPE},
+        HeaderMap,
+        HeaderValue,
+        StatusCode,
+    },
+    middleware,
+    response::Response,
+    routing::{get, MethodRouter},
+};
+use axum_extra::headers::{ETag, HeaderMapExt, IfNoneMatch, LastModified};
+use lru::LruCache;
+use parking_lot::Mutex;
+use std::{
+    fmt::Display,
+    num::NonZeroUsize,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// The maximum number of serialized responses kept in the response cache.
+const MAX_CACHED_RESPONSES: usize = 256;
+
+/// The `Cache-Control` header value of immutable data, which may be cached for a year.
+const CACHE_CONTROL_IMMUTABLE: &str = "public, max-age=31536000, immutable";
+/// The `Cache-Control` header value of data that must be revalidated before it is reused.
+const CACHE_CONTROL_REVALIDATE: &str = "no-cache";
+/// The `Cache-Control` header value of data that must not be cached.
+const CACHE_CONTROL_NO_STORE: &str = "no-store";
+
+/// The caching policy of a REST response.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CachePolicy {
+    /// The data never changes, e.g. a block requested by its hash, or a block that can no longer be rolled back.
+    Immutable,
+    /// The data may still change, e.g. a recent block that can be rolled back, so it is revalidated with its entity tag.
+    Revalidate,
+    /// The data changes frequently, e.g. the latest block or the memory pool, so it is never cached.
+    NoStore,
+}
+
+impl CachePolicy {
+    /// Returns the `Cache-Control` header value of the policy.
+    pub fn header_value(&self) -> HeaderValue {
+        match self {
+            Self::Immutable => HeaderValue::from_static(CACHE_CONTROL_IMMUTABLE),
+            Self::Revalidate => HeaderValue::from_static(CACHE_CONTROL_REVALIDATE),
+            Self::NoStore => HeaderValue::from_static(CACHE_CONTROL_NO_STORE),
+        }
+    }
+}
+
+/// A serialized JSON response body, and the time the data was last modified (if known).
+#[derive(Clone, Debug)]
+pub struct CachedResponse {
+    /// The serialized JSON body.
+    body: Bytes,
+    /// The time the data was last modified, if known.
+    last_modified: Option<SystemTime>,
+}
+
+impl CachedResponse {
+    /// Serializes the given value as pretty-printed JSON, last modified at the given UNIX timestamp (if known).
+    pub fn new<T: serde::Serialize>(value: &T, timestamp: Option<i64>) -> Result<Self> {
+        let body = Bytes::from(serde_json::to_vec_pretty(value)?);
+        let last_modified = timestamp.map(|timestamp| UNIX_EPOCH + Duration::from_secs(timestamp.max(0) as u64));
+        Ok(Self { body, last_modified })
+    }
+
+    /// Returns the response with the given entity tag and caching policy.
+    pub fn into_response(self, etag: ETag, policy: CachePolicy) -> Response {
+        let mut response = Response::new(Body::from(self.body));
+        response.headers_mut().insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        if let Some(last_modified) = self.last_modified {
+            response.headers_mut().typed_insert(LastModified::from(last_modified));
+        }
+        set_cache_headers(response.headers_mut(), etag, policy);
+        response
+    }
+}
+
+/// A bounded cache of serialized responses, keyed by the route and its parameter.
+/// Note: Only responses of immutable data (e.g. a block by its hash) may be cached, as entries are never invalidated.
+pub struct ResponseCache {
+    /// The map of keys to serialized responses, evicting the least recently used entry.
+    responses: Mutex<LruCache<String, CachedResponse>>,
+}
+
+impl Default for ResponseCache {
+    /// Initializes a new response cache.
+    fn default() -> Self {
+        Self { responses: Mutex::new(LruCache::new(NonZeroUsize::new(MAX_CACHED_RESPONSES).unwrap())) }
+    }
+}
+
+impl ResponseCache {
+    /// Returns the cached response for the given key, or caches the response from the given function.
+    pub fn get_or_try_insert(&self, key: String, f: impl FnOnce() -> Result<CachedResponse>) -> Result<CachedResponse> {
+        if let Some(response) = self.responses.lock().get(&key) {
+            return Ok(response.clone());
+        }
+        // Note: The lock is not held while the response is serialized.
+        let response = f()?;
+        self.responses.lock().put(key, response.clone());
+        Ok(response)
+    }
+
+    /// Returns the number of cached responses.
+    pub fn len(&self) -> usize {
+        self.responses.lock().len()
+    }
+
+    /// Returns `true` if there are no cached responses.
+    pub fn is_empty(&self) -> bool {
+        self.responses.lock().is_empty()
+    }
+}
+
+/// Returns the entity tag for the given identifier, e.g. a block hash or a transaction ID.
+pub fn entity_tag(id: impl Display) -> Result<ETag, RestError> {
+    format!("\"{id}\"").parse().map_err(|_| RestError(format!("Failed to construct the entity tag for '{id}'")))
+}
+
+/// Returns `true` if the request headers contain an `If-None-Match` header matching the given entity tag.
+pub fn is_not_modified(headers: &HeaderMap, etag: &ETag) -> bool {
+    headers.typed_get::<IfNoneMatch>().map(|if_none_match| !if_none_match.precondition_passes(etag)).unwrap_or(false)
+}
+
+/// Returns a `304 Not Modified` response with the given entity tag and caching policy.
+pub fn not_modified(etag: ETag, policy: CachePolicy) -> Response {
+    let mut response = Response::new(Body::empty());
+    *response.status_mut() = StatusCode::NOT_MODIFIED;
+    set_cache_headers(response.headers_mut(), etag, policy);
+    response
+}
+
+/// Returns a `GET` route of frequently changing data, such as the latest block or the memory pool,
+/// whose responses must not be cached.
+pub fn get_no_store<H, T, S>(handler: H) -> MethodRouter<S>
+where
+    H: Handler<T, S>,
+    T: 'static,
+    S: Clone + Send + Sync + 'static,
+{
+    get(handler).layer(middleware::map_response(no_store_middleware))
+}
+
+/// Disables caching of the response.
+async fn no_store_middleware(mut response: Response) -> Response {
+    response.headers_mut().insert(CACHE_CONTROL, CachePolicy::NoStore.header_value());
+    response
+}
+
+/// Sets the `ETag` and `Cache-Control` headers of a response.
+fn set_cache_headers(headers: &mut HeaderMap, etag: ETag, policy: CachePolicy) {
+    headers.typed_insert(etag);
+    headers.insert(CACHE_CONTROL, policy.header_value());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::Request, Router};
+    use serde_json::json;
+    use std::sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    };
+    use tower::ServiceExt;
+
+    /// Returns the request headers with the given `If-None-Match` header value.
+    fn if_none_match(value: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("if-none-match", HeaderValue::from_static(value));
+        headers
+    }
+
+    #[test]
+    fn test_not_modified() {
+        let etag = entity_tag("ab1hash").unwrap();
+
+        // Ensure a request without `If-None-Match` is served.
+        assert!(!is_not_modified(&HeaderMap::new(), &etag));
+        // Ensure a request with a matching `If-None-Match` is not modified.
+        assert!(is_not_modified(&if_none_match("\"ab1hash\""), &etag));
+        assert!(is_not_modified(&if_none_match("\"ab1other\", \"ab1hash\""), &etag));
+        assert!(is_not_modified(&if_none_match("W/\"ab1hash\""), &etag));
+        assert!(is_not_modified(&if_none_match("*"), &etag));
+        // Ensure a request with a different `If-None-Match` is served.
+        assert!(!is_not_modified(&if_none_match("\"ab1other\""), &etag));
+
+        // Ensure the 304 response has no body, and carries the entity tag and caching policy.
+        let response = not_modified(etag.clone(), CachePolicy::Immutable);
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers().typed_get::<ETag>(), Some(etag));
+        assert_eq!(response.headers()[CACHE_CONTROL], CACHE_CONTROL_IMMUTABLE);
+    }
+
+    #[test]
+    fn test_cached_response_headers() {
+        let etag = entity_tag("ab1hash").unwrap();
+        let cached = CachedResponse::new(&json!({ "height": 5 }), Some(1_700_000_000)).unwrap();
+
+        // Ensure an immutable response is cacheable for a long time, and carries its validators.
+        let response = cached.clone().into_response(etag.clone(), CachePolicy::Immutable);
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_TYPE], "application/json");
+        assert_eq!(response.headers()[CACHE_CONTROL], CACHE_CONTROL_IMMUTABLE);
+        assert_eq!(response.headers().typed_get::<ETag>(), Some(etag.clone()));
+        assert_eq!(
+            response.headers().typed_get::<LastModified>(),
+            Some(LastModified::from(UNIX_EPOCH + Duration::from_secs(1_700_000_000)))
+        );
+
+        // Ensure a response that may still change must be revalidated.
+        let response = cached.into_response(etag, CachePolicy::Revalidate);
+        assert_eq!(response.headers()[CACHE_CONTROL], CACHE_CONTROL_REVALIDATE);
+    }
+
+    #[test]
+    fn test_response_cache() {
+        let cache = ResponseCache::default();
+        let num_serializations = AtomicU32::new(0);
+        let serialize = || {
+            num_serializations.fetch_add(1, Ordering::Relaxed);
+            CachedResponse::new(&json!({ "height": 5 }), None)
+        };
+
+        // Ensure the response is serialized once, and then served from the cache.
+        let first = cache.get_or_try_insert("block/ab1hash".to_string(), serialize).unwrap();
+        let second = cache.get_or_try_insert("block/ab1hash".to_string(), serialize).unwrap();
+        assert_eq!(first.body, second.body);
+        assert_eq!(num_serializations.load(Ordering::Relaxed), 1);
+
+        // Ensure a failed serialization is not cached.
+        assert!(cache.get_or_try_insert("block/ab1other".to_string(), || anyhow::bail!("missing block")).is_err());
+        assert_eq!(cache.len(), 1);
+
+        // Ensure the cache is bounded.
+        for i in 0..2 * MAX_CACHED_RESPONSES {
+            cache.get_or_try_insert(format!("block/{i}"), serialize).unwrap();
+        }
+        assert_eq!(cache.len(), MAX_CACHED_RESPONSES);
+    }
+
+    #[tokio::test]
+    async fn test_no_store_routes() {
+        // Initialize a route of frequently changing data, e.g. the latest height.
+        let height = Arc::new(AtomicU32::new(0));
+        let router = Router::new().route(
+            "/latest/height",
+            get_no_store(move || {
+                let height = height.clone();
+                async move { height.fetch_add(1, Ordering::Relaxed).to_string() }
+            }),
+        );
+
+        // Ensure every request is served fresh, without validators, and with caching disabled.
+        for expected in 0..3 {
+            let request = Request::get("/latest/height").header("if-none-match", "*").body(Body::empty()).unwrap();
+            let response = router.clone().oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(response.headers()[CACHE_CONTROL], CACHE_CONTROL_NO_STORE);
+            assert!(response.headers().get("etag").is_none());
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            assert_eq!(body, expected.to_string());
+        }
+    }
+}
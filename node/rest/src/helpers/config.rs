@@ -0,0 +1,102 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{ApiToken, RestTls};
+
+use std::net::SocketAddr;
+
+/// The default maximum number of requests per second, from each IP address.
+pub const DEFAULT_REST_RPS: u32 = 10;
+
+/// The configuration of the REST server.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RestConfig {
+    /// The addresses to serve over HTTP.
+    pub rest_ips: Vec<SocketAddr>,
+    /// The configuration of the listeners served over HTTPS, if TLS is configured.
+    pub rest_tls: Option<RestTls>,
+    /// The maximum number of requests per second, from each IP address.
+    pub rest_rps: u32,
+    /// Whether the metrics route is enabled.
+    pub is_metrics_enabled: bool,
+    /// Whether the debug routes of the BFT are enabled.
+    pub is_debug_enabled: bool,
+    /// Whether the block archive is served.
+    pub is_block_archive_enabled: bool,
+    /// The API tokens that authorize requests to the sensitive routes.
+    pub api_tokens: Vec<ApiToken>,
+}
+
+impl Default for RestConfig {
+    /// Returns the configuration of a disabled REST server.
+    fn default() -> Self {
+        Self::new(vec![])
+    }
+}
+
+impl RestConfig {
+    /// Returns the configuration of a REST server serving HTTP on the given addresses.
+    pub fn new(rest_ips: Vec<SocketAddr>) -> Self {
+        Self {
+            rest_ips,
+            rest_tls: None,
+            rest_rps: DEFAULT_REST_RPS,
+            is_metrics_enabled: false,
+            is_debug_enabled: false,
+            is_block_archive_enabled: false,
+            api_tokens: vec![],
+        }
+    }
+
+    /// Sets the configuration of the listeners served over HTTPS.
+    pub fn with_tls(mut self, rest_tls: RestTls) -> Self {
+        self.rest_tls = Some(rest_tls);
+        self
+    }
+
+    /// Sets the maximum number of requests per second, from each IP address.
+    pub fn with_rps(mut self, rest_rps: u32) -> Self {
+        self.rest_rps = rest_rps;
+        self
+    }
+
+    /// Sets whether the metrics route is enabled.
+    pub fn with_metrics(mut self, is_metrics_enabled: bool) -> Self {
+        self.is_metrics_enabled = is_metrics_enabled;
+        self
+    }
+
+    /// Sets whether the debug routes of the BFT are enabled.
+    pub fn with_debug(mut self, is_debug_enabled: bool) -> Self {
+        self.is_debug_enabled = is_debug_enabled;
+        self
+    }
+
+    /// Sets whether the block archive is served.
+    pub fn with_block_archive(mut self, is_block_archive_enabled: bool) -> Self {
+        self.is_block_archive_enabled = is_block_archive_enabled;
+        self
+    }
+
+    /// Sets the API tokens that authorize requests to the sensitive routes.
+    pub fn with_api_tokens(mut self, api_tokens: Vec<ApiToken>) -> Self {
+        self.api_tokens = api_tokens;
+        self
+    }
+
+    /// Returns `true` if the REST server listens on at least one address.
+    pub fn is_enabled(&self) -> bool {
+        !self.rest_ips.is_empty() || self.rest_tls.is_some()
+    }
+}
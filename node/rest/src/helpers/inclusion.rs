@@ -0,0 +1,211 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkvm::{
+    console::program::{TransactionsPath, TransactionsTree},
+    ledger::block::ConfirmedTransaction,
+    prelude::{store::ConsensusStorage, Field, Ledger, Network, ToBits},
+};
+
+use anyhow::{ensure, Result};
+use lru::LruCache;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::{num::NonZeroUsize, sync::Arc};
+
+/// The maximum number of blocks whose transactions tree is kept in the cache.
+const MAX_CACHED_TREES: usize = 64;
+
+/// A proof that a transaction is included in a block, which a light client verifies against the
+/// transactions root in the header of the block.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct InclusionProof<N: Network> {
+    /// The ID of the transaction in the block, whose bits are the leaf of the path.
+    /// Note: A rejected transaction is included under the ID of its fee transaction.
+    pub transaction_id: N::TransactionID,
+    /// The height of the block.
+    pub block_height: u32,
+    /// The hash of the block.
+    pub block_hash: N::BlockHash,
+    /// The index of the transaction in the block.
+    pub transaction_index: u32,
+    /// The transactions root in the header of the block.
+    pub transactions_root: Field<N>,
+    /// The Merkle path from the transaction ID to the transactions root.
+    pub transactions_path: TransactionsPath<N>,
+}
+
+impl<N: Network> InclusionProof<N> {
+    /// Returns the inclusion proof of the given confirmed transaction, from the transactions tree of its block.
+    pub fn new(
+        tree: &TransactionsTree<N>,
+        confirmed: &ConfirmedTransaction<N>,
+        block_height: u32,
+        block_hash: N::BlockHash,
+    ) -> Result<Self> {
+        let transaction_id = confirmed.id();
+        let transaction_index = confirmed.index();
+        let transactions_path = tree.prove(transaction_index as usize, &transaction_id.to_bits_le())?;
+        Ok(Self {
+            transaction_id,
+            block_height,
+            block_hash,
+            transaction_index,
+            transactions_root: *tree.root(),
+            transactions_path,
+        })
+    }
+
+    /// Returns `true` if the path proves the transaction ID at its index, under the transactions root.
+    pub fn verify(&self) -> bool {
+        self.transactions_path.leaf_index() == self.transaction_index as u64
+            && N::verify_merkle_path_bhp(
+                &self.transactions_path,
+                &self.transactions_root,
+                &self.transaction_id.to_bits_le(),
+            )
+    }
+}
+
+/// A bounded cache of the transactions trees of blocks, keyed by block hash.
+pub struct TransactionsTreeCache<N: Network> {
+    /// The map of block hashes to transactions trees, evicting the least recently used entry.
+    trees: Mutex<LruCache<N::BlockHash, Arc<TransactionsTree<N>>>>,
+}
+
+impl<N: Network> Default for TransactionsTreeCache<N> {
+    /// Initializes a new transactions tree cache.
+    fn default() -> Self {
+        Self { trees: Mutex::new(LruCache::new(NonZeroUsize::new(MAX_CACHED_TREES).unwrap())) }
+    }
+}
+
+impl<N: Network> TransactionsTreeCache<N> {
+    /// Returns the cached transactions tree for the given block hash, or caches the tree from the given function.
+    pub fn get_or_try_insert(
+        &self,
+        hash: N::BlockHash,
+        f: impl FnOnce() -> Result<TransactionsTree<N>>,
+    ) -> Result<Arc<TransactionsTree<N>>> {
+        if let Some(tree) = self.trees.lock().get(&hash) {
+            return Ok(tree.clone());
+        }
+        // Note: The lock is not held while the tree is computed.
+        let tree = Arc::new(f()?);
+        self.trees.lock().put(hash, tree.clone());
+        Ok(tree)
+    }
+
+    /// Returns the inclusion proof of the given confirmed transaction in the given block of the ledger.
+    pub fn prove<C: ConsensusStorage<N>>(
+        &self,
+        ledger: &Ledger<N, C>,
+        block_hash: N::BlockHash,
+        confirmed: &ConfirmedTransaction<N>,
+    ) -> Result<InclusionProof<N>> {
+        let tree = self.get_or_try_insert(block_hash, || ledger.get_transactions(block_hash)?.to_tree())?;
+        let block_height = ledger.get_height(&block_hash)?;
+        let proof = InclusionProof::new(&tree, confirmed, block_height, block_hash)?;
+        // Ensure the proof matches the header, in case the ledger was rolled back since the tree was cached.
+        let header = ledger.get_header(block_height)?;
+        ensure!(
+            proof.transactions_root == header.transactions_root(),
+            "The transactions root of the block has changed"
+        );
+        Ok(proof)
+    }
+
+    /// Returns the number of cached blocks.
+    pub fn len(&self) -> usize {
+        self.trees.lock().len()
+    }
+
+    /// Returns `true` if there are no cached blocks.
+    pub fn is_empty(&self) -> bool {
+        self.trees.lock().is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkvm::{
+        ledger::{
+            block::Transactions,
+            ledger_test_helpers::{sample_deployment_transaction, sample_execution_transaction_with_fee},
+        },
+        prelude::{TestRng, Uniform},
+    };
+
+    type CurrentNetwork = snarkvm::prelude::MainnetV0;
+
+    /// Samples the confirmed transactions of a block.
+    fn sample_transactions(rng: &mut TestRng) -> Transactions<CurrentNetwork> {
+        [
+            ConfirmedTransaction::accepted_execute(0, sample_execution_transaction_with_fee(false, rng), vec![])
+                .unwrap(),
+            ConfirmedTransaction::accepted_deploy(1, sample_deployment_transaction(false, rng), vec![]).unwrap(),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn test_inclusion_proof() {
+        let rng = &mut TestRng::default();
+        let transactions = sample_transactions(rng);
+        let tree = transactions.to_tree().unwrap();
+        let hash = Field::rand(rng).into();
+
+        for confirmed in transactions.iter() {
+            // Ensure the proof verifies against the transactions root of the block.
+            let proof = InclusionProof::new(&tree, confirmed, 1, hash).unwrap();
+            assert_eq!(proof.transactions_root, transactions.to_transactions_root().unwrap());
+            assert!(proof.verify());
+
+            // Ensure the proof survives a round trip through JSON.
+            let json = serde_json::to_string(&proof).unwrap();
+            assert_eq!(serde_json::from_str::<InclusionProof<CurrentNetwork>>(&json).unwrap(), proof);
+
+            // Ensure the proof is refused for another transaction, index, or root.
+            let other = transactions.iter().find(|other| other.id() != confirmed.id()).unwrap();
+            assert!(!InclusionProof { transaction_id: other.id(), ..proof.clone() }.verify());
+            assert!(!InclusionProof { transaction_index: other.index(), ..proof.clone() }.verify());
+            assert!(!InclusionProof { transactions_root: Field::rand(rng), ..proof.clone() }.verify());
+        }
+    }
+
+    #[test]
+    fn test_transactions_tree_cache() {
+        let rng = &mut TestRng::default();
+        let transactions = sample_transactions(rng);
+        let cache = TransactionsTreeCache::<CurrentNetwork>::default();
+        let hash = Field::rand(rng).into();
+
+        // Ensure the tree is computed once, and then served from the cache.
+        let mut num_computations = 0;
+        for _ in 0..2 {
+            let tree = cache
+                .get_or_try_insert(hash, || {
+                    num_computations += 1;
+                    transactions.to_tree()
+                })
+                .unwrap();
+            assert_eq!(*tree.root(), transactions.to_transactions_root().unwrap());
+        }
+        assert_eq!(num_computations, 1);
+        assert_eq!(cache.len(), 1);
+    }
+}
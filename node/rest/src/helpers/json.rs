@@ -0,0 +1,101 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::{Error, Result};
+use axum::body::Body;
+use futures::{stream, Stream, StreamExt};
+use serde::Serialize;
+
+/// Returns a body that streams the given items as a pretty-printed JSON array, one element per chunk.
+/// The output is identical to serializing the collected items with `serde_json::to_string_pretty`.
+///
+/// If an item fails, the body is aborted, as the response status has already been sent.
+pub fn json_array_body<T, S>(items: S) -> Body
+where
+    T: Serialize,
+    S: Stream<Item = Result<T>> + Send + 'static,
+{
+    Body::from_stream(json_array_chunks(items))
+}
+
+/// Returns the chunks of the pretty-printed JSON array of the given items.
+fn json_array_chunks<T, S>(items: S) -> impl Stream<Item = Result<String>> + Send
+where
+    T: Serialize,
+    S: Stream<Item = Result<T>> + Send + 'static,
+{
+    // The state holds the items, the number of items written, and whether the array is finished.
+    stream::unfold((Box::pin(items), 0usize, false), |(mut items, index, is_done)| async move {
+        if is_done {
+            return None;
+        }
+        let chunk = match items.next().await {
+            Some(item) => item.and_then(|item| json_array_element(index, &item)),
+            None => return Some((Ok(json_array_end(index).to_string()), (items, index, true))),
+        };
+        // Abort the array on the first error.
+        let is_done = chunk.is_err();
+        Some((chunk, (items, index + 1, is_done)))
+    })
+}
+
+/// Returns the pretty-printed JSON array element at the given index, including its leading separator.
+fn json_array_element<T: Serialize>(index: usize, item: &T) -> Result<String> {
+    let element = serde_json::to_string_pretty(item).map_err(Error::from)?;
+    let separator = if index == 0 { "[\n  " } else { ",\n  " };
+    Ok(format!("{separator}{}", element.replace('\n', "\n  ")))
+}
+
+/// Returns the end of a pretty-printed JSON array with the given number of elements.
+fn json_array_end(num_elements: usize) -> &'static str {
+    match num_elements {
+        0 => "[]",
+        _ => "\n]",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+    use serde_json::json;
+
+    /// Returns the concatenated chunks for the given items, or the first error.
+    async fn render(items: Vec<Result<serde_json::Value>>) -> Result<String> {
+        json_array_chunks(stream::iter(items)).collect::<Vec<_>>().await.into_iter().collect()
+    }
+
+    #[tokio::test]
+    async fn test_json_array_matches_pretty() {
+        let values = vec![
+            json!({ "height": 0, "transactions": [{ "id": "at1", "fee": 1 }], "nested": { "a": [] } }),
+            json!("a string\nwith a newline"),
+            json!([1, 2, { "b": null }]),
+        ];
+        for num_values in 0..=values.len() {
+            let values = values[..num_values].to_vec();
+            let expected = serde_json::to_string_pretty(&values).unwrap();
+            assert_eq!(render(values.into_iter().map(Ok).collect()).await.unwrap(), expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_json_array_aborts_on_error() {
+        let items = vec![Ok(json!(1)), Err(anyhow!("missing block")), Ok(json!(2))];
+        let chunks = json_array_chunks(stream::iter(items)).collect::<Vec<_>>().await;
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].as_ref().unwrap(), "[\n  1");
+        assert!(chunks[1].is_err());
+    }
+}
@@ -0,0 +1,317 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use axum::{body::Body, extract::MatchedPath, http::Request, response::Response};
+use futures::future::BoxFuture;
+use indexmap::IndexMap;
+use parking_lot::Mutex;
+use std::{
+    fmt::{Display, Write},
+    sync::{
+        atomic::{AtomicU16, AtomicU64, Ordering},
+        Arc,
+        OnceLock,
+    },
+    task::{Context, Poll},
+};
+use tower::{Layer, Service};
+
+/// The content type of the Prometheus text exposition format.
+pub const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// The maximum number of distinct status codes that are counted for each route.
+const MAX_STATUSES_PER_ROUTE: usize = 16;
+
+/// The counters of the requests served by the REST server, by route and status code.
+/// Note: The counters of each route are registered when the router is built, so that serving a request
+/// only increments atomics. The registry is locked only to build the router and to take a snapshot.
+#[derive(Debug, Default)]
+pub struct RequestCounters {
+    /// The counters of each route.
+    routes: Mutex<Vec<Arc<RouteCounters>>>,
+}
+
+impl RequestCounters {
+    /// Registers the counters of a new route, and returns them.
+    fn register(&self) -> Arc<RouteCounters> {
+        let counters = Arc::new(RouteCounters::default());
+        self.routes.lock().push(counters.clone());
+        counters
+    }
+
+    /// Returns the number of requests for each route and status code.
+    pub fn snapshot(&self) -> Vec<(String, u16, u64)> {
+        // Note: A route has one set of counters for each of its methods, which are summed up.
+        let mut snapshot = IndexMap::<(String, u16), u64>::new();
+        for counters in self.routes.lock().iter() {
+            let Some(route) = counters.route.get() else { continue };
+            for (status, count) in counters.counts() {
+                *snapshot.entry((route.clone(), status)).or_default() += count;
+            }
+        }
+        snapshot.into_iter().map(|((route, status), count)| (route, status, count)).collect()
+    }
+}
+
+/// The counters of the requests served by a single route, by status code.
+#[derive(Debug, Default)]
+struct RouteCounters {
+    /// The route template, which is set by the first request to the route.
+    route: OnceLock<String>,
+    /// The status code of each slot, or `0` if the slot is unused.
+    statuses: [AtomicU16; MAX_STATUSES_PER_ROUTE],
+    /// The number of requests with the status code of each slot.
+    counts: [AtomicU64; MAX_STATUSES_PER_ROUTE],
+}
+
+impl RouteCounters {
+    /// Increments the counter for the given status code.
+    /// Note: Once every slot is used, the requests with any other status code are not counted.
+    fn increment(&self, status: u16) {
+        for (slot, count) in self.statuses.iter().zip(&self.counts) {
+            match slot.compare_exchange(0, status, Ordering::AcqRel, Ordering::Acquire) {
+                Ok(_) => {}
+                Err(existing) if existing == status => {}
+                Err(_) => continue,
+            }
+            count.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+    }
+
+    /// Returns the number of requests for each status code, in the order the status codes were first served.
+    fn counts(&self) -> impl Iterator<Item = (u16, u64)> + '_ {
+        self.statuses
+            .iter()
+            .zip(&self.counts)
+            .map(|(slot, count)| (slot.load(Ordering::Acquire), count.load(Ordering::Relaxed)))
+            .take_while(|(status, _)| *status != 0)
+    }
+}
+
+/// A layer that counts the requests to each route by status code.
+/// Note: The route is the matched path template (e.g. `/mainnet/block/:height_or_hash`), to bound the number of counters.
+#[derive(Clone, Debug)]
+pub struct RequestCountersLayer(pub Arc<RequestCounters>);
+
+impl<S> Layer<S> for RequestCountersLayer {
+    type Service = RequestCountersService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestCountersService { inner, counters: self.0.register() }
+    }
+}
+
+/// A service that counts the requests to a single route by status code.
+#[derive(Clone, Debug)]
+pub struct RequestCountersService<S> {
+    inner: S,
+    counters: Arc<RouteCounters>,
+}
+
+impl<S: Service<Request<Body>, Response = Response>> Service<Request<Body>> for RequestCountersService<S>
+where
+    S::Future: Send + 'static,
+{
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Response, S::Error>>;
+    type Response = Response;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        if self.counters.route.get().is_none() {
+            if let Some(path) = request.extensions().get::<MatchedPath>() {
+                let _ = self.counters.route.set(path.as_str().to_string());
+            }
+        }
+        let counters = self.counters.clone();
+        let future = self.inner.call(request);
+        Box::pin(async move {
+            let response = future.await?;
+            counters.increment(response.status().as_u16());
+            Ok(response)
+        })
+    }
+}
+
+/// A builder of metrics in the Prometheus text exposition format.
+#[derive(Debug, Default)]
+pub struct PrometheusText(String);
+
+impl PrometheusText {
+    /// Appends a gauge with a single sample.
+    pub fn gauge(&mut self, name: &str, help: &str, value: impl Display) {
+        self.family(name, help, "gauge", [(Labels::default(), value)]);
+    }
+
+    /// Appends a metric family of the given type, with the given labeled samples.
+    pub fn family<V: Display>(
+        &mut self,
+        name: &str,
+        help: &str,
+        kind: &str,
+        samples: impl IntoIterator<Item = (Labels, V)>,
+    ) {
+        // Note: Writing to a `String` is infallible.
+        let _ = writeln!(self.0, "# HELP {name} {help}");
+        let _ = writeln!(self.0, "# TYPE {name} {kind}");
+        for (labels, value) in samples {
+            let _ = writeln!(self.0, "{name}{labels} {value}");
+        }
+    }
+
+    /// Appends a histogram, with the number of samples in each bucket by the upper bound of the bucket,
+    /// the sum of the samples, and the number of samples.
+    pub fn histogram(
+        &mut self,
+        name: &str,
+        help: &str,
+        buckets: impl IntoIterator<Item = (f64, u64)>,
+        sum: f64,
+        count: u64,
+    ) {
+        // Note: The buckets of the text exposition format are cumulative.
+        let mut cumulative = 0;
+        let buckets = buckets.into_iter().map(|(bound, num_samples)| {
+            cumulative += num_samples;
+            (Labels::default().with("le", bound), cumulative)
+        });
+        let buckets = buckets.chain([(Labels::default().with("le", "+Inf"), count)]).collect::<Vec<_>>();
+        let _ = writeln!(self.0, "# HELP {name} {help}");
+        let _ = writeln!(self.0, "# TYPE {name} histogram");
+        for (labels, value) in buckets {
+            let _ = writeln!(self.0, "{name}_bucket{labels} {value}");
+        }
+        let _ = writeln!(self.0, "{name}_sum {sum}");
+        let _ = writeln!(self.0, "{name}_count {count}");
+    }
+
+    /// Returns the metrics in the Prometheus text exposition format.
+    pub fn finish(self) -> String {
+        self.0
+    }
+}
+
+/// The labels of a sample.
+#[derive(Debug, Default)]
+pub struct Labels(Vec<(&'static str, String)>);
+
+impl Labels {
+    /// Returns the labels with the given label appended.
+    pub fn with(mut self, name: &'static str, value: impl Display) -> Self {
+        self.0.push((name, value.to_string()));
+        self
+    }
+}
+
+impl Display for Labels {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.0.is_empty() {
+            return Ok(());
+        }
+        write!(f, "{{")?;
+        for (index, (name, value)) in self.0.iter().enumerate() {
+            if index > 0 {
+                write!(f, ",")?;
+            }
+            // Escape the label value, as required by the text exposition format.
+            let value = value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+            write!(f, "{name}=\"{value}\"")?;
+        }
+        write!(f, "}}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prometheus_text() {
+        let mut text = PrometheusText::default();
+        text.gauge("snarkos_blocks_height_total", "The height of the latest block.", 5);
+        text.family("snarkos_rest_requests_total", "The number of REST requests.", "counter", [
+            (Labels::default().with("route", "/mainnet/block/:height_or_hash").with("status", 200), 3),
+            (Labels::default().with("route", "a\"b"), 1),
+        ]);
+        assert_eq!(
+            text.finish(),
+            "# HELP snarkos_blocks_height_total The height of the latest block.\n\
+             # TYPE snarkos_blocks_height_total gauge\n\
+             snarkos_blocks_height_total 5\n\
+             # HELP snarkos_rest_requests_total The number of REST requests.\n\
+             # TYPE snarkos_rest_requests_total counter\n\
+             snarkos_rest_requests_total{route=\"/mainnet/block/:height_or_hash\",status=\"200\"} 3\n\
+             snarkos_rest_requests_total{route=\"a\\\"b\"} 1\n"
+        );
+    }
+
+    #[test]
+    fn test_prometheus_histogram() {
+        let mut text = PrometheusText::default();
+        text.histogram("snarkos_delay_secs", "The delay.", [(0.1, 2), (1.0, 1)], 0.75, 4);
+        assert_eq!(
+            text.finish(),
+            "# HELP snarkos_delay_secs The delay.\n\
+             # TYPE snarkos_delay_secs histogram\n\
+             snarkos_delay_secs_bucket{le=\"0.1\"} 2\n\
+             snarkos_delay_secs_bucket{le=\"1\"} 3\n\
+             snarkos_delay_secs_bucket{le=\"+Inf\"} 4\n\
+             snarkos_delay_secs_sum 0.75\n\
+             snarkos_delay_secs_count 4\n"
+        );
+    }
+
+    #[test]
+    fn test_request_counters() {
+        let counters = RequestCounters::default();
+        let block_get = counters.register();
+        let block_post = counters.register();
+        let metrics = counters.register();
+        counters.register();
+        block_get.route.set("/mainnet/block/latest".to_string()).unwrap();
+        block_post.route.set("/mainnet/block/latest".to_string()).unwrap();
+        metrics.route.set("/metrics".to_string()).unwrap();
+        block_get.increment(200);
+        block_get.increment(200);
+        block_get.increment(500);
+        block_post.increment(405);
+        block_post.increment(200);
+        metrics.increment(200);
+        // Ensure the counters of a route are summed up, and the routes without requests are skipped.
+        assert_eq!(counters.snapshot(), vec![
+            ("/mainnet/block/latest".to_string(), 200, 3),
+            ("/mainnet/block/latest".to_string(), 500, 1),
+            ("/mainnet/block/latest".to_string(), 405, 1),
+            ("/metrics".to_string(), 200, 1),
+        ]);
+    }
+
+    #[test]
+    fn test_route_counters_capacity() {
+        let counters = RouteCounters::default();
+        for status in 0..MAX_STATUSES_PER_ROUTE as u16 + 1 {
+            counters.increment(200 + status);
+        }
+        counters.increment(200);
+        // Ensure the status codes beyond the capacity are not counted.
+        let counts = counters.counts().collect::<Vec<_>>();
+        assert_eq!(counts.len(), MAX_STATUSES_PER_ROUTE);
+        assert_eq!(counts[0], (200, 2));
+        assert!(!counts.iter().any(|(status, _)| *status == 200 + MAX_STATUSES_PER_ROUTE as u16));
+    }
+}
@@ -12,8 +12,41 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod api_token;
+pub use api_token::*;
+
+mod archive;
+pub use archive::*;
+
 mod auth;
 pub use auth::*;
 
+mod broadcast;
+pub use broadcast::*;
+
+mod cache;
+pub use cache::*;
+
+mod config;
+pub use config::*;
+
 mod error;
 pub use error::*;
+
+mod inclusion;
+pub use inclusion::*;
+
+mod json;
+pub use json::*;
+
+mod metrics;
+pub use metrics::*;
+
+mod time_range;
+pub use time_range::*;
+
+mod tls;
+pub use tls::*;
+
+mod transactions;
+pub use transactions::*;
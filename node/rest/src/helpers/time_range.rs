@@ -0,0 +1,193 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkvm::prelude::{store::ConsensusStorage, Ledger, Network};
+
+use anyhow::{ensure, Result};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+/// The summary of a block, as returned by the time range queries.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct BlockTimeSummary<N: Network> {
+    /// The height of the block.
+    pub height: u32,
+    /// The hash of the block.
+    pub hash: N::BlockHash,
+    /// The UNIX timestamp of the block.
+    pub timestamp: i64,
+    /// The number of transactions in the block.
+    pub num_transactions: usize,
+}
+
+impl<N: Network> BlockTimeSummary<N> {
+    /// Returns the summary of the block at the given height in the ledger.
+    pub fn new<C: ConsensusStorage<N>>(ledger: &Ledger<N, C>, height: u32) -> Result<Self> {
+        let hash = ledger.get_hash(height)?;
+        let timestamp = ledger.get_header(height)?.timestamp();
+        let num_transactions = ledger.get_transactions(hash)?.len();
+        Ok(Self { height, hash, timestamp, num_transactions })
+    }
+}
+
+/// Returns the number of leading heights, from genesis up to and including the given latest height,
+/// that satisfy the predicate.
+/// Note: The predicate must hold for a prefix of the heights, as a comparison against the block timestamps does,
+/// since the timestamps are monotonically increasing with the height.
+fn partition_heights(latest_height: u32, predicate: impl Fn(u32) -> Result<bool>) -> Result<u32> {
+    let (mut low, mut high) = (0, latest_height.saturating_add(1));
+    while low < high {
+        let middle = low + (high - low) / 2;
+        match predicate(middle)? {
+            true => low = middle + 1,
+            false => high = middle,
+        }
+    }
+    Ok(low)
+}
+
+/// Returns the height of the last block at or before the given timestamp,
+/// or `None` if the timestamp precedes the genesis block.
+pub fn height_at_or_before(
+    latest_height: u32,
+    timestamp: i64,
+    timestamp_of: impl Fn(u32) -> Result<i64>,
+) -> Result<Option<u32>> {
+    Ok(partition_heights(latest_height, |height| Ok(timestamp_of(height)? <= timestamp))?.checked_sub(1))
+}
+
+/// Returns the heights of the blocks from the given start timestamp (inclusive) to the given end timestamp (exclusive).
+pub fn heights_in_time_range(
+    latest_height: u32,
+    start: i64,
+    end: i64,
+    timestamp_of: impl Fn(u32) -> Result<i64>,
+) -> Result<Range<u32>> {
+    ensure!(start <= end, "Invalid time range (the start {start} is after the end {end})");
+    let first = partition_heights(latest_height, |height| Ok(timestamp_of(height)? < start))?;
+    let last = partition_heights(latest_height, |height| Ok(timestamp_of(height)? < end))?;
+    Ok(first..last)
+}
+
+/// Returns the timestamp of the block at the given height, reading only its header.
+pub fn block_timestamp<N: Network, C: ConsensusStorage<N>>(ledger: &Ledger<N, C>, height: u32) -> Result<i64> {
+    Ok(ledger.get_header(height)?.timestamp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkos_node_bft_ledger_service::test_helpers::sample_ledger;
+    use snarkvm::prelude::TestRng;
+
+    use anyhow::anyhow;
+
+    /// The timestamps of a chain, including two blocks that share a timestamp.
+    const TIMESTAMPS: [i64; 6] = [100, 110, 120, 120, 135, 150];
+
+    /// Returns the timestamp at the given height of the sample chain.
+    fn timestamp_of(height: u32) -> Result<i64> {
+        TIMESTAMPS.get(height as usize).copied().ok_or_else(|| anyhow!("Missing block {height}"))
+    }
+
+    /// Returns the heights of the sample chain in the given time range.
+    fn heights(start: i64, end: i64) -> Range<u32> {
+        heights_in_time_range(TIMESTAMPS.len() as u32 - 1, start, end, timestamp_of).unwrap()
+    }
+
+    #[test]
+    fn test_height_at_or_before() {
+        let latest_height = TIMESTAMPS.len() as u32 - 1;
+        let height = |timestamp| height_at_or_before(latest_height, timestamp, timestamp_of).unwrap();
+        // Ensure a timestamp before genesis has no block.
+        assert_eq!(height(99), None);
+        assert_eq!(height(i64::MIN), None);
+        // Ensure an exact timestamp returns its block, and the last of the blocks sharing it.
+        assert_eq!(height(100), Some(0));
+        assert_eq!(height(110), Some(1));
+        assert_eq!(height(120), Some(3));
+        // Ensure a timestamp between blocks returns the previous block.
+        assert_eq!(height(134), Some(3));
+        // Ensure a timestamp after the tip returns the latest block.
+        assert_eq!(height(150), Some(5));
+        assert_eq!(height(i64::MAX), Some(5));
+        // Ensure a chain of only the genesis block is searched.
+        assert_eq!(height_at_or_before(0, 100, timestamp_of).unwrap(), Some(0));
+        assert_eq!(height_at_or_before(0, 99, timestamp_of).unwrap(), None);
+    }
+
+    #[test]
+    fn test_heights_in_time_range() {
+        // Ensure the start is inclusive, and the end is exclusive.
+        assert_eq!(heights(110, 135), 1..4);
+        assert_eq!(heights(100, 151), 0..6);
+        assert_eq!(heights(111, 136), 2..5);
+        // Ensure a range before genesis spans zero blocks.
+        assert_eq!(heights(0, 100), 0..0);
+        // Ensure a range that starts before genesis is bounded by genesis.
+        assert_eq!(heights(0, 111), 0..2);
+        // Ensure a range after the tip spans zero blocks.
+        assert_eq!(heights(151, 200), 6..6);
+        // Ensure a range that ends after the tip is bounded by the tip.
+        assert_eq!(heights(135, i64::MAX), 4..6);
+        // Ensure a range between two blocks spans zero blocks.
+        assert_eq!(heights(121, 135), 4..4);
+        assert_eq!(heights(120, 120), 2..2);
+        // Ensure an inverted range is refused.
+        assert!(heights_in_time_range(5, 120, 110, timestamp_of).is_err());
+    }
+
+    #[test]
+    fn test_time_range_of_ledger() {
+        let rng = &mut TestRng::default();
+        let (ledger, private_key) = sample_ledger(rng);
+
+        // Advance several blocks, recording the timestamp of each block.
+        for _ in 0..3 {
+            let block = ledger.prepare_advance_to_next_beacon_block(&private_key, vec![], vec![], vec![], rng).unwrap();
+            ledger.advance_to_next_block(&block).unwrap();
+        }
+        let latest_height = ledger.latest_height();
+        let timestamps =
+            (0..=latest_height).map(|height| ledger.get_block(height).unwrap().timestamp()).collect::<Vec<_>>();
+        let timestamp_of = |height| block_timestamp(&ledger, height);
+
+        // Ensure every block is found at its own timestamp.
+        for (height, timestamp) in timestamps.iter().enumerate() {
+            let found = height_at_or_before(latest_height, *timestamp, timestamp_of).unwrap().unwrap();
+            assert_eq!(timestamps[found as usize], *timestamp);
+            assert!(found >= height as u32);
+        }
+        // Ensure the edges of the chain are respected.
+        assert_eq!(height_at_or_before(latest_height, timestamps[0] - 1, timestamp_of).unwrap(), None);
+        assert_eq!(height_at_or_before(latest_height, i64::MAX, timestamp_of).unwrap(), Some(latest_height));
+        let last = timestamps[latest_height as usize];
+        assert_eq!(heights_in_time_range(latest_height, last + 1, i64::MAX, timestamp_of).unwrap().len(), 0);
+        assert_eq!(heights_in_time_range(latest_height, i64::MIN, timestamps[0], timestamp_of).unwrap().len(), 0);
+        assert_eq!(heights_in_time_range(latest_height, timestamps[0], last + 1, timestamp_of).unwrap(), 0..4);
+
+        // Ensure the summaries match the blocks.
+        for height in 0..=latest_height {
+            let block = ledger.get_block(height).unwrap();
+            let summary = BlockTimeSummary::new(&ledger, height).unwrap();
+            assert_eq!(summary, BlockTimeSummary {
+                height,
+                hash: block.hash(),
+                timestamp: block.timestamp(),
+                num_transactions: block.transactions().len(),
+            });
+        }
+    }
+}
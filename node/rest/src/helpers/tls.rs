@@ -0,0 +1,326 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::{anyhow, bail, Context, Result};
+use axum::{extract::ConnectInfo, Extension, Router};
+use hyper_util::{
+    rt::{TokioExecutor, TokioIo},
+    server::conn::auto,
+    service::TowerToHyperService,
+};
+use parking_lot::{Mutex, RwLock};
+use rustls::{
+    pki_types::{CertificateDer, SignatureVerificationAlgorithm},
+    server::{ClientHello, ResolvesServerCert},
+    sign::{CertifiedKey, SigningKey},
+    ServerConfig,
+    SignatureScheme,
+};
+use std::{
+    fs::File,
+    io::BufReader,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+use tokio::{net::TcpListener, task::JoinHandle, time::timeout};
+use tokio_rustls::TlsAcceptor;
+
+/// The interval between the checks for a renewed certificate and private key.
+pub const TLS_RELOAD_INTERVAL: Duration = Duration::from_secs(10);
+/// The maximum time allowed for a client to complete the TLS handshake.
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The configuration of the REST listeners served over HTTPS.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RestTls {
+    /// The path to the PEM file of the certificate chain.
+    pub cert_path: PathBuf,
+    /// The path to the PEM file of the private key.
+    pub key_path: PathBuf,
+    /// The addresses to serve over HTTPS.
+    pub rest_ips: Vec<SocketAddr>,
+}
+
+/// The certificate of the REST server, which is reloaded when its files change.
+#[derive(Debug)]
+pub struct TlsCertificate {
+    /// The path to the PEM file of the certificate chain.
+    cert_path: PathBuf,
+    /// The path to the PEM file of the private key.
+    key_path: PathBuf,
+    /// The certificate chain and private key being served.
+    certified_key: RwLock<Arc<CertifiedKey>>,
+    /// The modification times of the certificate and key files, when they were last loaded.
+    modified: Mutex<(Option<SystemTime>, Option<SystemTime>)>,
+}
+
+impl TlsCertificate {
+    /// Loads the certificate chain and private key from the given PEM files.
+    pub fn load(cert_path: &Path, key_path: &Path) -> Result<Self> {
+        let modified = modified_times(cert_path, key_path);
+        let certified_key = load_certified_key(cert_path, key_path)?;
+        Ok(Self {
+            cert_path: cert_path.to_path_buf(),
+            key_path: key_path.to_path_buf(),
+            certified_key: RwLock::new(Arc::new(certified_key)),
+            modified: Mutex::new(modified),
+        })
+    }
+
+    /// Returns the certificate chain being served.
+    pub fn certificates(&self) -> Vec<CertificateDer<'static>> {
+        self.certified_key.read().cert.clone()
+    }
+
+    /// Reloads the certificate chain and private key, if either file changed since they were last loaded.
+    /// Returns `true` if a new certificate was loaded; on error, the previous certificate is still served.
+    pub fn reload_if_changed(&self) -> Result<bool> {
+        let modified = modified_times(&self.cert_path, &self.key_path);
+        if *self.modified.lock() == modified {
+            return Ok(false);
+        }
+        // Note: The modification times are updated even on error, so that a broken renewal is reported once.
+        *self.modified.lock() = modified;
+        *self.certified_key.write() = Arc::new(load_certified_key(&self.cert_path, &self.key_path)?);
+        Ok(true)
+    }
+
+    /// Returns a TLS acceptor, which serves the latest certificate on every handshake.
+    pub fn acceptor(self: &Arc<Self>) -> TlsAcceptor {
+        let mut config = ServerConfig::builder().with_no_client_auth().with_cert_resolver(self.clone());
+        config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+        TlsAcceptor::from(Arc::new(config))
+    }
+
+    /// Spawns a task that reloads the certificate chain and private key at the given interval, if they changed.
+    pub fn spawn_reloader(self: &Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        let certificate = self.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                match certificate.reload_if_changed() {
+                    Ok(true) => info!("Reloaded the TLS certificate of the REST server"),
+                    Ok(false) => (),
+                    Err(error) => warn!("Failed to reload the TLS certificate of the REST server - {error}"),
+                }
+            }
+        })
+    }
+}
+
+impl ResolvesServerCert for TlsCertificate {
+    fn resolve(&self, _client_hello: ClientHello) -> Option<Arc<CertifiedKey>> {
+        Some(self.certified_key.read().clone())
+    }
+}
+
+/// Returns the modification times of the given certificate and key files.
+fn modified_times(cert_path: &Path, key_path: &Path) -> (Option<SystemTime>, Option<SystemTime>) {
+    let modified = |path: &Path| std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+    (modified(cert_path), modified(key_path))
+}
+
+/// Loads the certificate chain and private key from the given PEM files, ensuring the key matches the certificate.
+fn load_certified_key(cert_path: &Path, key_path: &Path) -> Result<CertifiedKey> {
+    // Read the certificate chain.
+    let cert_file = File::open(cert_path)
+        .with_context(|| format!("Failed to read the TLS certificate '{}'", cert_path.display()))?;
+    let certificates = rustls_pemfile::certs(&mut BufReader::new(cert_file))
+        .collect::<Result<Vec<_>, _>>()
+        .with_context(|| format!("Failed to parse the TLS certificate '{}'", cert_path.display()))?;
+    let Some(certificate) = certificates.first() else {
+        bail!("The TLS certificate '{}' contains no certificate", cert_path.display());
+    };
+    // Read the private key.
+    let key_file =
+        File::open(key_path).with_context(|| format!("Failed to read the TLS private key '{}'", key_path.display()))?;
+    let key = rustls_pemfile::private_key(&mut BufReader::new(key_file))
+        .with_context(|| format!("Failed to parse the TLS private key '{}'", key_path.display()))?
+        .ok_or_else(|| anyhow!("The TLS private key '{}' contains no private key", key_path.display()))?;
+    let signing_key = rustls::crypto::ring::sign::any_supported_type(&key)
+        .map_err(|error| anyhow!("Unsupported TLS private key '{}' - {error}", key_path.display()))?;
+    // Ensure the private key belongs to the certificate.
+    ensure_key_matches(certificate, signing_key.as_ref()).with_context(|| {
+        format!("The TLS private key '{}' does not match the certificate '{}'", key_path.display(), cert_path.display())
+    })?;
+    Ok(CertifiedKey::new(certificates, signing_key))
+}
+
+/// Ensures the given private key matches the given certificate, by verifying a signature of the key with the certificate.
+fn ensure_key_matches(certificate: &CertificateDer, key: &dyn SigningKey) -> Result<()> {
+    const MESSAGE: &[u8] = b"snarkos-rest-tls-key-check";
+
+    // Sign the message with the key.
+    let schemes = [
+        SignatureScheme::ECDSA_NISTP256_SHA256,
+        SignatureScheme::ECDSA_NISTP384_SHA384,
+        SignatureScheme::ED25519,
+        SignatureScheme::RSA_PSS_SHA256,
+    ];
+    let signer = key.choose_scheme(&schemes).ok_or_else(|| anyhow!("Unsupported signature scheme"))?;
+    let signature = signer.sign(MESSAGE)?;
+    // Verify the signature with the public key of the certificate.
+    let algorithm: &dyn SignatureVerificationAlgorithm = match signer.scheme() {
+        SignatureScheme::ECDSA_NISTP256_SHA256 => webpki::ring::ECDSA_P256_SHA256,
+        SignatureScheme::ECDSA_NISTP384_SHA384 => webpki::ring::ECDSA_P384_SHA384,
+        SignatureScheme::ED25519 => webpki::ring::ED25519,
+        _ => webpki::ring::RSA_PSS_2048_8192_SHA256_LEGACY_KEY,
+    };
+    let certificate = webpki::EndEntityCert::try_from(certificate).map_err(|error| anyhow!("{error:?}"))?;
+    certificate.verify_signature(algorithm, MESSAGE, &signature).map_err(|error| anyhow!("{error:?}"))
+}
+
+/// Serves the given router over HTTPS, accepting connections from the given listener.
+pub(crate) async fn serve_tls(listener: TcpListener, acceptor: TlsAcceptor, router: Router) {
+    loop {
+        let (stream, remote_addr) = match listener.accept().await {
+            Ok(connection) => connection,
+            Err(error) => {
+                warn!("Failed to accept a REST connection - {error}");
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                continue;
+            }
+        };
+        let (acceptor, router) = (acceptor.clone(), router.clone());
+        tokio::spawn(async move {
+            // Complete the TLS handshake, without holding up the listener.
+            let stream = match timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                Ok(Ok(stream)) => stream,
+                Ok(Err(error)) => {
+                    debug!("Failed the TLS handshake with '{remote_addr}' - {error}");
+                    return;
+                }
+                Err(_) => {
+                    debug!("The TLS handshake with '{remote_addr}' timed out");
+                    return;
+                }
+            };
+            // Provide the address of the client to the handlers, as on the HTTP listeners.
+            let service = TowerToHyperService::new(router.layer(Extension(ConnectInfo(remote_addr))));
+            if let Err(error) = auto::Builder::new(TokioExecutor::new())
+                .serve_connection_with_upgrades(TokioIo::new(stream), service)
+                .await
+            {
+                debug!("Failed to serve the REST connection with '{remote_addr}' - {error}");
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::get;
+    use rustls::{ClientConfig, RootCertStore};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio_rustls::TlsConnector;
+
+    /// Returns the path to the given test fixture.
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures").join(name)
+    }
+
+    /// Copies the given certificate and key fixtures into the given directory.
+    fn install(directory: &Path, index: u8) -> (PathBuf, PathBuf) {
+        let (cert_path, key_path) = (directory.join("cert.pem"), directory.join("key.pem"));
+        std::fs::copy(fixture(&format!("tls_cert_{index}.pem")), &cert_path).unwrap();
+        std::fs::copy(fixture(&format!("tls_key_{index}.pem")), &key_path).unwrap();
+        (cert_path, key_path)
+    }
+
+    /// Returns a new temporary directory.
+    fn temp_dir(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("snarkos-rest-tls-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    /// Sends a request over HTTPS, trusting both fixture certificates, and returns the served certificate and the response.
+    async fn get_over_https(addr: SocketAddr) -> (CertificateDer<'static>, String) {
+        let mut roots = RootCertStore::empty();
+        for index in [1, 2] {
+            let pem = std::fs::read(fixture(&format!("tls_cert_{index}.pem"))).unwrap();
+            for certificate in rustls_pemfile::certs(&mut pem.as_slice()) {
+                roots.add(certificate.unwrap()).unwrap();
+            }
+        }
+        let config = ClientConfig::builder().with_root_certificates(roots).with_no_client_auth();
+        let stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let server_name = "localhost".try_into().unwrap();
+        let mut stream = TlsConnector::from(Arc::new(config)).connect(server_name, stream).await.unwrap();
+        let certificate = stream.get_ref().1.peer_certificates().unwrap()[0].clone().into_owned();
+        stream.write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n").await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        (certificate, response)
+    }
+
+    #[test]
+    fn test_load_rejects_invalid_files() {
+        // A missing file is refused.
+        let error = TlsCertificate::load(&fixture("missing.pem"), &fixture("tls_key_1.pem")).unwrap_err();
+        assert!(error.to_string().contains("Failed to read the TLS certificate"));
+        // A private key in place of the certificate is refused.
+        assert!(TlsCertificate::load(&fixture("tls_key_1.pem"), &fixture("tls_key_1.pem")).is_err());
+        // The private key of another certificate is refused.
+        let error = TlsCertificate::load(&fixture("tls_cert_1.pem"), &fixture("tls_key_2.pem")).unwrap_err();
+        assert!(error.to_string().contains("does not match the certificate"));
+        // A matching pair is accepted.
+        assert!(TlsCertificate::load(&fixture("tls_cert_2.pem"), &fixture("tls_key_2.pem")).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_serve_and_reload() {
+        let directory = temp_dir("reload");
+        let (cert_path, key_path) = install(&directory, 1);
+
+        // Serve over HTTPS.
+        let certificate = Arc::new(TlsCertificate::load(&cert_path, &key_path).unwrap());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let router = Router::new()
+            .route("/", get(|ConnectInfo(addr): ConnectInfo<SocketAddr>| async move { addr.ip().to_string() }));
+        tokio::spawn(serve_tls(listener, certificate.acceptor(), router));
+        let reloader = certificate.spawn_reloader(Duration::from_millis(50));
+
+        // Ensure the first certificate is served, and the handlers see the address of the client.
+        let (served, response) = get_over_https(addr).await;
+        assert_eq!(served, certificate.certificates()[0]);
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("127.0.0.1"));
+
+        // Rotate the certificate, and ensure the new certificate is served on the same listener.
+        let previous = served;
+        install(&directory, 2);
+        for _ in 0..100 {
+            if certificate.certificates()[0] != previous {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        let (served, response) = get_over_https(addr).await;
+        assert_ne!(served, previous);
+        assert_eq!(served, certificate.certificates()[0]);
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+
+        // Ensure a broken renewal keeps the current certificate.
+        reloader.abort();
+        std::fs::write(&key_path, b"not a key").unwrap();
+        assert!(certificate.reload_if_changed().is_err());
+        assert_eq!(get_over_https(addr).await.0, served);
+    }
+}
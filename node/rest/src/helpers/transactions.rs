@@ -0,0 +1,385 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::RestError;
+use snarkos_node_consensus::{rejection_reason, ABORTED_REASON};
+use snarkvm::{
+    ledger::block::{ConfirmedTransaction, Transaction},
+    prelude::{store::ConsensusStorage, Ledger, Network, ProgramID},
+};
+
+use anyhow::Result;
+use indexmap::IndexSet;
+use lru::LruCache;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::{num::NonZeroUsize, sync::Arc};
+
+/// The default number of transactions per page.
+pub const DEFAULT_TRANSACTIONS_PER_PAGE: usize = 50;
+/// The maximum number of transactions per page.
+pub const MAX_TRANSACTIONS_PER_PAGE: usize = 100;
+/// The maximum number of blocks whose transaction summaries are kept in the cache.
+const MAX_CACHED_SUMMARIES: usize = 128;
+
+/// A summary of a confirmed transaction, for clients that do not need the full transaction, e.g. explorers.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(bound = "")]
+pub struct TransactionSummary<N: Network> {
+    /// The ID of the transaction in the block.
+    /// Note: A rejected transaction is confirmed under the ID of its fee transaction.
+    pub id: N::TransactionID,
+    /// The index of the transaction in the block.
+    pub index: u32,
+    /// The type of the transaction in the block, which is `deploy`, `execute`, or `fee`.
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    /// The status of the transaction, which is `accepted` or `rejected`.
+    pub status: &'static str,
+    /// The fee amount, in microcredits.
+    pub fee: u64,
+    /// The programs touched by the transitions of the transaction, in order of first appearance.
+    pub program_ids: Vec<ProgramID<N>>,
+    /// The number of transitions in the transaction.
+    pub num_transitions: usize,
+}
+
+impl<N: Network> TransactionSummary<N> {
+    /// Returns the summary of the given confirmed transaction.
+    pub fn new(confirmed: &ConfirmedTransaction<N>) -> Result<Self> {
+        let transaction = confirmed.transaction();
+        let kind = match transaction {
+            Transaction::Deploy(..) => "deploy",
+            Transaction::Execute(..) => "execute",
+            Transaction::Fee(..) => "fee",
+        };
+        let status = match confirmed.is_accepted() {
+            true => "accepted",
+            false => "rejected",
+        };
+        let program_ids = transaction.transitions().map(|transition| *transition.program_id()).collect::<IndexSet<_>>();
+        Ok(Self {
+            id: transaction.id(),
+            index: confirmed.index(),
+            kind,
+            status,
+            fee: *transaction.fee_amount()?,
+            program_ids: program_ids.into_iter().collect(),
+            num_transitions: transaction.transitions().count(),
+        })
+    }
+}
+
+/// Returns the summaries of the given confirmed transactions, in order.
+pub fn summarize_transactions<'a, N: Network>(
+    transactions: impl IntoIterator<Item = &'a ConfirmedTransaction<N>>,
+) -> Result<Vec<TransactionSummary<N>>> {
+    transactions.into_iter().map(TransactionSummary::new).collect()
+}
+
+/// A transaction that was rejected in a block, and the reason it was rejected.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(bound = "")]
+pub struct RejectedTransaction<N: Network> {
+    /// The ID of the transaction that was submitted.
+    pub id: N::TransactionID,
+    /// The ID of the fee transaction it was confirmed under, or `None` if it was aborted.
+    pub fee_id: Option<N::TransactionID>,
+    /// The index of the transaction in the block, or `None` if it was aborted.
+    pub index: Option<u32>,
+    /// The reason the transaction was rejected.
+    pub reason: String,
+}
+
+/// Returns the rejected transactions of a block, given its confirmed transactions and aborted transaction IDs.
+pub fn rejected_transactions<'a, N: Network>(
+    transactions: impl IntoIterator<Item = &'a ConfirmedTransaction<N>>,
+    aborted_transaction_ids: impl IntoIterator<Item = &'a N::TransactionID>,
+) -> Result<Vec<RejectedTransaction<N>>> {
+    // Retrieve the transactions that were rejected in the block, which are confirmed under their fee transaction.
+    let mut rejected = Vec::new();
+    for confirmed in transactions {
+        if let Some(reason) = rejection_reason(confirmed) {
+            rejected.push(RejectedTransaction {
+                id: confirmed.to_unconfirmed_transaction_id()?,
+                fee_id: Some(confirmed.id()),
+                index: Some(confirmed.index()),
+                reason,
+            });
+        }
+    }
+    // Retrieve the transactions that were aborted, which are not in the block.
+    rejected.extend(aborted_transaction_ids.into_iter().map(|id| RejectedTransaction {
+        id: *id,
+        fee_id: None,
+        index: None,
+        reason: ABORTED_REASON.to_string(),
+    }));
+    Ok(rejected)
+}
+
+/// Returns the block hash and the confirmed transaction of the given transaction ID, if it is in the ledger.
+///
+/// The transaction ID is the ID that was submitted, so a rejected transaction is found under its
+/// original ID rather than the ID of its fee transaction. An aborted transaction is in the ledger,
+/// without a confirmed transaction, so only its block hash is returned.
+pub fn find_confirmed_transaction<N: Network, C: ConsensusStorage<N>>(
+    ledger: &Ledger<N, C>,
+    transaction_id: &N::TransactionID,
+) -> Result<Option<(N::BlockHash, Option<ConfirmedTransaction<N>>)>> {
+    // Note: The block hash index covers the accepted, rejected, and aborted transaction IDs.
+    let Some(block_hash) = ledger.find_block_hash(transaction_id)? else {
+        return Ok(None);
+    };
+    // Find the confirmed transaction in the block, which is absent if the transaction was aborted.
+    for confirmed in ledger.get_transactions(block_hash)?.iter() {
+        if confirmed.to_unconfirmed_transaction_id()? == *transaction_id {
+            return Ok(Some((block_hash, Some(confirmed.clone()))));
+        }
+    }
+    Ok(Some((block_hash, None)))
+}
+
+/// The transaction summaries of a block, and the timestamp of the block.
+#[derive(Debug)]
+pub struct BlockSummaries<N: Network> {
+    /// The summaries of the transactions, in order.
+    pub summaries: Vec<TransactionSummary<N>>,
+    /// The timestamp of the block.
+    pub timestamp: i64,
+}
+
+/// A bounded cache of the transaction summaries of blocks, keyed by block hash.
+pub struct SummaryCache<N: Network> {
+    /// The map of block hashes to summaries, evicting the least recently used entry.
+    summaries: Mutex<LruCache<N::BlockHash, Arc<BlockSummaries<N>>>>,
+}
+
+impl<N: Network> Default for SummaryCache<N> {
+    /// Initializes a new summary cache.
+    fn default() -> Self {
+        Self { summaries: Mutex::new(LruCache::new(NonZeroUsize::new(MAX_CACHED_SUMMARIES).unwrap())) }
+    }
+}
+
+impl<N: Network> SummaryCache<N> {
+    /// Returns the cached summaries for the given block hash, or caches the summaries from the given function.
+    pub fn get_or_try_insert(
+        &self,
+        hash: N::BlockHash,
+        f: impl FnOnce() -> Result<BlockSummaries<N>>,
+    ) -> Result<Arc<BlockSummaries<N>>> {
+        if let Some(summaries) = self.summaries.lock().get(&hash) {
+            return Ok(summaries.clone());
+        }
+        // Note: The lock is not held while the summaries are computed.
+        let summaries = Arc::new(f()?);
+        self.summaries.lock().put(hash, summaries.clone());
+        Ok(summaries)
+    }
+
+    /// Returns the number of cached blocks.
+    pub fn len(&self) -> usize {
+        self.summaries.lock().len()
+    }
+
+    /// Returns `true` if there are no cached blocks.
+    pub fn is_empty(&self) -> bool {
+        self.summaries.lock().is_empty()
+    }
+}
+
+/// A page of the transactions of a block.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Page {
+    /// The page number, starting at 0.
+    pub page: usize,
+    /// The maximum number of transactions in the page.
+    pub limit: usize,
+}
+
+impl Page {
+    /// Returns the page with the given number and limit, which default to the first page and the default page size.
+    pub fn new(page: Option<usize>, limit: Option<usize>) -> Result<Self, RestError> {
+        let limit = limit.unwrap_or(DEFAULT_TRANSACTIONS_PER_PAGE);
+        if limit == 0 || limit > MAX_TRANSACTIONS_PER_PAGE {
+            return Err(RestError(format!(
+                "The limit must be between 1 and {MAX_TRANSACTIONS_PER_PAGE} transactions per page (requested {limit})"
+            )));
+        }
+        Ok(Self { page: page.unwrap_or(0), limit })
+    }
+
+    /// Returns the given page of the given items.
+    pub fn paginate<'a, T: Serialize>(&self, items: &'a [T]) -> Paginated<'a, T> {
+        let start = self.page.saturating_mul(self.limit).min(items.len());
+        let end = start.saturating_add(self.limit).min(items.len());
+        Paginated { transactions: &items[start..end], page: self.page, limit: self.limit, total: items.len() }
+    }
+}
+
+/// A page of the transactions of a block, and the total number of transactions in the block.
+#[derive(Debug, Serialize)]
+pub struct Paginated<'a, T: Serialize> {
+    /// The transactions in the page.
+    pub transactions: &'a [T],
+    /// The page number, starting at 0.
+    pub page: usize,
+    /// The maximum number of transactions in the page.
+    pub limit: usize,
+    /// The total number of transactions in the block.
+    pub total: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkvm::{
+        ledger::{
+            block::Transactions,
+            ledger_test_helpers::{
+                sample_deployment_transaction,
+                sample_execution_transaction_with_fee,
+                sample_fee_public_transaction,
+                sample_rejected_execution,
+            },
+        },
+        prelude::{Field, TestRng, Uniform},
+    };
+
+    use std::str::FromStr;
+
+    type CurrentNetwork = snarkvm::prelude::MainnetV0;
+
+    /// Samples the confirmed transactions of a block, with an accepted deployment and execution, and a rejected execution.
+    fn sample_transactions(rng: &mut TestRng) -> Transactions<CurrentNetwork> {
+        let deployment = sample_deployment_transaction(false, rng);
+        let execution = sample_execution_transaction_with_fee(false, rng);
+        let fee = sample_fee_public_transaction(rng);
+        let rejected = sample_rejected_execution(false, rng);
+        [
+            ConfirmedTransaction::accepted_deploy(0, deployment, vec![]).unwrap(),
+            ConfirmedTransaction::accepted_execute(1, execution, vec![]).unwrap(),
+            ConfirmedTransaction::rejected_execute(2, fee, rejected, vec![]).unwrap(),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn test_summaries_match_transactions() {
+        let rng = &mut TestRng::default();
+        let transactions = sample_transactions(rng);
+        let summaries = summarize_transactions(transactions.iter()).unwrap();
+        assert_eq!(summaries.len(), 3);
+
+        // Ensure each summary matches the contents of the full transaction.
+        for (summary, confirmed) in summaries.iter().zip(transactions.iter()) {
+            let full = serde_json::to_value(confirmed).unwrap();
+            let json = serde_json::to_value(summary).unwrap();
+            assert_eq!(json["id"], full["transaction"]["id"]);
+            assert_eq!(json["index"], full["index"]);
+            assert_eq!(json["type"], full["transaction"]["type"]);
+            assert_eq!(json["status"], full["status"]);
+
+            let transaction = confirmed.transaction();
+            assert_eq!(summary.fee, *transaction.fee_amount().unwrap());
+            assert_eq!(summary.num_transitions, transaction.transitions().count());
+            for transition in transaction.transitions() {
+                assert!(summary.program_ids.contains(transition.program_id()));
+            }
+        }
+
+        // Ensure the accepted and rejected transactions are told apart.
+        let kinds = summaries.iter().map(|summary| (summary.kind, summary.status)).collect::<Vec<_>>();
+        assert_eq!(kinds, vec![("deploy", "accepted"), ("execute", "accepted"), ("fee", "rejected")]);
+        // Ensure a rejected transaction only touches the fee program.
+        assert_eq!(summaries[2].program_ids, vec![ProgramID::from_str("credits.aleo").unwrap()]);
+        assert!(summaries.iter().all(|summary| summary.fee > 0));
+    }
+
+    #[test]
+    fn test_rejected_transactions() {
+        let rng = &mut TestRng::default();
+        let transactions = sample_transactions(rng);
+        let aborted_id = Field::rand(rng).into();
+
+        let rejected = rejected_transactions(transactions.iter(), [&aborted_id]).unwrap();
+        assert_eq!(rejected.len(), 2);
+
+        // Ensure the rejected execution is reported under the ID that was submitted, with its reason.
+        let confirmed = transactions.iter().nth(2).unwrap();
+        assert_eq!(rejected[0].id, confirmed.to_unconfirmed_transaction_id().unwrap());
+        assert_ne!(rejected[0].id, confirmed.id());
+        assert_eq!(rejected[0].fee_id, Some(confirmed.id()));
+        assert_eq!(rejected[0].index, Some(2));
+        assert_eq!(rejected[0].reason, rejection_reason(confirmed).unwrap());
+
+        // Ensure the aborted transaction is reported without a fee transaction.
+        assert_eq!(rejected[1], RejectedTransaction {
+            id: aborted_id,
+            fee_id: None,
+            index: None,
+            reason: ABORTED_REASON.to_string()
+        });
+
+        // Ensure a block without rejected transactions has none.
+        let accepted = transactions.iter().take(2);
+        assert!(rejected_transactions(accepted, []).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_summary_cache() {
+        let rng = &mut TestRng::default();
+        let transactions = sample_transactions(rng);
+        let cache = SummaryCache::<CurrentNetwork>::default();
+        let hash = Field::rand(rng).into();
+
+        // Ensure the summaries are computed once, and then served from the cache.
+        let mut num_computations = 0;
+        for _ in 0..2 {
+            let summaries = cache
+                .get_or_try_insert(hash, || {
+                    num_computations += 1;
+                    Ok(BlockSummaries { summaries: summarize_transactions(transactions.iter())?, timestamp: 0 })
+                })
+                .unwrap();
+            assert_eq!(summaries.summaries.len(), 3);
+        }
+        assert_eq!(num_computations, 1);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_pagination() {
+        let items = (0..7).collect::<Vec<u32>>();
+
+        // Ensure the pages cover the items, and the page past the end is empty.
+        let page = Page::new(None, Some(3)).unwrap();
+        assert_eq!(page.paginate(&items).transactions, &[0, 1, 2]);
+        let page = Page::new(Some(2), Some(3)).unwrap();
+        assert_eq!(page.paginate(&items).transactions, &[6]);
+        assert_eq!(page.paginate(&items).total, 7);
+        let page = Page::new(Some(3), Some(3)).unwrap();
+        assert!(page.paginate(&items).transactions.is_empty());
+        let page = Page::new(Some(usize::MAX), Some(3)).unwrap();
+        assert!(page.paginate(&items).transactions.is_empty());
+
+        // Ensure the limit defaults to the default page size, and is bounded.
+        assert_eq!(Page::new(None, None).unwrap(), Page { page: 0, limit: DEFAULT_TRANSACTIONS_PER_PAGE });
+        assert!(Page::new(None, Some(MAX_TRANSACTIONS_PER_PAGE)).is_ok());
+        assert!(Page::new(None, Some(MAX_TRANSACTIONS_PER_PAGE + 1)).is_err());
+        assert!(Page::new(None, Some(0)).is_err());
+    }
+}
@@ -22,26 +22,40 @@ pub use helpers::*;
 
 mod routes;
 
-use snarkos_node_consensus::Consensus;
+use snarkos_node_bft_ledger_service::{
+    DiskMonitor,
+    RecordIndex,
+    RecordScanner,
+    ReplayFilter,
+    RewardIndex,
+    StateChangeIndex,
+};
+use snarkos_node_consensus::{Consensus, ProgramDenylist};
 use snarkos_node_router::{
     messages::{Message, UnconfirmedTransaction},
     Routing,
 };
+use snarkos_node_sync::BlockSync;
 use snarkvm::{
     console::{program::ProgramID, types::Field},
     ledger::narwhal::Data,
-    prelude::{cfg_into_iter, store::ConsensusStorage, Ledger, Network},
+    prelude::{store::ConsensusStorage, Ledger, Network},
 };
 
-use anyhow::Result;
+use anyhow::{ensure, Context, Result};
 use axum::{
-    body::Body,
+    body::{Body, Bytes},
     extract::{ConnectInfo, DefaultBodyLimit, Path, Query, State},
-    http::{header::CONTENT_TYPE, Method, Request, StatusCode},
+    http::{
+        header::{CONTENT_TYPE, ETAG, IF_NONE_MATCH},
+        Method,
+        Request,
+        StatusCode,
+    },
     middleware,
     middleware::Next,
     response::Response,
-    routing::{get, post},
+    routing::{get, post, put},
     Json,
 };
 use axum_extra::response::ErasedJson;
@@ -63,25 +77,178 @@ pub struct Rest<N: Network, C: ConsensusStorage<N>, R: Routing<N>> {
     ledger: Ledger<N, C>,
     /// The node (routing).
     routing: Arc<R>,
+    /// The block sync module.
+    block_sync: BlockSync<N>,
+    /// The record index, if it is enabled.
+    record_index: Option<Arc<RecordIndex<N, C>>>,
+    /// The reward index, if it is enabled.
+    reward_index: Option<Arc<RewardIndex<N>>>,
+    /// The state change index, if it is enabled.
+    state_changes: Option<Arc<StateChangeIndex<N>>>,
+    /// The record scanner, if it is enabled.
+    record_scanner: Option<Arc<RecordScanner<N>>>,
+    /// The disk monitor, if it is enabled.
+    disk_monitor: Option<Arc<DiskMonitor>>,
+    /// The denylist of programs, whose transactions are refused by the node.
+    program_denylist: Arc<ProgramDenylist<N>>,
+    /// The replay filter of the recently confirmed transactions and solutions.
+    replay_filter: Arc<ReplayFilter<N, C>>,
+    /// The counters of the requests served, if the metrics route is enabled.
+    request_counters: Option<Arc<RequestCounters>>,
+    /// The cache of serialized responses for immutable data.
+    response_cache: Arc<ResponseCache>,
+    /// The cache of the transaction summaries of blocks.
+    summary_cache: Arc<SummaryCache<N>>,
+    /// The cache of the transactions trees of blocks, for the inclusion proofs.
+    transactions_tree_cache: Arc<TransactionsTreeCache<N>>,
+    /// The cache of the chunks of the block archive, if the block archive is served.
+    archive_cache: Option<Arc<ArchiveCache>>,
+    /// The API tokens of the sensitive routes, if any are defined.
+    api_tokens: Option<Arc<ApiTokens>>,
+    /// A flag indicating whether the development-only routes are enabled.
+    is_dev: bool,
+    /// A flag indicating whether the debug routes of the BFT are enabled.
+    is_debug: bool,
+    /// The addresses the server is listening on.
+    rest_ips: Arc<[SocketAddr]>,
+    /// The addresses the server is listening on over HTTPS.
+    rest_tls_ips: Arc<[SocketAddr]>,
     /// The server handles.
     handles: Arc<Mutex<Vec<JoinHandle<()>>>>,
 }
 
 impl<N: Network, C: 'static + ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
-    /// Initializes a new instance of the server.
-    pub async fn start(
-        rest_ip: SocketAddr,
-        rest_rps: u32,
-        consensus: Option<Consensus<N>>,
+    /// Initializes a new instance of the server, which serves the given ledger once it is started.
+    pub fn new(
         ledger: Ledger<N, C>,
         routing: Arc<R>,
-    ) -> Result<Self> {
-        // Initialize the server.
-        let mut server = Self { consensus, ledger, routing, handles: Default::default() };
+        block_sync: BlockSync<N>,
+        replay_filter: Arc<ReplayFilter<N, C>>,
+    ) -> Self {
+        Self {
+            consensus: None,
+            ledger,
+            routing,
+            block_sync,
+            record_index: None,
+            reward_index: None,
+            state_changes: None,
+            record_scanner: None,
+            disk_monitor: None,
+            program_denylist: Default::default(),
+            replay_filter,
+            request_counters: None,
+            response_cache: Default::default(),
+            summary_cache: Default::default(),
+            transactions_tree_cache: Default::default(),
+            archive_cache: None,
+            api_tokens: None,
+            is_dev: false,
+            is_debug: false,
+            rest_ips: Arc::new([]),
+            rest_tls_ips: Arc::new([]),
+            handles: Default::default(),
+        }
+    }
+
+    /// Sets the consensus module, which receives the transactions and solutions broadcast over the server.
+    pub fn with_consensus(mut self, consensus: Consensus<N>) -> Self {
+        self.consensus = Some(consensus);
+        self
+    }
+
+    /// Sets the record index, if it is enabled.
+    pub fn with_record_index(mut self, record_index: Option<Arc<RecordIndex<N, C>>>) -> Self {
+        self.record_index = record_index;
+        self
+    }
+
+    /// Sets the reward index, if it is enabled.
+    pub fn with_reward_index(mut self, reward_index: Option<Arc<RewardIndex<N>>>) -> Self {
+        self.reward_index = reward_index;
+        self
+    }
+
+    /// Sets the state change index, if it is enabled.
+    pub fn with_state_changes(mut self, state_changes: Option<Arc<StateChangeIndex<N>>>) -> Self {
+        self.state_changes = state_changes;
+        self
+    }
+
+    /// Sets the record scanner, if it is enabled.
+    pub fn with_record_scanner(mut self, record_scanner: Option<Arc<RecordScanner<N>>>) -> Self {
+        self.record_scanner = record_scanner;
+        self
+    }
+
+    /// Sets the disk monitor, which gates the intake of transactions and solutions.
+    pub fn with_disk_monitor(mut self, disk_monitor: Arc<DiskMonitor>) -> Self {
+        self.disk_monitor = Some(disk_monitor);
+        self
+    }
+
+    /// Sets the denylist of programs, whose transactions are refused by the node.
+    pub fn with_program_denylist(mut self, program_denylist: Arc<ProgramDenylist<N>>) -> Self {
+        self.program_denylist = program_denylist;
+        self
+    }
+
+    /// Sets whether the development-only routes are enabled.
+    pub fn with_dev_routes(mut self, is_dev: bool) -> Self {
+        self.is_dev = is_dev;
+        self
+    }
+
+    /// Starts the server, listening on each of the configured addresses,
+    /// and on each of the HTTPS addresses, if TLS is configured.
+    pub async fn start(mut self, config: RestConfig) -> Result<Self> {
+        // Load the TLS certificate, if it is configured, so that the server fails to start if it is invalid.
+        let (certificate, rest_tls_ips) = match config.rest_tls {
+            Some(rest_tls) => {
+                (Some(Arc::new(TlsCertificate::load(&rest_tls.cert_path, &rest_tls.key_path)?)), rest_tls.rest_ips)
+            }
+            None => (None, vec![]),
+        };
+        // Bind every address before serving any of them, so that the server fails to start if any address is unavailable.
+        ensure!(
+            !config.rest_ips.is_empty() || !rest_tls_ips.is_empty(),
+            "The REST server requires at least one address to listen on"
+        );
+        let listeners = Self::bind(&config.rest_ips).await?;
+        let tls_listeners = Self::bind(&rest_tls_ips).await?;
+        // Retrieve the bound addresses, which resolve any unspecified ports.
+        let rest_ips = listeners.iter().map(|listener| listener.local_addr()).collect::<Result<Vec<_>, _>>()?;
+        let rest_tls_ips = tls_listeners.iter().map(|listener| listener.local_addr()).collect::<Result<Vec<_>, _>>()?;
+        self.rest_ips = rest_ips.into();
+        self.rest_tls_ips = rest_tls_ips.into();
+
+        // Initialize the request counters, if the metrics route is enabled.
+        self.request_counters = config.is_metrics_enabled.then(Default::default);
+        // Enable the debug routes of the BFT, if they are configured.
+        self.is_debug = config.is_debug_enabled;
+        // Initialize the archive cache, if the block archive is served.
+        self.archive_cache = config.is_block_archive_enabled.then(Default::default);
+        // Initialize the API tokens, if any are defined.
+        self.api_tokens = match config.api_tokens.is_empty() {
+            true => None,
+            false => Some(Arc::new(ApiTokens::new(&config.api_tokens)?)),
+        };
         // Spawn the server.
-        server.spawn_server(rest_ip, rest_rps).await;
+        self.spawn_server(listeners, tls_listeners, certificate, config.rest_rps).await;
         // Return the server.
-        Ok(server)
+        Ok(self)
+    }
+
+    /// Binds a listener to each of the given addresses.
+    async fn bind(rest_ips: &[SocketAddr]) -> Result<Vec<TcpListener>> {
+        let mut listeners = Vec::with_capacity(rest_ips.len());
+        for rest_ip in rest_ips {
+            let listener = TcpListener::bind(rest_ip)
+                .await
+                .with_context(|| format!("Failed to bind the REST server to '{rest_ip}'"))?;
+            listeners.push(listener);
+        }
+        Ok(listeners)
     }
 }
 
@@ -91,18 +258,57 @@ impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
         &self.ledger
     }
 
+    /// Returns the addresses the server is listening on.
+    pub fn rest_ips(&self) -> &[SocketAddr] {
+        &self.rest_ips
+    }
+
+    /// Returns the addresses the server is listening on over HTTPS.
+    pub fn rest_tls_ips(&self) -> &[SocketAddr] {
+        &self.rest_tls_ips
+    }
+
     /// Returns the handles.
     pub const fn handles(&self) -> &Arc<Mutex<Vec<JoinHandle<()>>>> {
         &self.handles
     }
+
+    /// Shuts down the server, closing every listener.
+    pub fn shut_down(&self) {
+        self.handles.lock().iter().for_each(|handle| handle.abort());
+    }
+
+    /// Returns the block sync module.
+    /// Note: The block sync of a validator is the one of its BFT, which is rebuilt when the consensus is restarted.
+    fn block_sync(&self) -> BlockSync<N> {
+        match &self.consensus {
+            Some(consensus) => consensus.bft().primary().sync().block_sync().clone(),
+            None => self.block_sync.clone(),
+        }
+    }
+
+    /// Ensures the node has sufficient disk space to accept new transactions and solutions, if the disk monitor is enabled.
+    fn ensure_disk_space_for_intake(&self) -> Result<(), RestError> {
+        if let Some(disk_monitor) = &self.disk_monitor {
+            disk_monitor.ensure_intake()?;
+        }
+        Ok(())
+    }
 }
 
 impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
-    async fn spawn_server(&mut self, rest_ip: SocketAddr, rest_rps: u32) {
+    async fn spawn_server(
+        &mut self,
+        listeners: Vec<TcpListener>,
+        tls_listeners: Vec<TcpListener>,
+        certificate: Option<Arc<TlsCertificate>>,
+        rest_rps: u32,
+    ) {
         let cors = CorsLayer::new()
             .allow_origin(Any)
             .allow_methods([Method::GET, Method::POST, Method::OPTIONS])
-            .allow_headers([CONTENT_TYPE]);
+            .allow_headers([CONTENT_TYPE, IF_NONE_MATCH])
+            .expose_headers([ETAG]);
 
         // Log the REST rate limit per IP.
         debug!("REST rate limit per IP - {rest_rps} RPS");
@@ -118,10 +324,12 @@ impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
         );
 
         let router = {
-            axum::Router::new()
+            let routes = axum::Router::new()
 
             // All the endpoints before the call to `route_layer` are protected with JWT auth.
             .route("/mainnet/node/address", get(Self::get_node_address))
+            .route("/mainnet/node/ip", get(Self::get_node_ip))
+            .route("/mainnet/node/denylist", get(Self::get_node_denylist).put(Self::put_node_denylist))
             .route_layer(middleware::from_fn(auth_middleware))
 
             // ----------------- DEPRECATED ROUTES -----------------
@@ -129,30 +337,38 @@ impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
             // Please refer to the recommended routes for each endpoint:
 
             // Deprecated: use `/mainnet/block/height/latest` instead.
-            .route("/mainnet/latest/height", get(Self::latest_height))
+            .route("/mainnet/latest/height", get_no_store(Self::latest_height))
             // Deprecated: use `/mainnet/block/hash/latest` instead.
-            .route("/mainnet/latest/hash", get(Self::latest_hash))
+            .route("/mainnet/latest/hash", get_no_store(Self::latest_hash))
             // Deprecated: use `/mainnet/latest/block/height` instead.
-            .route("/mainnet/latest/block", get(Self::latest_block))
+            .route("/mainnet/latest/block", get_no_store(Self::latest_block))
             // Deprecated: use `/mainnet/stateRoot/latest` instead.
-            .route("/mainnet/latest/stateRoot", get(Self::latest_state_root))
+            .route("/mainnet/latest/stateRoot", get_no_store(Self::latest_state_root))
             // Deprecated: use `/mainnet/committee/latest` instead.
-            .route("/mainnet/latest/committee", get(Self::latest_committee))
+            .route("/mainnet/latest/committee", get_no_store(Self::latest_committee))
             // ------------------------------------------------------
 
             // GET ../block/..
-            .route("/mainnet/block/height/latest", get(Self::get_block_height_latest))
-            .route("/mainnet/block/hash/latest", get(Self::get_block_hash_latest))
-            .route("/mainnet/block/latest", get(Self::get_block_latest))
+            // Note: The routes of the latest block are never cached, while the routes of a given block are
+            // served with an entity tag, and are cacheable once the block can no longer be rolled back.
+            .route("/mainnet/block/height/latest", get_no_store(Self::get_block_height_latest))
+            .route("/mainnet/block/hash/latest", get_no_store(Self::get_block_hash_latest))
+            .route("/mainnet/block/latest", get_no_store(Self::get_block_latest))
+            .route("/mainnet/block/byTime/:timestamp", get_no_store(Self::get_block_by_time))
             .route("/mainnet/block/:height_or_hash", get(Self::get_block))
             // The path param here is actually only the height, but the name must match the route
             // above, otherwise there'll be a conflict at runtime.
             .route("/mainnet/block/:height_or_hash/transactions", get(Self::get_block_transactions))
+            .route("/mainnet/block/:height_or_hash/rejected", get(Self::get_block_rejected))
+            .route("/mainnet/block/:height_or_hash/stateChanges", get(Self::get_block_state_changes))
 
             // GET and POST ../transaction/..
             .route("/mainnet/transaction/:id", get(Self::get_transaction))
             .route("/mainnet/transaction/confirmed/:id", get(Self::get_confirmed_transaction))
+            .route("/mainnet/transaction/:id/outcome", get(Self::get_transaction_outcome))
+            .route("/mainnet/transaction/:id/inclusionProof", get(Self::get_transaction_inclusion_proof))
             .route("/mainnet/transaction/broadcast", post(Self::transaction_broadcast))
+            .route("/mainnet/transactions/broadcast", post(Self::transactions_broadcast))
 
             // POST ../solution/broadcast
             .route("/mainnet/solution/broadcast", post(Self::solution_broadcast))
@@ -160,6 +376,8 @@ impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
             // GET ../find/..
             .route("/mainnet/find/blockHash/:tx_id", get(Self::find_block_hash))
             .route("/mainnet/find/transactionID/deployment/:program_id", get(Self::find_transaction_id_from_program_id))
+            .route("/mainnet/find/transactionID/commitment/:commitment", get(Self::find_transaction_id_from_commitment))
+            .route("/mainnet/find/transactionID/serialNumber/:serial_number", get(Self::find_transaction_id_from_serial_number))
             .route("/mainnet/find/transactionID/:transition_id", get(Self::find_transaction_id_from_transition_id))
             .route("/mainnet/find/transitionID/:input_or_output_id", get(Self::find_transition_id))
 
@@ -167,6 +385,22 @@ impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
             .route("/mainnet/peers/count", get(Self::get_peers_count))
             .route("/mainnet/peers/all", get(Self::get_peers_all))
             .route("/mainnet/peers/all/metrics", get(Self::get_peers_all_metrics))
+            .route("/mainnet/peers/all/metrics/subnets", get(Self::get_peers_all_metrics_subnets))
+
+            // GET ../sync/..
+            .route("/mainnet/sync/status", get(Self::get_sync_status))
+
+            // GET ../node/..
+            .route("/mainnet/node/status", get_no_store(Self::get_node_status))
+            .route("/mainnet/node/sync", get_no_store(Self::get_node_sync))
+            .route("/mainnet/node/propagation", get_no_store(Self::get_node_propagation))
+
+            // GET ../puzzle/..
+            .route("/mainnet/puzzle/current", get(Self::get_puzzle_current))
+            .route("/mainnet/puzzle/estimate", get(Self::get_puzzle_estimate))
+
+            // GET ../prover/..
+            .route("/mainnet/prover/:address/rewards", get(Self::get_prover_rewards))
 
             // GET ../program/..
             .route("/mainnet/program/:id", get(Self::get_program))
@@ -175,14 +409,73 @@ impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
 
             // GET misc endpoints.
             .route("/mainnet/blocks", get(Self::get_blocks))
+            .route("/mainnet/blocks/byTime", get_no_store(Self::get_blocks_by_time))
             .route("/mainnet/height/:hash", get(Self::get_height))
-            .route("/mainnet/memoryPool/transmissions", get(Self::get_memory_pool_transmissions))
-            .route("/mainnet/memoryPool/solutions", get(Self::get_memory_pool_solutions))
-            .route("/mainnet/memoryPool/transactions", get(Self::get_memory_pool_transactions))
+            .route("/mainnet/memoryPool/transmissions", get_no_store(Self::get_memory_pool_transmissions))
+            .route("/mainnet/memoryPool/solutions", get_no_store(Self::get_memory_pool_solutions))
+            .route("/mainnet/memoryPool/transactions", get_no_store(Self::get_memory_pool_transactions))
             .route("/mainnet/statePath/:commitment", get(Self::get_state_path_for_commitment))
-            .route("/mainnet/stateRoot/latest", get(Self::get_state_root_latest))
-            .route("/mainnet/committee/latest", get(Self::get_committee_latest))
+            .route("/mainnet/stateRoot/latest", get_no_store(Self::get_state_root_latest))
+            .route("/mainnet/committee/latest", get_no_store(Self::get_committee_latest));
+
+            // GET ../{bft,debug}/.. (only exposed if enabled), inspecting the live state of the BFT.
+            let routes = match self.is_debug {
+                true => routes
+                    .route("/mainnet/bft/dag", get(Self::get_bft_dag))
+                    .route("/mainnet/bft/proposal", get(Self::get_bft_proposal))
+                    .route("/mainnet/bft/round", get(Self::get_bft_round))
+                    .route("/mainnet/bft/certificates/pending", get(Self::get_bft_pending_certificates))
+                    .route("/mainnet/debug/blockTimings", get(Self::get_debug_block_timings)),
+                false => routes,
+            };
 
+            // POST ../dev/.. and POST ../node/{check,bft/restart} (only in development mode)
+            let routes = match self.is_dev {
+                true => routes
+                    // Note: These routes accept private keys, and must never be mounted outside of development mode.
+                    .route("/mainnet/dev/deploy", post(Self::dev_deploy))
+                    .route("/mainnet/dev/execute", post(Self::dev_execute))
+                    .route("/mainnet/dev/compareChain", post(Self::dev_compare_chain))
+                    .route("/mainnet/node/check", post(Self::node_check))
+                    .route("/mainnet/node/bft/restart", post(Self::node_bft_restart)),
+                false => routes,
+            };
+
+            // POST ../scan/register and GET ../scan/.. (only exposed if enabled), protected with JWT auth.
+            let routes = match self.record_scanner.is_some() {
+                true => routes.merge(
+                    axum::Router::new()
+                        .route("/mainnet/scan/register", post(Self::scan_register))
+                        .route("/mainnet/scan/:address/records", get(Self::get_scanned_records))
+                        .route_layer(middleware::from_fn(auth_middleware)),
+                ),
+                false => routes,
+            };
+
+            // GET ../archive/.. (only exposed if enabled), serving the block archive to the CDN clients of other nodes.
+            let routes = match self.archive_cache.is_some() {
+                true => routes.route("/mainnet/archive/:file", get(Self::get_archive_file)),
+                false => routes,
+            };
+
+            // GET /metrics (only exposed if enabled), counting the requests to all of the routes above.
+            let routes = match &self.request_counters {
+                Some(counters) => routes
+                    .route("/metrics", get(Self::get_metrics))
+                    .route_layer(RequestCountersLayer(counters.clone())),
+                None => routes,
+            };
+
+            // Require an API token with the scope of each sensitive route (only if API tokens are defined).
+            // Note: The scopes of the routes are declared in `ROUTE_SCOPES`, and the other routes remain public.
+            let routes = match &self.api_tokens {
+                Some(api_tokens) => {
+                    routes.route_layer(middleware::from_fn_with_state(api_tokens.clone(), api_token_middleware))
+                }
+                None => routes,
+            };
+
+            routes
             // Pass in `Rest` to make things convenient.
             .with_state(self.clone())
             // Enable tower-http tracing.
@@ -199,12 +492,25 @@ impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
             })
         };
 
-        let rest_listener = TcpListener::bind(rest_ip).await.unwrap();
-        self.handles.lock().push(tokio::spawn(async move {
-            axum::serve(rest_listener, router.into_make_service_with_connect_info::<SocketAddr>())
-                .await
-                .expect("couldn't start rest server");
-        }))
+        // Serve the same router on every listener.
+        for (rest_listener, rest_ip) in listeners.into_iter().zip(self.rest_ips.iter()) {
+            info!("Listening for REST requests at {rest_ip}");
+            let router = router.clone();
+            self.handles.lock().push(tokio::spawn(async move {
+                axum::serve(rest_listener, router.into_make_service_with_connect_info::<SocketAddr>())
+                    .await
+                    .expect("couldn't start rest server");
+            }));
+        }
+        // Serve the same router on every HTTPS listener, reloading the certificate when it is renewed.
+        if let Some(certificate) = certificate {
+            let acceptor = certificate.acceptor();
+            for (rest_listener, rest_ip) in tls_listeners.into_iter().zip(self.rest_tls_ips.iter()) {
+                info!("Listening for REST requests over HTTPS at {rest_ip}");
+                self.handles.lock().push(tokio::spawn(serve_tls(rest_listener, acceptor.clone(), router.clone())));
+            }
+            self.handles.lock().push(certificate.spawn_reloader(TLS_RELOAD_INTERVAL));
+        }
     }
 }
 
@@ -13,16 +13,51 @@
 // limitations under the License.
 
 use super::*;
-use snarkos_node_router::messages::UnconfirmedSolution;
+use snarkos_node_bft::helpers::{init_primary_channels, DEFAULT_DAG_SNAPSHOT_ROUNDS, MAX_DAG_SNAPSHOT_ROUNDS};
+use snarkos_node_bft_ledger_service::{
+    block_rewards,
+    blocks_stream,
+    BlockReward,
+    ConsistencyCheck,
+    ConsistencyReport,
+    RecordScanner,
+    ScannedRecord,
+    StateChange,
+    ALEO_MAXIMUM_FORK_DEPTH,
+};
+use snarkos_node_cdn::{archive_height, encode_blocks, encode_latest_state, ArchiveFile, BLOCKS_PER_FILE};
+use snarkos_node_consensus::{
+    rejection_reason,
+    ConflictDecision,
+    TransactionOutcome,
+    ABORTED_REASON,
+    BLOCK_TIMINGS_CAPACITY,
+};
+use snarkos_node_router::{compare_chain, messages::UnconfirmedSolution, MAXIMUM_COMPARED_BLOCKS};
+use snarkos_node_tcp::{RefusedReason, P2P};
 use snarkvm::{
-    ledger::puzzle::Solution,
-    prelude::{block::Transaction, Identifier, Plaintext},
+    ledger::{
+        narwhal::TransmissionID,
+        puzzle::{Solution, SolutionID},
+    },
+    prelude::{block::Transaction, Address, Identifier, Plaintext, PrivateKey, Program, ProgramID, Value, ViewKey},
 };
 
+use ::time::OffsetDateTime;
+use axum::{
+    http::{header::CACHE_CONTROL, HeaderMap, HeaderValue},
+    response::IntoResponse,
+};
 use indexmap::IndexMap;
-use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::{
+    str::FromStr,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// The maximum number of blocks returned per call by the block range queries.
+const MAX_BLOCK_RANGE: u32 = 50;
 
 /// The `get_blocks` query object.
 #[derive(Deserialize, Serialize)]
@@ -33,13 +68,218 @@ pub(crate) struct BlockRange {
     end: u32,
 }
 
+/// The `get_blocks_by_time` query object.
+#[derive(Deserialize, Serialize)]
+pub(crate) struct TimeRange {
+    /// The starting UNIX timestamp (inclusive).
+    start: i64,
+    /// The ending UNIX timestamp (exclusive).
+    end: i64,
+}
+
+/// The `get_prover_rewards` query object.
+#[derive(Deserialize)]
+pub(crate) struct RewardsQuery {
+    /// The starting block height (inclusive).
+    start: u32,
+    /// The ending block height (exclusive).
+    end: u32,
+    /// Whether to return the rewards of each block, in addition to the totals.
+    #[serde(default)]
+    detail: bool,
+}
+
+/// The `get_scanned_records` query object.
+#[derive(Deserialize, Serialize)]
+pub(crate) struct ScanRange {
+    /// The starting block height (inclusive), which defaults to the genesis block.
+    start: Option<u32>,
+    /// The ending block height (exclusive), which defaults to beyond the latest block.
+    end: Option<u32>,
+}
+
+/// The `get_block_transactions` query object.
+#[derive(Deserialize)]
+pub(crate) struct BlockTransactionsQuery {
+    /// Whether to return the summaries of the transactions, instead of the full transactions.
+    #[serde(default)]
+    summary: bool,
+    /// The page number, starting at 0.
+    page: Option<usize>,
+    /// The maximum number of transactions per page, which defaults to `DEFAULT_TRANSACTIONS_PER_PAGE`.
+    limit: Option<usize>,
+}
+
 /// The `get_mapping_value` query object.
 #[derive(Deserialize, Serialize)]
 pub(crate) struct Metadata {
     metadata: bool,
 }
 
+/// The `get_puzzle_estimate` query object.
+#[derive(Deserialize, Serialize)]
+#[serde(bound = "")]
+pub(crate) struct SolutionCommitment<N: Network> {
+    /// The solution ID.
+    commitment: SolutionID<N>,
+}
+
+/// The `get_memory_pool_transactions` query object.
+#[derive(Deserialize)]
+#[serde(bound = "")]
+pub(crate) struct MemoryPoolFilter<N: Network> {
+    /// The program that the transitions of the transactions touch, if any.
+    program: Option<ProgramID<N>>,
+    /// The address that the fee payer, or the visible inputs and outputs, of the transactions involve, if any.
+    address: Option<Address<N>>,
+    /// The maximum number of transactions to return, which defaults to the maximum.
+    limit: Option<usize>,
+}
+
+/// The `get_bft_dag` query object.
+#[derive(Deserialize)]
+pub(crate) struct DagQuery {
+    /// The number of latest rounds to snapshot, which defaults to `DEFAULT_DAG_SNAPSHOT_ROUNDS`.
+    rounds: Option<u64>,
+    /// The format of the snapshot, which is either `json` (the default) or `dot`.
+    format: Option<String>,
+}
+
+/// The `dev_deploy` request object.
+#[derive(Deserialize)]
+#[serde(bound = "")]
+pub(crate) struct DevDeploy<N: Network> {
+    /// The program source.
+    program: String,
+    /// The priority fee, in microcredits.
+    fee: u64,
+    /// The private key of the deployer, which pays the fee.
+    private_key: PrivateKey<N>,
+}
+
+/// The `dev_execute` request object.
+#[derive(Deserialize)]
+#[serde(bound = "")]
+pub(crate) struct DevExecute<N: Network> {
+    /// The program ID.
+    program_id: ProgramID<N>,
+    /// The function name.
+    function: Identifier<N>,
+    /// The inputs to the function.
+    inputs: Vec<String>,
+    /// The priority fee, in microcredits.
+    fee: u64,
+    /// The private key of the caller, which pays the fee.
+    private_key: PrivateKey<N>,
+}
+
+/// The `scan_register` request object.
+#[derive(Deserialize)]
+#[serde(bound = "")]
+pub(crate) struct ScanRegister<N: Network> {
+    /// The view key to scan the records of.
+    view_key: ViewKey<N>,
+    /// The starting block height (inclusive) of the backfill, if any.
+    start: Option<u32>,
+    /// The ending block height (exclusive) of the backfill, which defaults to beyond the latest block.
+    end: Option<u32>,
+}
+
+/// The `node_check` request object.
+#[derive(Default, Deserialize)]
+pub(crate) struct NodeCheck {
+    /// Whether to re-validate the block contents, which can take a long time.
+    #[serde(default)]
+    deep: bool,
+    /// The number of latest blocks over which the state root is recomputed.
+    state_root_depth: Option<u32>,
+}
+
+/// The `dev_compare_chain` request object.
+#[derive(Deserialize)]
+pub(crate) struct CompareChain {
+    /// The listener IP of the peer to compare with.
+    peer: SocketAddr,
+    /// The starting block height (inclusive).
+    start: u32,
+    /// The ending block height (exclusive).
+    end: u32,
+}
+
+/// Cancels the consistency check when dropped, i.e. when the request is aborted.
+struct CancelOnDrop(Arc<AtomicBool>);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Returns the JSON representation of the given puzzle reward of a block.
+fn block_reward_to_json(height: u32, reward: &BlockReward) -> serde_json::Value {
+    json!({
+        "height": height,
+        "reward": reward.reward,
+        "num_solutions": reward.num_solutions,
+    })
+}
+
+/// Returns the JSON representation of the given state change.
+fn state_change_to_json<N: Network>(change: &StateChange<N>) -> serde_json::Value {
+    json!({
+        "transaction_id": change.transaction_id,
+        "program_id": change.mapping.map(|(program_id, _)| program_id),
+        "mapping_name": change.mapping.map(|(_, mapping_name)| mapping_name),
+        "mapping_id": change.mapping_id,
+        "operation": change.kind.as_str(),
+        "key_id": change.key_id,
+        "value_id": change.value_id,
+    })
+}
+
+/// Returns the JSON representation of the given scanned record.
+fn scanned_record_to_json<N: Network>(record: &ScannedRecord<N>) -> serde_json::Value {
+    json!({
+        "height": record.height,
+        "transaction_id": record.transaction_id,
+        "commitment": record.commitment,
+        "record": record.record,
+    })
+}
+
+/// Returns the JSON representation of the given consistency report.
+fn consistency_report_to_json(report: &ConsistencyReport) -> serde_json::Value {
+    let inconsistencies = report
+        .inconsistencies
+        .iter()
+        .map(|inconsistency| {
+            json!({
+                "height": inconsistency.height,
+                "kind": inconsistency.kind.as_str(),
+                "key": inconsistency.key,
+                "message": inconsistency.message,
+            })
+        })
+        .collect::<Vec<_>>();
+    json!({
+        "consistent": report.is_consistent(),
+        "latest_height": report.latest_height,
+        "deep": report.is_deep,
+        "cancelled": report.is_cancelled,
+        "inconsistencies": inconsistencies,
+    })
+}
+
+/// Returns the response serving the given file of the block archive, with the given content type and caching policy.
+fn archive_response(body: Bytes, content_type: &'static str, policy: CachePolicy) -> Response {
+    let headers = [(CONTENT_TYPE, HeaderValue::from_static(content_type)), (CACHE_CONTROL, policy.header_value())];
+    (headers, body).into_response()
+}
+
 impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
+    /// The number of most recent rounds for which the certificate counts are exposed in the metrics.
+    const METRICS_CERTIFICATE_ROUNDS: u64 = 5;
+
     // ----------------- DEPRECATED FUNCTIONS -----------------
     // The functions below are associated with deprecated routes.
     // Please use the recommended alternatives when implementing new features or refactoring.
@@ -91,37 +331,58 @@ impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
         ErasedJson::pretty(rest.ledger.latest_block())
     }
 
+    /// Returns the cache policy of the block at the given height,
+    /// which is immutable once the block can no longer be rolled back.
+    fn cache_policy_for_height(&self, height: u32) -> CachePolicy {
+        match height.saturating_add(ALEO_MAXIMUM_FORK_DEPTH) <= self.ledger.latest_height() {
+            true => CachePolicy::Immutable,
+            false => CachePolicy::Revalidate,
+        }
+    }
+
     // GET /mainnet/block/{height}
     // GET /mainnet/block/{blockHash}
     pub(crate) async fn get_block(
         State(rest): State<Self>,
         Path(height_or_hash): Path<String>,
-    ) -> Result<ErasedJson, RestError> {
+        headers: HeaderMap,
+    ) -> Result<Response, RestError> {
         // Manually parse the height or the height or the hash, axum doesn't support different types
         // for the same path param.
-        let block = if let Ok(height) = height_or_hash.parse::<u32>() {
-            rest.ledger.get_block(height)?
+        // Note: A block requested by its hash never changes, while a block requested by its height may be rolled back.
+        let (hash, policy) = if let Ok(height) = height_or_hash.parse::<u32>() {
+            (rest.ledger.get_hash(height)?, rest.cache_policy_for_height(height))
         } else {
             let hash = height_or_hash
                 .parse::<N::BlockHash>()
                 .map_err(|_| RestError("invalid input, it is neither a block height nor a block hash".to_string()))?;
+            // Ensure the block exists, before its entity tag is compared.
+            rest.ledger.get_height(&hash)?;
 
-            rest.ledger.get_block_by_hash(&hash)?
+            (hash, CachePolicy::Immutable)
         };
 
-        Ok(ErasedJson::pretty(block))
+        // Return early if the client already has the block.
+        let etag = entity_tag(hash)?;
+        if is_not_modified(&headers, &etag) {
+            return Ok(not_modified(etag, policy));
+        }
+        // Retrieve the serialized block, from the response cache if possible.
+        let response = rest.response_cache.get_or_try_insert(format!("block/{hash}"), || {
+            let block = rest.ledger.get_block_by_hash(&hash)?;
+            CachedResponse::new(&block, Some(block.timestamp()))
+        })?;
+        Ok(response.into_response(etag, policy))
     }
 
     // GET /mainnet/blocks?start={start_height}&end={end_height}
     pub(crate) async fn get_blocks(
         State(rest): State<Self>,
         Query(block_range): Query<BlockRange>,
-    ) -> Result<ErasedJson, RestError> {
+    ) -> Result<impl IntoResponse, RestError> {
         let start_height = block_range.start;
         let end_height = block_range.end;
 
-        const MAX_BLOCK_RANGE: u32 = 50;
-
         // Ensure the end height is greater than the start height.
         if start_height > end_height {
             return Err(RestError("Invalid block range".to_string()));
@@ -135,11 +396,108 @@ impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
             )));
         }
 
-        let blocks = cfg_into_iter!((start_height..end_height))
-            .map(|height| rest.ledger.get_block(height))
-            .collect::<Result<Vec<_>, _>>()?;
+        // Ensure the blocks exist, as the response status is sent before the blocks are streamed.
+        let latest_height = rest.ledger.latest_height();
+        if start_height < end_height && end_height - 1 > latest_height {
+            return Err(RestError(format!(
+                "Cannot request blocks beyond the latest block (requested {}, latest {latest_height})",
+                end_height - 1
+            )));
+        }
+
+        // Stream the blocks one at a time, instead of loading the entire range into memory.
+        let blocks = blocks_stream(rest.ledger.clone(), start_height..end_height);
+        Ok(([(CONTENT_TYPE, "application/json")], json_array_body(blocks)))
+    }
+
+    // GET /mainnet/blocks/byTime?start={unix}&end={unix}
+    pub(crate) async fn get_blocks_by_time(
+        State(rest): State<Self>,
+        Query(time_range): Query<TimeRange>,
+    ) -> Result<ErasedJson, RestError> {
+        // Find the heights of the blocks in the time range, reading only the headers of the blocks.
+        let latest_height = rest.ledger.latest_height();
+        let heights = heights_in_time_range(latest_height, time_range.start, time_range.end, |height| {
+            block_timestamp(&rest.ledger, height)
+        })?;
+
+        // Ensure the block range is bounded.
+        if heights.len() > MAX_BLOCK_RANGE as usize {
+            return Err(RestError(format!(
+                "Cannot request more than {MAX_BLOCK_RANGE} blocks per call (the time range spans {} blocks)",
+                heights.len()
+            )));
+        }
+
+        // Summarize the blocks in the time range.
+        let summaries =
+            heights.map(|height| BlockTimeSummary::new(&rest.ledger, height)).collect::<Result<Vec<_>, _>>()?;
+        Ok(ErasedJson::pretty(summaries))
+    }
 
-        Ok(ErasedJson::pretty(blocks))
+    // GET /mainnet/block/byTime/{unix}
+    pub(crate) async fn get_block_by_time(
+        State(rest): State<Self>,
+        Path(timestamp): Path<i64>,
+    ) -> Result<ErasedJson, RestError> {
+        // Find the height of the last block at or before the timestamp, reading only the headers of the blocks.
+        let latest_height = rest.ledger.latest_height();
+        let Some(height) =
+            height_at_or_before(latest_height, timestamp, |height| block_timestamp(&rest.ledger, height))?
+        else {
+            return Err(RestError(format!("No block at or before {timestamp}, as it precedes the genesis block")));
+        };
+        Ok(ErasedJson::pretty(rest.ledger.get_block(height)?))
+    }
+
+    // GET /mainnet/archive/latest.json
+    // GET /mainnet/archive/{start}.{end}.blocks
+    // GET /mainnet/archive/{start}.{end}.sum
+    pub(crate) async fn get_archive_file(
+        State(rest): State<Self>,
+        Path(file): Path<String>,
+    ) -> Result<Response, RestError> {
+        let Some(archive_cache) = rest.archive_cache.clone() else {
+            return Err(RestError("The block archive is not served by this node".to_string()));
+        };
+        let file = ArchiveFile::from_str(&file)?;
+        // Note: The archive only contains the complete chunks of blocks that can no longer be rolled back.
+        let archive_height = archive_height(rest.ledger.latest_height(), ALEO_MAXIMUM_FORK_DEPTH);
+
+        let start = match file {
+            ArchiveFile::Latest => {
+                let hash = match archive_height {
+                    0 => String::new(),
+                    _ => rest.ledger.get_hash(archive_height - 1)?.to_string(),
+                };
+                let latest = encode_latest_state(archive_height, hash)?;
+                return Ok(archive_response(latest.into(), "application/octet-stream", CachePolicy::NoStore));
+            }
+            ArchiveFile::Blocks(start) | ArchiveFile::Checksum(start) => start,
+        };
+        // Ensure the chunk is in the archive.
+        if start + BLOCKS_PER_FILE > archive_height {
+            return Err(RestError(format!(
+                "Cannot request blocks beyond the block archive (requested {}, archived {archive_height})",
+                start + BLOCKS_PER_FILE
+            )));
+        }
+
+        // Generate the chunk from the ledger, unless it was generated recently.
+        let ledger = rest.ledger.clone();
+        let chunk = tokio::task::spawn_blocking(move || {
+            archive_cache.get_or_try_insert(start, || {
+                let blocks = ledger.get_blocks(start..start + BLOCKS_PER_FILE)?;
+                Ok(ArchiveChunk::new(encode_blocks(&blocks)?))
+            })
+        })
+        .await
+        .map_err(|error| RestError(error.to_string()))??;
+
+        Ok(match file {
+            ArchiveFile::Checksum(_) => archive_response(chunk.checksum.into(), "text/plain", CachePolicy::Immutable),
+            _ => archive_response(chunk.blocks, "application/octet-stream", CachePolicy::Immutable),
+        })
     }
 
     // GET /mainnet/height/{blockHash}
@@ -151,19 +509,111 @@ impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
     }
 
     // GET /mainnet/block/{height}/transactions
+    // GET /mainnet/block/{height}/transactions?summary={bool}&page={page}&limit={limit}
     pub(crate) async fn get_block_transactions(
         State(rest): State<Self>,
         Path(height): Path<u32>,
+        Query(query): Query<BlockTransactionsQuery>,
+        headers: HeaderMap,
+    ) -> Result<Response, RestError> {
+        // Return early if the client already has the transactions of the block.
+        let hash = rest.ledger.get_hash(height)?;
+        let etag = entity_tag(hash)?;
+        let policy = rest.cache_policy_for_height(height);
+        if is_not_modified(&headers, &etag) {
+            return Ok(not_modified(etag, policy));
+        }
+        // If the query is not paginated, return every full transaction, from the response cache if possible.
+        if !query.summary && query.page.is_none() && query.limit.is_none() {
+            let response = rest.response_cache.get_or_try_insert(format!("block/{hash}/transactions"), || {
+                let block = rest.ledger.get_block_by_hash(&hash)?;
+                CachedResponse::new(block.transactions(), Some(block.timestamp()))
+            })?;
+            return Ok(response.into_response(etag, policy));
+        }
+        // Retrieve the requested page.
+        let page = Page::new(query.page, query.limit)?;
+        let response = match query.summary {
+            // Note: The summaries are computed once per block, and every page is served from them.
+            true => {
+                let block_summaries = rest.summary_cache.get_or_try_insert(hash, || {
+                    let block = rest.ledger.get_block_by_hash(&hash)?;
+                    let summaries = summarize_transactions(block.transactions().iter())?;
+                    Ok(BlockSummaries { summaries, timestamp: block.timestamp() })
+                })?;
+                CachedResponse::new(&page.paginate(&block_summaries.summaries), Some(block_summaries.timestamp))?
+            }
+            false => {
+                let key = format!("block/{hash}/transactions?page={}&limit={}", page.page, page.limit);
+                rest.response_cache.get_or_try_insert(key, || {
+                    let block = rest.ledger.get_block_by_hash(&hash)?;
+                    let transactions = block.transactions().iter().collect::<Vec<_>>();
+                    CachedResponse::new(&page.paginate(&transactions), Some(block.timestamp()))
+                })?
+            }
+        };
+        Ok(response.into_response(etag, policy))
+    }
+
+    // GET /mainnet/block/{height}/rejected
+    pub(crate) async fn get_block_rejected(
+        State(rest): State<Self>,
+        Path(height): Path<u32>,
+        headers: HeaderMap,
+    ) -> Result<Response, RestError> {
+        // Return early if the client already has the rejected transactions of the block.
+        let hash = rest.ledger.get_hash(height)?;
+        let etag = entity_tag(hash)?;
+        let policy = rest.cache_policy_for_height(height);
+        if is_not_modified(&headers, &etag) {
+            return Ok(not_modified(etag, policy));
+        }
+        // Retrieve the rejected transactions, from the response cache if possible.
+        let response = rest.response_cache.get_or_try_insert(format!("block/{hash}/rejected"), || {
+            let block = rest.ledger.get_block_by_hash(&hash)?;
+            let rejected = rejected_transactions(block.transactions().iter(), block.aborted_transaction_ids())?;
+            CachedResponse::new(&rejected, Some(block.timestamp()))
+        })?;
+        Ok(response.into_response(etag, policy))
+    }
+
+    // GET /mainnet/block/{height}/stateChanges
+    pub(crate) async fn get_block_state_changes(
+        State(rest): State<Self>,
+        Path(height): Path<u32>,
     ) -> Result<ErasedJson, RestError> {
-        Ok(ErasedJson::pretty(rest.ledger.get_transactions(height)?))
+        // Ensure the state changes are indexed.
+        let Some(state_changes) = &rest.state_changes else {
+            return Err(RestError("The state changes are not indexed (see '--enable-state-changes')".to_string()));
+        };
+        match state_changes.get_state_changes(height) {
+            Some(changes) => Ok(ErasedJson::pretty(json!({
+                "height": height,
+                "state_changes": changes.iter().map(state_change_to_json).collect::<Vec<_>>(),
+            }))),
+            None if height >= state_changes.next_height() => {
+                Err(RestError(format!("The state changes of block {height} are not indexed yet")))
+            }
+            None => Err(RestError(format!(
+                "The state changes of block {height} are beyond the retention window of the last {} blocks",
+                state_changes.retention()
+            ))),
+        }
     }
 
     // GET /mainnet/transaction/{transactionID}
     pub(crate) async fn get_transaction(
         State(rest): State<Self>,
         Path(tx_id): Path<N::TransactionID>,
-    ) -> Result<ErasedJson, RestError> {
-        Ok(ErasedJson::pretty(rest.ledger.get_transaction(tx_id)?))
+        headers: HeaderMap,
+    ) -> Result<Response, RestError> {
+        // Return early if the client already has the transaction, which never changes for a given ID.
+        let etag = entity_tag(tx_id)?;
+        if rest.ledger.contains_transaction_id(&tx_id)? && is_not_modified(&headers, &etag) {
+            return Ok(not_modified(etag, CachePolicy::Immutable));
+        }
+        let transaction = rest.ledger.get_transaction(tx_id)?;
+        Ok(CachedResponse::new(&transaction, None)?.into_response(etag, CachePolicy::Immutable))
     }
 
     // GET /mainnet/transaction/confirmed/{transactionID}
@@ -174,6 +624,64 @@ impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
         Ok(ErasedJson::pretty(rest.ledger.get_confirmed_transaction(tx_id)?))
     }
 
+    // GET /mainnet/transaction/{transactionID}/outcome
+    pub(crate) async fn get_transaction_outcome(
+        State(rest): State<Self>,
+        Path(tx_id): Path<N::TransactionID>,
+    ) -> Result<ErasedJson, RestError> {
+        let consensus = match rest.consensus {
+            Some(consensus) => consensus,
+            None => return Err(RestError("Route isn't available for this node type".to_string())),
+        };
+        // Retrieve the outcome from the journal of recent transactions.
+        let outcome = match consensus.transaction_outcome(&tx_id) {
+            Some(TransactionOutcome::Pending) => json!({ "status": "pending" }),
+            Some(TransactionOutcome::Accepted { height }) => json!({ "status": "accepted", "height": height }),
+            Some(TransactionOutcome::Rejected { height, reason }) => {
+                json!({ "status": "rejected", "height": height, "reason": reason })
+            }
+            Some(TransactionOutcome::Evicted { cause }) => json!({ "status": "evicted", "cause": cause }),
+            // If the transaction is no longer in the journal, check if it was accepted or rejected in the ledger.
+            None => match find_confirmed_transaction(&rest.ledger, &tx_id)? {
+                Some((block_hash, confirmed)) => {
+                    let height = rest.ledger.get_height(&block_hash)?;
+                    // Note: A transaction in the ledger without a confirmed transaction was aborted.
+                    match confirmed.as_ref().map(rejection_reason) {
+                        Some(None) => json!({ "status": "accepted", "height": height }),
+                        Some(Some(reason)) => json!({ "status": "rejected", "height": height, "reason": reason }),
+                        None => json!({ "status": "rejected", "height": height, "reason": ABORTED_REASON }),
+                    }
+                }
+                None => {
+                    let capacity = consensus.transaction_outcomes_capacity();
+                    return Err(RestError(format!(
+                        "No outcome is known for transaction '{tx_id}' - outcomes are kept in memory for the \
+                         {capacity} most recent transactions received by this node, and are cleared on restart"
+                    )));
+                }
+            },
+        };
+        Ok(ErasedJson::pretty(outcome))
+    }
+
+    // GET /mainnet/transaction/{transactionID}/inclusionProof
+    pub(crate) async fn get_transaction_inclusion_proof(
+        State(rest): State<Self>,
+        Path(tx_id): Path<N::TransactionID>,
+    ) -> Result<ErasedJson, RestError> {
+        match find_confirmed_transaction(&rest.ledger, &tx_id)? {
+            // Prove the transaction, from the cached transactions tree of its block if possible.
+            Some((block_hash, Some(confirmed))) => {
+                Ok(ErasedJson::pretty(rest.transactions_tree_cache.prove(&rest.ledger, block_hash, &confirmed)?))
+            }
+            Some((_, None)) => Err(RestError(format!("Transaction '{tx_id}' was aborted, and is not in its block"))),
+            None => match rest.consensus.as_ref().and_then(|consensus| consensus.get_unconfirmed_transaction(tx_id)) {
+                Some(_) => Err(RestError(format!("Transaction '{tx_id}' is not confirmed yet"))),
+                None => Err(RestError(format!("Transaction '{tx_id}' is not found"))),
+            },
+        }
+    }
+
     // GET /mainnet/memoryPool/transmissions
     pub(crate) async fn get_memory_pool_transmissions(State(rest): State<Self>) -> Result<ErasedJson, RestError> {
         match rest.consensus {
@@ -193,11 +701,154 @@ impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
     }
 
     // GET /mainnet/memoryPool/transactions
-    pub(crate) async fn get_memory_pool_transactions(State(rest): State<Self>) -> Result<ErasedJson, RestError> {
-        match rest.consensus {
-            Some(consensus) => Ok(ErasedJson::pretty(consensus.unconfirmed_transactions().collect::<IndexMap<_, _>>())),
-            None => Err(RestError("Route isn't available for this node type".to_string())),
+    // GET /mainnet/memoryPool/transactions?program={programID}&address={address}&limit={limit}
+    pub(crate) async fn get_memory_pool_transactions(
+        State(rest): State<Self>,
+        Query(filter): Query<MemoryPoolFilter<N>>,
+    ) -> Result<ErasedJson, RestError> {
+        const MAX_FILTERED_TRANSACTIONS: usize = 100;
+
+        let Some(consensus) = rest.consensus else {
+            return Err(RestError("Route isn't available for this node type".to_string()));
+        };
+        // If the query has no filter, return every unconfirmed transaction.
+        if filter.program.is_none() && filter.address.is_none() {
+            return Ok(ErasedJson::pretty(consensus.unconfirmed_transactions().collect::<IndexMap<_, _>>()));
+        }
+        // Ensure the limit is within the bounds.
+        let limit = filter.limit.unwrap_or(MAX_FILTERED_TRANSACTIONS);
+        if limit > MAX_FILTERED_TRANSACTIONS {
+            return Err(RestError(format!(
+                "Cannot request more than {MAX_FILTERED_TRANSACTIONS} transactions per call (requested {limit})"
+            )));
+        }
+        // Retrieve the matching unconfirmed transactions.
+        let (total, transactions) =
+            consensus.find_unconfirmed_transactions(filter.program.as_ref(), filter.address.as_ref(), limit);
+        Ok(ErasedJson::pretty(json!({
+            "transactions": transactions.into_iter().collect::<IndexMap<_, _>>(),
+            "total": total,
+        })))
+    }
+
+    // GET /mainnet/puzzle/current
+    pub(crate) async fn get_puzzle_current(State(rest): State<Self>) -> Result<ErasedJson, RestError> {
+        // Retrieve the latest block header.
+        let header = rest.ledger.latest_header();
+        // Compute the current epoch number.
+        let epoch_number = header.height() / N::NUM_BLOCKS_PER_EPOCH;
+        // Compute the block height at which the next epoch begins.
+        let next_epoch_height = (epoch_number + 1).saturating_mul(N::NUM_BLOCKS_PER_EPOCH);
+
+        Ok(ErasedJson::pretty(json!({
+            "epoch_hash": rest.ledger.latest_epoch_hash()?,
+            "epoch_number": epoch_number,
+            "proof_target": header.proof_target(),
+            "coinbase_target": header.coinbase_target(),
+            "height": header.height(),
+            "next_epoch_height": next_epoch_height,
+        })))
+    }
+
+    // GET /mainnet/puzzle/estimate?commitment={solutionID}
+    pub(crate) async fn get_puzzle_estimate(
+        State(rest): State<Self>,
+        Query(query): Query<SolutionCommitment<N>>,
+    ) -> Result<ErasedJson, RestError> {
+        let Some(consensus) = rest.consensus else {
+            return Err(RestError("Route isn't available for this node type".to_string()));
+        };
+        // Retrieve the solution from the memory pool.
+        let Some(solution) = consensus.get_unconfirmed_solution(query.commitment) else {
+            return Err(RestError(format!("Solution '{}' is not in the memory pool", query.commitment)));
+        };
+        let solution = solution.deserialize_blocking()?;
+
+        // Compute the proof target of the solution.
+        let solution_target = rest.ledger.puzzle().get_proof_target(&solution)?;
+        // Retrieve the latest proof target.
+        let proof_target = rest.ledger.latest_proof_target();
+        // Determine if the solution is for the current epoch.
+        let is_current_epoch = solution.epoch_hash() == rest.ledger.latest_epoch_hash()?;
+
+        Ok(ErasedJson::pretty(json!({
+            "solution_id": query.commitment,
+            "solution_target": solution_target,
+            "proof_target": proof_target,
+            "is_current_epoch": is_current_epoch,
+            "meets_proof_target": is_current_epoch && solution_target >= proof_target,
+        })))
+    }
+
+    // GET /mainnet/prover/{address}/rewards?start={start_height}&end={end_height}&detail={bool}
+    pub(crate) async fn get_prover_rewards(
+        State(rest): State<Self>,
+        Path(address): Path<Address<N>>,
+        Query(query): Query<RewardsQuery>,
+    ) -> Result<ErasedJson, RestError> {
+        let RewardsQuery { start, end, detail } = query;
+
+        const MAX_REWARD_RANGE: u32 = 1000;
+
+        // Ensure the end height is greater than the start height.
+        if start > end {
+            return Err(RestError("Invalid block range".to_string()));
         }
+
+        // Ensure the block range is bounded.
+        if end - start > MAX_REWARD_RANGE {
+            return Err(RestError(format!(
+                "Cannot request the rewards of more than {MAX_REWARD_RANGE} blocks per call (requested {})",
+                end - start
+            )));
+        }
+
+        // Ensure the blocks exist.
+        let latest_height = rest.ledger.latest_height();
+        if start < end && end - 1 > latest_height {
+            return Err(RestError(format!(
+                "Cannot request rewards beyond the latest block (requested {}, latest {latest_height})",
+                end - 1
+            )));
+        }
+
+        // Retrieve the rewards from the reward index if it covers the range, or compute them from the blocks.
+        let indexed =
+            rest.reward_index.as_ref().and_then(|reward_index| reward_index.get_rewards(&address, start..end));
+        let is_indexed = indexed.is_some();
+        let rewards = match indexed {
+            Some(rewards) => rewards,
+            None => {
+                let ledger = rest.ledger.clone();
+                tokio::task::spawn_blocking(move || {
+                    let mut rewards = Vec::new();
+                    for height in start..end {
+                        let block = ledger.get_block(height)?;
+                        if let Some(reward) = block_rewards(ledger.puzzle(), &block)?.get(&address) {
+                            rewards.push((height, *reward));
+                        }
+                    }
+                    Ok::<_, anyhow::Error>(rewards)
+                })
+                .await
+                .map_err(|error| RestError(error.to_string()))??
+            }
+        };
+
+        let total_reward = rewards.iter().map(|(_, reward)| reward.reward).sum::<u64>();
+        let num_solutions = rewards.iter().map(|(_, reward)| reward.num_solutions).sum::<u32>();
+        let mut response = json!({
+            "address": address,
+            "start": start,
+            "end": end,
+            "total_reward": total_reward,
+            "num_solutions": num_solutions,
+            "indexed": is_indexed,
+        });
+        if detail {
+            response["blocks"] = rewards.iter().map(|(height, reward)| block_reward_to_json(*height, reward)).collect();
+        }
+        Ok(ErasedJson::pretty(response))
     }
 
     // GET /mainnet/program/{programID}
@@ -271,11 +922,331 @@ impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
         ErasedJson::pretty(rest.routing.router().connected_metrics())
     }
 
+    // GET /mainnet/peers/all/metrics/subnets
+    pub(crate) async fn get_peers_all_metrics_subnets(State(rest): State<Self>) -> ErasedJson {
+        let distribution = rest.routing.router().connected_subnet_distribution();
+        ErasedJson::pretty(
+            distribution.into_iter().map(|(bucket, count)| (bucket.to_string(), count)).collect::<IndexMap<_, _>>(),
+        )
+    }
+
+    // GET /mainnet/sync/status
+    pub(crate) async fn get_sync_status(State(rest): State<Self>) -> ErasedJson {
+        let block_request_sizes = rest.block_sync().block_request_sizes();
+        ErasedJson::pretty(json!({
+            "is_synced": rest.block_sync().is_block_synced(),
+            "num_blocks_behind": rest.block_sync().num_blocks_behind(),
+            "block_request_sizes": block_request_sizes
+                .into_iter()
+                .map(|(peer_ip, size)| (peer_ip.to_string(), size))
+                .collect::<IndexMap<_, _>>(),
+        }))
+    }
+
+    // GET /mainnet/node/sync
+    pub(crate) async fn get_node_sync(State(rest): State<Self>) -> ErasedJson {
+        ErasedJson::pretty(rest.block_sync().sync_report())
+    }
+
+    // GET /mainnet/node/propagation
+    pub(crate) async fn get_node_propagation(State(rest): State<Self>) -> ErasedJson {
+        let propagation = rest.routing.router().propagation();
+        let histogram = propagation.histogram();
+        ErasedJson::pretty(json!({
+            "sample_rate": propagation.sample_rate(),
+            "count": histogram.count,
+            "sum_ms": histogram.sum_ms,
+            "p50_ms": histogram.percentile(50.0),
+            "p90_ms": histogram.percentile(90.0),
+            "buckets": histogram
+                .buckets
+                .iter()
+                .map(|(bound, count)| json!({ "le_ms": bound, "count": count }))
+                .collect::<Vec<_>>(),
+        }))
+    }
+
+    // GET /mainnet/node/status
+    pub(crate) async fn get_node_status(State(rest): State<Self>) -> ErasedJson {
+        let disk = rest.disk_monitor.as_ref().map(|disk_monitor| {
+            let usage = disk_monitor.usage();
+            json!({
+                "status": usage.status.to_string(),
+                "available_bytes": usage.available_bytes,
+                "ledger_bytes": usage.ledger_bytes,
+                "low_space_threshold": disk_monitor.config().low_space_threshold,
+                "critical_space_threshold": disk_monitor.config().critical_space_threshold,
+            })
+        });
+        let clock_skew = rest.routing.router().clock_skew();
+        let clock = json!({
+            "status": clock_skew.level().to_string(),
+            "offset_in_ms": clock_skew.offset_in_ms(),
+            "num_peers": clock_skew.num_peers(),
+            "is_proposing_halted": rest.consensus.as_ref().map(|consensus| consensus.bft().primary().is_clock_skewed()),
+        });
+        let memory_pool = rest.consensus.as_ref().map(|consensus| {
+            json!({
+                "solutions": consensus.num_unconfirmed_solutions(),
+                "transactions": consensus.num_unconfirmed_transactions(),
+            })
+        });
+        ErasedJson::pretty(json!({
+            "node_type": rest.routing.router().node_type().to_string(),
+            "latest_height": rest.ledger.latest_height(),
+            "latest_timestamp": rest.ledger.latest_block().timestamp(),
+            "is_synced": rest.block_sync().is_block_synced(),
+            "bft_round": rest.consensus.as_ref().map(|consensus| consensus.bft().primary().current_round()),
+            "rest_ips": &*rest.rest_ips,
+            "rest_tls_ips": &*rest.rest_tls_ips,
+            "memory_pool": memory_pool,
+            "disk": disk,
+            "clock": clock,
+        }))
+    }
+
+    // GET /mainnet/bft/proposal
+    pub(crate) async fn get_bft_proposal(State(rest): State<Self>) -> Result<ErasedJson, RestError> {
+        match rest.consensus {
+            Some(consensus) => Ok(ErasedJson::pretty(consensus.bft().primary().proposal_summary()?)),
+            None => Err(RestError("Route isn't available for this node type".to_string())),
+        }
+    }
+
+    // GET /mainnet/bft/round
+    pub(crate) async fn get_bft_round(State(rest): State<Self>) -> Result<ErasedJson, RestError> {
+        match rest.consensus {
+            Some(consensus) => Ok(ErasedJson::pretty(consensus.bft().primary().round_summary()?)),
+            None => Err(RestError("Route isn't available for this node type".to_string())),
+        }
+    }
+
+    // GET /mainnet/debug/blockTimings
+    pub(crate) async fn get_debug_block_timings(State(rest): State<Self>) -> Result<ErasedJson, RestError> {
+        let Some(consensus) = rest.consensus else {
+            return Err(RestError("Route isn't available for this node type".to_string()));
+        };
+        // Return the timing breakdowns of the most recent blocks, from newest to oldest.
+        let timings = consensus
+            .block_timings(BLOCK_TIMINGS_CAPACITY)
+            .into_iter()
+            .map(|timing| {
+                json!({
+                    "height": timing.height,
+                    "num_transactions": timing.num_transactions,
+                    "speculate_micros": timing.speculate_micros,
+                    "verify_micros": timing.verify_micros,
+                    "journal_micros": timing.journal_micros,
+                    "advance_micros": timing.advance_micros,
+                    "total_micros": timing.total_micros,
+                })
+            })
+            .collect::<Vec<_>>();
+        Ok(ErasedJson::pretty(timings))
+    }
+
+    // POST /mainnet/node/bft/restart
+    pub(crate) async fn node_bft_restart(State(rest): State<Self>) -> Result<ErasedJson, RestError> {
+        let Some(consensus) = rest.consensus else {
+            return Err(RestError("Route isn't available for this node type".to_string()));
+        };
+        // Stop the consensus, and run it again with fresh channels.
+        consensus.stop().await?;
+        let (primary_sender, primary_receiver) = init_primary_channels::<N>();
+        consensus.run(primary_sender, primary_receiver).await?;
+        Ok(ErasedJson::pretty(json!({
+            "restarted": true,
+            "bft_round": consensus.bft().primary().current_round(),
+            "latest_height": rest.ledger.latest_height(),
+        })))
+    }
+
+    // GET /mainnet/bft/certificates/pending
+    pub(crate) async fn get_bft_pending_certificates(State(rest): State<Self>) -> Result<ErasedJson, RestError> {
+        match rest.consensus {
+            Some(consensus) => Ok(ErasedJson::pretty(consensus.bft().primary().certificate_buffer_summary())),
+            None => Err(RestError("Route isn't available for this node type".to_string())),
+        }
+    }
+
+    // GET /mainnet/bft/dag
+    // GET /mainnet/bft/dag?rounds={K}&format={json|dot}
+    pub(crate) async fn get_bft_dag(
+        State(rest): State<Self>,
+        Query(query): Query<DagQuery>,
+    ) -> Result<Response, RestError> {
+        let Some(consensus) = rest.consensus else {
+            return Err(RestError("Route isn't available for this node type".to_string()));
+        };
+        // Ensure the number of rounds is within the bounds.
+        let num_rounds = query.rounds.unwrap_or(DEFAULT_DAG_SNAPSHOT_ROUNDS);
+        if num_rounds == 0 || num_rounds > MAX_DAG_SNAPSHOT_ROUNDS {
+            return Err(RestError(format!("The number of rounds must be between 1 and {MAX_DAG_SNAPSHOT_ROUNDS}")));
+        }
+        // Snapshot the DAG.
+        let snapshot = consensus.bft().dag_snapshot(num_rounds);
+        match query.format.as_deref() {
+            None | Some("json") => Ok(ErasedJson::pretty(snapshot).into_response()),
+            Some("dot") => Ok(([(CONTENT_TYPE, "text/vnd.graphviz")], snapshot.to_dot()).into_response()),
+            Some(format) => Err(RestError(format!("Unsupported DAG format '{format}' (expected 'json' or 'dot')"))),
+        }
+    }
+
+    // GET /metrics
+    pub(crate) async fn get_metrics(State(rest): State<Self>) -> impl IntoResponse {
+        let mut metrics = PrometheusText::default();
+
+        // Append the ledger metrics.
+        let latest_block = rest.ledger.latest_block();
+        metrics.gauge("snarkos_blocks_height_total", "The height of the latest block.", latest_block.height());
+        let block_age = OffsetDateTime::now_utc().unix_timestamp().saturating_sub(latest_block.timestamp()).max(0);
+        metrics.gauge("snarkos_blocks_latest_age_secs", "The number of seconds since the latest block.", block_age);
+
+        // Append the router metrics.
+        let router = rest.routing.router();
+        metrics.family("snarkos_router_connected_total", "The number of connected peers.", "gauge", [
+            (Labels::default().with("node_type", "client"), router.number_of_connected_clients()),
+            (Labels::default().with("node_type", "prover"), router.number_of_connected_provers()),
+            (Labels::default().with("node_type", "validator"), router.number_of_connected_validators()),
+        ]);
+        metrics.gauge(
+            "snarkos_router_queued_block_responses_total",
+            "The number of block responses awaiting processing.",
+            router.number_of_queued_block_responses(),
+        );
+        let propagation = router.propagation().histogram();
+        metrics.histogram(
+            "snarkos_router_propagation_delay_secs",
+            "The delay for the sampled transactions broadcast by the node to reach other nodes.",
+            propagation.buckets.iter().map(|(bound, count)| (*bound as f64 / 1000.0, *count)),
+            propagation.sum_ms as f64 / 1000.0,
+            propagation.count,
+        );
+        metrics.family(
+            "snarkos_tcp_refused_connections_total",
+            "The number of refused inbound connections, by reason.",
+            "counter",
+            RefusedReason::ALL.map(|reason| {
+                (Labels::default().with("reason", reason.as_str()), router.tcp().stats().refused(reason))
+            }),
+        );
+        metrics.family(
+            "snarkos_router_dropped_messages_total",
+            "The number of outbound messages dropped from the full queue of a peer, by peer and message type.",
+            "counter",
+            router.connected_dropped_messages().into_iter().flat_map(|(peer_ip, dropped)| {
+                dropped.into_iter().map(move |(message_type, count)| {
+                    (Labels::default().with("peer", peer_ip).with("type", message_type), count)
+                })
+            }),
+        );
+        metrics.gauge(
+            "snarkos_router_congested_peers",
+            "The number of connected peers whose outbound queues are full.",
+            router.number_of_congested_peers(),
+        );
+
+        // Append the sync metrics.
+        metrics.gauge(
+            "snarkos_sync_is_synced",
+            "Whether the node is synced.",
+            rest.block_sync().is_block_synced() as u8,
+        );
+        metrics.gauge(
+            "snarkos_sync_blocks_behind",
+            "The number of blocks the node is behind its peers.",
+            rest.block_sync().num_blocks_behind(),
+        );
+
+        // Append the consensus metrics.
+        if let Some(consensus) = &rest.consensus {
+            metrics.gauge(
+                "snarkos_consensus_unconfirmed_transactions_total",
+                "The number of unconfirmed transactions.",
+                consensus.num_unconfirmed_transactions(),
+            );
+            metrics.gauge(
+                "snarkos_consensus_unconfirmed_solutions_total",
+                "The number of unconfirmed solutions.",
+                consensus.num_unconfirmed_solutions(),
+            );
+            metrics.gauge(
+                "snarkos_consensus_unconfirmed_transmissions_total",
+                "The number of unconfirmed transmissions.",
+                consensus.num_unconfirmed_transmissions(),
+            );
+
+            // Append the BFT metrics.
+            let bft = consensus.bft();
+            let primary = bft.primary();
+            let storage = primary.storage();
+            let current_round = storage.current_round();
+            metrics.gauge("snarkos_bft_current_round", "The current round of the BFT.", current_round);
+            metrics.gauge("snarkos_bft_gc_round", "The garbage collection round of the BFT.", storage.gc_round());
+            metrics.gauge(
+                "snarkos_bft_connected_total",
+                "The number of connected validators.",
+                primary.gateway().number_of_connected_peers(),
+            );
+            // Append the number of certificates for the most recent rounds.
+            let oldest_round = current_round.saturating_sub(Self::METRICS_CERTIFICATE_ROUNDS - 1).max(1);
+            metrics.family(
+                "snarkos_bft_certificates_total",
+                "The number of certificates in storage for each recent round.",
+                "gauge",
+                (oldest_round..=current_round).map(|round| {
+                    (Labels::default().with("round", round), storage.get_certificate_authors_for_round(round).len())
+                }),
+            );
+        }
+
+        // Append the REST metrics.
+        if let Some(counters) = &rest.request_counters {
+            metrics.family(
+                "snarkos_rest_requests_total",
+                "The number of requests served by the REST server.",
+                "counter",
+                counters.snapshot().into_iter().map(|(route, status, count)| {
+                    (Labels::default().with("route", route).with("status", status), count)
+                }),
+            );
+        }
+
+        ([(CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)], metrics.finish())
+    }
+
     // GET /mainnet/node/address
     pub(crate) async fn get_node_address(State(rest): State<Self>) -> ErasedJson {
         ErasedJson::pretty(rest.routing.router().address())
     }
 
+    // GET /mainnet/node/ip
+    pub(crate) async fn get_node_ip(State(rest): State<Self>) -> ErasedJson {
+        let router = rest.routing.router();
+        ErasedJson::pretty(json!({ "bind": router.local_ip(), "external": router.external_ip() }))
+    }
+
+    // GET /mainnet/node/denylist
+    pub(crate) async fn get_node_denylist(State(rest): State<Self>) -> ErasedJson {
+        ErasedJson::pretty(rest.program_denylist.programs())
+    }
+
+    // PUT /mainnet/node/denylist
+    pub(crate) async fn put_node_denylist(
+        State(rest): State<Self>,
+        Json(programs): Json<Vec<ProgramID<N>>>,
+    ) -> ErasedJson {
+        // Replace the denylisted programs.
+        let added = rest.program_denylist.set(programs);
+        info!("Updated the program denylist to {} program(s)", rest.program_denylist.len());
+        // If programs were added, evict their transactions from the memory pool.
+        let evicted = match (&rest.consensus, added.is_empty()) {
+            (Some(consensus), false) => consensus.evict_denylisted_transactions(),
+            _ => vec![],
+        };
+        ErasedJson::pretty(json!({ "programs": rest.program_denylist.programs(), "evicted": evicted }))
+    }
+
     // GET /mainnet/find/blockHash/{transactionID}
     pub(crate) async fn find_block_hash(
         State(rest): State<Self>,
@@ -300,6 +1271,92 @@ impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
         Ok(ErasedJson::pretty(rest.ledger.find_transaction_id_from_transition_id(&transition_id)?))
     }
 
+    // GET /mainnet/find/transactionID/commitment/{commitment}
+    pub(crate) async fn find_transaction_id_from_commitment(
+        State(rest): State<Self>,
+        Path(commitment): Path<Field<N>>,
+    ) -> Result<ErasedJson, RestError> {
+        match rest.record_index()?.get_commitment(&commitment)? {
+            Some((height, transaction_id, output_index)) => Ok(ErasedJson::pretty(json!({
+                "height": height,
+                "transaction_id": transaction_id,
+                "output_index": output_index,
+            }))),
+            None => Ok(ErasedJson::pretty(Option::<()>::None)),
+        }
+    }
+
+    // GET /mainnet/find/transactionID/serialNumber/{serialNumber}
+    pub(crate) async fn find_transaction_id_from_serial_number(
+        State(rest): State<Self>,
+        Path(serial_number): Path<Field<N>>,
+    ) -> Result<ErasedJson, RestError> {
+        match rest.record_index()?.get_serial_number(&serial_number)? {
+            Some((height, transaction_id)) => {
+                Ok(ErasedJson::pretty(json!({ "height": height, "transaction_id": transaction_id })))
+            }
+            None => Ok(ErasedJson::pretty(Option::<()>::None)),
+        }
+    }
+
+    // POST /mainnet/scan/register
+    pub(crate) async fn scan_register(
+        State(rest): State<Self>,
+        Json(request): Json<ScanRegister<N>>,
+    ) -> Result<ErasedJson, RestError> {
+        let record_scanner = rest.record_scanner()?.clone();
+        // Determine the backfill range, if one is requested.
+        let backfill = match request.start {
+            Some(start) => {
+                let end = request.end.unwrap_or_else(|| rest.ledger.latest_height().saturating_add(1));
+                RecordScanner::<N>::check_backfill_range(&(start..end))?;
+                Some(start..end)
+            }
+            None => None,
+        };
+        // Register the view key.
+        let address = record_scanner.register(request.view_key)?;
+        // Backfill the requested range in the background.
+        if let Some(heights) = backfill.clone() {
+            let ledger = rest.ledger.clone();
+            tokio::task::spawn_blocking(move || {
+                // Note: The backfill is not interrupted on shutdown, as its range is bounded.
+                if let Err(error) = record_scanner.backfill(&ledger, &address, heights, &AtomicBool::new(false)) {
+                    error!("Failed to backfill the record scan of '{address}' - {error}");
+                }
+            });
+        }
+        let backfill = backfill.map(|heights| json!({ "start": heights.start, "end": heights.end }));
+        Ok(ErasedJson::pretty(json!({ "address": address, "backfill": backfill })))
+    }
+
+    // GET /mainnet/scan/{address}/records?start={start}&end={end}
+    pub(crate) async fn get_scanned_records(
+        State(rest): State<Self>,
+        Path(address): Path<Address<N>>,
+        Query(range): Query<ScanRange>,
+    ) -> Result<ErasedJson, RestError> {
+        let heights = range.start.unwrap_or(0)..range.end.unwrap_or(u32::MAX);
+        match rest.record_scanner()?.get_records(&address, heights) {
+            Some(records) => Ok(ErasedJson::pretty(records.iter().map(scanned_record_to_json).collect::<Vec<_>>())),
+            None => Err(RestError(format!("The view key of '{address}' is not registered for scanning"))),
+        }
+    }
+
+    /// Returns the record scanner, or an error if it is disabled.
+    fn record_scanner(&self) -> Result<&Arc<RecordScanner<N>>, RestError> {
+        self.record_scanner.as_ref().ok_or_else(|| {
+            RestError("The record scanner is disabled - restart the node with '--enable-record-scan'".to_string())
+        })
+    }
+
+    /// Returns the record index, or an error if it is disabled.
+    fn record_index(&self) -> Result<&Arc<RecordIndex<N, C>>, RestError> {
+        self.record_index.as_ref().ok_or_else(|| {
+            RestError("The record index is disabled - restart the node with '--enable-record-index'".to_string())
+        })
+    }
+
     // GET /mainnet/find/transitionID/{inputOrOutputID}
     pub(crate) async fn find_transition_id(
         State(rest): State<Self>,
@@ -309,14 +1366,33 @@ impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
     }
 
     // POST /mainnet/transaction/broadcast
-    pub(crate) async fn transaction_broadcast(
-        State(rest): State<Self>,
-        Json(tx): Json<Transaction<N>>,
-    ) -> Result<ErasedJson, RestError> {
+    pub(crate) async fn transaction_broadcast(State(rest): State<Self>, body: Bytes) -> Result<ErasedJson, RestError> {
+        // Ensure the node accepts transactions.
+        if rest.routing.router().node_type().is_archive() {
+            return Err(RestError("Archive nodes do not accept transaction broadcasts".to_string()));
+        }
+        // Ensure the node has sufficient disk space to accept transactions.
+        rest.ensure_disk_space_for_intake()?;
+        // If the transaction is already confirmed, report its block, without deserializing the transaction.
+        if let Some(transaction_id) = peek_transaction_id::<N>(&body) {
+            if let Some(height) = rest.replay_filter.confirmed_height(&TransmissionID::from(&transaction_id)) {
+                return Ok(ErasedJson::pretty(
+                    json!({ "transaction_id": transaction_id, "status": "confirmed", "block_height": height }),
+                ));
+            }
+        }
+        // Parse the transaction.
+        let tx: Transaction<N> =
+            serde_json::from_slice(&body).map_err(|error| RestError(format!("Malformed transaction - {error}")))?;
+        // Ensure the transaction does not reference a denylisted program.
+        rest.program_denylist.ensure_allowed(&tx)?;
         // If the consensus module is enabled, add the unconfirmed transaction to the memory pool.
+        let mut decision = ConflictDecision::Accepted;
         if let Some(consensus) = rest.consensus {
             // Add the unconfirmed transaction to the memory pool.
-            consensus.add_unconfirmed_transaction(tx.clone()).await?;
+            // Note: A transaction that conflicts with an unconfirmed transaction it cannot replace is either
+            // rejected with an error naming the conflicting transaction, or queued behind it.
+            decision = consensus.add_unconfirmed_transaction(tx.clone()).await?;
         }
 
         // Prepare the unconfirmed transaction message.
@@ -326,10 +1402,175 @@ impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
             transaction: Data::Object(tx),
         });
 
+        // Sample the propagation delay of the transaction, if enabled.
+        rest.routing.router().propagation().sample_broadcast(tx_id);
         // Broadcast the transaction.
         rest.routing.propagate(message, &[]);
 
-        Ok(ErasedJson::pretty(tx_id))
+        // If the transaction replaced or is queued behind any unconfirmed transactions,
+        // include their IDs in the response.
+        match decision {
+            ConflictDecision::Accepted => Ok(ErasedJson::pretty(tx_id)),
+            ConflictDecision::Replaced(replaced) => {
+                Ok(ErasedJson::pretty(json!({ "transaction_id": tx_id, "replaced": replaced })))
+            }
+            ConflictDecision::Queued(conflicts) => {
+                Ok(ErasedJson::pretty(json!({ "transaction_id": tx_id, "queued_behind": conflicts })))
+            }
+        }
+    }
+
+    // POST /mainnet/transactions/broadcast
+    pub(crate) async fn transactions_broadcast(State(rest): State<Self>, body: Bytes) -> Result<ErasedJson, RestError> {
+        // Ensure the node accepts transactions.
+        if rest.routing.router().node_type().is_archive() {
+            return Err(RestError("Archive nodes do not accept transaction broadcasts".to_string()));
+        }
+        // Ensure the node has sufficient disk space to accept transactions.
+        rest.ensure_disk_space_for_intake()?;
+        // Parse the transactions.
+        // Note: A malformed transaction is reported in its result, and does not fail the entire request.
+        let transactions = parse_transaction_batch::<N>(&body)?;
+
+        // If the consensus module is enabled, add the unconfirmed transactions to the memory pool, in order.
+        let (consensus, program_denylist) = (rest.consensus.clone(), rest.program_denylist.clone());
+        let (results, accepted) = add_transaction_batch(transactions, |transaction| {
+            let (consensus, program_denylist) = (consensus.clone(), program_denylist.clone());
+            async move {
+                // Ensure the transaction does not reference a denylisted program.
+                program_denylist.ensure_allowed(&transaction)?;
+                if let Some(consensus) = consensus {
+                    consensus.add_unconfirmed_transaction(transaction).await?;
+                }
+                Ok(())
+            }
+        })
+        .await;
+
+        // Broadcast the accepted transactions.
+        for transaction in accepted {
+            let message = Message::UnconfirmedTransaction(UnconfirmedTransaction {
+                transaction_id: transaction.id(),
+                transaction: Data::Object(transaction),
+            });
+            rest.routing.propagate(message, &[]);
+        }
+
+        // Return the result of each transaction, in the original order.
+        let results = results
+            .into_iter()
+            .map(|result| match result {
+                Ok(transaction_id) => json!({ "status": "accepted", "transaction_id": transaction_id }),
+                Err(error) => json!({ "status": "rejected", "error": error }),
+            })
+            .collect::<Vec<_>>();
+        Ok(ErasedJson::pretty(results))
+    }
+
+    // POST /mainnet/dev/deploy
+    pub(crate) async fn dev_deploy(
+        State(rest): State<Self>,
+        Json(request): Json<DevDeploy<N>>,
+    ) -> Result<ErasedJson, RestError> {
+        let program = Program::<N>::from_str(&request.program)?;
+        // Build the deployment with the node's VM.
+        let ledger = rest.ledger.clone();
+        let transaction = tokio::task::spawn_blocking(move || {
+            ledger.vm().deploy(&request.private_key, &program, None, request.fee, None, &mut rand::thread_rng())
+        })
+        .await
+        .map_err(|error| RestError(error.to_string()))??;
+        // Broadcast the deployment.
+        let body = serde_json::to_vec(&transaction).map_err(|error| RestError(error.to_string()))?;
+        Self::transaction_broadcast(State(rest), Bytes::from(body)).await
+    }
+
+    // POST /mainnet/dev/execute
+    pub(crate) async fn dev_execute(
+        State(rest): State<Self>,
+        Json(request): Json<DevExecute<N>>,
+    ) -> Result<ErasedJson, RestError> {
+        let inputs = request.inputs.iter().map(|input| Value::<N>::from_str(input)).collect::<Result<Vec<_>>>()?;
+        // Build the execution with the node's VM.
+        let ledger = rest.ledger.clone();
+        let transaction = tokio::task::spawn_blocking(move || {
+            ledger.vm().execute(
+                &request.private_key,
+                (request.program_id, request.function),
+                inputs.into_iter(),
+                None,
+                request.fee,
+                None,
+                &mut rand::thread_rng(),
+            )
+        })
+        .await
+        .map_err(|error| RestError(error.to_string()))??;
+        // Broadcast the execution.
+        let body = serde_json::to_vec(&transaction).map_err(|error| RestError(error.to_string()))?;
+        Self::transaction_broadcast(State(rest), Bytes::from(body)).await
+    }
+
+    // POST /mainnet/node/check
+    pub(crate) async fn node_check(
+        State(rest): State<Self>,
+        request: Option<Json<NodeCheck>>,
+    ) -> Result<ErasedJson, RestError> {
+        let request = request.map(|Json(request)| request).unwrap_or_default();
+        let mut check = ConsistencyCheck::default().with_deep(request.deep);
+        if let Some(state_root_depth) = request.state_root_depth {
+            check = check.with_state_root_depth(state_root_depth);
+        }
+        // Run the check on a blocking thread, cancelling it if the request is dropped.
+        let cancel = Arc::new(AtomicBool::new(false));
+        let _guard = CancelOnDrop(cancel.clone());
+        let ledger = rest.ledger.clone();
+        let report = tokio::task::spawn_blocking(move || {
+            check.run(&ledger, &cancel, |progress| {
+                info!("Checked the {} up to block {}/{}", progress.stage, progress.height, progress.latest_height)
+            })
+        })
+        .await
+        .map_err(|error| RestError(error.to_string()))??;
+        Ok(ErasedJson::pretty(consistency_report_to_json(&report)))
+    }
+
+    // POST /mainnet/dev/compareChain
+    pub(crate) async fn dev_compare_chain(
+        State(rest): State<Self>,
+        Json(request): Json<CompareChain>,
+    ) -> Result<ErasedJson, RestError> {
+        let CompareChain { peer, start, end } = request;
+        // Ensure the range is well-formed, and only covers the local blocks.
+        let end = end.min(rest.ledger.latest_height().saturating_add(1));
+        if start >= end {
+            return Err(RestError(format!("Invalid block range ({start}..{end})")));
+        }
+        if end - start > MAXIMUM_COMPARED_BLOCKS {
+            return Err(RestError(format!("Cannot compare more than {MAXIMUM_COMPARED_BLOCKS} blocks at once")));
+        }
+
+        // Retrieve the local block hashes.
+        let ledger = rest.ledger.clone();
+        let local_hashes = tokio::task::spawn_blocking(move || {
+            (start..end).map(|height| ledger.get_hash(height)).collect::<Result<Vec<_>, _>>()
+        })
+        .await
+        .map_err(|error| RestError(error.to_string()))??;
+
+        // Compare the local blocks with the chain of the peer.
+        let comparison = compare_chain(&*rest.routing, peer, start, &local_hashes).await?;
+        let divergent_hash =
+            comparison.divergent_height.map(|height| local_hashes[(height - comparison.start_height) as usize]);
+        Ok(ErasedJson::pretty(json!({
+            "peer": peer,
+            "start": comparison.start_height,
+            "end": comparison.end_height,
+            "consistent": comparison.is_consistent(),
+            "last_common_height": comparison.last_common_height,
+            "divergent_height": comparison.divergent_height,
+            "local_divergent_hash": divergent_hash,
+        })))
     }
 
     // POST /mainnet/solution/broadcast
@@ -337,6 +1578,18 @@ impl<N: Network, C: ConsensusStorage<N>, R: Routing<N>> Rest<N, C, R> {
         State(rest): State<Self>,
         Json(solution): Json<Solution<N>>,
     ) -> Result<ErasedJson, RestError> {
+        // Ensure the node accepts solutions.
+        if rest.routing.router().node_type().is_archive() {
+            return Err(RestError("Archive nodes do not accept solution broadcasts".to_string()));
+        }
+        // Ensure the node has sufficient disk space to accept solutions.
+        rest.ensure_disk_space_for_intake()?;
+        // If the solution is already confirmed, report its block.
+        if let Some(height) = rest.replay_filter.confirmed_height(&TransmissionID::from(solution.id())) {
+            return Ok(ErasedJson::pretty(
+                json!({ "solution_id": solution.id(), "status": "confirmed", "block_height": height }),
+            ));
+        }
         // If the consensus module is enabled, add the unconfirmed solution to the memory pool.
         if let Some(consensus) = rest.consensus {
             // Add the unconfirmed solution to the memory pool.
@@ -0,0 +1,27 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Regenerates the committed test vectors of the message serialization.
+
+use snarkos_node_router_messages::test_vectors::{to_test_vectors, MESSAGE_TEST_VECTORS_PATH};
+use snarkvm::prelude::MainnetV0;
+
+use std::path::Path;
+
+fn main() -> anyhow::Result<()> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join(MESSAGE_TEST_VECTORS_PATH);
+    std::fs::write(&path, to_test_vectors::<MainnetV0>()?)?;
+    println!("Wrote the message test vectors to '{}'", path.display());
+    Ok(())
+}
@@ -0,0 +1,113 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+
+use snarkvm::prelude::{FromBytes, ToBytes};
+
+use std::borrow::Cow;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlockByHashRequest<N: Network> {
+    /// The hashes of the requested blocks.
+    pub block_hashes: Vec<N::BlockHash>,
+}
+
+impl<N: Network> BlockByHashRequest<N> {
+    /// The maximum number of block hashes that can be requested in a single message.
+    pub const MAXIMUM_NUMBER_OF_HASHES: usize = 10;
+}
+
+impl<N: Network> MessageTrait for BlockByHashRequest<N> {
+    /// Returns the message name.
+    #[inline]
+    fn name(&self) -> Cow<'static, str> {
+        format!("BlockByHashRequest ({} hashes)", self.block_hashes.len()).into()
+    }
+}
+
+impl<N: Network> ToBytes for BlockByHashRequest<N> {
+    fn write_le<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        // Ensure the number of block hashes is within the bounds.
+        if self.block_hashes.len() > Self::MAXIMUM_NUMBER_OF_HASHES {
+            return Err(error(format!("Too many block hashes ({})", self.block_hashes.len())));
+        }
+        (self.block_hashes.len() as u8).write_le(&mut writer)?;
+        self.block_hashes.iter().try_for_each(|block_hash| block_hash.write_le(&mut writer))
+    }
+}
+
+impl<N: Network> FromBytes for BlockByHashRequest<N> {
+    fn read_le<R: io::Read>(mut reader: R) -> io::Result<Self> {
+        // Ensure the number of block hashes is within the bounds.
+        let num_hashes = u8::read_le(&mut reader)? as usize;
+        if num_hashes > Self::MAXIMUM_NUMBER_OF_HASHES {
+            return Err(error(format!("Too many block hashes ({num_hashes})")));
+        }
+        let block_hashes = (0..num_hashes).map(|_| N::BlockHash::read_le(&mut reader)).collect::<io::Result<_>>()?;
+        Ok(Self { block_hashes })
+    }
+}
+
+#[cfg(test)]
+pub mod prop_tests {
+    use crate::BlockByHashRequest;
+    use snarkvm::{
+        console::prelude::{FromBytes, ToBytes},
+        prelude::{Rng, TestRng},
+    };
+
+    use bytes::{Buf, BufMut, BytesMut};
+    use proptest::{
+        collection::vec,
+        prelude::{any, BoxedStrategy, Strategy},
+    };
+    use test_strategy::proptest;
+
+    type CurrentNetwork = snarkvm::prelude::MainnetV0;
+
+    pub fn any_block_by_hash_request() -> BoxedStrategy<BlockByHashRequest<CurrentNetwork>> {
+        vec(any::<u64>(), 0..=BlockByHashRequest::<CurrentNetwork>::MAXIMUM_NUMBER_OF_HASHES)
+            .prop_map(|seeds| BlockByHashRequest {
+                block_hashes: seeds.into_iter().map(|seed| TestRng::fixed(seed).gen()).collect(),
+            })
+            .boxed()
+    }
+
+    #[proptest]
+    fn block_by_hash_request_roundtrip(
+        #[strategy(any_block_by_hash_request())] original: BlockByHashRequest<CurrentNetwork>,
+    ) {
+        let mut buf = BytesMut::default().writer();
+        BlockByHashRequest::write_le(&original, &mut buf).unwrap();
+
+        let deserialized: BlockByHashRequest<CurrentNetwork> =
+            BlockByHashRequest::read_le(buf.into_inner().reader()).unwrap();
+        assert_eq!(original, deserialized);
+    }
+
+    #[test]
+    fn block_by_hash_request_rejects_too_many_hashes() {
+        let rng = &mut TestRng::default();
+        let num_hashes = BlockByHashRequest::<CurrentNetwork>::MAXIMUM_NUMBER_OF_HASHES + 1;
+        let request =
+            BlockByHashRequest::<CurrentNetwork> { block_hashes: (0..num_hashes).map(|_| rng.gen()).collect() };
+        assert!(request.to_bytes_le().is_err());
+
+        // Ensure an oversized request is rejected when decoding.
+        let mut bytes = vec![num_hashes as u8];
+        request.block_hashes.iter().for_each(|hash| bytes.extend(hash.to_bytes_le().unwrap()));
+        assert!(BlockByHashRequest::<CurrentNetwork>::read_le(&bytes[..]).is_err());
+    }
+}
@@ -0,0 +1,188 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+use crate::helpers::MAXIMUM_MESSAGE_SIZE;
+
+use snarkos_node_bft_events::DataLimit;
+use snarkvm::{
+    ledger::narwhal::Data,
+    prelude::{block::Block, FromBytes, ToBytes},
+};
+
+use std::borrow::Cow;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlockByHashResponse<N: Network> {
+    /// The original block-by-hash request.
+    pub request: BlockByHashRequest<N>,
+    /// The blocks, in the order of the requested hashes.
+    pub blocks: Data<BlocksByHash<N>>,
+}
+
+impl<N: Network> MessageTrait for BlockByHashResponse<N> {
+    /// Returns the message name.
+    #[inline]
+    fn name(&self) -> Cow<'static, str> {
+        format!("BlockByHashResponse ({} hashes)", self.request.block_hashes.len()).into()
+    }
+}
+
+impl<N: Network> ToBytes for BlockByHashResponse<N> {
+    fn write_le<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        self.request.write_le(&mut writer)?;
+        self.blocks.write_le(writer)
+    }
+}
+
+impl<N: Network> FromBytes for BlockByHashResponse<N> {
+    fn read_le<R: io::Read>(mut reader: R) -> io::Result<Self> {
+        let request = BlockByHashRequest::read_le(&mut reader)?;
+        let blocks = read_data(reader)?;
+        Ok(Self { request, blocks })
+    }
+}
+
+/// The blocks in a `BlockByHashResponse`, where `None` marks a block hash that is unknown to the peer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlocksByHash<N: Network>(pub Vec<Option<Block<N>>>);
+
+impl<N: Network> BlocksByHash<N> {
+    /// Ensures that the blocks are well-formed in a response to the given block hashes.
+    pub fn ensure_response_is_well_formed(&self, peer_ip: SocketAddr, block_hashes: &[N::BlockHash]) -> io::Result<()> {
+        // Ensure there is an entry for every requested block hash.
+        if self.0.len() != block_hashes.len() {
+            return Err(error(format!(
+                "Peer '{peer_ip}' sent {} blocks for {} block hashes",
+                self.0.len(),
+                block_hashes.len()
+            )));
+        }
+        // Ensure every block matches its requested block hash.
+        for (block, block_hash) in self.0.iter().zip(block_hashes) {
+            if let Some(block) = block {
+                if block.hash() != *block_hash {
+                    return Err(error(format!("Peer '{peer_ip}' sent block {} for hash '{block_hash}'", block.hash())));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<N: Network> DataLimit for BlocksByHash<N> {
+    const MAXIMUM_SIZE: usize = MAXIMUM_MESSAGE_SIZE;
+    const NAME: &'static str = "blocks by hash";
+}
+
+impl<N: Network> ToBytes for BlocksByHash<N> {
+    fn write_le<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        // Ensure the number of blocks is within the bounds.
+        if self.0.len() > BlockByHashRequest::<N>::MAXIMUM_NUMBER_OF_HASHES {
+            return Err(error(format!("Too many blocks ({})", self.0.len())));
+        }
+        (self.0.len() as u8).write_le(&mut writer)?;
+        for block in &self.0 {
+            match block {
+                Some(block) => {
+                    1u8.write_le(&mut writer)?;
+                    block.write_le(&mut writer)?;
+                }
+                None => 0u8.write_le(&mut writer)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<N: Network> FromBytes for BlocksByHash<N> {
+    fn read_le<R: io::Read>(mut reader: R) -> io::Result<Self> {
+        // Ensure the number of blocks is within the bounds.
+        let num_blocks = u8::read_le(&mut reader)? as usize;
+        if num_blocks > BlockByHashRequest::<N>::MAXIMUM_NUMBER_OF_HASHES {
+            return Err(error(format!("Too many blocks ({num_blocks})")));
+        }
+        let mut blocks = Vec::with_capacity(num_blocks);
+        for _ in 0..num_blocks {
+            match u8::read_le(&mut reader)? {
+                0 => blocks.push(None),
+                1 => blocks.push(Some(Block::read_le(&mut reader)?)),
+                variant => return Err(error(format!("Invalid block marker ({variant})"))),
+            }
+        }
+        Ok(Self(blocks))
+    }
+}
+
+#[cfg(test)]
+pub mod prop_tests {
+    use crate::{
+        block_by_hash_request::prop_tests::any_block_by_hash_request,
+        block_response::prop_tests::any_block,
+        BlockByHashResponse,
+        BlocksByHash,
+    };
+    use snarkvm::{
+        ledger::ledger_test_helpers::sample_genesis_block,
+        prelude::{narwhal::Data, Rng},
+        utilities::{FromBytes, TestRng, ToBytes},
+    };
+
+    use bytes::{Buf, BufMut, BytesMut};
+    use proptest::{
+        collection::vec,
+        option,
+        prelude::{BoxedStrategy, Strategy},
+    };
+    use test_strategy::proptest;
+
+    type CurrentNetwork = snarkvm::prelude::MainnetV0;
+
+    pub fn any_blocks_by_hash() -> BoxedStrategy<BlocksByHash<CurrentNetwork>> {
+        vec(option::of(any_block()), 0..=2).prop_map(BlocksByHash).boxed()
+    }
+
+    pub fn any_block_by_hash_response() -> BoxedStrategy<BlockByHashResponse<CurrentNetwork>> {
+        (any_block_by_hash_request(), any_blocks_by_hash())
+            .prop_map(|(request, blocks)| BlockByHashResponse { request, blocks: Data::Object(blocks) })
+            .boxed()
+    }
+
+    #[proptest]
+    fn block_by_hash_response_roundtrip(
+        #[strategy(any_block_by_hash_response())] original: BlockByHashResponse<CurrentNetwork>,
+    ) {
+        let mut bytes = BytesMut::default().writer();
+        original.write_le(&mut bytes).unwrap();
+        let decoded = BlockByHashResponse::<CurrentNetwork>::read_le(&mut bytes.into_inner().reader()).unwrap();
+        assert_eq!(original.request, decoded.request);
+        assert_eq!(original.blocks.deserialize_blocking().unwrap(), decoded.blocks.deserialize_blocking().unwrap());
+    }
+
+    #[test]
+    fn blocks_by_hash_well_formed() {
+        let rng = &mut TestRng::default();
+        let peer_ip = "127.0.0.1:4130".parse().unwrap();
+        let block = sample_genesis_block(rng);
+        let unknown_hash = rng.gen();
+        let blocks = BlocksByHash::<CurrentNetwork>(vec![Some(block.clone()), None]);
+
+        // Ensure the blocks are well-formed for the requested hashes.
+        assert!(blocks.ensure_response_is_well_formed(peer_ip, &[block.hash(), unknown_hash]).is_ok());
+        // Ensure a block that does not match its requested hash is rejected.
+        assert!(blocks.ensure_response_is_well_formed(peer_ip, &[unknown_hash, unknown_hash]).is_err());
+        // Ensure a response with a missing entry is rejected.
+        assert!(blocks.ensure_response_is_well_formed(peer_ip, &[block.hash()]).is_err());
+    }
+}
@@ -0,0 +1,95 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+use crate::compact_block::{read_entries, write_entries, MAXIMUM_NUMBER_OF_TRANSACTIONS};
+
+use snarkvm::prelude::{FromBytes, ToBytes};
+
+use std::borrow::Cow;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlockTransactions<N: Network> {
+    /// The hash of the announced block.
+    pub block_hash: N::BlockHash,
+    /// The positions of the transactions in the compact block.
+    pub positions: Vec<u32>,
+    /// The requested transactions.
+    pub transactions: Vec<Transaction<N>>,
+}
+
+impl<N: Network> MessageTrait for BlockTransactions<N> {
+    /// Returns the message name.
+    #[inline]
+    fn name(&self) -> Cow<'static, str> {
+        "BlockTransactions".into()
+    }
+}
+
+impl<N: Network> ToBytes for BlockTransactions<N> {
+    fn write_le<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        self.block_hash.write_le(&mut writer)?;
+        write_entries(&self.positions, &mut writer)?;
+        write_entries(&self.transactions, &mut writer)
+    }
+}
+
+impl<N: Network> FromBytes for BlockTransactions<N> {
+    fn read_le<R: io::Read>(mut reader: R) -> io::Result<Self> {
+        Ok(Self {
+            block_hash: N::BlockHash::read_le(&mut reader)?,
+            positions: read_entries(&mut reader, MAXIMUM_NUMBER_OF_TRANSACTIONS, "transaction positions")?,
+            transactions: read_entries(&mut reader, MAXIMUM_NUMBER_OF_TRANSACTIONS, "transactions")?,
+        })
+    }
+}
+
+#[cfg(test)]
+pub mod prop_tests {
+    use crate::{
+        get_block_transactions::prop_tests::any_get_block_transactions,
+        unconfirmed_transaction::prop_tests::any_transaction,
+        BlockTransactions,
+    };
+    use snarkvm::console::prelude::{FromBytes, ToBytes};
+
+    use bytes::{Buf, BufMut, BytesMut};
+    use proptest::{
+        collection::vec,
+        prelude::{BoxedStrategy, Strategy},
+    };
+    use test_strategy::proptest;
+
+    type CurrentNetwork = snarkvm::prelude::MainnetV0;
+
+    pub fn any_block_transactions() -> BoxedStrategy<BlockTransactions<CurrentNetwork>> {
+        (any_get_block_transactions(), vec(any_transaction(), 0..=2))
+            .prop_map(|(request, transactions)| BlockTransactions {
+                block_hash: request.block_hash,
+                positions: request.positions,
+                transactions,
+            })
+            .boxed()
+    }
+
+    #[proptest]
+    fn block_transactions_roundtrip(#[strategy(any_block_transactions())] original: BlockTransactions<CurrentNetwork>) {
+        let mut buf = BytesMut::default().writer();
+        BlockTransactions::write_le(&original, &mut buf).unwrap();
+
+        let deserialized: BlockTransactions<CurrentNetwork> =
+            BlockTransactions::read_le(buf.into_inner().reader()).unwrap();
+        assert_eq!(original, deserialized);
+    }
+}
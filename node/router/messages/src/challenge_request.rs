@@ -16,15 +16,20 @@ use super::*;
 
 use snarkvm::prelude::{FromBytes, ToBytes};
 
-use std::borrow::Cow;
+use std::{borrow::Cow, net::SocketAddr};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ChallengeRequest<N: Network> {
     pub version: u32,
+    pub minimum_version: u32,
     pub listener_port: u16,
     pub node_type: NodeType,
     pub address: Address<N>,
     pub nonce: u64,
+    pub capabilities: Capabilities,
+    /// The address at which the sender is reachable, if it differs from the address observed by the receiver,
+    /// e.g. if the sender connects through a proxy. It is only encoded if the capabilities carry its bit.
+    pub external_addr: Option<SocketAddr>,
 }
 
 impl<N: Network> MessageTrait for ChallengeRequest<N> {
@@ -38,10 +43,19 @@ impl<N: Network> MessageTrait for ChallengeRequest<N> {
 impl<N: Network> ToBytes for ChallengeRequest<N> {
     fn write_le<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
         self.version.write_le(&mut writer)?;
+        self.minimum_version.write_le(&mut writer)?;
         self.listener_port.write_le(&mut writer)?;
         self.node_type.write_le(&mut writer)?;
         self.address.write_le(&mut writer)?;
         self.nonce.write_le(&mut writer)?;
+        // Ensure the capabilities are consistent with the presence of the external address.
+        if self.capabilities.has_external_address() != self.external_addr.is_some() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Inconsistent external address capability"));
+        }
+        self.capabilities.write_le(&mut writer)?;
+        if let Some(external_addr) = self.external_addr {
+            external_addr.write_le(&mut writer)?;
+        }
         Ok(())
     }
 }
@@ -49,31 +63,70 @@ impl<N: Network> ToBytes for ChallengeRequest<N> {
 impl<N: Network> FromBytes for ChallengeRequest<N> {
     fn read_le<R: io::Read>(mut reader: R) -> io::Result<Self> {
         let version = u32::read_le(&mut reader)?;
+        let minimum_version = u32::read_le(&mut reader)?;
         let listener_port = u16::read_le(&mut reader)?;
         let node_type = NodeType::read_le(&mut reader)?;
         let address = Address::<N>::read_le(&mut reader)?;
         let nonce = u64::read_le(&mut reader)?;
+        let capabilities = Capabilities::read_le(&mut reader)?;
+        let external_addr = match capabilities.has_external_address() {
+            true => Some(SocketAddr::read_le(&mut reader)?),
+            false => None,
+        };
 
-        Ok(Self { version, listener_port, node_type, address, nonce })
+        Ok(Self { version, minimum_version, listener_port, node_type, address, nonce, capabilities, external_addr })
     }
 }
 
 impl<N: Network> ChallengeRequest<N> {
     pub fn new(listener_port: u16, node_type: NodeType, address: Address<N>, nonce: u64) -> Self {
-        Self { version: Message::<N>::VERSION, listener_port, node_type, address, nonce }
+        let capabilities = Capabilities::for_node_type(node_type);
+        let versions = VersionRange::supported();
+        Self {
+            version: versions.maximum(),
+            minimum_version: versions.minimum(),
+            listener_port,
+            node_type,
+            address,
+            nonce,
+            capabilities,
+            external_addr: None,
+        }
+    }
+
+    /// Returns the challenge request, advertising the given external address, if any.
+    pub fn with_external_addr(mut self, external_addr: Option<SocketAddr>) -> Self {
+        self.capabilities = self.capabilities.with_external_address(external_addr.is_some());
+        self.external_addr = external_addr;
+        self
+    }
+
+    /// Returns the challenge request, advertising the given range of supported versions.
+    pub fn with_versions(mut self, versions: VersionRange) -> Self {
+        self.version = versions.maximum();
+        self.minimum_version = versions.minimum();
+        self
+    }
+
+    /// Returns the range of versions supported by the sender.
+    pub const fn versions(&self) -> VersionRange {
+        VersionRange::new(self.minimum_version, self.version)
     }
 }
 
 #[cfg(test)]
 pub mod prop_tests {
-    use crate::{ChallengeRequest, NodeType};
+    use crate::{peer_response::prop_tests::any_valid_socket_addr, Capabilities, ChallengeRequest, NodeType};
     use snarkvm::{
         console::prelude::{FromBytes, ToBytes},
         prelude::{Address, TestRng, Uniform},
     };
 
     use bytes::{Buf, BufMut, BytesMut};
-    use proptest::prelude::{any, BoxedStrategy, Strategy};
+    use proptest::{
+        option,
+        prelude::{any, BoxedStrategy, Strategy},
+    };
     use test_strategy::proptest;
 
     type CurrentNetwork = snarkvm::prelude::MainnetV0;
@@ -83,25 +136,50 @@ pub mod prop_tests {
     }
 
     pub fn any_node_type() -> BoxedStrategy<NodeType> {
-        (0..=2)
+        (0..=3)
             .prop_map(|id| match id {
                 0 => NodeType::Client,
                 1 => NodeType::Prover,
                 2 => NodeType::Validator,
+                3 => NodeType::Archive,
                 _ => unreachable!(),
             })
             .boxed()
     }
 
     pub fn any_challenge_request() -> BoxedStrategy<ChallengeRequest<CurrentNetwork>> {
-        (any_valid_address(), any::<u64>(), any::<u32>(), any::<u16>(), any_node_type())
-            .prop_map(|(address, nonce, version, listener_port, node_type)| ChallengeRequest {
-                address,
-                nonce,
-                version,
-                listener_port,
-                node_type,
-            })
+        (
+            any_valid_address(),
+            any::<u64>(),
+            any::<(u32, u32)>(),
+            any::<u16>(),
+            any_node_type(),
+            any::<u32>(),
+            option::of(any_valid_socket_addr()),
+        )
+            .prop_map(
+                |(
+                    address,
+                    nonce,
+                    (version, minimum_version),
+                    listener_port,
+                    node_type,
+                    capabilities,
+                    external_addr,
+                )| {
+                    ChallengeRequest {
+                        address,
+                        nonce,
+                        version,
+                        minimum_version,
+                        listener_port,
+                        node_type,
+                        capabilities: Capabilities::new(capabilities),
+                        external_addr: None,
+                    }
+                    .with_external_addr(external_addr)
+                },
+            )
             .boxed()
     }
 
@@ -28,6 +28,24 @@ pub struct ChallengeResponse<N: Network> {
     pub nonce: u64,
 }
 
+impl<N: Network> ChallengeResponse<N> {
+    /// Returns the bytes that are signed in a challenge response, which bind the signature to the counterparty nonce,
+    /// the response nonce, the genesis header, and the listener port of the signer.
+    pub fn signed_bytes(
+        peer_nonce: u64,
+        response_nonce: u64,
+        genesis_header: &Header<N>,
+        listener_port: u16,
+    ) -> io::Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        peer_nonce.write_le(&mut bytes)?;
+        response_nonce.write_le(&mut bytes)?;
+        genesis_header.write_le(&mut bytes)?;
+        listener_port.write_le(&mut bytes)?;
+        Ok(bytes)
+    }
+}
+
 impl<N: Network> MessageTrait for ChallengeResponse<N> {
     /// Returns the message name.
     #[inline]
@@ -48,7 +66,7 @@ impl<N: Network> FromBytes for ChallengeResponse<N> {
     fn read_le<R: io::Read>(mut reader: R) -> io::Result<Self> {
         Ok(Self {
             genesis_header: Header::read_le(&mut reader)?,
-            signature: Data::read_le(&mut reader)?,
+            signature: read_data(&mut reader)?,
             nonce: u64::read_le(reader)?,
         })
     }
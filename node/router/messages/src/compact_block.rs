@@ -0,0 +1,585 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+
+use snarkvm::{
+    ledger::{
+        authority::Authority,
+        block::{Block, ConfirmedTransaction, Ratifications, Solutions, Transactions},
+    },
+    prelude::{bail, ensure, FromBytes, Result, ToBytes},
+    synthesizer::program::FinalizeOperation,
+};
+
+use std::{
+    borrow::Cow,
+    collections::{hash_map::Entry, HashMap},
+    hash::Hasher,
+};
+
+/// The maximum number of transactions in a compact block, which also bounds the aborted transaction IDs of the block,
+/// and the transactions (or their positions) that are requested from it.
+pub(crate) const MAXIMUM_NUMBER_OF_TRANSACTIONS: u32 = u16::MAX as u32;
+/// The maximum number of aborted solution IDs in a compact block.
+pub(crate) const MAXIMUM_NUMBER_OF_ABORTED_SOLUTIONS: u32 = u16::MAX as u32;
+/// The maximum number of finalize operations of an accepted transaction in a compact block.
+pub(crate) const MAXIMUM_NUMBER_OF_FINALIZE_OPERATIONS: u32 = u16::MAX as u32;
+
+/// Returns the 8-byte short ID of the given transaction ID, keyed by the given announcement nonce.
+pub fn short_id<N: Network>(nonce: u64, transaction_id: &N::TransactionID) -> u64 {
+    // Note: `SipHasher` is deprecated only in favor of `DefaultHasher`, whose algorithm is unspecified;
+    // the short IDs must be stable across nodes, so SipHash-2-4 is used directly.
+    #[allow(deprecated)]
+    let mut hasher = std::hash::SipHasher::new_with_keys(nonce, 0);
+    hasher.write(&transaction_id.to_bytes_le().unwrap_or_default());
+    hasher.finish()
+}
+
+/// A transaction in a compact block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CompactTransaction<N: Network> {
+    /// An accepted transaction, identified by the short ID of its transaction ID.
+    Accepted { short_id: u64, index: u32, is_deploy: bool, finalize_operations: Vec<FinalizeOperation<N>> },
+    /// A transaction that is sent in full, as peers are not expected to have it in their memory pool.
+    Prefilled(ConfirmedTransaction<N>),
+}
+
+impl<N: Network> ToBytes for CompactTransaction<N> {
+    fn write_le<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        match self {
+            Self::Accepted { short_id, index, is_deploy, finalize_operations } => {
+                0u8.write_le(&mut writer)?;
+                short_id.write_le(&mut writer)?;
+                index.write_le(&mut writer)?;
+                is_deploy.write_le(&mut writer)?;
+                write_entries(finalize_operations, &mut writer)
+            }
+            Self::Prefilled(transaction) => {
+                1u8.write_le(&mut writer)?;
+                transaction.write_le(&mut writer)
+            }
+        }
+    }
+}
+
+impl<N: Network> FromBytes for CompactTransaction<N> {
+    fn read_le<R: io::Read>(mut reader: R) -> io::Result<Self> {
+        match u8::read_le(&mut reader)? {
+            0 => Ok(Self::Accepted {
+                short_id: u64::read_le(&mut reader)?,
+                index: u32::read_le(&mut reader)?,
+                is_deploy: bool::read_le(&mut reader)?,
+                finalize_operations: read_entries(
+                    &mut reader,
+                    MAXIMUM_NUMBER_OF_FINALIZE_OPERATIONS,
+                    "finalize operations",
+                )?,
+            }),
+            1 => Ok(Self::Prefilled(ConfirmedTransaction::read_le(&mut reader)?)),
+            _ => Err(error("Invalid compact transaction variant")),
+        }
+    }
+}
+
+/// A block announcement, where the accepted transactions are replaced by their short IDs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompactBlock<N: Network> {
+    /// The key for the short IDs of this announcement.
+    pub nonce: u64,
+    /// The hash of the block.
+    pub block_hash: N::BlockHash,
+    /// The hash of the previous block.
+    pub previous_hash: N::BlockHash,
+    /// The header of the block.
+    pub header: Header<N>,
+    /// The authority of the block.
+    pub authority: Authority<N>,
+    /// The ratifications of the block.
+    pub ratifications: Ratifications<N>,
+    /// The solutions of the block.
+    pub solutions: Solutions<N>,
+    /// The aborted solution IDs of the block.
+    pub aborted_solution_ids: Vec<SolutionID<N>>,
+    /// The (compact) transactions of the block.
+    pub transactions: Vec<CompactTransaction<N>>,
+    /// The aborted transaction IDs of the block.
+    pub aborted_transaction_ids: Vec<N::TransactionID>,
+}
+
+impl<N: Network> CompactBlock<N> {
+    /// Initializes a new compact block from the given block, keyed by the given nonce.
+    pub fn new(block: &Block<N>, nonce: u64) -> Self {
+        let transactions = block
+            .transactions()
+            .iter()
+            .map(|confirmed| match confirmed {
+                ConfirmedTransaction::AcceptedDeploy(index, transaction, finalize_operations) => {
+                    CompactTransaction::Accepted {
+                        short_id: short_id::<N>(nonce, &transaction.id()),
+                        index: *index,
+                        is_deploy: true,
+                        finalize_operations: finalize_operations.clone(),
+                    }
+                }
+                ConfirmedTransaction::AcceptedExecute(index, transaction, finalize_operations) => {
+                    CompactTransaction::Accepted {
+                        short_id: short_id::<N>(nonce, &transaction.id()),
+                        index: *index,
+                        is_deploy: false,
+                        finalize_operations: finalize_operations.clone(),
+                    }
+                }
+                // Rejected transactions are stored as their fee transaction, which peers do not have.
+                ConfirmedTransaction::RejectedDeploy(..) | ConfirmedTransaction::RejectedExecute(..) => {
+                    CompactTransaction::Prefilled(confirmed.clone())
+                }
+            })
+            .collect();
+
+        Self {
+            nonce,
+            block_hash: block.hash(),
+            previous_hash: block.previous_hash(),
+            header: *block.header(),
+            authority: block.authority().clone(),
+            ratifications: block.ratifications().clone(),
+            solutions: block.solutions().clone(),
+            aborted_solution_ids: block.aborted_solution_ids().clone(),
+            transactions,
+            aborted_transaction_ids: block.aborted_transaction_ids().clone(),
+        }
+    }
+
+    /// Returns the height of the announced block.
+    pub fn height(&self) -> u32 {
+        self.header.height()
+    }
+
+    /// Prepares a partial block, by matching the short IDs against the given (unconfirmed) transactions.
+    /// Returns an error if a short ID collision is detected, in which case the full block must be requested.
+    pub fn to_partial_block(
+        &self,
+        unconfirmed: impl IntoIterator<Item = (N::TransactionID, Transaction<N>)>,
+    ) -> Result<PartialBlock<N>> {
+        let unconfirmed =
+            unconfirmed.into_iter().map(|(id, transaction)| (short_id::<N>(self.nonce, &id), id, transaction));
+        self.match_short_ids(unconfirmed)
+    }
+
+    /// Prepares a partial block, by matching the short IDs against the given (unconfirmed) transactions,
+    /// where each transaction is given as `(short ID, transaction ID, transaction)`.
+    fn match_short_ids(
+        &self,
+        unconfirmed: impl IntoIterator<Item = (u64, N::TransactionID, Transaction<N>)>,
+    ) -> Result<PartialBlock<N>> {
+        // Map each short ID in the compact block to its position.
+        let mut positions = HashMap::with_capacity(self.transactions.len());
+        for (position, transaction) in self.transactions.iter().enumerate() {
+            if let CompactTransaction::Accepted { short_id, .. } = transaction {
+                // Ensure the short IDs in the compact block are unique.
+                ensure!(positions.insert(*short_id, position).is_none(), "Duplicate short ID in compact block");
+            }
+        }
+
+        // Fill the positions with the matching unconfirmed transactions.
+        let mut transactions = vec![None; self.transactions.len()];
+        let mut matched = HashMap::<usize, N::TransactionID>::new();
+        for (short_id, transaction_id, transaction) in unconfirmed {
+            let Some(position) = positions.get(&short_id) else { continue };
+            // Ensure that at most one unconfirmed transaction matches each short ID.
+            match matched.entry(*position) {
+                Entry::Occupied(entry) if *entry.get() != transaction_id => {
+                    bail!("Short ID collision in compact block {}", self.block_hash)
+                }
+                Entry::Occupied(_) => continue,
+                Entry::Vacant(entry) => {
+                    entry.insert(transaction_id);
+                }
+            }
+            transactions[*position] = Some(transaction);
+        }
+
+        Ok(PartialBlock { compact: self.clone(), transactions })
+    }
+}
+
+/// A compact block that is being reconstructed from the memory pool.
+#[derive(Clone, Debug)]
+pub struct PartialBlock<N: Network> {
+    /// The compact block.
+    compact: CompactBlock<N>,
+    /// The reconstructed transactions, indexed by their position in the compact block.
+    transactions: Vec<Option<Transaction<N>>>,
+}
+
+impl<N: Network> PartialBlock<N> {
+    /// Returns the compact block.
+    pub const fn compact(&self) -> &CompactBlock<N> {
+        &self.compact
+    }
+
+    /// Returns the positions of the accepted transactions that are missing.
+    pub fn missing_positions(&self) -> Vec<u32> {
+        self.compact
+            .transactions
+            .iter()
+            .zip(&self.transactions)
+            .enumerate()
+            .filter_map(|(position, (compact, transaction))| match (compact, transaction) {
+                (CompactTransaction::Accepted { .. }, None) => Some(position as u32),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Fills the given positions with the given transactions, as received from a peer.
+    pub fn fill(&mut self, positions: &[u32], transactions: Vec<Transaction<N>>) -> Result<()> {
+        ensure!(positions.len() == transactions.len(), "Mismatched number of block transactions");
+        for (position, transaction) in positions.iter().zip(transactions) {
+            // Ensure the transaction matches the short ID at the given position.
+            match self.compact.transactions.get(*position as usize) {
+                Some(CompactTransaction::Accepted { short_id: expected, .. })
+                    if *expected == short_id::<N>(self.compact.nonce, &transaction.id()) =>
+                {
+                    self.transactions[*position as usize] = Some(transaction)
+                }
+                _ => bail!("Block transaction at position {position} does not match the compact block"),
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the reconstructed block, if all transactions are present and the block is well-formed.
+    pub fn into_block(self) -> Result<Block<N>> {
+        let CompactBlock {
+            nonce: _,
+            block_hash,
+            previous_hash,
+            header,
+            authority,
+            ratifications,
+            solutions,
+            aborted_solution_ids,
+            transactions: compact_transactions,
+            aborted_transaction_ids,
+        } = self.compact;
+
+        // Reconstruct the confirmed transactions.
+        let mut confirmed = Vec::with_capacity(compact_transactions.len());
+        for (compact, transaction) in compact_transactions.into_iter().zip(self.transactions) {
+            confirmed.push(match (compact, transaction) {
+                (CompactTransaction::Prefilled(transaction), _) => transaction,
+                (CompactTransaction::Accepted { index, is_deploy: true, finalize_operations, .. }, Some(tx)) => {
+                    ConfirmedTransaction::accepted_deploy(index, tx, finalize_operations)?
+                }
+                (CompactTransaction::Accepted { index, is_deploy: false, finalize_operations, .. }, Some(tx)) => {
+                    ConfirmedTransaction::accepted_execute(index, tx, finalize_operations)?
+                }
+                (CompactTransaction::Accepted { index, .. }, None) => bail!("Missing block transaction {index}"),
+            });
+        }
+        let transactions = Transactions::from_iter(confirmed);
+        // Ensure the transactions match the header, to detect undetected short ID collisions.
+        ensure!(
+            transactions.to_transactions_root()? == header.transactions_root(),
+            "Reconstructed transactions do not match the header of block {block_hash}"
+        );
+
+        // Construct the block.
+        let block = Block::from(
+            previous_hash,
+            header,
+            authority,
+            ratifications,
+            solutions,
+            aborted_solution_ids,
+            transactions,
+            aborted_transaction_ids,
+        )?;
+        // Ensure the block hash matches the announcement.
+        ensure!(block.hash() == block_hash, "Reconstructed block hash does not match the announced block {block_hash}");
+        Ok(block)
+    }
+}
+
+impl<N: Network> MessageTrait for CompactBlock<N> {
+    /// Returns the message name.
+    #[inline]
+    fn name(&self) -> Cow<'static, str> {
+        format!("CompactBlock {}", self.height()).into()
+    }
+}
+
+impl<N: Network> ToBytes for CompactBlock<N> {
+    fn write_le<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        self.nonce.write_le(&mut writer)?;
+        self.block_hash.write_le(&mut writer)?;
+        self.previous_hash.write_le(&mut writer)?;
+        self.header.write_le(&mut writer)?;
+        self.authority.write_le(&mut writer)?;
+        self.ratifications.write_le(&mut writer)?;
+        self.solutions.write_le(&mut writer)?;
+        write_entries(&self.aborted_solution_ids, &mut writer)?;
+        write_entries(&self.transactions, &mut writer)?;
+        write_entries(&self.aborted_transaction_ids, &mut writer)
+    }
+}
+
+impl<N: Network> FromBytes for CompactBlock<N> {
+    fn read_le<R: io::Read>(mut reader: R) -> io::Result<Self> {
+        Ok(Self {
+            nonce: u64::read_le(&mut reader)?,
+            block_hash: N::BlockHash::read_le(&mut reader)?,
+            previous_hash: N::BlockHash::read_le(&mut reader)?,
+            header: Header::read_le(&mut reader)?,
+            authority: Authority::read_le(&mut reader)?,
+            ratifications: Ratifications::read_le(&mut reader)?,
+            solutions: Solutions::read_le(&mut reader)?,
+            aborted_solution_ids: read_entries(
+                &mut reader,
+                MAXIMUM_NUMBER_OF_ABORTED_SOLUTIONS,
+                "aborted solution IDs",
+            )?,
+            transactions: read_entries(&mut reader, MAXIMUM_NUMBER_OF_TRANSACTIONS, "transactions")?,
+            aborted_transaction_ids: read_entries(
+                &mut reader,
+                MAXIMUM_NUMBER_OF_TRANSACTIONS,
+                "aborted transaction IDs",
+            )?,
+        })
+    }
+}
+
+/// Writes the given entries, prefixed by their number.
+pub(crate) fn write_entries<T: ToBytes, W: io::Write>(entries: &[T], mut writer: W) -> io::Result<()> {
+    u32::try_from(entries.len()).map_err(error)?.write_le(&mut writer)?;
+    entries.iter().try_for_each(|entry| entry.write_le(&mut writer))
+}
+
+/// Reads a list of entries, prefixed by their number, failing if the number exceeds the given maximum.
+/// Note: The list grows with the entries that are actually read, so that a forged prefix cannot trigger a large allocation.
+pub(crate) fn read_entries<T: FromBytes, R: io::Read>(mut reader: R, maximum: u32, name: &str) -> io::Result<Vec<T>> {
+    let num_entries = u32::read_le(&mut reader)?;
+    if num_entries > maximum {
+        return Err(error(format!("Too many {name} ({num_entries}, at most {maximum} are allowed)")));
+    }
+    (0..num_entries).map(|_| T::read_le(&mut reader)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkvm::{
+        ledger::ledger_test_helpers::sample_genesis_block,
+        prelude::{Field, Rng, TestRng, Uniform},
+    };
+
+    use bytes::{Buf, BufMut, BytesMut};
+
+    type CurrentNetwork = snarkvm::prelude::MainnetV0;
+
+    /// Returns the unconfirmed transactions of the given block.
+    fn sample_unconfirmed(
+        block: &Block<CurrentNetwork>,
+    ) -> Vec<(<CurrentNetwork as Network>::TransactionID, Transaction<CurrentNetwork>)> {
+        block
+            .transactions()
+            .iter()
+            .map(|confirmed| (confirmed.transaction().id(), confirmed.transaction().clone()))
+            .collect()
+    }
+
+    /// Reconstructs the given block from a memory pool that contains the given percentage of its transactions.
+    fn check_reconstruction(percentage: usize) {
+        let rng = &mut TestRng::default();
+        let block = sample_genesis_block(rng);
+        let unconfirmed = sample_unconfirmed(&block);
+        let num_in_memory_pool = unconfirmed.len() * percentage / 100;
+
+        // Prepare the compact block, and check that it serializes correctly.
+        let compact = CompactBlock::new(&block, rng.gen());
+        let mut buf = BytesMut::default().writer();
+        compact.write_le(&mut buf).unwrap();
+        assert_eq!(compact, CompactBlock::read_le(buf.into_inner().reader()).unwrap());
+
+        // Match the compact block against the memory pool.
+        let mut partial = compact.to_partial_block(unconfirmed.iter().take(num_in_memory_pool).cloned()).unwrap();
+        // Check that only the transactions outside of the memory pool are missing.
+        let missing = partial.missing_positions();
+        assert_eq!(missing, (num_in_memory_pool as u32..unconfirmed.len() as u32).collect::<Vec<_>>());
+
+        // Fill the missing transactions, as a peer would.
+        let transactions = missing.iter().map(|position| unconfirmed[*position as usize].1.clone()).collect();
+        partial.fill(&missing, transactions).unwrap();
+        assert!(partial.missing_positions().is_empty());
+
+        // Check that the block is reconstructed.
+        assert_eq!(partial.into_block().unwrap(), block);
+    }
+
+    #[test]
+    fn test_reconstruction_with_no_overlap() {
+        check_reconstruction(0);
+    }
+
+    #[test]
+    fn test_reconstruction_with_half_overlap() {
+        check_reconstruction(50);
+    }
+
+    #[test]
+    fn test_reconstruction_with_full_overlap() {
+        check_reconstruction(100);
+    }
+
+    #[test]
+    fn test_missing_transactions_are_rejected() {
+        let rng = &mut TestRng::default();
+        let block = sample_genesis_block(rng);
+        let compact = CompactBlock::new(&block, rng.gen());
+
+        // Check that the block cannot be reconstructed without its transactions.
+        let partial = compact.to_partial_block(vec![]).unwrap();
+        assert!(partial.into_block().is_err());
+    }
+
+    #[test]
+    fn test_duplicate_short_ids_are_a_collision() {
+        let rng = &mut TestRng::default();
+        let block = sample_genesis_block(rng);
+        let mut compact = CompactBlock::new(&block, rng.gen());
+
+        // Duplicate the first short ID in the compact block.
+        let duplicate = compact.transactions[0].clone();
+        compact.transactions.push(duplicate);
+        // Check that the collision is detected.
+        assert!(compact.to_partial_block(sample_unconfirmed(&block)).is_err());
+    }
+
+    #[test]
+    fn test_memory_pool_short_id_collision() {
+        let rng = &mut TestRng::default();
+        let block = sample_genesis_block(rng);
+        let compact = CompactBlock::new(&block, rng.gen());
+        let unconfirmed = sample_unconfirmed(&block);
+        let (transaction_id, transaction) = unconfirmed[0].clone();
+        let CompactTransaction::Accepted { short_id, .. } = compact.transactions[0] else {
+            panic!("Expected a short ID")
+        };
+
+        // Check that the same transaction matching twice is not a collision.
+        let duplicates = vec![(short_id, transaction_id, transaction.clone()); 2];
+        assert!(compact.match_short_ids(duplicates).is_ok());
+
+        // Check that two distinct transactions matching the same short ID is a collision.
+        let colliding =
+            vec![(short_id, transaction_id, transaction.clone()), (short_id, Field::rand(rng).into(), transaction)];
+        assert!(compact.match_short_ids(colliding).is_err());
+    }
+
+    #[test]
+    fn test_undetected_collision_is_rejected() {
+        let rng = &mut TestRng::default();
+        let block = sample_genesis_block(rng);
+        let compact = CompactBlock::new(&block, rng.gen());
+        let unconfirmed = sample_unconfirmed(&block);
+        let CompactTransaction::Accepted { short_id, .. } = compact.transactions[0] else {
+            panic!("Expected a short ID")
+        };
+
+        // Prepare a transaction that is not in the block, but whose (colliding) short ID matches the first position.
+        let other_block = sample_genesis_block(&mut TestRng::fixed(rng.gen()));
+        let (other_id, other_transaction) = sample_unconfirmed(&other_block)[0].clone();
+        let mut partial = compact.match_short_ids(vec![(short_id, other_id, other_transaction.clone())]).unwrap();
+        let missing = partial.missing_positions();
+        let transactions = missing.iter().map(|position| unconfirmed[*position as usize].1.clone()).collect();
+        partial.fill(&missing, transactions).unwrap();
+
+        // Check that the reconstruction fails, so that the full block is requested instead.
+        assert!(partial.into_block().is_err());
+
+        // Check that a peer cannot fill a position with a transaction that does not match its short ID.
+        let mut partial = compact.to_partial_block(vec![]).unwrap();
+        assert!(partial.fill(&[0], vec![other_transaction]).is_err());
+    }
+
+    #[test]
+    fn test_forged_entry_counts_are_rejected() {
+        let rng = &mut TestRng::default();
+        let block = sample_genesis_block(rng);
+        let compact = CompactBlock::new(&block, rng.gen());
+
+        // Serialize the compact block up to its first list.
+        let mut bytes = vec![];
+        compact.nonce.write_le(&mut bytes).unwrap();
+        compact.block_hash.write_le(&mut bytes).unwrap();
+        compact.previous_hash.write_le(&mut bytes).unwrap();
+        compact.header.write_le(&mut bytes).unwrap();
+        compact.authority.write_le(&mut bytes).unwrap();
+        compact.ratifications.write_le(&mut bytes).unwrap();
+        compact.solutions.write_le(&mut bytes).unwrap();
+
+        // Returns the error of reading a compact block whose next list is prefixed by the given number of entries.
+        let forge = |bytes: &[u8], num_entries: u32| {
+            let mut forged = bytes.to_vec();
+            forged.extend_from_slice(&num_entries.to_le_bytes());
+            CompactBlock::<CurrentNetwork>::read_le(&forged[..]).unwrap_err().to_string()
+        };
+
+        // Check that each list rejects a count above its maximum, and a count that is not followed by its entries.
+        assert!(forge(&bytes, u32::MAX).contains("Too many aborted solution IDs"));
+        assert!(!forge(&bytes, MAXIMUM_NUMBER_OF_ABORTED_SOLUTIONS).contains("Too many"));
+        write_entries(&compact.aborted_solution_ids, &mut bytes).unwrap();
+        assert!(forge(&bytes, u32::MAX).contains("Too many transactions"));
+        assert!(!forge(&bytes, MAXIMUM_NUMBER_OF_TRANSACTIONS).contains("Too many"));
+        write_entries(&compact.transactions, &mut bytes).unwrap();
+        assert!(forge(&bytes, u32::MAX).contains("Too many aborted transaction IDs"));
+        assert!(!forge(&bytes, MAXIMUM_NUMBER_OF_TRANSACTIONS).contains("Too many"));
+
+        // Check that the finalize operations of an accepted transaction reject a count above their maximum.
+        let mut bytes = vec![0u8];
+        bytes.extend_from_slice(&rng.gen::<u64>().to_le_bytes());
+        bytes.extend_from_slice(&0u32.to_le_bytes());
+        bytes.push(0u8);
+        bytes.extend_from_slice(&u32::MAX.to_le_bytes());
+        let error = CompactTransaction::<CurrentNetwork>::read_le(&bytes[..]).unwrap_err();
+        assert!(error.to_string().contains("Too many finalize operations"), "{error}");
+    }
+}
+
+#[cfg(test)]
+pub mod prop_tests {
+    use crate::{block_response::prop_tests::any_block, CompactBlock};
+    use snarkvm::console::prelude::{FromBytes, ToBytes};
+
+    use bytes::{Buf, BufMut, BytesMut};
+    use proptest::prelude::{any, BoxedStrategy, Strategy};
+    use test_strategy::proptest;
+
+    type CurrentNetwork = snarkvm::prelude::MainnetV0;
+
+    pub fn any_compact_block() -> BoxedStrategy<CompactBlock<CurrentNetwork>> {
+        (any_block(), any::<u64>()).prop_map(|(block, nonce)| CompactBlock::new(&block, nonce)).boxed()
+    }
+
+    #[proptest]
+    fn compact_block_roundtrip(#[strategy(any_compact_block())] original: CompactBlock<CurrentNetwork>) {
+        let mut buf = BytesMut::default().writer();
+        CompactBlock::write_le(&original, &mut buf).unwrap();
+
+        let deserialized: CompactBlock<CurrentNetwork> = CompactBlock::read_le(buf.into_inner().reader()).unwrap();
+        assert_eq!(original, deserialized);
+    }
+}
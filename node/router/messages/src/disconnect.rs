@@ -50,7 +50,7 @@ impl FromBytes for Disconnect {
 }
 
 #[cfg(test)]
-mod tests {
+pub mod tests {
     use crate::{Disconnect, DisconnectReason};
     use snarkvm::{
         console::prelude::{FromBytes, ToBytes},
@@ -58,6 +58,45 @@ mod tests {
     };
 
     use bytes::{Buf, BufMut, BytesMut};
+    use proptest::{
+        prelude::{any, prop_oneof, BoxedStrategy, Strategy},
+        sample::select,
+    };
+    use test_strategy::proptest;
+
+    pub fn any_disconnect_reason() -> BoxedStrategy<DisconnectReason> {
+        prop_oneof![
+            select(vec![
+                DisconnectReason::ExceededForkRange,
+                DisconnectReason::InvalidChallengeResponse,
+                DisconnectReason::InvalidForkDepth,
+                DisconnectReason::INeedToSyncFirst,
+                DisconnectReason::NoReasonGiven,
+                DisconnectReason::ProtocolViolation,
+                DisconnectReason::OutdatedClientVersion,
+                DisconnectReason::PeerHasDisconnected,
+                DisconnectReason::PeerRefresh,
+                DisconnectReason::ShuttingDown,
+                DisconnectReason::SyncComplete,
+                DisconnectReason::TooManyFailures,
+                DisconnectReason::TooManyPeers,
+                DisconnectReason::YouNeedToSyncFirst,
+            ]),
+            any::<u16>().prop_map(DisconnectReason::YourPortIsClosed),
+        ]
+        .boxed()
+    }
+
+    pub fn any_disconnect() -> BoxedStrategy<Disconnect> {
+        any_disconnect_reason().prop_map(Disconnect::from).boxed()
+    }
+
+    #[proptest]
+    fn disconnect_prop_roundtrip(#[strategy(any_disconnect())] original: Disconnect) {
+        let mut buf = BytesMut::default().writer();
+        original.write_le(&mut buf).unwrap();
+        assert_eq!(original, Disconnect::read_le(buf.into_inner().reader()).unwrap());
+    }
 
     #[test]
     fn disconnect_roundtrip() {
@@ -0,0 +1,87 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+use crate::compact_block::{read_entries, write_entries, MAXIMUM_NUMBER_OF_TRANSACTIONS};
+
+use snarkvm::prelude::{FromBytes, ToBytes};
+
+use std::borrow::Cow;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GetBlockTransactions<N: Network> {
+    /// The hash of the announced block.
+    pub block_hash: N::BlockHash,
+    /// The positions of the requested transactions in the compact block.
+    pub positions: Vec<u32>,
+}
+
+impl<N: Network> MessageTrait for GetBlockTransactions<N> {
+    /// Returns the message name.
+    #[inline]
+    fn name(&self) -> Cow<'static, str> {
+        "GetBlockTransactions".into()
+    }
+}
+
+impl<N: Network> ToBytes for GetBlockTransactions<N> {
+    fn write_le<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        self.block_hash.write_le(&mut writer)?;
+        write_entries(&self.positions, &mut writer)
+    }
+}
+
+impl<N: Network> FromBytes for GetBlockTransactions<N> {
+    fn read_le<R: io::Read>(mut reader: R) -> io::Result<Self> {
+        let block_hash = N::BlockHash::read_le(&mut reader)?;
+        let positions = read_entries(&mut reader, MAXIMUM_NUMBER_OF_TRANSACTIONS, "transaction positions")?;
+        Ok(Self { block_hash, positions })
+    }
+}
+
+#[cfg(test)]
+pub mod prop_tests {
+    use crate::GetBlockTransactions;
+    use snarkvm::{
+        console::prelude::{FromBytes, ToBytes},
+        prelude::{Rng, TestRng},
+    };
+
+    use bytes::{Buf, BufMut, BytesMut};
+    use proptest::{
+        collection::vec,
+        prelude::{any, BoxedStrategy, Strategy},
+    };
+    use test_strategy::proptest;
+
+    type CurrentNetwork = snarkvm::prelude::MainnetV0;
+
+    pub fn any_get_block_transactions() -> BoxedStrategy<GetBlockTransactions<CurrentNetwork>> {
+        (any::<u64>(), vec(any::<u32>(), 0..64))
+            .prop_map(|(seed, positions)| GetBlockTransactions { block_hash: TestRng::fixed(seed).gen(), positions })
+            .boxed()
+    }
+
+    #[proptest]
+    fn get_block_transactions_roundtrip(
+        #[strategy(any_get_block_transactions())] original: GetBlockTransactions<CurrentNetwork>,
+    ) {
+        let mut buf = BytesMut::default().writer();
+        GetBlockTransactions::write_le(&original, &mut buf).unwrap();
+
+        let deserialized: GetBlockTransactions<CurrentNetwork> =
+            GetBlockTransactions::read_le(buf.into_inner().reader()).unwrap();
+        assert_eq!(original, deserialized);
+    }
+}
@@ -0,0 +1,208 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::NodeType;
+use snarkvm::prelude::{FromBytes, ToBytes};
+
+use std::io;
+
+/// The set of optional protocol features a node supports, exchanged in the handshake.
+///
+/// Note: New messages and fields are gated on a capability, rather than on a new message version,
+/// so that the nodes that do not support them remain able to connect.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Capabilities(u32);
+
+impl Capabilities {
+    /// The bit indicating the node accepts `BlockByHashRequest` messages.
+    pub const BLOCK_BY_HASH: u32 = 1 << 5;
+    /// The bit indicating the node accepts timestamps in `Ping` and `Pong` messages.
+    pub const CLOCK_SYNC: u32 = 1 << 7;
+    /// The bit indicating the node accepts `CompactBlock` announcements.
+    pub const COMPACT_BLOCKS: u32 = 1 << 0;
+    /// The bit indicating the node accepts compressed message payloads.
+    pub const COMPRESSION: u32 = 1 << 2;
+    /// The bit indicating the `ChallengeRequest` carries the external address of the node.
+    pub const EXTERNAL_ADDRESS: u32 = 1 << 3;
+    /// The bit indicating the node accepts last-seen timestamps and services in `PeerResponse` messages.
+    pub const PEER_METADATA: u32 = 1 << 4;
+    /// The bit indicating the node accepts `PropagationSample` messages.
+    pub const PROPAGATION_SAMPLE: u32 = 1 << 8;
+    /// The capabilities supported by every node type.
+    const PROTOCOL: u32 =
+        Self::PEER_METADATA | Self::BLOCK_BY_HASH | Self::REJECT | Self::CLOCK_SYNC | Self::PROPAGATION_SAMPLE;
+    /// The bit indicating the node accepts `Reject` messages.
+    pub const REJECT: u32 = 1 << 6;
+    /// The bit indicating the node serves the REST API.
+    pub const REST: u32 = 1 << 1;
+
+    /// Initializes a new set of capabilities from the given bits.
+    pub const fn new(bits: u32) -> Self {
+        Self(bits)
+    }
+
+    /// Returns the capabilities supported by the given node type.
+    pub const fn for_node_type(node_type: NodeType) -> Self {
+        match node_type {
+            // Clients and archives advance their ledger from block announcements.
+            NodeType::Client | NodeType::Archive => Self(Self::PROTOCOL | Self::COMPACT_BLOCKS),
+            // Provers do not maintain a ledger, and validators advance their ledger through the BFT.
+            NodeType::Prover | NodeType::Validator => Self(Self::PROTOCOL),
+        }
+    }
+
+    /// Returns the capabilities with the REST bit set or cleared.
+    pub const fn with_rest(self, is_enabled: bool) -> Self {
+        match is_enabled {
+            true => Self(self.0 | Self::REST),
+            false => Self(self.0 & !Self::REST),
+        }
+    }
+
+    /// Returns the capabilities with the compression bit set or cleared.
+    pub const fn with_compression(self, is_enabled: bool) -> Self {
+        match is_enabled {
+            true => Self(self.0 | Self::COMPRESSION),
+            false => Self(self.0 & !Self::COMPRESSION),
+        }
+    }
+
+    /// Returns the capabilities with the external address bit set or cleared.
+    pub const fn with_external_address(self, is_enabled: bool) -> Self {
+        match is_enabled {
+            true => Self(self.0 | Self::EXTERNAL_ADDRESS),
+            false => Self(self.0 & !Self::EXTERNAL_ADDRESS),
+        }
+    }
+
+    /// Returns the bits of the capabilities.
+    pub const fn bits(&self) -> u32 {
+        self.0
+    }
+
+    /// Returns `true` if the node accepts `CompactBlock` announcements.
+    pub const fn supports_compact_blocks(&self) -> bool {
+        self.0 & Self::COMPACT_BLOCKS != 0
+    }
+
+    /// Returns `true` if the node serves the REST API.
+    pub const fn supports_rest(&self) -> bool {
+        self.0 & Self::REST != 0
+    }
+
+    /// Returns `true` if the node accepts compressed message payloads.
+    pub const fn supports_compression(&self) -> bool {
+        self.0 & Self::COMPRESSION != 0
+    }
+
+    /// Returns `true` if the `ChallengeRequest` carries the external address of the node.
+    pub const fn has_external_address(&self) -> bool {
+        self.0 & Self::EXTERNAL_ADDRESS != 0
+    }
+
+    /// Returns `true` if the node supports the given protocol feature.
+    pub const fn supports(&self, feature: Feature) -> bool {
+        self.0 & feature.bit() != 0
+    }
+}
+
+/// The protocol features that a node only uses with the peers that advertise their capability.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Feature {
+    /// Block announcements as `CompactBlock` messages.
+    CompactBlocks,
+    /// Last-seen timestamps and services in `PeerResponse` messages.
+    PeerMetadata,
+    /// Block requests by block hash.
+    BlockByHash,
+    /// Compressed message payloads.
+    Compression,
+    /// `Reject` messages for dropped gossip.
+    Reject,
+    /// Timestamps in `Ping` and `Pong` messages, to estimate the clock skew.
+    ClockSync,
+    /// `PropagationSample` messages, to measure the propagation delay of transactions.
+    PropagationSample,
+}
+
+impl Feature {
+    /// The list of all features.
+    pub const ALL: [Self; 7] = [
+        Self::CompactBlocks,
+        Self::PeerMetadata,
+        Self::BlockByHash,
+        Self::Compression,
+        Self::Reject,
+        Self::ClockSync,
+        Self::PropagationSample,
+    ];
+
+    /// Returns the capability bit of the feature.
+    pub const fn bit(&self) -> u32 {
+        match self {
+            Self::CompactBlocks => Capabilities::COMPACT_BLOCKS,
+            Self::PeerMetadata => Capabilities::PEER_METADATA,
+            Self::BlockByHash => Capabilities::BLOCK_BY_HASH,
+            Self::Compression => Capabilities::COMPRESSION,
+            Self::Reject => Capabilities::REJECT,
+            Self::ClockSync => Capabilities::CLOCK_SYNC,
+            Self::PropagationSample => Capabilities::PROPAGATION_SAMPLE,
+        }
+    }
+}
+
+impl ToBytes for Capabilities {
+    fn write_le<W: io::Write>(&self, writer: W) -> io::Result<()> {
+        self.0.write_le(writer)
+    }
+}
+
+impl FromBytes for Capabilities {
+    fn read_le<R: io::Read>(reader: R) -> io::Result<Self> {
+        // Note: Unknown bits are retained, so that newer capabilities do not break older nodes.
+        Ok(Self(u32::read_le(reader)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feature_bits() {
+        // Ensure the features do not share a capability bit, nor reuse the bits that are not features.
+        let bits = Feature::ALL.iter().map(Feature::bit).collect::<Vec<_>>();
+        assert!(bits.iter().all(|bit| bit.count_ones() == 1));
+        assert_eq!(bits.iter().fold(0, |all, bit| all | bit).count_ones() as usize, Feature::ALL.len());
+        assert!(bits.iter().all(|bit| bit & (Capabilities::REST | Capabilities::EXTERNAL_ADDRESS) == 0));
+    }
+
+    #[test]
+    fn test_supported_features() {
+        for node_type in [NodeType::Client, NodeType::Prover, NodeType::Validator, NodeType::Archive] {
+            let capabilities = Capabilities::for_node_type(node_type);
+            // Ensure only the nodes that advance their ledger from block announcements accept compact blocks.
+            assert_eq!(capabilities.supports(Feature::CompactBlocks), node_type.is_client() || node_type.is_archive());
+            // Ensure compression is only advertised once it is enabled.
+            assert!(!capabilities.supports(Feature::Compression));
+            assert!(capabilities.with_compression(true).supports(Feature::Compression));
+            // Ensure the remaining features are supported by every node type.
+            let mut features =
+                Feature::ALL.into_iter().filter(|f| ![Feature::CompactBlocks, Feature::Compression].contains(f));
+            assert!(features.all(|feature| capabilities.supports(feature)));
+        }
+        // Ensure a legacy peer, which advertises no capabilities, supports no feature.
+        assert!(Feature::ALL.iter().all(|feature| !Capabilities::default().supports(*feature)));
+    }
+}
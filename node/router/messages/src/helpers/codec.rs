@@ -12,11 +12,18 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::Message;
+use crate::{Capabilities, Feature, Message};
 use snarkvm::prelude::{FromBytes, Network, ToBytes};
 
-use ::bytes::{Buf, BufMut, BytesMut};
+use ::bytes::{Buf, BufMut, Bytes, BytesMut};
 use core::marker::PhantomData;
+use std::{
+    io::{self, Read},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
 use tokio_util::codec::{Decoder, Encoder, LengthDelimitedCodec};
 
 /// The maximum size of a message that can be transmitted during the handshake.
@@ -25,9 +32,29 @@ const MAXIMUM_HANDSHAKE_MESSAGE_SIZE: usize = 1024 * 1024; // 1 MiB
 /// The maximum size of a message that can be transmitted in the network.
 pub(crate) const MAXIMUM_MESSAGE_SIZE: usize = 128 * 1024 * 1024; // 128 MiB
 
+/// The minimum size of a message payload to compress, as compressing smaller payloads costs more than it saves.
+pub const COMPRESSION_THRESHOLD: usize = 8 * 1024; // 8 KiB
+/// The zstd compression level, which favors speed over the compression ratio.
+const COMPRESSION_LEVEL: i32 = 1;
+
+/// The frame header of an uncompressed payload, once compression is negotiated.
+const FRAME_UNCOMPRESSED: u8 = 0;
+/// The frame header of a compressed payload, once compression is negotiated.
+const FRAME_COMPRESSED: u8 = 1;
+
+/// The handler that is invoked when a peer sends a message that cannot be deserialized.
+pub type InvalidMessageHandler = Arc<dyn Fn() + Send + Sync>;
+
 /// The codec used to decode and encode network `Message`s.
+///
+/// If both sides of the connection support compression, each frame starts with a header byte,
+/// which indicates whether the payload is compressed with zstd.
 pub struct MessageCodec<N: Network> {
     codec: LengthDelimitedCodec,
+    /// The number of bytes saved by compression, if compression is negotiated for the connection.
+    compression: Option<Arc<AtomicU64>>,
+    /// The handler that is invoked when the peer sends a message that cannot be deserialized.
+    on_invalid_message: Option<InvalidMessageHandler>,
     _phantom: PhantomData<N>,
 }
 
@@ -37,12 +64,36 @@ impl<N: Network> MessageCodec<N> {
         codec.codec.set_max_frame_length(MAXIMUM_HANDSHAKE_MESSAGE_SIZE);
         codec
     }
+
+    /// Returns the codec for a connection with the given capabilities on both sides,
+    /// which compresses the large payloads and records the bytes saved, if both sides support compression.
+    pub fn negotiated(ours: Capabilities, theirs: Capabilities, bytes_saved: Arc<AtomicU64>) -> Self {
+        let mut codec = Self::default();
+        if ours.supports(Feature::Compression) && theirs.supports(Feature::Compression) {
+            codec.compression = Some(bytes_saved);
+        }
+        codec
+    }
+
+    /// Sets the handler that is invoked when the peer sends a message that cannot be deserialized,
+    /// e.g. a message with a length prefix beyond the bounds of its field.
+    pub fn with_invalid_message_handler(mut self, handler: InvalidMessageHandler) -> Self {
+        self.on_invalid_message = Some(handler);
+        self
+    }
+
+    /// Returns `true` if compression is negotiated for the connection.
+    pub fn is_compressed(&self) -> bool {
+        self.compression.is_some()
+    }
 }
 
 impl<N: Network> Default for MessageCodec<N> {
     fn default() -> Self {
         Self {
             codec: LengthDelimitedCodec::builder().max_frame_length(MAXIMUM_MESSAGE_SIZE).little_endian().new_codec(),
+            compression: None,
+            on_invalid_message: None,
             _phantom: Default::default(),
         }
     }
@@ -60,7 +111,13 @@ impl<N: Network> Encoder<Message<N>> for MessageCodec<N> {
 
         let serialized_message = dst.split_to(dst.len()).freeze();
 
-        self.codec.encode(serialized_message, dst)
+        // If compression is negotiated, prepend the frame header, compressing the payload if it is large enough.
+        let frame = match &self.compression {
+            Some(bytes_saved) => compress_frame(serialized_message, bytes_saved),
+            None => serialized_message,
+        };
+
+        self.codec.encode(frame, dst)
     }
 }
 
@@ -76,13 +133,268 @@ impl<N: Network> Decoder for MessageCodec<N> {
         };
 
         // Convert the bytes to a message, or fail if it is not valid.
-        let reader = bytes.reader();
-        match Message::read_le(reader) {
+        match self.deserialize(bytes.freeze()) {
             Ok(message) => Ok(Some(message)),
             Err(error) => {
                 warn!("Failed to deserialize a message - {}", error);
+                if let Some(on_invalid_message) = &self.on_invalid_message {
+                    on_invalid_message();
+                }
                 Err(std::io::ErrorKind::InvalidData.into())
             }
         }
     }
 }
+
+impl<N: Network> MessageCodec<N> {
+    /// Returns the message in the given frame.
+    fn deserialize(&self, frame: Bytes) -> io::Result<Message<N>> {
+        // If compression is negotiated, strip the frame header, decompressing the payload if it is compressed.
+        let bytes = match &self.compression {
+            Some(bytes_saved) => decompress_frame(frame, bytes_saved)?,
+            None => frame,
+        };
+        Message::read_le(bytes.reader())
+    }
+}
+
+/// Returns the frame for the given payload, compressing the payload if it is large enough and compression saves space.
+fn compress_frame(payload: Bytes, bytes_saved: &AtomicU64) -> Bytes {
+    if payload.len() >= COMPRESSION_THRESHOLD {
+        if let Ok(compressed) = zstd::bulk::compress(&payload, COMPRESSION_LEVEL) {
+            if compressed.len() < payload.len() {
+                bytes_saved.fetch_add((payload.len() - compressed.len()) as u64, Ordering::Relaxed);
+                let mut frame = BytesMut::with_capacity(1 + compressed.len());
+                frame.put_u8(FRAME_COMPRESSED);
+                frame.extend_from_slice(&compressed);
+                return frame.freeze();
+            }
+        }
+    }
+    let mut frame = BytesMut::with_capacity(1 + payload.len());
+    frame.put_u8(FRAME_UNCOMPRESSED);
+    frame.extend_from_slice(&payload);
+    frame.freeze()
+}
+
+/// Returns the payload of the given frame, decompressing the payload if it is compressed.
+fn decompress_frame(mut frame: Bytes, bytes_saved: &AtomicU64) -> io::Result<Bytes> {
+    if frame.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "missing frame header"));
+    }
+    match frame.get_u8() {
+        FRAME_UNCOMPRESSED => Ok(frame),
+        FRAME_COMPRESSED => {
+            let payload = decompress(&frame, MAXIMUM_MESSAGE_SIZE)?;
+            bytes_saved.fetch_add(payload.len().saturating_sub(frame.len()) as u64, Ordering::Relaxed);
+            Ok(payload.into())
+        }
+        header => Err(io::Error::new(io::ErrorKind::InvalidData, format!("invalid frame header {header}"))),
+    }
+}
+
+/// Decompresses the given zstd payload, failing if the decompressed payload exceeds the given maximum size.
+/// Note: The payload is decompressed incrementally, so that a malicious payload cannot exhaust the memory.
+fn decompress(compressed: &[u8], maximum_size: usize) -> io::Result<Vec<u8>> {
+    let mut payload = Vec::with_capacity(compressed.len().saturating_mul(4).min(maximum_size));
+    zstd::stream::read::Decoder::new(compressed)?.take(maximum_size as u64 + 1).read_to_end(&mut payload)?;
+    if payload.len() > maximum_size {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("the decompressed payload exceeds the maximum size of {maximum_size} bytes"),
+        ));
+    }
+    Ok(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BlockRequest, BlockResponse, DataBlocks, Ping};
+    use snarkvm::{
+        ledger::{ledger_test_helpers::sample_genesis_block, narwhal::Data},
+        prelude::TestRng,
+    };
+
+    type CurrentNetwork = snarkvm::prelude::MainnetV0;
+
+    fn compressed_codec() -> MessageCodec<CurrentNetwork> {
+        let capabilities = Capabilities::default().with_compression(true);
+        MessageCodec::negotiated(capabilities, capabilities, Default::default())
+    }
+
+    fn sample_block_response() -> Message<CurrentNetwork> {
+        let block = sample_genesis_block(&mut TestRng::default());
+        let blocks = DataBlocks(vec![block; DataBlocks::<CurrentNetwork>::MAXIMUM_NUMBER_OF_BLOCKS as usize]);
+        Message::BlockResponse(BlockResponse {
+            request: BlockRequest { start_height: 0, end_height: blocks.0.len() as u32 },
+            blocks: Data::Object(blocks),
+        })
+    }
+
+    fn roundtrip(
+        encoder: &mut MessageCodec<CurrentNetwork>,
+        decoder: &mut MessageCodec<CurrentNetwork>,
+        message: Message<CurrentNetwork>,
+    ) -> (Message<CurrentNetwork>, usize) {
+        let mut buffer = BytesMut::new();
+        encoder.encode(message, &mut buffer).unwrap();
+        let frame_length = buffer.len();
+        (decoder.decode(&mut buffer).unwrap().unwrap(), frame_length)
+    }
+
+    #[test]
+    fn test_negotiated_compression() {
+        let bytes_saved = Arc::new(AtomicU64::new(0));
+        let with_compression = Capabilities::default().with_compression(true);
+        let without_compression = Capabilities::default();
+
+        let negotiated = |ours, theirs| {
+            MessageCodec::<CurrentNetwork>::negotiated(ours, theirs, bytes_saved.clone()).is_compressed()
+        };
+
+        // Ensure compression is only negotiated if both sides support it.
+        assert!(negotiated(with_compression, with_compression));
+        assert!(!negotiated(with_compression, without_compression));
+        assert!(!negotiated(without_compression, with_compression));
+    }
+
+    #[test]
+    fn test_mixed_capability_peers() {
+        let message = sample_block_response();
+        let expected = message.to_bytes_le().unwrap();
+
+        // Ensure a peer with compression talks to a peer without compression using the uncompressed frames.
+        let bytes_saved = Arc::new(AtomicU64::new(0));
+        let mut encoder = MessageCodec::<CurrentNetwork>::negotiated(
+            Capabilities::default().with_compression(true),
+            Capabilities::default(),
+            bytes_saved.clone(),
+        );
+        let (decoded, _) = roundtrip(&mut encoder, &mut MessageCodec::default(), message);
+        assert_eq!(decoded.to_bytes_le().unwrap(), expected);
+        assert_eq!(bytes_saved.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn test_compressed_block_roundtrip() {
+        let message = sample_block_response();
+        let expected = message.to_bytes_le().unwrap();
+
+        // Ensure the block-sized payload is compressed, and decompressed to the same bytes.
+        let (mut encoder, mut decoder) = (compressed_codec(), compressed_codec());
+        let (decoded, frame_length) = roundtrip(&mut encoder, &mut decoder, message);
+        assert_eq!(decoded.to_bytes_le().unwrap(), expected);
+        assert!(frame_length < expected.len());
+
+        // Ensure the bytes saved are recorded on both sides.
+        let saved = encoder.compression.as_ref().unwrap().load(Ordering::Relaxed);
+        assert!(saved > 0);
+        assert_eq!(decoder.compression.as_ref().unwrap().load(Ordering::Relaxed), saved);
+    }
+
+    #[test]
+    fn test_small_message_is_not_compressed() {
+        let message = Message::<CurrentNetwork>::Ping(Ping {
+            version: 0,
+            node_type: crate::NodeType::Client,
+            block_locators: None,
+            timestamp: None,
+        });
+        let expected = message.to_bytes_le().unwrap();
+        assert!(expected.len() < COMPRESSION_THRESHOLD);
+
+        // Ensure the small message is sent uncompressed, with the frame header.
+        let mut buffer = BytesMut::new();
+        compressed_codec().encode(message, &mut buffer).unwrap();
+        assert_eq!(buffer[4], FRAME_UNCOMPRESSED);
+        assert_eq!(&buffer[5..], &expected[..]);
+
+        // Ensure the small message is decoded.
+        let decoded = compressed_codec().decode(&mut buffer).unwrap().unwrap();
+        assert_eq!(decoded.to_bytes_le().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_decompression_size_cap() {
+        const MAXIMUM_SIZE: usize = 1024 * 1024;
+
+        // Ensure a payload within the cap is decompressed.
+        let compressed = zstd::bulk::compress(&vec![0u8; MAXIMUM_SIZE], COMPRESSION_LEVEL).unwrap();
+        assert_eq!(decompress(&compressed, MAXIMUM_SIZE).unwrap().len(), MAXIMUM_SIZE);
+
+        // Ensure a payload that expands beyond the cap is rejected.
+        let compressed = zstd::bulk::compress(&vec![0u8; MAXIMUM_SIZE + 1], COMPRESSION_LEVEL).unwrap();
+        assert!(compressed.len() < 1024);
+        assert!(decompress(&compressed, MAXIMUM_SIZE).is_err());
+
+        // Ensure a malformed compressed frame is rejected.
+        let mut frame = BytesMut::new();
+        frame.put_u8(FRAME_COMPRESSED);
+        frame.extend_from_slice(b"not zstd");
+        assert!(decompress_frame(frame.freeze(), &AtomicU64::new(0)).is_err());
+    }
+
+    /// Returns the frame of the given message payload, as written by an uncompressed codec.
+    fn frame(payload: Vec<u8>) -> BytesMut {
+        let mut buffer = BytesMut::new();
+        MessageCodec::<CurrentNetwork>::default().codec.encode(Bytes::from(payload), &mut buffer).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn test_forged_length_prefixes_are_rejected() {
+        // Each payload follows the fixed-size fields of a message with a forged length prefix.
+        let hash = [0u8; 32];
+        let forged_payloads: Vec<(u16, Vec<u8>, &str)> = vec![
+            (1, [[0u8; 8].as_slice(), &u32::MAX.to_le_bytes()].concat(), "blocks is too large"),
+            (7, [[0u8; 4].as_slice(), &[1, 1], &u32::MAX.to_le_bytes()].concat(), "Too many recent block hashes"),
+            (10, [hash.as_slice(), &u32::MAX.to_le_bytes()].concat(), "block header is too large"),
+            (11, [[0u8; 8].as_slice(), &u32::MAX.to_le_bytes()].concat(), "solution is too large"),
+            (12, [hash.as_slice(), &u32::MAX.to_le_bytes()].concat(), "transaction is too large"),
+            (14, [hash.as_slice(), &u32::MAX.to_le_bytes()].concat(), "Too many transaction positions"),
+            (15, [hash.as_slice(), &0u32.to_le_bytes(), &u32::MAX.to_le_bytes()].concat(), "Too many transactions"),
+            (16, vec![u8::MAX], "Too many block hashes"),
+            (17, [[0u8].as_slice(), &u32::MAX.to_le_bytes()].concat(), "blocks by hash is too large"),
+        ];
+        for (id, payload, expected) in forged_payloads {
+            let bytes = [id.to_le_bytes().as_slice(), &payload].concat();
+            let error = Message::<CurrentNetwork>::read_le(&bytes[..]).unwrap_err();
+            assert!(error.to_string().contains(expected), "Message {id}: {error}");
+        }
+    }
+
+    #[test]
+    fn test_forged_length_prefixes_fuzz() {
+        // Count the messages that are reported as invalid.
+        let num_reported = Arc::new(AtomicU64::new(0));
+        let num_reported_ = num_reported.clone();
+        let mut codec = MessageCodec::<CurrentNetwork>::default().with_invalid_message_handler(Arc::new(move || {
+            num_reported_.fetch_add(1, Ordering::Relaxed);
+        }));
+
+        // Place a forged length prefix of every width at every offset of a short payload, for every message ID.
+        // Note: A forged prefix that is trusted for an allocation aborts the test, so completing it bounds the memory use.
+        let prefixes = [vec![u8::MAX], u16::MAX.to_le_bytes().to_vec(), u32::MAX.to_le_bytes().to_vec()];
+        let mut num_invalid = 0;
+        for id in 0..=19u16 {
+            for filler in [0u8, 1u8] {
+                for offset in 0..64 {
+                    for prefix in &prefixes {
+                        let payload = [id.to_le_bytes().as_slice(), &vec![filler; offset], prefix].concat();
+                        match codec.decode(&mut frame(payload)) {
+                            Ok(message) => assert!(message.is_some()),
+                            Err(error) => {
+                                assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+                                num_invalid += 1;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        // Ensure every invalid message was reported, so that the peer is penalized.
+        assert!(num_invalid > 0);
+        assert_eq!(num_reported.load(Ordering::Relaxed), num_invalid);
+    }
+}
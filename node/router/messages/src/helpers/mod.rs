@@ -12,11 +12,24 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod capabilities;
+pub use capabilities::{Capabilities, Feature};
+
 mod codec;
-pub use codec::MessageCodec;
+pub use codec::{InvalidMessageHandler, MessageCodec};
+pub(crate) use codec::MAXIMUM_MESSAGE_SIZE;
 
 mod disconnect;
 pub use disconnect::DisconnectReason;
 
 mod node_type;
 pub use node_type::*;
+
+mod reject;
+pub use reject::RejectReason;
+
+mod services;
+pub use services::PeerServices;
+
+mod version;
+pub use version::*;
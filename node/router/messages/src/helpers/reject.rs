@@ -0,0 +1,81 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkvm::prelude::{error, FromBytes, ToBytes};
+
+use std::{fmt, io};
+
+/// The reason behind the node rejecting an item that was supplied by a peer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RejectReason {
+    /// The item is invalid, and will never be accepted.
+    Invalid,
+    /// The item exceeds the maximum size, and will never be accepted.
+    Oversized,
+    /// The item was produced for a stale epoch, and will never be accepted.
+    StaleEpoch,
+    /// The node is temporarily unable to accept the item (e.g. the memory pool or the disk is full).
+    TemporarilyFull,
+}
+
+impl RejectReason {
+    /// Returns the numeric code of the reason.
+    pub const fn code(&self) -> u8 {
+        match self {
+            Self::Invalid => 0,
+            Self::Oversized => 1,
+            Self::StaleEpoch => 2,
+            Self::TemporarilyFull => 3,
+        }
+    }
+
+    /// Returns the reason for the given numeric code, if the code is known.
+    pub const fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0 => Some(Self::Invalid),
+            1 => Some(Self::Oversized),
+            2 => Some(Self::StaleEpoch),
+            3 => Some(Self::TemporarilyFull),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the item will never be accepted by the peer, and should not be resent to it.
+    pub const fn is_permanent(&self) -> bool {
+        !matches!(self, Self::TemporarilyFull)
+    }
+}
+
+impl fmt::Display for RejectReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Invalid => write!(f, "invalid"),
+            Self::Oversized => write!(f, "oversized"),
+            Self::StaleEpoch => write!(f, "stale epoch"),
+            Self::TemporarilyFull => write!(f, "temporarily full"),
+        }
+    }
+}
+
+impl ToBytes for RejectReason {
+    fn write_le<W: io::Write>(&self, writer: W) -> io::Result<()> {
+        self.code().write_le(writer)
+    }
+}
+
+impl FromBytes for RejectReason {
+    fn read_le<R: io::Read>(reader: R) -> io::Result<Self> {
+        Self::from_code(u8::read_le(reader)?).ok_or_else(|| error("Invalid reject reason"))
+    }
+}
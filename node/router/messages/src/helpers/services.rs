@@ -0,0 +1,129 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::NodeType;
+use snarkvm::prelude::{FromBytes, ToBytes};
+
+use serde::{Deserialize, Serialize};
+use std::io;
+
+/// The mask of the node type bits of `PeerServices`.
+const NODE_TYPE_MASK: u8 =
+    PeerServices::CLIENT | PeerServices::PROVER | PeerServices::VALIDATOR | PeerServices::ARCHIVE;
+
+/// The set of services a peer is known to offer, advertised alongside its address in a `PeerResponse`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PeerServices(u8);
+
+impl PeerServices {
+    /// The bit indicating the peer is an archive, serving the full ledger for bulk block download.
+    pub const ARCHIVE: u8 = 1 << 4;
+    /// The bit indicating the peer is a client.
+    pub const CLIENT: u8 = 1 << 0;
+    /// The bit indicating the peer is a prover.
+    pub const PROVER: u8 = 1 << 1;
+    /// The bit indicating the peer serves the REST API.
+    pub const REST: u8 = 1 << 3;
+    /// The bit indicating the peer is a validator.
+    pub const VALIDATOR: u8 = 1 << 2;
+
+    /// Initializes the services of a peer with the given node type.
+    pub const fn new(node_type: NodeType, has_rest: bool) -> Self {
+        let node_type = match node_type {
+            NodeType::Client => Self::CLIENT,
+            NodeType::Prover => Self::PROVER,
+            NodeType::Validator => Self::VALIDATOR,
+            NodeType::Archive => Self::ARCHIVE,
+        };
+        match has_rest {
+            true => Self(node_type | Self::REST),
+            false => Self(node_type),
+        }
+    }
+
+    /// Initializes a new set of services from the given bits.
+    pub const fn from_bits(bits: u8) -> Self {
+        Self(bits)
+    }
+
+    /// Returns the bits of the services.
+    pub const fn bits(&self) -> u8 {
+        self.0
+    }
+
+    /// Returns `true` if nothing is known about the services of the peer.
+    pub const fn is_unknown(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns the node type of the peer, if it is known.
+    pub const fn node_type(&self) -> Option<NodeType> {
+        match self.0 & NODE_TYPE_MASK {
+            Self::CLIENT => Some(NodeType::Client),
+            Self::PROVER => Some(NodeType::Prover),
+            Self::VALIDATOR => Some(NodeType::Validator),
+            Self::ARCHIVE => Some(NodeType::Archive),
+            // Note: An unset or conflicting node type is treated as unknown.
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the peer serves the REST API.
+    pub const fn has_rest(&self) -> bool {
+        self.0 & Self::REST != 0
+    }
+
+    /// Returns `true` if the peer is an archive.
+    pub const fn is_archive(&self) -> bool {
+        matches!(self.node_type(), Some(NodeType::Archive))
+    }
+}
+
+impl ToBytes for PeerServices {
+    fn write_le<W: io::Write>(&self, writer: W) -> io::Result<()> {
+        self.0.write_le(writer)
+    }
+}
+
+impl FromBytes for PeerServices {
+    fn read_le<R: io::Read>(reader: R) -> io::Result<Self> {
+        // Note: Unknown bits are retained, so that newer services do not break older nodes.
+        Ok(Self(u8::read_le(reader)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_peer_services() {
+        for node_type in [NodeType::Client, NodeType::Prover, NodeType::Validator, NodeType::Archive] {
+            for has_rest in [true, false] {
+                let services = PeerServices::new(node_type, has_rest);
+                assert_eq!(services.node_type(), Some(node_type));
+                assert_eq!(services.has_rest(), has_rest);
+                assert!(!services.is_unknown());
+                assert_eq!(services.is_archive(), node_type.is_archive());
+            }
+        }
+
+        // Check that unset and conflicting node types are unknown.
+        assert!(PeerServices::default().is_unknown());
+        assert_eq!(PeerServices::default().node_type(), None);
+        assert_eq!(PeerServices::from_bits(PeerServices::CLIENT | PeerServices::VALIDATOR).node_type(), None);
+        assert_eq!(PeerServices::from_bits(PeerServices::CLIENT | PeerServices::ARCHIVE).node_type(), None);
+    }
+}
@@ -0,0 +1,131 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+/// The version of the network protocol; it can be incremented in order to force users to update.
+///
+/// Note: The version is only incremented for the changes that cannot be gated on the capabilities
+/// advertised in the handshake, such as the layout of the handshake itself. New messages and fields
+/// are gated on a capability instead, so that they do not cut off the peers that predate them.
+pub const MESSAGE_VERSION: u32 = 15;
+/// The number of versions below the current version on which peers may still connect, with a deprecation warning.
+pub const MESSAGE_VERSION_DEPRECATION_WINDOW: u32 = 1;
+/// The first version that advertises its supported version range and its capabilities in the handshake.
+/// Note: Peers below this version are unable to parse the handshake, and are always refused.
+const FIRST_NEGOTIATED_VERSION: u32 = 15;
+/// The minimum version of the network protocol supported by this node.
+pub const MINIMUM_MESSAGE_VERSION: u32 = {
+    let minimum = MESSAGE_VERSION.saturating_sub(MESSAGE_VERSION_DEPRECATION_WINDOW);
+    if minimum < FIRST_NEGOTIATED_VERSION {
+        FIRST_NEGOTIATED_VERSION
+    } else {
+        minimum
+    }
+};
+
+/// The range of message versions supported by a node, advertised in the handshake.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct VersionRange {
+    /// The minimum supported version.
+    minimum: u32,
+    /// The maximum supported version.
+    maximum: u32,
+}
+
+impl Default for VersionRange {
+    /// Returns the range of versions supported by this node.
+    fn default() -> Self {
+        Self::supported()
+    }
+}
+
+impl VersionRange {
+    /// Initializes a new version range, from the given minimum to the given maximum version (inclusive).
+    pub const fn new(minimum: u32, maximum: u32) -> Self {
+        Self { minimum, maximum }
+    }
+
+    /// Returns the range of versions supported by this node.
+    pub const fn supported() -> Self {
+        Self::new(MINIMUM_MESSAGE_VERSION, MESSAGE_VERSION)
+    }
+
+    /// Returns the minimum supported version.
+    pub const fn minimum(&self) -> u32 {
+        self.minimum
+    }
+
+    /// Returns the maximum supported version.
+    pub const fn maximum(&self) -> u32 {
+        self.maximum
+    }
+
+    /// Returns `true` if the given version is in the range.
+    pub const fn contains(&self, version: u32) -> bool {
+        self.minimum <= version && version <= self.maximum
+    }
+
+    /// Returns the highest version supported by both ranges, or `None` if the ranges do not overlap.
+    pub fn negotiate(&self, other: &Self) -> Option<u32> {
+        let version = self.maximum.min(other.maximum);
+        (self.contains(version) && other.contains(version)).then_some(version)
+    }
+
+    /// Returns `true` if the given version is supported, but deprecated, as it is below the maximum version.
+    pub const fn is_deprecated(&self, version: u32) -> bool {
+        self.contains(version) && version < self.maximum
+    }
+}
+
+impl fmt::Display for VersionRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}, {}]", self.minimum, self.maximum)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_supported_versions() {
+        let supported = VersionRange::supported();
+        assert_eq!(supported.maximum(), MESSAGE_VERSION);
+        assert!(supported.minimum() >= FIRST_NEGOTIATED_VERSION);
+        assert!(supported.minimum() <= supported.maximum());
+        // Ensure the deprecation window never exceeds the configured number of versions.
+        assert!(supported.maximum() - supported.minimum() <= MESSAGE_VERSION_DEPRECATION_WINDOW);
+    }
+
+    #[test]
+    fn test_negotiate() {
+        let ours = VersionRange::new(22, 23);
+
+        // Ensure the highest common version is negotiated.
+        assert_eq!(ours.negotiate(&VersionRange::new(22, 23)), Some(23));
+        assert_eq!(ours.negotiate(&VersionRange::new(23, 25)), Some(23));
+        assert_eq!(ours.negotiate(&VersionRange::new(20, 22)), Some(22));
+        // Ensure the negotiation is symmetric.
+        assert_eq!(VersionRange::new(20, 22).negotiate(&ours), Some(22));
+        // Ensure ranges that do not overlap fail to negotiate.
+        assert_eq!(ours.negotiate(&VersionRange::new(18, 21)), None);
+        assert_eq!(ours.negotiate(&VersionRange::new(24, 25)), None);
+
+        // Ensure a version below the maximum is deprecated, but supported.
+        assert!(!ours.is_deprecated(23));
+        assert!(ours.is_deprecated(22));
+        assert!(!ours.is_deprecated(21));
+    }
+}
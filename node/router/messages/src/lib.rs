@@ -20,26 +20,41 @@ extern crate tracing;
 pub mod helpers;
 pub use helpers::*;
 
+mod block_by_hash_request;
+pub use block_by_hash_request::BlockByHashRequest;
+
+mod block_by_hash_response;
+pub use block_by_hash_response::{BlockByHashResponse, BlocksByHash};
+
 mod block_request;
 pub use block_request::BlockRequest;
 
 mod block_response;
 pub use block_response::BlockResponse;
 
+mod block_transactions;
+pub use block_transactions::BlockTransactions;
+
 mod challenge_request;
 pub use challenge_request::ChallengeRequest;
 
 mod challenge_response;
 pub use challenge_response::ChallengeResponse;
 
+mod compact_block;
+pub use compact_block::{short_id, CompactBlock, CompactTransaction, PartialBlock};
+
 mod disconnect;
 pub use disconnect::Disconnect;
 
+mod get_block_transactions;
+pub use get_block_transactions::GetBlockTransactions;
+
 mod peer_request;
 pub use peer_request::PeerRequest;
 
 mod peer_response;
-pub use peer_response::PeerResponse;
+pub use peer_response::{AdvertisedPeer, PeerResponse};
 
 mod ping;
 pub use ping::Ping;
@@ -47,19 +62,28 @@ pub use ping::Ping;
 mod pong;
 pub use pong::Pong;
 
+mod propagation_sample;
+pub use propagation_sample::PropagationSample;
+
 mod puzzle_request;
 pub use puzzle_request::PuzzleRequest;
 
 mod puzzle_response;
 pub use puzzle_response::PuzzleResponse;
 
+mod reject;
+pub use reject::{Reject, RejectedItem};
+
 mod unconfirmed_solution;
 pub use unconfirmed_solution::UnconfirmedSolution;
 
 mod unconfirmed_transaction;
 pub use unconfirmed_transaction::UnconfirmedTransaction;
 
-pub use snarkos_node_bft_events::DataBlocks;
+#[cfg(any(test, feature = "test"))]
+pub mod test_vectors;
+
+pub use snarkos_node_bft_events::{deserialize_data, ensure_data_size, read_data, DataBlocks, DataBytes};
 
 use snarkos_node_sync_locators::BlockLocators;
 use snarkvm::prelude::{
@@ -88,17 +112,24 @@ pub trait MessageTrait: ToBytes + FromBytes {
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Message<N: Network> {
+    BlockByHashRequest(BlockByHashRequest<N>),
+    BlockByHashResponse(BlockByHashResponse<N>),
     BlockRequest(BlockRequest),
     BlockResponse(BlockResponse<N>),
+    BlockTransactions(BlockTransactions<N>),
     ChallengeRequest(ChallengeRequest<N>),
     ChallengeResponse(ChallengeResponse<N>),
+    CompactBlock(CompactBlock<N>),
     Disconnect(Disconnect),
+    GetBlockTransactions(GetBlockTransactions<N>),
     PeerRequest(PeerRequest),
     PeerResponse(PeerResponse),
     Ping(Ping<N>),
     Pong(Pong),
+    PropagationSample(PropagationSample<N>),
     PuzzleRequest(PuzzleRequest),
     PuzzleResponse(PuzzleResponse<N>),
+    Reject(Reject<N>),
     UnconfirmedSolution(UnconfirmedSolution<N>),
     UnconfirmedTransaction(UnconfirmedTransaction<N>),
 }
@@ -111,23 +142,30 @@ impl<N: Network> From<DisconnectReason> for Message<N> {
 
 impl<N: Network> Message<N> {
     /// The version of the network protocol; it can be incremented in order to force users to update.
-    pub const VERSION: u32 = 14;
+    pub const VERSION: u32 = MESSAGE_VERSION;
 
     /// Returns the message name.
     #[inline]
     pub fn name(&self) -> Cow<'static, str> {
         match self {
+            Self::BlockByHashRequest(message) => message.name(),
+            Self::BlockByHashResponse(message) => message.name(),
             Self::BlockRequest(message) => message.name(),
             Self::BlockResponse(message) => message.name(),
+            Self::BlockTransactions(message) => message.name(),
             Self::ChallengeRequest(message) => message.name(),
             Self::ChallengeResponse(message) => message.name(),
+            Self::CompactBlock(message) => message.name(),
             Self::Disconnect(message) => message.name(),
+            Self::GetBlockTransactions(message) => message.name(),
             Self::PeerRequest(message) => message.name(),
             Self::PeerResponse(message) => message.name(),
             Self::Ping(message) => message.name(),
             Self::Pong(message) => message.name(),
+            Self::PropagationSample(message) => message.name(),
             Self::PuzzleRequest(message) => message.name(),
             Self::PuzzleResponse(message) => message.name(),
+            Self::Reject(message) => message.name(),
             Self::UnconfirmedSolution(message) => message.name(),
             Self::UnconfirmedTransaction(message) => message.name(),
         }
@@ -150,6 +188,30 @@ impl<N: Network> Message<N> {
             Self::PuzzleResponse(..) => 10,
             Self::UnconfirmedSolution(..) => 11,
             Self::UnconfirmedTransaction(..) => 12,
+            Self::CompactBlock(..) => 13,
+            Self::GetBlockTransactions(..) => 14,
+            Self::BlockTransactions(..) => 15,
+            Self::BlockByHashRequest(..) => 16,
+            Self::BlockByHashResponse(..) => 17,
+            Self::Reject(..) => 18,
+            Self::PropagationSample(..) => 19,
+        }
+    }
+
+    /// Returns the message with its object payloads in their serialized form.
+    /// This ensures a message that is propagated to many peers is only serialized once.
+    pub fn into_buffered(self) -> Self {
+        match self {
+            Self::UnconfirmedSolution(UnconfirmedSolution { solution_id, solution }) => {
+                Self::UnconfirmedSolution(UnconfirmedSolution { solution_id, solution: solution.into_buffer() })
+            }
+            Self::UnconfirmedTransaction(UnconfirmedTransaction { transaction_id, transaction }) => {
+                Self::UnconfirmedTransaction(UnconfirmedTransaction {
+                    transaction_id,
+                    transaction: transaction.into_buffer(),
+                })
+            }
+            message => message,
         }
     }
 }
@@ -159,17 +221,24 @@ impl<N: Network> ToBytes for Message<N> {
         self.id().write_le(&mut writer)?;
 
         match self {
+            Self::BlockByHashRequest(message) => message.write_le(writer),
+            Self::BlockByHashResponse(message) => message.write_le(writer),
             Self::BlockRequest(message) => message.write_le(writer),
             Self::BlockResponse(message) => message.write_le(writer),
+            Self::BlockTransactions(message) => message.write_le(writer),
             Self::ChallengeRequest(message) => message.write_le(writer),
             Self::ChallengeResponse(message) => message.write_le(writer),
+            Self::CompactBlock(message) => message.write_le(writer),
             Self::Disconnect(message) => message.write_le(writer),
+            Self::GetBlockTransactions(message) => message.write_le(writer),
             Self::PeerRequest(message) => message.write_le(writer),
             Self::PeerResponse(message) => message.write_le(writer),
             Self::Ping(message) => message.write_le(writer),
             Self::Pong(message) => message.write_le(writer),
+            Self::PropagationSample(message) => message.write_le(writer),
             Self::PuzzleRequest(message) => message.write_le(writer),
             Self::PuzzleResponse(message) => message.write_le(writer),
+            Self::Reject(message) => message.write_le(writer),
             Self::UnconfirmedSolution(message) => message.write_le(writer),
             Self::UnconfirmedTransaction(message) => message.write_le(writer),
         }
@@ -198,7 +267,14 @@ impl<N: Network> FromBytes for Message<N> {
             10 => Self::PuzzleResponse(PuzzleResponse::read_le(&mut reader)?),
             11 => Self::UnconfirmedSolution(UnconfirmedSolution::read_le(&mut reader)?),
             12 => Self::UnconfirmedTransaction(UnconfirmedTransaction::read_le(&mut reader)?),
-            13.. => return Err(error("Unknown message ID {id}")),
+            13 => Self::CompactBlock(CompactBlock::read_le(&mut reader)?),
+            14 => Self::GetBlockTransactions(GetBlockTransactions::read_le(&mut reader)?),
+            15 => Self::BlockTransactions(BlockTransactions::read_le(&mut reader)?),
+            16 => Self::BlockByHashRequest(BlockByHashRequest::read_le(&mut reader)?),
+            17 => Self::BlockByHashResponse(BlockByHashResponse::read_le(&mut reader)?),
+            18 => Self::Reject(Reject::read_le(&mut reader)?),
+            19 => Self::PropagationSample(PropagationSample::read_le(&mut reader)?),
+            20.. => return Err(error("Unknown message ID {id}")),
         };
 
         // Ensure that there are no "dangling" bytes.
@@ -209,3 +285,124 @@ impl<N: Network> FromBytes for Message<N> {
         Ok(message)
     }
 }
+
+#[cfg(test)]
+mod prop_tests {
+    use super::*;
+    use crate::{
+        block_by_hash_request::prop_tests::any_block_by_hash_request,
+        block_by_hash_response::prop_tests::any_block_by_hash_response,
+        block_request::prop_tests::any_block_request,
+        block_response::prop_tests::any_block_response,
+        block_transactions::prop_tests::any_block_transactions,
+        challenge_request::prop_tests::any_challenge_request,
+        challenge_response::prop_tests::any_challenge_response,
+        compact_block::{prop_tests::any_compact_block, MAXIMUM_NUMBER_OF_TRANSACTIONS},
+        disconnect::tests::any_disconnect,
+        get_block_transactions::prop_tests::any_get_block_transactions,
+        peer_request::tests::any_peer_request,
+        peer_response::prop_tests::{any_advertised_peer, any_peer_response},
+        ping::prop_tests::any_ping,
+        pong::tests::any_pong,
+        propagation_sample::prop_tests::any_propagation_sample,
+        puzzle_request::tests::any_puzzle_request,
+        puzzle_response::prop_tests::any_puzzle_response,
+        reject::prop_tests::{any_reject, any_reject_reason, any_rejected_item},
+        unconfirmed_solution::prop_tests::any_unconfirmed_solution,
+        unconfirmed_transaction::prop_tests::any_unconfirmed_transaction,
+    };
+    use snarkvm::prelude::{Rng, TestRng};
+
+    use proptest::{
+        collection::vec,
+        prelude::{any, BoxedStrategy, ProptestConfig, Strategy},
+        strategy::Union,
+    };
+    use test_strategy::proptest;
+
+    type CurrentNetwork = snarkvm::prelude::MainnetV0;
+
+    /// Returns a strategy for every variant of the message.
+    pub fn any_message() -> BoxedStrategy<Message<CurrentNetwork>> {
+        Union::new([
+            any_block_by_hash_request().prop_map(Message::BlockByHashRequest).boxed(),
+            any_block_by_hash_response().prop_map(Message::BlockByHashResponse).boxed(),
+            any_block_request().prop_map(Message::BlockRequest).boxed(),
+            any_block_response().prop_map(Message::BlockResponse).boxed(),
+            any_block_transactions().prop_map(Message::BlockTransactions).boxed(),
+            any_challenge_request().prop_map(Message::ChallengeRequest).boxed(),
+            any_challenge_response().prop_map(Message::ChallengeResponse).boxed(),
+            any_compact_block().prop_map(Message::CompactBlock).boxed(),
+            any_disconnect().prop_map(Message::Disconnect).boxed(),
+            any_get_block_transactions().prop_map(Message::GetBlockTransactions).boxed(),
+            any_peer_request().prop_map(Message::PeerRequest).boxed(),
+            any_peer_response().prop_map(Message::PeerResponse).boxed(),
+            any_ping().prop_map(Message::Ping).boxed(),
+            any_pong().prop_map(Message::Pong).boxed(),
+            any_propagation_sample().prop_map(Message::PropagationSample).boxed(),
+            any_puzzle_request().prop_map(Message::PuzzleRequest).boxed(),
+            any_puzzle_response().prop_map(Message::PuzzleResponse).boxed(),
+            any_reject().prop_map(Message::Reject).boxed(),
+            any_unconfirmed_solution().prop_map(Message::UnconfirmedSolution).boxed(),
+            any_unconfirmed_transaction().prop_map(Message::UnconfirmedTransaction).boxed(),
+        ])
+        .boxed()
+    }
+
+    /// Returns a strategy for the messages whose collections are at their maximum size.
+    pub fn any_message_at_capacity() -> BoxedStrategy<Message<CurrentNetwork>> {
+        let max_hashes = BlockByHashRequest::<CurrentNetwork>::MAXIMUM_NUMBER_OF_HASHES;
+        let max_message_length = Reject::<CurrentNetwork>::MAXIMUM_MESSAGE_LENGTH;
+        Union::new([
+            any::<u64>()
+                .prop_map(move |seed| {
+                    let rng = &mut TestRng::fixed(seed);
+                    let block_hashes = (0..max_hashes).map(|_| rng.gen()).collect();
+                    Message::BlockByHashRequest(BlockByHashRequest { block_hashes })
+                })
+                .boxed(),
+            vec(any_advertised_peer(), PeerResponse::MAXIMUM_NUMBER_OF_PEERS)
+                .prop_map(|peers| Message::PeerResponse(PeerResponse { peers }))
+                .boxed(),
+            any::<u64>()
+                .prop_map(|seed| {
+                    Message::GetBlockTransactions(GetBlockTransactions {
+                        block_hash: TestRng::fixed(seed).gen(),
+                        positions: (0..MAXIMUM_NUMBER_OF_TRANSACTIONS).collect(),
+                    })
+                })
+                .boxed(),
+            (any_rejected_item(), any_reject_reason(), format!("[a-z]{{{max_message_length}}}"))
+                .prop_map(|(item, reason, message)| Message::Reject(Reject::new(item, reason, &message)))
+                .boxed(),
+            any_propagation_sample()
+                .prop_map(|sample| {
+                    Message::PropagationSample(PropagationSample {
+                        hops: PropagationSample::<CurrentNetwork>::MAXIMUM_HOPS,
+                        ..sample
+                    })
+                })
+                .boxed(),
+        ])
+        .boxed()
+    }
+
+    /// Checks that the given message is deserialized from its bytes, and serialized back into the same bytes.
+    fn check_roundtrip(message: &Message<CurrentNetwork>) {
+        let bytes = message.to_bytes_le().unwrap();
+        let decoded = Message::<CurrentNetwork>::read_le(&bytes[..]).unwrap();
+        assert_eq!(decoded.id(), message.id());
+        // Note: The messages with lazily deserialized payloads are compared by their bytes.
+        assert_eq!(decoded.to_bytes_le().unwrap(), bytes);
+    }
+
+    #[proptest]
+    fn message_roundtrip(#[strategy(any_message())] message: Message<CurrentNetwork>) {
+        check_roundtrip(&message);
+    }
+
+    #[proptest(ProptestConfig::with_cases(16))]
+    fn message_at_capacity_roundtrip(#[strategy(any_message_at_capacity())] message: Message<CurrentNetwork>) {
+        check_roundtrip(&message);
+    }
+}
@@ -47,6 +47,11 @@ pub mod tests {
     use snarkvm::utilities::{FromBytes, ToBytes};
 
     use bytes::{Buf, BufMut, BytesMut};
+    use proptest::prelude::{BoxedStrategy, Just, Strategy};
+
+    pub fn any_peer_request() -> BoxedStrategy<PeerRequest> {
+        Just(PeerRequest).boxed()
+    }
 
     #[test]
     fn peer_request_roundtrip() {
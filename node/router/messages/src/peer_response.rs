@@ -18,9 +18,54 @@ use snarkvm::prelude::{FromBytes, ToBytes};
 
 use std::borrow::Cow;
 
+/// A peer address advertised in a `PeerResponse`, along with what the sender knows about it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct AdvertisedPeer {
+    /// The listener address of the peer.
+    pub ip: SocketAddr,
+    /// The UNIX timestamp (in seconds) at which the sender last heard from the peer, or `0` if unknown.
+    pub last_seen: u64,
+    /// The services the peer is known to offer.
+    pub services: PeerServices,
+}
+
+impl From<SocketAddr> for AdvertisedPeer {
+    /// Returns an advertised peer with no known metadata.
+    fn from(ip: SocketAddr) -> Self {
+        Self { ip, last_seen: 0, services: PeerServices::default() }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct PeerResponse {
-    pub peers: Vec<SocketAddr>,
+    pub peers: Vec<AdvertisedPeer>,
+}
+
+impl PeerResponse {
+    /// The leading byte that marks a response in the current format.
+    ///
+    /// In the legacy format, the leading byte is the number of peers, which is followed by the first address;
+    /// as an address starts with its IP version (`0` or `1`), the format byte below is never ambiguous.
+    const FORMAT_MARKER: u8 = u8::MAX;
+    /// The format of a response carrying last-seen timestamps and services.
+    const FORMAT_WITH_METADATA: u8 = 2;
+    /// The maximum number of peers in a response, as their number is encoded in a single byte.
+    pub const MAXIMUM_NUMBER_OF_PEERS: usize = u8::MAX as usize;
+
+    /// Returns the advertised peer addresses.
+    pub fn addresses(&self) -> impl Iterator<Item = SocketAddr> + '_ {
+        self.peers.iter().map(|peer| peer.ip)
+    }
+
+    /// Returns the response without the last-seen timestamps and services, for peers that do not support them.
+    pub fn without_metadata(self) -> Self {
+        Self { peers: self.addresses().map(AdvertisedPeer::from).collect() }
+    }
+
+    /// Returns `true` if any advertised peer carries a last-seen timestamp or services.
+    fn has_metadata(&self) -> bool {
+        self.peers.iter().any(|peer| peer.last_seen != 0 || !peer.services.is_unknown())
+    }
 }
 
 impl MessageTrait for PeerResponse {
@@ -34,13 +79,26 @@ impl MessageTrait for PeerResponse {
 impl ToBytes for PeerResponse {
     fn write_le<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
         // Return error if the number of peers exceeds the maximum.
-        if self.peers.len() > u8::MAX as usize {
+        if self.peers.len() > Self::MAXIMUM_NUMBER_OF_PEERS {
             return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Too many peers: {}", self.peers.len())));
         }
 
+        // Note: A response without metadata is written in the legacy format, which every peer is able to read.
+        if !self.has_metadata() {
+            (self.peers.len() as u8).write_le(&mut writer)?;
+            for peer in self.peers.iter() {
+                peer.ip.write_le(&mut writer)?;
+            }
+            return Ok(());
+        }
+
+        Self::FORMAT_MARKER.write_le(&mut writer)?;
+        Self::FORMAT_WITH_METADATA.write_le(&mut writer)?;
         (self.peers.len() as u8).write_le(&mut writer)?;
         for peer in self.peers.iter() {
-            peer.write_le(&mut writer)?;
+            peer.ip.write_le(&mut writer)?;
+            peer.last_seen.write_le(&mut writer)?;
+            peer.services.write_le(&mut writer)?;
         }
         Ok(())
     }
@@ -48,19 +106,44 @@ impl ToBytes for PeerResponse {
 
 impl FromBytes for PeerResponse {
     fn read_le<R: io::Read>(mut reader: R) -> io::Result<Self> {
-        let count = u8::read_le(&mut reader)?;
+        let leading_byte = u8::read_le(&mut reader)?;
+        // Read a response in the current format.
+        if leading_byte == Self::FORMAT_MARKER {
+            let format = u8::read_le(&mut reader)?;
+            if format == Self::FORMAT_WITH_METADATA {
+                let count = u8::read_le(&mut reader)?;
+                let mut peers = Vec::with_capacity(count as usize);
+                for _ in 0..count {
+                    let ip = SocketAddr::read_le(&mut reader)?;
+                    let last_seen = u64::read_le(&mut reader)?;
+                    let services = PeerServices::read_le(&mut reader)?;
+                    peers.push(AdvertisedPeer { ip, last_seen, services });
+                }
+                return Ok(Self { peers });
+            }
+            // Otherwise, this is a legacy response with the maximum number of peers,
+            // so the byte that was read belongs to the first address.
+            return Self::read_legacy(leading_byte, io::Read::chain(&[format][..], reader));
+        }
+        // Read a response in the legacy format, which carries only the addresses.
+        Self::read_legacy(leading_byte, reader)
+    }
+}
+
+impl PeerResponse {
+    /// Reads the given number of addresses of a response in the legacy format.
+    fn read_legacy<R: io::Read>(count: u8, mut reader: R) -> io::Result<Self> {
         let mut peers = Vec::with_capacity(count as usize);
         for _ in 0..count {
-            peers.push(SocketAddr::read_le(&mut reader)?);
+            peers.push(AdvertisedPeer::from(SocketAddr::read_le(&mut reader)?));
         }
-
         Ok(Self { peers })
     }
 }
 
 #[cfg(test)]
 pub mod prop_tests {
-    use crate::PeerResponse;
+    use crate::{AdvertisedPeer, PeerResponse, PeerServices};
     use snarkvm::utilities::{FromBytes, ToBytes};
 
     use bytes::{Buf, BufMut, BytesMut};
@@ -75,8 +158,18 @@ pub mod prop_tests {
         any::<(IpAddr, u16)>().prop_map(|(ip_addr, port)| SocketAddr::new(ip_addr, port)).boxed()
     }
 
-    pub fn any_vec() -> BoxedStrategy<Vec<SocketAddr>> {
-        vec(any_valid_socket_addr(), 0..50).prop_map(|v| v).boxed()
+    pub fn any_advertised_peer() -> BoxedStrategy<AdvertisedPeer> {
+        (any_valid_socket_addr(), any::<u64>(), any::<u8>())
+            .prop_map(|(ip, last_seen, services)| AdvertisedPeer {
+                ip,
+                last_seen,
+                services: PeerServices::from_bits(services),
+            })
+            .boxed()
+    }
+
+    pub fn any_vec() -> BoxedStrategy<Vec<AdvertisedPeer>> {
+        vec(any_advertised_peer(), 0..50).prop_map(|v| v).boxed()
     }
 
     pub fn any_peer_response() -> BoxedStrategy<PeerResponse> {
@@ -90,4 +183,52 @@ pub mod prop_tests {
         let decoded = PeerResponse::read_le(&mut bytes.into_inner().reader()).unwrap();
         assert_eq!(decoded, peer_response);
     }
+
+    /// Returns the given addresses, serialized in the legacy format.
+    fn to_legacy_bytes(peers: &[SocketAddr]) -> Vec<u8> {
+        let mut bytes = vec![peers.len() as u8];
+        for peer in peers {
+            peer.write_le(&mut bytes).unwrap();
+        }
+        bytes
+    }
+
+    #[proptest]
+    fn legacy_peer_response_parses(#[strategy(vec(any_valid_socket_addr(), 0..50))] peers: Vec<SocketAddr>) {
+        let decoded = PeerResponse::read_le(&to_legacy_bytes(&peers)[..]).unwrap();
+        // Check that the addresses are retained, without any metadata.
+        assert_eq!(decoded.addresses().collect::<Vec<_>>(), peers);
+        assert!(decoded.peers.iter().all(|peer| peer.last_seen == 0 && peer.services.is_unknown()));
+    }
+
+    #[test]
+    fn legacy_peer_response_with_maximum_peers_parses() {
+        // Note: The legacy count of 255 coincides with the format marker.
+        for ip in ["1.2.3.4:4130", "[2001:db8::1]:4130"] {
+            let peers = vec![ip.parse::<SocketAddr>().unwrap(); u8::MAX as usize];
+            let decoded = PeerResponse::read_le(&to_legacy_bytes(&peers)[..]).unwrap();
+            assert_eq!(decoded.addresses().collect::<Vec<_>>(), peers);
+        }
+    }
+
+    #[test]
+    fn peer_response_carries_metadata() {
+        let peer = AdvertisedPeer {
+            ip: "1.2.3.4:4130".parse().unwrap(),
+            last_seen: 1_700_000_000,
+            services: PeerServices::new(crate::NodeType::Validator, true),
+        };
+        let bytes = PeerResponse { peers: vec![peer] }.to_bytes_le().unwrap();
+        // Check that the response is not serialized in the legacy format.
+        assert_eq!(bytes[..3], [u8::MAX, 2, 1]);
+        assert_eq!(PeerResponse::read_le(&bytes[..]).unwrap().peers, vec![peer]);
+    }
+
+    #[proptest]
+    fn peer_response_without_metadata_is_legacy(#[strategy(any_peer_response())] peer_response: PeerResponse) {
+        let addresses = peer_response.addresses().collect::<Vec<_>>();
+        let bytes = peer_response.without_metadata().to_bytes_le().unwrap();
+        // Check that a response without metadata is serialized in the legacy format, for the peers that predate it.
+        assert_eq!(bytes, to_legacy_bytes(&addresses));
+    }
 }
@@ -23,6 +23,8 @@ pub struct Ping<N: Network> {
     pub version: u32,
     pub node_type: NodeType,
     pub block_locators: Option<BlockLocators<N>>,
+    /// The UNIX timestamp in milliseconds at which the ping was sent, if the peer supports clock sync.
+    pub timestamp: Option<i64>,
 }
 
 impl<N: Network> MessageTrait for Ping<N> {
@@ -43,6 +45,11 @@ impl<N: Network> ToBytes for Ping<N> {
         } else {
             0u8.write_le(&mut writer)?;
         }
+        // Note: The timestamp is a trailing field, which is omitted for peers that do not support clock sync.
+        if let Some(timestamp) = self.timestamp {
+            1u8.write_le(&mut writer)?;
+            timestamp.write_le(&mut writer)?;
+        }
 
         Ok(())
     }
@@ -59,14 +66,31 @@ impl<N: Network> FromBytes for Ping<N> {
             1 => Some(BlockLocators::read_le(&mut reader)?),
             _ => return Err(error("Invalid block locators marker")),
         };
+        let timestamp = read_trailing_timestamp(&mut reader)?;
 
-        Ok(Self { version, node_type, block_locators })
+        Ok(Self { version, node_type, block_locators, timestamp })
     }
 }
 
 impl<N: Network> Ping<N> {
     pub fn new(node_type: NodeType, block_locators: Option<BlockLocators<N>>) -> Self {
-        Self { version: <Message<N>>::VERSION, node_type, block_locators }
+        Self { version: <Message<N>>::VERSION, node_type, block_locators, timestamp: None }
+    }
+
+    /// Sets the UNIX timestamp in milliseconds at which the ping is sent.
+    pub fn with_timestamp(mut self, timestamp: i64) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+}
+
+/// Reads the optional trailing timestamp of a `Ping` or `Pong` message.
+pub(crate) fn read_trailing_timestamp<R: io::Read>(mut reader: R) -> io::Result<Option<i64>> {
+    let mut marker = [0u8; 1];
+    match reader.read(&mut marker)? {
+        0 => Ok(None),
+        _ if marker[0] == 1 => Ok(Some(i64::read_le(&mut reader)?)),
+        _ => Err(error("Invalid timestamp marker")),
     }
 }
 
@@ -77,7 +101,10 @@ pub mod prop_tests {
     use snarkvm::utilities::{FromBytes, ToBytes};
 
     use bytes::{Buf, BufMut, BytesMut};
-    use proptest::prelude::{any, BoxedStrategy, Strategy};
+    use proptest::{
+        option::of,
+        prelude::{any, BoxedStrategy, Strategy},
+    };
     use test_strategy::proptest;
 
     type CurrentNetwork = snarkvm::prelude::MainnetV0;
@@ -87,8 +114,13 @@ pub mod prop_tests {
     }
 
     pub fn any_ping() -> BoxedStrategy<Ping<CurrentNetwork>> {
-        (any::<u32>(), any_block_locators(), any_node_type())
-            .prop_map(|(version, bls, node_type)| Ping { version, block_locators: Some(bls), node_type })
+        (any::<u32>(), any_block_locators(), any_node_type(), of(any::<i64>()))
+            .prop_map(|(version, bls, node_type, timestamp)| Ping {
+                version,
+                block_locators: Some(bls),
+                node_type,
+                timestamp,
+            })
             .boxed()
     }
 
@@ -21,6 +21,9 @@ use std::borrow::Cow;
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Pong {
     pub is_fork: Option<bool>,
+    /// The UNIX timestamp in milliseconds of the `Ping`, and the UNIX timestamp in milliseconds
+    /// at which the `Ping` was answered, if the `Ping` contained a timestamp.
+    pub timestamps: Option<(i64, i64)>,
 }
 
 impl MessageTrait for Pong {
@@ -32,14 +35,22 @@ impl MessageTrait for Pong {
 }
 
 impl ToBytes for Pong {
-    fn write_le<W: io::Write>(&self, writer: W) -> io::Result<()> {
+    fn write_le<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
         let serialized_is_fork: u8 = match self.is_fork {
             Some(true) => 0,
             Some(false) => 1,
             None => 2,
         };
+        serialized_is_fork.write_le(&mut writer)?;
 
-        serialized_is_fork.write_le(writer)
+        // Note: The timestamps are a trailing field, which is omitted for peers that do not support clock sync.
+        if let Some((sent, answered)) = self.timestamps {
+            1u8.write_le(&mut writer)?;
+            sent.write_le(&mut writer)?;
+            answered.write_le(&mut writer)?;
+        }
+
+        Ok(())
     }
 }
 
@@ -51,8 +62,12 @@ impl FromBytes for Pong {
             2 => None,
             _ => return Err(error("Invalid 'Pong' message")),
         };
+        let timestamps = match crate::ping::read_trailing_timestamp(&mut reader)? {
+            Some(sent) => Some((sent, i64::read_le(&mut reader)?)),
+            None => None,
+        };
 
-        Ok(Self { is_fork })
+        Ok(Self { is_fork, timestamps })
     }
 }
 
@@ -69,7 +84,9 @@ pub mod tests {
     use test_strategy::proptest;
 
     pub fn any_pong() -> BoxedStrategy<Pong> {
-        of(any::<bool>()).prop_map(|is_fork| Pong { is_fork }).boxed()
+        (of(any::<bool>()), of(any::<(i64, i64)>()))
+            .prop_map(|(is_fork, timestamps)| Pong { is_fork, timestamps })
+            .boxed()
     }
 
     #[proptest]
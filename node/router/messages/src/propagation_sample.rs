@@ -0,0 +1,119 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+
+use snarkvm::prelude::{FromBytes, ToBytes};
+
+use std::borrow::Cow;
+
+/// A report of the time at which a node first saw a transaction, which is sent back to the origin of the transaction
+/// along the path the transaction was gossiped on, to measure how long transactions take to propagate.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PropagationSample<N: Network> {
+    /// The ID of the transaction.
+    pub transaction_id: N::TransactionID,
+    /// The UNIX timestamp in milliseconds at which the reporting node first saw the transaction.
+    pub first_seen: i64,
+    /// The number of nodes that relayed the report towards the origin.
+    pub hops: u8,
+}
+
+impl<N: Network> PropagationSample<N> {
+    /// The maximum number of nodes that may relay a report towards the origin.
+    pub const MAXIMUM_HOPS: u8 = 8;
+}
+
+impl<N: Network> MessageTrait for PropagationSample<N> {
+    /// Returns the message name.
+    #[inline]
+    fn name(&self) -> Cow<'static, str> {
+        "PropagationSample".into()
+    }
+}
+
+impl<N: Network> ToBytes for PropagationSample<N> {
+    fn write_le<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        // Ensure the number of hops is within the bounds.
+        if self.hops > Self::MAXIMUM_HOPS {
+            return Err(error(format!("PropagationSample has too many hops ({})", self.hops)));
+        }
+        self.transaction_id.write_le(&mut writer)?;
+        self.first_seen.write_le(&mut writer)?;
+        self.hops.write_le(&mut writer)
+    }
+}
+
+impl<N: Network> FromBytes for PropagationSample<N> {
+    fn read_le<R: io::Read>(mut reader: R) -> io::Result<Self> {
+        let transaction_id = N::TransactionID::read_le(&mut reader)?;
+        let first_seen = i64::read_le(&mut reader)?;
+        // Ensure the number of hops is within the bounds.
+        let hops = u8::read_le(&mut reader)?;
+        if hops > Self::MAXIMUM_HOPS {
+            return Err(error(format!("PropagationSample has too many hops ({hops})")));
+        }
+        Ok(Self { transaction_id, first_seen, hops })
+    }
+}
+
+#[cfg(test)]
+pub mod prop_tests {
+    use crate::PropagationSample;
+    use snarkvm::{
+        console::prelude::{FromBytes, ToBytes},
+        prelude::{Rng, TestRng},
+    };
+
+    use bytes::{Buf, BufMut, BytesMut};
+    use proptest::prelude::{any, BoxedStrategy, Strategy};
+    use test_strategy::proptest;
+
+    type CurrentNetwork = snarkvm::prelude::MainnetV0;
+
+    pub fn any_propagation_sample() -> BoxedStrategy<PropagationSample<CurrentNetwork>> {
+        (any::<u64>(), any::<i64>(), 0..=PropagationSample::<CurrentNetwork>::MAXIMUM_HOPS)
+            .prop_map(|(seed, first_seen, hops)| PropagationSample {
+                transaction_id: TestRng::fixed(seed).gen(),
+                first_seen,
+                hops,
+            })
+            .boxed()
+    }
+
+    #[proptest]
+    fn propagation_sample_roundtrip(#[strategy(any_propagation_sample())] original: PropagationSample<CurrentNetwork>) {
+        let mut buf = BytesMut::default().writer();
+        PropagationSample::write_le(&original, &mut buf).unwrap();
+
+        let deserialized: PropagationSample<CurrentNetwork> =
+            PropagationSample::read_le(buf.into_inner().reader()).unwrap();
+        assert_eq!(original, deserialized);
+    }
+
+    #[test]
+    fn propagation_sample_rejects_excessive_hops() {
+        let mut sample = PropagationSample::<CurrentNetwork> {
+            transaction_id: TestRng::default().gen(),
+            first_seen: 0,
+            hops: PropagationSample::<CurrentNetwork>::MAXIMUM_HOPS,
+        };
+        let mut bytes = sample.to_bytes_le().unwrap();
+        // Ensure a report beyond the maximum number of hops is neither encoded nor decoded.
+        sample.hops += 1;
+        assert!(sample.to_bytes_le().is_err());
+        *bytes.last_mut().unwrap() += 1;
+        assert!(PropagationSample::<CurrentNetwork>::read_le(&bytes[..]).is_err());
+    }
+}
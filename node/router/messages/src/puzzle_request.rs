@@ -47,6 +47,11 @@ pub mod tests {
     use snarkvm::utilities::{FromBytes, ToBytes};
 
     use bytes::{Buf, BufMut, BytesMut};
+    use proptest::prelude::{BoxedStrategy, Just, Strategy};
+
+    pub fn any_puzzle_request() -> BoxedStrategy<PuzzleRequest> {
+        Just(PuzzleRequest).boxed()
+    }
 
     #[test]
     fn puzzle_request_roundtrip() {
@@ -44,7 +44,7 @@ impl<N: Network> ToBytes for PuzzleResponse<N> {
 
 impl<N: Network> FromBytes for PuzzleResponse<N> {
     fn read_le<R: io::Read>(mut reader: R) -> io::Result<Self> {
-        Ok(Self { epoch_hash: N::BlockHash::read_le(&mut reader)?, block_header: Data::read_le(reader)? })
+        Ok(Self { epoch_hash: N::BlockHash::read_le(&mut reader)?, block_header: read_data(reader)? })
     }
 }
 
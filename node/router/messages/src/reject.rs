@@ -0,0 +1,211 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+
+use snarkvm::prelude::{FromBytes, ToBytes};
+
+use std::borrow::Cow;
+
+/// An item that was supplied by a peer, and rejected by the node.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RejectedItem<N: Network> {
+    /// The ID of a rejected transaction.
+    Transaction(N::TransactionID),
+    /// The ID of a rejected solution.
+    Solution(SolutionID<N>),
+    /// The hash of a rejected block.
+    Block(N::BlockHash),
+}
+
+impl<N: Network> Display for RejectedItem<N> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Transaction(transaction_id) => write!(f, "transaction '{transaction_id}'"),
+            Self::Solution(solution_id) => write!(f, "solution '{solution_id}'"),
+            Self::Block(block_hash) => write!(f, "block '{block_hash}'"),
+        }
+    }
+}
+
+impl<N: Network> ToBytes for RejectedItem<N> {
+    fn write_le<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        match self {
+            Self::Transaction(transaction_id) => {
+                0u8.write_le(&mut writer)?;
+                transaction_id.write_le(&mut writer)
+            }
+            Self::Solution(solution_id) => {
+                1u8.write_le(&mut writer)?;
+                solution_id.write_le(&mut writer)
+            }
+            Self::Block(block_hash) => {
+                2u8.write_le(&mut writer)?;
+                block_hash.write_le(&mut writer)
+            }
+        }
+    }
+}
+
+impl<N: Network> FromBytes for RejectedItem<N> {
+    fn read_le<R: io::Read>(mut reader: R) -> io::Result<Self> {
+        match u8::read_le(&mut reader)? {
+            0 => Ok(Self::Transaction(N::TransactionID::read_le(&mut reader)?)),
+            1 => Ok(Self::Solution(SolutionID::read_le(&mut reader)?)),
+            2 => Ok(Self::Block(N::BlockHash::read_le(&mut reader)?)),
+            _ => Err(error("Invalid rejected item category")),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Reject<N: Network> {
+    /// The rejected item.
+    pub item: RejectedItem<N>,
+    /// The reason the item was rejected.
+    pub reason: RejectReason,
+    /// A short, human-readable description of the rejection.
+    pub message: String,
+}
+
+impl<N: Network> Reject<N> {
+    /// The maximum length of the description, in bytes.
+    pub const MAXIMUM_MESSAGE_LENGTH: usize = 128;
+
+    /// Initializes a new `Reject` message, truncating the description to the maximum length.
+    pub fn new(item: RejectedItem<N>, reason: RejectReason, message: &str) -> Self {
+        // Truncate the description on a character boundary.
+        let mut length = message.len().min(Self::MAXIMUM_MESSAGE_LENGTH);
+        while !message.is_char_boundary(length) {
+            length -= 1;
+        }
+        Self { item, reason, message: message[..length].to_string() }
+    }
+}
+
+impl<N: Network> MessageTrait for Reject<N> {
+    /// Returns the message name.
+    #[inline]
+    fn name(&self) -> Cow<'static, str> {
+        format!("Reject ({})", self.reason).into()
+    }
+}
+
+impl<N: Network> ToBytes for Reject<N> {
+    fn write_le<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        // Ensure the description is within the bounds.
+        if self.message.len() > Self::MAXIMUM_MESSAGE_LENGTH {
+            return Err(error(format!("Reject description is too long ({} bytes)", self.message.len())));
+        }
+        self.item.write_le(&mut writer)?;
+        self.reason.write_le(&mut writer)?;
+        (self.message.len() as u8).write_le(&mut writer)?;
+        writer.write_all(self.message.as_bytes())
+    }
+}
+
+impl<N: Network> FromBytes for Reject<N> {
+    fn read_le<R: io::Read>(mut reader: R) -> io::Result<Self> {
+        let item = RejectedItem::read_le(&mut reader)?;
+        let reason = RejectReason::read_le(&mut reader)?;
+        // Ensure the description is within the bounds.
+        let length = u8::read_le(&mut reader)? as usize;
+        if length > Self::MAXIMUM_MESSAGE_LENGTH {
+            return Err(error(format!("Reject description is too long ({length} bytes)")));
+        }
+        let mut bytes = vec![0u8; length];
+        reader.read_exact(&mut bytes)?;
+        let message = String::from_utf8(bytes).map_err(|_| error("Reject description is not valid UTF-8"))?;
+        Ok(Self { item, reason, message })
+    }
+}
+
+#[cfg(test)]
+pub mod prop_tests {
+    use crate::{Reject, RejectReason, RejectedItem};
+    use snarkvm::{
+        console::prelude::{FromBytes, ToBytes},
+        prelude::{puzzle::SolutionID, Rng, TestRng},
+    };
+
+    use bytes::{Buf, BufMut, BytesMut};
+    use proptest::prelude::{any, BoxedStrategy, Strategy};
+    use test_strategy::proptest;
+
+    type CurrentNetwork = snarkvm::prelude::MainnetV0;
+
+    pub fn any_rejected_item() -> BoxedStrategy<RejectedItem<CurrentNetwork>> {
+        (0..3u8, any::<u64>())
+            .prop_map(|(category, seed)| {
+                let rng = &mut TestRng::fixed(seed);
+                match category {
+                    0 => RejectedItem::Transaction(rng.gen()),
+                    1 => RejectedItem::Solution(SolutionID::from(rng.gen::<u64>())),
+                    _ => RejectedItem::Block(rng.gen()),
+                }
+            })
+            .boxed()
+    }
+
+    pub fn any_reject_reason() -> BoxedStrategy<RejectReason> {
+        (0..4u8).prop_map(|code| RejectReason::from_code(code).unwrap()).boxed()
+    }
+
+    pub fn any_reject() -> BoxedStrategy<Reject<CurrentNetwork>> {
+        (any_rejected_item(), any_reject_reason(), "[a-z ]{0,200}")
+            .prop_map(|(item, reason, message)| Reject::new(item, reason, &message))
+            .boxed()
+    }
+
+    #[proptest]
+    fn reject_roundtrip(#[strategy(any_reject())] original: Reject<CurrentNetwork>) {
+        let mut buf = BytesMut::default().writer();
+        Reject::write_le(&original, &mut buf).unwrap();
+
+        let deserialized: Reject<CurrentNetwork> = Reject::read_le(buf.into_inner().reader()).unwrap();
+        assert_eq!(original, deserialized);
+    }
+
+    #[test]
+    fn reject_truncates_long_descriptions() {
+        let item = RejectedItem::Solution(SolutionID::from(1u64));
+        // Ensure a multi-byte character is not split when truncating.
+        let message = "é".repeat(Reject::<CurrentNetwork>::MAXIMUM_MESSAGE_LENGTH);
+        let reject = Reject::<CurrentNetwork>::new(item, RejectReason::Invalid, &message);
+        assert_eq!(reject.message.len(), Reject::<CurrentNetwork>::MAXIMUM_MESSAGE_LENGTH);
+        assert!(reject.to_bytes_le().is_ok());
+
+        // Ensure an oversized description is rejected when decoding.
+        let mut bytes = item.to_bytes_le().unwrap();
+        bytes.push(RejectReason::Invalid.code());
+        bytes.push(Reject::<CurrentNetwork>::MAXIMUM_MESSAGE_LENGTH as u8 + 1);
+        bytes.extend(vec![b'a'; Reject::<CurrentNetwork>::MAXIMUM_MESSAGE_LENGTH + 1]);
+        assert!(Reject::<CurrentNetwork>::read_le(&bytes[..]).is_err());
+    }
+
+    #[test]
+    fn reject_reason_codes_are_stable() {
+        for code in 0..=u8::MAX {
+            match RejectReason::from_code(code) {
+                Some(reason) => assert_eq!(reason.code(), code),
+                None => assert!(code > 3),
+            }
+        }
+        // Ensure only a temporarily full node invites a retry.
+        assert!(RejectReason::Invalid.is_permanent());
+        assert!(RejectReason::Oversized.is_permanent());
+        assert!(RejectReason::StaleEpoch.is_permanent());
+        assert!(!RejectReason::TemporarilyFull.is_permanent());
+    }
+}
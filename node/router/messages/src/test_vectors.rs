@@ -0,0 +1,151 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::*;
+
+use snarkvm::prelude::{bail, ensure, Field, Result};
+
+/// The path of the message test vectors, relative to the root of this crate.
+pub const MESSAGE_TEST_VECTORS_PATH: &str = "test-vectors/messages.txt";
+
+/// The header of the message test vectors.
+const HEADER: &str = "\
+# The serialized messages whose encoding is pinned by the conformance tests, as `<name> <hex>` lines.
+# Regenerate with `cargo run -p snarkos-node-router-messages --features test --bin message-test-vectors`.
+";
+
+/// The UNIX timestamp in milliseconds used by the sample messages.
+const TIMESTAMP: i64 = 1_700_000_000_000;
+
+/// Returns the sample messages that are pinned by the test vectors, along with their names.
+///
+/// Note: The messages that carry ledger objects (blocks, headers, transactions, solutions, or signatures)
+/// are serialized by snarkVM, and are only covered by the round-trip fuzz tests.
+pub fn sample_messages<N: Network>() -> Vec<(&'static str, Message<N>)> {
+    vec![
+        ("BlockRequest", Message::BlockRequest(BlockRequest { start_height: 5, end_height: 10 })),
+        ("Disconnect", Message::from(DisconnectReason::NoReasonGiven)),
+        ("DisconnectPortClosed", Message::from(DisconnectReason::YourPortIsClosed(4130))),
+        ("PeerRequest", Message::PeerRequest(PeerRequest)),
+        ("PeerResponse", Message::PeerResponse(PeerResponse { peers: vec![] })),
+        (
+            "Ping",
+            Message::Ping(Ping {
+                version: 17,
+                node_type: NodeType::Validator,
+                block_locators: None,
+                timestamp: Some(TIMESTAMP),
+            }),
+        ),
+        (
+            "PingLegacy",
+            Message::Ping(Ping { version: 17, node_type: NodeType::Client, block_locators: None, timestamp: None }),
+        ),
+        ("Pong", Message::Pong(Pong { is_fork: Some(false), timestamps: Some((TIMESTAMP, TIMESTAMP + 250)) })),
+        ("PongLegacy", Message::Pong(Pong { is_fork: None, timestamps: None })),
+        ("PuzzleRequest", Message::PuzzleRequest(PuzzleRequest)),
+        (
+            "GetBlockTransactions",
+            Message::GetBlockTransactions(GetBlockTransactions {
+                block_hash: Field::from_u64(7).into(),
+                positions: vec![0, 3, 65534],
+            }),
+        ),
+        (
+            "BlockByHashRequest",
+            Message::BlockByHashRequest(BlockByHashRequest {
+                block_hashes: vec![Field::from_u64(1).into(), Field::from_u64(2).into()],
+            }),
+        ),
+        (
+            "Reject",
+            Message::Reject(Reject::new(
+                RejectedItem::Transaction(Field::from_u64(9).into()),
+                RejectReason::StaleEpoch,
+                "stale epoch",
+            )),
+        ),
+        (
+            "PropagationSample",
+            Message::PropagationSample(PropagationSample {
+                transaction_id: Field::from_u64(42).into(),
+                first_seen: TIMESTAMP,
+                hops: 3,
+            }),
+        ),
+    ]
+}
+
+/// Returns the test vectors of the sample messages.
+pub fn to_test_vectors<N: Network>() -> Result<String> {
+    let mut vectors = HEADER.to_string();
+    for (name, message) in sample_messages::<N>() {
+        let bytes = message.to_bytes_le()?;
+        vectors.push_str(&format!("{name} {}\n", bytes.iter().map(|byte| format!("{byte:02x}")).collect::<String>()));
+    }
+    Ok(vectors)
+}
+
+/// Parses the given test vectors into the names and the bytes of the serialized messages.
+pub fn parse_test_vectors(vectors: &str) -> Result<Vec<(String, Vec<u8>)>> {
+    let mut parsed = Vec::new();
+    for line in vectors.lines().filter(|line| !line.is_empty() && !line.starts_with('#')) {
+        let Some((name, hex)) = line.split_once(' ') else {
+            bail!("Malformed test vector '{line}'");
+        };
+        ensure!(hex.len() % 2 == 0 && hex.is_ascii(), "Malformed hex in the test vector '{name}'");
+        let bytes = (0..hex.len())
+            .step_by(2)
+            .map(|index| u8::from_str_radix(&hex[index..index + 2], 16))
+            .collect::<Result<Vec<_>, _>>()?;
+        parsed.push((name.to_string(), bytes));
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type CurrentNetwork = snarkvm::prelude::MainnetV0;
+
+    /// The committed test vectors.
+    const VECTORS: &str = include_str!("../test-vectors/messages.txt");
+
+    #[test]
+    fn test_serialization_matches_test_vectors() {
+        // Note: A failure here means the wire format changed; if that is intended, regenerate the test vectors.
+        assert_eq!(to_test_vectors::<CurrentNetwork>().unwrap(), VECTORS);
+    }
+
+    #[test]
+    fn test_test_vectors_deserialize() {
+        let samples = sample_messages::<CurrentNetwork>();
+        let vectors = parse_test_vectors(VECTORS).unwrap();
+        assert_eq!(vectors.len(), samples.len());
+
+        for ((name, bytes), (expected_name, expected)) in vectors.into_iter().zip(samples) {
+            assert_eq!(name, expected_name);
+            let message = Message::<CurrentNetwork>::read_le(&bytes[..]).unwrap();
+            assert_eq!(message, expected, "Test vector '{name}' does not deserialize to the sample message");
+        }
+    }
+
+    #[test]
+    fn test_malformed_test_vectors_are_rejected() {
+        assert!(parse_test_vectors("BlockRequest").is_err());
+        assert!(parse_test_vectors("BlockRequest 000").is_err());
+        assert!(parse_test_vectors("BlockRequest 00zz").is_err());
+    }
+}
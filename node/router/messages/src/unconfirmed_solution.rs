@@ -44,7 +44,7 @@ impl<N: Network> ToBytes for UnconfirmedSolution<N> {
 
 impl<N: Network> FromBytes for UnconfirmedSolution<N> {
     fn read_le<R: io::Read>(mut reader: R) -> io::Result<Self> {
-        Ok(Self { solution_id: SolutionID::read_le(&mut reader)?, solution: Data::read_le(reader)? })
+        Ok(Self { solution_id: SolutionID::read_le(&mut reader)?, solution: read_data(reader)? })
     }
 }
 
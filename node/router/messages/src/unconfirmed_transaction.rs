@@ -52,13 +52,13 @@ impl<N: Network> ToBytes for UnconfirmedTransaction<N> {
 
 impl<N: Network> FromBytes for UnconfirmedTransaction<N> {
     fn read_le<R: io::Read>(mut reader: R) -> io::Result<Self> {
-        Ok(Self { transaction_id: N::TransactionID::read_le(&mut reader)?, transaction: Data::read_le(reader)? })
+        Ok(Self { transaction_id: N::TransactionID::read_le(&mut reader)?, transaction: read_data(reader)? })
     }
 }
 
 #[cfg(test)]
 pub mod prop_tests {
-    use crate::{Transaction, UnconfirmedTransaction};
+    use crate::{Message, Transaction, UnconfirmedTransaction};
     use snarkvm::{
         ledger::{ledger_test_helpers::sample_fee_public_transaction, narwhal::Data},
         prelude::{FromBytes, TestRng, ToBytes},
@@ -100,4 +100,18 @@ pub mod prop_tests {
             deserialized.transaction.deserialize_blocking().unwrap(),
         );
     }
+    #[proptest]
+    fn buffered_unconfirmed_transaction_is_wire_compatible(
+        #[strategy(any_unconfirmed_transaction())] original: UnconfirmedTransaction<CurrentNetwork>,
+    ) {
+        let message = Message::UnconfirmedTransaction(original);
+        let expected = message.to_bytes_le().unwrap();
+
+        let buffered = message.into_buffered();
+        let Message::UnconfirmedTransaction(UnconfirmedTransaction { transaction, .. }) = &buffered else {
+            unreachable!()
+        };
+        assert!(matches!(transaction, Data::Buffer(_)));
+        assert_eq!(expected, buffered.to_bytes_le().unwrap());
+    }
 }
@@ -13,14 +13,29 @@
 // limitations under the License.
 
 use crate::{
-    messages::{ChallengeRequest, ChallengeResponse, DisconnectReason, Message, MessageCodec, MessageTrait},
+    messages::{
+        deserialize_data,
+        ChallengeRequest,
+        ChallengeResponse,
+        DisconnectReason,
+        Message,
+        MessageCodec,
+        MessageTrait,
+    },
+    AuditEvent,
+    AuditEventKind,
+    Initiator,
+    Offense,
     Peer,
+    PeerEvent,
+    PeerState,
+    PeerStateGuard,
     Router,
 };
 use snarkos_node_tcp::{ConnectionSide, Tcp, P2P};
 use snarkvm::{
     ledger::narwhal::Data,
-    prelude::{block::Header, error, Address, Network},
+    prelude::{block::Header, error, Network},
 };
 
 use anyhow::{bail, Result};
@@ -88,6 +103,13 @@ impl<N: Network> Router<N> {
         peer_side: ConnectionSide,
         genesis_header: Header<N>,
     ) -> io::Result<(SocketAddr, Framed<&mut TcpStream, MessageCodec<N>>)> {
+        // Record the connection in the audit log.
+        let initiator = match peer_side {
+            ConnectionSide::Initiator => Initiator::Remote,
+            ConnectionSide::Responder => Initiator::Local,
+        };
+        self.record_audit_event(|| AuditEvent::new(AuditEventKind::Connect, peer_addr).with_initiator(initiator));
+
         // If this is an inbound connection, we log it, but don't know the listening address yet.
         // Otherwise, we can immediately register the listening address.
         let mut peer_ip = if peer_side == ConnectionSide::Initiator {
@@ -105,14 +127,24 @@ impl<N: Network> Router<N> {
             self.handshake_inner_responder(peer_addr, &mut peer_ip, stream, genesis_header).await
         };
 
-        // Remove the address from the collection of connecting peers (if the handshake got to the point where it's known).
-        if let Some(ip) = peer_ip {
-            self.connecting_peers.lock().remove(&ip);
-        }
-
-        // If the handshake succeeded, announce it.
-        if let Ok((ref peer_ip, _)) = handshake_result {
-            info!("Connected to '{peer_ip}'");
+        match handshake_result {
+            // If the handshake succeeded, announce it.
+            Ok((ref peer_ip, _)) => {
+                info!("Connected to '{peer_ip}'");
+                self.record_audit_event(|| {
+                    let event = AuditEvent::new(AuditEventKind::HandshakeSuccess, *peer_ip).with_initiator(initiator);
+                    match self.get_connected_peer(peer_ip) {
+                        Some(peer) => event.with_peer(&peer),
+                        None => event,
+                    }
+                });
+            }
+            // Otherwise, record the reason of the failure.
+            Err(ref error) => self.record_audit_event(|| {
+                AuditEvent::new(AuditEventKind::HandshakeFailure, peer_ip.unwrap_or(peer_addr))
+                    .with_initiator(initiator)
+                    .with_reason(error.to_string())
+            }),
         }
 
         handshake_result
@@ -128,6 +160,12 @@ impl<N: Network> Router<N> {
     ) -> io::Result<(SocketAddr, Framed<&mut TcpStream, MessageCodec<N>>)> {
         // This value is immediately guaranteed to be present, so it can be unwrapped.
         let peer_ip = peer_ip.unwrap();
+        // Start the handshake, as the dial succeeded.
+        // Note: A connection that was not dialed by the router (e.g. directly over TCP) is dialed first.
+        if self.peer_state(&peer_ip) == PeerState::Disconnected {
+            let _ = self.transition_peer(peer_ip, PeerEvent::Dial);
+        }
+        let _guard = self.start_handshake(peer_ip, PeerEvent::DialSucceeded)?;
         // Construct the stream.
         let mut framed = Framed::new(stream, MessageCodec::<N>::handshake());
 
@@ -139,7 +177,12 @@ impl<N: Network> Router<N> {
         // Sample a random nonce.
         let our_nonce = rng.gen();
         // Send a challenge request to the peer.
-        let our_request = ChallengeRequest::new(self.local_ip().port(), self.node_type, self.address(), our_nonce);
+        let our_request = ChallengeRequest {
+            capabilities: self.capabilities(),
+            ..ChallengeRequest::new(self.advertised_ip().port(), self.node_type, self.address(), our_nonce)
+                .with_versions(self.versions())
+        }
+        .with_external_addr(self.proxy_external_ip());
         send(&mut framed, peer_addr, Message::ChallengeRequest(our_request)).await?;
 
         /* Step 2: Receive the peer's challenge response followed by the challenge request. */
@@ -150,22 +193,29 @@ impl<N: Network> Router<N> {
         let peer_request = expect_message!(Message::ChallengeRequest, framed, peer_addr);
 
         // Verify the challenge response. If a disconnect reason was returned, send the disconnect message and abort.
-        if let Some(reason) = self
-            .verify_challenge_response(peer_addr, peer_request.address, peer_response, genesis_header, our_nonce)
-            .await
+        if let Some(reason) =
+            self.verify_challenge_response(peer_addr, &peer_request, peer_response, genesis_header, our_nonce).await
         {
             send(&mut framed, peer_addr, reason.into()).await?;
             return Err(error(format!("Dropped '{peer_addr}' for reason: {reason:?}")));
         }
         // Verify the challenge request. If a disconnect reason was returned, send the disconnect message and abort.
-        if let Some(reason) = self.verify_challenge_request(peer_addr, &peer_request) {
-            send(&mut framed, peer_addr, reason.into()).await?;
-            return Err(error(format!("Dropped '{peer_addr}' for reason: {reason:?}")));
-        }
+        let version = match self.verify_challenge_request(peer_addr, &peer_request) {
+            Ok(version) => version,
+            Err(reason) => {
+                send(&mut framed, peer_addr, reason.into()).await?;
+                return Err(error(format!("Dropped '{peer_addr}' for reason: {reason:?}")));
+            }
+        };
         /* Step 3: Send the challenge response. */
 
         let response_nonce: u64 = rng.gen();
-        let data = [peer_request.nonce.to_le_bytes(), response_nonce.to_le_bytes()].concat();
+        let data = ChallengeResponse::signed_bytes(
+            peer_request.nonce,
+            response_nonce,
+            &genesis_header,
+            self.advertised_ip().port(),
+        )?;
         // Sign the counterparty nonce.
         let Ok(our_signature) = self.account.sign_bytes(&data, rng) else {
             return Err(error(format!("Failed to sign the challenge request nonce from '{peer_addr}'")));
@@ -176,7 +226,8 @@ impl<N: Network> Router<N> {
         send(&mut framed, peer_addr, Message::ChallengeResponse(our_response)).await?;
 
         // Add the peer to the router.
-        self.insert_connected_peer(Peer::new(peer_ip, &peer_request), peer_addr);
+        let is_deprecated = self.versions().is_deprecated(version);
+        self.insert_connected_peer(Peer::new(peer_ip, &peer_request, version, is_deprecated), peer_addr);
 
         Ok((peer_ip, framed))
     }
@@ -205,11 +256,16 @@ impl<N: Network> Router<N> {
         if let Err(forbidden_message) = self.ensure_peer_is_allowed(peer_ip) {
             return Err(error(format!("{forbidden_message}")));
         }
+        // Start the handshake, as the connection was accepted.
+        let _guard = self.start_handshake(peer_ip, PeerEvent::Accept)?;
         // Verify the challenge request. If a disconnect reason was returned, send the disconnect message and abort.
-        if let Some(reason) = self.verify_challenge_request(peer_addr, &peer_request) {
-            send(&mut framed, peer_addr, reason.into()).await?;
-            return Err(error(format!("Dropped '{peer_addr}' for reason: {reason:?}")));
-        }
+        let version = match self.verify_challenge_request(peer_addr, &peer_request) {
+            Ok(version) => version,
+            Err(reason) => {
+                send(&mut framed, peer_addr, reason.into()).await?;
+                return Err(error(format!("Dropped '{peer_addr}' for reason: {reason:?}")));
+            }
+        };
         /* Step 2: Send the challenge response followed by own challenge request. */
 
         // Initialize an RNG.
@@ -217,7 +273,12 @@ impl<N: Network> Router<N> {
 
         // Sign the counterparty nonce.
         let response_nonce: u64 = rng.gen();
-        let data = [peer_request.nonce.to_le_bytes(), response_nonce.to_le_bytes()].concat();
+        let data = ChallengeResponse::signed_bytes(
+            peer_request.nonce,
+            response_nonce,
+            &genesis_header,
+            self.advertised_ip().port(),
+        )?;
         let Ok(our_signature) = self.account.sign_bytes(&data, rng) else {
             return Err(error(format!("Failed to sign the challenge request nonce from '{peer_addr}'")));
         };
@@ -229,7 +290,12 @@ impl<N: Network> Router<N> {
         // Sample a random nonce.
         let our_nonce = rng.gen();
         // Send the challenge request.
-        let our_request = ChallengeRequest::new(self.local_ip().port(), self.node_type, self.address(), our_nonce);
+        let our_request = ChallengeRequest {
+            capabilities: self.capabilities(),
+            ..ChallengeRequest::new(self.advertised_ip().port(), self.node_type, self.address(), our_nonce)
+                .with_versions(self.versions())
+        }
+        .with_external_addr(self.proxy_external_ip());
         send(&mut framed, peer_addr, Message::ChallengeRequest(our_request)).await?;
 
         /* Step 3: Receive the challenge response. */
@@ -237,19 +303,28 @@ impl<N: Network> Router<N> {
         // Listen for the challenge response message.
         let peer_response = expect_message!(Message::ChallengeResponse, framed, peer_addr);
         // Verify the challenge response. If a disconnect reason was returned, send the disconnect message and abort.
-        if let Some(reason) = self
-            .verify_challenge_response(peer_addr, peer_request.address, peer_response, genesis_header, our_nonce)
-            .await
+        if let Some(reason) =
+            self.verify_challenge_response(peer_addr, &peer_request, peer_response, genesis_header, our_nonce).await
         {
             send(&mut framed, peer_addr, reason.into()).await?;
             return Err(error(format!("Dropped '{peer_addr}' for reason: {reason:?}")));
         }
         // Add the peer to the router.
-        self.insert_connected_peer(Peer::new(peer_ip, &peer_request), peer_addr);
+        let is_deprecated = self.versions().is_deprecated(version);
+        self.insert_connected_peer(Peer::new(peer_ip, &peer_request, version, is_deprecated), peer_addr);
 
         Ok((peer_ip, framed))
     }
 
+    /// Starts the handshake with the given peer, and returns a guard that fails the handshake when it is dropped,
+    /// unless it succeeded. This ensures the peer is not left handshaking if the handshake fails, or is dropped.
+    fn start_handshake(&self, peer_ip: SocketAddr, event: PeerEvent) -> io::Result<PeerStateGuard> {
+        match self.transition_peer(peer_ip, event) {
+            Ok(_) => Ok(self.guard_peer_state(peer_ip, PeerEvent::HandshakeFailed)),
+            Err(transition_error) => Err(error(format!("Dropping connection with '{peer_ip}' ({transition_error})"))),
+        }
+    }
+
     /// Ensure the peer is allowed to connect.
     fn ensure_peer_is_allowed(&self, peer_ip: SocketAddr) -> Result<()> {
         // Ensure the peer IP is not this node.
@@ -257,7 +332,7 @@ impl<N: Network> Router<N> {
             bail!("Dropping connection request from '{peer_ip}' (attempted to self-connect)")
         }
         // Ensure the node is not already connecting to this peer.
-        if !self.connecting_peers.lock().insert(peer_ip) {
+        if self.peer_state(&peer_ip).is_connecting() {
             bail!("Dropping connection request from '{peer_ip}' (already shaking hands as the initiator)")
         }
         // Ensure the node is not already connected to this peer.
@@ -286,28 +361,51 @@ impl<N: Network> Router<N> {
         Ok(())
     }
 
-    /// Verifies the given challenge request. Returns a disconnect reason if the request is invalid.
+    /// Verifies the given challenge request. Returns the negotiated message version,
+    /// or a disconnect reason if the request is invalid.
     fn verify_challenge_request(
         &self,
         peer_addr: SocketAddr,
         message: &ChallengeRequest<N>,
-    ) -> Option<DisconnectReason> {
-        // Retrieve the components of the challenge request.
-        let &ChallengeRequest { version, listener_port: _, node_type: _, address: _, nonce: _ } = message;
-
-        // Ensure the message protocol version is not outdated.
-        if version < Message::<N>::VERSION {
-            warn!("Dropping '{peer_addr}' on version {version} (outdated)");
-            return Some(DisconnectReason::OutdatedClientVersion);
+    ) -> Result<u32, DisconnectReason> {
+        // Retrieve the range of versions supported by the peer.
+        let (ours, theirs) = (self.versions(), message.versions());
+
+        // Ensure the range of versions is well-formed.
+        if theirs.minimum() > theirs.maximum() {
+            warn!("Dropping '{peer_addr}' on versions {theirs} (malformed)");
+            return Err(DisconnectReason::ProtocolViolation);
+        }
+        // Negotiate the highest version supported by both sides.
+        match ours.negotiate(&theirs) {
+            Some(version) => {
+                // Warn if the peer is only supported within the deprecation window.
+                if ours.is_deprecated(version) {
+                    warn!("Connecting to '{peer_addr}' on deprecated version {version} (supported versions {ours})");
+                }
+                Ok(version)
+            }
+            // Ensure the message protocol version is not outdated.
+            None if theirs.maximum() < ours.minimum() => {
+                warn!("Dropping '{peer_addr}' on versions {theirs} (outdated)");
+                Err(DisconnectReason::OutdatedClientVersion)
+            }
+            // Otherwise, this node is outdated.
+            None => {
+                warn!("Dropping '{peer_addr}' on versions {theirs} (this node supports {ours}, please update)");
+                Err(DisconnectReason::NoReasonGiven)
+            }
         }
-        None
     }
 
     /// Verifies the given challenge response. Returns a disconnect reason if the response is invalid.
+    ///
+    /// The signature must be produced by the address claimed in the peer's challenge request,
+    /// over our nonce, the response nonce, the genesis header, and the peer's listener port.
     async fn verify_challenge_response(
         &self,
         peer_addr: SocketAddr,
-        peer_address: Address<N>,
+        peer_request: &ChallengeRequest<N>,
         response: ChallengeResponse<N>,
         expected_genesis_header: Header<N>,
         expected_nonce: u64,
@@ -320,14 +418,28 @@ impl<N: Network> Router<N> {
             warn!("Handshake with '{peer_addr}' failed (incorrect block header)");
             return Some(DisconnectReason::InvalidChallengeResponse);
         }
+        // Retrieve the listener IP of the peer, to score any misbehavior.
+        let peer_ip = SocketAddr::new(peer_addr.ip(), peer_request.listener_port);
         // Perform the deferred non-blocking deserialization of the signature.
-        let Ok(signature) = signature.deserialize().await else {
+        let Ok(signature) = deserialize_data(signature).await else {
             warn!("Handshake with '{peer_addr}' failed (cannot deserialize the signature)");
+            self.record_misbehavior(peer_ip, Offense::FailedDeserialization);
+            return Some(DisconnectReason::InvalidChallengeResponse);
+        };
+        // Construct the signed bytes.
+        let Ok(data) = ChallengeResponse::signed_bytes(
+            expected_nonce,
+            nonce,
+            &expected_genesis_header,
+            peer_request.listener_port,
+        ) else {
+            warn!("Handshake with '{peer_addr}' failed (cannot construct the signed bytes)");
             return Some(DisconnectReason::InvalidChallengeResponse);
         };
-        // Verify the signature.
-        if !signature.verify_bytes(&peer_address, &[expected_nonce.to_le_bytes(), nonce.to_le_bytes()].concat()) {
+        // Verify the signature against the claimed address.
+        if !signature.verify_bytes(&peer_request.address, &data) {
             warn!("Handshake with '{peer_addr}' failed (invalid signature)");
+            self.record_misbehavior(peer_ip, Offense::InvalidSignature);
             return Some(DisconnectReason::InvalidChallengeResponse);
         }
         None
@@ -13,9 +13,13 @@
 // limitations under the License.
 
 use crate::{
-    messages::{DisconnectReason, Message, PeerRequest},
+    messages::{DisconnectReason, Message, NodeType, PeerRequest},
+    now_unix,
+    select_fresh_peers,
     Outbound,
+    PeerEvent,
     Router,
+    SyncStatus,
 };
 use snarkvm::prelude::Network;
 
@@ -42,6 +46,13 @@ pub trait Heartbeat<N: Network>: Outbound<N> {
     const MAXIMUM_NUMBER_OF_PEERS: usize = 21;
     /// The maximum number of provers to maintain connections with.
     const MAXIMUM_NUMBER_OF_PROVERS: usize = Self::MAXIMUM_NUMBER_OF_PEERS / 4;
+    /// The default maximum number of (untrusted) peers to maintain connections with in a single subnet bucket.
+    const MAXIMUM_PEERS_PER_SUBNET: usize = max(Self::MAXIMUM_NUMBER_OF_PEERS / 7, 1);
+
+    /// Returns the sync status of the node, which the heartbeat backs off for while syncing.
+    fn sync_status(&self) -> SyncStatus {
+        SyncStatus::Synced
+    }
 
     /// Handles the heartbeat request.
     fn heartbeat(&self) {
@@ -94,6 +105,7 @@ pub trait Heartbeat<N: Network>: Outbound<N> {
             if elapsed > Router::<N>::RADIO_SILENCE_IN_SECS {
                 warn!("Peer {} has not communicated in {elapsed} seconds", peer.ip());
                 // Disconnect from this peer.
+                let _ = self.router().transition_peer(peer.ip(), PeerEvent::InactivityTimeout);
                 self.router().disconnect(peer.ip());
             }
         }
@@ -217,11 +229,33 @@ pub trait Heartbeat<N: Network>: Outbound<N> {
             // Initialize an RNG.
             let rng = &mut OsRng;
 
+            // Determine the maximum number of peers per subnet bucket, unless it is configured.
+            // Note: In development mode, all peers are expected to reside in the same subnet.
+            let max_per_bucket = match self.router().max_peers_per_subnet() {
+                Some(max_per_bucket) => max_per_bucket,
+                None if self.router().is_dev() => usize::MAX,
+                None => Self::MAXIMUM_PEERS_PER_SUBNET,
+            };
+            // Determine if archives should be preferred, as the node syncs blocks from its peers.
+            let prefer_archives = matches!(self.router().node_type(), NodeType::Client | NodeType::Archive);
+            // Select the candidate peers to connect to, preferring recently-seen peers in subnets we have no peers in.
+            let peer_ips = select_fresh_peers(
+                self.router().candidate_peers_with_info(),
+                &self.router().connected_peers(),
+                self.router().trusted_peers(),
+                num_deficient,
+                max_per_bucket,
+                prefer_archives,
+                now_unix(),
+                rng,
+            );
             // Attempt to connect to more peers.
-            for peer_ip in self.router().candidate_peers().into_iter().choose_multiple(rng, num_deficient) {
+            for peer_ip in peer_ips {
                 self.router().connect(peer_ip);
             }
-            if self.router().allow_external_peers() {
+            // Determine if the heartbeat schedule allows peer discovery.
+            let allow_discovery = self.router().heartbeat_plan().map_or(true, |plan| plan.allow_discovery);
+            if self.router().allow_external_peers() && allow_discovery {
                 // Request more peers from the connected peers.
                 for peer_ip in self.router().connected_peers().into_iter().choose_multiple(rng, 3) {
                     self.send(peer_ip, Message::PeerRequest(PeerRequest));
@@ -0,0 +1,130 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::messages::{BlockRequest, PartialBlock};
+use snarkvm::prelude::{
+    block::{Block, Transaction},
+    Network,
+};
+
+use indexmap::{IndexMap, IndexSet};
+use parking_lot::RwLock;
+use std::net::SocketAddr;
+
+/// The maximum number of recent unconfirmed transactions to retain for block reconstruction.
+const MAX_RECENT_TRANSACTIONS: usize = 1 << 13;
+/// The maximum number of recently-announced blocks to retain, to serve `GetBlockTransactions` requests.
+const MAX_ANNOUNCED_BLOCKS: usize = 8;
+/// The maximum number of compact blocks to retain, while awaiting their missing transactions.
+const MAX_PENDING_BLOCKS: usize = 8;
+
+/// The state for compact block announcements.
+#[derive(Debug)]
+pub struct Announcements<N: Network> {
+    /// The recent unconfirmed transactions, used to reconstruct compact blocks.
+    recent_transactions: RwLock<IndexMap<N::TransactionID, Transaction<N>>>,
+    /// The recently-announced blocks, used to serve missing transactions to peers.
+    announced_blocks: RwLock<IndexMap<N::BlockHash, Block<N>>>,
+    /// The partial blocks awaiting their missing transactions, along with the announcing peer.
+    pending_blocks: RwLock<IndexMap<N::BlockHash, (SocketAddr, Vec<u32>, PartialBlock<N>)>>,
+    /// The full block requests sent as a fallback for a failed reconstruction.
+    fallback_requests: RwLock<IndexSet<(SocketAddr, BlockRequest)>>,
+}
+
+impl<N: Network> Default for Announcements<N> {
+    /// Initializes a new instance of the announcements.
+    fn default() -> Self {
+        Self {
+            recent_transactions: Default::default(),
+            announced_blocks: Default::default(),
+            pending_blocks: Default::default(),
+            fallback_requests: Default::default(),
+        }
+    }
+}
+
+impl<N: Network> Announcements<N> {
+    /// Inserts the given unconfirmed transaction, evicting the oldest transaction if the limit is reached.
+    pub fn insert_transaction(&self, transaction: Transaction<N>) {
+        Self::insert_bounded(&self.recent_transactions, transaction.id(), transaction, MAX_RECENT_TRANSACTIONS);
+    }
+
+    /// Returns the recent unconfirmed transactions.
+    pub fn recent_transactions(&self) -> Vec<(N::TransactionID, Transaction<N>)> {
+        self.recent_transactions.read().iter().map(|(id, transaction)| (*id, transaction.clone())).collect()
+    }
+
+    /// Removes the transactions in the given block from the recent unconfirmed transactions.
+    pub fn remove_transactions(&self, block: &Block<N>) {
+        let mut recent_transactions = self.recent_transactions.write();
+        for transaction_id in block.transaction_ids() {
+            recent_transactions.shift_remove(transaction_id);
+        }
+    }
+
+    /// Inserts the given block as announced.
+    pub fn insert_announced_block(&self, block: Block<N>) {
+        Self::insert_bounded(&self.announced_blocks, block.hash(), block, MAX_ANNOUNCED_BLOCKS);
+    }
+
+    /// Returns the announced block for the given block hash, if it exists.
+    pub fn get_announced_block(&self, block_hash: &N::BlockHash) -> Option<Block<N>> {
+        self.announced_blocks.read().get(block_hash).cloned()
+    }
+
+    /// Inserts the given partial block, awaiting the given missing positions from the given peer.
+    pub fn insert_pending_block(&self, peer_ip: SocketAddr, positions: Vec<u32>, partial: PartialBlock<N>) {
+        let block_hash = partial.compact().block_hash;
+        Self::insert_bounded(&self.pending_blocks, block_hash, (peer_ip, positions, partial), MAX_PENDING_BLOCKS);
+    }
+
+    /// Removes and returns the partial block for the given block hash and its requested positions,
+    /// if the missing transactions were requested from the given peer.
+    pub fn remove_pending_block(
+        &self,
+        peer_ip: SocketAddr,
+        block_hash: &N::BlockHash,
+    ) -> Option<(Vec<u32>, PartialBlock<N>)> {
+        let mut pending_blocks = self.pending_blocks.write();
+        match pending_blocks.get(block_hash) {
+            Some((pending_ip, ..)) if *pending_ip == peer_ip => {
+                pending_blocks.shift_remove(block_hash).map(|(_, positions, partial)| (positions, partial))
+            }
+            _ => None,
+        }
+    }
+
+    /// Inserts the given block request, as a fallback for the given peer's block announcement.
+    pub fn insert_fallback_request(&self, peer_ip: SocketAddr, request: BlockRequest) {
+        let mut fallback_requests = self.fallback_requests.write();
+        if fallback_requests.len() >= MAX_PENDING_BLOCKS {
+            fallback_requests.shift_remove_index(0);
+        }
+        fallback_requests.insert((peer_ip, request));
+    }
+
+    /// Removes the given block request, returning `true` if it was a fallback for a block announcement.
+    pub fn remove_fallback_request(&self, peer_ip: SocketAddr, request: &BlockRequest) -> bool {
+        self.fallback_requests.write().shift_remove(&(peer_ip, *request))
+    }
+
+    /// Inserts the given key-value pair into the given map, evicting the oldest entry if the limit is reached.
+    fn insert_bounded<K: Eq + core::hash::Hash, V>(map: &RwLock<IndexMap<K, V>>, key: K, value: V, limit: usize) {
+        let mut map = map.write();
+        if map.len() >= limit && !map.contains_key(&key) {
+            map.shift_remove_index(0);
+        }
+        map.insert(key, value);
+    }
+}
@@ -0,0 +1,369 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::Peer;
+use snarkvm::prelude::Network;
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{self, SyncSender, TrySendError},
+    },
+    thread,
+    time::Duration,
+};
+
+/// The default size of the audit log file at which it is rotated, in bytes.
+pub const DEFAULT_AUDIT_LOG_MAX_SIZE: u64 = 64 * 1024 * 1024;
+/// The number of rotated audit log files that are retained, in addition to the current one.
+pub const AUDIT_LOG_ROTATED_FILES: usize = 3;
+/// The maximum number of audit events waiting to be written, before new events are dropped.
+const AUDIT_LOG_QUEUE_DEPTH: usize = 1024;
+
+/// The kind of a peer connection event.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEventKind {
+    /// A connection was established, and the handshake is about to start.
+    Connect,
+    /// The handshake with the peer succeeded.
+    HandshakeSuccess,
+    /// The handshake with the peer failed.
+    HandshakeFailure,
+    /// The peer was disconnected.
+    Disconnect,
+    /// The peer was restricted for misbehavior.
+    Ban,
+}
+
+/// The side that initiated a connection or a disconnection.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Initiator {
+    /// This node.
+    Local,
+    /// The peer.
+    Remote,
+}
+
+/// A peer connection event, as written to the audit log.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEvent {
+    /// The UTC epoch timestamp of the event.
+    pub timestamp: i64,
+    /// The kind of the event.
+    pub event: AuditEventKind,
+    /// The IP address of the peer.
+    pub peer_ip: SocketAddr,
+    /// The Aleo address claimed by the peer, if the handshake got that far.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    /// The node type of the peer, if the handshake got that far.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node_type: Option<String>,
+    /// The message version negotiated with the peer, if the handshake succeeded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<u32>,
+    /// The side that initiated the connection or the disconnection.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub initiator: Option<Initiator>,
+    /// The reason for a failed handshake, a disconnection, or a ban.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// The duration of the session in seconds, for a disconnection.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_secs: Option<u64>,
+}
+
+impl AuditEvent {
+    /// Initializes a new audit event of the given kind for the given peer, timestamped now.
+    pub fn new(event: AuditEventKind, peer_ip: SocketAddr) -> Self {
+        Self {
+            timestamp: time::OffsetDateTime::now_utc().unix_timestamp(),
+            event,
+            peer_ip,
+            address: None,
+            node_type: None,
+            version: None,
+            initiator: None,
+            reason: None,
+            session_secs: None,
+        }
+    }
+
+    /// Sets the claimed address, node type, and negotiated version of the given connected peer.
+    pub fn with_peer<N: Network>(mut self, peer: &Peer<N>) -> Self {
+        self.address = Some(peer.address().to_string());
+        self.node_type = Some(peer.node_type().to_string());
+        self.version = Some(peer.version());
+        self
+    }
+
+    /// Sets the side that initiated the connection or the disconnection.
+    pub fn with_initiator(mut self, initiator: Initiator) -> Self {
+        self.initiator = Some(initiator);
+        self
+    }
+
+    /// Sets the reason of the event.
+    pub fn with_reason(mut self, reason: impl Into<String>) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+
+    /// Sets the duration of the session.
+    pub fn with_session(mut self, session: Duration) -> Self {
+        self.session_secs = Some(session.as_secs());
+        self
+    }
+}
+
+/// Returns the path of the rotated audit log with the given index, where `1` is the most recent.
+pub fn rotated_audit_log_path(path: &Path, index: usize) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(format!(".{index}"));
+    path.with_file_name(file_name)
+}
+
+/// Reads the audit events from the given audit log and its rotated files, from the oldest to the newest.
+/// The lines that are not valid audit events (e.g. a line truncated by a crash) are skipped.
+pub fn read_audit_log(path: &Path) -> Result<Vec<AuditEvent>> {
+    let mut paths: Vec<_> = (1..=AUDIT_LOG_ROTATED_FILES).rev().map(|i| rotated_audit_log_path(path, i)).collect();
+    paths.push(path.to_path_buf());
+    // Ensure at least one of the files exists.
+    if !paths.iter().any(|path| path.exists()) {
+        bail!("The audit log '{}' does not exist", path.display())
+    }
+
+    let mut events = Vec::new();
+    for path in paths.iter().filter(|path| path.exists()) {
+        for line in BufReader::new(File::open(path)?).lines() {
+            if let Ok(event) = serde_json::from_str(&line?) {
+                events.push(event);
+            }
+        }
+    }
+    Ok(events)
+}
+
+/// The writer of the audit log, which appends one JSON line per event and rotates the file once it grows too large.
+struct AuditLogWriter {
+    /// The path of the audit log.
+    path: PathBuf,
+    /// The size of the audit log file at which it is rotated, in bytes.
+    max_size: u64,
+    /// The current audit log file.
+    file: File,
+    /// The current size of the audit log file, in bytes.
+    size: u64,
+}
+
+impl AuditLogWriter {
+    /// Opens the audit log at the given path, appending to it if it already exists.
+    fn open(path: PathBuf, max_size: u64) -> Result<Self> {
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self { path, max_size, file, size })
+    }
+
+    /// Appends the given event to the audit log.
+    fn write(&mut self, event: &AuditEvent) -> Result<()> {
+        let mut line = serde_json::to_vec(event)?;
+        line.push(b'\n');
+        // Rotate the audit log, if the line would exceed the maximum size.
+        if self.size > 0 && self.size + line.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
+        self.file.write_all(&line)?;
+        self.file.flush()?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+
+    /// Shifts the rotated files by one, dropping the oldest one, and starts a new audit log file.
+    fn rotate(&mut self) -> Result<()> {
+        for index in (1..AUDIT_LOG_ROTATED_FILES).rev() {
+            let from = rotated_audit_log_path(&self.path, index);
+            if from.exists() {
+                fs::rename(from, rotated_audit_log_path(&self.path, index + 1))?;
+            }
+        }
+        fs::rename(&self.path, rotated_audit_log_path(&self.path, 1))?;
+        self.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+/// The audit log of the peer connection events.
+///
+/// The events are handed to a dedicated writer thread through a bounded queue, so recording an event never
+/// blocks the networking tasks; if the writer falls behind and the queue is full, the event is dropped.
+pub struct AuditLog {
+    /// The path of the audit log.
+    path: PathBuf,
+    /// The sender of the events to the writer thread.
+    sender: SyncSender<AuditEvent>,
+    /// The number of events that were dropped.
+    num_dropped: AtomicU64,
+}
+
+impl AuditLog {
+    /// Opens the audit log at the given path, rotating it at the given size, and starts its writer thread.
+    pub fn start(path: impl Into<PathBuf>, max_size: u64) -> Result<Self> {
+        Self::start_with_queue_depth(path.into(), max_size, AUDIT_LOG_QUEUE_DEPTH)
+    }
+
+    /// Opens the audit log with the given queue depth, and starts its writer thread.
+    fn start_with_queue_depth(path: PathBuf, max_size: u64, queue_depth: usize) -> Result<Self> {
+        // Ensure the maximum size is nonzero.
+        if max_size == 0 {
+            bail!("The maximum size of the audit log must be nonzero")
+        }
+        let mut writer = AuditLogWriter::open(path.clone(), max_size)?;
+        let (sender, receiver) = mpsc::sync_channel::<AuditEvent>(queue_depth);
+        thread::Builder::new().name("audit-log".to_string()).spawn(move || {
+            // The thread exits once the audit log is dropped.
+            while let Ok(event) = receiver.recv() {
+                if let Err(error) = writer.write(&event) {
+                    warn!("Unable to write to the peer audit log '{}' - {error}", writer.path.display());
+                }
+            }
+        })?;
+        Ok(Self { path, sender, num_dropped: Default::default() })
+    }
+
+    /// Returns the path of the audit log.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the number of events that were dropped, as the writer was unable to keep up.
+    pub fn num_dropped(&self) -> u64 {
+        self.num_dropped.load(Ordering::Relaxed)
+    }
+
+    /// Queues the given event to be written to the audit log, or drops it if the queue is full.
+    pub fn record(&self, event: AuditEvent) {
+        if let Err(TrySendError::Full(_) | TrySendError::Disconnected(_)) = self.sender.try_send(event) {
+            self.num_dropped.fetch_add(1, Ordering::Relaxed);
+            #[cfg(feature = "metrics")]
+            metrics::increment_counter(metrics::router::DROPPED_AUDIT_EVENTS);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Instant;
+
+    /// Returns a fresh path for an audit log.
+    fn sample_path(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("snarkos-audit-log-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        directory.join("peers.jsonl")
+    }
+
+    /// Waits until the audit log holds the given number of events.
+    fn wait_for_events(path: &Path, num_events: usize) -> Vec<AuditEvent> {
+        let start = Instant::now();
+        loop {
+            let events = read_audit_log(path).unwrap_or_default();
+            if events.len() >= num_events || start.elapsed() > Duration::from_secs(5) {
+                return events;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    fn sample_event(port: u16) -> AuditEvent {
+        AuditEvent::new(AuditEventKind::Connect, SocketAddr::from(([127, 0, 0, 1], port)))
+            .with_initiator(Initiator::Remote)
+    }
+
+    #[test]
+    fn test_audit_event_serialization() {
+        let event = sample_event(4130).with_reason("Some reason").with_session(Duration::from_millis(2_500));
+        let line = serde_json::to_string(&event).unwrap();
+        assert!(line.contains(r#""event":"connect""#));
+        assert!(line.contains(r#""initiator":"remote""#));
+        assert!(line.contains(r#""session_secs":2"#));
+        // The fields that are not set are omitted.
+        assert!(!line.contains("address"));
+        assert_eq!(serde_json::from_str::<AuditEvent>(&line).unwrap(), event);
+    }
+
+    #[test]
+    fn test_audit_log_rotation() {
+        let path = sample_path("rotation");
+        let line_size = serde_json::to_vec(&sample_event(4130)).unwrap().len() as u64 + 1;
+        // Rotate the audit log after every two events.
+        let audit_log = AuditLog::start(&path, 2 * line_size).unwrap();
+        let num_events = 2 * (AUDIT_LOG_ROTATED_FILES + 1) + 2;
+        for port in 0..num_events {
+            audit_log.record(sample_event(port as u16));
+        }
+        // The oldest events were dropped along with the oldest rotated file.
+        let events = wait_for_events(&path, 2 * (AUDIT_LOG_ROTATED_FILES + 1));
+        let ports: Vec<_> = events.iter().map(|event| event.peer_ip.port() as usize).collect();
+        assert_eq!(ports, (2..num_events).collect::<Vec<_>>());
+        assert!(rotated_audit_log_path(&path, AUDIT_LOG_ROTATED_FILES).exists());
+        assert!(!rotated_audit_log_path(&path, AUDIT_LOG_ROTATED_FILES + 1).exists());
+        assert_eq!(audit_log.num_dropped(), 0);
+    }
+
+    #[test]
+    fn test_audit_log_skips_malformed_lines() {
+        let path = sample_path("malformed");
+        let audit_log = AuditLog::start(&path, DEFAULT_AUDIT_LOG_MAX_SIZE).unwrap();
+        audit_log.record(sample_event(1));
+        wait_for_events(&path, 1);
+        // Append a truncated line.
+        OpenOptions::new().append(true).open(&path).unwrap().write_all(b"{\"timestamp\":1,\n").unwrap();
+        audit_log.record(sample_event(2));
+        let events = wait_for_events(&path, 2);
+        assert_eq!(events.iter().map(|event| event.peer_ip.port()).collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_audit_log_drops_when_full() {
+        let path = sample_path("full");
+        let audit_log = AuditLog::start_with_queue_depth(path.clone(), DEFAULT_AUDIT_LOG_MAX_SIZE, 0).unwrap();
+        // With a rendezvous queue, the events are dropped unless the writer is waiting.
+        thread::sleep(Duration::from_millis(100));
+        for port in 0..100 {
+            audit_log.record(sample_event(port));
+        }
+        assert!(audit_log.num_dropped() > 0);
+        // The events that were not dropped are written.
+        let num_accepted = 100 - audit_log.num_dropped() as usize;
+        assert_eq!(wait_for_events(&path, num_accepted).len(), num_accepted);
+    }
+
+    #[test]
+    fn test_read_missing_audit_log() {
+        assert!(read_audit_log(&sample_path("missing")).is_err());
+    }
+}
@@ -0,0 +1,147 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::messages::BlockResponse;
+use snarkvm::prelude::Network;
+
+use parking_lot::Mutex;
+use std::net::SocketAddr;
+use tokio::sync::Notify;
+
+/// The maximum number of block responses awaiting processing.
+pub const MAX_QUEUED_BLOCK_RESPONSES: usize = 64;
+
+/// A block response awaiting processing, with its blocks still serialized.
+#[derive(Debug)]
+pub struct QueuedBlockResponse<N: Network> {
+    /// The IP of the peer that sent the block response.
+    pub peer_ip: SocketAddr,
+    /// The block response.
+    pub response: BlockResponse<N>,
+    /// Whether the block request was a fallback for a block announcement.
+    pub is_announced: bool,
+}
+
+/// A bounded queue of the block responses awaiting processing.
+///
+/// Block responses are processed by a dedicated worker in ascending order of their start height,
+/// so that the response extending the current tip is applied first.
+#[derive(Debug, Default)]
+pub struct BlockResponseQueue<N: Network> {
+    /// The block responses awaiting processing.
+    responses: Mutex<Vec<QueuedBlockResponse<N>>>,
+    /// The notifier for the worker, signaled when a block response is enqueued.
+    notify: Notify,
+}
+
+impl<N: Network> BlockResponseQueue<N> {
+    /// Returns the number of block responses awaiting processing.
+    pub fn len(&self) -> usize {
+        self.responses.lock().len()
+    }
+
+    /// Returns `true` if there are no block responses awaiting processing.
+    pub fn is_empty(&self) -> bool {
+        self.responses.lock().is_empty()
+    }
+
+    /// Enqueues the given block response, and returns `false` if the queue is full.
+    pub fn push(&self, response: QueuedBlockResponse<N>) -> bool {
+        let mut responses = self.responses.lock();
+        if responses.len() >= MAX_QUEUED_BLOCK_RESPONSES {
+            return false;
+        }
+        responses.push(response);
+        #[cfg(feature = "metrics")]
+        metrics::gauge(metrics::router::QUEUED_BLOCK_RESPONSES, responses.len() as f64);
+        drop(responses);
+        // Signal the worker.
+        self.notify.notify_one();
+        true
+    }
+
+    /// Dequeues the block response with the lowest start height, waiting for one if the queue is empty.
+    pub async fn pop(&self) -> QueuedBlockResponse<N> {
+        loop {
+            if let Some(response) = self.try_pop() {
+                return response;
+            }
+            // Note: A signal sent before this point is retained, so a concurrent `push` is not missed.
+            self.notify.notified().await;
+        }
+    }
+
+    /// Dequeues the block response with the lowest start height, if one exists.
+    fn try_pop(&self) -> Option<QueuedBlockResponse<N>> {
+        let mut responses = self.responses.lock();
+        let (index, _) = responses.iter().enumerate().min_by_key(|(_, queued)| queued.response.request.start_height)?;
+        let response = responses.swap_remove(index);
+        #[cfg(feature = "metrics")]
+        metrics::gauge(metrics::router::QUEUED_BLOCK_RESPONSES, responses.len() as f64);
+        Some(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages::{BlockRequest, DataBlocks};
+    use snarkvm::ledger::narwhal::Data;
+
+    use std::{sync::Arc, time::Duration};
+
+    type CurrentNetwork = snarkvm::prelude::MainnetV0;
+
+    fn sample_response(start_height: u32) -> QueuedBlockResponse<CurrentNetwork> {
+        let request = BlockRequest { start_height, end_height: start_height + 1 };
+        let response = BlockResponse { request, blocks: Data::Object(DataBlocks(vec![])) };
+        QueuedBlockResponse { peer_ip: SocketAddr::from(([127, 0, 0, 1], 4130)), response, is_announced: false }
+    }
+
+    #[tokio::test]
+    async fn test_queue_is_bounded_and_ordered() {
+        let queue = BlockResponseQueue::<CurrentNetwork>::default();
+
+        // Flood the queue with overlapping responses, in reverse order.
+        for start_height in (0..MAX_QUEUED_BLOCK_RESPONSES as u32).rev() {
+            assert!(queue.push(sample_response(start_height)));
+        }
+        assert_eq!(queue.len(), MAX_QUEUED_BLOCK_RESPONSES);
+        // Ensure further responses are dropped.
+        assert!(!queue.push(sample_response(0)));
+        assert_eq!(queue.len(), MAX_QUEUED_BLOCK_RESPONSES);
+
+        // Ensure the responses are dequeued in ascending order of height.
+        for start_height in 0..MAX_QUEUED_BLOCK_RESPONSES as u32 {
+            assert_eq!(queue.pop().await.response.request.start_height, start_height);
+        }
+        assert!(queue.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_pop_waits_for_push() {
+        let queue = Arc::new(BlockResponseQueue::<CurrentNetwork>::default());
+
+        // Start waiting for a response.
+        let queue_clone = queue.clone();
+        let handle = tokio::spawn(async move { queue_clone.pop().await.response.request.start_height });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!handle.is_finished());
+
+        // Enqueue a response, and ensure the waiting worker receives it.
+        assert!(queue.push(sample_response(7)));
+        assert_eq!(tokio::time::timeout(Duration::from_secs(1), handle).await.unwrap().unwrap(), 7);
+        assert!(queue.is_empty());
+    }
+}
@@ -161,6 +161,16 @@ impl<N: Network> Cache<N> {
         Self::refresh_and_insert(&self.seen_outbound_transactions, (peer_ip, transaction))
     }
 
+    /// Removes the solution ID from the cache, so that the solution may be resent to the given peer.
+    pub fn remove_outbound_solution(&self, peer_ip: SocketAddr, solution_id: SolutionID<N>) -> bool {
+        self.seen_outbound_solutions.write().remove(&(peer_ip, solution_id)).is_some()
+    }
+
+    /// Removes the transaction ID from the cache, so that the transaction may be resent to the given peer.
+    pub fn remove_outbound_transaction(&self, peer_ip: SocketAddr, transaction: N::TransactionID) -> bool {
+        self.seen_outbound_transactions.write().remove(&(peer_ip, transaction)).is_some()
+    }
+
     /// Returns `true` if the cache contains a peer request from the given peer.
     pub fn contains_outbound_peer_request(&self, peer_ip: SocketAddr) -> bool {
         self.seen_outbound_peer_requests.read().get(&peer_ip).map(|r| *r > 0).unwrap_or(false)
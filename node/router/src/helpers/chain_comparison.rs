@@ -0,0 +1,145 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    messages::{BlockByHashRequest, BlocksByHash},
+    Outbound,
+};
+use snarkvm::prelude::Network;
+
+use anyhow::{bail, ensure, Result};
+use std::{net::SocketAddr, time::Duration};
+use tokio::sync::oneshot;
+
+/// The maximum number of blocks that can be compared with a peer at once.
+pub const MAXIMUM_COMPARED_BLOCKS: u32 = 1_000;
+/// The duration in seconds to wait for a `BlockByHashResponse` from a peer.
+pub const BLOCK_BY_HASH_TIMEOUT_IN_SECS: u64 = 10;
+
+/// A `BlockByHashRequest` that is awaiting a response from a peer.
+pub struct PendingBlockByHash<N: Network> {
+    /// The request sent to the peer.
+    pub request: BlockByHashRequest<N>,
+    /// The callback for the blocks in the response.
+    pub callback: oneshot::Sender<BlocksByHash<N>>,
+}
+
+/// The result of comparing a range of local blocks with the chain of a peer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ChainComparison {
+    /// The starting block height (inclusive).
+    pub start_height: u32,
+    /// The ending block height (exclusive).
+    pub end_height: u32,
+    /// The latest height in the range at which the peer has the local block, up to the divergence.
+    pub last_common_height: Option<u32>,
+    /// The first height in the range at which the peer does not have the local block.
+    pub divergent_height: Option<u32>,
+}
+
+impl ChainComparison {
+    /// Initializes a new comparison for the given range, from whether the peer knows each local block in order.
+    ///
+    /// The comparison may stop early at the first unknown block, so `known` can be shorter than the range.
+    pub fn new(start_height: u32, end_height: u32, known: &[bool]) -> Self {
+        // Find the first local block that is unknown to the peer.
+        let divergence = known.iter().position(|is_known| !is_known);
+        let num_common = divergence.unwrap_or(known.len()) as u32;
+        Self {
+            start_height,
+            end_height,
+            last_common_height: num_common.checked_sub(1).map(|offset| start_height + offset),
+            divergent_height: divergence.map(|offset| start_height + offset as u32),
+        }
+    }
+
+    /// Returns `true` if the peer has every local block in the range.
+    pub fn is_consistent(&self) -> bool {
+        self.divergent_height.is_none()
+    }
+}
+
+/// Compares the given local block hashes, starting at the given height, with the chain of the given peer.
+///
+/// The hashes are requested from the peer with `BlockByHashRequest`s, stopping at the first hash it does not know.
+pub async fn compare_chain<N: Network, O: Outbound<N>>(
+    node: &O,
+    peer_ip: SocketAddr,
+    start_height: u32,
+    local_hashes: &[N::BlockHash],
+) -> Result<ChainComparison> {
+    // Ensure the number of blocks is within the bounds.
+    ensure!(
+        local_hashes.len() <= MAXIMUM_COMPARED_BLOCKS as usize,
+        "Cannot compare more than {MAXIMUM_COMPARED_BLOCKS} blocks at once"
+    );
+    let end_height = start_height + local_hashes.len() as u32;
+
+    let mut known = Vec::with_capacity(local_hashes.len());
+    for block_hashes in local_hashes.chunks(BlockByHashRequest::<N>::MAXIMUM_NUMBER_OF_HASHES) {
+        // Request the blocks from the peer.
+        let callback = node.request_blocks_by_hash(peer_ip, block_hashes.to_vec())?;
+        let blocks = match tokio::time::timeout(Duration::from_secs(BLOCK_BY_HASH_TIMEOUT_IN_SECS), callback).await {
+            Ok(Ok(blocks)) => blocks,
+            Ok(Err(_)) => bail!("Peer '{peer_ip}' disconnected before responding to the block-by-hash request"),
+            Err(_) => {
+                node.router().remove_block_by_hash_request(&peer_ip);
+                bail!("Peer '{peer_ip}' did not respond to the block-by-hash request in time")
+            }
+        };
+        known.extend(blocks.0.iter().map(Option::is_some));
+        // Stop at the first divergence.
+        if known.contains(&false) {
+            break;
+        }
+    }
+    Ok(ChainComparison::new(start_height, end_height, &known))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_consistent_chains() {
+        let comparison = ChainComparison::new(10, 15, &[true; 5]);
+        assert!(comparison.is_consistent());
+        assert_eq!(comparison.last_common_height, Some(14));
+        assert_eq!(comparison.divergent_height, None);
+
+        // Check that an empty range has no common height.
+        let comparison = ChainComparison::new(10, 10, &[]);
+        assert!(comparison.is_consistent());
+        assert_eq!(comparison.last_common_height, None);
+    }
+
+    #[test]
+    fn test_divergent_chains() {
+        // The peer has the blocks up to height 12, and is on a different branch from height 13.
+        let comparison = ChainComparison::new(10, 20, &[true, true, true, false]);
+        assert!(!comparison.is_consistent());
+        assert_eq!(comparison.last_common_height, Some(12));
+        assert_eq!(comparison.divergent_height, Some(13));
+
+        // Check that later known blocks do not mask the divergence.
+        let comparison = ChainComparison::new(10, 20, &[true, false, true]);
+        assert_eq!(comparison.last_common_height, Some(10));
+        assert_eq!(comparison.divergent_height, Some(11));
+
+        // Check that a divergence at the start of the range has no common height.
+        let comparison = ChainComparison::new(10, 20, &[false]);
+        assert_eq!(comparison.last_common_height, None);
+        assert_eq!(comparison.divergent_height, Some(10));
+    }
+}
@@ -0,0 +1,262 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use parking_lot::RwLock;
+use std::{collections::HashMap, fmt, net::SocketAddr};
+
+/// The clock skew in milliseconds against the network, above which the node warns the operator.
+pub const CLOCK_SKEW_WARNING_IN_MS: i64 = 2_000;
+/// The clock skew in milliseconds against the network, above which a validator stops proposing batches.
+/// Note: This matches the maximum timestamp delta accepted by the BFT.
+pub const CLOCK_SKEW_HALT_IN_MS: i64 = 10_000;
+/// The minimum number of peers with a clock sample, before the clock skew is estimated.
+pub const MINIMUM_CLOCK_SKEW_PEERS: usize = 3;
+/// The maximum round-trip time in milliseconds of a clock sample; slower samples are too imprecise to be used.
+const MAXIMUM_CLOCK_SAMPLE_RTT_IN_MS: i64 = 5_000;
+/// The weight of a new clock sample in the smoothed offset of a peer, as a divisor.
+const CLOCK_SAMPLE_SMOOTHING: i64 = 4;
+
+/// Returns the current UNIX timestamp in milliseconds.
+pub fn now_unix_millis() -> i64 {
+    (time::OffsetDateTime::now_utc().unix_timestamp_nanos() / 1_000_000) as i64
+}
+
+/// The severity of the clock skew of the node against the network.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ClockSkewLevel {
+    /// The clock is in sync with the network, or there are not enough samples to tell.
+    #[default]
+    Normal,
+    /// The clock is skewed beyond the warning threshold.
+    Warning,
+    /// The clock is skewed beyond the timestamp tolerance of the BFT.
+    Halt,
+}
+
+impl ClockSkewLevel {
+    /// Returns the level of the given clock skew in milliseconds.
+    pub const fn from_offset(offset_in_ms: i64) -> Self {
+        match offset_in_ms.unsigned_abs() {
+            skew if skew > CLOCK_SKEW_HALT_IN_MS as u64 => Self::Halt,
+            skew if skew > CLOCK_SKEW_WARNING_IN_MS as u64 => Self::Warning,
+            _ => Self::Normal,
+        }
+    }
+
+    /// Returns `true` if the clock is skewed beyond the timestamp tolerance of the BFT.
+    pub const fn is_halt(&self) -> bool {
+        matches!(self, Self::Halt)
+    }
+}
+
+impl fmt::Display for ClockSkewLevel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Normal => write!(f, "normal"),
+            Self::Warning => write!(f, "warning"),
+            Self::Halt => write!(f, "halt"),
+        }
+    }
+}
+
+/// The estimate of the clock skew of the node against the network, from the `Ping` and `Pong` timestamps.
+///
+/// The offset of each peer is smoothed over its samples, and the skew of the node is the median offset
+/// over the peers, so that a minority of peers with a wrong (or lying) clock cannot move the estimate.
+#[derive(Debug, Default)]
+pub struct ClockSkew {
+    /// The map of the peer IP to its smoothed clock offset in milliseconds.
+    offsets: RwLock<HashMap<SocketAddr, i64>>,
+    /// The current level of the clock skew.
+    level: RwLock<ClockSkewLevel>,
+}
+
+impl ClockSkew {
+    /// Initializes a new instance of the clock skew estimate.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of peers with a clock sample.
+    pub fn num_peers(&self) -> usize {
+        self.offsets.read().len()
+    }
+
+    /// Returns the estimated clock skew of the network against the node in milliseconds,
+    /// or `None` if there are not enough peers with a clock sample.
+    pub fn offset_in_ms(&self) -> Option<i64> {
+        let mut offsets = self.offsets.read().values().copied().collect::<Vec<_>>();
+        if offsets.len() < MINIMUM_CLOCK_SKEW_PEERS {
+            return None;
+        }
+        offsets.sort_unstable();
+        // Note: The lower median is used for an even number of peers, to avoid averaging an outlier.
+        Some(offsets[(offsets.len() - 1) / 2])
+    }
+
+    /// Returns the current level of the clock skew.
+    pub fn level(&self) -> ClockSkewLevel {
+        *self.level.read()
+    }
+
+    /// Records a clock sample from the given peer, where `sent` and `received` are the local timestamps
+    /// of the `Ping` and `Pong`, and `answered` is the timestamp of the peer when it answered the `Ping`.
+    /// Returns the new level of the clock skew, if it changed.
+    pub fn observe(&self, peer_ip: SocketAddr, sent: i64, answered: i64, received: i64) -> Option<ClockSkewLevel> {
+        // Ensure the round-trip time is plausible, and small enough for the sample to be precise.
+        let rtt = received.saturating_sub(sent);
+        if !(0..=MAXIMUM_CLOCK_SAMPLE_RTT_IN_MS).contains(&rtt) {
+            return None;
+        }
+        // Compute the offset of the peer, assuming the latency is symmetric.
+        let sample = answered.saturating_sub(sent.saturating_add(rtt / 2));
+        // Update the smoothed offset of the peer.
+        self.offsets
+            .write()
+            .entry(peer_ip)
+            .and_modify(|offset| {
+                *offset = offset.saturating_add(sample.saturating_sub(*offset) / CLOCK_SAMPLE_SMOOTHING)
+            })
+            .or_insert(sample);
+        self.update_level()
+    }
+
+    /// Removes the clock samples of the given peer, and returns the new level of the clock skew, if it changed.
+    pub fn remove_peer(&self, peer_ip: SocketAddr) -> Option<ClockSkewLevel> {
+        self.offsets.write().remove(&peer_ip)?;
+        self.update_level()
+    }
+
+    /// Updates the level of the clock skew, and returns the new level, if it changed.
+    fn update_level(&self) -> Option<ClockSkewLevel> {
+        let level = self.offset_in_ms().map_or(ClockSkewLevel::Normal, ClockSkewLevel::from_offset);
+        let previous = std::mem::replace(&mut *self.level.write(), level);
+        (previous != level).then_some(level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::net::{IpAddr, Ipv4Addr};
+
+    /// Returns a sample peer IP with the given port.
+    fn sample_peer_ip(port: u16) -> SocketAddr {
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port)
+    }
+
+    /// Records a clock sample with the given offset and round-trip time from the given peer.
+    fn observe_offset(clock_skew: &ClockSkew, port: u16, offset: i64, rtt: i64) -> Option<ClockSkewLevel> {
+        let sent = 1_700_000_000_000;
+        clock_skew.observe(sample_peer_ip(port), sent, sent + rtt / 2 + offset, sent + rtt)
+    }
+
+    #[test]
+    fn test_offset_requires_minimum_peers() {
+        let clock_skew = ClockSkew::new();
+        for port in 0..MINIMUM_CLOCK_SKEW_PEERS as u16 - 1 {
+            assert_eq!(observe_offset(&clock_skew, port, 60_000, 100), None);
+            assert_eq!(clock_skew.offset_in_ms(), None);
+        }
+        // Ensure the skew is only estimated once enough peers have a clock sample.
+        assert_eq!(observe_offset(&clock_skew, 100, 60_000, 100), Some(ClockSkewLevel::Halt));
+        assert_eq!(clock_skew.offset_in_ms(), Some(60_000));
+        assert_eq!(clock_skew.num_peers(), MINIMUM_CLOCK_SKEW_PEERS);
+    }
+
+    #[test]
+    fn test_offset_accounts_for_latency() {
+        let clock_skew = ClockSkew::new();
+        // Ensure the round-trip time is compensated for, regardless of the latency of each peer.
+        for (port, rtt) in [(0, 0), (1, 400), (2, 2_000)] {
+            observe_offset(&clock_skew, port, 1_500, rtt);
+        }
+        assert_eq!(clock_skew.offset_in_ms(), Some(1_500));
+
+        // Ensure implausible or imprecise samples are ignored.
+        observe_offset(&clock_skew, 3, 60_000, -10);
+        observe_offset(&clock_skew, 4, 60_000, MAXIMUM_CLOCK_SAMPLE_RTT_IN_MS + 1);
+        assert_eq!(clock_skew.num_peers(), 3);
+    }
+
+    #[test]
+    fn test_offset_is_smoothed() {
+        let clock_skew = ClockSkew::new();
+        for port in 0..3 {
+            observe_offset(&clock_skew, port, 0, 100);
+        }
+        // Ensure a single sample only moves the offset of the peer partially.
+        for port in 0..3 {
+            observe_offset(&clock_skew, port, 4_000, 100);
+        }
+        assert_eq!(clock_skew.offset_in_ms(), Some(1_000));
+        // Ensure the offset converges with repeated samples.
+        for _ in 0..50 {
+            for port in 0..3 {
+                observe_offset(&clock_skew, port, 4_000, 100);
+            }
+        }
+        assert!((3_990..=4_000).contains(&clock_skew.offset_in_ms().unwrap()));
+    }
+
+    #[test]
+    fn test_offset_resists_adversarial_peers() {
+        let clock_skew = ClockSkew::new();
+        // Add the honest peers, with a small spread around the true offset.
+        for (port, offset) in [(0, -120), (1, 30), (2, 80), (3, 150), (4, -40)] {
+            observe_offset(&clock_skew, port, offset, 100);
+        }
+        // Add a minority of lying peers, reporting extreme clocks in both directions.
+        for (port, offset) in [(10, i64::MAX / 4), (11, i64::MIN / 4), (12, 3_600_000), (13, 3_600_000)] {
+            observe_offset(&clock_skew, port, offset, 100);
+        }
+        // Ensure the estimate stays within the range of the honest peers.
+        let offset = clock_skew.offset_in_ms().unwrap();
+        assert!((-120..=150).contains(&offset));
+        assert_eq!(clock_skew.level(), ClockSkewLevel::Normal);
+    }
+
+    #[test]
+    fn test_level_transitions() {
+        let clock_skew = ClockSkew::new();
+        for port in 0..3 {
+            observe_offset(&clock_skew, port, 500, 100);
+        }
+        assert_eq!(clock_skew.level(), ClockSkewLevel::Normal);
+
+        // Ensure the level changes to a warning, once the majority of the peers are skewed beyond the threshold.
+        for port in 10..13 {
+            assert_eq!(observe_offset(&clock_skew, port, CLOCK_SKEW_WARNING_IN_MS + 500, 100), None);
+        }
+        assert_eq!(observe_offset(&clock_skew, 13, CLOCK_SKEW_WARNING_IN_MS + 500, 100), Some(ClockSkewLevel::Warning));
+
+        // Ensure the level changes to a halt, once the skew exceeds the timestamp tolerance of the BFT.
+        for port in 20..30 {
+            observe_offset(&clock_skew, port, -(CLOCK_SKEW_HALT_IN_MS + 1_000), 100);
+        }
+        assert_eq!(clock_skew.level(), ClockSkewLevel::Halt);
+        assert!(clock_skew.level().is_halt());
+
+        // Ensure the level returns to normal, once the skewed peers disconnect.
+        let mut transitions = vec![];
+        for port in (10..14).chain(20..30) {
+            transitions.extend(clock_skew.remove_peer(sample_peer_ip(port)));
+        }
+        assert_eq!(clock_skew.level(), ClockSkewLevel::Normal);
+        assert_eq!(transitions.last(), Some(&ClockSkewLevel::Normal));
+        // Ensure removing an unknown peer does not change the level.
+        assert_eq!(clock_skew.remove_peer(sample_peer_ip(10)), None);
+    }
+}
@@ -0,0 +1,84 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{messages::NodeType, ProxyConfig};
+
+use std::net::SocketAddr;
+
+/// The configuration of the router.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RouterConfig {
+    /// The listener address of the node.
+    pub node_ip: SocketAddr,
+    /// The node type.
+    pub node_type: NodeType,
+    /// The maximum number of connected peers.
+    pub max_peers: u16,
+    /// The trusted peers, which are exempt from the limit of connections per IP.
+    pub trusted_peers: Vec<SocketAddr>,
+    /// Whether the node serves the REST API, which is advertised to peers.
+    pub is_rest_enabled: bool,
+    /// Whether the node engages in P2P gossip to request more peers.
+    pub allow_external_peers: bool,
+    /// Whether the node is part of a development network.
+    pub is_dev: bool,
+    /// The configuration of the proxy for the outbound connections, if enabled.
+    pub proxy: Option<ProxyConfig>,
+}
+
+impl RouterConfig {
+    /// Returns the configuration of a router listening on the given address, which accepts external peers.
+    pub fn new(node_ip: SocketAddr, node_type: NodeType, max_peers: u16) -> Self {
+        Self {
+            node_ip,
+            node_type,
+            max_peers,
+            trusted_peers: vec![],
+            is_rest_enabled: false,
+            allow_external_peers: true,
+            is_dev: false,
+            proxy: None,
+        }
+    }
+
+    /// Sets the trusted peers.
+    pub fn with_trusted_peers(mut self, trusted_peers: &[SocketAddr]) -> Self {
+        self.trusted_peers = trusted_peers.to_vec();
+        self
+    }
+
+    /// Sets whether the node serves the REST API.
+    pub fn with_rest(mut self, is_rest_enabled: bool) -> Self {
+        self.is_rest_enabled = is_rest_enabled;
+        self
+    }
+
+    /// Sets whether the node engages in P2P gossip to request more peers.
+    pub fn with_external_peers(mut self, allow_external_peers: bool) -> Self {
+        self.allow_external_peers = allow_external_peers;
+        self
+    }
+
+    /// Sets whether the node is part of a development network.
+    pub fn with_dev(mut self, is_dev: bool) -> Self {
+        self.is_dev = is_dev;
+        self
+    }
+
+    /// Sets the configuration of the proxy for the outbound connections.
+    pub fn with_proxy(mut self, proxy: Option<ProxyConfig>) -> Self {
+        self.proxy = proxy;
+        self
+    }
+}
@@ -0,0 +1,200 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::Duration;
+
+/// The sync status of the node, as seen by the heartbeat.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SyncStatus {
+    /// The node is behind its peers, and is downloading blocks.
+    Syncing,
+    /// The node is caught up with its peers.
+    Synced,
+}
+
+/// The intervals and actions chosen by the heartbeat schedule until the next heartbeat.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct HeartbeatPlan {
+    /// The duration to wait in between heartbeats.
+    pub heartbeat_interval: Duration,
+    /// The duration to wait in between pings with a connected peer.
+    pub ping_interval: Duration,
+    /// Whether the heartbeat may request more peers from its connected peers.
+    pub allow_discovery: bool,
+}
+
+/// An adaptive heartbeat schedule, which backs off the ping and peer discovery load
+/// when the node is syncing, or when its peer connections are saturated and stable.
+///
+/// The schedule does not depend on the network, and is driven with the node status and peer counts.
+#[derive(Clone, Debug)]
+pub struct HeartbeatSchedule {
+    /// The minimum number of peers, below which peer discovery is never suspended.
+    minimum_peers: usize,
+    /// The maximum number of peers.
+    maximum_peers: usize,
+    /// The base duration to wait in between heartbeats.
+    heartbeat_interval: Duration,
+    /// The base duration to wait in between pings with a connected peer.
+    ping_interval: Duration,
+    /// The number of heartbeats since the last one that allowed peer discovery.
+    ticks_since_discovery: u32,
+    /// The last plan of the schedule.
+    last_plan: Option<HeartbeatPlan>,
+}
+
+impl HeartbeatSchedule {
+    /// The maximum number of peers (dis)connecting in between heartbeats for the connections to be considered stable.
+    const LOW_CHURN: usize = 1;
+    /// The number of heartbeats in between peer discovery rounds, while the connections are saturated and stable.
+    const SATURATED_DISCOVERY_TICKS: u32 = 4;
+    /// The factor by which the ping interval is lengthened while the node is syncing.
+    const SYNCING_PING_FACTOR: u32 = 3;
+
+    /// Initializes a new heartbeat schedule, with the given peer limits and base intervals.
+    pub fn new(
+        minimum_peers: usize,
+        maximum_peers: usize,
+        heartbeat_interval: Duration,
+        ping_interval: Duration,
+    ) -> Self {
+        Self {
+            minimum_peers,
+            maximum_peers,
+            heartbeat_interval,
+            ping_interval,
+            ticks_since_discovery: 0,
+            last_plan: None,
+        }
+    }
+
+    /// Returns the last plan of the schedule, if any.
+    pub const fn last_plan(&self) -> Option<HeartbeatPlan> {
+        self.last_plan
+    }
+
+    /// Returns `true` if the given number of connected peers warrants an immediate peer discovery round.
+    pub const fn is_below_minimum(&self, num_connected: usize) -> bool {
+        num_connected < self.minimum_peers
+    }
+
+    /// Returns the plan for the next heartbeat, given the status of the node, its number of connected peers,
+    /// and the number of peers that connected or disconnected since the previous heartbeat.
+    pub fn next(&mut self, status: SyncStatus, num_connected: usize, num_churned: usize) -> HeartbeatPlan {
+        // Lengthen the ping interval while syncing, to leave the bandwidth to the block download.
+        let ping_interval = match status {
+            SyncStatus::Syncing => self.ping_interval * Self::SYNCING_PING_FACTOR,
+            SyncStatus::Synced => self.ping_interval,
+        };
+        // Determine if peer discovery is allowed.
+        let allow_discovery = if self.is_below_minimum(num_connected) {
+            // Never suspend discovery with too few peers.
+            true
+        } else if status == SyncStatus::Syncing {
+            // Suspend discovery while syncing.
+            false
+        } else if num_connected >= self.maximum_peers && num_churned <= Self::LOW_CHURN {
+            // Reduce the discovery frequency while the connections are saturated and stable.
+            self.ticks_since_discovery + 1 >= Self::SATURATED_DISCOVERY_TICKS
+        } else {
+            true
+        };
+        match allow_discovery {
+            true => self.ticks_since_discovery = 0,
+            false => self.ticks_since_discovery += 1,
+        }
+
+        let plan = HeartbeatPlan { heartbeat_interval: self.heartbeat_interval, ping_interval, allow_discovery };
+        // Log the intervals if they changed.
+        let last_intervals = self.last_plan.map(|last| (last.heartbeat_interval, last.ping_interval));
+        if last_intervals != Some((plan.heartbeat_interval, plan.ping_interval)) {
+            debug!(
+                "Heartbeat every {}s, pinging peers every {}s ({status:?}, {num_connected} peers)",
+                plan.heartbeat_interval.as_secs(),
+                plan.ping_interval.as_secs()
+            );
+        }
+        self.last_plan = Some(plan);
+        plan
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEARTBEAT: Duration = Duration::from_secs(25);
+    const PING: Duration = Duration::from_secs(20);
+
+    fn sample_schedule() -> HeartbeatSchedule {
+        HeartbeatSchedule::new(3, 21, HEARTBEAT, PING)
+    }
+
+    #[test]
+    fn test_synced() {
+        let mut schedule = sample_schedule();
+        assert!(schedule.last_plan().is_none());
+
+        // A synced node with some peers pings at the base interval, and discovers peers on every heartbeat.
+        for _ in 0..10 {
+            let plan = schedule.next(SyncStatus::Synced, 10, 0);
+            assert_eq!(plan, HeartbeatPlan {
+                heartbeat_interval: HEARTBEAT,
+                ping_interval: PING,
+                allow_discovery: true
+            });
+        }
+        assert_eq!(schedule.last_plan().unwrap().ping_interval, PING);
+    }
+
+    #[test]
+    fn test_syncing() {
+        let mut schedule = sample_schedule();
+
+        // A syncing node pings less often, and suspends discovery.
+        let plan = schedule.next(SyncStatus::Syncing, 10, 0);
+        assert_eq!(plan.ping_interval, PING * 3);
+        assert!(!plan.allow_discovery);
+        assert!(!schedule.next(SyncStatus::Syncing, 21, 5).allow_discovery);
+
+        // A syncing node below the minimum number of peers still discovers peers.
+        let plan = schedule.next(SyncStatus::Syncing, 2, 0);
+        assert_eq!(plan.ping_interval, PING * 3);
+        assert!(plan.allow_discovery);
+
+        // Once synced, the node returns to the base intervals.
+        let plan = schedule.next(SyncStatus::Synced, 10, 0);
+        assert_eq!(plan.ping_interval, PING);
+        assert!(plan.allow_discovery);
+    }
+
+    #[test]
+    fn test_saturated() {
+        let mut schedule = sample_schedule();
+
+        // A saturated node with low churn discovers peers on every fourth heartbeat.
+        let allowed = (0..12).map(|_| schedule.next(SyncStatus::Synced, 21, 1).allow_discovery).collect::<Vec<_>>();
+        assert_eq!(allowed.iter().filter(|allowed| **allowed).count(), 3);
+        assert_eq!(&allowed[..4], &[false, false, false, true]);
+
+        // A saturated node with high churn discovers peers on every heartbeat.
+        assert!((0..4).all(|_| schedule.next(SyncStatus::Synced, 21, 5).allow_discovery));
+
+        // A node that drops below the minimum number of peers discovers peers immediately.
+        assert!(!schedule.next(SyncStatus::Synced, 21, 0).allow_discovery);
+        assert!(schedule.is_below_minimum(2));
+        assert!(!schedule.is_below_minimum(3));
+        assert!(schedule.next(SyncStatus::Synced, 2, 19).allow_discovery);
+    }
+}
@@ -0,0 +1,145 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::{Duration, Instant};
+
+/// The misbehavior score at which a peer is disconnected and temporarily restricted.
+pub const MISBEHAVIOR_THRESHOLD: f64 = 100.0;
+/// The duration in seconds over which a misbehavior score decays to half of its value (10 minutes).
+pub const MISBEHAVIOR_HALF_LIFE_IN_SECS: u64 = 600;
+/// The misbehavior score below which the score of a peer is forgotten.
+pub const MISBEHAVIOR_NEGLIGIBLE_SCORE: f64 = 1.0;
+
+/// An offense committed by a peer, which adds to its misbehavior score.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Offense {
+    /// The peer sent a message that is well-encoded, but malformed (e.g. an invalid range).
+    MalformedMessage,
+    /// The peer sent a message payload that failed to deserialize.
+    FailedDeserialization,
+    /// The peer sent an invalid signature.
+    InvalidSignature,
+    /// The peer sent a solution that does not meet the target.
+    BelowTargetSolution,
+    /// The peer sent a response that this node did not request.
+    MismatchedResponse,
+    /// The peer sent a message that is not permitted in the current state of the protocol.
+    ProtocolViolation,
+    /// The peer advertised a block height that cannot have been produced since the latest block.
+    ImplausibleBlockHeight,
+}
+
+impl Offense {
+    /// Returns the amount that the offense adds to the misbehavior score of a peer.
+    pub const fn weight(&self) -> f64 {
+        match self {
+            Self::MalformedMessage => 20.0,
+            Self::FailedDeserialization => 25.0,
+            Self::InvalidSignature => 50.0,
+            Self::BelowTargetSolution => 10.0,
+            Self::MismatchedResponse => 10.0,
+            Self::ProtocolViolation => 20.0,
+            Self::ImplausibleBlockHeight => 20.0,
+        }
+    }
+}
+
+/// The misbehavior score of a peer, which decays exponentially over time.
+#[derive(Copy, Clone, Debug)]
+pub struct MisbehaviorScore {
+    /// The score, as of the last update.
+    score: f64,
+    /// The time of the last update.
+    updated_at: Instant,
+}
+
+impl MisbehaviorScore {
+    /// Initializes a new misbehavior score of zero, as of the given time.
+    pub fn new(now: Instant) -> Self {
+        Self { score: 0.0, updated_at: now }
+    }
+
+    /// Returns the decayed score, as of the given time.
+    pub fn score_at(&self, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        let half_lives = elapsed / Duration::from_secs(MISBEHAVIOR_HALF_LIFE_IN_SECS).as_secs_f64();
+        self.score * 0.5f64.powf(half_lives)
+    }
+
+    /// Records the given offense as of the given time, and returns `true` if the score reached the threshold.
+    pub fn record(&mut self, offense: Offense, now: Instant) -> bool {
+        self.score = self.score_at(now) + offense.weight();
+        self.updated_at = now;
+        self.score >= MISBEHAVIOR_THRESHOLD
+    }
+
+    /// Returns `true` if the score has decayed to a negligible amount, as of the given time.
+    pub fn is_negligible(&self, now: Instant) -> bool {
+        self.score_at(now) < MISBEHAVIOR_NEGLIGIBLE_SCORE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the duration of the given number of half-lives.
+    fn half_lives(n: u32) -> Duration {
+        Duration::from_secs(MISBEHAVIOR_HALF_LIFE_IN_SECS) * n
+    }
+
+    #[test]
+    fn test_score_decays() {
+        let start = Instant::now();
+        let mut score = MisbehaviorScore::new(start);
+        assert!(!score.record(Offense::InvalidSignature, start));
+
+        // Check that the score halves after each half-life.
+        let weight = Offense::InvalidSignature.weight();
+        assert_eq!(score.score_at(start), weight);
+        assert!((score.score_at(start + half_lives(1)) - weight / 2.0).abs() < 1e-9);
+        assert!((score.score_at(start + half_lives(2)) - weight / 4.0).abs() < 1e-9);
+        // Check that the score eventually becomes negligible.
+        assert!(!score.is_negligible(start + half_lives(5)));
+        assert!(score.is_negligible(start + half_lives(6)));
+    }
+
+    #[test]
+    fn test_threshold_is_crossed() {
+        let start = Instant::now();
+        let mut score = MisbehaviorScore::new(start);
+
+        // Check that repeated offenses in quick succession reach the threshold.
+        let offense = Offense::FailedDeserialization;
+        let num_offenses = (MISBEHAVIOR_THRESHOLD / offense.weight()).ceil() as usize;
+        for _ in 1..num_offenses {
+            assert!(!score.record(offense, start));
+        }
+        assert!(score.record(offense, start));
+    }
+
+    #[test]
+    fn test_threshold_is_not_crossed_over_time() {
+        let start = Instant::now();
+        let mut score = MisbehaviorScore::new(start);
+
+        // Check that the same offenses, spread out over time, do not reach the threshold.
+        let offense = Offense::FailedDeserialization;
+        for i in 0..100 {
+            assert!(!score.record(offense, start + half_lives(i)));
+        }
+        // The score converges to twice the weight of the offense.
+        assert!(score.score_at(start + half_lives(99)) < 2.0 * offense.weight());
+    }
+}
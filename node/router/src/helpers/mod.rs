@@ -12,11 +12,71 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod announcements;
+pub use announcements::Announcements;
+
+mod audit_log;
+pub use audit_log::*;
+
+mod block_responses;
+pub use block_responses::*;
+
 mod cache;
 pub use cache::Cache;
 
+mod chain_comparison;
+pub use chain_comparison::*;
+
+mod config;
+pub use config::*;
+
+mod clock_skew;
+pub use clock_skew::*;
+
+mod heartbeat_schedule;
+pub use heartbeat_schedule::*;
+
+mod misbehavior;
+pub use misbehavior::*;
+
 mod peer;
 pub use peer::*;
 
+mod peer_filter;
+pub use peer_filter::*;
+
+mod peer_info;
+pub use peer_info::*;
+
+mod peer_state;
+pub use peer_state::*;
+
+mod port_mapping;
+pub use port_mapping::*;
+
+mod propagation;
+pub use propagation::*;
+
+mod proxy;
+pub use proxy::*;
+
+mod queue_policy;
+pub use queue_policy::*;
+
+mod rejections;
+pub use rejections::*;
+
 mod resolver;
 pub use resolver::*;
+
+mod seeds;
+pub use seeds::*;
+
+mod solution_verifier;
+pub use solution_verifier::*;
+
+mod subnet;
+pub use subnet::*;
+
+mod traffic_class;
+pub use traffic_class::*;
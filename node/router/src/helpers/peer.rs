@@ -12,10 +12,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::messages::{ChallengeRequest, NodeType};
+use crate::{
+    messages::{Capabilities, ChallengeRequest, Feature, NodeType, PeerServices},
+    PeerInfo,
+};
 use snarkvm::prelude::{Address, Network};
 
-use std::{net::SocketAddr, time::Instant};
+use std::{
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
 
 /// The state for each connected peer.
 #[derive(Clone, Debug)]
@@ -26,24 +36,41 @@ pub struct Peer<N: Network> {
     address: Address<N>,
     /// The node type of the peer.
     node_type: NodeType,
-    /// The message version of the peer.
+    /// The message version negotiated with the peer.
     version: u32,
+    /// The flag indicating whether the negotiated message version is deprecated.
+    is_deprecated: bool,
+    /// The protocol capabilities of the peer.
+    capabilities: Capabilities,
+    /// The address at which the peer is reachable, if it advertised one, e.g. as it connects through a proxy.
+    external_addr: Option<SocketAddr>,
     /// The timestamp of the first message received from the peer.
     first_seen: Instant,
     /// The timestamp of the last message received from this peer.
     last_seen: Instant,
+    /// The number of bytes saved by compressing the messages exchanged with the peer.
+    bytes_saved: Arc<AtomicU64>,
 }
 
 impl<N: Network> Peer<N> {
-    /// Initializes a new instance of `Peer`.
-    pub fn new(listening_ip: SocketAddr, challenge_request: &ChallengeRequest<N>) -> Self {
+    /// Initializes a new instance of `Peer`, with the message version negotiated during the handshake.
+    pub fn new(
+        listening_ip: SocketAddr,
+        challenge_request: &ChallengeRequest<N>,
+        version: u32,
+        is_deprecated: bool,
+    ) -> Self {
         Self {
             peer_ip: listening_ip,
             address: challenge_request.address,
             node_type: challenge_request.node_type,
-            version: challenge_request.version,
+            version,
+            is_deprecated,
+            capabilities: challenge_request.capabilities,
+            external_addr: challenge_request.external_addr,
             first_seen: Instant::now(),
             last_seen: Instant::now(),
+            bytes_saved: Default::default(),
         }
     }
 
@@ -52,6 +79,13 @@ impl<N: Network> Peer<N> {
         self.peer_ip
     }
 
+    /// Returns the address at which the peer is reachable by other nodes,
+    /// which is its external address if it advertised one, and its IP address otherwise.
+    /// Note: The external address is unverified, so it is only shared with peers, as any gossiped address.
+    pub fn advertised_ip(&self) -> SocketAddr {
+        self.external_addr.unwrap_or(self.peer_ip)
+    }
+
     /// Returns the Aleo address of the peer.
     pub const fn address(&self) -> Address<N> {
         self.address
@@ -77,11 +111,42 @@ impl<N: Network> Peer<N> {
         self.node_type.is_client()
     }
 
-    /// Returns the message version of the peer.
+    /// Returns `true` if the peer is an archive.
+    pub const fn is_archive(&self) -> bool {
+        self.node_type.is_archive()
+    }
+
+    /// Returns the message version negotiated with the peer.
     pub const fn version(&self) -> u32 {
         self.version
     }
 
+    /// Returns `true` if the message version negotiated with the peer is deprecated.
+    pub const fn is_deprecated(&self) -> bool {
+        self.is_deprecated
+    }
+
+    /// Returns `true` if the peer advertised the capability of the given protocol feature.
+    pub const fn supports_feature(&self, feature: Feature) -> bool {
+        self.capabilities.supports(feature)
+    }
+
+    /// Returns the protocol capabilities of the peer.
+    pub const fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+
+    /// Returns the services offered by the peer.
+    pub const fn services(&self) -> PeerServices {
+        PeerServices::new(self.node_type, self.capabilities.supports_rest())
+    }
+
+    /// Returns the metadata of the peer, as of the given UNIX timestamp.
+    pub fn info(&self, now: u64) -> PeerInfo {
+        let last_seen = now.saturating_sub(self.last_seen.elapsed().as_secs());
+        PeerInfo { last_seen, services: self.services(), ..Default::default() }
+    }
+
     /// Returns the first seen timestamp of the peer.
     pub fn first_seen(&self) -> Instant {
         self.first_seen
@@ -91,6 +156,16 @@ impl<N: Network> Peer<N> {
     pub fn last_seen(&self) -> Instant {
         self.last_seen
     }
+
+    /// Returns the number of bytes saved by compressing the messages exchanged with the peer.
+    pub fn bytes_saved(&self) -> u64 {
+        self.bytes_saved.load(Ordering::Relaxed)
+    }
+
+    /// Returns the counter of the bytes saved by compression, which is shared with the codecs of the connection.
+    pub fn bytes_saved_counter(&self) -> Arc<AtomicU64> {
+        self.bytes_saved.clone()
+    }
 }
 
 impl<N: Network> Peer<N> {
@@ -99,11 +174,6 @@ impl<N: Network> Peer<N> {
         self.node_type = node_type;
     }
 
-    /// Updates the version.
-    pub fn set_version(&mut self, version: u32) {
-        self.version = version;
-    }
-
     /// Updates the last seen timestamp of the peer.
     pub fn set_last_seen(&mut self, last_seen: Instant) {
         self.last_seen = last_seen;
@@ -0,0 +1,177 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    fmt,
+    net::{IpAddr, SocketAddr},
+};
+
+/// The reason a candidate peer address is rejected.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum InvalidPeerAddress {
+    /// The address of this node.
+    Local,
+    /// An unspecified address, e.g. `0.0.0.0`.
+    Unspecified,
+    /// A loopback address, e.g. `127.0.0.1`.
+    Loopback,
+    /// A multicast address.
+    Multicast,
+    /// The IPv4 broadcast address.
+    Broadcast,
+    /// An address with port 0.
+    ZeroPort,
+    /// A private address, e.g. in `192.168.0.0/16`, while this node is not on a private network.
+    Private,
+}
+
+impl fmt::Display for InvalidPeerAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Local => write!(f, "the address of this node"),
+            Self::Unspecified => write!(f, "an unspecified address"),
+            Self::Loopback => write!(f, "a loopback address"),
+            Self::Multicast => write!(f, "a multicast address"),
+            Self::Broadcast => write!(f, "a broadcast address"),
+            Self::ZeroPort => write!(f, "port 0"),
+            Self::Private => write!(f, "a private address"),
+        }
+    }
+}
+
+/// Returns `true` if the given IP address is in a private range,
+/// i.e. an IPv4 private or link-local address, or an IPv6 unique local or link-local address.
+pub fn is_private_ip(ip: IpAddr) -> bool {
+    // Treat IPv4-mapped IPv6 addresses as IPv4 addresses.
+    let ip = match ip {
+        IpAddr::V6(ipv6) => ipv6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+        ip => ip,
+    };
+    match ip {
+        IpAddr::V4(ipv4) => ipv4.is_private() || ipv4.is_link_local(),
+        IpAddr::V6(ipv6) => {
+            let segment = ipv6.segments()[0];
+            // Check for the unique local (fc00::/7) and link-local (fe80::/10) ranges.
+            segment & 0xfe00 == 0xfc00 || segment & 0xffc0 == 0xfe80
+        }
+    }
+}
+
+/// Validates the candidate peer addresses received from peers, before they are inserted into the peer book.
+#[derive(Clone, Debug)]
+pub struct PeerAddressFilter {
+    /// The addresses of this node, i.e. its listening address, and its external address if it is mapped.
+    local_ips: Vec<SocketAddr>,
+    /// Whether private addresses are accepted, i.e. if this node is bound to a private address.
+    allow_private: bool,
+}
+
+impl PeerAddressFilter {
+    /// Initializes a new filter for a node with the given listening address, and external address if it is mapped.
+    /// Note: Private addresses are only accepted if the node itself is bound to a private address (e.g. on a devnet).
+    pub fn new(local_ip: SocketAddr, external_ip: Option<SocketAddr>) -> Self {
+        Self {
+            local_ips: [local_ip].into_iter().chain(external_ip).collect(),
+            allow_private: is_private_ip(local_ip.ip()),
+        }
+    }
+
+    /// Checks the given candidate peer address, returning the reason it is rejected, if any.
+    pub fn check(&self, peer_ip: &SocketAddr) -> Result<(), InvalidPeerAddress> {
+        // Treat IPv4-mapped IPv6 addresses as IPv4 addresses.
+        let ip = match peer_ip.ip() {
+            IpAddr::V6(ipv6) => ipv6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(peer_ip.ip()),
+            ip => ip,
+        };
+        if self.local_ips.iter().any(|local_ip| local_ip.ip() == ip && local_ip.port() == peer_ip.port()) {
+            Err(InvalidPeerAddress::Local)
+        } else if ip.is_unspecified() {
+            Err(InvalidPeerAddress::Unspecified)
+        } else if ip.is_loopback() {
+            Err(InvalidPeerAddress::Loopback)
+        } else if ip.is_multicast() {
+            Err(InvalidPeerAddress::Multicast)
+        } else if matches!(ip, IpAddr::V4(ipv4) if ipv4.is_broadcast()) {
+            Err(InvalidPeerAddress::Broadcast)
+        } else if peer_ip.port() == 0 {
+            Err(InvalidPeerAddress::ZeroPort)
+        } else if !self.allow_private && is_private_ip(ip) {
+            Err(InvalidPeerAddress::Private)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns `true` if the given candidate peer address is valid.
+    pub fn is_valid(&self, peer_ip: &SocketAddr) -> bool {
+        self.check(peer_ip).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn addr(addr: &str) -> SocketAddr {
+        SocketAddr::from_str(addr).unwrap()
+    }
+
+    #[test]
+    fn test_rejected_categories() {
+        let filter = PeerAddressFilter::new(addr("1.2.3.4:4130"), Some(addr("5.6.7.8:4133")));
+
+        let rejected = [
+            ("1.2.3.4:4130", InvalidPeerAddress::Local),
+            ("5.6.7.8:4133", InvalidPeerAddress::Local),
+            ("[::ffff:1.2.3.4]:4130", InvalidPeerAddress::Local),
+            ("0.0.0.0:4130", InvalidPeerAddress::Unspecified),
+            ("[::]:4130", InvalidPeerAddress::Unspecified),
+            ("127.0.0.1:4130", InvalidPeerAddress::Loopback),
+            ("127.0.0.1:4131", InvalidPeerAddress::Loopback),
+            ("[::1]:4130", InvalidPeerAddress::Loopback),
+            ("224.0.0.1:4130", InvalidPeerAddress::Multicast),
+            ("[ff02::1]:4130", InvalidPeerAddress::Multicast),
+            ("255.255.255.255:4130", InvalidPeerAddress::Broadcast),
+            ("9.9.9.9:0", InvalidPeerAddress::ZeroPort),
+            ("10.0.0.1:4130", InvalidPeerAddress::Private),
+            ("172.16.0.1:4130", InvalidPeerAddress::Private),
+            ("192.168.1.1:4130", InvalidPeerAddress::Private),
+            ("169.254.0.1:4130", InvalidPeerAddress::Private),
+            ("[fd00::1]:4130", InvalidPeerAddress::Private),
+            ("[fe80::1]:4130", InvalidPeerAddress::Private),
+        ];
+        for (peer_ip, reason) in rejected {
+            assert_eq!(filter.check(&addr(peer_ip)), Err(reason), "{peer_ip} should be rejected as {reason}");
+        }
+
+        // Public addresses are accepted, including other ports of this node's address.
+        for peer_ip in ["9.9.9.9:4130", "1.2.3.4:4131", "[2001:db8::1]:4130"] {
+            assert_eq!(filter.check(&addr(peer_ip)), Ok(()), "{peer_ip} should be accepted");
+        }
+    }
+
+    #[test]
+    fn test_private_node_accepts_private_addresses() {
+        // A node bound to a private address (e.g. on a devnet) accepts private addresses.
+        let filter = PeerAddressFilter::new(addr("10.0.0.1:4130"), None);
+        for peer_ip in ["10.0.0.2:4130", "192.168.1.1:4130", "[fd00::1]:4130", "9.9.9.9:4130"] {
+            assert!(filter.is_valid(&addr(peer_ip)), "{peer_ip} should be accepted");
+        }
+        // All other categories are still rejected.
+        assert_eq!(filter.check(&addr("10.0.0.1:4130")), Err(InvalidPeerAddress::Local));
+        assert_eq!(filter.check(&addr("127.0.0.1:4130")), Err(InvalidPeerAddress::Loopback));
+        assert_eq!(filter.check(&addr("10.0.0.2:0")), Err(InvalidPeerAddress::ZeroPort));
+    }
+}
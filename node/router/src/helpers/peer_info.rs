@@ -0,0 +1,392 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    messages::{AdvertisedPeer, PeerServices},
+    select_diverse_peers,
+};
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    net::SocketAddr,
+};
+
+/// The metadata known about a candidate peer.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerInfo {
+    /// The UNIX timestamp (in seconds) at which the peer was last seen, or `0` if unknown.
+    pub last_seen: u64,
+    /// The services the peer is known to offer.
+    pub services: PeerServices,
+    /// The retry state of dialing the peer.
+    #[serde(default)]
+    pub backoff: DialBackoff,
+    /// The source from which the peer was first discovered.
+    #[serde(default)]
+    pub source: PeerSource,
+}
+
+impl PeerInfo {
+    /// The duration in seconds within which a peer that was last seen is considered fresh (3 hours).
+    pub const FRESHNESS_IN_SECS: u64 = 3 * 60 * 60;
+
+    /// Returns `true` if the peer was last seen within the freshness window, as of the given UNIX timestamp.
+    pub fn is_fresh(&self, now: u64) -> bool {
+        self.last_seen != 0 && now.saturating_sub(self.last_seen) <= Self::FRESHNESS_IN_SECS
+    }
+
+    /// Merges the given metadata into this one, retaining the most recent information.
+    /// Note: The retry state and the source are local to this node, so they are never merged.
+    pub fn merge(&mut self, other: PeerInfo) {
+        if other.last_seen >= self.last_seen {
+            self.last_seen = other.last_seen;
+            // Only overwrite the services if the other metadata knows about them.
+            if !other.services.is_unknown() {
+                self.services = other.services;
+            }
+        }
+    }
+}
+
+impl From<&AdvertisedPeer> for PeerInfo {
+    fn from(peer: &AdvertisedPeer) -> Self {
+        Self {
+            last_seen: peer.last_seen,
+            services: peer.services,
+            backoff: DialBackoff::default(),
+            source: PeerSource::default(),
+        }
+    }
+}
+
+/// The source from which a candidate peer was discovered.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PeerSource {
+    /// The peer was advertised by another peer.
+    #[default]
+    Gossip,
+    /// The peer was resolved from a DNS seed.
+    DnsSeed,
+    /// The peer was listed in a signed seed list.
+    SeedList,
+}
+
+/// The retry state of dialing a candidate peer, which delays the next dial exponentially after each failure.
+///
+/// After `MAX_FAILURES` consecutive failures, the peer is dormant, and is only dialed once a day,
+/// until it is advertised again by another peer. The state is reset once the peer is connected,
+/// as a connected peer is removed from the candidate peers.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DialBackoff {
+    /// The number of consecutive failed dials.
+    pub failures: u32,
+    /// The UNIX timestamp (in seconds) before which the peer is not dialed, or `0` if it was never dialed.
+    pub next_attempt: u64,
+}
+
+impl DialBackoff {
+    /// The delay in seconds after the first failure (30 seconds).
+    pub const BASE_DELAY_IN_SECS: u64 = 30;
+    /// The delay in seconds between the dials of a dormant peer (1 day).
+    pub const DORMANT_DELAY_IN_SECS: u64 = 24 * 60 * 60;
+    /// The maximum jitter added to a delay, as a percentage of the delay.
+    pub const JITTER_PERCENT: u64 = 20;
+    /// The maximum delay in seconds of the exponential backoff (2 hours).
+    pub const MAX_DELAY_IN_SECS: u64 = 2 * 60 * 60;
+    /// The number of consecutive failures after which the peer is dormant.
+    pub const MAX_FAILURES: u32 = 12;
+
+    /// Returns the delay in seconds before the next dial after the given number of consecutive failures, without jitter.
+    pub fn delay_in_secs(failures: u32) -> u64 {
+        match failures {
+            0 => 0,
+            failures if failures >= Self::MAX_FAILURES => Self::DORMANT_DELAY_IN_SECS,
+            failures => {
+                Self::BASE_DELAY_IN_SECS.saturating_mul(1 << (failures - 1).min(32)).min(Self::MAX_DELAY_IN_SECS)
+            }
+        }
+    }
+
+    /// Returns `true` if the peer may be dialed, as of the given UNIX timestamp.
+    pub fn is_eligible(&self, now: u64) -> bool {
+        now >= self.next_attempt
+    }
+
+    /// Returns `true` if the peer reached the maximum number of consecutive failures.
+    pub fn is_dormant(&self) -> bool {
+        self.failures >= Self::MAX_FAILURES
+    }
+
+    /// Records a failed dial at the given UNIX timestamp, and schedules the next dial with jitter.
+    pub fn record_failure<R: Rng>(&mut self, now: u64, rng: &mut R) {
+        self.failures = self.failures.saturating_add(1);
+        let delay = Self::delay_in_secs(self.failures);
+        let jitter = rng.gen_range(0..=delay * Self::JITTER_PERCENT / 100);
+        self.next_attempt = now.saturating_add(delay).saturating_add(jitter);
+    }
+
+    /// Records that the peer was advertised by another peer at the given UNIX timestamp.
+    /// A dormant peer resumes the backoff from half the maximum number of failures, so it is dialed sooner.
+    pub fn record_advertisement(&mut self, now: u64) {
+        if self.is_dormant() {
+            self.failures = Self::MAX_FAILURES / 2;
+            let next_attempt = now.saturating_add(Self::delay_in_secs(self.failures));
+            self.next_attempt = self.next_attempt.min(next_attempt);
+        }
+    }
+}
+
+/// Returns the current UNIX timestamp in seconds.
+pub fn now_unix() -> u64 {
+    time::OffsetDateTime::now_utc().unix_timestamp().max(0) as u64
+}
+
+/// Selects up to `num_peers` of the given candidate peers to dial, preferring the candidates that were seen
+/// recently. The fresh candidates are selected first, and the remaining slots are filled with the stale ones.
+/// If `prefer_archives` is set, the fresh archives are selected ahead of the other fresh candidates,
+/// as they are advertised for bulk block download.
+///
+/// Within each group, the candidates are selected for subnet diversity, as in `select_diverse_peers`.
+/// Trusted peers are treated as fresh, regardless of when they were last seen.
+/// The candidates whose next dial is delayed by their backoff are skipped, except for trusted peers.
+#[allow(clippy::too_many_arguments)]
+pub fn select_fresh_peers<R: Rng>(
+    candidates: HashMap<SocketAddr, PeerInfo>,
+    connected: &[SocketAddr],
+    trusted: &HashSet<SocketAddr>,
+    num_peers: usize,
+    max_per_bucket: usize,
+    prefer_archives: bool,
+    now: u64,
+    rng: &mut R,
+) -> Vec<SocketAddr> {
+    // Skip the candidates that are backing off.
+    let candidates = candidates.into_iter().filter(|(ip, info)| info.backoff.is_eligible(now) || trusted.contains(ip));
+    // Split the candidates by freshness, where trusted peers are always selected first.
+    let (fresh, stale): (Vec<_>, Vec<_>) =
+        candidates.partition(|(ip, info)| info.is_fresh(now) || trusted.contains(ip));
+    // If archives are preferred, split them from the fresh candidates.
+    let (archives, fresh): (Vec<_>, Vec<_>) = match prefer_archives {
+        true => fresh.into_iter().partition(|(_, info)| info.services.is_archive()),
+        false => (Vec::new(), fresh),
+    };
+
+    // Select the candidates in order of preference, counting the selected peers against their buckets.
+    let mut selected = Vec::with_capacity(num_peers);
+    for group in [archives, fresh, stale] {
+        let remaining = num_peers.saturating_sub(selected.len());
+        if remaining == 0 {
+            break;
+        }
+        let connected = connected.iter().chain(selected.iter()).copied().collect::<Vec<_>>();
+        let group = group.into_iter().map(|(ip, _)| ip);
+        selected.extend(select_diverse_peers(group, &connected, trusted, remaining, max_per_bucket, rng));
+    }
+    selected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages::NodeType;
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::net::Ipv4Addr;
+
+    /// The UNIX timestamp used as the current time in the tests.
+    const NOW: u64 = 1_700_000_000;
+    /// The number of seconds in a day.
+    const DAY_IN_SECS: u64 = 24 * 60 * 60;
+
+    /// Returns a peer address in the `a.b.0.0/16` bucket.
+    fn peer(a: u8, b: u8, c: u8) -> SocketAddr {
+        SocketAddr::new(Ipv4Addr::new(a, b, c, 1).into(), 4130)
+    }
+
+    /// Returns the metadata of a client that was last seen at the given timestamp.
+    fn seen_at(last_seen: u64) -> PeerInfo {
+        PeerInfo { last_seen, services: PeerServices::new(NodeType::Client, false), ..Default::default() }
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut info = seen_at(NOW - DAY_IN_SECS);
+        // Check that older metadata is ignored.
+        info.merge(PeerInfo { last_seen: NOW - 2 * DAY_IN_SECS, ..Default::default() });
+        assert_eq!(info, seen_at(NOW - DAY_IN_SECS));
+        // Check that newer metadata without services retains the known services.
+        info.merge(PeerInfo { last_seen: NOW, ..Default::default() });
+        assert_eq!(info, seen_at(NOW));
+        // Check that the retry state is never merged.
+        info.backoff.record_failure(NOW, &mut StdRng::seed_from_u64(0));
+        let backoff = info.backoff;
+        info.merge(PeerInfo { last_seen: NOW + 1, ..Default::default() });
+        assert_eq!(info.backoff, backoff);
+    }
+
+    #[test]
+    fn test_backoff_schedule() {
+        let rng = &mut StdRng::seed_from_u64(0);
+        let mut backoff = DialBackoff::default();
+        assert!(backoff.is_eligible(0));
+
+        // Simulate consecutive failures, one at each eligible time.
+        let mut now = NOW;
+        let mut previous_delay = 0;
+        for failures in 1..=DialBackoff::MAX_FAILURES + 3 {
+            backoff.record_failure(now, rng);
+            let delay = DialBackoff::delay_in_secs(failures);
+            // Check that the delay doubles from the base delay, up to the maximum delay, until the peer is dormant.
+            match failures {
+                1 => assert_eq!(delay, DialBackoff::BASE_DELAY_IN_SECS),
+                failures if failures < DialBackoff::MAX_FAILURES => {
+                    assert_eq!(delay, (previous_delay * 2).min(DialBackoff::MAX_DELAY_IN_SECS))
+                }
+                _ => assert_eq!(delay, DialBackoff::DORMANT_DELAY_IN_SECS),
+            }
+            assert_eq!(backoff.failures, failures);
+            assert_eq!(backoff.is_dormant(), failures >= DialBackoff::MAX_FAILURES);
+            // Check that the peer is not eligible before the delay, and is eligible once the delay and jitter elapsed.
+            assert!(!backoff.is_eligible(now + delay - 1));
+            assert!(backoff.is_eligible(now + delay + delay * DialBackoff::JITTER_PERCENT / 100));
+            previous_delay = delay;
+            now = backoff.next_attempt;
+        }
+        assert_eq!(DialBackoff::delay_in_secs(DialBackoff::MAX_FAILURES - 1), DialBackoff::MAX_DELAY_IN_SECS);
+        assert_eq!(DialBackoff::delay_in_secs(u32::MAX), DialBackoff::DORMANT_DELAY_IN_SECS);
+    }
+
+    #[test]
+    fn test_backoff_jitter_bounds() {
+        let rng = &mut StdRng::seed_from_u64(0);
+        for failures in 0..DialBackoff::MAX_FAILURES + 1 {
+            let delay = DialBackoff::delay_in_secs(failures + 1);
+            let max_jitter = delay * DialBackoff::JITTER_PERCENT / 100;
+            // Check that the jitter stays within its bounds, and spreads the dials.
+            let mut next_attempts = HashSet::new();
+            for _ in 0..100 {
+                let mut backoff = DialBackoff { failures, next_attempt: 0 };
+                backoff.record_failure(NOW, rng);
+                assert!((NOW + delay..=NOW + delay + max_jitter).contains(&backoff.next_attempt));
+                next_attempts.insert(backoff.next_attempt);
+            }
+            assert!(next_attempts.len() > 1);
+        }
+    }
+
+    #[test]
+    fn test_backoff_resets_on_advertisement() {
+        let rng = &mut StdRng::seed_from_u64(0);
+        // Check that an advertisement does not reset a peer that is still backing off.
+        let mut backoff = DialBackoff::default();
+        for _ in 0..3 {
+            backoff.record_failure(NOW, rng);
+        }
+        let expected = backoff;
+        backoff.record_advertisement(NOW);
+        assert_eq!(backoff, expected);
+
+        // Check that an advertisement partially resets a dormant peer.
+        let mut backoff = DialBackoff { failures: DialBackoff::MAX_FAILURES, next_attempt: NOW + DAY_IN_SECS };
+        backoff.record_advertisement(NOW);
+        assert!(!backoff.is_dormant());
+        assert_eq!(backoff.failures, DialBackoff::MAX_FAILURES / 2);
+        assert_eq!(backoff.next_attempt, NOW + DialBackoff::delay_in_secs(DialBackoff::MAX_FAILURES / 2));
+        // Check that the peer becomes dormant again after as many failures.
+        for _ in 0..DialBackoff::MAX_FAILURES / 2 {
+            backoff.record_failure(backoff.next_attempt, rng);
+        }
+        assert!(backoff.is_dormant());
+    }
+
+    #[test]
+    fn test_select_skips_backing_off_peers() {
+        let rng = &mut StdRng::seed_from_u64(0);
+        let (eligible, backing_off, trusted) = (peer(1, 0, 0), peer(2, 0, 0), peer(3, 0, 0));
+        let delayed = PeerInfo { backoff: DialBackoff { failures: 1, next_attempt: NOW + 1 }, ..seen_at(NOW) };
+        let candidates = HashMap::from([(eligible, seen_at(NOW)), (backing_off, delayed), (trusted, delayed)]);
+
+        // Check that the peer that is backing off is skipped, unless it is trusted.
+        let selected = select_fresh_peers(candidates.clone(), &[], &HashSet::from([trusted]), 3, 3, false, NOW, rng);
+        assert_eq!(selected.into_iter().collect::<HashSet<_>>(), HashSet::from([eligible, trusted]));
+        // Check that the peer is selected once its next dial is due.
+        let selected = select_fresh_peers(candidates, &[], &HashSet::new(), 3, 3, false, NOW + 1, rng);
+        assert_eq!(selected.len(), 3);
+    }
+
+    #[test]
+    fn test_serialize_peer_info() {
+        let info = PeerInfo { backoff: DialBackoff { failures: 3, next_attempt: NOW + 120 }, ..seen_at(NOW) };
+        let json = serde_json::to_string(&info).unwrap();
+        assert_eq!(serde_json::from_str::<PeerInfo>(&json).unwrap(), info);
+        // Check that a peer book without the retry state is still read.
+        let info: PeerInfo = serde_json::from_str(&format!("{{\"last_seen\":{NOW},\"services\":1}}")).unwrap();
+        assert_eq!(info, seen_at(NOW));
+    }
+
+    #[test]
+    fn test_select_prefers_fresh_peers() {
+        for seed in 0..10 {
+            let rng = &mut StdRng::seed_from_u64(seed);
+            // Prepare candidates that were seen days ago, and candidates that were seen recently.
+            let mut candidates = HashMap::new();
+            for i in 0..10 {
+                candidates.insert(peer(1, i, 0), seen_at(NOW - 3 * DAY_IN_SECS));
+                candidates.insert(peer(2, i, 0), PeerInfo::default());
+            }
+            for i in 0..3 {
+                candidates.insert(peer(3, i, 0), seen_at(NOW - 60));
+            }
+
+            // Check that the fresh candidates are dialed first.
+            let selected = select_fresh_peers(candidates.clone(), &[], &HashSet::new(), 3, 3, false, NOW, rng);
+            assert_eq!(selected.len(), 3);
+            assert!(selected.iter().all(|ip| candidates[ip].is_fresh(NOW)));
+
+            // Check that the remaining slots are filled with the stale candidates.
+            let selected = select_fresh_peers(candidates.clone(), &[], &HashSet::new(), 5, 3, false, NOW, rng);
+            assert_eq!(selected.len(), 5);
+            assert_eq!(selected.iter().filter(|ip| candidates[ip].is_fresh(NOW)).count(), 3);
+        }
+    }
+
+    #[test]
+    fn test_select_prefers_archives() {
+        for seed in 0..10 {
+            let rng = &mut StdRng::seed_from_u64(seed);
+            // Prepare fresh clients, and fresh archives.
+            let mut candidates = HashMap::new();
+            for i in 0..10 {
+                candidates.insert(peer(1, i, 0), seen_at(NOW - 60));
+            }
+            for i in 0..2 {
+                let services = PeerServices::new(NodeType::Archive, true);
+                candidates.insert(peer(2, i, 0), PeerInfo { last_seen: NOW - 60, services, ..Default::default() });
+            }
+            let is_archive = |ip: &SocketAddr| candidates[ip].services.is_archive();
+
+            // Check that the archives are dialed first, if they are preferred.
+            let selected = select_fresh_peers(candidates.clone(), &[], &HashSet::new(), 3, 3, true, NOW, rng);
+            assert_eq!(selected.len(), 3);
+            assert_eq!(selected.iter().filter(|ip| is_archive(ip)).count(), 2);
+
+            // Check that stale archives are not preferred over fresh peers.
+            let mut stale = candidates.clone();
+            stale.values_mut().filter(|info| info.services.is_archive()).for_each(|info| info.last_seen = 0);
+            let selected = select_fresh_peers(stale, &[], &HashSet::new(), 3, 3, true, NOW, rng);
+            assert!(selected.iter().all(|ip| !is_archive(ip)));
+        }
+    }
+}
@@ -0,0 +1,313 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::{bail, Result};
+use parking_lot::Mutex;
+use std::{collections::HashMap, net::SocketAddr, sync::Arc};
+
+/// The state of the connection with a peer.
+///
+/// An outbound connection goes from `Disconnected` through `Dialing`, `Handshaking`, `Connected`, and `Draining`
+/// back to `Disconnected`, while an inbound connection starts at `Handshaking`. A peer in any state may be `Banned`,
+/// and it is `Disconnected` once the restriction expires.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PeerState {
+    /// The node is not connected to the peer.
+    Disconnected,
+    /// The node is dialing the peer.
+    Dialing,
+    /// The node is performing the handshake with the peer.
+    Handshaking,
+    /// The node is connected to the peer.
+    Connected,
+    /// The node is disconnecting from the peer.
+    Draining,
+    /// The peer is restricted, and the node does not connect to it.
+    Banned,
+}
+
+/// An event that drives the state of the connection with a peer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PeerEvent {
+    /// The node starts dialing the peer.
+    Dial,
+    /// The node accepted a connection from the peer.
+    Accept,
+    /// The dial succeeded, and the handshake starts.
+    DialSucceeded,
+    /// The dial failed, or was dropped before it succeeded.
+    DialFailed,
+    /// The handshake succeeded.
+    HandshakeSucceeded,
+    /// The handshake failed, or was dropped before it succeeded.
+    HandshakeFailed,
+    /// The peer has not communicated within the predefined time.
+    InactivityTimeout,
+    /// The node disconnects from the peer.
+    Disconnect,
+    /// The connection with the peer was closed.
+    Closed,
+    /// The peer is restricted.
+    Ban,
+    /// The restriction of the peer expired.
+    Unban,
+}
+
+impl PeerState {
+    /// The states of the connection with a peer.
+    pub const ALL: [Self; 6] =
+        [Self::Disconnected, Self::Dialing, Self::Handshaking, Self::Connected, Self::Draining, Self::Banned];
+
+    /// Returns `true` if the node is dialing or performing the handshake with the peer.
+    pub const fn is_connecting(&self) -> bool {
+        matches!(self, Self::Dialing | Self::Handshaking)
+    }
+
+    /// Returns the state after the given event, or an error if the event is invalid in this state.
+    pub fn transition(self, event: PeerEvent) -> Result<Self> {
+        use PeerEvent::*;
+        use PeerState::*;
+
+        Ok(match (self, event) {
+            // Any peer may be restricted, and it remains restricted until the restriction expires.
+            (_, Ban) => Banned,
+            (Banned, Closed) => Banned,
+            (Banned, Unban) => Disconnected,
+            // Dial the peer, or accept its connection.
+            (Disconnected, Dial) => Dialing,
+            (Disconnected, Accept) => Handshaking,
+            (Dialing, DialSucceeded) => Handshaking,
+            (Dialing, DialFailed) => Disconnected,
+            // Perform the handshake.
+            (Handshaking, HandshakeSucceeded) => Connected,
+            (Handshaking, HandshakeFailed) => Disconnected,
+            // Disconnect from the peer, or observe that its connection was closed.
+            (Connected, InactivityTimeout | Disconnect) => Draining,
+            (Draining, InactivityTimeout | Disconnect) => Draining,
+            (Connected | Draining, Closed) => Disconnected,
+            (state, event) => bail!("Invalid transition of the peer state from {state:?} on {event:?}"),
+        })
+    }
+}
+
+impl PeerEvent {
+    /// The events that drive the state of the connection with a peer.
+    pub const ALL: [Self; 11] = [
+        Self::Dial,
+        Self::Accept,
+        Self::DialSucceeded,
+        Self::DialFailed,
+        Self::HandshakeSucceeded,
+        Self::HandshakeFailed,
+        Self::InactivityTimeout,
+        Self::Disconnect,
+        Self::Closed,
+        Self::Ban,
+        Self::Unban,
+    ];
+}
+
+/// The states of the connections with the peers, by their listener IP.
+///
+/// Peers that are disconnected are not retained, so the number of entries is bounded
+/// by the number of peers that are connecting, connected, draining, or restricted.
+#[derive(Clone, Debug, Default)]
+pub struct PeerStates(Arc<Mutex<HashMap<SocketAddr, PeerState>>>);
+
+impl PeerStates {
+    /// Returns the state of the connection with the given peer.
+    pub fn get(&self, peer_ip: &SocketAddr) -> PeerState {
+        self.0.lock().get(peer_ip).copied().unwrap_or(PeerState::Disconnected)
+    }
+
+    /// Returns the number of peers in the given state.
+    pub fn count(&self, state: PeerState) -> usize {
+        self.0.lock().values().filter(|s| **s == state).count()
+    }
+
+    /// Applies the given event to the state of the given peer, and returns the new state.
+    /// If the event is invalid in the current state, the state is unchanged.
+    pub fn transition(&self, peer_ip: SocketAddr, event: PeerEvent) -> Result<PeerState> {
+        let mut states = self.0.lock();
+        let state = states.get(&peer_ip).copied().unwrap_or(PeerState::Disconnected).transition(event)?;
+        match state {
+            PeerState::Disconnected => states.remove(&peer_ip),
+            state => states.insert(peer_ip, state),
+        };
+        Ok(state)
+    }
+
+    /// Returns a guard that applies the given event to the given peer when it is dropped,
+    /// if the peer is still in its current state. This ensures a dial or handshake that is dropped
+    /// before it completes, e.g. on a timeout, does not leave the peer stuck in that state.
+    pub fn guard(&self, peer_ip: SocketAddr, event: PeerEvent) -> PeerStateGuard {
+        PeerStateGuard { states: self.clone(), peer_ip, state: self.get(&peer_ip), event }
+    }
+}
+
+/// A guard that applies an event to a peer when it is dropped, if the peer is still in the guarded state.
+#[must_use]
+pub struct PeerStateGuard {
+    /// The states of the peers.
+    states: PeerStates,
+    /// The listener IP of the peer.
+    peer_ip: SocketAddr,
+    /// The guarded state.
+    state: PeerState,
+    /// The event to apply, if the peer is still in the guarded state.
+    event: PeerEvent,
+}
+
+impl Drop for PeerStateGuard {
+    fn drop(&mut self) {
+        // Note: The lock is held for the check and the transition, as another task may transition the peer.
+        let mut states = self.states.0.lock();
+        if states.get(&self.peer_ip).copied().unwrap_or(PeerState::Disconnected) != self.state {
+            return;
+        }
+        if let Ok(state) = self.state.transition(self.event) {
+            match state {
+                PeerState::Disconnected => states.remove(&self.peer_ip),
+                state => states.insert(self.peer_ip, state),
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use PeerEvent::*;
+    use PeerState::*;
+
+    /// Returns the expected state after the given event, or `None` if the event is invalid in the given state.
+    fn expected_transition(state: PeerState, event: PeerEvent) -> Option<PeerState> {
+        match (state, event) {
+            (_, Ban) => Some(Banned),
+            (Banned, Closed) => Some(Banned),
+            (Banned, Unban) => Some(Disconnected),
+            (Disconnected, Dial) => Some(Dialing),
+            (Disconnected, Accept) => Some(Handshaking),
+            (Dialing, DialSucceeded) => Some(Handshaking),
+            (Dialing, DialFailed) => Some(Disconnected),
+            (Handshaking, HandshakeSucceeded) => Some(Connected),
+            (Handshaking, HandshakeFailed) => Some(Disconnected),
+            (Connected | Draining, InactivityTimeout | Disconnect) => Some(Draining),
+            (Connected | Draining, Closed) => Some(Disconnected),
+            _ => None,
+        }
+    }
+
+    fn sample_peer_ip() -> SocketAddr {
+        "1.2.3.4:4130".parse().unwrap()
+    }
+
+    #[test]
+    fn test_transitions() {
+        for state in PeerState::ALL {
+            for event in PeerEvent::ALL {
+                match expected_transition(state, event) {
+                    Some(expected) => assert_eq!(state.transition(event).unwrap(), expected, "{state:?} on {event:?}"),
+                    None => assert!(state.transition(event).is_err(), "{state:?} on {event:?}"),
+                }
+            }
+        }
+        // Ensure every state is reachable from a disconnected peer.
+        for state in PeerState::ALL {
+            assert!(
+                PeerState::ALL
+                    .into_iter()
+                    .flat_map(|from| PeerEvent::ALL.map(move |event| from.transition(event).ok()))
+                    .any(|to| to == Some(state)),
+                "{state:?} is unreachable"
+            );
+        }
+    }
+
+    #[test]
+    fn test_peer_lifecycle() {
+        let states = PeerStates::default();
+        let peer_ip = sample_peer_ip();
+
+        // Dial the peer, connect, and disconnect.
+        for (event, expected) in [
+            (Dial, Dialing),
+            (DialSucceeded, Handshaking),
+            (HandshakeSucceeded, Connected),
+            (InactivityTimeout, Draining),
+        ] {
+            assert_eq!(states.transition(peer_ip, event).unwrap(), expected);
+            assert_eq!(states.get(&peer_ip), expected);
+        }
+        // Ensure a second dial or an inbound connection is refused while the peer is draining.
+        assert!(states.transition(peer_ip, Dial).is_err());
+        assert!(states.transition(peer_ip, Accept).is_err());
+        assert_eq!(states.get(&peer_ip), Draining);
+        // Ensure the disconnected peer is not retained.
+        assert_eq!(states.transition(peer_ip, Closed).unwrap(), Disconnected);
+        assert_eq!(states.count(Disconnected), 0);
+
+        // Ban the peer, and ensure it is not dialed until the ban is lifted.
+        states.transition(peer_ip, Accept).unwrap();
+        states.transition(peer_ip, Ban).unwrap();
+        assert!(states.transition(peer_ip, Dial).is_err());
+        assert_eq!(states.transition(peer_ip, Closed).unwrap(), Banned);
+        assert_eq!(states.transition(peer_ip, Unban).unwrap(), Disconnected);
+        assert_eq!(states.transition(peer_ip, Dial).unwrap(), Dialing);
+    }
+
+    #[test]
+    fn test_guard() {
+        let states = PeerStates::default();
+        let peer_ip = sample_peer_ip();
+
+        // Ensure a dropped dial does not leave the peer connecting.
+        states.transition(peer_ip, Dial).unwrap();
+        drop(states.guard(peer_ip, DialFailed));
+        assert_eq!(states.get(&peer_ip), Disconnected);
+
+        // Ensure a dropped handshake does not leave the peer connecting.
+        states.transition(peer_ip, Accept).unwrap();
+        drop(states.guard(peer_ip, HandshakeFailed));
+        assert_eq!(states.get(&peer_ip), Disconnected);
+
+        // Ensure the guard does not apply its event, if the peer moved on from the guarded state.
+        states.transition(peer_ip, Accept).unwrap();
+        let guard = states.guard(peer_ip, HandshakeFailed);
+        states.transition(peer_ip, HandshakeSucceeded).unwrap();
+        drop(guard);
+        assert_eq!(states.get(&peer_ip), Connected);
+    }
+
+    #[tokio::test]
+    async fn test_guard_on_aborted_dial() {
+        let states = PeerStates::default();
+        let peer_ip = sample_peer_ip();
+
+        // Dial the peer in a task that never completes, and abort it.
+        states.transition(peer_ip, Dial).unwrap();
+        let guard = states.guard(peer_ip, DialFailed);
+        let handle = tokio::spawn(async move {
+            let _guard = guard;
+            std::future::pending::<()>().await
+        });
+        assert_eq!(states.get(&peer_ip), Dialing);
+        handle.abort();
+        let _ = handle.await;
+
+        // Ensure the peer is no longer connecting.
+        assert!(!states.get(&peer_ip).is_connecting());
+        assert!(states.transition(peer_ip, Dial).is_ok());
+    }
+}
@@ -0,0 +1,248 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::Result;
+use igd_next::{
+    aio::{
+        tokio::{search_gateway, Tokio},
+        Gateway,
+    },
+    PortMappingProtocol,
+    SearchOptions,
+};
+use parking_lot::{Mutex, RwLock};
+use std::{
+    net::{IpAddr, SocketAddr, UdpSocket},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// The duration of the port mapping lease requested from the gateway.
+pub const PORT_MAPPING_LEASE: Duration = Duration::from_secs(60 * 60);
+/// The description of the port mapping on the gateway.
+const PORT_MAPPING_DESCRIPTION: &str = "snarkOS";
+
+/// A gateway that maps ports on its external address to this node.
+#[async_trait]
+pub trait PortMappingGateway: Send + Sync {
+    /// Returns the external IP address of the gateway.
+    async fn external_ip(&self) -> Result<IpAddr>;
+
+    /// Maps the given external port to the given local address, for the given lease duration.
+    async fn add_port(&self, external_port: u16, local_addr: SocketAddr, lease: Duration) -> Result<()>;
+
+    /// Removes the mapping of the given external port.
+    async fn remove_port(&self, external_port: u16) -> Result<()>;
+}
+
+/// A port mapping on the gateway, valid until the lease expires.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PortMappingLease {
+    /// The external address that is mapped to the listening port of this node.
+    pub external_addr: SocketAddr,
+    /// The time at which the lease expires, unless it is renewed.
+    pub expires_at: Instant,
+}
+
+/// Returns the address to advertise to peers, which is the external address of an unexpired lease,
+/// or otherwise the local listening address.
+pub fn advertised_addr(local_addr: SocketAddr, lease: Option<&PortMappingLease>, now: Instant) -> SocketAddr {
+    match lease {
+        Some(lease) if now < lease.expires_at => lease.external_addr,
+        _ => local_addr,
+    }
+}
+
+/// The mapping of the listening port of this node on the gateway of its network.
+#[derive(Default)]
+pub struct PortMapping {
+    /// The current lease, if the port is mapped.
+    lease: RwLock<Option<PortMappingLease>>,
+    /// The gateway that holds the lease.
+    gateway: Mutex<Option<Arc<dyn PortMappingGateway>>>,
+}
+
+impl PortMapping {
+    /// Returns the external address of this node, if the port is mapped and the lease has not expired.
+    pub fn external_addr(&self) -> Option<SocketAddr> {
+        let lease = *self.lease.read();
+        lease.filter(|lease| Instant::now() < lease.expires_at).map(|lease| lease.external_addr)
+    }
+
+    /// Returns the address to advertise to peers, given the local listening address.
+    pub fn advertised_addr(&self, local_addr: SocketAddr) -> SocketAddr {
+        advertised_addr(local_addr, self.lease.read().as_ref(), Instant::now())
+    }
+
+    /// Maps (or renews the mapping of) the port of the given local address on the given gateway,
+    /// and returns the new lease.
+    pub async fn map(&self, gateway: Arc<dyn PortMappingGateway>, local_addr: SocketAddr) -> Result<PortMappingLease> {
+        let external_ip = gateway.external_ip().await?;
+        gateway.add_port(local_addr.port(), local_addr, PORT_MAPPING_LEASE).await?;
+        let lease = PortMappingLease {
+            external_addr: SocketAddr::new(external_ip, local_addr.port()),
+            expires_at: Instant::now() + PORT_MAPPING_LEASE,
+        };
+        *self.lease.write() = Some(lease);
+        *self.gateway.lock() = Some(gateway);
+        Ok(lease)
+    }
+
+    /// Removes the port mapping from the gateway, if there is one.
+    pub async fn unmap(&self) {
+        let gateway = self.gateway.lock().take();
+        let lease = self.lease.write().take();
+        if let (Some(gateway), Some(lease)) = (gateway, lease) {
+            match gateway.remove_port(lease.external_addr.port()).await {
+                Ok(()) => debug!("Removed the port mapping for '{}'", lease.external_addr),
+                Err(error) => warn!("Failed to remove the port mapping for '{}' - {error}", lease.external_addr),
+            }
+        }
+    }
+}
+
+/// An Internet Gateway Device, which maps ports over UPnP.
+pub struct IgdGateway(Gateway<Tokio>);
+
+impl IgdGateway {
+    /// Searches for the gateway of the local network.
+    pub async fn search() -> Result<Self> {
+        Ok(Self(search_gateway(SearchOptions::default()).await?))
+    }
+
+    /// Returns the IP address of this node on the local network of the gateway.
+    fn local_ip(&self) -> Result<IpAddr> {
+        // Note: Connecting a UDP socket selects the local interface of the route, without sending any packets.
+        let socket = UdpSocket::bind(SocketAddr::new(IpAddr::from([0, 0, 0, 0]), 0))?;
+        socket.connect(self.0.addr)?;
+        Ok(socket.local_addr()?.ip())
+    }
+}
+
+#[async_trait]
+impl PortMappingGateway for IgdGateway {
+    async fn external_ip(&self) -> Result<IpAddr> {
+        Ok(self.0.get_external_ip().await?)
+    }
+
+    async fn add_port(&self, external_port: u16, local_addr: SocketAddr, lease: Duration) -> Result<()> {
+        // The gateway forwards to the address of this node on its network, rather than to an unspecified address.
+        let local_addr = match local_addr.ip().is_unspecified() {
+            true => SocketAddr::new(self.local_ip()?, local_addr.port()),
+            false => local_addr,
+        };
+        let lease = u32::try_from(lease.as_secs()).unwrap_or(u32::MAX);
+        self.0.add_port(PortMappingProtocol::TCP, external_port, local_addr, lease, PORT_MAPPING_DESCRIPTION).await?;
+        Ok(())
+    }
+
+    async fn remove_port(&self, external_port: u16) -> Result<()> {
+        Ok(self.0.remove_port(PortMappingProtocol::TCP, external_port).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::bail;
+
+    /// A gateway that records its port mappings.
+    #[derive(Default)]
+    struct MockGateway {
+        /// Whether the gateway rejects the requests.
+        is_unavailable: bool,
+        /// The mapped external ports.
+        ports: Mutex<Vec<u16>>,
+    }
+
+    #[async_trait]
+    impl PortMappingGateway for MockGateway {
+        async fn external_ip(&self) -> Result<IpAddr> {
+            match self.is_unavailable {
+                true => bail!("The gateway is unavailable"),
+                false => Ok(IpAddr::from([203, 0, 113, 7])),
+            }
+        }
+
+        async fn add_port(&self, external_port: u16, _local_addr: SocketAddr, _lease: Duration) -> Result<()> {
+            self.ports.lock().push(external_port);
+            Ok(())
+        }
+
+        async fn remove_port(&self, external_port: u16) -> Result<()> {
+            self.ports.lock().retain(|port| *port != external_port);
+            Ok(())
+        }
+    }
+
+    fn sample_local_addr() -> SocketAddr {
+        "0.0.0.0:4130".parse().unwrap()
+    }
+
+    #[test]
+    fn test_advertised_addr() {
+        let local_addr = sample_local_addr();
+        let external_addr = "203.0.113.7:4130".parse().unwrap();
+        let now = Instant::now();
+
+        // Ensure the local address is advertised without a mapping.
+        assert_eq!(advertised_addr(local_addr, None, now), local_addr);
+
+        // Ensure the external address is advertised while the lease is valid.
+        let lease = PortMappingLease { external_addr, expires_at: now + Duration::from_secs(1) };
+        assert_eq!(advertised_addr(local_addr, Some(&lease), now), external_addr);
+
+        // Ensure the local address is advertised once the lease expired.
+        assert_eq!(advertised_addr(local_addr, Some(&lease), now + Duration::from_secs(1)), local_addr);
+        assert_eq!(advertised_addr(local_addr, Some(&lease), now + Duration::from_secs(2)), local_addr);
+    }
+
+    #[tokio::test]
+    async fn test_map_and_unmap() {
+        let local_addr = sample_local_addr();
+        let gateway = Arc::new(MockGateway::default());
+        let mapping = PortMapping::default();
+        assert_eq!(mapping.external_addr(), None);
+        assert_eq!(mapping.advertised_addr(local_addr), local_addr);
+
+        // Ensure the mapped external address is advertised.
+        let lease = mapping.map(gateway.clone(), local_addr).await.unwrap();
+        assert_eq!(lease.external_addr, "203.0.113.7:4130".parse().unwrap());
+        assert_eq!(mapping.external_addr(), Some(lease.external_addr));
+        assert_eq!(mapping.advertised_addr(local_addr), lease.external_addr);
+        assert_eq!(*gateway.ports.lock(), vec![4130]);
+
+        // Ensure the mapping is removed from the gateway, and the local address is advertised again.
+        mapping.unmap().await;
+        assert!(gateway.ports.lock().is_empty());
+        assert_eq!(mapping.external_addr(), None);
+        assert_eq!(mapping.advertised_addr(local_addr), local_addr);
+    }
+
+    #[tokio::test]
+    async fn test_map_unavailable_gateway() {
+        let local_addr = sample_local_addr();
+        let gateway = Arc::new(MockGateway { is_unavailable: true, ..Default::default() });
+        let mapping = PortMapping::default();
+
+        // Ensure a failed mapping leaves the local address advertised.
+        assert!(mapping.map(gateway.clone(), local_addr).await.is_err());
+        assert!(gateway.ports.lock().is_empty());
+        assert_eq!(mapping.external_addr(), None);
+        assert_eq!(mapping.advertised_addr(local_addr), local_addr);
+
+        // Ensure unmapping without a mapping is a no-op.
+        mapping.unmap().await;
+    }
+}
@@ -0,0 +1,358 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{messages::PropagationSample, now_unix_millis};
+use snarkvm::prelude::Network;
+
+use linked_hash_map::LinkedHashMap;
+use parking_lot::{Mutex, RwLock};
+use rand::Rng;
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+/// The upper bounds in milliseconds of the buckets of the propagation delay histogram.
+pub const PROPAGATION_DELAY_BUCKETS_IN_MS: [u64; 10] = [25, 50, 100, 250, 500, 1_000, 2_500, 5_000, 10_000, 60_000];
+/// The probability that a node reports the time it first saw a transaction, as `1` in this number.
+pub const PROPAGATION_REPORT_PROBABILITY: u32 = 16;
+/// The maximum number of reports that are accepted from a peer in each interval.
+const MAXIMUM_REPORTS_PER_INTERVAL: usize = 10;
+/// The duration in seconds of the interval over which the reports from a peer are counted.
+const REPORT_INTERVAL_IN_SECS: u64 = 1;
+/// The maximum propagation delay in milliseconds; later reports are discarded as implausible.
+const MAXIMUM_PROPAGATION_DELAY_IN_MS: i64 = 60_000;
+/// The maximum number of sampled transactions that await reports.
+const MAXIMUM_SAMPLED_TRANSACTIONS: usize = 1 << 10;
+/// The maximum number of recently seen transactions whose reports are relayed towards their origin.
+const MAXIMUM_RELAYED_TRANSACTIONS: usize = 1 << 14;
+
+/// The outcome of a propagation report received from a peer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PropagationOutcome<N: Network> {
+    /// The report is for a transaction sampled by this node, and its delay in milliseconds was recorded.
+    Recorded(u64),
+    /// The report is relayed to the given peer, which this node first received the transaction from.
+    Relayed(SocketAddr, PropagationSample<N>),
+    /// The report is discarded, as it is unknown, implausible, or exceeded the number of hops.
+    Discarded,
+    /// The report is discarded, as the peer exceeded its rate of reports.
+    RateLimited,
+}
+
+/// The histogram of the propagation delays of the sampled transactions.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct PropagationHistogram {
+    /// The number of recorded delays.
+    pub count: u64,
+    /// The sum of the recorded delays in milliseconds.
+    pub sum_ms: u64,
+    /// The number of recorded delays in each bucket, by the upper bound of the bucket in milliseconds.
+    pub buckets: Vec<(u64, u64)>,
+}
+
+impl Default for PropagationHistogram {
+    /// Initializes a new histogram, with no recorded delays.
+    fn default() -> Self {
+        Self { count: 0, sum_ms: 0, buckets: PROPAGATION_DELAY_BUCKETS_IN_MS.iter().map(|bound| (*bound, 0)).collect() }
+    }
+}
+
+impl PropagationHistogram {
+    /// Records the given delay in milliseconds.
+    fn record(&mut self, delay_ms: u64) {
+        if let Some((_, count)) = self.buckets.iter_mut().find(|(bound, _)| delay_ms <= *bound) {
+            *count += 1;
+            self.count += 1;
+            self.sum_ms += delay_ms;
+        }
+    }
+
+    /// Returns the upper bound in milliseconds of the bucket containing the given percentile, if any delay was recorded.
+    pub fn percentile(&self, percentile: f64) -> Option<u64> {
+        let target = (self.count as f64 * percentile / 100.0).ceil().max(1.0) as u64;
+        let mut cumulative = 0;
+        for (bound, count) in &self.buckets {
+            cumulative += count;
+            if cumulative >= target {
+                return Some(*bound);
+            }
+        }
+        None
+    }
+}
+
+/// The sampler of the propagation delay of transactions.
+///
+/// A fraction of the transactions broadcast by this node are sampled, and their broadcast time is retained.
+/// Every node reports the time it first saw a small fraction of the transactions to the peer it received them from,
+/// which relays the report along the same path, until it reaches the origin of the transaction.
+pub struct PropagationSampler<N: Network> {
+    /// The fraction of the transactions broadcast by this node that are sampled.
+    sample_rate: RwLock<f64>,
+    /// The probability that this node reports the time it first saw a transaction, as `1` in this number.
+    report_probability: u32,
+    /// The sampled transactions, mapped to the UNIX timestamp in milliseconds at which they were broadcast.
+    sampled: Mutex<LinkedHashMap<N::TransactionID, i64>>,
+    /// The recently seen transactions, mapped to the peer they were first received from.
+    upstream: Mutex<LinkedHashMap<N::TransactionID, SocketAddr>>,
+    /// The start of the current interval, and the number of reports received from each peer in the interval.
+    reports: Mutex<(Instant, HashMap<SocketAddr, usize>)>,
+    /// The histogram of the propagation delays.
+    histogram: RwLock<PropagationHistogram>,
+}
+
+impl<N: Network> Default for PropagationSampler<N> {
+    /// Initializes a new propagation sampler, which does not sample any transactions.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N: Network> PropagationSampler<N> {
+    /// Initializes a new propagation sampler, which does not sample any transactions.
+    pub fn new() -> Self {
+        Self {
+            sample_rate: RwLock::new(0.0),
+            report_probability: PROPAGATION_REPORT_PROBABILITY,
+            sampled: Default::default(),
+            upstream: Default::default(),
+            reports: Mutex::new((Instant::now(), HashMap::new())),
+            histogram: Default::default(),
+        }
+    }
+
+    /// Sets the probability that this node reports the time it first saw a transaction, as `1` in the given number.
+    pub fn with_report_probability(mut self, report_probability: u32) -> Self {
+        self.report_probability = report_probability.max(1);
+        self
+    }
+
+    /// Returns the fraction of the transactions broadcast by this node that are sampled.
+    pub fn sample_rate(&self) -> f64 {
+        *self.sample_rate.read()
+    }
+
+    /// Sets the fraction of the transactions broadcast by this node that are sampled, between `0.0` and `1.0`.
+    pub fn set_sample_rate(&self, sample_rate: f64) {
+        *self.sample_rate.write() = sample_rate.clamp(0.0, 1.0);
+    }
+
+    /// Returns the histogram of the propagation delays.
+    pub fn histogram(&self) -> PropagationHistogram {
+        self.histogram.read().clone()
+    }
+
+    /// Samples the given transaction, which is broadcast by this node, with the configured probability.
+    /// Returns `true` if the transaction was sampled.
+    pub fn sample_broadcast(&self, transaction_id: N::TransactionID) -> bool {
+        let sample_rate = self.sample_rate();
+        if sample_rate <= 0.0 || !rand::thread_rng().gen_bool(sample_rate) {
+            return false;
+        }
+        self.insert_sampled(transaction_id, now_unix_millis());
+        true
+    }
+
+    /// Records that the given transaction was first received from the given peer, and returns the report
+    /// to send to the peer, if this node reports the time it first saw the transaction.
+    pub fn observe_first_seen(
+        &self,
+        peer_ip: SocketAddr,
+        transaction_id: N::TransactionID,
+    ) -> Option<PropagationSample<N>> {
+        // Skip the transactions broadcast by this node, as they are echoed back by its peers.
+        if self.sampled.lock().contains_key(&transaction_id) {
+            return None;
+        }
+        // Retain the peer, to relay the reports of the transaction towards its origin.
+        {
+            let mut upstream = self.upstream.lock();
+            upstream.insert(transaction_id, peer_ip);
+            while upstream.len() > MAXIMUM_RELAYED_TRANSACTIONS {
+                upstream.pop_front();
+            }
+        }
+        // Report the time this node first saw the transaction, with a small probability.
+        match rand::thread_rng().gen_ratio(1, self.report_probability) {
+            true => Some(PropagationSample { transaction_id, first_seen: now_unix_millis(), hops: 0 }),
+            false => None,
+        }
+    }
+
+    /// Processes the given report from the given peer.
+    pub fn process_report(&self, peer_ip: SocketAddr, sample: PropagationSample<N>) -> PropagationOutcome<N> {
+        self.process_report_at(peer_ip, sample, Instant::now())
+    }
+
+    /// Records the given transaction as sampled, as broadcast at the given UNIX timestamp in milliseconds.
+    fn insert_sampled(&self, transaction_id: N::TransactionID, broadcast_at: i64) {
+        let mut sampled = self.sampled.lock();
+        sampled.insert(transaction_id, broadcast_at);
+        while sampled.len() > MAXIMUM_SAMPLED_TRANSACTIONS {
+            sampled.pop_front();
+        }
+    }
+
+    /// Processes the given report from the given peer, as of the given time.
+    fn process_report_at(
+        &self,
+        peer_ip: SocketAddr,
+        sample: PropagationSample<N>,
+        now: Instant,
+    ) -> PropagationOutcome<N> {
+        // Ensure the peer is within its rate of reports.
+        {
+            let mut reports = self.reports.lock();
+            if now.saturating_duration_since(reports.0) >= Duration::from_secs(REPORT_INTERVAL_IN_SECS) {
+                *reports = (now, HashMap::new());
+            }
+            let num_reports = reports.1.entry(peer_ip).or_default();
+            *num_reports += 1;
+            if *num_reports > MAXIMUM_REPORTS_PER_INTERVAL {
+                return PropagationOutcome::RateLimited;
+            }
+        }
+
+        // If the transaction was sampled by this node, record its propagation delay.
+        if let Some(broadcast_at) = self.sampled.lock().get(&sample.transaction_id).copied() {
+            let delay = sample.first_seen.saturating_sub(broadcast_at);
+            // Note: A report from before the broadcast, or long after it, is the result of a skewed clock.
+            if !(0..=MAXIMUM_PROPAGATION_DELAY_IN_MS).contains(&delay) {
+                return PropagationOutcome::Discarded;
+            }
+            self.histogram.write().record(delay as u64);
+            return PropagationOutcome::Recorded(delay as u64);
+        }
+
+        // Otherwise, relay the report to the peer the transaction was first received from.
+        match self.upstream.lock().get(&sample.transaction_id).copied() {
+            Some(upstream) if upstream != peer_ip && sample.hops < PropagationSample::<N>::MAXIMUM_HOPS => {
+                PropagationOutcome::Relayed(upstream, PropagationSample { hops: sample.hops + 1, ..sample })
+            }
+            _ => PropagationOutcome::Discarded,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkvm::prelude::{MainnetV0, TestRng};
+
+    use std::net::{IpAddr, Ipv4Addr};
+
+    type CurrentNetwork = MainnetV0;
+
+    /// Returns a sample peer IP with the given port.
+    fn sample_peer_ip(port: u16) -> SocketAddr {
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port)
+    }
+
+    /// Returns a report of the given transaction, first seen at the given UNIX timestamp in milliseconds.
+    fn sample_report(
+        transaction_id: <CurrentNetwork as Network>::TransactionID,
+        first_seen: i64,
+    ) -> PropagationSample<CurrentNetwork> {
+        PropagationSample { transaction_id, first_seen, hops: 0 }
+    }
+
+    #[test]
+    fn test_record_sampled_transaction() {
+        let rng = &mut TestRng::default();
+        let sampler = PropagationSampler::<CurrentNetwork>::new();
+        let peer_ip = sample_peer_ip(1234);
+        let (sampled, unknown) = (rng.gen(), rng.gen());
+
+        // Ensure no transaction is sampled by default.
+        assert!(!sampler.sample_broadcast(sampled));
+        sampler.set_sample_rate(1.0);
+        assert!(sampler.sample_broadcast(sampled));
+        sampler.insert_sampled(sampled, 1_000);
+
+        // Ensure the delay of a sampled transaction is recorded.
+        let now = Instant::now();
+        assert_eq!(
+            sampler.process_report_at(peer_ip, sample_report(sampled, 1_200), now),
+            PropagationOutcome::Recorded(200)
+        );
+        assert_eq!(
+            sampler.process_report_at(peer_ip, sample_report(sampled, 1_030), now),
+            PropagationOutcome::Recorded(30)
+        );
+        // Ensure implausible delays and unknown transactions are discarded.
+        assert_eq!(sampler.process_report_at(peer_ip, sample_report(sampled, 900), now), PropagationOutcome::Discarded);
+        assert_eq!(
+            sampler.process_report_at(peer_ip, sample_report(sampled, 100_000), now),
+            PropagationOutcome::Discarded
+        );
+        assert_eq!(
+            sampler.process_report_at(peer_ip, sample_report(unknown, 1_200), now),
+            PropagationOutcome::Discarded
+        );
+
+        // Check the histogram.
+        let histogram = sampler.histogram();
+        assert_eq!(histogram.count, 2);
+        assert_eq!(histogram.sum_ms, 230);
+        assert_eq!(histogram.buckets.iter().map(|(_, count)| count).sum::<u64>(), 2);
+        assert_eq!(histogram.percentile(50.0), Some(50));
+        assert_eq!(histogram.percentile(90.0), Some(250));
+        assert_eq!(PropagationHistogram::default().percentile(50.0), None);
+    }
+
+    #[test]
+    fn test_relay_report_upstream() {
+        let rng = &mut TestRng::default();
+        let sampler = PropagationSampler::<CurrentNetwork>::new();
+        let (upstream, downstream) = (sample_peer_ip(1234), sample_peer_ip(5678));
+        let transaction_id = rng.gen();
+
+        // Ensure the report is relayed to the peer the transaction was first received from.
+        sampler.observe_first_seen(upstream, transaction_id);
+        let now = Instant::now();
+        let report = sample_report(transaction_id, 1_000);
+        let relayed = PropagationSample { hops: 1, ..report };
+        assert_eq!(sampler.process_report_at(downstream, report, now), PropagationOutcome::Relayed(upstream, relayed));
+        // Ensure the report is not relayed back to the peer it was received from.
+        assert_eq!(sampler.process_report_at(upstream, report, now), PropagationOutcome::Discarded);
+        // Ensure the report is not relayed beyond the maximum number of hops.
+        let exhausted = PropagationSample { hops: PropagationSample::<CurrentNetwork>::MAXIMUM_HOPS, ..report };
+        assert_eq!(sampler.process_report_at(downstream, exhausted, now), PropagationOutcome::Discarded);
+        // Ensure the transactions broadcast by this node are not relayed, nor reported.
+        sampler.insert_sampled(transaction_id, 1_000);
+        assert!(sampler.observe_first_seen(upstream, transaction_id).is_none());
+    }
+
+    #[test]
+    fn test_report_rate_limit() {
+        let rng = &mut TestRng::default();
+        let sampler = PropagationSampler::<CurrentNetwork>::new();
+        let (peer_ip, other_ip) = (sample_peer_ip(1234), sample_peer_ip(5678));
+        let report = sample_report(rng.gen(), 1_000);
+
+        // Ensure the reports beyond the rate of a peer are rate limited, without affecting other peers.
+        let now = Instant::now();
+        for _ in 0..MAXIMUM_REPORTS_PER_INTERVAL {
+            assert_eq!(sampler.process_report_at(peer_ip, report, now), PropagationOutcome::Discarded);
+        }
+        assert_eq!(sampler.process_report_at(peer_ip, report, now), PropagationOutcome::RateLimited);
+        assert_eq!(sampler.process_report_at(other_ip, report, now), PropagationOutcome::Discarded);
+        // Ensure the rate resets in the next interval.
+        let later = now + Duration::from_secs(REPORT_INTERVAL_IN_SECS);
+        assert_eq!(sampler.process_report_at(peer_ip, report, later), PropagationOutcome::Discarded);
+    }
+}
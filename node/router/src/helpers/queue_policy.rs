@@ -0,0 +1,43 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::messages::Message;
+use snarkos_node_tcp::QueuePolicy;
+use snarkvm::prelude::Network;
+
+/// Returns the queue policy of the given outbound message, which determines whether it is dropped
+/// if the outbound queue of the peer is full. Gossip is dropped first, and direct responses are never dropped.
+pub fn queue_policy<N: Network>(message: &Message<N>) -> QueuePolicy {
+    match message {
+        Message::UnconfirmedSolution(..) | Message::UnconfirmedTransaction(..) => QueuePolicy::Gossip,
+        Message::BlockByHashResponse(..)
+        | Message::BlockResponse(..)
+        | Message::BlockTransactions(..)
+        | Message::PeerResponse(..)
+        | Message::PuzzleResponse(..) => QueuePolicy::Response,
+        Message::BlockByHashRequest(..)
+        | Message::BlockRequest(..)
+        | Message::ChallengeRequest(..)
+        | Message::ChallengeResponse(..)
+        | Message::CompactBlock(..)
+        | Message::Disconnect(..)
+        | Message::GetBlockTransactions(..)
+        | Message::PeerRequest(..)
+        | Message::Ping(..)
+        | Message::Pong(..)
+        | Message::PropagationSample(..)
+        | Message::PuzzleRequest(..)
+        | Message::Reject(..) => QueuePolicy::Refuse,
+    }
+}
@@ -0,0 +1,155 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::messages::{RejectReason, RejectedItem};
+use snarkvm::prelude::Network;
+
+use linked_hash_map::LinkedHashMap;
+use parking_lot::RwLock;
+use std::{
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+/// The duration in seconds after which an item that a peer was temporarily unable to accept may be resent to it.
+pub const REJECTION_RETRY_DELAY_IN_SECS: u64 = 60;
+/// The maximum number of rejected items to track.
+const MAXIMUM_REJECTIONS: usize = 1 << 16;
+
+/// A helper containing the peer IP and the item it rejected.
+type RejectionKey<N> = (SocketAddr, RejectedItem<N>);
+
+/// The items that were rejected by peers, which are not resent to the peer that rejected them.
+#[derive(Debug)]
+pub struct Rejections<N: Network> {
+    /// The map of the peer IP and rejected item, to the reason and time of the rejection.
+    rejections: RwLock<LinkedHashMap<RejectionKey<N>, (RejectReason, Instant)>>,
+}
+
+impl<N: Network> Default for Rejections<N> {
+    /// Initializes a new instance of the rejections.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N: Network> Rejections<N> {
+    /// Initializes a new instance of the rejections.
+    pub fn new() -> Self {
+        Self { rejections: Default::default() }
+    }
+
+    /// Records that the given peer rejected the given item for the given reason.
+    pub fn insert(&self, peer_ip: SocketAddr, item: RejectedItem<N>, reason: RejectReason) {
+        self.insert_at(peer_ip, item, reason, Instant::now())
+    }
+
+    /// Returns the reason the given peer rejected the given item, if the rejection is still in effect.
+    pub fn get(&self, peer_ip: SocketAddr, item: RejectedItem<N>) -> Option<RejectReason> {
+        self.get_at(peer_ip, item, Instant::now())
+    }
+
+    /// Returns `true` if the given item should not be sent to the given peer, as the peer rejected it.
+    pub fn is_rejected(&self, peer_ip: SocketAddr, item: RejectedItem<N>) -> bool {
+        self.get(peer_ip, item).is_some()
+    }
+
+    /// Returns the number of tracked rejections.
+    pub fn len(&self) -> usize {
+        self.rejections.read().len()
+    }
+
+    /// Returns `true` if there are no tracked rejections.
+    pub fn is_empty(&self) -> bool {
+        self.rejections.read().is_empty()
+    }
+
+    /// Records that the given peer rejected the given item for the given reason, as of the given time.
+    fn insert_at(&self, peer_ip: SocketAddr, item: RejectedItem<N>, reason: RejectReason, now: Instant) {
+        let mut rejections = self.rejections.write();
+        rejections.insert((peer_ip, item), (reason, now));
+        // Evict the oldest rejections, if the map exceeds the maximum size.
+        while rejections.len() > MAXIMUM_REJECTIONS {
+            rejections.pop_front();
+        }
+    }
+
+    /// Returns the reason the given peer rejected the given item, if the rejection is in effect as of the given time.
+    /// A permanent rejection is always in effect, while a temporary rejection expires after the retry delay.
+    fn get_at(&self, peer_ip: SocketAddr, item: RejectedItem<N>, now: Instant) -> Option<RejectReason> {
+        let key = (peer_ip, item);
+        let (reason, rejected_at) = *self.rejections.read().get(&key)?;
+        // Determine whether the rejection is still in effect.
+        let retry_delay = Duration::from_secs(REJECTION_RETRY_DELAY_IN_SECS);
+        if reason.is_permanent() || now < rejected_at + retry_delay {
+            return Some(reason);
+        }
+        // Forget the expired rejection.
+        self.rejections.write().remove(&key);
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkvm::prelude::{puzzle::SolutionID, MainnetV0, Rng, TestRng};
+
+    use std::net::{IpAddr, Ipv4Addr};
+
+    type CurrentNetwork = MainnetV0;
+
+    /// Returns a sample peer IP with the given port.
+    fn sample_peer_ip(port: u16) -> SocketAddr {
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port)
+    }
+
+    #[test]
+    fn test_permanent_rejection_is_never_retried() {
+        let rng = &mut TestRng::default();
+        let rejections = Rejections::<CurrentNetwork>::new();
+        let (peer_ip, other_ip) = (sample_peer_ip(1234), sample_peer_ip(5678));
+        let item = RejectedItem::Transaction(rng.gen());
+
+        let now = Instant::now();
+        rejections.insert_at(peer_ip, item, RejectReason::Invalid, now);
+
+        // Check that the item is rejected only by the peer that rejected it.
+        assert_eq!(rejections.get_at(peer_ip, item, now), Some(RejectReason::Invalid));
+        assert_eq!(rejections.get_at(other_ip, item, now), None);
+        // Check that the item is still rejected long after the rejection.
+        let later = now + Duration::from_secs(100 * REJECTION_RETRY_DELAY_IN_SECS);
+        assert_eq!(rejections.get_at(peer_ip, item, later), Some(RejectReason::Invalid));
+        assert_eq!(rejections.len(), 1);
+    }
+
+    #[test]
+    fn test_temporary_rejection_is_retried_later() {
+        let rejections = Rejections::<CurrentNetwork>::new();
+        let peer_ip = sample_peer_ip(1234);
+        let item = RejectedItem::Solution(SolutionID::from(1u64));
+
+        let now = Instant::now();
+        rejections.insert_at(peer_ip, item, RejectReason::TemporarilyFull, now);
+
+        // Check that the item is rejected until the retry delay has elapsed.
+        let retry_delay = Duration::from_secs(REJECTION_RETRY_DELAY_IN_SECS);
+        let reason = Some(RejectReason::TemporarilyFull);
+        assert_eq!(rejections.get_at(peer_ip, item, now), reason);
+        assert_eq!(rejections.get_at(peer_ip, item, now + retry_delay - Duration::from_secs(1)), reason);
+        assert_eq!(rejections.get_at(peer_ip, item, now + retry_delay), None);
+        // Check that the expired rejection was forgotten.
+        assert!(rejections.is_empty());
+    }
+}
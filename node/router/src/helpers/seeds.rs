@@ -0,0 +1,435 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::PeerSource;
+use snarkos_node_tcp::resolve_through_proxy;
+use snarkvm::prelude::{Address, Network, PrivateKey, Signature};
+
+use anyhow::{bail, ensure, Result};
+use parking_lot::Mutex;
+use rand::{CryptoRng, Rng};
+use serde::{Deserialize, Serialize};
+use std::{
+    net::SocketAddr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// The port of the peers resolved from a DNS seed, if the seed does not specify one.
+pub const DEFAULT_SEED_PORT: u16 = 4130;
+/// The duration between the checks of whether the seeds should be queried again.
+pub const SEED_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// The minimum duration between two discoveries from the seeds (5 minutes).
+pub const SEED_REDISCOVERY_INTERVAL: Duration = Duration::from_secs(5 * 60);
+/// The number of connected peers below which the seeds are queried again.
+pub const SEED_REDISCOVERY_THRESHOLD: usize = 3;
+/// The maximum number of peers accepted from a single seed list.
+pub const SEED_LIST_MAX_PEERS: usize = 1000;
+/// The maximum age in seconds of a seed list, so that a stale list cannot be replayed (7 days).
+pub const SEED_LIST_MAX_AGE_IN_SECS: i64 = 7 * 24 * 60 * 60;
+/// The timeout of fetching a seed list.
+const SEED_LIST_TIMEOUT: Duration = Duration::from_secs(10);
+/// The domain separator of the signed bytes of a seed list.
+const SEED_LIST_DOMAIN: &[u8] = b"snarkOS seed list";
+
+/// A resolver of the hostnames of the DNS seeds.
+#[async_trait]
+pub trait SeedResolver: Send + Sync {
+    /// Resolves the given `host:port` into its addresses.
+    async fn resolve(&self, host: &str) -> Result<Vec<SocketAddr>>;
+}
+
+/// A fetcher of the seed lists.
+#[async_trait]
+pub trait SeedListFetcher: Send + Sync {
+    /// Fetches the document at the given URL.
+    async fn fetch(&self, url: &str) -> Result<Vec<u8>>;
+}
+
+/// A resolver over the DNS of the operating system.
+pub struct DnsSeedResolver;
+
+#[async_trait]
+impl SeedResolver for DnsSeedResolver {
+    async fn resolve(&self, host: &str) -> Result<Vec<SocketAddr>> {
+        Ok(tokio::net::lookup_host(host).await?.collect())
+    }
+}
+
+/// A resolver through a SOCKS5 proxy, so that the hostnames of the DNS seeds are not resolved locally.
+/// Note: The proxy must support the `RESOLVE` extension of Tor, which returns a single address.
+pub struct ProxySeedResolver {
+    /// The address of the proxy.
+    proxy: SocketAddr,
+}
+
+impl ProxySeedResolver {
+    /// Initializes a resolver through the given proxy.
+    pub const fn new(proxy: SocketAddr) -> Self {
+        Self { proxy }
+    }
+}
+
+#[async_trait]
+impl SeedResolver for ProxySeedResolver {
+    async fn resolve(&self, host: &str) -> Result<Vec<SocketAddr>> {
+        let Some((hostname, port)) = host.rsplit_once(':') else {
+            bail!("The DNS seed '{host}' is missing a port");
+        };
+        let port = port.parse::<u16>()?;
+        let ip = resolve_through_proxy(self.proxy, hostname.trim_start_matches('[').trim_end_matches(']')).await?;
+        Ok(vec![SocketAddr::new(ip, port)])
+    }
+}
+
+/// A fetcher of the seed lists over HTTPS.
+pub struct HttpSeedListFetcher;
+
+#[async_trait]
+impl SeedListFetcher for HttpSeedListFetcher {
+    async fn fetch(&self, url: &str) -> Result<Vec<u8>> {
+        ensure!(url.starts_with("https://"), "The seed list '{url}' is not served over HTTPS");
+        let client = reqwest::Client::builder().timeout(SEED_LIST_TIMEOUT).build()?;
+        let response = client.get(url).send().await?.error_for_status()?;
+        Ok(response.bytes().await?.to_vec())
+    }
+}
+
+/// A list of candidate peers, published by the operator of a seed list.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeedList {
+    /// The ID of the network of the peers.
+    pub network: u16,
+    /// The UNIX timestamp (in seconds) at which the list was signed.
+    pub timestamp: i64,
+    /// The listener addresses of the peers.
+    pub peers: Vec<SocketAddr>,
+}
+
+impl SeedList {
+    /// Returns the bytes of the list that are signed.
+    pub fn to_signing_bytes(&self) -> Vec<u8> {
+        let mut bytes = SEED_LIST_DOMAIN.to_vec();
+        bytes.extend_from_slice(&self.network.to_le_bytes());
+        bytes.extend_from_slice(&self.timestamp.to_le_bytes());
+        for peer in &self.peers {
+            bytes.extend_from_slice(peer.to_string().as_bytes());
+            bytes.push(b'\n');
+        }
+        bytes
+    }
+}
+
+/// A seed list, signed by its operator. The document is served as JSON.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedSeedList<N: Network> {
+    /// The list of candidate peers.
+    pub list: SeedList,
+    /// The signature of the list by its operator.
+    pub signature: Signature<N>,
+}
+
+impl<N: Network> SignedSeedList<N> {
+    /// Signs the given list with the given private key.
+    pub fn sign<R: Rng + CryptoRng>(private_key: &PrivateKey<N>, list: SeedList, rng: &mut R) -> Result<Self> {
+        let signature = Signature::sign_bytes(private_key, &list.to_signing_bytes(), rng)?;
+        Ok(Self { list, signature })
+    }
+
+    /// Parses the given document, and returns its peers if it is signed by the given signer,
+    /// and is a recent list for the network of this node, as of the given UNIX timestamp.
+    pub fn verify(document: &[u8], signer: &Address<N>, now: i64) -> Result<Vec<SocketAddr>> {
+        let Self { list, signature } = serde_json::from_slice::<Self>(document)?;
+        if !signature.verify_bytes(signer, &list.to_signing_bytes()) {
+            bail!("The seed list is not signed by '{signer}'");
+        }
+        ensure!(list.network == N::ID, "The seed list is for network ID {} (expected {})", list.network, N::ID);
+        ensure!(now.saturating_sub(list.timestamp) <= SEED_LIST_MAX_AGE_IN_SECS, "The seed list is stale");
+        ensure!(list.peers.len() <= SEED_LIST_MAX_PEERS, "The seed list has too many peers ({})", list.peers.len());
+        Ok(list.peers)
+    }
+}
+
+/// The location of a seed list, and the address of its operator that signs it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SeedListSource<N: Network> {
+    /// The HTTPS URL of the seed list.
+    pub url: String,
+    /// The address that signs the seed list.
+    /// Note: The signer is pinned in the configuration of the node, so that a hijacked DNS record cannot forge a list.
+    pub signer: Address<N>,
+}
+
+/// The discovery of candidate peers from the DNS seeds and the seed lists.
+pub struct SeedDiscovery<N: Network> {
+    /// The hostnames of the DNS seeds, with an optional port.
+    dns_seeds: Vec<String>,
+    /// The seed lists.
+    seed_lists: Vec<SeedListSource<N>>,
+    /// The resolver of the DNS seeds.
+    resolver: Arc<dyn SeedResolver>,
+    /// The fetcher of the seed lists.
+    fetcher: Arc<dyn SeedListFetcher>,
+    /// The time of the last discovery, if the seeds were queried.
+    last_discovery: Mutex<Option<Instant>>,
+}
+
+impl<N: Network> SeedDiscovery<N> {
+    /// Initializes the discovery from the given DNS seeds and seed lists.
+    pub fn new(dns_seeds: Vec<String>, seed_lists: Vec<SeedListSource<N>>) -> Self {
+        Self {
+            dns_seeds,
+            seed_lists,
+            resolver: Arc::new(DnsSeedResolver),
+            fetcher: Arc::new(HttpSeedListFetcher),
+            last_discovery: Default::default(),
+        }
+    }
+
+    /// Sets the resolver of the DNS seeds.
+    pub fn with_resolver(mut self, resolver: Arc<dyn SeedResolver>) -> Self {
+        self.resolver = resolver;
+        self
+    }
+
+    /// Sets the fetcher of the seed lists.
+    pub fn with_fetcher(mut self, fetcher: Arc<dyn SeedListFetcher>) -> Self {
+        self.fetcher = fetcher;
+        self
+    }
+
+    /// Returns `true` if there are no seeds to query.
+    pub fn is_empty(&self) -> bool {
+        self.dns_seeds.is_empty() && self.seed_lists.is_empty()
+    }
+
+    /// Returns `true` if the seeds should be queried, given the number of connected peers, as of the given time.
+    /// The seeds are queried once on startup, and again while the node has few peers, at most once per interval.
+    pub fn is_due(&self, num_connected: usize, now: Instant) -> bool {
+        match *self.last_discovery.lock() {
+            None => true,
+            Some(last_discovery) => {
+                num_connected < SEED_REDISCOVERY_THRESHOLD
+                    && now.saturating_duration_since(last_discovery) >= SEED_REDISCOVERY_INTERVAL
+            }
+        }
+    }
+
+    /// Queries the DNS seeds and the seed lists, and returns the discovered peers along with their source.
+    /// The seeds that fail are logged and skipped, so that the node proceeds with the peers it already knows.
+    pub async fn discover(&self) -> Vec<(SocketAddr, PeerSource)> {
+        *self.last_discovery.lock() = Some(Instant::now());
+
+        let mut peers = Vec::new();
+        for seed in &self.dns_seeds {
+            // Note: The port of the seed is optional, as DNS only resolves the IP addresses.
+            let host = match seed.contains(':') {
+                true => seed.clone(),
+                false => format!("{seed}:{DEFAULT_SEED_PORT}"),
+            };
+            match self.resolver.resolve(&host).await {
+                Ok(addrs) => {
+                    debug!("Resolved {} peers from the DNS seed '{seed}'", addrs.len());
+                    peers.extend(addrs.into_iter().map(|addr| (addr, PeerSource::DnsSeed)));
+                }
+                Err(error) => warn!("Unable to resolve the DNS seed '{seed}' - {error}"),
+            }
+        }
+        let now = time::OffsetDateTime::now_utc().unix_timestamp();
+        for source in &self.seed_lists {
+            let result = match self.fetcher.fetch(&source.url).await {
+                Ok(document) => SignedSeedList::<N>::verify(&document, &source.signer, now),
+                Err(error) => Err(error),
+            };
+            match result {
+                Ok(addrs) => {
+                    debug!("Fetched {} peers from the seed list '{}'", addrs.len(), source.url);
+                    peers.extend(addrs.into_iter().map(|addr| (addr, PeerSource::SeedList)));
+                }
+                Err(error) => warn!("Unable to fetch the seed list '{}' - {error}", source.url),
+            }
+        }
+        peers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkvm::prelude::{MainnetV0, TestRng};
+
+    use std::collections::HashMap;
+
+    type CurrentNetwork = MainnetV0;
+
+    /// A resolver of fixed hostnames, which counts its queries.
+    #[derive(Default)]
+    struct MockResolver {
+        hosts: HashMap<String, Vec<SocketAddr>>,
+        num_queries: Mutex<usize>,
+    }
+
+    #[async_trait]
+    impl SeedResolver for MockResolver {
+        async fn resolve(&self, host: &str) -> Result<Vec<SocketAddr>> {
+            *self.num_queries.lock() += 1;
+            match self.hosts.get(host) {
+                Some(addrs) => Ok(addrs.clone()),
+                None => bail!("Unknown host '{host}'"),
+            }
+        }
+    }
+
+    /// A fetcher of fixed documents.
+    #[derive(Default)]
+    struct MockFetcher {
+        documents: HashMap<String, Vec<u8>>,
+    }
+
+    #[async_trait]
+    impl SeedListFetcher for MockFetcher {
+        async fn fetch(&self, url: &str) -> Result<Vec<u8>> {
+            match self.documents.get(url) {
+                Some(document) => Ok(document.clone()),
+                None => bail!("Unknown URL '{url}'"),
+            }
+        }
+    }
+
+    fn addr(addr: &str) -> SocketAddr {
+        addr.parse().unwrap()
+    }
+
+    fn now() -> i64 {
+        time::OffsetDateTime::now_utc().unix_timestamp()
+    }
+
+    /// Returns a signer, and a seed list document that it signed.
+    fn sample_signed_list(rng: &mut TestRng) -> (Address<CurrentNetwork>, SignedSeedList<CurrentNetwork>) {
+        let private_key = PrivateKey::new(rng).unwrap();
+        let list = SeedList {
+            network: CurrentNetwork::ID,
+            timestamp: now(),
+            peers: vec![addr("1.2.3.4:4130"), addr("5.6.7.8:4130")],
+        };
+        (Address::try_from(&private_key).unwrap(), SignedSeedList::sign(&private_key, list, rng).unwrap())
+    }
+
+    #[test]
+    fn test_seed_list_signature_is_verified() {
+        let rng = &mut TestRng::default();
+        let (signer, signed) = sample_signed_list(rng);
+        let document = serde_json::to_vec(&signed).unwrap();
+
+        // Ensure the signed list is accepted.
+        let peers = SignedSeedList::<CurrentNetwork>::verify(&document, &signer, now()).unwrap();
+        assert_eq!(peers, signed.list.peers);
+
+        // Ensure a list with an injected peer is refused.
+        let mut tampered = signed.clone();
+        tampered.list.peers.push(addr("9.9.9.9:4130"));
+        let document = serde_json::to_vec(&tampered).unwrap();
+        assert!(SignedSeedList::<CurrentNetwork>::verify(&document, &signer, now()).is_err());
+
+        // Ensure a list signed by another key is refused.
+        let (other_signer, _) = sample_signed_list(rng);
+        let document = serde_json::to_vec(&signed).unwrap();
+        let error = SignedSeedList::<CurrentNetwork>::verify(&document, &other_signer, now()).unwrap_err();
+        assert!(error.to_string().contains("is not signed by"), "{error}");
+
+        // Ensure a malformed document is refused.
+        assert!(SignedSeedList::<CurrentNetwork>::verify(b"{\"peers\": []}", &signer, now()).is_err());
+    }
+
+    #[test]
+    fn test_seed_list_refuses_stale_or_foreign_lists() {
+        let rng = &mut TestRng::default();
+        let private_key = PrivateKey::<CurrentNetwork>::new(rng).unwrap();
+        let signer = Address::try_from(&private_key).unwrap();
+        let sign = |list: SeedList, rng: &mut TestRng| {
+            serde_json::to_vec(&SignedSeedList::sign(&private_key, list, rng).unwrap()).unwrap()
+        };
+
+        // Ensure a stale list cannot be replayed.
+        let list =
+            SeedList { network: CurrentNetwork::ID, timestamp: now() - SEED_LIST_MAX_AGE_IN_SECS - 1, peers: vec![] };
+        let error = SignedSeedList::<CurrentNetwork>::verify(&sign(list, rng), &signer, now()).unwrap_err();
+        assert!(error.to_string().contains("stale"), "{error}");
+
+        // Ensure a list for another network is refused.
+        let list = SeedList { network: CurrentNetwork::ID + 1, timestamp: now(), peers: vec![] };
+        let error = SignedSeedList::<CurrentNetwork>::verify(&sign(list, rng), &signer, now()).unwrap_err();
+        assert!(error.to_string().contains("network ID"), "{error}");
+    }
+
+    #[tokio::test]
+    async fn test_discover_tags_sources_and_skips_failures() {
+        let rng = &mut TestRng::default();
+        let (signer, signed) = sample_signed_list(rng);
+        let resolver = MockResolver {
+            hosts: [("seed.example.com:4130".to_string(), vec![addr("10.1.1.1:4130")])].into_iter().collect(),
+            ..Default::default()
+        };
+        let (good_url, missing_url) = ("https://seeds.example.com/good.json", "https://seeds.example.com/missing.json");
+        let fetcher = MockFetcher {
+            documents: [(good_url.to_string(), serde_json::to_vec(&signed).unwrap())].into_iter().collect(),
+        };
+        let seed_lists = vec![SeedListSource { url: good_url.to_string(), signer }, SeedListSource {
+            url: missing_url.to_string(),
+            signer,
+        }];
+        let discovery = SeedDiscovery::<CurrentNetwork>::new(
+            vec!["seed.example.com".to_string(), "unreachable.example.com:4130".to_string()],
+            seed_lists,
+        )
+        .with_resolver(Arc::new(resolver))
+        .with_fetcher(Arc::new(fetcher));
+
+        // Ensure the peers of the working seeds are discovered, tagged with their source.
+        let peers = discovery.discover().await;
+        assert_eq!(peers, vec![
+            (addr("10.1.1.1:4130"), PeerSource::DnsSeed),
+            (addr("1.2.3.4:4130"), PeerSource::SeedList),
+            (addr("5.6.7.8:4130"), PeerSource::SeedList),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_discover_with_all_seeds_failing() {
+        let discovery = SeedDiscovery::<CurrentNetwork>::new(vec!["unreachable.example.com".to_string()], vec![])
+            .with_resolver(Arc::new(MockResolver::default()));
+        assert!(discovery.discover().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rediscovery_is_due_with_few_peers() {
+        let resolver = Arc::new(MockResolver::default());
+        let discovery = SeedDiscovery::<CurrentNetwork>::new(vec!["seed.example.com".to_string()], vec![])
+            .with_resolver(resolver.clone());
+
+        // Ensure the seeds are queried on startup.
+        let now = Instant::now();
+        assert!(discovery.is_due(10, now));
+        discovery.discover().await;
+        assert_eq!(*resolver.num_queries.lock(), 1);
+
+        // Ensure the seeds are not queried again while the node has enough peers.
+        let later = Instant::now() + SEED_REDISCOVERY_INTERVAL;
+        assert!(!discovery.is_due(SEED_REDISCOVERY_THRESHOLD, later));
+        // Ensure the seeds are not queried again within the interval, even with few peers.
+        assert!(!discovery.is_due(0, Instant::now()));
+        // Ensure the seeds are queried again once the peer count is low, after the interval.
+        assert!(discovery.is_due(SEED_REDISCOVERY_THRESHOLD - 1, later));
+    }
+}
@@ -0,0 +1,180 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkvm::prelude::{puzzle::SolutionID, Network};
+
+use anyhow::Result;
+use lru::LruCache;
+use parking_lot::Mutex;
+use std::{num::NonZeroUsize, sync::Arc};
+use tokio::sync::Semaphore;
+
+/// The maximum number of solutions that may be verified at the same time.
+pub const MAX_PENDING_SOLUTION_VERIFICATIONS: usize = 8;
+/// The maximum number of solution verification results to cache.
+const MAX_CACHED_SOLUTION_VERIFICATIONS: usize = 1 << 12;
+
+/// The outcome of verifying an unconfirmed solution.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SolutionVerdict {
+    /// The solution is valid for the epoch and proof target, and may be propagated.
+    Valid,
+    /// The solution is invalid, or below the proof target.
+    Invalid,
+    /// The solution was not verified, as too many verifications are pending.
+    Shed,
+}
+
+/// Verifies unconfirmed solutions before they are relayed.
+///
+/// The number of pending verifications is bounded, and the verdict is cached per solution ID,
+/// epoch hash, and proof target, so that a solution is verified at most once for a given block.
+pub struct SolutionVerifier<N: Network> {
+    /// The permits for pending verifications.
+    permits: Arc<Semaphore>,
+    /// The cached verdicts, keyed by the solution ID, epoch hash, and proof target.
+    verdicts: Mutex<LruCache<(SolutionID<N>, N::BlockHash, u64), bool>>,
+}
+
+impl<N: Network> Default for SolutionVerifier<N> {
+    fn default() -> Self {
+        Self::new(MAX_PENDING_SOLUTION_VERIFICATIONS)
+    }
+}
+
+impl<N: Network> SolutionVerifier<N> {
+    /// Initializes a new solution verifier, with the given maximum number of pending verifications.
+    pub fn new(max_pending: usize) -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(max_pending)),
+            verdicts: Mutex::new(LruCache::new(NonZeroUsize::new(MAX_CACHED_SOLUTION_VERIFICATIONS).unwrap())),
+        }
+    }
+
+    /// Returns the verdict for the given solution, running `check` on the blocking pool if it is not cached.
+    pub async fn verify<F>(
+        &self,
+        solution_id: SolutionID<N>,
+        epoch_hash: N::BlockHash,
+        proof_target: u64,
+        check: F,
+    ) -> SolutionVerdict
+    where
+        F: FnOnce() -> Result<()> + Send + 'static,
+    {
+        let key = (solution_id, epoch_hash, proof_target);
+        // Return the cached verdict, if it exists.
+        if let Some(is_valid) = self.verdicts.lock().get(&key) {
+            return Self::verdict(*is_valid);
+        }
+        // Shed the verification, if too many verifications are pending.
+        let Ok(_permit) = self.permits.clone().try_acquire_owned() else {
+            return SolutionVerdict::Shed;
+        };
+        // Verify the solution.
+        let is_valid = match tokio::task::spawn_blocking(check).await {
+            Ok(result) => result.is_ok(),
+            Err(error) => {
+                warn!("Failed to verify the solution - {error}");
+                return SolutionVerdict::Shed;
+            }
+        };
+        // Cache the verdict.
+        self.verdicts.lock().put(key, is_valid);
+        Self::verdict(is_valid)
+    }
+
+    /// Returns the verdict for the given validity.
+    const fn verdict(is_valid: bool) -> SolutionVerdict {
+        match is_valid {
+            true => SolutionVerdict::Valid,
+            false => SolutionVerdict::Invalid,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkvm::prelude::{Field, MainnetV0, TestRng, Uniform};
+
+    use anyhow::bail;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    type CurrentNetwork = MainnetV0;
+
+    /// Returns a sample epoch hash.
+    fn sample_epoch_hash() -> <CurrentNetwork as Network>::BlockHash {
+        Field::<CurrentNetwork>::rand(&mut TestRng::default()).into()
+    }
+
+    #[tokio::test]
+    async fn test_invalid_solution_is_cached() {
+        let verifier = SolutionVerifier::<CurrentNetwork>::default();
+        let epoch_hash = sample_epoch_hash();
+        let num_checks = Arc::new(AtomicUsize::new(0));
+
+        // An invalid proof, or a proof below the target, fails the check.
+        for _ in 0..2 {
+            let num_checks_ = num_checks.clone();
+            let verdict = verifier
+                .verify(SolutionID::from(1u64), epoch_hash, 10, move || {
+                    num_checks_.fetch_add(1, Ordering::SeqCst);
+                    bail!("Solution does not meet the proof target")
+                })
+                .await;
+            assert_eq!(verdict, SolutionVerdict::Invalid);
+        }
+        // Ensure the solution was only verified once.
+        assert_eq!(num_checks.load(Ordering::SeqCst), 1);
+
+        // Ensure the solution is verified again for a new proof target.
+        let verdict = verifier.verify(SolutionID::from(1u64), epoch_hash, 11, || Ok(())).await;
+        assert_eq!(verdict, SolutionVerdict::Valid);
+    }
+
+    #[tokio::test]
+    async fn test_verifications_are_shed_when_full() {
+        let verifier = Arc::new(SolutionVerifier::<CurrentNetwork>::new(1));
+        let epoch_hash = sample_epoch_hash();
+
+        // Start a verification that blocks until released.
+        let (release, released) = std::sync::mpsc::channel::<()>();
+        let verifier_ = verifier.clone();
+        let pending = tokio::spawn(async move {
+            verifier_
+                .verify(SolutionID::from(1u64), epoch_hash, 10, move || {
+                    released.recv().unwrap();
+                    Ok(())
+                })
+                .await
+        });
+        // Wait for the verification to take the only permit.
+        while verifier.permits.available_permits() > 0 {
+            tokio::task::yield_now().await;
+        }
+
+        // Ensure a new solution is shed, and not cached.
+        let verdict = verifier.verify(SolutionID::from(2u64), epoch_hash, 10, || Ok(())).await;
+        assert_eq!(verdict, SolutionVerdict::Shed);
+
+        // Release the pending verification.
+        release.send(()).unwrap();
+        assert_eq!(pending.await.unwrap(), SolutionVerdict::Valid);
+
+        // Ensure the shed solution is verified once a permit is available.
+        let verdict = verifier.verify(SolutionID::from(2u64), epoch_hash, 10, || Ok(())).await;
+        assert_eq!(verdict, SolutionVerdict::Valid);
+    }
+}
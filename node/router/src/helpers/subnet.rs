@@ -0,0 +1,222 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use rand::{seq::SliceRandom, Rng};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+};
+
+/// A network prefix used to group peers for diversity, i.e. a /16 for IPv4 and a /32 for IPv6.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SubnetBucket(IpAddr);
+
+impl SubnetBucket {
+    /// The prefix length (in bits) of an IPv4 bucket.
+    pub const IPV4_PREFIX_LEN: u8 = 16;
+    /// The prefix length (in bits) of an IPv6 bucket.
+    pub const IPV6_PREFIX_LEN: u8 = 32;
+
+    /// Returns the bucket for the given IP address.
+    pub fn new(ip: IpAddr) -> Self {
+        // Treat IPv4-mapped IPv6 addresses as IPv4 addresses.
+        let ip = match ip {
+            IpAddr::V6(ipv6) => ipv6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+            ip => ip,
+        };
+        match ip {
+            IpAddr::V4(ipv4) => {
+                let [a, b, _, _] = ipv4.octets();
+                Self(IpAddr::V4(Ipv4Addr::new(a, b, 0, 0)))
+            }
+            IpAddr::V6(ipv6) => {
+                let [a, b, ..] = ipv6.segments();
+                Self(IpAddr::V6(Ipv6Addr::new(a, b, 0, 0, 0, 0, 0, 0)))
+            }
+        }
+    }
+
+    /// Returns the bucket for the given peer address.
+    pub fn from_peer(peer_ip: &SocketAddr) -> Self {
+        Self::new(peer_ip.ip())
+    }
+}
+
+impl fmt::Display for SubnetBucket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            IpAddr::V4(ip) => write!(f, "{ip}/{}", Self::IPV4_PREFIX_LEN),
+            IpAddr::V6(ip) => write!(f, "{ip}/{}", Self::IPV6_PREFIX_LEN),
+        }
+    }
+}
+
+/// Returns the number of given peers in each bucket.
+pub fn bucket_distribution<'a>(peers: impl IntoIterator<Item = &'a SocketAddr>) -> HashMap<SubnetBucket, usize> {
+    let mut distribution = HashMap::new();
+    for peer_ip in peers {
+        *distribution.entry(SubnetBucket::from_peer(peer_ip)).or_default() += 1;
+    }
+    distribution
+}
+
+/// Selects up to `num_peers` of the given candidate peers to dial, such that no bucket holds more
+/// than `max_per_bucket` of the connected and selected peers. Candidates from buckets with the fewest
+/// peers are preferred; if diversity is impossible, the remaining slots are filled from the buckets
+/// that still have room under the cap.
+///
+/// Trusted peers are exempt from the cap - they are neither counted against a bucket, nor capped.
+pub fn select_diverse_peers<R: Rng>(
+    candidates: impl IntoIterator<Item = SocketAddr>,
+    connected: &[SocketAddr],
+    trusted: &HashSet<SocketAddr>,
+    num_peers: usize,
+    max_per_bucket: usize,
+    rng: &mut R,
+) -> Vec<SocketAddr> {
+    // Count the connected (untrusted) peers in each bucket.
+    let mut counts = bucket_distribution(connected.iter().filter(|peer_ip| !trusted.contains(peer_ip)));
+
+    // Shuffle the candidates, so that ties within and across buckets are broken at random.
+    let mut candidates = candidates.into_iter().collect::<Vec<_>>();
+    candidates.shuffle(rng);
+
+    let mut selected = Vec::with_capacity(num_peers.min(candidates.len()));
+    // Select the trusted candidates first, as they are not subject to the cap.
+    candidates.retain(|peer_ip| match trusted.contains(peer_ip) {
+        true => {
+            selected.push(*peer_ip);
+            false
+        }
+        false => true,
+    });
+    selected.truncate(num_peers);
+
+    // Repeatedly select a candidate from the least-populated bucket that is still under the cap.
+    while selected.len() < num_peers {
+        let least_populated = candidates
+            .iter()
+            .enumerate()
+            .map(|(index, peer_ip)| (index, counts.get(&SubnetBucket::from_peer(peer_ip)).copied().unwrap_or(0)))
+            .filter(|(_, count)| *count < max_per_bucket)
+            .min_by_key(|(_, count)| *count);
+        // If every remaining candidate is in a full bucket, stop.
+        let Some((index, _)) = least_populated else { break };
+        // Select the candidate, and count it against its bucket.
+        let peer_ip = candidates.swap_remove(index);
+        *counts.entry(SubnetBucket::from_peer(&peer_ip)).or_default() += 1;
+        selected.push(peer_ip);
+    }
+    selected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    /// Returns a peer address in the `a.b.0.0/16` bucket.
+    fn peer(a: u8, b: u8, c: u8) -> SocketAddr {
+        SocketAddr::new(Ipv4Addr::new(a, b, c, 1).into(), 4130)
+    }
+
+    #[test]
+    fn test_subnet_bucket() {
+        // Check that IPv4 addresses are bucketed by /16.
+        assert_eq!(SubnetBucket::from_peer(&peer(1, 2, 3)), SubnetBucket::from_peer(&peer(1, 2, 4)));
+        assert_ne!(SubnetBucket::from_peer(&peer(1, 2, 3)), SubnetBucket::from_peer(&peer(1, 3, 3)));
+        assert_eq!(SubnetBucket::from_peer(&peer(1, 2, 3)).to_string(), "1.2.0.0/16");
+
+        // Check that IPv6 addresses are bucketed by /32.
+        let a = SubnetBucket::new("2001:db8:1::1".parse().unwrap());
+        let b = SubnetBucket::new("2001:db8:2::1".parse().unwrap());
+        let c = SubnetBucket::new("2001:db9::1".parse().unwrap());
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(a.to_string(), "2001:db8::/32");
+
+        // Check that IPv4-mapped IPv6 addresses share the bucket of the IPv4 address.
+        assert_eq!(SubnetBucket::new("::ffff:1.2.9.9".parse().unwrap()), SubnetBucket::from_peer(&peer(1, 2, 3)));
+    }
+
+    #[test]
+    fn test_select_prefers_empty_buckets() {
+        let rng = &mut StdRng::seed_from_u64(0);
+        // Connect to a peer in bucket 1.1.0.0/16.
+        let connected = vec![peer(1, 1, 0)];
+        // Prepare candidates in bucket 1.1.0.0/16, and one in each of two other buckets.
+        let candidates = vec![peer(1, 1, 1), peer(1, 1, 2), peer(1, 1, 3), peer(2, 2, 0), peer(3, 3, 0)];
+
+        let selected = select_diverse_peers(candidates, &connected, &HashSet::new(), 2, 3, rng);
+        // Check that the empty buckets are selected first.
+        assert_eq!(selected.len(), 2);
+        assert!(selected.contains(&peer(2, 2, 0)));
+        assert!(selected.contains(&peer(3, 3, 0)));
+    }
+
+    #[test]
+    fn test_select_enforces_bucket_cap() {
+        for seed in 0..10 {
+            let rng = &mut StdRng::seed_from_u64(seed);
+            // Connect to two peers in bucket 1.1.0.0/16.
+            let connected = vec![peer(1, 1, 0), peer(1, 1, 1)];
+            // Prepare many candidates across three buckets.
+            let candidates = (0..30).map(|i| peer(1 + i % 3, 1 + i % 3, 10 + i)).collect::<Vec<_>>();
+
+            let max_per_bucket = 3;
+            let selected = select_diverse_peers(candidates, &connected, &HashSet::new(), 20, max_per_bucket, rng);
+            // Check that only the remaining room under the cap is filled.
+            assert_eq!(selected.len(), 1 + 3 + 3);
+            // Check that the cap holds in every bucket.
+            let distribution = bucket_distribution(connected.iter().chain(selected.iter()));
+            assert!(distribution.values().all(|count| *count <= max_per_bucket));
+        }
+    }
+
+    #[test]
+    fn test_select_fills_slots_when_diversity_is_impossible() {
+        let rng = &mut StdRng::seed_from_u64(0);
+        // Prepare candidates that all reside in a single bucket.
+        let candidates = (0..10).map(|i| peer(1, 1, i)).collect::<Vec<_>>();
+
+        // Check that the available slots are still filled, up to the cap.
+        let selected = select_diverse_peers(candidates.clone(), &[], &HashSet::new(), 3, 5, rng);
+        assert_eq!(selected.len(), 3);
+        let selected = select_diverse_peers(candidates, &[], &HashSet::new(), 8, 5, rng);
+        assert_eq!(selected.len(), 5);
+
+        // Prepare candidates in two buckets, where one bucket has a single candidate.
+        let candidates = vec![peer(1, 1, 0), peer(2, 2, 0), peer(2, 2, 1), peer(2, 2, 2)];
+        let selected = select_diverse_peers(candidates, &[], &HashSet::new(), 4, 5, rng);
+        // Check that every candidate is selected.
+        assert_eq!(selected.len(), 4);
+    }
+
+    #[test]
+    fn test_select_exempts_trusted_peers() {
+        let rng = &mut StdRng::seed_from_u64(0);
+        let trusted = [peer(1, 1, 0), peer(1, 1, 1), peer(1, 1, 2)].into_iter().collect::<HashSet<_>>();
+        // Connect to the first trusted peer, and an untrusted peer in the same bucket.
+        let connected = vec![peer(1, 1, 0), peer(1, 1, 3)];
+        // Prepare the remaining trusted peers, and an untrusted peer in the same bucket.
+        let candidates = vec![peer(1, 1, 1), peer(1, 1, 2), peer(1, 1, 4)];
+
+        let selected = select_diverse_peers(candidates, &connected, &trusted, 3, 1, rng);
+        // Check that the trusted peers are selected, despite the bucket being full.
+        assert_eq!(selected.len(), 2);
+        assert!(selected.contains(&peer(1, 1, 1)));
+        assert!(selected.contains(&peer(1, 1, 2)));
+    }
+}
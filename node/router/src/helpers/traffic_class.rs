@@ -0,0 +1,43 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::messages::Message;
+use snarkos_node_tcp::TrafficClass;
+use snarkvm::prelude::Network;
+
+/// Returns the traffic class of the given outbound message, which determines its share of the outbound bandwidth.
+pub fn traffic_class<N: Network>(message: &Message<N>) -> TrafficClass {
+    match message {
+        Message::BlockByHashResponse(..)
+        | Message::BlockResponse(..)
+        | Message::BlockTransactions(..)
+        | Message::CompactBlock(..) => TrafficClass::Blocks,
+        Message::PuzzleRequest(..)
+        | Message::PuzzleResponse(..)
+        | Message::UnconfirmedSolution(..)
+        | Message::UnconfirmedTransaction(..) => TrafficClass::Gossip,
+        Message::BlockByHashRequest(..)
+        | Message::BlockRequest(..)
+        | Message::ChallengeRequest(..)
+        | Message::ChallengeResponse(..)
+        | Message::Disconnect(..)
+        | Message::GetBlockTransactions(..)
+        | Message::PeerRequest(..)
+        | Message::PeerResponse(..)
+        | Message::Ping(..)
+        | Message::Pong(..)
+        | Message::PropagationSample(..)
+        | Message::Reject(..) => TrafficClass::Maintenance,
+    }
+}
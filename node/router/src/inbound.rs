@@ -14,38 +14,60 @@
 
 use crate::{
     messages::{
+        deserialize_data,
+        ensure_data_size,
+        AdvertisedPeer,
+        BlockByHashRequest,
+        BlockByHashResponse,
         BlockRequest,
         BlockResponse,
+        BlockTransactions,
+        Capabilities,
+        CompactBlock,
         DataBlocks,
+        Feature,
+        GetBlockTransactions,
         Message,
+        PartialBlock,
         PeerResponse,
         Ping,
         Pong,
+        PropagationSample,
+        RejectReason,
+        RejectedItem,
         UnconfirmedSolution,
         UnconfirmedTransaction,
     },
+    now_unix,
+    Initiator,
+    Offense,
     Outbound,
     Peer,
+    PeerSource,
+    PropagationOutcome,
+    QueuedBlockResponse,
 };
 use snarkos_node_tcp::protocols::Reading;
 use snarkvm::prelude::{
     block::{Block, Header, Transaction},
-    puzzle::Solution,
+    puzzle::{Solution, SolutionID},
     Network,
 };
 
 use anyhow::{anyhow, bail, Result};
 use snarkos_node_tcp::is_bogon_ip;
-use std::{net::SocketAddr, time::Instant};
+use std::{
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
 use tokio::task::spawn_blocking;
 
-/// The max number of peers to send in a `PeerResponse` message.
-const MAX_PEERS_TO_SEND: usize = u8::MAX as usize;
-
 #[async_trait]
 pub trait Inbound<N: Network>: Reading + Outbound<N> {
     /// The maximum number of puzzle requests per interval.
     const MAXIMUM_PUZZLE_REQUESTS_PER_INTERVAL: usize = 5;
+    /// The maximum number of peers to send and accept in a `PeerResponse` message.
+    const MAXIMUM_PEERS_PER_RESPONSE: usize = 100;
     /// The duration in seconds to sleep in between ping requests with a connected peer.
     const PING_SLEEP_IN_SECS: u64 = 20; // 20 seconds
     /// The time frame to enforce the `MESSAGE_LIMIT`.
@@ -53,6 +75,11 @@ pub trait Inbound<N: Network>: Reading + Outbound<N> {
     /// The maximum number of messages accepted within `MESSAGE_LIMIT_TIME_FRAME_IN_SECS`.
     const MESSAGE_LIMIT: usize = 500;
 
+    /// Returns the duration to sleep in between ping requests with a connected peer, as planned by the heartbeat.
+    fn ping_interval(&self) -> Duration {
+        self.router().heartbeat_plan().map_or(Duration::from_secs(Self::PING_SLEEP_IN_SECS), |plan| plan.ping_interval)
+    }
+
     /// Handles the inbound message from the peer.
     async fn inbound(&self, peer_addr: SocketAddr, message: Message<N>) -> Result<()> {
         // Retrieve the listener IP for the peer.
@@ -73,15 +100,53 @@ pub trait Inbound<N: Network>: Reading + Outbound<N> {
         // This match statement handles the inbound message by deserializing the message,
         // checking that the message is valid, and then calling the appropriate (trait) handler.
         match message {
+            Message::BlockByHashRequest(message) => {
+                let node = self.clone();
+                match spawn_blocking(move || node.block_by_hash_request(peer_ip, message)).await? {
+                    true => Ok(()),
+                    false => bail!("Peer '{peer_ip}' sent an invalid block-by-hash request"),
+                }
+            }
+            Message::BlockByHashResponse(response) => {
+                let BlockByHashResponse { request, blocks } = response;
+
+                // Remove the pending request, checking if this node previously sent the request to this peer.
+                let Some(pending) = self.router().remove_block_by_hash_request(&peer_ip) else {
+                    self.router().record_misbehavior(peer_ip, Offense::MismatchedResponse);
+                    bail!("Peer '{peer_ip}' is not following the protocol (unexpected block-by-hash response)")
+                };
+                if pending.request != request {
+                    self.router().record_misbehavior(peer_ip, Offense::MismatchedResponse);
+                    bail!("Peer '{peer_ip}' sent a block-by-hash response that was not requested")
+                }
+                // Perform the deferred non-blocking deserialization of the blocks.
+                let blocks = match deserialize_data(blocks).await {
+                    Ok(blocks) => blocks,
+                    Err(error) => {
+                        self.router().record_misbehavior(peer_ip, Offense::FailedDeserialization);
+                        bail!("[BlockByHashResponse] {error}")
+                    }
+                };
+                // Ensure the block-by-hash response is well-formed.
+                if let Err(error) = blocks.ensure_response_is_well_formed(peer_ip, &request.block_hashes) {
+                    self.router().record_misbehavior(peer_ip, Offense::MalformedMessage);
+                    bail!("{error}")
+                }
+                // Return the blocks to the requester. Note: The requester may have stopped waiting.
+                let _ = pending.callback.send(blocks);
+                Ok(())
+            }
             Message::BlockRequest(message) => {
                 let BlockRequest { start_height, end_height } = &message;
 
                 // Ensure the block request is well-formed.
                 if start_height >= end_height {
+                    self.router().record_misbehavior(peer_ip, Offense::MalformedMessage);
                     bail!("Block request from '{peer_ip}' has an invalid range ({start_height}..{end_height})")
                 }
                 // Ensure that the block request is within the allowed bounds.
                 if end_height - start_height > DataBlocks::<N>::MAXIMUM_NUMBER_OF_BLOCKS as u32 {
+                    self.router().record_misbehavior(peer_ip, Offense::MalformedMessage);
                     bail!("Block request from '{peer_ip}' has an excessive range ({start_height}..{end_height})")
                 }
 
@@ -91,38 +156,86 @@ pub trait Inbound<N: Network>: Reading + Outbound<N> {
                     false => bail!("Peer '{peer_ip}' sent an invalid block request"),
                 }
             }
-            Message::BlockResponse(message) => {
-                let BlockResponse { request, blocks } = message;
+            Message::BlockResponse(response) => {
+                let request = response.request;
 
                 // Remove the block request, checking if this node previously sent a block request to this peer.
                 if !self.router().cache.remove_outbound_block_request(peer_ip, &request) {
+                    self.router().record_misbehavior(peer_ip, Offense::MismatchedResponse);
                     bail!("Peer '{peer_ip}' is not following the protocol (unexpected block response)")
                 }
-                // Perform the deferred non-blocking deserialization of the blocks.
-                let blocks = blocks.deserialize().await.map_err(|error| anyhow!("[BlockResponse] {error}"))?;
-                // Ensure the block response is well-formed.
-                blocks.ensure_response_is_well_formed(peer_ip, request.start_height, request.end_height)?;
+                // Determine if the block request was a fallback for a block announcement.
+                let is_announced = self.router().announcements.remove_fallback_request(peer_ip, &request);
+
+                // Enqueue the block response, to be processed by the block response worker.
+                // Note: This returns promptly, so that a burst of block responses does not delay other messages.
+                let queued = QueuedBlockResponse { peer_ip, response, is_announced };
+                if !self.router().block_responses.push(queued) {
+                    debug!("Dropping a block response from '{peer_ip}', as the block response queue is full");
+                    self.block_response_dropped(peer_ip, request);
+                }
+                Ok(())
+            }
+            Message::BlockTransactions(message) => {
+                // Remove the partial block, checking if this node previously requested the transactions from this peer.
+                let Some((positions, partial)) =
+                    self.router().announcements.remove_pending_block(peer_ip, &message.block_hash)
+                else {
+                    self.router().record_misbehavior(peer_ip, Offense::MismatchedResponse);
+                    bail!("Peer '{peer_ip}' is not following the protocol (unexpected block transactions)")
+                };
+                // Ensure the block transactions match the request.
+                if message.positions != positions {
+                    self.router().record_misbehavior(peer_ip, Offense::MismatchedResponse);
+                    bail!("Peer '{peer_ip}' sent block transactions that were not requested")
+                }
 
-                // Process the block response.
+                // Process the block transactions.
                 let node = self.clone();
-                match spawn_blocking(move || node.block_response(peer_ip, blocks.0)).await? {
+                match spawn_blocking(move || node.block_transactions(peer_ip, partial, message)).await? {
                     true => Ok(()),
-                    false => bail!("Peer '{peer_ip}' sent an invalid block response"),
+                    false => bail!("Peer '{peer_ip}' sent invalid block transactions"),
                 }
             }
             Message::ChallengeRequest(..) | Message::ChallengeResponse(..) => {
                 // Disconnect as the peer is not following the protocol.
+                self.router().record_misbehavior(peer_ip, Offense::ProtocolViolation);
                 bail!("Peer '{peer_ip}' is not following the protocol")
             }
+            Message::CompactBlock(message) => {
+                // Ensure this node advertised that it accepts compact blocks.
+                if !Capabilities::for_node_type(self.router().node_type()).supports_compact_blocks() {
+                    self.router().record_misbehavior(peer_ip, Offense::ProtocolViolation);
+                    bail!("Peer '{peer_ip}' is not following the protocol (unexpected compact block)")
+                }
+                // Determine whether to process the block announcement.
+                if !self.is_block_announcement_wanted(message.height()) {
+                    trace!("Skipping 'CompactBlock {}' from '{peer_ip}'", message.height());
+                    return Ok(());
+                }
+
+                // Process the compact block.
+                let node = self.clone();
+                match spawn_blocking(move || node.compact_block(peer_ip, message)).await? {
+                    true => Ok(()),
+                    false => bail!("Peer '{peer_ip}' sent an invalid compact block"),
+                }
+            }
             Message::Disconnect(message) => {
+                self.router().note_disconnect(peer_ip, Initiator::Remote, format!("{:?}", message.reason));
                 bail!("{:?}", message.reason)
             }
+            Message::GetBlockTransactions(message) => match self.get_block_transactions(peer_ip, message) {
+                true => Ok(()),
+                false => bail!("Peer '{peer_ip}' sent an invalid block transactions request"),
+            },
             Message::PeerRequest(..) => match self.peer_request(peer_ip) {
                 true => Ok(()),
                 false => bail!("Peer '{peer_ip}' sent an invalid peer request"),
             },
             Message::PeerResponse(message) => {
                 if !self.router().cache.contains_outbound_peer_request(peer_ip) {
+                    self.router().record_misbehavior(peer_ip, Offense::MismatchedResponse);
                     bail!("Peer '{peer_ip}' is not following the protocol (unexpected peer response)")
                 }
                 if !self.router().allow_external_peers() {
@@ -136,25 +249,25 @@ pub trait Inbound<N: Network>: Reading + Outbound<N> {
             }
             Message::Ping(message) => {
                 // Ensure the message protocol version is not outdated.
-                if message.version < Message::<N>::VERSION {
+                if message.version < self.router().versions().minimum() {
                     bail!("Dropping '{peer_ip}' on message version {} (outdated)", message.version);
                 }
 
-                // If the peer is a client or validator, ensure there are block locators.
-                let is_client_or_validator = message.node_type.is_client() || message.node_type.is_validator();
-                if is_client_or_validator && message.block_locators.is_none() {
+                // If the peer is a client, validator, or archive, ensure there are block locators.
+                let has_ledger = !message.node_type.is_prover();
+                if has_ledger && message.block_locators.is_none() {
+                    self.router().record_misbehavior(peer_ip, Offense::MalformedMessage);
                     bail!("Peer '{peer_ip}' is a {}, but no block locators were provided", message.node_type);
                 }
                 // If the peer is a prover, ensure there are no block locators.
                 else if message.node_type.is_prover() && message.block_locators.is_some() {
+                    self.router().record_misbehavior(peer_ip, Offense::MalformedMessage);
                     bail!("Peer '{peer_ip}' is a prover or client, but block locators were provided");
                 }
 
                 // Update the connected peer.
                 if let Err(error) =
                     self.router().update_connected_peer(peer_ip, message.node_type, |peer: &mut Peer<N>| {
-                        // Update the version of the peer.
-                        peer.set_version(message.version);
                         // Update the node type of the peer.
                         peer.set_node_type(message.node_type);
                         // Update the last seen timestamp of the peer.
@@ -170,15 +283,39 @@ pub trait Inbound<N: Network>: Reading + Outbound<N> {
                     false => bail!("Peer '{peer_ip}' sent an invalid ping"),
                 }
             }
-            Message::Pong(message) => match self.pong(peer_ip, message) {
-                true => Ok(()),
-                false => bail!("Peer '{peer_ip}' sent an invalid pong"),
-            },
+            Message::Pong(message) => {
+                // If the peer echoed the timestamp of the ping, update the estimate of the clock skew.
+                if let Some((sent, answered)) = message.timestamps {
+                    self.router().observe_clock_sample(peer_ip, sent, answered);
+                }
+                // Process the pong message.
+                match self.pong(peer_ip, message) {
+                    true => Ok(()),
+                    false => bail!("Peer '{peer_ip}' sent an invalid pong"),
+                }
+            }
+            Message::PropagationSample(sample) => {
+                match self.router().propagation().process_report(peer_ip, sample) {
+                    PropagationOutcome::Recorded(delay) => {
+                        trace!("Transaction '{}' reached a peer in {delay}ms", sample.transaction_id);
+                    }
+                    PropagationOutcome::Relayed(upstream, sample) => {
+                        self.send(upstream, Message::PropagationSample(sample));
+                    }
+                    PropagationOutcome::Discarded => (),
+                    PropagationOutcome::RateLimited => {
+                        self.router().record_misbehavior(peer_ip, Offense::ProtocolViolation);
+                        bail!("Peer '{peer_ip}' is not following the protocol (excessive propagation samples)")
+                    }
+                }
+                Ok(())
+            }
             Message::PuzzleRequest(..) => {
                 // Insert the puzzle request for the peer, and fetch the recent frequency.
                 let frequency = self.router().cache.insert_inbound_puzzle_request(peer_ip);
                 // Check if the number of puzzle requests is within the limit.
                 if frequency > Self::MAXIMUM_PUZZLE_REQUESTS_PER_INTERVAL {
+                    self.router().record_misbehavior(peer_ip, Offense::ProtocolViolation);
                     bail!("Peer '{peer_ip}' is not following the protocol (excessive puzzle requests)")
                 }
                 // Process the puzzle request.
@@ -190,15 +327,19 @@ pub trait Inbound<N: Network>: Reading + Outbound<N> {
             Message::PuzzleResponse(message) => {
                 // Check that this node previously sent a puzzle request to this peer.
                 if !self.router().cache.contains_outbound_puzzle_request(&peer_ip) {
+                    self.router().record_misbehavior(peer_ip, Offense::MismatchedResponse);
                     bail!("Peer '{peer_ip}' is not following the protocol (unexpected puzzle response)")
                 }
                 // Decrement the number of puzzle requests.
                 self.router().cache.decrement_outbound_puzzle_requests(peer_ip);
 
                 // Perform the deferred non-blocking deserialization of the block header.
-                let header = match message.block_header.deserialize().await {
+                let header = match deserialize_data(message.block_header).await {
                     Ok(header) => header,
-                    Err(error) => bail!("[PuzzleResponse] {error}"),
+                    Err(error) => {
+                        self.router().record_misbehavior(peer_ip, Offense::FailedDeserialization);
+                        bail!("[PuzzleResponse] {error}")
+                    }
                 };
                 // Process the puzzle response.
                 match self.puzzle_response(peer_ip, message.epoch_hash, header) {
@@ -206,6 +347,24 @@ pub trait Inbound<N: Network>: Reading + Outbound<N> {
                     false => bail!("Peer '{peer_ip}' sent an invalid puzzle response"),
                 }
             }
+            Message::Reject(message) => {
+                debug!("Peer '{peer_ip}' rejected {} ({}) - {}", message.item, message.reason, message.message);
+                // Record the rejection, so that the item is not resent to the peer.
+                self.router().rejections.insert(peer_ip, message.item, message.reason);
+                // If the peer is temporarily unable to accept the item, allow it to be resent once the rejection expires.
+                if !message.reason.is_permanent() {
+                    match message.item {
+                        RejectedItem::Transaction(transaction_id) => {
+                            self.router().cache.remove_outbound_transaction(peer_ip, transaction_id);
+                        }
+                        RejectedItem::Solution(solution_id) => {
+                            self.router().cache.remove_outbound_solution(peer_ip, solution_id);
+                        }
+                        RejectedItem::Block(_) => (),
+                    }
+                }
+                Ok(())
+            }
             Message::UnconfirmedSolution(message) => {
                 // Clone the serialized message.
                 let serialized = message.clone();
@@ -216,19 +375,38 @@ pub trait Inbound<N: Network>: Reading + Outbound<N> {
                     trace!("Skipping 'UnconfirmedSolution' from '{peer_ip}'");
                     return Ok(());
                 }
+                // Skip the solution if it is already confirmed, before it is deserialized.
+                if let Some(height) = self.confirmed_solution_height(&message.solution_id) {
+                    trace!("Skipping 'UnconfirmedSolution' from '{peer_ip}' (confirmed in block {height})");
+                    return Ok(());
+                }
+                // Ensure the solution does not exceed the maximum size.
+                if let Err(error) = ensure_data_size(&message.solution) {
+                    let item = RejectedItem::Solution(message.solution_id);
+                    self.send_reject(peer_ip, item, RejectReason::Oversized, &error.to_string());
+                    self.router().record_misbehavior(peer_ip, Offense::FailedDeserialization);
+                    bail!("[UnconfirmedSolution] {error}")
+                }
                 // Perform the deferred non-blocking deserialization of the solution.
-                let solution = match message.solution.deserialize().await {
+                let solution = match deserialize_data(message.solution).await {
                     Ok(solution) => solution,
-                    Err(error) => bail!("[UnconfirmedSolution] {error}"),
+                    Err(error) => {
+                        self.router().record_misbehavior(peer_ip, Offense::FailedDeserialization);
+                        bail!("[UnconfirmedSolution] {error}")
+                    }
                 };
                 // Check that the solution parameters match.
                 if message.solution_id != solution.id() {
+                    self.router().record_misbehavior(peer_ip, Offense::MalformedMessage);
                     bail!("Peer '{peer_ip}' is not following the 'UnconfirmedSolution' protocol")
                 }
                 // Handle the unconfirmed solution.
                 match self.unconfirmed_solution(peer_ip, serialized, solution).await {
                     true => Ok(()),
-                    false => bail!("Peer '{peer_ip}' sent an invalid unconfirmed solution"),
+                    false => {
+                        self.router().record_misbehavior(peer_ip, Offense::BelowTargetSolution);
+                        bail!("Peer '{peer_ip}' sent an invalid unconfirmed solution")
+                    }
                 }
             }
             Message::UnconfirmedTransaction(message) => {
@@ -242,15 +420,39 @@ pub trait Inbound<N: Network>: Reading + Outbound<N> {
                     trace!("Skipping 'UnconfirmedTransaction' from '{peer_ip}'");
                     return Ok(());
                 }
+                // Skip the transaction if it is already confirmed, before it is deserialized.
+                if let Some(height) = self.confirmed_transaction_height(&message.transaction_id) {
+                    trace!("Skipping 'UnconfirmedTransaction' from '{peer_ip}' (confirmed in block {height})");
+                    return Ok(());
+                }
+                // Ensure the transaction does not exceed the maximum size.
+                if let Err(error) = ensure_data_size(&message.transaction) {
+                    let item = RejectedItem::Transaction(message.transaction_id);
+                    self.send_reject(peer_ip, item, RejectReason::Oversized, &error.to_string());
+                    self.router().record_misbehavior(peer_ip, Offense::FailedDeserialization);
+                    bail!("[UnconfirmedTransaction] {error}")
+                }
                 // Perform the deferred non-blocking deserialization of the transaction.
-                let transaction = match message.transaction.deserialize().await {
+                let transaction = match deserialize_data(message.transaction).await {
                     Ok(transaction) => transaction,
-                    Err(error) => bail!("[UnconfirmedTransaction] {error}"),
+                    Err(error) => {
+                        self.router().record_misbehavior(peer_ip, Offense::FailedDeserialization);
+                        bail!("[UnconfirmedTransaction] {error}")
+                    }
                 };
                 // Check that the transaction parameters match.
                 if message.transaction_id != transaction.id() {
+                    self.router().record_misbehavior(peer_ip, Offense::MalformedMessage);
                     bail!("Peer '{peer_ip}' is not following the 'UnconfirmedTransaction' protocol")
                 }
+                // Report the time this node first saw the transaction, if it is sampled.
+                if let Some(sample) = self.router().propagation().observe_first_seen(peer_ip, message.transaction_id) {
+                    self.send(peer_ip, Message::PropagationSample(sample));
+                }
+                // Retain the transaction, to reconstruct compact blocks.
+                if Capabilities::for_node_type(self.router().node_type()).supports_compact_blocks() {
+                    self.router().announcements.insert_transaction(transaction.clone());
+                }
                 // Handle the unconfirmed transaction.
                 match self.unconfirmed_transaction(peer_ip, serialized, transaction).await {
                     true => Ok(()),
@@ -260,49 +462,194 @@ pub trait Inbound<N: Network>: Reading + Outbound<N> {
         }
     }
 
+    /// Handles a `BlockByHashRequest` message.
+    fn block_by_hash_request(&self, peer_ip: SocketAddr, _message: BlockByHashRequest<N>) -> bool;
+
     /// Handles a `BlockRequest` message.
     fn block_request(&self, peer_ip: SocketAddr, _message: BlockRequest) -> bool;
 
+    /// Processes a queued `BlockResponse` message.
+    async fn process_block_response(&self, queued: QueuedBlockResponse<N>) -> Result<()> {
+        let QueuedBlockResponse { peer_ip, response: BlockResponse { request, blocks }, is_announced } = queued;
+
+        // Perform the deferred non-blocking deserialization of the blocks.
+        let blocks = deserialize_data(blocks).await.map_err(|error| {
+            self.router().record_misbehavior(peer_ip, Offense::FailedDeserialization);
+            anyhow!("[BlockResponse] {error}")
+        })?;
+        // Ensure the block response is well-formed.
+        if let Err(error) = blocks.ensure_response_is_well_formed(peer_ip, request.start_height, request.end_height) {
+            self.router().record_misbehavior(peer_ip, Offense::MalformedMessage);
+            return Err(error);
+        }
+
+        // Process the block response.
+        let node = self.clone();
+        match spawn_blocking(move || match is_announced {
+            true => blocks.0.into_iter().all(|block| node.announced_block(peer_ip, block)),
+            false => node.block_response(peer_ip, blocks.0),
+        })
+        .await?
+        {
+            true => Ok(()),
+            false => bail!("Peer '{peer_ip}' sent an invalid block response"),
+        }
+    }
+
     /// Handles a `BlockResponse` message.
     fn block_response(&self, peer_ip: SocketAddr, _blocks: Vec<Block<N>>) -> bool;
 
+    /// Handles a `BlockResponse` message that was dropped, as the block response queue is full.
+    /// By default, the dropped block response is ignored.
+    fn block_response_dropped(&self, _peer_ip: SocketAddr, _request: BlockRequest) {}
+
+    /// Returns `true` if the node processes a block announcement at the given height.
+    /// By default, block announcements are ignored.
+    fn is_block_announcement_wanted(&self, _height: u32) -> bool {
+        false
+    }
+
+    /// Handles a block announced by the peer. By default, announced blocks are rejected.
+    fn announced_block(&self, _peer_ip: SocketAddr, _block: Block<N>) -> bool {
+        false
+    }
+
+    /// Handles a `CompactBlock` message, by reconstructing the block from the recent unconfirmed transactions.
+    fn compact_block(&self, peer_ip: SocketAddr, compact: CompactBlock<N>) -> bool {
+        // Match the short IDs against the recent unconfirmed transactions.
+        let partial = match compact.to_partial_block(self.router().announcements.recent_transactions()) {
+            Ok(partial) => partial,
+            Err(error) => {
+                debug!("Requesting block {} in full from '{peer_ip}' - {error}", compact.height());
+                self.request_announced_block(peer_ip, compact.height());
+                return true;
+            }
+        };
+        // If any transactions are missing, request them from the peer.
+        let missing = partial.missing_positions();
+        if !missing.is_empty() {
+            let block_hash = compact.block_hash;
+            self.router().announcements.insert_pending_block(peer_ip, missing.clone(), partial);
+            self.send(peer_ip, Message::GetBlockTransactions(GetBlockTransactions { block_hash, positions: missing }));
+            return true;
+        }
+        // Reconstruct the block.
+        self.reconstruct_announced_block(peer_ip, partial)
+    }
+
+    /// Handles a `GetBlockTransactions` message.
+    fn get_block_transactions(&self, peer_ip: SocketAddr, message: GetBlockTransactions<N>) -> bool {
+        let GetBlockTransactions { block_hash, positions } = message;
+        // Retrieve the announced block. If it was evicted, the peer falls back to syncing the block.
+        let Some(block) = self.router().announcements.get_announced_block(&block_hash) else {
+            return true;
+        };
+        // Retrieve the requested transactions.
+        let confirmed = block.transactions().iter().collect::<Vec<_>>();
+        let mut transactions = Vec::with_capacity(positions.len());
+        for position in &positions {
+            match confirmed.get(*position as usize) {
+                Some(confirmed) => transactions.push(confirmed.transaction().clone()),
+                None => return false,
+            }
+        }
+        // Send the `BlockTransactions` message to the peer.
+        self.send(peer_ip, Message::BlockTransactions(BlockTransactions { block_hash, positions, transactions }));
+        true
+    }
+
+    /// Handles a `BlockTransactions` message.
+    fn block_transactions(
+        &self,
+        peer_ip: SocketAddr,
+        mut partial: PartialBlock<N>,
+        message: BlockTransactions<N>,
+    ) -> bool {
+        // Fill the missing transactions, ensuring they match the short IDs of the compact block.
+        if let Err(error) = partial.fill(&message.positions, message.transactions) {
+            warn!("{error}");
+            return false;
+        }
+        // Reconstruct the block.
+        self.reconstruct_announced_block(peer_ip, partial)
+    }
+
+    /// Reconstructs the block from the given partial block, requesting the full block on failure.
+    fn reconstruct_announced_block(&self, peer_ip: SocketAddr, partial: PartialBlock<N>) -> bool {
+        let height = partial.compact().height();
+        match partial.into_block() {
+            Ok(block) => self.announced_block(peer_ip, block),
+            Err(error) => {
+                debug!("Requesting block {height} in full from '{peer_ip}' - {error}");
+                self.request_announced_block(peer_ip, height);
+                true
+            }
+        }
+    }
+
+    /// Requests the announced block at the given height in full, as a fallback for a failed reconstruction.
+    fn request_announced_block(&self, peer_ip: SocketAddr, height: u32) {
+        let request = BlockRequest { start_height: height, end_height: height + 1 };
+        self.router().announcements.insert_fallback_request(peer_ip, request);
+        self.send(peer_ip, Message::BlockRequest(request));
+    }
+
     /// Handles a `PeerRequest` message.
     fn peer_request(&self, peer_ip: SocketAddr) -> bool {
         // Retrieve the connected peers.
-        let peers = self.router().connected_peers();
+        let peers = self.router().get_connected_peers();
         // Filter out invalid addresses.
         let peers = match self.router().is_dev() {
             // In development mode, relax the validity requirements to make operating devnets more flexible.
-            true => {
-                peers.into_iter().filter(|ip| *ip != peer_ip && !is_bogon_ip(ip.ip())).take(MAX_PEERS_TO_SEND).collect()
-            }
+            true => peers
+                .into_iter()
+                .filter(|peer| peer.ip() != peer_ip && !is_bogon_ip(peer.advertised_ip().ip()))
+                .take(Self::MAXIMUM_PEERS_PER_RESPONSE)
+                .collect::<Vec<_>>(),
             // In production mode, ensure the peer IPs are valid.
             false => peers
                 .into_iter()
-                .filter(|ip| *ip != peer_ip && self.router().is_valid_peer_ip(ip))
-                .take(MAX_PEERS_TO_SEND)
+                .filter(|peer| peer.ip() != peer_ip && self.router().is_valid_peer_ip(&peer.advertised_ip()))
+                .take(Self::MAXIMUM_PEERS_PER_RESPONSE)
                 .collect(),
         };
+        // Advertise the peers along with when they were last seen, and the services they offer.
+        let now = now_unix();
+        let peers = peers
+            .into_iter()
+            .map(|peer| {
+                let info = peer.info(now);
+                AdvertisedPeer { ip: peer.advertised_ip(), last_seen: info.last_seen, services: info.services }
+            })
+            .collect();
+        let response = match self.router().get_connected_peer(&peer_ip) {
+            Some(peer) if peer.supports_feature(Feature::PeerMetadata) => PeerResponse { peers },
+            // Advertise only the addresses to the peers that do not support the metadata.
+            _ => PeerResponse { peers }.without_metadata(),
+        };
         // Send a `PeerResponse` message to the peer.
-        self.send(peer_ip, Message::PeerResponse(PeerResponse { peers }));
+        self.send(peer_ip, Message::PeerResponse(response));
         true
     }
 
     /// Handles a `PeerResponse` message.
-    fn peer_response(&self, _peer_ip: SocketAddr, peers: &[SocketAddr]) -> bool {
-        // Check if the number of peers received is less than MAX_PEERS_TO_SEND.
-        if peers.len() > MAX_PEERS_TO_SEND {
+    fn peer_response(&self, peer_ip: SocketAddr, peers: &[AdvertisedPeer]) -> bool {
+        // Ensure the number of peers received is at most MAXIMUM_PEERS_PER_RESPONSE.
+        if peers.len() > Self::MAXIMUM_PEERS_PER_RESPONSE {
+            // Restrict the peer, as it is attempting to flood the peer book.
+            self.router().insert_restricted_peer(peer_ip);
             return false;
         }
-        // Filter out invalid addresses.
-        let peers = match self.router().is_dev() {
-            // In development mode, relax the validity requirements to make operating devnets more flexible.
-            true => peers.iter().copied().filter(|ip| !is_bogon_ip(ip.ip())).collect::<Vec<_>>(),
-            // In production mode, ensure the peer IPs are valid.
-            false => peers.iter().copied().filter(|ip| self.router().is_valid_peer_ip(ip)).collect(),
-        };
+        // Filter out invalid addresses, and ensure the advertised last-seen timestamps are not in the future.
+        let filter = self.router().peer_address_filter();
+        let now = now_unix();
+        let peers = peers
+            .iter()
+            .filter(|peer| filter.is_valid(&peer.ip))
+            .map(|peer| AdvertisedPeer { last_seen: peer.last_seen.min(now), ..*peer })
+            .collect::<Vec<_>>();
         // Adds the given peer IPs to the list of candidate peers.
-        self.router().insert_candidate_peers(&peers);
+        self.router().insert_candidate_peers(&peers, PeerSource::Gossip);
         true
     }
 
@@ -318,6 +665,16 @@ pub trait Inbound<N: Network>: Reading + Outbound<N> {
     /// Handles a `PuzzleResponse` message.
     fn puzzle_response(&self, peer_ip: SocketAddr, _epoch_hash: N::BlockHash, _header: Header<N>) -> bool;
 
+    /// Returns the height of the block that confirmed the given solution, if it is known to be confirmed.
+    fn confirmed_solution_height(&self, _solution_id: &SolutionID<N>) -> Option<u32> {
+        None
+    }
+
+    /// Returns the height of the block that confirmed the given transaction, if it is known to be confirmed.
+    fn confirmed_transaction_height(&self, _transaction_id: &N::TransactionID) -> Option<u32> {
+        None
+    }
+
     /// Handles an `UnconfirmedSolution` message.
     async fn unconfirmed_solution(
         &self,
@@ -38,23 +38,37 @@ pub use outbound::*;
 mod routing;
 pub use routing::*;
 
-use crate::messages::NodeType;
+use crate::messages::{
+    AdvertisedPeer,
+    Capabilities,
+    Feature,
+    MessageCodec,
+    NodeType,
+    RejectReason,
+    RejectedItem,
+    VersionRange,
+};
 use snarkos_account::Account;
-use snarkos_node_tcp::{is_bogon_ip, is_unspecified_or_broadcast_ip, Config, Tcp};
+use snarkos_node_tcp::{is_bogon_ip, is_proxy_unreachable, is_unspecified_or_broadcast_ip, Config, Tcp};
 use snarkvm::prelude::{Address, Network, PrivateKey, ViewKey};
 
 use anyhow::{bail, Result};
+use colored::Colorize;
 use parking_lot::{Mutex, RwLock};
 use std::{
+    borrow::Cow,
     collections::{HashMap, HashSet},
     future::Future,
     net::SocketAddr,
     ops::Deref,
     str::FromStr,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
     time::Instant,
 };
-use tokio::task::JoinHandle;
+use tokio::{sync::Notify, task::JoinHandle};
 
 #[derive(Clone)]
 pub struct Router<N: Network>(Arc<InnerRouter<N>>);
@@ -72,25 +86,59 @@ pub struct InnerRouter<N: Network> {
     tcp: Tcp,
     /// The node type.
     node_type: NodeType,
+    /// The protocol capabilities of the node.
+    capabilities: Capabilities,
+    /// The range of message versions supported by the node.
+    versions: RwLock<VersionRange>,
     /// The account of the node.
     account: Account<N>,
     /// The cache.
     cache: Cache<N>,
+    /// The compact block announcements.
+    announcements: Announcements<N>,
+    /// The items that were rejected by peers.
+    rejections: Rejections<N>,
+    /// The estimate of the clock skew of the node against its peers.
+    clock_skew: ClockSkew,
+    /// The sampler of the propagation delay of transactions.
+    propagation: PropagationSampler<N>,
+    /// The queue of block responses awaiting processing.
+    block_responses: BlockResponseQueue<N>,
     /// The resolver.
     resolver: Resolver,
     /// The set of trusted peers.
     trusted_peers: HashSet<SocketAddr>,
     /// The map of connected peer IPs to their peer handlers.
     connected_peers: RwLock<HashMap<SocketAddr, Peer<N>>>,
-    /// The set of handshaking peers. While `Tcp` already recognizes the connecting IP addresses
+    /// The states of the connections with the peers. While `Tcp` already recognizes the connecting IP addresses
     /// and prevents duplicate outbound connection attempts to the same IP address, it is unable to
     /// prevent simultaneous "two-way" connections between two peers (i.e. both nodes simultaneously
-    /// attempt to connect to each other). This set is used to prevent this from happening.
-    connecting_peers: Mutex<HashSet<SocketAddr>>,
-    /// The set of candidate peer IPs.
-    candidate_peers: RwLock<HashSet<SocketAddr>>,
+    /// attempt to connect to each other). The states are used to prevent this from happening.
+    peer_states: PeerStates,
+    /// The map of candidate peer IPs to their metadata.
+    candidate_peers: RwLock<HashMap<SocketAddr, PeerInfo>>,
+    /// The mapping of the listening port on the gateway of the local network, if enabled.
+    port_mapping: PortMapping,
+    /// The configuration of the proxy for the outbound connections, if enabled.
+    proxy: Option<ProxyConfig>,
     /// The set of restricted peer IPs.
     restricted_peers: RwLock<HashMap<SocketAddr, Instant>>,
+    /// The misbehavior scores of the peers, by their listener IP.
+    misbehavior: RwLock<HashMap<SocketAddr, MisbehaviorScore>>,
+    /// The pending block-by-hash requests, by the listener IP of the peer.
+    block_by_hash_requests: Mutex<HashMap<SocketAddr, PendingBlockByHash<N>>>,
+    /// The plan of the heartbeat, until the next heartbeat.
+    heartbeat_plan: RwLock<Option<HeartbeatPlan>>,
+    /// The number of peers that connected or disconnected since the last heartbeat.
+    num_churned: AtomicUsize,
+    /// The notification of a peer disconnecting, to wake up the heartbeat.
+    peer_disconnected: Notify,
+    /// The audit log of the peer connection events, if enabled.
+    audit_log: RwLock<Option<Arc<AuditLog>>>,
+    /// The maximum number of (untrusted) peers to connect to in a single subnet bucket, if configured.
+    max_peers_per_subnet: RwLock<Option<usize>>,
+    /// The side and reason of the pending disconnections, to be recorded in the audit log.
+    disconnect_reasons: Mutex<HashMap<SocketAddr, (Initiator, String)>>,
     /// The spawned handles.
     handles: Mutex<Vec<JoinHandle<()>>>,
     /// If the flag is set, the node will engage in P2P gossip to request more peers.
@@ -111,29 +159,63 @@ impl<N: Network> Router<N> {
 
 impl<N: Network> Router<N> {
     /// Initializes a new `Router` instance.
-    pub async fn new(
-        node_ip: SocketAddr,
-        node_type: NodeType,
-        account: Account<N>,
-        trusted_peers: &[SocketAddr],
-        max_peers: u16,
-        allow_external_peers: bool,
-        is_dev: bool,
-    ) -> Result<Self> {
-        // Initialize the TCP stack.
-        let tcp = Tcp::new(Config::new(node_ip, max_peers));
+    pub async fn new(account: Account<N>, config: RouterConfig) -> Result<Self> {
+        let RouterConfig {
+            node_ip,
+            node_type,
+            max_peers,
+            trusted_peers,
+            is_rest_enabled,
+            allow_external_peers,
+            is_dev,
+            proxy,
+        } = config;
+        // Initialize the TCP stack, exempting the trusted peers from the limit of connections per IP.
+        let mut tcp_config = Config::new(node_ip, max_peers);
+        tcp_config.trusted_ips = trusted_peers.iter().map(|peer_ip| peer_ip.ip()).collect();
+        // Note: The nodes of a development network run on a single host.
+        if is_dev {
+            tcp_config.max_connections_per_ip = max_peers;
+        }
+        // Establish the outbound connections through the proxy, if enabled.
+        if let Some(proxy) = proxy {
+            tcp_config.proxy = Some(proxy.proxy);
+            tcp_config.connection_timeout_ms = PROXY_CONNECTION_TIMEOUT_MS;
+        }
+        let tcp = Tcp::new(tcp_config);
         // Initialize the router.
         Ok(Self(Arc::new(InnerRouter {
             tcp,
             node_type,
+            capabilities: Capabilities::for_node_type(node_type).with_rest(is_rest_enabled).with_compression(true),
+            versions: RwLock::new(VersionRange::supported()),
             account,
             cache: Default::default(),
+            announcements: Default::default(),
+            rejections: Default::default(),
+            clock_skew: Default::default(),
+            // Note: Development nodes report every transaction, so that the delays are sampled in small devnets.
+            propagation: match is_dev {
+                true => PropagationSampler::new().with_report_probability(1),
+                false => Default::default(),
+            },
+            block_responses: Default::default(),
             resolver: Default::default(),
-            trusted_peers: trusted_peers.iter().copied().collect(),
+            trusted_peers: trusted_peers.into_iter().collect(),
             connected_peers: Default::default(),
-            connecting_peers: Default::default(),
+            peer_states: Default::default(),
             candidate_peers: Default::default(),
+            port_mapping: Default::default(),
+            proxy,
             restricted_peers: Default::default(),
+            misbehavior: Default::default(),
+            block_by_hash_requests: Default::default(),
+            heartbeat_plan: Default::default(),
+            num_churned: Default::default(),
+            peer_disconnected: Default::default(),
+            audit_log: Default::default(),
+            max_peers_per_subnet: Default::default(),
+            disconnect_reasons: Default::default(),
             handles: Default::default(),
             allow_external_peers,
             is_dev,
@@ -150,8 +232,11 @@ impl<N: Network> Router<N> {
             return None;
         }
 
+        // Note: The guard ensures the peer is not left dialing if the dial fails, or if the task is dropped.
+        let guard = self.peer_states.guard(peer_ip, PeerEvent::DialFailed);
         let router = self.clone();
         Some(tokio::spawn(async move {
+            let _guard = guard;
             // Attempt to connect to the candidate peer.
             match router.tcp.connect(peer_ip).await {
                 // Remove the peer from the candidate peers.
@@ -159,10 +244,15 @@ impl<N: Network> Router<N> {
                     router.remove_candidate_peer(peer_ip);
                     true
                 }
-                // If the connection was not allowed, log the error.
+                // If the proxy is unreachable, log the error, without holding it against the candidate peer.
+                Err(error) if is_proxy_unreachable(&error) => {
+                    warn!("Unable to connect to '{peer_ip}' through the proxy - {error}");
+                    false
+                }
+                // If the connection was not allowed, log the error, and back off from the candidate peer.
                 Err(error) => {
-                    router.connecting_peers.lock().remove(&peer_ip);
                     warn!("Unable to connect to '{peer_ip}' - {error}");
+                    router.record_dial_failure(peer_ip);
                     false
                 }
             }
@@ -188,14 +278,21 @@ impl<N: Network> Router<N> {
             bail!("Dropping connection attempt to '{peer_ip}' (restricted)")
         }
         // Ensure the node is not already connecting to this peer.
-        if !self.connecting_peers.lock().insert(peer_ip) {
+        if self.peer_state(&peer_ip).is_connecting() {
             bail!("Dropping connection attempt to '{peer_ip}' (already shaking hands as the initiator)")
         }
+        // Start dialing the peer.
+        if let Err(error) = self.transition_peer(peer_ip, PeerEvent::Dial) {
+            bail!("Dropping connection attempt to '{peer_ip}' ({error})")
+        }
         Ok(())
     }
 
     /// Disconnects from the given peer IP, if the peer is connected.
     pub fn disconnect(&self, peer_ip: SocketAddr) -> JoinHandle<bool> {
+        self.note_disconnect(peer_ip, Initiator::Local, "No reason given");
+        // Note: The peer may already be draining, or not be connected at all.
+        let _ = self.transition_peer(peer_ip, PeerEvent::Disconnect);
         let router = self.clone();
         tokio::spawn(async move {
             if let Some(peer_addr) = router.resolve_to_ambiguous(&peer_ip) {
@@ -214,15 +311,54 @@ impl<N: Network> Router<N> {
         })
     }
 
+    /// Disconnects from the given peer IP for the given reason, if the peer is connected.
+    pub fn disconnect_with_reason(&self, peer_ip: SocketAddr, reason: impl ToString) -> JoinHandle<bool> {
+        self.note_disconnect(peer_ip, Initiator::Local, reason);
+        self.disconnect(peer_ip)
+    }
+
+    /// Notes the side and reason of an upcoming disconnection from the given peer, for the audit log.
+    /// If a reason was already noted for the peer, it is retained, as it is the original cause.
+    pub(crate) fn note_disconnect(&self, peer_ip: SocketAddr, initiator: Initiator, reason: impl ToString) {
+        if self.audit_log.read().is_some() && self.is_connected(&peer_ip) {
+            self.disconnect_reasons.lock().entry(peer_ip).or_insert_with(|| (initiator, reason.to_string()));
+        }
+    }
+
     /// Returns the IP address of this node.
     pub fn local_ip(&self) -> SocketAddr {
         self.tcp.listening_addr().expect("The TCP listener is not enabled")
     }
 
+    /// Returns the external IP address of this node, if one is configured for the proxy,
+    /// or if its listening port is mapped on the gateway.
+    pub fn external_ip(&self) -> Option<SocketAddr> {
+        self.proxy_external_ip().or_else(|| self.port_mapping.external_addr())
+    }
+
+    /// Returns the external IP address of this node that is configured for the proxy, if any.
+    pub fn proxy_external_ip(&self) -> Option<SocketAddr> {
+        self.proxy.and_then(|proxy| proxy.external_addr)
+    }
+
+    /// Returns the IP address of this node that is advertised to peers.
+    pub fn advertised_ip(&self) -> SocketAddr {
+        match self.proxy_external_ip() {
+            Some(external_ip) => external_ip,
+            None => self.port_mapping.advertised_addr(self.local_ip()),
+        }
+    }
+
+    /// Returns the configuration of the proxy for the outbound connections, if enabled.
+    pub fn proxy(&self) -> Option<ProxyConfig> {
+        self.proxy
+    }
+
     /// Returns `true` if the given IP is this node.
     pub fn is_local_ip(&self, ip: &SocketAddr) -> bool {
         *ip == self.local_ip()
             || (ip.ip().is_unspecified() || ip.ip().is_loopback()) && ip.port() == self.local_ip().port()
+            || Some(*ip) == self.external_ip()
     }
 
     /// Returns `true` if the given IP is not this node, is not a bogon address, and is not unspecified.
@@ -230,11 +366,31 @@ impl<N: Network> Router<N> {
         !self.is_local_ip(ip) && !is_bogon_ip(ip.ip()) && !is_unspecified_or_broadcast_ip(ip.ip())
     }
 
+    /// Returns the filter for the candidate peer addresses received from peers.
+    pub fn peer_address_filter(&self) -> PeerAddressFilter {
+        PeerAddressFilter::new(self.local_ip(), self.external_ip())
+    }
+
     /// Returns the node type.
     pub fn node_type(&self) -> NodeType {
         self.node_type
     }
 
+    /// Returns the protocol capabilities of the node.
+    pub fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+
+    /// Returns the range of message versions supported by the node.
+    pub fn versions(&self) -> VersionRange {
+        *self.versions.read()
+    }
+
+    /// Updates the range of message versions supported by the node, for the subsequent handshakes.
+    pub fn set_versions(&self, versions: VersionRange) {
+        *self.versions.write() = versions;
+    }
+
     /// Returns the account private key of the node.
     pub fn private_key(&self) -> &PrivateKey<N> {
         self.account.private_key()
@@ -275,6 +431,12 @@ impl<N: Network> Router<N> {
         self.connected_peers.read().contains_key(ip)
     }
 
+    /// Returns `true` if the outbound queue of the given peer IP is full, i.e. the peer does not keep up
+    /// with the messages sent to it. Congested peers are not selected for new requests.
+    pub fn is_congested(&self, peer_ip: &SocketAddr) -> bool {
+        self.resolve_to_ambiguous(peer_ip).is_some_and(|peer_addr| self.tcp.is_congested(peer_addr))
+    }
+
     /// Returns `true` if the given peer IP is a connected validator.
     pub fn is_connected_validator(&self, peer_ip: &SocketAddr) -> bool {
         self.connected_peers.read().get(peer_ip).map_or(false, |peer| peer.is_validator())
@@ -290,9 +452,34 @@ impl<N: Network> Router<N> {
         self.connected_peers.read().get(peer_ip).map_or(false, |peer| peer.is_client())
     }
 
+    /// Returns `true` if the given peer IP is a connected archive.
+    pub fn is_connected_archive(&self, peer_ip: &SocketAddr) -> bool {
+        self.connected_peers.read().get(peer_ip).map_or(false, |peer| peer.is_archive())
+    }
+
     /// Returns `true` if the node is currently connecting to the given peer IP.
     pub fn is_connecting(&self, ip: &SocketAddr) -> bool {
-        self.connecting_peers.lock().contains(ip)
+        self.peer_state(ip).is_connecting()
+    }
+
+    /// Returns the state of the connection with the given peer IP.
+    pub fn peer_state(&self, ip: &SocketAddr) -> PeerState {
+        self.peer_states.get(ip)
+    }
+
+    /// Applies the given event to the state of the connection with the given peer, and returns the new state.
+    /// If the peer is banned and its restriction expired, the ban is lifted first.
+    pub(crate) fn transition_peer(&self, peer_ip: SocketAddr, event: PeerEvent) -> Result<PeerState> {
+        if self.peer_states.get(&peer_ip) == PeerState::Banned && !self.is_restricted(&peer_ip) {
+            let _ = self.peer_states.transition(peer_ip, PeerEvent::Unban);
+        }
+        self.peer_states.transition(peer_ip, event)
+    }
+
+    /// Returns a guard that applies the given event to the given peer when it is dropped,
+    /// if the peer is still in its current state.
+    pub(crate) fn guard_peer_state(&self, peer_ip: SocketAddr, event: PeerEvent) -> PeerStateGuard {
+        self.peer_states.guard(peer_ip, event)
     }
 
     /// Returns `true` if the given IP is restricted.
@@ -309,6 +496,51 @@ impl<N: Network> Router<N> {
         self.trusted_peers.contains(ip)
     }
 
+    /// Returns the current misbehavior score of the given IP.
+    pub fn misbehavior_score(&self, ip: &SocketAddr) -> f64 {
+        self.misbehavior.read().get(ip).map_or(0.0, |score| score.score_at(Instant::now()))
+    }
+
+    /// Returns the reason the given peer rejected the given item, if the item should not be resent to it.
+    pub fn rejection_reason(&self, peer_ip: &SocketAddr, item: RejectedItem<N>) -> Option<RejectReason> {
+        self.rejections.get(*peer_ip, item)
+    }
+
+    /// Returns the estimate of the clock skew of the node against its peers.
+    pub fn clock_skew(&self) -> &ClockSkew {
+        &self.clock_skew
+    }
+
+    /// Returns the sampler of the propagation delay of transactions.
+    pub fn propagation(&self) -> &PropagationSampler<N> {
+        &self.propagation
+    }
+
+    /// Records a clock sample from the given peer, where `sent` is the local timestamp of the `Ping`,
+    /// and `answered` is the timestamp of the peer when it answered the `Ping`, in UNIX milliseconds.
+    pub fn observe_clock_sample(&self, peer_ip: SocketAddr, sent: i64, answered: i64) {
+        let level = self.clock_skew.observe(peer_ip, sent, answered, now_unix_millis());
+        self.log_clock_skew_level(level);
+    }
+
+    /// Logs the given change in the level of the clock skew, if there is one.
+    fn log_clock_skew_level(&self, level: Option<ClockSkewLevel>) {
+        let offset = self.clock_skew.offset_in_ms().unwrap_or_default();
+        match level {
+            Some(ClockSkewLevel::Normal) => info!("The system clock is back in sync with the network"),
+            Some(ClockSkewLevel::Warning) => warn!(
+                "{}",
+                format!("The system clock is off by {offset}ms against the network - please check the clock").bold()
+            ),
+            Some(ClockSkewLevel::Halt) => error!(
+                "{}",
+                format!("The system clock is off by {offset}ms against the network, beyond the tolerance of the BFT")
+                    .bold()
+            ),
+            None => (),
+        }
+    }
+
     /// Returns the maximum number of connected peers.
     pub fn max_connected_peers(&self) -> usize {
         self.tcp.config().max_connections as usize
@@ -319,6 +551,11 @@ impl<N: Network> Router<N> {
         self.connected_peers.read().len()
     }
 
+    /// Returns the number of block responses awaiting processing.
+    pub fn number_of_queued_block_responses(&self) -> usize {
+        self.block_responses.len()
+    }
+
     /// Returns the number of connected validators.
     pub fn number_of_connected_validators(&self) -> usize {
         self.connected_peers.read().values().filter(|peer| peer.is_validator()).count()
@@ -374,11 +611,36 @@ impl<N: Network> Router<N> {
         self.connected_peers.read().iter().filter(|(_, peer)| peer.is_client()).map(|(ip, _)| *ip).collect()
     }
 
+    /// Returns the list of connected archives.
+    pub fn connected_archives(&self) -> Vec<SocketAddr> {
+        self.connected_peers.read().iter().filter(|(_, peer)| peer.is_archive()).map(|(ip, _)| *ip).collect()
+    }
+
+    /// Returns the list of connected peers that accept compact block announcements.
+    pub fn connected_compact_block_peers(&self) -> Vec<SocketAddr> {
+        self.connected_peers
+            .read()
+            .iter()
+            .filter(|(_, peer)| peer.supports_feature(Feature::CompactBlocks))
+            .map(|(ip, _)| *ip)
+            .collect()
+    }
+
     /// Returns the list of candidate peers.
     pub fn candidate_peers(&self) -> HashSet<SocketAddr> {
+        self.candidate_peers.read().keys().copied().collect()
+    }
+
+    /// Returns the candidate peers, along with their metadata.
+    pub fn candidate_peers_with_info(&self) -> HashMap<SocketAddr, PeerInfo> {
         self.candidate_peers.read().clone()
     }
 
+    /// Returns the metadata of the given candidate peer, if it exists.
+    pub fn get_candidate_peer_info(&self, peer_ip: &SocketAddr) -> Option<PeerInfo> {
+        self.candidate_peers.read().get(peer_ip).copied()
+    }
+
     /// Returns the list of restricted peers.
     pub fn restricted_peers(&self) -> Vec<SocketAddr> {
         self.restricted_peers.read().keys().copied().collect()
@@ -403,9 +665,105 @@ impl<N: Network> Router<N> {
         }
     }
 
-    /// Returns the list of metrics for the connected peers.
-    pub fn connected_metrics(&self) -> Vec<(SocketAddr, NodeType)> {
-        self.connected_peers.read().iter().map(|(ip, peer)| (*ip, peer.node_type())).collect()
+    /// Returns the plan of the heartbeat, if the heartbeat has started.
+    pub fn heartbeat_plan(&self) -> Option<HeartbeatPlan> {
+        *self.heartbeat_plan.read()
+    }
+
+    /// Sets the plan of the heartbeat, until the next heartbeat.
+    pub fn set_heartbeat_plan(&self, plan: HeartbeatPlan) {
+        *self.heartbeat_plan.write() = Some(plan);
+    }
+
+    /// Returns the audit log of the peer connection events, if enabled.
+    pub fn audit_log(&self) -> Option<Arc<AuditLog>> {
+        self.audit_log.read().clone()
+    }
+
+    /// Sets the audit log of the peer connection events.
+    pub fn set_audit_log(&self, audit_log: Arc<AuditLog>) {
+        *self.audit_log.write() = Some(audit_log);
+    }
+
+    /// Returns the maximum number of (untrusted) peers to connect to in a single subnet bucket, if configured.
+    pub fn max_peers_per_subnet(&self) -> Option<usize> {
+        *self.max_peers_per_subnet.read()
+    }
+
+    /// Sets the maximum number of (untrusted) peers to connect to in a single subnet bucket.
+    pub fn set_max_peers_per_subnet(&self, max_peers_per_subnet: usize) {
+        *self.max_peers_per_subnet.write() = Some(max_peers_per_subnet.max(1));
+    }
+
+    /// Records the given event in the audit log, if enabled. The event is only constructed if it is recorded.
+    pub(crate) fn record_audit_event(&self, event: impl FnOnce() -> AuditEvent) {
+        if let Some(audit_log) = self.audit_log.read().as_ref() {
+            audit_log.record(event());
+        }
+    }
+
+    /// Returns the number of peers that connected or disconnected since the last call, and resets it.
+    pub fn take_num_churned(&self) -> usize {
+        self.num_churned.swap(0, Ordering::Relaxed)
+    }
+
+    /// Returns the list of metrics for the connected peers, as their node type, misbehavior score,
+    /// the number of bytes saved by compression, and whether their message version is deprecated.
+    pub fn connected_metrics(&self) -> Vec<(SocketAddr, NodeType, f64, u64, bool)> {
+        let connected_peers = self.connected_peers.read();
+        connected_peers
+            .iter()
+            .map(|(ip, peer)| {
+                (*ip, peer.node_type(), self.misbehavior_score(ip), peer.bytes_saved(), peer.is_deprecated())
+            })
+            .collect()
+    }
+
+    /// Returns the number of outbound messages to each connected peer that were dropped from its full queue,
+    /// by message type. The peers without any dropped messages are omitted.
+    pub fn connected_dropped_messages(&self) -> Vec<(SocketAddr, HashMap<Cow<'static, str>, u64>)> {
+        self.connected_peers()
+            .into_iter()
+            .filter_map(|peer_ip| {
+                let stats = self.tcp.known_peers().get(self.resolve_to_ambiguous(&peer_ip)?)?;
+                Some((peer_ip, stats.dropped()))
+            })
+            .filter(|(_, dropped)| !dropped.is_empty())
+            .collect()
+    }
+
+    /// Returns the number of connected peers whose outbound queues are full.
+    pub fn number_of_congested_peers(&self) -> usize {
+        self.connected_peers().iter().filter(|peer_ip| self.is_congested(peer_ip)).count()
+    }
+
+    /// Returns the message codec for the connection with the given peer address,
+    /// which compresses the large messages if both sides support compression,
+    /// and penalizes the peer for every message that cannot be deserialized.
+    /// Note: The peer is inserted into the connected peers during the handshake, before the codecs are created.
+    pub fn message_codec(&self, peer_addr: SocketAddr) -> MessageCodec<N> {
+        let Some(peer_ip) = self.resolve_to_listener(&peer_addr) else {
+            return Default::default();
+        };
+        let Some(peer) = self.get_connected_peer(&peer_ip) else {
+            return Default::default();
+        };
+        // Note: The codec holds a weak reference, as it lives in a connection task that is owned by the router.
+        let router = Arc::downgrade(&self.0);
+        let on_invalid_message = Arc::new(move || {
+            if let Some(router) = router.upgrade() {
+                Router(router).record_misbehavior(peer_ip, Offense::FailedDeserialization);
+            }
+        });
+        MessageCodec::negotiated(self.capabilities, peer.capabilities(), peer.bytes_saved_counter())
+            .with_invalid_message_handler(on_invalid_message)
+    }
+
+    /// Returns the number of connected peers in each subnet bucket, sorted by bucket.
+    pub fn connected_subnet_distribution(&self) -> Vec<(SubnetBucket, usize)> {
+        let mut distribution = bucket_distribution(self.connected_peers.read().keys()).into_iter().collect::<Vec<_>>();
+        distribution.sort_unstable();
+        distribution
     }
 
     #[cfg(feature = "metrics")]
@@ -413,11 +771,17 @@ impl<N: Network> Router<N> {
         metrics::gauge(metrics::router::CONNECTED, self.connected_peers.read().len() as f64);
         metrics::gauge(metrics::router::CANDIDATE, self.candidate_peers.read().len() as f64);
         metrics::gauge(metrics::router::RESTRICTED, self.restricted_peers.read().len() as f64);
+        let num_deprecated = self.connected_peers.read().values().filter(|peer| peer.is_deprecated()).count();
+        metrics::gauge(metrics::router::DEPRECATED, num_deprecated as f64);
     }
 
     /// Inserts the given peer into the connected peers.
     pub fn insert_connected_peer(&self, peer: Peer<N>, peer_addr: SocketAddr) {
         let peer_ip = peer.ip();
+        // Complete the handshake in the state of the peer.
+        if let Err(error) = self.transition_peer(peer_ip, PeerEvent::HandshakeSucceeded) {
+            debug!("Connected to '{peer_ip}' out of order - {error}");
+        }
         // Adds a bidirectional map between the listener address and (ambiguous) peer address.
         self.resolver.insert_peer(peer_ip, peer_addr);
         // Add an entry for this `Peer` in the connected peers.
@@ -426,28 +790,39 @@ impl<N: Network> Router<N> {
         self.candidate_peers.write().remove(&peer_ip);
         // Remove this peer from the restricted peers, if it exists.
         self.restricted_peers.write().remove(&peer_ip);
+        // Count the connection towards the peer churn.
+        self.num_churned.fetch_add(1, Ordering::Relaxed);
         #[cfg(feature = "metrics")]
         self.update_metrics();
     }
 
-    /// Inserts the given peer IPs to the set of candidate peers.
+    /// Inserts the given peers to the set of candidate peers, merging their metadata with any already known.
+    /// The peers that are not known yet are tagged with the given source.
     ///
     /// This method skips adding any given peers if the combined size exceeds the threshold,
     /// as the peer providing this list could be subverting the protocol.
-    pub fn insert_candidate_peers(&self, peers: &[SocketAddr]) {
+    pub fn insert_candidate_peers(&self, peers: &[AdvertisedPeer], source: PeerSource) {
         // Compute the maximum number of candidate peers.
         let max_candidate_peers = Self::MAXIMUM_CANDIDATE_PEERS.saturating_sub(self.number_of_candidate_peers());
         // Ensure the combined number of peers does not surpass the threshold.
         let eligible_peers = peers
             .iter()
-            .filter(|peer_ip| {
+            .filter(|peer| {
                 // Ensure the peer is not itself, is not already connected, and is not restricted.
-                !self.is_local_ip(peer_ip) && !self.is_connected(peer_ip) && !self.is_restricted(peer_ip)
+                !self.is_local_ip(&peer.ip) && !self.is_connected(&peer.ip) && !self.is_restricted(&peer.ip)
             })
             .take(max_candidate_peers);
 
-        // Proceed to insert the eligible candidate peer IPs.
-        self.candidate_peers.write().extend(eligible_peers);
+        // Proceed to insert the eligible candidate peers.
+        let now = now_unix();
+        let mut candidate_peers = self.candidate_peers.write();
+        for peer in eligible_peers {
+            let info = candidate_peers.entry(peer.ip).or_insert_with(|| PeerInfo { source, ..Default::default() });
+            info.merge(PeerInfo::from(peer));
+            // Note: An advertisement partially resets the backoff of a dormant candidate peer.
+            info.backoff.record_advertisement(now);
+        }
+        drop(candidate_peers);
         #[cfg(feature = "metrics")]
         self.update_metrics();
     }
@@ -458,10 +833,49 @@ impl<N: Network> Router<N> {
         self.candidate_peers.write().remove(&peer_ip);
         // Add the peer to the restricted peers.
         self.restricted_peers.write().insert(peer_ip, Instant::now());
+        let _ = self.peer_states.transition(peer_ip, PeerEvent::Ban);
         #[cfg(feature = "metrics")]
         self.update_metrics();
     }
 
+    /// Records the given offense against the peer, and returns `true` if the peer is restricted as a result.
+    ///
+    /// Once the misbehavior score of the peer reaches the threshold, the peer is disconnected and restricted,
+    /// and its score is reset. Trusted peers are scored, but never restricted.
+    pub fn record_misbehavior(&self, peer_ip: SocketAddr, offense: Offense) -> bool {
+        let now = Instant::now();
+        let mut misbehavior = self.misbehavior.write();
+        // Forget the negligible scores, to bound the number of scored peers.
+        if misbehavior.len() >= Self::MAXIMUM_CANDIDATE_PEERS {
+            misbehavior.retain(|_, score| !score.is_negligible(now));
+        }
+        // Record the offense.
+        let score = misbehavior.entry(peer_ip).or_insert_with(|| MisbehaviorScore::new(now));
+        if !score.record(offense, now) {
+            debug!("Recorded {offense:?} from '{peer_ip}' (score = {:.1})", score.score_at(now));
+            return false;
+        }
+        // Ensure the peer is not trusted.
+        if self.is_trusted(&peer_ip) {
+            warn!("Trusted peer '{peer_ip}' reached the misbehavior threshold ({offense:?})");
+            return false;
+        }
+        misbehavior.remove(&peer_ip);
+        drop(misbehavior);
+
+        warn!("Restricting '{peer_ip}' for misbehavior ({offense:?})");
+        self.insert_restricted_peer(peer_ip);
+        self.record_audit_event(|| {
+            let event = AuditEvent::new(AuditEventKind::Ban, peer_ip).with_reason(format!("{offense:?}"));
+            match self.get_connected_peer(&peer_ip) {
+                Some(peer) => event.with_peer(&peer),
+                None => event,
+            }
+        });
+        self.disconnect_with_reason(peer_ip, format!("Restricted for misbehavior ({offense:?})"));
+        true
+    }
+
     /// Updates the connected peer with the given function.
     pub fn update_connected_peer<Fn: FnMut(&mut Peer<N>)>(
         &self,
@@ -486,9 +900,35 @@ impl<N: Network> Router<N> {
         // Removes the bidirectional map between the listener address and (ambiguous) peer address.
         self.resolver.remove_peer(&peer_ip);
         // Remove this peer from the connected peers, if it exists.
-        self.connected_peers.write().remove(&peer_ip);
-        // Add the peer to the candidate peers.
-        self.candidate_peers.write().insert(peer_ip);
+        let peer = self.connected_peers.write().remove(&peer_ip);
+        // Note: A banned peer remains banned once its connection is closed.
+        let _ = self.transition_peer(peer_ip, PeerEvent::Closed);
+        // Record the disconnection in the audit log.
+        let (initiator, reason) = self
+            .disconnect_reasons
+            .lock()
+            .remove(&peer_ip)
+            .unwrap_or_else(|| (Initiator::Remote, "The connection was closed".to_string()));
+        if let Some(peer) = &peer {
+            self.record_audit_event(|| {
+                AuditEvent::new(AuditEventKind::Disconnect, peer_ip)
+                    .with_peer(peer)
+                    .with_initiator(initiator)
+                    .with_reason(reason)
+                    .with_session(peer.first_seen().elapsed())
+            });
+        }
+        // Add the peer to the candidate peers, retaining what is known about it.
+        let info = peer.map(|peer| peer.info(now_unix())).unwrap_or_default();
+        self.candidate_peers.write().entry(peer_ip).or_default().merge(info);
+        // Drop the pending block-by-hash request to this peer, if it exists.
+        self.block_by_hash_requests.lock().remove(&peer_ip);
+        // Drop the clock samples of this peer.
+        let level = self.clock_skew.remove_peer(peer_ip);
+        self.log_clock_skew_level(level);
+        // Count the disconnection towards the peer churn, and wake up the heartbeat.
+        self.num_churned.fetch_add(1, Ordering::Relaxed);
+        self.peer_disconnected.notify_one();
         #[cfg(feature = "metrics")]
         self.update_metrics();
     }
@@ -500,6 +940,30 @@ impl<N: Network> Router<N> {
         self.update_metrics();
     }
 
+    /// Inserts the given block-by-hash request as pending a response from the given peer.
+    /// Only one block-by-hash request may be pending per peer.
+    pub fn insert_block_by_hash_request(&self, peer_ip: SocketAddr, pending: PendingBlockByHash<N>) -> Result<()> {
+        let mut requests = self.block_by_hash_requests.lock();
+        if requests.contains_key(&peer_ip) {
+            bail!("A block-by-hash request to '{peer_ip}' is already pending")
+        }
+        requests.insert(peer_ip, pending);
+        Ok(())
+    }
+
+    /// Removes the pending block-by-hash request to the given peer, if it exists.
+    pub fn remove_block_by_hash_request(&self, peer_ip: &SocketAddr) -> Option<PendingBlockByHash<N>> {
+        self.block_by_hash_requests.lock().remove(peer_ip)
+    }
+
+    /// Records a failed dial to the given candidate peer, delaying its next dial.
+    fn record_dial_failure(&self, peer_ip: SocketAddr) {
+        if let Some(info) = self.candidate_peers.write().get_mut(&peer_ip) {
+            info.backoff.record_failure(now_unix(), &mut rand::thread_rng());
+            debug!("Backing off from '{peer_ip}' for {}s", info.backoff.next_attempt.saturating_sub(now_unix()));
+        }
+    }
+
     /// Removes the given address from the candidate peers, if it exists.
     pub fn remove_candidate_peer(&self, peer_ip: SocketAddr) {
         self.candidate_peers.write().remove(&peer_ip);
@@ -507,6 +971,78 @@ impl<N: Network> Router<N> {
         self.update_metrics();
     }
 
+    /// Starts mapping the listening port on the gateway of the local network over UPnP, and renews the lease
+    /// until the router shuts down. If the port cannot be mapped, the node continues without a mapping.
+    pub fn start_port_mapping(&self) {
+        let router = self.clone();
+        self.spawn(async move {
+            let local_ip = router.local_ip();
+            // Find the gateway, and map the listening port.
+            let result = match IgdGateway::search().await {
+                Ok(gateway) => {
+                    let gateway: Arc<dyn PortMappingGateway> = Arc::new(gateway);
+                    router.port_mapping.map(gateway.clone(), local_ip).await.map(|lease| (gateway, lease))
+                }
+                Err(error) => Err(error),
+            };
+            let gateway = match result {
+                Ok((gateway, lease)) => {
+                    info!("Mapped the listening port on the gateway, advertising '{}' to peers", lease.external_addr);
+                    gateway
+                }
+                Err(error) => {
+                    warn!("Unable to map the listening port over UPnP, the node may be unreachable from outside its network - {error}");
+                    return;
+                }
+            };
+            // Renew the lease before it expires.
+            loop {
+                tokio::time::sleep(PORT_MAPPING_LEASE / 2).await;
+                if let Err(error) = router.port_mapping.map(gateway.clone(), local_ip).await {
+                    warn!("Failed to renew the port mapping on the gateway - {error}");
+                }
+            }
+        });
+    }
+
+    /// Queries the given seeds, and inserts the valid peers they return into the candidate peers.
+    /// Returns the number of valid peers returned by the seeds.
+    pub async fn discover_seed_peers(&self, discovery: &SeedDiscovery<N>) -> usize {
+        let filter = self.peer_address_filter();
+        let peers = discovery.discover().await;
+        let mut num_valid = 0;
+        for source in [PeerSource::DnsSeed, PeerSource::SeedList] {
+            // Note: The seeds are untrusted, so their peers are inserted without metadata, as low-priority candidates.
+            let peers = peers
+                .iter()
+                .filter(|(peer_ip, peer_source)| *peer_source == source && filter.is_valid(peer_ip))
+                .map(|(peer_ip, _)| AdvertisedPeer::from(*peer_ip))
+                .collect::<Vec<_>>();
+            num_valid += peers.len();
+            self.insert_candidate_peers(&peers, source);
+        }
+        num_valid
+    }
+
+    /// Starts the discovery of candidate peers from the given seeds, which are queried on startup,
+    /// and again whenever the node has few connected peers. If every seed fails, the node continues
+    /// with the peers it already knows, e.g. its trusted peers.
+    pub fn start_seed_discovery(&self, discovery: SeedDiscovery<N>) {
+        if discovery.is_empty() {
+            return;
+        }
+        let router = self.clone();
+        self.spawn(async move {
+            loop {
+                if discovery.is_due(router.number_of_connected_peers(), Instant::now()) {
+                    let num_peers = router.discover_seed_peers(&discovery).await;
+                    info!("Discovered {num_peers} candidate peers from the seeds");
+                }
+                tokio::time::sleep(SEED_CHECK_INTERVAL).await;
+            }
+        });
+    }
+
     /// Spawns a task with the given future; it should only be used for long-running tasks.
     pub fn spawn<T: Future<Output = ()> + Send + 'static>(&self, future: T) {
         self.handles.lock().push(tokio::spawn(future));
@@ -517,6 +1053,8 @@ impl<N: Network> Router<N> {
         info!("Shutting down the router...");
         // Abort the tasks.
         self.handles.lock().iter().for_each(|handle| handle.abort());
+        // Remove the port mapping, if there is one.
+        self.port_mapping.unmap().await;
         // Close the listener.
         self.tcp.shut_down().await;
     }
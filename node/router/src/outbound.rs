@@ -13,14 +13,27 @@
 // limitations under the License.
 
 use crate::{
-    messages::{Message, Ping},
+    messages::{
+        BlockByHashRequest,
+        BlocksByHash,
+        CompactBlock,
+        Feature,
+        Message,
+        Ping,
+        Reject,
+        RejectReason,
+        RejectedItem,
+    },
+    now_unix_millis,
+    PendingBlockByHash,
     Router,
 };
 use snarkos_node_sync_locators::BlockLocators;
 use snarkos_node_tcp::protocols::Writing;
-use snarkvm::prelude::Network;
+use snarkvm::prelude::{block::Block, Network};
 use std::io;
 
+use anyhow::{bail, ensure, Result};
 use std::net::SocketAddr;
 use tokio::sync::oneshot;
 
@@ -30,7 +43,51 @@ pub trait Outbound<N: Network>: Writing<Message = Message<N>> {
 
     /// Sends a "Ping" message to the given peer.
     fn send_ping(&self, peer_ip: SocketAddr, block_locators: Option<BlockLocators<N>>) {
-        self.send(peer_ip, Message::Ping(Ping::new(self.router().node_type(), block_locators)));
+        let mut ping = Ping::new(self.router().node_type(), block_locators);
+        // Include the timestamp, if the peer supports clock sync.
+        if self.router().get_connected_peer(&peer_ip).is_some_and(|peer| peer.supports_feature(Feature::ClockSync)) {
+            ping = ping.with_timestamp(now_unix_millis());
+        }
+        self.send(peer_ip, Message::Ping(ping));
+    }
+
+    /// Sends a "Reject" message to the given peer, informing it that the given item it supplied was dropped.
+    fn send_reject(&self, peer_ip: SocketAddr, item: RejectedItem<N>, reason: RejectReason, message: &str) {
+        debug!("Rejecting {item} from '{peer_ip}' ({reason}) - {message}");
+        self.send(peer_ip, Message::Reject(Reject::new(item, reason, message)));
+    }
+
+    /// Sends a "BlockByHashRequest" message to the given peer, and returns a receiver for the blocks in its response.
+    fn request_blocks_by_hash(
+        &self,
+        peer_ip: SocketAddr,
+        block_hashes: Vec<N::BlockHash>,
+    ) -> Result<oneshot::Receiver<BlocksByHash<N>>> {
+        // Ensure the number of block hashes is within the bounds.
+        ensure!(
+            block_hashes.len() <= BlockByHashRequest::<N>::MAXIMUM_NUMBER_OF_HASHES,
+            "Cannot request more than {} blocks by hash at once",
+            BlockByHashRequest::<N>::MAXIMUM_NUMBER_OF_HASHES
+        );
+        // Ensure the peer is connected, and understands block requests by hash.
+        let peer = self.router().get_connected_peer(&peer_ip);
+        ensure!(peer.is_some(), "Peer '{peer_ip}' is not connected");
+        ensure!(
+            peer.is_some_and(|peer| peer.supports_feature(Feature::BlockByHash)),
+            "Peer '{peer_ip}' does not support block requests by hash"
+        );
+
+        // Register the request, to match it with the response of the peer.
+        let request = BlockByHashRequest { block_hashes };
+        let (callback, receiver) = oneshot::channel();
+        let pending = PendingBlockByHash { request: request.clone(), callback };
+        self.router().insert_block_by_hash_request(peer_ip, pending)?;
+        // Send the request to the peer.
+        if self.send(peer_ip, Message::BlockByHashRequest(request)).is_none() {
+            self.router().remove_block_by_hash_request(&peer_ip);
+            bail!("Unable to send the block-by-hash request to '{peer_ip}'")
+        }
+        Ok(receiver)
     }
 
     /// Sends the given message to specified peer.
@@ -79,21 +136,8 @@ pub trait Outbound<N: Network>: Writing<Message = Message<N>> {
 
     /// Sends the given message to every connected peer, excluding the sender and any specified peer IPs.
     fn propagate(&self, message: Message<N>, excluded_peers: &[SocketAddr]) {
-        // TODO (howardwu): Serialize large messages once only.
-        // // Perform ahead-of-time, non-blocking serialization just once for applicable objects.
-        // if let Message::UnconfirmedSolution(ref mut message) = message {
-        //     if let Ok(serialized_solution) = Data::serialize(message.solution.clone()).await {
-        //         let _ = std::mem::replace(&mut message.solution, Data::Buffer(serialized_solution));
-        //     } else {
-        //         error!("Solution serialization is bugged");
-        //     }
-        // } else if let Message::UnconfirmedTransaction(ref mut message) = message {
-        //     if let Ok(serialized_transaction) = Data::serialize(message.transaction.clone()).await {
-        //         let _ = std::mem::replace(&mut message.transaction, Data::Buffer(serialized_transaction));
-        //     } else {
-        //         error!("Transaction serialization is bugged");
-        //     }
-        // }
+        // Serialize the payload once, instead of once per peer.
+        let message = message.into_buffered();
 
         // Prepare the peers to send to.
         let connected_peers = self.router().connected_peers();
@@ -107,21 +151,8 @@ pub trait Outbound<N: Network>: Writing<Message = Message<N>> {
 
     /// Sends the given message to every connected validator, excluding the sender and any specified IPs.
     fn propagate_to_validators(&self, message: Message<N>, excluded_peers: &[SocketAddr]) {
-        // TODO (howardwu): Serialize large messages once only.
-        // // Perform ahead-of-time, non-blocking serialization just once for applicable objects.
-        // if let Message::UnconfirmedSolution(ref mut message) = message {
-        //     if let Ok(serialized_solution) = Data::serialize(message.solution.clone()).await {
-        //         let _ = std::mem::replace(&mut message.solution, Data::Buffer(serialized_solution));
-        //     } else {
-        //         error!("Solution serialization is bugged");
-        //     }
-        // } else if let Message::UnconfirmedTransaction(ref mut message) = message {
-        //     if let Ok(serialized_transaction) = Data::serialize(message.transaction.clone()).await {
-        //         let _ = std::mem::replace(&mut message.transaction, Data::Buffer(serialized_transaction));
-        //     } else {
-        //         error!("Transaction serialization is bugged");
-        //     }
-        // }
+        // Serialize the payload once, instead of once per peer.
+        let message = message.into_buffered();
 
         // Prepare the peers to send to.
         let connected_validators = self.router().connected_validators();
@@ -133,6 +164,25 @@ pub trait Outbound<N: Network>: Writing<Message = Message<N>> {
         }
     }
 
+    /// Announces the given block to every connected peer that accepts compact blocks, excluding the specified peer IPs.
+    fn announce_block(&self, block: &Block<N>, excluded_peers: &[SocketAddr]) {
+        // Remove the transactions of the block from the recent unconfirmed transactions.
+        self.router().announcements.remove_transactions(block);
+        // Retain the block, in order to serve its transactions to peers that are missing them.
+        self.router().announcements.insert_announced_block(block.clone());
+        // Prepare the compact block, with the short IDs keyed by a fresh nonce for this announcement.
+        let message = Message::CompactBlock(CompactBlock::new(block, rand::random()));
+
+        // Prepare the peers to send to.
+        let connected_peers = self.router().connected_compact_block_peers();
+        let peers = connected_peers.iter().filter(|peer_ip| !excluded_peers.contains(peer_ip));
+
+        // Iterate through all peers that accept compact blocks and are not excluded.
+        for peer_ip in peers {
+            self.send(*peer_ip, message.clone());
+        }
+    }
+
     /// Returns `true` if the message can be sent.
     fn can_send(&self, peer_ip: SocketAddr, message: &Message<N>) -> bool {
         // Ensure the peer is connected before sending.
@@ -142,19 +192,41 @@ pub trait Outbound<N: Network>: Writing<Message = Message<N>> {
         }
         // Determine whether to send the message.
         match message {
+            Message::CompactBlock(message) => {
+                // Determine whether the peer rejected the block.
+                !self.router().rejections.is_rejected(peer_ip, RejectedItem::Block(message.block_hash))
+            }
             Message::UnconfirmedSolution(message) => {
+                // Ensure the peer did not reject the solution.
+                if self.router().rejections.is_rejected(peer_ip, RejectedItem::Solution(message.solution_id)) {
+                    return false;
+                }
                 // Update the timestamp for the unconfirmed solution.
                 let seen_before = self.router().cache.insert_outbound_solution(peer_ip, message.solution_id).is_some();
                 // Determine whether to send the solution.
                 !seen_before
             }
             Message::UnconfirmedTransaction(message) => {
+                // Ensure the peer did not reject the transaction.
+                if self.router().rejections.is_rejected(peer_ip, RejectedItem::Transaction(message.transaction_id)) {
+                    return false;
+                }
                 // Update the timestamp for the unconfirmed transaction.
                 let seen_before =
                     self.router().cache.insert_outbound_transaction(peer_ip, message.transaction_id).is_some();
                 // Determine whether to send the transaction.
                 !seen_before
             }
+            Message::Reject(_) => {
+                // Ensure the peer understands rejections.
+                self.router().get_connected_peer(&peer_ip).is_some_and(|peer| peer.supports_feature(Feature::Reject))
+            }
+            Message::PropagationSample(_) => {
+                // Ensure the peer understands propagation samples.
+                self.router()
+                    .get_connected_peer(&peer_ip)
+                    .is_some_and(|peer| peer.supports_feature(Feature::PropagationSample))
+            }
             // For all other message types, return `true`.
             _ => true,
         }
@@ -12,7 +12,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{Heartbeat, Inbound, Outbound};
+use crate::{
+    messages::{DisconnectReason, Message},
+    Heartbeat,
+    HeartbeatSchedule,
+    Inbound,
+    Outbound,
+};
 use snarkos_node_tcp::{
     protocols::{Disconnect, Handshake, OnConnect},
     P2P,
@@ -37,6 +43,8 @@ pub trait Routing<N: Network>:
         self.enable_listener().await;
         // Initialize the heartbeat.
         self.initialize_heartbeat();
+        // Initialize the block response worker.
+        self.initialize_block_response_worker();
     }
 
     // Start listening for inbound connections.
@@ -48,11 +56,58 @@ pub trait Routing<N: Network>:
     fn initialize_heartbeat(&self) {
         let self_clone = self.clone();
         self.router().spawn(async move {
+            let router = self_clone.router();
+            let mut schedule = HeartbeatSchedule::new(
+                Self::MINIMUM_NUMBER_OF_PEERS,
+                Self::MAXIMUM_NUMBER_OF_PEERS,
+                Duration::from_secs(Self::HEARTBEAT_IN_SECS),
+                Duration::from_secs(Self::PING_SLEEP_IN_SECS),
+            );
             loop {
+                // Plan the heartbeat, given the sync status and the peer churn.
+                let plan = schedule.next(
+                    self_clone.sync_status(),
+                    router.number_of_connected_peers(),
+                    router.take_num_churned(),
+                );
+                router.set_heartbeat_plan(plan);
                 // Process a heartbeat in the router.
                 self_clone.heartbeat();
-                // Sleep for `HEARTBEAT_IN_SECS` seconds.
-                tokio::time::sleep(Duration::from_secs(Self::HEARTBEAT_IN_SECS)).await;
+                // Sleep until the next heartbeat, or until the connected peers drop below the minimum.
+                let deadline = tokio::time::Instant::now() + plan.heartbeat_interval;
+                loop {
+                    tokio::select! {
+                        _ = tokio::time::sleep_until(deadline) => break,
+                        _ = router.peer_disconnected.notified() => {
+                            if schedule.is_below_minimum(router.number_of_connected_peers()) {
+                                debug!("Dropped below the minimum number of peers, starting a discovery round");
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    /// Initialize the worker that processes the queued block responses, one at a time.
+    fn initialize_block_response_worker(&self) {
+        let self_clone = self.clone();
+        self.router().spawn(async move {
+            loop {
+                // Wait for the next block response.
+                let queued = self_clone.router().block_responses.pop().await;
+                let peer_ip = queued.peer_ip;
+                // Process the block response. Disconnect if the peer violated the protocol.
+                if let Err(error) = self_clone.process_block_response(queued).await {
+                    warn!("Disconnecting from '{peer_ip}' - {error}");
+                    Outbound::send(
+                        &self_clone,
+                        peer_ip,
+                        Message::Disconnect(DisconnectReason::ProtocolViolation.into()),
+                    );
+                    self_clone.router().disconnect(peer_ip);
+                }
             }
         });
     }
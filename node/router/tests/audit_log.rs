@@ -0,0 +1,146 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod common;
+use common::*;
+
+use snarkos_node_router::{
+    read_audit_log,
+    AuditEvent,
+    AuditEventKind,
+    AuditLog,
+    Initiator,
+    DEFAULT_AUDIT_LOG_MAX_SIZE,
+};
+use snarkos_node_tcp::{
+    protocols::{Disconnect, Handshake},
+    P2P,
+};
+
+use core::time::Duration;
+use deadline::deadline;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Returns a fresh path for an audit log.
+fn sample_path(name: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("snarkos-router-audit-log-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&directory);
+    directory.join("peers.jsonl")
+}
+
+/// Returns the kinds of the events in the given audit log.
+fn event_kinds(path: &Path) -> Vec<AuditEventKind> {
+    read_audit_log(path).unwrap_or_default().iter().map(|event| event.event).collect()
+}
+
+/// Returns the event of the given kind in the given audit log.
+fn find_event(path: &Path, kind: AuditEventKind) -> AuditEvent {
+    read_audit_log(path).unwrap().into_iter().find(|event| event.event == kind).unwrap()
+}
+
+#[tokio::test]
+async fn test_audit_log_connect_and_disconnect() {
+    // Create 2 routers, recording their peer connection events.
+    let node0 = validator(0, 1).await;
+    let node1 = client(0, 1).await;
+    let (path0, path1) = (sample_path("validator"), sample_path("client"));
+    node0.set_audit_log(Arc::new(AuditLog::start(&path0, DEFAULT_AUDIT_LOG_MAX_SIZE).unwrap()));
+    node1.set_audit_log(Arc::new(AuditLog::start(&path1, DEFAULT_AUDIT_LOG_MAX_SIZE).unwrap()));
+
+    // Enable handshake and disconnect protocols.
+    node0.enable_handshake().await;
+    node1.enable_handshake().await;
+    node0.enable_disconnect().await;
+    node1.enable_disconnect().await;
+
+    // Start listening.
+    node0.tcp().enable_listener().await.unwrap();
+    node1.tcp().enable_listener().await.unwrap();
+
+    // Connect node0 to node1.
+    node0.connect(node1.local_ip());
+    let (node0_, node1_) = (node0.clone(), node1.clone());
+    deadline!(Duration::from_secs(5), move || node0_.number_of_connected_peers() == 1
+        && node1_.number_of_connected_peers() == 1);
+
+    // Disconnect node0 from node1.
+    node0.disconnect_with_reason(node1.local_ip(), "Testing the audit log");
+    let path0_ = path0.clone();
+    deadline!(Duration::from_secs(5), move || event_kinds(&path0_).len() == 3);
+
+    // Check the events of the initiator.
+    assert_eq!(event_kinds(&path0), vec![
+        AuditEventKind::Connect,
+        AuditEventKind::HandshakeSuccess,
+        AuditEventKind::Disconnect
+    ]);
+    let connect = find_event(&path0, AuditEventKind::Connect);
+    assert_eq!(connect.peer_ip, node1.local_ip());
+    assert_eq!(connect.initiator, Some(Initiator::Local));
+    let handshake = find_event(&path0, AuditEventKind::HandshakeSuccess);
+    assert_eq!(handshake.peer_ip, node1.local_ip());
+    assert_eq!(handshake.address, Some(node1.address().to_string()));
+    assert_eq!(handshake.node_type.as_deref(), Some("Client"));
+    assert!(handshake.version.is_some());
+    let disconnect = find_event(&path0, AuditEventKind::Disconnect);
+    assert_eq!(disconnect.peer_ip, node1.local_ip());
+    assert_eq!(disconnect.address, Some(node1.address().to_string()));
+    assert_eq!(disconnect.initiator, Some(Initiator::Local));
+    assert_eq!(disconnect.reason.as_deref(), Some("Testing the audit log"));
+    assert!(disconnect.session_secs.is_some());
+
+    // Check the events of the responder, which only learns the listening address of node0 during the handshake.
+    let path1_ = path1.clone();
+    deadline!(Duration::from_secs(5), move || event_kinds(&path1_).len() >= 2);
+    assert_eq!(event_kinds(&path1)[..2], [AuditEventKind::Connect, AuditEventKind::HandshakeSuccess]);
+    assert_eq!(find_event(&path1, AuditEventKind::Connect).initiator, Some(Initiator::Remote));
+    let handshake = find_event(&path1, AuditEventKind::HandshakeSuccess);
+    assert_eq!(handshake.peer_ip, node0.local_ip());
+    assert_eq!(handshake.address, Some(node0.address().to_string()));
+    assert_eq!(handshake.node_type.as_deref(), Some("Validator"));
+    assert_eq!(handshake.initiator, Some(Initiator::Remote));
+}
+
+#[tokio::test]
+async fn test_audit_log_handshake_failure() {
+    // Create 2 routers; only node0 performs the handshake, so it cannot complete.
+    let node0 = validator(0, 1).await;
+    let node1 = client(0, 1).await;
+    let path = sample_path("handshake-failure");
+    node0.set_audit_log(Arc::new(AuditLog::start(&path, DEFAULT_AUDIT_LOG_MAX_SIZE).unwrap()));
+    node0.enable_handshake().await;
+
+    // Start listening.
+    node0.tcp().enable_listener().await.unwrap();
+    node1.tcp().enable_listener().await.unwrap();
+
+    // Connect node0 to node1, and drop the connection on the side of node1.
+    node0.connect(node1.local_ip());
+    let node1_ = node1.clone();
+    deadline!(Duration::from_secs(5), move || node1_.tcp().num_connected() == 1);
+    node1.tcp().shut_down().await;
+
+    let path_ = path.clone();
+    deadline!(Duration::from_secs(5), move || event_kinds(&path_).len() == 2);
+    assert_eq!(event_kinds(&path), vec![AuditEventKind::Connect, AuditEventKind::HandshakeFailure]);
+    let failure = find_event(&path, AuditEventKind::HandshakeFailure);
+    assert_eq!(failure.peer_ip, node1.local_ip());
+    assert_eq!(failure.initiator, Some(Initiator::Local));
+    assert!(failure.reason.is_some());
+    // The peer was never connected, so there is no disconnection.
+    assert_eq!(node0.number_of_connected_peers(), 0);
+}
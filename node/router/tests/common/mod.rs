@@ -23,7 +23,7 @@ use std::{
 };
 
 use snarkos_account::Account;
-use snarkos_node_router::{messages::NodeType, Router};
+use snarkos_node_router::{messages::NodeType, Router, RouterConfig};
 use snarkvm::prelude::{block::Block, FromBytes, MainnetV0 as CurrentNetwork, Network};
 
 /// A helper macro to print the TCP listening address, along with the connected and connecting peers.
@@ -71,50 +71,23 @@ pub fn initialize_logger(level: u8) {
 /// Initializes a client router. Setting the `listening_port = 0` will result in a random port being assigned.
 #[allow(dead_code)]
 pub async fn client(listening_port: u16, max_peers: u16) -> TestRouter<CurrentNetwork> {
-    Router::new(
-        SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), listening_port),
-        NodeType::Client,
-        sample_account(),
-        &[],
-        max_peers,
-        true,
-        true,
-    )
-    .await
-    .expect("couldn't create client router")
-    .into()
+    let node_ip = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), listening_port);
+    let config = RouterConfig::new(node_ip, NodeType::Client, max_peers).with_dev(true);
+    Router::new(sample_account(), config).await.expect("couldn't create client router").into()
 }
 
 /// Initializes a prover router. Setting the `listening_port = 0` will result in a random port being assigned.
 #[allow(dead_code)]
 pub async fn prover(listening_port: u16, max_peers: u16) -> TestRouter<CurrentNetwork> {
-    Router::new(
-        SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), listening_port),
-        NodeType::Prover,
-        sample_account(),
-        &[],
-        max_peers,
-        true,
-        true,
-    )
-    .await
-    .expect("couldn't create prover router")
-    .into()
+    let node_ip = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), listening_port);
+    let config = RouterConfig::new(node_ip, NodeType::Prover, max_peers).with_dev(true);
+    Router::new(sample_account(), config).await.expect("couldn't create prover router").into()
 }
 
 /// Initializes a validator router. Setting the `listening_port = 0` will result in a random port being assigned.
 #[allow(dead_code)]
 pub async fn validator(listening_port: u16, max_peers: u16) -> TestRouter<CurrentNetwork> {
-    Router::new(
-        SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), listening_port),
-        NodeType::Validator,
-        sample_account(),
-        &[],
-        max_peers,
-        false,
-        true,
-    )
-    .await
-    .expect("couldn't create validator router")
-    .into()
+    let node_ip = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), listening_port);
+    let config = RouterConfig::new(node_ip, NodeType::Validator, max_peers).with_external_peers(false).with_dev(true);
+    Router::new(sample_account(), config).await.expect("couldn't create validator router").into()
 }
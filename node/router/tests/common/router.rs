@@ -15,12 +15,17 @@
 use crate::common::sample_genesis_block;
 use snarkos_node_router::{
     messages::{
+        BlockByHashRequest,
+        BlockByHashResponse,
         BlockRequest,
+        BlocksByHash,
         DisconnectReason,
         Message,
         MessageCodec,
         Ping,
         Pong,
+        RejectReason,
+        RejectedItem,
         UnconfirmedSolution,
         UnconfirmedTransaction,
     },
@@ -37,10 +42,13 @@ use snarkos_node_tcp::{
     Tcp,
     P2P,
 };
-use snarkvm::prelude::{
-    block::{Block, Header, Transaction},
-    puzzle::Solution,
-    Network,
+use snarkvm::{
+    ledger::narwhal::Data,
+    prelude::{
+        block::{Block, Header, Transaction},
+        puzzle::Solution,
+        Network,
+    },
 };
 
 use async_trait::async_trait;
@@ -110,8 +118,8 @@ impl<N: Network> Writing for TestRouter<N> {
 
     /// Creates an [`Encoder`] used to write the outbound messages to the target stream.
     /// The `side` parameter indicates the connection side **from the node's perspective**.
-    fn codec(&self, _addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
-        Default::default()
+    fn codec(&self, addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
+        self.router().message_codec(addr)
     }
 }
 
@@ -122,8 +130,8 @@ impl<N: Network> Reading for TestRouter<N> {
 
     /// Creates a [`Decoder`] used to interpret messages from the network.
     /// The `side` param indicates the connection side **from the node's perspective**.
-    fn codec(&self, _peer_addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
-        Default::default()
+    fn codec(&self, peer_addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
+        self.router().message_codec(peer_addr)
     }
 
     /// Processes a message received from the network.
@@ -133,7 +141,7 @@ impl<N: Network> Reading for TestRouter<N> {
             warn!("Disconnecting from '{peer_ip}' - {error}");
             self.send(peer_ip, Message::Disconnect(DisconnectReason::ProtocolViolation.into()));
             // Disconnect from this peer.
-            self.router().disconnect(peer_ip);
+            self.router().disconnect_with_reason(peer_ip, error);
         }
         Ok(())
     }
@@ -153,6 +161,19 @@ impl<N: Network> Outbound<N> for TestRouter<N> {
 
 #[async_trait]
 impl<N: Network> Inbound<N> for TestRouter<N> {
+    /// Handles a `BlockByHashRequest` message, where only the genesis block is known.
+    fn block_by_hash_request(&self, peer_ip: SocketAddr, message: BlockByHashRequest<N>) -> bool {
+        let genesis = sample_genesis_block::<N>();
+        let blocks = message
+            .block_hashes
+            .iter()
+            .map(|block_hash| (*block_hash == genesis.hash()).then(|| genesis.clone()))
+            .collect();
+        let response = BlockByHashResponse { request: message, blocks: Data::Object(BlocksByHash(blocks)) };
+        self.send(peer_ip, Message::BlockByHashResponse(response));
+        true
+    }
+
     /// Handles a `BlockRequest` message.
     fn block_request(&self, _peer_ip: SocketAddr, _message: BlockRequest) -> bool {
         true
@@ -193,13 +214,17 @@ impl<N: Network> Inbound<N> for TestRouter<N> {
         true
     }
 
-    /// Handles an `UnconfirmedTransaction` message.
+    /// Handles an `UnconfirmedTransaction` message, where fee transactions are rejected as invalid.
     async fn unconfirmed_transaction(
         &self,
-        _peer_ip: SocketAddr,
-        _serialized: UnconfirmedTransaction<N>,
-        _transaction: Transaction<N>,
+        peer_ip: SocketAddr,
+        serialized: UnconfirmedTransaction<N>,
+        transaction: Transaction<N>,
     ) -> bool {
+        if transaction.is_fee() {
+            let item = RejectedItem::Transaction(serialized.transaction_id);
+            self.send_reject(peer_ip, item, RejectReason::Invalid, "Fee transactions are not accepted");
+        }
         true
     }
 }
@@ -0,0 +1,78 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod common;
+use common::*;
+
+use snarkos_node_router::compare_chain;
+use snarkos_node_tcp::{protocols::Handshake, P2P};
+use snarkvm::prelude::{MainnetV0 as CurrentNetwork, Network, Rng, TestRng};
+
+use core::time::Duration;
+use deadline::deadline;
+
+/// Returns two clients, where the second is connected to the first.
+async fn connected_pair() -> (TestRouter<CurrentNetwork>, TestRouter<CurrentNetwork>) {
+    let node0 = client(0, 2).await;
+    let node1 = client(0, 2).await;
+    // Enable the handshake protocol.
+    node0.enable_handshake().await;
+    node1.enable_handshake().await;
+    // Start listening.
+    node0.tcp().enable_listener().await.unwrap();
+    node1.tcp().enable_listener().await.unwrap();
+    // Connect node1 to node0.
+    node1.connect(node0.local_ip());
+    let (node0_, node1_) = (node0.clone(), node1.clone());
+    deadline!(Duration::from_secs(5), move || node0_.number_of_connected_peers() == 1
+        && node1_.number_of_connected_peers() == 1);
+    (node0, node1)
+}
+
+#[tokio::test]
+async fn test_compare_chain_on_the_same_branch() {
+    let (node0, node1) = connected_pair().await;
+
+    // Compare the genesis block, which both nodes have.
+    let local_hashes = [sample_genesis_block::<CurrentNetwork>().hash()];
+    let comparison = compare_chain(&node1, node0.local_ip(), 0, &local_hashes).await.unwrap();
+    assert!(comparison.is_consistent());
+    assert_eq!(comparison.last_common_height, Some(0));
+    assert_eq!(comparison.divergent_height, None);
+}
+
+#[tokio::test]
+async fn test_compare_chain_on_different_branches() {
+    let (node0, node1) = connected_pair().await;
+    let rng = &mut TestRng::default();
+
+    // Prepare a local branch that diverges from the peer after the genesis block.
+    // Note: The test peer only knows the genesis block, so the remaining blocks are on another branch.
+    let mut local_hashes = vec![sample_genesis_block::<CurrentNetwork>().hash()];
+    local_hashes.extend((0..25).map(|_| rng.gen::<<CurrentNetwork as Network>::BlockHash>()));
+
+    // Ensure the comparison identifies the fork point.
+    let comparison = compare_chain(&node1, node0.local_ip(), 0, &local_hashes).await.unwrap();
+    assert!(!comparison.is_consistent());
+    assert_eq!(comparison.start_height, 0);
+    assert_eq!(comparison.end_height, 26);
+    assert_eq!(comparison.last_common_height, Some(0));
+    assert_eq!(comparison.divergent_height, Some(1));
+
+    // Ensure the nodes remain connected, and another comparison can be issued.
+    assert_eq!(node0.number_of_connected_peers(), 1);
+    let comparison = compare_chain(&node1, node0.local_ip(), 5, &local_hashes[5..]).await.unwrap();
+    assert_eq!(comparison.last_common_height, None);
+    assert_eq!(comparison.divergent_height, Some(5));
+}
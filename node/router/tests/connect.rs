@@ -15,9 +15,15 @@
 mod common;
 use common::*;
 
-use snarkos_node_tcp::{protocols::Handshake, P2P};
+use snarkos_node_router::PeerState;
+use snarkos_node_tcp::{
+    protocols::{Disconnect, Handshake, Reading, Writing},
+    P2P,
+};
+use snarkvm::prelude::MainnetV0 as CurrentNetwork;
 
 use core::time::Duration;
+use deadline::deadline;
 
 #[tokio::test]
 async fn test_connect_without_handshake() {
@@ -185,3 +191,64 @@ async fn test_connect_simultaneously_with_handshake() {
         assert_eq!(node1.number_of_connected_peers(), 1);
     }
 }
+
+/// Enables the protocols of the given router, so that it notices when a connection is closed.
+async fn enable_protocols(node: &TestRouter<CurrentNetwork>) {
+    node.enable_handshake().await;
+    node.enable_reading().await;
+    node.enable_writing().await;
+    node.enable_disconnect().await;
+    node.tcp().enable_listener().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_reconnect_after_remote_restart() {
+    // Create 2 routers.
+    let node0 = validator(0, 2).await;
+    let node1 = client(0, 2).await;
+    enable_protocols(&node0).await;
+    enable_protocols(&node1).await;
+    let node1_ip = node1.local_ip();
+
+    // Connect node0 to node1.
+    assert!(node0.connect(node1_ip).unwrap().await.unwrap());
+    let node0_clone = node0.clone();
+    deadline!(Duration::from_secs(5), move || node0_clone.peer_state(&node1_ip) == PeerState::Connected);
+
+    // Restart node1 on the same port.
+    node1.shut_down().await;
+    let node0_clone = node0.clone();
+    deadline!(Duration::from_secs(5), move || node0_clone.peer_state(&node1_ip) == PeerState::Disconnected);
+    assert_eq!(node0.number_of_connected_peers(), 0);
+    let node1 = client(node1_ip.port(), 2).await;
+    enable_protocols(&node1).await;
+
+    // Ensure node0 reconnects to node1, as it is neither stuck connecting nor draining.
+    assert!(!node0.is_connecting(&node1_ip));
+    assert!(node0.connect(node1_ip).unwrap().await.unwrap());
+    let node0_clone = node0.clone();
+    deadline!(Duration::from_secs(5), move || node0_clone.peer_state(&node1_ip) == PeerState::Connected);
+    let node1_clone = node1.clone();
+    deadline!(Duration::from_secs(5), move || node1_clone.number_of_connected_peers() == 1);
+    assert_eq!(node0.number_of_connected_peers(), 1);
+}
+
+#[tokio::test]
+async fn test_timed_out_handshake_is_cleaned_up() {
+    // Create a router, and a listener that accepts connections but never completes the handshake.
+    let node0 = validator(0, 2).await;
+    node0.enable_handshake().await;
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let peer_ip = listener.local_addr().unwrap();
+
+    // Dial the listener, until the handshake is dropped on its timeout.
+    let handle = node0.connect(peer_ip).unwrap();
+    let node0_clone = node0.clone();
+    deadline!(Duration::from_secs(5), move || node0_clone.peer_state(&peer_ip) == PeerState::Handshaking);
+    assert!(!handle.await.unwrap());
+
+    // Ensure the peer is not left connecting, so that it can be dialed again.
+    assert_eq!(node0.peer_state(&peer_ip), PeerState::Disconnected);
+    assert!(node0.connect(peer_ip).is_some());
+    drop(listener);
+}
@@ -0,0 +1,89 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod common;
+use common::*;
+
+use snarkos_node_router::{
+    messages::{Message, UnconfirmedSolution, UnconfirmedTransaction},
+    Outbound,
+};
+use snarkos_node_tcp::{protocols::Handshake, P2P};
+use snarkvm::{
+    ledger::narwhal::Data,
+    prelude::{puzzle::SolutionID, Field, MainnetV0 as CurrentNetwork, Network},
+};
+
+use bytes::Bytes;
+use core::time::Duration;
+use deadline::deadline;
+
+/// Returns two clients, where the second is connected to the first.
+async fn connected_pair() -> (TestRouter<CurrentNetwork>, TestRouter<CurrentNetwork>) {
+    let node0 = client(0, 2).await;
+    let node1 = client(0, 2).await;
+    // Enable the handshake protocol.
+    node0.enable_handshake().await;
+    node1.enable_handshake().await;
+    // Start listening.
+    node0.tcp().enable_listener().await.unwrap();
+    node1.tcp().enable_listener().await.unwrap();
+    // Connect node1 to node0.
+    node1.connect(node0.local_ip());
+    let (node0_, node1_) = (node0.clone(), node1.clone());
+    deadline!(Duration::from_secs(5), move || node0_.number_of_connected_peers() == 1
+        && node1_.number_of_connected_peers() == 1);
+    (node0, node1)
+}
+
+/// Ensures the given node has disconnected from its peer, and still accepts new connections.
+async fn assert_penalized_and_running(node0: &TestRouter<CurrentNetwork>) {
+    // Ensure the sending peer was disconnected.
+    let node0_ = node0.clone();
+    deadline!(Duration::from_secs(5), move || node0_.number_of_connected_peers() == 0);
+
+    // Ensure the node keeps accepting connections.
+    let node2 = client(0, 2).await;
+    node2.enable_handshake().await;
+    node2.tcp().enable_listener().await.unwrap();
+    node2.connect(node0.local_ip());
+    let node0_ = node0.clone();
+    deadline!(Duration::from_secs(5), move || node0_.number_of_connected_peers() == 1);
+}
+
+#[tokio::test]
+async fn test_oversized_transaction_disconnects_peer() {
+    let (node0, node1) = connected_pair().await;
+
+    // Send a transaction that exceeds the maximum size.
+    let transaction_id = <CurrentNetwork as Network>::TransactionID::from(Field::from_u64(1));
+    let transaction = Data::Buffer(Bytes::from(vec![0u8; 1024 * 1024 + 1]));
+    let message = Message::UnconfirmedTransaction(UnconfirmedTransaction { transaction_id, transaction });
+    node1.send(node0.local_ip(), message);
+
+    assert_penalized_and_running(&node0).await;
+}
+
+#[tokio::test]
+async fn test_truncated_solution_disconnects_peer() {
+    let (node0, node1) = connected_pair().await;
+
+    // Send a solution that cannot be deserialized.
+    let solution_id = SolutionID::from(1u64);
+    let solution = Data::Buffer(Bytes::from(vec![0u8; 16]));
+    let message = Message::UnconfirmedSolution(UnconfirmedSolution { solution_id, solution });
+    node1.send(node0.local_ip(), message);
+
+    assert_penalized_and_running(&node0).await;
+}
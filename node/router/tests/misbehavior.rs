@@ -0,0 +1,160 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod common;
+use common::*;
+
+use snarkos_node_router::{
+    messages::{Message, Pong, UnconfirmedSolution, UnconfirmedTransaction},
+    Offense,
+    Outbound,
+    MISBEHAVIOR_THRESHOLD,
+};
+use snarkos_node_tcp::{protocols::Handshake, P2P};
+use snarkvm::{
+    ledger::narwhal::Data,
+    prelude::{puzzle::SolutionID, Field, MainnetV0 as CurrentNetwork, Network},
+};
+
+use bytes::Bytes;
+use core::time::Duration;
+use deadline::deadline;
+
+/// Returns a client that is listening for connections.
+async fn listening_client() -> TestRouter<CurrentNetwork> {
+    let node = client(0, 2).await;
+    node.enable_handshake().await;
+    node.tcp().enable_listener().await.unwrap();
+    node
+}
+
+#[tokio::test]
+async fn test_garbage_spewing_peer_is_restricted() {
+    let node0 = listening_client().await;
+    let node1 = listening_client().await;
+
+    // Determine the number of offenses that reach the misbehavior threshold.
+    let offense = Offense::FailedDeserialization;
+    let num_offenses = (MISBEHAVIOR_THRESHOLD / offense.weight()).ceil() as u64;
+
+    for i in 0..num_offenses {
+        // Check that the peer is not restricted yet.
+        assert!(!node0.is_restricted(&node1.local_ip()));
+
+        // Connect node1 to node0.
+        node1.connect(node0.local_ip());
+        let (node0_, node1_) = (node0.clone(), node1.clone());
+        deadline!(Duration::from_secs(5), move || node0_.number_of_connected_peers() == 1
+            && node1_.number_of_connected_peers() == 1);
+
+        // Send a solution that cannot be deserialized.
+        let solution_id = SolutionID::from(i + 1);
+        let solution = Data::Buffer(Bytes::from(vec![0u8; 16]));
+        let message = Message::UnconfirmedSolution(UnconfirmedSolution { solution_id, solution });
+        node1.send(node0.local_ip(), message);
+
+        // Ensure the peer was disconnected.
+        let (node0_, node1_) = (node0.clone(), node1.clone());
+        deadline!(Duration::from_secs(5), move || node0_.number_of_connected_peers() == 0
+            && node1_.number_of_connected_peers() == 0);
+    }
+
+    // Ensure the peer is restricted.
+    assert!(node0.is_restricted(&node1.local_ip()));
+
+    // Ensure the peer cannot reconnect.
+    node1.connect(node0.local_ip());
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(node0.number_of_connected_peers(), 0);
+}
+
+#[tokio::test]
+async fn test_forged_length_prefix_is_penalized() {
+    let node0 = listening_client().await;
+    let node1 = listening_client().await;
+
+    // Connect node1 to node0.
+    node1.connect(node0.local_ip());
+    let (node0_, node1_) = (node0.clone(), node1.clone());
+    deadline!(Duration::from_secs(5), move || node0_.number_of_connected_peers() == 1
+        && node1_.number_of_connected_peers() == 1);
+
+    // Send a transaction whose length prefix exceeds the maximum size of a transaction.
+    let transaction_id = <CurrentNetwork as Network>::TransactionID::from(Field::from_u64(1));
+    let transaction = Data::Buffer(Bytes::from(vec![0u8; 1024 * 1024 + 1]));
+    let message = Message::UnconfirmedTransaction(UnconfirmedTransaction { transaction_id, transaction });
+    node1.send(node0.local_ip(), message);
+
+    // Ensure the peer was disconnected, and penalized for the message that failed to deserialize.
+    let node0_ = node0.clone();
+    deadline!(Duration::from_secs(5), move || node0_.number_of_connected_peers() == 0);
+    let score = node0.misbehavior_score(&node1.local_ip());
+    assert!(score > 0.0 && score <= Offense::FailedDeserialization.weight(), "{score}");
+    assert!(!node0.is_restricted(&node1.local_ip()));
+}
+
+#[tokio::test]
+async fn test_honest_peer_under_load_is_not_restricted() {
+    let node0 = listening_client().await;
+    let node1 = listening_client().await;
+
+    // Connect node1 to node0.
+    node1.connect(node0.local_ip());
+    let (node0_, node1_) = (node0.clone(), node1.clone());
+    deadline!(Duration::from_secs(5), move || node0_.number_of_connected_peers() == 1
+        && node1_.number_of_connected_peers() == 1);
+
+    // Send many valid messages, within the message limit.
+    for _ in 0..400 {
+        node1.send(node0.local_ip(), Message::Pong(Pong { is_fork: None, timestamps: None }));
+    }
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    // Ensure the peer remains connected, without a misbehavior score.
+    assert_eq!(node0.number_of_connected_peers(), 1);
+    assert_eq!(node0.misbehavior_score(&node1.local_ip()), 0.0);
+    assert!(!node0.is_restricted(&node1.local_ip()));
+}
+
+#[tokio::test]
+async fn test_peer_advertising_implausible_heights_is_restricted() {
+    let node0 = listening_client().await;
+    let node1 = listening_client().await;
+
+    // Connect node1 to node0.
+    node1.connect(node0.local_ip());
+    let (node0_, node1_) = (node0.clone(), node1.clone());
+    deadline!(Duration::from_secs(5), move || node0_.number_of_connected_peers() == 1
+        && node1_.number_of_connected_peers() == 1);
+
+    // Determine the number of offenses that reach the misbehavior threshold.
+    let offense = Offense::ImplausibleBlockHeight;
+    let num_offenses = (MISBEHAVIOR_THRESHOLD / offense.weight()).ceil() as u64;
+
+    // Record the offenses below the threshold, as the node would for each implausible `Ping`.
+    for _ in 0..num_offenses - 1 {
+        assert!(!node0.record_misbehavior(node1.local_ip(), offense));
+    }
+    // Ensure the peer remains connected, as a single implausible height may be a mistake.
+    assert_eq!(node0.number_of_connected_peers(), 1);
+    assert!(!node0.is_restricted(&node1.local_ip()));
+
+    // Record the offense that reaches the threshold.
+    assert!(node0.record_misbehavior(node1.local_ip(), offense));
+
+    // Ensure the peer was disconnected and restricted.
+    let node0_ = node0.clone();
+    deadline!(Duration::from_secs(5), move || node0_.number_of_connected_peers() == 0);
+    assert!(node0.is_restricted(&node1.local_ip()));
+}
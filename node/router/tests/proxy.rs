@@ -0,0 +1,205 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod common;
+use common::*;
+
+use snarkos_node_router::{
+    messages::{AdvertisedPeer, NodeType},
+    PeerSource,
+    ProxyConfig,
+    Router,
+    RouterConfig,
+};
+use snarkos_node_tcp::{protocols::Handshake, P2P};
+use snarkvm::prelude::MainnetV0 as CurrentNetwork;
+
+use core::time::Duration;
+use deadline::deadline;
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+use tokio::{
+    io::{copy_bidirectional, AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    task::JoinHandle,
+};
+
+/// A SOCKS5 proxy that relays the connections to their targets, without authentication.
+struct TestProxy {
+    /// The address of the proxy.
+    addr: SocketAddr,
+    /// The number of connections relayed to their target.
+    num_relayed: Arc<AtomicUsize>,
+    /// The task accepting the connections.
+    handle: JoinHandle<()>,
+}
+
+impl TestProxy {
+    /// Starts a proxy on a random port.
+    async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let num_relayed = Arc::new(AtomicUsize::new(0));
+        let num_relayed_ = num_relayed.clone();
+        let handle = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let num_relayed = num_relayed_.clone();
+                tokio::spawn(async move {
+                    let _ = Self::relay(stream, num_relayed).await;
+                });
+            }
+        });
+        Self { addr, num_relayed, handle }
+    }
+
+    /// Negotiates a session with the given client, and relays it to the requested target.
+    async fn relay(mut client: TcpStream, num_relayed: Arc<AtomicUsize>) -> std::io::Result<()> {
+        // Accept the session without authentication.
+        let mut greeting = [0u8; 2];
+        client.read_exact(&mut greeting).await?;
+        let mut methods = vec![0u8; greeting[1] as usize];
+        client.read_exact(&mut methods).await?;
+        client.write_all(&[0x05, 0x00]).await?;
+        // Read the target of the `CONNECT` request.
+        let mut header = [0u8; 4];
+        client.read_exact(&mut header).await?;
+        let ip = match header[3] {
+            0x01 => {
+                let mut octets = [0u8; 4];
+                client.read_exact(&mut octets).await?;
+                IpAddr::from(octets)
+            }
+            _ => {
+                let mut octets = [0u8; 16];
+                client.read_exact(&mut octets).await?;
+                IpAddr::from(octets)
+            }
+        };
+        let mut port = [0u8; 2];
+        client.read_exact(&mut port).await?;
+        let target = SocketAddr::new(ip, u16::from_be_bytes(port));
+        // Connect to the target, and report the outcome.
+        let mut server = match TcpStream::connect(target).await {
+            Ok(server) => server,
+            Err(error) => {
+                // Reply with `connection refused`.
+                client.write_all(&[0x05, 0x05, 0x00, 0x01, 0, 0, 0, 0, 0, 0]).await?;
+                return Err(error);
+            }
+        };
+        client.write_all(&[0x05, 0x00, 0x00, 0x01, 0, 0, 0, 0, 0, 0]).await?;
+        num_relayed.fetch_add(1, Ordering::SeqCst);
+        copy_bidirectional(&mut client, &mut server).await.map(|_| ())
+    }
+
+    /// Returns the number of connections relayed to their target.
+    fn num_relayed(&self) -> usize {
+        self.num_relayed.load(Ordering::SeqCst)
+    }
+
+    /// Stops accepting connections.
+    async fn stop(self) {
+        self.handle.abort();
+        let _ = self.handle.await;
+    }
+}
+
+/// Returns a client that connects to its peers through the given proxy.
+async fn proxied_client(proxy: ProxyConfig) -> TestRouter<CurrentNetwork> {
+    let node_ip = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 0);
+    let config = RouterConfig::new(node_ip, NodeType::Client, 10).with_dev(true).with_proxy(Some(proxy));
+    let router: TestRouter<CurrentNetwork> =
+        Router::new(sample_account(), config).await.expect("couldn't create client router").into();
+    router.enable_handshake().await;
+    router.tcp().enable_listener().await.unwrap();
+    router
+}
+
+/// Returns a client that is listening for connections.
+async fn listening_client() -> TestRouter<CurrentNetwork> {
+    let node = client(0, 10).await;
+    node.enable_handshake().await;
+    node.tcp().enable_listener().await.unwrap();
+    node
+}
+
+/// Returns the number of failed dials recorded against the given candidate peer.
+fn num_dial_failures(node: &TestRouter<CurrentNetwork>, peer_ip: SocketAddr) -> u32 {
+    node.candidate_peers_with_info().get(&peer_ip).map(|info| info.backoff.failures).unwrap_or_default()
+}
+
+#[tokio::test]
+async fn test_connect_through_proxy() {
+    let proxy = TestProxy::start().await;
+    let external_addr: SocketAddr = "203.0.113.7:4130".parse().unwrap();
+    let node0 = proxied_client(ProxyConfig { proxy: proxy.addr, external_addr: Some(external_addr) }).await;
+    let node1 = listening_client().await;
+
+    // Connect node0 to node1, through the proxy.
+    let connection = node0.connect(node1.local_ip()).unwrap();
+    assert!(connection.await.unwrap());
+    let node0_ = node0.clone();
+    let node1_ = node1.clone();
+    deadline!(Duration::from_secs(5), move || node0_.number_of_connected_peers() == 1
+        && node1_.number_of_connected_peers() == 1);
+    assert_eq!(proxy.num_relayed(), 1);
+
+    // Ensure node1 observed the connection from the proxy, and learned the external address of node0.
+    let peer = node1.get_connected_peers().pop().unwrap();
+    assert_eq!(peer.ip(), SocketAddr::new(proxy.addr.ip(), external_addr.port()));
+    assert_eq!(peer.advertised_ip(), external_addr);
+    // Ensure node0 considers its external address as its own.
+    assert!(node0.is_local_ip(&external_addr));
+    assert_eq!(node0.advertised_ip(), external_addr);
+    proxy.stop().await;
+}
+
+#[tokio::test]
+async fn test_connect_fails_when_proxy_is_down() {
+    let proxy = TestProxy::start().await;
+    let node0 = proxied_client(ProxyConfig { proxy: proxy.addr, external_addr: None }).await;
+    let node1 = listening_client().await;
+    node0.insert_candidate_peers(&[AdvertisedPeer::from(node1.local_ip())], PeerSource::Gossip);
+
+    // Stop the proxy, and ensure the connection fails cleanly, without backing off from the peer.
+    proxy.stop().await;
+    let connection = node0.connect(node1.local_ip()).unwrap();
+    assert!(!connection.await.unwrap());
+    assert_eq!(node0.number_of_connected_peers(), 0);
+    assert_eq!(node0.tcp().num_connecting(), 0);
+    assert_eq!(node1.number_of_connected_peers(), 0);
+    assert_eq!(num_dial_failures(&node0, node1.local_ip()), 0);
+    assert!(node0.candidate_peers().contains(&node1.local_ip()));
+}
+
+#[tokio::test]
+async fn test_connect_fails_when_peer_is_unreachable_through_proxy() {
+    let proxy = TestProxy::start().await;
+    let node0 = proxied_client(ProxyConfig { proxy: proxy.addr, external_addr: None }).await;
+    // Reserve an address, and release it, so that nothing listens on it.
+    let peer_ip = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+    node0.insert_candidate_peers(&[AdvertisedPeer::from(peer_ip)], PeerSource::Gossip);
+
+    // Ensure the connection fails, and that the failure is held against the peer, as the proxy is reachable.
+    let connection = node0.connect(peer_ip).unwrap();
+    assert!(!connection.await.unwrap());
+    assert_eq!(proxy.num_relayed(), 0);
+    assert_eq!(num_dial_failures(&node0, peer_ip), 1);
+    proxy.stop().await;
+}
@@ -0,0 +1,67 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod common;
+use common::*;
+
+use snarkos_node_router::{
+    messages::{Message, RejectReason, RejectedItem, UnconfirmedTransaction},
+    Outbound,
+};
+use snarkos_node_tcp::{protocols::Handshake, P2P};
+use snarkvm::{
+    ledger::ledger_test_helpers::sample_fee_public_transaction,
+    prelude::{MainnetV0 as CurrentNetwork, TestRng},
+};
+
+use core::time::Duration;
+use deadline::deadline;
+
+/// Returns a client that is listening for connections.
+async fn listening_client() -> TestRouter<CurrentNetwork> {
+    let node = client(0, 2).await;
+    node.enable_handshake().await;
+    node.tcp().enable_listener().await.unwrap();
+    node
+}
+
+#[tokio::test]
+async fn test_invalid_transaction_is_rejected_and_not_resent() {
+    let node0 = listening_client().await;
+    let node1 = listening_client().await;
+
+    // Connect node1 to node0.
+    node1.connect(node0.local_ip());
+    let (node0_, node1_) = (node0.clone(), node1.clone());
+    deadline!(Duration::from_secs(5), move || node0_.number_of_connected_peers() == 1
+        && node1_.number_of_connected_peers() == 1);
+
+    // Broadcast a fee transaction, which node0 considers invalid.
+    let transaction = sample_fee_public_transaction(&mut TestRng::default());
+    let item = RejectedItem::Transaction(transaction.id());
+    node1.propagate(Message::UnconfirmedTransaction(UnconfirmedTransaction::from(transaction.clone())), &[]);
+
+    // Ensure node1 receives a rejection with the correct reason.
+    let (node0_ip, node1_) = (node0.local_ip(), node1.clone());
+    deadline!(Duration::from_secs(5), move || node1_.rejection_reason(&node0_ip, item).is_some());
+    assert_eq!(node1.rejection_reason(&node0.local_ip(), item), Some(RejectReason::Invalid));
+
+    // Ensure the rejection did not cost the connection.
+    assert_eq!(node0.number_of_connected_peers(), 1);
+    assert_eq!(node1.number_of_connected_peers(), 1);
+
+    // Ensure node1 does not resend the rejected transaction to node0.
+    let message = Message::UnconfirmedTransaction(UnconfirmedTransaction::from(transaction));
+    assert!(node1.send(node0.local_ip(), message).is_none());
+}
@@ -0,0 +1,93 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod common;
+use common::*;
+
+use snarkos_node_router::messages::{Feature, VersionRange, MESSAGE_VERSION};
+use snarkos_node_tcp::{protocols::Handshake, P2P};
+use snarkvm::prelude::MainnetV0 as CurrentNetwork;
+
+use core::time::Duration;
+use deadline::deadline;
+
+/// Returns a client that is listening for connections, supporting the given range of versions.
+async fn listening_client(versions: VersionRange) -> TestRouter<CurrentNetwork> {
+    let node = client(0, 2).await;
+    node.set_versions(versions);
+    node.enable_handshake().await;
+    node.tcp().enable_listener().await.unwrap();
+    node
+}
+
+#[tokio::test]
+async fn test_negotiate_latest_version() {
+    let node0 = listening_client(VersionRange::supported()).await;
+    let node1 = listening_client(VersionRange::supported()).await;
+
+    // Connect node0 to node1.
+    node0.connect(node1.local_ip());
+    let (node0_, node1_) = (node0.clone(), node1.clone());
+    deadline!(Duration::from_secs(5), move || node0_.number_of_connected_peers() == 1
+        && node1_.number_of_connected_peers() == 1);
+
+    // Ensure both sides negotiated the latest version, which is not deprecated.
+    for (node, peer_ip) in [(&node0, node1.local_ip()), (&node1, node0.local_ip())] {
+        let peer = node.get_connected_peer(&peer_ip).unwrap();
+        assert_eq!(peer.version(), MESSAGE_VERSION);
+        assert!(!peer.is_deprecated());
+        assert!(Feature::ALL.iter().all(|feature| peer.supports_feature(*feature)));
+    }
+}
+
+#[tokio::test]
+async fn test_negotiate_deprecated_version() {
+    // Pretend node0 is one version ahead, while still supporting the current version.
+    let node0 = listening_client(VersionRange::new(MESSAGE_VERSION, MESSAGE_VERSION + 1)).await;
+    let node1 = listening_client(VersionRange::supported()).await;
+
+    // Connect node1 to node0.
+    node1.connect(node0.local_ip());
+    let (node0_, node1_) = (node0.clone(), node1.clone());
+    deadline!(Duration::from_secs(5), move || node0_.number_of_connected_peers() == 1
+        && node1_.number_of_connected_peers() == 1);
+
+    // Ensure both sides negotiated the highest common version.
+    let peer_of_node0 = node0.get_connected_peer(&node1.local_ip()).unwrap();
+    let peer_of_node1 = node1.get_connected_peer(&node0.local_ip()).unwrap();
+    assert_eq!(peer_of_node0.version(), MESSAGE_VERSION);
+    assert_eq!(peer_of_node1.version(), MESSAGE_VERSION);
+
+    // Ensure only node0 considers the connection to be deprecated.
+    assert!(peer_of_node0.is_deprecated());
+    assert!(!peer_of_node1.is_deprecated());
+    assert!(node0.connected_metrics()[0].4);
+    assert!(!node1.connected_metrics()[0].4);
+}
+
+#[tokio::test]
+async fn test_refuse_disjoint_versions() {
+    // Pretend node0 dropped support for the current version.
+    let node0 = listening_client(VersionRange::new(MESSAGE_VERSION + 1, MESSAGE_VERSION + 1)).await;
+    let node1 = listening_client(VersionRange::supported()).await;
+
+    // Attempt to connect node1 to node0.
+    node1.connect(node0.local_ip());
+    // Sleep briefly.
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    // Ensure the connection was refused on both sides.
+    assert_eq!(node0.number_of_connected_peers(), 0);
+    assert_eq!(node1.number_of_connected_peers(), 0);
+}
@@ -0,0 +1,270 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod router;
+
+use crate::{traits::NodeInterface, RecordScanMode};
+use snarkos_account::Account;
+use snarkos_node_bft::ledger_service::{CoreLedgerService, ReplayFilter};
+use snarkos_node_rest::{Rest, RestConfig};
+use snarkos_node_router::{
+    messages::{Message, NodeType, UnconfirmedSolution},
+    Heartbeat,
+    Inbound,
+    Outbound,
+    ProxyConfig,
+    Router,
+    RouterConfig,
+    Routing,
+};
+use snarkos_node_sync::{BlockSync, BlockSyncMode};
+use snarkos_node_tcp::{
+    protocols::{Disconnect, Handshake, OnConnect, Reading, Writing},
+    P2P,
+};
+use snarkvm::{
+    console::network::Network,
+    ledger::{
+        block::{Block, Header},
+        puzzle::Solution,
+        store::ConsensusStorage,
+        Ledger,
+    },
+};
+
+use aleo_std::StorageMode;
+use anyhow::Result;
+use core::future::Future;
+use parking_lot::Mutex;
+use std::{
+    net::SocketAddr,
+    sync::{atomic::AtomicBool, Arc},
+};
+use tokio::task::JoinHandle;
+
+/// An archive node is a read-only full node, capable of serving the ledger to the network.
+///
+/// An archive syncs blocks from its peers, and serves them over REST and to syncing peers.
+/// It does not participate in consensus, and does not maintain a memory pool.
+#[derive(Clone)]
+pub struct Archive<N: Network, C: ConsensusStorage<N>> {
+    /// The ledger of the node.
+    ledger: Ledger<N, C>,
+    /// The router of the node.
+    router: Router<N>,
+    /// The REST server of the node.
+    rest: Option<Rest<N, C, Self>>,
+    /// The sync module.
+    sync: Arc<BlockSync<N>>,
+    /// The genesis block.
+    genesis: Block<N>,
+    /// The replay filter of the recently confirmed transactions and solutions.
+    replay_filter: Arc<ReplayFilter<N, C>>,
+    /// The spawned handles.
+    handles: Arc<Mutex<Vec<JoinHandle<()>>>>,
+    /// The shutdown signal.
+    shutdown: Arc<AtomicBool>,
+}
+
+impl<N: Network, C: ConsensusStorage<N>> Archive<N, C> {
+    /// Initializes a new archive node.
+    pub async fn new(
+        node_ip: SocketAddr,
+        rest: RestConfig,
+        enable_record_index: bool,
+        record_scan: RecordScanMode,
+        state_changes_retention: Option<u32>,
+        account: Account<N>,
+        trusted_peers: &[SocketAddr],
+        proxy: Option<ProxyConfig>,
+        genesis: Block<N>,
+        cdn: Option<Vec<String>>,
+        storage_mode: StorageMode,
+    ) -> Result<Self> {
+        // Prepare the shutdown flag.
+        let shutdown: Arc<AtomicBool> = Default::default();
+
+        // Initialize the signal handler.
+        let signal_node = Self::handle_signals(shutdown.clone());
+
+        // Initialize the ledger.
+        let ledger = Ledger::<N, C>::load(genesis.clone(), storage_mode.clone())?;
+
+        // Initialize the CDN.
+        if let Some(base_urls) = cdn {
+            // Sync the ledger with the CDN.
+            if let Err((_, error)) =
+                snarkos_node_cdn::sync_ledger_with_cdn(&base_urls, ledger.clone(), shutdown.clone()).await
+            {
+                crate::log_clean_error(&storage_mode);
+                return Err(error);
+            }
+        }
+
+        // Initialize the record index, if it is enabled.
+        let record_index = crate::open_record_index(enable_record_index, &ledger, &storage_mode)?;
+        // Initialize the reward index, if the record index is enabled.
+        let reward_index = crate::open_reward_index::<N>(enable_record_index, &storage_mode)?;
+        // Initialize the record scanner, if it is enabled.
+        let record_scanner = crate::open_record_scanner::<N>(record_scan, &storage_mode)?;
+        // Initialize the state change index, if it is enabled.
+        let state_changes = crate::open_state_changes::<N>(state_changes_retention, &storage_mode)?;
+        // Initialize the ledger service.
+        let mut ledger_service = CoreLedgerService::<N, C>::new(ledger.clone(), shutdown.clone());
+        if let Some(reward_index) = &reward_index {
+            ledger_service = ledger_service.with_reward_index(reward_index.clone());
+        }
+        if let Some(state_changes) = &state_changes {
+            ledger_service = ledger_service.with_state_changes(state_changes.clone());
+        }
+        if let Some(record_scanner) = &record_scanner {
+            ledger_service = ledger_service.with_record_scanner(record_scanner.clone());
+        }
+        // Initialize the disk monitor, and stop advancing blocks once the disk space is critically low.
+        let disk_monitor = crate::open_disk_monitor::<N>(&storage_mode);
+        ledger_service = ledger_service.with_disk_monitor(disk_monitor.clone());
+        // Initialize the replay filter of the recently confirmed transactions and solutions.
+        let replay_filter = Arc::new(ReplayFilter::new(ledger.clone()));
+        ledger_service = ledger_service.with_replay_filter(replay_filter.clone());
+        let ledger_service = Arc::new(ledger_service);
+        // Initialize the sync module.
+        let sync = BlockSync::new(BlockSyncMode::Router, ledger_service.clone());
+        // Determine if the archive should allow external peers.
+        let allow_external_peers = true;
+
+        // Initialize the node router.
+        let router_config = RouterConfig::new(node_ip, NodeType::Archive, Self::MAXIMUM_NUMBER_OF_PEERS as u16)
+            .with_trusted_peers(trusted_peers)
+            .with_rest(rest.is_enabled())
+            .with_external_peers(allow_external_peers)
+            .with_dev(matches!(storage_mode, StorageMode::Development(_)))
+            .with_proxy(proxy);
+        let router = Router::new(account, router_config).await?;
+        // Initialize the node.
+        let mut node = Self {
+            ledger: ledger.clone(),
+            router,
+            rest: None,
+            sync: Arc::new(sync),
+            genesis,
+            replay_filter: replay_filter.clone(),
+            handles: Default::default(),
+            shutdown,
+        };
+
+        // Initialize the REST server.
+        if rest.is_enabled() {
+            let block_sync = (*node.sync).clone();
+            node.rest = Some(
+                Rest::new(ledger.clone(), Arc::new(node.clone()), block_sync, replay_filter.clone())
+                    .with_record_index(record_index)
+                    .with_reward_index(reward_index.clone())
+                    .with_state_changes(state_changes.clone())
+                    .with_record_scanner(record_scanner)
+                    .with_disk_monitor(disk_monitor.clone())
+                    .with_dev_routes(matches!(storage_mode, StorageMode::Development(_)))
+                    .start(rest)
+                    .await?,
+            );
+        }
+        // Backfill the replay filter, from the latest block.
+        node.handles.lock().push(crate::start_replay_filter_backfill(replay_filter, node.shutdown.clone()));
+        // Backfill the reward index, if it is enabled.
+        if let Some(reward_index) = reward_index {
+            let backfill = crate::start_reward_index_backfill(reward_index, ledger.clone(), node.shutdown.clone());
+            node.handles.lock().push(backfill);
+        }
+        // Backfill the state change index, if it is enabled.
+        if let Some(state_changes) = state_changes {
+            let backfill = crate::start_state_changes_backfill(state_changes, ledger.clone(), node.shutdown.clone());
+            node.handles.lock().push(backfill);
+        }
+        // Initialize the routing.
+        node.initialize_routing().await;
+        // Initialize the sync module.
+        node.initialize_sync();
+        // Initialize the disk monitor loop.
+        node.handles.lock().push(crate::start_disk_monitor(node.clone(), disk_monitor));
+        // Initialize the notification message loop.
+        node.handles.lock().push(crate::start_notification_message_loop());
+        // Pass the node to the signal handler.
+        let _ = signal_node.set(node.clone());
+        // Return the node.
+        Ok(node)
+    }
+
+    /// Returns the ledger.
+    pub fn ledger(&self) -> &Ledger<N, C> {
+        &self.ledger
+    }
+
+    /// Returns the REST server.
+    pub fn rest(&self) -> &Option<Rest<N, C, Self>> {
+        &self.rest
+    }
+}
+
+impl<N: Network, C: ConsensusStorage<N>> Archive<N, C> {
+    /// Initializes the sync pool.
+    fn initialize_sync(&self) {
+        // Start the sync loop.
+        let node = self.clone();
+        self.handles.lock().push(tokio::spawn(async move {
+            loop {
+                // If the Ctrl-C handler registered the signal, stop the node.
+                if node.shutdown.load(std::sync::atomic::Ordering::Relaxed) {
+                    info!("Shutting down block production");
+                    break;
+                }
+
+                // Sleep briefly to avoid triggering spam detection.
+                tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+                // Perform the sync routine.
+                node.sync.try_block_sync(&node).await;
+            }
+        }));
+    }
+
+    /// Spawns a task with the given future; it should only be used for long-running tasks.
+    pub fn spawn<T: Future<Output = ()> + Send + 'static>(&self, future: T) {
+        self.handles.lock().push(tokio::spawn(future));
+    }
+}
+
+#[async_trait]
+impl<N: Network, C: ConsensusStorage<N>> NodeInterface<N> for Archive<N, C> {
+    /// Shuts down the node.
+    async fn shut_down(&self) {
+        info!("Shutting down...");
+
+        // Shut down the node.
+        trace!("Shutting down the node...");
+        self.shutdown.store(true, std::sync::atomic::Ordering::Relaxed);
+
+        // Abort the tasks.
+        trace!("Shutting down the validator...");
+        self.handles.lock().iter().for_each(|handle| handle.abort());
+
+        // Shut down the REST server.
+        if let Some(rest) = &self.rest {
+            trace!("Shutting down the REST server...");
+            rest.shut_down();
+        }
+
+        // Shut down the router.
+        self.router.shut_down().await;
+
+        info!("Node has shut down.");
+    }
+}
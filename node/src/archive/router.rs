@@ -0,0 +1,387 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::*;
+use snarkos_node_router::{
+    messages::{
+        BlockByHashRequest,
+        BlockByHashResponse,
+        BlockRequest,
+        BlockResponse,
+        BlocksByHash,
+        DataBlocks,
+        DisconnectReason,
+        MessageCodec,
+        Ping,
+        Pong,
+        PuzzleResponse,
+        RejectReason,
+        RejectedItem,
+        UnconfirmedTransaction,
+    },
+    now_unix_millis,
+    queue_policy,
+    traffic_class,
+    Offense,
+    Routing,
+    SyncStatus,
+};
+use snarkos_node_tcp::{Connection, ConnectionSide, QueuePolicy, Tcp, TrafficClass};
+use snarkvm::{
+    ledger::{
+        narwhal::{Data, TransmissionID},
+        puzzle::SolutionID,
+    },
+    prelude::{block::Transaction, Network},
+};
+
+use snarkos_node_sync::{communication_service::CommunicationService, ImplausibleHeight};
+use std::{borrow::Cow, io, net::SocketAddr};
+
+impl<N: Network, C: ConsensusStorage<N>> P2P for Archive<N, C> {
+    /// Returns a reference to the TCP instance.
+    fn tcp(&self) -> &Tcp {
+        self.router.tcp()
+    }
+}
+
+#[async_trait]
+impl<N: Network, C: ConsensusStorage<N>> Handshake for Archive<N, C> {
+    /// Performs the handshake protocol.
+    async fn perform_handshake(&self, mut connection: Connection) -> io::Result<Connection> {
+        // Perform the handshake.
+        let peer_addr = connection.addr();
+        let conn_side = connection.side();
+        let stream = self.borrow_stream(&mut connection);
+        let genesis_header = *self.genesis.header();
+        self.router.handshake(peer_addr, stream, conn_side, genesis_header).await?;
+
+        Ok(connection)
+    }
+}
+
+#[async_trait]
+impl<N: Network, C: ConsensusStorage<N>> OnConnect for Archive<N, C>
+where
+    Self: Outbound<N>,
+{
+    async fn on_connect(&self, peer_addr: SocketAddr) {
+        // Resolve the peer address to the listener address.
+        let Some(peer_ip) = self.router.resolve_to_listener(&peer_addr) else { return };
+        // Retrieve the block locators.
+        let block_locators = match self.sync.get_block_locators() {
+            Ok(block_locators) => Some(block_locators),
+            Err(e) => {
+                error!("Failed to get block locators: {e}");
+                return;
+            }
+        };
+        // Send the first `Ping` message to the peer.
+        self.send_ping(peer_ip, block_locators);
+    }
+}
+
+#[async_trait]
+impl<N: Network, C: ConsensusStorage<N>> Disconnect for Archive<N, C> {
+    /// Any extra operations to be performed during a disconnect.
+    async fn handle_disconnect(&self, peer_addr: SocketAddr) {
+        if let Some(peer_ip) = self.router.resolve_to_listener(&peer_addr) {
+            self.sync.remove_peer(&peer_ip);
+            self.router.remove_connected_peer(peer_ip);
+        }
+    }
+}
+
+#[async_trait]
+impl<N: Network, C: ConsensusStorage<N>> Writing for Archive<N, C> {
+    type Codec = MessageCodec<N>;
+    type Message = Message<N>;
+
+    /// Creates an [`Encoder`] used to write the outbound messages to the target stream.
+    /// The `side` parameter indicates the connection side **from the node's perspective**.
+    fn codec(&self, addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
+        self.router().message_codec(addr)
+    }
+
+    /// Returns the traffic class of the given outbound message, which determines its share of the outbound bandwidth.
+    fn traffic_class(&self, message: &Self::Message) -> TrafficClass {
+        traffic_class(message)
+    }
+
+    /// Returns the queue policy of the given outbound message, which determines whether it can be dropped.
+    fn queue_policy(&self, message: &Self::Message) -> QueuePolicy {
+        queue_policy(message)
+    }
+
+    /// Returns the type of the given outbound message, under which it is counted if it is dropped.
+    fn message_type(&self, message: &Self::Message) -> Cow<'static, str> {
+        message.name()
+    }
+}
+
+#[async_trait]
+impl<N: Network, C: ConsensusStorage<N>> Reading for Archive<N, C> {
+    type Codec = MessageCodec<N>;
+    type Message = Message<N>;
+
+    /// Creates a [`Decoder`] used to interpret messages from the network.
+    /// The `side` param indicates the connection side **from the node's perspective**.
+    fn codec(&self, peer_addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
+        self.router().message_codec(peer_addr)
+    }
+
+    /// Processes a message received from the network.
+    async fn process_message(&self, peer_addr: SocketAddr, message: Self::Message) -> io::Result<()> {
+        // Process the message. Disconnect if the peer violated the protocol.
+        if let Err(error) = self.inbound(peer_addr, message).await {
+            if let Some(peer_ip) = self.router().resolve_to_listener(&peer_addr) {
+                warn!("Disconnecting from '{peer_ip}' - {error}");
+                Outbound::send(self, peer_ip, Message::Disconnect(DisconnectReason::ProtocolViolation.into()));
+                // Disconnect from this peer.
+                self.router().disconnect_with_reason(peer_ip, error);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<N: Network, C: ConsensusStorage<N>> CommunicationService for Archive<N, C> {
+    /// The message type.
+    type Message = Message<N>;
+
+    /// Prepares a block request to be sent.
+    fn prepare_block_request(start_height: u32, end_height: u32) -> Self::Message {
+        debug_assert!(start_height < end_height, "Invalid block request format");
+        Message::BlockRequest(BlockRequest { start_height, end_height })
+    }
+
+    /// Sends the given message to specified peer.
+    ///
+    /// This function returns as soon as the message is queued to be sent,
+    /// without waiting for the actual delivery; instead, the caller is provided with a [`oneshot::Receiver`]
+    /// which can be used to determine when and whether the message has been delivered.
+    async fn send(
+        &self,
+        peer_ip: SocketAddr,
+        message: Self::Message,
+    ) -> Option<tokio::sync::oneshot::Receiver<io::Result<()>>> {
+        Outbound::send(self, peer_ip, message)
+    }
+
+    /// Returns `true` if the outbound queue of the specified peer is full.
+    fn is_congested(&self, peer_ip: SocketAddr) -> bool {
+        self.router().is_congested(&peer_ip)
+    }
+}
+
+#[async_trait]
+impl<N: Network, C: ConsensusStorage<N>> Routing<N> for Archive<N, C> {}
+
+impl<N: Network, C: ConsensusStorage<N>> Heartbeat<N> for Archive<N, C> {
+    /// Returns the sync status of the node.
+    fn sync_status(&self) -> SyncStatus {
+        match self.sync.is_block_synced() {
+            true => SyncStatus::Synced,
+            false => SyncStatus::Syncing,
+        }
+    }
+}
+
+impl<N: Network, C: ConsensusStorage<N>> Outbound<N> for Archive<N, C> {
+    /// Returns a reference to the router.
+    fn router(&self) -> &Router<N> {
+        &self.router
+    }
+}
+
+#[async_trait]
+impl<N: Network, C: ConsensusStorage<N>> Inbound<N> for Archive<N, C> {
+    /// Handles a `BlockByHashRequest` message.
+    fn block_by_hash_request(&self, peer_ip: SocketAddr, message: BlockByHashRequest<N>) -> bool {
+        // Retrieve the blocks for the requested hashes, where `None` marks an unknown hash.
+        let blocks = message
+            .block_hashes
+            .iter()
+            .map(|block_hash| self.ledger.get_height(block_hash).and_then(|height| self.ledger.get_block(height)).ok())
+            .collect();
+        // Send the `BlockByHashResponse` message to the peer.
+        let response = BlockByHashResponse { request: message, blocks: Data::Object(BlocksByHash(blocks)) };
+        Outbound::send(self, peer_ip, Message::BlockByHashResponse(response));
+        true
+    }
+
+    /// Handles a `BlockRequest` message.
+    fn block_request(&self, peer_ip: SocketAddr, message: BlockRequest) -> bool {
+        let BlockRequest { start_height, end_height } = &message;
+
+        // Retrieve the blocks within the requested range.
+        let blocks = match self.ledger.get_blocks(*start_height..*end_height) {
+            Ok(blocks) => Data::Object(DataBlocks(blocks)),
+            Err(error) => {
+                error!("Failed to retrieve blocks {start_height} to {end_height} from the ledger - {error}");
+                return false;
+            }
+        };
+        // Send the `BlockResponse` message to the peer.
+        Outbound::send(self, peer_ip, Message::BlockResponse(BlockResponse { request: message, blocks }));
+        true
+    }
+
+    /// Handles a `BlockResponse` message.
+    fn block_response(&self, peer_ip: SocketAddr, blocks: Vec<Block<N>>) -> bool {
+        // Tries to advance with blocks from the sync module.
+        match self.sync.advance_with_sync_blocks(peer_ip, blocks) {
+            Ok(()) => true,
+            // The block response was rejected as a whole, so penalize the peer.
+            Err(error) => {
+                warn!("{error}");
+                self.router.record_misbehavior(peer_ip, Offense::MismatchedResponse);
+                false
+            }
+        }
+    }
+
+    /// Resets the block request of a dropped block response, so that the blocks are requested again.
+    fn block_response_dropped(&self, _peer_ip: SocketAddr, request: BlockRequest) {
+        self.sync.reset_block_requests(request.start_height..request.end_height);
+    }
+
+    /// Returns `true` if the block at the given height is the next block.
+    fn is_block_announcement_wanted(&self, height: u32) -> bool {
+        height == self.ledger.latest_height() + 1
+    }
+
+    /// Advances the ledger with the announced block, and relays the announcement to the other peers.
+    fn announced_block(&self, peer_ip: SocketAddr, block: Block<N>) -> bool {
+        match self.sync.advance_with_announced_block(&block) {
+            Ok(true) => {
+                // Relay the block announcement.
+                self.announce_block(&block, &[peer_ip]);
+                true
+            }
+            Ok(false) => true,
+            Err(error) => {
+                warn!("Failed to advance to announced block {} - {error}", block.height());
+                self.send_reject(peer_ip, RejectedItem::Block(block.hash()), RejectReason::Invalid, &error.to_string());
+                false
+            }
+        }
+    }
+
+    /// Processes the block locators and sends back a `Pong` message.
+    fn ping(&self, peer_ip: SocketAddr, message: Ping<N>) -> bool {
+        // Check if the sync module is in router mode.
+        if self.sync.mode().is_router() {
+            // If block locators were provided, then update the peer in the sync pool.
+            if let Some(block_locators) = message.block_locators {
+                // Check the block locators are valid, and update the peer in the sync pool.
+                if let Err(error) = self.sync.update_peer_locators(peer_ip, block_locators) {
+                    // A peer advertising an implausible height is penalized and excluded from sync,
+                    // but is not disconnected, as it may have been misled by another peer.
+                    if error.is::<ImplausibleHeight>() {
+                        warn!("Peer '{peer_ip}' sent implausible block locators: {error}");
+                        self.router.record_misbehavior(peer_ip, Offense::ImplausibleBlockHeight);
+                    } else {
+                        warn!("Peer '{peer_ip}' sent invalid block locators: {error}");
+                        return false;
+                    }
+                }
+            }
+        }
+
+        // Echo the timestamp of the ping, if it was provided, so the peer can estimate its clock skew.
+        let timestamps = message.timestamp.map(|timestamp| (timestamp, now_unix_millis()));
+        // Report whether the peer is on a fork of the canonical chain.
+        let is_fork = Some(self.sync.is_peer_on_fork(&peer_ip));
+        // Send a `Pong` message to the peer.
+        Outbound::send(self, peer_ip, Message::Pong(Pong { is_fork, timestamps }));
+        true
+    }
+
+    /// Sleeps for a period and then sends a `Ping` message to the peer.
+    fn pong(&self, peer_ip: SocketAddr, _message: Pong) -> bool {
+        // Spawn an asynchronous task for the `Ping` request.
+        let self_ = self.clone();
+        tokio::spawn(async move {
+            // Sleep for the ping interval before sending a `Ping` request.
+            tokio::time::sleep(self_.ping_interval()).await;
+            // Check that the peer is still connected.
+            if self_.router().is_connected(&peer_ip) {
+                // Retrieve the block locators.
+                match self_.sync.get_block_locators() {
+                    // Send a `Ping` message to the peer.
+                    Ok(block_locators) => self_.send_ping(peer_ip, Some(block_locators)),
+                    Err(e) => error!("Failed to get block locators - {e}"),
+                }
+            }
+        });
+        true
+    }
+
+    /// Retrieves the latest epoch hash and latest block header, and returns the puzzle response to the peer.
+    fn puzzle_request(&self, peer_ip: SocketAddr) -> bool {
+        // Retrieve the latest epoch hash.
+        let epoch_hash = match self.ledger.latest_epoch_hash() {
+            Ok(epoch_hash) => epoch_hash,
+            Err(error) => {
+                error!("Failed to prepare a puzzle request for '{peer_ip}': {error}");
+                return false;
+            }
+        };
+        // Retrieve the latest block header.
+        let block_header = Data::Object(self.ledger.latest_header());
+        // Send the `PuzzleResponse` message to the peer.
+        Outbound::send(self, peer_ip, Message::PuzzleResponse(PuzzleResponse { epoch_hash, block_header }));
+        true
+    }
+
+    /// Saves the latest epoch hash and latest block header in the node.
+    fn puzzle_response(&self, peer_ip: SocketAddr, _epoch_hash: N::BlockHash, _header: Header<N>) -> bool {
+        debug!("Disconnecting '{peer_ip}' for the following reason - {:?}", DisconnectReason::ProtocolViolation);
+        false
+    }
+
+    /// Returns the height of the block that confirmed the given solution, if it is in the replay filter.
+    fn confirmed_solution_height(&self, solution_id: &SolutionID<N>) -> Option<u32> {
+        self.replay_filter.confirmed_height(&TransmissionID::from(*solution_id))
+    }
+
+    /// Returns the height of the block that confirmed the given transaction, if it is in the replay filter.
+    fn confirmed_transaction_height(&self, transaction_id: &N::TransactionID) -> Option<u32> {
+        self.replay_filter.confirmed_height(&TransmissionID::from(transaction_id))
+    }
+
+    /// Ignores the unconfirmed solution, as archives do not maintain a memory pool.
+    async fn unconfirmed_solution(
+        &self,
+        peer_ip: SocketAddr,
+        serialized: UnconfirmedSolution<N>,
+        _solution: Solution<N>,
+    ) -> bool {
+        trace!("Ignoring unconfirmed solution '{}' from '{peer_ip}' (archive)", serialized.solution_id);
+        true // Maintain the connection.
+    }
+
+    /// Ignores the unconfirmed transaction, as archives do not maintain a memory pool.
+    async fn unconfirmed_transaction(
+        &self,
+        peer_ip: SocketAddr,
+        serialized: UnconfirmedTransaction<N>,
+        _transaction: Transaction<N>,
+    ) -> bool {
+        trace!("Ignoring unconfirmed transaction '{}' from '{peer_ip}' (archive)", serialized.transaction_id);
+        true // Maintain the connection.
+    }
+}
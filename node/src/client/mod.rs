@@ -14,17 +14,22 @@
 
 mod router;
 
-use crate::traits::NodeInterface;
+use crate::{traits::NodeInterface, RecordScanMode};
 use snarkos_account::Account;
-use snarkos_node_bft::ledger_service::CoreLedgerService;
-use snarkos_node_rest::Rest;
+use snarkos_node_bft::ledger_service::{CoreLedgerService, ReplayFilter};
+use snarkos_node_consensus::ProgramDenylist;
+use snarkos_node_rest::{Rest, RestConfig};
 use snarkos_node_router::{
     messages::{Message, NodeType, UnconfirmedSolution},
     Heartbeat,
     Inbound,
     Outbound,
+    ProxyConfig,
     Router,
+    RouterConfig,
     Routing,
+    SolutionVerdict,
+    SolutionVerifier,
 };
 use snarkos_node_sync::{BlockSync, BlockSyncMode};
 use snarkos_node_tcp::{
@@ -32,7 +37,7 @@ use snarkos_node_tcp::{
     P2P,
 };
 use snarkvm::{
-    console::network::Network,
+    console::{network::Network, program::ProgramID},
     ledger::{
         block::{Block, Header},
         puzzle::{Puzzle, Solution},
@@ -66,6 +71,12 @@ pub struct Client<N: Network, C: ConsensusStorage<N>> {
     genesis: Block<N>,
     /// The puzzle.
     puzzle: Puzzle<N>,
+    /// The verifier for unconfirmed solutions, prior to propagation.
+    solution_verifier: Arc<SolutionVerifier<N>>,
+    /// The denylist of programs, whose transactions are not propagated.
+    program_denylist: Arc<ProgramDenylist<N>>,
+    /// The replay filter of the recently confirmed transactions and solutions.
+    replay_filter: Arc<ReplayFilter<N, C>>,
     /// The spawned handles.
     handles: Arc<Mutex<Vec<JoinHandle<()>>>>,
     /// The shutdown signal.
@@ -76,13 +87,17 @@ impl<N: Network, C: ConsensusStorage<N>> Client<N, C> {
     /// Initializes a new client node.
     pub async fn new(
         node_ip: SocketAddr,
-        rest_ip: Option<SocketAddr>,
-        rest_rps: u32,
+        rest: RestConfig,
+        enable_record_index: bool,
+        record_scan: RecordScanMode,
+        state_changes_retention: Option<u32>,
         account: Account<N>,
         trusted_peers: &[SocketAddr],
+        proxy: Option<ProxyConfig>,
         genesis: Block<N>,
-        cdn: Option<String>,
+        cdn: Option<Vec<String>>,
         storage_mode: StorageMode,
+        program_denylist: Vec<ProgramID<N>>,
     ) -> Result<Self> {
         // Prepare the shutdown flag.
         let shutdown: Arc<AtomicBool> = Default::default();
@@ -94,34 +109,55 @@ impl<N: Network, C: ConsensusStorage<N>> Client<N, C> {
         let ledger = Ledger::<N, C>::load(genesis.clone(), storage_mode.clone())?;
 
         // Initialize the CDN.
-        if let Some(base_url) = cdn {
+        if let Some(base_urls) = cdn {
             // Sync the ledger with the CDN.
             if let Err((_, error)) =
-                snarkos_node_cdn::sync_ledger_with_cdn(&base_url, ledger.clone(), shutdown.clone()).await
+                snarkos_node_cdn::sync_ledger_with_cdn(&base_urls, ledger.clone(), shutdown.clone()).await
             {
                 crate::log_clean_error(&storage_mode);
                 return Err(error);
             }
         }
 
+        // Initialize the record index, if it is enabled.
+        let record_index = crate::open_record_index(enable_record_index, &ledger, &storage_mode)?;
+        // Initialize the reward index, if the record index is enabled.
+        let reward_index = crate::open_reward_index::<N>(enable_record_index, &storage_mode)?;
+        // Initialize the record scanner, if it is enabled.
+        let record_scanner = crate::open_record_scanner::<N>(record_scan, &storage_mode)?;
+        // Initialize the state change index, if it is enabled.
+        let state_changes = crate::open_state_changes::<N>(state_changes_retention, &storage_mode)?;
         // Initialize the ledger service.
-        let ledger_service = Arc::new(CoreLedgerService::<N, C>::new(ledger.clone(), shutdown.clone()));
+        let mut ledger_service = CoreLedgerService::<N, C>::new(ledger.clone(), shutdown.clone());
+        if let Some(reward_index) = &reward_index {
+            ledger_service = ledger_service.with_reward_index(reward_index.clone());
+        }
+        if let Some(state_changes) = &state_changes {
+            ledger_service = ledger_service.with_state_changes(state_changes.clone());
+        }
+        if let Some(record_scanner) = &record_scanner {
+            ledger_service = ledger_service.with_record_scanner(record_scanner.clone());
+        }
+        // Initialize the disk monitor, and stop advancing blocks once the disk space is critically low.
+        let disk_monitor = crate::open_disk_monitor::<N>(&storage_mode);
+        ledger_service = ledger_service.with_disk_monitor(disk_monitor.clone());
+        // Initialize the replay filter of the recently confirmed transactions and solutions.
+        let replay_filter = Arc::new(ReplayFilter::new(ledger.clone()));
+        ledger_service = ledger_service.with_replay_filter(replay_filter.clone());
+        let ledger_service = Arc::new(ledger_service);
         // Initialize the sync module.
         let sync = BlockSync::new(BlockSyncMode::Router, ledger_service.clone());
         // Determine if the client should allow external peers.
         let allow_external_peers = true;
 
         // Initialize the node router.
-        let router = Router::new(
-            node_ip,
-            NodeType::Client,
-            account,
-            trusted_peers,
-            Self::MAXIMUM_NUMBER_OF_PEERS as u16,
-            allow_external_peers,
-            matches!(storage_mode, StorageMode::Development(_)),
-        )
-        .await?;
+        let router_config = RouterConfig::new(node_ip, NodeType::Client, Self::MAXIMUM_NUMBER_OF_PEERS as u16)
+            .with_trusted_peers(trusted_peers)
+            .with_rest(rest.is_enabled())
+            .with_external_peers(allow_external_peers)
+            .with_dev(matches!(storage_mode, StorageMode::Development(_)))
+            .with_proxy(proxy);
+        let router = Router::new(account, router_config).await?;
         // Initialize the node.
         let mut node = Self {
             ledger: ledger.clone(),
@@ -130,18 +166,47 @@ impl<N: Network, C: ConsensusStorage<N>> Client<N, C> {
             sync: Arc::new(sync),
             genesis,
             puzzle: ledger.puzzle().clone(),
+            solution_verifier: Default::default(),
+            program_denylist: Arc::new(ProgramDenylist::new(program_denylist)),
+            replay_filter: replay_filter.clone(),
             handles: Default::default(),
             shutdown,
         };
 
         // Initialize the REST server.
-        if let Some(rest_ip) = rest_ip {
-            node.rest = Some(Rest::start(rest_ip, rest_rps, None, ledger.clone(), Arc::new(node.clone())).await?);
+        if rest.is_enabled() {
+            let block_sync = (*node.sync).clone();
+            node.rest = Some(
+                Rest::new(ledger.clone(), Arc::new(node.clone()), block_sync, replay_filter.clone())
+                    .with_record_index(record_index)
+                    .with_reward_index(reward_index.clone())
+                    .with_state_changes(state_changes.clone())
+                    .with_record_scanner(record_scanner)
+                    .with_disk_monitor(disk_monitor.clone())
+                    .with_program_denylist(node.program_denylist.clone())
+                    .with_dev_routes(matches!(storage_mode, StorageMode::Development(_)))
+                    .start(rest)
+                    .await?,
+            );
+        }
+        // Backfill the replay filter, from the latest block.
+        node.handles.lock().push(crate::start_replay_filter_backfill(replay_filter, node.shutdown.clone()));
+        // Backfill the reward index, if it is enabled.
+        if let Some(reward_index) = reward_index {
+            let backfill = crate::start_reward_index_backfill(reward_index, ledger.clone(), node.shutdown.clone());
+            node.handles.lock().push(backfill);
+        }
+        // Backfill the state change index, if it is enabled.
+        if let Some(state_changes) = state_changes {
+            let backfill = crate::start_state_changes_backfill(state_changes, ledger.clone(), node.shutdown.clone());
+            node.handles.lock().push(backfill);
         }
         // Initialize the routing.
         node.initialize_routing().await;
         // Initialize the sync module.
         node.initialize_sync();
+        // Initialize the disk monitor loop.
+        node.handles.lock().push(crate::start_disk_monitor(node.clone(), disk_monitor));
         // Initialize the notification message loop.
         node.handles.lock().push(crate::start_notification_message_loop());
         // Pass the node to the signal handler.
@@ -202,6 +267,12 @@ impl<N: Network, C: ConsensusStorage<N>> NodeInterface<N> for Client<N, C> {
         trace!("Shutting down the validator...");
         self.handles.lock().iter().for_each(|handle| handle.abort());
 
+        // Shut down the REST server.
+        if let Some(rest) = &self.rest {
+            trace!("Shutting down the REST server...");
+            rest.shut_down();
+        }
+
         // Shut down the router.
         self.router.shut_down().await;
 
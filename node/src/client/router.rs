@@ -13,28 +13,42 @@
 // limitations under the License.
 
 use super::*;
+use snarkos_node_bft::ledger_service::{verification_pool, VerificationCategory};
 use snarkos_node_router::{
     messages::{
+        BlockByHashRequest,
+        BlockByHashResponse,
         BlockRequest,
         BlockResponse,
+        BlocksByHash,
         DataBlocks,
         DisconnectReason,
         MessageCodec,
         Ping,
         Pong,
         PuzzleResponse,
+        RejectReason,
+        RejectedItem,
         UnconfirmedTransaction,
     },
+    now_unix_millis,
+    queue_policy,
+    traffic_class,
+    Offense,
     Routing,
+    SyncStatus,
 };
-use snarkos_node_tcp::{Connection, ConnectionSide, Tcp};
+use snarkos_node_tcp::{Connection, ConnectionSide, QueuePolicy, Tcp, TrafficClass};
 use snarkvm::{
-    ledger::narwhal::Data,
+    ledger::{
+        narwhal::{Data, TransmissionID},
+        puzzle::SolutionID,
+    },
     prelude::{block::Transaction, Network},
 };
 
-use snarkos_node_sync::communication_service::CommunicationService;
-use std::{io, net::SocketAddr, time::Duration};
+use snarkos_node_sync::{communication_service::CommunicationService, ImplausibleHeight};
+use std::{borrow::Cow, io, net::SocketAddr};
 
 impl<N: Network, C: ConsensusStorage<N>> P2P for Client<N, C> {
     /// Returns a reference to the TCP instance.
@@ -97,8 +111,23 @@ impl<N: Network, C: ConsensusStorage<N>> Writing for Client<N, C> {
 
     /// Creates an [`Encoder`] used to write the outbound messages to the target stream.
     /// The `side` parameter indicates the connection side **from the node's perspective**.
-    fn codec(&self, _addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
-        Default::default()
+    fn codec(&self, addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
+        self.router().message_codec(addr)
+    }
+
+    /// Returns the traffic class of the given outbound message, which determines its share of the outbound bandwidth.
+    fn traffic_class(&self, message: &Self::Message) -> TrafficClass {
+        traffic_class(message)
+    }
+
+    /// Returns the queue policy of the given outbound message, which determines whether it can be dropped.
+    fn queue_policy(&self, message: &Self::Message) -> QueuePolicy {
+        queue_policy(message)
+    }
+
+    /// Returns the type of the given outbound message, under which it is counted if it is dropped.
+    fn message_type(&self, message: &Self::Message) -> Cow<'static, str> {
+        message.name()
     }
 }
 
@@ -109,8 +138,8 @@ impl<N: Network, C: ConsensusStorage<N>> Reading for Client<N, C> {
 
     /// Creates a [`Decoder`] used to interpret messages from the network.
     /// The `side` param indicates the connection side **from the node's perspective**.
-    fn codec(&self, _peer_addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
-        Default::default()
+    fn codec(&self, peer_addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
+        self.router().message_codec(peer_addr)
     }
 
     /// Processes a message received from the network.
@@ -121,7 +150,7 @@ impl<N: Network, C: ConsensusStorage<N>> Reading for Client<N, C> {
                 warn!("Disconnecting from '{peer_ip}' - {error}");
                 Outbound::send(self, peer_ip, Message::Disconnect(DisconnectReason::ProtocolViolation.into()));
                 // Disconnect from this peer.
-                self.router().disconnect(peer_ip);
+                self.router().disconnect_with_reason(peer_ip, error);
             }
         }
         Ok(())
@@ -151,12 +180,25 @@ impl<N: Network, C: ConsensusStorage<N>> CommunicationService for Client<N, C> {
     ) -> Option<tokio::sync::oneshot::Receiver<io::Result<()>>> {
         Outbound::send(self, peer_ip, message)
     }
+
+    /// Returns `true` if the outbound queue of the specified peer is full.
+    fn is_congested(&self, peer_ip: SocketAddr) -> bool {
+        self.router().is_congested(&peer_ip)
+    }
 }
 
 #[async_trait]
 impl<N: Network, C: ConsensusStorage<N>> Routing<N> for Client<N, C> {}
 
-impl<N: Network, C: ConsensusStorage<N>> Heartbeat<N> for Client<N, C> {}
+impl<N: Network, C: ConsensusStorage<N>> Heartbeat<N> for Client<N, C> {
+    /// Returns the sync status of the node.
+    fn sync_status(&self) -> SyncStatus {
+        match self.sync.is_block_synced() {
+            true => SyncStatus::Synced,
+            false => SyncStatus::Syncing,
+        }
+    }
+}
 
 impl<N: Network, C: ConsensusStorage<N>> Outbound<N> for Client<N, C> {
     /// Returns a reference to the router.
@@ -167,6 +209,20 @@ impl<N: Network, C: ConsensusStorage<N>> Outbound<N> for Client<N, C> {
 
 #[async_trait]
 impl<N: Network, C: ConsensusStorage<N>> Inbound<N> for Client<N, C> {
+    /// Handles a `BlockByHashRequest` message.
+    fn block_by_hash_request(&self, peer_ip: SocketAddr, message: BlockByHashRequest<N>) -> bool {
+        // Retrieve the blocks for the requested hashes, where `None` marks an unknown hash.
+        let blocks = message
+            .block_hashes
+            .iter()
+            .map(|block_hash| self.ledger.get_height(block_hash).and_then(|height| self.ledger.get_block(height)).ok())
+            .collect();
+        // Send the `BlockByHashResponse` message to the peer.
+        let response = BlockByHashResponse { request: message, blocks: Data::Object(BlocksByHash(blocks)) };
+        Outbound::send(self, peer_ip, Message::BlockByHashResponse(response));
+        true
+    }
+
     /// Handles a `BlockRequest` message.
     fn block_request(&self, peer_ip: SocketAddr, message: BlockRequest) -> bool {
         let BlockRequest { start_height, end_height } = &message;
@@ -189,8 +245,37 @@ impl<N: Network, C: ConsensusStorage<N>> Inbound<N> for Client<N, C> {
         // Tries to advance with blocks from the sync module.
         match self.sync.advance_with_sync_blocks(peer_ip, blocks) {
             Ok(()) => true,
+            // The block response was rejected as a whole, so penalize the peer.
             Err(error) => {
                 warn!("{error}");
+                self.router.record_misbehavior(peer_ip, Offense::MismatchedResponse);
+                false
+            }
+        }
+    }
+
+    /// Resets the block request of a dropped block response, so that the blocks are requested again.
+    fn block_response_dropped(&self, _peer_ip: SocketAddr, request: BlockRequest) {
+        self.sync.reset_block_requests(request.start_height..request.end_height);
+    }
+
+    /// Returns `true` if the block at the given height is the next block.
+    fn is_block_announcement_wanted(&self, height: u32) -> bool {
+        height == self.ledger.latest_height() + 1
+    }
+
+    /// Advances the ledger with the announced block, and relays the announcement to the other peers.
+    fn announced_block(&self, peer_ip: SocketAddr, block: Block<N>) -> bool {
+        match self.sync.advance_with_announced_block(&block) {
+            Ok(true) => {
+                // Relay the block announcement.
+                self.announce_block(&block, &[peer_ip]);
+                true
+            }
+            Ok(false) => true,
+            Err(error) => {
+                warn!("Failed to advance to announced block {} - {error}", block.height());
+                self.send_reject(peer_ip, RejectedItem::Block(block.hash()), RejectReason::Invalid, &error.to_string());
                 false
             }
         }
@@ -204,14 +289,25 @@ impl<N: Network, C: ConsensusStorage<N>> Inbound<N> for Client<N, C> {
             if let Some(block_locators) = message.block_locators {
                 // Check the block locators are valid, and update the peer in the sync pool.
                 if let Err(error) = self.sync.update_peer_locators(peer_ip, block_locators) {
-                    warn!("Peer '{peer_ip}' sent invalid block locators: {error}");
-                    return false;
+                    // A peer advertising an implausible height is penalized and excluded from sync,
+                    // but is not disconnected, as it may have been misled by another peer.
+                    if error.is::<ImplausibleHeight>() {
+                        warn!("Peer '{peer_ip}' sent implausible block locators: {error}");
+                        self.router.record_misbehavior(peer_ip, Offense::ImplausibleBlockHeight);
+                    } else {
+                        warn!("Peer '{peer_ip}' sent invalid block locators: {error}");
+                        return false;
+                    }
                 }
             }
         }
 
+        // Echo the timestamp of the ping, if it was provided, so the peer can estimate its clock skew.
+        let timestamps = message.timestamp.map(|timestamp| (timestamp, now_unix_millis()));
+        // Report whether the peer is on a fork of the canonical chain.
+        let is_fork = Some(self.sync.is_peer_on_fork(&peer_ip));
         // Send a `Pong` message to the peer.
-        Outbound::send(self, peer_ip, Message::Pong(Pong { is_fork: Some(false) }));
+        Outbound::send(self, peer_ip, Message::Pong(Pong { is_fork, timestamps }));
         true
     }
 
@@ -220,8 +316,8 @@ impl<N: Network, C: ConsensusStorage<N>> Inbound<N> for Client<N, C> {
         // Spawn an asynchronous task for the `Ping` request.
         let self_ = self.clone();
         tokio::spawn(async move {
-            // Sleep for the preset time before sending a `Ping` request.
-            tokio::time::sleep(Duration::from_secs(Self::PING_SLEEP_IN_SECS)).await;
+            // Sleep for the ping interval before sending a `Ping` request.
+            tokio::time::sleep(self_.ping_interval()).await;
             // Check that the peer is still connected.
             if self_.router().is_connected(&peer_ip) {
                 // Retrieve the block locators.
@@ -258,6 +354,16 @@ impl<N: Network, C: ConsensusStorage<N>> Inbound<N> for Client<N, C> {
         false
     }
 
+    /// Returns the height of the block that confirmed the given solution, if it is in the replay filter.
+    fn confirmed_solution_height(&self, solution_id: &SolutionID<N>) -> Option<u32> {
+        self.replay_filter.confirmed_height(&TransmissionID::from(*solution_id))
+    }
+
+    /// Returns the height of the block that confirmed the given transaction, if it is in the replay filter.
+    fn confirmed_transaction_height(&self, transaction_id: &N::TransactionID) -> Option<u32> {
+        self.replay_filter.confirmed_height(&TransmissionID::from(transaction_id))
+    }
+
     /// Propagates the unconfirmed solution to all connected validators.
     async fn unconfirmed_solution(
         &self,
@@ -270,21 +376,43 @@ impl<N: Network, C: ConsensusStorage<N>> Inbound<N> for Client<N, C> {
             // Retrieve the latest proof target.
             let proof_target = self.ledger.latest_block().header().proof_target();
             // Ensure that the solution is valid for the given epoch.
+            let solution_id = solution.id();
             let puzzle = self.puzzle.clone();
-            let is_valid =
-                tokio::task::spawn_blocking(move || puzzle.check_solution(&solution, epoch_hash, proof_target)).await;
+            let verdict = self
+                .solution_verifier
+                .verify(solution_id, epoch_hash, proof_target, move || {
+                    puzzle.check_solution(&solution, epoch_hash, proof_target)
+                })
+                .await;
 
-            match is_valid {
+            match verdict {
                 // If the solution is valid, propagate the `UnconfirmedSolution`.
-                Ok(Ok(())) => {
+                SolutionVerdict::Valid => {
                     let message = Message::UnconfirmedSolution(serialized);
                     // Propagate the "UnconfirmedSolution".
                     self.propagate(message, &[peer_ip]);
                 }
-                Ok(Err(_)) => {
-                    trace!("Invalid solution '{}' for the proof target.", solution.id())
+                // If the solution is invalid, disconnect from the peer.
+                // Note: A lagging node may reject a solution for a newer epoch, so the peer is only penalized once synced.
+                SolutionVerdict::Invalid => {
+                    debug!("Invalid solution '{solution_id}' for the proof target from '{peer_ip}'");
+                    if !self.sync.is_block_synced() {
+                        return true;
+                    }
+                    let item = RejectedItem::Solution(solution_id);
+                    self.send_reject(
+                        peer_ip,
+                        item,
+                        RejectReason::Invalid,
+                        "The solution does not meet the proof target",
+                    );
+                    return false;
+                }
+                SolutionVerdict::Shed => {
+                    trace!("Skipping solution '{solution_id}' (too many pending verifications)");
+                    let item = RejectedItem::Solution(solution_id);
+                    self.send_reject(peer_ip, item, RejectReason::TemporarilyFull, "Too many pending verifications");
                 }
-                Err(error) => warn!("Failed to verify the solution: {error}"),
             }
         }
         true
@@ -299,12 +427,27 @@ impl<N: Network, C: ConsensusStorage<N>> Inbound<N> for Client<N, C> {
     ) -> bool {
         // Check that the transaction is not a fee transaction.
         if transaction.is_fee() {
+            let item = RejectedItem::Transaction(serialized.transaction_id);
+            self.send_reject(peer_ip, item, RejectReason::Invalid, "Fee transactions are not accepted");
+            return true; // Maintain the connection.
+        }
+        // Check that the transaction does not reference a denylisted program.
+        if let Err(error) = self.program_denylist.ensure_allowed(&transaction) {
+            let item = RejectedItem::Transaction(serialized.transaction_id);
+            self.send_reject(peer_ip, item, RejectReason::Invalid, &error.to_string());
             return true; // Maintain the connection.
         }
-        // Check that the transaction is well-formed and unique.
-        if self.ledger.check_transaction_basic(&transaction, None, &mut rand::thread_rng()).is_ok() {
+        // Check that the transaction is well-formed and unique, on the verification pool.
+        let ledger = self.ledger.clone();
+        let verification = move || ledger.check_transaction_basic(&transaction, None, &mut rand::thread_rng());
+        match verification_pool().run(VerificationCategory::Transaction, verification).await {
             // Propagate the `UnconfirmedTransaction`.
-            self.propagate(Message::UnconfirmedTransaction(serialized), &[peer_ip]);
+            Ok(Ok(())) => self.propagate(Message::UnconfirmedTransaction(serialized), &[peer_ip]),
+            // Inform the peer that the transaction was dropped.
+            Ok(Err(error)) | Err(error) => {
+                let item = RejectedItem::Transaction(serialized.transaction_id);
+                self.send_reject(peer_ip, item, RejectReason::Invalid, &error.to_string());
+            }
         }
         true
     }
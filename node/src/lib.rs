@@ -30,6 +30,9 @@ pub use snarkos_node_sync as sync;
 pub use snarkos_node_tcp as tcp;
 pub use snarkvm;
 
+mod archive;
+pub use archive::*;
+
 mod client;
 pub use client::*;
 
@@ -45,7 +48,26 @@ pub use node::*;
 mod traits;
 pub use traits::*;
 
+use snarkos_node_bft::ledger_service::{
+    DiskConfig,
+    DiskMonitor,
+    DiskStatus,
+    LedgerMetadata,
+    RecordIndex,
+    RecordScanner,
+    ReplayFilter,
+    RewardIndex,
+    StateChangeIndex,
+    INDEXES_DIRECTORY,
+};
+use snarkvm::{
+    ledger::{block::Block, store::ConsensusStorage, Ledger},
+    prelude::Network,
+};
+
 use aleo_std::StorageMode;
+use anyhow::Result;
+use std::sync::{atomic::AtomicBool, Arc};
 
 /// A helper to log instructions to recover.
 pub fn log_clean_error(storage_mode: &StorageMode) {
@@ -60,6 +82,189 @@ pub fn log_clean_error(storage_mode: &StorageMode) {
     }
 }
 
+/// Ensures the ledger directory was created for the running network, genesis block, and development index,
+/// writes the metadata of the ledger if it is new, and migrates the ledger if it uses an older storage schema.
+pub fn ensure_ledger_metadata<N: Network>(genesis: &Block<N>, storage_mode: &StorageMode) -> Result<()> {
+    let dev = match storage_mode {
+        StorageMode::Development(id) => Some(*id),
+        _ => None,
+    };
+    let directory = aleo_std::aleo_ledger_dir(N::ID, storage_mode.clone());
+    LedgerMetadata::<N>::new(genesis.hash(), dev).ensure_compatible(&directory)
+}
+
+/// Returns the directory of the indexes maintained by snarkOS, creating it if it does not exist.
+/// Note: The indexes are kept in the ledger directory, so that `snarkos clean` removes them alongside the ledger.
+fn indexes_dir<N: Network>(storage_mode: &StorageMode) -> Result<std::path::PathBuf> {
+    let directory = aleo_std::aleo_ledger_dir(N::ID, storage_mode.clone()).join(INDEXES_DIRECTORY);
+    std::fs::create_dir_all(&directory)?;
+    Ok(directory)
+}
+
+/// Opens the record index over the given ledger, if it is enabled.
+/// Note: The record index is served from the ledger storage, so the log of earlier versions is removed.
+pub fn open_record_index<N: Network, C: ConsensusStorage<N>>(
+    is_enabled: bool,
+    ledger: &Ledger<N, C>,
+    storage_mode: &StorageMode,
+) -> Result<Option<Arc<RecordIndex<N, C>>>> {
+    if !is_enabled {
+        return Ok(None);
+    }
+    let legacy_log = indexes_dir::<N>(storage_mode)?.join("record_index");
+    if legacy_log.exists() {
+        std::fs::remove_file(&legacy_log)?;
+        info!("Removed the log of the record index, as it is now served from the ledger");
+    }
+    Ok(Some(Arc::new(RecordIndex::new(ledger.clone()))))
+}
+
+/// Opens the reward index in the ledger directory, if it is enabled.
+/// Note: The reward index is enabled alongside the record index.
+pub fn open_reward_index<N: Network>(
+    is_enabled: bool,
+    storage_mode: &StorageMode,
+) -> Result<Option<Arc<RewardIndex<N>>>> {
+    if !is_enabled {
+        return Ok(None);
+    }
+    let directory = indexes_dir::<N>(storage_mode)?;
+    let reward_index = RewardIndex::open(Some(directory.join("reward_index")))?;
+    info!("Opened the reward index, resuming from block {}", reward_index.next_height());
+    Ok(Some(Arc::new(reward_index)))
+}
+
+/// Opens the state change index in the ledger directory, if it is enabled with the given retention window.
+pub fn open_state_changes<N: Network>(
+    retention: Option<u32>,
+    storage_mode: &StorageMode,
+) -> Result<Option<Arc<StateChangeIndex<N>>>> {
+    let Some(retention) = retention else {
+        return Ok(None);
+    };
+    let directory = indexes_dir::<N>(storage_mode)?;
+    let state_changes = StateChangeIndex::open(Some(directory.join("state_changes")), retention)?;
+    info!("Opened the state changes, retaining the last {retention} blocks");
+    Ok(Some(Arc::new(state_changes)))
+}
+
+/// The mode of the record scanner, which decrypts the records of the registered view keys.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum RecordScanMode {
+    /// The record scanner is disabled.
+    #[default]
+    Disabled,
+    /// The view keys and their records are held in memory only.
+    Memory,
+    /// The view keys and their records are persisted in the ledger directory.
+    Persistent,
+}
+
+/// Opens the record scanner, if it is enabled.
+pub fn open_record_scanner<N: Network>(
+    mode: RecordScanMode,
+    storage_mode: &StorageMode,
+) -> Result<Option<Arc<RecordScanner<N>>>> {
+    let path = match mode {
+        RecordScanMode::Disabled => return Ok(None),
+        RecordScanMode::Memory => None,
+        RecordScanMode::Persistent => Some(indexes_dir::<N>(storage_mode)?.join("record_scanner")),
+    };
+    let record_scanner = RecordScanner::open(path)?;
+    info!("Opened the record scanner, with {} registered view keys", record_scanner.num_view_keys());
+    Ok(Some(Arc::new(record_scanner)))
+}
+
+/// Starts the backfill of the reward index, from the next block to index up to the latest block in the ledger.
+pub fn start_reward_index_backfill<N: Network, C: ConsensusStorage<N>>(
+    reward_index: Arc<RewardIndex<N>>,
+    ledger: Ledger<N, C>,
+    shutdown: Arc<AtomicBool>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::task::spawn_blocking(move || {
+        if let Err(error) = reward_index.backfill(&ledger, &shutdown) {
+            error!("Failed to backfill the reward index - {error}");
+        }
+    })
+}
+
+/// Starts the backfill of the state change index, over the retention window up to the latest block in the ledger.
+pub fn start_state_changes_backfill<N: Network, C: ConsensusStorage<N>>(
+    state_changes: Arc<StateChangeIndex<N>>,
+    ledger: Ledger<N, C>,
+    shutdown: Arc<AtomicBool>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::task::spawn_blocking(move || {
+        if let Err(error) = state_changes.backfill(&ledger, &shutdown) {
+            error!("Failed to backfill the state changes - {error}");
+        }
+    })
+}
+
+/// Starts the backfill of the replay filter, from the latest block down to the oldest block it holds.
+pub fn start_replay_filter_backfill<N: Network, C: ConsensusStorage<N>>(
+    replay_filter: Arc<ReplayFilter<N, C>>,
+    shutdown: Arc<AtomicBool>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::task::spawn_blocking(move || {
+        if let Err(error) = replay_filter.backfill(&shutdown) {
+            error!("Failed to backfill the replay filter - {error}");
+        }
+    })
+}
+
+/// Opens the disk monitor of the ledger directory, and measures the disk space.
+pub fn open_disk_monitor<N: Network>(storage_mode: &StorageMode) -> Arc<DiskMonitor> {
+    let directory = aleo_std::aleo_ledger_dir(N::ID, storage_mode.clone());
+    let disk_monitor = DiskMonitor::new(directory, DiskConfig::default());
+    let usage = disk_monitor.refresh();
+    info!(
+        "The ledger uses {} MiB of disk space, with {} MiB available",
+        usage.ledger_bytes / (1024 * 1024),
+        usage.available_bytes / (1024 * 1024)
+    );
+    Arc::new(disk_monitor)
+}
+
+/// Starts the disk monitor loop, which periodically measures the disk space of the ledger,
+/// and shuts down the node once the disk space is critically low.
+pub fn start_disk_monitor<N: Network, T: NodeInterface<N>>(
+    node: T,
+    disk_monitor: Arc<DiskMonitor>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(disk_monitor.config().check_interval).await;
+            // Measure the disk space on a blocking thread, as the size of the ledger is summed over its files.
+            let disk_monitor_ = disk_monitor.clone();
+            let Ok(usage) = tokio::task::spawn_blocking(move || disk_monitor_.refresh()).await else {
+                continue;
+            };
+            #[cfg(feature = "metrics")]
+            {
+                metrics::gauge(metrics::disk::AVAILABLE_BYTES, usage.available_bytes as f64);
+                metrics::gauge(metrics::disk::LEDGER_BYTES, usage.ledger_bytes as f64);
+            }
+            let available_mib = usage.available_bytes / (1024 * 1024);
+            match usage.status {
+                DiskStatus::Healthy => (),
+                DiskStatus::Low => {
+                    warn!("The node is low on disk space ({available_mib} MiB available) - refusing new transmissions")
+                }
+                DiskStatus::Critical => {
+                    error!("The node is critically low on disk space ({available_mib} MiB available) - shutting down");
+                    // Shut down the node in a separate task, as the shutdown aborts the tasks of the node.
+                    tokio::spawn(async move {
+                        node.shut_down().await;
+                        std::process::exit(1);
+                    });
+                    break;
+                }
+            }
+        }
+    })
+}
+
 /// Starts the notification message loop.
 pub fn start_notification_message_loop() -> tokio::task::JoinHandle<()> {
     // let mut interval = tokio::time::interval(std::time::Duration::from_secs(180));
@@ -12,21 +12,26 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{traits::NodeInterface, Client, Prover, Validator};
+use crate::{ensure_ledger_metadata, traits::NodeInterface, Archive, Client, Prover, RecordScanMode, Validator};
 use snarkos_account::Account;
-use snarkos_node_router::messages::NodeType;
+use snarkos_node_bft::helpers::{EmptyBatchMode, Signer};
+use snarkos_node_consensus::TransmissionStorageMode;
+use snarkos_node_rest::RestConfig;
+use snarkos_node_router::{messages::NodeType, AuditLog, Outbound, ProxyConfig, SeedDiscovery};
+use snarkos_node_tcp::{BandwidthConfig, BandwidthScheduler, P2P};
 use snarkvm::prelude::{
     block::Block,
     store::helpers::{memory::ConsensusMemory, rocksdb::ConsensusDB},
     Address,
     Network,
     PrivateKey,
+    ProgramID,
     ViewKey,
 };
 
 use aleo_std::StorageMode;
 use anyhow::Result;
-use std::{net::SocketAddr, sync::Arc};
+use std::{net::SocketAddr, path::PathBuf, sync::Arc};
 
 pub enum Node<N: Network> {
     /// A validator is a full node, capable of validating blocks.
@@ -35,6 +40,8 @@ pub enum Node<N: Network> {
     Prover(Arc<Prover<N, ConsensusMemory<N>>>),
     /// A client node is a full node, capable of querying with the network.
     Client(Arc<Client<N, ConsensusDB<N>>>),
+    /// An archive node is a read-only full node, capable of serving the ledger to the network.
+    Archive(Arc<Archive<N, ConsensusDB<N>>>),
 }
 
 impl<N: Network> Node<N> {
@@ -42,31 +49,49 @@ impl<N: Network> Node<N> {
     pub async fn new_validator(
         node_ip: SocketAddr,
         bft_ip: Option<SocketAddr>,
-        rest_ip: Option<SocketAddr>,
-        rest_rps: u32,
+        rest: RestConfig,
+        enable_record_index: bool,
+        record_scan: RecordScanMode,
+        state_changes_retention: Option<u32>,
         account: Account<N>,
+        signer: Option<Arc<dyn Signer<N>>>,
         trusted_peers: &[SocketAddr],
         trusted_validators: &[SocketAddr],
         genesis: Block<N>,
-        cdn: Option<String>,
+        cdn: Option<Vec<String>>,
         storage_mode: StorageMode,
+        transmission_storage: TransmissionStorageMode,
         allow_external_peers: bool,
         dev_txs: bool,
+        allow_unstaked: bool,
+        event_log: Option<PathBuf>,
+        program_denylist: Vec<ProgramID<N>>,
+        empty_batch_mode: EmptyBatchMode,
     ) -> Result<Self> {
+        // Ensure the ledger was created for this network, before opening it.
+        ensure_ledger_metadata(&genesis, &storage_mode)?;
         Ok(Self::Validator(Arc::new(
             Validator::new(
                 node_ip,
                 bft_ip,
-                rest_ip,
-                rest_rps,
+                rest,
+                enable_record_index,
+                record_scan,
+                state_changes_retention,
                 account,
+                signer,
                 trusted_peers,
                 trusted_validators,
                 genesis,
                 cdn,
                 storage_mode,
+                transmission_storage,
                 allow_external_peers,
                 dev_txs,
+                allow_unstaked,
+                event_log,
+                program_denylist,
+                empty_batch_mode,
             )
             .await?,
         )))
@@ -77,25 +102,94 @@ impl<N: Network> Node<N> {
         node_ip: SocketAddr,
         account: Account<N>,
         trusted_peers: &[SocketAddr],
+        proxy: Option<ProxyConfig>,
         genesis: Block<N>,
         storage_mode: StorageMode,
+        prover_threads: Option<usize>,
+        prover_duty_cycle: u8,
     ) -> Result<Self> {
-        Ok(Self::Prover(Arc::new(Prover::new(node_ip, account, trusted_peers, genesis, storage_mode).await?)))
+        Ok(Self::Prover(Arc::new(
+            Prover::new(
+                node_ip,
+                account,
+                trusted_peers,
+                proxy,
+                genesis,
+                storage_mode,
+                prover_threads,
+                prover_duty_cycle,
+            )
+            .await?,
+        )))
     }
 
     /// Initializes a new client node.
     pub async fn new_client(
         node_ip: SocketAddr,
-        rest_ip: Option<SocketAddr>,
-        rest_rps: u32,
+        rest: RestConfig,
+        enable_record_index: bool,
+        record_scan: RecordScanMode,
+        state_changes_retention: Option<u32>,
         account: Account<N>,
         trusted_peers: &[SocketAddr],
+        proxy: Option<ProxyConfig>,
         genesis: Block<N>,
-        cdn: Option<String>,
+        cdn: Option<Vec<String>>,
         storage_mode: StorageMode,
+        program_denylist: Vec<ProgramID<N>>,
     ) -> Result<Self> {
+        // Ensure the ledger was created for this network, before opening it.
+        ensure_ledger_metadata(&genesis, &storage_mode)?;
         Ok(Self::Client(Arc::new(
-            Client::new(node_ip, rest_ip, rest_rps, account, trusted_peers, genesis, cdn, storage_mode).await?,
+            Client::new(
+                node_ip,
+                rest,
+                enable_record_index,
+                record_scan,
+                state_changes_retention,
+                account,
+                trusted_peers,
+                proxy,
+                genesis,
+                cdn,
+                storage_mode,
+                program_denylist,
+            )
+            .await?,
+        )))
+    }
+
+    /// Initializes a new archive node.
+    pub async fn new_archive(
+        node_ip: SocketAddr,
+        rest: RestConfig,
+        enable_record_index: bool,
+        record_scan: RecordScanMode,
+        state_changes_retention: Option<u32>,
+        account: Account<N>,
+        trusted_peers: &[SocketAddr],
+        proxy: Option<ProxyConfig>,
+        genesis: Block<N>,
+        cdn: Option<Vec<String>>,
+        storage_mode: StorageMode,
+    ) -> Result<Self> {
+        // Ensure the ledger was created for this network, before opening it.
+        ensure_ledger_metadata(&genesis, &storage_mode)?;
+        Ok(Self::Archive(Arc::new(
+            Archive::new(
+                node_ip,
+                rest,
+                enable_record_index,
+                record_scan,
+                state_changes_retention,
+                account,
+                trusted_peers,
+                proxy,
+                genesis,
+                cdn,
+                storage_mode,
+            )
+            .await?,
         )))
     }
 
@@ -105,6 +199,7 @@ impl<N: Network> Node<N> {
             Self::Validator(validator) => validator.node_type(),
             Self::Prover(prover) => prover.node_type(),
             Self::Client(client) => client.node_type(),
+            Self::Archive(archive) => archive.node_type(),
         }
     }
 
@@ -114,6 +209,7 @@ impl<N: Network> Node<N> {
             Self::Validator(node) => node.private_key(),
             Self::Prover(node) => node.private_key(),
             Self::Client(node) => node.private_key(),
+            Self::Archive(node) => node.private_key(),
         }
     }
 
@@ -123,6 +219,7 @@ impl<N: Network> Node<N> {
             Self::Validator(node) => node.view_key(),
             Self::Prover(node) => node.view_key(),
             Self::Client(node) => node.view_key(),
+            Self::Archive(node) => node.view_key(),
         }
     }
 
@@ -132,6 +229,7 @@ impl<N: Network> Node<N> {
             Self::Validator(node) => node.address(),
             Self::Prover(node) => node.address(),
             Self::Client(node) => node.address(),
+            Self::Archive(node) => node.address(),
         }
     }
 
@@ -141,6 +239,93 @@ impl<N: Network> Node<N> {
             Self::Validator(node) => node.is_dev(),
             Self::Prover(node) => node.is_dev(),
             Self::Client(node) => node.is_dev(),
+            Self::Archive(node) => node.is_dev(),
+        }
+    }
+
+    /// Returns the height of the latest block in the ledger of the node, if the node maintains a ledger.
+    pub fn latest_height(&self) -> Option<u32> {
+        match self {
+            Self::Validator(node) => Some(node.ledger().latest_height()),
+            Self::Prover(_) => None,
+            Self::Client(node) => Some(node.ledger().latest_height()),
+            Self::Archive(node) => Some(node.ledger().latest_height()),
+        }
+    }
+
+    /// Shuts down the node.
+    pub async fn shut_down(&self) {
+        match self {
+            Self::Validator(node) => node.shut_down().await,
+            Self::Prover(node) => node.shut_down().await,
+            Self::Client(node) => node.shut_down().await,
+            Self::Archive(node) => node.shut_down().await,
+        }
+    }
+
+    /// Starts mapping the listening port of the node on the gateway of its network, over UPnP.
+    pub fn start_port_mapping(&self) {
+        match self {
+            Self::Validator(node) => node.router().start_port_mapping(),
+            Self::Prover(node) => node.router().start_port_mapping(),
+            Self::Client(node) => node.router().start_port_mapping(),
+            Self::Archive(node) => node.router().start_port_mapping(),
+        }
+    }
+
+    /// Starts the discovery of candidate peers from the given seeds, if any are configured.
+    pub fn start_seed_discovery(&self, discovery: SeedDiscovery<N>) {
+        match self {
+            Self::Validator(node) => node.router().start_seed_discovery(discovery),
+            Self::Prover(node) => node.router().start_seed_discovery(discovery),
+            Self::Client(node) => node.router().start_seed_discovery(discovery),
+            Self::Archive(node) => node.router().start_seed_discovery(discovery),
+        }
+    }
+
+    /// Limits the outbound bandwidth of the node with the given configuration, if it is not unlimited.
+    pub fn set_outbound_bandwidth(&self, config: BandwidthConfig) -> Result<()> {
+        // If the outbound bandwidth is unlimited, skip the scheduler.
+        if config.bytes_per_sec.is_none() {
+            return Ok(());
+        }
+        let scheduler = Arc::new(BandwidthScheduler::new(config));
+        match self {
+            Self::Validator(node) => node.set_bandwidth_scheduler(scheduler)?,
+            Self::Prover(node) => node.router().tcp().set_bandwidth_scheduler(scheduler)?,
+            Self::Client(node) => node.router().tcp().set_bandwidth_scheduler(scheduler)?,
+            Self::Archive(node) => node.router().tcp().set_bandwidth_scheduler(scheduler)?,
+        }
+        Ok(())
+    }
+
+    /// Records the peer connection events of the node in the given audit log.
+    pub fn set_peer_audit_log(&self, audit_log: Arc<AuditLog>) {
+        match self {
+            Self::Validator(node) => node.router().set_audit_log(audit_log),
+            Self::Prover(node) => node.router().set_audit_log(audit_log),
+            Self::Client(node) => node.router().set_audit_log(audit_log),
+            Self::Archive(node) => node.router().set_audit_log(audit_log),
+        }
+    }
+
+    /// Sets the maximum number of (untrusted) peers the node connects to in a single subnet bucket.
+    pub fn set_max_peers_per_subnet(&self, max_peers_per_subnet: usize) {
+        match self {
+            Self::Validator(node) => node.router().set_max_peers_per_subnet(max_peers_per_subnet),
+            Self::Prover(node) => node.router().set_max_peers_per_subnet(max_peers_per_subnet),
+            Self::Client(node) => node.router().set_max_peers_per_subnet(max_peers_per_subnet),
+            Self::Archive(node) => node.router().set_max_peers_per_subnet(max_peers_per_subnet),
+        }
+    }
+
+    /// Sets the fraction of the transactions broadcast by the node, whose propagation delay is sampled.
+    pub fn set_propagation_sample_rate(&self, sample_rate: f64) {
+        match self {
+            Self::Validator(node) => node.router().propagation().set_sample_rate(sample_rate),
+            Self::Prover(node) => node.router().propagation().set_sample_rate(sample_rate),
+            Self::Client(node) => node.router().propagation().set_sample_rate(sample_rate),
+            Self::Archive(node) => node.router().propagation().set_sample_rate(sample_rate),
         }
     }
 }
@@ -14,6 +14,9 @@
 
 mod router;
 
+mod throttle;
+pub use throttle::*;
+
 use crate::traits::NodeInterface;
 use snarkos_account::Account;
 use snarkos_node_bft::ledger_service::ProverLedgerService;
@@ -22,8 +25,12 @@ use snarkos_node_router::{
     Heartbeat,
     Inbound,
     Outbound,
+    ProxyConfig,
     Router,
+    RouterConfig,
     Routing,
+    SolutionVerdict,
+    SolutionVerifier,
 };
 use snarkos_node_sync::{BlockSync, BlockSyncMode};
 use snarkos_node_tcp::{
@@ -68,6 +75,8 @@ pub struct Prover<N: Network, C: ConsensusStorage<N>> {
     genesis: Block<N>,
     /// The puzzle.
     puzzle: Puzzle<N>,
+    /// The verifier for unconfirmed solutions, prior to propagation.
+    solution_verifier: Arc<SolutionVerifier<N>>,
     /// The latest epoch hash.
     latest_epoch_hash: Arc<RwLock<Option<N::BlockHash>>>,
     /// The latest block header.
@@ -76,6 +85,8 @@ pub struct Prover<N: Network, C: ConsensusStorage<N>> {
     puzzle_instances: Arc<AtomicU8>,
     /// The maximum number of puzzle instances.
     max_puzzle_instances: u8,
+    /// The throttle of the puzzle.
+    throttle: Arc<ProverThrottle>,
     /// The spawned handles.
     handles: Arc<Mutex<Vec<JoinHandle<()>>>>,
     /// The shutdown signal.
@@ -90,8 +101,11 @@ impl<N: Network, C: ConsensusStorage<N>> Prover<N, C> {
         node_ip: SocketAddr,
         account: Account<N>,
         trusted_peers: &[SocketAddr],
+        proxy: Option<ProxyConfig>,
         genesis: Block<N>,
         storage_mode: StorageMode,
+        prover_threads: Option<usize>,
+        prover_duty_cycle: u8,
     ) -> Result<Self> {
         // Prepare the shutdown flag.
         let shutdown: Arc<AtomicBool> = Default::default();
@@ -107,28 +121,28 @@ impl<N: Network, C: ConsensusStorage<N>> Prover<N, C> {
         let allow_external_peers = true;
 
         // Initialize the node router.
-        let router = Router::new(
-            node_ip,
-            NodeType::Prover,
-            account,
-            trusted_peers,
-            Self::MAXIMUM_NUMBER_OF_PEERS as u16,
-            allow_external_peers,
-            matches!(storage_mode, StorageMode::Development(_)),
-        )
-        .await?;
-        // Compute the maximum number of puzzle instances.
-        let max_puzzle_instances = num_cpus::get().saturating_sub(2).clamp(1, 6);
+        let router_config = RouterConfig::new(node_ip, NodeType::Prover, Self::MAXIMUM_NUMBER_OF_PEERS as u16)
+            .with_trusted_peers(trusted_peers)
+            .with_external_peers(allow_external_peers)
+            .with_dev(matches!(storage_mode, StorageMode::Development(_)))
+            .with_proxy(proxy);
+        let router = Router::new(account, router_config).await?;
+        // Initialize the throttle of the puzzle.
+        let throttle = ProverThrottle::new(prover_threads, prover_duty_cycle)?;
+        // Compute the maximum number of puzzle instances, which share the threads of the throttle.
+        let max_puzzle_instances = num_cpus::get().saturating_sub(2).clamp(1, 6).min(throttle.num_threads());
         // Initialize the node.
         let node = Self {
             router,
             sync: Arc::new(sync),
             genesis,
             puzzle: VM::<N, C>::new_puzzle()?,
+            solution_verifier: Default::default(),
             latest_epoch_hash: Default::default(),
             latest_block_header: Default::default(),
             puzzle_instances: Default::default(),
             max_puzzle_instances: u8::try_from(max_puzzle_instances)?,
+            throttle: Arc::new(throttle),
             handles: Default::default(),
             shutdown,
             _phantom: Default::default(),
@@ -137,6 +151,8 @@ impl<N: Network, C: ConsensusStorage<N>> Prover<N, C> {
         node.initialize_routing().await;
         // Initialize the puzzle.
         node.initialize_puzzle().await;
+        // Initialize the status logs of the throttle.
+        node.initialize_throttle_status();
         // Initialize the notification message loop.
         node.handles.lock().push(crate::start_notification_message_loop());
         // Pass the node to the signal handler.
@@ -168,6 +184,31 @@ impl<N: Network, C: ConsensusStorage<N>> NodeInterface<N> for Prover<N, C> {
 }
 
 impl<N: Network, C: ConsensusStorage<N>> Prover<N, C> {
+    /// The interval between the status logs of the throttle.
+    const THROTTLE_STATUS_INTERVAL: Duration = Duration::from_secs(60);
+
+    /// Returns the throttle of the puzzle, whose duty cycle may be adjusted at runtime.
+    pub fn throttle(&self) -> &ProverThrottle {
+        &self.throttle
+    }
+
+    /// Initialize the periodic logs of the throttle, with its setting and the realized proving rate.
+    fn initialize_throttle_status(&self) {
+        let prover = self.clone();
+        self.handles.lock().push(tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(Self::THROTTLE_STATUS_INTERVAL).await;
+                let throttle = prover.throttle();
+                info!(
+                    "Proving on {} threads at a {}% duty cycle ({} attempts in the last minute)",
+                    throttle.num_threads(),
+                    throttle.duty_cycle(),
+                    throttle.attempts_per_minute()
+                );
+            }
+        }));
+    }
+
     /// Initialize a new instance of the puzzle.
     async fn initialize_puzzle(&self) {
         for _ in 0..self.max_puzzle_instances {
@@ -208,16 +249,29 @@ impl<N: Network, C: ConsensusStorage<N>> Prover<N, C> {
             if let (Some(epoch_hash), Some((coinbase_target, proof_target))) = (latest_epoch_hash, latest_state) {
                 // Execute the puzzle.
                 let prover = self.clone();
+                let start = std::time::Instant::now();
                 let result = tokio::task::spawn_blocking(move || {
                     prover.puzzle_iteration(epoch_hash, coinbase_target, proof_target, &mut OsRng)
                 })
                 .await;
+                let busy = start.elapsed();
 
                 // If the prover found a solution, then broadcast it.
                 if let Ok(Some((solution_target, solution))) = result {
-                    info!("Found a Solution '{}' (Proof Target {solution_target})", solution.id());
-                    // Broadcast the solution.
-                    self.broadcast_solution(solution);
+                    // Abandon the solution if the epoch changed while proving, as it would be refused.
+                    if *self.latest_epoch_hash.read() != Some(epoch_hash) {
+                        debug!("Abandoning the solution '{}' of the previous epoch", solution.id());
+                    } else {
+                        info!("Found a Solution '{}' (Proof Target {solution_target})", solution.id());
+                        // Broadcast the solution.
+                        self.broadcast_solution(solution);
+                    }
+                }
+
+                // Idle for the remainder of the duty cycle.
+                let idle = self.throttle.idle_after(busy);
+                if !idle.is_zero() {
+                    tokio::time::sleep(idle).await;
                 }
             } else {
                 // Otherwise, sleep for a brief period of time, to await for puzzle state.
@@ -249,11 +303,13 @@ impl<N: Network, C: ConsensusStorage<N>> Prover<N, C> {
             format!("(Coinbase Target {coinbase_target}, Proof Target {proof_target})").dimmed()
         );
 
-        // Compute the solution.
-        let result =
-            self.puzzle.prove(epoch_hash, self.address(), rng.gen(), Some(proof_target)).ok().and_then(|solution| {
+        // Compute the solution, on the threads of the throttle.
+        let nonce = rng.gen();
+        let result = self.throttle.prove(|| {
+            self.puzzle.prove(epoch_hash, self.address(), nonce, Some(proof_target)).ok().and_then(|solution| {
                 self.puzzle.get_proof_target(&solution).ok().map(|solution_target| (solution_target, solution))
-            });
+            })
+        });
 
         // Decrement the puzzle instances.
         self.decrement_puzzle_instances();
@@ -14,20 +14,30 @@
 
 use super::*;
 
-use snarkos_node_router::messages::{
-    BlockRequest,
-    DisconnectReason,
-    Message,
-    MessageCodec,
-    Ping,
-    Pong,
-    PuzzleRequest,
-    UnconfirmedTransaction,
+use snarkos_node_router::{
+    messages::{
+        BlockByHashRequest,
+        BlockRequest,
+        DisconnectReason,
+        Message,
+        MessageCodec,
+        Ping,
+        Pong,
+        PuzzleRequest,
+        RejectReason,
+        RejectedItem,
+        UnconfirmedTransaction,
+    },
+    now_unix_millis,
+    queue_policy,
+    traffic_class,
+    Offense,
 };
-use snarkos_node_tcp::{Connection, ConnectionSide, Tcp};
+use snarkos_node_sync::ImplausibleHeight;
+use snarkos_node_tcp::{Connection, ConnectionSide, QueuePolicy, Tcp, TrafficClass};
 use snarkvm::prelude::{block::Transaction, Network};
 
-use std::{io, net::SocketAddr};
+use std::{borrow::Cow, io, net::SocketAddr};
 
 impl<N: Network, C: ConsensusStorage<N>> P2P for Prover<N, C> {
     /// Returns a reference to the TCP instance.
@@ -82,8 +92,23 @@ impl<N: Network, C: ConsensusStorage<N>> Writing for Prover<N, C> {
 
     /// Creates an [`Encoder`] used to write the outbound messages to the target stream.
     /// The `side` parameter indicates the connection side **from the node's perspective**.
-    fn codec(&self, _addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
-        Default::default()
+    fn codec(&self, addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
+        self.router().message_codec(addr)
+    }
+
+    /// Returns the traffic class of the given outbound message, which determines its share of the outbound bandwidth.
+    fn traffic_class(&self, message: &Self::Message) -> TrafficClass {
+        traffic_class(message)
+    }
+
+    /// Returns the queue policy of the given outbound message, which determines whether it can be dropped.
+    fn queue_policy(&self, message: &Self::Message) -> QueuePolicy {
+        queue_policy(message)
+    }
+
+    /// Returns the type of the given outbound message, under which it is counted if it is dropped.
+    fn message_type(&self, message: &Self::Message) -> Cow<'static, str> {
+        message.name()
     }
 }
 
@@ -94,8 +119,8 @@ impl<N: Network, C: ConsensusStorage<N>> Reading for Prover<N, C> {
 
     /// Creates a [`Decoder`] used to interpret messages from the network.
     /// The `side` param indicates the connection side **from the node's perspective**.
-    fn codec(&self, _peer_addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
-        Default::default()
+    fn codec(&self, peer_addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
+        self.router().message_codec(peer_addr)
     }
 
     /// Processes a message received from the network.
@@ -106,7 +131,7 @@ impl<N: Network, C: ConsensusStorage<N>> Reading for Prover<N, C> {
                 warn!("Disconnecting from '{peer_addr}' - {error}");
                 Outbound::send(self, peer_ip, Message::Disconnect(DisconnectReason::ProtocolViolation.into()));
                 // Disconnect from this peer.
-                self.router().disconnect(peer_ip);
+                self.router().disconnect_with_reason(peer_ip, error);
             }
         }
         Ok(())
@@ -139,6 +164,12 @@ impl<N: Network, C: ConsensusStorage<N>> Outbound<N> for Prover<N, C> {
 
 #[async_trait]
 impl<N: Network, C: ConsensusStorage<N>> Inbound<N> for Prover<N, C> {
+    /// Handles a `BlockByHashRequest` message.
+    fn block_by_hash_request(&self, peer_ip: SocketAddr, _message: BlockByHashRequest<N>) -> bool {
+        debug!("Disconnecting '{peer_ip}' for the following reason - {:?}", DisconnectReason::ProtocolViolation);
+        false
+    }
+
     /// Handles a `BlockRequest` message.
     fn block_request(&self, peer_ip: SocketAddr, _message: BlockRequest) -> bool {
         debug!("Disconnecting '{peer_ip}' for the following reason - {:?}", DisconnectReason::ProtocolViolation);
@@ -159,14 +190,25 @@ impl<N: Network, C: ConsensusStorage<N>> Inbound<N> for Prover<N, C> {
             if let Some(block_locators) = message.block_locators {
                 // Check the block locators are valid, and update the peer in the sync pool.
                 if let Err(error) = self.sync.update_peer_locators(peer_ip, block_locators) {
-                    warn!("Peer '{peer_ip}' sent invalid block locators: {error}");
-                    return false;
+                    // A peer advertising an implausible height is penalized and excluded from sync,
+                    // but is not disconnected, as it may have been misled by another peer.
+                    if error.is::<ImplausibleHeight>() {
+                        warn!("Peer '{peer_ip}' sent implausible block locators: {error}");
+                        self.router.record_misbehavior(peer_ip, Offense::ImplausibleBlockHeight);
+                    } else {
+                        warn!("Peer '{peer_ip}' sent invalid block locators: {error}");
+                        return false;
+                    }
                 }
             }
         }
 
+        // Echo the timestamp of the ping, if it was provided, so the peer can estimate its clock skew.
+        let timestamps = message.timestamp.map(|timestamp| (timestamp, now_unix_millis()));
+        // Report whether the peer is on a fork of the canonical chain.
+        let is_fork = Some(self.sync.is_peer_on_fork(&peer_ip));
         // Send a `Pong` message to the peer.
-        Outbound::send(self, peer_ip, Message::Pong(Pong { is_fork: Some(false) }));
+        Outbound::send(self, peer_ip, Message::Pong(Pong { is_fork, timestamps }));
         true
     }
 
@@ -175,8 +217,8 @@ impl<N: Network, C: ConsensusStorage<N>> Inbound<N> for Prover<N, C> {
         // Spawn an asynchronous task for the `Ping` request.
         let self_clone = self.clone();
         tokio::spawn(async move {
-            // Sleep for the preset time before sending a `Ping` request.
-            tokio::time::sleep(Duration::from_secs(Self::PING_SLEEP_IN_SECS)).await;
+            // Sleep for the ping interval before sending a `Ping` request.
+            tokio::time::sleep(self_.ping_interval()).await;
             // Check that the peer is still connected.
             if self_clone.router().is_connected(&peer_ip) {
                 // Send a `Ping` message to the peer.
@@ -226,21 +268,39 @@ impl<N: Network, C: ConsensusStorage<N>> Inbound<N> for Prover<N, C> {
 
         if let (Some(epoch_hash), Some(proof_target)) = (epoch_hash, proof_target) {
             // Ensure that the solution is valid for the given epoch.
+            let solution_id = solution.id();
             let puzzle = self.puzzle.clone();
-            let is_valid =
-                tokio::task::spawn_blocking(move || puzzle.check_solution(&solution, epoch_hash, proof_target)).await;
-
-            match is_valid {
+            let verdict = self
+                .solution_verifier
+                .verify(solution_id, epoch_hash, proof_target, move || {
+                    puzzle.check_solution(&solution, epoch_hash, proof_target)
+                })
+                .await;
+
+            match verdict {
                 // If the solution is valid, propagate the `UnconfirmedSolution`.
-                Ok(Ok(())) => {
+                SolutionVerdict::Valid => {
                     let message = Message::UnconfirmedSolution(serialized);
                     // Propagate the "UnconfirmedSolution".
                     self.propagate(message, &[peer_ip]);
                 }
-                Ok(Err(_)) => {
-                    trace!("Invalid solution '{}' for the proof target.", solution.id())
+                // If the solution is invalid, disconnect from the peer.
+                SolutionVerdict::Invalid => {
+                    debug!("Invalid solution '{solution_id}' for the proof target from '{peer_ip}'");
+                    let item = RejectedItem::Solution(solution_id);
+                    self.send_reject(
+                        peer_ip,
+                        item,
+                        RejectReason::Invalid,
+                        "The solution does not meet the proof target",
+                    );
+                    return false;
+                }
+                SolutionVerdict::Shed => {
+                    trace!("Skipping solution '{solution_id}' (too many pending verifications)");
+                    let item = RejectedItem::Solution(solution_id);
+                    self.send_reject(peer_ip, item, RejectReason::TemporarilyFull, "Too many pending verifications");
                 }
-                Err(error) => warn!("Failed to verify the solution - {error}"),
             }
         }
         true
@@ -0,0 +1,209 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use anyhow::{ensure, Result};
+use parking_lot::Mutex;
+use std::{
+    collections::VecDeque,
+    sync::atomic::{AtomicU8, Ordering},
+    time::{Duration, Instant},
+};
+
+/// The window over which the realized proving rate is measured.
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// The throttle of the puzzle of a prover, which caps the threads used for proving,
+/// and the share of time spent proving (i.e. its duty cycle).
+///
+/// The duty cycle is enforced by idling after each attempt, in proportion to the time spent proving,
+/// so that an instance of the puzzle proves for `duty_cycle` percent of the time, on average.
+pub struct ProverThrottle {
+    /// The thread pool of the puzzle.
+    #[cfg(feature = "parallel")]
+    pool: rayon::ThreadPool,
+    /// The number of threads used for proving.
+    num_threads: usize,
+    /// The percentage of time spent proving, from 1 to 100.
+    duty_cycle: AtomicU8,
+    /// The times of the proving attempts in the rate window.
+    attempts: Mutex<VecDeque<Instant>>,
+}
+
+impl ProverThrottle {
+    /// The default duty cycle, in percent.
+    pub const DEFAULT_DUTY_CYCLE: u8 = 100;
+
+    /// Initializes a new throttle with the given number of threads (by default, all the cores) and duty cycle.
+    pub fn new(num_threads: Option<usize>, duty_cycle: u8) -> Result<Self> {
+        Self::check_duty_cycle(duty_cycle)?;
+        let num_threads = match num_threads {
+            Some(num_threads) => {
+                ensure!(num_threads > 0, "The number of prover threads must be greater than zero");
+                num_threads
+            }
+            None => std::thread::available_parallelism().map(|cores| cores.get()).unwrap_or(1),
+        };
+        #[cfg(feature = "parallel")]
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(num_threads)
+            .thread_name(|index| format!("prover-{index}"))
+            .build()?;
+        Ok(Self {
+            #[cfg(feature = "parallel")]
+            pool,
+            num_threads,
+            duty_cycle: AtomicU8::new(duty_cycle),
+            attempts: Default::default(),
+        })
+    }
+
+    /// Ensures the given duty cycle is a percentage from 1 to 100.
+    fn check_duty_cycle(duty_cycle: u8) -> Result<()> {
+        ensure!(
+            (1..=100).contains(&duty_cycle),
+            "The prover duty cycle must be between 1 and 100 (found {duty_cycle})"
+        );
+        Ok(())
+    }
+
+    /// Returns the number of threads used for proving.
+    pub fn num_threads(&self) -> usize {
+        self.num_threads
+    }
+
+    /// Returns the duty cycle, in percent.
+    pub fn duty_cycle(&self) -> u8 {
+        self.duty_cycle.load(Ordering::Relaxed)
+    }
+
+    /// Sets the duty cycle, in percent, which applies from the next proving attempt.
+    pub fn set_duty_cycle(&self, duty_cycle: u8) -> Result<()> {
+        Self::check_duty_cycle(duty_cycle)?;
+        self.duty_cycle.store(duty_cycle, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Runs the given proving attempt on the threads of the throttle, and records it.
+    pub fn prove<T: Send>(&self, prove: impl FnOnce() -> T + Send) -> T {
+        #[cfg(feature = "parallel")]
+        let result = self.pool.install(prove);
+        #[cfg(not(feature = "parallel"))]
+        let result = prove();
+        self.record_attempt(Instant::now());
+        result
+    }
+
+    /// Returns the time to idle after a proving attempt that took the given time, to honor the duty cycle.
+    pub fn idle_after(&self, busy: Duration) -> Duration {
+        let duty_cycle = self.duty_cycle() as u32;
+        busy * (100 - duty_cycle) / duty_cycle
+    }
+
+    /// Records a proving attempt at the given time.
+    fn record_attempt(&self, now: Instant) {
+        let mut attempts = self.attempts.lock();
+        attempts.push_back(now);
+        Self::prune_attempts(&mut attempts, now);
+    }
+
+    /// Removes the attempts that are older than the rate window.
+    fn prune_attempts(attempts: &mut VecDeque<Instant>, now: Instant) {
+        while attempts.front().is_some_and(|attempt| now.duration_since(*attempt) > RATE_WINDOW) {
+            attempts.pop_front();
+        }
+    }
+
+    /// Returns the number of proving attempts in the last minute.
+    pub fn attempts_per_minute(&self) -> usize {
+        let mut attempts = self.attempts.lock();
+        Self::prune_attempts(&mut attempts, Instant::now());
+        attempts.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs a proving workload of the given duration, honoring the duty cycle, for the given time.
+    fn run_workload(throttle: &ProverThrottle, work: Duration, time: Duration) {
+        let start = Instant::now();
+        while start.elapsed() < time {
+            let busy = Instant::now();
+            throttle.prove(|| std::thread::sleep(work));
+            std::thread::sleep(throttle.idle_after(busy.elapsed()));
+        }
+    }
+
+    #[test]
+    fn test_num_threads() {
+        // Ensure the thread cap is honored by the pool of the puzzle.
+        let throttle = ProverThrottle::new(Some(2), 100).unwrap();
+        assert_eq!(throttle.num_threads(), 2);
+        #[cfg(feature = "parallel")]
+        assert_eq!(throttle.prove(rayon::current_num_threads), 2);
+        let throttle = ProverThrottle::new(Some(1), 100).unwrap();
+        assert_eq!(throttle.num_threads(), 1);
+
+        // Ensure the number of threads must be positive.
+        assert!(ProverThrottle::new(Some(0), 100).is_err());
+    }
+
+    #[test]
+    fn test_duty_cycle() {
+        let throttle = ProverThrottle::new(Some(1), ProverThrottle::DEFAULT_DUTY_CYCLE).unwrap();
+        // Ensure proving at full duty never idles.
+        assert_eq!(throttle.idle_after(Duration::from_secs(1)), Duration::ZERO);
+
+        // Ensure the idle time is proportional to the time spent proving.
+        throttle.set_duty_cycle(25).unwrap();
+        assert_eq!(throttle.duty_cycle(), 25);
+        assert_eq!(throttle.idle_after(Duration::from_secs(1)), Duration::from_secs(3));
+        throttle.set_duty_cycle(50).unwrap();
+        assert_eq!(throttle.idle_after(Duration::from_millis(200)), Duration::from_millis(200));
+
+        // Ensure the duty cycle must be a percentage.
+        assert!(throttle.set_duty_cycle(0).is_err());
+        assert!(throttle.set_duty_cycle(101).is_err());
+        assert_eq!(throttle.duty_cycle(), 50);
+        assert!(ProverThrottle::new(Some(1), 0).is_err());
+    }
+
+    #[test]
+    fn test_attempts_per_minute() {
+        let throttle = ProverThrottle::new(Some(1), 100).unwrap();
+
+        // Measure the proving attempts at full duty.
+        run_workload(&throttle, Duration::from_millis(10), Duration::from_millis(300));
+        let full = throttle.attempts_per_minute();
+        assert!(full > 0);
+
+        // Lower the duty cycle at runtime, and ensure the proving attempts slow down.
+        throttle.set_duty_cycle(20).unwrap();
+        run_workload(&throttle, Duration::from_millis(10), Duration::from_millis(300));
+        let throttled = throttle.attempts_per_minute() - full;
+        assert!(throttled * 2 < full, "Expected fewer attempts at 20% duty ({throttled} vs {full})");
+
+        // Raise the duty cycle again, and ensure the proving attempts speed up.
+        throttle.set_duty_cycle(100).unwrap();
+        run_workload(&throttle, Duration::from_millis(10), Duration::from_millis(300));
+        let restored = throttle.attempts_per_minute() - full - throttled;
+        assert!(restored > throttled * 2, "Expected more attempts at full duty ({restored} vs {throttled})");
+
+        // Ensure attempts older than the rate window are no longer counted.
+        let mut attempts = throttle.attempts.lock();
+        ProverThrottle::prune_attempts(&mut attempts, Instant::now() + RATE_WINDOW + Duration::from_secs(1));
+        assert!(attempts.is_empty());
+    }
+}
@@ -14,42 +14,60 @@
 
 mod router;
 
-use crate::traits::NodeInterface;
+use crate::{traits::NodeInterface, RecordScanMode};
 use snarkos_account::Account;
-use snarkos_node_bft::{helpers::init_primary_channels, ledger_service::CoreLedgerService, spawn_blocking};
-use snarkos_node_consensus::Consensus;
-use snarkos_node_rest::Rest;
+use snarkos_node_bft::{
+    helpers::{init_primary_channels, EmptyBatchMode, EventRecorder, Signer},
+    ledger_service::{CoreLedgerService, ReplayFilter},
+    spawn_blocking,
+};
+use snarkos_node_consensus::{Consensus, ProgramDenylist, TransmissionStorageMode};
+use snarkos_node_rest::{Rest, RestConfig};
 use snarkos_node_router::{
     messages::{NodeType, PuzzleResponse, UnconfirmedSolution, UnconfirmedTransaction},
     Heartbeat,
     Inbound,
     Outbound,
     Router,
+    RouterConfig,
     Routing,
 };
 use snarkos_node_sync::{BlockSync, BlockSyncMode};
 use snarkos_node_tcp::{
     protocols::{Disconnect, Handshake, OnConnect, Reading, Writing},
+    BandwidthScheduler,
     P2P,
 };
-use snarkvm::prelude::{
-    block::{Block, Header},
-    puzzle::Solution,
-    store::ConsensusStorage,
-    Ledger,
-    Network,
+use snarkvm::{
+    ledger::committee::MIN_VALIDATOR_STAKE,
+    prelude::{
+        block::{Block, Header},
+        puzzle::Solution,
+        store::ConsensusStorage,
+        Address,
+        Ledger,
+        Network,
+        ProgramID,
+    },
 };
 
 use aleo_std::StorageMode;
-use anyhow::Result;
+use anyhow::{bail, Result};
+use colored::Colorize;
 use core::future::Future;
 use parking_lot::Mutex;
 use std::{
     net::SocketAddr,
+    path::PathBuf,
     sync::{atomic::AtomicBool, Arc},
     time::Duration,
 };
-use tokio::task::JoinHandle;
+use tokio::{sync::Notify, task::JoinHandle};
+
+/// The interval at which the validator checks its membership in the latest committee.
+const COMMITTEE_MEMBERSHIP_INTERVAL: Duration = Duration::from_secs(10);
+/// The interval at which the validator checks the clock skew against the network.
+const CLOCK_SKEW_INTERVAL: Duration = Duration::from_secs(5);
 
 /// A validator is a full node, capable of validating blocks.
 #[derive(Clone)]
@@ -64,6 +82,8 @@ pub struct Validator<N: Network, C: ConsensusStorage<N>> {
     rest: Option<Rest<N, C, Self>>,
     /// The sync module.
     sync: BlockSync<N>,
+    /// The replay filter of the recently confirmed transactions and solutions.
+    replay_filter: Arc<ReplayFilter<N, C>>,
     /// The spawned handles.
     handles: Arc<Mutex<Vec<JoinHandle<()>>>>,
     /// The shutdown signal.
@@ -75,16 +95,24 @@ impl<N: Network, C: ConsensusStorage<N>> Validator<N, C> {
     pub async fn new(
         node_ip: SocketAddr,
         bft_ip: Option<SocketAddr>,
-        rest_ip: Option<SocketAddr>,
-        rest_rps: u32,
+        rest: RestConfig,
+        enable_record_index: bool,
+        record_scan: RecordScanMode,
+        state_changes_retention: Option<u32>,
         account: Account<N>,
+        signer: Option<Arc<dyn Signer<N>>>,
         trusted_peers: &[SocketAddr],
         trusted_validators: &[SocketAddr],
         genesis: Block<N>,
-        cdn: Option<String>,
+        cdn: Option<Vec<String>>,
         storage_mode: StorageMode,
+        transmission_storage: TransmissionStorageMode,
         allow_external_peers: bool,
         dev_txs: bool,
+        allow_unstaked: bool,
+        event_log: Option<PathBuf>,
+        program_denylist: Vec<ProgramID<N>>,
+        empty_batch_mode: EmptyBatchMode,
     ) -> Result<Self> {
         // Prepare the shutdown flag.
         let shutdown: Arc<AtomicBool> = Default::default();
@@ -96,40 +124,118 @@ impl<N: Network, C: ConsensusStorage<N>> Validator<N, C> {
         let ledger = Ledger::load(genesis, storage_mode.clone())?;
 
         // Initialize the CDN.
-        if let Some(base_url) = cdn {
+        if let Some(base_urls) = cdn {
             // Sync the ledger with the CDN.
             if let Err((_, error)) =
-                snarkos_node_cdn::sync_ledger_with_cdn(&base_url, ledger.clone(), shutdown.clone()).await
+                snarkos_node_cdn::sync_ledger_with_cdn(&base_urls, ledger.clone(), shutdown.clone()).await
             {
                 crate::log_clean_error(&storage_mode);
                 return Err(error);
             }
         }
 
+        // Ensure the validator is a staked member of the latest committee, unless it may start as an observer.
+        let address = signer.as_ref().map_or_else(|| account.address(), |signer| signer.address());
+        let is_committee_member = is_staked_committee_member(&ledger, address)?;
+        if !is_committee_member {
+            if !allow_unstaked {
+                bail!(
+                    "The validator address '{address}' is not a member of the latest committee with at least {MIN_VALIDATOR_STAKE} microcredits of stake - \
+                     bond the validator before starting it, or pass '--allow-unstaked' to start it as an observer"
+                );
+            }
+            warn!(
+                "The validator address '{address}' is not a member of the latest committee - starting as an observer"
+            );
+        }
+
+        // Initialize the record index, if it is enabled.
+        let record_index = crate::open_record_index(enable_record_index, &ledger, &storage_mode)?;
+        // Initialize the reward index, if the record index is enabled.
+        let reward_index = crate::open_reward_index::<N>(enable_record_index, &storage_mode)?;
+        // Initialize the record scanner, if it is enabled.
+        let record_scanner = crate::open_record_scanner::<N>(record_scan, &storage_mode)?;
+        // Initialize the state change index, if it is enabled.
+        let state_changes = crate::open_state_changes::<N>(state_changes_retention, &storage_mode)?;
         // Initialize the ledger service.
-        let ledger_service = Arc::new(CoreLedgerService::new(ledger.clone(), shutdown.clone()));
+        let mut ledger_service = CoreLedgerService::new(ledger.clone(), shutdown.clone());
+        if let Some(reward_index) = &reward_index {
+            ledger_service = ledger_service.with_reward_index(reward_index.clone());
+        }
+        if let Some(state_changes) = &state_changes {
+            ledger_service = ledger_service.with_state_changes(state_changes.clone());
+        }
+        if let Some(record_scanner) = &record_scanner {
+            ledger_service = ledger_service.with_record_scanner(record_scanner.clone());
+        }
+        // Initialize the disk monitor, and stop advancing blocks once the disk space is critically low.
+        let disk_monitor = crate::open_disk_monitor::<N>(&storage_mode);
+        ledger_service = ledger_service.with_disk_monitor(disk_monitor.clone());
+        // Initialize the replay filter of the recently confirmed transactions and solutions.
+        let replay_filter = Arc::new(ReplayFilter::new(ledger.clone()));
+        ledger_service = ledger_service.with_replay_filter(replay_filter.clone());
+        // Notify the block announcements whenever the ledger advances.
+        let block_advanced = Arc::new(Notify::new());
+        ledger_service = ledger_service.with_block_notify(block_advanced.clone());
+        let ledger_service = Arc::new(ledger_service);
         // Initialize the sync module.
-        let sync = BlockSync::new(BlockSyncMode::Gateway, ledger_service.clone());
-
-        // Initialize the consensus.
-        let mut consensus =
-            Consensus::new(account.clone(), ledger_service, bft_ip, trusted_validators, storage_mode.clone())?;
+        // Note: A validator that may run as an observer syncs blocks from its router peers while it is not participating.
+        let sync_mode = match allow_unstaked {
+            true => BlockSyncMode::Router,
+            false => BlockSyncMode::Gateway,
+        };
+        let sync = BlockSync::new(sync_mode, ledger_service.clone());
+
+        // Initialize the consensus, signing with the remote signer if one is given.
+        let mut consensus = match signer {
+            Some(signer) => Consensus::new(
+                signer,
+                ledger_service,
+                bft_ip,
+                trusted_validators,
+                storage_mode.clone(),
+                transmission_storage,
+            )?,
+            None => Consensus::new(
+                account.clone(),
+                ledger_service,
+                bft_ip,
+                trusted_validators,
+                storage_mode.clone(),
+                transmission_storage,
+            )?,
+        };
+        // Refuse new transactions and solutions while the disk space is low.
+        consensus = consensus.with_disk_monitor(disk_monitor.clone());
+        // Refuse the transactions that reference a denylisted program.
+        let program_denylist = Arc::new(ProgramDenylist::new(program_denylist));
+        consensus = consensus.with_program_denylist(program_denylist.clone());
+        // Delay proposing batches without transactions, per the empty batch mode.
+        consensus = consensus.with_empty_batch_mode(empty_batch_mode);
+        // Participate in the BFT only as a staked member of the committee.
+        consensus.bft().primary().set_participating(is_committee_member);
+        // Record the events of the BFT, if an event log is given.
+        if let Some(path) = event_log {
+            let recorder = EventRecorder::create(&path, address, &ledger.get_block(0)?)?;
+            consensus.bft().primary().gateway().set_event_recorder(recorder)?;
+            info!("Recording the events of the BFT to '{}'", path.display());
+        }
         // Initialize the primary channels.
         let (primary_sender, primary_receiver) = init_primary_channels::<N>();
         // Start the consensus.
         consensus.run(primary_sender, primary_receiver).await?;
 
+        // Determine if the node is running in development mode.
+        let is_dev = matches!(storage_mode, StorageMode::Development(_));
+
         // Initialize the node router.
-        let router = Router::new(
-            node_ip,
-            NodeType::Validator,
-            account,
-            trusted_peers,
-            Self::MAXIMUM_NUMBER_OF_PEERS as u16,
-            allow_external_peers,
-            matches!(storage_mode, StorageMode::Development(_)),
-        )
-        .await?;
+        let router_config = RouterConfig::new(node_ip, NodeType::Validator, Self::MAXIMUM_NUMBER_OF_PEERS as u16)
+            .with_trusted_peers(trusted_peers)
+            .with_rest(rest.is_enabled())
+            .with_external_peers(allow_external_peers)
+            .with_dev(is_dev)
+            .with_proxy(None);
+        let router = Router::new(account, router_config).await?;
 
         // Initialize the node.
         let mut node = Self {
@@ -138,6 +244,7 @@ impl<N: Network, C: ConsensusStorage<N>> Validator<N, C> {
             router,
             rest: None,
             sync,
+            replay_filter: replay_filter.clone(),
             handles: Default::default(),
             shutdown,
         };
@@ -145,12 +252,49 @@ impl<N: Network, C: ConsensusStorage<N>> Validator<N, C> {
         node.initialize_transaction_pool(storage_mode, dev_txs)?;
 
         // Initialize the REST server.
-        if let Some(rest_ip) = rest_ip {
-            node.rest =
-                Some(Rest::start(rest_ip, rest_rps, Some(consensus), ledger.clone(), Arc::new(node.clone())).await?);
+        if rest.is_enabled() {
+            // Note: The REST server reports the sync status of the BFT, which drives the block sync of validators.
+            let block_sync = consensus.bft().primary().sync().block_sync().clone();
+            node.rest = Some(
+                Rest::new(ledger.clone(), Arc::new(node.clone()), block_sync, replay_filter.clone())
+                    .with_consensus(consensus)
+                    .with_record_index(record_index)
+                    .with_reward_index(reward_index.clone())
+                    .with_state_changes(state_changes.clone())
+                    .with_record_scanner(record_scanner)
+                    .with_disk_monitor(disk_monitor.clone())
+                    .with_program_denylist(program_denylist)
+                    .with_dev_routes(is_dev)
+                    .start(rest)
+                    .await?,
+            );
+        }
+        // Backfill the replay filter, from the latest block.
+        node.handles.lock().push(crate::start_replay_filter_backfill(replay_filter, node.shutdown.clone()));
+        // Backfill the reward index, if it is enabled.
+        if let Some(reward_index) = reward_index {
+            let backfill = crate::start_reward_index_backfill(reward_index, ledger.clone(), node.shutdown.clone());
+            node.handles.lock().push(backfill);
+        }
+        // Backfill the state change index, if it is enabled.
+        if let Some(state_changes) = state_changes {
+            let backfill = crate::start_state_changes_backfill(state_changes, ledger.clone(), node.shutdown.clone());
+            node.handles.lock().push(backfill);
         }
         // Initialize the routing.
         node.initialize_routing().await;
+        // Initialize the block announcements.
+        node.initialize_block_announcements(block_advanced);
+        // Initialize the committee membership checks.
+        node.initialize_committee_membership();
+        // Initialize the clock skew checks.
+        node.initialize_clock_skew();
+        // Initialize the observer sync, if the validator may run as an observer.
+        if node.sync.mode().is_router() {
+            node.initialize_observer_sync();
+        }
+        // Initialize the disk monitor loop.
+        node.handles.lock().push(crate::start_disk_monitor(node.clone(), disk_monitor));
         // Initialize the notification message loop.
         node.handles.lock().push(crate::start_notification_message_loop());
         // Pass the node to the signal handler.
@@ -164,10 +308,82 @@ impl<N: Network, C: ConsensusStorage<N>> Validator<N, C> {
         &self.ledger
     }
 
+    /// Returns the consensus module.
+    pub fn consensus(&self) -> &Consensus<N> {
+        &self.consensus
+    }
+
     /// Returns the REST server.
     pub fn rest(&self) -> &Option<Rest<N, C, Self>> {
         &self.rest
     }
+
+    /// Returns `true` if the validator participates in the BFT, as a staked member of the committee.
+    pub fn is_participating(&self) -> bool {
+        self.consensus.bft().primary().is_participating()
+    }
+
+    /// Updates the BFT participation of the validator to its membership in the latest committee,
+    /// and returns `true` if the participation changed.
+    pub fn update_participation(&self) -> Result<bool> {
+        let bft = self.consensus.bft();
+        let primary = bft.primary();
+        // Determine if the validator is a staked member of the latest committee.
+        let address = primary.gateway().signer().address();
+        let is_committee_member = is_staked_committee_member(&self.ledger, address)?;
+        // If the membership is unchanged, return early.
+        if is_committee_member == primary.is_participating() {
+            return Ok(false);
+        }
+        match is_committee_member {
+            true => info!(
+                "{}",
+                format!("The validator '{address}' joined the committee, and is participating in the BFT").bold()
+            ),
+            false => warn!(
+                "{}",
+                format!("The validator '{address}' left the committee, and stopped participating in the BFT").bold()
+            ),
+        }
+        primary.set_participating(is_committee_member);
+        Ok(true)
+    }
+
+    /// Halts the batch proposals of the validator while its system clock is skewed against the network
+    /// beyond the timestamp tolerance of the BFT, and returns `true` if the halt changed.
+    pub fn update_clock_skew(&self) -> bool {
+        let bft = self.consensus.bft();
+        let primary = bft.primary();
+        // Determine if the clock skew exceeds the timestamp tolerance of the BFT.
+        let is_clock_skewed = self.router.clock_skew().level().is_halt();
+        // If the halt is unchanged, return early.
+        if is_clock_skewed == primary.is_clock_skewed() {
+            return false;
+        }
+        match is_clock_skewed {
+            true => error!("{}", "The validator stopped proposing batches, until its system clock is in sync".bold()),
+            false => info!("{}", "The validator resumed proposing batches, as its system clock is in sync".bold()),
+        }
+        primary.set_clock_skewed(is_clock_skewed);
+        true
+    }
+
+    /// Limits the outbound bandwidth of the router and the BFT gateway to the given scheduler, so that
+    /// the consensus messages of the gateway share the budget with the messages of the router.
+    pub fn set_bandwidth_scheduler(&self, scheduler: Arc<BandwidthScheduler>) -> Result<()> {
+        self.router.tcp().set_bandwidth_scheduler(scheduler.clone())?;
+        self.consensus.bft().primary().gateway().tcp().set_bandwidth_scheduler(scheduler)?;
+        Ok(())
+    }
+}
+
+/// Returns `true` if the given address is a member of the latest committee, with at least the minimum stake.
+fn is_staked_committee_member<N: Network, C: ConsensusStorage<N>>(
+    ledger: &Ledger<N, C>,
+    address: Address<N>,
+) -> Result<bool> {
+    let committee = ledger.latest_committee()?;
+    Ok(committee.is_committee_member(address) && committee.get_stake(address) >= MIN_VALIDATOR_STAKE)
 }
 
 impl<N: Network, C: ConsensusStorage<N>> Validator<N, C> {
@@ -421,6 +637,66 @@ impl<N: Network, C: ConsensusStorage<N>> Validator<N, C> {
         Ok(())
     }
 
+    /// Initialize the block announcements, to announce new blocks to peers that accept compact blocks.
+    fn initialize_block_announcements(&self, block_advanced: Arc<Notify>) {
+        let self_ = self.clone();
+        self.spawn(async move {
+            // Announce only the blocks that are produced from here on.
+            let mut last_announced_height = self_.ledger.latest_height();
+            loop {
+                // Wait for the ledger to advance.
+                block_advanced.notified().await;
+
+                // Retrieve the latest block.
+                let block = self_.ledger.latest_block();
+                // If the ledger advanced, announce the latest block.
+                if block.height() > last_announced_height {
+                    last_announced_height = block.height();
+                    self_.announce_block(&block, &[]);
+                }
+            }
+        });
+    }
+
+    /// Initialize the committee membership checks, to start and stop participating in the BFT as the committee changes.
+    fn initialize_committee_membership(&self) {
+        let self_ = self.clone();
+        self.spawn(async move {
+            loop {
+                tokio::time::sleep(COMMITTEE_MEMBERSHIP_INTERVAL).await;
+                if let Err(error) = self_.update_participation() {
+                    warn!("Failed to check the committee membership of the validator - {error}");
+                }
+            }
+        });
+    }
+
+    /// Initialize the clock skew checks, to halt the batch proposals while the system clock is skewed.
+    fn initialize_clock_skew(&self) {
+        let self_ = self.clone();
+        self.spawn(async move {
+            loop {
+                tokio::time::sleep(CLOCK_SKEW_INTERVAL).await;
+                self_.update_clock_skew();
+            }
+        });
+    }
+
+    /// Initialize the observer sync, to sync blocks from the router peers while the validator is not participating.
+    fn initialize_observer_sync(&self) {
+        let self_ = self.clone();
+        self.spawn(async move {
+            loop {
+                // Sleep briefly to avoid triggering spam detection.
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                // Note: A participating validator syncs blocks through the BFT instead.
+                if !self_.is_participating() {
+                    self_.sync.try_block_sync(&self_).await;
+                }
+            }
+        });
+    }
+
     /// Spawns a task with the given future; it should only be used for long-running tasks.
     pub fn spawn<T: Future<Output = ()> + Send + 'static>(&self, future: T) {
         self.handles.lock().push(tokio::spawn(future));
@@ -441,6 +717,12 @@ impl<N: Network, C: ConsensusStorage<N>> NodeInterface<N> for Validator<N, C> {
         trace!("Shutting down the validator...");
         self.handles.lock().iter().for_each(|handle| handle.abort());
 
+        // Shut down the REST server.
+        if let Some(rest) = &self.rest {
+            trace!("Shutting down the REST server...");
+            rest.shut_down();
+        }
+
         // Shut down the router.
         self.router.shut_down().await;
 
@@ -455,18 +737,145 @@ impl<N: Network, C: ConsensusStorage<N>> NodeInterface<N> for Validator<N, C> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use snarkos_node_bft::MAX_TIMESTAMP_DELTA_IN_SECS;
+    use snarkos_node_router::{CLOCK_SKEW_HALT_IN_MS, MINIMUM_CLOCK_SKEW_PEERS};
     use snarkvm::prelude::{
         store::{helpers::memory::ConsensusMemory, ConsensusStore},
         MainnetV0,
         VM,
     };
 
-    use anyhow::bail;
     use rand::SeedableRng;
     use rand_chacha::ChaChaRng;
     use std::str::FromStr;
 
     type CurrentNetwork = MainnetV0;
+    type CurrentValidator = Validator<CurrentNetwork, ConsensusMemory<CurrentNetwork>>;
+
+    /// Returns a genesis block with a committee that includes the given account.
+    fn sample_genesis(account: &Account<CurrentNetwork>, rng: &mut ChaChaRng) -> Block<CurrentNetwork> {
+        let vm =
+            VM::from(ConsensusStore::<CurrentNetwork, ConsensusMemory<CurrentNetwork>>::open(None).unwrap()).unwrap();
+        vm.genesis_beacon(account.private_key(), rng).unwrap()
+    }
+
+    /// Starts a validator with the given account and genesis block, in its own storage directory.
+    async fn sample_validator(
+        name: &str,
+        account: Account<CurrentNetwork>,
+        genesis: Block<CurrentNetwork>,
+        allow_unstaked: bool,
+    ) -> Result<CurrentValidator> {
+        let path = std::env::temp_dir().join(format!("snarkos-validator-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        CurrentValidator::new(
+            SocketAddr::from_str("127.0.0.1:0").unwrap(),
+            Some(SocketAddr::from_str("127.0.0.1:0").unwrap()),
+            RestConfig::default(),
+            false,
+            RecordScanMode::Disabled,
+            None,
+            account,
+            None,
+            &[],
+            &[],
+            genesis,
+            None,
+            StorageMode::Custom(path),
+            TransmissionStorageMode::Persistent,
+            false,
+            false,
+            allow_unstaked,
+            None,
+            vec![],
+            EmptyBatchMode::default(),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_start_with_stake() {
+        let mut rng = ChaChaRng::seed_from_u64(1);
+        let account = Account::<CurrentNetwork>::new(&mut rng).unwrap();
+        let genesis = sample_genesis(&account, &mut rng);
+
+        // Ensure a staked validator participates in the BFT, with or without the observer fallback.
+        let validator = sample_validator("staked", account, genesis, false).await.unwrap();
+        assert!(validator.is_participating());
+        assert!(!validator.update_participation().unwrap());
+        assert!(validator.is_participating());
+        validator.shut_down().await;
+    }
+
+    #[tokio::test]
+    async fn test_start_without_stake() {
+        let mut rng = ChaChaRng::seed_from_u64(2);
+        let genesis = sample_genesis(&Account::new(&mut rng).unwrap(), &mut rng);
+        let account = Account::<CurrentNetwork>::new(&mut rng).unwrap();
+
+        // Ensure an unstaked validator refuses to start by default.
+        let error = sample_validator("unstaked", account.clone(), genesis.clone(), false).await.unwrap_err();
+        assert!(error.to_string().contains("--allow-unstaked"), "{error}");
+
+        // Ensure an unstaked validator starts as an observer, if it is allowed to.
+        let validator = sample_validator("observer", account, genesis, true).await.unwrap();
+        assert!(!validator.is_participating());
+        assert!(!validator.update_participation().unwrap());
+        assert!(!validator.is_participating());
+        validator.shut_down().await;
+    }
+
+    #[tokio::test]
+    async fn test_committee_membership_transitions() {
+        let mut rng = ChaChaRng::seed_from_u64(3);
+        let staked = Account::<CurrentNetwork>::new(&mut rng).unwrap();
+        let genesis = sample_genesis(&staked, &mut rng);
+        let unstaked = Account::<CurrentNetwork>::new(&mut rng).unwrap();
+
+        // Ensure a validator that is not participating starts participating once it is in the committee.
+        let validator = sample_validator("joined", staked, genesis.clone(), true).await.unwrap();
+        validator.consensus.bft().primary().set_participating(false);
+        assert!(validator.update_participation().unwrap());
+        assert!(validator.is_participating());
+        validator.shut_down().await;
+
+        // Ensure a participating validator stops participating once it is no longer in the committee.
+        let validator = sample_validator("left", unstaked, genesis, true).await.unwrap();
+        validator.consensus.bft().primary().set_participating(true);
+        assert!(validator.update_participation().unwrap());
+        assert!(!validator.is_participating());
+        validator.shut_down().await;
+    }
+
+    #[tokio::test]
+    async fn test_clock_skew_transitions() {
+        // Ensure the clock skew halts the proposals at the timestamp tolerance of the BFT.
+        assert_eq!(CLOCK_SKEW_HALT_IN_MS, MAX_TIMESTAMP_DELTA_IN_SECS * 1000);
+
+        let mut rng = ChaChaRng::seed_from_u64(4);
+        let account = Account::<CurrentNetwork>::new(&mut rng).unwrap();
+        let genesis = sample_genesis(&account, &mut rng);
+        let validator = sample_validator("skewed", account, genesis, false).await.unwrap();
+        assert!(!validator.update_clock_skew());
+
+        // Simulate peers whose clocks are ahead, beyond the timestamp tolerance of the BFT.
+        let peer_ips = (0..MINIMUM_CLOCK_SKEW_PEERS as u16).map(|port| SocketAddr::from(([127, 0, 0, 1], 4000 + port)));
+        for peer_ip in peer_ips.clone() {
+            validator.router.clock_skew().observe(peer_ip, 0, CLOCK_SKEW_HALT_IN_MS + 1_000, 0);
+        }
+        // Ensure the validator stops proposing batches.
+        assert!(validator.update_clock_skew());
+        assert!(validator.consensus.bft().primary().is_clock_skewed());
+        assert!(!validator.update_clock_skew());
+
+        // Ensure the validator resumes proposing batches, once the skew is back in range.
+        for peer_ip in peer_ips {
+            validator.router.clock_skew().remove_peer(peer_ip);
+        }
+        assert!(validator.update_clock_skew());
+        assert!(!validator.consensus.bft().primary().is_clock_skewed());
+        validator.shut_down().await;
+    }
 
     /// Use `RUST_MIN_STACK=67108864 cargo test --release profiler --features timer` to run this test.
     #[ignore]
@@ -494,7 +903,12 @@ mod tests {
             None,
             Some(rest),
             10,
+            false,
+            false,
+            RecordScanMode::Disabled,
+            None,
             account,
+            None,
             &[],
             &[],
             genesis,
@@ -502,6 +916,7 @@ mod tests {
             storage_mode,
             false,
             dev_txs,
+            false,
         )
         .await
         .unwrap();
@@ -13,24 +13,40 @@
 // limitations under the License.
 
 use super::*;
-use snarkos_node_router::messages::{
-    BlockRequest,
-    BlockResponse,
-    DataBlocks,
-    DisconnectReason,
-    Message,
-    MessageCodec,
-    Ping,
-    Pong,
-    UnconfirmedTransaction,
+use snarkos_node_router::{
+    messages::{
+        BlockByHashRequest,
+        BlockByHashResponse,
+        BlockRequest,
+        BlockResponse,
+        BlocksByHash,
+        DataBlocks,
+        DisconnectReason,
+        Message,
+        MessageCodec,
+        Ping,
+        Pong,
+        RejectReason,
+        RejectedItem,
+        UnconfirmedTransaction,
+    },
+    now_unix_millis,
+    queue_policy,
+    traffic_class,
+    Offense,
+    SyncStatus,
 };
-use snarkos_node_tcp::{Connection, ConnectionSide, Tcp};
+use snarkos_node_tcp::{Connection, ConnectionSide, QueuePolicy, Tcp, TrafficClass};
 use snarkvm::{
-    ledger::narwhal::Data,
+    ledger::{
+        narwhal::{Data, TransmissionID},
+        puzzle::SolutionID,
+    },
     prelude::{block::Transaction, error, Network},
 };
 
-use std::{io, net::SocketAddr, time::Duration};
+use snarkos_node_sync::{communication_service::CommunicationService, ImplausibleHeight};
+use std::{borrow::Cow, io, net::SocketAddr};
 
 impl<N: Network, C: ConsensusStorage<N>> P2P for Validator<N, C> {
     /// Returns a reference to the TCP instance.
@@ -93,8 +109,23 @@ impl<N: Network, C: ConsensusStorage<N>> Writing for Validator<N, C> {
 
     /// Creates an [`Encoder`] used to write the outbound messages to the target stream.
     /// The `side` parameter indicates the connection side **from the node's perspective**.
-    fn codec(&self, _addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
-        Default::default()
+    fn codec(&self, addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
+        self.router().message_codec(addr)
+    }
+
+    /// Returns the traffic class of the given outbound message, which determines its share of the outbound bandwidth.
+    fn traffic_class(&self, message: &Self::Message) -> TrafficClass {
+        traffic_class(message)
+    }
+
+    /// Returns the queue policy of the given outbound message, which determines whether it can be dropped.
+    fn queue_policy(&self, message: &Self::Message) -> QueuePolicy {
+        queue_policy(message)
+    }
+
+    /// Returns the type of the given outbound message, under which it is counted if it is dropped.
+    fn message_type(&self, message: &Self::Message) -> Cow<'static, str> {
+        message.name()
     }
 }
 
@@ -105,8 +136,8 @@ impl<N: Network, C: ConsensusStorage<N>> Reading for Validator<N, C> {
 
     /// Creates a [`Decoder`] used to interpret messages from the network.
     /// The `side` param indicates the connection side **from the node's perspective**.
-    fn codec(&self, _peer_addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
-        Default::default()
+    fn codec(&self, peer_addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
+        self.router().message_codec(peer_addr)
     }
 
     /// Processes a message received from the network.
@@ -117,7 +148,7 @@ impl<N: Network, C: ConsensusStorage<N>> Reading for Validator<N, C> {
                 warn!("Disconnecting from '{peer_ip}' - {error}");
                 Outbound::send(self, peer_ip, Message::Disconnect(DisconnectReason::ProtocolViolation.into()));
                 // Disconnect from this peer.
-                self.router().disconnect(peer_ip);
+                self.router().disconnect_with_reason(peer_ip, error);
             }
         }
         Ok(())
@@ -125,11 +156,49 @@ impl<N: Network, C: ConsensusStorage<N>> Reading for Validator<N, C> {
 }
 
 #[async_trait]
+#[async_trait]
+impl<N: Network, C: ConsensusStorage<N>> CommunicationService for Validator<N, C> {
+    /// The message type.
+    type Message = Message<N>;
+
+    /// Prepares a block request to be sent.
+    fn prepare_block_request(start_height: u32, end_height: u32) -> Self::Message {
+        debug_assert!(start_height < end_height, "Invalid block request format");
+        Message::BlockRequest(BlockRequest { start_height, end_height })
+    }
+
+    /// Sends the given message to specified peer.
+    ///
+    /// This function returns as soon as the message is queued to be sent,
+    /// without waiting for the actual delivery; instead, the caller is provided with a [`oneshot::Receiver`]
+    /// which can be used to determine when and whether the message has been delivered.
+    async fn send(
+        &self,
+        peer_ip: SocketAddr,
+        message: Self::Message,
+    ) -> Option<tokio::sync::oneshot::Receiver<io::Result<()>>> {
+        Outbound::send(self, peer_ip, message)
+    }
+
+    /// Returns `true` if the outbound queue of the specified peer is full.
+    fn is_congested(&self, peer_ip: SocketAddr) -> bool {
+        self.router().is_congested(&peer_ip)
+    }
+}
+
 impl<N: Network, C: ConsensusStorage<N>> Routing<N> for Validator<N, C> {}
 
 impl<N: Network, C: ConsensusStorage<N>> Heartbeat<N> for Validator<N, C> {
     /// The maximum number of peers permitted to maintain connections with.
     const MAXIMUM_NUMBER_OF_PEERS: usize = 200;
+
+    /// Returns the sync status of the node.
+    fn sync_status(&self) -> SyncStatus {
+        match self.sync.is_block_synced() {
+            true => SyncStatus::Synced,
+            false => SyncStatus::Syncing,
+        }
+    }
 }
 
 impl<N: Network, C: ConsensusStorage<N>> Outbound<N> for Validator<N, C> {
@@ -141,6 +210,20 @@ impl<N: Network, C: ConsensusStorage<N>> Outbound<N> for Validator<N, C> {
 
 #[async_trait]
 impl<N: Network, C: ConsensusStorage<N>> Inbound<N> for Validator<N, C> {
+    /// Retrieves the blocks for the requested hashes, and returns the block-by-hash response to the peer.
+    fn block_by_hash_request(&self, peer_ip: SocketAddr, message: BlockByHashRequest<N>) -> bool {
+        // Retrieve the blocks for the requested hashes, where `None` marks an unknown hash.
+        let blocks = message
+            .block_hashes
+            .iter()
+            .map(|block_hash| self.ledger.get_height(block_hash).and_then(|height| self.ledger.get_block(height)).ok())
+            .collect();
+        // Send the `BlockByHashResponse` message to the peer.
+        let response = BlockByHashResponse { request: message, blocks: Data::Object(BlocksByHash(blocks)) };
+        Outbound::send(self, peer_ip, Message::BlockByHashResponse(response));
+        true
+    }
+
     /// Retrieves the blocks within the block request range, and returns the block response to the peer.
     fn block_request(&self, peer_ip: SocketAddr, message: BlockRequest) -> bool {
         let BlockRequest { start_height, end_height } = &message;
@@ -163,13 +246,20 @@ impl<N: Network, C: ConsensusStorage<N>> Inbound<N> for Validator<N, C> {
         // Tries to advance with blocks from the sync module.
         match self.sync.advance_with_sync_blocks(peer_ip, blocks) {
             Ok(()) => true,
+            // The block response was rejected as a whole, so penalize the peer.
             Err(error) => {
                 warn!("{error}");
+                self.router.record_misbehavior(peer_ip, Offense::MismatchedResponse);
                 false
             }
         }
     }
 
+    /// Resets the block request of a dropped block response, so that the blocks are requested again.
+    fn block_response_dropped(&self, _peer_ip: SocketAddr, request: BlockRequest) {
+        self.sync.reset_block_requests(request.start_height..request.end_height);
+    }
+
     /// Processes the block locators and sends back a `Pong` message.
     fn ping(&self, peer_ip: SocketAddr, message: Ping<N>) -> bool {
         // Check if the sync module is in router mode.
@@ -178,14 +268,25 @@ impl<N: Network, C: ConsensusStorage<N>> Inbound<N> for Validator<N, C> {
             if let Some(block_locators) = message.block_locators {
                 // Check the block locators are valid, and update the peer in the sync pool.
                 if let Err(error) = self.sync.update_peer_locators(peer_ip, block_locators) {
-                    warn!("Peer '{peer_ip}' sent invalid block locators: {error}");
-                    return false;
+                    // A peer advertising an implausible height is penalized and excluded from sync,
+                    // but is not disconnected, as it may have been misled by another peer.
+                    if error.is::<ImplausibleHeight>() {
+                        warn!("Peer '{peer_ip}' sent implausible block locators: {error}");
+                        self.router.record_misbehavior(peer_ip, Offense::ImplausibleBlockHeight);
+                    } else {
+                        warn!("Peer '{peer_ip}' sent invalid block locators: {error}");
+                        return false;
+                    }
                 }
             }
         }
 
+        // Echo the timestamp of the ping, if it was provided, so the peer can estimate its clock skew.
+        let timestamps = message.timestamp.map(|timestamp| (timestamp, now_unix_millis()));
+        // Report whether the peer is on a fork of the canonical chain.
+        let is_fork = Some(self.sync.is_peer_on_fork(&peer_ip));
         // Send a `Pong` message to the peer.
-        Outbound::send(self, peer_ip, Message::Pong(Pong { is_fork: Some(false) }));
+        Outbound::send(self, peer_ip, Message::Pong(Pong { is_fork, timestamps }));
         true
     }
 
@@ -194,8 +295,8 @@ impl<N: Network, C: ConsensusStorage<N>> Inbound<N> for Validator<N, C> {
         // Spawn an asynchronous task for the `Ping` request.
         let self_ = self.clone();
         tokio::spawn(async move {
-            // Sleep for the preset time before sending a `Ping` request.
-            tokio::time::sleep(Duration::from_secs(Self::PING_SLEEP_IN_SECS)).await;
+            // Sleep for the ping interval before sending a `Ping` request.
+            tokio::time::sleep(self_.ping_interval()).await;
             // Check that the peer is still connected.
             if self_.router().is_connected(&peer_ip) {
                 // Retrieve the block locators.
@@ -232,6 +333,16 @@ impl<N: Network, C: ConsensusStorage<N>> Inbound<N> for Validator<N, C> {
         false
     }
 
+    /// Returns the height of the block that confirmed the given solution, if it is in the replay filter.
+    fn confirmed_solution_height(&self, solution_id: &SolutionID<N>) -> Option<u32> {
+        self.replay_filter.confirmed_height(&TransmissionID::from(*solution_id))
+    }
+
+    /// Returns the height of the block that confirmed the given transaction, if it is in the replay filter.
+    fn confirmed_transaction_height(&self, transaction_id: &N::TransactionID) -> Option<u32> {
+        self.replay_filter.confirmed_height(&TransmissionID::from(transaction_id))
+    }
+
     /// Propagates the unconfirmed solution to all connected validators.
     async fn unconfirmed_solution(
         &self,
@@ -239,6 +350,12 @@ impl<N: Network, C: ConsensusStorage<N>> Inbound<N> for Validator<N, C> {
         serialized: UnconfirmedSolution<N>,
         solution: Solution<N>,
     ) -> bool {
+        // Ensure the node has sufficient disk space to accept the solution.
+        if let Err(error) = self.consensus.ensure_disk_space_for_intake() {
+            let item = RejectedItem::Solution(serialized.solution_id);
+            self.send_reject(peer_ip, item, RejectReason::TemporarilyFull, &error.to_string());
+            return true; // Maintain the connection.
+        }
         // Add the unconfirmed solution to the memory pool.
         if let Err(error) = self.consensus.add_unconfirmed_solution(solution).await {
             trace!("[UnconfirmedSolution] {error}");
@@ -257,6 +374,24 @@ impl<N: Network, C: ConsensusStorage<N>> Inbound<N> for Validator<N, C> {
         serialized: UnconfirmedTransaction<N>,
         transaction: Transaction<N>,
     ) -> bool {
+        // Ensure the node has sufficient disk space to accept the transaction.
+        if let Err(error) = self.consensus.ensure_disk_space_for_intake() {
+            let item = RejectedItem::Transaction(serialized.transaction_id);
+            self.send_reject(peer_ip, item, RejectReason::TemporarilyFull, &error.to_string());
+            return true; // Maintain the connection.
+        }
+        // Ensure the transaction is not a fee transaction, which is never gossiped on its own.
+        if transaction.is_fee() {
+            let item = RejectedItem::Transaction(serialized.transaction_id);
+            self.send_reject(peer_ip, item, RejectReason::Invalid, "Fee transactions are not accepted");
+            return true; // Maintain the connection.
+        }
+        // Ensure the transaction does not reference a denylisted program.
+        if let Err(error) = self.consensus.program_denylist().ensure_allowed(&transaction) {
+            let item = RejectedItem::Transaction(serialized.transaction_id);
+            self.send_reject(peer_ip, item, RejectReason::Invalid, &error.to_string());
+            return true; // Maintain the connection.
+        }
         // Add the unconfirmed transaction to the memory pool.
         if let Err(error) = self.consensus.add_unconfirmed_transaction(transaction).await {
             trace!("[UnconfirmedTransaction] {error}");
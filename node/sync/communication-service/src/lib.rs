@@ -34,4 +34,10 @@ pub trait CommunicationService: Send + Sync {
     /// without waiting for the actual delivery; instead, the caller is provided with a [`oneshot::Receiver`]
     /// which can be used to determine when and whether the message has been delivered.
     async fn send(&self, peer_ip: SocketAddr, message: Self::Message) -> Option<oneshot::Receiver<io::Result<()>>>;
+
+    /// Returns `true` if the outbound queue of the specified peer is full, in which case
+    /// the peer is not selected for new requests until its queue drains.
+    fn is_congested(&self, _peer_ip: SocketAddr) -> bool {
+        false
+    }
 }
@@ -25,6 +25,12 @@ pub const NUM_RECENT_BLOCKS: usize = 100; // 100 blocks
 const RECENT_INTERVAL: u32 = 1; // 1 block intervals
 /// The interval between block checkpoints.
 pub const CHECKPOINT_INTERVAL: u32 = 10_000; // 10,000 block intervals
+/// The maximum number of block checkpoints, i.e. one for every checkpoint interval up to the maximum block height.
+pub const MAXIMUM_NUMBER_OF_CHECKPOINTS: usize = (u32::MAX / CHECKPOINT_INTERVAL) as usize + 1;
+/// The minimum number of blocks that may be requested in a single block request.
+pub const MINIMUM_BLOCKS_PER_REQUEST: u32 = 1; // 1 block
+/// The maximum number of blocks that may be requested (and served) in a single block request.
+pub const MAXIMUM_BLOCKS_PER_REQUEST: u32 = 5; // 5 blocks
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct BlockLocators<N: Network> {
@@ -263,6 +269,10 @@ impl<N: Network> FromBytes for BlockLocators<N> {
     fn read_le<R: Read>(mut reader: R) -> IoResult<Self> {
         // Read the number of recent block hashes.
         let num_recents = u32::read_le(&mut reader)?;
+        // Ensure the number of recent block hashes is within the bounds, before reading them.
+        if num_recents as usize > NUM_RECENT_BLOCKS {
+            return Err(error(format!("Too many recent block hashes ({num_recents})")));
+        }
         // Read the recent block hashes.
         let mut recents = IndexMap::new();
         for _ in 0..num_recents {
@@ -273,6 +283,10 @@ impl<N: Network> FromBytes for BlockLocators<N> {
 
         // Read the number of checkpoints.
         let num_checkpoints = u32::read_le(&mut reader)?;
+        // Ensure the number of checkpoints is within the bounds, before reading them.
+        if num_checkpoints as usize > MAXIMUM_NUMBER_OF_CHECKPOINTS {
+            return Err(error(format!("Too many block checkpoints ({num_checkpoints})")));
+        }
         // Read the checkpoints.
         let mut checkpoints = IndexMap::new();
         for _ in 0..num_checkpoints {
@@ -594,4 +608,24 @@ mod tests {
         second_locators.ensure_is_consistent_with(&wrong_second_locators).unwrap_err();
         wrong_second_locators.ensure_is_consistent_with(&second_locators).unwrap_err();
     }
+
+    #[test]
+    fn test_read_le_rejects_forged_counts() {
+        let locators = test_helpers::sample_block_locators(25_000);
+        let bytes = locators.to_bytes_le().unwrap();
+        assert_eq!(BlockLocators::<CurrentNetwork>::read_le(&bytes[..]).unwrap(), locators);
+
+        // Check that a count of recent block hashes above the maximum is rejected.
+        for num_recents in [NUM_RECENT_BLOCKS as u32 + 1, u32::MAX] {
+            let error = BlockLocators::<CurrentNetwork>::read_le(&num_recents.to_le_bytes()[..]).unwrap_err();
+            assert!(error.to_string().contains("Too many recent block hashes"), "{error}");
+        }
+
+        // Check that a count of checkpoints above the maximum is rejected.
+        let checkpoints_offset = 4 + locators.recents.len() * (4 + 32);
+        let mut forged = bytes[..checkpoints_offset].to_vec();
+        forged.extend_from_slice(&(MAXIMUM_NUMBER_OF_CHECKPOINTS as u32 + 1).to_le_bytes());
+        let error = BlockLocators::<CurrentNetwork>::read_le(&forged[..]).unwrap_err();
+        assert!(error.to_string().contains("Too many block checkpoints"), "{error}");
+    }
 }
@@ -13,7 +13,16 @@
 // limitations under the License.
 
 use crate::{
-    helpers::{PeerPair, SyncRequest},
+    helpers::{
+        BlockRequestWindow,
+        PeerPair,
+        SyncProgress,
+        SyncReport,
+        SyncRequest,
+        VerificationLookahead,
+        VerificationPipeline,
+        DEFAULT_BLOCKS_PER_REQUEST,
+    },
     locators::BlockLocators,
 };
 use snarkos_node_bft_ledger_service::LedgerService;
@@ -28,12 +37,14 @@ use parking_lot::{Mutex, RwLock};
 use rand::{prelude::IteratorRandom, CryptoRng, Rng};
 use std::{
     collections::BTreeMap,
+    fmt,
     net::{IpAddr, Ipv4Addr, SocketAddr},
+    ops::Range,
     sync::{
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicU32, Ordering},
         Arc,
     },
-    time::Instant,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 #[cfg(not(test))]
@@ -50,6 +61,12 @@ const MAX_BLOCK_REQUEST_TIMEOUTS: usize = 5; // 5 timeouts
 /// The maximum number of blocks tolerated before the primary is considered behind its peers.
 pub const MAX_BLOCKS_BEHIND: u32 = 1; // blocks
 
+/// The default multiplier on the number of blocks that could have been produced since the latest block,
+/// within which a peer may claim to be ahead of this node.
+pub const DEFAULT_HEIGHT_PLAUSIBILITY_MULTIPLIER: u32 = 2;
+/// The number of blocks that a peer may claim to be ahead of this node, regardless of the time since the latest block.
+const MINIMUM_PLAUSIBLE_LEAD: u32 = 1_000; // blocks
+
 /// This is a dummy IP address that is used to represent the local node.
 /// Note: This here does not need to be a real IP address, but it must be unique/distinct from all other connections.
 const DUMMY_SELF_IP: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 0);
@@ -72,6 +89,27 @@ impl BlockSyncMode {
     }
 }
 
+/// The height, hash, and previous hash of a block in a block response.
+type BlockLink<N> = (u32, <N as Network>::BlockHash, <N as Network>::BlockHash);
+
+/// The error returned when a peer advertises a block height that cannot have been produced
+/// in the time since the latest block of this node.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ImplausibleHeight {
+    /// The block height advertised by the peer.
+    pub height: u32,
+    /// The greatest block height that a peer can plausibly advertise.
+    pub maximum_height: u32,
+}
+
+impl fmt::Display for ImplausibleHeight {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Block height {} is implausible (at most {} is plausible)", self.height, self.maximum_height)
+    }
+}
+
+impl std::error::Error for ImplausibleHeight {}
+
 /// A struct that keeps track of the current block sync state.
 ///
 /// # State
@@ -92,6 +130,8 @@ pub struct BlockSync<N: Network> {
     /// The map of peer IP to their block locators.
     /// The block locators are consistent with the canonical map and every other peer's block locators.
     locators: Arc<RwLock<IndexMap<SocketAddr, BlockLocators<N>>>>,
+    /// The set of peer IPs whose block locators conflict with the canonical chain.
+    forked_peers: Arc<RwLock<IndexSet<SocketAddr>>>,
     /// The map of peer-to-peer to their common ancestor.
     /// This map is used to determine which peers to request blocks from.
     common_ancestors: Arc<RwLock<IndexMap<PeerPair, u32>>>,
@@ -107,8 +147,25 @@ pub struct BlockSync<N: Network> {
     /// The map of (timed out) peer IPs to their request timestamps.
     /// This map is used to determine which peers to remove if they have timed out too many times.
     request_timeouts: Arc<RwLock<IndexMap<SocketAddr, Vec<Instant>>>>,
+    /// The set of peer IPs whose outbound queues were full as of the last sync iteration.
+    /// These peers are not selected for new block requests until their queues drain.
+    congested_peers: Arc<RwLock<IndexSet<SocketAddr>>>,
+    /// The number of blocks per block request that a peer starts with.
+    default_block_request_size: u32,
+    /// The map of peer IPs to their block request windows.
+    /// This map is used to determine how many blocks to request from each peer in a single block request.
+    block_request_windows: Arc<RwLock<IndexMap<SocketAddr, BlockRequestWindow>>>,
+    /// The bounds on the blocks that are checked ahead of the block being applied.
+    verification_lookahead: VerificationLookahead,
+    /// The multiplier on the number of blocks that could have been produced since the latest block,
+    /// within which a peer may claim to be ahead of this node.
+    height_plausibility_multiplier: u32,
     /// The boolean indicator of whether the node is synced up to the latest block (within the given tolerance).
     is_block_synced: Arc<AtomicBool>,
+    /// The number of blocks the node is behind the greatest peer height, as of the last sync update.
+    num_blocks_behind: Arc<AtomicU32>,
+    /// The sync progress of the node, including the rate at which blocks are applied.
+    progress: Arc<SyncProgress>,
     /// The lock to guarantee advance_with_sync_blocks() is called only once at a time.
     advance_with_sync_blocks_lock: Arc<Mutex<()>>,
 }
@@ -120,27 +177,86 @@ impl<N: Network> BlockSync<N> {
             mode,
             canon: ledger,
             locators: Default::default(),
+            forked_peers: Default::default(),
             common_ancestors: Default::default(),
             requests: Default::default(),
             responses: Default::default(),
             request_timestamps: Default::default(),
             request_timeouts: Default::default(),
+            congested_peers: Default::default(),
+            default_block_request_size: DEFAULT_BLOCKS_PER_REQUEST,
+            block_request_windows: Default::default(),
+            verification_lookahead: Default::default(),
+            height_plausibility_multiplier: DEFAULT_HEIGHT_PLAUSIBILITY_MULTIPLIER,
             is_block_synced: Default::default(),
+            num_blocks_behind: Default::default(),
+            progress: Default::default(),
             advance_with_sync_blocks_lock: Default::default(),
         }
     }
 
+    /// Sets the number of blocks per block request that each peer starts with,
+    /// bounded by the minimum and maximum number of blocks per block request.
+    pub fn with_block_request_size(mut self, size: u32) -> Self {
+        self.default_block_request_size = BlockRequestWindow::new(size).size();
+        self
+    }
+
+    /// Sets the bounds on the blocks that are checked ahead of the block being applied.
+    pub fn with_verification_lookahead(mut self, lookahead: VerificationLookahead) -> Self {
+        self.verification_lookahead = lookahead;
+        self
+    }
+
+    /// Sets the multiplier on the number of blocks that could have been produced since the latest block,
+    /// within which a peer may claim to be ahead of this node.
+    pub fn with_height_plausibility_multiplier(mut self, multiplier: u32) -> Self {
+        self.height_plausibility_multiplier = multiplier;
+        self
+    }
+
     /// Returns the block sync mode.
     #[inline]
     pub const fn mode(&self) -> BlockSyncMode {
         self.mode
     }
 
+    /// Returns the bounds on the blocks that are checked ahead of the block being applied.
+    #[inline]
+    pub const fn verification_lookahead(&self) -> VerificationLookahead {
+        self.verification_lookahead
+    }
+
     /// Returns `true` if the node is synced up to the latest block (within the given tolerance).
     #[inline]
     pub fn is_block_synced(&self) -> bool {
         self.is_block_synced.load(Ordering::SeqCst)
     }
+
+    /// Returns the number of blocks the node is behind the greatest peer height, as of the last sync update.
+    #[inline]
+    pub fn num_blocks_behind(&self) -> u32 {
+        self.num_blocks_behind.load(Ordering::Relaxed)
+    }
+
+    /// Returns the sync report of the node, as of the last sync update.
+    pub fn sync_report(&self) -> SyncReport {
+        self.progress.report(self.canon.latest_block_height(), self.is_block_synced())
+    }
+
+    /// Limits the number of blocks per block request to the given peer, e.g. if it predates larger block responses.
+    pub fn set_block_request_limit(&self, peer_ip: SocketAddr, limit: u32) {
+        let mut block_request_windows = self.block_request_windows.write();
+        let window = block_request_windows
+            .entry(peer_ip)
+            .or_insert_with(|| BlockRequestWindow::new(self.default_block_request_size));
+        *window = window.with_limit(limit);
+    }
+
+    /// Returns the current number of blocks per block request for each peer with a block request window.
+    pub fn block_request_sizes(&self) -> IndexMap<SocketAddr, u32> {
+        self.block_request_windows.read().iter().map(|(peer_ip, window)| (*peer_ip, window.size())).collect()
+    }
 }
 
 #[allow(dead_code)]
@@ -186,6 +302,28 @@ impl<N: Network> BlockSync<N> {
     fn get_block_request_timestamp(&self, height: u32) -> Option<Instant> {
         self.request_timestamps.read().get(&height).copied()
     }
+
+    /// Returns the hash of the block preceding the given height, if it is known from the block requests,
+    /// or from the canonical ledger.
+    fn get_expected_previous_hash(&self, height: u32) -> Option<N::BlockHash> {
+        let previous_height = height.checked_sub(1)?;
+        {
+            let requests = self.requests.read();
+            // Retrieve the previous hash from the block request for the given height.
+            if let Some((_, Some(previous_hash), _)) = requests.get(&height) {
+                return Some(*previous_hash);
+            }
+            // Retrieve the hash from the block request for the previous height.
+            if let Some((Some(hash), _, _)) = requests.get(&previous_height) {
+                return Some(*hash);
+            }
+        }
+        // Retrieve the hash from the canonical ledger.
+        match self.canon.contains_block_height(previous_height) {
+            true => self.canon.get_block_hash(previous_height).ok(),
+            false => None,
+        }
+    }
 }
 
 impl<N: Network> BlockSync<N> {
@@ -216,6 +354,11 @@ impl<N: Network> BlockSync<N> {
     /// Performs one iteration of the block sync.
     #[inline]
     pub async fn try_block_sync<C: CommunicationService>(&self, communication: &C) {
+        // Determine the congested peers, which are not selected for new block requests.
+        let congested_peers =
+            self.locators.read().keys().copied().filter(|peer_ip| communication.is_congested(*peer_ip)).collect();
+        self.set_congested_peers(congested_peers);
+
         // Prepare the block requests, if any.
         // In the process, we update the state of `is_block_synced` for the sync module.
         let block_requests = self.prepare_block_requests();
@@ -235,27 +378,36 @@ impl<N: Network> BlockSync<N> {
             return;
         }
 
-        // Process the block requests.
-        'outer: for (height, (hash, previous_hash, sync_ips)) in block_requests {
-            // Insert the block request into the sync pool.
-            if let Err(error) = self.insert_block_request(height, (hash, previous_hash, sync_ips.clone())) {
-                warn!("Block sync failed - {error}");
-                // Break out of the loop.
-                break 'outer;
+        // Process the block requests, in batches of consecutive heights to the same sync peers.
+        'outer: for batch in self.batch_block_requests(block_requests) {
+            // Retrieve the height range and the sync peers of the batch.
+            let (start_height, (_, _, sync_ips)) = &batch[0];
+            let (start_height, sync_ips) = (*start_height, sync_ips.clone());
+            let end_height = start_height + batch.len() as u32;
+
+            // Insert the block requests into the sync pool.
+            for (height, request) in batch {
+                if let Err(error) = self.insert_block_request(height, request) {
+                    warn!("Block sync failed - {error}");
+                    // Remove the block requests of the batch that were already inserted.
+                    self.reset_block_requests(start_height..height);
+                    // Break out of the loop.
+                    break 'outer;
+                }
             }
 
             /* Send the block request to the peers */
 
             // Construct the message.
-            let message = C::prepare_block_request(height, height + 1);
+            let message = C::prepare_block_request(start_height, end_height);
             // Send the message to the peers.
             for sync_ip in sync_ips {
                 let sender = communication.send(sync_ip, message.clone()).await;
-                // If the send fails for any peer, remove the block request from the sync pool.
+                // If the send fails for any peer, remove the block requests from the sync pool.
                 if sender.is_none() {
                     warn!("Failed to send block request to peer '{sync_ip}'");
-                    // Remove the entire block request from the sync pool.
-                    self.remove_block_request(height);
+                    // Remove the entire batch of block requests from the sync pool.
+                    self.reset_block_requests(start_height..end_height);
                     // Break out of the loop.
                     break 'outer;
                 }
@@ -265,15 +417,42 @@ impl<N: Network> BlockSync<N> {
         }
     }
 
+    /// Checks that the blocks in a block response from the given peer were requested from the peer,
+    /// are contiguous, and connect to the block preceding the response, if it is known.
+    /// This check is cheap, so an invalid block response is rejected as a whole before any block is verified.
+    pub fn check_block_responses(&self, peer_ip: &SocketAddr, blocks: &[Block<N>]) -> Result<()> {
+        let links: Vec<_> = blocks.iter().map(|block| (block.height(), block.hash(), block.previous_hash())).collect();
+        self.check_block_response_links(peer_ip, &links)
+    }
+
     /// Processes the block response from the given peer IP.
+    /// On failure, this function removes all block requests from the given peer IP.
     #[inline]
     pub fn process_block_response(&self, peer_ip: SocketAddr, blocks: Vec<Block<N>>) -> Result<()> {
+        // Ensure the block response is valid as a whole, before inserting any of its blocks.
+        if let Err(error) = self.check_block_responses(&peer_ip, &blocks) {
+            // Remove all block requests to the peer.
+            self.remove_block_requests_to_peer(&peer_ip);
+            return Err(error);
+        }
+
+        // Retrieve the time elapsed since the blocks were requested.
+        let latency =
+            blocks.first().and_then(|block| self.get_block_request_timestamp(block.height())).map(|t| t.elapsed());
+        // Retrieve the number of blocks in the response.
+        let num_blocks = blocks.len() as u32;
+
         // Insert the candidate blocks into the sync pool.
         for block in blocks {
             if let Err(error) = self.insert_block_response(peer_ip, block) {
                 bail!("{error}");
             }
         }
+
+        // Adjust the block request window of the peer to the latency of the response.
+        if let Some(latency) = latency {
+            self.update_block_request_window(peer_ip, num_blocks, latency);
+        }
         Ok(())
     }
 
@@ -304,26 +483,53 @@ impl<N: Network> BlockSync<N> {
         Ok(())
     }
 
+    /// Removes the block requests for the given heights, so that they are requested again in a later sync round.
+    pub fn reset_block_requests(&self, heights: Range<u32>) {
+        heights.for_each(|height| self.remove_block_request(height));
+    }
+
+    /// Attempts to advance with the given block, as announced by a peer.
+    /// Returns `true` if the ledger advanced to the given block.
+    pub fn advance_with_announced_block(&self, block: &Block<N>) -> Result<bool> {
+        // Acquire the lock to ensure the ledger is not concurrently advanced with the sync pool.
+        // If the lock is already acquired, return early.
+        let Some(_lock) = self.advance_with_sync_blocks_lock.try_lock() else {
+            trace!("Skipping announced block {} as advance_with_sync_blocks() is in progress", block.height());
+            return Ok(false);
+        };
+
+        // Ensure the block is the next block.
+        if block.height() != self.canon.latest_block_height() + 1 {
+            return Ok(false);
+        }
+        // Check the next block.
+        self.canon.check_next_block(block)?;
+        // Attempt to advance to the next block.
+        self.canon.advance_to_next_block(block)?;
+        // Record the new block height.
+        self.progress.record_height(block.height());
+        // Remove the block request for the block, as it is no longer needed.
+        self.remove_block_request(block.height());
+        Ok(true)
+    }
+
     /// Handles the block responses from the sync pool.
-    fn try_advancing_with_block_responses(&self, mut current_height: u32) {
-        while let Some(block) = self.remove_block_response(current_height + 1) {
+    fn try_advancing_with_block_responses(&self, current_height: u32) {
+        // Check the upcoming blocks ahead of time, while each block is applied in order.
+        let mut pipeline = VerificationPipeline::new(self.canon.clone(), self.verification_lookahead);
+        let mut next_height = current_height + 1;
+        let next_block = || {
+            let block = self.remove_block_response(next_height)?;
             // Ensure the block height matches.
-            if block.height() != current_height + 1 {
-                warn!("Block height mismatch: expected {}, found {}", current_height + 1, block.height());
-                break;
-            }
-            // Check the next block.
-            if let Err(error) = self.canon.check_next_block(&block) {
-                warn!("The next block ({}) is invalid - {error}", block.height());
-                break;
+            if block.height() != next_height {
+                warn!("Block height mismatch: expected {next_height}, found {}", block.height());
+                return None;
             }
-            // Attempt to advance to the next block.
-            if let Err(error) = self.canon.advance_to_next_block(&block) {
-                warn!("{error}");
-                break;
-            }
-            // Update the latest height.
-            current_height = self.canon.latest_block_height();
+            next_height += 1;
+            Some(block)
+        };
+        if let Err(error) = pipeline.advance_with(next_block, |block| self.progress.record_height(block.height())) {
+            warn!("The next block ({}) could not be applied - {error}", self.canon.latest_block_height() + 1);
         }
     }
 }
@@ -355,6 +561,20 @@ impl<N: Network> BlockSync<N> {
 
         // Ensure the given block locators are well-formed.
         locators.ensure_is_valid()?;
+        // Ensure the peer does not claim a height that cannot have been produced since the latest block.
+        if let Some(maximum_height) = self.maximum_plausible_height() {
+            let height = locators.latest_locator_height();
+            if height > maximum_height {
+                // Exclude the peer from sync, as it cannot serve the blocks it claims to have.
+                self.remove_peer(&peer_ip);
+                return Err(ImplausibleHeight { height, maximum_height }.into());
+            }
+        }
+        // Mark the peer as on a fork, if it advertises a block hash that conflicts with the canonical chain.
+        match self.conflicts_with_canon(&locators) {
+            true => self.forked_peers.write().insert(peer_ip),
+            false => self.forked_peers.write().swap_remove(&peer_ip),
+        };
         // Update the locators entry for the given peer IP.
         self.locators.write().insert(peer_ip, locators.clone());
 
@@ -400,10 +620,21 @@ impl<N: Network> BlockSync<N> {
     pub fn remove_peer(&self, peer_ip: &SocketAddr) {
         // Remove the locators entry for the given peer IP.
         self.locators.write().swap_remove(peer_ip);
+        // Remove the peer from the forked peers.
+        self.forked_peers.write().swap_remove(peer_ip);
         // Remove all block requests to the peer.
         self.remove_block_requests_to_peer(peer_ip);
         // Remove the timeouts for the peer.
         self.request_timeouts.write().swap_remove(peer_ip);
+        // Remove the block request window for the peer.
+        self.block_request_windows.write().swap_remove(peer_ip);
+        // Remove the peer from the congested peers.
+        self.congested_peers.write().swap_remove(peer_ip);
+    }
+
+    /// Sets the peers whose outbound queues are full, which are not selected for new block requests.
+    fn set_congested_peers(&self, congested_peers: IndexSet<SocketAddr>) {
+        *self.congested_peers.write() = congested_peers;
     }
 }
 
@@ -416,20 +647,22 @@ impl<N: Network> BlockSync<N> {
         if let Some((sync_peers, min_common_ancestor)) = self.find_sync_peers_inner() {
             // Retrieve the highest block height.
             let greatest_peer_height = sync_peers.values().map(|l| l.latest_locator_height()).max().unwrap_or(0);
+            // Count the peers at the highest block height.
+            let num_peers = sync_peers.values().filter(|l| l.latest_locator_height() == greatest_peer_height).count();
             // Update the state of `is_block_synced` for the sync module.
-            self.update_is_block_synced(greatest_peer_height, MAX_BLOCKS_BEHIND);
+            self.update_is_block_synced(greatest_peer_height, num_peers as u32, MAX_BLOCKS_BEHIND);
             // Return the list of block requests.
             self.construct_requests(sync_peers, min_common_ancestor, &mut rand::thread_rng())
         } else {
             // Update the state of `is_block_synced` for the sync module.
-            self.update_is_block_synced(0, MAX_BLOCKS_BEHIND);
+            self.update_is_block_synced(0, 0, MAX_BLOCKS_BEHIND);
             // Return an empty list of block requests.
             Vec::new()
         }
     }
 
     /// Updates the state of `is_block_synced` for the sync module.
-    fn update_is_block_synced(&self, greatest_peer_height: u32, max_blocks_behind: u32) {
+    fn update_is_block_synced(&self, greatest_peer_height: u32, num_peers: u32, max_blocks_behind: u32) {
         // Retrieve the latest block height.
         let canon_height = self.canon.latest_block_height();
         trace!(
@@ -441,6 +674,53 @@ impl<N: Network> BlockSync<N> {
         let is_synced = num_blocks_behind <= max_blocks_behind;
         // Update the sync status.
         self.is_block_synced.store(is_synced, Ordering::SeqCst);
+        self.num_blocks_behind.store(num_blocks_behind, Ordering::Relaxed);
+        // Update the sync progress.
+        self.progress.update_peer_height(greatest_peer_height, num_peers);
+        self.progress.record_height(canon_height);
+    }
+
+    /// Adjusts the block request window of the given peer for a block response of the given number of blocks.
+    fn update_block_request_window(&self, peer_ip: SocketAddr, num_blocks: u32, latency: Duration) {
+        self.block_request_windows
+            .write()
+            .entry(peer_ip)
+            .or_insert_with(|| BlockRequestWindow::new(self.default_block_request_size))
+            .on_response(num_blocks, latency);
+    }
+
+    /// Returns the number of blocks per block request to the given sync peers,
+    /// which is the smallest block request window among them.
+    fn get_block_request_size(&self, sync_ips: &IndexSet<SocketAddr>) -> u32 {
+        let block_request_windows = self.block_request_windows.read();
+        sync_ips
+            .iter()
+            .map(|peer_ip| block_request_windows.get(peer_ip).map(|window| window.size()))
+            .map(|size| size.unwrap_or(self.default_block_request_size))
+            .min()
+            .unwrap_or(self.default_block_request_size)
+    }
+
+    /// Groups the given block requests into batches of consecutive heights to the same sync peers,
+    /// such that each batch does not exceed the block request size of its sync peers.
+    fn batch_block_requests(&self, block_requests: Vec<(u32, SyncRequest<N>)>) -> Vec<Vec<(u32, SyncRequest<N>)>> {
+        let mut batches: Vec<Vec<(u32, SyncRequest<N>)>> = Vec::new();
+        for (height, request) in block_requests {
+            // Append the request to the last batch, if it continues the batch and the batch is not full.
+            if let Some(batch) = batches.last_mut() {
+                let (last_height, (_, _, last_sync_ips)) = &batch[batch.len() - 1];
+                if *last_height + 1 == height
+                    && *last_sync_ips == request.2
+                    && (batch.len() as u32) < self.get_block_request_size(&request.2)
+                {
+                    batch.push((height, request));
+                    continue;
+                }
+            }
+            // Otherwise, start a new batch.
+            batches.push(vec![(height, request)]);
+        }
+        batches
     }
 
     /// Inserts a block request for the given height.
@@ -544,6 +824,42 @@ impl<N: Network> BlockSync<N> {
         }
     }
 
+    /// Checks that the given links of a block response from the given peer were requested from the peer,
+    /// are contiguous, and connect to the block preceding the response, if it is known.
+    fn check_block_response_links(&self, peer_ip: &SocketAddr, links: &[BlockLink<N>]) -> Result<()> {
+        // Ensure the block response is not empty.
+        let Some((start_height, _, start_previous_hash)) = links.first() else {
+            bail!("Peer '{peer_ip}' sent an empty block response")
+        };
+        // Ensure the blocks are contiguous, with each block linking to the block before it.
+        for ((height, hash, _), (next_height, _, next_previous_hash)) in links.iter().tuple_windows() {
+            ensure!(
+                height.checked_add(1) == Some(*next_height),
+                "Peer '{peer_ip}' sent non-contiguous blocks (block {height} is followed by block {next_height})"
+            );
+            ensure!(hash == next_previous_hash, "Block {next_height} from '{peer_ip}' does not link to block {height}");
+        }
+        // Ensure every block was requested from the peer.
+        {
+            let requests = self.requests.read();
+            for (height, ..) in links {
+                match requests.get(height) {
+                    Some((_, _, sync_ips)) if sync_ips.contains(peer_ip) => (),
+                    _ => bail!("The sync pool did not request block {height} from '{peer_ip}'"),
+                }
+            }
+        }
+        // Ensure the first block connects to the block preceding it, if it is known.
+        if let Some(expected_previous_hash) = self.get_expected_previous_hash(*start_height) {
+            ensure!(
+                *start_previous_hash == expected_previous_hash,
+                "Block {start_height} from '{peer_ip}' does not connect to block {}",
+                start_height - 1
+            );
+        }
+        Ok(())
+    }
+
     /// Removes the entire block request for the given height, if it exists.
     fn remove_block_request(&self, height: u32) {
         // Remove the request entry for the given height.
@@ -663,15 +979,48 @@ impl<N: Network> BlockSync<N> {
         if !timeout_ips.is_empty() {
             // Acquire the write lock on the request timeouts map.
             let mut request_timeouts = self.request_timeouts.write();
-            // Add each timeout IP to the request timeouts map.
+            // Acquire the write lock on the block request windows map.
+            let mut block_request_windows = self.block_request_windows.write();
+            // Add each timeout IP to the request timeouts map, and shrink its block request window.
             for timeout_ip in timeout_ips {
                 request_timeouts.entry(timeout_ip).or_default().push(now);
+                block_request_windows
+                    .entry(timeout_ip)
+                    .or_insert_with(|| BlockRequestWindow::new(self.default_block_request_size))
+                    .on_timeout();
             }
         }
 
         num_timed_out_block_requests
     }
 
+    /// Returns `true` if the given peer advertised a block hash that conflicts with the canonical chain.
+    pub fn is_peer_on_fork(&self, peer_ip: &SocketAddr) -> bool {
+        self.forked_peers.read().contains(peer_ip)
+    }
+
+    /// Returns the greatest block height that a peer can plausibly advertise, given the time since the latest block,
+    /// or `None` if the timestamp of the latest block is unknown.
+    fn maximum_plausible_height(&self) -> Option<u32> {
+        let latest_timestamp = self.canon.latest_block_timestamp()?;
+        // Compute the number of seconds since the latest block.
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs() as i64);
+        let elapsed = now.saturating_sub(latest_timestamp).max(0) as u64;
+        // Compute the number of blocks that could have been produced since the latest block.
+        let num_blocks =
+            (elapsed / u64::from(N::BLOCK_TIME).max(1)).saturating_mul(self.height_plausibility_multiplier as u64);
+        let lead = MINIMUM_PLAUSIBLE_LEAD.saturating_add(u32::try_from(num_blocks).unwrap_or(u32::MAX));
+        Some(self.canon.latest_block_height().saturating_add(lead))
+    }
+
+    /// Returns `true` if the given block locators advertise a block hash that conflicts with the canonical chain.
+    fn conflicts_with_canon(&self, locators: &BlockLocators<N>) -> bool {
+        locators.clone().into_iter().any(|(height, hash)| match self.canon.get_block_hash(height) {
+            Ok(canon_hash) => canon_hash != hash,
+            Err(_) => false,
+        })
+    }
+
     /// Returns the sync peers and their minimum common ancestor, if the node needs to sync.
     fn find_sync_peers_inner(&self) -> Option<(IndexMap<SocketAddr, BlockLocators<N>>, u32)> {
         // Retrieve the latest canon height.
@@ -684,6 +1033,8 @@ impl<N: Network> BlockSync<N> {
             .iter()
             .map(|(peer_ip, timestamps)| (*peer_ip, timestamps.len()))
             .collect::<IndexMap<_, _>>();
+        // Retrieve the congested peers.
+        let congested_peers = self.congested_peers.read().clone();
 
         // Pick a set of peers above the latest canon height, and include their locators.
         let candidate_locators: IndexMap<_, _> = self
@@ -692,6 +1043,7 @@ impl<N: Network> BlockSync<N> {
             .iter()
             .filter(|(_, locators)| locators.latest_locator_height() > latest_canon_height)
             .filter(|(ip, _)| timeouts.get(*ip).map(|count| *count < MAX_BLOCK_REQUEST_TIMEOUTS).unwrap_or(true))
+            .filter(|(ip, _)| !congested_peers.contains(*ip))
             .sorted_by(|(_, a), (_, b)| b.latest_locator_height().cmp(&a.latest_locator_height()))
             .take(NUM_SYNC_CANDIDATE_PEERS)
             .map(|(peer_ip, locators)| (*peer_ip, locators.clone()))
@@ -776,10 +1128,15 @@ impl<N: Network> BlockSync<N> {
         let end_height = (min_common_ancestor + 1).min(start_height + MAX_BLOCK_REQUESTS as u32);
 
         let mut requests = Vec::with_capacity((start_height..end_height).len());
+        // Track the sync IPs of the current batch, the number of sync IPs they were picked for,
+        // and the number of requests in the batch.
+        let mut batch: Option<(IndexSet<SocketAddr>, usize, u32)> = None;
 
         for height in start_height..end_height {
             // Ensure the current height is not canonized or already requested.
             if self.check_block_request(height).is_err() {
+                // As the heights are no longer consecutive, end the current batch.
+                batch = None;
                 continue;
             }
 
@@ -796,11 +1153,24 @@ impl<N: Network> BlockSync<N> {
                 }
             }
 
-            // Pick the sync peers.
-            let sync_ips = sync_peers.keys().copied().choose_multiple(rng, num_sync_ips);
+            // Pick the sync peers, reusing the sync peers of the current batch until it fills their block request size.
+            let sync_ips = match &mut batch {
+                Some((sync_ips, num, num_requests))
+                    if is_honest && *num == num_sync_ips && *num_requests < self.get_block_request_size(sync_ips) =>
+                {
+                    *num_requests += 1;
+                    sync_ips.clone()
+                }
+                _ => {
+                    let sync_ips: IndexSet<_> =
+                        sync_peers.keys().copied().choose_multiple(rng, num_sync_ips).into_iter().collect();
+                    batch = Some((sync_ips.clone(), num_sync_ips, 1));
+                    sync_ips
+                }
+            };
 
             // Append the request.
-            requests.push((height, (hash, previous_hash, sync_ips.into_iter().collect())));
+            requests.push((height, (hash, previous_hash, sync_ips)));
         }
 
         requests
@@ -885,6 +1255,8 @@ mod tests {
     use crate::locators::{
         test_helpers::{sample_block_locators, sample_block_locators_with_fork},
         CHECKPOINT_INTERVAL,
+        MAXIMUM_BLOCKS_PER_REQUEST,
+        MINIMUM_BLOCKS_PER_REQUEST,
         NUM_RECENT_BLOCKS,
     };
     use snarkos_node_bft_ledger_service::MockLedgerService;
@@ -1012,7 +1384,42 @@ mod tests {
             }
 
             // If all peers are ahead, then requests should be prepared.
-            check_prepare_block_requests(sync, 10, peers);
+            check_prepare_block_requests(sync.clone(), 10, peers);
+            // Check that the number of blocks behind reflects the greatest peer height.
+            assert_eq!(sync.num_blocks_behind(), if num_peers > 0 { 10 } else { 0 });
+            // Check that the sync report reflects the peers at the greatest peer height.
+            let report = sync.sync_report();
+            assert_eq!(report.current_height, 0);
+            assert_eq!(report.peer_height, if num_peers > 0 { Some(10) } else { None });
+            assert_eq!(report.num_peers_at_peer_height, num_peers as u32);
+            assert_eq!(report.num_blocks_remaining, if num_peers > 0 { 10 } else { 0 });
+        }
+    }
+
+    #[test]
+    fn test_prepare_block_requests_skips_congested_peers() {
+        let sync = sample_sync_at_height(0);
+        sync.update_peer_locators(sample_peer_ip(1), sample_block_locators(10)).unwrap();
+        sync.update_peer_locators(sample_peer_ip(2), sample_block_locators(10)).unwrap();
+
+        // If a peer is congested, then the requests should only be sent to the other peer.
+        sync.set_congested_peers(indexset![sample_peer_ip(1)]);
+        let requests = sync.prepare_block_requests();
+        assert_eq!(requests.len(), 10);
+        for (_, (_, _, sync_ips)) in requests {
+            assert_eq!(sync_ips, indexset![sample_peer_ip(2)]);
+        }
+
+        // If all peers are congested, then no requests should be prepared.
+        sync.set_congested_peers(indexset![sample_peer_ip(1), sample_peer_ip(2)]);
+        assert!(sync.prepare_block_requests().is_empty());
+
+        // Once the queues drain, both peers should be selected again.
+        sync.set_congested_peers(indexset![]);
+        let requests = sync.prepare_block_requests();
+        assert_eq!(requests.len(), 10);
+        for (_, (_, _, sync_ips)) in requests {
+            assert_eq!(sync_ips, indexset![sample_peer_ip(1), sample_peer_ip(2)]);
         }
     }
 
@@ -1317,5 +1724,299 @@ mod tests {
         }
     }
 
+    /// Returns the block hash for the given value.
+    fn sample_hash(value: u32) -> <CurrentNetwork as Network>::BlockHash {
+        Field::<CurrentNetwork>::from_u32(value).into()
+    }
+
+    /// Returns the links of a contiguous block response for the given heights, connecting to the canonical ledger.
+    fn sample_links(heights: Range<u32>) -> Vec<BlockLink<CurrentNetwork>> {
+        // The hashes of the responded blocks are offset, so they are distinct from the canonical hashes.
+        let hash = |height: u32| match height < heights.start {
+            true => sample_hash(height),
+            false => sample_hash(1_000 + height),
+        };
+        heights.clone().map(|height| (height, hash(height), hash(height - 1))).collect()
+    }
+
+    /// Returns the sync pool at height 9, with block requests to the given peer for the heights 10 to 14,
+    /// without the expected hashes, as if they were beyond the block locators.
+    fn sample_sync_with_unhashed_requests(peer_ip: SocketAddr) -> BlockSync<CurrentNetwork> {
+        let sync = sample_sync_at_height(9);
+        for height in 10..15 {
+            sync.insert_block_request(height, (None, None, indexset![peer_ip])).unwrap();
+        }
+        sync
+    }
+
+    #[test]
+    fn test_check_block_response_links() {
+        let peer_ip = sample_peer_ip(1);
+        let sync = sample_sync_with_unhashed_requests(peer_ip);
+
+        // A contiguous block response that connects to the canonical ledger is valid.
+        sync.check_block_response_links(&peer_ip, &sample_links(10..15)).unwrap();
+        // The block response may cover a part of the requested heights.
+        sync.check_block_response_links(&peer_ip, &sample_links(10..12)).unwrap();
+        // An empty block response is invalid.
+        sync.check_block_response_links(&peer_ip, &[]).unwrap_err();
+        // A block response from another peer is invalid.
+        sync.check_block_response_links(&sample_peer_ip(2), &sample_links(10..15)).unwrap_err();
+        // A block response with heights that were not requested is invalid.
+        sync.check_block_response_links(&peer_ip, &sample_links(10..16)).unwrap_err();
+    }
+
+    #[test]
+    fn test_check_block_response_links_with_gap() {
+        let peer_ip = sample_peer_ip(1);
+        let sync = sample_sync_with_unhashed_requests(peer_ip);
+
+        // Skip block 12.
+        let mut links = sample_links(10..15);
+        links.remove(2);
+        let error = sync.check_block_response_links(&peer_ip, &links).unwrap_err();
+        assert!(error.to_string().contains("non-contiguous"), "{error}");
+
+        // Claim the heights are contiguous, while block 13 does not link to block 12.
+        let mut links = sample_links(10..15);
+        links[3].2 = sample_hash(2_000);
+        let error = sync.check_block_response_links(&peer_ip, &links).unwrap_err();
+        assert!(error.to_string().contains("does not link"), "{error}");
+    }
+
+    #[test]
+    fn test_check_block_response_links_with_wrong_parent() {
+        let peer_ip = sample_peer_ip(1);
+        let sync = sample_sync_with_unhashed_requests(peer_ip);
+
+        // The first block does not connect to the canonical block 9.
+        let mut links = sample_links(10..15);
+        links[0].2 = sample_hash(2_000);
+        let error = sync.check_block_response_links(&peer_ip, &links).unwrap_err();
+        assert!(error.to_string().contains("does not connect"), "{error}");
+
+        // If the preceding block is unknown, the first block cannot be checked against it.
+        let links = sample_links(12..15);
+        sync.check_block_response_links(&peer_ip, &links).unwrap();
+        // Once the block request for the preceding height has an expected hash, the first block must connect to it.
+        sync.requests.write().get_mut(&11).unwrap().0 = Some(sample_hash(2_011));
+        let error = sync.check_block_response_links(&peer_ip, &links).unwrap_err();
+        assert!(error.to_string().contains("does not connect"), "{error}");
+    }
+
+    #[test]
+    fn test_check_block_response_links_with_shuffled_order() {
+        let peer_ip = sample_peer_ip(1);
+        let sync = sample_sync_with_unhashed_requests(peer_ip);
+
+        let mut links = sample_links(10..15);
+        links.swap(1, 3);
+        let error = sync.check_block_response_links(&peer_ip, &links).unwrap_err();
+        assert!(error.to_string().contains("non-contiguous"), "{error}");
+
+        // Reversing the blocks is also rejected.
+        links.reverse();
+        sync.check_block_response_links(&peer_ip, &links).unwrap_err();
+    }
+
+    #[test]
+    fn test_process_block_response_rejects_before_inserting() {
+        let peer_ip = sample_peer_ip(1);
+        let sync = sample_sync_with_unhashed_requests(peer_ip);
+
+        // An empty block response is rejected, and the block requests to the peer are removed,
+        // so the blocks are requested from another peer.
+        sync.process_block_response(peer_ip, vec![]).unwrap_err();
+        assert!(sync.responses.read().is_empty());
+        assert!(sync.requests.read().is_empty());
+    }
+
+    #[test]
+    fn test_reset_block_requests() {
+        let sync = sample_sync_at_height(0);
+
+        // Add a peer.
+        sync.update_peer_locators(sample_peer_ip(1), sample_block_locators(10)).unwrap();
+
+        // Insert the block requests.
+        let requests = sync.prepare_block_requests();
+        assert_eq!(requests.len(), 10);
+        for (height, request) in requests {
+            sync.insert_block_request(height, request).unwrap();
+        }
+        // As all of the blocks are requested, there are no further block requests to prepare.
+        assert!(sync.prepare_block_requests().is_empty());
+
+        // Reset the block requests for a dropped block response.
+        sync.reset_block_requests(3..6);
+        for height in 1..=10 {
+            assert_eq!(sync.get_block_request(height).is_some(), !(3..6).contains(&height));
+        }
+
+        // Ensure the reset blocks are requested again.
+        let requests = sync.prepare_block_requests();
+        assert_eq!(requests.into_iter().map(|(height, _)| height).collect::<Vec<_>>(), vec![3, 4, 5]);
+    }
+
+    #[test]
+    fn test_block_request_window_grows_for_fast_peer() {
+        let sync = sample_sync_at_height(0);
+
+        // Add a peer.
+        let peer_ip = sample_peer_ip(1);
+        sync.update_peer_locators(peer_ip, sample_block_locators(20)).unwrap();
+        assert_eq!(sync.get_block_request_size(&indexset![peer_ip]), DEFAULT_BLOCKS_PER_REQUEST);
+
+        // Simulate fast and complete block responses from the peer.
+        let mut expected_size = DEFAULT_BLOCKS_PER_REQUEST;
+        for _ in 0..5 {
+            let size = sync.get_block_request_size(&indexset![peer_ip]);
+            sync.update_block_request_window(peer_ip, size, Duration::from_millis(100));
+            // Ensure the window grows multiplicatively, up to the maximum.
+            expected_size = (expected_size * 2).min(MAXIMUM_BLOCKS_PER_REQUEST);
+            assert_eq!(sync.block_request_sizes().get(&peer_ip), Some(&expected_size));
+        }
+        assert_eq!(expected_size, MAXIMUM_BLOCKS_PER_REQUEST);
+
+        // Ensure the block requests to the peer are batched to the size of the window.
+        let batches = sync.batch_block_requests(sync.prepare_block_requests());
+        assert_eq!(batches.len(), 20 / MAXIMUM_BLOCKS_PER_REQUEST as usize);
+        for (i, batch) in batches.iter().enumerate() {
+            let heights = batch.iter().map(|(height, _)| *height).collect::<Vec<_>>();
+            let start_height = 1 + i as u32 * MAXIMUM_BLOCKS_PER_REQUEST;
+            assert_eq!(heights, (start_height..start_height + MAXIMUM_BLOCKS_PER_REQUEST).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_block_request_window_shrinks_for_slow_peer() {
+        let sync = sample_sync_at_height(0).with_block_request_size(MAXIMUM_BLOCKS_PER_REQUEST);
+
+        let fast_ip = sample_peer_ip(1);
+        let slow_ip = sample_peer_ip(2);
+
+        // Simulate a fast block response from one peer, and slow block responses from the other.
+        sync.update_block_request_window(fast_ip, MAXIMUM_BLOCKS_PER_REQUEST, Duration::from_millis(100));
+        let mut expected_size = MAXIMUM_BLOCKS_PER_REQUEST;
+        for _ in 0..5 {
+            let size = sync.get_block_request_size(&indexset![slow_ip]);
+            sync.update_block_request_window(slow_ip, size, Duration::from_secs(20));
+            // Ensure the window shrinks multiplicatively, down to the minimum.
+            expected_size = (expected_size / 2).max(MINIMUM_BLOCKS_PER_REQUEST);
+            assert_eq!(sync.block_request_sizes().get(&slow_ip), Some(&expected_size));
+        }
+        assert_eq!(expected_size, MINIMUM_BLOCKS_PER_REQUEST);
+
+        // Ensure the slow peer does not penalize the fast peer.
+        assert_eq!(sync.block_request_sizes().get(&fast_ip), Some(&MAXIMUM_BLOCKS_PER_REQUEST));
+        // Ensure a block request to both peers is bounded by the slow peer.
+        assert_eq!(sync.get_block_request_size(&indexset![fast_ip, slow_ip]), MINIMUM_BLOCKS_PER_REQUEST);
+    }
+
+    #[test]
+    fn test_block_request_window_shrinks_on_timeout() {
+        let sync = sample_sync_at_height(0).with_block_request_size(4);
+
+        // Add a peer.
+        let peer_ip = sample_peer_ip(1);
+        sync.update_peer_locators(peer_ip, sample_block_locators(10)).unwrap();
+
+        // Insert the block requests, and expire them.
+        for (height, request) in sync.prepare_block_requests() {
+            sync.insert_block_request(height, request).unwrap();
+        }
+        let expired = Instant::now() - Duration::from_secs(BLOCK_REQUEST_TIMEOUT_IN_SECS + 1);
+        sync.request_timestamps.write().values_mut().for_each(|timestamp| *timestamp = expired);
+
+        // Ensure the timed out block requests shrink the window of the peer once.
+        assert_eq!(sync.remove_timed_out_block_requests(), 10);
+        assert_eq!(sync.block_request_sizes().get(&peer_ip), Some(&2));
+
+        // Ensure the window is removed with the peer.
+        sync.remove_peer(&peer_ip);
+        assert!(sync.block_request_sizes().is_empty());
+    }
+
+    /// Returns the sync pool, with the canonical ledger initialized to the given height,
+    /// and its latest block produced the given number of seconds ago.
+    fn sample_sync_with_latest_block_age(height: u32, age_in_secs: i64) -> BlockSync<CurrentNetwork> {
+        let ledger = sample_ledger_service(height);
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+        ledger.set_latest_block_timestamp(now - age_in_secs);
+        BlockSync::<CurrentNetwork>::new(BlockSyncMode::Router, Arc::new(ledger))
+    }
+
+    #[test]
+    fn test_update_peer_locators_rejects_implausible_height() {
+        let sync = sample_sync_with_latest_block_age(20, 0);
+
+        // Add an honest peer, and a peer claiming a height that cannot have been produced since the latest block.
+        let honest_ip = sample_peer_ip(1);
+        let liar_ip = sample_peer_ip(2);
+        sync.update_peer_locators(honest_ip, sample_block_locators(500)).unwrap();
+        let error = sync.update_peer_locators(liar_ip, sample_block_locators(100_000)).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<ImplausibleHeight>(),
+            Some(&ImplausibleHeight { height: 100_000, maximum_height: 20 + MINIMUM_PLAUSIBLE_LEAD })
+        );
+
+        // Check the liar is excluded from sync, and the honest peer is chosen.
+        assert!(sync.get_peer_height(&liar_ip).is_none());
+        let (sync_peers, min_common_ancestor) = sync.find_sync_peers().unwrap();
+        assert_eq!(sync_peers.into_keys().collect::<Vec<_>>(), vec![honest_ip]);
+        assert_eq!(min_common_ancestor, 20);
+    }
+
+    #[test]
+    fn test_update_peer_locators_plausible_height_grows_with_time() {
+        // The latest block was produced one day ago.
+        let age_in_secs = 24 * 60 * 60;
+        let sync = sample_sync_with_latest_block_age(20, age_in_secs);
+
+        // Check a peer may be ahead by the number of blocks that could have been produced since.
+        let num_blocks =
+            (age_in_secs as u32 / CurrentNetwork::BLOCK_TIME as u32) * DEFAULT_HEIGHT_PLAUSIBILITY_MULTIPLIER;
+        let peer_ip = sample_peer_ip(1);
+        sync.update_peer_locators(peer_ip, sample_block_locators(20 + num_blocks)).unwrap();
+        assert_eq!(sync.get_peer_height(&peer_ip), Some(20 + num_blocks));
+
+        // Check the multiplier is configurable.
+        let sync = sample_sync_with_latest_block_age(20, age_in_secs).with_height_plausibility_multiplier(1);
+        assert!(sync
+            .update_peer_locators(peer_ip, sample_block_locators(20 + num_blocks + MINIMUM_PLAUSIBLE_LEAD))
+            .is_err());
+    }
+
+    #[test]
+    fn test_update_peer_locators_skips_plausibility_without_timestamp() {
+        let sync = sample_sync_at_height(20);
+
+        // Check the height is not bounded if the timestamp of the latest block is unknown.
+        let peer_ip = sample_peer_ip(1);
+        sync.update_peer_locators(peer_ip, sample_block_locators(100_000)).unwrap();
+        assert_eq!(sync.get_peer_height(&peer_ip), Some(100_000));
+    }
+
+    #[test]
+    fn test_is_peer_on_fork() {
+        let sync = sample_sync_at_height(20);
+
+        // Check a peer on the canonical chain is not on a fork.
+        let peer_ip = sample_peer_ip(1);
+        sync.update_peer_locators(peer_ip, sample_block_locators(30)).unwrap();
+        assert!(!sync.is_peer_on_fork(&peer_ip));
+
+        // Check a peer whose chain forked from the canonical chain is on a fork.
+        sync.update_peer_locators(peer_ip, sample_block_locators_with_fork(30, 15)).unwrap();
+        assert!(sync.is_peer_on_fork(&peer_ip));
+
+        // Check the flag is cleared once the peer returns to the canonical chain, or is removed.
+        sync.update_peer_locators(peer_ip, sample_block_locators(31)).unwrap();
+        assert!(!sync.is_peer_on_fork(&peer_ip));
+        sync.update_peer_locators(peer_ip, sample_block_locators_with_fork(30, 15)).unwrap();
+        sync.remove_peer(&peer_ip);
+        assert!(!sync.is_peer_on_fork(&peer_ip));
+    }
+
     // TODO: duplicate responses, ensure fails.
 }
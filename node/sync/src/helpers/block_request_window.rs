@@ -0,0 +1,128 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkos_node_sync_locators::{MAXIMUM_BLOCKS_PER_REQUEST, MINIMUM_BLOCKS_PER_REQUEST};
+
+use std::time::Duration;
+
+/// The default number of blocks in a block request, before it is adjusted for a peer.
+pub const DEFAULT_BLOCKS_PER_REQUEST: u32 = MINIMUM_BLOCKS_PER_REQUEST;
+
+/// The latency below which a full block response is considered fast, and the window grows.
+const FAST_BLOCK_RESPONSE_IN_MILLIS: u64 = 1_000; // 1 second
+/// The latency above which a block response is considered slow, and the window shrinks.
+const SLOW_BLOCK_RESPONSE_IN_MILLIS: u64 = 10_000; // 10 seconds
+
+/// The number of blocks to request from a peer in a single block request.
+/// The window grows multiplicatively when a peer responds quickly with a full window of blocks,
+/// and shrinks multiplicatively when a peer responds slowly or a block request to the peer times out.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct BlockRequestWindow {
+    /// The current number of blocks per block request.
+    size: u32,
+    /// The maximum number of blocks per block request that the peer serves.
+    limit: u32,
+}
+
+impl BlockRequestWindow {
+    /// Initializes a new block request window, bounded by the protocol limits.
+    pub fn new(size: u32) -> Self {
+        Self {
+            size: size.clamp(MINIMUM_BLOCKS_PER_REQUEST, MAXIMUM_BLOCKS_PER_REQUEST),
+            limit: MAXIMUM_BLOCKS_PER_REQUEST,
+        }
+    }
+
+    /// Returns the window, bounded by the given maximum number of blocks per block request that the peer serves.
+    pub fn with_limit(self, limit: u32) -> Self {
+        let limit = limit.clamp(MINIMUM_BLOCKS_PER_REQUEST, MAXIMUM_BLOCKS_PER_REQUEST);
+        Self { size: self.size.min(limit), limit }
+    }
+
+    /// Returns the current number of blocks per block request.
+    pub const fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// Adjusts the window for a block response of the given number of blocks, received after the given latency.
+    pub fn on_response(&mut self, num_blocks: u32, latency: Duration) {
+        if latency > Duration::from_millis(SLOW_BLOCK_RESPONSE_IN_MILLIS) {
+            self.shrink();
+        } else if latency < Duration::from_millis(FAST_BLOCK_RESPONSE_IN_MILLIS) && num_blocks >= self.size {
+            self.grow();
+        }
+    }
+
+    /// Adjusts the window for a block request to the peer that timed out.
+    pub fn on_timeout(&mut self) {
+        self.shrink();
+    }
+
+    /// Doubles the window, up to the maximum number of blocks per block request that the peer serves.
+    fn grow(&mut self) {
+        self.size = self.size.saturating_mul(2).min(self.limit);
+    }
+
+    /// Halves the window, down to the minimum number of blocks per block request.
+    fn shrink(&mut self) {
+        self.size = (self.size / 2).max(MINIMUM_BLOCKS_PER_REQUEST);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_request_window_bounds() {
+        assert_eq!(BlockRequestWindow::new(0).size(), MINIMUM_BLOCKS_PER_REQUEST);
+        assert_eq!(BlockRequestWindow::new(u32::MAX).size(), MAXIMUM_BLOCKS_PER_REQUEST);
+
+        // Ensure the window never grows above the maximum.
+        let mut window = BlockRequestWindow::new(MAXIMUM_BLOCKS_PER_REQUEST);
+        window.on_response(MAXIMUM_BLOCKS_PER_REQUEST, Duration::ZERO);
+        assert_eq!(window.size(), MAXIMUM_BLOCKS_PER_REQUEST);
+
+        // Ensure the window never shrinks below the minimum.
+        let mut window = BlockRequestWindow::new(MINIMUM_BLOCKS_PER_REQUEST);
+        window.on_timeout();
+        assert_eq!(window.size(), MINIMUM_BLOCKS_PER_REQUEST);
+    }
+
+    #[test]
+    fn test_block_request_window_limit() {
+        // Ensure the window shrinks to the limit, and never grows above it.
+        let mut window = BlockRequestWindow::new(MAXIMUM_BLOCKS_PER_REQUEST).with_limit(MINIMUM_BLOCKS_PER_REQUEST);
+        assert_eq!(window.size(), MINIMUM_BLOCKS_PER_REQUEST);
+        window.on_response(MINIMUM_BLOCKS_PER_REQUEST, Duration::ZERO);
+        assert_eq!(window.size(), MINIMUM_BLOCKS_PER_REQUEST);
+        // Ensure the limit is bounded by the protocol limits.
+        assert_eq!(BlockRequestWindow::new(2).with_limit(0).size(), MINIMUM_BLOCKS_PER_REQUEST);
+        let mut window = BlockRequestWindow::new(2).with_limit(u32::MAX);
+        window.on_response(2, Duration::ZERO);
+        window.on_response(4, Duration::ZERO);
+        assert_eq!(window.size(), MAXIMUM_BLOCKS_PER_REQUEST);
+    }
+
+    #[test]
+    fn test_block_request_window_ignores_partial_responses() {
+        let mut window = BlockRequestWindow::new(2);
+        // A fast response that does not fill the window does not grow it.
+        window.on_response(1, Duration::ZERO);
+        assert_eq!(window.size(), 2);
+        // A response that is neither fast nor slow does not change the window.
+        window.on_response(2, Duration::from_millis(FAST_BLOCK_RESPONSE_IN_MILLIS));
+        assert_eq!(window.size(), 2);
+    }
+}
@@ -12,6 +12,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod block_request_window;
+pub use block_request_window::*;
+
+mod sync_progress;
+pub use sync_progress::*;
+
+mod verification_pipeline;
+pub use verification_pipeline::*;
+
 use snarkvm::prelude::Network;
 
 use core::hash::Hash;
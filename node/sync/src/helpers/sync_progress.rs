@@ -0,0 +1,200 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::{
+    collections::VecDeque,
+    sync::atomic::{AtomicU32, Ordering},
+    time::{Duration, Instant},
+};
+
+/// The window over which the rate of applied blocks is measured.
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// The source the node is syncing blocks from.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncSource {
+    /// The node requests blocks from its peers.
+    P2p,
+}
+
+/// A report of the sync progress of the node.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct SyncReport {
+    /// Whether the node is syncing, i.e. it is more than the tolerated number of blocks behind its peers.
+    pub is_syncing: bool,
+    /// The source the node is syncing blocks from, if it is syncing.
+    /// Note: A sync from the CDN completes before the node starts, and is never reported here.
+    pub source: Option<SyncSource>,
+    /// The latest block height of the node.
+    pub current_height: u32,
+    /// The greatest block height reported by the sync peers, if any.
+    pub peer_height: Option<u32>,
+    /// The number of sync peers that report the greatest block height.
+    pub num_peers_at_peer_height: u32,
+    /// The number of blocks remaining to reach the greatest block height reported by the sync peers.
+    pub num_blocks_remaining: u32,
+    /// The number of blocks applied in the last minute.
+    pub blocks_in_last_minute: u32,
+    /// The estimated number of seconds until the node is synced, at the rate of the last minute.
+    pub eta_secs: Option<u64>,
+}
+
+/// The sync progress of the node, recorded by the sync loop and read by the REST server.
+///
+/// The sync loop never waits on a reader: the peer height is atomic, and a height sample is skipped if a reader
+/// holds the samples, which only delays the rate by a sample.
+#[derive(Debug, Default)]
+pub struct SyncProgress {
+    /// The greatest block height reported by the sync peers, as of the last sync update.
+    peer_height: AtomicU32,
+    /// The number of sync peers that report the greatest block height, as of the last sync update.
+    num_peers_at_peer_height: AtomicU32,
+    /// The samples of `(timestamp, block height)` within the rate window, from oldest to newest.
+    samples: Mutex<VecDeque<(Instant, u32)>>,
+}
+
+impl SyncProgress {
+    /// Records the greatest block height reported by the sync peers, and the number of sync peers reporting it.
+    pub fn update_peer_height(&self, peer_height: u32, num_peers: u32) {
+        self.peer_height.store(peer_height, Ordering::Relaxed);
+        self.num_peers_at_peer_height.store(num_peers, Ordering::Relaxed);
+    }
+
+    /// Records the latest block height of the node.
+    pub fn record_height(&self, height: u32) {
+        self.record_height_at(height, Instant::now());
+    }
+
+    /// Records the latest block height of the node, at the given time.
+    fn record_height_at(&self, height: u32, now: Instant) {
+        // Skip the sample if a reader holds the samples.
+        let Some(mut samples) = self.samples.try_lock() else {
+            return;
+        };
+        // Skip the sample if the height did not change.
+        if samples.back().is_some_and(|(_, latest)| *latest == height) {
+            return;
+        }
+        samples.push_back((now, height));
+        // Remove the samples that fell out of the rate window.
+        while samples.front().is_some_and(|(timestamp, _)| now.saturating_duration_since(*timestamp) > RATE_WINDOW) {
+            samples.pop_front();
+        }
+    }
+
+    /// Returns the number of blocks applied over the rate window, up to the given block height.
+    fn blocks_in_window(&self, current_height: u32, now: Instant) -> u32 {
+        let samples = self.samples.lock();
+        // Find the oldest sample within the rate window.
+        let oldest = samples.iter().find(|(timestamp, _)| now.saturating_duration_since(*timestamp) <= RATE_WINDOW);
+        oldest.map(|(_, height)| current_height.saturating_sub(*height)).unwrap_or(0)
+    }
+
+    /// Returns the sync report, for the given latest block height and sync status of the node.
+    pub fn report(&self, current_height: u32, is_synced: bool) -> SyncReport {
+        self.report_at(current_height, is_synced, Instant::now())
+    }
+
+    /// Returns the sync report at the given time.
+    fn report_at(&self, current_height: u32, is_synced: bool, now: Instant) -> SyncReport {
+        let peer_height = self.peer_height.load(Ordering::Relaxed);
+        let num_peers_at_peer_height = self.num_peers_at_peer_height.load(Ordering::Relaxed);
+        let num_blocks_remaining = peer_height.saturating_sub(current_height);
+        let blocks_in_last_minute = self.blocks_in_window(current_height, now);
+        // Estimate the remaining time from the rate of the last minute.
+        let eta_secs = match (num_blocks_remaining, blocks_in_last_minute) {
+            (0, _) => Some(0),
+            (_, 0) => None,
+            (remaining, rate) => Some((remaining as u64 * RATE_WINDOW.as_secs()).div_ceil(rate as u64)),
+        };
+        SyncReport {
+            is_syncing: !is_synced,
+            source: (!is_synced).then_some(SyncSource::P2p),
+            current_height,
+            peer_height: (num_peers_at_peer_height > 0).then_some(peer_height),
+            num_peers_at_peer_height,
+            num_blocks_remaining,
+            blocks_in_last_minute,
+            eta_secs,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_at_tip() {
+        let progress = SyncProgress::default();
+
+        // A node without sync peers is trivially synced.
+        let report = progress.report(5, true);
+        assert!(!report.is_syncing);
+        assert_eq!(report.source, None);
+        assert_eq!(report.peer_height, None);
+        assert_eq!(report.num_blocks_remaining, 0);
+        assert_eq!(report.eta_secs, Some(0));
+
+        // A node at the height of its peers is synced.
+        progress.update_peer_height(5, 2);
+        let report = progress.report(5, true);
+        assert_eq!(report.peer_height, Some(5));
+        assert_eq!(report.num_peers_at_peer_height, 2);
+        assert_eq!(report.num_blocks_remaining, 0);
+    }
+
+    #[test]
+    fn test_report_while_syncing() {
+        let progress = SyncProgress::default();
+        let start = Instant::now();
+        progress.update_peer_height(1_000, 3);
+
+        // Apply 10 blocks every 6 seconds, i.e. 100 blocks per minute.
+        let mut remaining = vec![];
+        for i in 0..=20u32 {
+            let now = start + Duration::from_secs(6 * i as u64);
+            progress.record_height_at(10 * i, now);
+            let report = progress.report_at(10 * i, false, now);
+            assert!(report.is_syncing);
+            assert_eq!(report.source, Some(SyncSource::P2p));
+            assert_eq!(report.peer_height, Some(1_000));
+            remaining.push(report.num_blocks_remaining);
+        }
+        // The number of remaining blocks decreases as blocks are applied.
+        assert!(remaining.windows(2).all(|pair| pair[1] < pair[0]));
+
+        // The rate is measured over the last minute only.
+        let now = start + Duration::from_secs(120);
+        let report = progress.report_at(200, false, now);
+        assert_eq!(report.blocks_in_last_minute, 100);
+        // The remaining 800 blocks take 8 minutes at 100 blocks per minute.
+        assert_eq!(report.num_blocks_remaining, 800);
+        assert_eq!(report.eta_secs, Some(480));
+
+        // Without any blocks in the last minute, the remaining time is unknown.
+        let report = progress.report_at(200, false, now + Duration::from_secs(120));
+        assert_eq!(report.blocks_in_last_minute, 0);
+        assert_eq!(report.eta_secs, None);
+
+        // Once the node reaches its peers, it is synced.
+        progress.record_height_at(1_000, now + Duration::from_secs(180));
+        let report = progress.report_at(1_000, true, now + Duration::from_secs(180));
+        assert!(!report.is_syncing);
+        assert_eq!(report.num_blocks_remaining, 0);
+    }
+}
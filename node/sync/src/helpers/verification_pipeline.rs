@@ -0,0 +1,384 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use snarkos_node_bft_ledger_service::LedgerService;
+use snarkvm::prelude::{block::Block, Network, ToBytes};
+
+use anyhow::{anyhow, Result};
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc,
+        Arc,
+    },
+};
+
+/// The default number of blocks that are checked ahead of the block being applied.
+pub const DEFAULT_VERIFICATION_LOOKAHEAD: usize = 8;
+/// The default maximum total size of the blocks buffered in the verification pipeline.
+pub const DEFAULT_VERIFICATION_BUFFER_IN_BYTES: usize = 512 * 1024 * 1024; // 512 MiB
+
+/// The bounds on the blocks that are checked ahead of the block being applied.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct VerificationLookahead {
+    /// The maximum number of blocks that are checked ahead of the block being applied.
+    /// If this is zero, each block is only checked as it is applied.
+    pub num_blocks: usize,
+    /// The maximum total serialized size of the buffered blocks, in bytes.
+    pub max_bytes: usize,
+}
+
+impl Default for VerificationLookahead {
+    /// Returns the default lookahead.
+    fn default() -> Self {
+        Self { num_blocks: DEFAULT_VERIFICATION_LOOKAHEAD, max_bytes: DEFAULT_VERIFICATION_BUFFER_IN_BYTES }
+    }
+}
+
+impl VerificationLookahead {
+    /// Returns a lookahead that checks each block only as it is applied.
+    pub const fn disabled() -> Self {
+        Self { num_blocks: 0, max_bytes: 0 }
+    }
+
+    /// Returns `true` if blocks are checked ahead of the block being applied.
+    pub const fn is_enabled(&self) -> bool {
+        self.num_blocks > 0
+    }
+}
+
+/// A block in the verification pipeline.
+struct PendingBlock<N: Network> {
+    /// The block height.
+    height: u32,
+    /// The serialized size of the block, in bytes.
+    num_bytes: usize,
+    /// The receiver for the block and the result of its stateless checks.
+    receiver: mpsc::Receiver<(Block<N>, Result<()>)>,
+}
+
+/// A pipeline that runs the stateless checks (signatures, transaction proofs, and structure) of the upcoming blocks
+/// on the rayon thread pool, while the stateful checks and the advance to each block are performed in order.
+pub struct VerificationPipeline<N: Network> {
+    /// The ledger service.
+    ledger: Arc<dyn LedgerService<N>>,
+    /// The bounds on the buffered blocks.
+    lookahead: VerificationLookahead,
+    /// The buffered blocks, in ascending order of height.
+    pending: VecDeque<PendingBlock<N>>,
+    /// The total serialized size of the buffered blocks, in bytes.
+    num_bytes: usize,
+    /// The flag that signals the outstanding stateless checks to stop, once the buffered blocks are discarded.
+    discarded: Arc<AtomicBool>,
+}
+
+impl<N: Network> VerificationPipeline<N> {
+    /// Initializes a new verification pipeline.
+    pub fn new(ledger: Arc<dyn LedgerService<N>>, lookahead: VerificationLookahead) -> Self {
+        Self { ledger, lookahead, pending: Default::default(), num_bytes: 0, discarded: Default::default() }
+    }
+
+    /// Returns the number of buffered blocks.
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Returns `true` if there are no buffered blocks.
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Returns the total serialized size of the buffered blocks, in bytes.
+    pub const fn num_bytes(&self) -> usize {
+        self.num_bytes
+    }
+
+    /// Returns the heights of the buffered blocks, in ascending order.
+    pub fn heights(&self) -> Vec<u32> {
+        self.pending.iter().map(|pending| pending.height).collect()
+    }
+
+    /// Returns `true` if the pipeline does not accept another block.
+    /// Note: The pipeline always accepts a block when it is empty, so the memory bound is exceeded by at most one block.
+    pub fn is_full(&self) -> bool {
+        !self.pending.is_empty()
+            && (self.pending.len() > self.lookahead.num_blocks || self.num_bytes >= self.lookahead.max_bytes)
+    }
+
+    /// Buffers the given block, and starts its stateless checks on the rayon thread pool.
+    pub fn push(&mut self, block: Block<N>) {
+        let height = block.height();
+        let (sender, receiver) = mpsc::sync_channel(1);
+
+        let num_bytes = if self.lookahead.is_enabled() {
+            // Compute the serialized size of the block.
+            let num_bytes = block.to_bytes_le().map_or(usize::MAX, |bytes| bytes.len());
+            // Start the stateless checks of the block.
+            let (ledger, discarded) = (self.ledger.clone(), self.discarded.clone());
+            rayon::spawn(move || {
+                // If the block was discarded, skip the stateless checks.
+                if discarded.load(Ordering::Relaxed) {
+                    return;
+                }
+                let result = ledger.check_block_stateless(&block);
+                let _ = sender.send((block, result));
+            });
+            num_bytes
+        } else {
+            // If the lookahead is disabled, the block is only checked as it is applied.
+            let _ = sender.send((block, Ok(())));
+            0
+        };
+
+        self.num_bytes = self.num_bytes.saturating_add(num_bytes);
+        self.pending.push_back(PendingBlock { height, num_bytes, receiver });
+    }
+
+    /// Removes the next buffered block, once its stateless checks are complete.
+    fn next_checked(&mut self) -> Option<Result<Block<N>>> {
+        let pending = self.pending.pop_front()?;
+        self.num_bytes = self.num_bytes.saturating_sub(pending.num_bytes);
+
+        match pending.receiver.recv() {
+            Ok((block, Ok(()))) => Some(Ok(block)),
+            // The stateless checks ran before the preceding blocks were applied, so a failure may stem from
+            // their contents (e.g. a program deployment). Repeat the checks before the block is rejected.
+            Ok((block, Err(_))) => Some(self.ledger.check_block_stateless(&block).map(|_| block)),
+            Err(_) => Some(Err(anyhow!("The stateless checks of block {} did not complete", pending.height))),
+        }
+    }
+
+    /// Discards the buffered blocks, and stops their outstanding stateless checks.
+    /// Returns the heights of the discarded blocks.
+    pub fn discard(&mut self) -> Vec<u32> {
+        // Signal the outstanding stateless checks to stop.
+        self.discarded.store(true, Ordering::Relaxed);
+        self.discarded = Default::default();
+        // Clear the buffered blocks.
+        self.num_bytes = 0;
+        self.pending.drain(..).map(|pending| pending.height).collect()
+    }
+
+    /// Advances the ledger with the blocks from the given source, until the source is exhausted.
+    /// The source must return the blocks in ascending order of height, and `on_advance` is invoked after each advance.
+    /// On failure, the remaining buffered blocks are discarded, and the error is returned.
+    pub fn advance_with(
+        &mut self,
+        mut next_block: impl FnMut() -> Option<Block<N>>,
+        mut on_advance: impl FnMut(&Block<N>),
+    ) -> Result<()> {
+        loop {
+            // Buffer the upcoming blocks, up to the lookahead.
+            while !self.is_full() {
+                match next_block() {
+                    Some(block) => self.push(block),
+                    None => break,
+                }
+            }
+            // Retrieve the next block, once its stateless checks are complete.
+            let Some(result) = self.next_checked() else {
+                return Ok(());
+            };
+            let result = result.and_then(|block| {
+                // Check the next block against the latest state of the ledger.
+                self.ledger.check_next_block(&block)?;
+                // Attempt to advance to the next block.
+                self.ledger.advance_to_next_block(&block)?;
+                Ok(block)
+            });
+            match result {
+                Ok(block) => on_advance(&block),
+                Err(error) => {
+                    let discarded = self.discard();
+                    if !discarded.is_empty() {
+                        debug!("Discarded the speculative checks of {} upcoming blocks", discarded.len());
+                    }
+                    return Err(error);
+                }
+            }
+        }
+    }
+}
+
+impl<N: Network> Drop for VerificationPipeline<N> {
+    /// Stops the outstanding stateless checks.
+    fn drop(&mut self) {
+        self.discarded.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use snarkos_node_bft_ledger_service::{test_helpers::sample_ledger, CoreLedgerService};
+    use snarkvm::{
+        ledger::{store::helpers::memory::ConsensusMemory, Ledger},
+        prelude::{Address, MainnetV0, TestRng, Value},
+    };
+
+    use aleo_std::StorageMode;
+    use std::{str::FromStr, time::Instant};
+
+    type CurrentNetwork = MainnetV0;
+    type CurrentLedger = Ledger<CurrentNetwork, ConsensusMemory<CurrentNetwork>>;
+
+    /// Returns the genesis block and the given number of subsequent blocks of a devnet chain.
+    /// If `with_transactions` is set, each block contains a public transfer.
+    fn sample_chain(
+        num_blocks: u32,
+        with_transactions: bool,
+        rng: &mut TestRng,
+    ) -> (Block<CurrentNetwork>, Vec<Block<CurrentNetwork>>) {
+        let (ledger, private_key) = sample_ledger(rng);
+        let address = Address::try_from(&private_key).unwrap();
+        let genesis = ledger.get_block(0).unwrap();
+
+        let mut blocks = Vec::with_capacity(num_blocks as usize);
+        for _ in 0..num_blocks {
+            let mut transactions = vec![];
+            if with_transactions {
+                let inputs = [Value::from_str(&address.to_string()).unwrap(), Value::from_str("1u64").unwrap()];
+                let locator = ("credits.aleo", "transfer_public");
+                transactions
+                    .push(ledger.vm().execute(&private_key, locator, inputs.into_iter(), None, 0, None, rng).unwrap());
+            }
+            let block =
+                ledger.prepare_advance_to_next_beacon_block(&private_key, vec![], vec![], transactions, rng).unwrap();
+            ledger.advance_to_next_block(&block).unwrap();
+            blocks.push(block);
+        }
+        (genesis, blocks)
+    }
+
+    /// Returns the ledger service for a new ledger with the given genesis block.
+    fn sample_service(genesis: &Block<CurrentNetwork>) -> Arc<dyn LedgerService<CurrentNetwork>> {
+        let ledger = CurrentLedger::load(genesis.clone(), StorageMode::Production).unwrap();
+        Arc::new(CoreLedgerService::new(ledger, Default::default()))
+    }
+
+    /// Returns a lookahead of the given number of blocks, without a memory bound.
+    fn sample_lookahead(num_blocks: usize) -> VerificationLookahead {
+        VerificationLookahead { num_blocks, max_bytes: usize::MAX }
+    }
+
+    #[test]
+    fn test_pipeline_advances_in_order() {
+        let rng = &mut TestRng::default();
+        let (genesis, blocks) = sample_chain(6, false, rng);
+        let ledger = sample_service(&genesis);
+
+        let mut pipeline = VerificationPipeline::new(ledger.clone(), sample_lookahead(3));
+        let mut source = blocks.into_iter();
+        let mut advanced = vec![];
+        pipeline.advance_with(|| source.next(), |block| advanced.push(block.height())).unwrap();
+        assert_eq!(advanced, vec![1, 2, 3, 4, 5, 6]);
+        assert_eq!(ledger.latest_block_height(), 6);
+        assert!(pipeline.is_empty());
+    }
+
+    #[test]
+    fn test_pipeline_is_bounded() {
+        let rng = &mut TestRng::default();
+        let (genesis, blocks) = sample_chain(4, false, rng);
+        let ledger = sample_service(&genesis);
+
+        // Buffers the blocks into the given pipeline, until it is full.
+        let fill = |pipeline: &mut VerificationPipeline<CurrentNetwork>| {
+            for block in &blocks {
+                if pipeline.is_full() {
+                    break;
+                }
+                pipeline.push(block.clone());
+            }
+        };
+
+        // Ensure the buffered blocks are bounded by the lookahead.
+        let mut pipeline = VerificationPipeline::new(ledger.clone(), sample_lookahead(2));
+        fill(&mut pipeline);
+        assert_eq!(pipeline.heights(), vec![1, 2, 3]);
+
+        // Ensure the buffered blocks are bounded by their serialized size.
+        let max_bytes = blocks[0].to_bytes_le().unwrap().len();
+        let mut pipeline =
+            VerificationPipeline::new(ledger.clone(), VerificationLookahead { num_blocks: 8, max_bytes });
+        fill(&mut pipeline);
+        assert_eq!(pipeline.heights(), vec![1]);
+        assert_eq!(pipeline.num_bytes(), max_bytes);
+
+        // Ensure a disabled lookahead buffers a single block.
+        let mut pipeline = VerificationPipeline::new(ledger.clone(), VerificationLookahead::disabled());
+        fill(&mut pipeline);
+        assert_eq!(pipeline.heights(), vec![1]);
+
+        // Ensure the discarded blocks are cleared, without advancing the ledger.
+        assert_eq!(pipeline.discard(), vec![1]);
+        assert!(pipeline.is_empty());
+        assert_eq!(pipeline.num_bytes(), 0);
+        assert_eq!(ledger.latest_block_height(), 0);
+    }
+
+    #[test]
+    fn test_pipeline_discards_after_invalid_block() {
+        let rng = &mut TestRng::default();
+        let (genesis, mut blocks) = sample_chain(6, false, rng);
+        // Replace the third block with the third block of another chain.
+        let (_, other_blocks) = sample_chain(3, false, rng);
+        blocks[2] = other_blocks[2].clone();
+        let ledger = sample_service(&genesis);
+
+        let mut pipeline = VerificationPipeline::new(ledger.clone(), sample_lookahead(2));
+        let mut source = blocks.into_iter();
+        let mut advanced = vec![];
+        assert!(pipeline.advance_with(|| source.next(), |block| advanced.push(block.height())).is_err());
+
+        // Ensure the blocks before the invalid block are applied.
+        assert_eq!(advanced, vec![1, 2]);
+        assert_eq!(ledger.latest_block_height(), 2);
+        // Ensure the blocks after the invalid block are discarded.
+        assert!(pipeline.is_empty());
+        assert_eq!(pipeline.num_bytes(), 0);
+        // Ensure the source is only drained up to the lookahead past the invalid block.
+        assert_eq!(source.next().map(|block| block.height()), Some(6));
+    }
+
+    /// This test syncs a devnet chain with and without the verification pipeline, and compares the wall-clock time.
+    /// It takes several minutes to sample the chain, so it runs with `cargo test -p snarkos-node-sync -- --ignored`.
+    #[test]
+    #[ignore = "long-running benchmark"]
+    fn test_pipeline_reduces_sync_time() {
+        const NUM_BLOCKS: u32 = 32;
+
+        let rng = &mut TestRng::default();
+        let (genesis, blocks) = sample_chain(NUM_BLOCKS, true, rng);
+
+        // Syncs a new ledger with the chain, and returns the elapsed time.
+        let sync = |lookahead| {
+            let ledger = sample_service(&genesis);
+            let mut source = blocks.clone().into_iter();
+            let timer = Instant::now();
+            VerificationPipeline::new(ledger.clone(), lookahead).advance_with(|| source.next(), |_| ()).unwrap();
+            assert_eq!(ledger.latest_block_height(), NUM_BLOCKS);
+            timer.elapsed()
+        };
+        let serial = sync(VerificationLookahead::disabled());
+        let pipelined = sync(VerificationLookahead::default());
+        println!("Synced {NUM_BLOCKS} blocks in {serial:?} serially, and in {pipelined:?} with the pipeline");
+
+        // Ensure the pipeline reduces the sync time, if there are multiple cores.
+        if rayon::current_num_threads() > 1 {
+            assert!(pipelined < serial, "The pipeline did not reduce the sync time");
+        }
+    }
+}
@@ -0,0 +1,398 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    fmt,
+    sync::atomic::{AtomicU64, Ordering::Relaxed},
+    time::Duration,
+};
+
+use parking_lot::Mutex;
+use tokio::time::Instant;
+
+/// The default weights of the traffic classes, in the order of [`TrafficClass::ALL`].
+pub const DEFAULT_TRAFFIC_WEIGHTS: [u32; TrafficClass::COUNT] = [8, 4, 2, 1];
+/// The default maximum time an outbound message waits for bandwidth before it is dropped, in milliseconds.
+pub const DEFAULT_MAX_BANDWIDTH_DELAY_IN_MS: u64 = 2_000;
+
+/// The class of an outbound message, which determines its share of the outbound bandwidth.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TrafficClass {
+    /// The consensus messages, such as the BFT events.
+    Consensus,
+    /// The blocks served to peers.
+    Blocks,
+    /// The gossip of unconfirmed transactions and solutions.
+    Gossip,
+    /// The peer maintenance messages, such as pings and peer lists.
+    Maintenance,
+}
+
+impl TrafficClass {
+    /// The traffic classes, from the highest to the lowest default priority.
+    pub const ALL: [Self; Self::COUNT] = [Self::Consensus, Self::Blocks, Self::Gossip, Self::Maintenance];
+    /// The number of traffic classes.
+    pub const COUNT: usize = 4;
+
+    /// Returns the index of the traffic class in [`TrafficClass::ALL`].
+    pub const fn index(self) -> usize {
+        match self {
+            Self::Consensus => 0,
+            Self::Blocks => 1,
+            Self::Gossip => 2,
+            Self::Maintenance => 3,
+        }
+    }
+
+    /// Returns the name of the traffic class.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Consensus => "consensus",
+            Self::Blocks => "blocks",
+            Self::Gossip => "gossip",
+            Self::Maintenance => "maintenance",
+        }
+    }
+}
+
+impl fmt::Display for TrafficClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// The configuration of the outbound bandwidth scheduler.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BandwidthConfig {
+    /// The total outbound budget in bytes per second, or `None` if the outbound bandwidth is unlimited.
+    pub bytes_per_sec: Option<u64>,
+    /// The weights of the traffic classes, in the order of [`TrafficClass::ALL`]. Each class is guaranteed
+    /// its weighted share of the budget, and may use the share left unused by the other classes.
+    pub weights: [u32; TrafficClass::COUNT],
+    /// The maximum time an outbound message waits for bandwidth before it is dropped.
+    pub max_delay: Duration,
+}
+
+impl Default for BandwidthConfig {
+    /// Initializes an unlimited bandwidth configuration, with the default weights.
+    fn default() -> Self {
+        Self {
+            bytes_per_sec: None,
+            weights: DEFAULT_TRAFFIC_WEIGHTS,
+            max_delay: Duration::from_millis(DEFAULT_MAX_BANDWIDTH_DELAY_IN_MS),
+        }
+    }
+}
+
+impl BandwidthConfig {
+    /// Sets the total outbound budget, in bytes per second.
+    pub fn with_bytes_per_sec(mut self, bytes_per_sec: u64) -> Self {
+        self.bytes_per_sec = Some(bytes_per_sec);
+        self
+    }
+
+    /// Sets the weight of the given traffic class.
+    pub fn with_weight(mut self, class: TrafficClass, weight: u32) -> Self {
+        self.weights[class.index()] = weight;
+        self
+    }
+
+    /// Sets the maximum time an outbound message waits for bandwidth before it is dropped.
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+}
+
+/// A token bucket, whose balance in bytes may become negative, as a message is only measured once it is sent.
+#[derive(Debug)]
+struct Bucket {
+    /// The number of bytes that may be sent.
+    balance: f64,
+    /// The number of bytes added per second.
+    rate: f64,
+    /// The maximum balance, which bounds the bursts.
+    capacity: f64,
+    /// The time at which the balance was last updated.
+    updated_at: Instant,
+}
+
+impl Bucket {
+    /// Initializes a full bucket with the given rate, holding up to one second of bytes.
+    fn new(rate: f64, now: Instant) -> Self {
+        Self { balance: rate, rate, capacity: rate, updated_at: now }
+    }
+
+    /// Adds the bytes accrued since the last update.
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.balance = (self.balance + elapsed * self.rate).min(self.capacity);
+        self.updated_at = now;
+    }
+
+    /// Returns the time until the balance is positive, or `None` if it never will be.
+    fn time_until_positive(&self) -> Option<Duration> {
+        match self.balance > 0.0 {
+            true => Some(Duration::ZERO),
+            false if self.rate > 0.0 => Some(Duration::from_secs_f64((-self.balance + 1.0) / self.rate)),
+            false => None,
+        }
+    }
+}
+
+/// The buckets of a limited outbound bandwidth.
+#[derive(Debug)]
+struct Buckets {
+    /// The bucket of the total budget.
+    total: Bucket,
+    /// The buckets of the guaranteed shares of the traffic classes.
+    classes: [Bucket; TrafficClass::COUNT],
+}
+
+/// Schedules the outbound messages of the node within its bandwidth budget.
+///
+/// Each traffic class is guaranteed its weighted share of the budget, so that consensus messages are sent
+/// even when serving blocks saturates the budget, and may borrow the share left unused by the other classes.
+/// A message that cannot be sent within the maximum delay is dropped, instead of blocking its writer.
+#[derive(Debug)]
+pub struct BandwidthScheduler {
+    /// The configuration of the scheduler.
+    config: BandwidthConfig,
+    /// The buckets, or `None` if the outbound bandwidth is unlimited.
+    buckets: Option<Mutex<Buckets>>,
+    /// The number of bytes sent, per traffic class.
+    bytes_sent: [AtomicU64; TrafficClass::COUNT],
+    /// The number of dropped messages, per traffic class.
+    num_dropped: [AtomicU64; TrafficClass::COUNT],
+}
+
+impl BandwidthScheduler {
+    /// Initializes a new bandwidth scheduler with the given configuration.
+    pub fn new(config: BandwidthConfig) -> Self {
+        let now = Instant::now();
+        let buckets = config.bytes_per_sec.map(|bytes_per_sec| {
+            let total_rate = bytes_per_sec as f64;
+            let total_weight = config.weights.iter().map(|weight| *weight as f64).sum::<f64>();
+            let class_rate = |class: TrafficClass| match total_weight > 0.0 {
+                true => total_rate * config.weights[class.index()] as f64 / total_weight,
+                false => 0.0,
+            };
+            Mutex::new(Buckets {
+                total: Bucket::new(total_rate, now),
+                classes: TrafficClass::ALL.map(|class| Bucket::new(class_rate(class), now)),
+            })
+        });
+        Self { config, buckets, bytes_sent: Default::default(), num_dropped: Default::default() }
+    }
+
+    /// Returns the configuration of the scheduler.
+    pub fn config(&self) -> &BandwidthConfig {
+        &self.config
+    }
+
+    /// Returns `true` if the outbound bandwidth is unlimited.
+    pub fn is_unlimited(&self) -> bool {
+        self.buckets.is_none()
+    }
+
+    /// Returns the number of bytes sent in the given traffic class.
+    pub fn bytes_sent(&self, class: TrafficClass) -> u64 {
+        self.bytes_sent[class.index()].load(Relaxed)
+    }
+
+    /// Returns the number of messages dropped in the given traffic class.
+    pub fn num_dropped(&self, class: TrafficClass) -> u64 {
+        self.num_dropped[class.index()].load(Relaxed)
+    }
+
+    /// Waits until a message of the given traffic class may be sent, and returns `true`,
+    /// or returns `false` if the message should be dropped, as it cannot be sent within the maximum delay.
+    pub async fn acquire(&self, class: TrafficClass) -> bool {
+        let deadline = Instant::now() + self.config.max_delay;
+        loop {
+            let now = Instant::now();
+            match self.time_until_admitted(class, now) {
+                Some(wait) if wait.is_zero() => return true,
+                // Wait for the bandwidth, if it becomes available before the deadline.
+                Some(wait) if now + wait <= deadline => tokio::time::sleep(wait).await,
+                // Otherwise, drop the message.
+                _ => {
+                    self.register_dropped(class);
+                    return false;
+                }
+            }
+        }
+    }
+
+    /// Records a message of the given size that was sent in the given traffic class.
+    pub fn record(&self, class: TrafficClass, num_bytes: usize) {
+        self.record_at(class, num_bytes, Instant::now())
+    }
+
+    /// Returns the time until a message of the given traffic class may be sent at the given time,
+    /// or `None` if it never may be sent.
+    ///
+    /// A message may be sent if its class has not used up its guaranteed share, or if the total budget is not used up.
+    fn time_until_admitted(&self, class: TrafficClass, now: Instant) -> Option<Duration> {
+        let Some(buckets) = &self.buckets else {
+            return Some(Duration::ZERO);
+        };
+        let mut buckets = buckets.lock();
+        buckets.total.refill(now);
+        let bucket = &mut buckets.classes[class.index()];
+        bucket.refill(now);
+        match (bucket.time_until_positive(), buckets.total.time_until_positive()) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    /// Records a message of the given size that was sent in the given traffic class at the given time.
+    ///
+    /// The message is charged to the guaranteed share of its class if the share is not used up,
+    /// as it was then admitted on that share, and is always charged to the total budget.
+    fn record_at(&self, class: TrafficClass, num_bytes: usize, now: Instant) {
+        self.bytes_sent[class.index()].fetch_add(num_bytes as u64, Relaxed);
+        if let Some(buckets) = &self.buckets {
+            let mut buckets = buckets.lock();
+            buckets.total.refill(now);
+            buckets.total.balance -= num_bytes as f64;
+            let bucket = &mut buckets.classes[class.index()];
+            bucket.refill(now);
+            if bucket.balance > 0.0 {
+                bucket.balance -= num_bytes as f64;
+            }
+        }
+    }
+
+    /// Registers a dropped message in the given traffic class.
+    fn register_dropped(&self, class: TrafficClass) {
+        self.num_dropped[class.index()].fetch_add(1, Relaxed);
+        #[cfg(feature = "metrics")]
+        metrics::increment_counter(metrics::tcp::DROPPED_OUTBOUND_MESSAGES);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The duration of a step of the synthetic message streams.
+    const STEP: Duration = Duration::from_millis(1);
+
+    /// Runs the synthetic message streams for the given duration, where each stream is a traffic class
+    /// with the size of its messages, that sends a message whenever it is admitted, and returns the bytes sent per class.
+    fn run_streams(scheduler: &BandwidthScheduler, streams: &[(TrafficClass, usize)], duration: Duration) -> Vec<u64> {
+        let start = Instant::now();
+        let num_steps = duration.as_millis() as u32;
+        for step in 0..num_steps {
+            let now = start + STEP * step;
+            // Rotate the order of the streams, so that no stream is favored by the order of the checks.
+            for index in 0..streams.len() {
+                let (class, size) = streams[(index + step as usize) % streams.len()];
+                if scheduler.time_until_admitted(class, now) == Some(Duration::ZERO) {
+                    scheduler.record_at(class, size, now);
+                }
+            }
+        }
+        streams.iter().map(|(class, _)| scheduler.bytes_sent(*class)).collect()
+    }
+
+    #[test]
+    fn test_unlimited_bandwidth() {
+        let scheduler = BandwidthScheduler::new(BandwidthConfig::default());
+        assert!(scheduler.is_unlimited());
+        // Ensure every message is admitted at once.
+        let sent = run_streams(&scheduler, &[(TrafficClass::Blocks, 1 << 20)], Duration::from_millis(100));
+        assert_eq!(sent, vec![100 << 20]);
+    }
+
+    #[test]
+    fn test_bandwidth_is_weighted() {
+        const BUDGET: u64 = 100_000;
+        const SECS: u64 = 20;
+
+        let config = BandwidthConfig::default()
+            .with_bytes_per_sec(BUDGET)
+            .with_weight(TrafficClass::Consensus, 3)
+            .with_weight(TrafficClass::Blocks, 1)
+            .with_weight(TrafficClass::Gossip, 0)
+            .with_weight(TrafficClass::Maintenance, 0);
+        let scheduler = BandwidthScheduler::new(config);
+
+        // Saturate the budget with both classes.
+        let streams = [(TrafficClass::Consensus, 1_000), (TrafficClass::Blocks, 1_000)];
+        let sent = run_streams(&scheduler, &streams, Duration::from_secs(SECS));
+
+        // Ensure the total stays within the budget, up to the initial bursts.
+        let total = sent.iter().sum::<u64>();
+        assert!(total <= (SECS + 3) * BUDGET, "The total of {total} bytes exceeds the budget");
+        assert!(total >= (SECS - 1) * BUDGET, "The total of {total} bytes does not use the budget");
+        // Ensure the consensus class receives about 3/4 of the total.
+        let share = sent[0] as f64 / total as f64;
+        assert!((0.65..=0.85).contains(&share), "The consensus share is {share}");
+    }
+
+    #[test]
+    fn test_unused_bandwidth_is_borrowed() {
+        const BUDGET: u64 = 100_000;
+        const SECS: u64 = 20;
+
+        let scheduler = BandwidthScheduler::new(BandwidthConfig::default().with_bytes_per_sec(BUDGET));
+        // Ensure a single class uses the whole budget, beyond its guaranteed share.
+        let sent = run_streams(&scheduler, &[(TrafficClass::Blocks, 10_000)], Duration::from_secs(SECS));
+        assert!(sent[0] >= (SECS - 1) * BUDGET, "The blocks class only sent {} bytes", sent[0]);
+        assert!(sent[0] <= (SECS + 3) * BUDGET, "The blocks class sent {} bytes", sent[0]);
+    }
+
+    #[test]
+    fn test_consensus_is_not_starved() {
+        const BUDGET: u64 = 100_000;
+        const SECS: u64 = 20;
+
+        let scheduler = BandwidthScheduler::new(BandwidthConfig::default().with_bytes_per_sec(BUDGET));
+        // Saturate the budget with large block responses, and every other class.
+        let streams = [
+            (TrafficClass::Blocks, 64 * 1024),
+            (TrafficClass::Gossip, 4 * 1024),
+            (TrafficClass::Maintenance, 1024),
+            (TrafficClass::Consensus, 500),
+        ];
+        let sent = run_streams(&scheduler, &streams, Duration::from_secs(SECS));
+
+        // Ensure the consensus class receives at least its guaranteed share (8/15 with the default weights).
+        let guaranteed = SECS * BUDGET * 8 / 15;
+        assert!(sent[3] >= guaranteed * 95 / 100, "The consensus class only sent {} of {guaranteed} bytes", sent[3]);
+        // Ensure the consensus class is admitted promptly, even as the budget is used up by the other classes.
+        let now = Instant::now() + Duration::from_secs(SECS);
+        scheduler.record_at(TrafficClass::Blocks, 1 << 20, now);
+        let wait = scheduler.time_until_admitted(TrafficClass::Consensus, now).unwrap();
+        assert!(wait <= Duration::from_millis(100), "The consensus class waits {wait:?}");
+    }
+
+    #[tokio::test]
+    async fn test_message_is_dropped_after_max_delay() {
+        let config = BandwidthConfig::default().with_bytes_per_sec(1_000).with_max_delay(Duration::from_millis(50));
+        let scheduler = BandwidthScheduler::new(config);
+
+        // Use up the budget, and ensure the next message is dropped instead of waiting.
+        assert!(scheduler.acquire(TrafficClass::Gossip).await);
+        scheduler.record(TrafficClass::Gossip, 100_000);
+        assert!(!scheduler.acquire(TrafficClass::Gossip).await);
+        assert_eq!(scheduler.num_dropped(TrafficClass::Gossip), 1);
+        // Ensure the consensus class is still admitted within its guaranteed share.
+        assert!(scheduler.acquire(TrafficClass::Consensus).await);
+        assert_eq!(scheduler.num_dropped(TrafficClass::Consensus), 0);
+    }
+}
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use std::{
+    collections::HashSet,
     io::{self, ErrorKind::*},
     net::{IpAddr, Ipv4Addr, SocketAddr},
 };
@@ -54,6 +55,23 @@ pub struct Config {
     pub max_connections: u16,
     /// The maximum time (in milliseconds) allowed to establish a raw (before the [`Handshake`] protocol) TCP connection.
     pub connection_timeout_ms: u16,
+    /// The maximum number of connections, either pending or active, with a single remote IP address.
+    ///
+    /// note: Only inbound connection attempts are refused; the IP addresses in [`Config::trusted_ips`] are exempt.
+    pub max_connections_per_ip: u16,
+    /// The IP addresses that are exempt from [`Config::max_connections_per_ip`].
+    pub trusted_ips: HashSet<IpAddr>,
+    /// The maximum number of inbound connections that may be set up (e.g. perform the [`Handshake`]) at any given time.
+    /// Further inbound connection attempts are refused, before reading from them.
+    pub max_concurrent_handshakes: u16,
+    /// The maximum time (in milliseconds) allowed for an inbound connection to send its first bytes.
+    ///
+    /// note: Tcp needs to implement the [`Handshake`] protocol in order for it to have any effect.
+    pub first_message_timeout_ms: u16,
+    /// The SOCKS5 proxy through which the outbound connections are established, if any.
+    ///
+    /// note: Inbound connections are unaffected; [`Config::connection_timeout_ms`] covers the proxy negotiation too.
+    pub proxy: Option<SocketAddr>,
 }
 
 impl Config {
@@ -90,6 +108,11 @@ impl Default for Config {
             fatal_io_errors: vec![ConnectionReset, ConnectionAborted, BrokenPipe, InvalidData, UnexpectedEof],
             max_connections: 100,
             connection_timeout_ms: 1_000,
+            max_connections_per_ip: 4,
+            trusted_ips: Default::default(),
+            max_concurrent_handshakes: 16,
+            first_message_timeout_ms: 1_000,
+            proxy: None,
         }
     }
 }
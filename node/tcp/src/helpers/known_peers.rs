@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{collections::HashMap, net::SocketAddr, sync::Arc};
+use std::{borrow::Cow, collections::HashMap, net::SocketAddr, sync::Arc};
 
 use parking_lot::RwLock;
 
@@ -63,4 +63,11 @@ impl KnownPeers {
             stats.register_failure();
         }
     }
+
+    /// Registers an outbound message of the given type to the given address that was dropped from a full queue.
+    pub fn register_dropped(&self, to: SocketAddr, message_type: Cow<'static, str>) {
+        if let Some(stats) = self.0.read().get(&to) {
+            stats.register_dropped(message_type);
+        }
+    }
 }
@@ -12,6 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod bandwidth;
+pub use bandwidth::*;
+
 mod config;
 pub use config::Config;
 
@@ -21,8 +24,15 @@ pub use connections::{Connection, ConnectionSide};
 mod known_peers;
 pub use known_peers::KnownPeers;
 
+mod outbound_queue;
+pub use outbound_queue::QueuePolicy;
+pub(crate) use outbound_queue::{OutboundQueue, Pushed};
+
 mod stats;
-pub use stats::Stats;
+pub use stats::{RefusedReason, Stats};
+
+mod socks;
+pub use socks::{connect_through_proxy, is_proxy_unreachable, resolve_through_proxy, ProxyError, ProxyTarget};
 
 use tracing::{debug_span, error_span, info_span, trace_span, warn_span, Span};
 
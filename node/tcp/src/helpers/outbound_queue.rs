@@ -0,0 +1,167 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::VecDeque;
+
+use parking_lot::Mutex;
+use tokio::sync::Notify;
+
+/// Determines what happens to an outbound message if the outbound queue of its connection is full.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum QueuePolicy {
+    /// The message is refused if the queue is full.
+    #[default]
+    Refuse,
+    /// The message is gossip; if the queue is full, the oldest queued gossip is dropped to make room for it.
+    Gossip,
+    /// The message is a direct response, which is never dropped; the queue may exceed its capacity for it,
+    /// in which case the connection remains congested until the queue drains.
+    Response,
+}
+
+/// The outcome of pushing a message to an [`OutboundQueue`].
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Pushed<T> {
+    /// The message was queued.
+    Queued,
+    /// The message was queued in place of the oldest queued gossip, which is returned.
+    Evicted(T),
+    /// The message was not queued, as the queue is full; it is returned.
+    Refused(T),
+}
+
+/// The bounded queue of the outbound messages of a single connection.
+pub(crate) struct OutboundQueue<T> {
+    /// The queued messages, alongside their policies.
+    entries: Mutex<VecDeque<(T, QueuePolicy)>>,
+    /// Notifies the writer of a newly queued message.
+    notify: Notify,
+    /// The number of messages the queue holds before it is considered full.
+    capacity: usize,
+}
+
+impl<T> OutboundQueue<T> {
+    /// Initializes a new queue with the given capacity.
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self { entries: Default::default(), notify: Default::default(), capacity }
+    }
+
+    /// Returns the number of queued messages.
+    pub(crate) fn len(&self) -> usize {
+        self.entries.lock().len()
+    }
+
+    /// Returns `true` if the queue is full, i.e. its messages are not written as fast as they are queued.
+    pub(crate) fn is_congested(&self) -> bool {
+        self.len() >= self.capacity
+    }
+
+    /// Queues the given message, applying its policy if the queue is full. This method never waits.
+    pub(crate) fn push(&self, message: T, policy: QueuePolicy) -> Pushed<T> {
+        let mut entries = self.entries.lock();
+        let outcome = if entries.len() < self.capacity || policy == QueuePolicy::Response {
+            Pushed::Queued
+        } else if policy == QueuePolicy::Gossip {
+            // Drop the oldest queued gossip, if there is any.
+            match entries.iter().position(|(_, policy)| *policy == QueuePolicy::Gossip) {
+                Some(index) => Pushed::Evicted(entries.remove(index).expect("the index is in bounds").0),
+                None => return Pushed::Refused(message),
+            }
+        } else {
+            return Pushed::Refused(message);
+        };
+        entries.push_back((message, policy));
+        drop(entries);
+
+        self.notify.notify_one();
+        outcome
+    }
+
+    /// Returns the oldest queued message, waiting for one if the queue is empty.
+    pub(crate) async fn pop(&self) -> T {
+        loop {
+            let entry = self.entries.lock().pop_front();
+            if let Some((message, _)) = entry {
+                return message;
+            }
+            // Note: A notification sent in the meantime is stored, so it is not missed.
+            self.notify.notified().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_within_capacity() {
+        let queue = OutboundQueue::new(2);
+        assert_eq!(queue.push(1, QueuePolicy::Refuse), Pushed::Queued);
+        assert_eq!(queue.push(2, QueuePolicy::Gossip), Pushed::Queued);
+        assert_eq!(queue.len(), 2);
+        assert!(queue.is_congested());
+    }
+
+    #[test]
+    fn test_full_queue_drops_the_oldest_gossip() {
+        let queue = OutboundQueue::new(3);
+        assert_eq!(queue.push(1, QueuePolicy::Refuse), Pushed::Queued);
+        assert_eq!(queue.push(2, QueuePolicy::Gossip), Pushed::Queued);
+        assert_eq!(queue.push(3, QueuePolicy::Gossip), Pushed::Queued);
+
+        // The oldest gossip is dropped for new gossip.
+        assert_eq!(queue.push(4, QueuePolicy::Gossip), Pushed::Evicted(2));
+        // Other messages are refused, even if there is gossip to drop.
+        assert_eq!(queue.push(5, QueuePolicy::Refuse), Pushed::Refused(5));
+        assert_eq!(queue.len(), 3);
+    }
+
+    #[test]
+    fn test_full_queue_without_gossip_refuses_gossip() {
+        let queue = OutboundQueue::new(1);
+        assert_eq!(queue.push(1, QueuePolicy::Refuse), Pushed::Queued);
+        assert_eq!(queue.push(2, QueuePolicy::Gossip), Pushed::Refused(2));
+    }
+
+    #[test]
+    fn test_full_queue_never_drops_responses() {
+        let queue = OutboundQueue::new(1);
+        assert_eq!(queue.push(1, QueuePolicy::Gossip), Pushed::Queued);
+        assert_eq!(queue.push(2, QueuePolicy::Response), Pushed::Queued);
+        assert_eq!(queue.push(3, QueuePolicy::Response), Pushed::Queued);
+        assert_eq!(queue.len(), 3);
+        assert!(queue.is_congested());
+    }
+
+    #[tokio::test]
+    async fn test_pop_in_order() {
+        let queue = OutboundQueue::new(4);
+        queue.push(1, QueuePolicy::Gossip);
+        queue.push(2, QueuePolicy::Response);
+        assert_eq!(queue.pop().await, 1);
+        assert_eq!(queue.pop().await, 2);
+        assert!(!queue.is_congested());
+    }
+
+    #[tokio::test]
+    async fn test_pop_waits_for_a_message() {
+        let queue = std::sync::Arc::new(OutboundQueue::new(4));
+        let queue_clone = queue.clone();
+        let popper = tokio::spawn(async move { queue_clone.pop().await });
+        tokio::task::yield_now().await;
+        queue.push(7, QueuePolicy::Refuse);
+        assert_eq!(popper.await.unwrap(), 7);
+    }
+}
@@ -0,0 +1,251 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    error::Error,
+    fmt,
+    io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+/// The version of the SOCKS protocol.
+const SOCKS_VERSION: u8 = 0x05;
+/// The authentication method that requires no authentication.
+const NO_AUTHENTICATION: u8 = 0x00;
+/// The command to connect to a target.
+const COMMAND_CONNECT: u8 = 0x01;
+/// The command to resolve a hostname; this is an extension of the protocol, supported by Tor.
+const COMMAND_RESOLVE: u8 = 0xF0;
+/// The type of an IPv4 address.
+const ADDRESS_IPV4: u8 = 0x01;
+/// The type of a domain name.
+const ADDRESS_DOMAIN: u8 = 0x03;
+/// The type of an IPv6 address.
+const ADDRESS_IPV6: u8 = 0x04;
+/// The reply indicating the request succeeded.
+const REPLY_SUCCEEDED: u8 = 0x00;
+
+/// The target of a request through a SOCKS5 proxy.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProxyTarget<'a> {
+    /// A socket address.
+    Addr(SocketAddr),
+    /// A hostname and a port, which are resolved by the proxy.
+    Domain(&'a str, u16),
+}
+
+/// The failure of a request through a SOCKS5 proxy.
+#[derive(Debug)]
+pub enum ProxyError {
+    /// The proxy could not be reached, or did not follow the protocol.
+    Unreachable { proxy: SocketAddr, reason: String },
+    /// The proxy was reached, but could not reach the target, with the given reply code.
+    TargetUnreachable { proxy: SocketAddr, reply: u8 },
+}
+
+impl ProxyError {
+    /// Returns a description of the given reply code.
+    fn describe_reply(reply: u8) -> &'static str {
+        match reply {
+            0x01 => "general failure",
+            0x02 => "connection not allowed by the ruleset",
+            0x03 => "network unreachable",
+            0x04 => "host unreachable",
+            0x05 => "connection refused",
+            0x06 => "TTL expired",
+            0x07 => "command not supported",
+            0x08 => "address type not supported",
+            _ => "unknown reply",
+        }
+    }
+}
+
+impl fmt::Display for ProxyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Unreachable { proxy, reason } => write!(f, "the proxy {proxy} is unreachable ({reason})"),
+            Self::TargetUnreachable { proxy, reply } => {
+                write!(f, "the proxy {proxy} could not reach the target ({})", Self::describe_reply(*reply))
+            }
+        }
+    }
+}
+
+impl Error for ProxyError {}
+
+impl From<ProxyError> for io::Error {
+    fn from(error: ProxyError) -> Self {
+        let kind = match error {
+            ProxyError::Unreachable { .. } => io::ErrorKind::NotConnected,
+            ProxyError::TargetUnreachable { .. } => io::ErrorKind::ConnectionRefused,
+        };
+        io::Error::new(kind, error)
+    }
+}
+
+/// Returns `true` if the given error was caused by an unreachable proxy, rather than by an unreachable target.
+pub fn is_proxy_unreachable(error: &io::Error) -> bool {
+    matches!(error.get_ref().and_then(|error| error.downcast_ref::<ProxyError>()), Some(ProxyError::Unreachable { .. }))
+}
+
+/// Connects to the given target through the given SOCKS5 proxy.
+pub async fn connect_through_proxy(proxy: SocketAddr, target: ProxyTarget<'_>) -> io::Result<TcpStream> {
+    let mut stream = open(proxy).await?;
+    request(&mut stream, proxy, COMMAND_CONNECT, target).await?;
+    Ok(stream)
+}
+
+/// Resolves the given hostname through the given SOCKS5 proxy.
+///
+/// note: This relies on the `RESOLVE` extension of Tor, so that the hostname is not resolved locally.
+pub async fn resolve_through_proxy(proxy: SocketAddr, host: &str) -> io::Result<IpAddr> {
+    let mut stream = open(proxy).await?;
+    let addr = request(&mut stream, proxy, COMMAND_RESOLVE, ProxyTarget::Domain(host, 0)).await?;
+    Ok(addr.ip())
+}
+
+/// Connects to the given proxy, and negotiates a session without authentication.
+async fn open(proxy: SocketAddr) -> io::Result<TcpStream> {
+    let unreachable = |reason: String| ProxyError::Unreachable { proxy, reason };
+
+    let mut stream = TcpStream::connect(proxy).await.map_err(|e| unreachable(e.to_string()))?;
+    stream.write_all(&[SOCKS_VERSION, 1, NO_AUTHENTICATION]).await.map_err(|e| unreachable(e.to_string()))?;
+    let mut reply = [0u8; 2];
+    stream.read_exact(&mut reply).await.map_err(|e| unreachable(e.to_string()))?;
+    match reply {
+        [SOCKS_VERSION, NO_AUTHENTICATION] => Ok(stream),
+        [SOCKS_VERSION, _] => Err(unreachable("the proxy requires authentication".to_string()).into()),
+        _ => Err(unreachable("the proxy does not support SOCKS5".to_string()).into()),
+    }
+}
+
+/// Sends the given command for the given target, and returns the address bound by the proxy.
+async fn request(
+    stream: &mut TcpStream,
+    proxy: SocketAddr,
+    command: u8,
+    target: ProxyTarget<'_>,
+) -> io::Result<SocketAddr> {
+    let unreachable = |reason: String| ProxyError::Unreachable { proxy, reason };
+
+    // Prepare the request.
+    let mut bytes = vec![SOCKS_VERSION, command, 0x00];
+    let port = match target {
+        ProxyTarget::Addr(SocketAddr::V4(addr)) => {
+            bytes.push(ADDRESS_IPV4);
+            bytes.extend_from_slice(&addr.ip().octets());
+            addr.port()
+        }
+        ProxyTarget::Addr(SocketAddr::V6(addr)) => {
+            bytes.push(ADDRESS_IPV6);
+            bytes.extend_from_slice(&addr.ip().octets());
+            addr.port()
+        }
+        ProxyTarget::Domain(host, port) => {
+            let length = u8::try_from(host.len()).map_err(|_| {
+                io::Error::new(io::ErrorKind::InvalidInput, format!("The hostname '{host}' is too long"))
+            })?;
+            bytes.push(ADDRESS_DOMAIN);
+            bytes.push(length);
+            bytes.extend_from_slice(host.as_bytes());
+            port
+        }
+    };
+    bytes.extend_from_slice(&port.to_be_bytes());
+    stream.write_all(&bytes).await.map_err(|e| unreachable(e.to_string()))?;
+
+    // Read the reply.
+    let mut header = [0u8; 4];
+    stream.read_exact(&mut header).await.map_err(|e| unreachable(e.to_string()))?;
+    let [version, reply, _, address_type] = header;
+    if version != SOCKS_VERSION {
+        return Err(unreachable("the proxy does not support SOCKS5".to_string()).into());
+    }
+    if reply != REPLY_SUCCEEDED {
+        return Err(ProxyError::TargetUnreachable { proxy, reply }.into());
+    }
+    let ip = match address_type {
+        ADDRESS_IPV4 => {
+            let mut octets = [0u8; 4];
+            stream.read_exact(&mut octets).await.map_err(|e| unreachable(e.to_string()))?;
+            IpAddr::V4(Ipv4Addr::from(octets))
+        }
+        ADDRESS_IPV6 => {
+            let mut octets = [0u8; 16];
+            stream.read_exact(&mut octets).await.map_err(|e| unreachable(e.to_string()))?;
+            IpAddr::V6(Ipv6Addr::from(octets))
+        }
+        _ => {
+            return Err(unreachable(format!("the proxy replied with an unsupported address type {address_type}")).into())
+        }
+    };
+    let mut port = [0u8; 2];
+    stream.read_exact(&mut port).await.map_err(|e| unreachable(e.to_string()))?;
+    Ok(SocketAddr::new(ip, u16::from_be_bytes(port)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    /// Spawns a proxy that accepts a single session, and replies to its request with the given reply code.
+    async fn sample_proxy(reply: u8) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut greeting = [0u8; 3];
+            stream.read_exact(&mut greeting).await.unwrap();
+            stream.write_all(&[SOCKS_VERSION, NO_AUTHENTICATION]).await.unwrap();
+            // Read a request for an IPv4 address.
+            let mut request = [0u8; 10];
+            stream.read_exact(&mut request).await.unwrap();
+            stream.write_all(&[SOCKS_VERSION, reply, 0, ADDRESS_IPV4, 10, 0, 0, 1, 0x10, 0x22]).await.unwrap();
+        });
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_proxy_unreachable() {
+        // Reserve an address, and release it, so that nothing listens on it.
+        let proxy = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+        let target = ProxyTarget::Addr("127.0.0.1:4130".parse().unwrap());
+        let error = connect_through_proxy(proxy, target).await.unwrap_err();
+        assert!(is_proxy_unreachable(&error));
+        assert_eq!(error.kind(), io::ErrorKind::NotConnected);
+    }
+
+    #[tokio::test]
+    async fn test_target_unreachable() {
+        let proxy = sample_proxy(0x04).await;
+        let target = ProxyTarget::Addr("127.0.0.1:4130".parse().unwrap());
+        let error = connect_through_proxy(proxy, target).await.unwrap_err();
+        assert!(!is_proxy_unreachable(&error));
+        assert_eq!(error.kind(), io::ErrorKind::ConnectionRefused);
+        assert!(error.to_string().contains("host unreachable"));
+    }
+
+    #[tokio::test]
+    async fn test_connect_succeeds() {
+        let proxy = sample_proxy(REPLY_SUCCEEDED).await;
+        let target = ProxyTarget::Addr("127.0.0.1:4130".parse().unwrap());
+        assert!(connect_through_proxy(proxy, target).await.is_ok());
+    }
+}
@@ -12,7 +12,57 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::sync::atomic::{AtomicU64, Ordering::Relaxed};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    sync::atomic::{AtomicU64, Ordering::Relaxed},
+};
+
+use parking_lot::Mutex;
+
+/// The reason an inbound connection attempt was refused.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RefusedReason {
+    /// The connection is from the Tcp itself.
+    SelfConnect,
+    /// The maximum number of connections was reached.
+    MaxConnections,
+    /// The maximum number of connections with the remote IP address was reached.
+    MaxConnectionsPerIp,
+    /// The maximum number of concurrent inbound handshakes was reached.
+    MaxConcurrentHandshakes,
+    /// The connection did not send its first bytes in time.
+    FirstMessageTimeout,
+}
+
+impl RefusedReason {
+    /// The reasons, in the order of their indices.
+    pub const ALL: [Self; Self::COUNT] = [
+        Self::SelfConnect,
+        Self::MaxConnections,
+        Self::MaxConnectionsPerIp,
+        Self::MaxConcurrentHandshakes,
+        Self::FirstMessageTimeout,
+    ];
+    /// The number of reasons.
+    pub const COUNT: usize = 5;
+
+    /// Returns the index of the reason.
+    pub const fn index(&self) -> usize {
+        *self as usize
+    }
+
+    /// Returns the name of the reason, as used in the metrics.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::SelfConnect => "self_connect",
+            Self::MaxConnections => "max_connections",
+            Self::MaxConnectionsPerIp => "max_connections_per_ip",
+            Self::MaxConcurrentHandshakes => "max_concurrent_handshakes",
+            Self::FirstMessageTimeout => "first_message_timeout",
+        }
+    }
+}
 
 /// Contains statistics related to Tcp.
 #[derive(Default)]
@@ -27,6 +77,10 @@ pub struct Stats {
     bytes_received: AtomicU64,
     /// The number of failures.
     failures: AtomicU64,
+    /// The number of refused inbound connection attempts, by reason.
+    refused: [AtomicU64; RefusedReason::COUNT],
+    /// The number of outbound messages dropped from a full queue, by message type.
+    dropped: Mutex<HashMap<Cow<'static, str>, u64>>,
 }
 
 impl Stats {
@@ -63,8 +117,28 @@ impl Stats {
         self.bytes_received.fetch_add(size as u64, Relaxed);
     }
 
+    /// Returns the number of inbound connection attempts that were refused for the given reason.
+    pub fn refused(&self, reason: RefusedReason) -> u64 {
+        self.refused[reason.index()].load(Relaxed)
+    }
+
+    /// Returns the number of outbound messages that were dropped from a full queue, by message type.
+    pub fn dropped(&self) -> HashMap<Cow<'static, str>, u64> {
+        self.dropped.lock().clone()
+    }
+
     /// Registers a failure.
     pub fn register_failure(&self) {
         self.failures.fetch_add(1, Relaxed);
     }
+
+    /// Registers an inbound connection attempt that was refused for the given reason.
+    pub fn register_refused(&self, reason: RefusedReason) {
+        self.refused[reason.index()].fetch_add(1, Relaxed);
+    }
+
+    /// Registers an outbound message of the given type that was dropped from a full queue.
+    pub fn register_dropped(&self, message_type: Cow<'static, str>) {
+        *self.dropped.lock().entry(message_type).or_default() += 1;
+    }
 }
@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{any::Any, collections::HashMap, io, net::SocketAddr, sync::Arc};
+use std::{any::Any, borrow::Cow, collections::HashMap, io, net::SocketAddr, sync::Arc};
 
 use async_trait::async_trait;
 use futures_util::sink::SinkExt;
@@ -30,10 +30,14 @@ use crate::{
     protocols::{Protocol, ProtocolHandler, ReturnableConnection},
     Connection,
     ConnectionSide,
+    OutboundQueue,
+    Pushed,
+    QueuePolicy,
+    TrafficClass,
     P2P,
 };
 
-type WritingSenders = Arc<RwLock<HashMap<SocketAddr, mpsc::Sender<WrappedMessage>>>>;
+type WritingSenders = Arc<RwLock<HashMap<SocketAddr, Arc<OutboundQueue<WrappedMessage>>>>>;
 
 /// Can be used to specify and enable writing, i.e. sending outbound messages. If the [`Handshake`]
 /// protocol is enabled too, it goes into force only after the handshake has been concluded.
@@ -45,6 +49,7 @@ where
     /// The depth of per-connection queues used to send outbound messages; the greater it is, the more outbound
     /// messages the node can enqueue. Setting it to a large value is not recommended, as doing it might
     /// obscure potential issues with your implementation (like slow serialization) or network.
+    /// Once a queue is full, its messages are dropped or refused according to their [`QueuePolicy`].
     ///
     /// The default value is 1024.
     const MESSAGE_QUEUE_DEPTH: usize = 1024;
@@ -92,6 +97,27 @@ where
     /// The `side` param indicates the connection side **from the node's perspective**.
     fn codec(&self, addr: SocketAddr, side: ConnectionSide) -> Self::Codec;
 
+    /// Returns the [`TrafficClass`] of the given outbound message, which determines its share of the outbound
+    /// bandwidth if [`Tcp::set_bandwidth_scheduler`] was called.
+    ///
+    /// The default class is [`TrafficClass::Consensus`].
+    fn traffic_class(&self, _message: &Self::Message) -> TrafficClass {
+        TrafficClass::Consensus
+    }
+
+    /// Returns the [`QueuePolicy`] of the given outbound message, which determines whether it is dropped
+    /// or refused if the outbound queue of its connection is full.
+    ///
+    /// The default policy is [`QueuePolicy::Refuse`].
+    fn queue_policy(&self, _message: &Self::Message) -> QueuePolicy {
+        QueuePolicy::Refuse
+    }
+
+    /// Returns the type of the given outbound message, under which it is counted if it is dropped.
+    fn message_type(&self, _message: &Self::Message) -> Cow<'static, str> {
+        Cow::Borrowed("message")
+    }
+
     /// Sends the provided message to the specified [`SocketAddr`]. Returns as soon as the message is queued to
     /// be sent, without waiting for the actual delivery; instead, the caller is provided with a [`oneshot::Receiver`]
     /// which can be used to determine when and whether the message has been delivered.
@@ -100,22 +126,20 @@ where
     ///
     /// The following errors can be returned:
     /// - [`io::ErrorKind::NotConnected`] if the node is not connected to the provided address
-    /// - [`io::ErrorKind::Other`] if the outbound message queue for this address is full
+    /// - [`io::ErrorKind::Other`] if the outbound message queue for this address is full and the
+    ///   [`QueuePolicy`] of the message doesn't allow it to be queued
     /// - [`io::ErrorKind::Unsupported`] if [`Writing::enable_writing`] hadn't been called yet
     fn unicast(&self, addr: SocketAddr, message: Self::Message) -> io::Result<oneshot::Receiver<io::Result<()>>> {
         // access the protocol handler
         if let Some(handler) = self.tcp().protocols.writing.get() {
-            // find the message sender for the given address
-            if let Some(sender) = handler.senders.read().get(&addr).cloned() {
+            // find the message queue for the given address
+            if let Some(queue) = handler.senders.read().get(&addr).cloned() {
                 let (msg, delivery) = WrappedMessage::new(Box::new(message));
-                sender
-                    .try_send(msg)
-                    .map_err(|e| {
-                        error!(parent: self.tcp().span(), "can't send a message to {}: {}", addr, e);
-                        self.tcp().stats().register_failure();
-                        io::ErrorKind::Other.into()
-                    })
-                    .map(|_| delivery)
+                if self.queue_message(addr, &queue, msg) {
+                    Ok(delivery)
+                } else {
+                    Err(io::ErrorKind::Other.into())
+                }
             } else {
                 Err(io::ErrorKind::NotConnected.into())
             }
@@ -125,7 +149,8 @@ where
     }
 
     /// Broadcasts the provided message to all connected peers. Returns as soon as the message is queued to
-    /// be sent to all the peers, without waiting for the actual delivery. This method doesn't provide the
+    /// be sent to all the peers, without waiting for the actual delivery or for any peer's queue to drain;
+    /// if the queue of a peer is full, the [`QueuePolicy`] of the message applies. This method doesn't provide the
     /// means to check when and if the messages actually get delivered; you can achieve that by calling
     /// [`Writing::unicast`] for each address returned by [`Tcp::connected_addrs`].
     ///
@@ -139,12 +164,9 @@ where
         // access the protocol handler
         if let Some(handler) = self.tcp().protocols.writing.get() {
             let senders = handler.senders.read().clone();
-            for (addr, message_sender) in senders {
+            for (addr, queue) in senders {
                 let (msg, _delivery) = WrappedMessage::new(Box::new(message.clone()));
-                let _ = message_sender.try_send(msg).map_err(|e| {
-                    error!(parent: self.tcp().span(), "can't send a message to {}: {}", addr, e);
-                    self.tcp().stats().register_failure();
-                });
+                self.queue_message(addr, &queue, msg);
             }
 
             Ok(())
@@ -157,6 +179,13 @@ where
 /// This trait is used to restrict access to methods that would otherwise be public in [`Writing`].
 #[async_trait]
 trait WritingInternal: Writing {
+    /// Queues the given message to the given address, without waiting; returns `false` if it was refused.
+    /// A message that is dropped or refused is counted under its type.
+    fn queue_message(&self, addr: SocketAddr, queue: &OutboundQueue<WrappedMessage>, msg: WrappedMessage) -> bool;
+
+    /// Counts the given message to the given address as dropped, and notifies its sender.
+    fn register_dropped(&self, addr: SocketAddr, msg: WrappedMessage);
+
     /// Writes the given message to the network stream and returns the number of written bytes.
     async fn write_to_stream<W: AsyncWrite + Unpin + Send>(
         &self,
@@ -170,6 +199,30 @@ trait WritingInternal: Writing {
 
 #[async_trait]
 impl<W: Writing> WritingInternal for W {
+    fn queue_message(&self, addr: SocketAddr, queue: &OutboundQueue<WrappedMessage>, msg: WrappedMessage) -> bool {
+        let policy = self.queue_policy(msg.msg.downcast_ref().unwrap());
+        match queue.push(msg, policy) {
+            Pushed::Queued => true,
+            Pushed::Evicted(evicted) => {
+                self.register_dropped(addr, evicted);
+                true
+            }
+            Pushed::Refused(refused) => {
+                self.register_dropped(addr, refused);
+                self.tcp().stats().register_failure();
+                false
+            }
+        }
+    }
+
+    fn register_dropped(&self, addr: SocketAddr, msg: WrappedMessage) {
+        let message_type = self.message_type(msg.msg.downcast_ref().unwrap());
+        debug!(parent: self.tcp().span(), "dropped a '{}' message to {} (the queue is full)", message_type, addr);
+        self.tcp().known_peers().register_dropped(addr, message_type.clone());
+        self.tcp().stats().register_dropped(message_type);
+        let _ = msg.delivery_notification.send(Err(io::ErrorKind::Other.into()));
+    }
+
     async fn write_to_stream<A: AsyncWrite + Unpin + Send>(
         &self,
         message: Self::Message,
@@ -192,12 +245,12 @@ impl<W: Writing> WritingInternal for W {
         let writer = conn.writer.take().expect("missing connection writer!");
         let mut framed = FramedWrite::new(writer, codec);
 
-        let (outbound_message_sender, mut outbound_message_receiver) = mpsc::channel(Self::MESSAGE_QUEUE_DEPTH);
+        let outbound_queue = Arc::new(OutboundQueue::new(Self::MESSAGE_QUEUE_DEPTH));
 
-        // register the connection's message sender with the Writing protocol handler
-        conn_senders.write().insert(addr, outbound_message_sender);
+        // register the connection's message queue with the Writing protocol handler
+        conn_senders.write().insert(addr, outbound_queue.clone());
 
-        // this will automatically drop the sender upon a disconnect
+        // this will automatically drop the queue upon a disconnect
         let auto_cleanup = SenderCleanup { addr, senders: Arc::clone(conn_senders) };
 
         // use a channel to know when the writer task is ready
@@ -213,11 +266,26 @@ impl<W: Writing> WritingInternal for W {
             // move the cleanup into the task that gets aborted on disconnect
             let _auto_cleanup = auto_cleanup;
 
-            while let Some(wrapped_msg) = outbound_message_receiver.recv().await {
+            loop {
+                let wrapped_msg = outbound_queue.pop().await;
                 let msg = wrapped_msg.msg.downcast().unwrap();
 
+                // wait for the outbound bandwidth, if it is limited
+                let class = self_clone.traffic_class(&msg);
+                let scheduler = node.bandwidth_scheduler();
+                if let Some(scheduler) = scheduler {
+                    if !scheduler.acquire(class).await {
+                        debug!(parent: node.span(), "dropped a {} message to {} (out of bandwidth)", class, addr);
+                        let _ = wrapped_msg.delivery_notification.send(Err(io::ErrorKind::TimedOut.into()));
+                        continue;
+                    }
+                }
+
                 match self_clone.write_to_stream(*msg, &mut framed).await {
                     Ok(len) => {
+                        if let Some(scheduler) = scheduler {
+                            scheduler.record(class, len);
+                        }
                         let _ = wrapped_msg.delivery_notification.send(Ok(()));
                         node.known_peers().register_sent_message(addr, len);
                         node.stats().register_sent_message(len);
@@ -268,6 +336,13 @@ pub(crate) struct WritingHandler {
     senders: WritingSenders,
 }
 
+impl WritingHandler {
+    /// Returns `true` if the outbound queue of the given connection is full.
+    pub(crate) fn is_congested(&self, addr: SocketAddr) -> bool {
+        self.senders.read().get(&addr).map(|queue| queue.is_congested()).unwrap_or(false)
+    }
+}
+
 impl Protocol<Connection, io::Result<Connection>> for WritingHandler {
     fn trigger(&self, item: ReturnableConnection) {
         self.handler.trigger(item);
@@ -37,10 +37,14 @@ use tokio::{
 use tracing::*;
 
 use crate::{
+    connect_through_proxy,
     connections::{Connection, ConnectionSide, Connections},
     protocols::{Protocol, Protocols},
+    BandwidthScheduler,
     Config,
     KnownPeers,
+    ProxyTarget,
+    RefusedReason,
     Stats,
 };
 
@@ -71,12 +75,16 @@ pub struct InnerTcp {
     pub(crate) protocols: Protocols,
     /// A set of connections that have not been finalized yet.
     connecting: Mutex<HashSet<SocketAddr>>,
+    /// The number of inbound connections that are being set up.
+    num_inbound_connecting: AtomicUsize,
     /// Contains objects related to the node's active connections.
     connections: Connections,
     /// Collects statistics related to the node's peers.
     known_peers: KnownPeers,
     /// Collects statistics related to the node itself.
     stats: Stats,
+    /// The scheduler of the node's outbound bandwidth, if it is limited.
+    bandwidth: OnceCell<Arc<BandwidthScheduler>>,
     /// The node's tasks.
     pub(crate) tasks: Mutex<Vec<JoinHandle<()>>>,
 }
@@ -99,9 +107,11 @@ impl Tcp {
             listening_addr: Default::default(),
             protocols: Default::default(),
             connecting: Default::default(),
+            num_inbound_connecting: Default::default(),
             connections: Default::default(),
             known_peers: Default::default(),
             stats: Default::default(),
+            bandwidth: Default::default(),
             tasks: Default::default(),
         }));
 
@@ -134,6 +144,12 @@ impl Tcp {
         self.connections.is_connected(addr)
     }
 
+    /// Checks whether the outbound queue of the provided address is full, i.e. the connection doesn't keep up
+    /// with the messages sent to it. Always `false` if the [`Writing`](crate::protocols::Writing) protocol is disabled.
+    pub fn is_congested(&self, addr: SocketAddr) -> bool {
+        self.protocols.writing.get().map(|handler| handler.is_congested(addr)).unwrap_or(false)
+    }
+
     /// Checks if Tcp is currently setting up a connection with the provided address.
     pub fn is_connecting(&self, addr: SocketAddr) -> bool {
         self.connecting.lock().contains(&addr)
@@ -149,6 +165,12 @@ impl Tcp {
         self.connecting.lock().len()
     }
 
+    /// Returns the number of pending and active connections with the provided IP address.
+    pub fn num_connections_with_ip(&self, ip: IpAddr) -> usize {
+        let num_connecting = self.connecting.lock().iter().filter(|addr| addr.ip() == ip).count();
+        num_connecting + self.connections.addrs().iter().filter(|addr| addr.ip() == ip).count()
+    }
+
     /// Returns a list containing addresses of active connections.
     pub fn connected_addrs(&self) -> Vec<SocketAddr> {
         self.connections.addrs()
@@ -171,6 +193,18 @@ impl Tcp {
         &self.stats
     }
 
+    /// Sets the scheduler of the outbound bandwidth, which may be shared with other [`Tcp`] instances;
+    /// returns an error if a scheduler was already set.
+    pub fn set_bandwidth_scheduler(&self, scheduler: Arc<BandwidthScheduler>) -> io::Result<()> {
+        self.bandwidth.set(scheduler).map_err(|_| io::ErrorKind::AlreadyExists.into())
+    }
+
+    /// Returns the scheduler of the outbound bandwidth, if one was set.
+    #[inline]
+    pub fn bandwidth_scheduler(&self) -> Option<&Arc<BandwidthScheduler>> {
+        self.bandwidth.get()
+    }
+
     /// Returns the tracing [`Span`] associated with Tcp.
     #[inline]
     pub fn span(&self) -> &Span {
@@ -225,21 +259,25 @@ impl Tcp {
             return Err(io::ErrorKind::AlreadyExists.into());
         }
 
-        let stream =
-            match timeout(Duration::from_millis(self.config().connection_timeout_ms.into()), TcpStream::connect(addr))
-                .await
-            {
-                Ok(Ok(stream)) => Ok(stream),
-                Ok(err) => {
-                    self.connecting.lock().remove(&addr);
-                    err
-                }
-                Err(err) => {
-                    self.connecting.lock().remove(&addr);
-                    error!("connection timeout error: {}", err);
-                    Err(io::ErrorKind::TimedOut.into())
-                }
-            }?;
+        // Establish the connection, through the proxy if one is configured.
+        let dial = async {
+            match self.config().proxy {
+                Some(proxy) => connect_through_proxy(proxy, ProxyTarget::Addr(addr)).await,
+                None => TcpStream::connect(addr).await,
+            }
+        };
+        let stream = match timeout(Duration::from_millis(self.config().connection_timeout_ms.into()), dial).await {
+            Ok(Ok(stream)) => Ok(stream),
+            Ok(err) => {
+                self.connecting.lock().remove(&addr);
+                err
+            }
+            Err(err) => {
+                self.connecting.lock().remove(&addr);
+                error!("connection timeout error: {}", err);
+                Err(io::ErrorKind::TimedOut.into())
+            }
+        }?;
 
         let ret = self.adapt_stream(stream, addr, ConnectionSide::Initiator).await;
 
@@ -365,16 +403,21 @@ impl Tcp {
     fn handle_connection(&self, stream: TcpStream, addr: SocketAddr) {
         debug!(parent: self.span(), "Received a connection from {addr}");
 
-        if !self.can_add_connection() || self.is_self_connect(addr) {
-            debug!(parent: self.span(), "Rejecting the connection from {addr}");
+        // Refuse the connection before reading from it, if it exceeds any of the limits.
+        if let Err(reason) = self.check_inbound_connection(addr) {
+            debug!(parent: self.span(), "Rejecting the connection from {addr} ({})", reason.as_str());
+            self.stats.register_refused(reason);
             return;
         }
 
         self.connecting.lock().insert(addr);
+        self.num_inbound_connecting.fetch_add(1, Relaxed);
 
         let tcp = self.clone();
         tokio::spawn(async move {
-            if let Err(e) = tcp.adapt_stream(stream, addr, ConnectionSide::Responder).await {
+            let result = tcp.accept_stream(stream, addr).await;
+            tcp.num_inbound_connecting.fetch_sub(1, Relaxed);
+            if let Err(e) = result {
                 tcp.connecting.lock().remove(&addr);
                 tcp.known_peers().register_failure(addr);
                 error!(parent: tcp.span(), "Failed to connect with {addr}: {e}");
@@ -382,6 +425,45 @@ impl Tcp {
         });
     }
 
+    /// Checks whether the `Tcp` can accept an inbound connection from the provided address.
+    fn check_inbound_connection(&self, addr: SocketAddr) -> Result<(), RefusedReason> {
+        if !self.can_add_connection() {
+            return Err(RefusedReason::MaxConnections);
+        }
+        if self.is_self_connect(addr) {
+            return Err(RefusedReason::SelfConnect);
+        }
+        // Ensure a single host cannot occupy the connection slots, unless it is trusted.
+        if !self.config.trusted_ips.contains(&addr.ip())
+            && self.num_connections_with_ip(addr.ip()) >= self.config.max_connections_per_ip as usize
+        {
+            return Err(RefusedReason::MaxConnectionsPerIp);
+        }
+        // Ensure the handshakes in progress cannot tie up the node.
+        if self.num_inbound_connecting.load(Relaxed) >= self.config.max_concurrent_handshakes as usize {
+            return Err(RefusedReason::MaxConcurrentHandshakes);
+        }
+        Ok(())
+    }
+
+    /// Prepares a freshly accepted connection, once it sent its first bytes, if the handshake is enabled.
+    async fn accept_stream(&self, stream: TcpStream, addr: SocketAddr) -> io::Result<()> {
+        if self.protocols.handshake.get().is_some() {
+            // Drop the connection if it does not start the handshake promptly.
+            let first_message_timeout = Duration::from_millis(self.config.first_message_timeout_ms.into());
+            match timeout(first_message_timeout, stream.peek(&mut [0u8; 1])).await {
+                Ok(Ok(0)) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(Ok(_)) => (),
+                Ok(Err(e)) => return Err(e),
+                Err(_) => {
+                    self.stats.register_refused(RefusedReason::FirstMessageTimeout);
+                    return Err(io::ErrorKind::TimedOut.into());
+                }
+            }
+        }
+        self.adapt_stream(stream, addr, ConnectionSide::Responder).await
+    }
+
     /// Checks if the given IP address is the same as the listening address of this `Tcp`.
     fn is_self_connect(&self, addr: SocketAddr) -> bool {
         // SAFETY: if we're opening connections, this should never fail.
@@ -503,7 +585,19 @@ impl fmt::Debug for Tcp {
 mod tests {
     use super::*;
 
-    use std::net::{IpAddr, Ipv4Addr};
+    use crate::{
+        protocols::{Handshake, Writing},
+        QueuePolicy,
+        P2P,
+    };
+
+    use bytes::Bytes;
+    use std::{
+        borrow::Cow,
+        net::{IpAddr, Ipv4Addr},
+    };
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio_util::codec::BytesCodec;
 
     #[tokio::test]
     async fn test_new() {
@@ -675,6 +769,125 @@ mod tests {
         assert!(!tcp.is_connected(peer2_ip));
         assert!(!tcp.is_connecting(peer1_ip));
         assert!(!tcp.is_connecting(peer2_ip));
+        assert_eq!(tcp.stats().refused(RefusedReason::MaxConnections), 1);
+    }
+
+    /// A node whose handshakes never complete.
+    #[derive(Clone)]
+    struct StalledNode(Tcp);
+
+    impl P2P for StalledNode {
+        fn tcp(&self) -> &Tcp {
+            &self.0
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl Handshake for StalledNode {
+        async fn perform_handshake(&self, conn: Connection) -> io::Result<Connection> {
+            std::future::pending::<()>().await;
+            Ok(conn)
+        }
+    }
+
+    /// Waits until the provided condition holds, or panics after a second.
+    async fn wait_until(condition: impl Fn() -> bool) {
+        for _ in 0..100 {
+            if condition() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("The condition was not met in time");
+    }
+
+    #[tokio::test]
+    async fn test_max_connections_per_ip() {
+        let tcp = Tcp::new(Config {
+            listener_ip: Some(IpAddr::V4(Ipv4Addr::LOCALHOST)),
+            max_connections_per_ip: 2,
+            ..Default::default()
+        });
+        let node_ip = tcp.enable_listener().await.unwrap();
+
+        // Open more connections from a single IP than allowed.
+        let mut streams = Vec::new();
+        for _ in 0..5 {
+            streams.push(TcpStream::connect(node_ip).await.unwrap());
+        }
+        wait_until(|| tcp.stats().refused(RefusedReason::MaxConnectionsPerIp) == 3).await;
+        assert_eq!(tcp.num_connected(), 2);
+        assert_eq!(tcp.num_connections_with_ip(IpAddr::V4(Ipv4Addr::LOCALHOST)), 2);
+
+        // Ensure another IP can still connect.
+        let socket = tokio::net::TcpSocket::new_v4().unwrap();
+        socket.bind("127.0.0.2:0".parse().unwrap()).unwrap();
+        streams.push(socket.connect(node_ip).await.unwrap());
+        wait_until(|| tcp.num_connected() == 3).await;
+        assert_eq!(tcp.stats().refused(RefusedReason::MaxConnectionsPerIp), 3);
+    }
+
+    #[tokio::test]
+    async fn test_trusted_ip_is_exempt_from_the_per_ip_limit() {
+        let tcp = Tcp::new(Config {
+            listener_ip: Some(IpAddr::V4(Ipv4Addr::LOCALHOST)),
+            max_connections_per_ip: 1,
+            trusted_ips: [IpAddr::V4(Ipv4Addr::LOCALHOST)].into_iter().collect(),
+            ..Default::default()
+        });
+        let node_ip = tcp.enable_listener().await.unwrap();
+
+        let mut streams = Vec::new();
+        for _ in 0..3 {
+            streams.push(TcpStream::connect(node_ip).await.unwrap());
+        }
+        wait_until(|| tcp.num_connected() == 3).await;
+        assert_eq!(tcp.stats().refused(RefusedReason::MaxConnectionsPerIp), 0);
+    }
+
+    #[tokio::test]
+    async fn test_max_concurrent_handshakes() {
+        let node = StalledNode(Tcp::new(Config {
+            listener_ip: Some(IpAddr::V4(Ipv4Addr::LOCALHOST)),
+            max_connections_per_ip: 10,
+            max_concurrent_handshakes: 2,
+            first_message_timeout_ms: 100,
+            ..Default::default()
+        }));
+        node.enable_handshake().await;
+        let node_ip = node.tcp().enable_listener().await.unwrap();
+
+        // Start two handshakes that never complete.
+        let mut streams = Vec::new();
+        for _ in 0..2 {
+            let mut stream = TcpStream::connect(node_ip).await.unwrap();
+            stream.write_all(&[0u8]).await.unwrap();
+            streams.push(stream);
+        }
+        wait_until(|| node.tcp().num_connecting() == 2).await;
+
+        // Ensure any further connection is refused while they are in progress.
+        streams.push(TcpStream::connect(node_ip).await.unwrap());
+        wait_until(|| node.tcp().stats().refused(RefusedReason::MaxConcurrentHandshakes) == 1).await;
+        assert_eq!(node.tcp().num_connecting(), 2);
+        assert_eq!(node.tcp().num_connected(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_first_message_timeout() {
+        let node = StalledNode(Tcp::new(Config {
+            listener_ip: Some(IpAddr::V4(Ipv4Addr::LOCALHOST)),
+            first_message_timeout_ms: 100,
+            ..Default::default()
+        }));
+        node.enable_handshake().await;
+        let node_ip = node.tcp().enable_listener().await.unwrap();
+
+        // Connect without sending anything.
+        let _stream = TcpStream::connect(node_ip).await.unwrap();
+        wait_until(|| node.tcp().stats().refused(RefusedReason::FirstMessageTimeout) == 1).await;
+        wait_until(|| node.tcp().num_connecting() == 0).await;
+        assert_eq!(node.tcp().num_connected(), 0);
     }
 
     #[tokio::test]
@@ -705,4 +918,77 @@ mod tests {
         assert!(tcp.is_connected(peer_ip));
         assert!(!tcp.is_connecting(peer_ip));
     }
+
+    /// A node that broadcasts raw bytes as gossip.
+    #[derive(Clone)]
+    struct GossipNode(Tcp);
+
+    impl P2P for GossipNode {
+        fn tcp(&self) -> &Tcp {
+            &self.0
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl Writing for GossipNode {
+        type Codec = BytesCodec;
+        type Message = Bytes;
+
+        const MESSAGE_QUEUE_DEPTH: usize = 8;
+
+        fn codec(&self, _addr: SocketAddr, _side: ConnectionSide) -> Self::Codec {
+            BytesCodec::new()
+        }
+
+        fn queue_policy(&self, _message: &Self::Message) -> QueuePolicy {
+            QueuePolicy::Gossip
+        }
+
+        fn message_type(&self, _message: &Self::Message) -> Cow<'static, str> {
+            Cow::Borrowed("gossip")
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slow_peer_does_not_delay_broadcasts() {
+        // The size of each broadcast message.
+        const MESSAGE_SIZE: usize = 64 * 1024;
+        // The number of broadcasts, which exceeds the socket buffers of the slow peer.
+        const NUM_BROADCASTS: usize = 512;
+
+        let node =
+            GossipNode(Tcp::new(Config { listener_ip: Some(IpAddr::V4(Ipv4Addr::LOCALHOST)), ..Default::default() }));
+        let node_ip = node.tcp().enable_listener().await.unwrap();
+        node.enable_writing().await;
+
+        // Connect a slow peer, which never reads, and two fast peers.
+        let slow_peer = TcpStream::connect(node_ip).await.unwrap();
+        let mut fast_peers =
+            vec![TcpStream::connect(node_ip).await.unwrap(), TcpStream::connect(node_ip).await.unwrap()];
+        wait_until(|| node.tcp().num_connected() == 3).await;
+
+        // Ensure every broadcast reaches the fast peers promptly.
+        let mut buffer = vec![0u8; MESSAGE_SIZE];
+        for i in 0..NUM_BROADCASTS {
+            node.broadcast(Bytes::from(vec![i as u8; MESSAGE_SIZE])).unwrap();
+            for fast_peer in fast_peers.iter_mut() {
+                timeout(Duration::from_secs(1), fast_peer.read_exact(&mut buffer)).await.unwrap().unwrap();
+                assert!(buffer.iter().all(|byte| *byte == i as u8));
+            }
+        }
+
+        // Ensure the gossip to the slow peer was dropped, and the slow peer is congested.
+        let slow_addr = slow_peer.local_addr().unwrap();
+        let dropped = node.tcp().known_peers().get(slow_addr).unwrap().dropped();
+        assert!(dropped.get("gossip").copied().unwrap_or_default() > 0);
+        assert!(node.tcp().is_congested(slow_addr));
+        assert_eq!(node.tcp().stats().dropped(), dropped);
+
+        // Ensure nothing was dropped for the fast peers.
+        for fast_peer in &fast_peers {
+            let fast_addr = fast_peer.local_addr().unwrap();
+            assert!(node.tcp().known_peers().get(fast_addr).unwrap().dropped().is_empty());
+            assert!(!node.tcp().is_congested(fast_addr));
+        }
+    }
 }
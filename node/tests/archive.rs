@@ -0,0 +1,115 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![recursion_limit = "256"]
+
+#[allow(dead_code)]
+mod common;
+use common::{
+    node::client,
+    rest::{free_local_addr, get, post},
+    test_peer::{sample_account, sample_genesis_block},
+};
+
+use snarkos_node::{rest::RestConfig, Archive, RecordScanMode};
+use snarkos_node_router::{messages::NodeType, Outbound};
+use snarkvm::prelude::{
+    puzzle::Solution,
+    store::helpers::memory::ConsensusMemory,
+    MainnetV0 as CurrentNetwork,
+    TestRng,
+};
+
+use aleo_std::StorageMode;
+use deadline::deadline;
+use rand::Rng;
+use std::{net::SocketAddr, time::Duration};
+
+async fn archive_with_rest(rest_ip: SocketAddr) -> Archive<CurrentNetwork, ConsensusMemory<CurrentNetwork>> {
+    Archive::new(
+        "127.0.0.1:0".parse().unwrap(),
+        RestConfig::new(vec![rest_ip]),
+        false, // No record index.
+        RecordScanMode::Disabled,
+        None, // No state changes.
+        sample_account(),
+        &[],
+        None, // No proxy.
+        sample_genesis_block(),
+        None, // No CDN.
+        StorageMode::Production,
+    )
+    .await
+    .expect("couldn't create archive instance")
+}
+
+#[tokio::test]
+async fn test_archive_serves_ledger_routes() {
+    let rest_ip = free_local_addr();
+    let archive = archive_with_rest(rest_ip).await;
+    assert_eq!(archive.router().node_type(), NodeType::Archive);
+
+    // Ensure the ledger routes are served.
+    let response = get(rest_ip, "/mainnet/block/height/latest").await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+    let response = get(rest_ip, "/mainnet/block/0").await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+    let response = get(rest_ip, "/mainnet/blocks?start=0&end=1").await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+
+    // Ensure the memory pool routes are not available.
+    let response = get(rest_ip, "/mainnet/memoryPool/transmissions").await;
+    assert!(response.starts_with("HTTP/1.1 500"), "{response}");
+}
+
+#[tokio::test]
+async fn test_archive_refuses_broadcasts() {
+    let rest_ip = free_local_addr();
+    let _archive = archive_with_rest(rest_ip).await;
+
+    // Ensure a transaction broadcast is refused.
+    let genesis = sample_genesis_block();
+    let transaction = genesis.transactions().iter().next().unwrap().transaction();
+    let response = post(rest_ip, "/mainnet/transaction/broadcast", &serde_json::to_string(transaction).unwrap()).await;
+    assert!(response.starts_with("HTTP/1.1 500"), "{response}");
+    assert!(response.contains("Archive nodes do not accept transaction broadcasts"), "{response}");
+
+    // Ensure a solution broadcast is refused.
+    let rng = &mut TestRng::default();
+    let solution = Solution::<CurrentNetwork>::new(rng.gen(), sample_account().address(), rng.gen()).unwrap();
+    let response = post(rest_ip, "/mainnet/solution/broadcast", &serde_json::to_string(&solution).unwrap()).await;
+    assert!(response.starts_with("HTTP/1.1 500"), "{response}");
+    assert!(response.contains("Archive nodes do not accept solution broadcasts"), "{response}");
+}
+
+#[tokio::test]
+async fn test_client_connects_to_archive() {
+    let archive = common::node::archive().await;
+    let client = client().await;
+
+    // Connect the client to the archive.
+    let archive_ip = archive.router().local_ip();
+    client.router().connect(archive_ip).unwrap().await.unwrap();
+
+    // Ensure the client recognizes the archive, and the archive recognizes the client.
+    let client_clone = client.clone();
+    deadline!(Duration::from_secs(5), move || client_clone.router().is_connected_archive(&archive_ip));
+    let archive_clone = archive.clone();
+    deadline!(Duration::from_secs(5), move || archive_clone.router().connected_clients().len() == 1);
+    assert_eq!(client.router().connected_archives(), vec![archive_ip]);
+
+    // Ensure the archive advertises itself as an archive to the client's peers.
+    let peer = client.router().get_connected_peer(&archive_ip).unwrap();
+    assert!(peer.services().is_archive());
+}
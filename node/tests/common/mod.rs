@@ -13,12 +13,21 @@
 // limitations under the License.
 
 pub mod node;
+pub mod rest;
 pub mod test_peer;
 
 use std::{env, str::FromStr};
 
 use snarkos_account::Account;
-use snarkvm::prelude::{block::Block, FromBytes, MainnetV0 as CurrentNetwork, Network};
+use snarkvm::prelude::{
+    block::{Block, Transaction},
+    store::ConsensusStorage,
+    FromBytes,
+    Ledger,
+    MainnetV0 as CurrentNetwork,
+    Network,
+    Value,
+};
 
 /// Returns a fixed account.
 pub fn sample_account() -> Account<CurrentNetwork> {
@@ -30,6 +39,17 @@ pub fn sample_genesis_block() -> Block<CurrentNetwork> {
     Block::<CurrentNetwork>::from_bytes_le(CurrentNetwork::genesis_bytes()).unwrap()
 }
 
+/// Returns a new 'credits.aleo/transfer_public' transaction of the fixed account, which is not confirmed in the ledger.
+pub fn sample_transfer_transaction<C: ConsensusStorage<CurrentNetwork>>(
+    ledger: &Ledger<CurrentNetwork, C>,
+) -> Transaction<CurrentNetwork> {
+    let account = sample_account();
+    let inputs = [Value::from_str(&account.address().to_string()).unwrap(), Value::from_str("1u64").unwrap()];
+    let locator = ("credits.aleo", "transfer_public");
+    let rng = &mut rand::thread_rng();
+    ledger.vm().execute(account.private_key(), locator, inputs.into_iter(), None, 0, None, rng).unwrap()
+}
+
 /// Enables logging in tests.
 pub fn initialise_logger(level: u8) {
     match level {
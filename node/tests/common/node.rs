@@ -14,7 +14,17 @@
 
 use crate::common::test_peer::sample_genesis_block;
 use snarkos_account::Account;
-use snarkos_node::{Client, Prover, Validator};
+use snarkos_node::{
+    bft::helpers::EmptyBatchMode,
+    consensus::TransmissionStorageMode,
+    rest::RestConfig,
+    Archive,
+    Client,
+    Prover,
+    ProverThrottle,
+    RecordScanMode,
+    Validator,
+};
 use snarkvm::prelude::{store::helpers::memory::ConsensusMemory, MainnetV0 as CurrentNetwork};
 
 use aleo_std::StorageMode;
@@ -23,13 +33,17 @@ use std::str::FromStr;
 pub async fn client() -> Client<CurrentNetwork, ConsensusMemory<CurrentNetwork>> {
     Client::new(
         "127.0.0.1:0".parse().unwrap(),
-        None,
-        10,
+        RestConfig::default(), // No REST server.
+        false,                 // No record index.
+        RecordScanMode::Disabled,
+        None, // No state changes.
         Account::<CurrentNetwork>::from_str("APrivateKey1zkp2oVPTci9kKcUprnbzMwq95Di1MQERpYBhEeqvkrDirK1").unwrap(),
         &[],
+        None, // No proxy.
         sample_genesis_block(),
         None, // No CDN.
         StorageMode::Production,
+        vec![], // No program denylist.
     )
     .await
     .expect("couldn't create client instance")
@@ -40,8 +54,11 @@ pub async fn prover() -> Prover<CurrentNetwork, ConsensusMemory<CurrentNetwork>>
         "127.0.0.1:0".parse().unwrap(),
         Account::<CurrentNetwork>::from_str("APrivateKey1zkp2oVPTci9kKcUprnbzMwq95Di1MQERpYBhEeqvkrDirK1").unwrap(),
         &[],
+        None, // No proxy.
         sample_genesis_block(),
         StorageMode::Production,
+        None, // All the cores.
+        ProverThrottle::DEFAULT_DUTY_CYCLE,
     )
     .await
     .expect("couldn't create prover instance")
@@ -51,17 +68,43 @@ pub async fn validator() -> Validator<CurrentNetwork, ConsensusMemory<CurrentNet
     Validator::new(
         "127.0.0.1:0".parse().unwrap(),
         None,
-        None,
-        10,
+        RestConfig::default(), // No REST server.
+        false,                 // No record index.
+        RecordScanMode::Disabled,
+        None, // No state changes.
         Account::<CurrentNetwork>::from_str("APrivateKey1zkp2oVPTci9kKcUprnbzMwq95Di1MQERpYBhEeqvkrDirK1").unwrap(),
+        None, // No remote signer.
         &[],
         &[],
         sample_genesis_block(), // Should load the current network's genesis block.
         None,                   // No CDN.
         StorageMode::Production,
-        true,  // This test requires validators to connect to peers.
-        false, // No dev traffic in production mode.
+        TransmissionStorageMode::Persistent,
+        true,   // This test requires validators to connect to peers.
+        false,  // No dev traffic in production mode.
+        true,   // The sample account is not in the committee, so the validator starts as an observer.
+        None,   // No event log.
+        vec![], // No program denylist.
+        EmptyBatchMode::default(),
     )
     .await
     .expect("couldn't create validator instance")
 }
+
+pub async fn archive() -> Archive<CurrentNetwork, ConsensusMemory<CurrentNetwork>> {
+    Archive::new(
+        "127.0.0.1:0".parse().unwrap(),
+        RestConfig::default(), // No REST server.
+        false,                 // No record index.
+        RecordScanMode::Disabled,
+        None, // No state changes.
+        Account::<CurrentNetwork>::from_str("APrivateKey1zkp2oVPTci9kKcUprnbzMwq95Di1MQERpYBhEeqvkrDirK1").unwrap(),
+        &[],
+        None, // No proxy.
+        sample_genesis_block(),
+        None, // No CDN.
+        StorageMode::Production,
+    )
+    .await
+    .expect("couldn't create archive instance")
+}
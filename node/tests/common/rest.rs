@@ -0,0 +1,122 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use rustls::{ClientConfig, RootCertStore};
+use std::{
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+use tokio_rustls::TlsConnector;
+
+/// Returns a free local address for the REST server.
+pub fn free_local_addr() -> SocketAddr {
+    std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap()
+}
+
+/// Returns a free local IPv6 address for the REST server.
+pub fn free_local_addr_v6() -> SocketAddr {
+    std::net::TcpListener::bind("[::1]:0").unwrap().local_addr().unwrap()
+}
+
+/// Sends the given raw request, and returns the raw HTTP response.
+async fn send(rest_ip: SocketAddr, request: String) -> String {
+    let mut stream = TcpStream::connect(rest_ip).await.unwrap();
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    response
+}
+
+/// Returns the body of the given raw HTTP response.
+pub fn body(response: &str) -> &str {
+    response.split_once("\r\n\r\n").map(|(_, body)| body).unwrap_or_default()
+}
+
+/// Sends a `GET` request to the given path, and returns the raw HTTP response.
+pub async fn get(rest_ip: SocketAddr, path: &str) -> String {
+    send(rest_ip, format!("GET {path} HTTP/1.1\r\nHost: {rest_ip}\r\nConnection: close\r\n\r\n")).await
+}
+
+/// Returns the path to the given TLS fixture of the REST server.
+pub fn tls_fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("rest").join("tests").join("fixtures").join(name)
+}
+
+/// Sends a `GET` request to the given path over HTTPS, trusting the given certificate, and returns the raw HTTP response.
+pub async fn get_https(rest_ip: SocketAddr, path: &str, cert_path: &Path) -> String {
+    let mut roots = RootCertStore::empty();
+    let pem = std::fs::read(cert_path).unwrap();
+    for certificate in rustls_pemfile::certs(&mut pem.as_slice()) {
+        roots.add(certificate.unwrap()).unwrap();
+    }
+    let config = ClientConfig::builder().with_root_certificates(roots).with_no_client_auth();
+    let stream = TcpStream::connect(rest_ip).await.unwrap();
+    let server_name = "localhost".try_into().unwrap();
+    let mut stream = TlsConnector::from(Arc::new(config)).connect(server_name, stream).await.unwrap();
+    let request = format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n");
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    response
+}
+
+/// Sends a `POST` request with the given JSON body to the given path, and returns the raw HTTP response.
+pub async fn post(rest_ip: SocketAddr, path: &str, body: &str) -> String {
+    let length = body.len();
+    send(
+        rest_ip,
+        format!(
+            "POST {path} HTTP/1.1\r\nHost: {rest_ip}\r\nContent-Type: application/json\r\nContent-Length: {length}\r\nConnection: close\r\n\r\n{body}"
+        ),
+    )
+    .await
+}
+
+/// Sends a `GET` request to the given path with the given JWT, and returns the raw HTTP response.
+pub async fn get_authorized(rest_ip: SocketAddr, path: &str, token: &str) -> String {
+    send(
+        rest_ip,
+        format!("GET {path} HTTP/1.1\r\nHost: {rest_ip}\r\nAuthorization: Bearer {token}\r\nConnection: close\r\n\r\n"),
+    )
+    .await
+}
+
+/// Sends a `POST` request with the given JSON body and JWT to the given path, and returns the raw HTTP response.
+pub async fn post_authorized(rest_ip: SocketAddr, path: &str, body: &str, token: &str) -> String {
+    let length = body.len();
+    send(
+        rest_ip,
+        format!(
+            "POST {path} HTTP/1.1\r\nHost: {rest_ip}\r\nAuthorization: Bearer {token}\r\nContent-Type: application/json\r\nContent-Length: {length}\r\nConnection: close\r\n\r\n{body}"
+        ),
+    )
+    .await
+}
+
+/// Sends a `PUT` request with the given JSON body and JWT to the given path, and returns the raw HTTP response.
+pub async fn put_authorized(rest_ip: SocketAddr, path: &str, body: &str, token: &str) -> String {
+    let length = body.len();
+    send(
+        rest_ip,
+        format!(
+            "PUT {path} HTTP/1.1\r\nHost: {rest_ip}\r\nAuthorization: Bearer {token}\r\nContent-Type: application/json\r\nContent-Length: {length}\r\nConnection: close\r\n\r\n{body}"
+        ),
+    )
+    .await
+}
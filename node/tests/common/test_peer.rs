@@ -58,6 +58,8 @@ pub struct TestPeer {
     node: Node,
     node_type: NodeType,
     account: Account<CurrentNetwork>,
+    /// The address claimed in the handshake, which differs from the account's address when impersonating.
+    address: Address<CurrentNetwork>,
 }
 
 impl Pea2Pea for TestPeer {
@@ -79,7 +81,27 @@ impl TestPeer {
         Self::new(NodeType::Validator, sample_account()).await
     }
 
+    pub async fn archive() -> Self {
+        Self::new(NodeType::Archive, sample_account()).await
+    }
+
     pub async fn new(node_type: NodeType, account: Account<CurrentNetwork>) -> Self {
+        let address = account.address();
+        Self::with_claimed_address(node_type, account, address).await
+    }
+
+    /// Returns a test peer that claims the given address, but signs its handshake with a different account.
+    pub async fn impersonating(node_type: NodeType, address: Address<CurrentNetwork>) -> Self {
+        let account = Account::new(&mut TestRng::default()).unwrap();
+        assert_ne!(account.address(), address);
+        Self::with_claimed_address(node_type, account, address).await
+    }
+
+    async fn with_claimed_address(
+        node_type: NodeType,
+        account: Account<CurrentNetwork>,
+        address: Address<CurrentNetwork>,
+    ) -> Self {
         let peer = Self {
             node: Node::new(Config {
                 listener_ip: Some(IpAddr::V4(Ipv4Addr::LOCALHOST)),
@@ -88,6 +110,7 @@ impl TestPeer {
             }),
             node_type,
             account,
+            address,
         };
 
         peer.enable_handshake().await;
@@ -109,7 +132,7 @@ impl TestPeer {
     }
 
     pub fn address(&self) -> Address<CurrentNetwork> {
-        self.account.address()
+        self.address
     }
 }
 
@@ -141,7 +164,12 @@ impl Handshake for TestPeer {
 
                 // Sign the nonce.
                 let response_nonce: u64 = rng.gen();
-                let data = [peer_request.nonce.to_le_bytes(), response_nonce.to_le_bytes()].concat();
+                let data = ChallengeResponse::signed_bytes(
+                    peer_request.nonce,
+                    response_nonce,
+                    &genesis_header,
+                    local_ip.port(),
+                )?;
                 let signature = self.account().sign_bytes(&data, rng).unwrap();
 
                 // Send the challenge response.
@@ -155,7 +183,12 @@ impl Handshake for TestPeer {
 
                 // Sign the nonce.
                 let response_nonce: u64 = rng.gen();
-                let data = [peer_request.nonce.to_le_bytes(), response_nonce.to_le_bytes()].concat();
+                let data = ChallengeResponse::signed_bytes(
+                    peer_request.nonce,
+                    response_nonce,
+                    &genesis_header,
+                    local_ip.port(),
+                )?;
                 let signature = self.account().sign_bytes(&data, rng).unwrap();
 
                 // Send our challenge bundle.
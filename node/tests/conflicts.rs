@@ -0,0 +1,229 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![recursion_limit = "256"]
+
+#[allow(dead_code)]
+mod common;
+use common::rest::{body, free_local_addr, get, post};
+
+use snarkos_account::Account;
+use snarkos_node::{
+    bft::helpers::EmptyBatchMode,
+    consensus::{
+        ConflictPolicy,
+        TransmissionStorageMode,
+        DEFAULT_REPLACEMENT_FEE_INCREMENT,
+        TRANSACTION_OUTCOMES_CAPACITY,
+    },
+    rest::RestConfig,
+    RecordScanMode,
+    Validator,
+};
+use snarkvm::prelude::{
+    block::{Block, Transaction},
+    store::{helpers::memory::ConsensusMemory, ConsensusStore},
+    MainnetV0 as CurrentNetwork,
+    Plaintext,
+    Record,
+    RecordsFilter,
+    TestRng,
+    Value,
+    VM,
+};
+
+use aleo_std::StorageMode;
+use std::{net::SocketAddr, path::PathBuf, str::FromStr};
+
+type CurrentValidator = Validator<CurrentNetwork, ConsensusMemory<CurrentNetwork>>;
+
+/// Returns the account that owns the records of the genesis block, which is the only committee member.
+fn sample_beacon() -> Account<CurrentNetwork> {
+    Account::new(&mut TestRng::fixed(1)).unwrap()
+}
+
+/// Returns a genesis block, whose records are owned by the beacon account.
+fn sample_genesis_block() -> Block<CurrentNetwork> {
+    let vm = VM::from(ConsensusStore::<CurrentNetwork, ConsensusMemory<CurrentNetwork>>::open(None).unwrap()).unwrap();
+    vm.genesis_beacon(sample_beacon().private_key(), &mut TestRng::default()).unwrap()
+}
+
+/// Returns a directory for the ledger of a validator.
+fn sample_storage_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("snarkos-conflicts-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&path);
+    path
+}
+
+/// Returns a validator that is not in the committee, so that it never proposes the transactions it receives.
+async fn observer(rest_ip: SocketAddr, storage_path: PathBuf) -> CurrentValidator {
+    Validator::new(
+        "127.0.0.1:0".parse().unwrap(),
+        Some(free_local_addr()),
+        RestConfig::new(vec![rest_ip]),
+        false, // No record index.
+        RecordScanMode::Disabled,
+        None, // No state changes.
+        Account::new(&mut TestRng::fixed(2)).unwrap(),
+        None, // No remote signer.
+        &[],
+        &[],
+        sample_genesis_block(),
+        None, // No CDN.
+        StorageMode::Custom(storage_path),
+        TransmissionStorageMode::Memory,
+        false,  // No external peers.
+        false,  // No dev traffic.
+        true,   // The account is not in the committee, so the validator starts as an observer.
+        None,   // No event log.
+        vec![], // No program denylist.
+        EmptyBatchMode::default(),
+    )
+    .await
+    .expect("couldn't create validator instance")
+}
+
+/// Returns the unspent records of the beacon account.
+fn sample_records(validator: &CurrentValidator) -> Vec<Record<CurrentNetwork, Plaintext<CurrentNetwork>>> {
+    let beacon = sample_beacon();
+    validator
+        .ledger()
+        .find_records(beacon.view_key(), RecordsFilter::Unspent)
+        .unwrap()
+        .map(|(_, record)| record)
+        .collect()
+}
+
+/// Returns a private transfer of the given record of the beacon account, paying the given priority fee.
+/// Note: Transfers of the same record conflict, as they spend the same serial number.
+fn sample_transfer(
+    validator: &CurrentValidator,
+    record: &Record<CurrentNetwork, Plaintext<CurrentNetwork>>,
+    fee_record: &Record<CurrentNetwork, Plaintext<CurrentNetwork>>,
+    priority_fee: u64,
+) -> (Transaction<CurrentNetwork>, String) {
+    let beacon = sample_beacon();
+    let inputs = [
+        Value::Record(record.clone()),
+        Value::from_str(&beacon.address().to_string()).unwrap(),
+        Value::from_str("1u64").unwrap(),
+    ];
+    let locator = ("credits.aleo", "transfer_private");
+    let rng = &mut rand::thread_rng();
+    let transaction = validator
+        .ledger()
+        .vm()
+        .execute(beacon.private_key(), locator, inputs.into_iter(), Some(fee_record.clone()), priority_fee, None, rng)
+        .unwrap();
+    let body = serde_json::to_string(&transaction).unwrap();
+    (transaction, body)
+}
+
+#[tokio::test]
+async fn test_broadcast_reports_replaced_transactions() {
+    let rest_ip = free_local_addr();
+    let validator = observer(rest_ip, sample_storage_path("replaced")).await;
+    let records = sample_records(&validator);
+    assert!(records.len() >= 3);
+
+    // Prepare two transfers of the same record, where the second pays a sufficiently higher fee.
+    let (first, first_body) = sample_transfer(&validator, &records[0], &records[1], 0);
+    let (second, second_body) =
+        sample_transfer(&validator, &records[0], &records[2], 2 * DEFAULT_REPLACEMENT_FEE_INCREMENT);
+
+    // Ensure the first transfer is accepted, with its transaction ID as the response.
+    let response = post(rest_ip, "/mainnet/transaction/broadcast", &first_body).await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+    let json: serde_json::Value = serde_json::from_str(body(&response)).unwrap();
+    assert_eq!(json, serde_json::json!(first.id()));
+
+    // Ensure the second transfer replaces the first, and the response names the replaced transaction.
+    let response = post(rest_ip, "/mainnet/transaction/broadcast", &second_body).await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+    let json: serde_json::Value = serde_json::from_str(body(&response)).unwrap();
+    assert_eq!(json, serde_json::json!({ "transaction_id": second.id(), "replaced": [first.id()] }));
+}
+
+#[tokio::test]
+async fn test_broadcast_reports_conflict_decisions() {
+    let rest_ip = free_local_addr();
+    let validator = observer(rest_ip, sample_storage_path("decisions")).await;
+    let records = sample_records(&validator);
+    assert!(records.len() >= 4);
+
+    // Prepare three transfers of the same record, where none pays a sufficiently higher fee than the first.
+    let fee = DEFAULT_REPLACEMENT_FEE_INCREMENT;
+    let (first, first_body) = sample_transfer(&validator, &records[0], &records[1], fee);
+    let (_, second_body) = sample_transfer(&validator, &records[0], &records[2], fee);
+    let (third, third_body) = sample_transfer(&validator, &records[0], &records[3], fee);
+
+    // Ensure the first transfer is accepted, with its transaction ID as the response.
+    let response = post(rest_ip, "/mainnet/transaction/broadcast", &first_body).await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+    let json: serde_json::Value = serde_json::from_str(body(&response)).unwrap();
+    assert_eq!(json, serde_json::json!(first.id()));
+
+    // Ensure the second transfer is rejected, with a conflict error naming the first transfer.
+    let response = post(rest_ip, "/mainnet/transaction/broadcast", &second_body).await;
+    assert!(response.starts_with("HTTP/1.1 500"), "{response}");
+    assert!(
+        body(&response).contains(&format!("conflicts with unconfirmed transaction '{}'", first.id())),
+        "{response}"
+    );
+
+    // Ensure the third transfer is queued behind the first, once the conflict policy is to queue.
+    validator.consensus().set_conflict_policy(ConflictPolicy::Queue);
+    let response = post(rest_ip, "/mainnet/transaction/broadcast", &third_body).await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+    let json: serde_json::Value = serde_json::from_str(body(&response)).unwrap();
+    assert_eq!(json, serde_json::json!({ "transaction_id": third.id(), "queued_behind": [first.id()] }));
+}
+
+#[tokio::test]
+async fn test_transaction_outcome_route() {
+    let rest_ip = free_local_addr();
+    let validator = observer(rest_ip, sample_storage_path("outcomes")).await;
+    let records = sample_records(&validator);
+    assert!(records.len() >= 3);
+
+    // Prepare two transfers of the same record, where the second pays a sufficiently higher fee.
+    let (first, first_body) = sample_transfer(&validator, &records[0], &records[1], 0);
+    let (second, second_body) =
+        sample_transfer(&validator, &records[0], &records[2], 2 * DEFAULT_REPLACEMENT_FEE_INCREMENT);
+
+    // Ensure the outcome of a transaction that was never received is an error, which explains the journal.
+    let response = get(rest_ip, &format!("/mainnet/transaction/{}/outcome", first.id())).await;
+    assert!(response.starts_with("HTTP/1.1 500"), "{response}");
+    let expected =
+        format!("outcomes are kept in memory for the {TRANSACTION_OUTCOMES_CAPACITY} most recent transactions");
+    assert!(body(&response).contains(&expected), "{response}");
+
+    // Ensure the first transfer is pending, once it is received.
+    let response = post(rest_ip, "/mainnet/transaction/broadcast", &first_body).await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+    let response = get(rest_ip, &format!("/mainnet/transaction/{}/outcome", first.id())).await;
+    let json: serde_json::Value = serde_json::from_str(body(&response)).unwrap();
+    assert_eq!(json, serde_json::json!({ "status": "pending" }));
+
+    // Ensure the first transfer is evicted once the second replaces it, and the second is pending.
+    let response = post(rest_ip, "/mainnet/transaction/broadcast", &second_body).await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+    let response = get(rest_ip, &format!("/mainnet/transaction/{}/outcome", first.id())).await;
+    let json: serde_json::Value = serde_json::from_str(body(&response)).unwrap();
+    let cause = format!("Replaced by transaction '{}' with a higher fee", second.id());
+    assert_eq!(json, serde_json::json!({ "status": "evicted", "cause": cause }));
+    let response = get(rest_ip, &format!("/mainnet/transaction/{}/outcome", second.id())).await;
+    let json: serde_json::Value = serde_json::from_str(body(&response)).unwrap();
+    assert_eq!(json, serde_json::json!({ "status": "pending" }));
+}
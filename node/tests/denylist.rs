@@ -0,0 +1,133 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![recursion_limit = "256"]
+
+#[allow(dead_code)]
+mod common;
+use common::{
+    rest::{free_local_addr, get, get_authorized, post, put_authorized},
+    sample_transfer_transaction,
+    test_peer::{sample_account, sample_genesis_block},
+};
+
+use snarkos_node::{rest::RestConfig, Client, RecordScanMode};
+use snarkos_node_rest::Claims;
+use snarkvm::prelude::{store::helpers::memory::ConsensusMemory, MainnetV0 as CurrentNetwork, ProgramID};
+
+use aleo_std::StorageMode;
+use core::str::FromStr;
+use std::net::SocketAddr;
+
+async fn client_with_denylist(
+    rest_ip: SocketAddr,
+    program_denylist: Vec<ProgramID<CurrentNetwork>>,
+) -> Client<CurrentNetwork, ConsensusMemory<CurrentNetwork>> {
+    Client::new(
+        "127.0.0.1:0".parse().unwrap(),
+        RestConfig::new(vec![rest_ip]),
+        false, // No record index.
+        RecordScanMode::Disabled,
+        None, // No state changes.
+        sample_account(),
+        &[],
+        None, // No proxy.
+        sample_genesis_block(),
+        None, // No CDN.
+        StorageMode::Production,
+        program_denylist,
+    )
+    .await
+    .expect("couldn't create client instance")
+}
+
+/// Returns a JWT for the REST server of the sample account.
+fn sample_token() -> String {
+    Claims::new(sample_account().address()).to_jwt_string().unwrap()
+}
+
+#[tokio::test]
+async fn test_denylist_route_requires_jwt() {
+    let rest_ip = free_local_addr();
+    let first = ProgramID::from_str("first.aleo").unwrap();
+    let _client = client_with_denylist(rest_ip, vec![first]).await;
+
+    // Ensure the denylist route requires the JWT.
+    let response = get(rest_ip, "/mainnet/node/denylist").await;
+    assert!(response.starts_with("HTTP/1.1 401"), "{response}");
+
+    // Ensure the denylist from the startup configuration is returned.
+    let response = get_authorized(rest_ip, "/mainnet/node/denylist", &sample_token()).await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+    assert!(response.contains("first.aleo"), "{response}");
+}
+
+#[tokio::test]
+async fn test_denylist_refuses_broadcasts_at_runtime() {
+    let rest_ip = free_local_addr();
+    let client = client_with_denylist(rest_ip, vec![]).await;
+    let token = sample_token();
+
+    // Prepare an unconfirmed 'credits.aleo' transaction.
+    // Note: A confirmed transaction is reported as confirmed, before the denylist is checked.
+    let transaction = sample_transfer_transaction(client.ledger());
+    let body = serde_json::to_string(&transaction).unwrap();
+
+    // Ensure the transaction is relayed while the denylist is empty.
+    let response = post(rest_ip, "/mainnet/transaction/broadcast", &body).await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+
+    // Denylist 'credits.aleo' at runtime.
+    let response = put_authorized(rest_ip, "/mainnet/node/denylist", "[\"credits.aleo\"]", &token).await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+    assert!(response.contains("credits.aleo"), "{response}");
+
+    // Ensure the transaction is now refused, with a specific error.
+    let response = post(rest_ip, "/mainnet/transaction/broadcast", &body).await;
+    assert!(response.starts_with("HTTP/1.1 500"), "{response}");
+    assert!(response.contains("denylisted program 'credits.aleo'"), "{response}");
+    // Ensure the transaction is refused in a batch as well.
+    let response = post(rest_ip, "/mainnet/transactions/broadcast", &format!("[{body}]")).await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+    assert!(response.contains("rejected"), "{response}");
+
+    // Ensure the confirmed history of the denylisted program remains queryable.
+    let genesis = sample_genesis_block();
+    let confirmed = genesis.transactions().iter().next().unwrap().id();
+    let response = get(rest_ip, &format!("/mainnet/transaction/{confirmed}")).await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+    let response = get(rest_ip, "/mainnet/block/0").await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+
+    // Clear the denylist, and ensure the transaction is relayed again.
+    let response = put_authorized(rest_ip, "/mainnet/node/denylist", "[]", &token).await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+    let response = post(rest_ip, "/mainnet/transaction/broadcast", &body).await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+}
+
+#[tokio::test]
+async fn test_denylist_does_not_affect_block_validation() {
+    let rest_ip = free_local_addr();
+    let credits = ProgramID::from_str("credits.aleo").unwrap();
+    // Note: The genesis block consists of 'credits.aleo' transactions, which are validated as the ledger is loaded.
+    let client = client_with_denylist(rest_ip, vec![credits]).await;
+
+    // Ensure the block with the denylisted transactions was accepted into the ledger.
+    let genesis = sample_genesis_block();
+    assert_eq!(client.ledger().latest_hash(), genesis.hash());
+    for transaction in genesis.transactions().iter() {
+        assert!(client.ledger().contains_transaction_id(&transaction.id()).unwrap());
+    }
+}
@@ -0,0 +1,143 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![recursion_limit = "256"]
+
+#[allow(dead_code)]
+mod common;
+use common::{
+    rest::{free_local_addr, get, post},
+    test_peer::{sample_account, sample_genesis_block},
+};
+
+use snarkos_node::{rest::RestConfig, Client, RecordScanMode};
+use snarkvm::prelude::{store::helpers::memory::ConsensusMemory, MainnetV0 as CurrentNetwork};
+
+use aleo_std::StorageMode;
+use std::net::SocketAddr;
+
+async fn client_with_rest(
+    rest_config: RestConfig,
+    storage_mode: StorageMode,
+) -> Client<CurrentNetwork, ConsensusMemory<CurrentNetwork>> {
+    Client::new(
+        "127.0.0.1:0".parse().unwrap(),
+        rest_config,
+        false, // No record index.
+        RecordScanMode::Disabled,
+        None, // No state changes.
+        sample_account(),
+        &[],
+        None, // No proxy.
+        sample_genesis_block(),
+        None, // No CDN.
+        storage_mode,
+        vec![], // No program denylist.
+    )
+    .await
+    .expect("couldn't create client instance")
+}
+
+/// Returns a `dev/deploy` request body for the given program.
+fn deploy_body(program: &str) -> String {
+    serde_json::json!({ "program": program, "fee": 0, "private_key": sample_account().private_key() }).to_string()
+}
+
+/// Returns a `dev/execute` request body for `credits.aleo/transfer_public`.
+fn execute_body() -> String {
+    let address = sample_account().address();
+    serde_json::json!({
+        "program_id": "credits.aleo",
+        "function": "transfer_public",
+        "inputs": [address.to_string(), "1u64"],
+        "fee": 0,
+        "private_key": sample_account().private_key(),
+    })
+    .to_string()
+}
+
+/// Returns a `dev/compareChain` request body against a peer that is not connected.
+fn compare_chain_body() -> String {
+    serde_json::json!({ "peer": "127.0.0.1:4130", "start": 0, "end": 1 }).to_string()
+}
+
+#[tokio::test]
+async fn test_dev_routes_are_not_mounted_in_production() {
+    // Start a client outside of development mode.
+    let rest_ip = free_local_addr();
+    let _client = client_with_rest(RestConfig::new(vec![rest_ip]), StorageMode::Production).await;
+
+    // Ensure the node is serving requests.
+    let response = get(rest_ip, "/mainnet/block/height/latest").await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+
+    // Ensure the development routes do not exist.
+    let response = post(rest_ip, "/mainnet/dev/deploy", &deploy_body("program hello.aleo;")).await;
+    assert!(response.starts_with("HTTP/1.1 404"), "{response}");
+    let response = post(rest_ip, "/mainnet/dev/execute", &execute_body()).await;
+    assert!(response.starts_with("HTTP/1.1 404"), "{response}");
+    let response = post(rest_ip, "/mainnet/dev/compareChain", &compare_chain_body()).await;
+    assert!(response.starts_with("HTTP/1.1 404"), "{response}");
+    let response = post(rest_ip, "/mainnet/node/bft/restart", "").await;
+    assert!(response.starts_with("HTTP/1.1 404"), "{response}");
+}
+
+#[tokio::test]
+async fn test_dev_routes_are_mounted_in_development() {
+    // Start a client in development mode.
+    let rest_ip = free_local_addr();
+    let _client = client_with_rest(RestConfig::new(vec![rest_ip]), StorageMode::Development(0)).await;
+
+    // Ensure a malformed program is rejected by the deploy route.
+    let response = post(rest_ip, "/mainnet/dev/deploy", &deploy_body("not a program")).await;
+    assert!(response.starts_with("HTTP/1.1 500"), "{response}");
+
+    // Ensure a malformed input is rejected by the execute route.
+    let body = execute_body().replace("1u64", "1");
+    let response = post(rest_ip, "/mainnet/dev/execute", &body).await;
+    assert!(response.starts_with("HTTP/1.1 500"), "{response}");
+
+    // Ensure a comparison against a peer that is not connected is rejected.
+    let response = post(rest_ip, "/mainnet/dev/compareChain", &compare_chain_body()).await;
+    assert!(response.starts_with("HTTP/1.1 500"), "{response}");
+
+    // Ensure the BFT restart is refused by a node without a BFT.
+    let response = post(rest_ip, "/mainnet/node/bft/restart", "").await;
+    assert!(response.starts_with("HTTP/1.1 500"), "{response}");
+    assert!(response.contains("Route isn't available for this node type"), "{response}");
+
+    // Ensure the debug routes are not mounted by the development mode alone.
+    for path in ["/mainnet/bft/proposal", "/mainnet/bft/round", "/mainnet/bft/dag", "/mainnet/debug/blockTimings"] {
+        let response = get(rest_ip, path).await;
+        assert!(response.starts_with("HTTP/1.1 404"), "{response}");
+    }
+}
+
+#[tokio::test]
+async fn test_debug_routes_are_mounted_if_enabled() {
+    // Start a client outside of development mode, with the debug routes enabled.
+    let rest_ip = free_local_addr();
+    let _client = client_with_rest(RestConfig::new(vec![rest_ip]).with_debug(true), StorageMode::Production).await;
+
+    // Ensure the debug routes are mounted, and refused by a node without a BFT.
+    for path in ["/mainnet/bft/proposal", "/mainnet/bft/round", "/mainnet/bft/dag", "/mainnet/debug/blockTimings"] {
+        let response = get(rest_ip, path).await;
+        assert!(response.starts_with("HTTP/1.1 500"), "{response}");
+        assert!(response.contains("Route isn't available for this node type"), "{response}");
+    }
+
+    // Ensure the development routes remain unmounted.
+    let response = post(rest_ip, "/mainnet/node/bft/restart", "").await;
+    assert!(response.starts_with("HTTP/1.1 404"), "{response}");
+}
@@ -144,6 +144,24 @@ mod validator {
     }
 }
 
+mod archive {
+    // Full node disconnects from synthetic peer.
+    test_disconnect! {
+        archive |> client,
+        archive |> validator,
+        archive |> prover,
+        archive |> archive
+    }
+
+    // Synthetic peer disconnects from the full node.
+    test_disconnect! {
+        archive <| client,
+        archive <| validator,
+        archive <| prover,
+        archive <| archive
+    }
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn duplicate_disconnect_attempts() {
     // common::initialise_logger(3);
@@ -16,10 +16,13 @@
 
 #[allow(dead_code)]
 mod common;
-use common::{node::*, test_peer::TestPeer};
+use common::{
+    node::*,
+    test_peer::{sample_account, TestPeer},
+};
 
-use snarkos_node::{Client, Prover, Validator};
-use snarkos_node_router::Outbound;
+use snarkos_node::{Archive, Client, Prover, Validator};
+use snarkos_node_router::{messages::NodeType, Outbound};
 use snarkos_node_tcp::P2P;
 use snarkvm::prelude::{store::helpers::memory::ConsensusMemory, MainnetV0 as CurrentNetwork};
 
@@ -57,7 +60,7 @@ macro_rules! impl_connect {
     };
 }
 
-impl_connect!(Client, Prover, Validator);
+impl_connect!(Client, Prover, Validator, Archive);
 
 // Implement the `Connect` trait for the test peer.
 #[async_trait::async_trait]
@@ -182,6 +185,51 @@ mod validator {
     }
 }
 
+mod archive {
+    // Initiator side (full node connects to synthetic peer).
+    test_handshake! {
+        archive -> client,
+        archive -> validator,
+        archive -> prover,
+        archive -> archive
+    }
+
+    // Responder side (synthetic peer connects to full node).
+    test_handshake! {
+        archive <- client,
+        archive <- validator,
+        archive <- prover,
+        archive <- archive
+    }
+}
+
+#[tokio::test]
+async fn handshake_initiator_side_rejects_signature_for_wrong_address() {
+    // Spin up a full node.
+    let node = client().await;
+    // Spin up a test peer, which claims an address it cannot sign for.
+    let peer = TestPeer::impersonating(NodeType::Validator, sample_account().address()).await;
+
+    // Ensure the full node rejects the peer's challenge response.
+    assert!(node.connect(peer.listening_addr()).await.is_err());
+    assert_eq!(node.router().number_of_connected_peers(), 0);
+}
+
+#[tokio::test]
+async fn handshake_responder_side_rejects_signature_for_wrong_address() {
+    // Spin up a full node.
+    let node = client().await;
+    // Spin up a test peer, which claims an address it cannot sign for.
+    let peer = TestPeer::impersonating(NodeType::Validator, sample_account().address()).await;
+
+    // The test peer completes its side of the handshake, but the full node must reject it.
+    let _ = peer.connect(node.listening_addr()).await;
+    // A small anti-flakiness buffer.
+    sleep(Duration::from_millis(200)).await;
+    assert_eq!(node.router().number_of_connected_peers(), 0);
+    assert_eq!(node.tcp().num_connected(), 0);
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn simultaneous_connection_attempt() {
     // common::initialise_logger(3);
@@ -0,0 +1,100 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![recursion_limit = "256"]
+
+#[allow(dead_code)]
+mod common;
+use common::{
+    rest::{body, free_local_addr, get},
+    test_peer::{sample_account, sample_genesis_block},
+};
+
+use snarkos_node::{
+    rest::{InclusionProof, RestConfig},
+    Client,
+    RecordScanMode,
+};
+use snarkvm::prelude::{
+    store::helpers::memory::ConsensusMemory,
+    Field,
+    MainnetV0 as CurrentNetwork,
+    Network,
+    TestRng,
+    Uniform,
+};
+
+use aleo_std::StorageMode;
+use std::net::SocketAddr;
+
+async fn client_with_rest(rest_ip: SocketAddr) -> Client<CurrentNetwork, ConsensusMemory<CurrentNetwork>> {
+    Client::new(
+        "127.0.0.1:0".parse().unwrap(),
+        RestConfig::new(vec![rest_ip]),
+        false, // No record index.
+        RecordScanMode::Disabled,
+        None, // No state changes.
+        sample_account(),
+        &[],
+        None, // No proxy.
+        sample_genesis_block(),
+        None, // No CDN.
+        StorageMode::Production,
+        vec![], // No program denylist.
+    )
+    .await
+    .expect("couldn't create client instance")
+}
+
+#[tokio::test]
+async fn test_inclusion_proofs_of_genesis_block() {
+    let rest_ip = free_local_addr();
+    let _client = client_with_rest(rest_ip).await;
+    let genesis = sample_genesis_block();
+
+    for confirmed in genesis.transactions().iter() {
+        // Fetch the inclusion proof of the transaction.
+        let path = format!("/mainnet/transaction/{}/inclusionProof", confirmed.id());
+        let response = get(rest_ip, &path).await;
+        assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+        let proof: InclusionProof<CurrentNetwork> = serde_json::from_str(body(&response)).unwrap();
+
+        // Ensure the proof verifies against the transactions root in the header of the block.
+        assert_eq!(proof.transaction_id, confirmed.id());
+        assert_eq!(proof.block_height, 0);
+        assert_eq!(proof.block_hash, genesis.hash());
+        assert_eq!(proof.transaction_index, confirmed.index());
+        assert_eq!(proof.transactions_root, genesis.header().transactions_root());
+        assert!(proof.verify());
+
+        // Ensure the proof is refused if any element of the path is tampered with.
+        let mut json: serde_json::Value = serde_json::from_str(body(&response)).unwrap();
+        json["transactions_path"]["siblings"][0] =
+            serde_json::json!(Field::<CurrentNetwork>::rand(&mut TestRng::default()));
+        let tampered: InclusionProof<CurrentNetwork> = serde_json::from_value(json).unwrap();
+        assert!(!tampered.verify());
+    }
+}
+
+#[tokio::test]
+async fn test_inclusion_proof_of_unknown_transaction() {
+    let rest_ip = free_local_addr();
+    let _client = client_with_rest(rest_ip).await;
+
+    // Ensure an unknown transaction has no inclusion proof.
+    let transaction_id = <CurrentNetwork as Network>::TransactionID::from(Field::rand(&mut TestRng::default()));
+    let response = get(rest_ip, &format!("/mainnet/transaction/{transaction_id}/inclusionProof")).await;
+    assert!(response.starts_with("HTTP/1.1 500"), "{response}");
+    assert!(response.contains(&format!("Transaction '{transaction_id}' is not found")), "{response}");
+}
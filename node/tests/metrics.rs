@@ -0,0 +1,101 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![recursion_limit = "256"]
+
+#[allow(dead_code)]
+mod common;
+use common::{
+    rest::{free_local_addr, get},
+    test_peer::sample_genesis_block,
+};
+
+use snarkos_account::Account;
+use snarkos_node::{rest::RestConfig, Client, RecordScanMode};
+use snarkvm::prelude::{store::helpers::memory::ConsensusMemory, MainnetV0 as CurrentNetwork};
+
+use aleo_std::StorageMode;
+use std::{net::SocketAddr, str::FromStr};
+
+/// Returns the value of the sample with the given name and labels, if it exists.
+fn sample(response: &str, name_and_labels: &str) -> Option<f64> {
+    response.lines().find_map(|line| line.strip_prefix(name_and_labels)?.strip_prefix(' ')?.parse().ok())
+}
+
+async fn client_with_rest(
+    rest_ip: SocketAddr,
+    rest_metrics: bool,
+) -> Client<CurrentNetwork, ConsensusMemory<CurrentNetwork>> {
+    Client::new(
+        "127.0.0.1:0".parse().unwrap(),
+        RestConfig::new(vec![rest_ip]).with_metrics(rest_metrics),
+        false, // No record index.
+        RecordScanMode::Disabled,
+        None, // No state changes.
+        Account::<CurrentNetwork>::from_str("APrivateKey1zkp2oVPTci9kKcUprnbzMwq95Di1MQERpYBhEeqvkrDirK1").unwrap(),
+        &[],
+        None, // No proxy.
+        sample_genesis_block(),
+        None, // No CDN.
+        StorageMode::Production,
+        vec![], // No program denylist.
+    )
+    .await
+    .expect("couldn't create client instance")
+}
+
+#[tokio::test]
+async fn test_metrics_route() {
+    // Start a client with the metrics route enabled.
+    let rest_ip = free_local_addr();
+    let _client = client_with_rest(rest_ip, true).await;
+
+    // Request a route, so that it is counted.
+    let response = get(rest_ip, "/mainnet/block/height/latest").await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+
+    // Scrape the metrics.
+    let response = get(rest_ip, "/metrics").await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+    assert!(response.to_lowercase().contains("content-type: text/plain; version=0.0.4"), "{response}");
+
+    // Check the ledger, router, and sync metrics.
+    assert_eq!(sample(&response, "snarkos_blocks_height_total"), Some(0.0));
+    assert!(sample(&response, "snarkos_blocks_latest_age_secs").unwrap() >= 0.0);
+    for node_type in ["client", "prover", "validator"] {
+        let name = format!("snarkos_router_connected_total{{node_type=\"{node_type}\"}}");
+        assert_eq!(sample(&response, &name), Some(0.0));
+    }
+    assert_eq!(sample(&response, "snarkos_router_queued_block_responses_total"), Some(0.0));
+    assert_eq!(sample(&response, "snarkos_router_congested_peers"), Some(0.0));
+    assert!(sample(&response, "snarkos_sync_is_synced").is_some());
+    assert_eq!(sample(&response, "snarkos_sync_blocks_behind"), Some(0.0));
+    // Check the consensus metrics are not exposed by a client.
+    assert!(!response.contains("snarkos_consensus_"));
+    assert!(!response.contains("snarkos_bft_"));
+    // Check the request counters.
+    let name = "snarkos_rest_requests_total{route=\"/mainnet/block/height/latest\",status=\"200\"}";
+    assert_eq!(sample(&response, name), Some(1.0));
+}
+
+#[tokio::test]
+async fn test_metrics_route_disabled() {
+    // Start a client with the metrics route disabled.
+    let rest_ip = free_local_addr();
+    let _client = client_with_rest(rest_ip, false).await;
+
+    // Ensure the metrics route does not exist.
+    let response = get(rest_ip, "/metrics").await;
+    assert!(response.starts_with("HTTP/1.1 404"), "{response}");
+}
@@ -17,7 +17,7 @@ mod common;
 use common::test_peer::TestPeer;
 
 use snarkos_node_router::{
-    messages::{Message, PeerResponse},
+    messages::{AdvertisedPeer, Message, PeerResponse},
     Outbound,
 };
 use snarkos_node_tcp::P2P;
@@ -25,7 +25,7 @@ use snarkos_node_tcp::P2P;
 use deadline::deadline;
 use paste::paste;
 use pea2pea::{protocols::Writing, Pea2Pea};
-use std::time::Duration;
+use std::{net::SocketAddr, time::Duration};
 
 macro_rules! test_reject_unsolicited_peer_response {
     ($($node_type:ident),*) => {
@@ -54,13 +54,13 @@ macro_rules! test_reject_unsolicited_peer_response {
                     // Check the candidate peers.
                     assert_eq!(node.router().number_of_candidate_peers(), 0);
 
-                    let peers = vec!["1.1.1.1:1111".parse().unwrap(), "2.2.2.2:2222".parse().unwrap()];
+                    let peers: Vec<SocketAddr> = vec!["1.1.1.1:1111".parse().unwrap(), "2.2.2.2:2222".parse().unwrap()];
 
                     // Send a `PeerResponse` to the node.
                     assert!(
                         peer.unicast(
                             *peer.node().connected_addrs().first().unwrap(),
-                            Message::PeerResponse(PeerResponse { peers: peers.clone() })
+                            Message::PeerResponse(PeerResponse { peers: peers.iter().copied().map(AdvertisedPeer::from).collect() })
                         )
                         .is_ok()
                     );
@@ -79,4 +79,4 @@ macro_rules! test_reject_unsolicited_peer_response {
     };
 }
 
-test_reject_unsolicited_peer_response!(client, prover, validator);
+test_reject_unsolicited_peer_response!(client, prover, validator, archive);
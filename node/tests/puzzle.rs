@@ -0,0 +1,175 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![recursion_limit = "256"]
+
+#[allow(dead_code)]
+mod common;
+use common::{
+    node::client,
+    rest::{body, free_local_addr, get, post},
+    test_peer::{sample_account, sample_genesis_block},
+};
+
+use snarkos_node::{
+    bft::helpers::init_primary_channels,
+    consensus::{Consensus, TransmissionStorageMode},
+    rest::{Rest, RestConfig},
+    sync::{BlockSync, BlockSyncMode},
+    Client,
+};
+use snarkos_node_bft_ledger_service::{test_helpers::sample_ledger, ReplayFilter, TranslucentLedgerService};
+use snarkvm::{
+    ledger::{puzzle::SolutionID, Ledger},
+    prelude::{store::helpers::memory::ConsensusMemory, MainnetV0 as CurrentNetwork, Network, TestRng},
+};
+
+use aleo_std::StorageMode;
+use rand::Rng;
+use std::{net::SocketAddr, sync::Arc};
+
+type CurrentLedger = Ledger<CurrentNetwork, ConsensusMemory<CurrentNetwork>>;
+type CurrentRest =
+    Rest<CurrentNetwork, ConsensusMemory<CurrentNetwork>, Client<CurrentNetwork, ConsensusMemory<CurrentNetwork>>>;
+
+/// Starts a REST server with a running consensus, and returns it with its consensus and ledger.
+/// Note: The consensus skips the verification of the solutions, so that they enter the memory pool
+/// without meeting the proof target. The development ID determines the port of the gateway.
+async fn rest_with_consensus(rest_ip: SocketAddr, dev: u16) -> (CurrentRest, Consensus<CurrentNetwork>, CurrentLedger) {
+    let ledger = CurrentLedger::load(sample_genesis_block(), StorageMode::Production).unwrap();
+    let ledger_service = Arc::new(TranslucentLedgerService::new(ledger.clone(), Default::default()));
+    let consensus = Consensus::new(
+        sample_account(),
+        ledger_service.clone(),
+        None,
+        &[],
+        StorageMode::Development(dev),
+        TransmissionStorageMode::Memory,
+    )
+    .unwrap();
+    let (primary_sender, primary_receiver) = init_primary_channels();
+    consensus.run(primary_sender, primary_receiver).await.unwrap();
+
+    let block_sync = BlockSync::new(BlockSyncMode::Router, ledger_service);
+    let replay_filter = Arc::new(ReplayFilter::new(ledger.clone()));
+    let rest = Rest::new(ledger.clone(), Arc::new(client().await), block_sync, replay_filter)
+        .with_consensus(consensus.clone())
+        .start(RestConfig::new(vec![rest_ip]))
+        .await
+        .unwrap();
+    (rest, consensus, ledger)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_puzzle_current() {
+    let rest_ip = free_local_addr();
+    let (_rest, consensus, ledger) = rest_with_consensus(rest_ip, 40).await;
+
+    // Ensure the puzzle state of the genesis block is served.
+    let response = get(rest_ip, "/mainnet/puzzle/current").await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+    let json: serde_json::Value = serde_json::from_str(body(&response)).unwrap();
+    let header = sample_genesis_block().header().clone();
+    assert_eq!(json["epoch_hash"], serde_json::json!(ledger.latest_epoch_hash().unwrap()));
+    assert_eq!(json["epoch_number"], 0);
+    assert_eq!(json["proof_target"], header.proof_target());
+    assert_eq!(json["coinbase_target"], header.coinbase_target());
+    assert_eq!(json["height"], 0);
+    assert_eq!(json["next_epoch_height"], CurrentNetwork::NUM_BLOCKS_PER_EPOCH);
+
+    consensus.shut_down().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_puzzle_current_at_new_epoch() {
+    let rest_ip = free_local_addr();
+    let rng = &mut TestRng::default();
+
+    // Initialize a ledger with a genesis block of a fresh account, so that it can be advanced with beacon blocks.
+    let (ledger, private_key) = sample_ledger(rng);
+    let ledger_service = Arc::new(TranslucentLedgerService::new(ledger.clone(), Default::default()));
+    let block_sync = BlockSync::new(BlockSyncMode::Router, ledger_service);
+    let replay_filter = Arc::new(ReplayFilter::new(ledger.clone()));
+    let _rest: CurrentRest = Rest::new(ledger.clone(), Arc::new(client().await), block_sync, replay_filter)
+        .start(RestConfig::new(vec![rest_ip]))
+        .await
+        .unwrap();
+
+    /// Returns the puzzle state served by the REST server.
+    async fn puzzle_current(rest_ip: SocketAddr) -> serde_json::Value {
+        let response = get(rest_ip, "/mainnet/puzzle/current").await;
+        assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+        serde_json::from_str(body(&response)).unwrap()
+    }
+
+    // Advance to the last block of the first epoch.
+    let genesis_epoch_hash = ledger.latest_epoch_hash().unwrap();
+    for _ in 1..CurrentNetwork::NUM_BLOCKS_PER_EPOCH {
+        let block = ledger.prepare_advance_to_next_beacon_block(&private_key, vec![], vec![], vec![], rng).unwrap();
+        ledger.advance_to_next_block(&block).unwrap();
+    }
+    // Ensure the puzzle state is still the one of the first epoch.
+    let json = puzzle_current(rest_ip).await;
+    assert_eq!(json["epoch_hash"], serde_json::json!(genesis_epoch_hash));
+    assert_eq!(json["epoch_number"], 0);
+    assert_eq!(json["height"], CurrentNetwork::NUM_BLOCKS_PER_EPOCH - 1);
+    assert_eq!(json["next_epoch_height"], CurrentNetwork::NUM_BLOCKS_PER_EPOCH);
+
+    // Advance to the first block of the next epoch.
+    let block = ledger.prepare_advance_to_next_beacon_block(&private_key, vec![], vec![], vec![], rng).unwrap();
+    ledger.advance_to_next_block(&block).unwrap();
+    // Ensure the puzzle state is updated to the new epoch.
+    let epoch_hash = ledger.latest_epoch_hash().unwrap();
+    assert_ne!(epoch_hash, genesis_epoch_hash);
+    let json = puzzle_current(rest_ip).await;
+    assert_eq!(json["epoch_hash"], serde_json::json!(epoch_hash));
+    assert_eq!(json["epoch_number"], 1);
+    assert_eq!(json["proof_target"], block.header().proof_target());
+    assert_eq!(json["coinbase_target"], block.header().coinbase_target());
+    assert_eq!(json["height"], CurrentNetwork::NUM_BLOCKS_PER_EPOCH);
+    assert_eq!(json["next_epoch_height"], 2 * CurrentNetwork::NUM_BLOCKS_PER_EPOCH);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_puzzle_estimate() {
+    let rest_ip = free_local_addr();
+    let (_rest, consensus, ledger) = rest_with_consensus(rest_ip, 41).await;
+    let rng = &mut TestRng::default();
+
+    // Ensure an unknown solution is refused.
+    let solution_id = SolutionID::<CurrentNetwork>::from(rng.gen::<u64>());
+    let response = get(rest_ip, &format!("/mainnet/puzzle/estimate?commitment={solution_id}")).await;
+    assert!(response.starts_with("HTTP/1.1 500"), "{response}");
+    assert!(response.contains("is not in the memory pool"), "{response}");
+
+    // Broadcast a solution for the current epoch.
+    let epoch_hash = ledger.latest_epoch_hash().unwrap();
+    let solution = ledger.puzzle().prove(epoch_hash, sample_account().address(), rng.gen(), None).unwrap();
+    let response = post(rest_ip, "/mainnet/solution/broadcast", &serde_json::to_string(&solution).unwrap()).await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+
+    // Ensure the estimate of the solution is served, as it is in the memory pool.
+    let response = get(rest_ip, &format!("/mainnet/puzzle/estimate?commitment={}", solution.id())).await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+    let json: serde_json::Value = serde_json::from_str(body(&response)).unwrap();
+    let solution_target = ledger.puzzle().get_proof_target(&solution).unwrap();
+    let proof_target = ledger.latest_proof_target();
+    assert_eq!(json["solution_id"], serde_json::json!(solution.id()));
+    assert_eq!(json["solution_target"], solution_target);
+    assert_eq!(json["proof_target"], proof_target);
+    assert_eq!(json["is_current_epoch"], true);
+    assert_eq!(json["meets_proof_target"], solution_target >= proof_target);
+
+    consensus.shut_down().await;
+}
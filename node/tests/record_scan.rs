@@ -0,0 +1,113 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![recursion_limit = "256"]
+
+#[allow(dead_code)]
+mod common;
+use common::{
+    rest::{free_local_addr, get, get_authorized, post, post_authorized},
+    test_peer::{sample_account, sample_genesis_block},
+};
+
+use snarkos_node::{rest::RestConfig, Client, RecordScanMode};
+use snarkos_node_rest::Claims;
+use snarkvm::prelude::{
+    store::helpers::memory::ConsensusMemory,
+    MainnetV0 as CurrentNetwork,
+    PrivateKey,
+    TestRng,
+    ViewKey,
+};
+
+use aleo_std::StorageMode;
+use std::{net::SocketAddr, time::Duration};
+
+async fn client_with_record_scan(
+    rest_ip: SocketAddr,
+    record_scan: RecordScanMode,
+) -> Client<CurrentNetwork, ConsensusMemory<CurrentNetwork>> {
+    Client::new(
+        "127.0.0.1:0".parse().unwrap(),
+        RestConfig::new(vec![rest_ip]),
+        false, // No record index.
+        record_scan,
+        None, // No state changes.
+        sample_account(),
+        &[],
+        None, // No proxy.
+        sample_genesis_block(),
+        None, // No CDN.
+        StorageMode::Production,
+        vec![], // No program denylist.
+    )
+    .await
+    .expect("couldn't create client instance")
+}
+
+/// Returns a JWT for the REST server of the sample account.
+fn sample_token() -> String {
+    Claims::new(sample_account().address()).to_jwt_string().unwrap()
+}
+
+#[tokio::test]
+async fn test_scan_routes_are_not_mounted_when_disabled() {
+    let rest_ip = free_local_addr();
+    let _client = client_with_record_scan(rest_ip, RecordScanMode::Disabled).await;
+
+    // Ensure the scan routes do not exist.
+    let view_key = ViewKey::try_from(PrivateKey::<CurrentNetwork>::new(&mut TestRng::default()).unwrap()).unwrap();
+    let body = serde_json::json!({ "view_key": view_key }).to_string();
+    let response = post_authorized(rest_ip, "/mainnet/scan/register", &body, &sample_token()).await;
+    assert!(response.starts_with("HTTP/1.1 404"), "{response}");
+}
+
+#[tokio::test]
+async fn test_scan_register_and_get_records() {
+    let rest_ip = free_local_addr();
+    let _client = client_with_record_scan(rest_ip, RecordScanMode::Memory).await;
+    let token = sample_token();
+
+    let view_key = ViewKey::try_from(PrivateKey::<CurrentNetwork>::new(&mut TestRng::default()).unwrap()).unwrap();
+    let address = view_key.to_address();
+    let records_path = format!("/mainnet/scan/{address}/records");
+
+    // Ensure the scan routes require the JWT.
+    let body = serde_json::json!({ "view_key": view_key, "start": 0 }).to_string();
+    let response = post(rest_ip, "/mainnet/scan/register", &body).await;
+    assert!(response.starts_with("HTTP/1.1 401"), "{response}");
+    let response = get(rest_ip, &records_path).await;
+    assert!(response.starts_with("HTTP/1.1 401"), "{response}");
+
+    // Ensure an unregistered address is rejected.
+    let response = get_authorized(rest_ip, &records_path, &token).await;
+    assert!(response.starts_with("HTTP/1.1 500"), "{response}");
+
+    // Ensure a backfill range beyond the maximum is rejected.
+    let oversized = serde_json::json!({ "view_key": view_key, "start": 0, "end": u32::MAX }).to_string();
+    let response = post_authorized(rest_ip, "/mainnet/scan/register", &oversized, &token).await;
+    assert!(response.starts_with("HTTP/1.1 500"), "{response}");
+
+    // Register the view key, with a backfill of the genesis block.
+    let response = post_authorized(rest_ip, "/mainnet/scan/register", &body, &token).await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+    assert!(response.contains(&address.to_string()), "{response}");
+
+    // Ensure the records of the view key can be retrieved, once the backfill is done.
+    // Note: The view key is freshly sampled, so it owns no records in the genesis block.
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    let response = get_authorized(rest_ip, &format!("{records_path}?start=0&end=10"), &token).await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+    assert!(response.trim_end().ends_with("[]"), "{response}");
+}
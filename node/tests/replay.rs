@@ -0,0 +1,121 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![recursion_limit = "256"]
+
+#[allow(dead_code)]
+mod common;
+use common::{
+    rest::{free_local_addr, post},
+    sample_transfer_transaction,
+    test_peer::{sample_account, sample_genesis_block, TestPeer},
+};
+
+use snarkos_node::{rest::RestConfig, Client, RecordScanMode};
+use snarkos_node_bft::ledger_service::{verification_pool, VerificationCategory};
+use snarkos_node_router::{
+    messages::{Message, UnconfirmedTransaction},
+    Outbound,
+};
+use snarkvm::{
+    ledger::narwhal::Data,
+    prelude::{block::Transaction, store::helpers::memory::ConsensusMemory, MainnetV0 as CurrentNetwork},
+};
+
+use aleo_std::StorageMode;
+use deadline::deadline;
+use pea2pea::{protocols::Writing, Pea2Pea};
+use std::{net::SocketAddr, time::Duration};
+
+async fn client_with_rest(rest_ip: SocketAddr) -> Client<CurrentNetwork, ConsensusMemory<CurrentNetwork>> {
+    Client::new(
+        "127.0.0.1:0".parse().unwrap(),
+        RestConfig::new(vec![rest_ip]),
+        false, // No record index.
+        RecordScanMode::Disabled,
+        None, // No state changes.
+        sample_account(),
+        &[],
+        None, // No proxy.
+        sample_genesis_block(),
+        None, // No CDN.
+        StorageMode::Production,
+        vec![], // No program denylist.
+    )
+    .await
+    .expect("couldn't create client instance")
+}
+
+/// Broadcasts the given transaction over REST, until it is reported as confirmed, and returns the response.
+async fn broadcast_until_confirmed(rest_ip: SocketAddr, transaction: &Transaction<CurrentNetwork>) -> String {
+    let body = serde_json::to_string(transaction).unwrap();
+    // Note: The replay filter is backfilled in the background, once the node has started.
+    for _ in 0..50 {
+        let response = post(rest_ip, "/mainnet/transaction/broadcast", &body).await;
+        if response.contains("\"confirmed\"") {
+            return response;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("The transaction '{}' was not reported as confirmed", transaction.id())
+}
+
+#[tokio::test]
+async fn test_rest_broadcast_of_confirmed_transaction() {
+    let rest_ip = free_local_addr();
+    let _client = client_with_rest(rest_ip).await;
+
+    // Ensure a transaction of the genesis block is reported as confirmed, with its block.
+    let genesis = sample_genesis_block();
+    let transaction = genesis.transactions().iter().next().unwrap().transaction().clone();
+    let response = broadcast_until_confirmed(rest_ip, &transaction).await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+    assert!(response.contains(&transaction.id().to_string()), "{response}");
+    assert!(response.contains("\"block_height\": 0"), "{response}");
+}
+
+#[tokio::test]
+async fn test_gossip_of_confirmed_transaction_is_not_verified() {
+    let rest_ip = free_local_addr();
+    let client = client_with_rest(rest_ip).await;
+
+    // Wait for the genesis block to be loaded into the replay filter.
+    let genesis = sample_genesis_block();
+    let confirmed = genesis.transactions().iter().next().unwrap().transaction().clone();
+    broadcast_until_confirmed(rest_ip, &confirmed).await;
+
+    // Connect a test peer to the client.
+    let peer = TestPeer::client().await;
+    client.router().connect(peer.node().listening_addr().unwrap()).unwrap().await.unwrap();
+    let peer_clone = peer.clone();
+    deadline!(Duration::from_secs(5), move || peer_clone.node().num_connected() == 1);
+    let client_addr = *peer.node().connected_addrs().first().unwrap();
+
+    // Gossip the confirmed transaction, and ensure it is dropped without being verified.
+    let num_verifications = verification_pool().num_verifications(VerificationCategory::Transaction);
+    let message = UnconfirmedTransaction { transaction_id: confirmed.id(), transaction: Data::Object(confirmed) };
+    assert!(peer.unicast(client_addr, Message::UnconfirmedTransaction(message)).is_ok());
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert_eq!(verification_pool().num_verifications(VerificationCategory::Transaction), num_verifications);
+    // Ensure the peer remains connected.
+    assert_eq!(client.router().number_of_connected_peers(), 1);
+
+    // Gossip an unconfirmed transaction, and ensure it is verified.
+    let unconfirmed = sample_transfer_transaction(client.ledger());
+    let message = UnconfirmedTransaction { transaction_id: unconfirmed.id(), transaction: Data::Object(unconfirmed) };
+    assert!(peer.unicast(client_addr, Message::UnconfirmedTransaction(message)).is_ok());
+    deadline!(Duration::from_secs(30), move || {
+        verification_pool().num_verifications(VerificationCategory::Transaction) > num_verifications
+    });
+}
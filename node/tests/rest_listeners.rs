@@ -0,0 +1,146 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![recursion_limit = "256"]
+
+#[allow(dead_code)]
+mod common;
+use common::{
+    rest::{free_local_addr, free_local_addr_v6, get, get_https, tls_fixture},
+    test_peer::{sample_account, sample_genesis_block},
+};
+
+use snarkos_node::{
+    rest::{RestConfig, RestTls},
+    Client,
+    NodeInterface,
+    RecordScanMode,
+};
+use snarkvm::prelude::{store::helpers::memory::ConsensusMemory, MainnetV0 as CurrentNetwork};
+
+use aleo_std::StorageMode;
+use anyhow::Result;
+use std::{net::SocketAddr, time::Duration};
+use tokio::net::TcpStream;
+
+async fn client_with_rest(rest_ips: &[SocketAddr]) -> Result<Client<CurrentNetwork, ConsensusMemory<CurrentNetwork>>> {
+    client_with_rest_tls(rest_ips, None).await
+}
+
+async fn client_with_rest_tls(
+    rest_ips: &[SocketAddr],
+    rest_tls: Option<RestTls>,
+) -> Result<Client<CurrentNetwork, ConsensusMemory<CurrentNetwork>>> {
+    Client::new(
+        "127.0.0.1:0".parse().unwrap(),
+        RestConfig { rest_tls, ..RestConfig::new(rest_ips.to_vec()) },
+        false, // No record index.
+        RecordScanMode::Disabled,
+        None, // No state changes.
+        sample_account(),
+        &[],
+        None, // No proxy.
+        sample_genesis_block(),
+        None, // No CDN.
+        StorageMode::Production,
+        vec![], // No program denylist.
+    )
+    .await
+}
+
+#[tokio::test]
+async fn test_rest_serves_every_address() {
+    // Start a client listening on both the IPv4 and the IPv6 loopback addresses.
+    let rest_ips = [free_local_addr(), free_local_addr_v6()];
+    let _client = client_with_rest(&rest_ips).await.unwrap();
+
+    for rest_ip in rest_ips {
+        // Ensure the same route is served on each address.
+        let response = get(rest_ip, "/mainnet/block/height/latest").await;
+        assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+
+        // Ensure the node status reports every address.
+        let response = get(rest_ip, "/mainnet/node/status").await;
+        assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+        for rest_ip in rest_ips {
+            assert!(response.contains(&format!("\"{rest_ip}\"")), "{response}");
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_rest_fails_to_start_if_any_address_is_unavailable() {
+    // Occupy an address.
+    let occupied = std::net::TcpListener::bind("[::1]:0").unwrap();
+    let occupied_ip = occupied.local_addr().unwrap();
+
+    // Ensure the client fails to start, naming the unavailable address.
+    let Err(error) = client_with_rest(&[free_local_addr(), occupied_ip]).await else {
+        panic!("The client started with an unavailable REST address");
+    };
+    assert!(format!("{error:#}").contains(&occupied_ip.to_string()), "{error:#}");
+}
+
+#[tokio::test]
+async fn test_rest_shut_down_closes_every_listener() {
+    // Start a client listening on two addresses.
+    let rest_ips = [free_local_addr(), free_local_addr_v6()];
+    let client = client_with_rest(&rest_ips).await.unwrap();
+    for rest_ip in rest_ips {
+        assert!(TcpStream::connect(rest_ip).await.is_ok());
+    }
+
+    // Shut down the client, and ensure no address accepts connections anymore.
+    client.shut_down().await;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    for rest_ip in rest_ips {
+        assert!(TcpStream::connect(rest_ip).await.is_err(), "The REST server still listens at {rest_ip}");
+    }
+}
+
+#[tokio::test]
+async fn test_rest_serves_http_and_https_on_separate_addresses() {
+    // Start a client serving HTTP on one address, and HTTPS on another.
+    let (http_ip, https_ip) = (free_local_addr(), free_local_addr());
+    let rest_tls = RestTls {
+        cert_path: tls_fixture("tls_cert_1.pem"),
+        key_path: tls_fixture("tls_key_1.pem"),
+        rest_ips: vec![https_ip],
+    };
+    let client = client_with_rest_tls(&[http_ip], Some(rest_tls)).await.unwrap();
+    assert_eq!(client.rest().as_ref().unwrap().rest_tls_ips(), [https_ip]);
+
+    // Ensure the same route is served over both protocols.
+    let response = get(http_ip, "/mainnet/block/height/latest").await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+    let response = get_https(https_ip, "/mainnet/block/height/latest", &tls_fixture("tls_cert_1.pem")).await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+
+    // Ensure the HTTPS address does not answer plain HTTP.
+    let response = get(https_ip, "/mainnet/block/height/latest").await;
+    assert!(!response.starts_with("HTTP/1.1 200"), "{response}");
+}
+
+#[tokio::test]
+async fn test_rest_fails_to_start_with_a_mismatched_certificate() {
+    let rest_tls = RestTls {
+        cert_path: tls_fixture("tls_cert_1.pem"),
+        key_path: tls_fixture("tls_key_2.pem"),
+        rest_ips: vec![free_local_addr()],
+    };
+    let Err(error) = client_with_rest_tls(&[free_local_addr()], Some(rest_tls)).await else {
+        panic!("The client started with a mismatched TLS certificate and key");
+    };
+    assert!(format!("{error:#}").contains("does not match the certificate"), "{error:#}");
+}
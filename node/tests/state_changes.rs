@@ -0,0 +1,135 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![recursion_limit = "256"]
+
+#[allow(dead_code)]
+mod common;
+use common::{
+    rest::{body, free_local_addr, get},
+    test_peer::{sample_account, sample_genesis_block},
+};
+
+use snarkos_node::{rest::RestConfig, Client, RecordScanMode};
+use snarkvm::{
+    prelude::{store::helpers::memory::ConsensusMemory, MainnetV0 as CurrentNetwork},
+    synthesizer::program::FinalizeOperation,
+};
+
+use aleo_std::StorageMode;
+use std::{net::SocketAddr, path::PathBuf, time::Duration};
+
+async fn client_with_state_changes(
+    rest_ip: SocketAddr,
+    state_changes_retention: Option<u32>,
+    storage_path: PathBuf,
+) -> Client<CurrentNetwork, ConsensusMemory<CurrentNetwork>> {
+    Client::new(
+        "127.0.0.1:0".parse().unwrap(),
+        RestConfig::new(vec![rest_ip]),
+        false, // No record index.
+        RecordScanMode::Disabled,
+        state_changes_retention,
+        sample_account(),
+        &[],
+        None, // No proxy.
+        sample_genesis_block(),
+        None, // No CDN.
+        StorageMode::Custom(storage_path),
+        vec![], // No program denylist.
+    )
+    .await
+    .expect("couldn't create client instance")
+}
+
+/// Returns a directory for the ledger of a client.
+fn sample_storage_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("snarkos-state-changes-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&path);
+    path
+}
+
+#[tokio::test]
+async fn test_state_changes_of_genesis_block() {
+    let rest_ip = free_local_addr();
+    let storage_path = sample_storage_path("genesis");
+    let _client = client_with_state_changes(rest_ip, Some(10), storage_path.clone()).await;
+
+    // Wait for the backfill to index the genesis block.
+    let mut response = String::new();
+    for _ in 0..50 {
+        response = get(rest_ip, "/mainnet/block/0/stateChanges").await;
+        if response.starts_with("HTTP/1.1 200") {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+    let json: serde_json::Value = serde_json::from_str(body(&response)).unwrap();
+    assert_eq!(json["height"], 0);
+
+    // Collect the key-value operations of the transactions in the genesis block, which execute `credits.aleo`.
+    let mut expected = Vec::new();
+    for confirmed in sample_genesis_block().transactions().iter() {
+        for operation in confirmed.finalize_operations().iter() {
+            let (operation, key_id, value_id) = match operation {
+                FinalizeOperation::InsertKeyValue(_, key_id, value_id) => ("insert", key_id, Some(value_id)),
+                FinalizeOperation::UpdateKeyValue(_, key_id, value_id) => ("update", key_id, Some(value_id)),
+                FinalizeOperation::RemoveKeyValue(_, key_id) => ("remove", key_id, None),
+                _ => continue,
+            };
+            expected.push(serde_json::json!({
+                "transaction_id": confirmed.id(),
+                "operation": operation,
+                "key_id": key_id,
+                "value_id": value_id,
+            }));
+        }
+    }
+    assert!(!expected.is_empty());
+
+    // Ensure each operation is served, with its key and value IDs, and the mapping it changed.
+    let changes = json["state_changes"].as_array().unwrap();
+    assert_eq!(changes.len(), expected.len());
+    for (change, expected) in changes.iter().zip(expected) {
+        for field in ["transaction_id", "operation", "key_id", "value_id"] {
+            assert_eq!(change[field], expected[field], "{change}");
+        }
+        assert_eq!(change["program_id"], "credits.aleo", "{change}");
+        assert!(change["mapping_name"].is_string(), "{change}");
+    }
+
+    // Ensure a block that is not indexed yet is refused.
+    let response = get(rest_ip, "/mainnet/block/1/stateChanges").await;
+    assert!(response.starts_with("HTTP/1.1 500"), "{response}");
+    assert!(response.contains("not indexed yet"), "{response}");
+
+    std::fs::remove_dir_all(&storage_path).unwrap();
+}
+
+#[tokio::test]
+async fn test_state_changes_are_disabled_by_default() {
+    let rest_ip = free_local_addr();
+    let storage_path = sample_storage_path("disabled");
+    let _client = client_with_state_changes(rest_ip, None, storage_path.clone()).await;
+
+    // Ensure the state changes are refused, as they are not indexed.
+    let response = get(rest_ip, "/mainnet/block/0/stateChanges").await;
+    assert!(response.starts_with("HTTP/1.1 500"), "{response}");
+    assert!(response.contains("--enable-state-changes"), "{response}");
+    // Ensure the index was not created.
+    assert!(!storage_path.join("indexes").join("state_changes").exists());
+
+    let _ = std::fs::remove_dir_all(&storage_path);
+}
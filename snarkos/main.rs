@@ -12,9 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use snarkos_cli::{commands::CLI, helpers::Updater};
+use snarkos_cli::{
+    commands::{Command, CLI},
+    helpers::Updater,
+};
 
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches};
 use std::process::exit;
 
 #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
@@ -26,7 +29,15 @@ static GLOBAL: Jemalloc = Jemalloc;
 
 fn main() -> anyhow::Result<()> {
     // Parse the given arguments.
-    let cli = CLI::parse();
+    let matches = CLI::command().get_matches();
+    let mut cli = CLI::from_arg_matches(&matches).unwrap_or_else(|error| error.exit());
+    // Apply the node configuration file, if one is specified.
+    if let (Command::Start(start), Some(("start", start_matches))) = (&mut cli.command, matches.subcommand()) {
+        if let Err(error) = start.apply_config_file(start_matches) {
+            println!("⚠️  {error:#}\n");
+            exit(1);
+        }
+    }
     // Run the updater.
     println!("{}", Updater::print_cli());
     // Run the CLI.
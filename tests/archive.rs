@@ -0,0 +1,143 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[allow(dead_code)]
+mod common;
+use common::{get, Devnet, DEVNET_TIMEOUT};
+
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    process::{Child, Command, Stdio},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// The number of blocks per chunk of the block archive.
+const BLOCKS_PER_FILE: u32 = 50;
+/// The number of most recent blocks that are not archived, as they may still be rolled back.
+const MAXIMUM_FORK_DEPTH: u32 = 64;
+
+/// A node that is killed when the test ends, even if it fails.
+struct Node(Child);
+
+impl Drop for Node {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+/// Returns a local address on a port that is currently unused.
+fn unused_address() -> String {
+    TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().to_string()
+}
+
+/// Returns the latest block height of the node with the given REST API, if it is serving it.
+fn get_height(rest: &str) -> Option<u32> {
+    TcpStream::connect(rest.trim_start_matches("http://")).ok()?;
+    get(rest, "block/height/latest").trim().parse().ok()
+}
+
+/// Starts a mirror of the block archive of the given REST API, which corrupts every chunk of blocks it serves,
+/// and returns its base URL, along with the number of chunks it corrupted.
+fn start_corrupted_mirror(rest: &str) -> (String, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}/mainnet/archive", listener.local_addr().unwrap());
+    let host = rest.trim_start_matches("http://").to_string();
+    let num_corrupted = Arc::new(AtomicUsize::new(0));
+    let counter = num_corrupted.clone();
+    std::thread::spawn(move || {
+        for mut stream in listener.incoming().map_while(Result::ok) {
+            // Read the path of the request, and the rest of its headers.
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut header = String::new();
+            while reader.read_line(&mut header).unwrap_or(0) > 2 {
+                header.clear();
+            }
+            let path = request_line.split_whitespace().nth(1).unwrap_or_default().to_string();
+
+            // Forward the request to the REST API.
+            let mut upstream = TcpStream::connect(&host).unwrap();
+            write!(upstream, "GET {path} HTTP/1.1\r\nHost: {host}\r\nConnection: close\r\n\r\n").unwrap();
+            let mut response = Vec::new();
+            upstream.read_to_end(&mut response).unwrap();
+
+            // Corrupt a byte in the middle of the body of every chunk of blocks, leaving its checksum intact.
+            if path.ends_with(".blocks") {
+                let body_start = response.windows(4).position(|window| window == b"\r\n\r\n").unwrap() + 4;
+                let middle = body_start + (response.len() - body_start) / 2;
+                response[middle] ^= 0xff;
+                counter.fetch_add(1, Ordering::Relaxed);
+            }
+            let _ = stream.write_all(&response);
+        }
+    });
+    (base_url, num_corrupted)
+}
+
+/// This test boots a devnet whose nodes serve their block archive, and ensures a fresh node prefetches the archived
+/// blocks from it, failing over from a mirror that serves corrupted chunks of blocks.
+#[test]
+#[ignore = "Runs a devnet until it archives a chunk of blocks, which takes several minutes"]
+fn test_prefetch_blocks_from_archive() {
+    let devnet = Devnet::start_with("archive", &["--serve-block-archive"]);
+    let rest = devnet.rest.as_str();
+
+    // Wait for the first chunk of blocks to be archived.
+    devnet.wait_for_height(BLOCKS_PER_FILE + MAXIMUM_FORK_DEPTH);
+    let checksum = get(rest, &format!("archive/0.{BLOCKS_PER_FILE}.sum"));
+    assert_eq!(checksum.len(), 64, "{checksum}");
+    // Ensure the chunks are aligned, and the chunks that are not archived yet are refused.
+    let response = get(rest, "archive/10.60.blocks");
+    assert!(response.contains("Invalid chunk"), "{response}");
+    let response = get(rest, "archive/1000.1050.blocks");
+    assert!(response.contains("Cannot request blocks beyond the block archive"), "{response}");
+
+    // Start a fresh node without peers, which prefetches blocks from the corrupted mirror first, then from the archive.
+    let (corrupted_mirror, num_corrupted) = start_corrupted_mirror(rest);
+    let cdn = format!("{corrupted_mirror},{rest}/mainnet/archive");
+    let node_rest = unused_address();
+    let _node = Node(
+        Command::new(env!("CARGO_BIN_EXE_snarkos"))
+            .args(["start", "--nodisplay", "--client", "--dev", "9", "--dev-num-validators", "4", "--peers", ""])
+            .args(["--node", &unused_address(), "--rest", &node_rest, "--cdn", &cdn])
+            // Note: The ledger of a development node is in the current directory.
+            .current_dir(&devnet.storage)
+            .stdout(Stdio::null())
+            .spawn()
+            .expect("Failed to start the node"),
+    );
+
+    // Ensure the node prefetched the archived blocks, as it has no peers to sync from.
+    let node_rest = format!("http://{node_rest}");
+    let start = Instant::now();
+    loop {
+        let height = get_height(&node_rest);
+        if height == Some(BLOCKS_PER_FILE - 1) {
+            break;
+        }
+        assert!(start.elapsed() < DEVNET_TIMEOUT, "The node did not prefetch the archived blocks (height {height:?})");
+        std::thread::sleep(Duration::from_secs(1));
+    }
+    // Ensure the corrupted chunk was refused, and the chunk of the archive was inserted instead.
+    assert!(num_corrupted.load(Ordering::Relaxed) >= 1);
+    let last_block = format!("block/{}", BLOCKS_PER_FILE - 1);
+    assert_eq!(get(&node_rest, &last_block), get(rest, &last_block));
+}
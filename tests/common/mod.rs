@@ -0,0 +1,212 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpStream,
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    sync::mpsc,
+    time::{Duration, Instant},
+};
+
+/// The maximum time for the devnet to start.
+pub const DEVNET_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Removes the terminal styles from the given line.
+pub fn strip_styles(line: &str) -> String {
+    let mut stripped = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            // Skip the escape sequence, up to its final letter.
+            '\x1b' => {
+                chars.by_ref().find(|c| c.is_ascii_alphabetic());
+            }
+            c => stripped.push(c),
+        }
+    }
+    stripped
+}
+
+/// Returns the first word of the given line that starts with the given prefix, without trailing punctuation.
+pub fn find_word(line: &str, prefix: &str) -> Option<String> {
+    line.split_whitespace().find(|word| word.starts_with(prefix)).map(|word| word.trim_end_matches('.').to_string())
+}
+
+/// Sends the given raw request to the given REST API, and returns the body of the response.
+fn send(rest: &str, request: &str) -> String {
+    let mut stream = TcpStream::connect(rest.trim_start_matches("http://")).unwrap();
+    stream.write_all(request.as_bytes()).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response.split_once("\r\n\r\n").map(|(_, body)| body.to_string()).unwrap_or_default()
+}
+
+/// Returns the body of the response to a GET request of the given route of the REST API.
+pub fn get(rest: &str, route: &str) -> String {
+    let host = rest.trim_start_matches("http://");
+    send(rest, &format!("GET /mainnet/{route} HTTP/1.1\r\nHost: {host}\r\nConnection: close\r\n\r\n"))
+}
+
+/// Returns the body of the response to a POST request of the given route of the REST API, with the given JSON body.
+pub fn post(rest: &str, route: &str, body: &str) -> String {
+    let (host, length) = (rest.trim_start_matches("http://"), body.len());
+    send(
+        rest,
+        &format!(
+            "POST /mainnet/{route} HTTP/1.1\r\nHost: {host}\r\nContent-Type: application/json\r\nContent-Length: {length}\r\nConnection: close\r\n\r\n{body}"
+        ),
+    )
+}
+
+/// Returns a transfer of one microcredit from the given account to a new account, as the body of an execution.
+pub fn transfer_body(private_key: &str) -> String {
+    let account = snarkos(&["account", "new"]);
+    let recipient = account.lines().find_map(|line| find_word(line, "aleo1")).expect("Missing the new address");
+    format!(
+        r#"{{"program_id": "credits.aleo", "function": "transfer_public", "inputs": ["{recipient}", "1u64"], "fee": 0, "private_key": "{private_key}"}}"#
+    )
+}
+
+/// Returns the transaction ID in the given response of a broadcast.
+pub fn transaction_id(response: &str) -> String {
+    let start = response.find("at1").unwrap_or_else(|| panic!("Missing the transaction ID in '{response}'"));
+    response[start..].split('"').next().unwrap().to_string()
+}
+
+/// Returns the outcome of the given transaction, once it is included in a block, along with its block height.
+pub fn wait_for_outcome(rest: &str, transaction_id: &str) -> (String, u32) {
+    let start = Instant::now();
+    loop {
+        let outcome = get(rest, &format!("transaction/{transaction_id}/outcome"));
+        if let Some((_, height)) = outcome.split_once("\"height\": ") {
+            let height = height.split(|c: char| !c.is_ascii_digit()).next().unwrap().parse().unwrap();
+            return (outcome, height);
+        }
+        assert!(start.elapsed() < DEVNET_TIMEOUT, "Transaction {transaction_id} was not included in time: {outcome}");
+        std::thread::sleep(Duration::from_secs(1));
+    }
+}
+
+/// Runs the given `snarkos` command, and returns its output, after ensuring it succeeded.
+pub fn snarkos(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_snarkos")).args(args).env("NO_COLOR", "1").output().unwrap();
+    let stdout = strip_styles(&String::from_utf8_lossy(&output.stdout));
+    assert!(output.status.success(), "'snarkos {}' failed:\n{stdout}\n{}", args.join(" "), {
+        String::from_utf8_lossy(&output.stderr)
+    });
+    stdout
+}
+
+/// Runs the given `snarkos` command, and returns its output, after ensuring it failed.
+/// Note: The error of a failed command is printed to the standard output.
+pub fn snarkos_failure(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_snarkos")).args(args).env("NO_COLOR", "1").output().unwrap();
+    let stdout = strip_styles(&String::from_utf8_lossy(&output.stdout));
+    assert!(!output.status.success(), "'snarkos {}' unexpectedly succeeded:\n{stdout}", args.join(" "));
+    stdout
+}
+
+/// A devnet of validators in a single process, which is killed when the test ends, even if it fails.
+pub struct Devnet {
+    /// The devnet process.
+    child: Child,
+    /// The storage of the devnet.
+    pub storage: PathBuf,
+    /// The development private key of node 0.
+    pub private_key: String,
+    /// The REST API of node 0.
+    pub rest: String,
+    /// The IP of the node server of node 0.
+    pub node_ip: String,
+}
+
+impl Devnet {
+    /// Starts a devnet of 4 validators, without the development transactions, and waits for its first block.
+    pub fn start(name: &str) -> Self {
+        Self::start_with(name, &[])
+    }
+
+    /// Starts a devnet of 4 validators with the given additional arguments, and waits for its first block.
+    pub fn start_with(name: &str, args: &[&str]) -> Self {
+        let storage = Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!("{name}-{}", std::process::id()));
+        let mut child = Command::new(env!("CARGO_BIN_EXE_snarkos"))
+            .args(["devnet", "--validators", "4", "--clients", "0", "--no-dev-txs"])
+            .args(args)
+            .arg("--storage")
+            .arg(&storage)
+            .env("NO_COLOR", "1")
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("Failed to start the devnet");
+        let stdout = child.stdout.take().unwrap();
+
+        // Read the development private key and the REST API of node 0 from the output of the devnet.
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let (mut private_key, mut rest, mut node_ip) = (None, None, None);
+            for line in BufReader::new(stdout).lines().map_while(Result::ok).map(|line| strip_styles(&line)) {
+                if line.contains("private key for node 0 ") {
+                    private_key = find_word(&line, "APrivateKey1");
+                }
+                if line.contains("Node 0 ") {
+                    rest = find_word(&line, "http://");
+                    node_ip = find_word(&line, "127.0.0.1:").map(|ip| ip.trim_end_matches(',').to_string());
+                }
+                if let (Some(private_key), Some(rest), Some(node_ip)) = (&private_key, &rest, &node_ip) {
+                    let _ = sender.send((private_key.clone(), rest.clone(), node_ip.clone()));
+                }
+            }
+        });
+        let received = receiver.recv_timeout(DEVNET_TIMEOUT);
+        let (private_key, rest, node_ip) = received.unwrap_or_else(|_| {
+            let _ = child.kill();
+            panic!("The devnet did not start in time")
+        });
+        let devnet = Self { child, storage, private_key, rest, node_ip };
+
+        // Wait for the devnet to produce a block, so that the validators are running.
+        devnet.wait_for_height(1);
+        devnet
+    }
+
+    /// Stops the devnet, and keeps its storage until the test ends.
+    pub fn stop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+
+    /// Returns the latest block height of node 0.
+    pub fn latest_height(&self) -> u32 {
+        get(&self.rest, "block/height/latest").trim().parse().unwrap_or(0)
+    }
+
+    /// Waits for node 0 to reach the given block height.
+    pub fn wait_for_height(&self, height: u32) {
+        let start = Instant::now();
+        while self.latest_height() < height {
+            assert!(start.elapsed() < DEVNET_TIMEOUT, "The devnet did not reach block {height} in time");
+            std::thread::sleep(Duration::from_secs(1));
+        }
+    }
+}
+
+impl Drop for Devnet {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = std::fs::remove_dir_all(&self.storage);
+    }
+}
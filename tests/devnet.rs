@@ -0,0 +1,56 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    path::Path,
+    process::{Command, Stdio},
+    time::{Duration, Instant},
+};
+
+/// The number of blocks the devnet must produce.
+const NUM_BLOCKS: u32 = 3;
+/// The maximum time for the devnet to produce the blocks.
+const DEVNET_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// This test boots a devnet in a single process, and ensures it produces blocks and exits successfully.
+/// It takes several minutes, so it runs in its own CI job with `cargo test --test devnet -- --ignored`.
+#[test]
+#[ignore]
+fn test_devnet_produces_blocks() {
+    let storage = Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!("devnet-{}", std::process::id()));
+
+    let mut devnet = Command::new(env!("CARGO_BIN_EXE_snarkos"))
+        .args(["devnet", "--validators", "4", "--clients", "1", "--blocks", &NUM_BLOCKS.to_string()])
+        .arg("--storage")
+        .arg(&storage)
+        .stdout(Stdio::null())
+        .spawn()
+        .expect("Failed to start the devnet");
+
+    // Wait for the devnet to exit, or for the timeout to elapse.
+    let start = Instant::now();
+    let status = loop {
+        if let Some(status) = devnet.try_wait().unwrap() {
+            break status;
+        }
+        if start.elapsed() > DEVNET_TIMEOUT {
+            devnet.kill().unwrap();
+            panic!("The devnet did not produce {NUM_BLOCKS} blocks within {DEVNET_TIMEOUT:?}");
+        }
+        std::thread::sleep(Duration::from_secs(1));
+    };
+    let _ = std::fs::remove_dir_all(&storage);
+
+    assert!(status.success(), "The devnet exited with {status}");
+}
@@ -0,0 +1,68 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[allow(dead_code)]
+mod common;
+use common::{snarkos, snarkos_failure, Devnet};
+
+use snarkos_node::bft::ledger_service::{FilesystemSpaceReporter, SpaceReporter};
+
+use std::path::Path;
+
+/// Returns the number of blocks reported by the given output of `snarkos ledger clone`.
+fn num_blocks(output: &str) -> u32 {
+    let (_, blocks) = output.split_once(" entries, ").unwrap_or_else(|| panic!("Missing the blocks in '{output}'"));
+    blocks.split_whitespace().next().unwrap().parse().unwrap()
+}
+
+#[test]
+#[ignore = "Runs a devnet, which takes a few minutes"]
+fn ledger_compact_and_clone() {
+    let mut devnet = Devnet::start("ledger");
+    devnet.wait_for_height(5);
+    let ledger = devnet.storage.join(".ledger-0-0");
+    let ledger = ledger.to_str().unwrap();
+
+    // Ensure the ledger of a running node can't be compacted or cloned.
+    let error = snarkos_failure(&["ledger", "compact", "--path", ledger]);
+    assert!(error.contains("The ledger is in use"), "{error}");
+    let clone = devnet.storage.join("clone");
+    let clone = clone.to_str().unwrap();
+    let error = snarkos_failure(&["ledger", "clone", "--path", ledger, "--to", clone]);
+    assert!(error.contains("The ledger is in use"), "{error}");
+    assert!(!Path::new(clone).exists());
+
+    // Stop the devnet, and compact the ledger.
+    let height = devnet.latest_height();
+    devnet.stop();
+    let size_before = FilesystemSpaceReporter.directory_size(Path::new(ledger)).unwrap();
+    let output = snarkos(&["ledger", "compact", "--path", ledger]);
+    assert!(output.contains("Compacted the ledger"), "{output}");
+    assert!(FilesystemSpaceReporter.directory_size(Path::new(ledger)).unwrap() <= size_before);
+
+    // Ensure the compacted ledger is intact.
+    let output = snarkos(&["ledger", "check", "--path", ledger]);
+    assert!(output.contains("No inconsistencies were found"), "{output}");
+
+    // Clone the ledger, and ensure the clone is intact and has every block.
+    let output = snarkos(&["ledger", "clone", "--path", ledger, "--to", clone]);
+    assert!(output.contains("Cloned the ledger"), "{output}");
+    assert!(num_blocks(&output) > height);
+    let output = snarkos(&["ledger", "check", "--path", clone]);
+    assert!(output.contains("No inconsistencies were found"), "{output}");
+
+    // Ensure the clone does not overwrite an existing directory.
+    let error = snarkos_failure(&["ledger", "clone", "--path", ledger, "--to", clone]);
+    assert!(error.contains("already exists"), "{error}");
+}
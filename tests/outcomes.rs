@@ -0,0 +1,46 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[allow(dead_code)]
+mod common;
+use common::{find_word, get, post, snarkos, transaction_id, transfer_body, wait_for_outcome, Devnet};
+
+/// This test boots a devnet, and ensures the outcome route reports a transfer as pending and then accepted,
+/// and a transfer whose fee cannot be paid as evicted. The rejected outcome is covered by the `rejected` test.
+/// It takes several minutes, so it runs in its own CI job with `cargo test --test outcomes -- --ignored`.
+#[test]
+#[ignore]
+fn test_transaction_outcomes() {
+    let devnet = Devnet::start("outcomes");
+    let rest = devnet.rest.as_str();
+
+    // Transfer credits from the funded development account.
+    let accepted_id = transaction_id(&post(rest, "dev/execute", &transfer_body(&devnet.private_key)));
+    // Ensure the transfer is pending, as a block takes several rounds to commit it.
+    let outcome = get(rest, &format!("transaction/{accepted_id}/outcome"));
+    assert!(outcome.contains(r#""status": "pending""#), "{outcome}");
+    // Ensure the transfer is accepted, once it is included in a block.
+    let (outcome, height) = wait_for_outcome(rest, &accepted_id);
+    assert!(outcome.contains(r#""status": "accepted""#), "{outcome}");
+    assert!(height > 0);
+
+    // Transfer credits from a new account, which cannot pay the fee.
+    let account = snarkos(&["account", "new"]);
+    let private_key = account.lines().find_map(|line| find_word(line, "APrivateKey1")).expect("Missing the new key");
+    let evicted_id = transaction_id(&post(rest, "dev/execute", &transfer_body(&private_key)));
+    // Ensure the transfer is evicted, as the memory pool refuses it.
+    let outcome = get(rest, &format!("transaction/{evicted_id}/outcome"));
+    assert!(outcome.contains(r#""status": "evicted""#), "{outcome}");
+    assert!(outcome.contains("The memory pool refused the transaction"), "{outcome}");
+}
@@ -0,0 +1,58 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[allow(dead_code)]
+mod common;
+use common::{find_word, get, snarkos, Devnet};
+
+/// The number of transfers to broadcast.
+const NUM_TRANSFERS: usize = 3;
+
+/// Returns the unsigned integer of the given key in the given JSON, if it is present.
+fn find_u64(json: &str, key: &str) -> Option<u64> {
+    let start = json.find(&format!(r#""{key}": "#))? + key.len() + 4;
+    json[start..].split(|c: char| !c.is_ascii_digit()).next()?.parse().ok()
+}
+
+/// This test boots a devnet whose nodes sample every transaction they broadcast, broadcasts transfers to node 0,
+/// and ensures node 0 records a nonempty histogram of sub-second propagation delays.
+#[test]
+#[ignore = "Runs a devnet and broadcasts several transfers, which takes several minutes"]
+fn test_propagation_delay_sampling() {
+    let devnet = Devnet::start_with("propagation", &["--propagation-sample-rate", "1"]);
+    let rest = devnet.rest.as_str();
+
+    // Ensure no delay is recorded before a transaction is broadcast.
+    let propagation = get(rest, "node/propagation");
+    assert_eq!(find_u64(&propagation, "count"), Some(0), "{propagation}");
+
+    // Write the private key of the sender to a keystore.
+    let keystore = devnet.storage.join("private.key");
+    std::fs::write(&keystore, &devnet.private_key).unwrap();
+    let keystore = keystore.to_str().unwrap();
+
+    // Broadcast the transfers to node 0, and wait for each of them to be confirmed.
+    let account = snarkos(&["account", "new"]);
+    let recipient = account.lines().find_map(|line| find_word(line, "aleo1")).expect("Missing the new address");
+    for _ in 0..NUM_TRANSFERS {
+        let args = ["developer", "transfer", "--to", &recipient, "--amount", "1", "--fee", "0", "--keystore", keystore];
+        let output = snarkos(&[&args[..], &["--rest", rest, "--wait"]].concat());
+        assert!(output.contains("in block"), "{output}");
+    }
+
+    // Ensure the delays were recorded, and are sub-second on a local devnet.
+    let propagation = get(rest, "node/propagation");
+    assert!(find_u64(&propagation, "count").unwrap() > 0, "{propagation}");
+    assert!(find_u64(&propagation, "p50_ms").expect("Missing the median delay") < 1_000, "{propagation}");
+}
@@ -0,0 +1,68 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[allow(dead_code)]
+mod common;
+use common::{get, post, transaction_id, wait_for_outcome, Devnet};
+
+/// The reason the ledger reports for the rejected execution.
+const REASON: &str = "The execution of 'reject_me.aleo/check' failed to finalize, so only the fee was consumed";
+
+/// A program whose function fails to finalize for any input other than zero.
+const PROGRAM: &str = r"program reject_me.aleo;
+
+function check:
+    input r0 as u64.public;
+    async check r0 into r1;
+    output r1 as reject_me.aleo/check.future;
+
+finalize check:
+    input r0 as u64.public;
+    assert.eq r0 0u64;
+";
+
+/// This test boots a devnet, deploys a program, and executes it with an input that fails to finalize,
+/// and ensures the reason of the rejection is served by the outcome route and the rejected route of the block.
+/// It takes several minutes, so it runs in its own CI job with `cargo test --test rejected -- --ignored`.
+#[test]
+#[ignore]
+fn test_rejected_execution_reason() {
+    let devnet = Devnet::start("rejected");
+    let rest = devnet.rest.as_str();
+    let private_key = &devnet.private_key;
+
+    // Deploy the program, and wait for the deployment to be accepted.
+    let body = format!(r#"{{"program": {PROGRAM:?}, "fee": 0, "private_key": "{private_key}"}}"#);
+    let deployment_id = transaction_id(&post(rest, "dev/deploy", &body));
+    let (outcome, _) = wait_for_outcome(rest, &deployment_id);
+    assert!(outcome.contains(r#""status": "accepted""#), "{outcome}");
+
+    // Execute the program with an input that fails to finalize, so that only the fee is consumed.
+    let body = format!(
+        r#"{{"program_id": "reject_me.aleo", "function": "check", "inputs": ["1u64"], "fee": 0, "private_key": "{private_key}"}}"#
+    );
+    let execution_id = transaction_id(&post(rest, "dev/execute", &body));
+
+    // Ensure the outcome route reports the rejection and its reason.
+    let (outcome, height) = wait_for_outcome(rest, &execution_id);
+    assert!(outcome.contains(r#""status": "rejected""#), "{outcome}");
+    assert!(outcome.contains(REASON), "{outcome}");
+
+    // Ensure the block reports the execution as rejected, under the ID that was submitted, with its reason.
+    let rejected = get(rest, &format!("block/{height}/rejected"));
+    assert!(rejected.contains(&format!(r#""id": "{execution_id}""#)), "{rejected}");
+    assert!(rejected.contains(REASON), "{rejected}");
+    // Ensure the deployment, which was accepted, is not reported as rejected.
+    assert!(!rejected.contains(&deployment_id), "{rejected}");
+}
@@ -0,0 +1,62 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[allow(dead_code)]
+mod common;
+use common::{get, post, transaction_id, transfer_body, wait_for_outcome, Devnet};
+
+use std::time::Duration;
+
+/// The number of transfers to commit while the consensus restarts.
+const NUM_TRANSFERS: usize = 3;
+/// The number of times to restart the consensus.
+const NUM_RESTARTS: usize = 5;
+
+/// This test boots a devnet, and restarts the consensus of node 0 repeatedly while blocks with transfers are
+/// committed, so that some restarts interrupt a commit and the BFT re-delivers its subdag. It ensures no block
+/// rejects or aborts a transaction, e.g. as a duplicate of a confirmed transaction, and the chain continues advancing.
+/// It takes several minutes, so it runs in its own CI job with `cargo test --test restart -- --ignored`.
+#[test]
+#[ignore]
+fn test_restart_consensus_mid_commit() {
+    let devnet = Devnet::start("restart");
+    let rest = devnet.rest.as_str();
+    let start_height = devnet.latest_height();
+
+    // Broadcast the transfers, so that the next blocks commit transmissions.
+    let transaction_ids = (0..NUM_TRANSFERS)
+        .map(|_| transaction_id(&post(rest, "dev/execute", &transfer_body(&devnet.private_key))))
+        .collect::<Vec<_>>();
+    // Restart the consensus of node 0 while the transfers are committed.
+    for _ in 0..NUM_RESTARTS {
+        let response = post(rest, "node/bft/restart", "");
+        assert!(response.contains(r#""restarted": true"#), "{response}");
+        std::thread::sleep(Duration::from_secs(1));
+    }
+
+    // Ensure each transfer is accepted.
+    for transaction_id in &transaction_ids {
+        let (outcome, _) = wait_for_outcome(rest, transaction_id);
+        assert!(outcome.contains(r#""status": "accepted""#), "{outcome}");
+    }
+    // Ensure the chain continues advancing after the restarts.
+    let latest_height = devnet.latest_height();
+    devnet.wait_for_height(latest_height + 3);
+
+    // Ensure no block rejected or aborted a transaction.
+    for height in start_height..=devnet.latest_height() {
+        let rejected = get(rest, &format!("block/{height}/rejected"));
+        assert_eq!(rejected.trim(), "[]", "Block {height} rejected transactions: {rejected}");
+    }
+}
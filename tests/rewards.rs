@@ -0,0 +1,104 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[allow(dead_code)]
+mod common;
+use common::{find_word, get, snarkos, Devnet, DEVNET_TIMEOUT};
+
+use std::{
+    process::{Command, Stdio},
+    time::{Duration, Instant},
+};
+
+/// Returns the unsigned integer that follows the first occurrence of the given key in the given JSON.
+fn find_u64(json: &str, key: &str) -> Option<u64> {
+    let start = json.find(&format!(r#""{key}": "#))? + key.len() + 4;
+    json[start..].split(|c: char| !c.is_ascii_digit()).next()?.parse().ok()
+}
+
+/// Returns the puzzle reward of the given block, from its ratifications.
+fn puzzle_reward(block: &str) -> u64 {
+    let start = block.find(r#""puzzle_reward""#).unwrap_or_else(|| panic!("Missing the puzzle reward in '{block}'"));
+    find_u64(&block[start..], "amount").unwrap()
+}
+
+/// This test boots a devnet with an external prover, and ensures the puzzle rewards of the prover that are served
+/// by the rewards route match the puzzle rewards of the blocks that include its solutions.
+#[test]
+#[ignore = "Runs a devnet with a prover, which takes several minutes"]
+fn test_prover_rewards() {
+    let devnet = Devnet::start("rewards");
+    let rest = devnet.rest.as_str();
+
+    // Start a prover with a new account, which is the only prover of the devnet.
+    let account = snarkos(&["account", "new"]);
+    let private_key =
+        account.lines().find_map(|line| find_word(line, "APrivateKey1")).expect("Missing the private key");
+    let address = account.lines().find_map(|line| find_word(line, "aleo1")).expect("Missing the new address");
+    let logfile = devnet.storage.join("prover.log");
+    let mut prover = Command::new(env!("CARGO_BIN_EXE_snarkos"))
+        .args(["start", "--nodisplay", "--norest", "--prover", "--dev", "4", "--dev-num-validators", "4"])
+        .args(["--private-key", &private_key, "--peers", &devnet.node_ip])
+        .arg("--logfile")
+        .arg(&logfile)
+        .stdout(Stdio::null())
+        .spawn()
+        .expect("Failed to start the prover");
+
+    // Wait for a few solutions of the prover to be included in blocks.
+    let start = Instant::now();
+    let (end, rewards) = loop {
+        let end = devnet.latest_height() + 1;
+        let rewards = get(rest, &format!("prover/{address}/rewards?start=0&end={end}&detail=true"));
+        if find_u64(&rewards, "num_solutions").unwrap_or(0) >= 3 {
+            break (end, rewards);
+        }
+        if start.elapsed() > 2 * DEVNET_TIMEOUT {
+            let _ = prover.kill();
+            panic!("The solutions of the prover were not included in time: {rewards}");
+        }
+        std::thread::sleep(Duration::from_secs(5));
+    };
+    let _ = prover.kill();
+    let _ = prover.wait();
+    assert!(rewards.contains(&format!(r#""address": "{address}""#)), "{rewards}");
+
+    // Ensure the reward of each block is its puzzle reward, as the prover authored all of its solutions.
+    // Note: The shares of the solutions are rounded down, by less than one microcredit each.
+    let (mut total_reward, mut num_solutions) = (0, 0);
+    for entry in rewards.split(r#""height": "#).skip(1) {
+        let height: u32 = entry.split(|c: char| !c.is_ascii_digit()).next().unwrap().parse().unwrap();
+        let reward = find_u64(entry, "reward").unwrap();
+        let solutions = find_u64(entry, "num_solutions").unwrap();
+        let expected = puzzle_reward(&get(rest, &format!("block/{height}")));
+        assert!(reward <= expected && reward + solutions >= expected, "Block {height}: {reward} of {expected}");
+        total_reward += reward;
+        num_solutions += solutions;
+    }
+    assert!(num_solutions >= 3);
+    assert_eq!(find_u64(&rewards, "total_reward"), Some(total_reward), "{rewards}");
+    assert_eq!(find_u64(&rewards, "num_solutions"), Some(num_solutions), "{rewards}");
+
+    // Ensure the totals of a subrange add up to the totals of the entire range.
+    let middle = end / 2;
+    let first = get(rest, &format!("prover/{address}/rewards?start=0&end={middle}"));
+    let second = get(rest, &format!("prover/{address}/rewards?start={middle}&end={end}"));
+    assert!(!first.contains(r#""blocks""#), "{first}");
+    let sum = find_u64(&first, "total_reward").unwrap() + find_u64(&second, "total_reward").unwrap();
+    assert_eq!(sum, total_reward);
+
+    // Ensure an unbounded range is refused.
+    let response = get(rest, &format!("prover/{address}/rewards?start=0&end=100000"));
+    assert!(response.contains("Cannot request the rewards of more than"), "{response}");
+}
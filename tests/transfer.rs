@@ -0,0 +1,77 @@
+// Copyright (C) 2019-2023 Aleo Systems Inc.
+// This file is part of the snarkOS library.
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at:
+// http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[allow(dead_code)]
+mod common;
+use common::{find_word, get, snarkos, Devnet};
+
+use std::{
+    io::Read,
+    process::{Command, Stdio},
+};
+
+/// The number of microcredits to transfer.
+const AMOUNT: u64 = 1_000_000;
+
+/// This test boots a devnet, transfers credits from a funded development account to a new account with
+/// `snarkos developer transfer`, and ensures the balance of the recipient via the mapping route.
+/// It takes several minutes, so it runs in its own CI job with `cargo test --test transfer -- --ignored`.
+#[test]
+#[ignore]
+fn test_developer_transfer() {
+    // Start a devnet, without the traffic of node 0, so that only the transfer moves its credits.
+    let devnet = Devnet::start("transfer");
+    let rest = devnet.rest.as_str();
+
+    // Write the private key of the sender to a keystore that is readable only by the owner.
+    let keystore_dir = devnet.storage.join("keystore");
+    std::fs::create_dir_all(&keystore_dir).unwrap();
+    let keystore = keystore_dir.join("private.key");
+    std::fs::write(&keystore, &devnet.private_key).unwrap();
+    #[cfg(target_family = "unix")]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&keystore_dir, std::fs::Permissions::from_mode(0o700)).unwrap();
+        std::fs::set_permissions(&keystore, std::fs::Permissions::from_mode(0o600)).unwrap();
+    }
+
+    // Generate the account of the recipient.
+    let account = snarkos(&["account", "new"]);
+    let recipient = account.lines().find_map(|line| find_word(line, "aleo1")).expect("Missing the new address");
+    assert_eq!(get(rest, &format!("program/credits.aleo/mapping/account/{recipient}")).trim(), "null");
+
+    // Transfer the credits, and wait for the transfer to be confirmed.
+    let amount = AMOUNT.to_string();
+    let keystore = keystore.to_str().unwrap();
+    let args = ["developer", "transfer", "--to", &recipient, "--amount", &amount, "--fee", "0", "--keystore", keystore];
+    let output = snarkos(&[&args[..], &["--rest", rest, "--wait"]].concat());
+    assert!(output.contains("in block"), "{output}");
+
+    // Ensure the recipient received the credits.
+    let balance = get(rest, &format!("program/credits.aleo/mapping/account/{recipient}"));
+    assert_eq!(balance.trim(), format!("\"{AMOUNT}u64\""));
+
+    // Ensure a transfer beyond the balance of the sender is rejected, without being broadcast.
+    let mut child = Command::new(env!("CARGO_BIN_EXE_snarkos"))
+        .args(["developer", "transfer", "--to", &recipient, "--amount", &u64::MAX.to_string(), "--fee", "0"])
+        .args(["--keystore", keystore, "--rest", rest])
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stderr = String::new();
+    child.stderr.take().unwrap().read_to_string(&mut stderr).unwrap();
+    assert!(!child.wait().unwrap().success());
+    assert!(stderr.contains("Insufficient balance"), "{stderr}");
+}